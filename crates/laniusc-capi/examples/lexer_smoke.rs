@@ -0,0 +1,80 @@
+//! Loads the lexer shared library at run time, as a non-Rust host would, and
+//! lexes one line.
+//!
+//! ```text
+//! cargo build -p laniusc-capi
+//! cargo run -p laniusc-capi --example lexer_smoke -- target/debug/liblanius_lexer.so
+//! ```
+
+use std::ffi::c_char;
+
+use libloading::{Library, Symbol};
+
+type Status = i32;
+type Create = unsafe extern "C" fn(*mut *mut u8) -> Status;
+type Lex = unsafe extern "C" fn(*mut u8, *const u8, usize, *mut *mut u8, *mut u32) -> Status;
+type FreeTokens = unsafe extern "C" fn(*mut u8);
+type Destroy = unsafe extern "C" fn(*mut u8);
+type LastError = unsafe extern "C" fn(*mut c_char, usize) -> usize;
+
+const HEADER_BYTES: usize = 12;
+const RECORD_BYTES: usize = 12;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::args().nth(1).unwrap_or_else(|| {
+        libloading::library_filename("lanius_lexer")
+            .into_string()
+            .unwrap()
+    });
+    let source = "let answer = 6 * 7;";
+
+    // SAFETY: the library is this workspace's `laniusc-capi` cdylib, and the
+    // symbol types match `lanius_lexer.h`.
+    unsafe {
+        let lib = Library::new(&path)?;
+        let create: Symbol<Create> = lib.get(b"lanius_lexer_create")?;
+        let lex: Symbol<Lex> = lib.get(b"lanius_lexer_lex")?;
+        let free_tokens: Symbol<FreeTokens> = lib.get(b"lanius_tokens_free")?;
+        let destroy: Symbol<Destroy> = lib.get(b"lanius_lexer_destroy")?;
+        let last_error: Symbol<LastError> = lib.get(b"lanius_last_error_message")?;
+        let error = || {
+            let mut buf = [0 as c_char; 512];
+            let len = last_error(buf.as_mut_ptr(), buf.len()).min(buf.len() - 1);
+            let bytes: Vec<u8> = buf[..len].iter().map(|&c| c as u8).collect();
+            String::from_utf8_lossy(&bytes).into_owned()
+        };
+
+        let mut handle = std::ptr::null_mut();
+        if create(&mut handle) != 0 {
+            return Err(format!("lanius_lexer_create: {}", error()).into());
+        }
+        let mut tokens = std::ptr::null_mut();
+        let mut count = 0u32;
+        let status = lex(
+            handle,
+            source.as_ptr(),
+            source.len(),
+            &mut tokens,
+            &mut count,
+        );
+        if status != 0 {
+            destroy(handle);
+            return Err(format!("lanius_lexer_lex: {}", error()).into());
+        }
+
+        let buffer =
+            std::slice::from_raw_parts(tokens, HEADER_BYTES + count as usize * RECORD_BYTES);
+        println!("{count} tokens from {source:?}");
+        for record in buffer[HEADER_BYTES..].chunks_exact(RECORD_BYTES) {
+            let field = |i: usize| {
+                u32::from_le_bytes(record[i * 4..i * 4 + 4].try_into().unwrap()) as usize
+            };
+            let (kind, start, len) = (field(0), field(1), field(2));
+            println!("  kind {kind:>3}  {:?}", &source[start..start + len]);
+        }
+
+        free_tokens(tokens);
+        destroy(handle);
+    }
+    Ok(())
+}
@@ -0,0 +1,7 @@
+//! Shared library exposing the GPU lexer's C ABI.
+//!
+//! The entry points live in `laniusc_compiler::capi` and are declared in
+//! `crates/laniusc-gpu/include/lanius_lexer.h`; this crate only links
+//! them into a cdylib.
+
+pub use laniusc_compiler::capi::*;
@@ -0,0 +1,154 @@
+//! Drives the C ABI through its `extern "C"` functions and checks, with a
+//! per-thread counting allocator, that handles, token buffers, and error
+//! messages are all released.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    ffi::c_char,
+    ptr,
+};
+
+use lanius_lexer::*;
+
+struct CountingAlloc;
+
+thread_local! {
+    /// Bytes this thread allocated and has not freed yet.
+    static LIVE_BYTES: Cell<isize> = const { Cell::new(0) };
+}
+
+fn track(delta: isize) {
+    // Allocations during thread teardown have nowhere to be counted.
+    let _ = LIVE_BYTES.try_with(|live| live.set(live.get() + delta));
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        track(layout.size() as isize);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        track(-(layout.size() as isize));
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        track(new_size as isize - layout.size() as isize);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+fn live_bytes() -> isize {
+    LIVE_BYTES.with(Cell::get)
+}
+
+fn last_error() -> String {
+    let mut buf = [0 as c_char; 256];
+    // SAFETY: `buf` is a live local of the given length.
+    let len = unsafe { lanius_last_error_message(buf.as_mut_ptr(), buf.len()) };
+    let bytes: Vec<u8> = buf[..len.min(255)].iter().map(|&c| c as u8).collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[test]
+fn error_paths_do_not_grow_the_heap() {
+    // SAFETY: every pointer is null or a live local.
+    unsafe {
+        let mut tokens = ptr::null_mut();
+        let mut count = 0;
+        let fail_once = |tokens: &mut *mut u8, count: &mut u32| {
+            assert_eq!(
+                lanius_lexer_create(ptr::null_mut()),
+                LANIUS_ERR_NULL_ARGUMENT
+            );
+            assert_eq!(
+                lanius_lexer_lex(ptr::null_mut(), ptr::null(), 0, tokens, count),
+                LANIUS_ERR_NULL_ARGUMENT
+            );
+            lanius_tokens_free(ptr::null_mut());
+            lanius_lexer_destroy(ptr::null_mut());
+        };
+        // The first failure allocates this thread's last-error slot.
+        fail_once(&mut tokens, &mut count);
+        let before = live_bytes();
+        for _ in 0..100 {
+            fail_once(&mut tokens, &mut count);
+        }
+        assert!(tokens.is_null());
+        assert_eq!(last_error(), "handle must not be null");
+        assert_eq!(live_bytes() - before, 0, "error paths leaked");
+    }
+}
+
+#[test]
+fn physical_gpu_lex_and_free_cycles_do_not_leak() {
+    // SAFETY: every pointer is a live local or comes from the API.
+    unsafe {
+        let mut handle = ptr::null_mut();
+        assert_eq!(
+            lanius_lexer_create(&mut handle),
+            LANIUS_OK,
+            "{}",
+            last_error()
+        );
+        let source = "fn main() { let x = 1 + 2; }\n".repeat(64);
+        let expected = laniusc_compiler::lexer::test_cpu::lex_on_test_cpu(&source)
+            .expect("test CPU oracle")
+            .len() as u32;
+        let lex_and_free = || {
+            let mut tokens = ptr::null_mut();
+            let mut count = 0;
+            assert_eq!(
+                lanius_lexer_lex(
+                    handle,
+                    source.as_ptr(),
+                    source.len(),
+                    &mut tokens,
+                    &mut count
+                ),
+                LANIUS_OK,
+                "{}",
+                last_error()
+            );
+            assert_eq!(count, expected);
+            let token_bytes =
+                LANIUS_TOKENS_HEADER_BYTES + count as usize * LANIUS_TOKENS_RECORD_BYTES;
+            let before_free = live_bytes();
+            lanius_tokens_free(tokens);
+            assert_eq!(before_free - live_bytes(), token_bytes as isize);
+        };
+
+        // Warm the lexer's resident buffers and caches first.
+        for _ in 0..3 {
+            lex_and_free();
+        }
+        let before = live_bytes();
+        for _ in 0..20 {
+            lex_and_free();
+        }
+        let grown = live_bytes() - before;
+        assert!(grown < 64 * 1024, "20 warm lex cycles kept {grown} bytes");
+
+        let invalid = b"let \xff = 1;";
+        let mut tokens = ptr::null_mut();
+        let mut count = 0;
+        assert_eq!(
+            lanius_lexer_lex(
+                handle,
+                invalid.as_ptr(),
+                invalid.len(),
+                &mut tokens,
+                &mut count
+            ),
+            LANIUS_ERR_INVALID_UTF8
+        );
+        assert!(tokens.is_null());
+
+        lanius_lexer_destroy(handle);
+    }
+}
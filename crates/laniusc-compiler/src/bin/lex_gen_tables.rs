@@ -1,118 +0,0 @@
-use std::{
-    fs,
-    io::{BufWriter, Write},
-    path::Path,
-};
-
-use laniusc_compiler::lexer::tables::{
-    dfa::{N_STATES, StreamingDfa},
-    tokens::{INVALID_TOKEN, N_KINDS, TokenKind},
-};
-
-const MAGIC: &[u8; 8] = b"LXDFA001";
-
-fn main() -> std::io::Result<()> {
-    println!("[gen_tables] building compact DFA tables (no merge)...");
-    let dfa = StreamingDfa::new();
-
-    let total = 256 * N_STATES;
-    let mut next_emit_u16 = Vec::<u16>::with_capacity(total);
-    for b in 0u32..=255 {
-        for s in 0..N_STATES {
-            let nx = dfa.next[s][b as usize];
-            let next = nx.state & 0x7FFF;
-            let emit = if nx.emit { 1u16 } else { 0u16 };
-            next_emit_u16.push((emit << 15) | next);
-        }
-    }
-
-    let mut token_u16 = Vec::<u16>::with_capacity(N_STATES);
-    for &tk in &dfa.token_map {
-        token_u16.push(if tk == INVALID_TOKEN {
-            0xFFFF
-        } else {
-            tk as u16
-        });
-    }
-
-    let out_path = Path::new("tables/lexer_tables.bin");
-    if let Some(dir) = out_path.parent() {
-        fs::create_dir_all(dir)?;
-    }
-
-    let f = fs::File::create(out_path)?;
-    let mut w = BufWriter::new(f);
-
-    w.write_all(MAGIC)?;
-    w.write_all(&(N_STATES as u32).to_le_bytes())?;
-    w.write_all(&0u32.to_le_bytes())?;
-    for v in &next_emit_u16 {
-        w.write_all(&v.to_le_bytes())?;
-    }
-    for v in &token_u16 {
-        w.write_all(&v.to_le_bytes())?;
-    }
-    w.flush()?;
-
-    let bytes = 8 + 4 + 4 + next_emit_u16.len() * 2 + token_u16.len() * 2;
-    println!(
-        "[gen_tables] wrote {} bytes (~{:.1} KiB) → {}",
-        bytes,
-        bytes as f64 / 1024.0,
-        out_path.display()
-    );
-
-    write_generated_token_ids(Path::new("shaders/generated_token_ids.slang"))?;
-    Ok(())
-}
-
-fn write_generated_token_ids(out_path: &Path) -> std::io::Result<()> {
-    if let Some(dir) = out_path.parent() {
-        fs::create_dir_all(dir)?;
-    }
-
-    let mut text = String::from(
-        "// Generated by `cargo run --bin lex_gen_tables` from src/lexer/tables/tokens.rs.\n",
-    );
-    text.push_str("// Do not edit by hand.\n\n");
-    text.push_str(&format!(
-        "static const uint TOKEN_KIND_COUNT = {}u;\n",
-        N_KINDS
-    ));
-    text.push_str(&format!(
-        "static const uint TOKEN_INVALID = {}u;\n\n",
-        INVALID_TOKEN
-    ));
-    for &kind in TokenKind::ALL {
-        text.push_str(&format!(
-            "static const uint TK_{} = {}u;\n",
-            screaming_snake_token_name(kind),
-            kind as u32
-        ));
-    }
-
-    fs::write(out_path, text)?;
-    println!("[gen_tables] wrote {}", out_path.display());
-    Ok(())
-}
-
-fn screaming_snake_token_name(kind: TokenKind) -> String {
-    let name = format!("{kind:?}");
-    match name.as_str() {
-        "EqEq" => return "EQEQ".to_string(),
-        "AndAnd" => return "ANDAND".to_string(),
-        "OrOr" => return "OROR".to_string(),
-        _ => {}
-    }
-
-    let mut out = String::with_capacity(name.len() + 4);
-    let mut prev_was_lower_or_digit = false;
-    for ch in name.chars() {
-        if ch.is_ascii_uppercase() && prev_was_lower_or_digit {
-            out.push('_');
-        }
-        out.push(ch.to_ascii_uppercase());
-        prev_was_lower_or_digit = ch.is_ascii_lowercase() || ch.is_ascii_digit();
-    }
-    out
-}
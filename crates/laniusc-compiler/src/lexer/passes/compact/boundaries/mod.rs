@@ -1,4 +0,0 @@
-/// Compaction pass for all token boundaries.
-pub mod all;
-/// Compaction pass for kept token boundaries.
-pub mod kept;
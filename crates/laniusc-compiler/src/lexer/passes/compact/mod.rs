@@ -1,2 +0,0 @@
-/// All-boundary and kept-boundary compaction pass variants.
-pub mod boundaries;
@@ -1,294 +0,0 @@
-use std::collections::HashMap;
-
-use encase::UniformBuffer;
-use wgpu::util::DeviceExt;
-
-use crate::{
-    gpu::passes_core::{
-        DispatchDim,
-        InputElements,
-        PassData,
-        bind_group::create_bind_group_from_reflection,
-        compute_pass_batching_enabled,
-        validation_scopes_enabled,
-    },
-    lexer::{buffers::GpuBuffers, debug::DebugOutput, passes::ScanParams},
-};
-
-/// Second pair pass: prefix-scans per-block boundary totals.
-pub struct Pair02ScanBlockTotalsPass {
-    data: PassData,
-}
-
-crate::gpu::passes_core::impl_static_shader_pass!(
-    Pair02ScanBlockTotalsPass,
-    label: "pair_02_scan_block_totals",
-    entry: "pair_02_scan_block_totals",
-    shader: "lexer/pair/02_scan_block_totals"
-);
-
-impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for Pair02ScanBlockTotalsPass {
-    const NAME: &'static str = "pair_02_scan_block_totals";
-    const DIM: DispatchDim = DispatchDim::D1;
-
-    fn from_data(data: PassData) -> Self {
-        Self { data }
-    }
-    fn data(&self) -> &PassData {
-        &self.data
-    }
-
-    fn create_resource_map<'a>(
-        &self,
-        _b: &'a GpuBuffers,
-    ) -> HashMap<String, wgpu::BindingResource<'a>> {
-        panic!(
-            "we implement this in record_pass to deal with uniforms, which is actually hacky and bad but whatever"
-        );
-    }
-
-    fn record_pass<'a>(
-        &self,
-        ctx: &mut crate::gpu::passes_core::PassContext<'a, GpuBuffers, DebugOutput>,
-        input: crate::gpu::passes_core::InputElements,
-    ) -> anyhow::Result<(), anyhow::Error> {
-        let device = ctx.device;
-        let encoder = &mut ctx.encoder;
-        let b = ctx.buffers;
-        let maybe_timer = &mut ctx.maybe_timer;
-        let maybe_dbg = &mut ctx.maybe_dbg;
-
-        let use_scopes = validation_scopes_enabled();
-
-        let validation_scope = crate::gpu::passes_core::validation_scope(device, use_scopes);
-
-        let n = match input {
-            InputElements::Elements1D(n) => n,
-            _ => unreachable!(),
-        };
-
-        let scan_steps = super::block_total_scan_steps(n);
-
-        let pd = self.data();
-
-        let layout0 = &pd.bind_group_layouts[0];
-        let pipeline = &pd.pipeline;
-        let reflection = &pd.reflection;
-
-        if let Some(dbg) = maybe_dbg.as_deref_mut() {
-            dbg.gpu.pair_scan_rounds.clear();
-        }
-
-        let can_batch = maybe_timer.is_none()
-            && maybe_dbg.is_none()
-            && compute_pass_batching_enabled()
-            && !use_scopes;
-        let mut retained_scan_params = Vec::with_capacity(scan_steps.len());
-        let mut retained_bind_groups = Vec::with_capacity(scan_steps.len());
-
-        if can_batch {
-            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
-                label: Some(Self::NAME),
-                timestamp_writes: None,
-            });
-            for (r, step) in scan_steps.iter().copied().enumerate() {
-                let mut ub = UniformBuffer::new(Vec::new());
-                ub.write(&ScanParams {
-                    stride: step.scan_step,
-                    use_ping_as_src: u32::from(step.read_from_a),
-                })
-                .expect("write ScanParams");
-                let scan_params = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some(&format!("ScanParams[PAIR-BLOCKS][{r}]")),
-                    contents: ub.as_ref(),
-                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-                });
-
-                let block_pair_in = if step.read_from_a {
-                    &b.dfa_02_ping
-                } else {
-                    &b.dfa_02_pong
-                };
-                let block_pair_out = if step.write_to_a {
-                    &b.dfa_02_ping
-                } else {
-                    &b.dfa_02_pong
-                };
-
-                let res = HashMap::from([
-                    (
-                        "gParams".into(),
-                        wgpu::BindingResource::Buffer(b.params.as_entire_buffer_binding()),
-                    ),
-                    (
-                        "gScan".into(),
-                        wgpu::BindingResource::Buffer(scan_params.as_entire_buffer_binding()),
-                    ),
-                    // Reuse DFA ping/pong for pair scan
-                    ("block_pair_in".into(), block_pair_in.as_entire_binding()),
-                    ("block_pair_out".into(), block_pair_out.as_entire_binding()),
-                ]);
-
-                let bg = create_bind_group_from_reflection(
-                    device,
-                    Some(&format!("pair_blocks_bg[{r}]")),
-                    layout0,
-                    reflection,
-                    0,
-                    &res,
-                )
-                .expect("pair_blocks_bg reflection");
-
-                let (gx, gy, gz) = crate::gpu::passes_core::plan_workgroups(
-                    crate::gpu::passes_core::DispatchDim::D1,
-                    crate::gpu::passes_core::InputElements::Elements1D(n),
-                    [256, 1, 1],
-                )?;
-                pass.set_pipeline(pipeline);
-                pass.set_bind_group(0, &bg, &[]);
-                pass.dispatch_workgroups(gx, gy, gz);
-                retained_scan_params.push(scan_params);
-                retained_bind_groups.push(bg);
-            }
-        } else {
-            for (r, step) in scan_steps.iter().copied().enumerate() {
-                let mut ub = UniformBuffer::new(Vec::new());
-                ub.write(&ScanParams {
-                    stride: step.scan_step,
-                    use_ping_as_src: u32::from(step.read_from_a),
-                })
-                .expect("write ScanParams");
-                let scan_params = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some(&format!("ScanParams[PAIR-BLOCKS][{r}]")),
-                    contents: ub.as_ref(),
-                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-                });
-
-                let block_pair_in = if step.read_from_a {
-                    &b.dfa_02_ping
-                } else {
-                    &b.dfa_02_pong
-                };
-                let block_pair_out = if step.write_to_a {
-                    &b.dfa_02_ping
-                } else {
-                    &b.dfa_02_pong
-                };
-
-                let res = HashMap::from([
-                    (
-                        "gParams".into(),
-                        wgpu::BindingResource::Buffer(b.params.as_entire_buffer_binding()),
-                    ),
-                    (
-                        "gScan".into(),
-                        wgpu::BindingResource::Buffer(scan_params.as_entire_buffer_binding()),
-                    ),
-                    // Reuse DFA ping/pong for pair scan
-                    ("block_pair_in".into(), block_pair_in.as_entire_binding()),
-                    ("block_pair_out".into(), block_pair_out.as_entire_binding()),
-                ]);
-
-                let bg = create_bind_group_from_reflection(
-                    device,
-                    Some(&format!("pair_blocks_bg[{r}]")),
-                    layout0,
-                    reflection,
-                    0,
-                    &res,
-                )
-                .expect("pair_blocks_bg reflection");
-
-                // One workgroup per PAIR block; planner must not divide by tgsx.
-                let (gx, gy, gz) = crate::gpu::passes_core::plan_workgroups(
-                    crate::gpu::passes_core::DispatchDim::D1,
-                    crate::gpu::passes_core::InputElements::Elements1D(n),
-                    [256, 1, 1],
-                )?;
-                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
-                    label: Some(Self::NAME),
-                    timestamp_writes: None,
-                });
-                pass.set_pipeline(pipeline);
-                pass.set_bind_group(0, &bg, &[]);
-                pass.dispatch_workgroups(gx, gy, gz);
-
-                retained_scan_params.push(scan_params);
-                retained_bind_groups.push(bg);
-
-                #[cfg(feature = "gpu-debug")]
-                if let Some(dbg) = maybe_dbg.as_deref_mut() {
-                    use crate::lexer::debug::make_staging;
-                    let per_round_bytes_u64 = (n as usize * 2 * std::mem::size_of::<u32>()) as u64;
-                    // Debug: snapshot reused DFA block ping/pong
-                    let last_writer = if step.write_to_a {
-                        &b.dfa_02_ping
-                    } else {
-                        &b.dfa_02_pong
-                    };
-                    let staging =
-                        make_staging(device, "dbg.pair_scan_round", per_round_bytes_u64 as usize);
-                    encoder.copy_buffer_to_buffer(last_writer, 0, &staging, 0, per_round_bytes_u64);
-                    dbg.gpu.pair_scan_rounds.push(crate::lexer::DebugBuffer {
-                        label: "dbg.pair_scan_round",
-                        buffer: Some(staging),
-                        byte_len: per_round_bytes_u64 as usize,
-                    });
-                }
-            }
-        }
-
-        if let Some(t) = maybe_timer {
-            t.stamp(encoder, Self::NAME.to_string());
-        }
-
-        if let Some(err) = crate::gpu::passes_core::pop_validation_scope(validation_scope) {
-            return Err(anyhow::anyhow!(
-                "validation in pass {}: {:?}",
-                Self::NAME,
-                err
-            ));
-        }
-
-        if let Some(d) = maybe_dbg.as_deref_mut() {
-            (&self).record_debug(device, encoder, b, d);
-        }
-        Ok(())
-    }
-
-    fn record_debug(
-        &self,
-        device: &wgpu::Device,
-        encoder: &mut wgpu::CommandEncoder,
-        b: &GpuBuffers,
-        dbg: &mut DebugOutput,
-    ) {
-        dbg.gpu.block_pair_ping.set_from_copy(
-            device,
-            encoder,
-            &b.dfa_02_ping,
-            "dbg.block_pair_ping",
-            b.dfa_02_ping.byte_size,
-        );
-        dbg.gpu.block_pair_pong.set_from_copy(
-            device,
-            encoder,
-            &b.dfa_02_pong,
-            "dbg.block_pair_pong",
-            b.dfa_02_pong.byte_size,
-        );
-
-        let last = if super::block_total_scan_last_writer_is_ping(b.nb_sum) {
-            &b.dfa_02_ping
-        } else {
-            &b.dfa_02_pong
-        };
-        dbg.gpu.block_prefix_pair.set_from_copy(
-            device,
-            encoder,
-            last,
-            "dbg.block_prefix_pair",
-            last.byte_size,
-        );
-    }
-}
@@ -1,33 +0,0 @@
-// src/parser/debug.rs
-#![allow(dead_code)]
-
-use crate::gpu::debug::DebugBuffer;
-
-/// GPU-side debug snapshots for parser passes.
-/// We mirror the style used by `src/lexer/debug.rs`.
-#[derive(Default)]
-pub struct DebugGpuBuffers {
-    // llp_pairs
-    pub out_headers: DebugBuffer,
-
-    // pack_varlen
-    pub sc_offsets: DebugBuffer,
-    pub emit_offsets: DebugBuffer,
-    pub out_sc: DebugBuffer,
-    pub out_emit: DebugBuffer,
-
-    // bracket pairing
-    pub match_for_index: DebugBuffer,
-    pub depths_out: DebugBuffer,
-    pub valid_out: DebugBuffer,
-
-    // tree_build
-    pub node_kind: DebugBuffer,
-    pub parent: DebugBuffer,
-}
-
-#[derive(Default)]
-/// Host-visible parser debug output collected from debug readback buffers.
-pub struct DebugOutput {
-    pub gpu: DebugGpuBuffers,
-}
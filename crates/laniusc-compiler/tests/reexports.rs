@@ -0,0 +1,276 @@
+//! The facade must keep every `laniusc_compiler::...` path the examples, the
+//! tool binaries, and the fuzz targets import. The `use` list below is
+//! compile-checked against the facade, and the test checks that it names
+//! every path those sources use.
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "shader-hot-reload")]
+#[allow(unused_imports)]
+use laniusc_compiler::gpu::hot_reload::shader_root;
+#[allow(unused_imports)]
+use laniusc_compiler::{
+    codegen::{
+        lowering_ir::{LoweringCapacities, LoweringTarget, lowering_compiler_graph},
+        unit::{
+            CodegenUnitLimits,
+            SourcePackArtifactTarget,
+            SourcePackBuildShardLimits,
+            SourcePackJobBatchLimits,
+            SourcePackJobPlan,
+            SourcePackJobSchedule,
+            SourcePackLibraryDependency,
+        },
+    },
+    compiler::{
+        ExplicitSourceLibraryPathDependencyStream,
+        FilesystemArtifactStore,
+        GpuCompiler,
+        GpuCompilerBackends,
+        GpuLiveCapacityEstimateResult,
+        PreparedBuild,
+        prepare_artifact_build_chunk,
+        resume_metadata_chunk_for_target,
+    },
+    dev::generator::{PROFILES, SourceGenConfig, gen_source, gen_valid_program, gen_valid_source},
+    gpu::{
+        buffers,
+        device,
+        passes_core::{bgls_from_reflection, pipeline_from_spirv_and_bgls},
+        poll,
+        trace,
+    },
+    lexer::{
+        EscapeSpan,
+        GpuLexer,
+        LexOptions,
+        LexOutput,
+        LexemeError,
+        LexemePolicy,
+        MemoOptions,
+        ReadbackMode,
+        Token,
+        bom::bom_len,
+        boundary::is_kept,
+        constants::{DFA_BLOCK_WIDTH, PAIR_BLOCK_WIDTH},
+        diff::{MismatchReport, first_divergence, preview_lossy},
+        driver::{get_global_lexer, try_global_lexer},
+        features::{
+            LEXICALLY_PROVEN_PARSER_FEATURES,
+            PARSER_FEATURE_ARRAYS,
+            PARSER_FEATURE_ENUMS,
+            PARSER_FEATURE_IMPORTS,
+            PARSER_FEATURE_MATCHES,
+            PARSER_FEATURE_MEMBERS,
+            PARSER_FEATURE_PREDICATES,
+            PARSER_FEATURE_STRING_EXPRS,
+            PARSER_FEATURE_STRUCTS,
+            PARSER_FEATURE_TYPE_ALIASES,
+        },
+        roundtrip::{self, verify_kept_relex, verify_partition, verify_recovered_partition},
+        shebang::shebang_len,
+        tables::{
+            build::FunctionClosure,
+            compact::encode_compact_tables,
+            dfa::{N_STATES, S, StreamingDfa},
+            save_tables_bin_as,
+            tokens::{INVALID_TOKEN, N_KINDS, TokenKind},
+        },
+        test_cpu::{
+            Coverage,
+            TestCpuLexer,
+            coverage::{UNREACHABLE_STATES, state_from_name},
+            lex_on_test_cpu,
+            lex_on_test_cpu_all,
+            lex_on_test_cpu_all_parallel,
+            lex_on_test_cpu_bytes,
+            lex_on_test_cpu_parallel,
+            lex_on_test_cpu_recovering,
+            lex_on_test_cpu_with_coverage_parallel,
+            lex_on_test_cpu_with_options,
+        },
+        tokens_io::source_hash,
+        utf8::lexeme,
+        verify::{Reference, Verdict, verify_against, write_reference},
+    },
+    logging::init_default,
+    parser::{
+        GpuParser,
+        ast::{Ast, AstError, AstNode},
+        buffers::ActionHeader,
+        grammar::{Grammar, GrammarProduction, check_against_lexer},
+        kindmap::{density, renumber_kinds},
+        retag::retag_open_delimiters,
+        tables::{
+            DEFAULT_SENTINEL_KIND,
+            INVALID_TABLE_ENTRY,
+            OperatorPrecedence,
+            ParseTablesGpuBlob,
+            PrecomputedParseTables,
+            build_mvp_precomputed_tables,
+            encode_pop,
+            encode_push,
+        },
+    },
+    prelude::*,
+    reflection::parse_reflection_from_bytes,
+    self_test::{
+        self,
+        parse_tables,
+        vectors::{
+            CheckKind,
+            EMBEDDED_VECTORS,
+            Expected,
+            SelfTestVectors,
+            VectorCheck,
+            VectorOptions,
+        },
+    },
+    span::Span,
+    tables::FormatVersion,
+};
+
+const FACADE: &str = "laniusc_compiler";
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../..")
+}
+
+fn rust_sources(dir: &Path, out: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap_or_else(|e| panic!("read {}: {e}", dir.display())) {
+        let path = entry.expect("directory entry").path();
+        if path.is_dir() {
+            rust_sources(&path, out);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            out.push(path);
+        }
+    }
+}
+
+fn skip_space(src: &[u8], mut i: usize) -> usize {
+    while i < src.len() && src[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+fn ident_end(src: &[u8], mut i: usize) -> usize {
+    while i < src.len() && (src[i].is_ascii_alphanumeric() || src[i] == b'_') {
+        i += 1;
+    }
+    i
+}
+
+/// Reads the path tree after a `laniusc_compiler::` at `i`, with `{}`
+/// groups, globs, `self`, and `as` renames, into full paths below the facade.
+fn path_tree(src: &[u8], mut i: usize, prefix: &[String], out: &mut BTreeSet<String>) -> usize {
+    let mut segments = prefix.to_vec();
+    loop {
+        i = skip_space(src, i);
+        match src.get(i) {
+            Some(b'{') => {
+                i += 1;
+                loop {
+                    i = skip_space(src, i);
+                    match src.get(i) {
+                        Some(b',') => i += 1,
+                        Some(b'}') => return i + 1,
+                        Some(_) => i = path_tree(src, i, &segments, out),
+                        None => return i,
+                    }
+                }
+            }
+            Some(b'*') => {
+                segments.push("*".to_string());
+                out.insert(segments.join("::"));
+                return i + 1;
+            }
+            _ => {}
+        }
+        let end = ident_end(src, i);
+        if end == i {
+            if !segments.is_empty() {
+                out.insert(segments.join("::"));
+            }
+            return i;
+        }
+        let segment = std::str::from_utf8(&src[i..end]).expect("ASCII identifier");
+        if segment != "self" {
+            segments.push(segment.to_string());
+        }
+        i = end;
+        let continues = src[i..].starts_with(b"::")
+            && src
+                .get(i + 2)
+                .is_some_and(|&c| c == b'{' || c == b'*' || c == b'_' || c.is_ascii_alphabetic());
+        if continues {
+            i += 2;
+            continue;
+        }
+        out.insert(segments.join("::"));
+        let after = skip_space(src, i);
+        if src[after..].starts_with(b"as")
+            && src.get(after + 2).is_some_and(u8::is_ascii_whitespace)
+        {
+            i = ident_end(src, skip_space(src, after + 2));
+        }
+        return i;
+    }
+}
+
+fn facade_paths(src: &str) -> BTreeSet<String> {
+    let needle = format!("{FACADE}::");
+    let mut out = BTreeSet::new();
+    for (at, _) in src.match_indices(&needle) {
+        let boundary = src[..at]
+            .bytes()
+            .next_back()
+            .is_none_or(|c| !(c.is_ascii_alphanumeric() || c == b'_'));
+        if boundary {
+            path_tree(src.as_bytes(), at + needle.len(), &[], &mut out);
+        }
+    }
+    out
+}
+
+#[test]
+fn path_trees_expand_groups_globs_and_renames() {
+    let src = "use laniusc_compiler::{a::{self, b as c}, d::*};\n\
+               let x = laniusc_compiler::e::f::<u32>(1); not_laniusc_compiler::g::h;";
+    let expected: BTreeSet<String> = ["a", "a::b", "d::*", "e::f"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    assert_eq!(facade_paths(src), expected);
+}
+
+#[test]
+fn facade_covers_every_example_and_tool_import() {
+    let root = workspace_root();
+    let mut sources = Vec::new();
+    rust_sources(&root.join("examples"), &mut sources);
+    rust_sources(&root.join("crates/laniusc-tools/src"), &mut sources);
+    rust_sources(&root.join("fuzz/fuzz_targets"), &mut sources);
+    assert!(!sources.is_empty(), "no example or tool sources found");
+
+    let covered = facade_paths(include_str!("reexports.rs"));
+    let mut missing = Vec::new();
+    for path in &sources {
+        let text =
+            fs::read_to_string(path).unwrap_or_else(|e| panic!("read {}: {e}", path.display()));
+        for import in facade_paths(&text) {
+            if !covered.contains(&import) {
+                missing.push(format!("{FACADE}::{import} ({})", path.display()));
+            }
+        }
+    }
+    assert!(
+        missing.is_empty(),
+        "add these imports to the checked list in tests/reexports.rs:\n{}",
+        missing.join("\n")
+    );
+}
@@ -0,0 +1,32 @@
+//! Run-to-run reproducibility contract for read-back GPU outputs.
+//!
+//! Outputs built from prefix scans and index-addressed scatters are
+//! deterministic by construction. Outputs whose order follows atomic arrival
+//! (insert order of an atomically built table, the first writer of a slot) are
+//! not, unless the pass reduces them with an order-independent operation such
+//! as `atomicMin` over an index or `atomicOr` over bits.
+
+use serde::{Deserialize, Serialize};
+
+/// How much run-to-run variation a GPU phase may show for one input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Determinism {
+    /// Every read-back output is bit-identical across runs on the same input.
+    ///
+    /// Passes must use order-independent reductions, e.g. sort-by-key
+    /// compaction instead of atomic insert order, and the minimum index for
+    /// first-error reports.
+    #[default]
+    Strict,
+    /// Outputs whose order follows atomic arrival may differ between runs;
+    /// their contents as a set may not.
+    Relaxed,
+}
+
+impl Determinism {
+    /// Whether outputs must be bit-identical across runs.
+    pub fn is_strict(self) -> bool {
+        self == Self::Strict
+    }
+}
@@ -0,0 +1,1920 @@
+//! Random source generator used by fuzzing, perf, and tests.
+//!
+//! Notes:
+//! - Generates *at least* `target_len` bytes (may overshoot a bit).
+//! - Always appends a safe trailer to keep EOF and block comments well-formed.
+//! - Sometimes ends the output inside a `//` comment with no final newline.
+//! - [`gen_source`] draws each token from one family emitter picked through a
+//!   weighting table built from [`SourceGenConfig`]; every config yields
+//!   lexically valid output.
+//!
+//! [`gen_source`]: crate::dev::generator::gen_source
+//! [`SourceGenConfig`]: crate::dev::generator::SourceGenConfig
+
+use rand::Rng;
+
+/// Appended to every generated string to keep block-comments closed and
+/// ensure a clean EOF tokenization boundary.
+///
+/// NOTE:
+/// - The leading space prevents accidentally creating `"/*"` if the last
+///   generated byte was `'/'`.
+/// - The `*/` still appears contiguously to close any open block comment.
+/// - The `" 0\n"` gives a simple token and a hard newline at EOF; the
+///   newline is dropped when [`gen_source`] glues a trailing comment onto the
+///   `0`, and is `\r\n` under [`NewlineStyle::CrLf`].
+pub const SAFE_TRAILER: &str = " */ 0\n";
+
+/// Decimal integers such as `042`.
+pub const NUMERIC_DECIMAL: u32 = 1 << 0;
+/// Hexadecimal integers such as `0x1F`.
+pub const NUMERIC_HEX: u32 = 1 << 1;
+/// Binary integers such as `0b101`.
+pub const NUMERIC_BINARY: u32 = 1 << 2;
+/// Octal integers such as `0o17`.
+pub const NUMERIC_OCTAL: u32 = 1 << 3;
+/// Floats with a fraction: `1.5`, `1.`, and `.5`.
+pub const NUMERIC_FLOAT: u32 = 1 << 4;
+/// Floats with an exponent such as `1e9`, `2.5E-3`, and `7e+1`.
+pub const NUMERIC_EXPONENT: u32 = 1 << 5;
+/// Modifier: `_` digit separators inside the enabled styles.
+pub const NUMERIC_UNDERSCORES: u32 = 1 << 6;
+/// Every numeric style and modifier.
+pub const NUMERIC_ALL: u32 = NUMERIC_DECIMAL
+    | NUMERIC_HEX
+    | NUMERIC_BINARY
+    | NUMERIC_OCTAL
+    | NUMERIC_FLOAT
+    | NUMERIC_EXPONENT
+    | NUMERIC_UNDERSCORES;
+
+/// Line breaks emitted by whitespace, comments, and the trailer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NewlineStyle {
+    /// `\n` line breaks with stray `\r` whitespace mixed in.
+    Mixed,
+    /// `\n` only; no `\r` anywhere.
+    Lf,
+    /// `\r\n` only; no lone `\r` or `\n`.
+    CrLf,
+}
+
+/// Characters used for generated identifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdentifierCharset {
+    /// A letter or `_`, then letters and digits.
+    AsciiAlphanumeric,
+    /// Letters and `_` only.
+    Alphabetic,
+    /// Mostly `_` with a few letters and digits.
+    UnderscoreHeavy,
+}
+
+/// Family whose tokens [`LongTokens`] stretches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LongTokenKind {
+    /// Block comments, with the usual stray `*` and line breaks inside.
+    BlockComment,
+    /// Double-quoted strings, escaped per
+    /// [`SourceGenConfig::string_escape_rate`].
+    String,
+}
+
+/// Single tokens spanning many DFA blocks: every draw of `kind`'s family
+/// emits a body of `share` of `target_len` bytes, capped at `max_len`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LongTokens {
+    pub kind: LongTokenKind,
+    /// Body length as a share of [`SourceGenConfig::target_len`], in
+    /// `0.0..=1.0`.
+    pub share: f64,
+    pub max_len: usize,
+}
+
+/// Relative weights of the non-comment token families.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenWeights {
+    pub ident: u32,
+    pub number: u32,
+    pub whitespace: u32,
+    pub operator: u32,
+    pub string: u32,
+    /// Balanced `(`/`[`/`{` nesting bounded by
+    /// [`SourceGenConfig::max_bracket_depth`].
+    pub bracket: u32,
+}
+
+/// Knobs for [`gen_source`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceGenConfig {
+    /// Minimum output length before the trailer.
+    pub target_len: usize,
+    /// Share of draws that emit a line or block comment, in `0.0..=1.0`.
+    pub comment_density: f64,
+    /// Chance that a block comment chunk starts with a stray `/*` or `//`.
+    pub nested_comment_rate: f64,
+    /// Chance that a string character is an escape. `None` samples escapes
+    /// and plain characters from one alphabet, about 4 in 11.
+    pub string_escape_rate: Option<f64>,
+    /// Deepest nesting the bracket family opens.
+    pub max_bracket_depth: usize,
+    pub newline_style: NewlineStyle,
+    /// `NUMERIC_*` bits; `0` disables numbers.
+    pub numeric_styles: u32,
+    pub identifier_charset: IdentifierCharset,
+    pub weights: TokenWeights,
+    /// Very long block comments or strings; `None` keeps every token short.
+    pub long_tokens: Option<LongTokens>,
+}
+
+impl Default for SourceGenConfig {
+    fn default() -> Self {
+        Self {
+            target_len: 4096,
+            comment_density: 0.16,
+            nested_comment_rate: 0.0,
+            string_escape_rate: None,
+            max_bracket_depth: 32,
+            newline_style: NewlineStyle::Mixed,
+            numeric_styles: NUMERIC_ALL,
+            identifier_charset: IdentifierCharset::AsciiAlphanumeric,
+            weights: TokenWeights {
+                ident: 25,
+                number: 15,
+                whitespace: 15,
+                operator: 24,
+                string: 5,
+                bracket: 0,
+            },
+            long_tokens: None,
+        }
+    }
+}
+
+impl SourceGenConfig {
+    /// Default mix with `target_len` bytes.
+    pub fn with_len(target_len: usize) -> Self {
+        Self {
+            target_len,
+            ..Self::default()
+        }
+    }
+
+    /// Dense runs of short operators with little whitespace, the worst case
+    /// for token compaction.
+    pub fn operator_heavy() -> Self {
+        Self {
+            comment_density: 0.02,
+            numeric_styles: NUMERIC_ALL,
+            weights: TokenWeights {
+                ident: 10,
+                number: 8,
+                whitespace: 4,
+                operator: 70,
+                string: 1,
+                bracket: 7,
+            },
+            ..Self::default()
+        }
+    }
+
+    /// Mostly comments, including stray openers inside block comments and
+    /// CRLF line ends.
+    pub fn comment_heavy() -> Self {
+        Self {
+            comment_density: 0.7,
+            nested_comment_rate: 0.3,
+            newline_style: NewlineStyle::CrLf,
+            ..Self::default()
+        }
+    }
+
+    /// Long balanced bracket runs nested hundreds deep.
+    pub fn bracket_deep() -> Self {
+        Self {
+            comment_density: 0.02,
+            max_bracket_depth: 512,
+            weights: TokenWeights {
+                ident: 12,
+                number: 6,
+                whitespace: 8,
+                operator: 8,
+                string: 1,
+                bracket: 65,
+            },
+            ..Self::default()
+        }
+    }
+
+    /// Nearly all of the output in one block comment, so megabytes of
+    /// skipped input sit between two kept boundaries.
+    pub fn long_comment() -> Self {
+        Self {
+            comment_density: 0.01,
+            long_tokens: Some(LongTokens {
+                kind: LongTokenKind::BlockComment,
+                share: 1.0,
+                max_len: usize::MAX,
+            }),
+            ..Self::default()
+        }
+    }
+
+    /// Nearly all of the output in one string literal with escapes sprinkled
+    /// through it.
+    pub fn long_string() -> Self {
+        Self {
+            comment_density: 0.02,
+            string_escape_rate: Some(0.05),
+            long_tokens: Some(LongTokens {
+                kind: LongTokenKind::String,
+                share: 1.0,
+                max_len: usize::MAX,
+            }),
+            weights: TokenWeights {
+                string: 1,
+                ..Self::default().weights
+            },
+            ..Self::default()
+        }
+    }
+
+    /// Short operators alternating with block comments of a tenth of the
+    /// output, up to 1 MiB each.
+    pub fn long_alternating() -> Self {
+        Self {
+            comment_density: 0.5,
+            long_tokens: Some(LongTokens {
+                kind: LongTokenKind::BlockComment,
+                share: 0.1,
+                max_len: 1 << 20,
+            }),
+            weights: TokenWeights {
+                ident: 0,
+                number: 0,
+                whitespace: 0,
+                operator: 1,
+                string: 0,
+                bracket: 0,
+            },
+            ..Self::default()
+        }
+    }
+
+    /// Looks up a preset by name, one of [`PROFILES`].
+    pub fn from_profile(name: &str) -> Option<Self> {
+        Some(match name {
+            "default" => Self::default(),
+            "operator_heavy" => Self::operator_heavy(),
+            "comment_heavy" => Self::comment_heavy(),
+            "bracket_deep" => Self::bracket_deep(),
+            "long_comment" => Self::long_comment(),
+            "long_string" => Self::long_string(),
+            "long_alternating" => Self::long_alternating(),
+            _ => return None,
+        })
+    }
+
+    /// Body length of a long token of `kind`, if this config stretches it.
+    fn long_token_len(&self, kind: LongTokenKind) -> Option<usize> {
+        let long = self.long_tokens.filter(|long| long.kind == kind)?;
+        let len = self.target_len as f64 * long.share.clamp(0.0, 1.0);
+        Some((len as usize).min(long.max_len).max(1))
+    }
+}
+
+/// Names [`SourceGenConfig::from_profile`] accepts.
+pub const PROFILES: &[&str] = &[
+    "default",
+    "operator_heavy",
+    "comment_heavy",
+    "bracket_deep",
+    "long_comment",
+    "long_string",
+    "long_alternating",
+];
+
+/// Generate a random, lexically valid source string that is **at least**
+/// `target_len` bytes long (plus a short, safe trailer).
+///
+/// About one output in four then ends mid line comment, either right after the
+/// trailer's `0` or on a new line, so EOF-closed comments stay covered.
+///
+/// This is [`gen_source`] with the default config.
+pub fn gen_valid_source<R: Rng>(rng: &mut R, target_len: usize) -> String {
+    gen_source(rng, &SourceGenConfig::with_len(target_len))
+}
+
+/// Generate a random, lexically valid source string shaped by `config`.
+pub fn gen_source<R: Rng>(rng: &mut R, config: &SourceGenConfig) -> String {
+    let target_len = config.target_len;
+    let table = family_table(config);
+    let mut source_gen = SourceGen {
+        config,
+        open_brackets: Vec::new(),
+    };
+    let mut out = String::with_capacity(target_len + target_len / 8);
+
+    while out.len() < target_len {
+        let mut roll = rng.random_range(0..ROLL_SLOTS);
+        let family = table
+            .iter()
+            .find_map(|&(family, slots)| {
+                if roll < slots {
+                    Some(family)
+                } else {
+                    roll -= slots;
+                    None
+                }
+            })
+            .expect("family table covers every roll");
+        source_gen.emit(family, rng, &mut out);
+    }
+    source_gen.close_brackets(&mut out);
+
+    // Trailer keeps the last block-comment sane and ensures an EOF tokenization edge.
+    out.push_str(" */ 0");
+    source_gen.push_newline(&mut out);
+    match rng.random_range(0u32..8) {
+        0 => {
+            // Comment directly after the trailer's kept `0` token.
+            source_gen.pop_newline(&mut out);
+            source_gen.push_eof_line_comment(rng, &mut out);
+        }
+        1 => source_gen.push_eof_line_comment(rng, &mut out),
+        _ => {}
+    }
+    out
+}
+
+/// Generate a syntactically valid Lanius program for parser fuzzing.
+///
+/// This intentionally samples across the parser grammar surface: top-level
+/// items, types, generic parameters, where clauses, blocks, statements,
+/// expression precedence, postfix forms, literals, arrays, matches, and
+/// aggregate syntax. It is not a type-checking oracle; generated programs are
+/// parser-valid.
+pub fn gen_valid_program<R: Rng>(rng: &mut R, target_len: usize) -> String {
+    WideProgramGenerator::new(target_len).generate(rng)
+}
+
+/// Roll range for family selection; weights are rounded to these slots.
+const ROLL_SLOTS: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenFamily {
+    Ident,
+    Number,
+    Whitespace,
+    LineComment,
+    BlockComment,
+    Operator,
+    String,
+    Bracket,
+}
+
+/// Splits [`ROLL_SLOTS`] across the families: comments take
+/// `comment_density` (7:9 line to block) and the rest follows `weights`.
+fn family_table(config: &SourceGenConfig) -> Vec<(TokenFamily, u32)> {
+    let comment_slots = ((config.comment_density.clamp(0.0, 1.0) * ROLL_SLOTS as f64).round()
+        as u32)
+        .min(ROLL_SLOTS);
+    let line_slots = (comment_slots * 7 + 8) / 16;
+    let w = config.weights;
+    let number = if config.numeric_styles & !NUMERIC_UNDERSCORES == 0 {
+        0
+    } else {
+        w.number
+    };
+    let weighted = [
+        (TokenFamily::Ident, w.ident),
+        (TokenFamily::Number, number),
+        (TokenFamily::Whitespace, w.whitespace),
+        (TokenFamily::Operator, w.operator),
+        (TokenFamily::String, w.string),
+        (TokenFamily::Bracket, w.bracket),
+    ];
+    let mut rest = ROLL_SLOTS - comment_slots;
+    let total: u32 = weighted.iter().map(|&(_, weight)| weight).sum();
+    let mut slots = [0u32; 6];
+    if total == 0 {
+        // Nothing weighted: whitespace keeps the output growing.
+        slots[2] = rest;
+    } else {
+        // Largest remainder, so the slots always sum to `rest`.
+        let mut remainders = [(0u64, 0usize); 6];
+        for (i, &(_, weight)) in weighted.iter().enumerate() {
+            let exact = rest as u64 * weight as u64;
+            slots[i] = (exact / total as u64) as u32;
+            remainders[i] = (exact % total as u64, i);
+        }
+        rest -= slots.iter().sum::<u32>();
+        remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        for &(_, i) in remainders.iter().take(rest as usize) {
+            slots[i] += 1;
+        }
+    }
+    // Ordered as the original single-roll match so default output is unchanged.
+    vec![
+        (TokenFamily::Ident, slots[0]),
+        (TokenFamily::Number, slots[1]),
+        (TokenFamily::Whitespace, slots[2]),
+        (TokenFamily::LineComment, line_slots),
+        (TokenFamily::BlockComment, comment_slots - line_slots),
+        (TokenFamily::Operator, slots[3]),
+        (TokenFamily::String, slots[4]),
+        (TokenFamily::Bracket, slots[5]),
+    ]
+}
+
+struct SourceGen<'a> {
+    config: &'a SourceGenConfig,
+    open_brackets: Vec<char>,
+}
+
+impl SourceGen<'_> {
+    fn emit<R: Rng>(&mut self, family: TokenFamily, rng: &mut R, out: &mut String) {
+        match family {
+            TokenFamily::Ident => self.push_ident(rng, out),
+            TokenFamily::Number => self.push_number(rng, out),
+            TokenFamily::Whitespace => self.push_ws(rng, out),
+            TokenFamily::LineComment => self.push_line_comment(rng, out),
+            TokenFamily::BlockComment => {
+                match self.config.long_token_len(LongTokenKind::BlockComment) {
+                    Some(len) => self.push_long_block_comment(rng, out, len),
+                    None => self.push_block_comment(rng, out),
+                }
+            }
+            TokenFamily::Operator => push_operator(rng, out),
+            TokenFamily::String => match self.config.long_token_len(LongTokenKind::String) {
+                Some(len) => self.push_long_string(rng, out, len),
+                None => self.push_string(rng, out),
+            },
+            TokenFamily::Bracket => self.push_bracket(rng, out),
+        }
+    }
+
+    fn push_newline(&self, out: &mut String) {
+        match self.config.newline_style {
+            NewlineStyle::CrLf => out.push_str("\r\n"),
+            NewlineStyle::Mixed | NewlineStyle::Lf => out.push('\n'),
+        }
+    }
+
+    fn pop_newline(&self, out: &mut String) {
+        let len = match self.config.newline_style {
+            NewlineStyle::CrLf => 2,
+            NewlineStyle::Mixed | NewlineStyle::Lf => 1,
+        };
+        out.truncate(out.len() - len);
+    }
+
+    fn push_ident<R: Rng>(&self, rng: &mut R, out: &mut String) {
+        let len = rng.random_range(1..=12);
+        let mut s = String::new();
+        s.push(random_alpha(rng));
+        for _ in 1..len {
+            match self.config.identifier_charset {
+                IdentifierCharset::AsciiAlphanumeric => {
+                    if rng.random_bool(0.6) {
+                        s.push(random_alpha(rng));
+                    } else {
+                        s.push(random_digit(rng));
+                    }
+                }
+                IdentifierCharset::Alphabetic => s.push(random_alpha(rng)),
+                IdentifierCharset::UnderscoreHeavy => match rng.random_range(0u32..4) {
+                    0 => s.push(random_alpha(rng)),
+                    1 => s.push(random_digit(rng)),
+                    _ => s.push('_'),
+                },
+            }
+        }
+        out.push_str(&s);
+    }
+
+    fn push_number<R: Rng>(&self, rng: &mut R, out: &mut String) {
+        const STYLES: [u32; 6] = [
+            NUMERIC_DECIMAL,
+            NUMERIC_HEX,
+            NUMERIC_BINARY,
+            NUMERIC_OCTAL,
+            NUMERIC_FLOAT,
+            NUMERIC_EXPONENT,
+        ];
+        let styles = self.config.numeric_styles;
+        if styles == NUMERIC_DECIMAL {
+            push_int(rng, out);
+            return;
+        }
+        let enabled: Vec<u32> = STYLES.into_iter().filter(|s| styles & s != 0).collect();
+        let separators = styles & NUMERIC_UNDERSCORES != 0;
+        match enabled[rng.random_range(0..enabled.len())] {
+            NUMERIC_DECIMAL => push_digits(rng, out, b"0123456789", separators),
+            NUMERIC_HEX => {
+                out.push_str(if rng.random_bool(0.5) { "0x" } else { "0X" });
+                push_digits(rng, out, b"0123456789abcdefABCDEF", separators);
+            }
+            NUMERIC_BINARY => {
+                out.push_str("0b");
+                push_digits(rng, out, b"01", separators);
+            }
+            NUMERIC_OCTAL => {
+                out.push_str("0o");
+                push_digits(rng, out, b"01234567", separators);
+            }
+            NUMERIC_FLOAT => match rng.random_range(0u32..3) {
+                0 => {
+                    push_digits(rng, out, b"0123456789", separators);
+                    out.push('.');
+                    push_digits(rng, out, b"0123456789", separators);
+                }
+                1 => {
+                    // Trailing-dot floats only stay one token before a space.
+                    push_digits(rng, out, b"0123456789", separators);
+                    out.push_str(". ");
+                }
+                _ => {
+                    out.push('.');
+                    push_digits(rng, out, b"0123456789", separators);
+                }
+            },
+            _ => {
+                push_digits(rng, out, b"0123456789", separators);
+                if rng.random_bool(0.5) {
+                    out.push('.');
+                    push_digits(rng, out, b"0123456789", separators);
+                }
+                out.push(if rng.random_bool(0.5) { 'e' } else { 'E' });
+                match rng.random_range(0u32..3) {
+                    0 => out.push('+'),
+                    1 => out.push('-'),
+                    _ => {}
+                }
+                push_digits(rng, out, b"0123456789", separators);
+            }
+        }
+    }
+
+    fn push_ws<R: Rng>(&self, rng: &mut R, out: &mut String) {
+        let len = rng.random_range(1..=8);
+        match self.config.newline_style {
+            NewlineStyle::Mixed => {
+                let opts: [char; 4] = [' ', '\t', '\r', '\n'];
+                for _ in 0..len {
+                    let i = rng.random_range(0..opts.len());
+                    out.push(opts[i]);
+                }
+            }
+            NewlineStyle::Lf | NewlineStyle::CrLf => {
+                for _ in 0..len {
+                    match rng.random_range(0u32..3) {
+                        0 => out.push(' '),
+                        1 => out.push('\t'),
+                        _ => self.push_newline(out),
+                    }
+                }
+            }
+        }
+    }
+
+    fn push_line_comment<R: Rng>(&self, rng: &mut R, out: &mut String) {
+        out.push_str("//");
+        let len = rng.random_range(0..=40);
+        const ALPH: &str =
+            "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 +-*/&|![]{}()<>=*";
+        let bytes = ALPH.as_bytes();
+        for _ in 0..len {
+            let i = rng.random_range(0..bytes.len());
+            out.push(bytes[i] as char);
+        }
+        self.push_newline(out);
+    }
+
+    /// Line comment closed by end of input rather than by a newline.
+    fn push_eof_line_comment<R: Rng>(&self, rng: &mut R, out: &mut String) {
+        self.push_line_comment(rng, out);
+        self.pop_newline(out);
+    }
+
+    fn push_block_comment<R: Rng>(&self, rng: &mut R, out: &mut String) {
+        out.push_str("/*");
+        let chunks = rng.random_range(0..=15);
+        for _ in 0..chunks {
+            self.push_block_comment_chunk(rng, out);
+        }
+        out.push_str("*/");
+    }
+
+    /// Block comment whose body is at least `len` bytes.
+    fn push_long_block_comment<R: Rng>(&self, rng: &mut R, out: &mut String, len: usize) {
+        out.push_str("/*");
+        let end = out.len() + len;
+        while out.len() < end {
+            self.push_block_comment_chunk(rng, out);
+        }
+        out.push_str("*/");
+    }
+
+    fn push_block_comment_chunk<R: Rng>(&self, rng: &mut R, out: &mut String) {
+        const BODY: &str =
+            "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 +-![]{}()<>=&|";
+        // Block comments do not nest; the leading space keeps a preceding
+        // `*` from closing the comment early.
+        const OPENERS: [&str; 3] = [" /*", " //", " /* /*"];
+        let bytes = BODY.as_bytes();
+        if self.config.nested_comment_rate > 0.0
+            && rng.random_bool(self.config.nested_comment_rate.min(1.0))
+        {
+            out.push_str(OPENERS[rng.random_range(0..OPENERS.len())]);
+        }
+        let k = rng.random_range(1..=8);
+        for _ in 0..k {
+            let i = rng.random_range(0..bytes.len());
+            out.push(bytes[i] as char);
+        }
+        if rng.random_bool(0.2) {
+            out.push('*');
+        }
+        if rng.random_bool(0.2) {
+            self.push_newline(out);
+        }
+    }
+
+    /// One character or escape of a double-quoted string body.
+    fn string_piece<R: Rng>(&self, rng: &mut R) -> &'static str {
+        const BODY: &[&str] = &[
+            "a", "Z", "0", " ", "/", "\\\\", "\\\"", "\\n", "\\t", "'", "*",
+        ];
+        const PLAIN: &[&str] = &["a", "Z", "0", " ", "/", "'", "*"];
+        const ESCAPES: &[&str] = &["\\\\", "\\\"", "\\n", "\\t"];
+        match self.config.string_escape_rate {
+            None => BODY[rng.random_range(0..BODY.len())],
+            Some(rate) if rng.random_bool(rate.clamp(0.0, 1.0)) => {
+                ESCAPES[rng.random_range(0..ESCAPES.len())]
+            }
+            Some(_) => PLAIN[rng.random_range(0..PLAIN.len())],
+        }
+    }
+
+    /// Double-quoted string whose body is at least `len` bytes.
+    fn push_long_string<R: Rng>(&self, rng: &mut R, out: &mut String, len: usize) {
+        out.push_str(" \"");
+        let end = out.len() + len;
+        while out.len() < end {
+            out.push_str(self.string_piece(rng));
+        }
+        out.push('"');
+    }
+
+    /// String literal with escapes and embedded quotes, a raw string, a char
+    /// literal, or a near-miss such as a bare `r` or a backslash-heavy body.
+    fn push_string<R: Rng>(&self, rng: &mut R, out: &mut String) {
+        const RAW_BODY: &[&str] = &["a", "Z", "0", " ", "/", "\\", "\\n", "'", "*", "r"];
+        // Leading space keeps a preceding identifier from absorbing `r`.
+        out.push(' ');
+        match rng.random_range(0u32..5) {
+            0 | 1 => {
+                out.push('"');
+                for _ in 0..rng.random_range(0..=8) {
+                    out.push_str(self.string_piece(rng));
+                }
+                out.push('"');
+            }
+            2 => {
+                out.push_str("r\"");
+                for _ in 0..rng.random_range(0..=8) {
+                    out.push_str(RAW_BODY[rng.random_range(0..RAW_BODY.len())]);
+                }
+                out.push('"');
+            }
+            3 => {
+                const CHARS: &[&str] = &["a", "Z", "0", " ", "\"", "\\\\", "\\'", "\\n", "\\t"];
+                out.push('\'');
+                out.push_str(CHARS[rng.random_range(0..CHARS.len())]);
+                out.push('\'');
+            }
+            _ => {
+                // `r` not followed by a quote stays an identifier.
+                let misses = ["r ", "r(", "rx", "r0", "r\n"];
+                match misses[rng.random_range(0..misses.len())] {
+                    "r\n" => {
+                        out.push('r');
+                        self.push_newline(out);
+                    }
+                    miss => out.push_str(miss),
+                }
+            }
+        }
+    }
+
+    fn push_bracket<R: Rng>(&mut self, rng: &mut R, out: &mut String) {
+        let depth = self.open_brackets.len();
+        let open = depth < self.config.max_bracket_depth && (depth == 0 || rng.random_bool(0.6));
+        if open {
+            let (opener, closer) = [('(', ')'), ('[', ']'), ('{', '}')][rng.random_range(0..3)];
+            out.push(opener);
+            self.open_brackets.push(closer);
+        } else if let Some(closer) = self.open_brackets.pop() {
+            out.push(closer);
+        }
+    }
+
+    fn close_brackets(&mut self, out: &mut String) {
+        while let Some(closer) = self.open_brackets.pop() {
+            out.push(closer);
+        }
+    }
+}
+
+fn push_int<R: Rng>(rng: &mut R, out: &mut String) {
+    let len = rng.random_range(1..=8);
+    for _ in 0..len {
+        out.push(random_digit(rng));
+    }
+}
+
+/// One to eight digits from `set`, with `_` only between digits.
+fn push_digits<R: Rng>(rng: &mut R, out: &mut String, set: &[u8], separators: bool) {
+    let len = rng.random_range(1..=8);
+    for i in 0..len {
+        if separators && i > 0 && rng.random_bool(0.2) {
+            out.push('_');
+        }
+        out.push(set[rng.random_range(0..set.len())] as char);
+    }
+}
+
+fn push_operator<R: Rng>(rng: &mut R, out: &mut String) {
+    // Every operator the lexer DFA accepts, so each of its operator states
+    // is reached.
+    let ops = [
+        "(", ")", "+", "*", "=", "/", "!", "[", "]", "{", "}", "<", "<=", ">", ">=", "==", "&",
+        "&&", "|", "||", "-", "%", "^", "~", ",", ";", ":", "?", ".", "..", "!=", "=>", "->", "<<",
+        ">>", "++", "--", "+=", "-=", "*=", "/=", "%=", "^=", "&=", "|=", "~=", "<<=", ">>=",
+    ];
+    let i = rng.random_range(0..ops.len());
+    out.push_str(ops[i]);
+    if rng.random_bool(0.25) {
+        out.push(' ');
+    }
+}
+
+fn random_alpha<R: Rng>(rng: &mut R) -> char {
+    let set = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_";
+    let i = rng.random_range(0..set.len());
+    set[i] as char
+}
+
+fn random_digit<R: Rng>(rng: &mut R) -> char {
+    let set = b"0123456789";
+    let i = rng.random_range(0..set.len());
+    set[i] as char
+}
+
+struct WideProgramGenerator {
+    target_len: usize,
+    item_index: usize,
+    fn_index: usize,
+    local_name_index: usize,
+    function_names: Vec<String>,
+    type_names: Vec<String>,
+    trait_names: Vec<String>,
+    struct_names: Vec<String>,
+    enum_names: Vec<String>,
+}
+
+impl WideProgramGenerator {
+    fn new(target_len: usize) -> Self {
+        Self {
+            target_len,
+            item_index: 0,
+            fn_index: 0,
+            local_name_index: 0,
+            function_names: Vec::new(),
+            type_names: vec![
+                "i32".to_string(),
+                "bool".to_string(),
+                "u32".to_string(),
+                "usize".to_string(),
+            ],
+            trait_names: vec!["Copy".to_string(), "Debug".to_string()],
+            struct_names: Vec::new(),
+            enum_names: Vec::new(),
+        }
+    }
+
+    fn generate<R: Rng>(mut self, rng: &mut R) -> String {
+        let mut out =
+            String::with_capacity(self.target_len.saturating_add(self.target_len / 3).max(512));
+        self.push_module_item(rng, &mut out);
+
+        let first_roll = rng.random_range(0..8);
+        match first_roll {
+            0 => self.push_struct_item(rng, &mut out),
+            1 => self.push_enum_item(rng, &mut out),
+            2 => self.push_trait_item(rng, &mut out),
+            3 => self.push_type_alias_item(rng, &mut out),
+            4 => self.push_extern_fn_item(rng, &mut out),
+            5 => self.push_const_item(rng, &mut out),
+            6 => self.push_import_item(rng, &mut out),
+            _ => self.push_function_item(rng, &mut out),
+        }
+
+        while out.len() < self.target_len {
+            self.push_random_item(rng, &mut out);
+        }
+
+        if self.function_names.is_empty() {
+            self.push_function_item(rng, &mut out);
+        }
+
+        out
+    }
+
+    fn push_random_item<R: Rng>(&mut self, rng: &mut R, out: &mut String) {
+        match rng.random_range(0..10) {
+            0 => self.push_module_item(rng, out),
+            1 => self.push_import_item(rng, out),
+            2 => self.push_const_item(rng, out),
+            3 => self.push_type_alias_item(rng, out),
+            4 => self.push_struct_item(rng, out),
+            5 => self.push_enum_item(rng, out),
+            6 => self.push_trait_item(rng, out),
+            7 => self.push_impl_item(rng, out),
+            8 => self.push_extern_fn_item(rng, out),
+            _ => self.push_function_item(rng, out),
+        }
+    }
+
+    fn push_module_item<R: Rng>(&mut self, rng: &mut R, out: &mut String) {
+        out.push_str("module ");
+        self.push_path(rng, out, "fuzz", 1, 3);
+        out.push_str(";\n\n");
+    }
+
+    fn push_import_item<R: Rng>(&mut self, rng: &mut R, out: &mut String) {
+        if rng.random_bool(0.25) {
+            out.push_str("import \"");
+            out.push_str(match rng.random_range(0..3) {
+                0 => "core",
+                1 => "std/io",
+                _ => "fuzz/support",
+            });
+            out.push_str("\";\n\n");
+            return;
+        }
+        out.push_str("import ");
+        self.push_path(rng, out, "pkg", 1, 4);
+        out.push_str(";\n\n");
+    }
+
+    fn push_const_item<R: Rng>(&mut self, rng: &mut R, out: &mut String) {
+        self.push_pub(rng, out);
+        out.push_str("const ");
+        out.push_str(&self.next_ident("C"));
+        out.push_str(": ");
+        self.push_type_expr(rng, out, 2);
+        out.push_str(" = ");
+        self.push_expr(rng, out, &[], 2);
+        out.push_str(";\n\n");
+    }
+
+    fn push_type_alias_item<R: Rng>(&mut self, rng: &mut R, out: &mut String) {
+        self.push_pub(rng, out);
+        out.push_str("type ");
+        let name = self.next_ident("Alias");
+        self.type_names.push(name.clone());
+        out.push_str(&name);
+        self.push_generics_opt(rng, out, 1);
+        self.push_where_clause_opt(rng, out, 1);
+        out.push_str(" = ");
+        self.push_type_expr(rng, out, 2);
+        out.push_str(";\n\n");
+    }
+
+    fn push_struct_item<R: Rng>(&mut self, rng: &mut R, out: &mut String) {
+        self.push_pub(rng, out);
+        out.push_str("struct ");
+        let name = self.next_ident("S");
+        self.type_names.push(name.clone());
+        self.struct_names.push(name.clone());
+        out.push_str(&name);
+        self.push_generics_opt(rng, out, 1);
+        self.push_where_clause_opt(rng, out, 1);
+        out.push_str(" {\n");
+        let fields = rng.random_range(0..=3);
+        for field_i in 0..fields {
+            out.push_str("    ");
+            out.push_str(&format!("field{field_i}"));
+            out.push_str(": ");
+            self.push_type_expr(rng, out, 2);
+            if field_i + 1 < fields || rng.random_bool(0.65) {
+                out.push(',');
+            }
+            out.push('\n');
+        }
+        out.push_str("}\n\n");
+    }
+
+    fn push_enum_item<R: Rng>(&mut self, rng: &mut R, out: &mut String) {
+        self.push_pub(rng, out);
+        out.push_str("enum ");
+        let name = self.next_ident("E");
+        self.type_names.push(name.clone());
+        self.enum_names.push(name.clone());
+        out.push_str(&name);
+        self.push_generics_opt(rng, out, 1);
+        self.push_where_clause_opt(rng, out, 1);
+        out.push_str(" {\n");
+        let variants = rng.random_range(0..=4);
+        for variant_i in 0..variants {
+            out.push_str("    ");
+            out.push_str(&format!("Variant{variant_i}"));
+            if rng.random_bool(0.45) {
+                out.push('(');
+                let payloads = rng.random_range(0..=3);
+                for payload_i in 0..payloads {
+                    if payload_i > 0 {
+                        out.push_str(", ");
+                    }
+                    self.push_type_expr(rng, out, 1);
+                }
+                if payloads > 0 && rng.random_bool(0.4) {
+                    out.push(',');
+                }
+                out.push(')');
+            }
+            if variant_i + 1 < variants || rng.random_bool(0.75) {
+                out.push(',');
+            }
+            out.push('\n');
+        }
+        out.push_str("}\n\n");
+    }
+
+    fn push_trait_item<R: Rng>(&mut self, rng: &mut R, out: &mut String) {
+        self.push_pub(rng, out);
+        out.push_str("trait ");
+        let name = self.next_ident("Trait");
+        self.trait_names.push(name.clone());
+        out.push_str(&name);
+        self.push_generics_opt(rng, out, 1);
+        self.push_where_clause_opt(rng, out, 1);
+        out.push_str(" {\n");
+        let methods = rng.random_range(0..=3);
+        for _ in 0..methods {
+            out.push_str("    ");
+            if rng.random_bool(0.35) {
+                out.push_str("pub ");
+            }
+            out.push_str("fn ");
+            out.push_str(&self.next_ident("trait_method"));
+            self.push_function_signature_tail(rng, out, true, true);
+            out.push_str(";\n");
+        }
+        out.push_str("}\n\n");
+    }
+
+    fn push_impl_item<R: Rng>(&mut self, rng: &mut R, out: &mut String) {
+        self.push_pub(rng, out);
+        out.push_str("impl ");
+        self.push_generics_opt(rng, out, 1);
+        if rng.random_bool(0.45) && !self.trait_names.is_empty() {
+            self.push_known_trait_path(rng, out);
+            out.push_str(" for ");
+            self.push_known_type_path(rng, out);
+        } else {
+            self.push_known_type_path(rng, out);
+        }
+        self.push_where_clause_opt(rng, out, 1);
+        out.push_str(" {\n");
+        let methods = rng.random_range(0..=3);
+        for _ in 0..methods {
+            out.push_str("    ");
+            if rng.random_bool(0.35) {
+                out.push_str("pub ");
+            }
+            out.push_str("fn ");
+            out.push_str(&self.next_ident("method"));
+            self.push_function_signature_tail(rng, out, true, false);
+            out.push_str(" {\n");
+            self.push_stmt_list(rng, out, 2, 1, false);
+            out.push_str("    }\n");
+        }
+        out.push_str("}\n\n");
+    }
+
+    fn push_extern_fn_item<R: Rng>(&mut self, rng: &mut R, out: &mut String) {
+        self.push_pub(rng, out);
+        out.push_str("extern ");
+        if rng.random_bool(0.7) {
+            out.push_str("\"C\" ");
+        }
+        out.push_str("fn ");
+        let name = self.next_fn_name();
+        out.push_str(&name);
+        self.push_function_signature_tail(rng, out, false, true);
+        out.push_str(";\n\n");
+    }
+
+    fn push_function_item<R: Rng>(&mut self, rng: &mut R, out: &mut String) {
+        self.push_pub(rng, out);
+        out.push_str("fn ");
+        let name = self.next_fn_name();
+        out.push_str(&name);
+        self.push_function_signature_tail(rng, out, false, false);
+        out.push_str(" {\n");
+        self.push_stmt_list(rng, out, 1, 0, false);
+        out.push_str("}\n\n");
+    }
+
+    fn push_function_signature_tail<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        out: &mut String,
+        allow_self: bool,
+        semicolon_body: bool,
+    ) {
+        self.push_generics_opt(rng, out, 1);
+        out.push('(');
+        self.push_param_list(rng, out, allow_self);
+        out.push(')');
+        if semicolon_body || rng.random_bool(0.8) {
+            out.push_str(" -> ");
+            self.push_type_expr(rng, out, 2);
+        }
+        self.push_where_clause_opt(rng, out, 1);
+    }
+
+    fn push_param_list<R: Rng>(&mut self, rng: &mut R, out: &mut String, allow_self: bool) {
+        let mut wrote = false;
+        if allow_self && rng.random_bool(0.45) {
+            if rng.random_bool(0.5) {
+                out.push_str("&self");
+            } else {
+                out.push_str("self");
+                if rng.random_bool(0.25) {
+                    out.push_str(": ");
+                    self.push_type_expr(rng, out, 1);
+                }
+            }
+            wrote = true;
+        }
+        let params = rng.random_range(0..=3);
+        for param_i in 0..params {
+            if wrote {
+                out.push_str(", ");
+            }
+            wrote = true;
+            out.push_str(&format!("p{param_i}"));
+            out.push_str(": ");
+            self.push_type_expr(rng, out, 2);
+        }
+        if wrote && rng.random_bool(0.2) {
+            out.push(',');
+        }
+    }
+
+    fn push_stmt_list<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        out: &mut String,
+        indent: usize,
+        depth: usize,
+        in_loop: bool,
+    ) {
+        let stmt_count = rng.random_range(1..=4);
+        let mut locals = Vec::new();
+        for param_i in 0..=2 {
+            locals.push(format!("p{param_i}"));
+        }
+        for _ in 0..stmt_count {
+            self.push_wide_stmt(rng, out, indent, depth, in_loop, &mut locals);
+        }
+        if rng.random_bool(0.65) {
+            self.push_indent(out, indent);
+            out.push_str("return ");
+            self.push_expr(rng, out, &locals, 2);
+            out.push_str(";\n");
+        }
+    }
+
+    fn push_wide_stmt<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        out: &mut String,
+        indent: usize,
+        depth: usize,
+        in_loop: bool,
+        locals: &mut Vec<String>,
+    ) {
+        match rng.random_range(0..10) {
+            0 | 1 => {
+                let name = self.next_local_name();
+                self.push_indent(out, indent);
+                out.push_str("let ");
+                out.push_str(&name);
+                if rng.random_bool(0.75) {
+                    out.push_str(": ");
+                    self.push_type_expr(rng, out, 2);
+                }
+                if rng.random_bool(0.8) {
+                    out.push_str(" = ");
+                    self.push_expr(rng, out, locals, 2);
+                }
+                out.push_str(";\n");
+                locals.push(name);
+            }
+            2 => {
+                self.push_indent(out, indent);
+                out.push_str("return");
+                if rng.random_bool(0.8) {
+                    out.push(' ');
+                    self.push_expr(rng, out, locals, 2);
+                }
+                out.push_str(";\n");
+            }
+            3 if depth < 2 => {
+                self.push_indent(out, indent);
+                out.push_str("if (");
+                self.push_expr(rng, out, locals, 2);
+                out.push_str(") {\n");
+                self.push_wide_stmt(rng, out, indent + 1, depth + 1, in_loop, locals);
+                self.push_indent(out, indent);
+                out.push_str("}\n");
+                if rng.random_bool(0.75) {
+                    self.push_indent(out, indent);
+                    out.push_str("else {\n");
+                    self.push_wide_stmt(rng, out, indent + 1, depth + 1, in_loop, locals);
+                    self.push_indent(out, indent);
+                    out.push_str("}\n");
+                }
+            }
+            4 if depth < 2 => {
+                self.push_indent(out, indent);
+                out.push_str("while (");
+                self.push_expr(rng, out, locals, 2);
+                out.push_str(") {\n");
+                self.push_wide_stmt(rng, out, indent + 1, depth + 1, true, locals);
+                if rng.random_bool(0.5) {
+                    self.push_indent(out, indent + 1);
+                    out.push_str("break;\n");
+                }
+                self.push_indent(out, indent);
+                out.push_str("}\n");
+            }
+            5 if depth < 2 => {
+                self.push_indent(out, indent);
+                out.push_str("for ");
+                out.push_str(&self.next_local_name());
+                out.push_str(" in ");
+                self.push_path(rng, out, "iter", 1, 3);
+                out.push_str(" {\n");
+                self.push_wide_stmt(rng, out, indent + 1, depth + 1, true, locals);
+                self.push_indent(out, indent);
+                out.push_str("}\n");
+            }
+            6 if in_loop => {
+                self.push_indent(out, indent);
+                out.push_str(if rng.random_bool(0.5) {
+                    "break;\n"
+                } else {
+                    "continue;\n"
+                });
+            }
+            7 if depth < 2 => {
+                self.push_indent(out, indent);
+                out.push_str("{\n");
+                self.push_wide_stmt(rng, out, indent + 1, depth + 1, in_loop, locals);
+                self.push_indent(out, indent);
+                out.push_str("}\n");
+            }
+            _ => {
+                self.push_indent(out, indent);
+                self.push_expr(rng, out, locals, 3);
+                out.push_str(";\n");
+            }
+        }
+    }
+
+    fn push_expr<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        out: &mut String,
+        locals: &[String],
+        depth: usize,
+    ) {
+        if depth == 0 {
+            self.push_primary_expr(rng, out, locals, 0);
+            return;
+        }
+
+        match rng.random_range(0..12) {
+            0 => self.push_primary_expr(rng, out, locals, depth),
+            1 => {
+                out.push('(');
+                self.push_expr(rng, out, locals, depth - 1);
+                out.push(')');
+            }
+            2 => {
+                // Keep recursively generated prefix operators lexically
+                // distinct from `++` and `--`.
+                out.push_str(match rng.random_range(0..3) {
+                    0 => "+",
+                    1 => "-",
+                    _ => "!",
+                });
+                out.push(' ');
+                self.push_expr(rng, out, locals, depth - 1);
+            }
+            3 if !locals.is_empty() => {
+                self.push_local_ref(rng, out, locals);
+                out.push(' ');
+                out.push_str(self.random_assignment_op(rng));
+                out.push(' ');
+                self.push_expr(rng, out, locals, depth - 1);
+            }
+            4 => {
+                self.push_expr(rng, out, locals, depth - 1);
+                out.push(' ');
+                out.push_str(self.random_binary_op(rng));
+                out.push(' ');
+                self.push_expr(rng, out, locals, depth - 1);
+            }
+            5 => self.push_array_literal(rng, out, locals, depth - 1),
+            6 => self.push_match_expr(rng, out, locals, depth - 1),
+            7 => self.push_struct_literal(rng, out, locals, depth - 1),
+            _ => self.push_postfix_expr(rng, out, locals, depth - 1),
+        }
+    }
+
+    fn push_primary_expr<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        out: &mut String,
+        locals: &[String],
+        depth: usize,
+    ) {
+        match rng.random_range(0..10) {
+            0 if !locals.is_empty() => self.push_local_ref(rng, out, locals),
+            1 => out.push_str(&rng.random_range(0..=999).to_string()),
+            2 => out.push_str(if rng.random_bool(0.5) {
+                "true"
+            } else {
+                "false"
+            }),
+            3 => out.push_str(match rng.random_range(0..3) {
+                0 => "\"text\"",
+                1 => "\"\"",
+                _ => "\"line\\ntext\"",
+            }),
+            4 => out.push_str(match rng.random_range(0..3) {
+                0 => "'a'",
+                1 => "'\\n'",
+                _ => "'0'",
+            }),
+            5 => out.push_str(match rng.random_range(0..3) {
+                0 => "0.0",
+                1 => "1.5",
+                _ => "10e2",
+            }),
+            6 if depth > 0 => self.push_array_literal(rng, out, locals, depth - 1),
+            7 if depth > 0 => self.push_struct_literal(rng, out, locals, depth - 1),
+            8 if depth > 0 => self.push_match_expr(rng, out, locals, depth - 1),
+            _ => self.push_path(rng, out, "value", 1, 3),
+        }
+    }
+
+    fn push_postfix_expr<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        out: &mut String,
+        locals: &[String],
+        depth: usize,
+    ) {
+        self.push_primary_expr(rng, out, locals, depth);
+        let postfixes = rng.random_range(0..=2);
+        for _ in 0..postfixes {
+            match rng.random_range(0..3) {
+                0 => {
+                    out.push('(');
+                    let args = rng.random_range(0..=3);
+                    for arg_i in 0..args {
+                        if arg_i > 0 {
+                            out.push_str(", ");
+                        }
+                        self.push_expr(rng, out, locals, depth.saturating_sub(1));
+                    }
+                    if args > 0 && rng.random_bool(0.25) {
+                        out.push(',');
+                    }
+                    out.push(')');
+                }
+                1 => {
+                    out.push('[');
+                    self.push_expr(rng, out, locals, depth.saturating_sub(1));
+                    out.push(']');
+                }
+                2 => {
+                    out.push('.');
+                    out.push_str(&format!("field{}", rng.random_range(0..=3)));
+                }
+                _ => unreachable!("postfix generator samples exactly three postfix forms"),
+            }
+        }
+    }
+
+    fn push_array_literal<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        out: &mut String,
+        locals: &[String],
+        depth: usize,
+    ) {
+        out.push('[');
+        let elems = rng.random_range(0..=3);
+        for elem_i in 0..elems {
+            if elem_i > 0 {
+                out.push_str(", ");
+            }
+            self.push_expr(rng, out, locals, depth);
+        }
+        if elems > 0 && rng.random_bool(0.35) {
+            out.push(',');
+        }
+        out.push(']');
+    }
+
+    fn push_struct_literal<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        out: &mut String,
+        locals: &[String],
+        depth: usize,
+    ) {
+        self.push_known_type_path(rng, out);
+        out.push_str(" { ");
+        let fields = rng.random_range(0..=3);
+        for field_i in 0..fields {
+            if field_i > 0 {
+                out.push_str(", ");
+            }
+            out.push_str(&format!("field{field_i}: "));
+            self.push_expr(rng, out, locals, depth);
+        }
+        if fields > 0 && rng.random_bool(0.35) {
+            out.push(',');
+        }
+        out.push_str(" }");
+    }
+
+    fn push_match_expr<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        out: &mut String,
+        locals: &[String],
+        depth: usize,
+    ) {
+        out.push_str("match (");
+        self.push_expr(rng, out, locals, depth);
+        out.push_str(") { ");
+        let arms = rng.random_range(0..=3);
+        for arm_i in 0..arms {
+            if arm_i > 0 {
+                out.push_str(", ");
+            }
+            self.push_pattern(rng, out, depth);
+            out.push_str(" => ");
+            self.push_expr(rng, out, locals, depth);
+        }
+        if arms > 0 && rng.random_bool(0.5) {
+            out.push(',');
+        }
+        out.push_str(" }");
+    }
+
+    fn push_pattern<R: Rng>(&mut self, rng: &mut R, out: &mut String, depth: usize) {
+        match rng.random_range(0..5) {
+            0 => out.push_str(&rng.random_range(0..=7).to_string()),
+            1 => out.push_str("true"),
+            2 => out.push_str("false"),
+            3 if depth > 0 => {
+                self.push_path(rng, out, "Variant", 1, 2);
+                out.push('(');
+                let patterns = rng.random_range(0..=2);
+                for pattern_i in 0..patterns {
+                    if pattern_i > 0 {
+                        out.push_str(", ");
+                    }
+                    self.push_pattern(rng, out, depth - 1);
+                }
+                if patterns > 0 && rng.random_bool(0.35) {
+                    out.push(',');
+                }
+                out.push(')');
+            }
+            _ => self.push_path(rng, out, "Pattern", 1, 2),
+        }
+    }
+
+    fn push_type_expr<R: Rng>(&mut self, rng: &mut R, out: &mut String, depth: usize) {
+        if depth == 0 {
+            self.push_known_type_path(rng, out);
+            return;
+        }
+        match rng.random_range(0..5) {
+            0 => {
+                // Keep adjacent reference tokens from maximal-munching into
+                // the expression-level `&&` token.
+                out.push_str("& ");
+                self.push_type_expr(rng, out, depth - 1);
+            }
+            1 => {
+                out.push('[');
+                self.push_type_expr(rng, out, depth - 1);
+                if rng.random_bool(0.55) {
+                    out.push_str("; ");
+                    if rng.random_bool(0.7) {
+                        out.push_str(&rng.random_range(0..=8).to_string());
+                    } else {
+                        out.push_str(&self.next_ident("N"));
+                    }
+                }
+                out.push(']');
+            }
+            _ => {
+                self.push_known_type_path(rng, out);
+                if rng.random_bool(0.35) {
+                    out.push('<');
+                    let args = rng.random_range(1..=3);
+                    for arg_i in 0..args {
+                        if arg_i > 0 {
+                            out.push_str(", ");
+                        }
+                        self.push_type_expr(rng, out, depth - 1);
+                    }
+                    if rng.random_bool(0.25) {
+                        out.push(',');
+                    }
+                    out.push('>');
+                }
+            }
+        }
+    }
+
+    fn push_generics_opt<R: Rng>(&mut self, rng: &mut R, out: &mut String, depth: usize) {
+        if depth == 0 || !rng.random_bool(0.35) {
+            return;
+        }
+        out.push('<');
+        let params = rng.random_range(1..=3);
+        for param_i in 0..params {
+            if param_i > 0 {
+                out.push_str(", ");
+            }
+            if rng.random_bool(0.25) {
+                out.push_str("const ");
+                out.push_str(&self.next_ident("N"));
+                out.push_str(": ");
+                self.push_type_expr(rng, out, depth - 1);
+            } else {
+                out.push_str(&self.next_ident("T"));
+                if rng.random_bool(0.45) {
+                    out.push_str(": ");
+                    self.push_bound_type_expr(rng, out, depth - 1);
+                    if rng.random_bool(0.35) {
+                        out.push_str(" + ");
+                        self.push_bound_type_expr(rng, out, depth - 1);
+                    }
+                }
+            }
+        }
+        if rng.random_bool(0.25) {
+            out.push(',');
+        }
+        out.push('>');
+    }
+
+    fn push_where_clause_opt<R: Rng>(&mut self, rng: &mut R, out: &mut String, depth: usize) {
+        if depth == 0 || !rng.random_bool(0.25) {
+            return;
+        }
+        out.push_str(" where ");
+        let predicates = rng.random_range(1..=2);
+        for pred_i in 0..predicates {
+            if pred_i > 0 {
+                out.push_str(", ");
+            }
+            out.push_str(&self.next_ident("T"));
+            out.push_str(": ");
+            self.push_bound_type_expr(rng, out, depth - 1);
+            if rng.random_bool(0.35) {
+                out.push_str(" + ");
+                self.push_bound_type_expr(rng, out, depth - 1);
+            }
+        }
+        if rng.random_bool(0.25) {
+            out.push(',');
+        }
+    }
+
+    fn push_bound_type_expr<R: Rng>(&mut self, rng: &mut R, out: &mut String, depth: usize) {
+        if depth > 0 && rng.random_bool(0.25) {
+            out.push_str("& ");
+            self.push_bound_type_expr(rng, out, depth - 1);
+            return;
+        }
+        self.push_known_trait_path(rng, out);
+        if depth > 0 && rng.random_bool(0.25) {
+            out.push('<');
+            self.push_bound_type_expr(rng, out, depth - 1);
+            if rng.random_bool(0.25) {
+                out.push(',');
+            }
+            out.push('>');
+        }
+    }
+
+    fn push_path<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        out: &mut String,
+        prefix: &str,
+        min_segments: usize,
+        max_segments: usize,
+    ) {
+        let segments = rng.random_range(min_segments..=max_segments);
+        for segment_i in 0..segments {
+            if segment_i > 0 {
+                out.push_str("::");
+            }
+            out.push_str(prefix);
+            out.push_str(&self.item_index.to_string());
+            out.push('_');
+            out.push_str(&segment_i.to_string());
+        }
+        self.item_index += 1;
+    }
+
+    fn push_known_type_path<R: Rng>(&mut self, rng: &mut R, out: &mut String) {
+        let names = if self.type_names.is_empty() {
+            &["i32"][..]
+        } else {
+            &[][..]
+        };
+        if self.type_names.is_empty() {
+            out.push_str(names[0]);
+        } else {
+            let i = rng.random_range(0..self.type_names.len());
+            out.push_str(&self.type_names[i]);
+        }
+    }
+
+    fn push_known_trait_path<R: Rng>(&mut self, rng: &mut R, out: &mut String) {
+        if self.trait_names.is_empty() {
+            out.push_str("Copy");
+        } else {
+            let i = rng.random_range(0..self.trait_names.len());
+            out.push_str(&self.trait_names[i]);
+        }
+    }
+
+    fn push_local_ref<R: Rng>(&self, rng: &mut R, out: &mut String, locals: &[String]) {
+        let i = rng.random_range(0..locals.len());
+        out.push_str(&locals[i]);
+    }
+
+    fn next_fn_name(&mut self) -> String {
+        let name = format!("f{}", self.fn_index);
+        self.fn_index += 1;
+        self.function_names.push(name.clone());
+        name
+    }
+
+    fn next_local_name(&mut self) -> String {
+        let name = format!("v{}", self.local_name_index);
+        self.local_name_index += 1;
+        name
+    }
+
+    fn next_ident(&mut self, prefix: &str) -> String {
+        let name = format!("{prefix}{}", self.item_index);
+        self.item_index += 1;
+        name
+    }
+
+    fn push_pub<R: Rng>(&self, rng: &mut R, out: &mut String) {
+        if rng.random_bool(0.35) {
+            out.push_str("pub ");
+        }
+    }
+
+    fn push_indent(&self, out: &mut String, indent: usize) {
+        for _ in 0..indent {
+            out.push_str("    ");
+        }
+    }
+
+    fn random_assignment_op<R: Rng>(&self, rng: &mut R) -> &'static str {
+        const OPS: [&str; 11] = [
+            "=", "+=", "-=", "*=", "/=", "%=", "^=", "<<=", ">>=", "&=", "|=",
+        ];
+        OPS[rng.random_range(0..OPS.len())]
+    }
+
+    fn random_binary_op<R: Rng>(&self, rng: &mut R) -> &'static str {
+        const OPS: [&str; 16] = [
+            "||", "&&", "|", "^", "&", "==", "!=", "<", ">", "<=", ">=", "<<", ">>", "+", "-", "*",
+        ];
+        OPS[rng.random_range(0..OPS.len())]
+    }
+}
+
+#[test]
+fn trailer_never_opens_comment_after_slash() {
+    let s = String::from("/") + SAFE_TRAILER;
+    assert!(
+        !s.contains("/*"),
+        "trailer must not create /* across the boundary"
+    );
+}
+
+#[test]
+fn trailer_can_close_open_comment() {
+    let s = String::from("/*") + SAFE_TRAILER;
+    assert!(
+        s.contains("*/"),
+        "trailer should contain a contiguous */ to close"
+    );
+}
+
+#[test]
+fn generated_sources_sometimes_end_inside_line_comment() {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let sources = (0..64)
+        .map(|_| gen_valid_source(&mut rng, 32))
+        .collect::<Vec<_>>();
+    let ends_in_comment = |s: &str| {
+        let last_line = s.rsplit('\n').next().unwrap_or_default();
+        !s.ends_with('\n') && last_line.contains("//")
+    };
+    assert!(
+        sources
+            .iter()
+            .any(|s| ends_in_comment(s) && s.contains(" 0//"))
+    );
+    assert!(
+        sources
+            .iter()
+            .any(|s| ends_in_comment(s) && !s.contains(" 0//"))
+    );
+    assert!(sources.iter().any(|s| s.ends_with(SAFE_TRAILER)));
+}
+
+/// Proptest strategy over [`SourceGenConfig`]s.
+pub fn arb_source_gen_config() -> impl proptest::strategy::Strategy<Value = SourceGenConfig> {
+    use proptest::{prelude::*, sample::select};
+
+    let weights = (0u32..50, 0u32..50, 0u32..50, 0u32..50, 0u32..50, 0u32..50).prop_map(
+        |(ident, number, whitespace, operator, string, bracket)| TokenWeights {
+            ident,
+            number,
+            whitespace,
+            operator,
+            string,
+            bracket,
+        },
+    );
+    (
+        0usize..512,
+        0.0f64..=1.0,
+        0.0f64..=1.0,
+        proptest::option::of(0.0f64..=1.0),
+        0usize..64,
+        select(vec![
+            NewlineStyle::Mixed,
+            NewlineStyle::Lf,
+            NewlineStyle::CrLf,
+        ]),
+        0u32..=NUMERIC_ALL,
+        select(vec![
+            IdentifierCharset::AsciiAlphanumeric,
+            IdentifierCharset::Alphabetic,
+            IdentifierCharset::UnderscoreHeavy,
+        ]),
+        weights,
+        proptest::option::of(
+            (
+                select(vec![LongTokenKind::BlockComment, LongTokenKind::String]),
+                0.0f64..=1.0,
+                1usize..4096,
+            )
+                .prop_map(|(kind, share, max_len)| LongTokens {
+                    kind,
+                    share,
+                    max_len,
+                }),
+        ),
+    )
+        .prop_map(
+            |(
+                target_len,
+                comment_density,
+                nested_comment_rate,
+                string_escape_rate,
+                max_bracket_depth,
+                newline_style,
+                numeric_styles,
+                identifier_charset,
+                weights,
+                long_tokens,
+            )| SourceGenConfig {
+                target_len,
+                comment_density,
+                nested_comment_rate,
+                string_escape_rate,
+                max_bracket_depth,
+                newline_style,
+                numeric_styles,
+                identifier_charset,
+                weights,
+                long_tokens,
+            },
+        )
+}
+
+#[test]
+fn every_config_generates_lexically_valid_source() {
+    use proptest::test_runner::{Config, TestCaseError, TestRunner};
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::lexer::test_cpu::lex_on_test_cpu;
+
+    let mut runner = TestRunner::new(Config {
+        cases: 1000,
+        failure_persistence: None,
+        ..Config::default()
+    });
+    runner
+        .run(
+            &(arb_source_gen_config(), proptest::num::u64::ANY),
+            |(config, seed)| {
+                let s = gen_source(&mut StdRng::seed_from_u64(seed), &config);
+                prop_assert_len(&s, &config)?;
+                lex_on_test_cpu(&s).map_err(|err| {
+                    TestCaseError::fail(format!("{err}\nconfig: {config:?}\nsource: {s:?}"))
+                })?;
+                match config.newline_style {
+                    NewlineStyle::Mixed => {}
+                    NewlineStyle::Lf => {
+                        if s.contains('\r') {
+                            return Err(TestCaseError::fail(format!("CR in LF output {s:?}")));
+                        }
+                    }
+                    NewlineStyle::CrLf => {
+                        if s.replace("\r\n", "").contains(['\r', '\n']) {
+                            return Err(TestCaseError::fail(format!(
+                                "lone CR or LF in CRLF output {s:?}"
+                            )));
+                        }
+                    }
+                }
+                Ok(())
+            },
+        )
+        .unwrap();
+
+    fn prop_assert_len(
+        s: &str,
+        config: &SourceGenConfig,
+    ) -> Result<(), proptest::test_runner::TestCaseError> {
+        if s.len() < config.target_len {
+            return Err(proptest::test_runner::TestCaseError::fail(format!(
+                "{} bytes is below target {}",
+                s.len(),
+                config.target_len
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn family_table_keeps_default_roll_ranges() {
+    let table = family_table(&SourceGenConfig::default());
+    let slots: Vec<u32> = table.iter().map(|&(_, slots)| slots).collect();
+    assert_eq!(slots, [25, 15, 15, 7, 9, 24, 5, 0]);
+
+    let mut no_numbers = SourceGenConfig::default();
+    no_numbers.numeric_styles = NUMERIC_UNDERSCORES;
+    let table = family_table(&no_numbers);
+    assert_eq!(table[1], (TokenFamily::Number, 0));
+    assert_eq!(table.iter().map(|&(_, s)| s).sum::<u32>(), ROLL_SLOTS);
+}
+
+#[test]
+fn presets_stress_their_target_families() {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::lexer::{
+        tables::tokens::TokenKind,
+        test_cpu::{lex_on_test_cpu, lex_on_test_cpu_all},
+    };
+
+    let sample = |config: SourceGenConfig| {
+        let config = SourceGenConfig {
+            target_len: 16 * 1024,
+            ..config
+        };
+        let s = gen_source(&mut StdRng::seed_from_u64(11), &config);
+        let tokens = lex_on_test_cpu(&s).expect("preset output lexes");
+        (s, tokens)
+    };
+    let max_depth = |s: &str| {
+        let mut depth = 0i64;
+        let mut max = 0;
+        for b in s.bytes() {
+            match b {
+                b'(' | b'[' | b'{' => depth += 1,
+                b')' | b']' | b'}' => depth -= 1,
+                _ => {}
+            }
+            max = max.max(depth);
+        }
+        max
+    };
+
+    let (default_src, default_tokens) = sample(SourceGenConfig::default());
+    let (ops_src, ops_tokens) = sample(SourceGenConfig::operator_heavy());
+    let density = |tokens: usize, s: &str| tokens as f64 / s.len() as f64;
+    assert!(
+        density(ops_tokens.len(), &ops_src) > 1.5 * density(default_tokens.len(), &default_src)
+    );
+
+    let (comment_src, _) = sample(SourceGenConfig::comment_heavy());
+    let comment_bytes: usize = lex_on_test_cpu_all(&comment_src)
+        .expect("comment-heavy output lexes")
+        .iter()
+        .filter(|t| matches!(t.kind, TokenKind::LineComment | TokenKind::BlockComment))
+        .map(|t| t.len())
+        .sum();
+    assert!(comment_bytes * 2 > comment_src.len());
+
+    let (deep_src, _) = sample(SourceGenConfig::bracket_deep());
+    assert!(max_depth(&deep_src) >= 64, "depth {}", max_depth(&deep_src));
+
+    let longest = |s: &str, kind: TokenKind| {
+        lex_on_test_cpu_all(s)
+            .expect("long-token output lexes")
+            .iter()
+            .filter(|t| t.kind == kind)
+            .map(|t| t.len())
+            .collect::<Vec<_>>()
+    };
+    let (long_comment_src, _) = sample(SourceGenConfig::long_comment());
+    let comments = longest(&long_comment_src, TokenKind::BlockComment);
+    assert!(
+        comments
+            .iter()
+            .any(|&len| len * 10 >= long_comment_src.len() * 9)
+    );
+    let (long_string_src, _) = sample(SourceGenConfig::long_string());
+    let strings = longest(&long_string_src, TokenKind::String);
+    assert!(
+        strings
+            .iter()
+            .any(|&len| len * 10 >= long_string_src.len() * 9)
+    );
+    let (alternating_src, _) = sample(SourceGenConfig::long_alternating());
+    let comments = longest(&alternating_src, TokenKind::BlockComment);
+    assert!(comments.len() >= 4, "{} comments", comments.len());
+    assert!(comments.iter().all(|&len| len >= 1600));
+
+    for name in PROFILES {
+        assert!(SourceGenConfig::from_profile(name).is_some(), "{name}");
+    }
+    assert!(SourceGenConfig::from_profile("uniform").is_none());
+}
+
+#[test]
+fn default_profile_covers_every_reachable_dfa_state() {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::lexer::test_cpu::{
+        Coverage,
+        coverage::UNREACHABLE_STATES,
+        lex_on_test_cpu_with_coverage,
+    };
+
+    let mut rng = StdRng::seed_from_u64(903);
+    let mut coverage = Coverage::new();
+    for _ in 0..4 {
+        let s = gen_source(&mut rng, &SourceGenConfig::with_len(64 * 1024));
+        lex_on_test_cpu_with_coverage(&s, &mut coverage).expect("default output lexes");
+    }
+    let report = coverage.report(1, UNREACHABLE_STATES);
+    assert!(report.is_complete(), "{report}");
+}
@@ -1,2 +1,4 @@
 /// Random valid-source and parser-program generators for fuzzing and perf work.
 pub mod generator;
+/// Pairwise operator-adjacency cases and the lexer snapshot over them.
+pub mod operator_adjacency;
@@ -0,0 +1,199 @@
+//! Pairwise operator-adjacency cases and their lexer snapshot.
+//!
+//! Every ordered pair of [`OPERATORS`] is lexed with each of [`SEPARATORS`]
+//! between the two lexemes. The snapshot lists each lexeme's own kinds, then
+//! only the cases whose kept kinds are not simply the left lexeme's kinds
+//! followed by the right lexeme's: merges such as `~` `=` into `TildeAssign`,
+//! splits such as `/` `/*c*/` into a line comment, and lex errors.
+//!
+//! The checked-in snapshot is `tests/cases/lexer/operator_adjacency.snap`; the
+//! unit test rewrites it when `LANIUS_UPDATE_SNAPSHOTS=1` is set.
+//!
+//! [`OPERATORS`]: crate::dev::operator_adjacency::OPERATORS
+//! [`SEPARATORS`]: crate::dev::operator_adjacency::SEPARATORS
+
+use std::{fmt::Write as _, path::PathBuf};
+
+use crate::lexer::{tables::tokens::TokenKind, test_cpu::lex_on_test_cpu};
+
+/// Operator and punctuation lexemes: singles, doubles, then compound
+/// assignments.
+pub const OPERATORS: &[&str] = &[
+    "(", ")", "[", "]", "{", "}", "+", "-", "*", "/", "%", "^", "~", "!", "=", "<", ">", "&", "|",
+    ".", ",", ";", ":", "?", "==", "!=", "<=", ">=", "<<", ">>", "&&", "||", "<>", "=>", "->",
+    "++", "--", "..", "+=", "-=", "*=", "/=", "%=", "^=", "~=", "&=", "|=", "<<=", ">>=",
+];
+
+/// Separator name and text placed between the two lexemes of a pair.
+pub const SEPARATORS: &[(&str, &str)] = &[("none", ""), ("space", " "), ("comment", "/*c*/")];
+
+/// One lexed case: a lone lexeme (`right` empty) or an ordered pair.
+#[derive(Clone, Copy, Debug)]
+pub struct Case {
+    pub left: &'static str,
+    /// Index into [`SEPARATORS`].
+    pub separator: usize,
+    pub right: &'static str,
+}
+
+impl Case {
+    /// Source text for this case, without a trailing newline.
+    pub fn source(&self) -> String {
+        if self.right.is_empty() {
+            return self.left.to_string();
+        }
+        format!(
+            "{}{}{}",
+            self.left, SEPARATORS[self.separator].1, self.right
+        )
+    }
+}
+
+/// Every lone lexeme in [`OPERATORS`] order, then every ordered pair under
+/// every separator.
+pub fn cases() -> Vec<Case> {
+    let alone = OPERATORS.iter().map(|&left| Case {
+        left,
+        separator: 0,
+        right: "",
+    });
+    let pairs = OPERATORS.iter().flat_map(|&left| {
+        OPERATORS.iter().flat_map(move |&right| {
+            (0..SEPARATORS.len()).map(move |separator| Case {
+                left,
+                separator,
+                right,
+            })
+        })
+    });
+    alone.chain(pairs).collect()
+}
+
+/// Kept token kinds for each case from the test CPU oracle.
+pub fn lex_cases_on_test_cpu(cases: &[Case]) -> Vec<Result<Vec<TokenKind>, String>> {
+    cases
+        .iter()
+        .map(|case| {
+            lex_on_test_cpu(&case.source())
+                .map(|tokens| tokens.into_iter().map(|token| token.kind).collect())
+        })
+        .collect()
+}
+
+/// Splits tokens of `source` into per-line kind lists, one per line.
+pub fn kinds_by_line(
+    source: &str,
+    tokens: impl IntoIterator<Item = (TokenKind, usize)>,
+) -> Vec<Vec<TokenKind>> {
+    let mut line_starts = vec![0];
+    line_starts.extend(source.match_indices('\n').map(|(i, _)| i + 1));
+    let mut lines = vec![Vec::new(); line_starts.len()];
+    for (kind, start) in tokens {
+        let line = line_starts.partition_point(|&s| s <= start) - 1;
+        lines[line].push(kind);
+    }
+    lines
+}
+
+/// Renders the snapshot for `cases` and their lexed kinds, which must start
+/// with the lone lexemes as [`cases`] orders them.
+pub fn render_snapshot(cases: &[Case], lexed: &[Result<Vec<TokenKind>, String>]) -> String {
+    fn kinds_text(result: &Result<Vec<TokenKind>, String>) -> String {
+        match result {
+            Ok(kinds) if kinds.is_empty() => "(none)".to_string(),
+            Ok(kinds) => kinds
+                .iter()
+                .map(|kind| format!("{kind:?}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Err(err) => format!("error: {err}"),
+        }
+    }
+
+    let alone = &lexed[..OPERATORS.len()];
+    let alone_kinds = |lexeme: &str| {
+        let i = OPERATORS.iter().position(|&op| op == lexeme)?;
+        alone[i].as_ref().ok()
+    };
+
+    let mut out = String::from("# lexeme -> kinds\n");
+    for (op, result) in OPERATORS.iter().zip(alone) {
+        writeln!(out, "{op} -> {}", kinds_text(result)).unwrap();
+    }
+    out.push_str("\n# separator left right -> kinds, where not left's kinds then right's\n");
+    for (case, result) in cases.iter().zip(lexed).skip(OPERATORS.len()) {
+        let trivial = match (result, alone_kinds(case.left), alone_kinds(case.right)) {
+            (Ok(kinds), Some(left), Some(right)) => {
+                kinds.len() == left.len() + right.len()
+                    && kinds[..left.len()] == left[..]
+                    && kinds[left.len()..] == right[..]
+            }
+            _ => false,
+        };
+        if !trivial {
+            writeln!(
+                out,
+                "{} {} {} -> {}",
+                SEPARATORS[case.separator].0,
+                case.left,
+                case.right,
+                kinds_text(result)
+            )
+            .unwrap();
+        }
+    }
+    out
+}
+
+/// Path of the checked-in snapshot.
+pub fn snapshot_path() -> PathBuf {
+    PathBuf::from(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../tests/cases/lexer/operator_adjacency.snap"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<TokenKind> {
+        lex_on_test_cpu(source)
+            .expect("lex")
+            .into_iter()
+            .map(|token| token.kind)
+            .collect()
+    }
+
+    #[test]
+    fn snapshot_matches_test_cpu_lexer() {
+        let cases = cases();
+        let rendered = render_snapshot(&cases, &lex_cases_on_test_cpu(&cases));
+        let path = snapshot_path();
+        if std::env::var("LANIUS_UPDATE_SNAPSHOTS").as_deref() == Ok("1") {
+            std::fs::write(&path, &rendered).expect("write snapshot");
+        }
+        let expected = std::fs::read_to_string(&path).expect("read snapshot");
+        assert!(
+            rendered == expected,
+            "operator adjacency snapshot is stale; rerun with LANIUS_UPDATE_SNAPSHOTS=1"
+        );
+    }
+
+    #[test]
+    fn operator_set_decisions() {
+        use TokenKind::*;
+        assert_eq!(kinds("~="), [TildeAssign]);
+        assert_eq!(kinds("&&="), [AndAnd, Assign]);
+        assert_eq!(kinds("||="), [OrOr, Assign]);
+        assert_eq!(kinds("!!"), [Not, Not]);
+        assert_eq!(kinds("!="), [NotEqual]);
+    }
+
+    #[test]
+    fn kinds_by_line_groups_on_token_start() {
+        use TokenKind::*;
+        let lines = kinds_by_line("a\n\nb c", [(Ident, 0), (Ident, 3), (Ident, 5)]);
+        assert_eq!(lines, [vec![Ident], vec![], vec![Ident, Ident]]);
+    }
+}
@@ -0,0 +1,46 @@
+//! Planned dispatch shapes, recorded by the GPU drivers and returned with
+//! their outputs.
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Logical input size supplied to dispatch planning.
+pub enum InputElements {
+    /// One-dimensional element count.
+    Elements1D(u32),
+    /// Two-dimensional width and height.
+    Elements2D(u32, u32),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Planned shape of one recorded dispatch, for tools without a graphics debugger.
+pub struct DispatchRecord {
+    /// Pass name, or `name[round=r, stride=s]` for one block-scan round.
+    pub label: String,
+    /// Planned workgroup counts; `None` when the counts come from a GPU buffer.
+    pub workgroups: Option<(u32, u32, u32)>,
+    /// Logical input the workgroups were planned from; `None` for indirect dispatches.
+    pub elements: Option<InputElements>,
+}
+
+impl DispatchRecord {
+    /// Describes a direct dispatch planned from `elements`.
+    pub fn direct(
+        label: impl Into<String>,
+        workgroups: (u32, u32, u32),
+        elements: InputElements,
+    ) -> Self {
+        Self {
+            label: label.into(),
+            workgroups: Some(workgroups),
+            elements: Some(elements),
+        }
+    }
+
+    /// Describes an indirect dispatch whose counts are only known on the GPU.
+    pub fn indirect(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            workgroups: None,
+            elements: None,
+        }
+    }
+}
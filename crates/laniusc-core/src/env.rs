@@ -1,40 +1,43 @@
 use std::path::PathBuf;
 
-use log::warn;
+use log::{debug, warn};
 
-fn warn_missing_env(var: &str, default: &str) {
-    warn!("{var} is unset; using default '{default}'");
+use crate::logging::GPU;
+
+// Most variables are optional tuning knobs, so an unset one is only traced.
+fn debug_missing_env(var: &str, default: &str) {
+    debug!(target: GPU, "{var} is unset; using default '{default}'");
 }
 
 fn warn_invalid_env(var: &str, value: &str, default: &str) {
-    warn!("{var} has invalid value '{value}'; using default '{default}'");
+    warn!(target: GPU, "{var} has invalid value '{value}'; using default '{default}'");
 }
 
-/// Reads a string environment variable, warning and returning `default` if unset.
-pub(crate) fn env_string(name: &str, default: &str) -> String {
+/// Reads a string environment variable, returning `default` if unset.
+pub fn env_string(name: &str, default: &str) -> String {
     match std::env::var(name) {
         Ok(value) => value,
         Err(_) => {
-            warn_missing_env(name, default);
+            debug_missing_env(name, default);
             default.to_string()
         }
     }
 }
 
-/// Reads a path environment variable, warning and returning `default` if unset.
-pub(crate) fn env_path(name: &str, default: PathBuf) -> PathBuf {
+/// Reads a path environment variable, returning `default` if unset.
+pub fn env_path(name: &str, default: PathBuf) -> PathBuf {
     let default_display = default.display().to_string();
     match std::env::var_os(name) {
         Some(value) => value.into(),
         None => {
-            warn_missing_env(name, &default_display);
+            debug_missing_env(name, &default_display);
             default
         }
     }
 }
 
 /// Reads a positive `u64` environment variable with warnings on invalid input.
-pub(crate) fn env_u64(name: &str, default: u64) -> u64 {
+pub fn env_u64(name: &str, default: u64) -> u64 {
     let default_display = default.to_string();
     match std::env::var(name) {
         Ok(value) => match value.parse::<u64>() {
@@ -49,14 +52,32 @@ pub(crate) fn env_u64(name: &str, default: u64) -> u64 {
             }
         },
         Err(_) => {
-            warn_missing_env(name, &default_display);
+            debug_missing_env(name, &default_display);
+            default
+        }
+    }
+}
+
+/// Reads an `f64` environment variable, returning `default` if unset or invalid.
+pub fn env_f64(name: &str, default: f64) -> f64 {
+    let default_display = default.to_string();
+    match std::env::var(name) {
+        Ok(value) => match value.trim().parse::<f64>() {
+            Ok(n) => n,
+            Err(err) => {
+                warn!("{name} could not be parsed as f64 ({err}); using default {default_display}");
+                default
+            }
+        },
+        Err(_) => {
+            debug_missing_env(name, &default_display);
             default
         }
     }
 }
 
 /// env var parser used where anything other than explicit false/0 means true.
-pub(crate) fn env_bool_truthy(name: &str, default: bool) -> bool {
+pub fn env_bool_truthy(name: &str, default: bool) -> bool {
     let default_display = if default { "true" } else { "false" };
     match std::env::var(name) {
         Ok(value) => {
@@ -74,14 +95,14 @@ pub(crate) fn env_bool_truthy(name: &str, default: bool) -> bool {
             }
         }
         _ => {
-            warn_missing_env(name, default_display);
+            debug_missing_env(name, default_display);
             default
         }
     }
 }
 
 /// env var parser used for strict boolean flags where only "1"/"true" are enabled.
-pub(crate) fn env_bool_strict(name: &str, default: bool) -> bool {
+pub fn env_bool_strict(name: &str, default: bool) -> bool {
     let default_display = if default { "true" } else { "false" };
     match std::env::var(name) {
         Ok(value) => {
@@ -96,7 +117,7 @@ pub(crate) fn env_bool_strict(name: &str, default: bool) -> bool {
             }
         }
         Err(_) => {
-            warn_missing_env(name, default_display);
+            debug_missing_env(name, default_display);
             default
         }
     }
@@ -0,0 +1,282 @@
+//! The language definition compiled into this build.
+//!
+//! Tools built on the compiler (the formatter, the language server, the
+//! conformance runner) record which language they were built against, so
+//! that token files, goldens, and caches made by a build with other keywords,
+//! token ids, or lexer tables are caught instead of silently misread.
+//! [`definition`] assembles the definition from the sources the lexer
+//! itself uses: the [`TokenKind`] enum, [`KEYWORDS`], [`is_skip_kind`], the
+//! checked-in DFA tables, and the host DFA.
+//!
+//! [`LANGUAGE_REVISION`] only moves forward. Every revision records the
+//! [`LanguageDef::fingerprint`] it was cut at in `REVISION_HISTORY`; a test
+//! fails when the definition changes without a new entry.
+//!
+//! [`definition`]: crate::language::definition
+//! [`TokenKind`]: crate::lexer::tables::tokens::TokenKind
+//! [`KEYWORDS`]: crate::lexer::tables::tokens::KEYWORDS
+//! [`is_skip_kind`]: crate::lexer::boundary::is_skip_kind
+//! [`LANGUAGE_REVISION`]: crate::language::LANGUAGE_REVISION
+//! [`LanguageDef::fingerprint`]: crate::language::LanguageDef::fingerprint
+
+use std::sync::OnceLock;
+
+use anyhow::{Result, bail};
+use log::warn;
+
+use crate::{
+    env::env_string,
+    lexer::{
+        boundary::is_skip_kind,
+        diff::fnv1a64,
+        tables::{
+            compact::CHECKED_IN_TABLES,
+            dfa::StreamingDfa,
+            tokens::{KEYWORDS, TokenKind},
+        },
+    },
+};
+
+/// `(revision, fingerprint)` of each language revision, oldest first. The
+/// last entry is the revision this build implements.
+const REVISION_HISTORY: &[(u32, u64)] = &[(1, 0xaeb8_1134_f5d3_cb36)];
+
+/// Revision of the language this build implements.
+pub const LANGUAGE_REVISION: u32 = REVISION_HISTORY[REVISION_HISTORY.len() - 1].0;
+
+/// Environment variable that selects the [`RevisionMismatch`] policy of
+/// readers: `refuse` (the default) or `warn`.
+pub const REVISION_MISMATCH_ENV: &str = "LANIUS_LANGUAGE_REVISION_MISMATCH";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Optional lexical features, read off the lexer rather than declared.
+pub struct LanguageFeatures {
+    /// Non-ASCII bytes can start and continue identifiers.
+    pub unicode_idents: bool,
+    /// `r"..."` lexes as one raw string token.
+    pub raw_strings: bool,
+    /// A leading `#!` line lexes as a shebang token.
+    pub shebang: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Everything about the language a tool built against this crate depends on.
+pub struct LanguageDef {
+    /// [`LANGUAGE_REVISION`].
+    pub revision: u32,
+    /// FNV-1a 64 of the checked-in `tables/lexer_tables.bin`.
+    pub dfa_hash: u64,
+    /// Name and discriminant of every [`TokenKind`], in discriminant order.
+    pub token_kinds: Vec<(&'static str, u32)>,
+    /// Identifier lexemes retagged as keywords, with their kinds.
+    pub keywords: &'static [(&'static str, TokenKind)],
+    /// Kinds dropped from the kept token stream by default.
+    pub skip_kinds: Vec<TokenKind>,
+    pub features: LanguageFeatures,
+}
+
+impl LanguageDef {
+    fn current() -> Self {
+        let dfa = StreamingDfa::new();
+        let start = dfa.start as usize;
+        let kind_of = |state: u16| TokenKind::from_u32(dfa.token_map[state as usize]);
+        Self {
+            revision: LANGUAGE_REVISION,
+            dfa_hash: fnv1a64(CHECKED_IN_TABLES),
+            token_kinds: TokenKind::ALL
+                .iter()
+                .map(|&kind| (kind.name(), kind as u32))
+                .collect(),
+            keywords: KEYWORDS,
+            skip_kinds: TokenKind::ALL
+                .iter()
+                .copied()
+                .filter(|&kind| is_skip_kind(kind))
+                .collect(),
+            features: LanguageFeatures {
+                unicode_idents: (0xC2..=0xF4)
+                    .all(|byte| kind_of(dfa.next[start][byte].state) == Some(TokenKind::Ident)),
+                raw_strings: (0..dfa.token_map.len() as u16)
+                    .any(|state| kind_of(state) == Some(TokenKind::RawString)),
+                // Produced by the driver's pre-scan, not by the DFA; see
+                // `lexer::shebang`.
+                shebang: true,
+            },
+        }
+    }
+
+    /// FNV-1a 64 over every field but the revision: what a new revision
+    /// has to be cut for when it changes.
+    pub fn fingerprint(&self) -> u64 {
+        let mut bytes = self.dfa_hash.to_le_bytes().to_vec();
+        for (name, id) in &self.token_kinds {
+            bytes.extend_from_slice(name.as_bytes());
+            bytes.push(0);
+            bytes.extend_from_slice(&id.to_le_bytes());
+        }
+        for (text, kind) in self.keywords {
+            bytes.extend_from_slice(text.as_bytes());
+            bytes.push(0);
+            bytes.extend_from_slice(&(*kind as u32).to_le_bytes());
+        }
+        for kind in &self.skip_kinds {
+            bytes.extend_from_slice(&(*kind as u32).to_le_bytes());
+        }
+        let LanguageFeatures {
+            unicode_idents,
+            raw_strings,
+            shebang,
+        } = self.features;
+        bytes.extend([unicode_idents, raw_strings, shebang].map(u8::from));
+        fnv1a64(&bytes)
+    }
+}
+
+/// The language definition compiled into this build.
+pub fn definition() -> &'static LanguageDef {
+    static DEFINITION: OnceLock<LanguageDef> = OnceLock::new();
+    DEFINITION.get_or_init(LanguageDef::current)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// What a reader does with data recorded under another language revision.
+pub enum RevisionMismatch {
+    /// Fail to load it.
+    #[default]
+    Refuse,
+    /// Log a warning and load it anyway.
+    Warn,
+}
+
+impl RevisionMismatch {
+    /// Reads [`REVISION_MISMATCH_ENV`]; an unknown value refuses.
+    pub fn from_env() -> Self {
+        match env_string(REVISION_MISMATCH_ENV, "refuse").as_str() {
+            "warn" => Self::Warn,
+            "refuse" => Self::Refuse,
+            other => {
+                warn!("{REVISION_MISMATCH_ENV} has invalid value '{other}'; using 'refuse'");
+                Self::Refuse
+            }
+        }
+    }
+
+    /// Checks the revision `what` was recorded under against
+    /// [`LANGUAGE_REVISION`]. Data that records no revision predates them
+    /// and passes.
+    pub fn check(self, what: &str, recorded: Option<u32>) -> Result<()> {
+        let Some(recorded) = recorded.filter(|&r| r != LANGUAGE_REVISION) else {
+            return Ok(());
+        };
+        let message = format!(
+            "{what} was recorded under language revision {recorded}, but this build implements \
+             revision {LANGUAGE_REVISION}"
+        );
+        match self {
+            Self::Refuse => bail!("{message}; set {REVISION_MISMATCH_ENV}=warn to load it anyway"),
+            Self::Warn => {
+                warn!("{message}");
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::test_cpu::{lex_on_test_cpu, lex_on_test_cpu_all};
+
+    fn only_kind(src: &str) -> Option<TokenKind> {
+        match lex_on_test_cpu(src).ok()?.as_slice() {
+            [token] => Some(token.kind),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn definition_matches_the_token_kinds() {
+        let def = definition();
+        assert_eq!(def.token_kinds.len(), TokenKind::ALL.len());
+        for (&kind, &(name, id)) in TokenKind::ALL.iter().zip(&def.token_kinds) {
+            assert_eq!(name, format!("{kind:?}"));
+            assert_eq!(TokenKind::from_name(name), Some(kind));
+            assert_eq!(TokenKind::from_u32(id), Some(kind));
+        }
+        for &(text, kind) in def.keywords {
+            assert_eq!(only_kind(text), Some(kind), "keyword {text:?}");
+        }
+        for &kind in TokenKind::ALL {
+            assert_eq!(
+                def.skip_kinds.contains(&kind),
+                is_skip_kind(kind),
+                "{kind:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn features_match_the_lexer() {
+        let features = definition().features;
+        assert_eq!(
+            only_kind("r\"raw\"") == Some(TokenKind::RawString),
+            features.raw_strings
+        );
+        assert_eq!(
+            only_kind("é") == Some(TokenKind::Ident),
+            features.unicode_idents
+        );
+        let shebang = lex_on_test_cpu_all("#!/bin/lani\n").unwrap();
+        assert_eq!(shebang[0].kind == TokenKind::Shebang, features.shebang);
+    }
+
+    #[test]
+    fn revisions_only_move_forward() {
+        assert!(
+            REVISION_HISTORY
+                .windows(2)
+                .all(|pair| pair[0].0 < pair[1].0),
+            "{REVISION_HISTORY:?}"
+        );
+    }
+
+    #[test]
+    fn language_changes_bump_the_revision() {
+        let def = definition();
+        let (revision, fingerprint) = REVISION_HISTORY[REVISION_HISTORY.len() - 1];
+        assert_eq!(
+            def.fingerprint(),
+            fingerprint,
+            "the language definition changed since revision {revision}; append \
+             ({}, {:#x}) to REVISION_HISTORY",
+            revision + 1,
+            def.fingerprint()
+        );
+
+        // Any feature flag takes part, so flipping one alone trips the check.
+        let mut flipped = def.clone();
+        flipped.features.raw_strings = !flipped.features.raw_strings;
+        assert_ne!(flipped.fingerprint(), fingerprint);
+        let mut renamed = def.clone();
+        renamed.token_kinds[0].0 = "Identifier";
+        assert_ne!(renamed.fingerprint(), fingerprint);
+    }
+
+    #[test]
+    fn mismatched_revisions_refuse_or_warn() {
+        let other = Some(LANGUAGE_REVISION + 1);
+        let err = RevisionMismatch::Refuse
+            .check("token file", other)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("revision"), "{err}");
+        assert!(err.contains(REVISION_MISMATCH_ENV), "{err}");
+        assert!(RevisionMismatch::Warn.check("token file", other).is_ok());
+        assert!(
+            RevisionMismatch::Refuse
+                .check("token file", Some(LANGUAGE_REVISION))
+                .is_ok()
+        );
+        assert!(RevisionMismatch::Refuse.check("token file", None).is_ok());
+    }
+}
@@ -0,0 +1,123 @@
+//! Boundary-level lex statistics, reduced on the GPU to a few words.
+//!
+//! `lexer_analyze` runs after the pair scans, over the boundary flags,
+//! `tok_types` and the inclusive all-boundary ranks in `s_all_final`. It
+//! writes [`LEX_ANALYSIS_WORDS`] words:
+//!
+//! | word | value                                             |
+//! |------|---------------------------------------------------|
+//! | 0    | all-boundary tokens                               |
+//! | 1    | kept tokens                                       |
+//! | 2    | bytes of line and block comments                  |
+//! | 3    | bytes of kept tokens                              |
+//! | 4    | `\n` bytes                                        |
+//! | 5    | `!start` of the first kept token, 0 when none     |
+//! | 6    | `!end` of the first all-boundary token, 0 when none |
+//!
+//! A token's start is the end of the token ranked just before it, which the
+//! kernel finds by binary search over the ranks instead of compacting the
+//! stream. [`decode`] turns the words into a [`LexAnalysis`] of the source
+//! as given, undoing the driver's BOM and shebang masking the way the
+//! all-boundary stream's retags do.
+//!
+//! [`LEX_ANALYSIS_WORDS`]: crate::lexer::constants::LEX_ANALYSIS_WORDS
+//! [`decode`]: crate::lexer::analysis::decode
+
+use crate::lexer::{
+    bom::bom_len,
+    boundary::PF_EOF,
+    constants::LEX_ANALYSIS_WORDS,
+    shebang::shebang_len,
+    types::LexAnalysis,
+};
+
+/// The words `lexer_analyze` writes.
+pub type AnalysisWords = [u32; LEX_ANALYSIS_WORDS as usize];
+
+/// Decodes the words `lexer_analyze` wrote for `input`.
+pub fn decode(input: &[u8], words: &AnalysisWords) -> LexAnalysis {
+    let [
+        all,
+        kept,
+        comment,
+        code,
+        newlines,
+        first_kept,
+        first_all_end,
+    ] = words.map(|w| w as usize);
+    let mut analysis = LexAnalysis {
+        kept_count: kept,
+        all_count: all,
+        first_kept_offset: (first_kept != 0).then(|| !words[5] as usize),
+        comment_bytes: comment,
+        code_bytes: code,
+        line_count: newlines + usize::from(input.last().is_some_and(|&b| b != b'\n')),
+    };
+    // A masked BOM lexes as spaces; the all-boundary stream splits it out of
+    // any longer leading whitespace token.
+    let bom = bom_len(input);
+    if bom != 0 && first_all_end != 0 && !words[6] as usize > bom {
+        analysis.all_count += 1;
+    }
+    // A masked shebang lexes as a line comment the stream retags `Shebang`.
+    if let Some(len) = shebang_len(input) {
+        analysis.comment_bytes -= len;
+    }
+    analysis
+}
+
+/// Where the all-boundary token ranked `rank` (one-based) ends, given the
+/// inclusive ranks and boundary flags of every byte; `lexer_analyze` finds
+/// token starts this way.
+pub fn end_of_rank(s_all: &[u32], flags: &[u32], rank: u32) -> usize {
+    if rank == 0 {
+        return 0;
+    }
+    let i = s_all.partition_point(|&s| s < rank);
+    // Both boundaries at `i` rank EMIT first: EMIT ends at `i`, EOF after it.
+    if flags[i] & PF_EOF != 0 && s_all[i] == rank {
+        i + 1
+    } else {
+        i
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::boundary::PF_EMIT;
+
+    #[test]
+    fn ranks_resolve_to_token_ends() {
+        // "a b": `a` and the space end at bytes 1 and 2, `b` at EOF.
+        let flags = [0, PF_EMIT, PF_EMIT | PF_EOF];
+        let s_all = [0, 1, 3];
+        let ends: Vec<_> = (0..=3)
+            .map(|rank| end_of_rank(&s_all, &flags, rank))
+            .collect();
+        assert_eq!(ends, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn decoding_undoes_bom_and_shebang_masking() {
+        let words = [3, 1, 6, 2, 1, !7, !6];
+        assert_eq!(
+            decode(b"#!/bin\nab", &words),
+            LexAnalysis {
+                kept_count: 1,
+                all_count: 3,
+                first_kept_offset: Some(7),
+                comment_bytes: 0,
+                code_bytes: 2,
+                line_count: 2,
+            }
+        );
+        // The masked BOM's whitespace token runs past it, so the BOM splits off.
+        let words = [2, 1, 0, 1, 1, !4, !4];
+        let analysis = decode(b"\xEF\xBB\xBF x\n", &words);
+        assert_eq!(analysis.all_count, 3);
+        assert_eq!(analysis.line_count, 1);
+        let words = [2, 1, 0, 1, 0, !3, !3];
+        assert_eq!(decode(b"\xEF\xBB\xBFx", &words).all_count, 2);
+    }
+}
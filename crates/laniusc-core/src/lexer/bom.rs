@@ -0,0 +1,107 @@
+//! UTF-8 byte order marks (`EF BB BF`) at the start of a source file.
+//!
+//! A leading BOM is reported as a skipped [`TokenKind::Bom`] token covering
+//! bytes `0..3`, so every other token keeps its offset into the original
+//! file. Like a shebang, the driver masks it before upload: the three bytes
+//! become spaces and lex as whitespace, and all-boundary streams split the
+//! BOM back out of that whitespace token. A BOM anywhere else still rejects.
+//!
+//! [`TokenKind::Bom`]: crate::lexer::tables::tokens::TokenKind::Bom
+
+use crate::lexer::{tables::tokens::TokenKind, types::Token};
+
+/// The UTF-8 encoding of U+FEFF.
+pub const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Returns the byte length of a BOM at the very start of `bytes`, or 0.
+pub fn bom_len(bytes: &[u8]) -> usize {
+    if bytes.starts_with(UTF8_BOM) {
+        UTF8_BOM.len()
+    } else {
+        0
+    }
+}
+
+/// Rewrites a leading BOM to spaces so the DFA lexes it as whitespace.
+/// Returns whether `bytes` started with a BOM.
+pub fn mask_bom(bytes: &mut [u8]) -> bool {
+    let len = bom_len(bytes);
+    bytes[..len].fill(b' ');
+    len != 0
+}
+
+/// Splits a masked BOM out of the leading whitespace token of an
+/// all-boundary token stream as a [`TokenKind::Bom`] token.
+pub fn retag_bom(source: &[u8], tokens: &mut Vec<Token>) {
+    let len = bom_len(source);
+    let Some(first) = tokens.first_mut() else {
+        return;
+    };
+    if len == 0 || first.start() != 0 || first.len() < len || first.kind != TokenKind::White {
+        return;
+    }
+    if first.len() == len {
+        first.kind = TokenKind::Bom;
+        first.raw_kind = TokenKind::Bom;
+        return;
+    }
+    first.span.start = len as u32;
+    tokens.insert(0, Token::new(TokenKind::Bom, 0, len));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(kind: TokenKind, start: usize, len: usize) -> (TokenKind, usize, usize) {
+        (kind, start, len)
+    }
+
+    fn retagged(
+        source: &[u8],
+        tokens: &[(TokenKind, usize, usize)],
+    ) -> Vec<(TokenKind, usize, usize)> {
+        let mut tokens = tokens
+            .iter()
+            .map(|&(kind, start, len)| Token::new(kind, start, len))
+            .collect();
+        retag_bom(source, &mut tokens);
+        tokens
+            .into_iter()
+            .map(|t| (t.kind, t.start(), t.len()))
+            .collect()
+    }
+
+    #[test]
+    fn masking_only_touches_a_leading_bom() {
+        let mut bytes = b"\xEF\xBB\xBFx = \"\xEF\xBB\xBF\"".to_vec();
+        assert!(mask_bom(&mut bytes));
+        assert_eq!(bytes, b"   x = \"\xEF\xBB\xBF\"");
+
+        let mut plain = b"x\xEF\xBB\xBF".to_vec();
+        assert!(!mask_bom(&mut plain));
+        assert_eq!(plain, b"x\xEF\xBB\xBF");
+        assert_eq!(bom_len(b"\xEF\xBB"), 0);
+    }
+
+    #[test]
+    fn retag_splits_the_bom_out_of_leading_whitespace() {
+        use TokenKind::*;
+
+        let bom_only = b"\xEF\xBB\xBF";
+        assert_eq!(
+            retagged(bom_only, &[token(White, 0, 3)]),
+            [token(Bom, 0, 3)]
+        );
+        let code = b"\xEF\xBB\xBF\n x";
+        assert_eq!(
+            retagged(code, &[token(White, 0, 5), token(Ident, 5, 1)]),
+            [token(Bom, 0, 3), token(White, 3, 2), token(Ident, 5, 1)]
+        );
+        // Without a BOM in the source, whitespace is left alone.
+        assert_eq!(
+            retagged(b"   x", &[token(White, 0, 3), token(Ident, 3, 1)]),
+            [token(White, 0, 3), token(Ident, 3, 1)]
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! Per-byte token-boundary decision and the kept-token filter.
+//!
+//! `dfa_03_apply_block_prefix` evaluates [`boundary_flags`] and [`keep_flags`]
+//! for every input byte and the test CPU oracle evaluates them while
+//! streaming; keep the shader and these functions in sync. The `PF_*` bits are
+//! defined once in [`crate::lexer::constants`] and generated into the shader
+//! header.
+//!
+//! The default pipeline scans the keep bits alongside the boundaries and
+//! compacts the kept stream straight from them. The opt-in late filter
+//! ignores them: it compacts the all-boundary stream with its kinds first and
+//! filters the kept stream from it ([`is_kept`]) in the `keep_*` passes and
+//! `compact_kept`. Both select the same tokens.
+//!
+//! When the final byte sets both [`PF_EMIT`] and [`PF_EOF`], the EMIT token is
+//! ordered first: it takes all-boundary index `j - 1` and the EOF token `j`.
+//! `tokens_build` recovers each kept start from the previous ALL boundary and
+//! counts adjacent kept tokens that fail to touch in `token_order_status`.
+//!
+//! [`boundary_flags`]: crate::lexer::boundary::boundary_flags
+//! [`is_kept`]: crate::lexer::boundary::is_kept
+//! [`keep_flags`]: crate::lexer::boundary::keep_flags
+//! [`PF_EMIT`]: crate::lexer::constants::PF_EMIT
+//! [`PF_EOF`]: crate::lexer::constants::PF_EOF
+
+pub use crate::lexer::constants::{PF_EMIT, PF_EOF, PF_KEEP_EMIT, PF_KEEP_EOF};
+use crate::lexer::tables::tokens::TokenKind;
+
+/// Returns whether the lexer drops tokens of `kind` from the kept stream.
+pub fn is_skip_kind(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::White
+            | TokenKind::LineComment
+            | TokenKind::BlockComment
+            | TokenKind::Shebang
+            | TokenKind::Bom
+    )
+}
+
+/// Returns whether an all-boundary token of `kind` is kept; `None` is a
+/// boundary closed from a non-accepting state, which is never kept.
+pub fn is_kept(kind: Option<TokenKind>) -> bool {
+    kind.is_some_and(|kind| !is_skip_kind(kind))
+}
+
+/// Computes the boundary flags for one byte.
+///
+/// `emit_here` is whether the DFA edge taken at this byte closed a token, and
+/// `eof_kind` the token accepted by the state after the byte; `None` means the
+/// state is not accepting. The EMIT and EOF boundaries are independent: both
+/// reach the all-boundary stream whatever their kinds.
+pub fn boundary_flags(emit_here: bool, at_eof: bool, eof_kind: Option<TokenKind>) -> u32 {
+    let mut flags = 0;
+    if emit_here {
+        flags |= PF_EMIT;
+    }
+    if at_eof && eof_kind.is_some() {
+        flags |= PF_EOF;
+    }
+    flags
+}
+
+/// Computes the keep bits `dfa_03` adds to the [`boundary_flags`] of one
+/// byte: [`PF_KEEP_EMIT`] when the EMIT boundary closes an [`is_kept`] token
+/// of `emit_kind`, and [`PF_KEEP_EOF`] likewise for the EOF boundary.
+pub fn keep_flags(flags: u32, emit_kind: Option<TokenKind>, eof_kind: Option<TokenKind>) -> u32 {
+    let mut keep = 0;
+    if flags & PF_EMIT != 0 && is_kept(emit_kind) {
+        keep |= PF_KEEP_EMIT;
+    }
+    if flags & PF_EOF != 0 && is_kept(eof_kind) {
+        keep |= PF_KEEP_EOF;
+    }
+    keep
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trailing_comment_at_eof_is_a_boundary_but_not_kept() {
+        assert_eq!(
+            boundary_flags(false, true, Some(TokenKind::LineComment)),
+            PF_EOF
+        );
+        assert!(!is_kept(Some(TokenKind::LineComment)));
+        assert_eq!(keep_flags(PF_EOF, None, Some(TokenKind::LineComment)), 0);
+    }
+
+    #[test]
+    fn skipped_eof_token_does_not_drop_emit() {
+        assert_eq!(
+            boundary_flags(true, true, Some(TokenKind::White)),
+            PF_EMIT | PF_EOF
+        );
+        assert!(is_kept(Some(TokenKind::Ident)));
+        assert_eq!(
+            keep_flags(
+                PF_EMIT | PF_EOF,
+                Some(TokenKind::Ident),
+                Some(TokenKind::White)
+            ),
+            PF_KEEP_EMIT
+        );
+    }
+
+    #[test]
+    fn non_accepting_eof_state_sets_no_eof_flag() {
+        assert_eq!(boundary_flags(true, true, None), PF_EMIT);
+        assert!(!is_kept(None));
+    }
+}
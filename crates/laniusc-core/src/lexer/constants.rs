@@ -0,0 +1,242 @@
+//! Lexer constants shared with the Slang shaders.
+//!
+//! This file is the single source of truth. The shader build script reads each
+//! item marked `// SHADER_CONST` and writes `shaders/generated_constants.slang`,
+//! which the lexer shaders import. Marked items must be written as
+//! `pub const NAME: TYPE = VALUE;` where `VALUE` is an integer literal or
+//! `1 << N`.
+
+// SHADER_CONST
+/// Number of DFA states; one per [`crate::lexer::tables::dfa::S`] variant.
+pub const N_STATES: usize = 83;
+
+// SHADER_CONST
+/// Input bytes per DFA block; also the DFA per-block workgroup size.
+pub const DFA_BLOCK_WIDTH: u32 = 256;
+
+// SHADER_CONST
+/// Chunks each DFA block is split into before composing block summaries.
+pub const DFA_CHUNK_COUNT: u32 = 3;
+
+// SHADER_CONST
+/// Input bytes per pair-sum block; also the pair per-block workgroup size.
+pub const PAIR_BLOCK_WIDTH: u32 = 256;
+
+// SHADER_CONST
+/// Skip-kind slots in the DFA apply parameters; unused slots hold `u32::MAX`.
+pub const SKIP_KIND_SLOTS: usize = 4;
+
+// SHADER_CONST
+/// The DFA edge taken at this byte closed the token that ended before it.
+pub const PF_EMIT: u32 = 1 << 0;
+
+// SHADER_CONST
+/// Input (or the current source-pack file) ends after this byte in an
+/// accepting state.
+pub const PF_EOF: u32 = 1 << 1;
+
+// SHADER_CONST
+/// The token closed by [`PF_EMIT`] is kept. Only the pair scans of the
+/// default `Split` lexer pipeline read the keep bits.
+pub const PF_KEEP_EMIT: u32 = 1 << 2;
+
+// SHADER_CONST
+/// The token closed by [`PF_EOF`] is kept.
+pub const PF_KEEP_EOF: u32 = 1 << 3;
+
+// SHADER_CONST
+/// Accept state reported for `..` tokens that `tokens_build` splits off a
+/// float such as `1..`; the [`S::DotDotDone`](crate::lexer::tables::dfa::S::DotDotDone) index.
+pub const DFA_STATE_DOT_DOT: u32 = 81;
+
+// SHADER_CONST
+/// State after the backslash of an escape inside a string literal; the
+/// [`S::StringEscape`](crate::lexer::tables::dfa::S::StringEscape) index.
+pub const DFA_STATE_STRING_ESCAPE: u32 = 59;
+
+// SHADER_CONST
+/// State after the backslash of an escape inside a char literal; the
+/// [`S::CharEscape`](crate::lexer::tables::dfa::S::CharEscape) index.
+pub const DFA_STATE_CHAR_ESCAPE: u32 = 62;
+
+// SHADER_CONST
+/// Suspect class: a one-byte token closing a file, where the EMIT of its
+/// start and the EOF of its end fall on the same byte.
+pub const SUSPECT_DUAL_BOUNDARY: u32 = 1 << 0;
+
+// SHADER_CONST
+/// Suspect class: a token ended by the end of its file rather than by an
+/// EMIT edge.
+pub const SUSPECT_EOF_RULE: u32 = 1 << 1;
+
+// SHADER_CONST
+/// Suspect class: a token whose length `tokens_build` clamped, to
+/// `max_token_len` or up from zero.
+pub const SUSPECT_CLAMPED: u32 = 1 << 2;
+
+// SHADER_CONST
+/// Suspect class: a token starting on, ending on, or spanning a DFA block
+/// boundary, where the block-scan carry decides its state.
+pub const SUSPECT_BLOCK_EDGE: u32 = 1 << 3;
+
+// SHADER_CONST
+/// Suspect class held by every kept token.
+pub const SUSPECT_ANY: u32 = 1 << 4;
+
+// SHADER_CONST
+/// Length byte of a compressed token word whose length does not fit below it;
+/// the token's exact range is in the escape list.
+pub const COMPRESSED_LEN_ESCAPE: u32 = 255;
+
+// SHADER_CONST
+/// Start-delta half of a compressed token word whose start is not within
+/// this many bytes of the previous token's start; the token's exact range is
+/// in the escape list.
+pub const COMPRESSED_DELTA_ESCAPE: u32 = 65535;
+
+// SHADER_CONST
+/// Compressed token readback holds at most one escaped token per this many
+/// input bytes; the driver reads raw tokens once more than one kept token in
+/// this many escapes.
+pub const COMPRESSED_ESCAPE_RATE_LIMIT: u32 = 4;
+
+// SHADER_CONST
+/// Bits of each token-kind lane of `tok_types` in the wide packing, where a
+/// byte's EMIT and EOF kinds fill one `u32`.
+pub const TOK_KIND_LANE_BITS_WIDE: u32 = 16;
+
+// SHADER_CONST
+/// Bits of each token-kind lane of `tok_types` in the narrow packing, where
+/// a byte's EMIT and EOF kinds fill one `u16` and two bytes share a `u32`.
+pub const TOK_KIND_LANE_BITS_NARROW: u32 = 8;
+
+// SHADER_CONST
+/// Words of the `lex_analysis` buffer `lexer_analyze` reduces boundary
+/// statistics into; see [`crate::lexer::analysis`].
+pub const LEX_ANALYSIS_WORDS: u32 = 7;
+
+const _: () =
+    assert!(DFA_STATE_DOT_DOT as usize == crate::lexer::tables::dfa::S::DotDotDone as usize);
+const _: () = assert!(
+    DFA_STATE_STRING_ESCAPE as usize == crate::lexer::tables::dfa::S::StringEscape as usize
+);
+const _: () =
+    assert!(DFA_STATE_CHAR_ESCAPE as usize == crate::lexer::tables::dfa::S::CharEscape as usize);
+
+// `dfa_01_scan_inblock` gives each (chunk, state) pair its own lane.
+const _: () = assert!(DFA_CHUNK_COUNT as usize * N_STATES <= DFA_BLOCK_WIDTH as usize);
+
+// `dfa_03_apply_block_prefix_seed_pairs` writes pair block totals from DFA
+// blocks, so the two block shapes must agree.
+const _: () = assert!(DFA_BLOCK_WIDTH == PAIR_BLOCK_WIDTH);
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::*;
+
+    const SHARED_NAMES: [&str; 23] = [
+        "N_STATES",
+        "DFA_BLOCK_WIDTH",
+        "DFA_CHUNK_COUNT",
+        "PAIR_BLOCK_WIDTH",
+        "SKIP_KIND_SLOTS",
+        "PF_EMIT",
+        "PF_EOF",
+        "PF_KEEP_EMIT",
+        "PF_KEEP_EOF",
+        "DFA_STATE_DOT_DOT",
+        "DFA_STATE_STRING_ESCAPE",
+        "DFA_STATE_CHAR_ESCAPE",
+        "SUSPECT_DUAL_BOUNDARY",
+        "SUSPECT_EOF_RULE",
+        "SUSPECT_CLAMPED",
+        "SUSPECT_BLOCK_EDGE",
+        "SUSPECT_ANY",
+        "COMPRESSED_LEN_ESCAPE",
+        "COMPRESSED_DELTA_ESCAPE",
+        "COMPRESSED_ESCAPE_RATE_LIMIT",
+        "TOK_KIND_LANE_BITS_WIDE",
+        "TOK_KIND_LANE_BITS_NARROW",
+        "LEX_ANALYSIS_WORDS",
+    ];
+
+    fn shader_root() -> &'static Path {
+        Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/../../shaders"))
+    }
+
+    fn generated_uint_const(source: &str, name: &str) -> Option<u32> {
+        let prefix = format!("public static const uint {name} = ");
+        source
+            .lines()
+            .find_map(|line| line.strip_prefix(&prefix))
+            .and_then(|value| value.strip_suffix("u;"))
+            .and_then(|value| value.parse().ok())
+    }
+
+    #[test]
+    fn generated_shader_constants_match_rust() {
+        let generated = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../shaders/generated_constants.slang"
+        ));
+        let values = [
+            N_STATES as u32,
+            DFA_BLOCK_WIDTH,
+            DFA_CHUNK_COUNT,
+            PAIR_BLOCK_WIDTH,
+            SKIP_KIND_SLOTS as u32,
+            PF_EMIT,
+            PF_EOF,
+            PF_KEEP_EMIT,
+            PF_KEEP_EOF,
+            DFA_STATE_DOT_DOT,
+            DFA_STATE_STRING_ESCAPE,
+            DFA_STATE_CHAR_ESCAPE,
+            SUSPECT_DUAL_BOUNDARY,
+            SUSPECT_EOF_RULE,
+            SUSPECT_CLAMPED,
+            SUSPECT_BLOCK_EDGE,
+            SUSPECT_ANY,
+            COMPRESSED_LEN_ESCAPE,
+            COMPRESSED_DELTA_ESCAPE,
+            COMPRESSED_ESCAPE_RATE_LIMIT,
+            TOK_KIND_LANE_BITS_WIDE,
+            TOK_KIND_LANE_BITS_NARROW,
+            LEX_ANALYSIS_WORDS,
+        ];
+        for (name, value) in SHARED_NAMES.into_iter().zip(values) {
+            assert_eq!(
+                generated_uint_const(generated, name),
+                Some(value),
+                "shaders/generated_constants.slang is stale for {name}; rebuild the shaders"
+            );
+        }
+    }
+
+    #[test]
+    fn lexer_shaders_do_not_redefine_shared_constants() {
+        let mut pending = vec![shader_root().join("lexer")];
+        while let Some(dir) = pending.pop() {
+            for entry in fs::read_dir(&dir).expect("read shader dir") {
+                let path = entry.expect("shader dir entry").path();
+                if path.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+                let text = fs::read_to_string(&path).expect("read shader");
+                for line in text.lines().map(str::trim) {
+                    for name in SHARED_NAMES {
+                        assert!(
+                            !line.starts_with(&format!("#define {name} "))
+                                && !line.contains(&format!("static const uint {name} =")),
+                            "{} redefines {name}; import generated_constants instead",
+                            path.display()
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
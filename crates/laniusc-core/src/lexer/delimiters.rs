@@ -0,0 +1,162 @@
+//! Where delimited tokens start and end.
+//!
+//! Every delimited kind's range includes its opener. Strings, chars, raw
+//! strings, and block comments also include their closing delimiter: the DFA
+//! accepts on the closing byte and emits on the byte after it. Line comments
+//! and shebang lines end at their newline terminator, which is excluded and
+//! starts the next whitespace token (or is absent at EOF).
+//! [`TokenKindInfo`] records this per kind, and [`Token::content_range`]
+//! strips delimiters by it, so consumers never hard-code the convention.
+//!
+//! [`TokenKindInfo`]: crate::lexer::delimiters::TokenKindInfo
+
+use std::ops::Range;
+
+use crate::lexer::{tables::tokens::TokenKind, types::Token};
+
+/// Delimiter convention of a token kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenKindInfo {
+    /// Bytes that open the token, such as `"` or `//`; empty when undelimited.
+    pub opener: &'static str,
+    /// Bytes that close the token, such as `"`, `*/`, or a line comment's
+    /// `\n`; empty when undelimited.
+    pub terminator: &'static str,
+    /// Whether the token's range includes `terminator`.
+    pub includes_terminator: bool,
+}
+
+impl TokenKindInfo {
+    const UNDELIMITED: Self = Self::closed("", "");
+
+    const fn closed(opener: &'static str, terminator: &'static str) -> Self {
+        Self {
+            opener,
+            terminator,
+            includes_terminator: true,
+        }
+    }
+
+    const fn line(opener: &'static str) -> Self {
+        Self {
+            opener,
+            terminator: "\n",
+            includes_terminator: false,
+        }
+    }
+}
+
+impl TokenKind {
+    /// Returns the delimiter convention of this kind.
+    pub const fn info(self) -> TokenKindInfo {
+        match self {
+            Self::String => TokenKindInfo::closed("\"", "\""),
+            Self::RawString => TokenKindInfo::closed("r\"", "\""),
+            Self::Char => TokenKindInfo::closed("'", "'"),
+            Self::BlockComment => TokenKindInfo::closed("/*", "*/"),
+            Self::LineComment => TokenKindInfo::line("//"),
+            Self::Shebang => TokenKindInfo::line("#!"),
+            _ => TokenKindInfo::UNDELIMITED,
+        }
+    }
+}
+
+impl Token {
+    /// Returns the byte range of this token's content in `src`, without its
+    /// opener or included terminator: the bytes between a string's quotes,
+    /// the text after `//` (less a CRLF's `\r`), or the text between a block
+    /// comment's markers. Undelimited kinds return the whole token.
+    ///
+    /// The range is clamped to `src`, and a missing delimiter is left in
+    /// place, so this never panics on a stale or mismatched token.
+    pub fn content_range(&self, src: &str) -> Range<usize> {
+        let end = self.end().min(src.len());
+        let start = self.start().min(end);
+        content_range_of(self.kind, src.as_bytes(), start..end)
+    }
+}
+
+fn content_range_of(kind: TokenKind, src: &[u8], range: Range<usize>) -> Range<usize> {
+    let info = kind.info();
+    let Range { mut start, mut end } = range;
+    if src[start..end].starts_with(info.opener.as_bytes()) {
+        start += info.opener.len();
+    }
+    if info.includes_terminator {
+        if src[start..end].ends_with(info.terminator.as_bytes()) {
+            end -= info.terminator.len();
+        }
+    } else if !info.terminator.is_empty() && src[start..end].ends_with(b"\r") {
+        end -= 1;
+    }
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::test_cpu::lex_on_test_cpu_all;
+
+    fn content<'a>(src: &'a str, kind: TokenKind) -> Vec<&'a str> {
+        lex_on_test_cpu_all(src)
+            .expect("lex")
+            .into_iter()
+            .filter(|token| token.kind == kind)
+            .map(|token| &src[token.content_range(src)])
+            .collect()
+    }
+
+    // Audits the DFA against each declared convention: the lexed token ends
+    // right after an included terminator, or right before an excluded one.
+    #[test]
+    fn dfa_token_ends_match_declared_conventions() {
+        let cases = [
+            ("\"ab\" x", TokenKind::String),
+            ("r\"a\\b\" x", TokenKind::RawString),
+            ("'a' x", TokenKind::Char),
+            ("/* a */ x", TokenKind::BlockComment),
+            ("// a\nx", TokenKind::LineComment),
+        ];
+        for (src, kind) in cases {
+            let token = lex_on_test_cpu_all(src)
+                .expect("lex")
+                .into_iter()
+                .find(|token| token.kind == kind)
+                .expect("delimited token");
+            let info = kind.info();
+            let text = &src[token.span.range()];
+            assert!(text.starts_with(info.opener), "{src:?}");
+            assert_eq!(
+                text.ends_with(info.terminator),
+                info.includes_terminator,
+                "{src:?}"
+            );
+            assert_eq!(
+                src[token.end()..].starts_with(info.terminator),
+                !info.includes_terminator,
+                "{src:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn content_range_strips_delimiters_per_kind() {
+        assert_eq!(content("x = \"a\\\"b\"", TokenKind::String), ["a\\\"b"]);
+        assert_eq!(content("\"\"", TokenKind::String), [""]);
+        assert_eq!(content("r\"a\\\"", TokenKind::RawString), ["a\\"]);
+        assert_eq!(content("'a''\\''", TokenKind::Char), ["a", "\\'"]);
+        assert_eq!(content("x/* a */", TokenKind::BlockComment), [" a "]);
+        assert_eq!(content("/**/", TokenKind::BlockComment), [""]);
+        assert_eq!(content("// a\r\nx", TokenKind::LineComment), [" a"]);
+        assert_eq!(content("x // a", TokenKind::LineComment), [" a"]);
+        assert_eq!(content("foo", TokenKind::Ident), ["foo"]);
+    }
+
+    #[test]
+    fn content_range_clamps_stale_tokens() {
+        let token = Token::new(TokenKind::String, 2, 10);
+        assert_eq!(token.content_range("x \"a"), 3..4);
+        let token = Token::new(TokenKind::String, 9, 1);
+        assert_eq!(token.content_range("\"\""), 2..2);
+    }
+}
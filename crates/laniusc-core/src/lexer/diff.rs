@@ -0,0 +1,405 @@
+//! Token-stream comparison and machine-readable mismatch reports.
+//!
+//! Fuzz tooling compares GPU lexer output against the test CPU oracle. This
+//! module finds and classifies the first divergence and packages it as a
+//! [`MismatchReport`] whose [`signature`](crate::lexer::diff::MismatchReport::signature) depends
+//! only on token kinds around the divergence, so the same bug seen in
+//! different inputs dedupes to one key.
+//!
+//! [`MismatchReport`]: crate::lexer::diff::MismatchReport
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::lexer::Token;
+
+/// Tokens kept on each side of the divergence when a caller asks for more.
+pub const MAX_REPORT_WINDOW: usize = 64;
+/// Default serialized-size cap for [`MismatchReport::to_json_capped`].
+pub const MAX_REPORT_BYTES: usize = 64 * 1024;
+
+/// Tokens on each side of the divergence that feed the signature.
+const SIGNATURE_RADIUS: usize = 3;
+const PREVIEW_HEAD_BYTES: usize = 16;
+const PREVIEW_TAIL_BYTES: usize = 16;
+
+/// How the first differing token pair differs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MismatchClass {
+    /// Same span, different kind or raw kind.
+    Kind,
+    /// Same start, different length.
+    Length,
+    /// Different start offset.
+    Boundary,
+    /// The expected stream continues after the actual stream ends.
+    MissingTokens,
+    /// The actual stream continues after the expected stream ends.
+    ExtraTokens,
+}
+
+impl MismatchClass {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Kind => "kind",
+            Self::Length => "length",
+            Self::Boundary => "boundary",
+            Self::MissingTokens => "missing_tokens",
+            Self::ExtraTokens => "extra_tokens",
+        }
+    }
+}
+
+/// First index where two token streams disagree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Divergence {
+    pub index: usize,
+    pub class: MismatchClass,
+}
+
+/// Finds and classifies the first disagreement, or `None` when the streams
+/// are identical.
+pub fn first_divergence(expected: &[Token], actual: &[Token]) -> Option<Divergence> {
+    let index = expected
+        .iter()
+        .zip(actual)
+        .position(|(e, a)| e != a)
+        .unwrap_or(expected.len().min(actual.len()));
+    let class = match (expected.get(index), actual.get(index)) {
+        (Some(e), Some(a)) if e.start() != a.start() => MismatchClass::Boundary,
+        (Some(e), Some(a)) if e.len() != a.len() => MismatchClass::Length,
+        (Some(_), Some(_)) => MismatchClass::Kind,
+        (Some(_), None) => MismatchClass::MissingTokens,
+        (None, Some(_)) => MismatchClass::ExtraTokens,
+        (None, None) => return None,
+    };
+    Some(Divergence { index, class })
+}
+
+/// Renders `bytes` lossily, eliding the middle of anything longer than
+/// `head + tail` bytes.
+pub fn preview_lossy(bytes: &[u8], head: usize, tail: usize) -> String {
+    if bytes.len() <= head + tail {
+        return String::from_utf8_lossy(bytes).into_owned();
+    }
+    let head_s = String::from_utf8_lossy(&bytes[..head]);
+    let tail_s = String::from_utf8_lossy(&bytes[bytes.len() - tail..]);
+    format!(
+        "{}…(+{} bytes)…{}",
+        head_s,
+        bytes.len() - head - tail,
+        tail_s
+    )
+}
+
+/// One token in a report window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportToken {
+    pub index: usize,
+    pub kind: String,
+    /// Pre-retag kind, present only when it differs from `kind`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_kind: Option<String>,
+    pub start: usize,
+    pub len: usize,
+    /// Head and tail of the token text; long tokens are elided.
+    pub preview: String,
+}
+
+/// Serializable summary of the first disagreement between an expected and an
+/// actual token stream.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MismatchReport {
+    /// `fnv1a64:<hex>` of the input bytes.
+    pub input_hash: String,
+    pub input_len: usize,
+    pub expected_count: usize,
+    pub actual_count: usize,
+    pub first_divergence: usize,
+    pub class: MismatchClass,
+    /// Dedupe key derived from the class and the kinds near the divergence.
+    pub signature: String,
+    pub expected_window: Vec<ReportToken>,
+    pub actual_window: Vec<ReportToken>,
+    /// Whether windows were shrunk to fit a size cap.
+    pub truncated: bool,
+}
+
+impl MismatchReport {
+    /// Builds a report with up to `window` tokens (at most
+    /// [`MAX_REPORT_WINDOW`]) on each side of the divergence, or `None` when
+    /// the streams agree.
+    pub fn new(src: &str, expected: &[Token], actual: &[Token], window: usize) -> Option<Self> {
+        let divergence = first_divergence(expected, actual)?;
+        let window = window.min(MAX_REPORT_WINDOW);
+        Some(Self {
+            input_hash: format!("fnv1a64:{:016x}", fnv1a64(src.as_bytes())),
+            input_len: src.len(),
+            expected_count: expected.len(),
+            actual_count: actual.len(),
+            first_divergence: divergence.index,
+            class: divergence.class,
+            signature: signature(divergence, expected, actual),
+            expected_window: report_window(src, expected, divergence.index, window),
+            actual_window: report_window(src, actual, divergence.index, window),
+            truncated: false,
+        })
+    }
+
+    /// Serializes the report, halving both windows until the JSON fits in
+    /// `max_bytes`.
+    pub fn to_json_capped(&self, max_bytes: usize) -> Result<String> {
+        let mut report = self.clone();
+        let mut radius = report.expected_window.len().max(report.actual_window.len());
+        loop {
+            let json =
+                serde_json::to_string_pretty(&report).context("serialize mismatch report")?;
+            if json.len() <= max_bytes || radius == 0 {
+                return Ok(json);
+            }
+            radius /= 2;
+            shrink_window(&mut report.expected_window, report.first_divergence, radius);
+            shrink_window(&mut report.actual_window, report.first_divergence, radius);
+            report.truncated = true;
+        }
+    }
+
+    /// Writes [`to_json_capped`](Self::to_json_capped) with
+    /// [`MAX_REPORT_BYTES`] to `path`.
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        let json = self.to_json_capped(MAX_REPORT_BYTES)?;
+        std::fs::write(path, json).with_context(|| format!("write {}", path.display()))
+    }
+}
+
+fn report_window(src: &str, tokens: &[Token], center: usize, radius: usize) -> Vec<ReportToken> {
+    let lo = center.saturating_sub(radius);
+    let hi = center.saturating_add(radius).min(tokens.len());
+    let bytes = src.as_bytes();
+    (lo..hi)
+        .map(|index| {
+            let t = tokens[index];
+            let start = t.start().min(bytes.len());
+            let end = t.end().min(bytes.len());
+            ReportToken {
+                index,
+                kind: format!("{:?}", t.kind),
+                raw_kind: (t.raw_kind != t.kind).then(|| format!("{:?}", t.raw_kind)),
+                start: t.start(),
+                len: t.len(),
+                preview: preview_lossy(&bytes[start..end], PREVIEW_HEAD_BYTES, PREVIEW_TAIL_BYTES),
+            }
+        })
+        .collect()
+}
+
+/// Keeps the `keep` tokens on each side of `center`.
+fn shrink_window(window: &mut Vec<ReportToken>, center: usize, keep: usize) {
+    window.retain(|t| t.index + keep >= center && t.index < center + keep);
+}
+
+/// Hashes the class and the kinds within [`SIGNATURE_RADIUS`] of the
+/// divergence on both sides; offsets, lengths, and indices are left out. A raw
+/// kind is hashed only where it differs from the kind, so streams without
+/// retags keep their signatures.
+fn signature(divergence: Divergence, expected: &[Token], actual: &[Token]) -> String {
+    let mut text = String::from(divergence.class.as_str());
+    for (side, tokens) in [("e", expected), ("a", actual)] {
+        text.push('|');
+        text.push_str(side);
+        for offset in 0..2 * SIGNATURE_RADIUS {
+            let kind = (divergence.index + offset)
+                .checked_sub(SIGNATURE_RADIUS)
+                .and_then(|i| tokens.get(i));
+            match kind {
+                Some(t) if t.raw_kind != t.kind => {
+                    text.push_str(&format!(",{:?}/{:?}", t.raw_kind, t.kind))
+                }
+                Some(t) => text.push_str(&format!(",{:?}", t.kind)),
+                None => text.push_str(",-"),
+            }
+        }
+    }
+    format!(
+        "lex-{}-{:016x}",
+        divergence.class.as_str(),
+        fnv1a64(text.as_bytes())
+    )
+}
+
+pub fn fnv1a64(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tables::tokens::TokenKind;
+
+    fn tok(kind: TokenKind, start: usize, len: usize) -> Token {
+        Token::new(kind, start, len)
+    }
+
+    /// `a = b + 1;` as expected tokens, shifted by `base`.
+    fn stream(base: usize) -> Vec<Token> {
+        vec![
+            tok(TokenKind::Ident, base, 1),
+            tok(TokenKind::Assign, base + 2, 1),
+            tok(TokenKind::Ident, base + 4, 1),
+            tok(TokenKind::Plus, base + 6, 1),
+            tok(TokenKind::Int, base + 8, 1),
+            tok(TokenKind::Semicolon, base + 9, 1),
+        ]
+    }
+
+    /// Same stream with the `+` misclassified.
+    fn bad_stream(base: usize) -> Vec<Token> {
+        let mut tokens = stream(base);
+        tokens[3].kind = TokenKind::Minus;
+        tokens[3].raw_kind = TokenKind::Minus;
+        tokens
+    }
+
+    #[test]
+    fn divergences_are_classified() {
+        let expected = stream(0);
+        assert_eq!(first_divergence(&expected, &expected), None);
+        assert_eq!(
+            first_divergence(&expected, &bad_stream(0)),
+            Some(Divergence {
+                index: 3,
+                class: MismatchClass::Kind
+            })
+        );
+
+        let mut longer = expected.clone();
+        longer[2].span.end = longer[2].span.start + 3;
+        assert_eq!(
+            first_divergence(&expected, &longer).map(|d| d.class),
+            Some(MismatchClass::Length)
+        );
+        let mut moved = expected.clone();
+        moved[2].span.start += 1;
+        moved[2].span.end += 1;
+        assert_eq!(
+            first_divergence(&expected, &moved).map(|d| d.class),
+            Some(MismatchClass::Boundary)
+        );
+        assert_eq!(
+            first_divergence(&expected, &expected[..4]),
+            Some(Divergence {
+                index: 4,
+                class: MismatchClass::MissingTokens
+            })
+        );
+        assert_eq!(
+            first_divergence(&expected[..4], &expected).map(|d| d.class),
+            Some(MismatchClass::ExtraTokens)
+        );
+    }
+
+    #[test]
+    fn signature_is_stable_and_ignores_offsets() {
+        let src = "a = b + 1;";
+        let report = MismatchReport::new(src, &stream(0), &bad_stream(0), 4).unwrap();
+        assert_eq!(report.first_divergence, 3);
+        assert_eq!(report.class, MismatchClass::Kind);
+        // Pinned so dedupe keys survive across runs and builds.
+        assert_eq!(report.signature, "lex-kind-bdc5f3d1b4113c89");
+
+        // The same bug after a prefix of unrelated tokens.
+        let shifted_src = format!("x; y; {src}");
+        let prefix = [
+            tok(TokenKind::Ident, 0, 1),
+            tok(TokenKind::Semicolon, 1, 1),
+            tok(TokenKind::Ident, 3, 1),
+            tok(TokenKind::Semicolon, 4, 1),
+        ];
+        let shifted = |tokens: Vec<Token>| {
+            let mut out = prefix.to_vec();
+            out.extend(tokens);
+            out
+        };
+        let moved = MismatchReport::new(
+            &shifted_src,
+            &shifted(stream(6)),
+            &shifted(bad_stream(6)),
+            4,
+        )
+        .unwrap();
+        assert_eq!(moved.first_divergence, 7);
+        assert_ne!(moved.input_hash, report.input_hash);
+        assert_eq!(moved.signature, report.signature);
+
+        let mut other = stream(0);
+        other[3].kind = TokenKind::Star;
+        other[3].raw_kind = TokenKind::Star;
+        let other = MismatchReport::new(src, &stream(0), &other, 4).unwrap();
+        assert_ne!(other.signature, report.signature);
+    }
+
+    #[test]
+    fn windows_preview_token_text() {
+        let src = "a = b + 1;";
+        let report = MismatchReport::new(src, &stream(0), &bad_stream(0), 2).unwrap();
+        let indices: Vec<_> = report.expected_window.iter().map(|t| t.index).collect();
+        assert_eq!(indices, [1, 2, 3, 4]);
+        assert_eq!(report.expected_window[2].kind, "Plus");
+        assert_eq!(report.actual_window[2].kind, "Minus");
+        assert_eq!(report.actual_window[2].preview, "+");
+        assert_eq!(preview_lossy(&[b'x'; 40], 4, 4), "xxxx…(+32 bytes)…xxxx");
+    }
+
+    #[test]
+    fn raw_kind_differences_are_kind_mismatches() {
+        let src = "a = b + 1;";
+        let mut expected = stream(0);
+        expected[0].kind = TokenKind::Let;
+        let mut actual = expected.clone();
+        actual[0].raw_kind = TokenKind::Let;
+
+        let report = MismatchReport::new(src, &expected, &actual, 2).unwrap();
+        assert_eq!(report.class, MismatchClass::Kind);
+        assert_eq!(report.expected_window[0].raw_kind.as_deref(), Some("Ident"));
+        assert_eq!(report.actual_window[0].raw_kind, None);
+        assert!(
+            !report
+                .to_json_capped(MAX_REPORT_BYTES)
+                .unwrap()
+                .contains("\"raw_kind\": null")
+        );
+    }
+
+    #[test]
+    fn serialized_report_respects_the_size_cap() {
+        let n = 100_000;
+        let src = "x".repeat(n * 8);
+        let expected: Vec<_> = (0..n).map(|i| tok(TokenKind::Ident, i * 8, 8)).collect();
+        let mut actual = expected.clone();
+        actual[n / 2].kind = TokenKind::Int;
+
+        let report = MismatchReport::new(&src, &expected, &actual, usize::MAX).unwrap();
+        assert_eq!(report.expected_window.len(), 2 * MAX_REPORT_WINDOW);
+        assert!(!report.truncated);
+
+        let json = report.to_json_capped(4096).unwrap();
+        assert!(json.len() <= 4096, "{} bytes", json.len());
+        let parsed: MismatchReport = serde_json::from_str(&json).unwrap();
+        assert!(parsed.truncated);
+        assert_eq!(parsed.signature, report.signature);
+        assert!(
+            parsed
+                .expected_window
+                .iter()
+                .any(|t| t.index == report.first_divergence)
+        );
+    }
+}
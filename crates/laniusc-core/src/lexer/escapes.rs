@@ -0,0 +1,109 @@
+//! Escape sequences inside string and char literals.
+//!
+//! [`LexOptions::capture_string_escapes`](crate::lexer::LexOptions::capture_string_escapes)
+//! has the GPU collect an [`EscapeSpan`] for every byte the DFA leaves in
+//! `StringEscape` or `CharEscape`, so highlighters need no second lexer over
+//! each literal. The spans are reported apart from tokens; the helpers here
+//! pair them back up.
+
+use crate::{
+    lexer::types::{EscapeSpan, Token},
+    span::Span,
+};
+
+/// The span of the escape whose backslash is `bytes[backslash]`: the
+/// backslash plus the whole UTF-8 character after it, clamped to `bytes`.
+pub fn escape_span_at(bytes: &[u8], backslash: usize) -> EscapeSpan {
+    let escaped = bytes.get(backslash + 1).map_or(0, |&lead| {
+        let width = match lead {
+            0xF0.. => 4,
+            0xE0.. => 3,
+            0xC0.. => 2,
+            _ => 1,
+        };
+        width.min(bytes.len() - (backslash + 1))
+    });
+    EscapeSpan {
+        span: Span::from_usize(backslash, 1 + escaped),
+    }
+}
+
+/// Index into `tokens` of the token containing `span`, if any.
+///
+/// `tokens` must be in source order and non-overlapping, as every lexer
+/// output is.
+pub fn token_of_escape_span(tokens: &[Token], span: EscapeSpan) -> Option<usize> {
+    let index = tokens.partition_point(|token| token.span.end <= span.span.start);
+    let token = tokens.get(index)?;
+    token.span.contains_span(span.span).then_some(index)
+}
+
+/// [`token_of_escape_span`] for every span in `spans`.
+pub fn escape_span_tokens(tokens: &[Token], spans: &[EscapeSpan]) -> Vec<Option<usize>> {
+    spans
+        .iter()
+        .map(|&span| token_of_escape_span(tokens, span))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tables::tokens::TokenKind;
+
+    fn token(kind: TokenKind, start: usize, len: usize) -> Token {
+        Token::new(kind, start, len)
+    }
+
+    #[test]
+    fn spans_cover_the_whole_escaped_character() {
+        let bytes = "\"\\n\\é\\".as_bytes();
+        assert_eq!(
+            escape_span_at(bytes, 1),
+            EscapeSpan {
+                span: Span::new(1, 3)
+            }
+        );
+        assert_eq!(
+            escape_span_at(bytes, 3),
+            EscapeSpan {
+                span: Span::new(3, 6)
+            }
+        );
+        // A backslash ending the input has nothing to escape.
+        assert_eq!(
+            escape_span_at(bytes, 6),
+            EscapeSpan {
+                span: Span::new(6, 7)
+            }
+        );
+    }
+
+    #[test]
+    fn spans_map_to_the_literal_containing_them() {
+        // `x = "a\n" + '\t'`
+        let tokens = [
+            token(TokenKind::Ident, 0, 1),
+            token(TokenKind::Assign, 2, 1),
+            token(TokenKind::String, 4, 5),
+            token(TokenKind::Plus, 10, 1),
+            token(TokenKind::Char, 12, 4),
+        ];
+        let spans = [
+            EscapeSpan {
+                span: Span::new(6, 8),
+            },
+            EscapeSpan {
+                span: Span::new(13, 15),
+            },
+            EscapeSpan {
+                span: Span::new(20, 22),
+            },
+        ];
+        assert_eq!(
+            escape_span_tokens(&tokens, &spans),
+            [Some(2), Some(4), None]
+        );
+        assert_eq!(token_of_escape_span(&[], spans[0]), None);
+    }
+}
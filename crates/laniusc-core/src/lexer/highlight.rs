@@ -0,0 +1,196 @@
+//! Coarse token classes for syntax highlighting.
+//!
+//! Editor themes color a handful of classes, not the lexer's retag kinds.
+//! [`HighlightScope::of`] folds every [`TokenKind`] into one of them:
+//! delimited kinds split into comments and quoted literals by their
+//! [`TokenKindInfo`](crate::lexer::delimiters::TokenKindInfo) terminator, and
+//! every context retag lands where its base lexeme does, so `LetIdent` is an
+//! identifier and `CallLParen` punctuation. Whitespace and byte order marks
+//! have no scope.
+//!
+//! [`TokenKind`]: crate::lexer::tables::tokens::TokenKind
+
+use std::ops::Range;
+
+use crate::{
+    lexer::{tables::tokens::TokenKind, types::Token},
+    span::Span,
+};
+
+/// Highlight class of a token kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HighlightScope {
+    Keyword,
+    Literal,
+    Comment,
+    Operator,
+    Punctuation,
+    Identifier,
+}
+
+impl HighlightScope {
+    /// Returns the class of `kind`, or `None` for whitespace and the BOM.
+    pub const fn of(kind: TokenKind) -> Option<Self> {
+        use TokenKind::*;
+        let info = kind.info();
+        if !info.opener.is_empty() {
+            let quoted = matches!(info.terminator.as_bytes(), [b'"'] | [b'\'']);
+            return Some(if quoted { Self::Literal } else { Self::Comment });
+        }
+        Some(match kind {
+            White | Bom => return None,
+            Pub | Fn | Let | Return | If | Else | While | Break | Continue | Const | Enum
+            | Struct | Match | Import | Module | Impl | Trait | For | In | Extern | Type
+            | Where | SelfValue | ImplPub | TraitPub | ImplFor | InherentImpl | TraitImpl
+            | ParamSelfValue | ParamSelfRefValue => Self::Keyword,
+            Int | Float | True | False | ImportString | ExternAbiString => Self::Literal,
+            Ident | LetIdent | ParamIdent | TypeIdent | MemberIdent | TypeAliasNameIdent
+            | TraitNameIdent | GenericParamIdent | WhereIdent | BoundTypeIdent | RangeEndIdent
+            | PathGenericIdent => Self::Identifier,
+            LParen | RParen | CallLParen | GroupLParen | GroupRParen | CallRParen | ParamLParen
+            | ParamRParen | PatternLParen | PatternRParen | EnumPayloadLParen
+            | EnumPayloadRParen | LBracket | RBracket | IndexLBracket | ArrayLBracket
+            | ArrayRBracket | IndexRBracket | TypeArrayLBracket | TypeArrayRBracket | LBrace
+            | RBrace | IfLBrace | IfRBrace | MatchLBrace | MatchRBrace | ImplLBrace
+            | ImplRBrace | TraitLBrace | TraitRBrace | StructLitLBrace | StructLitRBrace
+            | StructDeclLBrace | StructDeclRBrace | EnumLBrace | EnumRBrace | FnBlockLBrace
+            | FnBlockRBrace | ImplFnBlockLBrace | ImplFnBlockRBrace | AngleGeneric | TypeArgLt
+            | TypeArgGt | GenericParamLt | GenericParamGt | BoundTypeArgLt | BoundTypeArgGt
+            | PathTypeArgLt | PathTypeArgGt | Comma | ArgComma | ArrayComma | ParamComma
+            | TypeArgComma | GenericParamComma | EnumFieldComma | MatchArmComma | PatternComma
+            | WhereComma | EnumVariantComma | StructFieldComma | StructLitComma
+            | BoundTypeArgComma | PathTypeArgComma | Semicolon | TypeSemicolon
+            | TraitMethodSemicolon | ImportSemicolon | ModuleSemicolon | ExternSemicolon
+            | TypeAliasSemicolon | ConstSemicolon | LetSemicolon | ReturnSemicolon
+            | ExprSemicolon | BreakSemicolon | ContinueSemicolon | Colon | TypeColon
+            | BoundColon | PathColon | Dot | Arrow | ReturnArrow | MatchArrow | TypeAmpersand
+            | BoundTypeAmpersand | BoundPlus => Self::Punctuation,
+            _ => Self::Operator,
+        })
+    }
+}
+
+/// One highlighted token: its byte range, final kind and class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HighlightSpan {
+    pub span: Span,
+    pub kind: TokenKind,
+    pub scope: HighlightScope,
+}
+
+/// Result of one `GpuLexer::relex_highlight` call.
+#[derive(Debug, Clone, Default)]
+pub struct HighlightRangeOutput {
+    /// Highlighted tokens overlapping the range, with absolute full spans,
+    /// in source order.
+    pub spans: Vec<HighlightSpan>,
+    /// Byte window that was uploaded and lexed.
+    pub window: Span,
+    /// As [`RangeLexOutput::approximate`](crate::lexer::range::RangeLexOutput::approximate).
+    pub approximate: bool,
+}
+
+/// Highlights an all-boundary token stream, skipping tokens without a
+/// scope.
+pub fn highlight_spans(all_tokens: &[Token]) -> Vec<HighlightSpan> {
+    all_tokens
+        .iter()
+        .filter_map(|token| {
+            Some(HighlightSpan {
+                span: token.span,
+                kind: token.kind,
+                scope: HighlightScope::of(token.kind)?,
+            })
+        })
+        .collect()
+}
+
+/// Highlights the all-boundary tokens of a window lex starting at
+/// `window_start` that overlap `range`, shifted to absolute offsets.
+pub fn highlight_spans_in_range(
+    all_tokens: &[Token],
+    window_start: usize,
+    range: &Range<usize>,
+) -> Vec<HighlightSpan> {
+    highlight_spans(all_tokens)
+        .into_iter()
+        .map(|span| {
+            let Range { start, end } = span.span.range();
+            HighlightSpan {
+                span: Span::from_usize(start + window_start, end - start),
+                ..span
+            }
+        })
+        .filter(|span| {
+            let Range { start, end } = span.span.range();
+            start < range.end && end > range.start
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::{tables::tokens::KEYWORDS, test_cpu::lex_on_test_cpu_all};
+
+    #[test]
+    fn every_kind_name_lands_in_its_family() {
+        for &kind in TokenKind::ALL {
+            let name = kind.name();
+            let scope = HighlightScope::of(kind);
+            if KEYWORDS.iter().any(|&(_, k)| k == kind) {
+                let literal = matches!(kind, TokenKind::True | TokenKind::False);
+                let want = if literal {
+                    HighlightScope::Literal
+                } else {
+                    HighlightScope::Keyword
+                };
+                assert_eq!(scope, Some(want), "{name}");
+            } else if name.ends_with("Ident") {
+                assert_eq!(scope, Some(HighlightScope::Identifier), "{name}");
+            } else if ["Paren", "Bracket", "Brace", "Comma", "Semicolon"]
+                .iter()
+                .any(|part| name.contains(part))
+            {
+                assert_eq!(scope, Some(HighlightScope::Punctuation), "{name}");
+            } else if name.ends_with("Comment") || kind == TokenKind::Shebang {
+                assert_eq!(scope, Some(HighlightScope::Comment), "{name}");
+            } else if name.ends_with("String") || kind == TokenKind::Char {
+                assert_eq!(scope, Some(HighlightScope::Literal), "{name}");
+            }
+        }
+        assert_eq!(HighlightScope::of(TokenKind::White), None);
+        assert_eq!(HighlightScope::of(TokenKind::Bom), None);
+        assert_eq!(
+            HighlightScope::of(TokenKind::PlusAssign),
+            Some(HighlightScope::Operator)
+        );
+    }
+
+    #[test]
+    fn range_spans_are_absolute_and_overlap_the_range() {
+        let src = "let x = 1; // one\n";
+        let all = lex_on_test_cpu_all(src).unwrap();
+        let full = highlight_spans(&all);
+        assert!(full.iter().all(|span| span.kind != TokenKind::White));
+        assert_eq!(full.last().unwrap().scope, HighlightScope::Comment);
+
+        let window = 4;
+        let shifted = highlight_spans_in_range(
+            &lex_on_test_cpu_all(&src[window..]).unwrap(),
+            window,
+            &(6..9),
+        );
+        let spans: Vec<_> = shifted
+            .iter()
+            .map(|span| (&src[span.span.range()], span.scope))
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                ("=", HighlightScope::Operator),
+                ("1", HighlightScope::Literal)
+            ]
+        );
+    }
+}
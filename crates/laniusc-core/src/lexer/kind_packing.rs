@@ -0,0 +1,152 @@
+//! How `tok_types` stores each byte's pre-skip EMIT and EOF token kinds.
+//!
+//! `dfa_03_apply_block_prefix` writes the kinds of the boundaries at a byte
+//! and `compact_boundaries_all` reads them back into the compacted ALL
+//! stream. The wide packing gives each kind a
+//! [`TOK_KIND_LANE_BITS_WIDE`]-bit lane, so a byte fills one `u32`. When every
+//! kind id fits below the narrow lane's all-ones value, the narrow packing
+//! uses [`TOK_KIND_LANE_BITS_NARROW`]-bit lanes instead: two bytes share a
+//! `u32` and the buffer halves. Both decode to the same kinds.
+//!
+//! [`TOK_KIND_LANE_BITS_WIDE`]: crate::lexer::constants::TOK_KIND_LANE_BITS_WIDE
+//! [`TOK_KIND_LANE_BITS_NARROW`]: crate::lexer::constants::TOK_KIND_LANE_BITS_NARROW
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    language::LanguageDef,
+    lexer::constants::{TOK_KIND_LANE_BITS_NARROW, TOK_KIND_LANE_BITS_WIDE},
+};
+
+/// Kind read back for a lane with no boundary, in either packing.
+pub const ABSENT_KIND: u32 = 0xFFFF;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// Lane width of the `tok_types` buffer.
+pub enum KindPacking {
+    /// 16-bit lanes, one byte per `u32`; holds any kind id below `0xFFFF`.
+    #[default]
+    Wide,
+    /// 8-bit lanes, two bytes per `u32`; holds kind ids below `0xFF`.
+    Narrow,
+}
+
+impl KindPacking {
+    /// The narrowest packing that holds every kind id up to `max_kind`.
+    pub fn for_max_kind(max_kind: u32) -> Self {
+        if max_kind < Self::Narrow.absent() {
+            Self::Narrow
+        } else {
+            Self::Wide
+        }
+    }
+
+    /// The narrowest packing that holds every token kind of `def`.
+    pub fn for_language(def: &LanguageDef) -> Self {
+        let max_kind = def.token_kinds.iter().map(|&(_, id)| id).max();
+        Self::for_max_kind(max_kind.unwrap_or(0))
+    }
+
+    /// Bits of one kind lane.
+    pub const fn lane_bits(self) -> u32 {
+        match self {
+            Self::Wide => TOK_KIND_LANE_BITS_WIDE,
+            Self::Narrow => TOK_KIND_LANE_BITS_NARROW,
+        }
+    }
+
+    /// Input bytes whose two lanes share one `u32`.
+    pub const fn bytes_per_word(self) -> usize {
+        (32 / (2 * self.lane_bits())) as usize
+    }
+
+    /// `u32` words `tok_types` needs for `n` input bytes.
+    pub const fn words(self, n: usize) -> usize {
+        n.div_ceil(self.bytes_per_word())
+    }
+
+    /// All-ones lane value written for a boundary that is not present.
+    const fn absent(self) -> u32 {
+        (1 << self.lane_bits()) - 1
+    }
+
+    /// Stores byte `i`'s kinds the way `dfa_03_apply_block_prefix` does,
+    /// leaving the other bytes of its word untouched; `None` marks a missing
+    /// boundary.
+    pub fn pack(self, words: &mut [u32], i: usize, emit: Option<u32>, eof: Option<u32>) {
+        let bits = self.lane_bits();
+        let lane = |kind: Option<u32>| kind.map_or(self.absent(), |kind| kind & self.absent());
+        let pair = lane(emit) | (lane(eof) << bits);
+        let shift = (i % self.bytes_per_word()) as u32 * 2 * bits;
+        let mask = (((1u64 << (2 * bits)) - 1) as u32) << shift;
+        let word = &mut words[i / self.bytes_per_word()];
+        *word = (*word & !mask) | (pair << shift);
+    }
+
+    /// Byte `i`'s `(emit, eof)` kinds as `compact_boundaries_all` reads them,
+    /// with missing boundaries as [`ABSENT_KIND`].
+    pub fn unpack(self, words: &[u32], i: usize) -> (u32, u32) {
+        let bits = self.lane_bits();
+        let shift = (i % self.bytes_per_word()) as u32 * 2 * bits;
+        let pair = words[i / self.bytes_per_word()] >> shift;
+        let widen = |lane: u32| {
+            let lane = lane & self.absent();
+            if lane == self.absent() {
+                ABSENT_KIND
+            } else {
+                lane
+            }
+        };
+        (widen(pair), widen(pair >> bits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{language::definition, lexer::tables::tokens::TokenKind};
+
+    #[test]
+    fn current_kinds_fit_the_narrow_packing() {
+        assert_eq!(KindPacking::for_language(definition()), KindPacking::Narrow);
+        assert_eq!(KindPacking::for_max_kind(254), KindPacking::Narrow);
+        assert_eq!(KindPacking::for_max_kind(255), KindPacking::Wide);
+    }
+
+    #[test]
+    fn narrow_words_are_half_the_wide_ones() {
+        for n in [0, 1, 2, 3, 1 << 20] {
+            assert_eq!(KindPacking::Wide.words(n), n);
+            assert_eq!(KindPacking::Narrow.words(n), n.div_ceil(2));
+        }
+    }
+
+    #[test]
+    fn both_packings_round_trip_every_kind() {
+        let n = 5;
+        for packing in [KindPacking::Wide, KindPacking::Narrow] {
+            for &kind in TokenKind::ALL {
+                let kind = kind as u32;
+                // Neighbours written before and after must survive.
+                let mut words = vec![u32::MAX; packing.words(n)];
+                packing.pack(&mut words, 1, Some(7), None);
+                packing.pack(&mut words, 3, None, Some(9));
+                packing.pack(&mut words, 2, Some(kind), None);
+                assert_eq!(
+                    packing.unpack(&words, 2),
+                    (kind, ABSENT_KIND),
+                    "{packing:?}"
+                );
+                packing.pack(&mut words, 2, None, Some(kind));
+                assert_eq!(
+                    packing.unpack(&words, 2),
+                    (ABSENT_KIND, kind),
+                    "{packing:?}"
+                );
+                assert_eq!(packing.unpack(&words, 1), (7, ABSENT_KIND), "{packing:?}");
+                assert_eq!(packing.unpack(&words, 3), (ABSENT_KIND, 9), "{packing:?}");
+            }
+        }
+    }
+}
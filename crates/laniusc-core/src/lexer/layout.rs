@@ -0,0 +1,144 @@
+//! Per-token layout facts: the newlines, indentation and line comments in
+//! the trivia before each kept token.
+//!
+//! With [`LexOptions::layout_facts`](crate::lexer::LexOptions::layout_facts)
+//! set, `layout_01_facts` walks the gap between each kept token and the one
+//! before it, over the input bytes and the all-boundary kinds, and writes two
+//! words per kept token:
+//!
+//! | word | value                                                        |
+//! |------|--------------------------------------------------------------|
+//! | 0    | `\n` bytes in the gap                                        |
+//! | 1    | bits 0..16: indentation of the line, saturated               |
+//! |      | bits 16..32: line comments in the gap, saturated             |
+//!
+//! Only a token that starts a line, the first token or one after a newline,
+//! measures its line's indentation. `layout_03_apply_block_prefix` numbers
+//! the lines over a block scan of those tokens, and
+//! `layout_04_propagate_indent` copies each line's indentation to the rest
+//! of its tokens. [`decode`] turns the words into [`LayoutFacts`] of the
+//! source as given, undoing the driver's BOM and shebang masking.
+//!
+//! [`decode`]: crate::lexer::layout::decode
+
+use crate::lexer::{
+    bom::bom_len,
+    shebang::shebang_len,
+    tables::tokens::TokenKind,
+    types::{LayoutFacts, Token},
+};
+
+/// Words per kept token in the layout facts buffer.
+pub const LAYOUT_WORDS_PER_TOKEN: usize = 2;
+
+/// Bits of word 1 holding the indentation; line comments sit above them.
+const INDENT_MASK: u32 = 0xFFFF;
+
+/// Packs word 1 of one kept token, saturating both counts.
+pub fn pack_indent_and_comments(indent: usize, line_comments: usize) -> u32 {
+    let saturate = |count: usize| count.min(u16::MAX as usize) as u32;
+    saturate(indent) | saturate(line_comments) << 16
+}
+
+/// Decodes the words the layout passes wrote for `input`, two per kept token.
+pub fn decode(input: &[u8], words: &[u32]) -> Vec<LayoutFacts> {
+    let bom = bom_len(input) as u16;
+    let shebang = shebang_len(input).is_some();
+    let mut on_first_line = true;
+    words
+        .chunks_exact(LAYOUT_WORDS_PER_TOKEN)
+        .enumerate()
+        .map(|(k, pair)| {
+            on_first_line &= pair[0] == 0;
+            let mut indent_col = (pair[1] & INDENT_MASK) as u16;
+            // A masked BOM lexes as spaces that open the first line.
+            if on_first_line && indent_col != u16::MAX {
+                indent_col = indent_col.saturating_sub(bom);
+            }
+            // A masked shebang lexes as a line comment before the first token.
+            let line_comments = (pair[1] >> 16).saturating_sub(u32::from(k == 0 && shebang));
+            LayoutFacts {
+                newlines_before: pair[0].min(u32::from(u16::MAX)) as u16,
+                indent_col,
+                had_line_comment_before: line_comments != 0,
+            }
+        })
+        .collect()
+}
+
+/// Layout facts of the kept `tokens` of `input`, measured on the source
+/// itself; `all_tokens` is its all-boundary stream, which places the line
+/// comments. This is the definition [`decode`] must agree with.
+pub fn layout_facts_of(input: &[u8], tokens: &[Token], all_tokens: &[Token]) -> Vec<LayoutFacts> {
+    let comment_starts: Vec<usize> = all_tokens
+        .iter()
+        .filter(|token| token.kind == TokenKind::LineComment)
+        .map(Token::start)
+        .collect();
+    let saturate = |count: usize| count.min(u16::MAX as usize) as u16;
+    let mut line_start = bom_len(input);
+    let mut prev_end = 0;
+    tokens
+        .iter()
+        .map(|token| {
+            let gap = &input[prev_end..token.start()];
+            if let Some(last) = gap.iter().rposition(|&b| b == b'\n') {
+                line_start = prev_end + last + 1;
+            }
+            let comments_before_gap = comment_starts.partition_point(|&s| s < prev_end);
+            let comments_before_token = comment_starts.partition_point(|&s| s < token.start());
+            let indent = input[line_start..token.start()]
+                .iter()
+                .take_while(|&&b| b == b' ' || b == b'\t')
+                .count();
+            prev_end = token.end();
+            LayoutFacts {
+                newlines_before: saturate(gap.iter().filter(|&&b| b == b'\n').count()),
+                indent_col: saturate(indent),
+                had_line_comment_before: comments_before_token > comments_before_gap,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn facts(newlines_before: u16, indent_col: u16, had_line_comment_before: bool) -> LayoutFacts {
+        LayoutFacts {
+            newlines_before,
+            indent_col,
+            had_line_comment_before,
+        }
+    }
+
+    #[test]
+    fn decoding_undoes_bom_and_shebang_masking() {
+        // "\u{FEFF}  a b\n\tc": the BOM lexes as three more spaces.
+        let words = [
+            0,
+            pack_indent_and_comments(5, 0),
+            0,
+            pack_indent_and_comments(5, 0),
+            1,
+            pack_indent_and_comments(1, 0),
+        ];
+        assert_eq!(
+            decode(b"\xEF\xBB\xBF  a b\n\tc", &words),
+            [facts(0, 2, false), facts(0, 2, false), facts(1, 1, false)]
+        );
+        // "#!sh\n// c\nx": the shebang is one of the two line comments.
+        let words = [2, pack_indent_and_comments(0, 2)];
+        assert_eq!(decode(b"#!sh\n// c\nx", &words), [facts(2, 0, true)]);
+        let words = [1, pack_indent_and_comments(0, 1)];
+        assert_eq!(decode(b"#!sh\nx", &words), [facts(1, 0, false)]);
+    }
+
+    #[test]
+    fn counts_saturate_at_u16_max() {
+        assert_eq!(pack_indent_and_comments(70_000, 70_000), u32::MAX);
+        let words = [70_000, pack_indent_and_comments(70_000, 1)];
+        assert_eq!(decode(b"", &words), [facts(u16::MAX, u16::MAX, true)]);
+    }
+}
@@ -0,0 +1,164 @@
+//! Host-side lints over lexed token streams.
+//!
+//! Block comments do not nest: `/*` opens a comment and the first `*/` after
+//! it closes it, as in C. `/* a /* b */ c */` is therefore the comment
+//! `/* a /* b */`, then ` c */` as code, where the stray `*/` lexes as `Star`
+//! followed by `Slash`. The parser would report that `*/` as an operator
+//! error far from its cause, so [`scan`] names the shape directly, without
+//! running the parser.
+//!
+//! [`scan`]: crate::lexer::lints::scan
+
+use std::fmt;
+
+use crate::{
+    lexer::{tables::tokens::TokenKind, types::Token},
+    span::Span,
+};
+
+/// What a [`Lint`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintKind {
+    /// `*/` outside any block comment: an adjacent `Star` and `Slash` (or
+    /// `SlashAssign`) token.
+    UnopenedBlockCommentTerminator,
+    /// `/*` inside a block comment, which does not open a nested one.
+    PossibleNestedComment,
+}
+
+impl LintKind {
+    /// Short human-readable description.
+    pub const fn message(self) -> &'static str {
+        match self {
+            Self::UnopenedBlockCommentTerminator => "unopened block comment terminator",
+            Self::PossibleNestedComment => {
+                "possible nested comment; block comments do not nest, so the first `*/` closes it"
+            }
+        }
+    }
+}
+
+/// One lint finding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    pub kind: LintKind,
+    /// Source bytes of the offending `*/` or `/*`.
+    pub span: Span,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.kind.message(), self.span)
+    }
+}
+
+/// Lints `tokens`, lexed from `src`, ordered by span start.
+///
+/// Pass the all-boundary stream ([`LexOutput::all_tokens`](crate::lexer::LexOutput::all_tokens)
+/// or the test oracle's equivalent) to see comments; a kept stream still
+/// reports stray terminators but never nested comments. A `*/` inside a
+/// string or char literal is part of that token and is never linted; neither
+/// is `*//`, where the slash opens a line comment.
+pub fn scan(tokens: &[Token], src: &str) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        match token.raw_kind {
+            TokenKind::Star => {
+                let closes = tokens.get(i + 1).is_some_and(|next| {
+                    next.span.start == token.span.end
+                        && matches!(next.raw_kind, TokenKind::Slash | TokenKind::SlashAssign)
+                });
+                if closes {
+                    lints.push(Lint {
+                        kind: LintKind::UnopenedBlockCommentTerminator,
+                        span: Span::from_usize(token.start(), 2),
+                    });
+                }
+            }
+            TokenKind::BlockComment => {
+                let content = token.content_range(src);
+                let Some(text) = src.get(content.clone()) else {
+                    continue;
+                };
+                lints.extend(text.match_indices("/*").map(|(offset, _)| Lint {
+                    kind: LintKind::PossibleNestedComment,
+                    span: Span::from_usize(content.start + offset, 2),
+                }));
+            }
+            _ => {}
+        }
+    }
+    lints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::test_cpu::{lex_on_test_cpu, lex_on_test_cpu_all};
+
+    fn lints(src: &str) -> Vec<(LintKind, &str)> {
+        let tokens = lex_on_test_cpu_all(src).expect("test CPU lex");
+        scan(&tokens, src)
+            .into_iter()
+            .map(|lint| (lint.kind, &src[lint.span.range()]))
+            .collect()
+    }
+
+    #[test]
+    fn nested_comment_reports_the_inner_opener_and_the_stray_terminator() {
+        let src = "/* a /* b */ c */\n";
+        let found = scan(&lex_on_test_cpu_all(src).unwrap(), src);
+        assert_eq!(
+            found,
+            [
+                Lint {
+                    kind: LintKind::PossibleNestedComment,
+                    span: Span::new(5, 7),
+                },
+                Lint {
+                    kind: LintKind::UnopenedBlockCommentTerminator,
+                    span: Span::new(15, 17),
+                },
+            ]
+        );
+        assert_eq!(
+            found[1].to_string(),
+            "unopened block comment terminator at 15..17"
+        );
+    }
+
+    #[test]
+    fn stray_terminators_before_slash_or_slash_assign() {
+        use LintKind::UnopenedBlockCommentTerminator as Stray;
+        assert_eq!(lints("x */ y"), [(Stray, "*/")]);
+        assert_eq!(lints("x */= y"), [(Stray, "*/")]);
+        assert_eq!(lints("x **/ y"), [(Stray, "*/")]);
+        assert_eq!(lints("/* a */*/"), [(Stray, "*/")]);
+        // Not adjacent, or the slash opens a line comment.
+        assert!(lints("x * / y").is_empty());
+        assert!(lints("x *// y\n").is_empty());
+    }
+
+    #[test]
+    fn delimited_tokens_hide_comment_markers() {
+        for src in [
+            "let s = \"*/\";",
+            "let s = \"/* */\";",
+            "let c = '/';",
+            "a = 1 /* x */",
+            "/*/ x */",
+            "/**/",
+            "/*** doc ***/",
+        ] {
+            assert!(lints(src).is_empty(), "{src:?}: {:?}", lints(src));
+        }
+    }
+
+    #[test]
+    fn kept_streams_still_report_stray_terminators() {
+        let src = "/* a /* b */ c */";
+        let kept = lex_on_test_cpu(src).unwrap();
+        let kinds: Vec<_> = scan(&kept, src).into_iter().map(|lint| lint.kind).collect();
+        assert_eq!(kinds, [LintKind::UnopenedBlockCommentTerminator]);
+    }
+}
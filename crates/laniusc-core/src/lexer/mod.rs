@@ -0,0 +1,84 @@
+//! Host side of the lexer.
+//!
+//! Everything the GPU lexer shares with the host without a device: DFA and
+//! token tables, token records and options, boundary and UTF-8 policies, and
+//! the test CPU oracle the GPU output is checked against.
+
+/// Boundary-level lex statistics reduced without building tokens.
+pub mod analysis;
+/// Leading UTF-8 byte order mark handling shared by the driver and the test
+/// oracle.
+pub mod bom;
+/// Per-byte token-boundary decision shared with the lexer shaders.
+pub mod boundary;
+/// Constants shared with the lexer shaders through a generated header.
+pub mod constants;
+/// Delimiter and terminator conventions of string, char, and comment tokens.
+pub mod delimiters;
+/// Token-stream divergence classification and mismatch reports.
+pub mod diff;
+/// Escape spans inside string and char literals, and their containing tokens.
+pub mod escapes;
+/// GPU-produced conservative parser-family feature flags.
+pub mod features;
+/// Coarse token classes for syntax highlighting.
+pub mod highlight;
+/// Lane widths of the per-byte token-kind buffer the GPU passes share.
+pub mod kind_packing;
+/// Per-token newline, indentation and comment facts for formatters.
+pub mod layout;
+/// Host-side lints for block comment terminators and nesting attempts.
+pub mod lints;
+/// Integer literal decoding keyed on DFA accept states.
+pub mod numeric;
+/// Which pipeline stage produces each token kind.
+pub mod origin;
+/// Paranoia mode: CPU rechecks of the tokens the GPU flags as suspect.
+pub mod paranoia;
+/// Window selection and token clipping for range-restricted lexing.
+pub mod range;
+/// Safe lex restart points before arbitrary byte offsets.
+pub mod resync;
+/// Source round-trip checks over all-boundary and kept token streams.
+pub mod roundtrip;
+/// Leading `#!` line handling shared by the driver and the test oracle.
+pub mod shebang;
+/// Lexer DFA and token tables.
+pub mod tables;
+/// Flat binary token-stream files for non-Rust tools.
+pub mod tokens_io;
+/// Host token record types.
+pub mod types;
+/// UTF-8 boundary policy for token byte ranges.
+pub mod utf8;
+/// Small lexer helpers shared by driver and tests.
+pub mod util;
+/// Known-good reference hashes of token and parser emit streams.
+pub mod verify;
+
+pub use highlight::{HighlightRangeOutput, HighlightScope, HighlightSpan};
+pub use range::{LexRangeOptions, RangeLexOutput, RangeToken};
+pub use types::{
+    DEFAULT_SINGLE_SUBMISSION_MAX_BYTES,
+    EscapeSpan,
+    LayoutFacts,
+    LexAnalysis,
+    LexError,
+    LexOptions,
+    LexOutput,
+    LexReport,
+    LexReportViolation,
+    LexWarning,
+    ReadbackMode,
+    Token,
+};
+pub use utf8::{BoundaryViolation, LexemeError, LexemePolicy, check_token_boundaries, lexemes};
+
+/// TEST-ONLY CPU lexer oracle.
+///
+/// This module exists for integration tests and fuzz-test tooling that compare
+/// GPU lexer output against an intentionally named host oracle. Compiler code
+/// must not use it as a fallback; the opt-in paranoia recheck is its only
+/// caller outside tests and tooling.
+#[doc(hidden)]
+pub mod test_cpu;
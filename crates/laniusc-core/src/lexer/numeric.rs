@@ -0,0 +1,95 @@
+//! Integer literal decoding keyed on the DFA accept state.
+//!
+//! Several DFA states accept `TokenKind::Int`. The state already says which
+//! radix the literal uses and whether it carries a `0x`/`0b`/`0o` prefix, so
+//! decoding dispatches on it instead of re-reading the prefix bytes.
+
+use crate::lexer::tables::dfa::S;
+
+/// Radix and prefix length of an integer literal accepted in one DFA state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntLiteralForm {
+    /// Digit radix.
+    pub radix: u32,
+    /// Prefix bytes before the first digit.
+    pub prefix_len: usize,
+}
+
+/// Returns the literal form for an integer accept state, or `None` when
+/// `state` does not accept `TokenKind::Int`.
+pub fn int_literal_form(state: u16) -> Option<IntLiteralForm> {
+    let (radix, prefix_len) = match S::from_idx(state as usize)? {
+        S::Zero | S::Int | S::IntAfterUnderscore | S::MaybeExpFromInt => (10, 0),
+        S::HexStart | S::Hex => (16, 2),
+        S::BinStart | S::Bin => (2, 2),
+        S::OctStart | S::Oct => (8, 2),
+        _ => return None,
+    };
+    Some(IntLiteralForm { radix, prefix_len })
+}
+
+/// Decodes an integer literal's text using the DFA state that accepted it.
+///
+/// Underscore separators are skipped. Returns `None` for non-integer states,
+/// prefixes without digits (`0x`), and values that overflow `u64`.
+pub fn decode_int(text: &[u8], accept_state: u16) -> Option<u64> {
+    let form = int_literal_form(accept_state)?;
+    let mut digits = text.get(form.prefix_len..)?;
+    if accept_state as usize == S::MaybeExpFromInt.idx() {
+        // `1e` with no exponent digits: the DFA kept the `e` on the Int.
+        digits = digits.strip_suffix(b"e").or(digits.strip_suffix(b"E"))?;
+    }
+    let mut value = 0u64;
+    let mut seen_digit = false;
+    for &b in digits {
+        if b == b'_' {
+            continue;
+        }
+        let digit = (b as char).to_digit(form.radix)?;
+        value = value
+            .checked_mul(form.radix as u64)?
+            .checked_add(digit as u64)?;
+        seen_digit = true;
+    }
+    seen_digit.then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(text: &str, state: S) -> Option<u64> {
+        decode_int(text.as_bytes(), state.idx() as u16)
+    }
+
+    #[test]
+    fn decodes_each_radix_from_its_accept_state() {
+        assert_eq!(decode("0", S::Zero), Some(0));
+        assert_eq!(decode("1_000", S::Int), Some(1000));
+        assert_eq!(decode("1_", S::IntAfterUnderscore), Some(1));
+        assert_eq!(decode("0x1F", S::Hex), Some(0x1f));
+        assert_eq!(decode("0xf_f_", S::HexStart), Some(0xff));
+        assert_eq!(decode("0b1_01", S::Bin), Some(0b101));
+        assert_eq!(decode("0o17", S::Oct), Some(0o17));
+        assert_eq!(decode("12e", S::MaybeExpFromInt), Some(12));
+    }
+
+    #[test]
+    fn rejects_empty_prefixes_overflow_and_non_int_states() {
+        assert_eq!(decode("0x", S::HexStart), None);
+        assert_eq!(decode("0b_", S::BinStart), None);
+        assert_eq!(decode("18446744073709551616", S::Int), None);
+        assert_eq!(decode("1.5", S::FloatFrac), None);
+        assert_eq!(decode("abc", S::Ident), None);
+        assert_eq!(decode_int(b"1", u16::MAX), None);
+    }
+
+    #[test]
+    fn state_not_prefix_picks_the_radix() {
+        // The same digits decode differently under different accept states;
+        // nothing re-reads the `0x` prefix to decide.
+        assert_eq!(decode("0x10", S::Hex), Some(16));
+        assert_eq!(decode("0o10", S::Oct), Some(8));
+        assert_eq!(decode("0b10", S::Bin), Some(2));
+    }
+}
@@ -0,0 +1,116 @@
+//! Which pipeline stage produces each token kind.
+//!
+//! The lexer emits DFA kinds, keyword kinds retagged from identifier lexemes,
+//! and the driver's pre-scanned `#!` and BOM tokens. The parser token passes
+//! (`shaders/parser/tokens/to/*.slang`) then rewrite many raw kinds into
+//! context-specific ones, such as `(` into `CallLParen` or `GroupLParen`.
+//! [`TokenKind::origin`] records this so a grammar can be checked against the
+//! kinds the parser will actually see (see `parser::grammar`).
+//!
+//! [`TokenKind::origin`]: crate::lexer::tables::tokens::TokenKind::origin
+
+use crate::lexer::{boundary::is_skip_kind, tables::tokens::TokenKind};
+
+/// Where the token stream a parser reads gets a kind from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KindOrigin {
+    /// Emitted by the lexer into the kept stream.
+    Lexed,
+    /// Emitted by the lexer but dropped from the kept stream.
+    Trivia,
+    /// Written by the parser token passes in place of this raw lexer kind.
+    Retag(TokenKind),
+    /// Reserved: nothing in the current pipeline emits it.
+    Unproduced,
+}
+
+impl KindOrigin {
+    /// Whether the parser can see a token of this origin.
+    pub const fn reaches_parser(self) -> bool {
+        matches!(self, Self::Lexed | Self::Retag(_))
+    }
+}
+
+impl TokenKind {
+    /// Returns the stage that produces this kind.
+    pub fn origin(self) -> KindOrigin {
+        use TokenKind::*;
+        if is_skip_kind(self) {
+            return KindOrigin::Trivia;
+        }
+        let raw = match self {
+            LetIdent | ParamIdent | TypeIdent | MemberIdent | TypeAliasNameIdent
+            | TraitNameIdent | GenericParamIdent | WhereIdent | BoundTypeIdent | RangeEndIdent
+            | PathGenericIdent => Ident,
+            CallLParen | GroupLParen | ParamLParen | PatternLParen | EnumPayloadLParen => LParen,
+            CallRParen | GroupRParen | ParamRParen | PatternRParen | EnumPayloadRParen => RParen,
+            IndexLBracket | ArrayLBracket | TypeArrayLBracket => LBracket,
+            IndexRBracket | ArrayRBracket | TypeArrayRBracket => RBracket,
+            IfLBrace | MatchLBrace | ImplLBrace | TraitLBrace | StructLitLBrace
+            | StructDeclLBrace | EnumLBrace | FnBlockLBrace | ImplFnBlockLBrace => LBrace,
+            IfRBrace | MatchRBrace | ImplRBrace | TraitRBrace | StructLitRBrace
+            | StructDeclRBrace | EnumRBrace | FnBlockRBrace | ImplFnBlockRBrace => RBrace,
+            LetAssign | TypeAliasAssign | ConstAssign | RangeInclusiveAssign => Assign,
+            TypeSemicolon | TraitMethodSemicolon | ImportSemicolon | ModuleSemicolon
+            | ExternSemicolon | TypeAliasSemicolon | ConstSemicolon | LetSemicolon
+            | ReturnSemicolon | ExprSemicolon | BreakSemicolon | ContinueSemicolon => Semicolon,
+            ArgComma | ArrayComma | ParamComma | TypeArgComma | GenericParamComma
+            | EnumFieldComma | MatchArmComma | PatternComma | WhereComma | EnumVariantComma
+            | StructFieldComma | StructLitComma | BoundTypeArgComma | PathTypeArgComma => Comma,
+            BoundColon | TypeColon | PathColon => Colon,
+            TypeArgLt | GenericParamLt | BoundTypeArgLt | PathTypeArgLt => Lt,
+            TypeArgGt | GenericParamGt | BoundTypeArgGt | PathTypeArgGt => Gt,
+            TypeAmpersand | BoundTypeAmpersand => Ampersand,
+            PrefixPlus | InfixPlus | BoundPlus => Plus,
+            PrefixMinus | InfixMinus => Minus,
+            ReturnArrow => Arrow,
+            ImplPub | TraitPub => Pub,
+            ParamSelfValue | ParamSelfRefValue => SelfValue,
+            InherentImpl | TraitImpl => Impl,
+            ImplFor => For,
+            ImportString | ExternAbiString => String,
+            DeclAssign | PrefixInc | PostfixInc | PrefixDec | PostfixDec => {
+                return KindOrigin::Unproduced;
+            }
+            _ => return KindOrigin::Lexed,
+        };
+        KindOrigin::Retag(raw)
+    }
+
+    /// Kinds the parser token passes may write in place of this raw kind.
+    pub fn retags(self) -> impl Iterator<Item = TokenKind> {
+        Self::ALL
+            .iter()
+            .copied()
+            .filter(move |kind| kind.origin() == KindOrigin::Retag(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tables::dfa::{S, token_of_state};
+
+    #[test]
+    fn dfa_kinds_are_lexed_or_trivia() {
+        for kind in (0..).map_while(S::from_idx).filter_map(token_of_state) {
+            assert!(
+                matches!(kind.origin(), KindOrigin::Lexed | KindOrigin::Trivia),
+                "DFA accepts {kind:?} but its origin is {:?}",
+                kind.origin()
+            );
+        }
+    }
+
+    #[test]
+    fn retags_name_a_lexed_raw_kind() {
+        for &kind in TokenKind::ALL {
+            if let KindOrigin::Retag(raw) = kind.origin() {
+                assert_eq!(raw.origin(), KindOrigin::Lexed, "{kind:?} retags {raw:?}");
+            }
+        }
+        let parens: Vec<_> = TokenKind::LParen.retags().collect();
+        assert!(parens.contains(&TokenKind::CallLParen), "{parens:?}");
+        assert_eq!(TokenKind::Star.retags().count(), 0);
+    }
+}
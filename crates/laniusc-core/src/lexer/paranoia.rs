@@ -0,0 +1,297 @@
+//! Paranoia mode: cheap spot checks of GPU tokens against the test CPU oracle.
+//!
+//! A full differential lex costs a whole CPU lex. In paranoia mode
+//! `tokens_build` instead lists the kept tokens whose computation went through
+//! a known-risky path, tagged with `SUSPECT_*` classes, and the driver relexes
+//! only a small window around each on the host. Windows start at a
+//! [`resync::find_safe_start`](crate::lexer::resync::find_safe_start) point
+//! of the full lex, as `GpuLexer::lex_range` windows do, so a window lex that
+//! disagrees with the GPU points at a GPU fault rather than at a cut in the
+//! wrong place.
+
+use std::ops::Range;
+
+use crate::lexer::{
+    constants::{
+        DFA_BLOCK_WIDTH,
+        SUSPECT_ANY,
+        SUSPECT_BLOCK_EDGE,
+        SUSPECT_CLAMPED,
+        SUSPECT_DUAL_BOUNDARY,
+        SUSPECT_EOF_RULE,
+    },
+    range::{LexRangeOptions, lex_range_on_test_cpu, tokens_in_range},
+    types::{LexOptions, LexWarning, Token},
+};
+
+/// Window sizing of the CPU recheck; far shorter than a range lex, since
+/// suspects are single tokens.
+const RECHECK_WINDOW: LexRangeOptions = LexRangeOptions {
+    backward_slop: 64,
+    max_backward: 4096,
+    forward_slop: 64,
+    max_forward: 4096,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A kept token `tokens_build` listed for a CPU recheck in paranoia mode.
+pub struct SuspectToken {
+    /// Kept index of the token.
+    pub token_index: usize,
+    /// `SUSPECT_*` classes the token fell in, limited to those the paranoia
+    /// level asked for.
+    pub classes: u32,
+}
+
+/// `SUSPECT_*` classes flagged at a [`LexOptions::paranoia`](crate::lexer::LexOptions::paranoia) level.
+pub fn suspect_classes(level: u8) -> u32 {
+    match level {
+        0 => 0,
+        1 => SUSPECT_DUAL_BOUNDARY | SUSPECT_EOF_RULE | SUSPECT_CLAMPED,
+        2 => SUSPECT_DUAL_BOUNDARY | SUSPECT_EOF_RULE | SUSPECT_CLAMPED | SUSPECT_BLOCK_EDGE,
+        _ => SUSPECT_ANY,
+    }
+}
+
+/// Host model of the `tokens_build` flagging: the suspects among the kept
+/// `tokens` of one file ending at `file_end`, as `options` select them.
+pub fn suspect_tokens_of(
+    tokens: &[Token],
+    file_end: usize,
+    options: &LexOptions,
+) -> Vec<SuspectToken> {
+    let mask = suspect_classes(options.paranoia_level);
+    tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(token_index, token)| {
+            let classes = suspect_classes_of(token, file_end, options.max_token_len) & mask;
+            (classes != 0).then_some(SuspectToken {
+                token_index,
+                classes,
+            })
+        })
+        .collect()
+}
+
+/// Every `SUSPECT_*` class of one kept token, before the level's mask.
+fn suspect_classes_of(token: &Token, file_end: usize, max_token_len: u32) -> u32 {
+    let block = DFA_BLOCK_WIDTH as usize;
+    let end = token.end();
+    let mut classes = SUSPECT_ANY;
+    if end == file_end {
+        classes |= SUSPECT_EOF_RULE;
+        if token.len() == 1 {
+            classes |= SUSPECT_DUAL_BOUNDARY;
+        }
+    }
+    if token.is_empty() || token.len() > max_token_len as usize {
+        classes |= SUSPECT_CLAMPED;
+    }
+    if token.start().is_multiple_of(block) || token.start() / block != end / block {
+        classes |= SUSPECT_BLOCK_EDGE;
+    }
+    classes
+}
+
+/// Suspects whose byte ranges lie within one recheck slop of each other, and
+/// the byte range covering them.
+fn clusters(
+    src: &str,
+    tokens: &[Token],
+    suspects: &[SuspectToken],
+) -> Vec<(Range<usize>, Vec<usize>)> {
+    let mut out: Vec<(Range<usize>, Vec<usize>)> = Vec::new();
+    for suspect in suspects {
+        let Some(token) = tokens.get(suspect.token_index) else {
+            continue;
+        };
+        // Zero-length tokens still get a byte of range to be found by.
+        let end = (token.start() + token.len().max(1)).min(src.len());
+        let range = token.start().min(end)..end;
+        match out.last_mut() {
+            Some((cluster, members))
+                if range.start <= cluster.end + RECHECK_WINDOW.forward_slop =>
+            {
+                cluster.end = cluster.end.max(range.end);
+                members.push(suspect.token_index);
+            }
+            _ => out.push((range, vec![suspect.token_index])),
+        }
+    }
+    out
+}
+
+/// Relexes a window around each cluster of `suspects`, sorted by token index,
+/// on the test CPU oracle and returns a
+/// [`LexWarning::SuspectMismatch`] for each suspect whose GPU token the
+/// relex does not reproduce. When the window differs only in tokens that were
+/// not suspects, the first suspect of the cluster carries the warning.
+///
+/// Windows that cannot be placed at a proven resynchronization point, e.g.
+/// deep inside a long block comment, are skipped rather than reported.
+pub fn recheck_suspects(src: &str, tokens: &[Token], suspects: &[SuspectToken]) -> Vec<LexWarning> {
+    let mut warnings = Vec::new();
+    for (range, members) in clusters(src, tokens, suspects) {
+        let relexed = lex_range_on_test_cpu(src, range.clone(), &RECHECK_WINDOW);
+        if relexed.approximate {
+            log::debug!(
+                target: "laniusc::lexer",
+                "paranoia: skipped the unsynchronized window {}",
+                relexed.window
+            );
+            continue;
+        }
+        let cpu: Vec<Token> = relexed.tokens.iter().map(|t| t.token).collect();
+        let gpu: Vec<Token> = tokens_in_range(tokens, 0, &range)
+            .iter()
+            .map(|t| t.token)
+            .collect();
+        if cpu == gpu {
+            continue;
+        }
+        let before = warnings.len();
+        for &token_index in &members {
+            if !cpu.contains(&tokens[token_index]) {
+                warnings.push(LexWarning::SuspectMismatch {
+                    token_index,
+                    window: relexed.window,
+                });
+            }
+        }
+        if warnings.len() == before {
+            warnings.push(LexWarning::SuspectMismatch {
+                token_index: members[0],
+                window: relexed.window,
+            });
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::{tables::tokens::TokenKind, test_cpu::lex_on_test_cpu};
+
+    fn all_suspects(tokens: &[Token]) -> Vec<SuspectToken> {
+        (0..tokens.len())
+            .map(|token_index| SuspectToken {
+                token_index,
+                classes: SUSPECT_ANY,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn levels_add_classes_up_to_every_token() {
+        assert_eq!(suspect_classes(0), 0);
+        assert_eq!(suspect_classes(1) & SUSPECT_BLOCK_EDGE, 0);
+        assert_eq!(suspect_classes(2), suspect_classes(1) | SUSPECT_BLOCK_EDGE);
+        assert_eq!(suspect_classes(3), SUSPECT_ANY);
+        assert_eq!(suspect_classes(u8::MAX), SUSPECT_ANY);
+    }
+
+    #[test]
+    fn host_model_flags_file_ends_clamps_and_block_edges() {
+        let src = format!("{}let a = 1;\nb", " ".repeat(250));
+        let tokens = lex_on_test_cpu(&src).unwrap();
+        let classes: Vec<u32> = tokens
+            .iter()
+            .map(|t| suspect_classes_of(t, src.len(), 2) & !SUSPECT_ANY)
+            .collect();
+        // `let` is longer than the limit of 2, `=` starts at the block edge
+        // 256, and `b` is the one-byte token closing the file.
+        let at = |text: &str| tokens.iter().position(|t| &src[t.span.range()] == text);
+        assert_eq!(classes[at("let").unwrap()], SUSPECT_CLAMPED);
+        assert_eq!(classes[at("=").unwrap()], SUSPECT_BLOCK_EDGE);
+        assert_eq!(classes[at("1").unwrap()], 0);
+        assert_eq!(
+            classes[at("b").unwrap()],
+            SUSPECT_EOF_RULE | SUSPECT_DUAL_BOUNDARY
+        );
+    }
+
+    #[test]
+    fn host_model_masks_classes_by_level() {
+        let src = "let a = 1;\nb";
+        let tokens = lex_on_test_cpu(src).unwrap();
+        assert!(suspect_tokens_of(&tokens, src.len(), &LexOptions::default()).is_empty());
+        assert_eq!(
+            suspect_tokens_of(&tokens, src.len(), &LexOptions::paranoia(1)),
+            [SuspectToken {
+                token_index: tokens.len() - 1,
+                classes: SUSPECT_EOF_RULE | SUSPECT_DUAL_BOUNDARY,
+            }]
+        );
+        // `let` starts block 0.
+        assert_eq!(
+            suspect_tokens_of(&tokens, src.len(), &LexOptions::paranoia(2))[0],
+            SuspectToken {
+                token_index: 0,
+                classes: SUSPECT_BLOCK_EDGE,
+            }
+        );
+        let every = suspect_tokens_of(&tokens, src.len(), &LexOptions::paranoia(3));
+        assert_eq!(every, all_suspects(&tokens));
+    }
+
+    #[test]
+    fn recheck_accepts_tokens_the_oracle_reproduces() {
+        let src = "fn main() {\n    let x = 1 + 2;\n    /* c */ return x;\n}";
+        let tokens = lex_on_test_cpu(src).unwrap();
+        assert!(recheck_suspects(src, &tokens, &all_suspects(&tokens)).is_empty());
+    }
+
+    #[test]
+    fn recheck_reports_the_suspects_the_oracle_disagrees_with() {
+        let src = "let a = 1;\nlet b = 22;\nlet c = 3";
+        let mut tokens = lex_on_test_cpu(src).unwrap();
+        let last = tokens.len() - 1;
+        tokens[last].kind = TokenKind::Ident;
+
+        let warnings = recheck_suspects(
+            src,
+            &tokens,
+            &[SuspectToken {
+                token_index: last,
+                classes: SUSPECT_EOF_RULE | SUSPECT_DUAL_BOUNDARY,
+            }],
+        );
+        assert_eq!(warnings.len(), 1);
+        let LexWarning::SuspectMismatch {
+            token_index,
+            window,
+        } = &warnings[0]
+        else {
+            panic!("expected a suspect mismatch, got {warnings:?}");
+        };
+        assert_eq!(*token_index, last);
+        assert!(window.contains_span(tokens[last].span));
+        assert_eq!(window.range().end, src.len());
+
+        // Only the suspects' own bytes are compared, so a bad token that was
+        // not listed goes unnoticed even inside a suspect's window.
+        let mut tokens = lex_on_test_cpu(src).unwrap();
+        tokens[3].kind = TokenKind::Ident;
+        let suspect = SuspectToken {
+            token_index: last,
+            classes: SUSPECT_EOF_RULE,
+        };
+        assert!(recheck_suspects(src, &tokens, &[suspect]).is_empty());
+    }
+
+    #[test]
+    fn nearby_suspects_share_one_window() {
+        let src = "let a = 1; let b = 2;";
+        let tokens = lex_on_test_cpu(src).unwrap();
+        let grouped = clusters(src, &tokens, &all_suspects(&tokens));
+        assert_eq!(grouped.len(), 1);
+        assert_eq!(grouped[0].0, 0..src.len());
+        assert_eq!(grouped[0].1.len(), tokens.len());
+
+        let far = format!("a{}b", " ".repeat(1000));
+        let tokens = lex_on_test_cpu(&far).unwrap();
+        assert_eq!(clusters(&far, &tokens, &all_suspects(&tokens)).len(), 2);
+    }
+}
@@ -0,0 +1,428 @@
+//! Range-restricted lexing support.
+//!
+//! `GpuLexer::lex_range` lexes a window
+//! around a byte range instead of the whole buffer. The window has to start
+//! where the DFA of a full lex is back at its start state. [`sync_point`]
+//! finds such a byte on the host by running the DFA from every state at
+//! once over a short backward slop: once every surviving run ends a token at
+//! the same byte and lands in the same state, the lex from there on no longer
+//! depends on anything before the slop. The window itself starts at the
+//! last token boundary before the range that a walk from there reaches; see
+//! [`crate::lexer::resync`].
+//!
+//! [`sync_point`]: crate::lexer::range::sync_point
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    lexer::{
+        resync::find_safe_start_with,
+        tables::{dfa::StreamingDfa, tokens::INVALID_TOKEN},
+        test_cpu::{lex_on_test_cpu, lex_on_test_cpu_all},
+        types::Token,
+    },
+    span::Span,
+};
+
+/// Window sizing for `GpuLexer::lex_range`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LexRangeOptions {
+    /// Bytes before the range searched for a resynchronization point.
+    pub backward_slop: usize,
+    /// Furthest the window may start before the range when the slop holds
+    /// no resynchronization point, e.g. inside a long block comment.
+    pub max_backward: usize,
+    /// Bytes lexed past the range end so the last token's end is known.
+    pub forward_slop: usize,
+    /// Furthest the window may end past the range while that end is sought.
+    pub max_forward: usize,
+}
+
+impl Default for LexRangeOptions {
+    fn default() -> Self {
+        Self {
+            backward_slop: 256,
+            max_backward: 64 * 1024,
+            forward_slop: 256,
+            max_forward: 64 * 1024,
+        }
+    }
+}
+
+/// Kept token overlapping the requested range, with its full absolute span.
+#[derive(Debug, Clone)]
+pub struct RangeToken {
+    pub token: Token,
+    /// The token starts before the range.
+    pub clipped_start: bool,
+    /// The token ends after the range.
+    pub clipped_end: bool,
+}
+
+/// Result of one `GpuLexer::lex_range` call.
+#[derive(Debug, Clone, Default)]
+pub struct RangeLexOutput {
+    /// Kept tokens overlapping the range, in source order.
+    pub tokens: Vec<RangeToken>,
+    /// Byte window that was uploaded and lexed.
+    pub window: Span,
+    /// The window start is a heuristic rather than a proven resynchronization
+    /// point, or the last token's end lies past `max_forward`; the tokens may
+    /// then differ from a full lex.
+    pub approximate: bool,
+}
+
+/// Returns the first byte in `from..to` (or `to` itself, when in bounds) at
+/// which every DFA run started anywhere in `from..` ends a token and resumes
+/// in one state; `0` when `from` is the start of the input.
+///
+/// Runs that reject or end a token from a non-accepting state are dropped,
+/// since a lexically valid input never takes them.
+///
+/// The test CPU oracle cuts whole inputs at these points to walk the pieces
+/// on separate threads.
+pub fn sync_point(dfa: &StreamingDfa, src: &[u8], from: usize, to: usize) -> Option<usize> {
+    if from == 0 {
+        return Some(0);
+    }
+    let mut runs: Vec<u16> = (0..dfa.next.len() as u16)
+        .filter(|&state| state != dfa.reject)
+        .collect();
+    for (i, &byte) in src.iter().enumerate().take(to.saturating_add(1)).skip(from) {
+        let mut all_emit = true;
+        runs.retain_mut(|state| {
+            let next = dfa.next[*state as usize][byte as usize];
+            if next.state == dfa.reject
+                || (next.emit && dfa.token_map[*state as usize] == INVALID_TOKEN)
+            {
+                return false;
+            }
+            all_emit &= next.emit;
+            *state = next.state;
+            true
+        });
+        runs.sort_unstable();
+        runs.dedup();
+        match runs.len() {
+            0 => return None,
+            1 if all_emit => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Start of the nearest line at or before `before`, and after `floor`, that
+/// begins in column 0 with a non-whitespace byte: items at the top level of
+/// a file usually do, so such a line rarely starts inside a comment or string.
+fn top_level_line_start(src: &[u8], before: usize, floor: usize) -> Option<usize> {
+    (floor.max(1)..=before.min(src.len().saturating_sub(1)))
+        .rev()
+        .find(|&i| src[i - 1] == b'\n' && !src[i].is_ascii_whitespace())
+}
+
+/// Picks where the window for a range starting at `start` begins, and
+/// whether that start is only a heuristic.
+///
+/// The window starts at the [`find_safe_start_with`] point for `start`,
+/// searched within `backward_slop` bytes and then within `max_backward`.
+/// Failing both, it starts at a top-level line within `max_backward`, or
+/// the first character boundary there, and is marked approximate.
+pub fn window_start(
+    dfa: &StreamingDfa,
+    src: &str,
+    start: usize,
+    options: &LexRangeOptions,
+) -> (usize, bool) {
+    let bytes = src.as_bytes();
+    let safe = find_safe_start_with(dfa, bytes, start, options.backward_slop)
+        .or_else(|| find_safe_start_with(dfa, bytes, start, options.max_backward));
+    if let Some(safe) = safe {
+        return (safe.offset, false);
+    }
+    let near = start.saturating_sub(options.backward_slop);
+    let floor = start.saturating_sub(options.max_backward);
+    let line = top_level_line_start(bytes, near, floor);
+    (line.unwrap_or_else(|| src.ceil_char_boundary(floor)), true)
+}
+
+/// Returns the offset, relative to the window, up to which the tokens of a
+/// window lex match a full lex: the start of the second-to-last boundary
+/// token, since the last one may be cut by the window end and the kind of
+/// the one before may depend on it.
+pub fn trusted_window_end(all_tokens: &[Token]) -> usize {
+    all_tokens
+        .len()
+        .checked_sub(2)
+        .map_or(0, |i| all_tokens[i].start())
+}
+
+/// Shifts the kept tokens of a window lex starting at `window_start` to
+/// absolute offsets and keeps those overlapping `range`.
+pub fn tokens_in_range(
+    kept: &[Token],
+    window_start: usize,
+    range: &Range<usize>,
+) -> Vec<RangeToken> {
+    kept.iter()
+        .map(|token| Token {
+            span: Span::from_usize(token.start() + window_start, token.len()),
+            ..*token
+        })
+        .filter(|token| token.start() < range.end && token.end() > range.start)
+        .map(|token| RangeToken {
+            clipped_start: token.start() < range.start,
+            clipped_end: token.end() > range.end,
+            token,
+        })
+        .collect()
+}
+
+/// Host model of `GpuLexer::lex_range_with_options`,
+/// with the test CPU oracle in place of the GPU. Paranoia mode rechecks
+/// suspect tokens with it.
+pub fn lex_range_on_test_cpu(
+    src: &str,
+    range: Range<usize>,
+    options: &LexRangeOptions,
+) -> RangeLexOutput {
+    let dfa = StreamingDfa::new();
+    let (start, mut approximate) = window_start(&dfa, src, range.start, options);
+    let mut extra = options.forward_slop.max(1);
+    loop {
+        let end = src.ceil_char_boundary(range.end.saturating_add(extra).min(src.len()));
+        let window = &src[start..end];
+        // A window cut inside a string or comment does not lex on the
+        // oracle; the GPU drops that unterminated tail, so nothing in the
+        // window is trusted until it grows past it.
+        let (all, kept) = match (lex_on_test_cpu_all(window), lex_on_test_cpu(window)) {
+            (Ok(all), Ok(kept)) => (all, kept),
+            _ => (Vec::new(), Vec::new()),
+        };
+        let done = end == src.len() || range.end <= start + trusted_window_end(&all);
+        if done || extra >= options.max_forward {
+            approximate |= !done;
+            return RangeLexOutput {
+                tokens: tokens_in_range(&kept, start, &range),
+                window: Span::from_usize(start, end - start),
+                approximate,
+            };
+        }
+        extra = (extra * 2).min(options.max_forward);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::test_runner::{Config, TestCaseError, TestRunner};
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{
+        dev::generator::{arb_source_gen_config, gen_source},
+        lexer::tables::tokens::TokenKind,
+    };
+
+    fn keys(tokens: &[RangeToken]) -> Vec<(TokenKind, usize, usize, bool, bool)> {
+        tokens
+            .iter()
+            .map(|t| {
+                (
+                    t.token.kind,
+                    t.token.start(),
+                    t.token.len(),
+                    t.clipped_start,
+                    t.clipped_end,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sync_points_are_boundaries_of_the_full_lex() {
+        let dfa = StreamingDfa::new();
+        let mut runner = TestRunner::new(Config {
+            cases: 200,
+            failure_persistence: None,
+            ..Config::default()
+        });
+        runner
+            .run(
+                &(arb_source_gen_config(), proptest::num::u64::ANY),
+                |(config, seed)| {
+                    let src = gen_source(&mut StdRng::seed_from_u64(seed), &config);
+                    let full = lex_on_test_cpu(&src).unwrap();
+                    let starts = lex_on_test_cpu_all(&src)
+                        .unwrap()
+                        .iter()
+                        .map(|t| t.start())
+                        .collect::<Vec<_>>();
+                    for from in (0..src.len()).step_by(7) {
+                        let Some(p) = sync_point(&dfa, src.as_bytes(), from, src.len()) else {
+                            continue;
+                        };
+                        if starts.binary_search(&p).is_err() {
+                            return Err(TestCaseError::fail(format!(
+                                "sync point {p} from {from} is not a boundary of {src:?}"
+                            )));
+                        }
+                        let tail = lex_on_test_cpu(&src[p..]).unwrap();
+                        let expected = full.iter().filter(|t| t.start() >= p);
+                        if !tail
+                            .iter()
+                            .map(|t| (t.kind, t.start() + p, t.len()))
+                            .eq(expected.map(|t| (t.kind, t.start(), t.len())))
+                        {
+                            return Err(TestCaseError::fail(format!(
+                                "lex from sync point {p} differs in {src:?}"
+                            )));
+                        }
+                    }
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn window_start_falls_back_to_a_top_level_line_inside_long_comments() {
+        let dfa = StreamingDfa::new();
+        let lets: String = (0..100).map(|i| format!("let a{i} = {i};\n")).collect();
+        let src = format!(
+            "{lets}fn f() {{}}\n/*\n{}*/\nlet b = 2;\n",
+            "x x x\n".repeat(700)
+        );
+        let comment = src.find("/*").unwrap();
+        let inside = comment + 3000;
+
+        // No slop byte resynchronizes inside the comment, but the whole
+        // prefix is within `max_backward`, so the walk from the input start
+        // finds the comment token.
+        let options = LexRangeOptions {
+            backward_slop: 64,
+            ..LexRangeOptions::default()
+        };
+        assert_eq!(window_start(&dfa, &src, inside, &options), (comment, false));
+
+        // With a reach that ends inside the comment, the window starts at the
+        // nearest column-0 line, which cannot be proven to lie outside one.
+        let options = LexRangeOptions {
+            backward_slop: 64,
+            max_backward: 2000,
+            ..options
+        };
+        let line = src[..inside - 64].rfind('\n').unwrap() + 1;
+        assert_eq!(window_start(&dfa, &src, inside, &options), (line, true));
+        let out = lex_range_on_test_cpu(&src, inside..inside + 10, &options);
+        assert!(out.approximate);
+        // The comment text lexes as identifiers from there.
+        assert!(!out.tokens.is_empty());
+
+        // Past the comment the lexer resynchronizes within the slop, and the
+        // window starts at the token the range starts in.
+        let after = src.find("let b").unwrap() + 2;
+        assert_eq!(
+            window_start(&dfa, &src, after, &options),
+            (src.find("let b").unwrap(), false)
+        );
+    }
+
+    #[test]
+    fn range_lex_matches_the_full_lex_filtered_to_the_range() {
+        let mut rng = StdRng::seed_from_u64(887);
+        let options = LexRangeOptions {
+            backward_slop: 32,
+            max_backward: 4096,
+            forward_slop: 8,
+            max_forward: 4096,
+        };
+        for profile in ["default", "comment_heavy", "operator_heavy", "bracket_deep"] {
+            let mut config = crate::dev::generator::SourceGenConfig::from_profile(profile).unwrap();
+            config.target_len = 2048;
+            for _ in 0..8 {
+                let src = gen_source(&mut rng, &config);
+                let full = lex_on_test_cpu(&src).unwrap();
+                for _ in 0..32 {
+                    let a = src.floor_char_boundary(rng.random_range(0..=src.len()));
+                    let b = src.floor_char_boundary(rng.random_range(a..=src.len()));
+                    let range = a..b;
+                    let out = lex_range_on_test_cpu(&src, range.clone(), &options);
+                    if out.approximate {
+                        continue;
+                    }
+                    assert_eq!(
+                        keys(&out.tokens),
+                        keys(&tokens_in_range(&full, 0, &range)),
+                        "{profile} range {range:?} window {}",
+                        out.window
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ranges_after_multi_byte_characters_match_the_full_lex() {
+        let src = "// ok 😀\nlet s = \"é😀€\"; let t = s;\n/* 日本 */ f(t);\n";
+        let full = lex_on_test_cpu(src).unwrap();
+        let options = LexRangeOptions {
+            backward_slop: 3,
+            max_backward: 4096,
+            forward_slop: 1,
+            max_forward: 4096,
+        };
+        for (at, ch) in src.char_indices().filter(|(_, ch)| !ch.is_ascii()) {
+            for range in [at + ch.len_utf8()..src.len(), at..at + ch.len_utf8()] {
+                let out = lex_range_on_test_cpu(src, range.clone(), &options);
+                assert!(!out.approximate, "range {range:?}");
+                assert!(src.is_char_boundary(out.window.range().start));
+                assert_eq!(
+                    keys(&out.tokens),
+                    keys(&tokens_in_range(&full, 0, &range)),
+                    "range {range:?} window {}",
+                    out.window
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn clipped_flags_mark_tokens_crossing_the_range() {
+        let src = "let name = other;";
+        let kept = lex_on_test_cpu(src).unwrap();
+        let got = tokens_in_range(&kept, 0, &(6..13));
+        let flags = got
+            .iter()
+            .map(|t| (&src[t.token.span.range()], t.clipped_start, t.clipped_end))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            flags,
+            [
+                ("name", true, false),
+                ("=", false, false),
+                ("other", false, true)
+            ]
+        );
+    }
+
+    #[test]
+    fn emitting_edges_enter_the_state_the_start_state_enters() {
+        // What lets a lex resume from the start state at a sync point: the
+        // state after an emitting edge never depends on the token it ends.
+        let dfa = StreamingDfa::new();
+        for (state, row) in dfa.next.iter().enumerate() {
+            for (byte, next) in row.iter().enumerate() {
+                if next.emit {
+                    assert_ne!(state, dfa.start as usize, "start emits on {byte:#04x}");
+                    assert_eq!(
+                        next.state, dfa.next[dfa.start as usize][byte].state,
+                        "state {state} on {byte:#04x}"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,282 @@
+//! Safe lex start points before an arbitrary byte offset.
+//!
+//! Range lexing and the paranoia relex both cut a source at an offset they
+//! did not choose and have to restart the DFA at a byte where a full lex
+//! starts a token from the start state. [`find_safe_start`] finds one in
+//! two steps:
+//!
+//! 1. **Anchor.** The input start (after a BOM or shebang prefix) when the
+//!    budget reaches back that far, else the first [`sync_point`] at or after
+//!    `target - budget`. A sync point holds for every DFA state the scan could
+//!    have started in, so it is sound even when `target - budget` falls
+//!    inside a multi-byte character, string, or comment.
+//! 2. **Walk.** From the anchor a single DFA run follows the true lex up to
+//!    the target; the last token boundary it passes that is also a UTF-8
+//!    character boundary is the safe start.
+//!
+//! Byte-level heuristics such as the nearest line start are never returned:
+//! a caller that gets `None` widens the budget or falls back knowingly.
+//!
+//! [`find_safe_start`]: crate::lexer::resync::find_safe_start
+//! [`sync_point`]: crate::lexer::range::sync_point
+
+use crate::lexer::{
+    range::sync_point,
+    tables::{dfa::StreamingDfa, tokens::INVALID_TOKEN},
+    test_cpu::prefix_token,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Where a lex may restart for a given target offset.
+pub struct SafeStart {
+    /// Token boundary at or before the target from which a lex of the
+    /// suffix reproduces the full lex.
+    pub offset: usize,
+    /// Anchor the walk to `offset` started from: the input start, or a
+    /// [`sync_point`] within the budget.
+    pub anchor: usize,
+}
+
+/// [`find_safe_start_with`] over the lexer DFA.
+pub fn find_safe_start(src: &[u8], target: usize, budget: usize) -> Option<SafeStart> {
+    find_safe_start_with(&StreamingDfa::new(), src, target, budget)
+}
+
+/// Returns the latest offset at or before `target` (clamped to the input
+/// length) at which a lex of `src[offset..]` tokenizes exactly like the full
+/// lex of `src` from there on, looking at most `budget` bytes back.
+///
+/// `None` when no anchor lies within `budget` bytes before `target`, e.g.
+/// deep inside a block comment longer than the budget, or when the walk from
+/// the anchor finds no character-boundary token start before `target`.
+pub fn find_safe_start_with(
+    dfa: &StreamingDfa,
+    src: &[u8],
+    target: usize,
+    budget: usize,
+) -> Option<SafeStart> {
+    let target = target.min(src.len());
+    let prefix = prefix_token(src).map_or(0, |token| token.len());
+    if target <= prefix {
+        return Some(SafeStart {
+            offset: 0,
+            anchor: 0,
+        });
+    }
+    let floor = target - budget.min(target);
+    let anchor = if floor <= prefix {
+        prefix
+    } else {
+        sync_point(dfa, src, floor, target)?
+    };
+
+    let safe = |at: usize| {
+        at == 0
+            || ((at == src.len() || (src[at] & 0xC0) != 0x80) && prefix_token(&src[at..]).is_none())
+    };
+    // The input start is always safe; a sync point is a token start.
+    let mut best = if anchor == prefix { Some(0) } else { None };
+    if safe(anchor) {
+        best = Some(anchor);
+    }
+    let mut state = dfa.start as usize;
+    for (at, &byte) in src.iter().enumerate().take(target + 1).skip(anchor) {
+        let next = dfa.next[state][byte as usize];
+        if next.state == dfa.reject || (next.emit && dfa.token_map[state] == INVALID_TOKEN) {
+            break;
+        }
+        if next.emit && safe(at) {
+            best = Some(at);
+        }
+        state = next.state as usize;
+    }
+    // The end of the input ends the last token.
+    if target == src.len() && target > anchor && dfa.token_map[state] != INVALID_TOKEN {
+        best = Some(target);
+    }
+    best.map(|offset| SafeStart { offset, anchor })
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::test_runner::{Config, TestCaseError, TestRunner};
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{
+        dev::generator::{arb_source_gen_config, gen_source},
+        lexer::{
+            tables::tokens::TokenKind,
+            test_cpu::{lex_on_test_cpu, lex_on_test_cpu_all},
+        },
+    };
+
+    /// Why lexing `src` from `start` differs from the tail of its full lex,
+    /// if it does.
+    fn suffix_mismatch(src: &str, start: usize) -> Option<String> {
+        if !src.is_char_boundary(start) {
+            return Some(format!("{start} is inside a character"));
+        }
+        let full = lex_on_test_cpu(src).expect("full lex");
+        let tail = match lex_on_test_cpu(&src[start..]) {
+            Ok(tail) => tail,
+            Err(err) => return Some(format!("lex from {start} fails: {err}")),
+        };
+        let expected = full.iter().filter(|t| t.start() >= start);
+        let same = tail
+            .iter()
+            .map(|t| (t.kind, t.start() + start, t.len()))
+            .eq(expected.map(|t| (t.kind, t.start(), t.len())));
+        (!same).then(|| format!("lex from {start} differs from the full lex"))
+    }
+
+    fn assert_safe(src: &str, target: usize, budget: usize) -> Option<SafeStart> {
+        let found = find_safe_start(src.as_bytes(), target, budget);
+        if let Some(safe) = found {
+            assert!(safe.offset <= target, "{safe:?} past {target}");
+            assert!(
+                safe.anchor == 0 || safe.anchor + budget >= target,
+                "{safe:?} beyond budget {budget}"
+            );
+            if let Some(why) = suffix_mismatch(src, safe.offset) {
+                panic!("{why}: target {target} budget {budget} in {src:?}");
+            }
+        }
+        found
+    }
+
+    #[test]
+    fn emoji_right_before_the_target_is_never_split() {
+        let src = "let face = \"ok 😀\";\nlet next = face;\n";
+        let emoji = src.find('😀').unwrap();
+        let string = src.find('"').unwrap();
+        for target in emoji..=emoji + 4 {
+            for budget in 0..=target {
+                assert_safe(src, target, budget);
+            }
+            let safe = find_safe_start(src.as_bytes(), target, target).unwrap();
+            assert_eq!(safe.offset, string, "target {target}");
+        }
+
+        // The `;` after the closing quote starts a token, but a budget that
+        // begins inside the emoji cannot show it: a run that started inside a
+        // block comment would still be in it there.
+        let semi = emoji + 5;
+        assert_eq!(&src[semi..=semi], ";");
+        assert_eq!(find_safe_start(src.as_bytes(), semi, 4), None);
+        assert_eq!(
+            find_safe_start(src.as_bytes(), semi, semi).map(|s| s.offset),
+            Some(semi)
+        );
+    }
+
+    #[test]
+    fn multi_byte_strings_resolve_to_the_string_start() {
+        let src = "fn f() {\n    let s = \"αβγ € 𝄞 日本\";\n    g(s);\n}\n";
+        let open = src.find('"').unwrap();
+        let close = src.rfind('"').unwrap();
+        for target in open..=close {
+            let safe = assert_safe(src, target, 64).expect("a safe start within 64 bytes");
+            assert_eq!(safe.offset, open, "target {target}");
+            for budget in 0..8 {
+                assert_safe(src, target, budget);
+            }
+        }
+    }
+
+    #[test]
+    fn giant_comments_exhaust_the_budget() {
+        let body = "x ".repeat(1 << 20);
+        let src = format!("let a = 1;\n/* {body} */\nlet b = 2;\n");
+        let comment = src.find("/*").unwrap();
+        let inside = comment + body.len() / 2;
+
+        assert_eq!(find_safe_start(src.as_bytes(), inside, 64 * 1024), None);
+        assert_eq!(
+            find_safe_start(src.as_bytes(), inside, inside),
+            Some(SafeStart {
+                offset: comment,
+                anchor: 0
+            })
+        );
+
+        // A sync point just past the comment is within a small budget.
+        let b = src.find("let b").unwrap();
+        let safe = assert_safe(&src, b + 1, 16).unwrap();
+        assert_eq!(safe.offset, b);
+        assert!(safe.anchor > comment);
+    }
+
+    #[test]
+    fn shebang_and_bom_prefixes_are_not_split() {
+        for src in ["#!/usr/bin/env lani\nlet a = 1;\n", "\u{feff}let a = 1;\n"] {
+            for target in 0..=src.len() {
+                assert_safe(src, target, target);
+                assert_safe(src, target, 3);
+            }
+        }
+    }
+
+    /// `src` with multi-byte characters spliced into the bodies of some
+    /// string and comment tokens, which the generator only fills with ASCII.
+    fn with_multi_byte_bodies(src: &str, rng: &mut StdRng) -> String {
+        const CHARS: [&str; 5] = ["é", "€", "😀", "αβ", "𝄞日"];
+        let mut out = String::with_capacity(src.len() * 2);
+        let mut at = 0;
+        for token in lex_on_test_cpu_all(src).expect("generated source lexes") {
+            let open = match token.kind {
+                TokenKind::String => 1,
+                TokenKind::LineComment | TokenKind::BlockComment => 2,
+                _ => continue,
+            };
+            if !rng.random_bool(0.5) {
+                continue;
+            }
+            out.push_str(&src[at..token.start() + open]);
+            out.push_str(CHARS[rng.random_range(0..CHARS.len())]);
+            at = token.start() + open;
+        }
+        out.push_str(&src[at..]);
+        out
+    }
+
+    #[test]
+    fn lexing_from_a_safe_start_reproduces_the_full_lex() {
+        let mut runner = TestRunner::new(Config {
+            cases: 128,
+            failure_persistence: None,
+            ..Config::default()
+        });
+        runner
+            .run(
+                &(arb_source_gen_config(), proptest::num::u64::ANY),
+                |(config, seed)| {
+                    let mut rng = StdRng::seed_from_u64(seed);
+                    let src = with_multi_byte_bodies(&gen_source(&mut rng, &config), &mut rng);
+                    for _ in 0..16 {
+                        let target = rng.random_range(0..=src.len());
+                        let budget = rng.random_range(0..=src.len());
+                        let found = find_safe_start(src.as_bytes(), target, budget);
+                        if budget >= target && found.is_none() {
+                            return Err(TestCaseError::fail(format!(
+                                "no safe start for {target} with the whole prefix in budget"
+                            )));
+                        }
+                        let Some(safe) = found else {
+                            continue;
+                        };
+                        if safe.offset > target {
+                            return Err(TestCaseError::fail(format!("{safe:?} past {target}")));
+                        }
+                        if let Some(why) = suffix_mismatch(&src, safe.offset) {
+                            return Err(TestCaseError::fail(format!(
+                                "{why}: target {target} budget {budget} in {src:?}"
+                            )));
+                        }
+                    }
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+}
@@ -0,0 +1,381 @@
+//! Source round-trip checks over lexer token streams.
+//!
+//! The all-boundary stream must tile the source: concatenating its lexemes in
+//! order gives back the input byte for byte. Kind-level comparison against the
+//! test CPU oracle misses offset bugs in compaction or token building that both
+//! sides share; [`verify_partition`] catches them directly from the spans.
+//! [`verify_recovered_partition`] holds recovering lexes of arbitrary bytes
+//! to the same rule, with error spans filling in for tokens.
+//! [`verify_kept_relex`] checks the kept stream the other way round, by
+//! re-lexing its lexemes separated by single spaces.
+//!
+//! [`verify_partition`]: crate::lexer::roundtrip::verify_partition
+//! [`verify_recovered_partition`]: crate::lexer::roundtrip::verify_recovered_partition
+//! [`verify_kept_relex`]: crate::lexer::roundtrip::verify_kept_relex
+
+use std::fmt;
+
+use crate::{
+    lexer::{
+        tables::tokens::TokenKind,
+        test_cpu::{RecoveredLex, lex_on_test_cpu},
+        types::Token,
+        utf8::LexemePolicy,
+    },
+    span::Span,
+};
+
+/// First place where an all-boundary stream fails to tile its source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionError {
+    /// Bytes `start..end` are covered by no token; `index` is the token after
+    /// the gap, or the token count for a gap at the end of the source.
+    Gap {
+        index: usize,
+        start: usize,
+        end: usize,
+    },
+    /// Bytes `start..end` are covered by token `index` and an earlier token.
+    Overlap {
+        index: usize,
+        start: usize,
+        end: usize,
+    },
+    /// Token `index` covers bytes `start..end` past the end of the source.
+    PastEnd {
+        index: usize,
+        start: usize,
+        end: usize,
+    },
+}
+
+impl PartitionError {
+    /// Byte range the error reports.
+    pub fn range(&self) -> std::ops::Range<usize> {
+        match *self {
+            Self::Gap { start, end, .. }
+            | Self::Overlap { start, end, .. }
+            | Self::PastEnd { start, end, .. } => start..end,
+        }
+    }
+}
+
+impl fmt::Display for PartitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Gap { index, start, end } => {
+                write!(
+                    f,
+                    "bytes {start}..{end} before token #{index} are not covered"
+                )
+            }
+            Self::Overlap { index, start, end } => {
+                write!(f, "token #{index} re-covers bytes {start}..{end}")
+            }
+            Self::PastEnd { index, start, end } => {
+                write!(
+                    f,
+                    "token #{index} covers bytes {start}..{end} past the source end"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PartitionError {}
+
+/// Kept-stream token whose kind changed when the stream was re-lexed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelexError {
+    /// The test CPU oracle rejected the respaced source.
+    Lex(String),
+    /// Kept token `index` re-lexed as `actual`; `None` is a missing token.
+    Kind {
+        index: usize,
+        expected: Option<TokenKind>,
+        actual: Option<TokenKind>,
+    },
+}
+
+impl fmt::Display for RelexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lex(err) => write!(f, "respaced kept stream does not lex: {err}"),
+            Self::Kind {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "kept token #{index} re-lexed as {actual:?}, expected {expected:?}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RelexError {}
+
+/// Concatenates the lexeme bytes of `all_tokens` in order.
+///
+/// For the all-boundary stream of `src` this is `src` itself. Ranges past the
+/// end of `src` are clipped rather than panicking.
+pub fn detokenize_all(src: &str, all_tokens: &[Token]) -> Vec<u8> {
+    let bytes = src.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    for token in all_tokens {
+        let start = token.start().min(bytes.len());
+        let end = token.end().min(bytes.len());
+        out.extend_from_slice(&bytes[start..end.max(start)]);
+    }
+    out
+}
+
+/// Checks that `all_tokens` cover `src` exactly once, in order, with no gap
+/// or overlap, and reports the first byte range that breaks this.
+pub fn verify_partition(src: &str, all_tokens: &[Token]) -> Result<(), PartitionError> {
+    verify_tiling(src.len(), all_tokens.iter().map(|token| token.span))
+}
+
+/// [`verify_partition`] for
+/// [`lex_on_test_cpu_recovering`](crate::lexer::test_cpu::lex_on_test_cpu_recovering) output: its tokens
+/// and error spans, merged in order, must tile `input`. Indices in the error
+/// count both.
+pub fn verify_recovered_partition(
+    input: &[u8],
+    recovered: &RecoveredLex,
+) -> Result<(), PartitionError> {
+    let mut tokens = recovered
+        .all_tokens
+        .iter()
+        .map(|token| token.span)
+        .peekable();
+    let mut errors = recovered.errors.iter().copied().peekable();
+    let merged = std::iter::from_fn(|| match (tokens.peek(), errors.peek()) {
+        (Some(token), Some(error)) if error.start < token.start => errors.next(),
+        (Some(_), _) => tokens.next(),
+        (None, _) => errors.next(),
+    });
+    verify_tiling(input.len(), merged)
+}
+
+fn verify_tiling(len: usize, spans: impl Iterator<Item = Span>) -> Result<(), PartitionError> {
+    let mut covered = 0;
+    let mut count = 0;
+    for (index, span) in spans.enumerate() {
+        let (start, end) = (span.start as usize, span.end as usize);
+        if start > covered {
+            return Err(PartitionError::Gap {
+                index,
+                start: covered,
+                end: start,
+            });
+        }
+        if start < covered {
+            return Err(PartitionError::Overlap {
+                index,
+                start,
+                end: end.min(covered),
+            });
+        }
+        if end > len {
+            return Err(PartitionError::PastEnd {
+                index,
+                start: start.max(len),
+                end,
+            });
+        }
+        covered = end;
+        count = index + 1;
+    }
+    if covered < len {
+        return Err(PartitionError::Gap {
+            index: count,
+            start: covered,
+            end: len,
+        });
+    }
+    Ok(())
+}
+
+/// Joins the lexemes of the kept tokens with single spaces.
+///
+/// A [`TokenKind::DotDotEqual`] stays attached to the `=` after it, since the
+/// lexer only retags `..` that way when the two are adjacent.
+pub fn respace_kept(src: &str, kept: &[Token]) -> String {
+    let mut out = String::with_capacity(src.len());
+    for (i, token) in kept.iter().enumerate() {
+        if i > 0 && kept[i - 1].kind != TokenKind::DotDotEqual {
+            out.push(' ');
+        }
+        if let Ok(text) = token.lexeme(src, LexemePolicy::Lossy) {
+            out.push_str(&text);
+        }
+    }
+    out
+}
+
+/// Re-lexes [`respace_kept`] with the test CPU oracle and checks that it gives
+/// back the kind sequence of `kept`.
+pub fn verify_kept_relex(src: &str, kept: &[Token]) -> Result<(), RelexError> {
+    let relexed = lex_on_test_cpu(&respace_kept(src, kept)).map_err(RelexError::Lex)?;
+    for index in 0..kept.len().max(relexed.len()) {
+        let expected = kept.get(index).map(|token| token.kind);
+        let actual = relexed.get(index).map(|token| token.kind);
+        if expected != actual {
+            return Err(RelexError::Kind {
+                index,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::test_runner::{Config, TestCaseError, TestRunner};
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{
+        dev::generator::{SourceGenConfig, arb_source_gen_config, gen_source},
+        lexer::test_cpu::lex_on_test_cpu_all,
+    };
+
+    fn tok(start: usize, len: usize) -> Token {
+        Token::new(TokenKind::Ident, start, len)
+    }
+
+    #[test]
+    fn partition_errors_report_the_first_bad_range() {
+        let src = "abcdef";
+        assert_eq!(verify_partition(src, &[tok(0, 2), tok(2, 4)]), Ok(()));
+        assert_eq!(verify_partition("", &[]), Ok(()));
+        assert_eq!(
+            verify_partition(src, &[tok(0, 2), tok(3, 3)]),
+            Err(PartitionError::Gap {
+                index: 1,
+                start: 2,
+                end: 3
+            })
+        );
+        assert_eq!(
+            verify_partition(src, &[tok(0, 3), tok(1, 5)]),
+            Err(PartitionError::Overlap {
+                index: 1,
+                start: 1,
+                end: 3
+            })
+        );
+        assert_eq!(
+            verify_partition(src, &[tok(0, 4)]),
+            Err(PartitionError::Gap {
+                index: 1,
+                start: 4,
+                end: 6
+            })
+        );
+        assert_eq!(
+            verify_partition(src, &[tok(0, 6), tok(6, 2)]),
+            Err(PartitionError::PastEnd {
+                index: 1,
+                start: 6,
+                end: 8
+            })
+        );
+        assert_eq!(detokenize_all(src, &[tok(4, 9), tok(0, 2)]), b"efab");
+    }
+
+    #[test]
+    fn recovered_partitions_count_error_spans() {
+        let input = b"abcdef";
+        let recovered = |errors: &[Span]| RecoveredLex {
+            all_tokens: vec![tok(0, 2), tok(4, 2)],
+            errors: errors.to_vec(),
+            dfa_steps: 6,
+        };
+        assert_eq!(
+            verify_recovered_partition(input, &recovered(&[Span::new(2, 4)])),
+            Ok(())
+        );
+        assert_eq!(
+            verify_recovered_partition(input, &recovered(&[Span::new(2, 3)])),
+            Err(PartitionError::Gap {
+                index: 2,
+                start: 3,
+                end: 4
+            })
+        );
+        assert_eq!(
+            verify_recovered_partition(input, &recovered(&[Span::new(2, 5)])),
+            Err(PartitionError::Overlap {
+                index: 2,
+                start: 4,
+                end: 5
+            })
+        );
+    }
+
+    #[test]
+    fn respacing_keeps_inclusive_ranges_attached() {
+        let src = "for i in 0..=n {x}";
+        let kept = lex_on_test_cpu(src).unwrap();
+        assert_eq!(respace_kept(src, &kept), "for i in 0 ..= n { x }");
+        assert_eq!(verify_kept_relex(src, &kept), Ok(()));
+
+        let mut retagged = kept.clone();
+        retagged[1].kind = TokenKind::Int;
+        assert_eq!(
+            verify_kept_relex(src, &retagged),
+            Err(RelexError::Kind {
+                index: 1,
+                expected: Some(TokenKind::Int),
+                actual: Some(TokenKind::Ident)
+            })
+        );
+        assert_eq!(respace_kept(src, &kept[..2]), "for i");
+    }
+
+    #[test]
+    fn generated_sources_round_trip() {
+        let mut runner = TestRunner::new(Config {
+            cases: 500,
+            failure_persistence: None,
+            ..Config::default()
+        });
+        runner
+            .run(
+                &(arb_source_gen_config(), proptest::num::u64::ANY),
+                |(config, seed)| {
+                    let src = gen_source(&mut StdRng::seed_from_u64(seed), &config);
+                    let fail = |err: String| TestCaseError::fail(format!("{err}\nsource: {src:?}"));
+                    let all = lex_on_test_cpu_all(&src).map_err(fail)?;
+                    verify_partition(&src, &all).map_err(|err| fail(err.to_string()))?;
+                    if detokenize_all(&src, &all) != src.as_bytes() {
+                        return Err(fail("detokenized bytes differ".into()));
+                    }
+                    let kept = lex_on_test_cpu(&src).map_err(fail)?;
+                    verify_kept_relex(&src, &kept).map_err(|err| fail(err.to_string()))
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn long_token_presets_round_trip() {
+        let mut rng = StdRng::seed_from_u64(910);
+        for profile in ["long_comment", "long_string", "long_alternating"] {
+            let config = SourceGenConfig {
+                target_len: 64 * 1024,
+                ..SourceGenConfig::from_profile(profile).unwrap()
+            };
+            let src = gen_source(&mut rng, &config);
+            let all = lex_on_test_cpu_all(&src).unwrap();
+            assert_eq!(verify_partition(&src, &all), Ok(()), "{profile}");
+            assert_eq!(detokenize_all(&src, &all), src.as_bytes(), "{profile}");
+            let kept = lex_on_test_cpu(&src).unwrap();
+            assert_eq!(verify_kept_relex(&src, &kept), Ok(()), "{profile}");
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Shebang (`#!...`) lines at the start of a source file.
+//!
+//! The DFA is position-independent, so it cannot tell offset 0 from any other
+//! `#`. The driver instead rewrites a leading `#!` to `//` before upload: the
+//! line then lexes as a skipped line comment of the same length, and every
+//! token offset, line map, and source map stays unchanged. A `#` anywhere else,
+//! or at offset 0 without a following `!`, still rejects.
+
+use crate::lexer::{tables::tokens::TokenKind, types::Token};
+
+/// Returns the byte length of a shebang line at the very start of `bytes`,
+/// excluding its newline, or `None` when `bytes` does not start with `#!`.
+pub fn shebang_len(bytes: &[u8]) -> Option<usize> {
+    if !bytes.starts_with(b"#!") {
+        return None;
+    }
+    Some(
+        bytes
+            .iter()
+            .position(|&b| b == b'\n')
+            .unwrap_or(bytes.len()),
+    )
+}
+
+/// Rewrites a leading `#!` to `//` so the DFA lexes the shebang line as a
+/// line comment. Returns whether `bytes` started with a shebang.
+pub fn mask_shebang(bytes: &mut [u8]) -> bool {
+    if shebang_len(bytes).is_none() {
+        return false;
+    }
+    bytes[..2].copy_from_slice(b"//");
+    true
+}
+
+/// Restores the [`TokenKind::Shebang`] kind of a masked shebang line in an
+/// all-boundary token stream, where it was lexed as a line comment.
+pub fn retag_shebang(source: &[u8], tokens: &mut [Token]) {
+    let Some(len) = shebang_len(source) else {
+        return;
+    };
+    if let Some(first) = tokens.first_mut()
+        && first.start() == 0
+        && first.len() == len
+        && first.kind == TokenKind::LineComment
+    {
+        first.kind = TokenKind::Shebang;
+        first.raw_kind = TokenKind::Shebang;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shebang_requires_hash_bang_at_offset_zero() {
+        assert_eq!(
+            shebang_len(b"#!/usr/bin/env lanius\nfn main() {}"),
+            Some(21)
+        );
+        assert_eq!(shebang_len(b"#!"), Some(2));
+        assert_eq!(shebang_len(b"#!\r\n"), Some(3));
+        assert_eq!(shebang_len(b"#"), None);
+        assert_eq!(shebang_len(b"#x"), None);
+        assert_eq!(shebang_len(b" #!x"), None);
+        assert_eq!(shebang_len(b""), None);
+    }
+
+    #[test]
+    fn masking_preserves_length_and_only_touches_the_marker() {
+        let mut bytes = b"#!/bin/lanius #!\nx".to_vec();
+        assert!(mask_shebang(&mut bytes));
+        assert_eq!(bytes, b"///bin/lanius #!\nx");
+
+        let mut plain = b"x #!".to_vec();
+        assert!(!mask_shebang(&mut plain));
+        assert_eq!(plain, b"x #!");
+    }
+}
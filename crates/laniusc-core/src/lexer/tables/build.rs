@@ -0,0 +1,317 @@
+// src/lexer/tables/build.rs
+use std::time::Instant;
+
+use hashbrown::{HashMap, HashSet};
+use rayon::prelude::*;
+
+use super::{
+    Tables,
+    dfa::{N_STATES, Next, StreamingDfa},
+    io::write_tables_bin,
+};
+use crate::{logging::LEXER_TABLES, tables::FormatVersion};
+
+// Q -> (Q, emit)
+#[derive(Clone)]
+struct UFunc {
+    trans: Vec<Next>, // len = #states
+}
+
+#[inline]
+fn compose_trans(a: &[Next], b: &[Next]) -> Vec<Next> {
+    // Keep the LAST edge's emit flag.
+    a.iter()
+        .map(|&Next { state: mid, .. }| b[mid as usize])
+        .collect()
+}
+
+fn closure_fixpoint_parallel(funcs: &mut Vec<UFunc>, map: &mut HashMap<Vec<Next>, u32>) {
+    let mut round = 0usize;
+    let mut new_start = 0usize; // treat current set as "new" in first round
+
+    loop {
+        let cur_len = funcs.len();
+        let new_idxs: Vec<usize> = (new_start..cur_len).collect();
+        if new_idxs.is_empty() {
+            break;
+        }
+        let all_idxs: Vec<usize> = (0..cur_len).collect();
+
+        // new x all
+        let set1: HashSet<Vec<Next>> = new_idxs
+            .par_iter()
+            .fold(HashSet::new, |mut local, &i| {
+                let ai = &funcs[i].trans;
+                for &j in &all_idxs {
+                    let bj = &funcs[j].trans;
+                    let trans = compose_trans(ai, bj);
+                    if !map.contains_key(&trans) {
+                        local.insert(trans);
+                    }
+                }
+                local
+            })
+            .reduce(HashSet::new, |mut a, b| {
+                a.extend(b);
+                a
+            });
+
+        // all x new
+        let set2: HashSet<Vec<Next>> = all_idxs
+            .par_iter()
+            .fold(HashSet::new, |mut local, &i| {
+                let ai = &funcs[i].trans;
+                for &j in &new_idxs {
+                    let bj = &funcs[j].trans;
+                    let trans = compose_trans(ai, bj);
+                    if !map.contains_key(&trans) {
+                        local.insert(trans);
+                    }
+                }
+                local
+            })
+            .reduce(HashSet::new, |mut a, b| {
+                a.extend(b);
+                a
+            });
+
+        // Insert sequentially to assign stable IDs
+        let mut added = 0usize;
+        for trans in set1.into_iter().chain(set2) {
+            if !map.contains_key(&trans) {
+                let id = funcs.len() as u32;
+                map.insert(trans.clone(), id);
+                funcs.push(UFunc { trans });
+                added += 1;
+            }
+        }
+
+        round += 1;
+        log::debug!(
+            target: LEXER_TABLES,
+            "closure round {round}: size now {}",
+            funcs.len()
+        );
+
+        if added == 0 {
+            break;
+        }
+        new_start = cur_len;
+    }
+}
+
+/// Summary functions of the streaming DFA closed under composition, before
+/// the `m * m` merge table is materialized.
+///
+/// The merge table is the only quadratic part of [`Tables`]; holding the
+/// closure instead lets [`Self::save_bin_streaming`] compute merge rows on
+/// the fly and write them straight to disk.
+pub struct FunctionClosure {
+    funcs: Vec<UFunc>,
+    map: HashMap<Vec<Next>, u32>,
+    char_to_func: [u32; 256],
+    token_of: Vec<u32>,
+}
+
+/// Identity function id; the closure interns it first.
+const IDENTITY: u32 = 0;
+
+impl FunctionClosure {
+    /// Closes the summary functions of every byte value.
+    pub fn full() -> Self {
+        Self::for_bytes(0..=255)
+    }
+
+    /// Closes the summary functions of only the bytes in `alphabet`.
+    ///
+    /// Bytes outside the alphabet map to the constant reject function, so the
+    /// tables are only valid for inputs drawn from `alphabet`.
+    pub fn for_bytes(alphabet: impl IntoIterator<Item = u8>) -> Self {
+        let started = Instant::now();
+        let dfa = StreamingDfa::new();
+        let mut funcs = Vec::new();
+        let mut map = HashMap::new();
+        let mut intern = |trans: Vec<Next>| {
+            *map.entry(trans.clone()).or_insert_with(|| {
+                funcs.push(UFunc { trans });
+                (funcs.len() - 1) as u32
+            })
+        };
+
+        let identity = intern(
+            (0..N_STATES as u16)
+                .map(|state| Next { state, emit: false })
+                .collect(),
+        );
+        debug_assert_eq!(identity, IDENTITY);
+        let reject = intern(vec![
+            Next {
+                state: dfa.reject,
+                emit: false,
+            };
+            N_STATES
+        ]);
+        let mut char_to_func = [reject; 256];
+        for byte in alphabet {
+            char_to_func[byte as usize] =
+                intern((0..N_STATES).map(|s| dfa.next[s][byte as usize]).collect());
+        }
+
+        closure_fixpoint_parallel(&mut funcs, &mut map);
+        let token_of = funcs
+            .iter()
+            .map(|f| dfa.token_map[f.trans[dfa.start as usize].state as usize])
+            .collect();
+        log::info!(
+            target: LEXER_TABLES,
+            "closed {} summary functions in {} ms",
+            funcs.len(),
+            started.elapsed().as_millis()
+        );
+        Self {
+            funcs,
+            map,
+            char_to_func,
+            token_of,
+        }
+    }
+
+    /// Number of summary functions, i.e. the merge table's row length.
+    pub fn m(&self) -> u32 {
+        self.funcs.len() as u32
+    }
+
+    /// Fills `out` with merge rows `first_row..`; `out.len()` must be a
+    /// multiple of [`Self::m`].
+    pub fn fill_merge_rows(&self, first_row: usize, out: &mut [u32]) {
+        let m = self.funcs.len();
+        out.par_chunks_mut(m).enumerate().for_each(|(i, row)| {
+            let at = &self.funcs[first_row + i].trans;
+            for (b, slot) in row.iter_mut().enumerate() {
+                let trans = compose_trans(at, &self.funcs[b].trans);
+                *slot = *self
+                    .map
+                    .get(&trans)
+                    .expect("closure should intern all compositions");
+            }
+        });
+    }
+
+    /// Materializes the full tables, merge table included.
+    pub fn into_tables(self) -> Tables {
+        let m = self.funcs.len();
+        let mut merge = vec![0u32; m * m];
+        self.fill_merge_rows(0, &mut merge);
+        Tables {
+            char_to_func: self.char_to_func,
+            merge,
+            token_of: self.token_of,
+            m: m as u32,
+            identity: IDENTITY,
+        }
+    }
+
+    /// Writes the same bytes as [`save_tables_bin_as`] of
+    /// [`Self::into_tables`], computing merge rows a batch at a time instead
+    /// of holding all `m * m`.
+    ///
+    /// [`save_tables_bin_as`]: super::save_tables_bin_as
+    pub fn save_bin_streaming(
+        &self,
+        path: &std::path::Path,
+        version: FormatVersion,
+    ) -> std::io::Result<()> {
+        write_tables_bin(
+            path,
+            version,
+            self.m(),
+            IDENTITY,
+            &self.char_to_func,
+            &self.token_of,
+            |first_row, out| self.fill_merge_rows(first_row, out),
+        )
+    }
+}
+
+/// Builds the full tables over every byte value.
+pub fn build_tables() -> Tables {
+    FunctionClosure::full().into_tables()
+}
+
+/// Builds tables specialized to the bytes that occur in `input`.
+pub fn build_tables_for_bytes(input: &[u8]) -> Tables {
+    let mut seen = [false; 256];
+    for &byte in input {
+        seen[byte as usize] = true;
+    }
+    FunctionClosure::for_bytes((0..=255u8).filter(|&byte| seen[byte as usize])).into_tables()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tables::{load_tables_bin_bytes, save_tables_bin_as};
+
+    #[test]
+    fn streamed_merge_table_file_matches_the_buffered_one() {
+        let input = b"let x = (y + 1) * 2;";
+        let closure = FunctionClosure::for_bytes(input.iter().copied());
+        let dir = std::env::temp_dir();
+        let tag = format!("{}-{:?}", std::process::id(), std::thread::current().id());
+        let path = |kind: &str, version: FormatVersion| {
+            dir.join(format!("laniusc-merge-{kind}-{tag}-{version:?}.bin"))
+        };
+        let versions = [FormatVersion::V1, FormatVersion::V2];
+        for version in versions {
+            closure
+                .save_bin_streaming(&path("streamed", version), version)
+                .unwrap();
+        }
+        let tables = closure.into_tables();
+        let m = tables.m as usize;
+        assert!(m > 2 && m < 4096, "m = {m}");
+
+        for version in versions {
+            save_tables_bin_as(&path("buffered", version), &tables, version).unwrap();
+            let streamed = std::fs::read(path("streamed", version)).unwrap();
+            let buffered = std::fs::read(path("buffered", version)).unwrap();
+            let _ = std::fs::remove_file(path("streamed", version));
+            let _ = std::fs::remove_file(path("buffered", version));
+
+            assert!(
+                streamed == buffered,
+                "streamed {version:?} file differs from buffered file"
+            );
+            let header = match version {
+                FormatVersion::V1 => 16,
+                // Container header, then four section headers and `m, identity`.
+                FormatVersion::V2 => 16 + 4 * 20 + 8,
+            };
+            assert_eq!(streamed.len(), header + 512 + m * m * 2 + m * 2);
+            let loaded = load_tables_bin_bytes(&streamed).unwrap();
+            assert_eq!(loaded.merge, tables.merge);
+            assert_eq!(loaded.char_to_func, tables.char_to_func);
+            assert_eq!(loaded.token_of, tables.token_of);
+        }
+    }
+
+    #[test]
+    fn specialized_tables_compose_like_the_dfa() {
+        let input = b"a1 = b;";
+        let tables = build_tables_for_bytes(input);
+        let dfa = StreamingDfa::new();
+        let m = tables.m as usize;
+        let summary = input.iter().fold(tables.identity, |acc, &byte| {
+            tables.merge[acc as usize * m + tables.char_to_func[byte as usize] as usize]
+        });
+        let mut state = dfa.start as usize;
+        for &byte in input {
+            state = dfa.next[state][byte as usize].state as usize;
+        }
+        assert_eq!(tables.token_of[summary as usize], dfa.token_map[state]);
+        assert_eq!(
+            tables.char_to_func[b'@' as usize],
+            tables.char_to_func[b'#' as usize]
+        );
+    }
+}
@@ -0,0 +1,418 @@
+// src/lexer/tables/compact.rs
+// Compact DFA table produced by gen_tables, as tagged sections in the shared
+// table container (see `crate::tables::format`):
+//   magic:    "LXLEXDFA"
+//   LXDFHEAD: u32 n_states
+//   LXDFNEXT: u16 next_emit[256 * n_states]   // (emit<<15 | next_low15)
+//   LXDFTOKN: u16 token_map[n_states]         // INVALID=0xFFFF, else token kind as u16
+// Version 1 stored the same arrays after a fixed header:
+//   magic: 8  bytes  = "LXDFA001"
+//   u32:   n_states
+//   u32:   reserved (0)
+//   u16:   next_emit[256 * n_states]
+//   u16:   token_map[n_states]
+
+use super::{
+    dfa::START,
+    tokens::{INVALID_TOKEN, TokenKind},
+};
+use crate::{
+    lexer::boundary::{PF_EMIT, PF_EOF, boundary_flags, is_kept},
+    tables::{
+        FormatVersion,
+        format::{self, Container, Section, Tag},
+    },
+};
+
+/// The checked-in `tables/lexer_tables.bin` the GPU lexer uploads.
+pub const CHECKED_IN_TABLES: &[u8] = include_bytes!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../tables/lexer_tables.bin"
+));
+
+const MAGIC_V1: &[u8; 8] = b"LXDFA001";
+const MAGIC: Tag = *b"LXLEXDFA";
+const HEAD_TAG: Tag = *b"LXDFHEAD";
+const NEXT_EMIT_TAG: Tag = *b"LXDFNEXT";
+const TOKEN_MAP_TAG: Tag = *b"LXDFTOKN";
+
+#[inline]
+fn take_u32(buf: &mut &[u8]) -> Result<u32, String> {
+    if buf.len() < 4 {
+        return Err("truncated u32".into());
+    }
+    let mut le = [0u8; 4];
+    le.copy_from_slice(&buf[..4]);
+    *buf = &buf[4..];
+    Ok(u32::from_le_bytes(le))
+}
+
+#[inline]
+fn take_u16(buf: &mut &[u8]) -> Result<u16, String> {
+    if buf.len() < 2 {
+        return Err("truncated u16".into());
+    }
+    let mut le = [0u8; 2];
+    le.copy_from_slice(&buf[..2]);
+    *buf = &buf[2..];
+    Ok(u16::from_le_bytes(le))
+}
+
+/// Loads the compact runtime DFA table from either format version.
+///
+/// Returns `(n_states, next_emit_packed_u32, token_map_u32)`. `next_emit` is
+/// packed as two reflected `u16` transitions per `u32`, matching shader input.
+pub fn load_compact_tables_from_bytes(data: &[u8]) -> Result<(usize, Vec<u32>, Vec<u32>), String> {
+    if data.starts_with(MAGIC_V1) {
+        return load_compact_tables_v1(&data[MAGIC_V1.len()..]);
+    }
+    let container = Container::parse(data, &MAGIC, &[HEAD_TAG, NEXT_EMIT_TAG, TOKEN_MAP_TAG])
+        .map_err(|err| format!("compact tables .bin: {err}"))?;
+    let section = |tag: Tag| {
+        container
+            .require(&tag)
+            .map_err(|err| format!("compact tables .bin: {err}"))
+    };
+    let consumed = |tag: Tag, rest: &[u8]| {
+        format::expect_consumed(&tag, rest).map_err(|err| format!("compact tables .bin: {err}"))
+    };
+
+    let mut head = section(HEAD_TAG)?;
+    let n_states = take_u32(&mut head)? as usize;
+    consumed(HEAD_TAG, head)?;
+    let mut next_emit = section(NEXT_EMIT_TAG)?;
+    let next_emit_words = take_next_emit(&mut next_emit, n_states)?;
+    consumed(NEXT_EMIT_TAG, next_emit)?;
+    let mut token_map = section(TOKEN_MAP_TAG)?;
+    let token_map_u32 = take_token_map(&mut token_map, n_states)?;
+    consumed(TOKEN_MAP_TAG, token_map)?;
+
+    Ok((n_states, next_emit_words, token_map_u32))
+}
+
+/// Reads the version-1 layout after its magic.
+fn load_compact_tables_v1(mut data: &[u8]) -> Result<(usize, Vec<u32>, Vec<u32>), String> {
+    if data.len() < 4 + 4 {
+        return Err("compact bin too short".into());
+    }
+
+    let n_states = take_u32(&mut data)? as usize;
+    let _reserved = take_u32(&mut data)?;
+    let next_emit_words = take_next_emit(&mut data, n_states)?;
+    let token_map_u32 = take_token_map(&mut data, n_states)?;
+
+    Ok((n_states, next_emit_words, token_map_u32))
+}
+
+fn take_next_emit(data: &mut &[u8], n_states: usize) -> Result<Vec<u32>, String> {
+    // Read next_emit as u16, then pack 2x u16 per u32 (exactly what GPU buffer expects).
+    let ne_len = 256usize
+        .checked_mul(n_states)
+        .ok_or_else(|| "n_states overflow".to_string())?;
+    if data.len() / 2 < ne_len {
+        return Err("truncated u16".into());
+    }
+    let mut next_emit_words: Vec<u32> = vec![0; ne_len.div_ceil(2)];
+    for i in 0..ne_len {
+        let v = take_u16(data)?;
+        let w = i >> 1;
+        if (i & 1) == 0 {
+            next_emit_words[w] |= v as u32;
+        } else {
+            next_emit_words[w] |= (v as u32) << 16;
+        }
+    }
+    Ok(next_emit_words)
+}
+
+fn take_token_map(data: &mut &[u8], n_states: usize) -> Result<Vec<u32>, String> {
+    let mut token_map_u32 = Vec::with_capacity(n_states.min(data.len() / 2));
+    for state in 0..n_states {
+        let v = take_u16(data)?;
+        if v == 0xFFFF {
+            token_map_u32.push(INVALID_TOKEN);
+        } else {
+            let kind = v as u32;
+            if TokenKind::from_u32(kind).is_none() {
+                return Err(format!(
+                    "invalid token_map entry {kind} for compact DFA state {state}"
+                ));
+            }
+            token_map_u32.push(kind);
+        }
+    }
+    Ok(token_map_u32)
+}
+
+/// Encodes the compact runtime DFA table in the `version` layout.
+///
+/// Takes the same shapes [`load_compact_tables_from_bytes`] returns: two
+/// packed `u16` transitions per `next_emit` word and one token kind (or
+/// [`INVALID_TOKEN`]) per state.
+pub fn encode_compact_tables(
+    n_states: usize,
+    next_emit_words: &[u32],
+    token_map: &[u32],
+    version: FormatVersion,
+) -> Result<Vec<u8>, String> {
+    if next_emit_words.len() != 128 * n_states || token_map.len() != n_states {
+        return Err(format!(
+            "compact tables for {n_states} states need {} next_emit words and {n_states} \
+             token_map entries, got {} and {}",
+            128 * n_states,
+            next_emit_words.len(),
+            token_map.len()
+        ));
+    }
+    let n_states_u32 =
+        u32::try_from(n_states).map_err(|_| "n_states exceeds u32::MAX".to_string())?;
+    let next_emit: Vec<u8> = next_emit_words
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .collect();
+    let mut tokens = Vec::with_capacity(n_states * 2);
+    for &kind in token_map {
+        let v = if kind == INVALID_TOKEN {
+            0xFFFF
+        } else {
+            u16::try_from(kind).map_err(|_| format!("token_map entry {kind} exceeds u16"))?
+        };
+        tokens.extend_from_slice(&v.to_le_bytes());
+    }
+
+    Ok(match version {
+        FormatVersion::V1 => {
+            let mut data = Vec::with_capacity(8 + 4 + 4 + next_emit.len() + tokens.len());
+            data.extend_from_slice(MAGIC_V1);
+            data.extend_from_slice(&n_states_u32.to_le_bytes());
+            data.extend_from_slice(&0u32.to_le_bytes());
+            data.extend_from_slice(&next_emit);
+            data.extend_from_slice(&tokens);
+            data
+        }
+        FormatVersion::V2 => format::encode(
+            &MAGIC,
+            &[
+                Section::critical(HEAD_TAG, &n_states_u32.to_le_bytes()),
+                Section::critical(NEXT_EMIT_TAG, &next_emit),
+                Section::critical(TOKEN_MAP_TAG, &tokens),
+            ],
+        ),
+    })
+}
+
+/// Compact DFA tables in the layout the lexer shaders bind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactTables {
+    pub n_states: usize,
+    /// Two packed `u16` entries per word, indexed by `byte * n_states + state`:
+    /// the next state in the low 15 bits and the emit flag in bit 15.
+    pub next_emit: Vec<u32>,
+    /// Token kind per state, or [`INVALID_TOKEN`].
+    pub token_map: Vec<u32>,
+}
+
+impl CompactTables {
+    /// Loads tables from either format version.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
+        let (n_states, next_emit, token_map) = load_compact_tables_from_bytes(data)?;
+        Ok(Self {
+            n_states,
+            next_emit,
+            token_map,
+        })
+    }
+
+    /// Loads [`CHECKED_IN_TABLES`].
+    pub fn checked_in() -> Self {
+        Self::from_bytes(CHECKED_IN_TABLES).expect("checked-in lexer_tables.bin loads")
+    }
+
+    /// Returns the state after `byte` in `state` and whether the edge emits,
+    /// as `next_state_only` and `dfa_03_apply_block_prefix` decode it.
+    pub fn step(&self, state: u32, byte: u8) -> (u32, bool) {
+        let i = byte as usize * self.n_states + state as usize;
+        let entry = (self.next_emit[i >> 1] >> ((i & 1) * 16)) & 0xFFFF;
+        (entry & 0x7FFF, entry & 0x8000 != 0)
+    }
+
+    fn kind(&self, state: u32) -> Option<TokenKind> {
+        TokenKind::from_u32(self.token_map[state as usize])
+    }
+}
+
+/// Boundary totals of a [`walk`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WalkCounts {
+    /// EMIT and EOF boundaries, one all-boundary token each.
+    pub all: usize,
+    /// Boundaries whose kind [`is_kept`].
+    pub kept: usize,
+}
+
+/// Per-byte result of walking the compact DFA over one source.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WalkOutput {
+    /// DFA state after each byte; what `dfa_03_apply_block_prefix` captures.
+    pub f_final: Vec<u32>,
+    /// `PF_*` boundary flags for each byte.
+    pub flags: Vec<u32>,
+    pub counts: WalkCounts,
+}
+
+/// Walks `tables` over `bytes` from the start state, one byte at a time.
+///
+/// This is the sequential reference for the GPU DFA passes: it sees the bytes
+/// as uploaded, so a leading BOM or shebang must be masked first, and its
+/// counts come before the driver's keyword and range fix-ups.
+pub fn walk(bytes: &[u8], tables: &CompactTables) -> WalkOutput {
+    let mut out = WalkOutput {
+        f_final: Vec::with_capacity(bytes.len()),
+        flags: Vec::with_capacity(bytes.len()),
+        counts: WalkCounts::default(),
+    };
+    let mut state = START.idx() as u32;
+    for (i, &byte) in bytes.iter().enumerate() {
+        let (next, emit) = tables.step(state, byte);
+        let eof_kind = tables.kind(next);
+        let flags = boundary_flags(emit, i + 1 == bytes.len(), eof_kind);
+        for (bit, kind) in [(PF_EMIT, tables.kind(state)), (PF_EOF, eof_kind)] {
+            if flags & bit != 0 {
+                out.counts.all += 1;
+                out.counts.kept += usize::from(is_kept(kind));
+            }
+        }
+        out.f_final.push(next);
+        out.flags.push(flags);
+        state = next;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::{
+        tables::dfa::StreamingDfa,
+        test_cpu::{lex_on_test_cpu, lex_on_test_cpu_all},
+    };
+
+    fn compact_table_with_token_map_entry(entry: u16) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(MAGIC_V1);
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        for _ in 0..256 {
+            data.extend_from_slice(&0u16.to_le_bytes());
+        }
+        data.extend_from_slice(&entry.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn compact_loader_keeps_invalid_sentinel_invalid() {
+        let data = compact_table_with_token_map_entry(0xFFFF);
+
+        let (_, _, token_map) =
+            load_compact_tables_from_bytes(&data).expect("sentinel token map entry");
+
+        assert_eq!(token_map, vec![INVALID_TOKEN]);
+        assert_eq!(TokenKind::from_u32(token_map[0]), None);
+    }
+
+    #[test]
+    fn compact_loader_accepts_valid_token_kind() {
+        let data = compact_table_with_token_map_entry(TokenKind::Ident as u16);
+
+        let (_, _, token_map) =
+            load_compact_tables_from_bytes(&data).expect("valid token map entry");
+
+        assert_eq!(TokenKind::from_u32(token_map[0]), Some(TokenKind::Ident));
+    }
+
+    #[test]
+    fn compact_loader_rejects_invalid_token_kind() {
+        let data = compact_table_with_token_map_entry(0);
+
+        let err = load_compact_tables_from_bytes(&data).expect_err("invalid token map entry");
+
+        assert!(
+            err.contains("invalid token_map entry 0"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn checked_in_v1_tables_round_trip_through_v2() {
+        let v1 = CHECKED_IN_TABLES;
+        let (n_states, next_emit, token_map) = load_compact_tables_from_bytes(v1).unwrap();
+
+        let v2 =
+            encode_compact_tables(n_states, &next_emit, &token_map, FormatVersion::V2).unwrap();
+        assert!(v2.starts_with(&MAGIC));
+        assert_eq!(
+            load_compact_tables_from_bytes(&v2).unwrap(),
+            (n_states, next_emit.clone(), token_map.clone())
+        );
+        let legacy =
+            encode_compact_tables(n_states, &next_emit, &token_map, FormatVersion::V1).unwrap();
+        assert_eq!(legacy, v1);
+    }
+
+    #[test]
+    fn walk_flags_each_boundary_byte() {
+        let out = walk(b"ab c", &CompactTables::checked_in());
+        assert_eq!(out.flags, [0, 0, PF_EMIT, PF_EMIT | PF_EOF]);
+        assert_eq!(out.counts, WalkCounts { all: 3, kept: 2 });
+        assert!(walk(b"", &CompactTables::checked_in()).f_final.is_empty());
+    }
+
+    #[test]
+    fn walk_matches_the_hand_built_dfa_and_the_cpu_oracle() {
+        let src = "fn f(a: i32) -> i32 { return a >> 0b1 + 0x_ff; } // done\n\
+                   let s = \"h\\\"é\"; /* ∀x */ x += 3.25e+1 != 'c' ";
+        let out = walk(src.as_bytes(), &CompactTables::checked_in());
+
+        let dfa = StreamingDfa::new();
+        let mut state = dfa.start;
+        for (i, &byte) in src.as_bytes().iter().enumerate() {
+            let next = dfa.next[state as usize][byte as usize];
+            assert_eq!(out.f_final[i], u32::from(next.state), "byte {i}");
+            assert_eq!(out.flags[i] & PF_EMIT != 0, next.emit, "byte {i}");
+            state = next.state;
+        }
+        assert_eq!(out.counts.all, lex_on_test_cpu_all(src).unwrap().len());
+        assert_eq!(out.counts.kept, lex_on_test_cpu(src).unwrap().len());
+    }
+
+    #[test]
+    fn v2_tables_need_every_section_at_its_exact_length() {
+        let next_emit = vec![0u32; 128];
+        let v2 = encode_compact_tables(1, &next_emit, &[INVALID_TOKEN], FormatVersion::V2).unwrap();
+        let mut short = v2.clone();
+        short.truncate(v2.len() - 2);
+        assert!(load_compact_tables_from_bytes(&short).is_err());
+
+        let missing = format::encode(
+            &MAGIC,
+            &[
+                Section::critical(HEAD_TAG, &1u32.to_le_bytes()),
+                Section::critical(NEXT_EMIT_TAG, &[0; 512]),
+            ],
+        );
+        let err = load_compact_tables_from_bytes(&missing).unwrap_err();
+        assert!(err.contains("missing section LXDFTOKN"), "{err}");
+
+        let long_head = format::encode(
+            &MAGIC,
+            &[
+                Section::critical(HEAD_TAG, &[1, 0, 0, 0, 0, 0, 0, 0]),
+                Section::critical(NEXT_EMIT_TAG, &[0; 512]),
+                Section::critical(TOKEN_MAP_TAG, &[0xFF, 0xFF]),
+            ],
+        );
+        let err = load_compact_tables_from_bytes(&long_head).unwrap_err();
+        assert!(err.contains("LXDFHEAD has the wrong length"), "{err}");
+        assert!(encode_compact_tables(2, &next_emit, &[0, 0], FormatVersion::V2).is_err());
+    }
+}
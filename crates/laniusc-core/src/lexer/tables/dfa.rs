@@ -1,5 +1,6 @@
 // src/lexer/tables/dfa.rs
 use super::tokens::{INVALID_TOKEN, TokenKind};
+pub use crate::lexer::constants::N_STATES;
 
 /// Hand-built lexer DFA state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -63,15 +64,13 @@ pub enum S {
 
     // numeric separators and floats
     IntAfterUnderscore,
+    // `*Start` follows the radix prefix or a `_` separator; both need a digit
     HexStart,
     Hex,
-    HexAfterUnderscore,
     BinStart,
     Bin,
-    BinAfterUnderscore,
     OctStart,
     Oct,
-    OctAfterUnderscore,
 
     FloatDot,  // after seeing digits then '.' (accepting)
     FloatFrac, // fractional digits loop (accepting)
@@ -89,6 +88,9 @@ pub enum S {
     InChar,
     CharEscape,
     CharDone,
+    RawPrefix, // saw a lone `r`; Ident unless a quote follows
+    InRawString,
+    RawStringDone,
 
     // compound ops
     PlusAssignDone,
@@ -101,6 +103,7 @@ pub enum S {
     PipeAssignDone,
     ShlAssignDone,
     ShrAssignDone,
+    TildeAssignDone,
 
     // ++/--
     IncDone,
@@ -116,10 +119,13 @@ impl S {
     pub fn idx(self) -> usize {
         self as usize
     }
+
+    /// Returns the state with table index `idx`.
+    pub fn from_idx(idx: usize) -> Option<S> {
+        ALL_STATES.iter().copied().find(|state| state.idx() == idx)
+    }
 }
 
-/// Number of DFA states expected by Rust tables and generated shader constants.
-pub const N_STATES: usize = 82;
 /// Start state for normal lexing.
 pub const START: S = S::Start;
 /// Reject state for failed transitions.
@@ -172,13 +178,10 @@ const ALL_STATES: &[S] = &[
     S::IntAfterUnderscore,
     S::HexStart,
     S::Hex,
-    S::HexAfterUnderscore,
     S::BinStart,
     S::Bin,
-    S::BinAfterUnderscore,
     S::OctStart,
     S::Oct,
-    S::OctAfterUnderscore,
     S::FloatDot,
     S::FloatFrac,
     S::FloatFracAfterUnderscore,
@@ -193,6 +196,9 @@ const ALL_STATES: &[S] = &[
     S::InChar,
     S::CharEscape,
     S::CharDone,
+    S::RawPrefix,
+    S::InRawString,
+    S::RawStringDone,
     S::PlusAssignDone,
     S::MinusAssignDone,
     S::StarAssignDone,
@@ -203,6 +209,7 @@ const ALL_STATES: &[S] = &[
     S::PipeAssignDone,
     S::ShlAssignDone,
     S::ShrAssignDone,
+    S::TildeAssignDone,
     S::IncDone,
     S::DecDone,
     S::ArrowDone,
@@ -249,7 +256,7 @@ pub struct StreamingDfa {
 }
 
 /// Returns the token kind accepted by a DFA state, when the state is accepting.
-pub(crate) fn token_of_state(s: S) -> Option<TokenKind> {
+pub fn token_of_state(s: S) -> Option<TokenKind> {
     use S::*;
     match s {
         Ident => Some(TokenKind::Ident),
@@ -303,9 +310,6 @@ pub(crate) fn token_of_state(s: S) -> Option<TokenKind> {
         Oct => Some(TokenKind::Int),
         OctStart => Some(TokenKind::Int),
         IntAfterUnderscore => Some(TokenKind::Int),
-        HexAfterUnderscore => Some(TokenKind::Int),
-        BinAfterUnderscore => Some(TokenKind::Int),
-        OctAfterUnderscore => Some(TokenKind::Int),
         FloatDot => Some(TokenKind::Float),
         FloatFrac => Some(TokenKind::Float),
         FloatExp => Some(TokenKind::Float),
@@ -317,6 +321,8 @@ pub(crate) fn token_of_state(s: S) -> Option<TokenKind> {
 
         StringDone => Some(TokenKind::String),
         CharDone => Some(TokenKind::Char),
+        RawPrefix => Some(TokenKind::Ident),
+        RawStringDone => Some(TokenKind::RawString),
 
         PlusAssignDone => Some(TokenKind::PlusAssign),
         MinusAssignDone => Some(TokenKind::MinusAssign),
@@ -328,6 +334,7 @@ pub(crate) fn token_of_state(s: S) -> Option<TokenKind> {
         PipeAssignDone => Some(TokenKind::PipeAssign),
         ShlAssignDone => Some(TokenKind::ShlAssign),
         ShrAssignDone => Some(TokenKind::ShrAssign),
+        TildeAssignDone => Some(TokenKind::TildeAssign),
 
         IncDone => Some(TokenKind::Inc),
         DecDone => Some(TokenKind::Dec),
@@ -539,29 +546,11 @@ impl StreamingDfa {
                 emit: false,
             };
         }
+        // after underscore requires hex digit, exactly as after `0x`
         next[S::Hex.idx()][b'_' as usize] = Next {
-            state: S::HexAfterUnderscore.idx() as u16,
+            state: S::HexStart.idx() as u16,
             emit: false,
         };
-        // after underscore requires hex digit
-        for b in b'0'..=b'9' {
-            next[S::HexAfterUnderscore.idx()][b as usize] = Next {
-                state: S::Hex.idx() as u16,
-                emit: false,
-            };
-        }
-        for b in b'a'..=b'f' {
-            next[S::HexAfterUnderscore.idx()][b as usize] = Next {
-                state: S::Hex.idx() as u16,
-                emit: false,
-            };
-        }
-        for b in b'A'..=b'F' {
-            next[S::HexAfterUnderscore.idx()][b as usize] = Next {
-                state: S::Hex.idx() as u16,
-                emit: false,
-            };
-        }
 
         // Bin
         next[S::BinStart.idx()][b'0' as usize] = Next {
@@ -581,15 +570,7 @@ impl StreamingDfa {
             emit: false,
         };
         next[S::Bin.idx()][b'_' as usize] = Next {
-            state: S::BinAfterUnderscore.idx() as u16,
-            emit: false,
-        };
-        next[S::BinAfterUnderscore.idx()][b'0' as usize] = Next {
-            state: S::Bin.idx() as u16,
-            emit: false,
-        };
-        next[S::BinAfterUnderscore.idx()][b'1' as usize] = Next {
-            state: S::Bin.idx() as u16,
+            state: S::BinStart.idx() as u16,
             emit: false,
         };
 
@@ -605,15 +586,9 @@ impl StreamingDfa {
             };
         }
         next[S::Oct.idx()][b'_' as usize] = Next {
-            state: S::OctAfterUnderscore.idx() as u16,
+            state: S::OctStart.idx() as u16,
             emit: false,
         };
-        for b in b'0'..=b'7' {
-            next[S::OctAfterUnderscore.idx()][b as usize] = Next {
-                state: S::Oct.idx() as u16,
-                emit: false,
-            };
-        }
 
         // Whitespace
         for &b in b" \t\r\n" {
@@ -640,7 +615,11 @@ impl StreamingDfa {
         set(&mut next, S::BlockStar, b"/", S::BlockDone);
         set_all_except(&mut next, S::BlockStar, b"*/", S::BlockComment);
 
-        // Two-char operators
+        // Two-char operators. Every binary operator with an `op=` form gets
+        // one token, `~=` included. `&&=` and `||=` are not operators: they
+        // lex as `AndAnd`/`OrOr` then `Assign`. `!` only pairs with `=`, so
+        // `!!` is two `Not` tokens. `dev::operator_adjacency` snapshots the
+        // full pairwise behaviour.
         set(&mut next, S::MaybeLess, b"=", S::LessEqualDone);
         set(&mut next, S::MaybeLess, b">", S::AngleDone);
         set(&mut next, S::MaybeLess, b"<", S::ShlDone);
@@ -658,6 +637,7 @@ impl StreamingDfa {
         set(&mut next, S::AfterStar, b"=", S::StarAssignDone);
         set(&mut next, S::AfterPercent, b"=", S::PercentAssignDone);
         set(&mut next, S::AfterCaret, b"=", S::CaretAssignDone);
+        set(&mut next, S::AfterTilde, b"=", S::TildeAssignDone);
         set(&mut next, S::AfterPlus, b"+", S::IncDone);
         set(&mut next, S::AfterMinus, b"-", S::DecDone);
         set(&mut next, S::AfterMinus, b">", S::ArrowDone);
@@ -793,6 +773,40 @@ impl StreamingDfa {
             };
         }
 
+        // Raw strings: `r"..."` has no escapes and closes on the first quote.
+        // A lone `r` stays an identifier; any other identifier byte continues it.
+        next[S::Start.idx()][b'r' as usize] = Next {
+            state: S::RawPrefix.idx() as u16,
+            emit: false,
+        };
+        for b in 0u8..=255u8 {
+            if is_alnum(b) {
+                next[S::RawPrefix.idx()][b as usize] = Next {
+                    state: S::Ident.idx() as u16,
+                    emit: false,
+                };
+            }
+        }
+        next[S::RawPrefix.idx()][b'"' as usize] = Next {
+            state: S::InRawString.idx() as u16,
+            emit: false,
+        };
+        for b in 0u8..=255u8 {
+            match b {
+                b'"' | b'\n' => {}
+                _ => {
+                    next[S::InRawString.idx()][b as usize] = Next {
+                        state: S::InRawString.idx() as u16,
+                        emit: false,
+                    };
+                }
+            }
+        }
+        next[S::InRawString.idx()][b'"' as usize] = Next {
+            state: S::RawStringDone.idx() as u16,
+            emit: false,
+        };
+
         // Chars
         for b in 0u8..=255u8 {
             match b {
@@ -0,0 +1,378 @@
+// src/lexer/tables/io.rs
+use std::{
+    io::{BufWriter, Write},
+    time::Instant,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+use super::{Tables, tokens::INVALID_TOKEN};
+use crate::{
+    logging::LEXER_TABLES,
+    tables::{
+        FormatVersion,
+        format::{self, Container, ContainerWriter, SECTION_CRITICAL, Tag},
+    },
+};
+
+// -------------------- JSON (de)serialization --------------------
+
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+struct TablesDisk {
+    #[serde_as(as = "[_; 256]")]
+    char_to_func: [u32; 256],
+    merge: Vec<u32>,
+    token_of: Vec<u32>,
+    m: u32,
+    identity: u32,
+}
+impl From<&Tables> for TablesDisk {
+    fn from(t: &Tables) -> Self {
+        Self {
+            char_to_func: t.char_to_func,
+            merge: t.merge.clone(),
+            token_of: t.token_of.clone(),
+            m: t.m,
+            identity: t.identity,
+        }
+    }
+}
+impl TablesDisk {
+    fn into_tables(self) -> Tables {
+        Tables {
+            char_to_func: self.char_to_func,
+            merge: self.merge,
+            token_of: self.token_of,
+            m: self.m,
+            identity: self.identity,
+        }
+    }
+}
+
+/// Saves the full lexer table representation as JSON.
+pub fn save_tables_json(path: &std::path::Path, t: &Tables) -> std::io::Result<()> {
+    // Stream to disk to avoid giant intermediate strings.
+    let f = std::fs::File::create(path)?;
+    let mut w = BufWriter::new(f);
+    serde_json::to_writer(&mut w, &TablesDisk::from(t))?;
+    w.flush()
+}
+
+/// Loads the full lexer table representation from JSON bytes.
+pub fn load_tables_json_bytes(data: &[u8]) -> Result<Tables, String> {
+    serde_json::from_slice::<TablesDisk>(data)
+        .map(|d| d.into_tables())
+        .map_err(|e| format!("Failed to parse tables JSON: {e}"))
+}
+
+// -------------------- Compact binary (u16 packing) --------------------
+
+/// Magic of the version-1 layout: header, then the three arrays back to back.
+const BIN_MAGIC_V1: &[u8; 8] = b"LXTBLE02";
+/// Container magic of the version-2 merge tables.
+const MERGE_MAGIC: Tag = *b"LXLEXMRG";
+/// `m` and the identity function id.
+const HEAD_TAG: Tag = *b"LXMGHEAD";
+/// `char_to_func`: 256 x u16.
+const CHAR_TAG: Tag = *b"LXMGCHAR";
+/// `merge`: m*m x u16, row-major.
+const MERGE_TAG: Tag = *b"LXMGROWS";
+/// `token_of`: m x u16.
+const TOKEN_TAG: Tag = *b"LXMGTOKN";
+const INVALID_TOKEN_U16: u16 = 0xFFFF;
+
+/// Saves the full lexer table representation in the current binary format.
+pub fn save_tables_bin(path: &std::path::Path, t: &Tables) -> std::io::Result<()> {
+    save_tables_bin_as(path, t, FormatVersion::default())
+}
+
+/// Saves the full lexer table representation in the `version` layout.
+pub fn save_tables_bin_as(
+    path: &std::path::Path,
+    t: &Tables,
+    version: FormatVersion,
+) -> std::io::Result<()> {
+    let m = t.m as usize;
+    if Some(t.merge.len()) != m.checked_mul(m) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "merge has {} entries, expected m*m for m={m}",
+                t.merge.len()
+            ),
+        ));
+    }
+    write_tables_bin(
+        path,
+        version,
+        t.m,
+        t.identity,
+        &t.char_to_func,
+        &t.token_of,
+        |first_row, out| out.copy_from_slice(&t.merge[first_row * m..first_row * m + out.len()]),
+    )
+}
+
+/// Writes the `version` layout, asking `fill_rows(first_row, out)` for the
+/// merge table a batch of whole rows at a time so that callers never need
+/// all `m * m` entries in memory.
+pub(super) fn write_tables_bin(
+    path: &std::path::Path,
+    version: FormatVersion,
+    m_u32: u32,
+    identity: u32,
+    char_to_func: &[u32; 256],
+    token_of: &[u32],
+    fill_rows: impl FnMut(usize, &mut [u32]),
+) -> std::io::Result<()> {
+    let instant = Instant::now();
+    if m_u32 > u16::MAX as u32 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("m={m_u32} exceeds u16::MAX; cannot pack to u16"),
+        ));
+    }
+
+    let f = std::fs::File::create(path)?;
+
+    // Compute total size:
+    // header (m, identity) + char_to_func (256*2) + merge (m*m*2) + token_of (m*2)
+    let m = m_u32 as usize;
+    let size_char_to_func = 256 * 2;
+    let size_merge = m
+        .checked_mul(m)
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "m*m overflow"))?
+        * 2;
+    let size_token_of = m * 2;
+    let total_len = match version {
+        FormatVersion::V1 => (8 + 4 + 4 + size_char_to_func + size_merge + size_token_of) as u64,
+        FormatVersion::V2 => format::encoded_len(&[
+            8,
+            size_char_to_func as u64,
+            size_merge as u64,
+            size_token_of as u64,
+        ]),
+    };
+
+    // Pre-allocate.
+    f.set_len(total_len)?;
+
+    let mut w = BufWriter::new(f);
+    match version {
+        FormatVersion::V1 => {
+            w.write_all(BIN_MAGIC_V1)?;
+            w.write_all(&m_u32.to_le_bytes())?;
+            w.write_all(&identity.to_le_bytes())?;
+            write_char_to_func(&mut w, char_to_func)?;
+            write_merge_rows(&mut w, m, fill_rows)?;
+            write_token_of(&mut w, token_of)?;
+        }
+        FormatVersion::V2 => {
+            let mut c = ContainerWriter::new(&mut w, &MERGE_MAGIC, 4)?;
+            let mut head = [0u8; 8];
+            head[..4].copy_from_slice(&m_u32.to_le_bytes());
+            head[4..].copy_from_slice(&identity.to_le_bytes());
+            c.section(HEAD_TAG, SECTION_CRITICAL, &head)?;
+            c.begin_section(CHAR_TAG, SECTION_CRITICAL, size_char_to_func as u64)?;
+            write_char_to_func(&mut c, char_to_func)?;
+            c.begin_section(MERGE_TAG, SECTION_CRITICAL, size_merge as u64)?;
+            write_merge_rows(&mut c, m, fill_rows)?;
+            c.begin_section(TOKEN_TAG, SECTION_CRITICAL, size_token_of as u64)?;
+            write_token_of(&mut c, token_of)?;
+            c.finish()?;
+        }
+    }
+
+    let flush = w.flush();
+    log::info!(
+        target: LEXER_TABLES,
+        "saved tables to {} in {} ms",
+        path.display(),
+        instant.elapsed().as_millis()
+    );
+    flush
+}
+
+/// char_to_func: 256 x u16
+fn write_char_to_func(w: &mut impl Write, char_to_func: &[u32; 256]) -> std::io::Result<()> {
+    let mut buf = [0u8; 256 * 2];
+    for (i, &id) in char_to_func.iter().enumerate() {
+        let v = u16::try_from(id).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "char_to_func id > u16::MAX",
+            )
+        })?;
+        let p = i * 2;
+        buf[p..p + 2].copy_from_slice(&v.to_le_bytes());
+    }
+    w.write_all(&buf)
+}
+
+/// merge: m*m x u16, streamed in batches of about CHUNK entries
+fn write_merge_rows(
+    w: &mut impl Write,
+    m: usize,
+    mut fill_rows: impl FnMut(usize, &mut [u32]),
+) -> std::io::Result<()> {
+    const CHUNK: usize = 1 << 20;
+    if let Some(rows_per_batch) = CHUNK.checked_div(m) {
+        let rows_per_batch = rows_per_batch.max(1);
+        let mut ids = vec![0u32; rows_per_batch * m];
+        let mut bytes = vec![0u8; rows_per_batch * m * 2];
+        for first_row in (0..m).step_by(rows_per_batch) {
+            let len = rows_per_batch.min(m - first_row) * m;
+            fill_rows(first_row, &mut ids[..len]);
+            for (i, &id) in ids[..len].iter().enumerate() {
+                let v = u16::try_from(id).map_err(|_| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, "merge id > u16::MAX")
+                })?;
+                let p = i * 2;
+                bytes[p..p + 2].copy_from_slice(&v.to_le_bytes());
+            }
+            w.write_all(&bytes[..len * 2])?;
+        }
+    }
+    Ok(())
+}
+
+/// token_of: m x u16
+fn write_token_of(w: &mut impl Write, token_of: &[u32]) -> std::io::Result<()> {
+    let mut bytes = vec![0u8; token_of.len() * 2];
+    for (i, &tk) in token_of.iter().enumerate() {
+        let v = if tk == INVALID_TOKEN {
+            INVALID_TOKEN_U16
+        } else {
+            u16::try_from(tk).map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, "token_of > u16::MAX")
+            })?
+        };
+        let p = i * 2;
+        bytes[p..p + 2].copy_from_slice(&v.to_le_bytes());
+    }
+    w.write_all(&bytes)
+}
+
+fn read_u32(buf: &mut &[u8]) -> Result<u32, String> {
+    if buf.len() < 4 {
+        return Err("truncated u32".into());
+    }
+    let mut le = [0u8; 4];
+    le.copy_from_slice(&buf[..4]);
+    *buf = &buf[4..];
+    Ok(u32::from_le_bytes(le))
+}
+
+fn read_u16(buf: &mut &[u8]) -> Result<u16, String> {
+    if buf.len() < 2 {
+        return Err("truncated u16".into());
+    }
+    let mut le = [0u8; 2];
+    le.copy_from_slice(&buf[..2]);
+    *buf = &buf[2..];
+    Ok(u16::from_le_bytes(le))
+}
+
+/// Loads the full lexer table representation from binary bytes in either
+/// the version-2 container or the version-1 `LXTBLE02` layout.
+pub fn load_tables_bin_bytes(data: &[u8]) -> Result<Tables, String> {
+    if data.starts_with(BIN_MAGIC_V1) {
+        return load_tables_bin_v1(&data[BIN_MAGIC_V1.len()..]);
+    }
+    let container = Container::parse(
+        data,
+        &MERGE_MAGIC,
+        &[HEAD_TAG, CHAR_TAG, MERGE_TAG, TOKEN_TAG],
+    )
+    .map_err(|err| format!("tables .bin: {err}"))?;
+    let section = |tag: Tag| {
+        container
+            .require(&tag)
+            .map_err(|err| format!("tables .bin: {err}"))
+    };
+    let consumed = |tag: Tag, rest: &[u8]| {
+        format::expect_consumed(&tag, rest).map_err(|err| format!("tables .bin: {err}"))
+    };
+
+    let mut head = section(HEAD_TAG)?;
+    let m = read_u32(&mut head)? as usize;
+    let identity = read_u32(&mut head)?;
+    consumed(HEAD_TAG, head)?;
+
+    let mut chars = section(CHAR_TAG)?;
+    let char_to_func = read_char_to_func(&mut chars)?;
+    consumed(CHAR_TAG, chars)?;
+
+    let mut rows = section(MERGE_TAG)?;
+    let merge = read_merge(&mut rows, m)?;
+    consumed(MERGE_TAG, rows)?;
+
+    let mut tokens = section(TOKEN_TAG)?;
+    let token_of = read_token_of(&mut tokens, m)?;
+    consumed(TOKEN_TAG, tokens)?;
+
+    Ok(Tables {
+        char_to_func,
+        merge,
+        token_of,
+        m: m as u32,
+        identity,
+    })
+}
+
+/// Reads the version-1 layout after its magic.
+fn load_tables_bin_v1(mut data: &[u8]) -> Result<Tables, String> {
+    if data.len() < 4 + 4 {
+        return Err("bin too short".into());
+    }
+    let m = read_u32(&mut data)? as usize;
+    let identity = read_u32(&mut data)?;
+    let char_to_func = read_char_to_func(&mut data)?;
+    let merge = read_merge(&mut data, m)?;
+    let token_of = read_token_of(&mut data, m)?;
+
+    // Any remaining bytes (old V1 emit bits) are ignored intentionally.
+
+    Ok(Tables {
+        char_to_func,
+        merge,
+        token_of,
+        m: m as u32,
+        identity,
+    })
+}
+
+fn read_char_to_func(data: &mut &[u8]) -> Result<[u32; 256], String> {
+    let mut char_to_func = [0u32; 256];
+    for id in &mut char_to_func {
+        *id = read_u16(data)? as u32;
+    }
+    Ok(char_to_func)
+}
+
+fn read_merge(data: &mut &[u8], m: usize) -> Result<Vec<u32>, String> {
+    let mm = m.checked_mul(m).ok_or("m*m overflow")?;
+    if data.len() / 2 < mm {
+        return Err("truncated u16".into());
+    }
+    let mut merge = Vec::with_capacity(mm);
+    for _ in 0..mm {
+        merge.push(read_u16(data)? as u32);
+    }
+    Ok(merge)
+}
+
+fn read_token_of(data: &mut &[u8], m: usize) -> Result<Vec<u32>, String> {
+    let mut token_of = Vec::with_capacity(m.min(data.len() / 2));
+    for _ in 0..m {
+        let v = read_u16(data)?;
+        token_of.push(if v == INVALID_TOKEN_U16 {
+            INVALID_TOKEN
+        } else {
+            v as u32
+        });
+    }
+    Ok(token_of)
+}
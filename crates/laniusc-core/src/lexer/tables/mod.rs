@@ -1,3 +1,5 @@
+/// Summary-function closure and merge-table generation for [`Tables`].
+pub mod build;
 /// Compact runtime DFA table loader.
 pub mod compact;
 /// Hand-built DFA used by table generation and the CPU oracle.
@@ -7,7 +9,13 @@ pub mod io;
 /// Token kind definitions and token id constants.
 pub mod tokens;
 
-pub use io::{load_tables_bin_bytes, load_tables_json_bytes, save_tables_bin, save_tables_json};
+pub use io::{
+    load_tables_bin_bytes,
+    load_tables_json_bytes,
+    save_tables_bin,
+    save_tables_bin_as,
+    save_tables_json,
+};
 pub use tokens::{INVALID_TOKEN, TokenKind};
 
 /// Full lexer table form used by table generation and compatibility tests.
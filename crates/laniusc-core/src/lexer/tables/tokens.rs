@@ -24,6 +24,13 @@ macro_rules! define_token_kinds {
                     .filter(|kind| *kind as u32 == v)
             }
 
+            /// The variant name, as [`TokenKind::from_name`] accepts it.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Self::$name => stringify!($name),)+
+                }
+            }
+
             /// Resolves a grammar terminal name to its token kind.
             pub fn from_name(name: &str) -> Option<Self> {
                 let k = match name {
@@ -253,6 +260,16 @@ define_token_kinds! {
     ExternAbiString,
     DotDotEqual,
     RangeInclusiveAssign,
+    RawString,
+
+    // `#!` line at offset 0; produced by a driver pre-scan, never by the DFA
+    Shebang,
+
+    // `~=`; `&&=` and `||=` stay two tokens (`AndAnd`/`OrOr` then `Assign`)
+    TildeAssign,
+
+    // UTF-8 BOM at offset 0; produced by a driver pre-scan, never by the DFA
+    Bom,
 }
 
 impl core::convert::TryFrom<u32> for TokenKind {
@@ -262,6 +279,36 @@ impl core::convert::TryFrom<u32> for TokenKind {
     }
 }
 
+/// Identifier lexemes the token builder retags, with their kinds; the
+/// `retag_keyword` shader helper spells out the same list byte by byte.
+pub const KEYWORDS: &[(&str, TokenKind)] = &[
+    ("pub", TokenKind::Pub),
+    ("fn", TokenKind::Fn),
+    ("in", TokenKind::In),
+    ("let", TokenKind::Let),
+    ("for", TokenKind::For),
+    ("return", TokenKind::Return),
+    ("if", TokenKind::If),
+    ("else", TokenKind::Else),
+    ("while", TokenKind::While),
+    ("break", TokenKind::Break),
+    ("continue", TokenKind::Continue),
+    ("true", TokenKind::True),
+    ("false", TokenKind::False),
+    ("const", TokenKind::Const),
+    ("enum", TokenKind::Enum),
+    ("extern", TokenKind::Extern),
+    ("import", TokenKind::Import),
+    ("impl", TokenKind::Impl),
+    ("match", TokenKind::Match),
+    ("module", TokenKind::Module),
+    ("self", TokenKind::SelfValue),
+    ("struct", TokenKind::Struct),
+    ("trait", TokenKind::Trait),
+    ("type", TokenKind::Type),
+    ("where", TokenKind::Where),
+];
+
 /// Sentinel used by Rust and shaders for non-token DFA states.
 pub const INVALID_TOKEN: u32 = u32::MAX;
 /// Number of token ids including the invalid zero slot used by generated tables.
@@ -0,0 +1,2084 @@
+//! TEST-ONLY CPU lexer oracle.
+//!
+//! This module is not a compiler implementation and must not be used as a
+//! fallback. It exists so tests and fuzzers can compare GPU lexer output against
+//! a small host-side oracle while the production compiler lexes on the GPU.
+//! Opt-in paranoia mode compares small windows against it the same way.
+
+use std::{collections::VecDeque, thread};
+
+pub use self::coverage::{Coverage, CoverageReport};
+use crate::{
+    lexer::{
+        analysis::{self, AnalysisWords, end_of_rank},
+        bom::{bom_len, mask_bom},
+        boundary::{PF_EMIT, PF_EOF, boundary_flags, is_kept},
+        escapes::escape_span_at,
+        layout,
+        paranoia::suspect_tokens_of,
+        range::sync_point,
+        shebang::{mask_shebang, shebang_len},
+        tables::{
+            dfa::{S, StreamingDfa},
+            tokens::{INVALID_TOKEN, KEYWORDS, TokenKind},
+        },
+        types::{
+            EscapeSpan,
+            LayoutFacts,
+            LexAnalysis,
+            LexError,
+            LexOptions,
+            LexOutput,
+            ReadbackMode,
+            Token,
+        },
+        util::merge_kept_into_all,
+    },
+    span::Span,
+};
+
+/// DFA state and token-kind coverage for fuzz tooling.
+pub mod coverage;
+
+/// Former name of the oracle's token record, which is now the GPU [`Token`].
+pub type TestCpuToken = Token;
+
+fn keyword_kind(bytes: &[u8]) -> Option<TokenKind> {
+    KEYWORDS
+        .iter()
+        .find(|(text, _)| text.as_bytes() == bytes)
+        .map(|&(_, kind)| kind)
+}
+
+/// Retags an `Ident` whose lexeme is a keyword.
+fn retag_keyword(token: &mut Token, src: &[u8]) {
+    if token.kind != TokenKind::Ident {
+        return;
+    }
+    if let Some(kind) = src.get(token.span.range()).and_then(keyword_kind) {
+        token.kind = kind;
+    }
+}
+
+/// Splits a float ending in `.` and the `.` right after it into an `Int` and
+/// a `..`, as the GPU token builder does for `1..2`. Returns whether it split.
+fn split_numeric_dotdot(current: &mut Token, next: &mut Token, src: &[u8]) -> bool {
+    if current.kind != TokenKind::Float
+        || current.len() < 2
+        || next.kind != TokenKind::Dot
+        || next.len() != 1
+        || next.start() != current.end()
+    {
+        return false;
+    }
+    let dot = current.end() - 1;
+    if src.get(dot) != Some(&b'.') {
+        return false;
+    }
+
+    current.kind = TokenKind::Int;
+    current.span.end -= 1;
+    next.kind = TokenKind::DotDot;
+    next.span.start -= 1;
+    true
+}
+
+/// Whether `current` is a `..` with an `=` directly after it.
+fn is_inclusive_dotdot(current: &Token, next: &Token) -> bool {
+    current.kind == TokenKind::DotDot
+        && next.kind == TokenKind::Assign
+        && next.start() == current.end()
+}
+
+fn decode_dfa_token(kind_u32: u32, state: usize, at: usize) -> Result<TokenKind, String> {
+    TokenKind::from_u32(kind_u32).ok_or_else(|| {
+        if kind_u32 == INVALID_TOKEN {
+            format!("emit from non-accepting state={state} at i={at}")
+        } else {
+            format!("invalid token kind {kind_u32} from DFA state={state} at i={at}")
+        }
+    })
+}
+
+fn slice_dbg(src: &[u8], i: usize) -> (usize, String) {
+    let lo = i.saturating_sub(16);
+    let hi = (i + 16).min(src.len());
+    let mut s = String::new();
+    for &b in &src[lo..hi] {
+        s.push(
+            if b.is_ascii_graphic() || b == b' ' || b == b'\n' || b == b'\t' || b == b'\r' {
+                b as char
+            } else {
+                '.'
+            },
+        );
+    }
+    (lo, s)
+}
+
+/// The kept stream is a filter over the all-boundary stream, as on the GPU.
+#[cfg(test)]
+fn lex_raw_kept(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = lex_raw(input)?;
+    tokens.retain(|token| is_kept(Some(token.kind)));
+    Ok(tokens)
+}
+
+fn lex_raw(input: &str) -> Result<Vec<Token>, String> {
+    RawTokens::new(input).collect()
+}
+
+/// The driver lexes a leading BOM as masked whitespace and a leading `#!`
+/// line as a masked line comment; the oracle reports either directly and
+/// resumes the DFA from the start state after it.
+pub(crate) fn prefix_token(bytes: &[u8]) -> Option<Token> {
+    match (bom_len(bytes), shebang_len(bytes)) {
+        (0, None) => None,
+        (0, Some(len)) => Some((TokenKind::Shebang, len)),
+        (bom, _) => Some((TokenKind::Bom, bom)),
+    }
+    .map(|(kind, len)| Token::new(kind, 0, len))
+}
+
+/// Source of raw tokens for [`KeptTokens`].
+trait RawWalk<'a> {
+    fn bytes(&self) -> &'a [u8];
+
+    /// Next token, calling `visit` with every DFA state entered on the way.
+    fn next_visiting(&mut self, visit: impl FnMut(usize)) -> Option<Result<Token, String>>;
+}
+
+/// Streaming DFA walk over one input, yielding every token, skipped ones
+/// included, with raw DFA kinds. Stops after the first error.
+struct RawTokens<'a> {
+    bytes: &'a [u8],
+    dfa: Box<StreamingDfa>,
+    /// BOM or shebang token reported ahead of the DFA walk.
+    prefix: Option<Token>,
+    /// Next byte to feed the DFA.
+    at: usize,
+    /// Byte at which a chunk walk hands over to the next chunk; the input
+    /// length for a whole-input walk.
+    end: usize,
+    state: usize,
+    tok_start: usize,
+    entered_start: bool,
+    done: bool,
+}
+
+impl<'a> RawTokens<'a> {
+    fn new(input: &'a str) -> Self {
+        let bytes = input.as_bytes();
+        let prefix = prefix_token(bytes);
+        let from = prefix.map_or(0, |token| token.len());
+        Self {
+            prefix,
+            entered_start: false,
+            ..Self::chunk(input, from, bytes.len())
+        }
+    }
+
+    /// Walk over `input[from..end]` from the start state, with offsets into
+    /// all of `input`. `from` must be a [`sync_point`] of `input` and `end`
+    /// the next one, or the input length; the start state counts as already
+    /// entered, since the chunk before also enters the state at `from`.
+    fn chunk(input: &'a str, from: usize, end: usize) -> Self {
+        let bytes = input.as_bytes();
+        let dfa = Box::new(StreamingDfa::new());
+        let state = dfa.start as usize;
+        Self {
+            bytes,
+            dfa,
+            prefix: None,
+            at: from,
+            end,
+            state,
+            tok_start: from,
+            entered_start: true,
+            done: from == bytes.len(),
+        }
+    }
+
+    /// Walks the chunk to its end, counting the states it enters into a new
+    /// [`Coverage`] when `coverage` is set.
+    fn drain(mut self, coverage: bool) -> (Vec<Token>, Option<String>, Option<Coverage>) {
+        let mut visited = coverage.then(Coverage::new);
+        let mut tokens = Vec::new();
+        while let Some(token) = self.next_visiting(|state| {
+            if let Some(visited) = visited.as_mut() {
+                visited.record_state(state);
+            }
+        }) {
+            match token {
+                Ok(token) => tokens.push(token),
+                Err(err) => return (tokens, Some(err), visited),
+            }
+        }
+        (tokens, None, visited)
+    }
+}
+
+impl<'a> RawWalk<'a> for RawTokens<'a> {
+    fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    fn next_visiting(&mut self, mut visit: impl FnMut(usize)) -> Option<Result<Token, String>> {
+        if let Some(token) = self.prefix.take() {
+            return Some(Ok(token));
+        }
+        if self.done {
+            return None;
+        }
+        if !self.entered_start {
+            self.entered_start = true;
+            visit(self.state);
+        }
+
+        let n = self.bytes.len();
+        while self.at < n {
+            let i = self.at;
+            let b = self.bytes[i];
+            let state = self.state;
+            let next = self.dfa.next[state][b as usize];
+            // At a chunk end every valid run emits and enters the state the
+            // start state enters on `b`, so the last token ends here and the
+            // next chunk enters that state itself.
+            if i == self.end
+                && next.emit
+                && next.state as usize != S::Reject.idx()
+                && let Some(kind) = TokenKind::from_u32(self.dfa.token_map[state])
+            {
+                self.done = true;
+                return Some(Ok(Token::new(kind, self.tok_start, i - self.tok_start)));
+            }
+            visit(next.state as usize);
+
+            // Reject as-soon-as we see it; include a little context.
+            if next.state as usize == S::Reject.idx() {
+                self.done = true;
+                let (ctx_lo, ctx) = slice_dbg(self.bytes, i);
+                return Some(Err(format!(
+                    "fell into REJECT at byte {i} (char {:?}, 0x{:02X}) from state={state}; \
+                     context [{}..{}):\n{}",
+                    b as char,
+                    b,
+                    ctx_lo,
+                    ctx_lo + ctx.len(),
+                    ctx
+                )));
+            }
+            self.at += 1;
+            self.state = next.state as usize;
+
+            // If this edge "emits", a token just ended BEFORE consuming b.
+            if next.emit {
+                let token = decode_dfa_token(self.dfa.token_map[state], state, i)
+                    .map(|kind| Token::new(kind, self.tok_start, i - self.tok_start));
+                // The emitting edge already transitions as if we consumed `b`,
+                // so the next token starts at `i`.
+                self.tok_start = i;
+                self.done = token.is_err();
+                return Some(token);
+            }
+        }
+
+        self.done = true;
+        // End-of-input: if final state is accepting, emit the final token to `n`.
+        let end_kind_u32 = self.dfa.token_map[self.state];
+        if end_kind_u32 != INVALID_TOKEN {
+            return Some(
+                decode_dfa_token(end_kind_u32, self.state, n)
+                    .map(|kind| Token::new(kind, self.tok_start, n - self.tok_start)),
+            );
+        }
+
+        // If we got here and are in REJECT, tell the user where we last were OK.
+        if self.state == S::Reject.idx() {
+            return Some(Err("ended in REJECT".into()));
+        }
+
+        // Non-accepting but not reject (e.g., unterminated block comment): surface it clearly.
+        Some(Err(format!(
+            "ended in non-accepting state={} (unterminated token?)",
+            self.state
+        )))
+    }
+}
+
+/// Raw tokens walked ahead of time, e.g. by [`lex_raw_parallel`]. States
+/// were counted by the walk, so `visit` is never called.
+struct WalkedTokens<'a> {
+    bytes: &'a [u8],
+    tokens: std::vec::IntoIter<Token>,
+    error: Option<String>,
+}
+
+impl<'a> RawWalk<'a> for WalkedTokens<'a> {
+    fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    fn next_visiting(&mut self, _visit: impl FnMut(usize)) -> Option<Result<Token, String>> {
+        self.tokens
+            .next()
+            .map(Ok)
+            .or_else(|| self.error.take().map(Err))
+    }
+}
+
+impl Iterator for WalkedTokens<'_> {
+    type Item = Result<Token, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_visiting(|_| {})
+    }
+}
+
+/// Bytes after each evenly spaced target that [`chunk_starts`] searches for
+/// a split.
+const SPLIT_SEARCH_BYTES: usize = 64 * 1024;
+
+/// Start offsets of up to `chunks` pieces of `input` that lex independently:
+/// the first is 0 and the rest are [`sync_point`]s past evenly spaced
+/// targets. A target with no sync point within [`SPLIT_SEARCH_BYTES`], as
+/// inside a long block comment, is dropped, so an input without any lexes
+/// as one piece.
+fn chunk_starts(dfa: &StreamingDfa, bytes: &[u8], chunks: usize) -> Vec<usize> {
+    let n = bytes.len();
+    let floor = prefix_token(bytes).map_or(0, |token| token.len()).max(1);
+    let mut starts = vec![0];
+    for k in 1..chunks {
+        let last = *starts.last().expect("starts holds 0");
+        let target = (n / chunks * k).max(last + 1).max(floor);
+        if target >= n {
+            break;
+        }
+        let to = target.saturating_add(SPLIT_SEARCH_BYTES).min(n - 1);
+        if let Some(p) = sync_point(dfa, bytes, target, to) {
+            starts.push(p);
+        }
+    }
+    starts
+}
+
+/// [`lex_raw`] with the input split at [`chunk_starts`] and the pieces walked
+/// on scoped threads, one per piece. Tokens are stitched in order up to the
+/// first error, which is the error the sequential walk reports; the states
+/// entered up to it are added to `coverage`.
+fn lex_raw_parallel<'a>(
+    input: &'a str,
+    chunks: usize,
+    coverage: Option<&mut Coverage>,
+) -> WalkedTokens<'a> {
+    let bytes = input.as_bytes();
+    let starts = chunk_starts(&StreamingDfa::new(), bytes, chunks);
+    let ends = starts.iter().skip(1).copied().chain([bytes.len()]);
+    let counting = coverage.is_some();
+    let pieces = thread::scope(|scope| {
+        let walks = starts
+            .iter()
+            .zip(ends)
+            .map(|(&from, end)| {
+                scope.spawn(move || {
+                    let walk = if from == 0 {
+                        RawTokens {
+                            end,
+                            ..RawTokens::new(input)
+                        }
+                    } else {
+                        RawTokens::chunk(input, from, end)
+                    };
+                    walk.drain(counting)
+                })
+            })
+            .collect::<Vec<_>>();
+        walks
+            .into_iter()
+            .map(|walk| walk.join().expect("test CPU chunk walk panicked"))
+            .collect::<Vec<_>>()
+    });
+
+    let mut tokens = Vec::new();
+    let mut error = None;
+    let mut coverage = coverage;
+    for (piece, err, visited) in pieces {
+        tokens.extend(piece);
+        if let (Some(coverage), Some(visited)) = (coverage.as_deref_mut(), &visited) {
+            coverage.merge(visited);
+        }
+        if err.is_some() {
+            error = err;
+            break;
+        }
+    }
+    WalkedTokens {
+        bytes,
+        tokens: tokens.into_iter(),
+        error,
+    }
+}
+
+impl Iterator for RawTokens<'_> {
+    type Item = Result<Token, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_visiting(|_| {})
+    }
+}
+
+/// Kept tokens of one input with lexer-owned retags, lexed as they are
+/// pulled. Yields the error that stopped the walk after the tokens before it.
+struct KeptTokens<'a, R = RawTokens<'a>> {
+    raw: R,
+    max_token_len: u32,
+    /// Kept tokens read ahead for the pairwise range retags.
+    ahead: VecDeque<Token>,
+    error: Option<String>,
+    failed: bool,
+    coverage: Option<&'a mut Coverage>,
+}
+
+impl<'a> KeptTokens<'a> {
+    fn new(input: &'a str, max_token_len: u32, coverage: Option<&'a mut Coverage>) -> Self {
+        KeptTokens::over(RawTokens::new(input), max_token_len, coverage)
+    }
+}
+
+impl<'a, R: RawWalk<'a>> KeptTokens<'a, R> {
+    fn over(raw: R, max_token_len: u32, coverage: Option<&'a mut Coverage>) -> Self {
+        Self {
+            raw,
+            max_token_len,
+            ahead: VecDeque::with_capacity(2),
+            error: None,
+            failed: false,
+            coverage,
+        }
+    }
+
+    /// Walks the DFA until two kept tokens are read ahead or the walk stops.
+    fn fill(&mut self) {
+        while self.ahead.len() < 2 && !self.failed {
+            let coverage = &mut self.coverage;
+            let next = self.raw.next_visiting(|state| {
+                if let Some(coverage) = coverage.as_deref_mut() {
+                    coverage.record_state(state);
+                }
+            });
+            let token = match next {
+                None => return,
+                Some(Err(err)) => {
+                    self.stop(err);
+                    continue;
+                }
+                Some(Ok(token)) => token,
+            };
+            if token.len() > self.max_token_len as usize {
+                self.stop(
+                    LexError::TokenTooLong {
+                        kind: token.kind,
+                        start: token.start(),
+                        limit: self.max_token_len,
+                    }
+                    .to_string(),
+                );
+            } else if is_kept(Some(token.kind)) {
+                self.ahead.push_back(token);
+            } else if let Some(coverage) = self.coverage.as_deref_mut() {
+                coverage.record_token(token.kind, token.len());
+            }
+        }
+    }
+
+    fn stop(&mut self, err: String) {
+        self.error = Some(err);
+        self.failed = true;
+    }
+}
+
+impl<'a, R: RawWalk<'a>> Iterator for KeptTokens<'a, R> {
+    type Item = Result<Token, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.fill();
+        let Some(mut token) = self.ahead.pop_front() else {
+            return self.error.take().map(Err);
+        };
+        let src = self.raw.bytes();
+        if let Some(next) = self.ahead.front_mut()
+            && !split_numeric_dotdot(&mut token, next, src)
+            && is_inclusive_dotdot(&token, next)
+        {
+            token.kind = TokenKind::DotDotEqual;
+        }
+        retag_keyword(&mut token, src);
+        if let Some(coverage) = self.coverage.as_deref_mut() {
+            coverage.record_token(token.kind, token.len());
+        }
+        Some(Ok(token))
+    }
+}
+
+/// Test CPU oracle configured by the same [`LexOptions`] as the GPU lexer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TestCpuLexer {
+    options: LexOptions,
+    threads: usize,
+}
+
+impl TestCpuLexer {
+    pub fn new(options: LexOptions) -> Self {
+        Self {
+            options,
+            threads: 1,
+        }
+    }
+
+    /// Splits the DFA walk of [`lex`](Self::lex) across up to `threads`
+    /// threads, as [`lex_on_test_cpu_parallel`] does. The output is the
+    /// same for any thread count; 0 and 1 lex on the calling thread.
+    pub fn with_threads(self, threads: usize) -> Self {
+        Self { threads, ..self }
+    }
+
+    /// Kept tokens of `input`, lexed as they are pulled so callers can stop
+    /// early. Fails at the first DFA reject or token longer than
+    /// [`LexOptions::max_token_len`]; the other options do not apply.
+    pub fn iter<'a>(&self, input: &'a str) -> impl Iterator<Item = Result<Token, String>> + 'a {
+        KeptTokens::new(input, self.options.max_token_len, None)
+    }
+
+    /// What `GpuLexer::lex_with_options`
+    /// returns for `input` under the same options, without the GPU-only
+    /// [`LexOutput::report`].
+    pub fn lex(&self, input: &str) -> Result<LexOutput, String> {
+        let options = self.options;
+        if options.readback == ReadbackMode::None {
+            // The GPU reads nothing back, errors included.
+            return Ok(LexOutput::default());
+        }
+        let tokens = if self.threads > 1 {
+            KeptTokens::over(
+                lex_raw_parallel(input, self.threads, None),
+                options.max_token_len,
+                None,
+            )
+            .collect::<Result<Vec<_>, _>>()?
+        } else {
+            self.iter(input).collect::<Result<Vec<_>, _>>()?
+        };
+        if options.readback == ReadbackMode::CountOnly {
+            return Ok(LexOutput {
+                token_count: tokens.len(),
+                ..LexOutput::default()
+            });
+        }
+        let accept_states = if options.capture_accept_states {
+            accept_states_in_chunks(input, &tokens, self.threads)
+        } else {
+            Vec::new()
+        };
+        let escape_spans = if options.capture_string_escapes {
+            escape_spans_of(input, &tokens)
+        } else {
+            Vec::new()
+        };
+        let suspects = suspect_tokens_of(&tokens, input.len(), &options);
+        let layout_facts = if options.layout_facts {
+            layout_facts_on_test_cpu(input)?
+        } else {
+            Vec::new()
+        };
+        let all_tokens = if options.all_tokens {
+            let mut all = if self.threads > 1 {
+                lex_raw_parallel(input, self.threads, None).collect::<Result<Vec<_>, _>>()?
+            } else {
+                lex_raw(input)?
+            };
+            merge_kept_into_all(&mut all, &tokens)?;
+            all
+        } else {
+            Vec::new()
+        };
+        Ok(LexOutput {
+            token_count: tokens.len(),
+            tokens,
+            accept_states,
+            all_tokens,
+            escape_spans,
+            suspects,
+            layout_facts,
+            ..LexOutput::default()
+        })
+    }
+}
+
+/// Deterministic test CPU oracle for GPU lexer readback.
+/// Returns kept DFA tokens with lexer-owned keyword retags applied.
+pub fn lex_on_test_cpu(input: &str) -> Result<Vec<Token>, String> {
+    TestCpuLexer::default().iter(input).collect()
+}
+
+/// [`lex_on_test_cpu`] with the DFA walk split across up to `threads`
+/// threads; the result, errors included, is the same as the sequential one.
+///
+/// The input is cut only where every DFA run ends a token and lands in one
+/// state (see [`sync_point`]), so each piece lexes from the start state on
+/// its own. Pieces are stitched before the keep filter and retags, which
+/// look across token pairs. An input without such points near the evenly
+/// spaced targets, e.g. one long block comment, lexes on fewer threads.
+pub fn lex_on_test_cpu_parallel(input: &str, threads: usize) -> Result<Vec<Token>, String> {
+    KeptTokens::over(lex_raw_parallel(input, threads, None), u32::MAX, None).collect()
+}
+
+/// [`lex_on_test_cpu`] over bytes that must be UTF-8, as the GPU lexer
+/// requires.
+pub fn lex_on_test_cpu_bytes(input: &[u8]) -> Result<Vec<Token>, String> {
+    let input =
+        std::str::from_utf8(input).map_err(|err| format!("source is not valid UTF-8: {err}"))?;
+    lex_on_test_cpu(input)
+}
+
+/// Test CPU oracle for every [`LexOptions`] field; see [`TestCpuLexer::lex`].
+pub fn lex_on_test_cpu_with_options(input: &str, options: LexOptions) -> Result<LexOutput, String> {
+    TestCpuLexer::new(options).lex(input)
+}
+
+/// [`lex_on_test_cpu`] that also adds every DFA state entered, and every
+/// token, to `coverage`. Skipped tokens count under their raw kind and kept
+/// tokens under their retagged kind. States and tokens seen before a lex
+/// error are still counted.
+pub fn lex_on_test_cpu_with_coverage(
+    input: &str,
+    coverage: &mut Coverage,
+) -> Result<Vec<Token>, String> {
+    KeptTokens::new(input, u32::MAX, Some(coverage)).collect()
+}
+
+/// [`lex_on_test_cpu_with_coverage`] with the DFA walk split across up to
+/// `threads` threads, as in [`lex_on_test_cpu_parallel`]. Adds the same
+/// counts to `coverage` as the sequential walk.
+pub fn lex_on_test_cpu_with_coverage_parallel(
+    input: &str,
+    threads: usize,
+    coverage: &mut Coverage,
+) -> Result<Vec<Token>, String> {
+    let raw = lex_raw_parallel(input, threads, Some(coverage));
+    KeptTokens::over(raw, u32::MAX, Some(coverage)).collect()
+}
+
+/// Test CPU oracle for `LexOptions::capture_accept_states`.
+/// Returns the kept tokens of [`lex_on_test_cpu`] with the DFA state that
+/// accepted each one.
+pub fn lex_on_test_cpu_with_accept_states(input: &str) -> Result<(Vec<Token>, Vec<u16>), String> {
+    let tokens = lex_on_test_cpu(input)?;
+    let states = accept_states_of(input, &tokens);
+    Ok((tokens, states))
+}
+
+/// [`accept_states_of`] over up to `threads` runs of `tokens` at once.
+fn accept_states_in_chunks(input: &str, tokens: &[Token], threads: usize) -> Vec<u16> {
+    if threads <= 1 {
+        return accept_states_of(input, tokens);
+    }
+    let run = tokens.len().div_ceil(threads).max(1);
+    thread::scope(|scope| {
+        let runs = tokens
+            .chunks(run)
+            .map(|run| scope.spawn(move || accept_states_of(input, run)))
+            .collect::<Vec<_>>();
+        runs.into_iter()
+            .flat_map(|run| run.join().expect("test CPU accept-state walk panicked"))
+            .collect()
+    })
+}
+
+fn accept_states_of(input: &str, tokens: &[Token]) -> Vec<u16> {
+    let dfa = StreamingDfa::new();
+    let bytes = input.as_bytes();
+    tokens
+        .iter()
+        .map(|token| {
+            if matches!(token.kind, TokenKind::DotDot | TokenKind::DotDotEqual) {
+                // Covers `..` split off a float such as `1..`, which the DFA
+                // lexed as a lone dot.
+                return S::DotDotDone.idx() as u16;
+            }
+            bytes[token.span.range()]
+                .iter()
+                .fold(dfa.start, |state, &b| {
+                    dfa.next[state as usize][b as usize].state
+                })
+        })
+        .collect()
+}
+
+/// Test CPU oracle for `LexOptions::capture_string_escapes`.
+/// Returns the kept tokens of [`lex_on_test_cpu`] with the escape spans of
+/// their string and char literals.
+pub fn lex_on_test_cpu_with_escape_spans(
+    input: &str,
+) -> Result<(Vec<Token>, Vec<EscapeSpan>), String> {
+    let tokens = lex_on_test_cpu(input)?;
+    let spans = escape_spans_of(input, &tokens);
+    Ok((tokens, spans))
+}
+
+/// Walks each string and char literal from the start state and records the
+/// bytes after which the DFA sits in an escape state, as `escape_spans` does
+/// over the GPU's per-byte state stream.
+fn escape_spans_of(input: &str, tokens: &[Token]) -> Vec<EscapeSpan> {
+    let dfa = StreamingDfa::new();
+    let bytes = input.as_bytes();
+    let escape_states = [S::StringEscape.idx() as u16, S::CharEscape.idx() as u16];
+    let mut spans = Vec::new();
+    for token in tokens
+        .iter()
+        .filter(|token| matches!(token.raw_kind, TokenKind::String | TokenKind::Char))
+    {
+        let mut state = dfa.start;
+        for (offset, &b) in bytes[token.span.range()].iter().enumerate() {
+            state = dfa.next[state as usize][b as usize].state;
+            if escape_states.contains(&state) {
+                spans.push(escape_span_at(bytes, token.start() + offset));
+            }
+        }
+    }
+    spans
+}
+
+/// Test CPU oracle for `LexOptions::max_token_len`.
+/// Fails with the [`LexError::TokenTooLong`] message for the first token, kept
+/// or skipped, longer than `max_token_len`; otherwise matches [`lex_on_test_cpu`].
+pub fn lex_on_test_cpu_with_max_token_len(
+    input: &str,
+    max_token_len: u32,
+) -> Result<Vec<Token>, String> {
+    TestCpuLexer::new(LexOptions {
+        max_token_len,
+        ..LexOptions::default()
+    })
+    .iter(input)
+    .collect()
+}
+
+/// Test CPU oracle for the GPU all-boundary stream.
+/// Returns every DFA token, including skipped whitespace and comments, with raw
+/// DFA kinds and no keyword or range retags.
+pub fn lex_on_test_cpu_all(input: &str) -> Result<Vec<Token>, String> {
+    lex_raw(input)
+}
+
+/// [`lex_on_test_cpu_all`] with the DFA walk split across up to `threads`
+/// threads, as in [`lex_on_test_cpu_parallel`].
+pub fn lex_on_test_cpu_all_parallel(input: &str, threads: usize) -> Result<Vec<Token>, String> {
+    lex_raw_parallel(input, threads, None).collect()
+}
+
+/// Test CPU mirror of `lexer_analyze` for fuzz parity.
+///
+/// Computes the kernel's words the way it does, from the masked bytes'
+/// boundary flags and inclusive all-boundary ranks with each token start
+/// found by rank, then decodes them. Fails where [`lex_on_test_cpu_all`]
+/// does; the GPU analysis of such inputs is unspecified.
+pub fn analyze_on_test_cpu(input: &str) -> Result<LexAnalysis, String> {
+    lex_raw(input)?;
+    let mut bytes = input.as_bytes().to_vec();
+    mask_bom(&mut bytes);
+    mask_shebang(&mut bytes);
+
+    let dfa = StreamingDfa::new();
+    let kind_of = |state: usize| TokenKind::from_u32(dfa.token_map[state]);
+    let n = bytes.len();
+    let mut state = dfa.start as usize;
+    let mut flags = Vec::with_capacity(n);
+    let mut kinds = Vec::with_capacity(n);
+    for (i, &b) in bytes.iter().enumerate() {
+        let next = dfa.next[state][b as usize];
+        let after = next.state as usize;
+        flags.push(boundary_flags(next.emit, i + 1 == n, kind_of(after)));
+        kinds.push((kind_of(state), kind_of(after)));
+        state = after;
+    }
+    let s_all: Vec<u32> = flags
+        .iter()
+        .scan(0, |sum, &f| {
+            *sum += u32::from(f & PF_EMIT != 0) + u32::from(f & PF_EOF != 0);
+            Some(*sum)
+        })
+        .collect();
+
+    let mut words: AnalysisWords = Default::default();
+    words[0] = s_all.last().copied().unwrap_or(0);
+    words[4] = bytes.iter().filter(|&&b| b == b'\n').count() as u32;
+    for (i, (&f, &(emit, eof))) in flags.iter().zip(&kinds).enumerate() {
+        let mut rank = if i == 0 { 0 } else { s_all[i - 1] };
+        for (bit, end, kind) in [(PF_EMIT, i, emit), (PF_EOF, i + 1, eof)] {
+            if f & bit == 0 {
+                continue;
+            }
+            rank += 1;
+            let start = end_of_rank(&s_all, &flags, rank - 1);
+            let len = (end - start) as u32;
+            if is_kept(kind) {
+                words[1] += 1;
+                words[3] += len;
+                words[5] = words[5].max(!(start as u32));
+            }
+            if matches!(kind, Some(TokenKind::LineComment | TokenKind::BlockComment)) {
+                words[2] += len;
+            }
+            if rank == 1 {
+                words[6] = !(end as u32);
+            }
+        }
+    }
+    Ok(analysis::decode(input.as_bytes(), &words))
+}
+
+/// Test CPU mirror of the layout-fact passes for
+/// `LexOptions::layout_facts` parity.
+///
+/// Writes the kernels' words the way they do, from the gap between kept
+/// tokens of the masked bytes' all-boundary stream, then decodes them. Fails
+/// where [`lex_on_test_cpu_all`] does.
+pub fn layout_facts_on_test_cpu(input: &str) -> Result<Vec<LayoutFacts>, String> {
+    lex_raw(input)?;
+    let mut bytes = input.as_bytes().to_vec();
+    mask_bom(&mut bytes);
+    mask_shebang(&mut bytes);
+    let masked = String::from_utf8(bytes).expect("masking keeps UTF-8");
+
+    let mut words = Vec::new();
+    let mut prev_end = 0;
+    let mut line_comments = 0;
+    let mut line_indent = 0;
+    for token in lex_raw(&masked)? {
+        if !is_kept(Some(token.kind)) {
+            line_comments += usize::from(token.kind == TokenKind::LineComment);
+            continue;
+        }
+        let gap = &masked.as_bytes()[prev_end..token.start()];
+        let newlines = gap.iter().filter(|&&b| b == b'\n').count();
+        // Only a token that starts a line measures the indentation; the rest
+        // of the line copies it.
+        if words.is_empty() || newlines > 0 {
+            let line_start = gap
+                .iter()
+                .rposition(|&b| b == b'\n')
+                .map_or(0, |last| prev_end + last + 1);
+            line_indent = masked.as_bytes()[line_start..]
+                .iter()
+                .take_while(|&&b| b == b' ' || b == b'\t')
+                .count();
+        }
+        words.push(newlines as u32);
+        words.push(layout::pack_indent_and_comments(line_indent, line_comments));
+        prev_end = token.end();
+        line_comments = 0;
+    }
+    Ok(layout::decode(input.as_bytes(), &words))
+}
+
+/// Output of [`lex_on_test_cpu_recovering`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecoveredLex {
+    /// Every DFA token, as in [`lex_on_test_cpu_all`].
+    pub all_tokens: Vec<Token>,
+    /// Byte ranges no token covers, in order and never adjacent; together
+    /// with `all_tokens` they tile the input.
+    pub errors: Vec<Span>,
+    /// DFA transitions taken, one per byte after a BOM or shebang prefix.
+    pub dfa_steps: usize,
+}
+
+impl RecoveredLex {
+    /// Kept tokens with the lexer-owned retags, as [`lex_on_test_cpu`]
+    /// returns them for an input without errors. `input` must be the bytes
+    /// that were lexed.
+    pub fn kept_tokens(&self, input: &[u8]) -> Vec<Token> {
+        let walked = WalkedTokens {
+            bytes: input,
+            tokens: self.all_tokens.clone().into_iter(),
+            error: None,
+        };
+        KeptTokens::over(walked, u32::MAX, None).flatten().collect()
+    }
+
+    fn push_error(&mut self, start: usize, end: usize) {
+        match self.errors.last_mut() {
+            Some(last) if last.end as usize == start => last.end = end as u32,
+            _ => self.errors.push(Span::from_usize(start, end - start)),
+        }
+    }
+}
+
+/// Lexes arbitrary bytes, recovering from every error instead of stopping
+/// at the first.
+///
+/// A byte the DFA rejects ends the token before it, when that token is
+/// complete, and is reported as an error together with any partial token it
+/// cut short; the walk then restarts from the start state after it. A token
+/// ended in a non-accepting state, mid-walk or at the end of input, becomes
+/// an error span as well. Each byte is fed to the DFA at most once, so the
+/// walk is linear in the input with no lookahead or backtracking.
+///
+/// Bytes need not be UTF-8; the GPU lexer has no such mode and this is for
+/// fuzzing the oracle itself. An input [`lex_on_test_cpu_all`] accepts
+/// yields the same tokens and no errors.
+pub fn lex_on_test_cpu_recovering(input: &[u8]) -> RecoveredLex {
+    let dfa = StreamingDfa::new();
+    let start = dfa.start as usize;
+    let reject = dfa.reject as usize;
+    let n = input.len();
+    let mut out = RecoveredLex::default();
+    let mut at = 0;
+    if let Some(token) = prefix_token(input) {
+        at = token.len();
+        out.all_tokens.push(token);
+    }
+    let mut state = start;
+    let mut tok_start = at;
+    while at < n {
+        let i = at;
+        let next = dfa.next[state][input[i] as usize];
+        let kind = TokenKind::from_u32(dfa.token_map[state]);
+        out.dfa_steps += 1;
+        at += 1;
+        if next.state as usize == reject {
+            let error_start = match kind {
+                Some(kind) if next.emit && tok_start < i => {
+                    out.all_tokens
+                        .push(Token::new(kind, tok_start, i - tok_start));
+                    i
+                }
+                _ => tok_start,
+            };
+            out.push_error(error_start, at);
+            state = start;
+            tok_start = at;
+            continue;
+        }
+        state = next.state as usize;
+        if next.emit {
+            match kind {
+                Some(kind) => out
+                    .all_tokens
+                    .push(Token::new(kind, tok_start, i - tok_start)),
+                None if tok_start < i => out.push_error(tok_start, i),
+                None => {}
+            }
+            tok_start = i;
+        }
+    }
+    if tok_start < n {
+        match TokenKind::from_u32(dfa.token_map[state]) {
+            Some(kind) => out
+                .all_tokens
+                .push(Token::new(kind, tok_start, n - tok_start)),
+            None => out.push_error(tok_start, n),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{
+        dev::generator::{SourceGenConfig, gen_source},
+        lexer::{
+            boundary::{PF_KEEP_EMIT, PF_KEEP_EOF, is_skip_kind, keep_flags},
+            kind_packing::KindPacking,
+        },
+    };
+
+    /// Kept and all-boundary token streams produced by [`gpu_boundary_model`].
+    #[derive(Debug, PartialEq)]
+    struct ModelStreams {
+        kept: Vec<Token>,
+        /// One-based all-boundary index of each kept token, as in
+        /// `all_index_compact`.
+        kept_all_index: Vec<usize>,
+        all: Vec<Token>,
+    }
+
+    /// Host model of `dfa_03_apply_block_prefix`, `compact_boundaries_all`,
+    /// both kept-stream compactions, and the token start recovery in
+    /// `tokens_build`, for a single-file input. Mirrors the shader index
+    /// arithmetic, not just its intent, so EOF handling differences show up
+    /// here. Both `tok_types` packings must give the same streams, and the
+    /// keep-channel compaction (`compact_boundaries_kept`) the same kept
+    /// tokens as the late filter (the `keep_*` rank scan with `compact_kept`).
+    fn gpu_boundary_model(src: &str) -> ModelStreams {
+        let wide = gpu_boundary_model_packed(src, KindPacking::Wide);
+        assert_eq!(
+            gpu_boundary_model_packed(src, KindPacking::Narrow),
+            wide,
+            "{src:?}: narrow tok_types packing"
+        );
+        wide
+    }
+
+    fn gpu_boundary_model_packed(src: &str, packing: KindPacking) -> ModelStreams {
+        let dfa = StreamingDfa::new();
+        let bytes = src.as_bytes();
+        let n = bytes.len();
+        let kind_of = |state: usize| TokenKind::from_u32(dfa.token_map[state]);
+
+        let mut state = dfa.start as usize;
+        let mut flags = Vec::with_capacity(n);
+        let mut tok_types = vec![0u32; packing.words(n)];
+        for (i, &b) in bytes.iter().enumerate() {
+            let next = dfa.next[state][b as usize];
+            let after = next.state as usize;
+            let f = boundary_flags(next.emit, i + 1 == n, kind_of(after));
+            let f = f | keep_flags(f, kind_of(state), kind_of(after));
+            let present =
+                |bit: u32, kind: Option<TokenKind>| kind.filter(|_| f & bit != 0).map(|k| k as u32);
+            if f & (PF_EMIT | PF_EOF) != 0 {
+                packing.pack(
+                    &mut tok_types,
+                    i,
+                    present(PF_EMIT, kind_of(state)),
+                    present(PF_EOF, kind_of(after)),
+                );
+            }
+            flags.push(f);
+            state = after;
+        }
+
+        let bit = |f: u32, mask: u32| usize::from(f & mask != 0);
+        let s_all = flags
+            .iter()
+            .scan(0usize, |sum, &f| {
+                *sum += bit(f, PF_EMIT) + bit(f, PF_EOF);
+                Some(*sum)
+            })
+            .collect::<Vec<_>>();
+
+        let mut all = vec![(0usize, None); s_all.last().copied().unwrap_or(0)];
+        for (i, &f) in flags.iter().enumerate() {
+            if f & (PF_EMIT | PF_EOF) == 0 {
+                continue;
+            }
+            let prev = if i == 0 { 0 } else { s_all[i - 1] };
+            let (emit_kind, eof_kind) = packing.unpack(&tok_types, i);
+            let (emit_kind, eof_kind) = (
+                TokenKind::from_u32(emit_kind),
+                TokenKind::from_u32(eof_kind),
+            );
+            if s_all[i] - prev == 2 {
+                all[s_all[i] - 2] = (i, emit_kind);
+                all[s_all[i] - 1] = (i + 1, eof_kind);
+            } else if f & PF_EOF != 0 {
+                all[s_all[i] - 1] = (i + 1, eof_kind);
+            } else {
+                all[s_all[i] - 1] = (i, emit_kind);
+            }
+        }
+
+        // The kept stream ranks the compacted all-boundary tokens by kind and
+        // copies each kept one, with its one-based all-boundary index.
+        let s_keep = all
+            .iter()
+            .scan(0usize, |sum, &(_, kind)| {
+                *sum += usize::from(is_kept(kind));
+                Some(*sum)
+            })
+            .collect::<Vec<_>>();
+        let mut kept = vec![(0usize, None, 0usize); s_keep.last().copied().unwrap_or(0)];
+        for (j, &(end, kind)) in all.iter().enumerate() {
+            if is_kept(kind) {
+                kept[s_keep[j] - 1] = (end, kind, j + 1);
+            }
+        }
+
+        // The keep channel ranks the kept boundaries by their flags, and
+        // recovers each one's all-boundary index from the ALL ranks.
+        let s_keep_flags = flags
+            .iter()
+            .scan(0usize, |sum, &f| {
+                *sum +=
+                    bit(f, PF_EMIT) * bit(f, PF_KEEP_EMIT) + bit(f, PF_EOF) * bit(f, PF_KEEP_EOF);
+                Some(*sum)
+            })
+            .collect::<Vec<_>>();
+        let mut kept_by_flags = vec![(0usize, None, 0usize); kept.len()];
+        for (i, &f) in flags.iter().enumerate() {
+            let kept_emit = f & PF_EMIT != 0 && f & PF_KEEP_EMIT != 0;
+            let kept_eof = f & PF_EOF != 0 && f & PF_KEEP_EOF != 0;
+            if !(kept_emit || kept_eof) {
+                continue;
+            }
+            let (emit_kind, eof_kind) = packing.unpack(&tok_types, i);
+            let (emit_kind, eof_kind) = (
+                TokenKind::from_u32(emit_kind),
+                TokenKind::from_u32(eof_kind),
+            );
+            let pref = s_keep_flags[i];
+            let prev = if i == 0 { 0 } else { s_keep_flags[i - 1] };
+            let all_index = s_all[i];
+            if pref - prev == 2 {
+                kept_by_flags[pref - 2] = (i, emit_kind, all_index - 1);
+                kept_by_flags[pref - 1] = (i + 1, eof_kind, all_index);
+                continue;
+            }
+            let prev_all = if i == 0 { 0 } else { s_all[i - 1] };
+            let all_index = if all_index - prev_all == 2 && f & PF_KEEP_EMIT != 0 {
+                all_index - 1
+            } else {
+                all_index
+            };
+            kept_by_flags[pref - 1] = if kept_eof {
+                (i + 1, eof_kind, all_index)
+            } else {
+                (i, emit_kind, all_index)
+            };
+        }
+        assert_eq!(
+            s_keep_flags.last().copied().unwrap_or(0),
+            kept.len(),
+            "{src:?}: kept token count by keep channel"
+        );
+        assert_eq!(kept_by_flags, kept, "{src:?}: kept tokens by keep channel");
+
+        let kept_all_index = kept.iter().map(|&(_, _, all_index)| all_index).collect();
+        let kept = kept
+            .into_iter()
+            .map(|(end, kind, all_index)| {
+                let start = if all_index <= 1 {
+                    0
+                } else {
+                    all[all_index - 2].0
+                };
+                let kind = kind.expect("kept boundary carries a kind");
+                Token::new(kind, start, end - start)
+            })
+            .collect();
+        let mut start = 0;
+        let all = all
+            .into_iter()
+            .map(|(end, kind)| {
+                let kind = kind.expect("boundary carries a kind");
+                let token = Token::new(kind, start, end - start);
+                start = end;
+                token
+            })
+            .collect();
+        ModelStreams {
+            kept,
+            kept_all_index,
+            all,
+        }
+    }
+
+    /// Host form of the `tokens_build` ordering check: kept tokens with no
+    /// skipped token between them must touch.
+    fn assert_adjacent_kept_tokens_touch(src: &str, model: &ModelStreams) {
+        for k in 1..model.kept.len() {
+            if model.kept_all_index[k] != model.kept_all_index[k - 1] + 1 {
+                continue;
+            }
+            let prev = &model.kept[k - 1];
+            assert_eq!(
+                model.kept[k].start(),
+                prev.end(),
+                "{src:?}: kept token {k} overlaps or skips past its neighbour"
+            );
+        }
+    }
+
+    fn texts<'a>(src: &'a str, tokens: &[Token]) -> Vec<&'a str> {
+        tokens
+            .iter()
+            .map(|token| &src[token.span.range()])
+            .collect()
+    }
+
+    fn kinds(src: &str) -> Vec<TokenKind> {
+        lex_on_test_cpu(src)
+            .expect("lex")
+            .into_iter()
+            .map(|token| token.kind)
+            .collect()
+    }
+
+    #[test]
+    fn lexes_empty_input_as_empty_stream() {
+        assert_eq!(lex_on_test_cpu("").expect("lex empty input"), Vec::new());
+    }
+
+    #[test]
+    fn skips_leading_shebang_line() {
+        use TokenKind::*;
+
+        assert_eq!(kinds("#!/usr/bin/env lanius"), Vec::new());
+        let all = lex_on_test_cpu_all("#!/usr/bin/env lanius").expect("lex shebang");
+        assert_eq!(all, vec![Token::new(Shebang, 0, 21)]);
+
+        let src = "#!/usr/bin/env lanius\nfn main() {}\n";
+        assert_eq!(kinds(src), vec![Fn, Ident, LParen, RParen, LBrace, RBrace]);
+        let all = lex_on_test_cpu_all(src).expect("lex shebang and code");
+        assert_eq!((all[0].kind, all[0].len()), (Shebang, 21));
+        assert_eq!((all[1].kind, all[1].start()), (White, 21));
+    }
+
+    #[test]
+    fn skips_leading_bom() {
+        use TokenKind::*;
+
+        assert_eq!(kinds("\u{feff}"), Vec::new());
+        let all = lex_on_test_cpu_all("\u{feff}").expect("lex BOM");
+        assert_eq!(all, vec![Token::new(Bom, 0, 3)]);
+
+        let src = "\u{feff} x = \"\u{feff}\"";
+        assert_eq!(kinds(src), vec![Ident, Assign, String]);
+        let all = lex_on_test_cpu_all(src).expect("lex BOM and code");
+        assert_eq!((all[0].kind, all[0].len()), (Bom, 3));
+        assert_eq!((all[1].kind, all[1].start(), all[1].len()), (White, 3, 1));
+        assert_eq!((all[6].kind, all[6].start(), all[6].len()), (String, 8, 5));
+    }
+
+    #[test]
+    fn rejects_bom_after_offset_zero() {
+        assert!(lex_on_test_cpu("x\u{feff}").is_err());
+        assert!(lex_on_test_cpu(" \u{feff}x").is_err());
+        assert!(lex_on_test_cpu("\u{feff}\u{feff}").is_err());
+        assert!(lex_on_test_cpu("\u{feff}#!/bin/lanius").is_err());
+    }
+
+    #[test]
+    fn rejects_hash_outside_a_leading_shebang() {
+        assert!(lex_on_test_cpu("fn main() {}\n#!/bin/lanius\n").is_err());
+        assert!(lex_on_test_cpu(" #!/bin/lanius").is_err());
+        assert!(lex_on_test_cpu("#/bin/lanius").is_err());
+        assert!(lex_on_test_cpu("#").is_err());
+    }
+
+    #[test]
+    fn keeps_plus_and_minus_raw_at_lexer_boundary() {
+        use TokenKind::*;
+
+        assert_eq!(
+            kinds("-a + +b - c"),
+            vec![Minus, Ident, Plus, Plus, Ident, Minus, Ident]
+        );
+    }
+
+    #[test]
+    fn keeps_delimiters_raw_at_lexer_boundary() {
+        use TokenKind::*;
+
+        assert_eq!(
+            kinds("f(a)[b] + [c]"),
+            vec![
+                Ident, LParen, Ident, RParen, LBracket, Ident, RBracket, Plus, LBracket, Ident,
+                RBracket
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_close_delimiters_raw_at_lexer_boundary() {
+        use TokenKind::*;
+
+        assert_eq!(
+            kinds("(a) f(b)[c] + [d]"),
+            vec![
+                LParen, Ident, RParen, Ident, LParen, Ident, RParen, LBracket, Ident, RBracket,
+                Plus, LBracket, Ident, RBracket
+            ]
+        );
+    }
+
+    #[test]
+    fn retags_keywords_from_ident_lexemes() {
+        use TokenKind::*;
+
+        assert_eq!(
+            kinds(
+                "pub fn f() { let x = 1; if (x) { return x; } else { while (x) { break; continue; } } }"
+            ),
+            vec![
+                Pub, Fn, Ident, LParen, RParen, LBrace, Let, Ident, Assign, Int, Semicolon, If,
+                LParen, Ident, RParen, LBrace, Return, Ident, Semicolon, RBrace, Else, LBrace,
+                While, LParen, Ident, RParen, LBrace, Break, Semicolon, Continue, Semicolon,
+                RBrace, RBrace, RBrace
+            ]
+        );
+    }
+
+    #[test]
+    fn lexes_raw_strings_and_escaped_strings() {
+        use TokenKind::*;
+
+        let src = r#"r"a\b" "a\"b" "\\" r"" r"\""#;
+        let tokens = lex_on_test_cpu(src).expect("lex");
+        assert_eq!(
+            texts(src, &tokens),
+            vec![r#"r"a\b""#, r#""a\"b""#, r#""\\""#, r#"r"""#, r#"r"\""#]
+        );
+        assert_eq!(
+            kinds(src),
+            vec![RawString, String, String, RawString, RawString]
+        );
+    }
+
+    #[test]
+    fn lone_r_without_quote_stays_identifier() {
+        use TokenKind::*;
+
+        assert_eq!(
+            kinds("r x rx r( r0 return"),
+            vec![Ident, Ident, Ident, Ident, LParen, Ident, Return]
+        );
+    }
+
+    #[test]
+    fn rejects_unterminated_raw_string_at_eof() {
+        assert!(lex_on_test_cpu("a = r\"abc").is_err());
+        assert!(lex_on_test_cpu("a = r\"abc\n\"").is_err());
+    }
+
+    #[test]
+    fn qualified_module_and_import_paths_are_raw_identifier_segments() {
+        use TokenKind::*;
+
+        assert_eq!(
+            kinds("module app::main;\nimport core::f32;"),
+            vec![
+                Module, Ident, Colon, Colon, Ident, Semicolon, Import, Ident, Colon, Colon, Ident,
+                Semicolon
+            ]
+        );
+    }
+
+    #[test]
+    fn qualified_type_paths_are_raw_identifier_segments() {
+        use TokenKind::*;
+
+        assert_eq!(
+            kinds("let value: core::f32 = 0.0;"),
+            vec![
+                Let, Ident, Colon, Ident, Colon, Colon, Ident, Assign, Float, Semicolon
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_kinds_keep_the_dfa_classification_under_retags() {
+        use TokenKind::*;
+
+        let pairs: Vec<_> = lex_on_test_cpu("let r = 1..=n; f(x)")
+            .unwrap()
+            .iter()
+            .map(|token| (token.raw_kind, token.kind))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (Ident, Let),
+                (Ident, Ident),
+                (Assign, Assign),
+                (Float, Int),
+                (Dot, DotDotEqual),
+                (Assign, Assign),
+                (Ident, Ident),
+                (Semicolon, Semicolon),
+                (Ident, Ident),
+                (LParen, LParen),
+                (Ident, Ident),
+                (RParen, RParen),
+            ]
+        );
+        assert!(
+            lex_on_test_cpu_all("let r")
+                .unwrap()
+                .iter()
+                .all(|token| token.raw_kind == token.kind)
+        );
+    }
+
+    #[test]
+    fn lexes_numeric_exclusive_ranges_without_stealing_float_literals() {
+        use TokenKind::*;
+
+        assert_eq!(
+            kinds("0..samples 1.0 1. .5 ..rest 1..=end"),
+            vec![
+                Int,
+                DotDot,
+                Ident,
+                Float,
+                Float,
+                Float,
+                DotDot,
+                Ident,
+                Int,
+                DotDotEqual,
+                Assign,
+                Ident
+            ]
+        );
+    }
+
+    #[test]
+    fn trailing_line_comment_matches_gpu_model_with_and_without_newline() {
+        for src in [
+            "a = 1 // tail",
+            "a = 1 // tail\n",
+            "a = 1// tail",
+            "a = 1// tail\n",
+            "a = 1\n// tail",
+            "a = 1 //",
+            "a//",
+            "//",
+            "// only",
+        ] {
+            let oracle = lex_raw_kept(src).expect("lex");
+            assert_eq!(gpu_boundary_model(src).kept, oracle, "{src:?}");
+        }
+    }
+
+    #[test]
+    fn trailing_line_comment_keeps_preceding_token() {
+        let src = "a = 1// tail";
+        let tokens = lex_raw_kept(src).expect("lex");
+        assert_eq!(texts(src, &tokens), vec!["a", "=", "1"]);
+
+        let src = "a = 1 \t// tail";
+        let tokens = lex_raw_kept(src).expect("lex");
+        assert_eq!(texts(src, &tokens), vec!["a", "=", "1"]);
+    }
+
+    #[test]
+    fn gpu_model_matches_oracle_at_every_eof_cut() {
+        let src = "let x = a/b; // c\n/* d */ y+=2 // e";
+        for end in 1..=src.len() {
+            let cut = &src[..end];
+            let Ok(oracle) = lex_raw_kept(cut) else {
+                continue;
+            };
+            let model = gpu_boundary_model(cut);
+            assert_eq!(model.kept, oracle, "{cut:?}");
+            assert_adjacent_kept_tokens_touch(cut, &model);
+            assert_eq!(
+                model.all,
+                lex_on_test_cpu_all(cut).expect("lex all"),
+                "{cut:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn final_byte_emit_and_eof_tokens_keep_emit_first_order() {
+        use TokenKind::*;
+
+        // (input, kept kinds, kept all-boundary indices, all-stream kinds)
+        let cases: [(&str, &[TokenKind], &[usize], &[TokenKind]); 7] = [
+            ("a", &[Ident], &[1], &[Ident]),
+            (" ", &[], &[], &[White]),
+            (")a", &[RParen, Ident], &[1, 2], &[RParen, Ident]),
+            ("a)", &[Ident, RParen], &[1, 2], &[Ident, RParen]),
+            (" a", &[Ident], &[2], &[White, Ident]),
+            ("a ", &[Ident], &[1], &[Ident, White]),
+            ("  ", &[], &[], &[White]),
+        ];
+        for (src, kept_kinds, kept_all_index, all_kinds) in cases {
+            let model = gpu_boundary_model(src);
+            let oracle = lex_raw_kept(src).expect("lex");
+            assert_eq!(model.kept, oracle, "{src:?}");
+            assert_eq!(
+                model.kept.iter().map(|t| t.kind).collect::<Vec<_>>(),
+                kept_kinds,
+                "{src:?}"
+            );
+            assert_eq!(model.kept_all_index, kept_all_index, "{src:?}");
+            assert_eq!(
+                model.all.iter().map(|t| t.kind).collect::<Vec<_>>(),
+                all_kinds,
+                "{src:?}"
+            );
+            assert_eq!(model.all, lex_on_test_cpu_all(src).expect("lex all"));
+            assert_adjacent_kept_tokens_touch(src, &model);
+        }
+    }
+
+    /// The kept stream as the lexer chose it before filtering moved late:
+    /// each boundary decides whether to keep its token as the DFA emits it.
+    fn lex_kept_while_streaming(src: &str) -> Vec<Token> {
+        let dfa = StreamingDfa::new();
+        let bytes = src.as_bytes();
+        let mut state = dfa.start as usize;
+        let mut tok_start = shebang_len(bytes).unwrap_or_else(|| bom_len(bytes));
+        let mut out = Vec::new();
+        let mut keep = |kind_u32: u32, start: usize, end: usize| {
+            if let Some(kind) = TokenKind::from_u32(kind_u32)
+                && !is_skip_kind(kind)
+            {
+                out.push(Token::new(kind, start, end - start));
+            }
+        };
+        for (i, &b) in bytes.iter().enumerate().skip(tok_start) {
+            let next = dfa.next[state][b as usize];
+            if next.emit {
+                keep(dfa.token_map[state], tok_start, i);
+                tok_start = i;
+            }
+            state = next.state as usize;
+        }
+        if tok_start < bytes.len() {
+            keep(dfa.token_map[state], tok_start, bytes.len());
+        }
+        out
+    }
+
+    #[test]
+    fn late_kept_filter_matches_streaming_keep_decisions() {
+        let mut rng = StdRng::seed_from_u64(875);
+        for profile in ["default", "operator_heavy", "comment_heavy", "bracket_deep"] {
+            let config = SourceGenConfig {
+                target_len: 20_000,
+                ..SourceGenConfig::from_profile(profile).expect("profile")
+            };
+            let mut sources = vec![
+                "#!/usr/bin/env lanius\nfn main() {} // tail".to_string(),
+                "a = 1 /* c */ + 2 // d".to_string(),
+            ];
+            sources.extend((0..3).map(|_| gen_source(&mut rng, &config)));
+            for src in &sources {
+                let Ok(filtered) = lex_raw_kept(src) else {
+                    continue;
+                };
+                assert_eq!(filtered, lex_kept_while_streaming(src), "{profile}");
+                // The model does not mask a shebang line or BOM the way the
+                // driver does.
+                if shebang_len(src.as_bytes()).is_none() && bom_len(src.as_bytes()) == 0 {
+                    assert_eq!(gpu_boundary_model(src).kept, filtered, "{profile}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn boundary_analysis_matches_the_all_boundary_stream() {
+        let mut rng = StdRng::seed_from_u64(935);
+        let mut sources = [
+            "",
+            "x",
+            "\n\n",
+            "a = 1 /* c */ + 2 // d",
+            "#!/usr/bin/env lanius\nfn main() {} // tail",
+            "#!",
+            "\u{FEFF}",
+            "\u{FEFF}fn",
+            "\u{FEFF}  // c\nlet x = 1;\n",
+            "// only a comment",
+        ]
+        .map(String::from)
+        .to_vec();
+        for profile in crate::dev::generator::PROFILES {
+            let config = SourceGenConfig {
+                target_len: 4096,
+                ..SourceGenConfig::from_profile(profile).unwrap()
+            };
+            sources.push(gen_source(&mut rng, &config));
+        }
+        for src in &sources {
+            let all = lex_on_test_cpu_all(src).expect("lex");
+            assert_eq!(
+                analyze_on_test_cpu(src),
+                Ok(LexAnalysis::from_all_tokens(src, &all)),
+                "{src:?}"
+            );
+        }
+        assert!(analyze_on_test_cpu("@a").is_err());
+    }
+
+    #[test]
+    fn layout_fact_mirror_matches_the_source() {
+        let mut rng = StdRng::seed_from_u64(936);
+        let mut sources = [
+            "",
+            "a b  c",
+            "\n\n  // lead\n\tfn f() {\n\t\tx\n}",
+            "a /* x\n  y */ b // c\n   d",
+            "#!/usr/bin/env lanius\nfn main() {}",
+            "#!/usr/bin/env lanius\n// c\n  x",
+            "\u{FEFF}  a\n b",
+            "\u{FEFF}\n\t\t a",
+            "// only a comment",
+        ]
+        .map(String::from)
+        .to_vec();
+        for profile in crate::dev::generator::PROFILES {
+            let config = SourceGenConfig {
+                target_len: 4096,
+                ..SourceGenConfig::from_profile(profile).unwrap()
+            };
+            sources.push(gen_source(&mut rng, &config));
+        }
+        for src in &sources {
+            let tokens = lex_on_test_cpu(src).expect("lex");
+            let all = lex_on_test_cpu_all(src).expect("lex");
+            assert_eq!(
+                layout_facts_on_test_cpu(src),
+                Ok(layout::layout_facts_of(src.as_bytes(), &tokens, &all)),
+                "{src:?}"
+            );
+        }
+        let facts = layout_facts_on_test_cpu("\n\n  // lead\n\tfn f() {\n\t\tx\n}").unwrap();
+        let shape: Vec<_> = facts
+            .iter()
+            .map(|f| (f.newlines_before, f.indent_col, f.had_line_comment_before))
+            .collect();
+        assert_eq!(
+            shape,
+            [
+                (3, 1, true),
+                (0, 1, false),
+                (0, 1, false),
+                (0, 1, false),
+                (0, 1, false),
+                (1, 2, false),
+                (1, 0, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_prefix_before_final_byte_produces_no_kept_boundary() {
+        // A rejected or unterminated first byte never closes a token, so the
+        // accepting-looking final byte cannot claim a start from it.
+        for src in ["@a", "\"a", "'a"] {
+            assert!(lex_raw_kept(src).is_err(), "{src:?}");
+            let model = gpu_boundary_model(src);
+            assert!(model.kept.is_empty(), "{src:?}: {:?}", model.kept);
+            assert!(model.all.is_empty(), "{src:?}: {:?}", model.all);
+        }
+    }
+
+    #[test]
+    fn accept_states_distinguish_numeric_literal_forms() {
+        let src = "0 7 1_000 1_ 0x1F 0xF_F 0b101 0b1_0 0o17 0o1_7 1.5 x..=y 1..";
+        let (tokens, states) = lex_on_test_cpu_with_accept_states(src).expect("lex");
+        let got = texts(src, &tokens)
+            .into_iter()
+            .zip(states.iter().map(|&state| S::from_idx(state as usize)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            got,
+            [
+                ("0", Some(S::Zero)),
+                ("7", Some(S::Int)),
+                ("1_000", Some(S::Int)),
+                ("1_", Some(S::IntAfterUnderscore)),
+                ("0x1F", Some(S::Hex)),
+                ("0xF_F", Some(S::Hex)),
+                ("0b101", Some(S::Bin)),
+                ("0b1_0", Some(S::Bin)),
+                ("0o17", Some(S::Oct)),
+                ("0o1_7", Some(S::Oct)),
+                ("1.5", Some(S::FloatFrac)),
+                ("x", Some(S::Ident)),
+                ("..", Some(S::DotDotDone)),
+                ("=", Some(S::AfterAssign)),
+                ("y", Some(S::Ident)),
+                ("1", Some(S::Int)),
+                ("..", Some(S::DotDotDone)),
+            ]
+        );
+    }
+
+    #[test]
+    fn all_stream_keeps_skipped_tokens_with_raw_kinds() {
+        use TokenKind::*;
+
+        let src = "let x = 1..2 // c\n/* d */";
+        let tokens = lex_on_test_cpu_all(src).expect("lex all");
+        assert_eq!(
+            tokens.iter().map(|token| token.kind).collect::<Vec<_>>(),
+            vec![
+                Ident,
+                White,
+                Ident,
+                White,
+                Assign,
+                White,
+                Float,
+                Float,
+                White,
+                LineComment,
+                White,
+                BlockComment
+            ]
+        );
+        assert_eq!(
+            texts(src, &tokens).concat(),
+            src,
+            "all-boundary tokens tile the input"
+        );
+        assert_eq!(gpu_boundary_model(src).all, tokens);
+    }
+
+    #[test]
+    fn max_token_len_rejects_long_skipped_tokens_only() {
+        let src = format!("let x = 1; /*{}*/ x", "a".repeat(30));
+        let err = lex_on_test_cpu_with_max_token_len(&src, 16).unwrap_err();
+        assert_eq!(
+            err,
+            "BlockComment token at byte 11 is longer than the 16-byte token limit"
+        );
+        assert_eq!(
+            lex_on_test_cpu_with_max_token_len(&src, 34),
+            lex_on_test_cpu(&src)
+        );
+    }
+
+    #[test]
+    fn iterator_matches_collected_stream_and_stops_early() {
+        let src = "let r = 0..=n; for i in 1..2 { f(i) }";
+        let lexer = TestCpuLexer::default();
+        let streamed = lexer.iter(src).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(streamed, lex_on_test_cpu(src).unwrap());
+
+        let first: Vec<_> = lexer.iter(src).take(3).map(Result::unwrap).collect();
+        assert_eq!(first, streamed[..3]);
+
+        // Tokens before a DFA reject are still yielded.
+        let mut rejected = lexer.iter("x = `");
+        assert_eq!(rejected.next().unwrap().unwrap().kind, TokenKind::Ident);
+        assert_eq!(rejected.next().unwrap().unwrap().kind, TokenKind::Assign);
+        assert!(rejected.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn options_select_what_the_oracle_returns() {
+        let src = "let x = 1..=2; let s = \"a\\tb\"; // c";
+        let tokens = lex_on_test_cpu(src).unwrap();
+
+        let full = lex_on_test_cpu_with_options(src, LexOptions::default()).unwrap();
+        assert_eq!(full.tokens, tokens);
+        assert_eq!(full.token_count, tokens.len());
+        assert!(full.accept_states.is_empty() && full.all_tokens.is_empty());
+        assert!(full.escape_spans.is_empty());
+        assert!(full.report.is_none());
+
+        let extras = lex_on_test_cpu_with_options(
+            src,
+            LexOptions {
+                capture_accept_states: true,
+                all_tokens: true,
+                capture_string_escapes: true,
+                ..LexOptions::default()
+            },
+        )
+        .unwrap();
+        let (_, states) = lex_on_test_cpu_with_accept_states(src).unwrap();
+        assert_eq!(extras.accept_states, states);
+        let (_, spans) = lex_on_test_cpu_with_escape_spans(src).unwrap();
+        assert_eq!(extras.escape_spans, spans);
+        assert_eq!(
+            spans,
+            [EscapeSpan {
+                span: Span::new(25, 27)
+            }]
+        );
+        assert_eq!(texts(src, &extras.all_tokens).concat(), src);
+        let kept_in_all: Vec<_> = extras
+            .all_tokens
+            .iter()
+            .filter(|token| tokens.iter().any(|kept| kept.start() == token.start()))
+            .copied()
+            .collect();
+        assert_eq!(kept_in_all, tokens);
+
+        let count_only = LexOptions {
+            readback: ReadbackMode::CountOnly,
+            ..LexOptions::default()
+        };
+        assert_eq!(
+            lex_on_test_cpu_with_options(src, count_only).unwrap(),
+            LexOutput {
+                token_count: tokens.len(),
+                ..LexOutput::default()
+            }
+        );
+
+        let none = LexOptions {
+            readback: ReadbackMode::None,
+            max_token_len: 1,
+            ..LexOptions::default()
+        };
+        assert_eq!(
+            lex_on_test_cpu_with_options(src, none).unwrap(),
+            LexOutput::default()
+        );
+        let short = LexOptions {
+            max_token_len: 1,
+            ..count_only
+        };
+        assert!(lex_on_test_cpu_with_options(src, short).is_err());
+    }
+
+    #[test]
+    fn bytes_entry_point_requires_utf8() {
+        let src = "let s = \"é\";";
+        assert_eq!(lex_on_test_cpu_bytes(src.as_bytes()), lex_on_test_cpu(src));
+        let err = lex_on_test_cpu_bytes(b"let \xff = 1;").unwrap_err();
+        assert!(err.starts_with("source is not valid UTF-8"), "{err}");
+    }
+
+    /// Every output of the parallel walk on `chunks` threads, next to the
+    /// sequential one.
+    fn assert_parallel_matches(src: &str, chunks: usize) {
+        let what = format!("{chunks} chunks of {src:?}");
+        let what = &what[..what.len().min(200)];
+        assert_eq!(
+            lex_on_test_cpu_parallel(src, chunks),
+            lex_on_test_cpu(src),
+            "{what}"
+        );
+        assert_eq!(
+            lex_on_test_cpu_all_parallel(src, chunks),
+            lex_on_test_cpu_all(src),
+            "{what}"
+        );
+
+        let mut sequential = Coverage::new();
+        let mut parallel = Coverage::new();
+        assert_eq!(
+            lex_on_test_cpu_with_coverage_parallel(src, chunks, &mut parallel),
+            lex_on_test_cpu_with_coverage(src, &mut sequential),
+            "{what}"
+        );
+        assert_eq!(parallel, sequential, "{what}");
+
+        for max_token_len in [u32::MAX, 8] {
+            let lexer = TestCpuLexer::new(LexOptions {
+                capture_accept_states: true,
+                all_tokens: true,
+                max_token_len,
+                ..LexOptions::default()
+            });
+            assert_eq!(
+                lexer.with_threads(chunks).lex(src),
+                lexer.lex(src),
+                "{what}"
+            );
+        }
+    }
+
+    #[test]
+    fn parallel_walk_matches_sequential_on_generated_sources() {
+        let mut runner = proptest::test_runner::TestRunner::new(proptest::test_runner::Config {
+            cases: 200,
+            failure_persistence: None,
+            ..proptest::test_runner::Config::default()
+        });
+        runner
+            .run(
+                &(
+                    crate::dev::generator::arb_source_gen_config(),
+                    proptest::num::u64::ANY,
+                    2usize..=64,
+                ),
+                |(config, seed, chunks)| {
+                    let src = gen_source(&mut StdRng::seed_from_u64(seed), &config);
+                    assert_parallel_matches(&src, chunks);
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn parallel_walk_splits_large_sources() {
+        let dfa = StreamingDfa::new();
+        let mut rng = StdRng::seed_from_u64(916);
+        for profile in crate::dev::generator::PROFILES {
+            let config = SourceGenConfig {
+                target_len: 200_000,
+                ..SourceGenConfig::from_profile(profile).expect("profile")
+            };
+            let src = gen_source(&mut rng, &config);
+            let starts = chunk_starts(&dfa, src.as_bytes(), 8);
+            // A long string or comment may hold no sync point, and then
+            // lexes as one piece.
+            assert!(
+                starts.len() > 1 || profile.starts_with("long_"),
+                "{profile}: {starts:?}"
+            );
+            assert!(starts.is_sorted(), "{profile}: {starts:?}");
+            for chunks in [2, 8, 61] {
+                assert_parallel_matches(&src, chunks);
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_walk_keeps_sequential_errors_and_prefixes() {
+        let body = "let a = 1 + 2; // c\nfn f() { return \"s\"; }\n".repeat(64);
+        let sources = [
+            format!("{body}@{body}"),
+            format!("{body}/* unterminated {body}"),
+            format!("{body}\"unterminated {body}"),
+            format!("{body}'x{body}"),
+            format!("#!/usr/bin/env lanius\n{body}"),
+            format!("\u{feff}{body}"),
+            format!("{body}1..=2 x..y 3.5 let"),
+        ];
+        for src in &sources {
+            for chunks in [2, 3, 7, 32, 500] {
+                assert_parallel_matches(src, chunks);
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_walk_falls_back_inside_a_long_comment() {
+        let src = format!(
+            "let a = 1;\n/*{}*/\nlet b = 2;\n",
+            "x /* y ".repeat(5 * SPLIT_SEARCH_BYTES / 7)
+        );
+        let starts = chunk_starts(&StreamingDfa::new(), src.as_bytes(), 4);
+        assert_eq!(starts, [0], "no sync point inside the comment");
+        assert_parallel_matches(&src, 4);
+    }
+
+    fn assert_recovered_tiles(input: &[u8]) -> RecoveredLex {
+        let recovered = lex_on_test_cpu_recovering(input);
+        crate::lexer::roundtrip::verify_recovered_partition(input, &recovered)
+            .unwrap_or_else(|err| panic!("{:?}: {err:?}", String::from_utf8_lossy(input)));
+        recovered
+    }
+
+    #[test]
+    fn recovering_walk_matches_the_strict_one_on_valid_sources() {
+        let mut rng = StdRng::seed_from_u64(929);
+        for profile in crate::dev::generator::PROFILES {
+            let config = SourceGenConfig {
+                target_len: 4096,
+                ..SourceGenConfig::from_profile(profile).unwrap()
+            };
+            let src = gen_source(&mut rng, &config);
+            let recovered = assert_recovered_tiles(src.as_bytes());
+            assert_eq!(recovered.errors, [], "{profile}");
+            assert_eq!(
+                Ok(recovered.all_tokens.clone()),
+                lex_on_test_cpu_all(&src),
+                "{profile}"
+            );
+            assert_eq!(
+                Ok(recovered.kept_tokens(src.as_bytes())),
+                lex_on_test_cpu(&src),
+                "{profile}"
+            );
+        }
+        let shebang = "#!/usr/bin/env lanius\nlet a = 1;";
+        let recovered = assert_recovered_tiles(shebang.as_bytes());
+        assert_eq!(
+            recovered.dfa_steps,
+            shebang.len() - recovered.all_tokens[0].len(),
+            "the prefix is not walked"
+        );
+        assert_eq!(Ok(recovered.all_tokens), lex_on_test_cpu_all(shebang));
+    }
+
+    #[test]
+    fn recovering_walk_reports_errors_and_resumes_after_them() {
+        let spans = |tokens: &[Token]| {
+            tokens
+                .iter()
+                .map(|t| (t.kind, t.start(), t.len()))
+                .collect::<Vec<_>>()
+        };
+        let recovered = assert_recovered_tiles(b"a \x01\x01 b");
+        assert_eq!(recovered.errors, [Span::new(2, 4)], "adjacent bytes merge");
+        assert_eq!(
+            spans(&recovered.all_tokens),
+            [
+                (TokenKind::Ident, 0, 1),
+                (TokenKind::White, 1, 1),
+                (TokenKind::White, 4, 1),
+                (TokenKind::Ident, 5, 1),
+            ]
+        );
+
+        let recovered = assert_recovered_tiles(b"x=1;/* open");
+        assert_eq!(recovered.errors, [Span::new(4, 11)]);
+        assert_eq!(recovered.all_tokens.len(), 4);
+
+        for input in [
+            &b""[..],
+            b"\xff\xfe\xfd",
+            b"\"open \\",
+            b"'",
+            b"let s = \"\xc3(\";",
+            b"\xef\xbb\xbf\x00",
+            b"#!\xff\n@",
+            b"1..=@2 .. @@ x",
+        ] {
+            let recovered = assert_recovered_tiles(input);
+            if let Ok(src) = std::str::from_utf8(input) {
+                assert_eq!(
+                    recovered.errors.is_empty(),
+                    lex_on_test_cpu_all(src).is_ok(),
+                    "{src:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn recovering_walk_restarts_the_same_way_at_every_token() {
+        let src = b"let a = 1..=2; @ /* c */ x.y \"s\\\"\" 'c' \x01\x02 3.5e1 >>= y // end";
+        let whole = lex_on_test_cpu_recovering(src);
+        for token in whole.all_tokens.iter().skip(1) {
+            let from = token.start();
+            let suffix = lex_on_test_cpu_recovering(&src[from..]);
+            let shifted: Vec<_> = suffix
+                .all_tokens
+                .iter()
+                .map(|t| Token::new(t.kind, t.start() + from, t.len()))
+                .collect();
+            let rest: Vec<_> = whole
+                .all_tokens
+                .iter()
+                .filter(|t| t.start() >= from)
+                .copied()
+                .collect();
+            assert_eq!(shifted, rest, "from byte {from}");
+            assert_eq!(
+                suffix.errors.len(),
+                whole
+                    .errors
+                    .iter()
+                    .filter(|e| e.start as usize >= from)
+                    .count(),
+                "from byte {from}"
+            );
+        }
+    }
+
+    #[test]
+    fn recovering_walk_is_linear_on_adversarial_inputs() {
+        let len = 1 << 16;
+        let patterns: [&[u8]; 8] = [b"/*", b"\"\\", b"1.", b"..", b"'", b"\x01", b"0x", b"a\xff"];
+        for pattern in patterns {
+            let input: Vec<u8> = pattern.iter().copied().cycle().take(len).collect();
+            let recovered = assert_recovered_tiles(&input);
+            assert_eq!(
+                recovered.dfa_steps,
+                len,
+                "{:?}",
+                String::from_utf8_lossy(pattern)
+            );
+            assert!(recovered.all_tokens.len() + recovered.errors.len() <= len);
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! DFA state and token-kind coverage of the test CPU oracle.
+//!
+//! Fuzz tooling lexes generated sources through
+//! [`lex_on_test_cpu_with_coverage`](crate::lexer::test_cpu::lex_on_test_cpu_with_coverage) and
+//! checks the accumulated [`Coverage`], so table rows the generator never
+//! reaches show up as uncovered states instead of silently going untested on
+//! the GPU.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::lexer::tables::{
+    dfa::{N_STATES, S},
+    tokens::TokenKind,
+};
+
+/// States no valid source reaches; excluded from [`CoverageReport::uncovered`]
+/// by default.
+pub const UNREACHABLE_STATES: &[S] = &[S::Reject];
+
+/// Running visit counts, accumulated across any number of lexes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    /// Times each DFA state was entered, indexed by [`S::idx`].
+    state_visits: [u32; N_STATES],
+    /// Tokens of each kind, indexed by discriminant minus one.
+    kind_tokens: Vec<u32>,
+    /// Bytes covered by tokens of each kind.
+    kind_bytes: Vec<u64>,
+}
+
+impl Default for Coverage {
+    fn default() -> Self {
+        Self {
+            state_visits: [0; N_STATES],
+            kind_tokens: vec![0; TokenKind::ALL.len()],
+            kind_bytes: vec![0; TokenKind::ALL.len()],
+        }
+    }
+}
+
+impl Coverage {
+    /// Creates empty coverage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts one entry into `state`; saturates rather than wrapping.
+    #[inline]
+    pub(super) fn record_state(&mut self, state: usize) {
+        self.state_visits[state] = self.state_visits[state].saturating_add(1);
+    }
+
+    /// Counts one token of `kind` spanning `len` bytes.
+    pub(super) fn record_token(&mut self, kind: TokenKind, len: usize) {
+        let i = kind as usize - 1;
+        self.kind_tokens[i] = self.kind_tokens[i].saturating_add(1);
+        self.kind_bytes[i] = self.kind_bytes[i].saturating_add(len as u64);
+    }
+
+    /// Times `state` was entered.
+    pub fn state_visits(&self, state: S) -> u32 {
+        self.state_visits[state.idx()]
+    }
+
+    /// Tokens of `kind` seen so far.
+    pub fn kind_tokens(&self, kind: TokenKind) -> u32 {
+        self.kind_tokens[kind as usize - 1]
+    }
+
+    /// Bytes covered by tokens of `kind` so far.
+    pub fn kind_bytes(&self, kind: TokenKind) -> u64 {
+        self.kind_bytes[kind as usize - 1]
+    }
+
+    /// Adds `other`'s counts to these.
+    pub fn merge(&mut self, other: &Coverage) {
+        for (a, b) in self.state_visits.iter_mut().zip(other.state_visits) {
+            *a = a.saturating_add(b);
+        }
+        for (a, &b) in self.kind_tokens.iter_mut().zip(&other.kind_tokens) {
+            *a = a.saturating_add(b);
+        }
+        for (a, &b) in self.kind_bytes.iter_mut().zip(&other.kind_bytes) {
+            *a = a.saturating_add(b);
+        }
+    }
+
+    /// Report flagging every state outside `allowlist` entered fewer than
+    /// `min_visits` times.
+    pub fn report(&self, min_visits: u32, allowlist: &[S]) -> CoverageReport {
+        let states: Vec<StateCoverage> = all_states()
+            .map(|state| StateCoverage {
+                state: format!("{state:?}"),
+                visits: self.state_visits(state),
+                allowed: allowlist.contains(&state),
+            })
+            .collect();
+        let uncovered = states
+            .iter()
+            .filter(|s| !s.allowed && s.visits < min_visits)
+            .map(|s| s.state.clone())
+            .collect();
+        let kinds = TokenKind::ALL
+            .iter()
+            .map(|&kind| KindCoverage {
+                kind: format!("{kind:?}"),
+                tokens: self.kind_tokens(kind),
+                bytes: self.kind_bytes(kind),
+            })
+            .collect();
+        CoverageReport {
+            min_visits,
+            states,
+            kinds,
+            uncovered,
+        }
+    }
+}
+
+/// Visits of one DFA state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StateCoverage {
+    pub state: String,
+    pub visits: u32,
+    /// Whether the state is allowlisted as unreachable.
+    pub allowed: bool,
+}
+
+/// Tokens and bytes of one token kind.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KindCoverage {
+    pub kind: String,
+    pub tokens: u32,
+    pub bytes: u64,
+}
+
+/// Snapshot of [`Coverage`] against a visit threshold, serializable as a CI
+/// artifact.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CoverageReport {
+    /// Visits a state needs to count as covered.
+    pub min_visits: u32,
+    /// Every DFA state in table order.
+    pub states: Vec<StateCoverage>,
+    /// Every token kind in discriminant order.
+    pub kinds: Vec<KindCoverage>,
+    /// Names of non-allowlisted states below `min_visits`.
+    pub uncovered: Vec<String>,
+}
+
+impl CoverageReport {
+    /// Whether every non-allowlisted state reached the threshold.
+    pub fn is_complete(&self) -> bool {
+        self.uncovered.is_empty()
+    }
+}
+
+impl fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let checked = self.states.iter().filter(|s| !s.allowed).count();
+        writeln!(
+            f,
+            "DFA states: {}/{checked} covered (min visits {})",
+            checked - self.uncovered.len(),
+            self.min_visits
+        )?;
+        for s in &self.states {
+            let mark = if s.allowed {
+                " (allowlisted)"
+            } else if s.visits < self.min_visits {
+                " UNCOVERED"
+            } else {
+                ""
+            };
+            writeln!(f, "  {:<26} {:>12}{mark}", s.state, s.visits)?;
+        }
+        writeln!(f, "token kinds (tokens / bytes):")?;
+        for k in self.kinds.iter().filter(|k| k.tokens > 0) {
+            writeln!(f, "  {:<26} {:>12} / {}", k.kind, k.tokens, k.bytes)?;
+        }
+        Ok(())
+    }
+}
+
+/// Looks up a DFA state by its `Debug` name, such as `Reject`.
+pub fn state_from_name(name: &str) -> Option<S> {
+    all_states().find(|state| format!("{state:?}") == name)
+}
+
+fn all_states() -> impl Iterator<Item = S> {
+    (0..N_STATES).filter_map(S::from_idx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::test_cpu::lex_on_test_cpu_with_coverage;
+
+    #[test]
+    fn lexing_crafted_input_registers_its_states_and_kinds() {
+        let mut coverage = Coverage::new();
+        let src = "x >>= '\\n'; 0o7_1 // c\n";
+        lex_on_test_cpu_with_coverage(src, &mut coverage).expect("crafted input lexes");
+
+        for state in [
+            S::Start,
+            S::Ident,
+            S::MaybeGreater,
+            S::ShrDone,
+            S::ShrAssignDone,
+            S::InChar,
+            S::CharEscape,
+            S::CharDone,
+            S::OctStart,
+            S::Oct,
+            S::LineComment,
+        ] {
+            assert!(coverage.state_visits(state) > 0, "{state:?} not visited");
+        }
+        assert_eq!(coverage.state_visits(S::Reject), 0);
+        assert_eq!(coverage.state_visits(S::FloatExpAfterUnderscore), 0);
+        assert_eq!(coverage.state_visits(S::Start), 1);
+
+        assert_eq!(coverage.kind_tokens(TokenKind::ShrAssign), 1);
+        assert_eq!(coverage.kind_bytes(TokenKind::ShrAssign), 3);
+        assert_eq!(coverage.kind_tokens(TokenKind::Char), 1);
+        assert_eq!(coverage.kind_bytes(TokenKind::Char), 4);
+        // Skipped tokens count too.
+        assert_eq!(coverage.kind_tokens(TokenKind::LineComment), 1);
+        assert_eq!(coverage.kind_bytes(TokenKind::White), 5);
+
+        let mut twice = coverage.clone();
+        twice.merge(&coverage);
+        assert_eq!(
+            twice.state_visits(S::CharEscape),
+            2 * coverage.state_visits(S::CharEscape)
+        );
+        assert_eq!(twice.kind_bytes(TokenKind::Char), 8);
+    }
+
+    #[test]
+    fn report_lists_uncovered_states_and_serializes() {
+        let mut coverage = Coverage::new();
+        lex_on_test_cpu_with_coverage("a", &mut coverage).expect("lexes");
+
+        let report = coverage.report(1, UNREACHABLE_STATES);
+        assert!(!report.is_complete());
+        assert!(report.uncovered.iter().any(|s| s == "ShrAssignDone"));
+        assert!(
+            !report
+                .uncovered
+                .iter()
+                .any(|s| s == "Ident" || s == "Start")
+        );
+        assert!(!report.uncovered.iter().any(|s| s == "Reject"));
+        assert_eq!(report.states.len(), N_STATES);
+        assert!(report.to_string().contains("ShrAssignDone"));
+
+        let json: serde_json::Value = serde_json::to_value(&report).expect("report serializes");
+        assert_eq!(json["min_visits"], 1);
+        assert_eq!(
+            json["uncovered"].as_array().unwrap().len(),
+            report.uncovered.len()
+        );
+        let ident = json["kinds"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|k| k["kind"] == "Ident")
+            .expect("Ident kind listed");
+        assert_eq!(ident["tokens"], 1);
+        assert_eq!(ident["bytes"], 1);
+
+        assert_eq!(state_from_name("Reject"), Some(S::Reject));
+        assert_eq!(state_from_name("NoSuchState"), None);
+        assert!(coverage.report(0, &[]).is_complete());
+    }
+}
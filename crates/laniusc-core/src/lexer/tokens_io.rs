@@ -0,0 +1,357 @@
+//! Flat binary token-stream files for tools that do not link against Rust.
+//!
+//! All integers are little-endian:
+//!
+//! ```text
+//! offset  size       field
+//! 0       8          magic b"LXTOKS01"
+//! 8       4          u32 token count N
+//! 12      12 * N     N records of { u32 kind, u32 start, u32 len }
+//! 12+12N  8 + 8      optional: tag b"LXSRCH64", then u64 source hash
+//! ...     8 + 4 + 4  with the source hash: tag b"LXLANGRV", then u32 language
+//!                    revision and u32 zero
+//! ```
+//!
+//! `kind` is the [`TokenKind`] discriminant and `start`/`len` are byte offsets
+//! into the lexed source. The source hash is FNV-1a 64 of the source bytes
+//! ([`source_hash`]). A file that records its source also records the
+//! [`LANGUAGE_REVISION`] it was lexed under, since the kinds mean nothing to a
+//! build with other token ids; [`read_tokens`] applies
+//! [`RevisionMismatch::from_env`] to it. In Python one record is
+//! `struct.unpack_from("<III", ...)`.
+//! Only the retagged [`Token::kind`] is stored; tokens read back carry it as
+//! their `raw_kind` too.
+//!
+//! [`read_tokens`] treats its input as untrusted: a count that does not match
+//! the file size, an unknown kind, or trailing bytes fail instead of panicking
+//! or over-allocating.
+//!
+//! [`TokenKind`]: crate::lexer::tables::tokens::TokenKind
+//! [`source_hash`]: crate::lexer::tokens_io::source_hash
+//! [`LANGUAGE_REVISION`]: crate::language::LANGUAGE_REVISION
+//! [`read_tokens`]: crate::lexer::tokens_io::read_tokens
+//! [`RevisionMismatch::from_env`]: crate::language::RevisionMismatch::from_env
+
+use std::{
+    fs,
+    io::{self, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use crate::{
+    language::{LANGUAGE_REVISION, RevisionMismatch},
+    lexer::{tables::tokens::TokenKind, types::Token},
+    span::Span,
+};
+
+/// Leading magic of a token-stream file, which also carries the version.
+pub const TOKENS_MAGIC: [u8; 8] = *b"LXTOKS01";
+/// Tag of the optional trailing source-hash section.
+pub const SOURCE_HASH_TAG: [u8; 8] = *b"LXSRCH64";
+/// Tag of the language revision section that follows the source hash.
+pub const LANGUAGE_REVISION_TAG: [u8; 8] = *b"LXLANGRV";
+
+const HEADER_BYTES: usize = 12;
+const RECORD_BYTES: usize = 12;
+const SECTION_BYTES: usize = 16;
+
+/// Tokens read back from a token-stream file.
+#[derive(Debug, Clone, Default)]
+pub struct TokenFile {
+    pub tokens: Vec<Token>,
+    /// Hash of the source the tokens were lexed from, when recorded.
+    pub source_hash: Option<u64>,
+    /// Language revision the tokens were lexed under, when recorded.
+    pub language_revision: Option<u32>,
+}
+
+impl TokenFile {
+    /// Whether this file records `source` as the text it was lexed from.
+    pub fn matches_source(&self, source: &str) -> bool {
+        self.source_hash == Some(source_hash(source))
+    }
+}
+
+/// FNV-1a 64 of `source`, as stored in the trailing section.
+pub fn source_hash(source: &str) -> u64 {
+    crate::lexer::diff::fnv1a64(source.as_bytes())
+}
+
+/// Writes `tokens` as one complete token-stream file.
+pub fn write_tokens<W: Write>(
+    mut writer: W,
+    tokens: &[Token],
+    source_hash: Option<u64>,
+) -> io::Result<()> {
+    let count = u32::try_from(tokens.len())
+        .map_err(|_| invalid_input(format!("{} tokens exceed a u32 count", tokens.len())))?;
+    writer.write_all(&TOKENS_MAGIC)?;
+    writer.write_all(&count.to_le_bytes())?;
+    for token in tokens {
+        writer.write_all(&encode_record(token)?)?;
+    }
+    if let Some(hash) = source_hash {
+        write_source_sections(&mut writer, hash)?;
+    }
+    Ok(())
+}
+
+/// Writes a token-stream file one token at a time and patches the count in
+/// [`finish`](Self::finish), so the stream never has to be held in memory.
+pub struct TokenStreamWriter<W: Write + Seek> {
+    writer: W,
+    header_at: u64,
+    count: u32,
+}
+
+impl<W: Write + Seek> TokenStreamWriter<W> {
+    /// Writes a header with a placeholder count at the current position.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        let header_at = writer.stream_position()?;
+        writer.write_all(&TOKENS_MAGIC)?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        Ok(Self {
+            writer,
+            header_at,
+            count: 0,
+        })
+    }
+
+    /// Appends one token record.
+    pub fn push(&mut self, token: &Token) -> io::Result<()> {
+        let count = self
+            .count
+            .checked_add(1)
+            .ok_or_else(|| invalid_input("token stream exceeds a u32 count".to_string()))?;
+        self.writer.write_all(&encode_record(token)?)?;
+        self.count = count;
+        Ok(())
+    }
+
+    /// Tokens pushed so far.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Appends the optional source hash and language revision, patches the
+    /// count, and returns the writer positioned at the end of the file.
+    pub fn finish(mut self, source_hash: Option<u64>) -> io::Result<W> {
+        if let Some(hash) = source_hash {
+            write_source_sections(&mut self.writer, hash)?;
+        }
+        let end = self.writer.stream_position()?;
+        self.writer
+            .seek(SeekFrom::Start(self.header_at + TOKENS_MAGIC.len() as u64))?;
+        self.writer.write_all(&self.count.to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(end))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Parses a token-stream file, rejecting anything that is not exactly one
+/// well-formed stream, and checks its language revision with
+/// [`RevisionMismatch::from_env`].
+pub fn read_tokens(bytes: &[u8]) -> Result<TokenFile> {
+    read_tokens_with(bytes, RevisionMismatch::from_env())
+}
+
+/// [`read_tokens`] with an explicit policy for files lexed under another
+/// language revision.
+pub fn read_tokens_with(bytes: &[u8], mismatch: RevisionMismatch) -> Result<TokenFile> {
+    if bytes.len() < HEADER_BYTES {
+        bail!(
+            "token stream is {} bytes, shorter than its {HEADER_BYTES}-byte header",
+            bytes.len()
+        );
+    }
+    if bytes[..8] != TOKENS_MAGIC {
+        bail!("token stream does not start with {:?}", "LXTOKS01");
+    }
+    let count = read_u32(bytes, 8) as usize;
+    let body = &bytes[HEADER_BYTES..];
+    let records_bytes = count
+        .checked_mul(RECORD_BYTES)
+        .filter(|&n| n <= body.len())
+        .ok_or_else(|| {
+            anyhow!(
+                "token stream declares {count} tokens but holds only {} record bytes",
+                body.len()
+            )
+        })?;
+
+    let mut tokens = Vec::with_capacity(count);
+    for (index, record) in body[..records_bytes].chunks_exact(RECORD_BYTES).enumerate() {
+        let raw_kind = read_u32(record, 0);
+        let kind = TokenKind::from_u32(raw_kind)
+            .ok_or_else(|| anyhow!("token #{index} has unknown kind {raw_kind}"))?;
+        let (start, len) = (read_u32(record, 4), read_u32(record, 8));
+        let span = Span::from_start_len(start, len)
+            .ok_or_else(|| anyhow!("token #{index} at {start}+{len} passes u32::MAX"))?;
+        tokens.push(Token {
+            kind,
+            raw_kind: kind,
+            span,
+        });
+    }
+
+    // Files written before the revision section end after the source hash.
+    let trailer = &body[records_bytes..];
+    let mut sections = trailer.chunks(SECTION_BYTES);
+    let (hash_section, revision_section) = (sections.next(), sections.next());
+    let well_formed =
+        |section: &[u8], tag: [u8; 8]| section.len() == SECTION_BYTES && section[..8] == tag;
+    if sections.next().is_some()
+        || hash_section.is_some_and(|section| !well_formed(section, SOURCE_HASH_TAG))
+        || revision_section.is_some_and(|section| {
+            !well_formed(section, LANGUAGE_REVISION_TAG) || read_u32(section, 12) != 0
+        })
+    {
+        bail!(
+            "token stream has {} unexpected trailing bytes after {count} tokens",
+            trailer.len()
+        );
+    }
+    let language_revision = revision_section.map(|section| read_u32(section, 8));
+    mismatch.check("token stream", language_revision)?;
+    Ok(TokenFile {
+        tokens,
+        source_hash: hash_section
+            .map(|section| u64::from_le_bytes(section[8..].try_into().expect("8-byte hash"))),
+        language_revision,
+    })
+}
+
+/// Reads and parses the token-stream file at `path`.
+pub fn read_tokens_file(path: &Path) -> Result<TokenFile> {
+    let bytes = fs::read(path).with_context(|| format!("read token stream {}", path.display()))?;
+    read_tokens(&bytes).with_context(|| format!("parse token stream {}", path.display()))
+}
+
+fn encode_record(token: &Token) -> io::Result<[u8; RECORD_BYTES]> {
+    let field = |value: usize, name: &str| {
+        u32::try_from(value).map_err(|_| invalid_input(format!("token {name} {value} exceeds u32")))
+    };
+    let mut record = [0u8; RECORD_BYTES];
+    record[0..4].copy_from_slice(&(token.kind as u32).to_le_bytes());
+    record[4..8].copy_from_slice(&field(token.start(), "start")?.to_le_bytes());
+    record[8..12].copy_from_slice(&field(token.len(), "length")?.to_le_bytes());
+    Ok(record)
+}
+
+fn write_source_sections<W: Write>(writer: &mut W, hash: u64) -> io::Result<()> {
+    writer.write_all(&SOURCE_HASH_TAG)?;
+    writer.write_all(&hash.to_le_bytes())?;
+    writer.write_all(&LANGUAGE_REVISION_TAG)?;
+    writer.write_all(&LANGUAGE_REVISION.to_le_bytes())?;
+    writer.write_all(&0u32.to_le_bytes())
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().expect("4-byte field"))
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::lexer::test_cpu::lex_on_test_cpu;
+
+    const SOURCE: &str = "fn main() { let s = \"héllo\"; return s[0] + 1; } // done\n";
+
+    fn source_tokens() -> Vec<Token> {
+        lex_on_test_cpu(SOURCE).expect("lex source")
+    }
+
+    fn shape(tokens: &[Token]) -> Vec<(TokenKind, usize, usize)> {
+        tokens
+            .iter()
+            .map(|t| (t.kind, t.start(), t.len()))
+            .collect()
+    }
+
+    #[test]
+    fn whole_and_streamed_files_round_trip_identically() {
+        let tokens = source_tokens();
+        let hash = source_hash(SOURCE);
+        let mut whole = Vec::new();
+        write_tokens(&mut whole, &tokens, Some(hash)).unwrap();
+        assert_eq!(whole.len(), 12 + 12 * tokens.len() + 32);
+
+        let mut streamed = TokenStreamWriter::new(Cursor::new(Vec::new())).unwrap();
+        for token in &tokens {
+            streamed.push(token).unwrap();
+        }
+        assert_eq!(streamed.count() as usize, tokens.len());
+        let streamed = streamed.finish(Some(hash)).unwrap().into_inner();
+        assert_eq!(streamed, whole);
+
+        let file = read_tokens(&whole).unwrap();
+        assert_eq!(shape(&file.tokens), shape(&tokens));
+        assert!(file.matches_source(SOURCE));
+        assert!(!file.matches_source("fn main() {}"));
+        assert_eq!(file.language_revision, Some(LANGUAGE_REVISION));
+
+        let mut bare = Vec::new();
+        write_tokens(&mut bare, &[], None).unwrap();
+        let file = read_tokens(&bare).unwrap();
+        assert!(file.tokens.is_empty());
+        assert_eq!(file.source_hash, None);
+        assert_eq!(file.language_revision, None);
+    }
+
+    #[test]
+    fn other_language_revisions_refuse_or_warn() {
+        let mut bytes = Vec::new();
+        write_tokens(&mut bytes, &source_tokens(), Some(source_hash(SOURCE))).unwrap();
+        let revision_at = bytes.len() - 8;
+
+        // Files from before the revision section still load.
+        let file = read_tokens_with(&bytes[..revision_at - 8], RevisionMismatch::Refuse).unwrap();
+        assert!(file.matches_source(SOURCE));
+        assert_eq!(file.language_revision, None);
+
+        bytes[revision_at..revision_at + 4].copy_from_slice(&(LANGUAGE_REVISION + 1).to_le_bytes());
+        let err = read_tokens_with(&bytes, RevisionMismatch::Refuse)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("language revision"), "{err}");
+        let file = read_tokens_with(&bytes, RevisionMismatch::Warn).unwrap();
+        assert_eq!(file.language_revision, Some(LANGUAGE_REVISION + 1));
+    }
+
+    #[test]
+    fn malformed_files_fail_without_panicking() {
+        let mut bytes = Vec::new();
+        write_tokens(&mut bytes, &source_tokens(), Some(source_hash(SOURCE))).unwrap();
+
+        for cut in [0, 7, 11, 12, 20, bytes.len() - 17, bytes.len() - 1] {
+            assert!(read_tokens(&bytes[..cut]).is_err(), "truncated at {cut}");
+        }
+
+        let mut oversized = bytes.clone();
+        oversized[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        let err = read_tokens(&oversized).unwrap_err().to_string();
+        assert!(err.contains("declares 4294967295 tokens"), "{err}");
+
+        let mut bad_kind = bytes.clone();
+        bad_kind[12..16].copy_from_slice(&0xFFFFu32.to_le_bytes());
+        let err = read_tokens(&bad_kind).unwrap_err().to_string();
+        assert_eq!(err, "token #0 has unknown kind 65535");
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[7] = b'2';
+        assert!(read_tokens(&bad_magic).is_err());
+
+        let mut trailing = bytes;
+        trailing.push(0);
+        assert!(read_tokens(&trailing).is_err());
+    }
+}
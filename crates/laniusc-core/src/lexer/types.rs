@@ -0,0 +1,600 @@
+//! Host token records, per-call lexer options, and lex results.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{determinism::Determinism, lexer::tables::tokens::TokenKind, span::Span};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Host-readable token record produced by GPU readback and by the
+/// [`test_cpu`](crate::lexer::test_cpu) oracle.
+///
+/// Offsets are bytes of the source exactly as given, never normalized: a
+/// leading UTF-8 BOM is not stripped but covered by a skipped
+/// [`TokenKind::Bom`] token at `0..3`, so the first real token of such a file
+/// starts at 3. `LineMap` maps that offset to column 1.
+///
+/// `kind` is what the parser consumes; `raw_kind` is what the DFA classified
+/// the bytes as before lexer retags, so `let` is `raw_kind: Ident` with
+/// `kind: Let`. The two are equal whenever no retag applied.
+pub struct Token {
+    /// Token kind after lexer-level filtering and retags.
+    pub kind: TokenKind,
+    /// DFA token kind before keyword and range retags.
+    pub raw_kind: TokenKind,
+    /// Byte span in the concatenated source input.
+    pub span: Span,
+}
+
+impl Token {
+    /// A token covering `len` bytes from `start` whose raw kind is `kind`.
+    /// Panics when the span passes `u32::MAX`; see [`Span::from_usize`].
+    pub fn new(kind: TokenKind, start: usize, len: usize) -> Self {
+        Self {
+            kind,
+            raw_kind: kind,
+            span: Span::from_usize(start, len),
+        }
+    }
+
+    /// Start byte offset.
+    pub fn start(&self) -> usize {
+        self.span.start as usize
+    }
+
+    /// Byte length.
+    pub fn len(&self) -> usize {
+        self.span.len() as usize
+    }
+
+    /// One past the last byte.
+    pub fn end(&self) -> usize {
+        self.span.end as usize
+    }
+
+    /// Whether the token covers no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.span.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// How much of one lex result is read back to the host.
+pub enum ReadbackMode {
+    /// Read the kept-token count and every kept token.
+    #[default]
+    Full,
+    /// Read only the kept-token count into [`LexOutput::token_count`].
+    CountOnly,
+    /// Submit the GPU work without reading anything back.
+    None,
+}
+
+impl ReadbackMode {
+    /// `None` when `LANIUS_READBACK` or `PERF_ONE_READBACK` disables readback,
+    /// otherwise `Full`.
+    pub fn from_env() -> Self {
+        if crate::lexer::util::readback_enabled() {
+            Self::Full
+        } else {
+            Self::None
+        }
+    }
+}
+
+/// Default for [`LexOptions::single_submission_max_bytes`].
+pub const DEFAULT_SINGLE_SUBMISSION_MAX_BYTES: u64 = 256 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+/// Opt-in extras for one `GpuLexer::lex_with_options` call.
+///
+/// Deserializes from a partial map; missing fields keep their defaults.
+pub struct LexOptions {
+    /// Also return the DFA state that accepted each kept token.
+    pub capture_accept_states: bool,
+    /// What to read back; the environment does not override this.
+    pub readback: ReadbackMode,
+    /// Largest token output, in bytes, copied back whole in the same
+    /// submission as the token count. Larger outputs wait for the count and
+    /// copy exactly the kept tokens in a second submission.
+    pub single_submission_max_bytes: u64,
+    /// Longest token, kept or skipped, in bytes. Longer tokens fail the lex
+    /// with [`LexError::TokenTooLong`] unless readback is [`ReadbackMode::None`].
+    pub max_token_len: u32,
+    /// Reproducibility required of the outputs. Every current lexer output is
+    /// deterministic; under `Strict`, debug builds also check token order.
+    pub determinism: Determinism,
+    /// Also read back the all-boundary stream, skipped tokens included, into
+    /// [`LexOutput::all_tokens`]; only under [`ReadbackMode::Full`].
+    pub all_tokens: bool,
+    /// Build a [`LexReport`] into [`LexOutput::report`] and
+    /// `GpuLexer::last_report`.
+    /// On by default in debug builds.
+    pub report: bool,
+    /// Also return every escape sequence inside string and char literals in
+    /// [`LexOutput::escape_spans`]; only under [`ReadbackMode::Full`].
+    pub capture_string_escapes: bool,
+    /// Which tokens the GPU flags for a CPU recheck of the bytes around
+    /// them; `0` turns paranoia mode off. See [`LexOptions::paranoia`].
+    pub paranoia_level: u8,
+    /// Fail the lex with [`LexError::SuspectMismatch`] on the first suspect
+    /// the recheck disagrees with, instead of returning
+    /// [`LexWarning::SuspectMismatch`].
+    pub fail_on_suspect_mismatch: bool,
+    /// When the kept tokens are copied back in a second submission, pack
+    /// them on the GPU to one word each plus an escape list, falling back to
+    /// the raw records when too many escape. Decoded tokens are identical.
+    pub compress_readback: bool,
+    /// Also return the [`LayoutFacts`] of every kept token in
+    /// [`LexOutput::layout_facts`]; only under [`ReadbackMode::Full`].
+    pub layout_facts: bool,
+}
+
+impl Default for LexOptions {
+    fn default() -> Self {
+        Self {
+            capture_accept_states: false,
+            readback: ReadbackMode::Full,
+            single_submission_max_bytes: DEFAULT_SINGLE_SUBMISSION_MAX_BYTES,
+            max_token_len: u32::MAX,
+            determinism: Determinism::Strict,
+            all_tokens: false,
+            report: cfg!(debug_assertions),
+            capture_string_escapes: false,
+            paranoia_level: 0,
+            fail_on_suspect_mismatch: false,
+            compress_readback: false,
+            layout_facts: false,
+        }
+    }
+}
+
+impl LexOptions {
+    /// Default options in paranoia mode: `tokens_build` lists the kept tokens
+    /// that went through a known-risky path, and the driver relexes a small
+    /// window around each on the test CPU oracle, reporting disagreements in
+    /// [`LexOutput::warnings`]. Only under [`ReadbackMode::Full`].
+    ///
+    /// - `0`: off.
+    /// - `1`: tokens closing a file, whether by the EOF rule alone or by a
+    ///   same-byte EMIT and EOF, and tokens whose length was clamped.
+    /// - `2`: also tokens touching a DFA block boundary.
+    /// - `3` and up: every kept token, a full differential check done one
+    ///   window at a time.
+    pub fn paranoia(level: u8) -> Self {
+        Self {
+            paranoia_level: level,
+            ..Self::default()
+        }
+    }
+
+    /// The syntax-highlight profile: the ALL stream with final kept kinds,
+    /// kept tokens packed by `tokens_compress`, and no report, paranoia,
+    /// escape, layout, or accept-state work, whatever the defaults or build
+    /// say. `GpuLexer::highlight` lexes with these.
+    pub fn highlight() -> Self {
+        Self {
+            capture_accept_states: false,
+            readback: ReadbackMode::Full,
+            all_tokens: true,
+            report: false,
+            capture_string_escapes: false,
+            paranoia_level: 0,
+            fail_on_suspect_mismatch: false,
+            compress_readback: true,
+            layout_facts: false,
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Capacity and queue bookkeeping of one
+/// `GpuLexer::lex_with_options` call.
+pub struct LexReport {
+    /// Source bytes lexed.
+    pub input_len: usize,
+    /// Kept-token slots in the resident token buffer.
+    pub capacity: usize,
+    /// Kept tokens; zero under [`ReadbackMode::None`].
+    pub token_count: usize,
+    /// Tokens in the all-boundary stream, skipped ones included; zero under
+    /// [`ReadbackMode::None`].
+    pub all_boundary_count: usize,
+    /// The resident buffers were reallocated larger, or allocated for the
+    /// first time.
+    pub grew: bool,
+    /// The resident buffers were reallocated smaller.
+    pub shrank: bool,
+    /// The kept-token count exceeded `capacity`; the lex then fails.
+    pub overflow_flagged: bool,
+    /// What was read back.
+    pub readback_mode: ReadbackMode,
+    /// Lexer passes recorded.
+    pub passes_recorded: u32,
+    /// `queue.submit` calls, readbacks included.
+    pub submissions: u32,
+    /// Bytes copied back to the host, the count words included.
+    pub transfer_bytes: u64,
+    /// Kept tokens came back packed by `tokens_compress`.
+    pub compressed: bool,
+    /// Host time spent decoding the mapped token outputs.
+    pub decode_time: std::time::Duration,
+}
+
+impl LexReport {
+    /// Broken invariants of a report from a lex that succeeded.
+    pub fn violations(&self) -> Vec<LexReportViolation> {
+        let mut violations = Vec::new();
+        if self.token_count > self.all_boundary_count {
+            violations.push(LexReportViolation::KeptExceedsAll {
+                token_count: self.token_count,
+                all_boundary_count: self.all_boundary_count,
+            });
+        }
+        if self.all_boundary_count > self.input_len + 1 {
+            violations.push(LexReportViolation::AllExceedsInput {
+                all_boundary_count: self.all_boundary_count,
+                input_len: self.input_len,
+            });
+        }
+        if self.grew && self.shrank {
+            violations.push(LexReportViolation::GrewAndShrank);
+        }
+        if self.overflow_flagged {
+            // No path retries with more capacity, so an overflow must fail.
+            violations.push(LexReportViolation::OverflowSucceeded {
+                token_count: self.token_count,
+                capacity: self.capacity,
+            });
+        }
+        violations
+    }
+}
+
+impl std::fmt::Display for LexReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "input={} capacity={} tokens={} all={} readback={:?} passes={} submissions={} \
+             transfer={} decode={:?}",
+            self.input_len,
+            self.capacity,
+            self.token_count,
+            self.all_boundary_count,
+            self.readback_mode,
+            self.passes_recorded,
+            self.submissions,
+            self.transfer_bytes,
+            self.decode_time
+        )?;
+        for (set, flag) in [
+            (self.grew, "grew"),
+            (self.shrank, "shrank"),
+            (self.overflow_flagged, "overflow"),
+            (self.compressed, "compressed"),
+        ] {
+            if set {
+                write!(f, " {flag}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A [`LexReport`] invariant a successful lex broke. Debug builds assert on
+/// these; release builds log them and return them in [`LexOutput::warnings`].
+pub enum LexReportViolation {
+    /// More kept tokens than all-boundary tokens.
+    KeptExceedsAll {
+        token_count: usize,
+        all_boundary_count: usize,
+    },
+    /// More all-boundary tokens than `input_len + 1`.
+    AllExceedsInput {
+        all_boundary_count: usize,
+        input_len: usize,
+    },
+    /// The buffers both grew and shrank in one call.
+    GrewAndShrank,
+    /// The kept-token count overflowed but the lex still succeeded.
+    OverflowSucceeded { token_count: usize, capacity: usize },
+}
+
+impl std::fmt::Display for LexReportViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::KeptExceedsAll {
+                token_count,
+                all_boundary_count,
+            } => write!(
+                f,
+                "{token_count} kept tokens exceed {all_boundary_count} all-boundary tokens"
+            ),
+            Self::AllExceedsInput {
+                all_boundary_count,
+                input_len,
+            } => write!(
+                f,
+                "{all_boundary_count} all-boundary tokens exceed {input_len} input bytes plus one"
+            ),
+            Self::GrewAndShrank => write!(f, "resident buffers both grew and shrank"),
+            Self::OverflowSucceeded {
+                token_count,
+                capacity,
+            } => write!(
+                f,
+                "{token_count} kept tokens overflowed capacity {capacity} without failing"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Structured lexer failure, recoverable from an `anyhow::Error` by downcasting.
+pub enum LexError {
+    /// A token, possibly a skipped comment or whitespace run, is longer than
+    /// [`LexOptions::max_token_len`].
+    TokenTooLong {
+        /// Pre-skip DFA kind of the first offending token.
+        kind: TokenKind,
+        /// Start byte offset of the first offending token.
+        start: usize,
+        /// The configured limit.
+        limit: u32,
+    },
+    /// The paranoia recheck relexed the window around a suspect token on the
+    /// CPU and got different tokens, with
+    /// [`LexOptions::fail_on_suspect_mismatch`] set.
+    SuspectMismatch {
+        /// Kept index of the suspect token.
+        token_index: usize,
+        /// The relexed byte window.
+        window: Span,
+    },
+}
+
+impl std::fmt::Display for LexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TokenTooLong { kind, start, limit } => write!(
+                f,
+                "{kind:?} token at byte {start} is longer than the {limit}-byte token limit"
+            ),
+            Self::SuspectMismatch {
+                token_index,
+                window,
+            } => write!(
+                f,
+                "suspect token {token_index} disagrees with a CPU relex of bytes {window}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LexError {}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Kept tokens plus any extras requested through [`LexOptions`].
+pub struct LexOutput {
+    /// Kept tokens, as returned by `GpuLexer::lex`; empty unless readback
+    /// is [`ReadbackMode::Full`].
+    pub tokens: Vec<Token>,
+    /// Kept-token count; zero under [`ReadbackMode::None`].
+    pub token_count: usize,
+    /// [`S`](crate::lexer::tables::dfa::S) index that accepted each token;
+    /// empty unless [`LexOptions::capture_accept_states`] was set.
+    pub accept_states: Vec<u16>,
+    /// Every token, skipped whitespace and comments included, in source
+    /// order; empty unless [`LexOptions::all_tokens`] was set. Kept entries
+    /// equal the matching [`tokens`](Self::tokens); skipped entries carry
+    /// their pre-skip DFA kinds.
+    pub all_tokens: Vec<Token>,
+    /// Escape sequences inside string and char literals, sorted by start;
+    /// empty unless [`LexOptions::capture_string_escapes`] was set. Raw
+    /// strings have none.
+    pub escape_spans: Vec<EscapeSpan>,
+    /// Kept tokens the GPU listed for a CPU recheck, sorted by kept index;
+    /// empty unless [`LexOptions::paranoia_level`] was nonzero.
+    pub suspects: Vec<crate::lexer::paranoia::SuspectToken>,
+    /// Layout of the trivia before each kept token, parallel to
+    /// [`tokens`](Self::tokens); empty unless [`LexOptions::layout_facts`]
+    /// was set.
+    pub layout_facts: Vec<LayoutFacts>,
+    /// Bookkeeping of this call when [`LexOptions::report`] was set.
+    pub report: Option<LexReport>,
+    /// Report invariants this call broke, which debug builds assert on
+    /// instead, and paranoia-mode mismatches.
+    pub warnings: Vec<LexWarning>,
+    /// Dispatches this call recorded, in submission order; empty unless the
+    /// GPU lexer captures dispatch metadata.
+    pub dispatch_records: Vec<crate::dispatch::DispatchRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Something wrong with a lex that still returned its tokens.
+pub enum LexWarning {
+    /// A [`LexReport`] invariant the lex broke.
+    Report(LexReportViolation),
+    /// In paranoia mode, a CPU relex of `window` disagrees with the GPU
+    /// tokens there; the suspect at kept index `token_index` is one of them.
+    SuspectMismatch { token_index: usize, window: Span },
+}
+
+impl std::fmt::Display for LexWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Report(violation) => violation.fmt(f),
+            Self::SuspectMismatch {
+                token_index,
+                window,
+            } => write!(
+                f,
+                "suspect token {token_index} disagrees with a CPU relex of bytes {window}"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Boundary-level statistics of one source, as `GpuLexer::analyze` computes
+/// them without building tokens.
+pub struct LexAnalysis {
+    /// Kept tokens, as `GpuLexer::lex` would return.
+    pub kept_count: usize,
+    /// Tokens in the all-boundary stream, skipped ones included.
+    pub all_count: usize,
+    /// Start of the first kept token; `None` when every token is skipped.
+    pub first_kept_offset: Option<usize>,
+    /// Bytes of line and block comments; a shebang line is not a comment.
+    pub comment_bytes: usize,
+    /// Bytes of kept tokens.
+    pub code_bytes: usize,
+    /// Lines as [`str::lines`] counts them: a final line without a newline
+    /// counts, an empty input has none.
+    pub line_count: usize,
+}
+
+impl LexAnalysis {
+    /// The analysis of `input` derived from its all-boundary stream, as
+    /// `LexOutput::all_tokens` or `lex_on_test_cpu_all` give it.
+    pub fn from_all_tokens(input: &str, all_tokens: &[Token]) -> Self {
+        let mut analysis = Self {
+            all_count: all_tokens.len(),
+            line_count: input.lines().count(),
+            ..Self::default()
+        };
+        for token in all_tokens {
+            if crate::lexer::boundary::is_kept(Some(token.kind)) {
+                analysis.kept_count += 1;
+                analysis.code_bytes += token.len();
+                analysis.first_kept_offset.get_or_insert(token.start());
+            } else if matches!(token.kind, TokenKind::LineComment | TokenKind::BlockComment) {
+                analysis.comment_bytes += token.len();
+            }
+        }
+        analysis
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// One escape sequence inside a string or char literal: the backslash and the
+/// whole character after it, so `\n` is 2 bytes and `\é` is 3.
+///
+/// Spans are best-effort highlighting data, not validated escapes; `\q` is
+/// reported like any other. See [`escape_span_tokens`](crate::lexer::escapes::escape_span_tokens)
+/// to find the literal each span sits in.
+pub struct EscapeSpan {
+    /// Bytes of the escape, starting at the backslash.
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+/// Where a kept token sits relative to the trivia before it: enough for a
+/// formatter to lay tokens out again without reading the source.
+///
+/// Both counts saturate at `u16::MAX` (65535). Columns count bytes, so a tab
+/// is one column, as in `LineMap` columns.
+pub struct LayoutFacts {
+    /// `\n` bytes between the end of the previous kept token, or the start
+    /// of the source, and this token.
+    pub newlines_before: u16,
+    /// Spaces and tabs that start this token's line. The first line starts
+    /// after any byte order mark.
+    pub indent_col: u16,
+    /// Whether a line comment sits between the previous kept token and this
+    /// one. A shebang line is not a comment.
+    pub had_line_comment_before: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_violations_cover_each_invariant() {
+        let ok = LexReport {
+            input_len: 10,
+            capacity: 12,
+            token_count: 5,
+            all_boundary_count: 8,
+            grew: true,
+            ..LexReport::default()
+        };
+        assert!(ok.violations().is_empty());
+        // An empty input has one boundary at most.
+        assert!(
+            LexReport {
+                all_boundary_count: 1,
+                ..LexReport::default()
+            }
+            .violations()
+            .is_empty()
+        );
+
+        let broken = LexReport {
+            token_count: 13,
+            all_boundary_count: 12,
+            shrank: true,
+            overflow_flagged: true,
+            ..ok
+        };
+        assert_eq!(
+            broken.violations(),
+            [
+                LexReportViolation::KeptExceedsAll {
+                    token_count: 13,
+                    all_boundary_count: 12,
+                },
+                LexReportViolation::AllExceedsInput {
+                    all_boundary_count: 12,
+                    input_len: 10,
+                },
+                LexReportViolation::GrewAndShrank,
+                LexReportViolation::OverflowSucceeded {
+                    token_count: 13,
+                    capacity: 12,
+                },
+            ]
+        );
+        assert_eq!(
+            broken.to_string(),
+            "input=10 capacity=12 tokens=13 all=12 readback=Full passes=0 submissions=0 \
+             transfer=0 decode=0ns grew shrank overflow"
+        );
+        let compressed = LexReport {
+            transfer_bytes: 4096,
+            compressed: true,
+            decode_time: std::time::Duration::from_micros(15),
+            ..ok
+        };
+        assert!(compressed.violations().is_empty());
+        assert!(
+            compressed
+                .to_string()
+                .ends_with("transfer=4096 decode=15µs grew compressed"),
+            "{compressed}"
+        );
+    }
+
+    #[test]
+    fn options_deserialize_from_partial_config() {
+        let options: LexOptions =
+            serde_json::from_str(r#"{"readback": "count_only", "compress_readback": true}"#)
+                .unwrap();
+        assert_eq!(
+            options,
+            LexOptions {
+                readback: ReadbackMode::CountOnly,
+                compress_readback: true,
+                ..LexOptions::default()
+            }
+        );
+        assert_eq!(
+            serde_json::from_str::<LexOptions>("{}").unwrap(),
+            LexOptions::default()
+        );
+    }
+}
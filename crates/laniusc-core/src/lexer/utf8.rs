@@ -0,0 +1,200 @@
+//! UTF-8 policy for token byte ranges.
+//!
+//! The DFA works on bytes, so a valid token never splits a multi-byte
+//! character. A token whose `start` or end lands mid-codepoint therefore points
+//! at a driver or shader bug, typically a wrong scatter offset.
+
+use std::{borrow::Cow, fmt};
+
+use crate::lexer::{tables::tokens::TokenKind, types::Token};
+
+/// How [`Token::lexeme`] treats a byte range that is not on UTF-8 boundaries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LexemePolicy {
+    /// Fail unless both ends fall on char boundaries of the source.
+    #[default]
+    Strict,
+    /// Decode the bytes, replacing partial characters with U+FFFD.
+    Lossy,
+}
+
+/// Token byte range that a [`LexemePolicy`] rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LexemeError {
+    pub start: usize,
+    pub len: usize,
+    /// Byte length of the source the range was taken from.
+    pub source_len: usize,
+}
+
+impl fmt::Display for LexemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let end = self.start.saturating_add(self.len);
+        if end > self.source_len {
+            write!(
+                f,
+                "token bytes {}..{end} exceed the {}-byte source",
+                self.start, self.source_len
+            )
+        } else {
+            write!(
+                f,
+                "token bytes {}..{end} split a UTF-8 character",
+                self.start
+            )
+        }
+    }
+}
+
+impl std::error::Error for LexemeError {}
+
+/// Returns the text of `src[start..start + len]` under `policy`.
+///
+/// Never panics: out-of-range bytes are an error under either policy.
+pub fn lexeme(
+    src: &str,
+    start: usize,
+    len: usize,
+    policy: LexemePolicy,
+) -> Result<Cow<'_, str>, LexemeError> {
+    let error = LexemeError {
+        start,
+        len,
+        source_len: src.len(),
+    };
+    let end = start.checked_add(len).ok_or(error)?;
+    match policy {
+        LexemePolicy::Strict => src.get(start..end).map(Cow::Borrowed).ok_or(error),
+        LexemePolicy::Lossy => src
+            .as_bytes()
+            .get(start..end)
+            .map(String::from_utf8_lossy)
+            .ok_or(error),
+    }
+}
+
+impl Token {
+    /// Returns this token's text in `src`, the source it was lexed from.
+    pub fn lexeme<'a>(
+        &self,
+        src: &'a str,
+        policy: LexemePolicy,
+    ) -> Result<Cow<'a, str>, LexemeError> {
+        lexeme(src, self.start(), self.len(), policy)
+    }
+}
+
+/// Pairs each of `tokens` with its text in `src` under `policy`.
+pub fn lexemes<'a>(
+    src: &'a str,
+    tokens: &'a [Token],
+    policy: LexemePolicy,
+) -> impl Iterator<Item = (&'a Token, Result<Cow<'a, str>, LexemeError>)> + 'a {
+    tokens
+        .iter()
+        .map(move |token| (token, token.lexeme(src, policy)))
+}
+
+/// Kept token whose byte range is out of range or splits a UTF-8 character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundaryViolation {
+    /// Index of the token in the checked stream.
+    pub index: usize,
+    pub kind: TokenKind,
+    pub start: usize,
+    pub len: usize,
+}
+
+impl fmt::Display for BoundaryViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} {:?} at {}..{}",
+            self.index,
+            self.kind,
+            self.start,
+            self.start.saturating_add(self.len)
+        )
+    }
+}
+
+/// Lists every token whose range is not on char boundaries of `src`.
+pub fn check_token_boundaries(src: &str, tokens: &[Token]) -> Vec<BoundaryViolation> {
+    tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, token)| token.lexeme(src, LexemePolicy::Strict).is_err())
+        .map(|(index, token)| BoundaryViolation {
+            index,
+            kind: token.kind,
+            start: token.start(),
+            len: token.len(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::test_cpu::lex_on_test_cpu;
+
+    fn token(start: usize, len: usize) -> Token {
+        Token::new(TokenKind::String, start, len)
+    }
+
+    #[test]
+    fn mid_codepoint_ranges_fail_strict_and_replace_lossy() {
+        let src = "\"é\"";
+        let split = token(0, 2);
+        let err = split.lexeme(src, LexemePolicy::Strict).unwrap_err();
+        assert_eq!(err.to_string(), "token bytes 0..2 split a UTF-8 character");
+        assert_eq!(
+            split.lexeme(src, LexemePolicy::Lossy).unwrap(),
+            "\"\u{FFFD}"
+        );
+
+        assert_eq!(token(0, 4).lexeme(src, LexemePolicy::Strict).unwrap(), src);
+        let past_end = token(2, 9);
+        assert!(past_end.lexeme(src, LexemePolicy::Lossy).is_err());
+        assert!(past_end.lexeme(src, LexemePolicy::Strict).is_err());
+        assert!(
+            token(u32::MAX as usize - 2, 2)
+                .lexeme(src, LexemePolicy::Lossy)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn multibyte_strings_and_comments_keep_token_boundaries() {
+        let src = "let s = \"héllo → wörld 🦀\"; // ünïcödé\nlet t = r\"日本\" /* ∀x */;\n";
+        let tokens = lex_on_test_cpu(src).expect("lex multibyte source");
+        assert!(check_token_boundaries(src, &tokens).is_empty());
+
+        let shifted = [token(11, 3)];
+        let violations = check_token_boundaries(src, &shifted);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].to_string(), "#0 String at 11..14");
+    }
+
+    #[test]
+    fn lexemes_pair_tokens_with_their_text() {
+        let src = "let s = \"é\";";
+        let tokens = lex_on_test_cpu(src).expect("lex");
+        let texts: Vec<_> = lexemes(src, &tokens, LexemePolicy::Strict)
+            .map(|(token, text)| (token.kind, text.unwrap()))
+            .collect();
+        assert_eq!(
+            texts,
+            [
+                (TokenKind::Let, "let".into()),
+                (TokenKind::Ident, "s".into()),
+                (TokenKind::Assign, "=".into()),
+                (TokenKind::String, "\"é\"".into()),
+                (TokenKind::Semicolon, ";".into()),
+            ]
+        );
+        let split = [token(9, 1)];
+        let (_, text) = lexemes(src, &split, LexemePolicy::Strict).next().unwrap();
+        assert!(text.is_err());
+    }
+}
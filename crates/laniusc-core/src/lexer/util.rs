@@ -0,0 +1,621 @@
+//! Small helpers for readback and env flags.
+
+use crate::{
+    env,
+    lexer::{
+        constants::{COMPRESSED_DELTA_ESCAPE, COMPRESSED_ESCAPE_RATE_LIMIT, COMPRESSED_LEN_ESCAPE},
+        tables::tokens::{N_KINDS, TokenKind},
+        types::Token,
+    },
+    span::Span,
+};
+
+/// Read a little-endian u32 from the first 4 bytes.
+/// Decodes the first four bytes of a mapped readback range as little-endian `u32`.
+pub fn u32_from_first_4(bytes: &[u8]) -> u32 {
+    let mut le = [0u8; 4];
+    le.copy_from_slice(&bytes[..4]);
+    u32::from_le_bytes(le)
+}
+
+/// Treat any value other than "0"/"false" (case-insensitive) as true.
+/// Reads a truthy environment flag with a default value.
+pub fn env_flag_true(var: &str, default: bool) -> bool {
+    env::env_bool_truthy(var, default)
+}
+
+/// Gate for host readback of token payloads (can be turned off in perf runs).
+/// Returns whether lexer token readback should run for `GpuLexer::lex`.
+pub fn readback_enabled() -> bool {
+    env_flag_true("LANIUS_READBACK", true) && env_flag_true("PERF_ONE_READBACK", true)
+}
+
+/// Convert a mapped `[GpuToken]` byte slice into a `Vec<Token>`.
+/// Decodes mapped `GpuToken` bytes into host `Token` records.
+pub fn read_tokens_from_mapped(bytes: &[u8], count: usize) -> Result<Vec<Token>, String> {
+    use std::mem::size_of;
+
+    let stride = size_of::<u32>() * 3;
+    let needed = count
+        .checked_mul(stride)
+        .ok_or_else(|| "read_tokens_from_mapped: byte count overflow".to_string())?;
+    if bytes.len() < needed {
+        return Err(format!(
+            "read_tokens_from_mapped: mapped slice too small (have {}, need >= {})",
+            bytes.len(),
+            needed
+        ));
+    }
+
+    let mut out = Vec::with_capacity(count);
+    for (i, raw) in bytes[..needed].chunks_exact(stride).enumerate() {
+        let kind_u32 = u32::from_le_bytes(raw[0..4].try_into().expect("kind word"));
+        let start = u32::from_le_bytes(raw[4..8].try_into().expect("start word"));
+        let len = u32::from_le_bytes(raw[8..12].try_into().expect("len word"));
+
+        let kind = TokenKind::from_u32(kind_u32).ok_or_else(|| {
+            format!("read_tokens_from_mapped: invalid token kind {kind_u32} at token {i}")
+        })?;
+        let span = Span::from_start_len(start, len).ok_or_else(|| {
+            format!("read_tokens_from_mapped: token {i} at {start}+{len} passes u32::MAX")
+        })?;
+        out.push(Token {
+            kind,
+            raw_kind: kind,
+            span,
+        });
+    }
+    Ok(out)
+}
+
+/// Sets each token's `raw_kind` from mapped `types_compact` words, the DFA
+/// kinds `tokens_build` retagged from.
+pub fn apply_raw_kinds_from_mapped(tokens: &mut [Token], bytes: &[u8]) -> Result<(), String> {
+    let needed = tokens.len() * 4;
+    if bytes.len() < needed {
+        return Err(format!(
+            "apply_raw_kinds_from_mapped: mapped slice too small (have {}, need >= {})",
+            bytes.len(),
+            needed
+        ));
+    }
+    for (i, (token, word)) in tokens.iter_mut().zip(bytes.chunks_exact(4)).enumerate() {
+        let kind_u32 = u32::from_le_bytes(word.try_into().expect("kind word"));
+        token.raw_kind = TokenKind::from_u32(kind_u32).ok_or_else(|| {
+            format!("apply_raw_kinds_from_mapped: invalid raw kind {kind_u32} at token {i}")
+        })?;
+    }
+    Ok(())
+}
+
+/// Unpacks `count` accept states stored two `u16` lanes per mapped `u32`.
+pub fn read_accept_states_from_mapped(bytes: &[u8], count: usize) -> Vec<u16> {
+    bytes
+        .chunks_exact(2)
+        .take(count)
+        .map(|lane| u16::from_le_bytes([lane[0], lane[1]]))
+        .collect()
+}
+
+/// Words per compressed-readback escape entry: kept index, start, length.
+pub const COMPRESSED_ESCAPE_WORDS: usize = 3;
+
+// The compressed word carries the kind in one byte.
+const _: () = assert!(N_KINDS <= 256);
+
+/// How the kept tokens of one lex travel from the GPU to the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenTransport {
+    /// Three words per token, as `tokens_build` wrote them.
+    Raw,
+    /// One packed word per token plus the escape list of `tokens_compress`.
+    Compressed,
+}
+
+/// Picks the transport for `count` kept tokens of which `escapes` did not
+/// fit a packed word: raw once more than one token in
+/// [`COMPRESSED_ESCAPE_RATE_LIMIT`] escapes. The GPU escape list holds no
+/// more than that, so a compressed pick never misses an entry.
+pub fn choose_token_transport(count: usize, escapes: usize) -> TokenTransport {
+    if escapes.saturating_mul(COMPRESSED_ESCAPE_RATE_LIMIT as usize) > count {
+        TokenTransport::Raw
+    } else {
+        TokenTransport::Compressed
+    }
+}
+
+/// Bytes of token records `transport` copies for `count` kept tokens with
+/// `escapes` escaped ones.
+pub fn token_transfer_bytes(transport: TokenTransport, count: usize, escapes: usize) -> u64 {
+    let words = match transport {
+        TokenTransport::Raw => count * 3,
+        TokenTransport::Compressed => count + escapes * COMPRESSED_ESCAPE_WORDS,
+    };
+    words as u64 * 4
+}
+
+/// Packs kept tokens the way `tokens_compress` does, returning the packed
+/// words and the escape list in token order.
+///
+/// Each word holds the kind in bits 0..8, the length in bits 8..16, and the
+/// start minus the previous token's start (zero before the first) in bits
+/// 16..32. A token whose length or delta reaches its escape code is also
+/// listed as `(index, start, len)` words.
+pub fn encode_compressed_tokens(tokens: &[Token]) -> (Vec<u32>, Vec<u32>) {
+    let mut words = Vec::with_capacity(tokens.len());
+    let mut escapes = Vec::new();
+    let mut prev_start = 0usize;
+    for (i, token) in tokens.iter().enumerate() {
+        let len = u32::try_from(token.len())
+            .unwrap_or(u32::MAX)
+            .min(COMPRESSED_LEN_ESCAPE);
+        let delta = token
+            .start()
+            .checked_sub(prev_start)
+            .and_then(|delta| u32::try_from(delta).ok())
+            .unwrap_or(u32::MAX)
+            .min(COMPRESSED_DELTA_ESCAPE);
+        if len == COMPRESSED_LEN_ESCAPE || delta == COMPRESSED_DELTA_ESCAPE {
+            escapes.extend([i as u32, token.start() as u32, token.len() as u32]);
+        }
+        words.push(token.kind as u32 | len << 8 | delta << 16);
+        prev_start = token.start();
+    }
+    (words, escapes)
+}
+
+/// Decodes `count` kept tokens from mapped `tokens_compress` words and
+/// escape-list words; the escape entries may be in any order.
+///
+/// Like [`read_tokens_from_mapped`], `raw_kind` is set to the final kind
+/// until [`apply_raw_kinds_from_mapped`] overlays the raw kinds.
+pub fn decode_compressed_tokens(
+    words: &[u8],
+    escapes: &[u8],
+    count: usize,
+) -> Result<Vec<Token>, String> {
+    let needed = count
+        .checked_mul(4)
+        .ok_or_else(|| "decode_compressed_tokens: byte count overflow".to_string())?;
+    if words.len() < needed {
+        return Err(format!(
+            "decode_compressed_tokens: mapped slice too small (have {}, need >= {needed})",
+            words.len()
+        ));
+    }
+    let word = |bytes: &[u8]| u32::from_le_bytes(bytes.try_into().expect("escape word"));
+    let mut entries = escapes
+        .chunks_exact(COMPRESSED_ESCAPE_WORDS * 4)
+        .map(|entry| {
+            (
+                word(&entry[0..4]) as usize,
+                word(&entry[4..8]) as usize,
+                word(&entry[8..12]) as usize,
+            )
+        })
+        .collect::<Vec<_>>();
+    entries.sort_unstable_by_key(|&(index, _, _)| index);
+    let mut entries = entries.into_iter().peekable();
+
+    let mut out = Vec::with_capacity(count);
+    let mut prev_start = 0usize;
+    for (i, packed) in words[..needed].chunks_exact(4).map(word).enumerate() {
+        let kind_u32 = packed & 0xFF;
+        let kind = TokenKind::from_u32(kind_u32).ok_or_else(|| {
+            format!("decode_compressed_tokens: invalid token kind {kind_u32} at token {i}")
+        })?;
+        let len = (packed >> 8) & 0xFF;
+        let delta = packed >> 16;
+        let (start, len) = if len == COMPRESSED_LEN_ESCAPE || delta == COMPRESSED_DELTA_ESCAPE {
+            match entries.next() {
+                Some((index, start, len)) if index == i => (start, len),
+                _ => {
+                    return Err(format!(
+                        "decode_compressed_tokens: token {i} is escaped but has no escape entry"
+                    ));
+                }
+            }
+        } else {
+            (prev_start + delta as usize, len as usize)
+        };
+        let span = Span::try_from(start..start.saturating_add(len))
+            .map_err(|e| format!("decode_compressed_tokens: token {i}: {e}"))?;
+        out.push(Token {
+            kind,
+            raw_kind: kind,
+            span,
+        });
+        prev_start = start;
+    }
+    if let Some((index, _, _)) = entries.next() {
+        return Err(format!(
+            "decode_compressed_tokens: escape entry for token {index} matches no escaped token"
+        ));
+    }
+    Ok(out)
+}
+
+/// Builds host `Token` records for the all-boundary stream.
+///
+/// `end_positions` and `kinds` are the compacted ALL-stream outputs in boundary
+/// order. Boundaries are contiguous, so each token starts where the previous one
+/// ended.
+pub fn tokens_from_all_boundaries(
+    end_positions: &[u32],
+    kinds: &[u32],
+) -> Result<Vec<Token>, String> {
+    if end_positions.len() != kinds.len() {
+        return Err(format!(
+            "tokens_from_all_boundaries: {} end positions but {} kinds",
+            end_positions.len(),
+            kinds.len()
+        ));
+    }
+
+    let mut out = Vec::with_capacity(kinds.len());
+    let mut start = 0u32;
+    for (i, (&end, &kind_u32)) in end_positions.iter().zip(kinds).enumerate() {
+        let kind = TokenKind::from_u32(kind_u32).ok_or_else(|| {
+            format!("tokens_from_all_boundaries: invalid token kind {kind_u32} at boundary {i}")
+        })?;
+        if end < start {
+            return Err(format!(
+                "tokens_from_all_boundaries: boundary {i} ends at {end} before {start}"
+            ));
+        }
+        out.push(Token {
+            kind,
+            raw_kind: kind,
+            span: Span::new(start, end),
+        });
+        start = end;
+    }
+    Ok(out)
+}
+
+/// Replaces the kept entries of an all-boundary stream with the kept tokens.
+///
+/// The kept stream is a filter over `all` ([`is_kept`](crate::lexer::boundary::is_kept)),
+/// so its tokens map one-to-one, in order, onto the kept-kind entries of
+/// `all`; they carry the final kinds and ranges that `all` lacks.
+pub fn merge_kept_into_all(all: &mut [Token], kept: &[Token]) -> Result<(), String> {
+    let mut kept_tokens = kept.iter();
+    for (i, slot) in all.iter_mut().enumerate() {
+        if !crate::lexer::boundary::is_kept(Some(slot.kind)) {
+            continue;
+        }
+        let token = kept_tokens.next().ok_or_else(|| {
+            format!(
+                "merge_kept_into_all: ALL token {i} is kept, but only {} kept tokens were read",
+                kept.len()
+            )
+        })?;
+        *slot = *token;
+    }
+    if kept_tokens.next().is_some() {
+        return Err(format!(
+            "merge_kept_into_all: {} kept tokens but fewer kept-kind ALL tokens",
+            kept.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the number of power-of-two prefix-scan rounds for `val` elements.
+pub fn compute_rounds(val: u32) -> u32 {
+    let mut r = 0u32;
+    let mut s = 1u32;
+    while s < val {
+        r += 1;
+        s <<= 1;
+    }
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_bytes(kind: u32, start: u32, len: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&kind.to_le_bytes());
+        bytes.extend_from_slice(&start.to_le_bytes());
+        bytes.extend_from_slice(&len.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn read_tokens_from_mapped_decodes_valid_kind() {
+        let bytes = token_bytes(TokenKind::Ident as u32, 4, 3);
+
+        let tokens = read_tokens_from_mapped(&bytes, 1).expect("valid token readback");
+
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].kind, TokenKind::Ident);
+        assert_eq!(tokens[0].start(), 4);
+        assert_eq!(tokens[0].len(), 3);
+    }
+
+    #[test]
+    fn read_tokens_from_mapped_rejects_invalid_kind() {
+        let bytes = token_bytes(0, 4, 3);
+
+        let err = read_tokens_from_mapped(&bytes, 1).expect_err("invalid token kind");
+
+        assert!(
+            err.contains("invalid token kind 0"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn raw_kinds_overlay_retagged_tokens() {
+        let mut bytes = token_bytes(TokenKind::Let as u32, 0, 3);
+        bytes.extend(token_bytes(TokenKind::Ident as u32, 4, 1));
+        let mut tokens = read_tokens_from_mapped(&bytes, 2).expect("valid token readback");
+        assert_eq!(tokens[0].raw_kind, TokenKind::Let);
+
+        let raw = [TokenKind::Ident as u32, TokenKind::Ident as u32]
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>();
+        apply_raw_kinds_from_mapped(&mut tokens, &raw).expect("valid raw kinds");
+
+        assert_eq!(
+            (tokens[0].kind, tokens[0].raw_kind),
+            (TokenKind::Let, TokenKind::Ident)
+        );
+        assert_eq!(
+            (tokens[1].kind, tokens[1].raw_kind),
+            (TokenKind::Ident, TokenKind::Ident)
+        );
+        assert!(apply_raw_kinds_from_mapped(&mut tokens, &raw[..4]).is_err());
+        assert!(apply_raw_kinds_from_mapped(&mut tokens, &[0; 8]).is_err());
+    }
+
+    #[test]
+    fn all_boundary_tokens_start_at_previous_end() {
+        let tokens = tokens_from_all_boundaries(
+            &[3, 4, 6],
+            &[
+                TokenKind::Ident as u32,
+                TokenKind::White as u32,
+                TokenKind::Int as u32,
+            ],
+        )
+        .expect("valid all-boundary stream");
+
+        let spans = tokens
+            .iter()
+            .map(|token| (token.kind, token.start(), token.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spans,
+            [
+                (TokenKind::Ident, 0, 3),
+                (TokenKind::White, 3, 1),
+                (TokenKind::Int, 4, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn accept_states_unpack_low_lane_first() {
+        let bytes = [0x0302_0045u32, 0x0000_0009]
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            read_accept_states_from_mapped(&bytes, 3),
+            [0x45, 0x0302, 0x09]
+        );
+    }
+
+    #[test]
+    fn all_boundary_tokens_reject_invalid_kind() {
+        let err = tokens_from_all_boundaries(&[2], &[0xFFFF]).expect_err("invalid boundary kind");
+
+        assert!(
+            err.contains("invalid token kind 65535 at boundary 0"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn merge_kept_into_all_replaces_only_kept_entries() {
+        let mut all = tokens_from_all_boundaries(
+            &[2, 3, 5],
+            &[
+                TokenKind::Ident as u32,
+                TokenKind::White as u32,
+                TokenKind::Ident as u32,
+            ],
+        )
+        .expect("all stream");
+        let kept = vec![
+            Token {
+                kind: TokenKind::If,
+                raw_kind: TokenKind::Ident,
+                span: Span::new(0, 2),
+            },
+            Token::new(TokenKind::Ident, 3, 2),
+        ];
+
+        merge_kept_into_all(&mut all, &kept).expect("merge");
+
+        assert_eq!(
+            (all[0].kind, all[0].raw_kind),
+            (TokenKind::If, TokenKind::Ident)
+        );
+        assert_eq!(all[1].kind, TokenKind::White);
+        assert_eq!((all[2].start(), all[2].len()), (3, 2));
+        assert!(merge_kept_into_all(&mut all, &kept[..1]).is_err());
+    }
+
+    fn word_bytes(words: &[u32]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    fn kept(kind: TokenKind, start: usize, len: usize) -> Token {
+        Token::new(kind, start, len)
+    }
+
+    #[test]
+    fn adjacent_one_byte_tokens_compress_without_escapes() {
+        let tokens = (0..10_000)
+            .map(|i| kept(TokenKind::LParen, i, 1))
+            .collect::<Vec<_>>();
+
+        let (words, escapes) = encode_compressed_tokens(&tokens);
+
+        assert!(escapes.is_empty());
+        assert_eq!(
+            choose_token_transport(tokens.len(), 0),
+            TokenTransport::Compressed
+        );
+        assert_eq!(
+            token_transfer_bytes(TokenTransport::Compressed, tokens.len(), 0) * 3,
+            token_transfer_bytes(TokenTransport::Raw, tokens.len(), 0)
+        );
+        let decoded =
+            decode_compressed_tokens(&word_bytes(&words), &[], tokens.len()).expect("decode");
+        assert_eq!(decoded, tokens);
+    }
+
+    #[test]
+    fn tokens_between_megabyte_comments_fall_back_to_raw() {
+        // Each kept token follows a skipped 1 MiB block comment.
+        let stride = (1 << 20) + 1;
+        let tokens = (0..64)
+            .map(|i| kept(TokenKind::Ident, i * stride + (1 << 20), 1))
+            .collect::<Vec<_>>();
+
+        let (words, escapes) = encode_compressed_tokens(&tokens);
+        let escaped = escapes.len() / COMPRESSED_ESCAPE_WORDS;
+
+        assert_eq!(escaped, tokens.len());
+        assert_eq!(
+            choose_token_transport(tokens.len(), escaped),
+            TokenTransport::Raw
+        );
+        assert!(
+            token_transfer_bytes(TokenTransport::Compressed, tokens.len(), escaped)
+                > token_transfer_bytes(TokenTransport::Raw, tokens.len(), escaped)
+        );
+        let decoded =
+            decode_compressed_tokens(&word_bytes(&words), &word_bytes(&escapes), tokens.len())
+                .expect("decode");
+        assert_eq!(decoded, tokens);
+    }
+
+    #[test]
+    fn transport_falls_back_past_the_escape_rate_limit() {
+        let limit = COMPRESSED_ESCAPE_RATE_LIMIT as usize;
+        assert_eq!(choose_token_transport(0, 0), TokenTransport::Compressed);
+        assert_eq!(
+            choose_token_transport(100 * limit, 100),
+            TokenTransport::Compressed
+        );
+        assert_eq!(
+            choose_token_transport(100 * limit, 101),
+            TokenTransport::Raw
+        );
+        assert_eq!(choose_token_transport(3, 1), TokenTransport::Raw);
+    }
+
+    #[test]
+    fn long_tokens_escape_and_decode_from_shuffled_entries() {
+        let tokens = vec![
+            kept(TokenKind::Ident, 0, 3),
+            kept(TokenKind::BlockComment, 4, 255),
+            kept(TokenKind::Int, 259, 254),
+            kept(TokenKind::Ident, 70_000, 1),
+            kept(TokenKind::Ident, 70_001, 1),
+        ];
+
+        let (words, escapes) = encode_compressed_tokens(&tokens);
+        let mut entries = escapes
+            .chunks_exact(COMPRESSED_ESCAPE_WORDS)
+            .collect::<Vec<_>>();
+        assert_eq!(entries.len(), 2);
+        entries.reverse();
+        let shuffled = word_bytes(&entries.concat());
+
+        let decoded =
+            decode_compressed_tokens(&word_bytes(&words), &shuffled, tokens.len()).expect("decode");
+        assert_eq!(decoded, tokens);
+    }
+
+    #[test]
+    fn compressed_decode_rejects_missing_and_stray_escapes() {
+        let tokens = vec![
+            kept(TokenKind::Ident, 0, 300),
+            kept(TokenKind::Ident, 301, 1),
+        ];
+        let (words, escapes) = encode_compressed_tokens(&tokens);
+        let words = word_bytes(&words);
+
+        let err = decode_compressed_tokens(&words, &[], 2).expect_err("missing escape");
+        assert!(
+            err.contains("token 0 is escaped"),
+            "unexpected error: {err}"
+        );
+
+        let mut stray = escapes.clone();
+        stray.extend([1, 301, 1]);
+        let err =
+            decode_compressed_tokens(&words, &word_bytes(&stray), 2).expect_err("stray escape");
+        assert!(
+            err.contains("token 1 matches no"),
+            "unexpected error: {err}"
+        );
+
+        assert!(decode_compressed_tokens(&words[..4], &word_bytes(&escapes), 2).is_err());
+    }
+
+    #[test]
+    fn compressed_tokens_round_trip_generated_sources() {
+        use proptest::test_runner::{Config, TestCaseError, TestRunner};
+        use rand::{SeedableRng, rngs::StdRng};
+
+        use crate::{
+            dev::generator::{arb_source_gen_config, gen_source},
+            lexer::test_cpu::lex_on_test_cpu,
+        };
+
+        let mut runner = TestRunner::new(Config {
+            cases: 200,
+            failure_persistence: None,
+            ..Config::default()
+        });
+        runner
+            .run(
+                &(arb_source_gen_config(), proptest::num::u64::ANY),
+                |(config, seed)| {
+                    let src = gen_source(&mut StdRng::seed_from_u64(seed), &config);
+                    let tokens = lex_on_test_cpu(&src).unwrap();
+                    let (words, escapes) = encode_compressed_tokens(&tokens);
+                    let decoded = decode_compressed_tokens(
+                        &word_bytes(&words),
+                        &word_bytes(&escapes),
+                        tokens.len(),
+                    )
+                    .map_err(TestCaseError::fail)?;
+                    let spans = |tokens: &[Token]| {
+                        tokens
+                            .iter()
+                            .map(|t| (t.kind, t.start(), t.len()))
+                            .collect::<Vec<_>>()
+                    };
+                    if spans(&decoded) != spans(&tokens) {
+                        return Err(TestCaseError::fail(format!(
+                            "compressed round trip differs for {src:?}"
+                        )));
+                    }
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+}
@@ -0,0 +1,314 @@
+//! Known-good reference hashes, so benchmark runs also check their output.
+//!
+//! A [`Reference`] records what one input lexes (and, where a tool parses
+//! it, emits) to as FNV-1a 64 hashes. [`stream_hash`] hashes the canonical
+//! [`tokens_io`](crate::lexer::tokens_io) serialization rather than the
+//! in-memory tokens, so every readback variant of the same stream (raw or
+//! compressed, one submission or two) hashes the same, and `raw_kind` never
+//! takes part.
+//!
+//! References live one JSON file per input configuration in a directory,
+//! named by a caller-chosen key such as `seed42-len10000000`:
+//!
+//! ```json
+//! {"source_hash": 1, "token_count": 2, "token_hash": 3, "parse_hash": null}
+//! ```
+//!
+//! [`Reference`]: crate::lexer::verify::Reference
+//! [`stream_hash`]: crate::lexer::verify::stream_hash
+
+use std::{
+    fmt,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::lexer::{tokens_io, types::Token};
+
+/// Extension of reference files inside a reference directory.
+pub const REFERENCE_EXTENSION: &str = "json";
+
+/// FNV-1a 64 over everything written to it.
+#[derive(Debug, Clone, Copy)]
+struct Fnv1a64(u64);
+
+impl Default for Fnv1a64 {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Write for Fnv1a64 {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// FNV-1a 64 of `tokens` as [`tokens_io::write_tokens`] serializes them,
+/// without a source hash section.
+///
+/// Panics past `u32::MAX` tokens, which no lexer output reaches.
+pub fn stream_hash(tokens: &[Token]) -> u64 {
+    let mut hasher = Fnv1a64::default();
+    tokens_io::write_tokens(&mut hasher, tokens, None)
+        .expect("token counts and spans fit token-stream records");
+    hasher.0
+}
+
+/// FNV-1a 64 of a parser emit stream as little-endian `u32` words.
+pub fn emit_hash(emit: &[u32]) -> u64 {
+    let mut hasher = Fnv1a64::default();
+    for word in emit {
+        hasher
+            .write_all(&word.to_le_bytes())
+            .expect("hashing never fails");
+    }
+    hasher.0
+}
+
+/// Known-good output of one input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reference {
+    /// [`tokens_io::source_hash`] of the input the reference was recorded
+    /// from.
+    pub source_hash: u64,
+    /// Kept-token count.
+    pub token_count: usize,
+    /// [`stream_hash`] of the kept tokens.
+    pub token_hash: u64,
+    /// [`emit_hash`] of the parser emit stream, when the recording tool
+    /// parsed the input.
+    #[serde(default)]
+    pub parse_hash: Option<u64>,
+}
+
+impl Reference {
+    /// The reference of lexing `source` to `tokens`, without a parse hash.
+    pub fn for_tokens(source: &str, tokens: &[Token]) -> Self {
+        Self {
+            source_hash: tokens_io::source_hash(source),
+            token_count: tokens.len(),
+            token_hash: stream_hash(tokens),
+            parse_hash: None,
+        }
+    }
+
+    /// Adds the [`emit_hash`] of `emit`.
+    pub fn with_emit(self, emit: &[u32]) -> Self {
+        Self {
+            parse_hash: Some(emit_hash(emit)),
+            ..self
+        }
+    }
+
+    /// Checks `actual` against this known-good reference.
+    ///
+    /// A reference recorded from different source text says nothing about
+    /// `actual`, so that is [`Verdict::Unverified`] rather than a mismatch.
+    /// Parse hashes only count when both sides have one.
+    pub fn verify(&self, actual: &Reference) -> Verdict {
+        if self.source_hash != actual.source_hash {
+            return Verdict::Unverified(format!(
+                "reference was recorded from a different source ({:016x}, input is {:016x})",
+                self.source_hash, actual.source_hash
+            ));
+        }
+        let mut differences = Vec::new();
+        if self.token_count != actual.token_count {
+            differences.push(format!(
+                "token count {} (expected {})",
+                actual.token_count, self.token_count
+            ));
+        }
+        if self.token_hash != actual.token_hash {
+            differences.push(format!(
+                "token hash {:016x} (expected {:016x})",
+                actual.token_hash, self.token_hash
+            ));
+        }
+        if let (Some(expected), Some(found)) = (self.parse_hash, actual.parse_hash)
+            && expected != found
+        {
+            differences.push(format!(
+                "parse hash {found:016x} (expected {expected:016x})"
+            ));
+        }
+        if differences.is_empty() {
+            Verdict::Verified
+        } else {
+            Verdict::Mismatch(differences.join(", "))
+        }
+    }
+}
+
+/// Outcome of checking a run against its reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// The output matches the reference.
+    Verified,
+    /// Nothing to check against; the reason says why.
+    Unverified(String),
+    /// The output differs from the reference; the detail names each field.
+    Mismatch(String),
+}
+
+impl Verdict {
+    /// Whether a run should fail over this verdict.
+    pub fn is_mismatch(&self) -> bool {
+        matches!(self, Self::Mismatch(_))
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Verified => f.write_str("VERIFIED"),
+            Self::Unverified(reason) => write!(f, "UNVERIFIED ({reason})"),
+            Self::Mismatch(detail) => write!(f, "MISMATCH ({detail})"),
+        }
+    }
+}
+
+/// Path of the reference file for `key` inside `dir`.
+pub fn reference_path(dir: &Path, key: &str) -> PathBuf {
+    dir.join(format!("{key}.{REFERENCE_EXTENSION}"))
+}
+
+/// Writes `reference` as the reference for `key`, creating `dir` if needed.
+/// Returns the file written.
+pub fn write_reference(dir: &Path, key: &str, reference: &Reference) -> Result<PathBuf> {
+    fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    let path = reference_path(dir, key);
+    let mut json = serde_json::to_string_pretty(reference)?;
+    json.push('\n');
+    fs::write(&path, json).with_context(|| format!("write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Reads the reference for `key` from `dir`, or `None` when none was
+/// recorded.
+pub fn read_reference(dir: &Path, key: &str) -> Result<Option<Reference>> {
+    let path = reference_path(dir, key);
+    let json = match fs::read_to_string(&path) {
+        Ok(json) => json,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err).with_context(|| format!("read {}", path.display())),
+    };
+    let reference =
+        serde_json::from_str(&json).with_context(|| format!("parse {}", path.display()))?;
+    Ok(Some(reference))
+}
+
+/// Checks `actual` against the reference for `key` in `dir`, if one exists.
+pub fn verify_against(dir: &Path, key: &str, actual: &Reference) -> Result<Verdict> {
+    Ok(match read_reference(dir, key)? {
+        Some(reference) => reference.verify(actual),
+        None => Verdict::Unverified(format!(
+            "no reference at {}",
+            reference_path(dir, key).display()
+        )),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::{diff::fnv1a64, tables::tokens::TokenKind};
+
+    fn tokens() -> Vec<Token> {
+        vec![
+            Token::new(TokenKind::Ident, 0, 1),
+            Token::new(TokenKind::Assign, 2, 1),
+            Token::new(TokenKind::Int, 4, 2),
+        ]
+    }
+
+    #[test]
+    fn stream_hash_is_over_the_token_file_bytes() {
+        let tokens = tokens();
+        let mut bytes = Vec::new();
+        tokens_io::write_tokens(&mut bytes, &tokens, None).unwrap();
+        assert_eq!(stream_hash(&tokens), fnv1a64(&bytes));
+        assert_eq!(stream_hash(&[]), fnv1a64(b"LXTOKS01\0\0\0\0"));
+
+        let mut words = Vec::new();
+        for word in [7u32, 0x0102_0304] {
+            words.extend_from_slice(&word.to_le_bytes());
+        }
+        assert_eq!(emit_hash(&[7, 0x0102_0304]), fnv1a64(&words));
+    }
+
+    #[test]
+    fn stream_hash_ignores_raw_kinds_but_not_spans() {
+        let tokens = tokens();
+        let mut retagged = tokens.clone();
+        retagged[0].raw_kind = TokenKind::White;
+        assert_eq!(stream_hash(&retagged), stream_hash(&tokens));
+
+        let mut moved = tokens.clone();
+        moved[2] = Token::new(TokenKind::Int, 4, 3);
+        assert_ne!(stream_hash(&moved), stream_hash(&tokens));
+        assert_ne!(stream_hash(&tokens[..2]), stream_hash(&tokens));
+    }
+
+    #[test]
+    fn verdicts_compare_every_recorded_hash() {
+        let source = "x = 12";
+        let expected = Reference::for_tokens(source, &tokens());
+        assert_eq!(expected.verify(&expected), Verdict::Verified);
+
+        let mut short = tokens();
+        short.pop();
+        let verdict = expected.verify(&Reference::for_tokens(source, &short));
+        assert!(verdict.is_mismatch(), "{verdict}");
+        assert!(verdict.to_string().contains("token count 2 (expected 3)"));
+
+        let other = Reference::for_tokens("y = 12", &tokens());
+        assert!(matches!(expected.verify(&other), Verdict::Unverified(_)));
+
+        let parsed = expected.with_emit(&[1, 2, 3]);
+        assert_eq!(parsed.verify(&expected), Verdict::Verified);
+        assert!(parsed.verify(&expected.with_emit(&[1, 2])).is_mismatch());
+        assert_eq!(Verdict::Verified.to_string(), "VERIFIED");
+    }
+
+    #[test]
+    fn references_round_trip_through_a_directory() {
+        let dir = std::env::temp_dir().join(format!(
+            "laniusc-verify-{}-{:?}",
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        let key = "seed42-len6";
+        let reference = Reference::for_tokens("x = 12", &tokens()).with_emit(&[9]);
+
+        assert_eq!(read_reference(&dir, key).unwrap(), None);
+        let verdict = verify_against(&dir, key, &reference).unwrap();
+        assert!(matches!(verdict, Verdict::Unverified(_)), "{verdict}");
+
+        let path = write_reference(&dir, key, &reference).unwrap();
+        assert_eq!(path, dir.join("seed42-len6.json"));
+        assert_eq!(read_reference(&dir, key).unwrap(), Some(reference));
+        assert_eq!(
+            verify_against(&dir, key, &reference).unwrap(),
+            Verdict::Verified
+        );
+
+        fs::write(&path, "{").unwrap();
+        assert!(read_reference(&dir, key).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,36 @@
+//! Host-side core of the Lanius compiler: lexer tables and token types, the
+//! test CPU lexer, and the binary table format.
+//!
+//! Nothing here touches a GPU. `laniusc-gpu` builds the device drivers on top
+//! of these types and re-exports them at their old paths, and the
+//! `laniusc-compiler` facade re-exports both.
+
+/// Run-to-run reproducibility contract for read-back GPU outputs.
+pub mod determinism;
+
+/// Planned shapes of recorded GPU dispatches.
+pub mod dispatch;
+
+/// Development helpers and generated-workload support.
+#[cfg(any(test, feature = "dev"))]
+pub mod dev;
+
+/// Environment flag parsing helpers.
+#[doc(hidden)]
+pub mod env;
+
+/// The language revision, token kinds, keywords, and lexer features this
+/// build implements.
+pub mod language;
+
+/// Host lexer modules: tables, token records, and the test CPU oracle.
+pub mod lexer;
+
+/// Log targets used by library code and the default stderr logger.
+pub mod logging;
+
+/// Half-open byte spans shared by tokens, escapes, lints, and queries.
+pub mod span;
+
+/// Binary container format shared by the generated lexer and parser tables.
+pub mod tables;
@@ -0,0 +1,207 @@
+//! Log targets used by library code and a small stderr logger for binaries.
+//!
+//! Library code reports only through the `log` facade, under one target per
+//! module, so `RUST_LOG=laniusc::lexer::gpu=debug` gives a per-lex trace of
+//! buffer allocation, encode, submit, map-wait, and decode spans without the
+//! other phases. Output that an environment variable explicitly asks for,
+//! such as `LANIUS_GPU_TIMING`, is logged at `info`.
+
+use std::{io::Write, time::Instant};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Lexer driver: buffers, encode, submit, map-wait, and decode spans.
+pub const LEXER_GPU: &str = "laniusc::lexer::gpu";
+/// Lexer DFA table loading, building, and saving.
+pub const LEXER_TABLES: &str = "laniusc::lexer::tables";
+/// Parser driver and parser buffer events.
+pub const PARSER_GPU: &str = "laniusc::parser::gpu";
+/// Shared GPU device, pipeline, submit, and readback helpers.
+pub const GPU: &str = "laniusc::gpu";
+/// Resident GPU type checking.
+pub const TYPE_CHECKER: &str = "laniusc::type_checker";
+/// Backend lowering and linking.
+pub const CODEGEN: &str = "laniusc::codegen";
+/// Cross-phase compile orchestration.
+pub const COMPILER: &str = "laniusc::compiler";
+
+/// Filter used when `RUST_LOG` is unset: warnings from everything, plus this
+/// crate's opt-in `info` output.
+pub const DEFAULT_FILTER: &str = "warn,laniusc=info";
+
+/// Installs a stderr logger filtered by `RUST_LOG`, or by [`DEFAULT_FILTER`]
+/// when it is unset.
+///
+/// `RUST_LOG` is a comma-separated list of `level` or `target=level`
+/// directives; the longest matching target prefix wins. Fails if another
+/// logger is already installed.
+pub fn init_default() -> Result<(), log::SetLoggerError> {
+    let spec = std::env::var("RUST_LOG").unwrap_or_else(|_| DEFAULT_FILTER.to_string());
+    let logger = StderrLogger {
+        filter: LogFilter::parse(&spec),
+    };
+    log::set_max_level(logger.filter.max_level());
+    log::set_logger(Box::leak(Box::new(logger)))
+}
+
+/// Parsed `RUST_LOG`-style directives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFilter {
+    default: LevelFilter,
+    directives: Vec<(String, LevelFilter)>,
+}
+
+impl LogFilter {
+    /// Parses `level` and `target=level` directives, ignoring malformed ones.
+    pub fn parse(spec: &str) -> Self {
+        let mut filter = Self {
+            default: LevelFilter::Off,
+            directives: Vec::new(),
+        };
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    if let Ok(level) = level.trim().parse() {
+                        filter.directives.push((target.trim().to_string(), level));
+                    }
+                }
+                None => {
+                    if let Ok(level) = directive.parse() {
+                        filter.default = level;
+                    }
+                }
+            }
+        }
+        filter
+    }
+
+    /// Most verbose level any directive enables.
+    pub fn max_level(&self) -> LevelFilter {
+        self.directives
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+
+    /// Whether a record at `level` under `target` passes.
+    pub fn enabled(&self, target: &str, level: Level) -> bool {
+        let limit = self
+            .directives
+            .iter()
+            .filter(|(prefix, _)| {
+                target == prefix
+                    || target
+                        .strip_prefix(prefix.as_str())
+                        .is_some_and(|rest| rest.starts_with("::"))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default, |(_, level)| *level);
+        level <= limit
+    }
+}
+
+struct StderrLogger {
+    filter: LogFilter,
+}
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.filter.enabled(metadata.target(), metadata.level())
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let _ = writeln!(
+            std::io::stderr().lock(),
+            "[{} {}] {}",
+            record.level(),
+            record.target(),
+            record.args()
+        );
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stderr().flush();
+    }
+}
+
+/// Host-side span that logs its elapsed time at `debug` when dropped, so a
+/// span cut short by `?` is still reported.
+pub struct HostSpan {
+    target: &'static str,
+    name: &'static str,
+    started: Instant,
+    done: bool,
+}
+
+impl HostSpan {
+    /// Starts timing `name` under `target`.
+    pub fn enter(target: &'static str, name: &'static str) -> Self {
+        Self {
+            target,
+            name,
+            started: Instant::now(),
+            done: false,
+        }
+    }
+
+    /// Logs the span now instead of when it is dropped.
+    pub fn finish(self) {}
+
+    /// Logs the span followed by `detail`, e.g. the number of decoded tokens.
+    pub fn finish_with(mut self, detail: std::fmt::Arguments<'_>) {
+        self.done = true;
+        log::debug!(
+            target: self.target,
+            "{}: {:.3}ms {detail}",
+            self.name,
+            self.elapsed_ms()
+        );
+    }
+
+    fn elapsed_ms(&self) -> f64 {
+        self.started.elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+impl Drop for HostSpan {
+    fn drop(&mut self) {
+        if !self.done {
+            log::debug!(target: self.target, "{}: {:.3}ms", self.name, self.elapsed_ms());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_target_prefix_wins() {
+        let filter = LogFilter::parse("warn, laniusc=info ,laniusc::lexer::gpu=debug,bogus=loud");
+        assert!(filter.enabled(LEXER_GPU, Level::Debug));
+        assert!(!filter.enabled(LEXER_TABLES, Level::Debug));
+        assert!(filter.enabled(LEXER_TABLES, Level::Info));
+        assert!(!filter.enabled("wgpu_core::device", Level::Info));
+        assert!(filter.enabled("wgpu_core::device", Level::Warn));
+        assert_eq!(filter.max_level(), LevelFilter::Debug);
+    }
+
+    #[test]
+    fn target_prefixes_match_whole_path_segments() {
+        let filter = LogFilter::parse("laniusc=trace");
+        assert!(filter.enabled("laniusc", Level::Trace));
+        assert!(filter.enabled(GPU, Level::Trace));
+        assert!(!filter.enabled("laniusc_compiler::gpu::env", Level::Error));
+    }
+
+    #[test]
+    fn default_filter_keeps_other_crates_at_warn() {
+        let filter = LogFilter::parse(DEFAULT_FILTER);
+        assert!(filter.enabled(COMPILER, Level::Info));
+        assert!(!filter.enabled(COMPILER, Level::Debug));
+        assert!(!filter.enabled("wgpu_hal::vulkan", Level::Info));
+    }
+}
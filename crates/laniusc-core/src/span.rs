@@ -0,0 +1,292 @@
+//! Half-open byte spans over a source input.
+//!
+//! Offsets are `u32`: the GPU lexer addresses bytes in `u32` words and rejects
+//! inputs past its `MAX_INPUT_BYTES` limit, so every span it reports fits.
+//! Host code holding `usize` offsets converts at the boundary with
+//! [`Span::from_usize`] or `TryFrom<Range<usize>>`.
+//!
+//! [`Span::from_usize`]: crate::span::Span::from_usize
+
+use std::{fmt, ops::Range};
+
+/// Half-open byte range `start..end` of a source input.
+///
+/// Spans order by start, then end. An empty span (`start == end`) still sits
+/// at a position: spans around it contain it, but it intersects nothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Span {
+    /// First byte in the span.
+    pub start: u32,
+    /// One past the last byte in the span.
+    pub end: u32,
+}
+
+impl Span {
+    /// Returns `start..end`. Panics when `end < start`.
+    pub const fn new(start: u32, end: u32) -> Self {
+        assert!(start <= end, "span end before its start");
+        Self { start, end }
+    }
+
+    /// Returns the empty span at `offset`.
+    pub const fn empty(offset: u32) -> Self {
+        Self {
+            start: offset,
+            end: offset,
+        }
+    }
+
+    /// Returns `start..start + len`, or `None` when the end passes
+    /// `u32::MAX`.
+    pub const fn from_start_len(start: u32, len: u32) -> Option<Self> {
+        match start.checked_add(len) {
+            Some(end) => Some(Self { start, end }),
+            None => None,
+        }
+    }
+
+    /// Returns `start..start + len` for host offsets. Panics when the end
+    /// does not fit a `u32`, which no input the GPU lexer accepts reaches.
+    pub fn from_usize(start: usize, len: usize) -> Self {
+        start
+            .checked_add(len)
+            .and_then(|end| Self::try_from(start..end).ok())
+            .unwrap_or_else(|| panic!("span {start}+{len} does not fit u32 offsets"))
+    }
+
+    /// Byte length.
+    pub const fn len(self) -> u32 {
+        self.end - self.start
+    }
+
+    /// Whether the span covers no bytes.
+    pub const fn is_empty(self) -> bool {
+        self.start == self.end
+    }
+
+    /// The span as a `usize` range, for slicing the source.
+    pub const fn range(self) -> Range<usize> {
+        self.start as usize..self.end as usize
+    }
+
+    /// Whether byte `offset` lies inside the span; never for an empty span.
+    pub const fn contains(self, offset: u32) -> bool {
+        self.start <= offset && offset < self.end
+    }
+
+    /// Whether `other` lies entirely inside the span. An empty `other`
+    /// counts when its position is within `start..=end`.
+    pub const fn contains_span(self, other: Span) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    /// Whether the two spans share at least one byte; never for an empty
+    /// span.
+    pub const fn intersects(self, other: Span) -> bool {
+        !self.is_empty() && !other.is_empty() && self.start < other.end && other.start < self.end
+    }
+
+    /// The bytes both spans cover, or `None` when they share none.
+    pub fn intersection(self, other: Span) -> Option<Span> {
+        self.intersects(other).then(|| Span {
+            start: self.start.max(other.start),
+            end: self.end.min(other.end),
+        })
+    }
+
+    /// The smallest span covering both, including any gap between them.
+    pub fn union(self, other: Span) -> Span {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    /// The part of the span inside `bounds`. A span wholly outside `bounds`
+    /// becomes the empty span at the nearer edge of `bounds`.
+    pub fn clamp_to(self, bounds: Span) -> Span {
+        let start = self.start.clamp(bounds.start, bounds.end);
+        let end = self.end.clamp(start, bounds.end);
+        Span { start, end }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+impl From<Range<u32>> for Span {
+    /// Panics when the range is reversed.
+    fn from(range: Range<u32>) -> Self {
+        Self::new(range.start, range.end)
+    }
+}
+
+impl TryFrom<Range<usize>> for Span {
+    type Error = String;
+
+    /// Fails when the range is reversed or an offset does not fit a `u32`.
+    fn try_from(range: Range<usize>) -> Result<Self, String> {
+        let (Ok(start), Ok(end)) = (u32::try_from(range.start), u32::try_from(range.end)) else {
+            return Err(format!("span {range:?} does not fit u32 offsets"));
+        };
+        if end < start {
+            return Err(format!("span {range:?} ends before its start"));
+        }
+        Ok(Self { start, end })
+    }
+}
+
+impl From<Span> for Range<usize> {
+    fn from(span: Span) -> Self {
+        span.range()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX: u32 = u32::MAX;
+
+    fn s(start: u32, end: u32) -> Span {
+        Span::new(start, end)
+    }
+
+    #[test]
+    fn constructors_check_order_and_overflow() {
+        assert_eq!(Span::from_start_len(3, 4), Some(s(3, 7)));
+        assert_eq!(Span::from_start_len(MAX, 0), Some(Span::empty(MAX)));
+        assert_eq!(Span::from_start_len(MAX - 1, 1), Some(s(MAX - 1, MAX)));
+        assert_eq!(Span::from_start_len(MAX, 1), None);
+        assert_eq!(Span::from_start_len(1, MAX), None);
+        assert_eq!(Span::from_start_len(0, MAX), Some(s(0, MAX)));
+
+        assert_eq!(Span::from_usize(3, 4), s(3, 7));
+        assert_eq!(Span::from_usize(MAX as usize, 0), Span::empty(MAX));
+        assert!(std::panic::catch_unwind(|| Span::from_usize(MAX as usize, 1)).is_err());
+        assert!(std::panic::catch_unwind(|| Span::from_usize(usize::MAX, 1)).is_err());
+        assert!(std::panic::catch_unwind(|| Span::new(2, 1)).is_err());
+
+        assert_eq!(Span::try_from(2usize..5), Ok(s(2, 5)));
+        assert_eq!(Span::try_from(5usize..5), Ok(Span::empty(5)));
+        let reversed = Range::<usize> { start: 5, end: 2 };
+        assert!(Span::try_from(reversed).is_err());
+        assert!(Span::try_from(0..MAX as usize + 1).is_err());
+        assert_eq!(Span::from(2u32..5), s(2, 5));
+        assert_eq!(Range::<usize>::from(s(2, 5)), 2..5);
+    }
+
+    #[test]
+    fn length_and_emptiness() {
+        assert_eq!(s(3, 7).len(), 4);
+        assert_eq!(s(0, MAX).len(), MAX);
+        assert!(Span::empty(0).is_empty());
+        assert!(Span::empty(MAX).is_empty());
+        assert!(!s(MAX - 1, MAX).is_empty());
+        assert_eq!(Span::default(), Span::empty(0));
+        assert_eq!(&"abcdef"[s(1, 4).range()], "bcd");
+    }
+
+    #[test]
+    fn contains_is_half_open() {
+        let span = s(2, 5);
+        assert!(!span.contains(1));
+        assert!(span.contains(2));
+        assert!(span.contains(4));
+        assert!(!span.contains(5));
+        assert!(!Span::empty(3).contains(3));
+        assert!(s(MAX - 1, MAX).contains(MAX - 1));
+        assert!(!s(MAX - 1, MAX).contains(MAX));
+        assert!(!s(0, MAX).contains(MAX));
+    }
+
+    #[test]
+    fn contains_span_includes_edges_and_empty_spans() {
+        let span = s(2, 5);
+        assert!(span.contains_span(span));
+        assert!(span.contains_span(s(3, 4)));
+        assert!(span.contains_span(Span::empty(2)));
+        assert!(span.contains_span(Span::empty(5)));
+        assert!(!span.contains_span(Span::empty(6)));
+        assert!(!span.contains_span(s(1, 3)));
+        assert!(!span.contains_span(s(4, 6)));
+        assert!(Span::empty(4).contains_span(Span::empty(4)));
+        assert!(!Span::empty(4).contains_span(s(4, 5)));
+        assert!(s(0, MAX).contains_span(Span::empty(MAX)));
+    }
+
+    #[test]
+    fn intersects_needs_a_shared_byte() {
+        let span = s(2, 5);
+        assert!(span.intersects(s(4, 9)));
+        assert!(span.intersects(s(0, 3)));
+        assert!(span.intersects(s(3, 4)));
+        assert!(!span.intersects(s(5, 9)), "adjacent spans share no byte");
+        assert!(!span.intersects(s(0, 2)));
+        assert!(!span.intersects(Span::empty(3)));
+        assert!(!Span::empty(3).intersects(Span::empty(3)));
+        assert!(s(MAX - 1, MAX).intersects(s(0, MAX)));
+        for (a, b) in [(s(2, 5), s(4, 9)), (s(2, 5), s(5, 9)), (s(1, 1), s(0, 4))] {
+            assert_eq!(a.intersects(b), b.intersects(a), "{a} {b}");
+        }
+    }
+
+    #[test]
+    fn intersection_is_the_shared_bytes() {
+        assert_eq!(s(2, 5).intersection(s(4, 9)), Some(s(4, 5)));
+        assert_eq!(s(2, 9).intersection(s(4, 5)), Some(s(4, 5)));
+        assert_eq!(s(2, 5).intersection(s(5, 9)), None);
+        assert_eq!(s(2, 5).intersection(Span::empty(3)), None);
+        assert_eq!(
+            s(0, MAX).intersection(s(MAX - 1, MAX)),
+            Some(s(MAX - 1, MAX))
+        );
+    }
+
+    #[test]
+    fn union_covers_both_and_any_gap() {
+        assert_eq!(s(2, 5).union(s(4, 9)), s(2, 9));
+        assert_eq!(s(7, 9).union(s(2, 3)), s(2, 9));
+        assert_eq!(s(2, 5).union(s(3, 4)), s(2, 5));
+        assert_eq!(s(2, 5).union(Span::empty(8)), s(2, 8));
+        assert_eq!(Span::empty(0).union(Span::empty(MAX)), s(0, MAX));
+        assert_eq!(s(MAX, MAX).union(s(0, 1)), s(0, MAX));
+    }
+
+    #[test]
+    fn clamp_to_clips_into_bounds() {
+        let bounds = s(10, 20);
+        assert_eq!(s(12, 15).clamp_to(bounds), s(12, 15));
+        assert_eq!(s(5, 15).clamp_to(bounds), s(10, 15));
+        assert_eq!(s(15, 25).clamp_to(bounds), s(15, 20));
+        assert_eq!(s(0, 30).clamp_to(bounds), bounds);
+        assert_eq!(s(0, 5).clamp_to(bounds), Span::empty(10));
+        assert_eq!(s(25, 30).clamp_to(bounds), Span::empty(20));
+        assert_eq!(Span::empty(15).clamp_to(bounds), Span::empty(15));
+        assert_eq!(s(0, MAX).clamp_to(s(MAX, MAX)), Span::empty(MAX));
+        assert_eq!(s(5, 15).clamp_to(Span::empty(10)), Span::empty(10));
+        for span in [s(0, 5), s(5, 15), s(12, 15), s(15, 25), s(25, 30)] {
+            assert!(bounds.contains_span(span.clamp_to(bounds)), "{span}");
+        }
+    }
+
+    #[test]
+    fn spans_order_by_start_then_end() {
+        let mut spans = vec![s(4, 6), s(2, 9), s(2, 3), Span::empty(4), s(0, MAX)];
+        spans.sort();
+        assert_eq!(
+            spans,
+            [s(0, MAX), s(2, 3), s(2, 9), Span::empty(4), s(4, 6)]
+        );
+    }
+
+    #[test]
+    fn display_is_a_rust_range() {
+        assert_eq!(s(3, 7).to_string(), "3..7");
+        assert_eq!(Span::empty(0).to_string(), "0..0");
+        assert_eq!(s(0, MAX).to_string(), format!("0..{MAX}"));
+    }
+}
@@ -0,0 +1,449 @@
+//! Versioned container shared by the lexer and parser table files.
+//!
+//! A container starts with an 8-byte magic naming the table family, a `u32`
+//! format version, and a `u32` section count. Each section follows as an
+//! 8-byte tag, a `u32` flags word, a `u64` payload length, and the payload.
+//! All integers are little-endian.
+//!
+//! Readers skip sections whose tag they do not know, unless the writer
+//! flagged the section [`SECTION_CRITICAL`]. A feature that only adds data
+//! therefore adds a section rather than bumping [`FORMAT_VERSION`].
+//!
+//! Version 1 refers to the per-family layouts that predate the container:
+//! `LXTBLE02` merge tables, `LXDFA001` compact DFA tables, and `LXPRSE01` to
+//! `LXPRSE03` parse tables. Each family's loader still reads them, and its
+//! writer can still emit them for tools pinned to the old readers.
+//!
+//! [`SECTION_CRITICAL`]: crate::tables::format::SECTION_CRITICAL
+//! [`FORMAT_VERSION`]: crate::tables::format::FORMAT_VERSION
+
+use std::{
+    fmt,
+    io::{self, Write},
+};
+
+/// Version written into every container.
+pub const FORMAT_VERSION: u32 = 2;
+/// Section flag: a reader that does not know the tag must reject the file
+/// instead of skipping the section.
+pub const SECTION_CRITICAL: u32 = 1;
+
+/// Bytes before the first section.
+const HEADER_BYTES: u64 = 8 + 4 + 4;
+/// Bytes before each section payload.
+const SECTION_HEADER_BYTES: u64 = 8 + 4 + 8;
+
+/// Eight-byte magic or section tag.
+pub type Tag = [u8; 8];
+
+/// Layout a table writer emits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FormatVersion {
+    /// The family's layout from before the container.
+    V1,
+    /// Tagged sections in the shared container.
+    #[default]
+    V2,
+}
+
+/// One tagged section, either to write or as read from a container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section<'a> {
+    pub tag: Tag,
+    pub flags: u32,
+    pub data: &'a [u8],
+}
+
+impl<'a> Section<'a> {
+    /// A section that readers without its tag may skip.
+    pub fn new(tag: Tag, data: &'a [u8]) -> Self {
+        Self {
+            tag,
+            flags: 0,
+            data,
+        }
+    }
+
+    /// A section that readers without its tag must reject.
+    pub fn critical(tag: Tag, data: &'a [u8]) -> Self {
+        Self {
+            tag,
+            flags: SECTION_CRITICAL,
+            data,
+        }
+    }
+}
+
+/// Malformed or incompatible container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    /// The file does not start with the family's magic.
+    BadMagic { expected: Tag },
+    /// The container was written by an incompatible format version.
+    UnsupportedVersion(u32),
+    /// The file ends inside the header or a section.
+    Truncated,
+    /// Bytes follow the last section.
+    TrailingBytes(u64),
+    /// A tag occurs more than once.
+    DuplicateSection(Tag),
+    /// A section the reader does not know is flagged critical.
+    UnknownCriticalSection(Tag),
+    /// A section the reader needs is absent.
+    MissingSection(Tag),
+    /// A section payload ends early or has bytes its reader did not consume.
+    BadSectionLength(Tag),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tag = |tag: &Tag| tag.escape_ascii().to_string();
+        match self {
+            Self::BadMagic { expected } => write!(f, "bad magic, expected {}", tag(expected)),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported table format version {version}, expected {FORMAT_VERSION}"
+            ),
+            Self::Truncated => write!(f, "truncated table container"),
+            Self::TrailingBytes(n) => write!(f, "{n} trailing bytes after the last section"),
+            Self::DuplicateSection(t) => write!(f, "section {} occurs twice", tag(t)),
+            Self::UnknownCriticalSection(t) => {
+                write!(f, "unknown critical section {}", tag(t))
+            }
+            Self::MissingSection(t) => write!(f, "missing section {}", tag(t)),
+            Self::BadSectionLength(t) => {
+                write!(f, "section {} has the wrong length", tag(t))
+            }
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// Total encoded size of a container whose sections have these payload
+/// lengths.
+pub fn encoded_len(payload_lens: &[u64]) -> u64 {
+    HEADER_BYTES
+        + payload_lens
+            .iter()
+            .map(|len| SECTION_HEADER_BYTES + len)
+            .sum::<u64>()
+}
+
+/// Encodes `sections` in order under `magic`.
+pub fn encode(magic: &Tag, sections: &[Section<'_>]) -> Vec<u8> {
+    let lens: Vec<u64> = sections.iter().map(|s| s.data.len() as u64).collect();
+    let mut out = Vec::with_capacity(encoded_len(&lens) as usize);
+    let mut writer = ContainerWriter::new(&mut out, magic, sections.len() as u32)
+        .expect("writing to a Vec cannot fail");
+    for section in sections {
+        writer
+            .section(section.tag, section.flags, section.data)
+            .expect("writing to a Vec cannot fail");
+    }
+    writer.finish().expect("every declared section was written");
+    out
+}
+
+/// Streams a container whose section count and payload lengths are known up
+/// front, so large payloads never need to be held in memory.
+///
+/// Payload bytes go through [`Write`] after [`Self::begin_section`]; writing
+/// past the declared length, or starting a section before the previous one is
+/// complete, fails.
+pub struct ContainerWriter<W: Write> {
+    inner: W,
+    sections_left: u32,
+    open: Option<(Tag, u64)>,
+}
+
+impl<W: Write> ContainerWriter<W> {
+    /// Writes the header for `section_count` sections.
+    pub fn new(mut inner: W, magic: &Tag, section_count: u32) -> io::Result<Self> {
+        inner.write_all(magic)?;
+        inner.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        inner.write_all(&section_count.to_le_bytes())?;
+        Ok(Self {
+            inner,
+            sections_left: section_count,
+            open: None,
+        })
+    }
+
+    /// Starts a section whose payload is exactly `len` bytes.
+    pub fn begin_section(&mut self, tag: Tag, flags: u32, len: u64) -> io::Result<()> {
+        self.close_section()?;
+        if self.sections_left == 0 {
+            return Err(invalid_data("more sections than the header declares"));
+        }
+        self.sections_left -= 1;
+        self.inner.write_all(&tag)?;
+        self.inner.write_all(&flags.to_le_bytes())?;
+        self.inner.write_all(&len.to_le_bytes())?;
+        self.open = Some((tag, len));
+        Ok(())
+    }
+
+    /// Writes a whole section.
+    pub fn section(&mut self, tag: Tag, flags: u32, data: &[u8]) -> io::Result<()> {
+        self.begin_section(tag, flags, data.len() as u64)?;
+        self.write_all(data)
+    }
+
+    /// Checks that every declared section was written in full.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.close_section()?;
+        if self.sections_left != 0 {
+            return Err(invalid_data(format!(
+                "{} declared sections were not written",
+                self.sections_left
+            )));
+        }
+        Ok(self.inner)
+    }
+
+    fn close_section(&mut self) -> io::Result<()> {
+        match self.open.take() {
+            Some((tag, left)) if left != 0 => Err(invalid_data(format!(
+                "section {} is {left} bytes short",
+                tag.escape_ascii()
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<W: Write> Write for ContainerWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some((tag, left)) = &mut self.open else {
+            return Err(invalid_data("payload written outside a section"));
+        };
+        if buf.len() as u64 > *left {
+            return Err(invalid_data(format!(
+                "section {} payload exceeds its declared length",
+                tag.escape_ascii()
+            )));
+        }
+        let n = self.inner.write(buf)?;
+        *left -= n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Sections of a parsed container whose tags the reader knows.
+#[derive(Debug)]
+pub struct Container<'a> {
+    sections: Vec<Section<'a>>,
+}
+
+impl<'a> Container<'a> {
+    /// Parses a container with `magic`, keeping the sections tagged in
+    /// `known` and skipping the rest.
+    ///
+    /// Fails on truncation, trailing bytes, repeated tags, another format
+    /// version, or an unknown section flagged critical.
+    pub fn parse(data: &'a [u8], magic: &Tag, known: &[Tag]) -> Result<Self, FormatError> {
+        let mut rest = data;
+        if rest.len() < magic.len() || &rest[..magic.len()] != magic {
+            return Err(FormatError::BadMagic { expected: *magic });
+        }
+        rest = &rest[magic.len()..];
+        let version = take_u32(&mut rest)?;
+        if version != FORMAT_VERSION {
+            return Err(FormatError::UnsupportedVersion(version));
+        }
+        let count = take_u32(&mut rest)?;
+
+        let mut seen: Vec<Tag> = Vec::new();
+        let mut sections = Vec::new();
+        for _ in 0..count {
+            let tag: Tag = take_bytes(&mut rest, 8)?.try_into().expect("8 bytes");
+            let flags = take_u32(&mut rest)?;
+            let len = u64::from_le_bytes(take_bytes(&mut rest, 8)?.try_into().expect("8 bytes"));
+            let len = usize::try_from(len).map_err(|_| FormatError::Truncated)?;
+            let data = take_bytes(&mut rest, len)?;
+            if seen.contains(&tag) {
+                return Err(FormatError::DuplicateSection(tag));
+            }
+            seen.push(tag);
+            if known.contains(&tag) {
+                sections.push(Section { tag, flags, data });
+            } else if flags & SECTION_CRITICAL != 0 {
+                return Err(FormatError::UnknownCriticalSection(tag));
+            }
+        }
+        if !rest.is_empty() {
+            return Err(FormatError::TrailingBytes(rest.len() as u64));
+        }
+        Ok(Self { sections })
+    }
+
+    /// Payload of the section tagged `tag`, if present.
+    pub fn section(&self, tag: &Tag) -> Option<&'a [u8]> {
+        self.sections
+            .iter()
+            .find(|section| &section.tag == tag)
+            .map(|section| section.data)
+    }
+
+    /// Payload of the section tagged `tag`, which the reader needs.
+    pub fn require(&self, tag: &Tag) -> Result<&'a [u8], FormatError> {
+        self.section(tag).ok_or(FormatError::MissingSection(*tag))
+    }
+
+    /// Known sections in file order.
+    pub fn sections(&self) -> &[Section<'a>] {
+        &self.sections
+    }
+}
+
+/// Fails unless a section reader consumed the whole payload of `tag`.
+pub fn expect_consumed(tag: &Tag, rest: &[u8]) -> Result<(), FormatError> {
+    if rest.is_empty() {
+        Ok(())
+    } else {
+        Err(FormatError::BadSectionLength(*tag))
+    }
+}
+
+fn take_bytes<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8], FormatError> {
+    if buf.len() < len {
+        return Err(FormatError::Truncated);
+    }
+    let (head, tail) = buf.split_at(len);
+    *buf = tail;
+    Ok(head)
+}
+
+fn take_u32(buf: &mut &[u8]) -> Result<u32, FormatError> {
+    Ok(u32::from_le_bytes(
+        take_bytes(buf, 4)?.try_into().expect("4 bytes"),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAGIC: Tag = *b"LXTESTTB";
+    const HEAD: Tag = *b"TESTHEAD";
+    const BODY: Tag = *b"TESTBODY";
+    const NOTE: Tag = *b"TESTNOTE";
+
+    fn sample() -> Vec<u8> {
+        encode(
+            &MAGIC,
+            &[
+                Section::critical(HEAD, &[1, 2, 3, 4]),
+                Section::new(NOTE, b"skip me"),
+                Section::critical(BODY, &[9; 10]),
+            ],
+        )
+    }
+
+    #[test]
+    fn known_sections_round_trip_and_unknown_ones_are_skipped() {
+        let bytes = sample();
+        assert_eq!(bytes.len() as u64, encoded_len(&[4, 7, 10]));
+        let container = Container::parse(&bytes, &MAGIC, &[HEAD, BODY]).unwrap();
+        assert_eq!(container.require(&HEAD).unwrap(), [1, 2, 3, 4]);
+        assert_eq!(container.require(&BODY).unwrap(), [9; 10]);
+        assert_eq!(container.section(&NOTE), None);
+        assert_eq!(
+            container
+                .sections()
+                .iter()
+                .map(|s| s.tag)
+                .collect::<Vec<_>>(),
+            [HEAD, BODY]
+        );
+        assert_eq!(
+            container.require(&*b"TESTMISS").unwrap_err(),
+            FormatError::MissingSection(*b"TESTMISS")
+        );
+    }
+
+    #[test]
+    fn every_truncation_is_rejected() {
+        let bytes = sample();
+        for len in 0..bytes.len() {
+            let err = Container::parse(&bytes[..len], &MAGIC, &[HEAD, BODY]).unwrap_err();
+            let expected = if len < MAGIC.len() {
+                FormatError::BadMagic { expected: MAGIC }
+            } else {
+                FormatError::Truncated
+            };
+            assert_eq!(err, expected, "prefix of {len} bytes");
+        }
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert_eq!(
+            Container::parse(&extended, &MAGIC, &[HEAD, BODY]).unwrap_err(),
+            FormatError::TrailingBytes(1)
+        );
+    }
+
+    #[test]
+    fn unknown_critical_sections_duplicates_and_versions_are_rejected() {
+        let bytes = sample();
+        assert_eq!(
+            Container::parse(&bytes, &MAGIC, &[HEAD]).unwrap_err(),
+            FormatError::UnknownCriticalSection(BODY)
+        );
+
+        let twice = encode(&MAGIC, &[Section::new(NOTE, &[]), Section::new(NOTE, &[1])]);
+        assert_eq!(
+            Container::parse(&twice, &MAGIC, &[]).unwrap_err(),
+            FormatError::DuplicateSection(NOTE)
+        );
+
+        let mut future = bytes.clone();
+        future[8..12].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        assert_eq!(
+            Container::parse(&future, &MAGIC, &[HEAD, BODY]).unwrap_err(),
+            FormatError::UnsupportedVersion(FORMAT_VERSION + 1)
+        );
+        assert_eq!(
+            Container::parse(&bytes, b"LXOTHER_", &[HEAD, BODY]).unwrap_err(),
+            FormatError::BadMagic {
+                expected: *b"LXOTHER_"
+            }
+        );
+    }
+
+    #[test]
+    fn streamed_sections_must_match_their_declared_lengths() {
+        let mut writer = ContainerWriter::new(Vec::new(), &MAGIC, 2).unwrap();
+        writer.begin_section(BODY, 0, 4).unwrap();
+        writer.write_all(&[1, 2]).unwrap();
+        writer.write_all(&[3, 4]).unwrap();
+        assert!(writer.write_all(&[5]).is_err());
+        writer.begin_section(HEAD, 0, 2).unwrap();
+        writer.write_all(&[6]).unwrap();
+        assert!(writer.begin_section(NOTE, 0, 0).is_err());
+
+        let mut writer = ContainerWriter::new(Vec::new(), &MAGIC, 2).unwrap();
+        writer.section(HEAD, 0, &[7]).unwrap();
+        assert!(writer.finish().is_err());
+
+        let mut writer = ContainerWriter::new(Vec::new(), &MAGIC, 1).unwrap();
+        writer.begin_section(HEAD, SECTION_CRITICAL, 3).unwrap();
+        writer.write_all(&[1, 2, 3]).unwrap();
+        let bytes = writer.finish().unwrap();
+        let container = Container::parse(&bytes, &MAGIC, &[HEAD]).unwrap();
+        assert_eq!(container.sections()[0].flags, SECTION_CRITICAL);
+        assert_eq!(expect_consumed(&HEAD, &[]), Ok(()));
+        assert_eq!(
+            expect_consumed(&HEAD, &[0]),
+            Err(FormatError::BadSectionLength(HEAD))
+        );
+    }
+}
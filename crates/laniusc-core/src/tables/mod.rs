@@ -0,0 +1,4 @@
+/// Versioned section container shared by the lexer and parser table files.
+pub mod format;
+
+pub use format::{FormatError, FormatVersion};
@@ -105,7 +105,7 @@ fn workspace_root() -> PathBuf {
         .parent()
         .and_then(Path::parent)
         .map(Path::to_path_buf)
-        .expect("laniusc-compiler should live under crates/")
+        .expect("laniusc-gpu should live under crates/")
 }
 
 fn shader_artifact_root(workspace_root: &Path) -> PathBuf {
@@ -0,0 +1,92 @@
+/*
+ * C interface to the Lanius GPU lexer.
+ *
+ * Build the shared library with `cargo build -p laniusc-capi --release`;
+ * it is `liblanius_lexer.so`, `liblanius_lexer.dylib`, or `lanius_lexer.dll`.
+ * The Rust side lives in `crates/laniusc-gpu/src/capi.rs`, and a test
+ * there checks this header against it.
+ *
+ * Every function that can fail returns a lanius_status_t and, on failure,
+ * leaves a message for lanius_last_error_message() on the calling thread.
+ * Panics inside the library are caught and reported as LANIUS_ERR_PANIC.
+ */
+#ifndef LANIUS_LEXER_H
+#define LANIUS_LEXER_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef int32_t lanius_status_t;
+
+#define LANIUS_OK 0
+#define LANIUS_ERR_NULL_ARGUMENT 1
+#define LANIUS_ERR_INVALID_UTF8 2
+#define LANIUS_ERR_INIT 3
+#define LANIUS_ERR_LEX 4
+#define LANIUS_ERR_PANIC 5
+
+/*
+ * Token buffer layout, all integers little-endian:
+ *
+ *   offset  size    field
+ *   0       8       magic LANIUS_TOKENS_MAGIC (not NUL-terminated)
+ *   8       4       uint32_t token count N
+ *   12      12 * N  N records of lanius_token_t
+ *
+ * The buffer is 4-byte aligned, so on little-endian hosts the records can be
+ * read in place as lanius_token_t[N] starting at LANIUS_TOKENS_HEADER_BYTES.
+ */
+#define LANIUS_TOKENS_MAGIC "LXTOKS01"
+#define LANIUS_TOKENS_HEADER_BYTES 12
+#define LANIUS_TOKENS_RECORD_BYTES 12
+
+typedef struct lanius_token {
+    uint32_t kind;
+    uint32_t start;
+    uint32_t len;
+} lanius_token_t;
+
+/* Opaque lexer handle. */
+typedef struct lanius_lexer lanius_lexer_t;
+
+/*
+ * Creates a lexer on the process-wide GPU device. *out_handle is set to NULL
+ * on failure.
+ */
+lanius_status_t lanius_lexer_create(lanius_lexer_t **out_handle);
+
+/*
+ * Lexes len bytes of UTF-8 source; source may be NULL when len is 0. On
+ * success *out_tokens holds a token buffer to release with
+ * lanius_tokens_free() and *out_count its token count. On failure they are
+ * NULL and 0. Source that is not valid UTF-8 fails with
+ * LANIUS_ERR_INVALID_UTF8.
+ */
+lanius_status_t lanius_lexer_lex(lanius_lexer_t *handle, const uint8_t *source, size_t len, uint8_t **out_tokens, uint32_t *out_count);
+
+/*
+ * Frees a token buffer from lanius_lexer_lex(); its header must be unmodified.
+ * NULL is a no-op. Buffers outlive the lexer that produced them.
+ */
+void lanius_tokens_free(uint8_t *tokens);
+
+/* Destroys a lexer handle. NULL is a no-op. */
+void lanius_lexer_destroy(lanius_lexer_t *handle);
+
+/*
+ * Copies this thread's last error message into buf as a NUL-terminated string
+ * truncated to cap - 1 bytes, and returns the full message length without the
+ * NUL, or 0 when no call has failed. With buf NULL or cap 0 it only returns the
+ * length.
+ */
+size_t lanius_last_error_message(char *buf, size_t cap);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* LANIUS_LEXER_H */
@@ -0,0 +1,46 @@
+use std::fmt;
+
+use crate::shader_artifacts;
+
+/// Versions and shader provenance of this build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    pub profile: &'static str,
+    pub wgpu_version: &'static str,
+    /// `compiled` when `slangc` built the shaders, `vendored` when they were
+    /// copied from `shaders/prebuilt/`.
+    pub shader_mode: String,
+    /// Version of the `slangc` that built the shaders, in either mode.
+    pub slangc_version: String,
+    pub shader_digest: String,
+}
+
+/// Returns the provenance of this build, for bug reports.
+pub fn build_info() -> BuildInfo {
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        profile: option_env!("LANIUS_BUILD_PROFILE").unwrap_or("unknown"),
+        wgpu_version: option_env!("LANIUS_WGPU_VERSION").unwrap_or("unknown"),
+        shader_mode: shader_artifacts::build_mode(),
+        slangc_version: shader_artifacts::slangc_version().unwrap_or_else(|| {
+            option_env!("LANIUS_SLANGC_VERSION")
+                .unwrap_or("unknown")
+                .to_string()
+        }),
+        shader_digest: shader_artifacts::digest(),
+    }
+}
+
+impl fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "laniusc {} ({})", self.version, self.profile)?;
+        writeln!(f, "wgpu: {}", self.wgpu_version)?;
+        writeln!(
+            f,
+            "shaders: {} by slangc {}",
+            self.shader_mode, self.slangc_version
+        )?;
+        write!(f, "shader-artifact-digest: {}", self.shader_digest)
+    }
+}
@@ -0,0 +1,578 @@
+//! C ABI for embedding the GPU lexer in non-Rust hosts.
+//!
+//! `include/lanius_lexer.h` declares this surface; the `laniusc-capi` crate
+//! builds it into a cdylib. Every entry point catches panics and reports them
+//! as [`LANIUS_ERR_PANIC`], and every failure leaves a message for
+//! [`lanius_last_error_message`] on the calling thread.
+//!
+//! Tokens come back in the flat token-stream layout of
+//! [`tokens_io`](crate::lexer::tokens_io), without the optional source-hash
+//! section, in a buffer aligned for reading the records as [`LaniusToken`]s.
+//! Release it with [`lanius_tokens_free`].
+//!
+//! The lexer takes UTF-8 source: input that is not valid UTF-8 fails with
+//! [`LANIUS_ERR_INVALID_UTF8`] instead of being lexed.
+//!
+//! [`LANIUS_ERR_PANIC`]: crate::capi::LANIUS_ERR_PANIC
+//! [`lanius_last_error_message`]: crate::capi::lanius_last_error_message
+//! [`LaniusToken`]: crate::capi::LaniusToken
+//! [`lanius_tokens_free`]: crate::capi::lanius_tokens_free
+//! [`LANIUS_ERR_INVALID_UTF8`]: crate::capi::LANIUS_ERR_INVALID_UTF8
+
+use std::{
+    cell::RefCell,
+    ffi::{CString, c_char},
+    panic::{AssertUnwindSafe, catch_unwind},
+    ptr,
+};
+
+use anyhow::{Result, anyhow};
+
+use crate::lexer::{
+    GpuLexer,
+    LexOptions,
+    tokens_io::{self, TOKENS_MAGIC},
+};
+
+/// Result code of every fallible entry point.
+pub type LaniusStatus = i32;
+
+/// The call succeeded.
+pub const LANIUS_OK: LaniusStatus = 0;
+/// A required pointer argument was null.
+pub const LANIUS_ERR_NULL_ARGUMENT: LaniusStatus = 1;
+/// The source bytes are not valid UTF-8.
+pub const LANIUS_ERR_INVALID_UTF8: LaniusStatus = 2;
+/// The GPU device or lexer could not be initialized.
+pub const LANIUS_ERR_INIT: LaniusStatus = 3;
+/// Lexing failed, for example on a lexical error or a GPU failure.
+pub const LANIUS_ERR_LEX: LaniusStatus = 4;
+/// The call panicked; the panic was caught at the boundary.
+pub const LANIUS_ERR_PANIC: LaniusStatus = 5;
+
+/// Bytes before the first record of a token buffer.
+pub const LANIUS_TOKENS_HEADER_BYTES: usize = 12;
+/// Bytes per token record.
+pub const LANIUS_TOKENS_RECORD_BYTES: usize = 12;
+
+/// One token record, as laid out in a token buffer on a little-endian host.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LaniusToken {
+    /// `TokenKind` discriminant.
+    pub kind: u32,
+    /// Start byte offset into the lexed source.
+    pub start: u32,
+    /// Length in bytes.
+    pub len: u32,
+}
+
+/// Opaque lexer handle owned by the host between
+/// [`lanius_lexer_create`] and [`lanius_lexer_destroy`].
+///
+/// Calls block on the handle's lexer with `pollster`; no async runtime is
+/// involved.
+pub struct LaniusLexer {
+    lexer: GpuLexer,
+}
+
+thread_local! {
+    /// Message of the last failed call on this thread.
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    // Interior NULs would truncate the message on the C side anyway.
+    let message = CString::new(message.replace('\0', "\\0")).expect("NULs were replaced");
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Runs `body`, turning an error or a panic into a status code and a last
+/// error message.
+fn ffi_call(body: impl FnOnce() -> Result<(), (LaniusStatus, String)>) -> LaniusStatus {
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => LANIUS_OK,
+        Ok(Err((status, message))) => {
+            set_last_error(message);
+            status
+        }
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "non-string panic payload".to_string());
+            set_last_error(format!("panic in lanius C API: {message}"));
+            LANIUS_ERR_PANIC
+        }
+    }
+}
+
+fn null_argument(name: &str) -> (LaniusStatus, String) {
+    (LANIUS_ERR_NULL_ARGUMENT, format!("{name} must not be null"))
+}
+
+/// Encodes `tokens` into a word-aligned token buffer.
+fn encode_tokens(tokens: &[crate::lexer::Token]) -> Result<(Box<[u32]>, u32)> {
+    let mut bytes =
+        Vec::with_capacity(LANIUS_TOKENS_HEADER_BYTES + tokens.len() * LANIUS_TOKENS_RECORD_BYTES);
+    tokens_io::write_tokens(&mut bytes, tokens, None)?;
+    let count = u32::try_from(tokens.len())
+        .map_err(|_| anyhow!("{} tokens exceed a u32 count", tokens.len()))?;
+    // Header and records are whole words, so the byte image copies exactly.
+    let words = bytes
+        .chunks_exact(4)
+        .map(|word| u32::from_ne_bytes(word.try_into().expect("4-byte chunk")))
+        .collect();
+    Ok((words, count))
+}
+
+/// Word length of the token buffer whose header starts at `tokens`.
+///
+/// # Safety
+/// `tokens` must point at a header written by [`lanius_lexer_lex`].
+unsafe fn token_buffer_words(tokens: *const u32) -> usize {
+    // SAFETY: the caller passes an intact header, whose third word is the count.
+    let count = u32::from_le(unsafe { *tokens.add(2) }) as usize;
+    (LANIUS_TOKENS_HEADER_BYTES + count * LANIUS_TOKENS_RECORD_BYTES) / 4
+}
+
+/// Creates a lexer on the process-wide GPU device and stores it in
+/// `*out_handle`, which is set to null on failure.
+///
+/// # Safety
+/// `out_handle` must be null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn lanius_lexer_create(out_handle: *mut *mut LaniusLexer) -> LaniusStatus {
+    ffi_call(|| {
+        if out_handle.is_null() {
+            return Err(null_argument("out_handle"));
+        }
+        // SAFETY: checked non-null; the caller guarantees it is writable.
+        unsafe { out_handle.write(ptr::null_mut()) };
+        let lexer = pollster::block_on(GpuLexer::new())
+            .map_err(|err| (LANIUS_ERR_INIT, format!("create GPU lexer: {err:#}")))?;
+        let handle = Box::into_raw(Box::new(LaniusLexer { lexer }));
+        // SAFETY: as above.
+        unsafe { out_handle.write(handle) };
+        Ok(())
+    })
+}
+
+/// Lexes `len` bytes at `source` and stores a token buffer in `*out_tokens`
+/// and its token count in `*out_count`. On failure `*out_tokens` is null
+/// and `*out_count` is 0.
+///
+/// `source` may be null when `len` is 0.
+///
+/// # Safety
+/// `handle` must come from [`lanius_lexer_create`] and not be destroyed;
+/// `source` must be readable for `len` bytes; the out pointers must be null
+/// or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn lanius_lexer_lex(
+    handle: *mut LaniusLexer,
+    source: *const u8,
+    len: usize,
+    out_tokens: *mut *mut u8,
+    out_count: *mut u32,
+) -> LaniusStatus {
+    ffi_call(|| {
+        if out_tokens.is_null() {
+            return Err(null_argument("out_tokens"));
+        }
+        if out_count.is_null() {
+            return Err(null_argument("out_count"));
+        }
+        // SAFETY: checked non-null; the caller guarantees they are writable.
+        unsafe {
+            out_tokens.write(ptr::null_mut());
+            out_count.write(0);
+        }
+        if handle.is_null() {
+            return Err(null_argument("handle"));
+        }
+        if source.is_null() && len != 0 {
+            return Err(null_argument("source"));
+        }
+        let bytes = if len == 0 {
+            &[][..]
+        } else {
+            // SAFETY: checked non-null; the caller guarantees `len` readable bytes.
+            unsafe { std::slice::from_raw_parts(source, len) }
+        };
+        let source = std::str::from_utf8(bytes).map_err(|err| {
+            (
+                LANIUS_ERR_INVALID_UTF8,
+                format!("source is not valid UTF-8: {err}"),
+            )
+        })?;
+        // SAFETY: the caller passes a live handle from `lanius_lexer_create`.
+        let lexer = unsafe { &(*handle).lexer };
+        // Full readback regardless of `LANIUS_READBACK`: the host asked for tokens.
+        let tokens = pollster::block_on(lexer.lex_with_options(source, LexOptions::default()))
+            .map_err(|err| (LANIUS_ERR_LEX, format!("lex: {err:#}")))?
+            .tokens;
+        let (words, count) =
+            encode_tokens(&tokens).map_err(|err| (LANIUS_ERR_LEX, format!("{err:#}")))?;
+        let buffer = Box::into_raw(words).cast::<u32>();
+        // SAFETY: as above.
+        unsafe {
+            out_tokens.write(buffer.cast());
+            out_count.write(count);
+        }
+        Ok(())
+    })
+}
+
+/// Frees a token buffer from [`lanius_lexer_lex`]. Null is a no-op.
+///
+/// # Safety
+/// `tokens` must be null or a buffer from [`lanius_lexer_lex`] that has not
+/// been freed, with its header unmodified.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn lanius_tokens_free(tokens: *mut u8) {
+    if tokens.is_null() {
+        return;
+    }
+    let _ = ffi_call(|| {
+        let words = tokens.cast::<u32>();
+        // SAFETY: the caller passes an unfreed buffer with an intact header,
+        // allocated as a boxed word slice of exactly this length.
+        unsafe {
+            let len = token_buffer_words(words);
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(words, len)));
+        }
+        Ok(())
+    });
+}
+
+/// Destroys a lexer handle. Null is a no-op.
+///
+/// # Safety
+/// `handle` must be null or come from [`lanius_lexer_create`] and not be
+/// destroyed yet. Token buffers stay valid afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn lanius_lexer_destroy(handle: *mut LaniusLexer) {
+    if handle.is_null() {
+        return;
+    }
+    let _ = ffi_call(|| {
+        // SAFETY: the caller passes a live handle from `lanius_lexer_create`.
+        drop(unsafe { Box::from_raw(handle) });
+        Ok(())
+    });
+}
+
+/// Copies the calling thread's last error message into `buf` as a
+/// NUL-terminated string, truncated to `cap - 1` bytes, and returns the full
+/// message length without the NUL; 0 when no call has failed. With a null
+/// `buf` or a `cap` of 0 it only returns the length.
+///
+/// # Safety
+/// `buf` must be null or valid for writes of `cap` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn lanius_last_error_message(buf: *mut c_char, cap: usize) -> usize {
+    LAST_ERROR.with(|last| {
+        let last = last.borrow();
+        let Some(message) = last.as_ref() else {
+            if !buf.is_null() && cap > 0 {
+                // SAFETY: the caller guarantees `cap` writable bytes.
+                unsafe { buf.write(0) };
+            }
+            return 0;
+        };
+        let bytes = message.as_bytes();
+        if !buf.is_null() && cap > 0 {
+            let n = bytes.len().min(cap - 1);
+            // SAFETY: `n + 1 <= cap`, and the caller guarantees `cap` writable bytes.
+            unsafe {
+                ptr::copy_nonoverlapping(bytes.as_ptr().cast::<c_char>(), buf, n);
+                buf.add(n).write(0);
+            }
+        }
+        bytes.len()
+    })
+}
+
+const _: () = assert!(std::mem::size_of::<LaniusToken>() == LANIUS_TOKENS_RECORD_BYTES);
+const _: () = assert!(TOKENS_MAGIC.len() + 4 == LANIUS_TOKENS_HEADER_BYTES);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = include_str!("../include/lanius_lexer.h");
+
+    fn last_error() -> String {
+        // SAFETY: a null buffer only queries the length.
+        let len = unsafe { lanius_last_error_message(ptr::null_mut(), 0) };
+        let mut buf = vec![0u8; len + 1];
+        // SAFETY: `buf` holds `len + 1` writable bytes.
+        let written = unsafe { lanius_last_error_message(buf.as_mut_ptr().cast(), buf.len()) };
+        assert_eq!(written, len);
+        String::from_utf8(buf[..len].to_vec()).expect("messages are UTF-8")
+    }
+
+    #[test]
+    fn header_matches_the_rust_definitions() {
+        for (name, value) in [
+            ("LANIUS_OK", LANIUS_OK),
+            ("LANIUS_ERR_NULL_ARGUMENT", LANIUS_ERR_NULL_ARGUMENT),
+            ("LANIUS_ERR_INVALID_UTF8", LANIUS_ERR_INVALID_UTF8),
+            ("LANIUS_ERR_INIT", LANIUS_ERR_INIT),
+            ("LANIUS_ERR_LEX", LANIUS_ERR_LEX),
+            ("LANIUS_ERR_PANIC", LANIUS_ERR_PANIC),
+        ] {
+            let define = format!("#define {name} {value}\n");
+            assert!(HEADER.contains(&define), "header lacks {define:?}");
+        }
+        for (name, value) in [
+            ("LANIUS_TOKENS_HEADER_BYTES", LANIUS_TOKENS_HEADER_BYTES),
+            ("LANIUS_TOKENS_RECORD_BYTES", LANIUS_TOKENS_RECORD_BYTES),
+        ] {
+            let define = format!("#define {name} {value}\n");
+            assert!(HEADER.contains(&define), "header lacks {define:?}");
+        }
+        let magic = std::str::from_utf8(&TOKENS_MAGIC).unwrap();
+        assert!(HEADER.contains(&format!("#define LANIUS_TOKENS_MAGIC \"{magic}\"\n")));
+        assert!(HEADER.contains("typedef int32_t lanius_status_t;\n"));
+        assert!(HEADER.contains(
+            "typedef struct lanius_token {\n    uint32_t kind;\n    uint32_t start;\n    uint32_t len;\n} lanius_token_t;\n"
+        ));
+        assert!(HEADER.contains("typedef struct lanius_lexer lanius_lexer_t;\n"));
+
+        // The casts pin the Rust signatures the prototypes below describe.
+        let _ = lanius_lexer_create as unsafe extern "C" fn(*mut *mut LaniusLexer) -> LaniusStatus;
+        let _ = lanius_lexer_lex
+            as unsafe extern "C" fn(
+                *mut LaniusLexer,
+                *const u8,
+                usize,
+                *mut *mut u8,
+                *mut u32,
+            ) -> LaniusStatus;
+        let _ = lanius_tokens_free as unsafe extern "C" fn(*mut u8);
+        let _ = lanius_lexer_destroy as unsafe extern "C" fn(*mut LaniusLexer);
+        let _ = lanius_last_error_message as unsafe extern "C" fn(*mut c_char, usize) -> usize;
+        for prototype in [
+            "lanius_status_t lanius_lexer_create(lanius_lexer_t **out_handle);",
+            "lanius_status_t lanius_lexer_lex(lanius_lexer_t *handle, const uint8_t *source, size_t len, uint8_t **out_tokens, uint32_t *out_count);",
+            "void lanius_tokens_free(uint8_t *tokens);",
+            "void lanius_lexer_destroy(lanius_lexer_t *handle);",
+            "size_t lanius_last_error_message(char *buf, size_t cap);",
+        ] {
+            assert!(HEADER.contains(prototype), "header lacks {prototype:?}");
+        }
+    }
+
+    #[test]
+    fn null_arguments_fail_without_touching_the_gpu() {
+        // SAFETY: every pointer is null or points at a live local.
+        unsafe {
+            assert_eq!(
+                lanius_lexer_create(ptr::null_mut()),
+                LANIUS_ERR_NULL_ARGUMENT
+            );
+            assert_eq!(last_error(), "out_handle must not be null");
+
+            let mut tokens = 8usize as *mut u8;
+            let mut count = 7u32;
+            assert_eq!(
+                lanius_lexer_lex(ptr::null_mut(), ptr::null(), 0, &mut tokens, &mut count),
+                LANIUS_ERR_NULL_ARGUMENT
+            );
+            assert_eq!(last_error(), "handle must not be null");
+            assert!(tokens.is_null());
+            assert_eq!(count, 0);
+
+            assert_eq!(
+                lanius_lexer_lex(ptr::null_mut(), ptr::null(), 0, ptr::null_mut(), &mut count),
+                LANIUS_ERR_NULL_ARGUMENT
+            );
+            assert_eq!(last_error(), "out_tokens must not be null");
+            assert_eq!(
+                lanius_lexer_lex(
+                    ptr::null_mut(),
+                    ptr::null(),
+                    0,
+                    &mut tokens,
+                    ptr::null_mut()
+                ),
+                LANIUS_ERR_NULL_ARGUMENT
+            );
+            assert_eq!(last_error(), "out_count must not be null");
+
+            lanius_tokens_free(ptr::null_mut());
+            lanius_lexer_destroy(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn last_error_message_truncates_and_reports_full_length() {
+        std::thread::spawn(|| {
+            // SAFETY: null buffers only query; `buf` below is a live local.
+            unsafe {
+                assert_eq!(lanius_last_error_message(ptr::null_mut(), 0), 0);
+                let mut buf = [b'x' as c_char; 8];
+                assert_eq!(lanius_last_error_message(buf.as_mut_ptr(), buf.len()), 0);
+                assert_eq!(buf[0], 0);
+
+                assert_eq!(
+                    lanius_lexer_create(ptr::null_mut()),
+                    LANIUS_ERR_NULL_ARGUMENT
+                );
+                let full = "out_handle must not be null";
+                assert_eq!(
+                    lanius_last_error_message(buf.as_mut_ptr(), buf.len()),
+                    full.len()
+                );
+                let got: Vec<u8> = buf.iter().map(|&c| c as u8).collect();
+                assert_eq!(&got[..7], &full.as_bytes()[..7]);
+                assert_eq!(got[7], 0);
+            }
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn panics_become_status_codes() {
+        let status = ffi_call(|| panic!("boom"));
+        assert_eq!(status, LANIUS_ERR_PANIC);
+        assert_eq!(last_error(), "panic in lanius C API: boom");
+        let status = ffi_call(|| Err((LANIUS_ERR_LEX, "nope".to_string())));
+        assert_eq!(status, LANIUS_ERR_LEX);
+        assert_eq!(last_error(), "nope");
+    }
+
+    #[test]
+    fn token_buffers_round_trip_through_the_flat_layout() {
+        use crate::{
+            lexer::{Token, tables::tokens::TokenKind},
+            span::Span,
+        };
+
+        let tokens = [
+            Token {
+                kind: TokenKind::Let,
+                raw_kind: TokenKind::Ident,
+                span: Span::new(0, 3),
+            },
+            Token::new(TokenKind::Ident, 4, 1),
+        ];
+        let (words, count) = encode_tokens(&tokens).unwrap();
+        assert_eq!(count, 2);
+        let buffer = Box::into_raw(words).cast::<u32>();
+        // SAFETY: `buffer` is a live token buffer until freed below.
+        unsafe {
+            assert_eq!(token_buffer_words(buffer), 9);
+            let bytes = std::slice::from_raw_parts(buffer.cast::<u8>(), 36);
+            let file = tokens_io::read_tokens(bytes).unwrap();
+            assert_eq!(file.tokens.len(), 2);
+            assert_eq!(file.tokens[0].kind, TokenKind::Let);
+            if cfg!(target_endian = "little") {
+                let records = std::slice::from_raw_parts(
+                    buffer
+                        .cast::<u8>()
+                        .add(LANIUS_TOKENS_HEADER_BYTES)
+                        .cast::<LaniusToken>(),
+                    2,
+                );
+                assert_eq!(
+                    records[1],
+                    LaniusToken {
+                        kind: TokenKind::Ident as u32,
+                        start: 4,
+                        len: 1,
+                    }
+                );
+            }
+            lanius_tokens_free(buffer.cast());
+        }
+    }
+
+    #[test]
+    fn physical_gpu_lexes_through_the_c_abi_and_rejects_bad_input() {
+        // SAFETY: every pointer is a live local or comes from the API.
+        unsafe {
+            let mut handle = ptr::null_mut();
+            assert_eq!(
+                lanius_lexer_create(&mut handle),
+                LANIUS_OK,
+                "{}",
+                last_error()
+            );
+            assert!(!handle.is_null());
+
+            let source = b"let x = 1;";
+            for _ in 0..3 {
+                let mut tokens = ptr::null_mut();
+                let mut count = 0;
+                assert_eq!(
+                    lanius_lexer_lex(
+                        handle,
+                        source.as_ptr(),
+                        source.len(),
+                        &mut tokens,
+                        &mut count
+                    ),
+                    LANIUS_OK,
+                    "{}",
+                    last_error()
+                );
+                assert_eq!(count, 5);
+                let bytes = std::slice::from_raw_parts(
+                    tokens,
+                    LANIUS_TOKENS_HEADER_BYTES + count as usize * LANIUS_TOKENS_RECORD_BYTES,
+                );
+                let file = tokens_io::read_tokens(bytes).unwrap();
+                let expected = crate::lexer::test_cpu::lex_on_test_cpu("let x = 1;").unwrap();
+                let got: Vec<_> = file
+                    .tokens
+                    .iter()
+                    .map(|t| (t.kind, t.start(), t.len()))
+                    .collect();
+                let want: Vec<_> = expected
+                    .iter()
+                    .map(|t| (t.kind, t.start(), t.len()))
+                    .collect();
+                assert_eq!(got, want);
+                lanius_tokens_free(tokens);
+            }
+
+            let mut tokens = ptr::null_mut();
+            let mut count = 0;
+            assert_eq!(
+                lanius_lexer_lex(handle, ptr::null(), 0, &mut tokens, &mut count),
+                LANIUS_OK,
+                "{}",
+                last_error()
+            );
+            assert_eq!(count, 0);
+            lanius_tokens_free(tokens);
+
+            let invalid = b"let \xff = 1;";
+            assert_eq!(
+                lanius_lexer_lex(
+                    handle,
+                    invalid.as_ptr(),
+                    invalid.len(),
+                    &mut tokens,
+                    &mut count
+                ),
+                LANIUS_ERR_INVALID_UTF8
+            );
+            assert!(tokens.is_null());
+            assert!(last_error().starts_with("source is not valid UTF-8"));
+
+            assert_eq!(
+                lanius_lexer_lex(handle, ptr::null(), 4, &mut tokens, &mut count),
+                LANIUS_ERR_NULL_ARGUMENT
+            );
+            assert_eq!(last_error(), "source must not be null");
+
+            lanius_lexer_destroy(handle);
+        }
+    }
+}
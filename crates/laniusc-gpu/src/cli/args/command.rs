@@ -20,6 +20,12 @@ pub(crate) enum Command {
     Lsp(Vec<String>),
     /// Forward remaining args to `laniusc diagnostics`.
     Diagnostics(Vec<String>),
+    /// Forward remaining args to `laniusc lex`.
+    Lex(Vec<String>),
+    /// Forward remaining args to `laniusc tokens-dump`.
+    TokensDump(Vec<String>),
+    /// Forward remaining args to `laniusc plan`.
+    Plan(Vec<String>),
     /// Compile or check source using the parsed compile request.
     Compile(CompileRequest),
 }
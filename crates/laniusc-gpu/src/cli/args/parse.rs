@@ -188,6 +188,18 @@ where
             args.next();
             Some(Command::Diagnostics(args.collect()))
         }
+        "lex" => {
+            args.next();
+            Some(Command::Lex(args.collect()))
+        }
+        "tokens-dump" => {
+            args.next();
+            Some(Command::TokensDump(args.collect()))
+        }
+        "plan" => {
+            args.next();
+            Some(Command::Plan(args.collect()))
+        }
         _ => return None,
     }
 }
@@ -9,6 +9,8 @@ use super::{
     help,
     lsp,
     package,
+    plan,
+    tokens,
 };
 
 /// Parses raw CLI arguments and routes the resulting command to its owner.
@@ -28,6 +30,9 @@ pub(super) fn run(raw_args: Vec<String>) -> Result<(), CliError> {
         Command::Package(args) => package::run(args),
         Command::Lsp(args) => lsp::run(args),
         Command::Diagnostics(args) => diagnostics::run(args),
+        Command::Lex(args) => tokens::run_lex(args),
+        Command::TokensDump(args) => tokens::run_dump(args),
+        Command::Plan(args) => plan::run(args),
         Command::Compile(request) => compile::run(request),
     }
 }
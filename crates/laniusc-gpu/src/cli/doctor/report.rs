@@ -90,6 +90,7 @@ pub(super) fn json_pretty(skip_slangc_probe: bool) -> Result<String, CliError> {
                     "status": known_or_unknown_status(Some(shader_digest.as_str())),
                     "value": shader_digest,
                 },
+                "mode": shader_artifacts::build_mode(),
                 "count": shader_artifact_u64_metadata(shader_count),
                 "largest": {
                     "status": known_or_unknown_status(shader_max_spv_bytes.map(|_| "known")),
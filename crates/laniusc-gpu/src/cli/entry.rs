@@ -5,13 +5,16 @@ use super::{
     dispatch,
     fallback::cli_operation_failed_diagnostic,
 };
-use crate::compiler::Diagnostic;
+use crate::compiler::{Diagnostic, GpuCompilerOptions, set_compiler_options};
 
 /// Runs the CLI from process arguments and returns the process exit code.
 ///
-/// Error rendering is selected before dispatch so subcommand parsing errors can
-/// still honor `--diagnostic-format`.
+/// The `LANIUS_*` lexer and parser variables become the process-wide
+/// [`GpuCompilerOptions`] here, before any compiler exists. Error rendering
+/// is selected before dispatch so subcommand parsing errors can still honor
+/// `--diagnostic-format`.
 pub fn run_from_env() -> ExitCode {
+    set_compiler_options(GpuCompilerOptions::from_env());
     let args = env::args().skip(1).collect::<Vec<_>>();
     let diagnostic_format = diagnostic_format_from_args(args.iter().cloned());
     match dispatch::run(args) {
@@ -43,6 +43,9 @@ pub(crate) fn print_help() {
          Usage: laniusc diagnostics [--diagnostic-format text|json|lsp-json] source-pack-progress --source-pack-artifact-root dir [--emit wasm|x86_64]\n\
          Usage: laniusc doctor [--skip-slangc-probe] [--diagnostic-format text|json|lsp-json]\n\
          Usage: laniusc fmt [--check] [--diagnostic-format text|json|lsp-json] (<input.lani> [more-input.lani...]|--stdin|-)\n\
+         Usage: laniusc lex [--format text|bin] [-o output] <input.lani>\n\
+         Usage: laniusc tokens-dump <tokens.toks>\n\
+         Usage: laniusc plan [--tokens N] <input.lani>\n\
          Emits the selected target using GPU lexing, GPU parsing, GPU type checking, and GPU emission.\n\
          check runs the same bounded GPU compiler path for diagnostics and exits without writing target bytes.\n\
          daemon keeps one GPU compiler resident and accepts newline-delimited JSON compile, trim, status, and shutdown requests on stdio or one Unix-domain socket connection; source/job buffers are released after 30 seconds idle by default, and a zero idle-buffer timeout disables automatic trimming.\n\
@@ -51,6 +54,8 @@ pub(crate) fn print_help() {
          diagnostics registry prints the stable diagnostic registry JSON directly for tools that do not need LSP capability metadata; diagnostics commands prints the no-run metadata command index and placeholder contract directly; diagnostics codes prints a compact diagnostic code index for wrappers and completion; diagnostics code prints one compact registry row or known:false for an unknown code; diagnostics categories groups codes by stable category for filter-building tools; diagnostics formats prints the accepted diagnostic render formats and payload contracts; diagnostics formatter prints the alpha formatter policy, CLI commands, LSP request options, diagnostic codes, and no-run guard contract; diagnostics version-policy prints no-run machine-readable compiler, edition, distribution, compatibility, target, tooling schema policy, metadata command discovery, and command-template placeholder metadata; diagnostics explain prints one code-specific JSON explanation; diagnostics runtime-api prints fail-closed runtime binding metadata for one qualified or service-qualified stdlib API; diagnostics runtime-apis prints the full fail-closed stdlib runtime-bound API index; diagnostics runtime-service prints one fail-closed runtime service boundary selected by id, service name, module path, capability constant, runtime probe, or qualified runtime-bound API; diagnostics runtime-service-apis prints the known-unbound API rows owned by one runtime service selected through the same runtime-service selectors; diagnostics runtime-services prints the fail-closed runtime service boundary table; diagnostics source-pack-progress prints persisted work-queue progress from source-pack artifact records without loading source.\n\
          doctor prints a compact no-run JSON toolchain/readiness report for installation checks, including compiler version, language edition, target surface, language-slice inventory metadata, diagnostic format metadata, Slang availability from SLANGC or PATH unless --skip-slangc-probe is passed, build metadata, Slang build timeout guardrails, readiness gate metadata, pass-contract/Pareas-shape metadata, stdlib boundary counts, links to detailed diagnostics commands, and guards proving it did not compile source, run shader loop audits, execute readiness gates, or create a GPU device.\n\
          fmt formats one or more source files in place using the alpha lexical formatter; --check verifies formatting without writing.\n\
+         lex prints the GPU lexer's kept tokens for one file, or writes them as an LXTOKS01 binary token file with --format bin; tokens-dump prints a binary token file.\n\
+         plan prints the lexer and parser buffers, dispatches, required device limits, and estimated VRAM for one file without creating a GPU device.\n\
          Current language edition: {edition}; {policy}.\n\
          --edition selects the language edition for this invocation; only {edition} is accepted today and unsupported editions are rejected before compilation.\n\
          Accepted emit targets: {targets}; default emit target: {default_target}.\n\
@@ -188,8 +193,36 @@ pub(crate) fn print_fmt_help() {
     );
 }
 
+/// Prints help for `laniusc lex`.
+pub(crate) fn print_lex_help() {
+    eprintln!(
+        "Usage: laniusc lex [--format text|bin] [-o output] <input.lani>\n\
+         Lexes one source file on the GPU and prints its kept tokens.\n\
+         --format bin writes the LXTOKS01 binary token format described in `lexer::tokens_io`.\n\
+         -o writes to a file instead of stdout."
+    );
+}
+
+/// Prints help for `laniusc tokens-dump`.
+pub(crate) fn print_tokens_dump_help() {
+    eprintln!(
+        "Usage: laniusc tokens-dump <tokens.toks>\n\
+         Prints the tokens and source hash of an LXTOKS01 binary token file."
+    );
+}
+
+/// Prints help for `laniusc plan`.
+pub(crate) fn print_plan_help() {
+    eprintln!(
+        "Usage: laniusc plan [--tokens N] <input.lani>\n\
+         Prints the lexer and parser buffer plans, dispatches, required device limits, and estimated VRAM for one file without creating a GPU device.\n\
+         The parser is planned for the file's byte length in tokens unless --tokens gives the count."
+    );
+}
+
 /// Prints compiler and tooling version metadata.
 pub(crate) fn print_version() {
+    let build = crate::build_info();
     println!(
         "laniusc {}\n\
          language-edition: {}\n\
@@ -211,6 +244,7 @@ pub(crate) fn print_version() {
          wgpu: {}\n\
          build-profile: {}\n\
          shader-artifact-digest: {}\n\
+         shader-artifact-mode: {}\n\
          shader-artifact-count: {}\n\
          shader-artifact-max-bytes: {}\n\
          shader-artifact-max-name: {}\n\
@@ -234,10 +268,11 @@ pub(crate) fn print_version() {
         LANIUS_LSP_EXPERIMENTAL_SCHEMA_VERSION,
         LANIUS_LSP_ERROR_DATA_SCHEMA_NAME,
         LANIUS_LSP_ERROR_DATA_SCHEMA_VERSION,
-        option_env!("LANIUS_SLANGC_VERSION").unwrap_or("unknown"),
-        option_env!("LANIUS_WGPU_VERSION").unwrap_or("unknown"),
-        option_env!("LANIUS_BUILD_PROFILE").unwrap_or("unknown"),
-        shader_artifacts::digest(),
+        build.slangc_version,
+        build.wgpu_version,
+        build.profile,
+        build.shader_digest,
+        build.shader_mode,
         shader_artifacts::count_text(),
         shader_artifacts::max_spv_bytes_text(),
         shader_artifacts::max_spv_name(),
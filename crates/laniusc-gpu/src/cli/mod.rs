@@ -19,6 +19,8 @@ mod help;
 mod lsp;
 mod output;
 mod package;
+mod plan;
 mod source_pack;
+mod tokens;
 
 pub use entry::run_from_env;
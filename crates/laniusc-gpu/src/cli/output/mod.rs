@@ -6,7 +6,7 @@ mod stream;
 
 pub(crate) use emission::{CliEmission, write_cli_emission};
 pub(crate) use error::CliOutputError;
-pub(crate) use stream::write_output_stream_bytes;
+pub(crate) use stream::{write_output_stream_bytes, write_stdout_bytes};
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,94 @@
+use std::{fs, path::PathBuf};
+
+use super::{
+    common::{
+        CliError,
+        extra_cli_argument_error,
+        missing_cli_argument_error,
+        missing_cli_option_value_error,
+        parse_usize_value,
+        unknown_cli_option_error,
+        unsupported_cli_option_value_error,
+    },
+    help::print_plan_help,
+    output::write_stdout_bytes,
+};
+use crate::{
+    lexer::{GpuLexer, LexOptions},
+    parser::{GpuParser, tables::PrecomputedParseTables},
+};
+
+/// Runs `laniusc plan`, which prints the lexer and parser plans for one file
+/// without creating a GPU device.
+#[allow(clippy::result_large_err)]
+pub(crate) fn run(args: impl IntoIterator<Item = String>) -> Result<(), CliError> {
+    let mut tokens: Option<usize> = None;
+    let mut input: Option<PathBuf> = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print_plan_help();
+                return Ok(());
+            }
+            "--tokens" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| missing_cli_option_value_error("--tokens", "a token count"))?;
+                tokens = Some(parse_tokens(&value)?);
+            }
+            flag if flag.starts_with("--tokens=") => {
+                tokens = Some(parse_tokens(flag.trim_start_matches("--tokens="))?);
+            }
+            flag if flag.starts_with('-') => {
+                return Err(unknown_cli_option_error(
+                    "laniusc plan",
+                    flag,
+                    "--help, --tokens",
+                ));
+            }
+            path if input.is_none() => input = Some(PathBuf::from(path)),
+            extra => {
+                return Err(extra_cli_argument_error(
+                    "laniusc plan",
+                    extra,
+                    "one input file, --tokens",
+                ));
+            }
+        }
+    }
+
+    let input = input.ok_or_else(|| missing_cli_argument_error("laniusc plan", "an input file"))?;
+    let input_len = fs::metadata(&input)
+        .map_err(|err| CliError::Message(format!("read {}: {err}", input.display())))?
+        .len() as usize;
+    // Every kept token covers at least one byte, so the source length bounds
+    // the token count when the caller does not know it.
+    let n_tokens = tokens.unwrap_or(input_len);
+
+    let lex = GpuLexer::plan(input_len, LexOptions::default())
+        .map_err(|err| CliError::Message(format!("plan lexer: {err:#}")))?;
+    let tables = PrecomputedParseTables::load_bin_bytes(include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../tables/parse_tables.bin"
+    )))
+    .map_err(|err| CliError::Message(format!("load parse tables: {err:#}")))?;
+    let parse = GpuParser::plan(n_tokens, &tables)
+        .map_err(|err| CliError::Message(format!("plan parser: {err:#}")))?;
+
+    let report = format!(
+        "# lexer: {} ({input_len} bytes)\n{}\n# parser: {n_tokens} tokens\n{}",
+        input.display(),
+        lex.render(),
+        parse.render()
+    );
+    write_stdout_bytes("plan", "write plan", report.as_bytes()).map_err(CliError::from)
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_tokens(value: &str) -> Result<usize, CliError> {
+    parse_usize_value("--tokens", value).map_err(|err| {
+        unsupported_cli_option_value_error("--tokens", value, "a non-negative integer", Some(err))
+    })
+}
@@ -0,0 +1,206 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use super::{
+    common::{
+        CliError,
+        extra_cli_argument_error,
+        missing_cli_argument_error,
+        missing_cli_option_value_error,
+        unknown_cli_option_error,
+        unsupported_cli_option_value_error,
+    },
+    help::{print_lex_help, print_tokens_dump_help},
+    output::write_stdout_bytes,
+};
+use crate::lexer::{
+    Token,
+    lex_on_gpu,
+    tokens_io::{self, TokenStreamWriter},
+};
+
+const LEX_FORMATS: &str = "text, bin";
+
+/// Runs `laniusc lex`, which prints or writes one file's kept tokens.
+#[allow(clippy::result_large_err)]
+pub(crate) fn run_lex(args: impl IntoIterator<Item = String>) -> Result<(), CliError> {
+    let mut binary = false;
+    let mut output: Option<PathBuf> = None;
+    let mut input: Option<PathBuf> = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print_lex_help();
+                return Ok(());
+            }
+            "--format" => {
+                let value = args.next().ok_or_else(|| {
+                    missing_cli_option_value_error("--format", format!("one of: {LEX_FORMATS}"))
+                })?;
+                binary =
+                    parse_lex_format(&value).ok_or_else(|| unsupported_lex_format_error(&value))?;
+            }
+            flag if flag.starts_with("--format=") => {
+                let value = flag.trim_start_matches("--format=");
+                binary =
+                    parse_lex_format(value).ok_or_else(|| unsupported_lex_format_error(value))?;
+            }
+            "-o" | "--out" => {
+                output = Some(PathBuf::from(args.next().ok_or_else(|| {
+                    missing_cli_option_value_error(&arg, "an output path")
+                })?));
+            }
+            flag if flag.starts_with('-') => {
+                return Err(unknown_cli_option_error(
+                    "laniusc lex",
+                    flag,
+                    "--help, --format, -o/--out",
+                ));
+            }
+            path if input.is_none() => input = Some(PathBuf::from(path)),
+            extra => {
+                return Err(extra_cli_argument_error(
+                    "laniusc lex",
+                    extra,
+                    "one input file, --format, -o/--out",
+                ));
+            }
+        }
+    }
+
+    let input = input.ok_or_else(|| missing_cli_argument_error("laniusc lex", "an input file"))?;
+    let source = fs::read_to_string(&input)
+        .map_err(|err| CliError::Message(format!("read {}: {err}", input.display())))?;
+    let tokens = pollster::block_on(lex_on_gpu(&source))
+        .map_err(|err| CliError::Message(format!("lex {}: {err:#}", input.display())))?;
+    let hash = Some(tokens_io::source_hash(&source));
+
+    match (binary, output) {
+        (true, Some(path)) => write_token_file(&path, &tokens, hash)
+            .map_err(|err| CliError::Message(format!("write {}: {err}", path.display()))),
+        (true, None) => {
+            let mut bytes = Vec::new();
+            tokens_io::write_tokens(&mut bytes, &tokens, hash)
+                .map_err(|err| CliError::Message(format!("encode tokens: {err}")))?;
+            write_stdout_bytes("tokens", "write binary tokens", &bytes).map_err(CliError::from)
+        }
+        (false, Some(path)) => fs::write(&path, render_tokens(&tokens, hash))
+            .map_err(|err| CliError::Message(format!("write {}: {err}", path.display()))),
+        (false, None) => write_stdout_bytes(
+            "tokens",
+            "write token listing",
+            render_tokens(&tokens, hash).as_bytes(),
+        )
+        .map_err(CliError::from),
+    }
+}
+
+/// Runs `laniusc tokens-dump`, the reference reader for binary token files.
+#[allow(clippy::result_large_err)]
+pub(crate) fn run_dump(args: impl IntoIterator<Item = String>) -> Result<(), CliError> {
+    let mut input: Option<PathBuf> = None;
+    for arg in args {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print_tokens_dump_help();
+                return Ok(());
+            }
+            flag if flag.starts_with('-') => {
+                return Err(unknown_cli_option_error(
+                    "laniusc tokens-dump",
+                    flag,
+                    "--help",
+                ));
+            }
+            path if input.is_none() => input = Some(PathBuf::from(path)),
+            extra => {
+                return Err(extra_cli_argument_error(
+                    "laniusc tokens-dump",
+                    extra,
+                    "one token file",
+                ));
+            }
+        }
+    }
+
+    let input =
+        input.ok_or_else(|| missing_cli_argument_error("laniusc tokens-dump", "a token file"))?;
+    let file =
+        tokens_io::read_tokens_file(&input).map_err(|err| CliError::Message(format!("{err:#}")))?;
+    write_stdout_bytes(
+        "tokens",
+        "write token listing",
+        render_tokens(&file.tokens, file.source_hash).as_bytes(),
+    )
+    .map_err(CliError::from)
+}
+
+/// Whether `--format value` selects the binary format.
+fn parse_lex_format(value: &str) -> Option<bool> {
+    match value {
+        "text" => Some(false),
+        "bin" => Some(true),
+        _ => None,
+    }
+}
+
+fn unsupported_lex_format_error(value: &str) -> CliError {
+    unsupported_cli_option_value_error("--format", value, LEX_FORMATS, None)
+}
+
+fn write_token_file(path: &Path, tokens: &[Token], hash: Option<u64>) -> io::Result<()> {
+    let mut writer = TokenStreamWriter::new(BufWriter::new(fs::File::create(path)?))?;
+    for token in tokens {
+        writer.push(token)?;
+    }
+    writer.finish(hash)?;
+    Ok(())
+}
+
+/// One `index kind start len` line per token after a count/hash header line.
+fn render_tokens(tokens: &[Token], hash: Option<u64>) -> String {
+    let mut out = format!("# {} tokens", tokens.len());
+    if let Some(hash) = hash {
+        let _ = write!(out, ", source fnv1a64:{hash:016x}");
+    }
+    out.push('\n');
+    for (index, token) in tokens.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "{index}\t{:?}\t{}\t{}",
+            token.kind,
+            token.start(),
+            token.len()
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::tables::tokens::TokenKind, span::Span};
+
+    #[test]
+    fn token_listing_has_header_and_one_line_per_token() {
+        let tokens = [
+            Token {
+                kind: TokenKind::Fn,
+                raw_kind: TokenKind::Ident,
+                span: Span::new(0, 2),
+            },
+            Token::new(TokenKind::Ident, 3, 4),
+        ];
+        assert_eq!(
+            render_tokens(&tokens, Some(0xab)),
+            "# 2 tokens, source fnv1a64:00000000000000ab\n0\tFn\t0\t2\n1\tIdent\t3\t4\n"
+        );
+        assert_eq!(render_tokens(&[], None), "# 0 tokens\n");
+    }
+}
@@ -3327,10 +3327,7 @@ mod tests {
         let slice = buffer.slice(..);
         map_readback_blocking(device, &slice, "semantic LIR test readback").unwrap();
         let mapped = slice.get_mapped_range();
-        let result = mapped
-            .chunks_exact(4)
-            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
-            .collect();
+        let result = crate::gpu::decode::decode_u32s(&mapped);
         drop(mapped);
         buffer.unmap();
         result
@@ -352,7 +352,7 @@ impl GpuLoweringPipeline {
         drop(mapped);
         self.status_readback.unmap();
         if std::env::var_os("LANIUS_DEBUG_STAGE_ERRORS").is_some() {
-            eprintln!(
+            log::info!(target: crate::logging::CODEGEN,
                 "GPU lowering status: flags=0x{:x}, first HIR={}, required capacity={}, available capacity={}",
                 status.flags,
                 status.first_unsupported_hir,
@@ -379,7 +379,7 @@ impl GpuLoweringPipeline {
         for row in 0..hir_count as usize {
             let core = [0, 1, 2, 3].map(|field| word(DEBUG_HIR_CORE_OFFSET, row * 4 + field));
             let payload = [0, 1, 2, 3].map(|field| word(DEBUG_HIR_PAYLOAD_OFFSET, row * 4 + field));
-            eprintln!("compact HIR {row}: core={core:?}, payload={payload:?}");
+            log::info!(target: crate::logging::CODEGEN, "compact HIR {row}: core={core:?}, payload={payload:?}");
         }
         let semantic_total = self
             .semantic
@@ -391,7 +391,7 @@ impl GpuLoweringPipeline {
             let core =
                 [0, 1, 2, 3, 4, 5].map(|field| word(DEBUG_SEMANTIC_CORE_OFFSET, row * 6 + field));
             if core[0] != 0 || core[4] != 0 {
-                eprintln!("semantic LIR {row}: core={core:?}");
+                log::info!(target: crate::logging::CODEGEN, "semantic LIR {row}: core={core:?}");
             }
         }
         drop(mapped);
@@ -109,7 +109,7 @@ impl GpuWasmLinker {
 
 fn trace_wasm_codegen(stage: &str) {
     if crate::gpu::env::env_bool_strict("LANIUS_WASM_TRACE", false) {
-        eprintln!("[laniusc][wasm-link] {stage}");
+        log::info!(target: crate::logging::CODEGEN, "[laniusc][wasm-link] {stage}");
     }
 }
 
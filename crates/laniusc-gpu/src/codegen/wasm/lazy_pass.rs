@@ -84,11 +84,11 @@ impl LazyWasmPass {
             self.entry,
             &self.spirv,
             &bgl_refs,
-        );
+        )?;
         let end = Instant::now();
         let dt_ms = end.duration_since(start).as_secs_f64() * 1000.0;
         if crate::gpu::env::env_bool_truthy("LANIUS_GPU_COMPILE_HOST_TIMING", false) {
-            eprintln!(
+            log::info!(target: crate::logging::CODEGEN,
                 "[gpu_compile_host_timer] codegen.wasm.pipeline.{}: {:.3}ms",
                 self.stage, dt_ms
             );
@@ -64,7 +64,7 @@ impl GpuWasmLinkInput {
         }
         result.finish_validation()?;
         if crate::gpu::env::env_bool_strict("LANIUS_WASM_TRACE", false) {
-            eprintln!(
+            log::info!(target: crate::logging::CODEGEN,
                 "[laniusc][wasm-link] objects={} functions={} body_bytes={} relocations={}",
                 objects.len(),
                 result.function_count,
@@ -81,7 +81,7 @@ impl GpuWasmLinkInput {
                     .map(|byte| format!("{byte:02x}"))
                     .collect::<Vec<_>>()
                     .join(" ");
-                eprintln!(
+                log::info!(target: crate::logging::CODEGEN,
                     "[laniusc][wasm-link] relocation={index} site={site} target={:?}:{} identity={:?} bytes=[{bytes}]",
                     relocation.target_kind, relocation.target_index, relocation.target_identity
                 );
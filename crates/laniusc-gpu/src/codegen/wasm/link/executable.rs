@@ -233,13 +233,13 @@ impl GpuWasmLinker {
                 device,
                 &output_slice,
                 "codegen.wasm.link.page.output",
-                std::time::Duration::from_secs(30),
+                Some(std::time::Duration::from_secs(30)),
             )?;
             crate::gpu::passes_core::wait_for_readback_map(
                 device,
                 &status_slice,
                 "codegen.wasm.link.page.status",
-                std::time::Duration::from_secs(30),
+                Some(std::time::Duration::from_secs(30)),
             )?;
             let output_mapped = output_slice.get_mapped_range();
             let status_mapped = status_slice.get_mapped_range();
@@ -341,7 +341,7 @@ fn read_resolution_batch(
         device,
         &status_slice,
         "codegen.wasm.link.resolve.status",
-        std::time::Duration::from_secs(30),
+        Some(std::time::Duration::from_secs(30)),
     )?;
     let status_mapped = status_slice.get_mapped_range();
     let status_words =
@@ -361,7 +361,7 @@ fn read_resolution_batch(
         device,
         &targets_slice,
         "codegen.wasm.link.resolve.targets",
-        std::time::Duration::from_secs(30),
+        Some(std::time::Duration::from_secs(30)),
     )?;
     let targets_mapped = targets_slice.get_mapped_range();
     for (&relocation_index, target_bytes) in indices.iter().zip(targets_mapped.chunks_exact(4)) {
@@ -41,6 +41,7 @@ use crate::gpu::{
         CompilerGraphAllocations,
         CompilerGraphWorkspace,
     },
+    decode::decode_u32s,
     passes_core::{PassData, make_pass_data_from_shader_key, map_readback_blocking},
 };
 
@@ -671,12 +672,12 @@ impl GpuWasmObjectStage {
         let payload = payload_slice.get_mapped_range();
         let section = |start: u64, len: usize| &payload[start as usize..start as usize + len];
         let function_words =
-            decode_words(section(self.payload_layout.functions, function_count * 24));
-        let relocation_words = decode_words(section(
+            decode_u32s(section(self.payload_layout.functions, function_count * 24));
+        let relocation_words = decode_u32s(section(
             self.payload_layout.relocations,
             relocation_count * 32,
         ));
-        let definition_words = decode_words(section(
+        let definition_words = decode_u32s(section(
             self.payload_layout.definitions,
             definition_count * 32,
         ));
@@ -792,13 +793,6 @@ impl ObjectReadbackLayout {
     }
 }
 
-fn decode_words(bytes: &[u8]) -> Vec<u32> {
-    bytes
-        .chunks_exact(4)
-        .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
-        .collect()
-}
-
 fn push_symbol(
     identity_bytes: &mut Vec<u8>,
     symbols: &mut Vec<GpuWasmObjectSymbolRecord>,
@@ -396,7 +396,7 @@ impl GpuX86Linker {
             device,
             &slice,
             "codegen.x86.link.relocate",
-            std::time::Duration::from_secs(30),
+            Some(std::time::Duration::from_secs(30)),
         )?;
         let (bytes, status) = {
             let mapped = slice.get_mapped_range();
@@ -693,14 +693,11 @@ impl GpuX86Linker {
             device,
             &slice,
             "codegen.x86.link.symbols",
-            std::time::Duration::from_secs(30),
+            Some(std::time::Duration::from_secs(30)),
         )?;
         let (definition_rows, status_words) = {
             let mapped = slice.get_mapped_range();
-            let definition_rows = mapped[..definition_bytes]
-                .chunks_exact(4)
-                .map(|word| u32::from_le_bytes(word.try_into().expect("four bytes")))
-                .collect();
+            let definition_rows = crate::gpu::decode::decode_u32s(&mapped[..definition_bytes]);
             let status_words = crate::gpu::readback::read_u32_words::<4>(
                 &mapped[definition_bytes..definition_bytes + 16],
                 "x86 link symbol status",
@@ -1038,7 +1035,7 @@ impl GpuX86Linker {
             device,
             &slice,
             "codegen.x86.link.sections",
-            std::time::Duration::from_secs(30),
+            Some(std::time::Duration::from_secs(30)),
         )?;
         let bytes = {
             let mapped = slice.get_mapped_range();
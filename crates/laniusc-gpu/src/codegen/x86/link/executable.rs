@@ -339,7 +339,7 @@ impl GpuX86Linker {
                 device,
                 &slice,
                 "codegen.x86.link.page",
-                std::time::Duration::from_secs(30),
+                Some(std::time::Duration::from_secs(30)),
             )?;
             let mapped = slice.get_mapped_range();
             let output_status_words = crate::gpu::readback::read_u32_words::<4>(
@@ -274,7 +274,7 @@ impl GpuX86Linker {
                 device,
                 &slice,
                 "codegen.x86.link.layout_chunk",
-                std::time::Duration::from_secs(30),
+                Some(std::time::Duration::from_secs(30)),
             )?;
             let mapped = slice.get_mapped_range();
             for bytes in mapped.chunks_exact(8) {
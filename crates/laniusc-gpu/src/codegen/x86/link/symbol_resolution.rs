@@ -365,7 +365,7 @@ fn read_partition_batch(
         device,
         &status_slice,
         "codegen.x86.link.symbol_partition.status",
-        std::time::Duration::from_secs(30),
+        Some(std::time::Duration::from_secs(30)),
     )?;
     let mapped = status_slice.get_mapped_range();
     let status = crate::gpu::readback::read_u32_words::<4>(&mapped, "x86 symbol partition status")?;
@@ -383,7 +383,7 @@ fn read_partition_batch(
         device,
         &values_slice,
         "codegen.x86.link.symbol_partition.values",
-        std::time::Duration::from_secs(30),
+        Some(std::time::Duration::from_secs(30)),
     )?;
     let mapped = values_slice.get_mapped_range();
     let result = (|| {
@@ -17,13 +17,13 @@ fn trace_enabled() -> bool {
 
 pub(super) fn trace_x86_codegen(stage: &str) {
     if trace_enabled() {
-        eprintln!("[laniusc][x86-link] {stage}");
+        log::info!(target: crate::logging::CODEGEN, "[laniusc][x86-link] {stage}");
     }
 }
 
 fn trace_event(stage: &str, event: &str) {
     if trace_enabled() {
-        eprintln!("[laniusc][x86-link] {stage}.{event}");
+        log::info!(target: crate::logging::CODEGEN, "[laniusc][x86-link] {stage}.{event}");
     }
 }
 
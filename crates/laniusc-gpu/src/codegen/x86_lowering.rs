@@ -997,10 +997,7 @@ mod tests {
         let slice = buffer.slice(..);
         map_readback_blocking(device, &slice, "x86 LIR readback").unwrap();
         let mapped = slice.get_mapped_range();
-        let words = mapped
-            .chunks_exact(4)
-            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
-            .collect();
+        let words = crate::gpu::decode::decode_u32s(&mapped);
         drop(mapped);
         buffer.unmap();
         words
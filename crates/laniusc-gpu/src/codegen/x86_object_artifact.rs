@@ -39,6 +39,7 @@ use crate::gpu::{
         CompilerGraphAllocations,
         CompilerGraphWorkspace,
     },
+    decode::decode_u32s,
     passes_core::{PassData, make_pass_data_from_shader_key, map_readback_blocking},
 };
 
@@ -681,15 +682,15 @@ impl GpuX86ObjectStage {
         map_readback_blocking(device, &payload_slice, "x86 object payload readback")?;
         let payload = payload_slice.get_mapped_range();
         let section = |start: u64, len: usize| &payload[start as usize..start as usize + len];
-        let relocation_words = decode_words(section(
+        let relocation_words = decode_u32s(section(
             self.payload_layout.relocations,
             relocation_count * 32,
         ));
-        let undefined_words = decode_words(section(
+        let undefined_words = decode_u32s(section(
             self.payload_layout.undefined_symbols,
             symbol_count * 16,
         ));
-        let definition_words = decode_words(section(
+        let definition_words = decode_u32s(section(
             self.payload_layout.definitions,
             definition_count * 32,
         ));
@@ -789,13 +790,6 @@ impl ObjectReadbackLayout {
     }
 }
 
-fn decode_words(bytes: &[u8]) -> Vec<u32> {
-    bytes
-        .chunks_exact(4)
-        .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
-        .collect()
-}
-
 fn section_tag(value: u32, owner: &str, index: usize) -> Result<GpuX86ObjectSection> {
     match value {
         0 => Ok(GpuX86ObjectSection::Undefined),
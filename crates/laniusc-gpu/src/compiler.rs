@@ -9,7 +9,7 @@ use std::{
     collections::{BTreeMap, BTreeSet},
     fs,
     path::{Path, PathBuf},
-    sync::OnceLock,
+    sync::{Arc, OnceLock},
     time::{SystemTime, UNIX_EPOCH},
 };
 
@@ -73,10 +73,12 @@ use crate::{
         timer::GpuTimer,
     },
     lexer::{
+        LexerRuntimeOptions,
         buffers::GpuBuffers as LexerBuffers,
         driver::{GpuLexer, ResidentLexerParserInputs},
     },
     parser::{
+        BracketCache,
         buffers::ParserBuffers,
         driver::{GpuParser, Ll1AcceptResult},
         tables::PrecomputedParseTables,
@@ -12,6 +12,7 @@ use crate::{
         util::read_tokens_from_mapped,
     },
     parser::{driver::ParserFailure, tables::Ll1RejectionContext},
+    span::Span,
 };
 
 /// Public severity class used by rendered diagnostics and diagnostic registries.
@@ -514,6 +515,12 @@ pub const DIAGNOSTIC_CODE_REGISTRY: &[DiagnosticCodeInfo] = &[
         "tooling",
         DiagnosticPrimaryLabelPolicy::None,
     ),
+    DiagnosticCodeInfo::error(
+        "LNC0068",
+        "empty source not allowed",
+        "parsing",
+        DiagnosticPrimaryLabelPolicy::Required,
+    ),
 ];
 
 /// Stable diagnostic categories used by registry and category metadata output.
@@ -2337,8 +2344,8 @@ pub(in crate::compiler) fn parser_failure_to_compile_error_for_source(
                 diagnostic_label_from_source_span(
                     diagnostic_path,
                     source,
-                    label_token.start,
-                    label_token.len,
+                    label_token.start(),
+                    label_token.len(),
                     label_message,
                 )
             }
@@ -2550,24 +2557,20 @@ pub(in crate::compiler) fn syntax_error_to_compile_error_for_source_span(
     syntax_error_to_compile_error_for_source_span_with_message(
         diagnostic_path,
         source,
-        label_token.start,
-        label_token.len,
+        label_token.start(),
+        label_token.len(),
         label_message,
     )
 }
 
 fn source_token_for_syntax_span(source: &str, start: usize, len: usize) -> Token {
     if let Some((token, _)) = source_token_at_or_after(source, start)
-        && token.start == start
+        && token.start() == start
     {
         return token;
     }
 
-    Token {
-        kind: TokenKind::Ident,
-        start,
-        len,
-    }
+    Token::new(TokenKind::Ident, start, len)
 }
 
 struct SourceSyntaxLabel {
@@ -2586,7 +2589,7 @@ impl<'a> SourceSyntaxTokens<'a> {
         let mut tokens = Vec::new();
         let mut index = 0usize;
         while let Some((token, next_index)) = source_token_at_or_after(source, index) {
-            let next_index = next_index.max(token.start.saturating_add(token.len));
+            let next_index = next_index.max(token.end());
             tokens.push(token);
             index = next_index;
         }
@@ -2656,8 +2659,8 @@ fn ll1_rejection_syntax_label_for_source_pack<'a>(
         return Some((
             entry.file,
             SourceSyntaxLabel {
-                start: entry.token.start,
-                len: entry.token.len,
+                start: entry.token.start(),
+                len: entry.token.len(),
                 message,
             },
         ));
@@ -2716,8 +2719,8 @@ impl SourceSyntaxTokens<'_> {
 
         if let Some(token) = source_index.and_then(|index| self.tokens.get(index)) {
             SourceSyntaxLabel {
-                start: token.start,
-                len: token.len,
+                start: token.start(),
+                len: token.len(),
                 message,
             }
         } else {
@@ -2769,6 +2772,7 @@ fn expected_syntax_terminal_description(kind: u32) -> String {
         TokenKind::Int => "integer literal".to_string(),
         TokenKind::Float => "float literal".to_string(),
         TokenKind::String => "string literal".to_string(),
+        TokenKind::RawString => "raw string literal".to_string(),
         TokenKind::Char => "character literal".to_string(),
         kind if syntax_token_is_keyword(kind) => format!(
             "keyword `{}`",
@@ -2955,6 +2959,7 @@ fn expected_punctuation_text(kind: TokenKind) -> Option<&'static str> {
         TokenKind::ShrAssign => Some(">>="),
         TokenKind::AmpAssign => Some("&="),
         TokenKind::PipeAssign => Some("|="),
+        TokenKind::TildeAssign => Some("~="),
         TokenKind::Inc | TokenKind::PrefixInc | TokenKind::PostfixInc => Some("++"),
         TokenKind::Dec | TokenKind::PrefixDec | TokenKind::PostfixDec => Some("--"),
         _ => None,
@@ -2985,8 +2990,8 @@ fn missing_control_flow_block_label(tokens: &SourceSyntaxTokens<'_>) -> Option<S
                         match tokens.get(cursor + 1) {
                             Some(next_token) if next_token.kind != TokenKind::LBrace => {
                                 return Some(SourceSyntaxLabel {
-                                    start: next_token.start,
-                                    len: next_token.len,
+                                    start: next_token.start(),
+                                    len: next_token.len(),
                                     message: format!(
                                         "expected `{{` after {context} condition, found {}",
                                         syntax_token_description(tokens.source, next_token)
@@ -3029,8 +3034,8 @@ fn missing_assignment_expression_label(
         match tokens.get(index + 1) {
             Some(next_token) if token_ends_missing_assignment_expression(next_token.kind) => {
                 return Some(SourceSyntaxLabel {
-                    start: next_token.start,
-                    len: next_token.len,
+                    start: next_token.start(),
+                    len: next_token.len(),
                     message: "expected expression after `=`".to_string(),
                 });
             }
@@ -3061,15 +3066,15 @@ fn missing_module_path_label(tokens: &SourceSyntaxTokens<'_>) -> Option<SourceSy
         match tokens.get(index + 1) {
             Some(next_token) if token_ends_missing_module_path(next_token.kind) => {
                 return Some(SourceSyntaxLabel {
-                    start: next_token.start,
-                    len: next_token.len,
+                    start: next_token.start(),
+                    len: next_token.len(),
                     message: format!("expected module path after `{keyword}`"),
                 });
             }
             Some(next_token) if !token_can_start_module_path(next_token.kind) => {
                 return Some(SourceSyntaxLabel {
-                    start: next_token.start,
-                    len: next_token.len,
+                    start: next_token.start(),
+                    len: next_token.len(),
                     message: format!(
                         "expected module path after `{keyword}`, found {}",
                         syntax_token_description(tokens.source, next_token)
@@ -3117,8 +3122,8 @@ fn missing_statement_semicolon_label(tokens: &SourceSyntaxTokens<'_>) -> Option<
             }
             if expression_depth == 0 && scan_token.kind == TokenKind::RBrace {
                 return Some(SourceSyntaxLabel {
-                    start: scan_token.start,
-                    len: scan_token.len,
+                    start: scan_token.start(),
+                    len: scan_token.len(),
                     message: format!("expected ';' after {statement_context}"),
                 });
             }
@@ -3326,10 +3331,11 @@ pub(in crate::compiler) fn parser_failure_to_compile_error_for_source_pack(
 
     match read_single_token_from_buffer(device, queue, token_buffer, failure.ll1().error_pos) {
         Ok(token) => {
-            let Some(file) = source_pack_file_for_global_span(diagnostic_files, token.start) else {
-                return source_pack_nearest_file_for_global_span(diagnostic_files, token.start)
+            let Some(file) = source_pack_file_for_global_span(diagnostic_files, token.start())
+            else {
+                return source_pack_nearest_file_for_global_span(diagnostic_files, token.start())
                     .map(|fallback_file| {
-                        source_pack_fallback_syntax_error(fallback_file, token.start)
+                        source_pack_fallback_syntax_error(fallback_file, token.start())
                     })
                     .unwrap_or_else(|| {
                         super::CompileError::Diagnostic(Diagnostic::error(
@@ -3342,7 +3348,7 @@ pub(in crate::compiler) fn parser_failure_to_compile_error_for_source_pack(
             let previous_token = previous_source_token(&file.source, &local_token).or_else(|| {
                 previous_diagnostic_token(device, queue, token_buffer, failure.ll1().error_pos)
                     .and_then(|previous| {
-                        source_pack_file_for_global_span(diagnostic_files, previous.start)
+                        source_pack_file_for_global_span(diagnostic_files, previous.start())
                             .filter(|previous_file| std::ptr::eq(*previous_file, file))
                             .map(|_| source_pack_local_token(&previous, file))
                     })
@@ -3350,7 +3356,7 @@ pub(in crate::compiler) fn parser_failure_to_compile_error_for_source_pack(
             let next_token = next_source_token(&file.source, &local_token).or_else(|| {
                 next_diagnostic_token(device, queue, token_buffer, failure.ll1().error_pos)
                     .and_then(|next| {
-                        source_pack_file_for_global_span(diagnostic_files, next.start)
+                        source_pack_file_for_global_span(diagnostic_files, next.start())
                             .filter(|next_file| std::ptr::eq(*next_file, file))
                             .map(|_| source_pack_local_token(&next, file))
                     })
@@ -3364,8 +3370,8 @@ pub(in crate::compiler) fn parser_failure_to_compile_error_for_source_pack(
             syntax_error_to_compile_error_for_source_span_with_message(
                 &file.path,
                 &file.source,
-                label_token.start,
-                label_token.len,
+                label_token.start(),
+                label_token.len(),
                 label_message,
             )
         }
@@ -3408,20 +3414,20 @@ fn next_diagnostic_token(
 fn source_pack_local_token(token: &Token, file: &DiagnosticSourceFile) -> Token {
     Token {
         kind: token.kind,
-        start: file.local_start_for_global(token.start),
-        len: token.len,
+        raw_kind: token.raw_kind,
+        span: Span::from_usize(file.local_start_for_global(token.start()), token.len()),
     }
 }
 
 fn previous_source_token(source: &str, token: &Token) -> Option<Token> {
     let mut index = 0usize;
     let mut previous = None;
-    while index < token.start {
+    while index < token.start() {
         let (candidate, next_index) = source_token_at_or_after(source, index)?;
-        if candidate.start >= token.start {
+        if candidate.start() >= token.start() {
             break;
         }
-        let next_index = next_index.max(candidate.start.saturating_add(candidate.len));
+        let next_index = next_index.max(candidate.end());
         previous = Some(candidate);
         index = next_index;
     }
@@ -3429,7 +3435,7 @@ fn previous_source_token(source: &str, token: &Token) -> Option<Token> {
 }
 
 fn next_source_token(source: &str, token: &Token) -> Option<Token> {
-    let start = token.start.checked_add(token.len)?;
+    let start = token.start().checked_add(token.len())?;
     let (token, _) = source_token_at_or_after(source, start)?;
     Some(token)
 }
@@ -3452,8 +3458,8 @@ fn source_token_at_or_after(source: &str, start: usize) -> Option<(Token, usize)
         return Some((
             Token {
                 kind: source_identifier_token_kind(text),
-                start: token_start,
-                len: index - token_start,
+                raw_kind: TokenKind::Ident,
+                span: Span::from_usize(token_start, index - token_start),
             },
             index,
         ));
@@ -3469,24 +3475,13 @@ fn source_token_at_or_after(source: &str, start: usize) -> Option<(Token, usize)
         } else {
             TokenKind::Int
         };
-        return Some((
-            Token {
-                kind,
-                start: token_start,
-                len: index - token_start,
-            },
-            index,
-        ));
+        return Some((Token::new(kind, token_start, index - token_start), index));
     }
 
     if byte == b'"' {
         let end = skip_quoted_token(bytes, index, b'"').unwrap_or(index + 1);
         return Some((
-            Token {
-                kind: TokenKind::String,
-                start: token_start,
-                len: end - token_start,
-            },
+            Token::new(TokenKind::String, token_start, end - token_start),
             end,
         ));
     }
@@ -3494,24 +3489,13 @@ fn source_token_at_or_after(source: &str, start: usize) -> Option<(Token, usize)
     if byte == b'\'' {
         let end = skip_quoted_token(bytes, index, b'\'').unwrap_or(index + 1);
         return Some((
-            Token {
-                kind: TokenKind::Char,
-                start: token_start,
-                len: end - token_start,
-            },
+            Token::new(TokenKind::Char, token_start, end - token_start),
             end,
         ));
     }
 
     let (kind, len) = source_punctuation_token_kind(bytes, index)?;
-    Some((
-        Token {
-            kind,
-            start: token_start,
-            len,
-        },
-        token_start + len,
-    ))
+    Some((Token::new(kind, token_start, len), token_start + len))
 }
 
 fn skip_source_trivia(bytes: &[u8], mut index: usize) -> usize {
@@ -3599,6 +3583,7 @@ fn source_punctuation_token_kind(bytes: &[u8], index: usize) -> Option<(TokenKin
         Some(b"^=") => return Some((TokenKind::CaretAssign, 2)),
         Some(b"&=") => return Some((TokenKind::AmpAssign, 2)),
         Some(b"|=") => return Some((TokenKind::PipeAssign, 2)),
+        Some(b"~=") => return Some((TokenKind::TildeAssign, 2)),
         Some(b"++") => return Some((TokenKind::Inc, 2)),
         Some(b"--") => return Some((TokenKind::Dec, 2)),
         Some(b"..") => return Some((TokenKind::DotDot, 2)),
@@ -3671,7 +3656,7 @@ fn parser_rejection_label_target<'a>(
 
 fn syntax_token_description(source: &str, token: &Token) -> String {
     let Some(text) = token_source_text(source, token) else {
-        if token.start >= source.len() {
+        if token.start() >= source.len() {
             return "end of input".to_string();
         }
         return format!("{:?}", token.kind);
@@ -3685,8 +3670,10 @@ fn syntax_token_description(source: &str, token: &Token) -> String {
 }
 
 fn token_source_text<'a>(source: &'a str, token: &Token) -> Option<&'a str> {
-    let end = token.start.checked_add(token.len)?;
-    source.get(token.start..end).filter(|text| !text.is_empty())
+    let end = token.start().checked_add(token.len())?;
+    source
+        .get(token.start()..end)
+        .filter(|text| !text.is_empty())
 }
 
 fn syntax_token_is_keyword(kind: TokenKind) -> bool {
@@ -4662,6 +4649,7 @@ mod tests {
             ),
             ("LNC0066", "parser execution failed", "parsing"),
             ("LNC0067", "CLI operation failed", "tooling"),
+            ("LNC0068", "empty source not allowed", "parsing"),
         ];
 
         assert_eq!(DIAGNOSTIC_CODE_REGISTRY.len(), expected.len());
@@ -5250,30 +5238,30 @@ mod tests {
         let source = "fn fn main() { return 0; }\n";
         let previous = Token {
             kind: TokenKind::Fn,
-            start: 0,
-            len: 2,
+            raw_kind: TokenKind::Ident,
+            span: Span::from_usize(0, 2),
         };
         let rejected = Token {
             kind: TokenKind::Fn,
-            start: 3,
-            len: 2,
+            raw_kind: TokenKind::Ident,
+            span: Span::from_usize(3, 2),
         };
 
         let (target, message) =
             parser_rejection_label_target(source, &rejected, Some(&previous), None);
-        assert_eq!(target.start, rejected.start);
+        assert_eq!(target.start(), rejected.start());
         assert_eq!(message, "expected function name, found keyword `fn`");
 
         let (target, message) =
             parser_rejection_label_target(source, &previous, None, Some(&rejected));
-        assert_eq!(target.start, rejected.start);
+        assert_eq!(target.start(), rejected.start());
         assert_eq!(message, "expected function name, found keyword `fn`");
 
         let source_next = next_source_token(source, &previous)
             .expect("source-neighbor scan should find the duplicate keyword");
         let (target, message) =
             parser_rejection_label_target(source, &previous, None, Some(&source_next));
-        assert_eq!(target.start, rejected.start);
+        assert_eq!(target.start(), rejected.start());
         assert_eq!(message, "expected function name, found keyword `fn`");
     }
 
@@ -13,8 +13,10 @@ use buffers::OwnedTypecheckParserBuffers;
 
 mod helpers;
 mod host_timer;
+mod options;
 use helpers::{
     StageExecutionFailure,
+    empty_source_not_allowed,
     first_nonempty_source_span,
     hir_node_capacity_for_parser_emit,
     parser_execution_failed_for_source,
@@ -29,6 +31,7 @@ use helpers::{
 };
 pub(in crate::compiler) use helpers::{prepare_source_for_gpu, prepare_source_for_gpu_from_path};
 use host_timer::CompilerHostTimer;
+pub use options::{GpuCompilerOptions, compiler_options, set_compiler_options};
 
 mod source_pack_executor;
 pub use source_pack_executor::{
@@ -166,7 +169,7 @@ fn initialize_pipeline_families(
             )
         })?;
         if crate::gpu::env::env_bool_truthy("LANIUS_GPU_COMPILE_HOST_TIMING", false) {
-            eprintln!(
+            log::info!(target: crate::logging::COMPILER,
                 "[gpu_compile_host_timer] compiler.init.parallel.type_checker: {:.3}ms",
                 type_checker_elapsed.as_secs_f64() * 1000.0
             );
@@ -267,7 +270,8 @@ impl<'gpu> GpuCompiler<'gpu> {
         Self::new_with_device_and_backends(gpu, GpuCompilerBackends::all())
     }
 
-    /// Create a compiler for an existing GPU device and a selected backend set.
+    /// Create a compiler for an existing GPU device and a selected backend
+    /// set, with the process-wide [`compiler_options`].
     ///
     /// Frontend phases are always initialized. Disabled or failed backends are
     /// stored as deferred errors so frontend-only operations can still run.
@@ -275,17 +279,36 @@ impl<'gpu> GpuCompiler<'gpu> {
         gpu: &'gpu GpuDevice,
         backends: GpuCompilerBackends,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Self, CompileError>> + 'gpu>>
+    {
+        Self::new_with_device_backends_and_options(gpu, backends, compiler_options())
+    }
+
+    /// Create a compiler for an existing GPU device and a selected backend
+    /// set, with explicit frontend `options`.
+    pub fn new_with_device_backends_and_options(
+        gpu: &'gpu GpuDevice,
+        backends: GpuCompilerBackends,
+        options: GpuCompilerOptions,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Self, CompileError>> + 'gpu>>
     {
         Box::pin(async move {
             let mut host_timer = CompilerHostTimer::new("compiler.init");
             host_timer.pipeline_cache_size(gpu, "start");
-            let lexer = GpuLexer::new_with_device(gpu).await.map_err(|err| {
-                compiler_initialization_failed_error(
-                    "the compiler stopped while initializing GPU frontend pipelines",
-                    "initialize lexer",
-                    err,
-                )
-            })?;
+            // Typecheck reuses `tok_types` as a per-byte scratch row, so it
+            // keeps the wide packing's one word per byte.
+            let lexer_options = LexerRuntimeOptions {
+                wide_token_kinds: true,
+                ..options.lexer
+            };
+            let lexer = GpuLexer::new_with_device_and_options(gpu, lexer_options)
+                .await
+                .map_err(|err| {
+                    compiler_initialization_failed_error(
+                        "the compiler stopped while initializing GPU frontend pipelines",
+                        "initialize lexer",
+                        err,
+                    )
+                })?;
             host_timer.stamp("lexer");
             host_timer.pipeline_cache_size(gpu, "after_lexer");
             let parse_tables = PrecomputedParseTables::load_bin_bytes(include_bytes!(concat!(
@@ -300,13 +323,18 @@ impl<'gpu> GpuCompiler<'gpu> {
                 )
             })?;
             host_timer.stamp("parse_tables");
-            let parser = GpuParser::new_with_device(gpu).await.map_err(|err| {
-                compiler_initialization_failed_error(
-                    "the compiler stopped while initializing GPU frontend pipelines",
-                    "initialize parser",
-                    err,
-                )
-            })?;
+            let parser = GpuParser::new_with_device_and_options(gpu, options.parser)
+                .await
+                .map_err(|err| {
+                    compiler_initialization_failed_error(
+                        "the compiler stopped while initializing GPU frontend pipelines",
+                        "initialize parser",
+                        err,
+                    )
+                })?;
+            if options.bracket_cache.max_entries > 0 {
+                parser.set_bracket_cache(Some(Arc::new(BracketCache::new(options.bracket_cache))));
+            }
             host_timer.stamp("parser");
             host_timer.pipeline_cache_size(gpu, "after_parser");
             // These eager pipeline families have no construction-time data
@@ -347,6 +375,12 @@ impl<'gpu> GpuCompiler<'gpu> {
         self.gpu
     }
 
+    /// Return the bracket cache resident parses share, unless
+    /// [`GpuCompilerOptions::bracket_cache`] disabled it.
+    pub fn bracket_cache(&self) -> Option<Arc<BracketCache>> {
+        self.parser.bracket_cache()
+    }
+
     pub(super) fn ensure_lowering_capacity(
         &self,
         source_bytes: u32,
@@ -381,7 +415,11 @@ impl<'gpu> GpuCompiler<'gpu> {
         self.lexer.release_current_resident_buffers();
         self.parser.release_current_resident_buffers();
         self.type_checker.release_current_resident_state();
-        let _ = self.gpu.device.poll(wgpu::PollType::wait_indefinitely());
+        if let Err(err) =
+            crate::gpu::poll::wait_for_submitted_work(&self.gpu.device, "compiler.release")
+        {
+            log::warn!("waiting for released job buffers failed: {err:#}");
+        }
         GpuResidentJobBufferTrim
     }
 }
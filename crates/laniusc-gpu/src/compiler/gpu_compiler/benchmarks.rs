@@ -4,7 +4,7 @@ use super::*;
 
 fn benchmark_parser_execution_error(src: &str, err: impl std::fmt::Display) -> CompileError {
     if crate::gpu::env::env_bool_truthy("LANIUS_GPU_COMPILE_HOST_TIMING", false) {
-        eprintln!("[gpu_compile_host_timer] benchmark.parser.error: {err}");
+        log::info!(target: crate::logging::COMPILER, "[gpu_compile_host_timer] benchmark.parser.error: {err}");
     }
     parser_execution_failed_for_source(Path::new("<benchmark>"), src, err)
 }
@@ -78,6 +78,7 @@ impl<'gpu> GpuCompiler<'gpu> {
                             &self.parse_tables,
                             Some(parser_tree_capacity),
                             parser_capacity.parser_feature_flags,
+                            parser_capacity.bracket_key.as_ref(),
                             &mut timer,
                             |parse_bufs, encoder, timer| {
                                 self.parser
@@ -113,7 +114,7 @@ impl<'gpu> GpuCompiler<'gpu> {
                             "LANIUS_GPU_COMPILE_HOST_TIMING",
                             false,
                         ) {
-                            eprintln!(
+                            log::info!(target: crate::logging::COMPILER,
                                 "[gpu_compile_host_timer] benchmark.parser.status: accepted={} error_pos={} error_code={} detail={} steps={} emit_len={}",
                                 ll1.accepted,
                                 ll1.error_pos,
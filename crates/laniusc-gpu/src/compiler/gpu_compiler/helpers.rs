@@ -11,7 +11,7 @@ pub(super) fn hir_node_capacity_for_parser_emit(
 /// Emits a WASM compile trace line when WASM tracing is enabled.
 pub(super) fn trace_wasm_compile(stage: &str) {
     if crate::gpu::env::env_bool_strict("LANIUS_WASM_TRACE", false) {
-        eprintln!("[laniusc][wasm] {stage}");
+        log::info!(target: crate::logging::COMPILER, "[laniusc][wasm] {stage}");
     }
 }
 
@@ -185,7 +185,7 @@ pub(super) fn stage_execution_failed_for_source(
     err: impl std::fmt::Display,
 ) -> CompileError {
     if std::env::var_os("LANIUS_DEBUG_STAGE_EXECUTION").is_some() {
-        eprintln!("[laniusc][stage-execution] {err}");
+        log::info!(target: crate::logging::COMPILER, "[laniusc][stage-execution] {err}");
     }
     let (start, len) = first_nonempty_source_span(source);
     CompileError::Diagnostic(
@@ -208,7 +208,7 @@ pub(super) fn stage_execution_failed_for_source_pack(
     err: impl std::fmt::Display,
 ) -> CompileError {
     if std::env::var_os("LANIUS_DEBUG_STAGE_EXECUTION").is_some() {
-        eprintln!("[laniusc][stage-execution] {err}");
+        log::info!(target: crate::logging::COMPILER, "[laniusc][stage-execution] {err}");
     }
     let diagnostic = Diagnostic::error(failure.code, failure.message)
         .with_note(format!("source file count: {}", diagnostic_files.len()))
@@ -262,6 +262,22 @@ fn parser_execution_failure() -> StageExecutionFailure<'static> {
     }
 }
 
+/// A source with no tokens besides whitespace and comments, under parse
+/// tables whose grammar does not derive the empty program.
+pub(super) fn empty_source_not_allowed(diagnostic_path: &Path, source: &str) -> CompileError {
+    CompileError::Diagnostic(
+        Diagnostic::error("LNC0068", "empty source not allowed")
+            .with_primary_label(diagnostic_label_from_source_span(
+                diagnostic_path,
+                source,
+                0,
+                source_first_label_len(source),
+                "this source contains no tokens",
+            ))
+            .with_note("the grammar's start symbol does not derive an empty program"),
+    )
+}
+
 fn source_first_label_len(source: &str) -> usize {
     source.chars().next().map(char::len_utf8).unwrap_or(1)
 }
@@ -459,6 +475,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn empty_source_not_allowed_labels_the_start_of_the_file() {
+        for source in ["", "// nothing here\n"] {
+            match empty_source_not_allowed(Path::new("empty.lani"), source) {
+                CompileError::Diagnostic(diagnostic) => {
+                    assert_eq!(diagnostic.code, "LNC0068");
+                    assert_eq!(diagnostic.message, "empty source not allowed");
+                    let label = diagnostic
+                        .primary_label
+                        .as_ref()
+                        .expect("empty source diagnostic should carry a label");
+                    assert_eq!(label.path, PathBuf::from("empty.lani"));
+                    assert_eq!((label.line, label.column), (1, 1));
+                    assert!(
+                        diagnostic
+                            .render()
+                            .contains("error[LNC0068]: empty source not allowed")
+                    );
+                }
+                other => panic!("expected structured empty source diagnostic, got {other:?}"),
+            }
+        }
+    }
+
     #[test]
     fn parser_execution_failure_for_source_pack_is_structured_diagnostic() {
         let paths = [Some(PathBuf::from("first.lani"))];
@@ -40,7 +40,7 @@ impl CompilerHostTimer {
         let total_ms = now.duration_since(self.start).as_secs_f64() * 1000.0;
         let name = format!("{}.{stage}", self.label);
         if self.print_enabled {
-            eprintln!("[gpu_compile_host_timer] {name}: {dt_ms:.3}ms (total {total_ms:.3}ms)");
+            log::info!(target: crate::logging::COMPILER, "[gpu_compile_host_timer] {name}: {dt_ms:.3}ms (total {total_ms:.3}ms)");
         }
         if self.trace_enabled {
             crate::gpu::trace::record_host_span("host.compiler", &name, self.last, now);
@@ -59,7 +59,7 @@ impl CompilerHostTimer {
         let sample_ms = end.duration_since(start).as_secs_f64() * 1000.0;
         match size {
             Some(bytes) => {
-                eprintln!(
+                log::info!(target: crate::logging::COMPILER,
                     "[pipeline_cache_breakdown] stage={stage} bytes={bytes} sample_ms={sample_ms:.3}"
                 );
                 if self.trace_enabled {
@@ -78,7 +78,7 @@ impl CompilerHostTimer {
                 }
             }
             None => {
-                eprintln!(
+                log::info!(target: crate::logging::COMPILER,
                     "[pipeline_cache_breakdown] stage={stage} bytes=unavailable sample_ms={sample_ms:.3}"
                 );
             }
@@ -0,0 +1,56 @@
+use std::sync::Mutex;
+
+use crate::{
+    lexer::LexerRuntimeOptions,
+    parser::{BracketCacheLimits, ParserOptions},
+};
+
+/// Lexer and parser settings a [`GpuCompiler`](super::GpuCompiler) builds its
+/// frontend with.
+///
+/// The compiler never consults the environment; binaries that honour the
+/// `LANIUS_*` variables translate them with [`GpuCompilerOptions::from_env`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GpuCompilerOptions {
+    /// Lexer settings. Typecheck reuses `tok_types` as a per-byte scratch
+    /// row, so the compiler forces `wide_token_kinds` on.
+    pub lexer: LexerRuntimeOptions,
+    /// Parser settings.
+    pub parser: ParserOptions,
+    /// Bounds on the bracket cache the parser keeps across compiles, so an
+    /// edit that leaves the kind stream alone skips stack-effect validation.
+    /// `max_entries: 0` attaches no cache.
+    pub bracket_cache: BracketCacheLimits,
+}
+
+impl GpuCompilerOptions {
+    /// [`LexerRuntimeOptions::from_env`] and [`ParserOptions::from_env`],
+    /// with the default bracket cache.
+    pub fn from_env() -> Self {
+        Self {
+            lexer: LexerRuntimeOptions::from_env(),
+            parser: ParserOptions::from_env(),
+            bracket_cache: BracketCacheLimits::default(),
+        }
+    }
+}
+
+static COMPILER_OPTIONS: Mutex<Option<GpuCompilerOptions>> = Mutex::new(None);
+
+/// Replaces the process-wide options that compilers built without explicit
+/// options use, including the global compilers behind `compile_source`.
+/// Compilers that already exist keep their settings.
+pub fn set_compiler_options(options: GpuCompilerOptions) {
+    *COMPILER_OPTIONS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(options);
+}
+
+/// Returns the process-wide compiler options; the defaults until
+/// [`set_compiler_options`] is called.
+pub fn compiler_options() -> GpuCompilerOptions {
+    COMPILER_OPTIONS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .unwrap_or_default()
+}
@@ -198,6 +198,9 @@ impl<'gpu> GpuCompiler<'gpu> {
             .with_recorded_resident_tokens_after_count(
                 src,
                 |device, queue, bufs, token_count, encoder, mut timer| {
+                    if token_count == 0 && !self.parse_tables.allows_empty_input {
+                        return Err(empty_source_not_allowed(&diagnostic_path, src));
+                    }
                     let token_capacity = token_count.max(1);
                     let parser_capacity = self
                         .parser
@@ -232,6 +235,7 @@ impl<'gpu> GpuCompiler<'gpu> {
                             &self.parse_tables,
                             Some(parser_tree_capacity),
                             parser_feature_flags,
+                            parser_capacity.bracket_key.as_ref(),
                             &mut parser_timer,
                             |_parse_bufs, encoder, timer| {
                                 if let Some(timer) = timer.as_deref_mut() {
@@ -280,7 +284,10 @@ impl<'gpu> GpuCompiler<'gpu> {
                             parser_tree_capacity,
                             parser_feature_flags,
                             OwnedTypecheckParserBuffers::from_parser_buffers,
-                        );
+                        )
+                        .map_err(|err| {
+                            parser_execution_failed_for_source(&diagnostic_path, src, err)
+                        })?;
                     let type_check = self.record_typecheck_from_parse_buffers(
                         device,
                         queue,
@@ -538,6 +545,7 @@ impl<'gpu> GpuCompiler<'gpu> {
                             &self.parse_tables,
                             Some(parser_tree_capacity),
                             parser_feature_flags,
+                            parser_capacity.bracket_key.as_ref(),
                             &mut parser_timer,
                             |_parse_bufs, encoder, timer| {
                                 if let Some(timer) = timer.as_deref_mut() {
@@ -585,7 +593,10 @@ impl<'gpu> GpuCompiler<'gpu> {
                             parser_tree_capacity,
                             parser_feature_flags,
                             OwnedTypecheckParserBuffers::from_parser_buffers,
-                        );
+                        )
+                        .map_err(|err| {
+                            parser_execution_failed_for_source_pack(&diagnostic_files, err)
+                        })?;
                     let type_check = self.record_typecheck_from_parse_buffers(
                         device,
                         queue,
@@ -939,10 +950,12 @@ impl<'gpu> GpuCompiler<'gpu> {
             import_visible_value_count: &parse_bufs.hir_variant_payload_rank_b,
             import_visible_type_prefix: &parse_bufs.hir_variant_payload_owner_a,
             import_visible_value_prefix: &parse_bufs.hir_variant_payload_owner_b,
+            // Word per byte only because the compiler's lexer forces
+            // `wide_token_kinds`.
             resolved_type_decl: &lexer_bufs.tok_types.buffer,
             resolved_value_decl: &lexer_bufs.flags_packed.buffer,
             resolved_type_status: &lexer_bufs.s_all_final.buffer,
-            resolved_value_status: &lexer_bufs.s_keep_final.buffer,
+            resolved_value_status: &lexer_bufs.end_positions_all.buffer,
             // List-ranking workspaces are dead after parser HIR construction
             // and are not borrowed by x86. Use them for retained member/struct
             // type metadata produced after type-instance collection.
@@ -1015,10 +1028,10 @@ pub(super) fn type_check_error_to_compile_error_for_source(
             detail,
         } => {
             if std::env::var_os("LANIUS_DEBUG_STAGE_ERRORS").is_some() {
-                eprintln!("GPU type-check rejection: token={token} code={code:?} detail={detail}");
+                log::info!(target: crate::logging::COMPILER, "GPU type-check rejection: token={token} code={code:?} detail={detail}");
             }
             let (start, len) = read_single_token_for_diagnostic(device, queue, bufs, token)
-                .map(|token_record| (token_record.start, token_record.len))
+                .map(|token_record| (token_record.start(), token_record.len()))
                 .unwrap_or_else(|_| first_nonempty_source_span(src));
             type_check_diagnostic_at_span(diagnostic_path, src, start, len, code, detail)
         }
@@ -1040,7 +1053,7 @@ fn type_check_error_to_compile_error_for_source_pack(
             detail,
         } => {
             if std::env::var_os("LANIUS_DEBUG_STAGE_ERRORS").is_some() {
-                eprintln!(
+                log::info!(target: crate::logging::COMPILER,
                     "GPU source-pack type-check rejection: token={token} code={code:?} detail={detail}"
                 );
             }
@@ -1053,16 +1066,15 @@ fn type_check_error_to_compile_error_for_source_pack(
             )
             .ok()
             .and_then(|token_record| {
-                source_pack_nearest_file_for_global_span(diagnostic_files, token_record.start).map(
-                    |file| {
+                source_pack_nearest_file_for_global_span(diagnostic_files, token_record.start())
+                    .map(|file| {
                         (
                             file.path.as_path(),
                             file.source.as_str(),
-                            file.local_start_for_global(token_record.start),
-                            token_record.len,
+                            file.local_start_for_global(token_record.start()),
+                            token_record.len(),
                         )
-                    },
-                )
+                    })
             })
             .or_else(|| source_pack_fallback_type_check_span(diagnostic_files))
             {
@@ -1110,7 +1122,7 @@ pub(in crate::compiler::gpu_compiler) fn type_check_execution_failed_for_source(
     err: impl std::fmt::Display + std::fmt::Debug,
 ) -> CompileError {
     if std::env::var_os("LANIUS_DEBUG_STAGE_ERRORS").is_some() {
-        eprintln!("GPU type-check detail: {err:?}");
+        log::info!(target: crate::logging::COMPILER, "GPU type-check detail: {err:?}");
     }
     stage_execution_failed_for_source(type_check_execution_failure(), diagnostic_path, source, err)
 }
@@ -1120,7 +1132,7 @@ pub(in crate::compiler::gpu_compiler) fn type_check_execution_failed_for_source_
     err: impl std::fmt::Display + std::fmt::Debug,
 ) -> CompileError {
     if std::env::var_os("LANIUS_DEBUG_STAGE_ERRORS").is_some() {
-        eprintln!("GPU type-check detail: {err:?}");
+        log::info!(target: crate::logging::COMPILER, "GPU type-check detail: {err:?}");
     }
     stage_execution_failed_for_source_pack(type_check_execution_failure(), diagnostic_files, err)
 }
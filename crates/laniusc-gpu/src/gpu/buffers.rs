@@ -9,6 +9,9 @@ use std::{
     },
 };
 
+mod plan;
+pub use plan::{BufferKind, BufferPlan, PlannedBuffer, PlannedBuffers, SplitBinding};
+
 static LIVE_BUFFER_ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
 static LIVE_BUFFER_BYTES: AtomicU64 = AtomicU64::new(0);
 static PEAK_BUFFER_ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
@@ -418,6 +421,17 @@ pub fn readback_bytes(
     LaniusBuffer::new_labeled((raw, byte_size as u64), count, label)
 }
 
+/// Returns the std430-padded size of one `T` array element.
+pub fn storage_element_size<T>() -> usize
+where
+    T: Default + encase::ShaderType + encase::internal::WriteInto,
+{
+    let mut sb = encase::StorageBuffer::new(Vec::<u8>::new());
+    sb.write(&T::default())
+        .expect("failed to write default element into StorageBuffer");
+    sb.as_ref().len()
+}
+
 /// Create a STORAGE buffer (read/write) sized for an array of `T` using WGSL/std430 size/stride.
 /// We compute the **padded element size** by encoding one `T::default()` with `encase::StorageBuffer`.
 /// Requires `T: Default` so we can synthesize one element just to measure its layout.
@@ -425,10 +439,7 @@ pub fn storage_rw_for_array<T>(device: &wgpu::Device, label: &str, count: usize)
 where
     T: Default + encase::ShaderType + encase::internal::WriteInto,
 {
-    let mut sb = encase::StorageBuffer::new(Vec::<u8>::new());
-    sb.write(&T::default())
-        .expect("failed to write default element into StorageBuffer");
-    let elem_padded_bytes = sb.as_ref().len(); // encase gives us the correct std430-padded size
+    let elem_padded_bytes = storage_element_size::<T>();
     debug_assert!(
         elem_padded_bytes > 0,
         "encase reported zero-sized element for {label}"
@@ -0,0 +1,542 @@
+//! Declarative buffer-set planning.
+//!
+//! A [`BufferPlan`] records every buffer of a set with its element type, count,
+//! usage, and optional initial contents. Sizes are computed once, validated
+//! against device limits, and only then materialized, so limit violations and
+//! zero-size mistakes surface as one error before anything is allocated.
+//!
+//! A storage array declared with [`BufferPlan::storage_splittable`] may pass
+//! the binding limit as long as a [`SplitBinding`] fits it: the buffer is
+//! still allocated whole, and the kernels built for the split bind it as two
+//! windows.
+
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{Result, anyhow, bail};
+
+use super::{LaniusBuffer, create_buffer_init_checked, storage_element_size};
+use crate::gpu::limits::LimitError;
+
+/// Remedy reported with [`LimitError::InputExceedsDeviceLimits`] when the
+/// plan does not name its own.
+const DEFAULT_SUGGESTION: &str = "split the input into smaller batches";
+
+/// Binding class of a planned buffer; selects which binding limit applies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BufferKind {
+    Storage,
+    Uniform,
+}
+
+/// One declared buffer: its label, element type, and computed byte size.
+#[derive(Clone, Debug)]
+pub struct PlannedBuffer {
+    pub label: Arc<str>,
+    pub kind: BufferKind,
+    pub usage: wgpu::BufferUsages,
+    /// `std::any::type_name` of the element type the buffer is taken as.
+    pub element: &'static str,
+    /// Number of logical elements.
+    pub count: usize,
+    /// Allocated size in bytes.
+    pub byte_size: u64,
+    /// Whether the kernels can bind the buffer as a [`SplitBinding`].
+    pub splittable: bool,
+    init: Option<Vec<u8>>,
+}
+
+impl PlannedBuffer {
+    /// Initial contents uploaded at materialization, if any.
+    pub fn init(&self) -> Option<&[u8]> {
+        self.init.as_deref()
+    }
+
+    /// Smallest binding limit the buffer fits under, split if it may be.
+    pub fn min_binding_size(&self) -> u64 {
+        if !self.splittable || self.count < 2 {
+            return self.byte_size;
+        }
+        let element_size = self.byte_size / self.count as u64;
+        let low_elements = (self.count as u64).next_power_of_two() / 2;
+        (element_size * low_elements).min(self.byte_size)
+    }
+
+    /// Whether the buffer fits `binding_limit`, whole or split.
+    fn fits_binding(&self, binding_limit: u64, offset_alignment: u32) -> bool {
+        self.byte_size <= binding_limit
+            || (self.splittable
+                && SplitBinding::plan(self.count, self.byte_size, binding_limit, offset_alignment)
+                    .is_some())
+    }
+}
+
+/// Two storage bindings over one buffer too large for a single binding.
+///
+/// The low binding holds the first `1 << shift` elements and the high one the
+/// rest, at most as many again, so a kernel picks the binding by bit `shift`
+/// of an element index and indexes it with the bits below.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SplitBinding {
+    /// Log2 of the element count of the low binding.
+    pub shift: u32,
+    /// Byte offset of the high binding, which is the byte size of the low one.
+    pub low_bytes: u64,
+}
+
+impl SplitBinding {
+    /// The split of `count` elements totalling `byte_size` bytes when they
+    /// pass `binding_limit`, or `None` when they fit one binding or no split
+    /// keeps both bindings under it with the high one starting at a multiple
+    /// of `offset_alignment`.
+    pub fn plan(
+        count: usize,
+        byte_size: u64,
+        binding_limit: u64,
+        offset_alignment: u32,
+    ) -> Option<Self> {
+        if byte_size <= binding_limit || count < 2 {
+            return None;
+        }
+        let element_size = byte_size / count as u64;
+        let low_elements = binding_limit / element_size.max(1);
+        if low_elements == 0 {
+            return None;
+        }
+        let shift = low_elements.ilog2();
+        let low_bytes = element_size << shift;
+        let fits = shift < 32
+            && (count as u64) <= 2u64 << shift
+            && low_bytes.is_multiple_of(u64::from(offset_alignment.max(1)));
+        fits.then_some(Self { shift, low_bytes })
+    }
+
+    /// The low and high bindings of `buffer`, which holds `byte_size` bytes.
+    pub fn bindings(
+        self,
+        buffer: &wgpu::Buffer,
+        byte_size: u64,
+    ) -> (wgpu::BindingResource<'_>, wgpu::BindingResource<'_>) {
+        let window = |offset: u64, size: u64| {
+            wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer,
+                offset,
+                size: wgpu::BufferSize::new(size),
+            })
+        };
+        (
+            window(0, self.low_bytes),
+            window(self.low_bytes, byte_size - self.low_bytes),
+        )
+    }
+}
+
+/// Builder for a named set of GPU buffers.
+#[derive(Clone, Debug, Default)]
+pub struct BufferPlan {
+    entries: Vec<PlannedBuffer>,
+    /// Declaration errors deferred to `validate` so the builder stays chainable.
+    errors: Vec<String>,
+    /// Remedy named when a buffer exceeds the device limits.
+    suggestion: Option<&'static str>,
+}
+
+const STORAGE_USAGE: wgpu::BufferUsages = wgpu::BufferUsages::STORAGE
+    .union(wgpu::BufferUsages::COPY_SRC)
+    .union(wgpu::BufferUsages::COPY_DST);
+const UNIFORM_USAGE: wgpu::BufferUsages =
+    wgpu::BufferUsages::UNIFORM.union(wgpu::BufferUsages::COPY_DST);
+
+impl BufferPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares an uninitialized read/write storage array of `count` elements
+    /// using the std430-padded element size of `T`.
+    pub fn storage<T>(&mut self, label: impl Into<Arc<str>>, count: usize) -> &mut Self
+    where
+        T: Default + encase::ShaderType + encase::internal::WriteInto,
+    {
+        let label = label.into();
+        let byte_size = storage_element_size::<T>()
+            .checked_mul(count)
+            .map(|bytes| bytes as u64);
+        let Some(byte_size) = byte_size else {
+            self.errors
+                .push(format!("{label}: {count} elements overflow the byte size"));
+            return self;
+        };
+        self.push::<T>(label, BufferKind::Storage, count, byte_size, None)
+    }
+
+    /// Like [`BufferPlan::storage`], for an array the kernels can also bind as
+    /// a [`SplitBinding`] when it passes the storage binding limit.
+    pub fn storage_splittable<T>(&mut self, label: impl Into<Arc<str>>, count: usize) -> &mut Self
+    where
+        T: Default + encase::ShaderType + encase::internal::WriteInto,
+    {
+        let label = label.into();
+        self.storage::<T>(label.clone(), count);
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.label == label) {
+            entry.splittable = true;
+        }
+        self
+    }
+
+    /// Names the remedy reported when a buffer exceeds the device limits.
+    pub fn suggest(&mut self, suggestion: &'static str) -> &mut Self {
+        self.suggestion = Some(suggestion);
+        self
+    }
+
+    /// Declares an uninitialized storage buffer with an explicit byte size.
+    pub fn storage_bytes(
+        &mut self,
+        label: impl Into<Arc<str>>,
+        byte_size: usize,
+        count: usize,
+    ) -> &mut Self {
+        self.push::<u8>(
+            label.into(),
+            BufferKind::Storage,
+            count,
+            byte_size as u64,
+            None,
+        )
+    }
+
+    /// Declares a storage buffer initialized with `values`.
+    pub fn storage_u32s(&mut self, label: impl Into<Arc<str>>, values: &[u32]) -> &mut Self {
+        let bytes = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        self.storage_init::<u32>(label, bytes, values.len())
+    }
+
+    /// Declares a storage buffer of `count` `T` elements initialized from raw bytes.
+    pub fn storage_init<T: 'static>(
+        &mut self,
+        label: impl Into<Arc<str>>,
+        bytes: Vec<u8>,
+        count: usize,
+    ) -> &mut Self {
+        let byte_size = bytes.len() as u64;
+        self.push::<T>(
+            label.into(),
+            BufferKind::Storage,
+            count,
+            byte_size,
+            Some(bytes),
+        )
+    }
+
+    /// Declares a uniform buffer holding one std140-encoded `value`.
+    pub fn uniform<T>(&mut self, label: impl Into<Arc<str>>, value: &T) -> &mut Self
+    where
+        T: encase::ShaderType + encase::internal::WriteInto,
+    {
+        let mut ub = encase::UniformBuffer::new(Vec::<u8>::new());
+        ub.write(value)
+            .expect("failed to write value into UniformBuffer");
+        let bytes = ub.into_inner();
+        let byte_size = bytes.len() as u64;
+        self.push::<T>(label.into(), BufferKind::Uniform, 1, byte_size, Some(bytes))
+    }
+
+    fn push<T: ?Sized>(
+        &mut self,
+        label: Arc<str>,
+        kind: BufferKind,
+        count: usize,
+        byte_size: u64,
+        init: Option<Vec<u8>>,
+    ) -> &mut Self {
+        if self.entry(&label).is_some() {
+            self.errors.push(format!("{label}: declared twice"));
+            return self;
+        }
+        let usage = match kind {
+            BufferKind::Storage => STORAGE_USAGE,
+            BufferKind::Uniform => UNIFORM_USAGE,
+        };
+        self.entries.push(PlannedBuffer {
+            label,
+            kind,
+            usage,
+            element: std::any::type_name::<T>(),
+            count,
+            byte_size,
+            splittable: false,
+            init,
+        });
+        self
+    }
+
+    /// Declares every buffer of `other` after this plan's own; a label
+    /// declared by both is reported by `validate`.
+    pub fn append(&mut self, other: BufferPlan) -> &mut Self {
+        self.errors.extend(other.errors);
+        self.suggestion = self.suggestion.or(other.suggestion);
+        for entry in other.entries {
+            if self.entry(&entry.label).is_some() {
+                self.errors.push(format!("{}: declared twice", entry.label));
+            } else {
+                self.entries.push(entry);
+            }
+        }
+        self
+    }
+
+    /// Declared buffers in declaration order.
+    pub fn entries(&self) -> &[PlannedBuffer] {
+        &self.entries
+    }
+
+    /// Looks up one declared buffer by label.
+    pub fn entry(&self, label: &str) -> Option<&PlannedBuffer> {
+        self.entries.iter().find(|e| e.label.as_ref() == label)
+    }
+
+    /// Sum of all declared byte sizes.
+    pub fn total_bytes(&self) -> u64 {
+        self.entries.iter().map(|e| e.byte_size).sum()
+    }
+
+    /// Checks every declaration against `limits`.
+    ///
+    /// Uninitialized buffers must be non-empty: a zero-sized binding is always
+    /// a sizing bug. Initialized buffers may be empty, matching the upload
+    /// helpers, which create a zero-sized buffer for empty data. A buffer past
+    /// a size limit fails with [`LimitError::InputExceedsDeviceLimits`] for
+    /// the first such buffer, in a context listing every problem.
+    pub fn validate(&self, limits: &wgpu::Limits) -> Result<()> {
+        let mut problems = self.errors.clone();
+        let mut exceeded = None;
+        for e in &self.entries {
+            let (limit, binding_limit) = match e.kind {
+                BufferKind::Storage => (
+                    "max_storage_buffer_binding_size",
+                    limits.max_storage_buffer_binding_size,
+                ),
+                BufferKind::Uniform => (
+                    "max_uniform_buffer_binding_size",
+                    limits.max_uniform_buffer_binding_size,
+                ),
+            };
+            if e.byte_size > limits.max_buffer_size {
+                problems.push(format!(
+                    "{}: {} bytes exceeds max_buffer_size {}",
+                    e.label, e.byte_size, limits.max_buffer_size
+                ));
+                exceeded.get_or_insert(("max_buffer_size", e.byte_size, limits.max_buffer_size));
+            } else if !e.fits_binding(binding_limit, limits.min_storage_buffer_offset_alignment) {
+                problems.push(format!(
+                    "{}: {} bytes exceeds the {:?} binding limit {binding_limit}",
+                    e.label, e.byte_size, e.kind
+                ));
+                exceeded.get_or_insert((limit, e.min_binding_size(), binding_limit));
+            }
+            if e.byte_size == 0 && e.init.is_none() {
+                problems.push(format!("{}: zero-sized uninitialized buffer", e.label));
+            }
+        }
+        if problems.is_empty() {
+            return Ok(());
+        }
+        let message = format!("buffer plan is invalid: {}", problems.join("; "));
+        match exceeded {
+            Some((limit, needed, granted)) => {
+                Err(anyhow::Error::new(LimitError::InputExceedsDeviceLimits {
+                    limit,
+                    needed,
+                    granted,
+                    suggestion: self.suggestion.unwrap_or(DEFAULT_SUGGESTION),
+                })
+                .context(message))
+            }
+            None => bail!(message),
+        }
+    }
+
+    /// Validates against the device limits, then allocates every buffer.
+    ///
+    /// With a `queue`, initial contents are uploaded through
+    /// `queue.write_buffer`; without one, they are written through a mapping
+    /// at creation.
+    pub fn materialize(
+        self,
+        device: &wgpu::Device,
+        queue: Option<&wgpu::Queue>,
+    ) -> Result<PlannedBuffers> {
+        self.validate(&device.limits())?;
+        let mut buffers = HashMap::with_capacity(self.entries.len());
+        for e in self.entries {
+            let raw = match (&e.init, queue) {
+                (Some(bytes), None) => create_buffer_init_checked(device, &e.label, bytes, e.usage),
+                (init, queue) => {
+                    let raw = device.create_buffer(&wgpu::BufferDescriptor {
+                        label: Some(&e.label),
+                        size: e.byte_size,
+                        usage: e.usage,
+                        mapped_at_creation: false,
+                    });
+                    if let (Some(bytes), Some(queue)) = (init, queue)
+                        && !bytes.is_empty()
+                    {
+                        queue.write_buffer(&raw, 0, bytes);
+                    }
+                    raw
+                }
+            };
+            let buffer =
+                LaniusBuffer::<u8>::new_labeled((raw, e.byte_size), e.count, e.label.clone());
+            buffers.insert(e.label, (e.element, buffer));
+        }
+        Ok(PlannedBuffers { buffers })
+    }
+}
+
+/// Buffers allocated from a [`BufferPlan`], taken out by label and type.
+pub struct PlannedBuffers {
+    buffers: HashMap<Arc<str>, (&'static str, LaniusBuffer<u8>)>,
+}
+
+impl PlannedBuffers {
+    /// Removes one buffer, checking that `T` matches its declared element type.
+    pub fn take<T>(&mut self, label: &str) -> Result<LaniusBuffer<T>> {
+        let (element, buffer) = self
+            .buffers
+            .remove(label)
+            .ok_or_else(|| anyhow!("buffer plan has no buffer {label:?}"))?;
+        let requested = std::any::type_name::<T>();
+        if element != requested {
+            bail!("buffer {label:?} was planned as {element}, taken as {requested}");
+        }
+        let count = buffer.count;
+        Ok(buffer.reinterpret(count))
+    }
+
+    /// Errors when planned buffers were never taken.
+    pub fn finish(self) -> Result<()> {
+        if self.buffers.is_empty() {
+            return Ok(());
+        }
+        let mut labels: Vec<_> = self.buffers.keys().map(|l| l.as_ref()).collect();
+        labels.sort_unstable();
+        bail!("planned buffers were never used: {}", labels.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plans_exceeding_max_buffer_size_fail_validation() {
+        let limits = wgpu::Limits::default();
+        let words = (limits.max_buffer_size / 4 + 1) as usize;
+        let mut plan = BufferPlan::new();
+        plan.storage::<u32>("small", 16)
+            .storage::<u32>("huge", words);
+
+        let err = plan.validate(&limits).unwrap_err().to_string();
+        assert!(
+            err.contains("huge") && err.contains("max_buffer_size"),
+            "{err}"
+        );
+        assert!(!err.contains("small"), "{err}");
+    }
+
+    #[test]
+    fn storage_binding_limit_is_checked_separately_from_buffer_size() {
+        let limits = wgpu::Limits {
+            max_buffer_size: 1 << 20,
+            max_storage_buffer_binding_size: 1 << 10,
+            ..wgpu::Limits::default()
+        };
+        let mut plan = BufferPlan::new();
+        plan.storage::<u32>("fits", 256);
+        assert!(plan.validate(&limits).is_ok());
+        plan.storage::<u32>("binding", 257);
+        let err = plan.validate(&limits).unwrap_err().to_string();
+        assert!(err.contains("binding") && err.contains("Storage"), "{err}");
+    }
+
+    #[test]
+    fn split_bindings_cut_at_a_power_of_two_under_the_limit() {
+        // 12-byte records under a 1 KiB binding: 64 records (768 bytes) in
+        // the low window, offset-aligned to 256.
+        let split = SplitBinding::plan(100, 1200, 1024, 256).unwrap();
+        assert_eq!(
+            split,
+            SplitBinding {
+                shift: 6,
+                low_bytes: 768,
+            }
+        );
+        assert_eq!(SplitBinding::plan(128, 1536, 1024, 256), Some(split));
+        // Fits whole, or the high window would need bit 7 too.
+        assert_eq!(SplitBinding::plan(80, 960, 1024, 256), None);
+        assert_eq!(SplitBinding::plan(129, 1548, 1024, 256), None);
+        // A low window of 32 records ends at 384 bytes, off the alignment.
+        assert_eq!(SplitBinding::plan(60, 720, 512, 256), None);
+        assert_eq!(SplitBinding::plan(60, 720, 512, 4).unwrap().shift, 5);
+    }
+
+    #[test]
+    fn splittable_buffers_pass_the_binding_limit_by_up_to_twice() {
+        let limits = wgpu::Limits {
+            max_buffer_size: 1 << 20,
+            max_storage_buffer_binding_size: 1 << 10,
+            ..wgpu::Limits::default()
+        };
+        let mut plan = BufferPlan::new();
+        plan.storage_splittable::<u32>("split", 512);
+        assert!(plan.validate(&limits).is_ok());
+        assert!(plan.entry("split").unwrap().splittable);
+        assert_eq!(plan.entry("split").unwrap().min_binding_size(), 1 << 10);
+
+        plan.storage_splittable::<u32>("too_big", 513)
+            .suggest("use smaller batches");
+        let err = plan.validate(&limits).unwrap_err();
+        assert!(err.to_string().contains("too_big"), "{err}");
+        assert!(!err.to_string().contains("split:"), "{err}");
+        assert_eq!(
+            err.downcast_ref::<LimitError>(),
+            Some(&LimitError::InputExceedsDeviceLimits {
+                limit: "max_storage_buffer_binding_size",
+                needed: 2 << 10,
+                granted: 1 << 10,
+                suggestion: "use smaller batches",
+            })
+        );
+    }
+
+    #[test]
+    fn zero_sized_uninitialized_buffers_and_duplicates_are_rejected() {
+        let limits = wgpu::Limits::default();
+        let mut plan = BufferPlan::new();
+        plan.storage_u32s("empty_upload", &[]);
+        assert!(plan.validate(&limits).is_ok());
+
+        plan.storage::<u32>("empty", 0)
+            .storage::<u32>("empty_upload", 4);
+        let err = plan.validate(&limits).unwrap_err().to_string();
+        assert!(err.contains("empty: zero-sized"), "{err}");
+        assert!(err.contains("empty_upload: declared twice"), "{err}");
+    }
+
+    #[test]
+    fn totals_and_uniforms_use_encoded_sizes() {
+        let mut plan = BufferPlan::new();
+        plan.storage::<u32>("a", 10)
+            .storage_bytes("b", 7, 7)
+            .storage_u32s("c", &[1, 2, 3])
+            .uniform("u", &1u32);
+        let u = plan.entry("u").unwrap();
+        assert_eq!((u.kind, u.byte_size), (BufferKind::Uniform, 4));
+        assert_eq!(
+            plan.entry("c").unwrap().init(),
+            Some(&[1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0][..])
+        );
+        assert_eq!(plan.total_bytes(), 40 + 7 + 12 + 4);
+    }
+}
@@ -0,0 +1,241 @@
+//! Cooperative cancellation of long-running GPU calls.
+//!
+//! Work already on the queue cannot be stopped, so a cancelled call only stops
+//! waiting for it: it skips the remaining submissions and readbacks and
+//! returns [`Cancelled`]. Waits are sliced into [`CANCEL_POLL_SLICE`]-long
+//! device polls so cancellation is noticed promptly.
+//!
+//! [`Cancelled`]: crate::gpu::cancel::Cancelled
+//! [`CANCEL_POLL_SLICE`]: crate::gpu::cancel::CANCEL_POLL_SLICE
+
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        Arc,
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Result, anyhow};
+
+use crate::gpu::poll::{GpuError, PollOutcome, wait_with_timeout};
+
+/// Longest single device wait a cancellable call makes between checks.
+pub const CANCEL_POLL_SLICE: Duration = Duration::from_millis(2);
+
+#[derive(Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+/// Cheap, cloneable handle that asks a lex or parse call to stop.
+///
+/// Every clone observes the same flag; [`CancelToken::cancel`] also wakes
+/// tasks awaiting [`CancelToken::cancelled`].
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    state: Arc<CancelState>,
+}
+
+impl fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancelToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+impl CancelToken {
+    /// Creates a token that has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation and wakes every task awaiting it.
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Release);
+        let wakers = std::mem::take(
+            &mut *self
+                .state
+                .wakers
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+        );
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Returns whether cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Resolves once cancellation has been requested.
+    pub fn cancelled(&self) -> Cancellation {
+        Cancellation {
+            token: self.clone(),
+        }
+    }
+
+    /// Fails with [`Cancelled`] at `stage` once cancellation was requested.
+    pub(crate) fn check(&self, stage: &str, submitted: bool) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            return Err(Cancelled {
+                stage: stage.to_string(),
+                submitted,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Future returned by [`CancelToken::cancelled`].
+pub struct Cancellation {
+    token: CancelToken,
+}
+
+impl Future for Cancellation {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+        let mut wakers = self
+            .token
+            .state
+            .wakers
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        // `cancel` sets the flag before taking the wakers, so re-checking
+        // under the lock cannot miss a wake-up.
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+        if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+/// A call stopped by its [`CancelToken`], surfaced through `anyhow`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cancelled {
+    /// Stage that noticed the cancellation.
+    pub stage: String,
+    /// Whether submitted GPU work may still be running; it runs to
+    /// completion, and its buffers are not reused until it does.
+    pub submitted: bool,
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.submitted {
+            write!(f, "cancelled at {} with GPU work in flight", self.stage)
+        } else {
+            write!(f, "cancelled at {} with no GPU work in flight", self.stage)
+        }
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Waits for submitted work like
+/// [`wait_for_submitted_work`](crate::gpu::poll::wait_for_submitted_work),
+/// checking `cancel` between [`CANCEL_POLL_SLICE`]-long polls.
+pub(crate) fn wait_for_submitted_work_cancellable(
+    device: &wgpu::Device,
+    label: &str,
+    cancel: Option<&CancelToken>,
+) -> Result<()> {
+    let Some(cancel) = cancel else {
+        return crate::gpu::poll::wait_for_submitted_work(device, label);
+    };
+    let timeout = crate::gpu::device::device_options().wait_timeout;
+    let started = Instant::now();
+    loop {
+        cancel.check(label, true)?;
+        let elapsed = started.elapsed();
+        let slice = match timeout {
+            Some(timeout) if elapsed >= timeout => {
+                return Err(GpuError::timeout(label, elapsed, None).into());
+            }
+            Some(timeout) => (timeout - elapsed).min(CANCEL_POLL_SLICE),
+            None => CANCEL_POLL_SLICE,
+        };
+        match wait_with_timeout(device, Some(slice)) {
+            PollOutcome::Complete => return Ok(()),
+            PollOutcome::TimedOut { .. } => {}
+            PollOutcome::Failed(err) => return Err(anyhow!("{label} GPU wait failed: {err}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::task::Wake;
+
+    use super::*;
+
+    struct CountWakes(std::sync::atomic::AtomicUsize);
+
+    impl Wake for CountWakes {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn clones_share_the_flag_and_wake_waiters() {
+        let token = CancelToken::new();
+        let clone = token.clone();
+        assert!(clone.check("lex.count", false).is_ok());
+
+        let wakes = Arc::new(CountWakes(Default::default()));
+        let waker = Waker::from(wakes.clone());
+        let mut cx = Context::from_waker(&waker);
+        let mut cancelled = std::pin::pin!(clone.cancelled());
+        assert!(cancelled.as_mut().poll(&mut cx).is_pending());
+        // Re-polling with the same waker does not register it twice.
+        assert!(cancelled.as_mut().poll(&mut cx).is_pending());
+
+        token.cancel();
+        assert_eq!(wakes.0.load(Ordering::SeqCst), 1);
+        assert!(cancelled.as_mut().poll(&mut cx).is_ready());
+        assert_eq!(
+            clone.check("lex.count", true),
+            Err(Cancelled {
+                stage: "lex.count".into(),
+                submitted: true,
+            })
+        );
+    }
+
+    #[test]
+    fn cancelled_reports_whether_work_is_in_flight() {
+        let before = Cancelled {
+            stage: "lex.submit".into(),
+            submitted: false,
+        };
+        assert_eq!(
+            before.to_string(),
+            "cancelled at lex.submit with no GPU work in flight"
+        );
+        let after = Cancelled {
+            stage: "lex.tokens".into(),
+            submitted: true,
+        };
+        assert_eq!(
+            after.to_string(),
+            "cancelled at lex.tokens with GPU work in flight"
+        );
+    }
+}
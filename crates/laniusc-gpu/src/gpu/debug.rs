@@ -0,0 +1,403 @@
+//! GPU debugging utilities
+
+#![allow(dead_code)]
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use anyhow::Result;
+use wgpu;
+
+use crate::gpu::buffers::LaniusBuffer;
+
+static STAGING_BUFFERS_CREATED: AtomicU64 = AtomicU64::new(0);
+
+/// Number of debug staging buffers this process has created.
+pub fn debug_staging_buffer_count() -> u64 {
+    STAGING_BUFFERS_CREATED.load(Ordering::Relaxed)
+}
+
+/// Host-side view of one captured range of a debug staging arena.
+#[derive(Clone, Default)]
+pub struct DebugBuffer {
+    /// Label for the buffer
+    pub label: &'static str,
+    /// The arena chunk holding the capture
+    pub buffer: Option<wgpu::Buffer>,
+    /// Byte offset of the capture in `buffer`
+    pub offset: u64,
+    /// Size of the buffer in bytes
+    pub byte_len: usize,
+}
+
+impl DebugBuffer {
+    /// Returns true if the buffer is present
+    pub fn is_some(&self) -> bool {
+        self.buffer.is_some()
+    }
+
+    /// Reads the captured bytes; the arena must be mapped, see
+    /// [`DebugStagingArena::map_all`].
+    pub fn read_bytes(&self) -> Option<Vec<u8>> {
+        let buf = self.buffer.as_ref()?;
+        let view = buf
+            .slice(self.offset..self.offset + self.byte_len as u64)
+            .get_mapped_range();
+        Some(view.to_vec())
+    }
+
+    /// Reads the buffer contents as a vector of u32 values
+    pub fn read_u32s(&self) -> Option<Vec<u32>> {
+        self.read_bytes()
+            .map(|bytes| crate::gpu::decode::decode_u32s(&bytes))
+    }
+}
+
+/// Debug captures of one lexer or parser run, keyed by namespaced names such
+/// as `lexer.tokens_out` or `parser.out_sc`.
+///
+/// Passes declare the keys they write through
+/// [`Pass::expected_debug_keys`](crate::gpu::passes_core::Pass::expected_debug_keys);
+/// [`DebugOutput::audit`] then reports declared keys that were never captured
+/// and captured keys no recorded pass declared.
+#[derive(Default)]
+pub struct DebugOutput {
+    /// Staging arena every capture of this run is copied into.
+    pub arena: DebugStagingArena,
+    captures: HashMap<&'static str, DebugBuffer>,
+    rounds: HashMap<&'static str, Vec<DebugBuffer>>,
+    // Declared key -> first pass that declared it.
+    expected: BTreeMap<&'static str, &'static str>,
+}
+
+impl DebugOutput {
+    /// Records a copy of the first `bytes` bytes of `src` under `key`,
+    /// replacing an earlier capture of the same key.
+    pub fn capture<T>(
+        &mut self,
+        key: &'static str,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        src: &LaniusBuffer<T>,
+        bytes: usize,
+    ) {
+        let buffer = self.arena.capture(device, encoder, &src.buffer, key, bytes);
+        self.captures.insert(key, buffer);
+    }
+
+    /// Records one per-round snapshot under `key`; round 0 starts a new list.
+    ///
+    /// How many rounds a scan runs depends on the input size, so round lists
+    /// are not part of the [`DebugOutput::audit`].
+    pub fn capture_round<T>(
+        &mut self,
+        key: &'static str,
+        round: u32,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        src: &LaniusBuffer<T>,
+        bytes: usize,
+    ) {
+        let buffer = self.arena.capture(device, encoder, &src.buffer, key, bytes);
+        let rounds = self.rounds.entry(key).or_default();
+        if round == 0 {
+            rounds.clear();
+        }
+        rounds.push(buffer);
+    }
+
+    /// Declares that `pass` writes `keys` when it records debug captures.
+    pub fn expect(&mut self, pass: &'static str, keys: &'static [&'static str]) {
+        for &key in keys {
+            self.expected.entry(key).or_insert(pass);
+        }
+    }
+
+    /// Capture stored under `key`.
+    pub fn get(&self, key: &str) -> Option<&DebugBuffer> {
+        self.captures.get(key)
+    }
+
+    /// Capture under `key` read as `u32` words; the arena must be mapped.
+    pub fn get_u32s(&self, key: &str) -> Option<Vec<u32>> {
+        self.get(key)?.read_u32s()
+    }
+
+    /// Capture under `key` decoded with its shader layout, such as a uniform
+    /// struct or a `Vec` of records; the arena must be mapped.
+    pub fn get_struct<T>(&self, key: &str) -> Option<T>
+    where
+        T: encase::ShaderType + encase::internal::CreateFrom,
+    {
+        let bytes = self.get(key)?.read_bytes()?;
+        encase::StorageBuffer::new(bytes).create().ok()
+    }
+
+    /// Per-round snapshots stored under `key`, in round order.
+    pub fn rounds(&self, key: &str) -> &[DebugBuffer] {
+        self.rounds.get(key).map_or(&[], Vec::as_slice)
+    }
+
+    /// Captured keys, sorted.
+    pub fn keys(&self) -> Vec<&'static str> {
+        let mut keys: Vec<_> = self.captures.keys().copied().collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    /// Compares the captured keys with the keys recorded passes declared.
+    pub fn audit(&self) -> DebugAudit {
+        DebugAudit {
+            missing: self
+                .expected
+                .iter()
+                .filter(|(key, _)| !self.captures.contains_key(*key))
+                .map(|(&key, &pass)| (pass, key))
+                .collect(),
+            unexpected: self
+                .keys()
+                .into_iter()
+                .filter(|key| !self.expected.contains_key(key))
+                .collect(),
+        }
+    }
+}
+
+/// Debug sink a [`Pass`](crate::gpu::passes_core::Pass) records into, told
+/// which keys each pass declares before its captures are recorded.
+pub trait DebugSink {
+    /// Declares the keys `pass` writes; see [`DebugOutput::expect`].
+    fn declare_keys(&mut self, pass: &'static str, keys: &'static [&'static str]);
+}
+
+impl DebugSink for DebugOutput {
+    fn declare_keys(&mut self, pass: &'static str, keys: &'static [&'static str]) {
+        self.expect(pass, keys);
+    }
+}
+
+/// Result of [`DebugOutput::audit`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DebugAudit {
+    /// `(pass, key)` pairs declared but never captured.
+    pub missing: Vec<(&'static str, &'static str)>,
+    /// Captured keys that no recorded pass declared.
+    pub unexpected: Vec<&'static str>,
+}
+
+impl DebugAudit {
+    /// Whether every declared key was captured and nothing else was.
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty()
+    }
+}
+
+impl fmt::Display for DebugAudit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_clean() {
+            return write!(f, "debug captures match the declared keys");
+        }
+        for (pass, key) in &self.missing {
+            writeln!(f, "missing: {key} (declared by {pass})")?;
+        }
+        for key in &self.unexpected {
+            writeln!(f, "unexpected: {key}")?;
+        }
+        Ok(())
+    }
+}
+
+/// One capture recorded into a [`DebugStagingArena`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DebugRange {
+    pub label: &'static str,
+    /// Index of the arena chunk holding the capture.
+    pub chunk: usize,
+    pub offset: u64,
+    pub len: u64,
+}
+
+/// Bump allocator over a list of chunk sizes, kept apart from wgpu so the
+/// placement rules are testable without a device.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct ArenaLayout {
+    chunk_sizes: Vec<u64>,
+    /// Bytes used in the last chunk.
+    used: u64,
+}
+
+impl ArenaLayout {
+    /// Places `len` bytes and returns `(chunk, offset)`, plus the size of a new
+    /// chunk the caller must create when the last one has no room.
+    ///
+    /// A new chunk is at least `min_chunk` and at least as large as every
+    /// earlier chunk together, so the chunk count grows logarithmically with
+    /// the captured bytes; it never exceeds `max_chunk` unless `len` does.
+    fn place(&mut self, len: u64, min_chunk: u64, max_chunk: u64) -> (usize, u64, Option<u64>) {
+        let offset = self.used.next_multiple_of(DEBUG_ARENA_ALIGNMENT);
+        if let Some(&last) = self.chunk_sizes.last()
+            && offset + len <= last
+        {
+            self.used = offset + len;
+            return (self.chunk_sizes.len() - 1, offset, None);
+        }
+        let total: u64 = self.chunk_sizes.iter().sum();
+        let size = min_chunk.max(total).min(max_chunk).max(len);
+        self.chunk_sizes.push(size);
+        self.used = len;
+        (self.chunk_sizes.len() - 1, 0, Some(size))
+    }
+}
+
+/// Alignment of every capture, valid for both buffer copies and mapped ranges.
+const DEBUG_ARENA_ALIGNMENT: u64 = if wgpu::MAP_ALIGNMENT > wgpu::COPY_BUFFER_ALIGNMENT {
+    wgpu::MAP_ALIGNMENT
+} else {
+    wgpu::COPY_BUFFER_ALIGNMENT
+};
+
+/// Smallest arena chunk, so small runs capture into a single buffer.
+const DEBUG_ARENA_MIN_CHUNK_BYTES: u64 = 64 << 20;
+
+/// Growable MAP_READ staging arena that every debug capture of one run copies
+/// into.
+///
+/// Captures are bump-allocated sub-ranges of a few large chunks instead of one
+/// staging buffer each. After submission, [`DebugStagingArena::map_all`] maps
+/// every chunk with a single wait, and each [`DebugBuffer`] reads its range.
+/// Chunks are tracked under the `dbg.staging_arena` label.
+#[derive(Default)]
+pub struct DebugStagingArena {
+    layout: ArenaLayout,
+    chunks: Vec<LaniusBuffer<u8>>,
+    records: Vec<DebugRange>,
+}
+
+impl DebugStagingArena {
+    /// Records a copy of the first `size` bytes of `src` into the arena.
+    pub fn capture(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        src: &wgpu::Buffer,
+        label: &'static str,
+        size: usize,
+    ) -> DebugBuffer {
+        let len = size as u64;
+        let (chunk, offset, new_chunk) = self.layout.place(
+            len,
+            DEBUG_ARENA_MIN_CHUNK_BYTES,
+            device.limits().max_buffer_size,
+        );
+        if let Some(chunk_size) = new_chunk {
+            let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("dbg.staging_arena"),
+                size: chunk_size,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            });
+            STAGING_BUFFERS_CREATED.fetch_add(1, Ordering::Relaxed);
+            self.chunks.push(LaniusBuffer::new_labeled(
+                (buffer, chunk_size),
+                chunk_size as usize,
+                "dbg.staging_arena",
+            ));
+        }
+        let buffer = self.chunks[chunk].buffer.clone();
+        encoder.copy_buffer_to_buffer(src, 0, &buffer, offset, len);
+        self.records.push(DebugRange {
+            label,
+            chunk,
+            offset,
+            len,
+        });
+        DebugBuffer {
+            label,
+            buffer: Some(buffer),
+            offset,
+            byte_len: size,
+        }
+    }
+
+    /// Captures recorded so far, in recording order.
+    pub fn records(&self) -> &[DebugRange] {
+        &self.records
+    }
+
+    /// Bytes allocated across every chunk.
+    pub fn allocated_bytes(&self) -> u64 {
+        self.layout.chunk_sizes.iter().sum()
+    }
+
+    /// Maps every chunk for reading, waiting once for all of them. Call after
+    /// the work that recorded the captures has been submitted.
+    pub fn map_all(&self, device: &wgpu::Device) -> Result<()> {
+        let pending = self
+            .chunks
+            .iter()
+            .map(|chunk| {
+                crate::gpu::passes_core::begin_readback_map(&chunk.slice(..), "dbg.staging_arena")
+            })
+            .collect::<Vec<_>>();
+        for pending in pending {
+            crate::gpu::passes_core::finish_readback_map_blocking(device, pending)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn audit_reports_declared_but_missing_and_undeclared_captures() {
+        let mut dbg = DebugOutput::default();
+        dbg.expect("lex_a", &["lexer.a", "lexer.b"]);
+        dbg.expect("lex_b", &["lexer.b"]);
+        assert_eq!(
+            dbg.audit().missing,
+            [("lex_a", "lexer.a"), ("lex_a", "lexer.b")]
+        );
+
+        dbg.captures.insert("lexer.a", DebugBuffer::default());
+        dbg.captures.insert("lexer.b", DebugBuffer::default());
+        assert!(dbg.audit().is_clean(), "{}", dbg.audit());
+
+        dbg.captures.insert("lexer.c", DebugBuffer::default());
+        let audit = dbg.audit();
+        assert_eq!(audit.unexpected, ["lexer.c"]);
+        assert_eq!(audit.to_string(), "unexpected: lexer.c\n");
+        assert!(dbg.rounds("lexer.scan").is_empty());
+        assert_eq!(dbg.keys(), ["lexer.a", "lexer.b", "lexer.c"]);
+    }
+
+    #[test]
+    fn arena_layout_bump_allocates_aligned_ranges_and_grows_geometrically() {
+        let mut layout = ArenaLayout::default();
+        assert_eq!(layout.place(12, 64, 1 << 20), (0, 0, Some(64)));
+        assert_eq!(layout.place(20, 64, 1 << 20), (0, 16, None));
+        assert_eq!(layout.place(24, 64, 1 << 20), (0, 40, None));
+        // Full: the next chunk is as large as everything before it.
+        assert_eq!(layout.place(8, 64, 1 << 20), (1, 0, Some(64)));
+        assert_eq!(layout.place(100, 64, 1 << 20), (2, 0, Some(128)));
+
+        let mut layout = ArenaLayout::default();
+        let chunks = (0..1000)
+            .filter(|_| layout.place(1 << 10, 1 << 10, u64::MAX).2.is_some())
+            .count();
+        assert_eq!(chunks, 11);
+    }
+
+    #[test]
+    fn arena_layout_caps_chunks_at_the_device_limit() {
+        let mut layout = ArenaLayout::default();
+        assert_eq!(layout.place(10, 64, 32), (0, 0, Some(32)));
+        assert_eq!(layout.place(30, 64, 32), (1, 0, Some(32)));
+        // An oversized capture still gets a chunk that fits it.
+        assert_eq!(layout.place(40, 64, 32), (2, 0, Some(40)));
+    }
+}
@@ -0,0 +1,203 @@
+//! Little-endian decoding of mapped readback bytes.
+//!
+//! GPU buffers hold little-endian words laid out by `encase`, so readback
+//! paths decode them field by field here rather than reinterpreting the
+//! mapped bytes in host order or host struct layout. Each
+//! [`GpuDecodable`] impl spells out its field offsets; the tests check them
+//! against what `encase` writes for the same value.
+//!
+//! [`GpuDecodable`]: crate::gpu::decode::GpuDecodable
+
+use anyhow::{Result, anyhow};
+
+// Every decoder below, and the token and table encoders the lexer and parser
+// share with the shaders, assume the GPU and the host agree on byte order.
+#[cfg(target_endian = "big")]
+compile_error!(
+    "laniusc decodes GPU readbacks as little-endian and does not support big-endian hosts"
+);
+
+/// A value read back from a GPU buffer, decoded from its little-endian
+/// bytes with explicit field offsets.
+pub trait GpuDecodable: Sized {
+    /// Bytes of one element, its stride in an array.
+    const SIZE: usize;
+
+    /// Decodes one element from the first [`Self::SIZE`] bytes of `bytes`.
+    ///
+    /// Panics when `bytes` is shorter; [`decode_structs`] checks lengths.
+    fn decode(bytes: &[u8]) -> Self;
+}
+
+/// Returns the little-endian `u32` at word `index` of `bytes`.
+pub fn le_u32_at(bytes: &[u8], index: usize) -> u32 {
+    let start = index * 4;
+    u32::from_le_bytes(bytes[start..start + 4].try_into().expect("four bytes"))
+}
+
+impl GpuDecodable for u32 {
+    const SIZE: usize = 4;
+
+    fn decode(bytes: &[u8]) -> Self {
+        le_u32_at(bytes, 0)
+    }
+}
+
+impl GpuDecodable for i32 {
+    const SIZE: usize = 4;
+
+    fn decode(bytes: &[u8]) -> Self {
+        le_u32_at(bytes, 0) as i32
+    }
+}
+
+impl GpuDecodable for u16 {
+    const SIZE: usize = 2;
+
+    fn decode(bytes: &[u8]) -> Self {
+        u16::from_le_bytes([bytes[0], bytes[1]])
+    }
+}
+
+impl GpuDecodable for u64 {
+    const SIZE: usize = 8;
+
+    fn decode(bytes: &[u8]) -> Self {
+        u64::from_le_bytes(bytes[..8].try_into().expect("eight bytes"))
+    }
+}
+
+impl<T: GpuDecodable, const N: usize> GpuDecodable for [T; N] {
+    const SIZE: usize = T::SIZE * N;
+
+    fn decode(bytes: &[u8]) -> Self {
+        std::array::from_fn(|i| T::decode(&bytes[i * T::SIZE..]))
+    }
+}
+
+/// Decodes every whole little-endian `u32` word of `bytes`; a trailing
+/// partial word is ignored.
+pub fn decode_u32s(bytes: &[u8]) -> Vec<u32> {
+    decode_all(bytes)
+}
+
+/// Decodes every whole element of `bytes`; a trailing partial element is
+/// ignored.
+pub fn decode_all<T: GpuDecodable>(bytes: &[u8]) -> Vec<T> {
+    bytes.chunks_exact(T::SIZE).map(T::decode).collect()
+}
+
+/// Decodes the first `count` elements of `bytes`, failing with `context`
+/// in the message when the readback holds fewer.
+pub fn decode_structs<T: GpuDecodable>(
+    bytes: &[u8],
+    count: usize,
+    context: &str,
+) -> Result<Vec<T>> {
+    let expected = count
+        .checked_mul(T::SIZE)
+        .ok_or_else(|| anyhow!("{context} readback of {count} elements overflows usize"))?;
+    if bytes.len() < expected {
+        return Err(anyhow!(
+            "{context} readback was truncated: expected at least {expected} bytes, got {}",
+            bytes.len()
+        ));
+    }
+    Ok(decode_all(&bytes[..expected]))
+}
+
+/// Decodes one element from the front of `bytes`, failing with `context`
+/// in the message when the readback is shorter.
+pub fn decode_one<T: GpuDecodable>(bytes: &[u8], context: &str) -> Result<T> {
+    let mut decoded = decode_structs(bytes, 1, context)?;
+    Ok(decoded.remove(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use encase::{ShaderSize, ShaderType, StorageBuffer, internal::WriteInto};
+
+    use super::*;
+    use crate::{lexer::GpuToken, parser::buffers::ActionHeader};
+
+    /// Writes `value` the way `encase` lays it out in a storage buffer.
+    fn encase_bytes<T: ShaderType + ShaderSize + WriteInto>(value: &T) -> Vec<u8> {
+        let mut buffer = StorageBuffer::new(Vec::new());
+        buffer.write(value).expect("encase write");
+        buffer.into_inner()
+    }
+
+    #[test]
+    fn action_header_matches_the_shader_layout() {
+        assert_eq!(ActionHeader::SIZE as u64, ActionHeader::SHADER_SIZE.get());
+        assert_eq!(ActionHeader::SIZE, std::mem::size_of::<ActionHeader>());
+        let headers = [
+            ActionHeader {
+                push_len: 1,
+                emit_len: 2,
+                pop_tag: 0xdead_beef,
+                pop_count: 4,
+            },
+            ActionHeader {
+                push_len: u32::MAX,
+                emit_len: 0,
+                pop_tag: 7,
+                pop_count: 1 << 31,
+            },
+        ];
+        let bytes = encase_bytes(&headers);
+        let decoded: Vec<ActionHeader> = decode_structs(&bytes, 2, "headers").unwrap();
+        for (got, want) in decoded.iter().zip(&headers) {
+            assert_eq!(
+                (got.push_len, got.emit_len, got.pop_tag, got.pop_count),
+                (want.push_len, want.emit_len, want.pop_tag, want.pop_count)
+            );
+        }
+    }
+
+    #[test]
+    fn gpu_token_matches_the_shader_layout() {
+        assert_eq!(GpuToken::SIZE as u64, GpuToken::SHADER_SIZE.get());
+        assert_eq!(GpuToken::SIZE, std::mem::size_of::<GpuToken>());
+        let tokens = [
+            GpuToken {
+                kind: 3,
+                start: 0x0102_0304,
+                len: 9,
+            },
+            GpuToken {
+                kind: 88,
+                start: 17,
+                len: u32::MAX,
+            },
+        ];
+        let bytes = encase_bytes(&tokens);
+        let decoded: Vec<GpuToken> = decode_structs(&bytes, 2, "tokens").unwrap();
+        for (got, want) in decoded.iter().zip(&tokens) {
+            assert_eq!(
+                (got.kind, got.start, got.len),
+                (want.kind, want.start, want.len)
+            );
+        }
+    }
+
+    #[test]
+    fn signed_depths_round_trip() {
+        let depths = [-3i32, i32::MIN];
+        let bytes = encase_bytes(&depths);
+        assert_eq!(decode_one::<[i32; 2]>(&bytes, "depths").unwrap(), depths);
+    }
+
+    #[test]
+    fn truncated_readbacks_fail_and_partial_words_are_ignored() {
+        let bytes = [1, 0, 0, 0, 2, 0, 0];
+        assert_eq!(decode_u32s(&bytes), [1]);
+        let err = decode_structs::<u32>(&bytes, 2, "status").unwrap_err();
+        assert!(
+            err.to_string().contains("status readback was truncated"),
+            "{err}"
+        );
+        assert_eq!(decode_all::<u16>(&bytes), [1, 0, 2]);
+        assert_eq!(u64::decode(&[1, 0, 0, 0, 0, 0, 0, 1]), 1 | (1 << 56));
+    }
+}
@@ -15,6 +15,8 @@ use std::{
 
 use log::warn;
 
+use crate::language::LANGUAGE_REVISION;
+
 const PIPELINE_CACHE_FILE_MAGIC: [u8; 8] = *b"LANIUSPC";
 const PIPELINE_CACHE_FILE_VERSION: u32 = 1;
 const PIPELINE_CACHE_HEADER_LEN: usize = 8 + 4 + 4 + 8 + 8 + 8;
@@ -29,6 +31,15 @@ pub struct GpuDevice {
     pub queue: Arc<wgpu::Queue>,
     /// Whether timestamp queries were requested successfully.
     pub timers_supported: bool,
+    /// How shader modules are created on this device.
+    pub shader_path: ShaderPath,
+    /// Limits the adapter reported before negotiation.
+    pub adapter_limits: wgpu::Limits,
+    /// Limits the device was granted: the compiler's wishes capped by
+    /// [`adapter_limits`](Self::adapter_limits) and
+    /// [`DeviceOptions::max_storage_binding_bytes`]. Buffer plans validate
+    /// against these.
+    pub granted_limits: wgpu::Limits,
     /// Pipeline cache associated with this device, when supported.
     pipeline_cache: Mutex<Option<Arc<wgpu::PipelineCache>>>,
     pipeline_cache_path: Option<PathBuf>,
@@ -84,6 +95,119 @@ pub struct WgpuResourceStats {
     pub query_sets: WgpuRegistryStats,
 }
 
+/// How Slang's SPIR-V reaches the driver.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShaderPath {
+    /// Handed to the driver unchanged through `PASSTHROUGH_SHADERS`.
+    Passthrough,
+    /// Translated and validated by Naga, for adapters without passthrough.
+    Validated,
+}
+
+impl ShaderPath {
+    /// Returns the path pipelines created on `device` take.
+    pub fn for_device(device: &wgpu::Device) -> Self {
+        if device
+            .features()
+            .contains(wgpu::Features::PASSTHROUGH_SHADERS)
+        {
+            Self::Passthrough
+        } else {
+            Self::Validated
+        }
+    }
+
+    /// Short name used in logs and timing output.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Passthrough => "passthrough",
+            Self::Validated => "validated",
+        }
+    }
+}
+
+impl std::fmt::Display for ShaderPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Default limit for one blocking GPU wait.
+pub const DEFAULT_WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Process-wide settings for how compiler code creates and waits on the device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeviceOptions {
+    /// Longest one blocking wait may take before it fails with
+    /// [`GpuError::Timeout`](crate::gpu::poll::GpuError::Timeout); `None`
+    /// waits forever, which is useful under a debugger.
+    pub wait_timeout: Option<Duration>,
+    /// Whether to request SPIR-V passthrough when the adapter offers it.
+    /// Read once, when a device is created; `false` forces
+    /// [`ShaderPath::Validated`].
+    pub spirv_passthrough: bool,
+    /// Caps the storage binding size requested from the adapter, so split
+    /// bindings and limit errors can be exercised on a capable adapter. Read
+    /// once, when a device is created.
+    pub max_storage_binding_bytes: Option<u64>,
+}
+
+impl Default for DeviceOptions {
+    fn default() -> Self {
+        Self {
+            wait_timeout: Some(DEFAULT_WAIT_TIMEOUT),
+            spirv_passthrough: true,
+            max_storage_binding_bytes: None,
+        }
+    }
+}
+
+impl DeviceOptions {
+    /// Defaults with `LANIUS_GPU_WAIT_TIMEOUT_MS` applied, where `0` disables
+    /// the timeout, `LANIUS_SPIRV_PASSTHROUGH=0` forcing validated shaders,
+    /// and `LANIUS_MAX_STORAGE_BINDING_BYTES` capping the storage binding size.
+    pub fn from_env() -> Self {
+        let binding_cap = crate::gpu::env::env_u64("LANIUS_MAX_STORAGE_BINDING_BYTES", u64::MAX);
+        Self {
+            wait_timeout: wait_timeout_from_env("LANIUS_GPU_WAIT_TIMEOUT_MS")
+                .unwrap_or(Some(DEFAULT_WAIT_TIMEOUT)),
+            spirv_passthrough: crate::gpu::env::env_bool_truthy("LANIUS_SPIRV_PASSTHROUGH", true),
+            max_storage_binding_bytes: (binding_cap != u64::MAX).then_some(binding_cap),
+        }
+    }
+}
+
+/// Reads a millisecond wait timeout where `0` means "no timeout"; `None` when
+/// the variable is unset or invalid.
+pub(crate) fn wait_timeout_from_env(name: &str) -> Option<Option<Duration>> {
+    let value = std::env::var(name).ok()?;
+    match value.trim().parse::<u64>() {
+        Ok(0) => Some(None),
+        Ok(ms) => Some(Some(Duration::from_millis(ms))),
+        Err(err) => {
+            warn!("{name} could not be parsed as milliseconds ({err}); ignoring it");
+            None
+        }
+    }
+}
+
+static DEVICE_OPTIONS: Mutex<Option<DeviceOptions>> = Mutex::new(None);
+
+/// Replaces the process-wide device options.
+pub fn set_device_options(options: DeviceOptions) {
+    *DEVICE_OPTIONS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(options);
+}
+
+/// Returns the process-wide device options, reading the environment on first use.
+pub fn device_options() -> DeviceOptions {
+    *DEVICE_OPTIONS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .get_or_insert_with(DeviceOptions::from_env)
+}
+
 impl GpuDevice {
     /// Creates a GPU device/queue resource that can be shared across compiler subsystems.
     pub fn new() -> Self {
@@ -312,7 +436,7 @@ impl PipelineCachePersistTimer {
         let name = format!("pipeline_cache.{prefix}.{stage}");
         if self.print_enabled {
             let dt_ms = end.duration_since(start).as_secs_f64() * 1000.0;
-            eprintln!("[gpu_compile_host_timer] {name}: {dt_ms:.3}ms");
+            log::info!(target: crate::logging::GPU, "[gpu_compile_host_timer] {name}: {dt_ms:.3}ms");
         }
         if self.trace_enabled {
             crate::gpu::trace::record_host_span("host.pipeline_cache", &name, start, end);
@@ -325,7 +449,7 @@ impl PipelineCachePersistTimer {
 
     fn bytes_prefixed(&self, lane_suffix: &str, name: &str, at: Instant, bytes: usize) {
         if self.print_enabled {
-            eprintln!("[gpu_compile_host_timer] {name}: {bytes} bytes");
+            log::info!(target: crate::logging::GPU, "[gpu_compile_host_timer] {name}: {bytes} bytes");
         }
         if self.trace_enabled {
             crate::gpu::trace::record_counter(
@@ -376,12 +500,31 @@ fn create_context() -> Result<GpuDevice, GpuDeviceInitializationError> {
     )?;
 
     let adapter_limits = adapter.limits();
-    let limits = compiler_device_limits(&adapter_limits);
+    let limits =
+        compiler_device_limits(&adapter_limits, device_options().max_storage_binding_bytes);
+    if limits.max_storage_buffer_binding_size < MAX_COMPILER_BUFFER_BYTES {
+        log::info!(
+            target: crate::logging::GPU,
+            "storage bindings limited to {} bytes (adapter supports {}); larger inputs split \
+             their token buffer or need the chunked APIs",
+            limits.max_storage_buffer_binding_size,
+            adapter_limits.max_storage_buffer_binding_size
+        );
+    }
 
     let adapter_features = adapter.features();
 
-    // Enable SPIR-V passthrough always; add timestamp features if supported so timing can be toggled at runtime.
-    let mut required_features = wgpu::Features::empty() | wgpu::Features::PASSTHROUGH_SHADERS;
+    // Prefer SPIR-V passthrough; add timestamp features if supported so timing can be toggled at runtime.
+    let mut required_features = wgpu::Features::empty();
+    let shader_path = select_shader_path(
+        adapter_features,
+        device_options().spirv_passthrough,
+        &adapter_info.name,
+        adapter_info.backend,
+    );
+    if shader_path == ShaderPath::Passthrough {
+        required_features |= wgpu::Features::PASSTHROUGH_SHADERS;
+    }
     if adapter_features.contains(wgpu::Features::TIMESTAMP_QUERY) {
         required_features |= wgpu::Features::TIMESTAMP_QUERY;
         if adapter_features.contains(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS) {
@@ -401,7 +544,8 @@ fn create_context() -> Result<GpuDevice, GpuDeviceInitializationError> {
         required_features,
         required_limits: limits,
         // SAFETY: Lanius consumes Slang-produced SPIR-V directly through wgpu's
-        // passthrough path so the compiler does not route shaders through Naga.
+        // passthrough path so the compiler does not route shaders through Naga
+        // unless the adapter lacks it.
         experimental_features: unsafe { wgpu::ExperimentalFeatures::enabled() },
         memory_hints: wgpu::MemoryHints::default(),
         trace: wgpu::Trace::default(),
@@ -409,7 +553,7 @@ fn create_context() -> Result<GpuDevice, GpuDeviceInitializationError> {
     .map_err(|err| GpuDeviceInitializationError::RequestDevice(err.to_string()))?;
 
     device.on_uncaptured_error(Arc::new(|e| {
-        eprintln!("[wgpu uncaptured] {e:?}");
+        log::error!(target: crate::logging::GPU, "[wgpu uncaptured] {e:?}");
     }));
 
     let timers_supported = adapter_features.contains(wgpu::Features::TIMESTAMP_QUERY);
@@ -424,6 +568,7 @@ fn create_context() -> Result<GpuDevice, GpuDeviceInitializationError> {
     } else {
         (None, None, None, false, None)
     };
+    let granted_limits = device.limits();
     let device = Arc::new(device);
     let pipeline_cache = pipeline_cache.map(Arc::new);
     let pipeline_cache_dirty = Arc::new(AtomicBool::new(pipeline_cache_should_persist));
@@ -434,6 +579,9 @@ fn create_context() -> Result<GpuDevice, GpuDeviceInitializationError> {
         device,
         queue: Arc::new(queue),
         timers_supported,
+        shader_path,
+        granted_limits,
+        adapter_limits,
         pipeline_cache: Mutex::new(pipeline_cache),
         pipeline_cache_path,
         pipeline_cache_identity_hash,
@@ -442,6 +590,27 @@ fn create_context() -> Result<GpuDevice, GpuDeviceInitializationError> {
     })
 }
 
+fn select_shader_path(
+    adapter_features: wgpu::Features,
+    passthrough_requested: bool,
+    name: &str,
+    backend: wgpu::Backend,
+) -> ShaderPath {
+    if !passthrough_requested {
+        log::info!(target: crate::logging::GPU,
+            "SPIR-V passthrough disabled by device options; validating shaders through Naga"
+        );
+        return ShaderPath::Validated;
+    }
+    if adapter_features.contains(wgpu::Features::PASSTHROUGH_SHADERS) {
+        return ShaderPath::Passthrough;
+    }
+    warn!(
+        "adapter {name:?} on {backend:?} lacks SPIR-V passthrough; validating shaders through Naga"
+    );
+    ShaderPath::Validated
+}
+
 fn require_hardware_adapter(
     device_type: wgpu::DeviceType,
     name: &str,
@@ -475,6 +644,21 @@ mod adapter_policy_tests {
         }
     }
 
+    #[test]
+    fn passthrough_is_used_only_when_offered_and_requested() {
+        let offered = wgpu::Features::PASSTHROUGH_SHADERS | wgpu::Features::TIMESTAMP_QUERY;
+        let select = |features, requested| {
+            select_shader_path(features, requested, "adapter", wgpu::Backend::Vulkan)
+        };
+        assert_eq!(select(offered, true), ShaderPath::Passthrough);
+        assert_eq!(select(offered, false), ShaderPath::Validated);
+        assert_eq!(
+            select(wgpu::Features::TIMESTAMP_QUERY, true),
+            ShaderPath::Validated
+        );
+        assert_eq!(ShaderPath::Validated.to_string(), "validated");
+    }
+
     #[test]
     fn cpu_software_adapter_requires_explicit_opt_in() {
         let error = require_hardware_adapter(
@@ -504,7 +688,10 @@ mod adapter_policy_tests {
     }
 }
 
-fn compiler_device_limits(adapter_limits: &wgpu::Limits) -> wgpu::Limits {
+/// Limits to request from an adapter reporting `adapter_limits`: what the
+/// compiler wants, never more than the adapter supports, and storage bindings
+/// no larger than `binding_cap`.
+fn compiler_device_limits(adapter_limits: &wgpu::Limits, binding_cap: Option<u64>) -> wgpu::Limits {
     let mut limits = wgpu::Limits::defaults();
     // Native compiler stages use wide GPU record tables; request the adapter's
     // native storage-buffer limit so reflected pass layouts fail only when the
@@ -520,7 +707,8 @@ fn compiler_device_limits(adapter_limits: &wgpu::Limits) -> wgpu::Limits {
         .min(48 * 1024);
     limits.max_storage_buffer_binding_size = adapter_limits
         .max_storage_buffer_binding_size
-        .min(MAX_COMPILER_BUFFER_BYTES);
+        .min(MAX_COMPILER_BUFFER_BYTES)
+        .min(binding_cap.unwrap_or(u64::MAX));
     limits.max_buffer_size = adapter_limits
         .max_buffer_size
         .min(MAX_COMPILER_BUFFER_BYTES);
@@ -737,7 +925,10 @@ fn pipeline_cache_filename(adapter_key: &str) -> (String, u64) {
     // the compiler build changes, while new pipeline descriptors miss and are
     // compiled normally. Key the file only by the opaque-cache compatibility
     // boundary instead of invalidating every pipeline on every shader edit.
-    let identity = format!("adapter={adapter_key};wgpu={wgpu_version};format=1");
+    // A new language revision renumbers the token ids baked into most
+    // shaders, so it starts a fresh file rather than a mostly dead one.
+    let identity =
+        format!("adapter={adapter_key};wgpu={wgpu_version};format=1;language={LANGUAGE_REVISION}");
     let identity_hash = stable_hash_u64(identity.as_bytes());
     let identity_digest = format!("{identity_hash:016x}");
     let filename = format!(
@@ -1002,7 +1193,7 @@ mod tests {
         adapter.max_storage_buffer_binding_size = 512 * 1024 * 1024;
         adapter.max_buffer_size = 768 * 1024 * 1024;
 
-        let requested = compiler_device_limits(&adapter);
+        let requested = compiler_device_limits(&adapter, None);
 
         assert_eq!(
             requested.max_storage_buffer_binding_size,
@@ -1017,7 +1208,7 @@ mod tests {
         adapter.max_storage_buffer_binding_size = u64::MAX;
         adapter.max_buffer_size = u64::MAX;
 
-        let requested = compiler_device_limits(&adapter);
+        let requested = compiler_device_limits(&adapter, None);
 
         assert_eq!(
             requested.max_storage_buffer_binding_size,
@@ -1026,6 +1217,23 @@ mod tests {
         assert_eq!(requested.max_buffer_size, MAX_COMPILER_BUFFER_BYTES);
     }
 
+    #[test]
+    fn binding_caps_lower_only_the_storage_binding_limit() {
+        let mut adapter = wgpu::Limits::defaults();
+        adapter.max_storage_buffer_binding_size = 512 * 1024 * 1024;
+        adapter.max_buffer_size = 768 * 1024 * 1024;
+
+        let capped = compiler_device_limits(&adapter, Some(1 << 20));
+        assert_eq!(capped.max_storage_buffer_binding_size, 1 << 20);
+        assert_eq!(capped.max_buffer_size, adapter.max_buffer_size);
+
+        let loose = compiler_device_limits(&adapter, Some(u64::MAX));
+        assert_eq!(
+            loose.max_storage_buffer_binding_size,
+            adapter.max_storage_buffer_binding_size
+        );
+    }
+
     #[test]
     fn pipeline_cache_file_round_trips_opaque_blob() {
         let identity_hash = 0x1234_5678_9abc_def0;
@@ -0,0 +1,413 @@
+//! Device-free dry runs of a GPU pipeline.
+//!
+//! A [`PipelinePlan`] records what one run would allocate and dispatch: the
+//! [`BufferPlan`] of its per-run buffers, the tables it shares across runs,
+//! and every direct dispatch as a [`DispatchRecord`] planned with
+//! [`plan_dispatch`]. Building one needs no adapter. Pass shapes come from
+//! the built reflection artifacts through [`ArtifactShapes`], or from
+//! [`DeclaredShapes`] before the shaders are built, so sizes, pass order, and
+//! limit checks can be asserted on machines without a GPU.
+//!
+//! [`PipelinePlan`]: crate::gpu::dry_run::PipelinePlan
+//! [`BufferPlan`]: crate::gpu::buffers::BufferPlan
+//! [`DispatchRecord`]: crate::gpu::passes_core::DispatchRecord
+//! [`plan_dispatch`]: crate::gpu::passes_core::plan_dispatch
+//! [`ArtifactShapes`]: crate::gpu::dry_run::ArtifactShapes
+//! [`DeclaredShapes`]: crate::gpu::dry_run::DeclaredShapes
+
+use std::{collections::HashMap, fmt::Write as _};
+
+use anyhow::{Result, anyhow, bail};
+
+use super::{
+    buffers::{BufferKind, BufferPlan},
+    passes_core::{
+        BindingContract,
+        DispatchConvention,
+        DispatchRecord,
+        InputElements,
+        MAX_GROUPS_PER_DIM,
+        plan_dispatch,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Reflected compute shape of one pass.
+pub struct PassShape {
+    /// Reflected compute thread-group size.
+    pub thread_group_size: [u32; 3],
+    /// Storage buffers the compute stage binds.
+    pub storage_buffers: usize,
+}
+
+/// Source of pass shapes for a dry run.
+pub trait PassShapes {
+    /// Returns the shape of the pass `contract` describes.
+    fn shape(&self, contract: &BindingContract) -> Result<PassShape>;
+}
+
+/// Shapes read from the built reflection artifacts, checked against each
+/// contract as [`BindingContract::check_artifact`] does.
+pub struct ArtifactShapes;
+
+impl PassShapes for ArtifactShapes {
+    fn shape(&self, contract: &BindingContract) -> Result<PassShape> {
+        let parsed = contract.parse_artifact()?;
+        Ok(PassShape {
+            thread_group_size: parsed
+                .thread_group_size
+                .expect("parse_artifact rejects a missing thread_group_size"),
+            storage_buffers: parsed.storage_buffer_count()?,
+        })
+    }
+}
+
+/// Shapes assumed from each contract's declared dispatch convention:
+/// per-element-group passes use their declared width, every other pass
+/// `group_width` lanes, and every pass binds `storage_buffers` buffers.
+pub struct DeclaredShapes {
+    pub group_width: u32,
+    pub storage_buffers: usize,
+}
+
+impl PassShapes for DeclaredShapes {
+    fn shape(&self, contract: &BindingContract) -> Result<PassShape> {
+        let width = match contract.dispatch {
+            DispatchConvention::PerElementGroups { group_size } => group_size,
+            DispatchConvention::PerElementThreads | DispatchConvention::SingleGroup => {
+                self.group_width
+            }
+        };
+        Ok(PassShape {
+            thread_group_size: [width, 1, 1],
+            storage_buffers: self.storage_buffers,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Device limits one planned run needs; each field mirrors the
+/// [`wgpu::Limits`] field of the same name.
+pub struct RequiredLimits {
+    pub max_buffer_size: u64,
+    pub max_storage_buffer_binding_size: u64,
+    pub max_uniform_buffer_binding_size: u64,
+    pub max_storage_buffers_per_shader_stage: u32,
+    pub max_compute_invocations_per_workgroup: u32,
+    pub max_compute_workgroup_size_x: u32,
+    pub max_compute_workgroup_size_y: u32,
+    pub max_compute_workgroups_per_dimension: u32,
+}
+
+impl RequiredLimits {
+    /// Checks every required limit against `limits`, reporting all misses.
+    pub fn check(&self, limits: &wgpu::Limits) -> Result<()> {
+        let checks = [
+            (
+                "max_buffer_size",
+                self.max_buffer_size,
+                limits.max_buffer_size,
+            ),
+            (
+                "max_storage_buffer_binding_size",
+                self.max_storage_buffer_binding_size,
+                limits.max_storage_buffer_binding_size,
+            ),
+            (
+                "max_uniform_buffer_binding_size",
+                self.max_uniform_buffer_binding_size,
+                limits.max_uniform_buffer_binding_size,
+            ),
+            (
+                "max_storage_buffers_per_shader_stage",
+                u64::from(self.max_storage_buffers_per_shader_stage),
+                u64::from(limits.max_storage_buffers_per_shader_stage),
+            ),
+            (
+                "max_compute_invocations_per_workgroup",
+                u64::from(self.max_compute_invocations_per_workgroup),
+                u64::from(limits.max_compute_invocations_per_workgroup),
+            ),
+            (
+                "max_compute_workgroup_size_x",
+                u64::from(self.max_compute_workgroup_size_x),
+                u64::from(limits.max_compute_workgroup_size_x),
+            ),
+            (
+                "max_compute_workgroup_size_y",
+                u64::from(self.max_compute_workgroup_size_y),
+                u64::from(limits.max_compute_workgroup_size_y),
+            ),
+            (
+                "max_compute_workgroups_per_dimension",
+                u64::from(self.max_compute_workgroups_per_dimension),
+                u64::from(limits.max_compute_workgroups_per_dimension),
+            ),
+        ];
+        let misses: Vec<String> = checks
+            .into_iter()
+            .filter(|&(_, required, limit)| required > limit)
+            .map(|(name, required, limit)| format!("{name} needs {required}, limit is {limit}"))
+            .collect();
+        if misses.is_empty() {
+            Ok(())
+        } else {
+            bail!("plan exceeds device limits: {}", misses.join("; "))
+        }
+    }
+}
+
+/// Buffers, shared tables, and dispatches of one planned pipeline run.
+#[derive(Debug, Clone, Default)]
+pub struct PipelinePlan {
+    /// Per-run buffers.
+    pub buffers: BufferPlan,
+    /// Tables uploaded once and shared by every run, with their byte sizes.
+    pub shared_tables: Vec<(&'static str, u64)>,
+    /// Direct dispatches in recording order.
+    pub dispatches: Vec<DispatchRecord>,
+    /// Worst-case bytes of mapped staging buffers the run reads back through.
+    pub readback_bytes: u64,
+    shapes: HashMap<&'static str, PassShape>,
+}
+
+impl PipelinePlan {
+    pub fn new(buffers: BufferPlan) -> Self {
+        Self {
+            buffers,
+            ..Self::default()
+        }
+    }
+
+    /// Records a table of `bytes` bytes shared across runs.
+    pub fn share_table(&mut self, label: &'static str, bytes: u64) -> &mut Self {
+        self.shared_tables.push((label, bytes));
+        self
+    }
+
+    /// Plans one direct dispatch of the pass `contract` describes, recorded
+    /// as `label`.
+    pub fn dispatch(
+        &mut self,
+        shapes: &dyn PassShapes,
+        contract: &BindingContract,
+        label: impl Into<String>,
+        input: InputElements,
+    ) -> Result<()> {
+        let shape = match self.shapes.get(contract.label) {
+            Some(&shape) => shape,
+            None => {
+                let shape = shapes.shape(contract)?;
+                self.shapes.insert(contract.label, shape);
+                shape
+            }
+        };
+        let workgroups = plan_dispatch(
+            contract.dispatch,
+            contract.dim,
+            input,
+            shape.thread_group_size,
+        )
+        .map_err(|err| anyhow!("plan {}: {err}", contract.label))?;
+        self.dispatches
+            .push(DispatchRecord::direct(label, workgroups, input));
+        Ok(())
+    }
+
+    /// Limits the planned buffers and dispatches need.
+    pub fn required_limits(&self) -> RequiredLimits {
+        let mut required = RequiredLimits::default();
+        for entry in self.buffers.entries() {
+            required.max_buffer_size = required.max_buffer_size.max(entry.byte_size);
+            let binding = match entry.kind {
+                BufferKind::Storage => &mut required.max_storage_buffer_binding_size,
+                BufferKind::Uniform => &mut required.max_uniform_buffer_binding_size,
+            };
+            *binding = (*binding).max(entry.min_binding_size());
+        }
+        for &(_, bytes) in &self.shared_tables {
+            required.max_buffer_size = required.max_buffer_size.max(bytes);
+            required.max_storage_buffer_binding_size =
+                required.max_storage_buffer_binding_size.max(bytes);
+        }
+        for shape in self.shapes.values() {
+            let [x, y, z] = shape.thread_group_size;
+            required.max_storage_buffers_per_shader_stage = required
+                .max_storage_buffers_per_shader_stage
+                .max(shape.storage_buffers as u32);
+            required.max_compute_invocations_per_workgroup = required
+                .max_compute_invocations_per_workgroup
+                .max(x.saturating_mul(y).saturating_mul(z));
+            required.max_compute_workgroup_size_x = required.max_compute_workgroup_size_x.max(x);
+            required.max_compute_workgroup_size_y = required.max_compute_workgroup_size_y.max(y);
+        }
+        for (gx, gy, gz) in self.dispatches.iter().filter_map(|d| d.workgroups) {
+            required.max_compute_workgroups_per_dimension = required
+                .max_compute_workgroups_per_dimension
+                .max(gx.max(gy).max(gz));
+        }
+        debug_assert!(required.max_compute_workgroups_per_dimension <= MAX_GROUPS_PER_DIM);
+        required
+    }
+
+    /// Bytes the run needs at its peak: its buffers, the shared tables, and
+    /// the readback staging.
+    pub fn estimated_vram_bytes(&self) -> u64 {
+        self.buffers.total_bytes()
+            + self.shared_tables.iter().map(|&(_, b)| b).sum::<u64>()
+            + self.readback_bytes
+    }
+
+    /// Checks the buffers and the required limits against `limits`,
+    /// reporting the failures of both.
+    pub fn check_limits(&self, limits: &wgpu::Limits) -> Result<()> {
+        match (
+            self.buffers.validate(limits),
+            self.required_limits().check(limits),
+        ) {
+            (Ok(()), Ok(())) => Ok(()),
+            (Err(err), Ok(())) | (Ok(()), Err(err)) => Err(err),
+            (Err(buffers), Err(required)) => bail!("{buffers}; {required}"),
+        }
+    }
+
+    /// Renders the plan as text: buffers, shared tables, dispatches, readback
+    /// staging, the required limits, and the VRAM estimate.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let entries = self.buffers.entries();
+        let _ = writeln!(
+            out,
+            "buffers: {} ({} bytes)",
+            entries.len(),
+            self.buffers.total_bytes()
+        );
+        for entry in entries {
+            let _ = writeln!(
+                out,
+                "  {}\t{} bytes\t{} x {}",
+                entry.label, entry.byte_size, entry.count, entry.element
+            );
+        }
+        let _ = writeln!(out, "shared tables: {}", self.shared_tables.len());
+        for (label, bytes) in &self.shared_tables {
+            let _ = writeln!(out, "  {label}\t{bytes} bytes");
+        }
+        let _ = writeln!(out, "dispatches: {}", self.dispatches.len());
+        for dispatch in &self.dispatches {
+            let (gx, gy, gz) = dispatch.workgroups.unwrap_or_default();
+            let elements = match dispatch.elements {
+                Some(InputElements::Elements1D(n)) => n.to_string(),
+                Some(InputElements::Elements2D(w, h)) => format!("{w}x{h}"),
+                None => "indirect".to_string(),
+            };
+            let _ = writeln!(
+                out,
+                "  {}\telements={elements}\tgroups={gx}x{gy}x{gz}",
+                dispatch.label
+            );
+        }
+        let _ = writeln!(out, "readback staging: {} bytes", self.readback_bytes);
+        let required = self.required_limits();
+        let _ = writeln!(out, "required limits: {required:#?}");
+        let _ = writeln!(out, "estimated VRAM: {} bytes", self.estimated_vram_bytes());
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu::passes_core::DispatchDim;
+
+    const THREADS: BindingContract = BindingContract {
+        label: "threads",
+        reflection: "threads.reflect.json",
+        expected_bindings: &[],
+        dim: DispatchDim::D1,
+        dispatch: DispatchConvention::PerElementThreads,
+    };
+
+    #[test]
+    fn dispatches_are_planned_from_declared_shapes() {
+        let shapes = DeclaredShapes {
+            group_width: 256,
+            storage_buffers: 5,
+        };
+        let groups = BindingContract {
+            label: "groups",
+            dispatch: DispatchConvention::PerElementGroups { group_size: 64 },
+            ..THREADS
+        };
+        let mut plan = PipelinePlan::new(BufferPlan::new());
+        plan.dispatch(
+            &shapes,
+            &THREADS,
+            "threads",
+            InputElements::Elements1D(1000),
+        )
+        .unwrap();
+        plan.dispatch(&shapes, &groups, "groups", InputElements::Elements1D(3))
+            .unwrap();
+        plan.dispatch(
+            &shapes,
+            &THREADS,
+            "tiled",
+            InputElements::Elements1D(256 * (MAX_GROUPS_PER_DIM + 1)),
+        )
+        .unwrap();
+
+        let groups: Vec<_> = plan.dispatches.iter().map(|d| d.workgroups).collect();
+        assert_eq!(
+            groups,
+            [
+                Some((4, 1, 1)),
+                Some((3, 1, 1)),
+                Some((MAX_GROUPS_PER_DIM, 2, 1))
+            ]
+        );
+        let required = plan.required_limits();
+        assert_eq!(required.max_compute_workgroup_size_x, 256);
+        assert_eq!(required.max_compute_invocations_per_workgroup, 256);
+        assert_eq!(required.max_storage_buffers_per_shader_stage, 5);
+        assert_eq!(
+            required.max_compute_workgroups_per_dimension,
+            MAX_GROUPS_PER_DIM
+        );
+    }
+
+    #[test]
+    fn limit_checks_report_every_miss() {
+        let mut buffers = BufferPlan::new();
+        buffers.storage::<u32>("rows", 1 << 20);
+        let mut plan = PipelinePlan::new(buffers);
+        plan.share_table("table", 64);
+        plan.readback_bytes = 12;
+        plan.dispatch(
+            &DeclaredShapes {
+                group_width: 256,
+                storage_buffers: 6,
+            },
+            &THREADS,
+            "threads",
+            InputElements::Elements1D(1),
+        )
+        .unwrap();
+        assert_eq!(plan.estimated_vram_bytes(), (4 << 20) + 64 + 12);
+        assert!(plan.check_limits(&wgpu::Limits::default()).is_ok());
+
+        let err = plan
+            .check_limits(&wgpu::Limits::downlevel_webgl2_defaults())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("max_storage_buffers_per_shader_stage needs 6"),
+            "{err}"
+        );
+        assert!(
+            err.contains("max_compute_workgroup_size_x needs 256"),
+            "{err}"
+        );
+        assert!(err.contains("rows: 4194304 bytes exceeds"), "{err}");
+    }
+}
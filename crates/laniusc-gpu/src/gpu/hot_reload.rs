@@ -0,0 +1,529 @@
+//! Dev-only shader hot reload behind the `shader-hot-reload` feature.
+//!
+//! Pass constructors load SPIR-V and reflection through [`artifact_path`],
+//! which prefers a reload override over the build-time artifact root. A
+//! reload rebuilds each shader a driver loaded into a fresh staging
+//! directory, points the overrides at the changed artifacts, and rebuilds the
+//! driver's passes. Bind group layouts come from the new reflection, so added
+//! or removed bindings are picked up. When any shader fails to rebuild or the
+//! new pipelines fail to create, the overrides are restored and the caller
+//! keeps its old passes.
+//!
+//! [`artifact_path`]: crate::gpu::hot_reload::artifact_path
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    sync::{
+        RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use anyhow::{Context, Result, anyhow};
+
+#[allow(dead_code)]
+#[path = "../../../laniusc-shaders/src/slang_compile.rs"]
+mod slang_compile;
+
+use slang_compile::SlangcFlags;
+
+const DEFAULT_COMPILE_TIMEOUT: Duration = Duration::from_secs(120);
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+static OVERRIDES: RwLock<Option<HashMap<String, PathBuf>>> = RwLock::new(None);
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static RECORDER: RefCell<Option<Vec<LoadedShader>>> = const { RefCell::new(None) };
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+/// SPIR-V and reflection artifact names loaded by one pass constructor.
+pub struct LoadedShader {
+    /// SPIR-V artifact name, e.g. `lexer/dfa_01_scan_inblock.spv`.
+    pub spv: String,
+    /// Reflection artifact name.
+    pub reflection: String,
+}
+
+impl LoadedShader {
+    /// Artifact key: the SPIR-V name without its extension.
+    pub fn key(&self) -> &str {
+        self.spv.strip_suffix(".spv").unwrap_or(&self.spv)
+    }
+}
+
+/// Resolves an artifact name, preferring a hot-reload override.
+pub fn artifact_path(file: &str) -> PathBuf {
+    OVERRIDES
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .as_ref()
+        .and_then(|overrides| overrides.get(file).cloned())
+        .unwrap_or_else(|| crate::shader_artifacts::artifact_path(file))
+}
+
+/// Notes an artifact load for the innermost [`record_loads`] on this thread.
+pub(crate) fn note_loaded(spv: &str, reflection: &str) {
+    RECORDER.with(|recorder| {
+        if let Some(loaded) = recorder.borrow_mut().as_mut() {
+            let shader = LoadedShader {
+                spv: spv.to_string(),
+                reflection: reflection.to_string(),
+            };
+            if !loaded.contains(&shader) {
+                loaded.push(shader);
+            }
+        }
+    });
+}
+
+/// Runs `build` and returns the shaders it loaded, in first-load order.
+pub(crate) fn record_loads<T>(build: impl FnOnce() -> T) -> (T, Vec<LoadedShader>) {
+    let outer = RECORDER.with(|recorder| recorder.replace(Some(Vec::new())));
+    let value = build();
+    let loaded = RECORDER.with(|recorder| recorder.replace(outer).unwrap_or_default());
+    (value, loaded)
+}
+
+fn swap_overrides(entries: Vec<(String, Option<PathBuf>)>) -> Vec<(String, Option<PathBuf>)> {
+    let mut guard = OVERRIDES
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let overrides = guard.get_or_insert_with(HashMap::new);
+    entries
+        .into_iter()
+        .map(|(file, path)| {
+            let previous = match path {
+                Some(path) => overrides.insert(file.clone(), path),
+                None => overrides.remove(&file),
+            };
+            (file, previous)
+        })
+        .collect()
+}
+
+/// Produces replacement artifacts for a reload.
+pub trait ShaderRebuild {
+    /// Writes fresh artifacts for `shader` to `spv_out` and `reflection_out`.
+    ///
+    /// Returns `Ok(false)` when this source has nothing for `shader`.
+    fn rebuild(&self, shader: &LoadedShader, spv_out: &Path, reflection_out: &Path)
+    -> Result<bool>;
+}
+
+/// Rebuilds shaders by running `slangc` on the sources named in the build
+/// script's source manifest.
+pub struct SlangcRebuild {
+    slangc: PathBuf,
+    shader_root: PathBuf,
+    sources: HashMap<String, PathBuf>,
+    timeout: Option<Duration>,
+}
+
+impl SlangcRebuild {
+    /// Locates `slangc` (`$SLANGC`, then `PATH`) and reads the source manifest.
+    pub fn from_env() -> Result<Self> {
+        let slangc = std::env::var_os("SLANGC")
+            .map(PathBuf::from)
+            .filter(|path| path.is_file())
+            .map(Ok)
+            .unwrap_or_else(|| which::which("slangc"))
+            .context("locate `slangc` for shader hot reload; set $SLANGC or add it to PATH")?;
+        let manifest_path = crate::shader_artifacts::artifact_path(slang_compile::SOURCE_MANIFEST);
+        let manifest = std::fs::read_to_string(&manifest_path)
+            .with_context(|| format!("read shader source manifest {}", manifest_path.display()))?;
+        Ok(Self {
+            slangc,
+            shader_root: shader_root(),
+            sources: slang_compile::parse_source_manifest(&manifest)
+                .into_iter()
+                .collect(),
+            timeout: Some(DEFAULT_COMPILE_TIMEOUT),
+        })
+    }
+
+    /// Directory holding the Slang sources, watched by `watch_lex`.
+    pub fn shader_root(&self) -> &Path {
+        &self.shader_root
+    }
+}
+
+impl ShaderRebuild for SlangcRebuild {
+    fn rebuild(
+        &self,
+        shader: &LoadedShader,
+        spv_out: &Path,
+        reflection_out: &Path,
+    ) -> Result<bool> {
+        let key = shader.key();
+        let Some(source) = self.sources.get(key) else {
+            return Ok(false);
+        };
+        let flags = SlangcFlags {
+            opt_level: if slang_compile::forces_minimum_optimization(key) {
+                "0".into()
+            } else {
+                "1".into()
+            },
+            ..SlangcFlags::default()
+        };
+        let mut cmd = slang_compile::slangc_command(
+            &self.slangc,
+            &self.shader_root,
+            source,
+            spv_out,
+            reflection_out,
+            &flags,
+        );
+        let out = slang_compile::command_output_with_timeout(&mut cmd, self.timeout)
+            .with_context(|| format!("run slangc for {}", source.display()))?;
+        if !out.status.success() {
+            return Err(anyhow!(
+                "slangc failed on {} (exit: {:?}):\n{}",
+                source.display(),
+                out.status.code(),
+                String::from_utf8_lossy(&out.stderr).trim_end()
+            ));
+        }
+        Ok(true)
+    }
+}
+
+/// Rebuilds shaders by copying prebuilt artifact pairs, for tests and tools
+/// that produce SPIR-V outside `slangc`.
+#[derive(Default)]
+pub struct PrebuiltRebuild {
+    artifacts: HashMap<String, (PathBuf, PathBuf)>,
+}
+
+impl PrebuiltRebuild {
+    /// Creates an empty set; unlisted shaders are left unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serves `spv` and `reflection` for the shader with artifact `key`.
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        spv: impl Into<PathBuf>,
+        reflection: impl Into<PathBuf>,
+    ) {
+        self.artifacts
+            .insert(key.into(), (spv.into(), reflection.into()));
+    }
+}
+
+impl ShaderRebuild for PrebuiltRebuild {
+    fn rebuild(
+        &self,
+        shader: &LoadedShader,
+        spv_out: &Path,
+        reflection_out: &Path,
+    ) -> Result<bool> {
+        let Some((spv, reflection)) = self.artifacts.get(shader.key()) else {
+            return Ok(false);
+        };
+        std::fs::copy(spv, spv_out).with_context(|| format!("copy {}", spv.display()))?;
+        std::fs::copy(reflection, reflection_out)
+            .with_context(|| format!("copy {}", reflection.display()))?;
+        Ok(true)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// One shader or pipeline that failed during a reload.
+pub struct ReloadFailure {
+    /// Artifact key, or `pipelines` when pass creation failed.
+    pub shader: String,
+    /// Rendered error chain.
+    pub error: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+/// Outcome of one `reload_shaders` call.
+pub struct ReloadReport {
+    /// Shaders whose rebuilt artifacts differ from the live ones.
+    pub changed: Vec<String>,
+    /// Shaders whose rebuilt artifacts are byte-identical to the live ones.
+    pub unchanged: usize,
+    /// Rebuild or pipeline-creation failures; non-empty means nothing was swapped.
+    pub failures: Vec<ReloadFailure>,
+    /// Whether the driver now runs rebuilt pipelines.
+    pub swapped: bool,
+}
+
+impl ReloadReport {
+    /// Whether the reload finished without failures.
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for ReloadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "shader reload: {} changed, {} unchanged, {}",
+            self.changed.len(),
+            self.unchanged,
+            if self.swapped {
+                "pipelines swapped"
+            } else {
+                "pipelines kept"
+            }
+        )?;
+        for shader in &self.changed {
+            write!(f, "\n  changed: {shader}")?;
+        }
+        for failure in &self.failures {
+            write!(f, "\n  failed: {}: {}", failure.shader, failure.error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rebuilds `shaders` with `source` and, when any changed, recreates passes
+/// with `build` against the new artifacts.
+///
+/// Returns the new passes with the shaders they loaded only when the report
+/// says `swapped`.
+pub(crate) fn reload<T>(
+    shaders: &[LoadedShader],
+    source: &dyn ShaderRebuild,
+    build: impl FnOnce() -> Result<T>,
+) -> (Option<(T, Vec<LoadedShader>)>, ReloadReport) {
+    let mut report = ReloadReport::default();
+    let generation = GENERATION.fetch_add(1, Ordering::Relaxed);
+    let stage_root = crate::shader_artifacts::artifact_path(".hot-reload")
+        .join(format!("{}-{generation}", std::process::id()));
+
+    let mut staged = Vec::new();
+    for shader in shaders {
+        match stage_shader(shader, source, &stage_root) {
+            Ok(Some((spv, reflection))) => {
+                report.changed.push(shader.key().to_string());
+                staged.push((shader.spv.clone(), Some(spv)));
+                staged.push((shader.reflection.clone(), Some(reflection)));
+            }
+            Ok(None) => report.unchanged += 1,
+            Err(err) => report.failures.push(ReloadFailure {
+                shader: shader.key().to_string(),
+                error: format!("{err:#}"),
+            }),
+        }
+    }
+    if !report.failures.is_empty() || staged.is_empty() {
+        return (None, report);
+    }
+
+    let previous = swap_overrides(staged);
+    let (built, loaded) = record_loads(build);
+    match built {
+        Ok(passes) => {
+            report.swapped = true;
+            (Some((passes, loaded)), report)
+        }
+        Err(err) => {
+            swap_overrides(previous);
+            report.failures.push(ReloadFailure {
+                shader: "pipelines".into(),
+                error: format!("{err:#}"),
+            });
+            (None, report)
+        }
+    }
+}
+
+/// Runs a pass `build` inside a validation error scope so pipeline errors
+/// surface as `Err` instead of reaching the device's uncaptured handler.
+pub(crate) fn build_validated<T>(
+    device: &wgpu::Device,
+    build: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    let scope = device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let built = build();
+    match (built, pollster::block_on(scope.pop())) {
+        (Ok(_), Some(err)) => Err(anyhow!("pipeline validation: {err}")),
+        (built, _) => built,
+    }
+}
+
+/// Rebuilds one shader into `stage_root`, returning the staged paths when
+/// the artifacts differ from the live ones.
+fn stage_shader(
+    shader: &LoadedShader,
+    source: &dyn ShaderRebuild,
+    stage_root: &Path,
+) -> Result<Option<(PathBuf, PathBuf)>> {
+    let spv_out = stage_root.join(&shader.spv);
+    let reflection_out = stage_root.join(&shader.reflection);
+    for path in [&spv_out, &reflection_out] {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("create staging dir {}", parent.display()))?;
+        }
+    }
+    if !source.rebuild(shader, &spv_out, &reflection_out)? {
+        return Ok(None);
+    }
+    check_artifacts(&spv_out, &reflection_out)?;
+    let same = |live: &str, staged: &Path| -> Result<bool> {
+        let staged = std::fs::read(staged)
+            .with_context(|| format!("read rebuilt artifact {}", staged.display()))?;
+        Ok(std::fs::read(artifact_path(live)).ok().as_deref() == Some(&staged[..]))
+    };
+    if same(&shader.spv, &spv_out)? && same(&shader.reflection, &reflection_out)? {
+        return Ok(None);
+    }
+    Ok(Some((spv_out, reflection_out)))
+}
+
+/// Rejects artifacts that would reach the driver as invalid SPIR-V or
+/// unparsable reflection, since SPIR-V is passed through unvalidated.
+fn check_artifacts(spv: &Path, reflection: &Path) -> Result<()> {
+    let bytes = std::fs::read(spv).with_context(|| format!("read {}", spv.display()))?;
+    if bytes.len() < 20 || bytes.len() % 4 != 0 || bytes[..4] != SPIRV_MAGIC.to_le_bytes() {
+        return Err(anyhow!("{} is not a SPIR-V module", spv.display()));
+    }
+    crate::reflection::parse_reflection_from_file(reflection)
+        .map_err(|err| anyhow!("parse {}: {err}", reflection.display()))?;
+    Ok(())
+}
+
+/// Workspace `shaders/` directory.
+pub fn shader_root() -> PathBuf {
+    PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../../shaders"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shader(key: &str) -> LoadedShader {
+        LoadedShader {
+            spv: format!("{key}.spv"),
+            reflection: format!("{key}.reflect.json"),
+        }
+    }
+
+    struct FailingRebuild;
+
+    impl ShaderRebuild for FailingRebuild {
+        fn rebuild(&self, shader: &LoadedShader, _: &Path, _: &Path) -> Result<bool> {
+            Err(anyhow!("syntax error in {}", shader.key()))
+        }
+    }
+
+    fn spirv_header(generator: u32) -> Vec<u8> {
+        [SPIRV_MAGIC, 0x0001_0300, generator, 1, 0]
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn record_loads_collects_nested_loads_once() {
+        let (value, loaded) = record_loads(|| {
+            note_loaded("a.spv", "a.reflect.json");
+            let ((), inner) = record_loads(|| note_loaded("b.spv", "b.reflect.json"));
+            assert_eq!(inner, [shader("b")]);
+            note_loaded("a.spv", "a.reflect.json");
+            7
+        });
+        assert_eq!(value, 7);
+        assert_eq!(loaded, [shader("a")]);
+        note_loaded("c.spv", "c.reflect.json");
+    }
+
+    #[test]
+    fn failed_rebuild_keeps_old_artifacts_and_reports_the_error() {
+        let shaders = [shader("test/hot_reload/failing")];
+        let (built, report) = reload(&shaders, &FailingRebuild, || Ok(()));
+        assert!(built.is_none());
+        assert!(!report.swapped);
+        assert_eq!(report.failures.len(), 1);
+        assert!(report.failures[0].error.contains("syntax error"));
+        assert_eq!(
+            artifact_path("test/hot_reload/failing.spv"),
+            crate::shader_artifacts::artifact_path("test/hot_reload/failing.spv")
+        );
+    }
+
+    #[test]
+    fn changed_artifacts_are_overridden_until_a_build_fails() {
+        let dir = std::env::temp_dir().join(format!("lanius-hot-reload-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let key = "test/hot_reload/swapped";
+        let spv = dir.join("alt.spv");
+        let reflection = dir.join("alt.reflect.json");
+        std::fs::write(&spv, spirv_header(1)).unwrap();
+        std::fs::write(&reflection, b"{}").unwrap();
+        let mut source = PrebuiltRebuild::new();
+        source.insert(key, &spv, &reflection);
+        let shaders = [shader(key)];
+
+        let (built, report) = reload(&shaders, &source, || {
+            note_loaded(&shaders[0].spv, &shaders[0].reflection);
+            Ok(std::fs::read(artifact_path(&shaders[0].spv))?)
+        });
+        let (bytes, loaded) = built.expect("swap");
+        assert_eq!(bytes, spirv_header(1));
+        assert_eq!(loaded, shaders);
+        assert_eq!(report.changed, [key]);
+        assert!(report.swapped && report.is_ok());
+
+        // Identical bytes are not a change.
+        let (built, report) = reload(&shaders, &source, || Ok(()));
+        assert!(built.is_none());
+        assert_eq!(report.unchanged, 1);
+
+        // A pipeline failure restores the previous override.
+        std::fs::write(&spv, spirv_header(2)).unwrap();
+        let live = artifact_path(&shaders[0].spv);
+        let (built, report) = reload(&shaders, &source, || Err::<(), _>(anyhow!("bad module")));
+        assert!(built.is_none());
+        assert_eq!(report.failures[0].shader, "pipelines");
+        assert_eq!(artifact_path(&shaders[0].spv), live);
+
+        // Artifacts that are not SPIR-V never reach pipeline creation.
+        std::fs::write(&spv, [9; 20]).unwrap();
+        let (built, report) = reload(&shaders, &source, || -> Result<()> {
+            panic!("invalid SPIR-V must not be built")
+        });
+        assert!(built.is_none());
+        assert_eq!(report.failures[0].shader, key);
+        assert!(report.failures[0].error.contains("not a SPIR-V module"));
+        assert_eq!(artifact_path(&shaders[0].spv), live);
+
+        swap_overrides(vec![
+            (shaders[0].spv.clone(), None),
+            (shaders[0].reflection.clone(), None),
+        ]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn report_lists_changes_and_failures() {
+        let report = ReloadReport {
+            changed: vec!["lexer/dfa_01_scan_inblock".into()],
+            unchanged: 9,
+            failures: vec![ReloadFailure {
+                shader: "lexer/tokens_build".into(),
+                error: "slangc failed".into(),
+            }],
+            swapped: false,
+        };
+        assert_eq!(
+            report.to_string(),
+            "shader reload: 1 changed, 9 unchanged, pipelines kept\n\
+             \x20 changed: lexer/dfa_01_scan_inblock\n\
+             \x20 failed: lexer/tokens_build: slangc failed"
+        );
+    }
+}
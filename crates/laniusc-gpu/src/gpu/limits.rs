@@ -0,0 +1,133 @@
+//! Hard size limits of GPU-side `u32` counts and offsets.
+//!
+//! Lengths, offsets, and prefix sums reach the shaders as `u32`. Host code
+//! accumulates them in `u64` and narrows the final value with
+//! [`narrow_u32`], so an input that would overflow a GPU field fails with
+//! [`LimitError::InputTooLarge`] before anything is allocated or dispatched,
+//! instead of wrapping or saturating into a plausible wrong size.
+//!
+//! Shader-side running sums (`s_all`, `s_keep`, pack offsets) are bounded by
+//! the host totals checked here, so they need no checks of their own.
+//!
+//! Buffers past the binding and buffer sizes the device granted fail
+//! [`BufferPlan::validate`](crate::gpu::buffers::BufferPlan::validate) with
+//! [`LimitError::InputExceedsDeviceLimits`] instead.
+//!
+//! [`narrow_u32`]: crate::gpu::limits::narrow_u32
+//! [`LimitError::InputTooLarge`]: crate::gpu::limits::LimitError::InputTooLarge
+//! [`LimitError::InputExceedsDeviceLimits`]: crate::gpu::limits::LimitError::InputExceedsDeviceLimits
+
+/// Most source bytes one lex call accepts. Byte buffers are rounded up to a
+/// whole `u32` word, and the rounded size must still fit a `u32`.
+pub const MAX_INPUT_BYTES: u32 = u32::MAX - 3;
+
+/// Most parser tokens between the sentinels. Framing adds two token slots,
+/// which must still fit a `u32`.
+pub const MAX_PARSER_TOKENS: u32 = u32::MAX - 2;
+
+/// Most stack-change codes one parse may produce; `valid_up_to` reports the
+/// total itself, so it must fit a `u32`.
+pub const MAX_STACK_CHANGES: u32 = u32::MAX;
+
+/// Most partial-parse emits one parse may produce. The tree prefix scan
+/// covers one position past the last emit.
+pub const MAX_EMITS: u32 = u32::MAX - 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Size-limit failure, recoverable from an `anyhow::Error` by downcasting.
+pub enum LimitError {
+    /// `value` of `what` exceeds the GPU-side `limit`.
+    InputTooLarge {
+        what: &'static str,
+        value: u64,
+        limit: u64,
+    },
+    /// One binding needs `needed` bytes of the device limit named `limit`,
+    /// which granted only `granted`; `suggestion` names the API that stays
+    /// under it.
+    InputExceedsDeviceLimits {
+        limit: &'static str,
+        needed: u64,
+        granted: u64,
+        suggestion: &'static str,
+    },
+}
+
+impl std::fmt::Display for LimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InputTooLarge { what, value, limit } => {
+                write!(f, "{what} is {value}, over the GPU limit of {limit}")
+            }
+            Self::InputExceedsDeviceLimits {
+                limit,
+                needed,
+                granted,
+                suggestion,
+            } => write!(
+                f,
+                "input needs {needed} bytes of {limit}, but the device granted {granted}; {suggestion}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+/// Narrows a host-side `u64` count of `what` to the `u32` the GPU sees,
+/// failing when it exceeds `limit`.
+pub fn narrow_u32(what: &'static str, value: u64, limit: u32) -> Result<u32, LimitError> {
+    if value > u64::from(limit) {
+        return Err(LimitError::InputTooLarge {
+            what,
+            value,
+            limit: u64::from(limit),
+        });
+    }
+    Ok(value as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn narrowing_accepts_the_limit_and_rejects_one_past_it() {
+        assert_eq!(narrow_u32("tokens", 7, 7), Ok(7));
+        assert_eq!(
+            narrow_u32("tokens", 8, 7),
+            Err(LimitError::InputTooLarge {
+                what: "tokens",
+                value: 8,
+                limit: 7,
+            })
+        );
+        let err =
+            narrow_u32("stack changes", u64::from(u32::MAX) + 1, MAX_STACK_CHANGES).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "stack changes is 4294967296, over the GPU limit of 4294967295"
+        );
+    }
+
+    #[test]
+    fn device_limit_errors_name_the_limit_and_the_remedy() {
+        let err = LimitError::InputExceedsDeviceLimits {
+            limit: "max_storage_buffer_binding_size",
+            needed: 300,
+            granted: 128,
+            suggestion: "lex in ranges",
+        };
+        assert_eq!(
+            err.to_string(),
+            "input needs 300 bytes of max_storage_buffer_binding_size, but the device granted 128; lex in ranges"
+        );
+    }
+
+    #[test]
+    fn limits_leave_room_for_their_derived_sizes() {
+        assert_eq!(MAX_INPUT_BYTES % 4, 0);
+        assert!(MAX_PARSER_TOKENS.checked_add(2).is_some());
+        assert!(MAX_EMITS.checked_add(1).is_some());
+    }
+}
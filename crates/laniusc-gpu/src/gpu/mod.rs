@@ -2,20 +2,37 @@
 
 /// Typed buffer wrappers and allocation helpers.
 pub mod buffers;
+/// Cooperative cancellation of long-running lex and parse calls.
+pub mod cancel;
 /// Logical compiler-pass ownership, access, and lifetime graph.
 pub mod compiler_graph;
 /// Optional debug readback buffer helpers.
 pub mod debug;
+/// Little-endian decoding of mapped readback bytes.
+pub mod decode;
+/// Run-to-run reproducibility contract for GPU outputs.
+pub use laniusc_core::determinism;
 /// Global device, queue, and pipeline-cache management.
 pub mod device;
+/// Device-free dry runs: planned buffers, dispatches, and required limits.
+pub mod dry_run;
 /// Environment flag parsing helpers for GPU infrastructure.
-pub mod env;
+pub use laniusc_core::env;
+/// Dev-only runtime shader recompilation and pipeline swapping.
+#[cfg(feature = "shader-hot-reload")]
+pub mod hot_reload;
+/// Hard limits of GPU-side `u32` counts and the overflow error.
+pub mod limits;
 /// Compute pass construction, bind groups, dispatch, and submission helpers.
 pub mod passes_core;
+/// Bounded device waits and timeout diagnostics.
+pub mod poll;
 /// Fixed-width readback decoders.
 pub mod readback;
 /// Shared ping/pong prefix-scan planning helpers.
 pub mod scan;
+/// Deferred release of buffers that in-flight GPU work may still use.
+pub mod teardown;
 /// GPU timestamp-query helper.
 pub mod timer;
 /// Chrome/Perfetto trace event collection.
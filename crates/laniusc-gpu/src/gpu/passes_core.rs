@@ -13,13 +13,16 @@ use anyhow::{Result, anyhow};
 use log::{info, warn};
 use wgpu;
 
-use crate::reflection::{
-    EntryPointReflection,
-    ParameterReflection,
-    SlangReflection,
-    get_thread_group_size,
-    parse_reflection_from_bytes,
-    slang_category_and_type_to_wgpu,
+use crate::{
+    gpu::{debug::DebugSink, device::ShaderPath},
+    reflection::{
+        EntryPointReflection,
+        ParameterReflection,
+        SlangReflection,
+        get_thread_group_size,
+        parse_reflection_from_bytes,
+        slang_category_and_type_to_wgpu,
+    },
 };
 
 static PIPELINE_CREATION_COUNT: AtomicU64 = AtomicU64::new(0);
@@ -32,12 +35,25 @@ pub(crate) fn pipeline_creation_count() -> u64 {
     PIPELINE_CREATION_COUNT.load(Ordering::Relaxed)
 }
 
-/// Returns whether selected GPU operations should use wgpu validation scopes.
+/// Returns whether `LANIUS_VALIDATION_SCOPES` asks for wgpu validation scopes.
+///
+/// Only the `from_env` option constructors read it; recorded passes take the
+/// setting from [`PassContext::validation_scopes`] and pipeline creation from
+/// [`ParsedPass::realize`].
 pub fn validation_scopes_enabled() -> bool {
     crate::gpu::env::env_bool_truthy("LANIUS_VALIDATION_SCOPES", false)
 }
 
-/// Returns whether compatible compute passes may share one `wgpu::ComputePass`.
+/// Returns whether `LANIUS_DEBUG_GROUPS` asks passes to wrap their dispatches
+/// in encoder debug groups.
+///
+/// Defaults to on in debug builds; the variable overrides either way.
+pub fn debug_groups_enabled() -> bool {
+    crate::gpu::env::env_bool_truthy("LANIUS_DEBUG_GROUPS", cfg!(debug_assertions))
+}
+
+/// Returns whether `LANIUS_BATCH_COMPUTE_PASSES` lets compatible compute
+/// passes share one `wgpu::ComputePass`; on unless set to `0` or `false`.
 pub fn compute_pass_batching_enabled() -> bool {
     match std::env::var("LANIUS_BATCH_COMPUTE_PASSES") {
         Ok(value) => !matches!(value.trim().to_ascii_lowercase().as_str(), "0" | "false"),
@@ -64,6 +80,7 @@ enum DeferredComputeCommand {
 struct DeferredComputeState {
     active: bool,
     label: Option<&'static str>,
+    host_timing: bool,
     commands: Vec<DeferredComputeCommand>,
 }
 
@@ -75,12 +92,14 @@ thread_local! {
 /// Scope for coalescing ordered compute dispatches until the next explicit
 /// encoder clear/copy boundary. GPU handles are cloned into deferred commands,
 /// so their lifetimes remain valid through the eventual compute pass.
+/// `host_timing` logs the host time of each flush, as the owning driver's
+/// options request.
 pub(crate) struct DeferredComputeBatchGuard {
     enabled: bool,
 }
 
 impl DeferredComputeBatchGuard {
-    pub(crate) fn begin(enabled: bool, label: &'static str) -> Self {
+    pub(crate) fn begin(enabled: bool, label: &'static str, host_timing: bool) -> Self {
         if enabled {
             DEFERRED_COMPUTE.with(|state| {
                 let mut state = state.borrow_mut();
@@ -88,6 +107,7 @@ impl DeferredComputeBatchGuard {
                 assert!(state.commands.is_empty());
                 state.active = true;
                 state.label = Some(label);
+                state.host_timing = host_timing;
             });
         }
         Self { enabled }
@@ -101,6 +121,7 @@ impl Drop for DeferredComputeBatchGuard {
                 let mut state = state.borrow_mut();
                 state.active = false;
                 state.label = None;
+                state.host_timing = false;
                 // Early recording errors must not leak commands into the next
                 // compilation on this worker thread.
                 state.commands.clear();
@@ -250,17 +271,17 @@ pub(crate) fn record_or_defer_compute_indirect_offset(
 
 /// Flushes all deferred dispatches as one ordered compute pass.
 pub(crate) fn flush_deferred_compute(encoder: &mut wgpu::CommandEncoder) {
-    let (label, commands) = DEFERRED_COMPUTE.with(|state| {
+    let (label, host_timing, commands) = DEFERRED_COMPUTE.with(|state| {
         let mut state = state.borrow_mut();
         (
             state.label.unwrap_or("compute.batch"),
+            state.host_timing,
             std::mem::take(&mut state.commands),
         )
     });
     if commands.is_empty() {
         return;
     }
-    let host_timing = crate::gpu::env::env_bool_truthy("LANIUS_GPU_COMPILE_HOST_TIMING", false);
     let started = host_timing.then(Instant::now);
     let command_count = commands.len();
     let mut compute = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
@@ -302,7 +323,7 @@ pub(crate) fn flush_deferred_compute(encoder: &mut wgpu::CommandEncoder) {
     }
     drop(compute);
     if let Some(started) = started {
-        eprintln!(
+        log::info!(target: crate::logging::GPU,
             "[gpu_compile_host_timer] compute_batch.flush: label={label} commands={command_count} elapsed_ms={:.3}",
             started.elapsed().as_secs_f64() * 1000.0,
         );
@@ -341,7 +362,25 @@ pub(crate) fn submit_with_optional_validation(
     let scope = validation_scope(device, validation_enabled);
     let timing = submit_with_progress(queue, label, command_buffer);
     if let Some(err) = pop_validation_scope(scope) {
-        eprintln!("[wgpu submit] validation while submitting {validation_label}: {err:#?}");
+        log::warn!(target: crate::logging::GPU, "[wgpu submit] validation while submitting {validation_label}: {err:#?}");
+    }
+    timing
+}
+
+/// Like [`submit_with_optional_validation`] for several command buffers
+/// handed to the queue in one `submit` call.
+pub(crate) fn submit_all_with_optional_validation(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    label: &str,
+    command_buffers: Vec<wgpu::CommandBuffer>,
+    validation_enabled: bool,
+    validation_label: &str,
+) -> SubmitTiming {
+    let scope = validation_scope(device, validation_enabled);
+    let timing = submit_all_with_progress(queue, label, command_buffers);
+    if let Some(err) = pop_validation_scope(scope) {
+        log::warn!(target: crate::logging::GPU, "[wgpu submit] validation while submitting {validation_label}: {err:#?}");
     }
     timing
 }
@@ -388,15 +427,64 @@ pub enum DispatchDim {
     D2,
 }
 
-#[derive(Copy, Clone, Debug)]
-/// Logical input size supplied to dispatch planning.
-pub enum InputElements {
-    /// One-dimensional element count.
-    Elements1D(u32),
-    /// Two-dimensional width and height.
-    Elements2D(u32, u32),
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// How a pass maps logical input elements onto workgroups.
+///
+/// Declared on each [`Pass`] and checked against the reflected
+/// `thread_group_size` when the pass is built and before it is recorded, so a
+/// shader whose `numthreads` changes cannot silently get the wrong dispatch.
+pub enum DispatchConvention {
+    /// One invocation per element, indexed by `SV_DispatchThreadID`; the
+    /// planner divides the element count by the reflected group width.
+    PerElementThreads,
+    /// One workgroup per element, indexed by `SV_GroupID`, whose lanes
+    /// cooperate on that element; `group_size` is the `numthreads` width the
+    /// shader's lane indexing assumes.
+    PerElementGroups {
+        /// Required reflected group width.
+        group_size: u32,
+    },
+    /// Exactly one workgroup whatever the element count.
+    SingleGroup,
+}
+
+impl DispatchConvention {
+    /// Checks a reflected `thread_group_size` against this convention.
+    pub fn validate(
+        self,
+        label: &str,
+        dim: DispatchDim,
+        thread_group_size: [u32; 3],
+    ) -> Result<()> {
+        let [tgsx, tgsy, tgsz] = thread_group_size;
+        let problem = if tgsx == 0 || tgsy == 0 || tgsz == 0 {
+            Some("has a zero dimension".to_string())
+        } else if tgsz != 1 {
+            Some("tiles Z, which no planner dispatches".to_string())
+        } else {
+            match (self, dim) {
+                (Self::PerElementThreads, DispatchDim::D1) if tgsy != 1 => {
+                    Some("tiles Y for one-dimensional input".to_string())
+                }
+                (Self::PerElementGroups { group_size }, _)
+                    if thread_group_size != [group_size, 1, 1] =>
+                {
+                    Some(format!("differs from the declared [{group_size}, 1, 1]"))
+                }
+                _ => None,
+            }
+        };
+        match problem {
+            Some(problem) => Err(anyhow!(
+                "pass {label} declares {self:?} dispatch, but its reflected thread_group_size {thread_group_size:?} {problem}"
+            )),
+            None => Ok(()),
+        }
+    }
 }
 
+pub use laniusc_core::dispatch::{DispatchRecord, InputElements};
+
 /// Creates bind group layouts from Slang reflection metadata.
 pub fn bgls_from_reflection(
     device: &wgpu::Device,
@@ -464,21 +552,7 @@ pub fn bgls_from_reflection(
 /// so program-layout descriptor sets must be summed rather than checked one at
 /// a time. Flat Slang reflection uses the top-level parameter list instead.
 fn reflected_compute_storage_buffer_count(reflection: &SlangReflection) -> Result<usize> {
-    let entry = reflection
-        .entry_points
-        .iter()
-        .find(|entry| entry.stage.as_deref() == Some("compute"))
-        .ok_or_else(|| anyhow!("no compute entry point found in reflection"))?;
-    let parameters = if let Some(layout) = entry.program_layout.as_ref() {
-        layout
-            .parameters
-            .iter()
-            .flat_map(|set| set.parameters.iter())
-            .collect::<Vec<_>>()
-    } else {
-        reflection.parameters.iter().collect::<Vec<_>>()
-    };
-    Ok(parameters
+    Ok(reflected_compute_parameters(reflection)?
         .into_iter()
         .filter(|parameter| {
             matches!(
@@ -492,6 +566,134 @@ fn reflected_compute_storage_buffer_count(reflection: &SlangReflection) -> Resul
         .count())
 }
 
+/// Parameters of every descriptor set visible to the reflected compute stage.
+fn reflected_compute_parameters(reflection: &SlangReflection) -> Result<Vec<&ParameterReflection>> {
+    let entry = reflection
+        .entry_points
+        .iter()
+        .find(|entry| entry.stage.as_deref() == Some("compute"))
+        .ok_or_else(|| anyhow!("no compute entry point found in reflection"))?;
+    Ok(if let Some(layout) = entry.program_layout.as_ref() {
+        layout
+            .parameters
+            .iter()
+            .flat_map(|set| set.parameters.iter())
+            .collect()
+    } else {
+        reflection.parameters.iter().collect()
+    })
+}
+
+/// Checks that the bindings reflected for pass `label` are exactly
+/// `expected_bindings`.
+///
+/// An empty list means the pass declares no contract and skips the check. The
+/// error names every binding the shader has but the pass does not expect,
+/// with its reflected type, and every expected binding the shader lacks.
+pub fn validate_expected_bindings(
+    label: &str,
+    reflection: &SlangReflection,
+    expected_bindings: &[&str],
+) -> Result<()> {
+    if expected_bindings.is_empty() {
+        return Ok(());
+    }
+    let expected = expected_bindings
+        .iter()
+        .copied()
+        .collect::<std::collections::BTreeSet<_>>();
+    if expected.len() != expected_bindings.len() {
+        return Err(anyhow!(
+            "pass {label} lists a binding more than once in expected_bindings"
+        ));
+    }
+    let reflected = reflected_compute_parameters(reflection)?
+        .into_iter()
+        .filter(|parameter| parameter.binding.index.is_some() && parameter.ty.kind.is_some())
+        .map(|parameter| (parameter.name.as_str(), parameter))
+        .collect::<std::collections::BTreeMap<_, _>>();
+    let unexpected = reflected
+        .iter()
+        .filter(|(name, _)| !expected.contains(*name))
+        .map(|(name, parameter)| format!("'{name}' ({})", reflected_type_text(&parameter.ty)))
+        .collect::<Vec<_>>();
+    let missing = expected
+        .iter()
+        .filter(|name| !reflected.contains_key(*name))
+        .map(|name| format!("'{name}'"))
+        .collect::<Vec<_>>();
+    if unexpected.is_empty() && missing.is_empty() {
+        return Ok(());
+    }
+    let mut problems = Vec::new();
+    if !unexpected.is_empty() {
+        problems.push(format!(
+            "shader binds {} not in expected_bindings",
+            unexpected.join(", ")
+        ));
+    }
+    if !missing.is_empty() {
+        problems.push(format!(
+            "expected_bindings lists {} absent from shader reflection",
+            missing.join(", ")
+        ));
+    }
+    Err(anyhow!(
+        "pass {label} bindings differ from its shader: {}",
+        problems.join("; ")
+    ))
+}
+
+/// Reflected type of a binding, such as `resource structuredBuffer ReadWrite`.
+fn reflected_type_text(ty: &crate::reflection::TypeLayout) -> String {
+    [&ty.kind, &ty.base_shape, &ty.access]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A pass's declared binding names and the reflection artifact they are
+/// checked against.
+#[derive(Clone, Copy, Debug)]
+pub struct BindingContract {
+    /// Pass label used in errors.
+    pub label: &'static str,
+    /// Reflection artifact name relative to the shader artifact root.
+    pub reflection: &'static str,
+    /// The pass's [`Pass::expected_bindings`].
+    pub expected_bindings: &'static [&'static str],
+    /// The pass's [`Pass::DIM`].
+    pub dim: DispatchDim,
+    /// The pass's [`Pass::DISPATCH`].
+    pub dispatch: DispatchConvention,
+}
+
+impl BindingContract {
+    /// Checks the contract against the built reflection artifact. Needs no
+    /// device, so shader/Rust binding or `numthreads` drift fails without a
+    /// GPU.
+    pub fn check_artifact(&self) -> Result<()> {
+        self.parse_artifact().map(|_| ())
+    }
+
+    /// Parses the built reflection artifact and checks it against the
+    /// contract, without a device.
+    pub fn parse_artifact(&self) -> Result<ParsedPass> {
+        let path = shader_artifact_path(self.reflection);
+        let json = std::fs::read(&path)
+            .map_err(|err| anyhow!("read shader reflection {}: {err}", path.display()))?;
+        let parsed = parse_pass_data(self.label, &json, self.expected_bindings)?;
+        let thread_group_size = parsed
+            .thread_group_size
+            .ok_or_else(|| anyhow!("pass {} reflection has no thread_group_size", self.label))?;
+        self.dispatch
+            .validate(self.label, self.dim, thread_group_size)?;
+        Ok(parsed)
+    }
+}
+
 fn validate_reflected_compute_limits(
     reflection: &SlangReflection,
     label: &str,
@@ -581,36 +783,81 @@ mod reflected_limit_tests {
         assert!(message.contains("requires 2 storage buffers"));
         assert!(message.contains("supports 1"));
     }
+
+    #[test]
+    fn expected_bindings_must_match_reflection_both_ways() {
+        let reflection = SlangReflection {
+            parameters: vec![storage_parameter("left", 0), storage_parameter("right", 1)],
+            entry_points: vec![EntryPointReflection {
+                stage: Some("compute".to_owned()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        validate_expected_bindings("lexer.example", &reflection, &["right", "left"]).unwrap();
+        validate_expected_bindings("lexer.example", &reflection, &[]).unwrap();
+
+        let message =
+            validate_expected_bindings("lexer.example", &reflection, &["left", "renamed"])
+                .expect_err("a renamed binding must fail")
+                .to_string();
+        assert!(message.contains("lexer.example"));
+        assert!(
+            message.contains("'right' (resource structuredBuffer Read) not in expected_bindings")
+        );
+        assert!(message.contains("'renamed' absent from shader reflection"));
+
+        let message = validate_expected_bindings("lexer.example", &reflection, &["left", "left"])
+            .expect_err("a duplicate binding must fail")
+            .to_string();
+        assert!(message.contains("more than once"));
+    }
 }
 
 /// Creates a compute pipeline from SPIR-V and reflected bind group layouts.
+///
+/// Devices without SPIR-V passthrough load the module through Naga instead;
+/// a module Naga rejects fails with an error naming the shader.
 pub fn pipeline_from_spirv_and_bgls(
     device: &wgpu::Device,
     label: &str,
     entry: &str,
     spirv: &[u8],
     bgls: &[&wgpu::BindGroupLayout],
-) -> wgpu::ComputePipeline {
+) -> Result<wgpu::ComputePipeline> {
     let total_start = Instant::now();
     let shader_module_start = total_start;
-    trace_pipeline(label, "shader_module.start");
-    // SAFETY: Slang produced this SPIR-V module for the selected backend;
-    // Lanius intentionally bypasses Naga translation for shader modules.
-    let module = unsafe {
-        device.create_shader_module_passthrough(wgpu::ShaderModuleDescriptorPassthrough {
-            label: Some(label),
-            spirv: Some(wgpu::util::make_spirv_raw(spirv)),
-            ..Default::default()
-        })
+    let shader_path = ShaderPath::for_device(device);
+    trace_pipeline(label, &format!("shader_module.start path={shader_path}"));
+    let module = match shader_path {
+        // SAFETY: Slang produced this SPIR-V module for the selected backend;
+        // Lanius intentionally bypasses Naga translation for shader modules.
+        ShaderPath::Passthrough => unsafe {
+            device.create_shader_module_passthrough(wgpu::ShaderModuleDescriptorPassthrough {
+                label: Some(label),
+                spirv: Some(wgpu::util::make_spirv_raw(spirv)),
+                ..Default::default()
+            })
+        },
+        ShaderPath::Validated => {
+            let scope = device.push_error_scope(wgpu::ErrorFilter::Validation);
+            let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(label),
+                source: wgpu::util::make_spirv(spirv),
+            });
+            if let Some(err) = pollster::block_on(scope.pop()) {
+                return Err(anyhow!(
+                    "shader {label} failed Naga validation of its SPIR-V: {err}"
+                ));
+            }
+            module
+        }
     };
     let shader_module_end = Instant::now();
     trace_pipeline(label, "shader_module.done");
     let pipeline_layout_start = shader_module_end;
     trace_pipeline(label, "pipeline_layout.start");
-    // let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-    //     label: Some(label),
-    //     source: wgpu::util::make_spirv(spirv),
-    // });
     let bind_group_layouts: Vec<_> = bgls.iter().copied().map(Some).collect();
     let pl = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: Some(&format!("pl_{label}")),
@@ -640,12 +887,12 @@ pub fn pipeline_from_spirv_and_bgls(
         compute_pipeline_end.duration_since(compute_pipeline_start),
         compute_pipeline_end.duration_since(total_start),
     );
-    pipeline
+    Ok(pipeline)
 }
 
 fn trace_pipeline(label: &str, stage: &str) {
     if crate::gpu::env::env_bool_strict("LANIUS_PIPELINE_TRACE", false) {
-        eprintln!("[laniusc][pipeline][{label}] {stage}");
+        log::info!(target: crate::logging::GPU, "[laniusc][pipeline][{label}] {stage}");
     }
 }
 
@@ -667,7 +914,7 @@ fn trace_pipeline_timing(
     if total_ms < minimum_ms {
         return;
     }
-    eprintln!(
+    log::info!(target: crate::logging::GPU,
         "[laniusc][pipeline-timing] label={label} total_ms={total_ms:.3} shader_module_ms={:.3} pipeline_layout_ms={:.3} compute_pipeline_ms={:.3}",
         shader_module.as_secs_f64() * 1000.0,
         pipeline_layout.as_secs_f64() * 1000.0,
@@ -694,7 +941,7 @@ pub(crate) fn trace_gpu_progress(label: &str) {
         if log::log_enabled!(log::Level::Info) {
             info!("[laniusc][gpu-progress] {label}");
         } else {
-            eprintln!("[laniusc][gpu-progress] {label}");
+            log::info!(target: crate::logging::GPU, "[laniusc][gpu-progress] {label}");
         }
     }
 }
@@ -704,10 +951,21 @@ pub(crate) fn submit_with_progress(
     queue: &wgpu::Queue,
     label: &str,
     command_buffer: wgpu::CommandBuffer,
+) -> SubmitTiming {
+    submit_all_with_progress(queue, label, vec![command_buffer])
+}
+
+/// Submits command buffers in one `queue.submit` and records host-side
+/// submit timing.
+pub(crate) fn submit_all_with_progress(
+    queue: &wgpu::Queue,
+    label: &str,
+    command_buffers: Vec<wgpu::CommandBuffer>,
 ) -> SubmitTiming {
     trace_gpu_progress(&format!("submit.start :: {label}"));
+    crate::gpu::poll::note_submission(label);
     let start = Instant::now();
-    queue.submit(Some(command_buffer));
+    queue.submit(command_buffers);
     let end = Instant::now();
     crate::gpu::trace::record_host_span("host.submit", label, start, end);
     trace_gpu_progress(&format!("submit.done :: {label}"));
@@ -726,11 +984,70 @@ pub(crate) fn map_readback_for_progress(slice: &wgpu::BufferSlice<'_>, label: &s
     trace_gpu_progress(&format!("map.queued :: {label}"));
 }
 
-/// Polls a device while emitting progress messages for a pending map.
-pub(crate) fn wait_for_map_progress(device: &wgpu::Device, label: &str, poll_type: wgpu::PollType) {
+/// Waits for submitted work while emitting progress messages for a pending map.
+///
+/// Fails with [`GpuError::Timeout`](crate::gpu::poll::GpuError::Timeout) once
+/// the configured device wait timeout elapses.
+pub(crate) fn wait_for_map_progress(device: &wgpu::Device, label: &str) -> Result<()> {
+    wait_for_map_progress_cancellable(device, label, None)
+}
+
+/// Like [`wait_for_map_progress`], failing with
+/// [`Cancelled`](crate::gpu::cancel::Cancelled) once `cancel` is cancelled.
+pub(crate) fn wait_for_map_progress_cancellable(
+    device: &wgpu::Device,
+    label: &str,
+    cancel: Option<&crate::gpu::cancel::CancelToken>,
+) -> Result<()> {
     trace_gpu_progress(&format!("poll.start :: {label}"));
-    let _ = device.poll(poll_type);
+    crate::gpu::cancel::wait_for_submitted_work_cancellable(device, label, cancel)?;
     trace_gpu_progress(&format!("poll.done :: {label}"));
+    Ok(())
+}
+
+/// Waits until everything submitted to `queue` so far has finished.
+///
+/// Unlike [`wait_for_map_progress`] this polls without blocking the device, so
+/// work other threads submit afterwards is not waited for. Fails with
+/// [`GpuError::Timeout`](crate::gpu::poll::GpuError::Timeout) once the
+/// configured device wait timeout elapses, or with
+/// [`Cancelled`](crate::gpu::cancel::Cancelled) once `cancel` is cancelled.
+pub(crate) fn wait_for_queue_progress(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    label: &str,
+    cancel: Option<&crate::gpu::cancel::CancelToken>,
+) -> Result<()> {
+    let (tx, rx) = mpsc::channel();
+    queue.on_submitted_work_done(move || {
+        let _ = tx.send(());
+    });
+    trace_gpu_progress(&format!("queue.wait.start :: {label}"));
+    let timeout = crate::gpu::device::device_options().wait_timeout;
+    let started = Instant::now();
+    loop {
+        device
+            .poll(wgpu::PollType::Poll)
+            .map_err(|err| anyhow!("{label} queue poll failed: {err}"))?;
+        match rx.try_recv() {
+            Ok(()) => {
+                trace_gpu_progress(&format!("queue.wait.done :: {label}"));
+                return Ok(());
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => {
+                return Err(anyhow!("{label} work-done callback disconnected"));
+            }
+        }
+        if let Some(cancel) = cancel {
+            cancel.check(label, true)?;
+        }
+        let elapsed = started.elapsed();
+        if timeout.is_some_and(|timeout| elapsed >= timeout) {
+            return Err(crate::gpu::poll::GpuError::timeout(label, elapsed, None).into());
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
 }
 
 /// Blocks until a readback map completes or the configured timeout expires.
@@ -742,11 +1059,10 @@ pub(crate) fn map_readback_blocking(
     wait_for_readback_map(device, slice, label, readback_timeout())
 }
 
-fn readback_timeout() -> Duration {
-    Duration::from_millis(crate::gpu::env::env_u64(
-        "LANIUS_READBACK_TIMEOUT_MS",
-        120_000,
-    ))
+/// `LANIUS_READBACK_TIMEOUT_MS` when set (`0` disables), else the device wait timeout.
+fn readback_timeout() -> Option<Duration> {
+    crate::gpu::device::wait_timeout_from_env("LANIUS_READBACK_TIMEOUT_MS")
+        .unwrap_or_else(|| crate::gpu::device::device_options().wait_timeout)
 }
 
 /// Waits for a readback map callback with explicit timeout and progress output.
@@ -754,7 +1070,7 @@ pub(crate) fn wait_for_readback_map(
     device: &wgpu::Device,
     slice: &wgpu::BufferSlice<'_>,
     label: &str,
-    timeout: Duration,
+    timeout: Option<Duration>,
 ) -> Result<()> {
     let pending = begin_readback_map(slice, label);
     finish_readback_map(device, pending, timeout)
@@ -798,7 +1114,7 @@ pub(crate) fn begin_readback_map(slice: &wgpu::BufferSlice<'_>, label: &str) ->
 pub(crate) fn finish_readback_map(
     device: &wgpu::Device,
     pending: PendingReadbackMap,
-    timeout: Duration,
+    timeout: Option<Duration>,
 ) -> Result<()> {
     let PendingReadbackMap {
         receiver,
@@ -831,11 +1147,8 @@ pub(crate) fn finish_readback_map(
             }
         }
         let elapsed = started.elapsed();
-        if elapsed >= timeout {
-            return Err(anyhow!(
-                "{label} readback did not complete within {} ms",
-                timeout.as_millis()
-            ));
+        if timeout.is_some_and(|timeout| elapsed >= timeout) {
+            return Err(crate::gpu::poll::GpuError::timeout(&label, elapsed, Some(false)).into());
         }
         if elapsed >= next_progress {
             trace_gpu_progress(&format!(
@@ -848,48 +1161,110 @@ pub(crate) fn finish_readback_map(
     }
 }
 
-/// Builds `PassData` from SPIR-V bytes and Slang reflection JSON.
+/// Device-free half of a pass: its parsed reflection and compute shape.
+///
+/// [`parse_pass_data`] builds one from reflection JSON alone, so binding,
+/// thread-group-size, and storage-buffer checks run without a GPU;
+/// [`ParsedPass::realize`] then creates the pipeline on a device.
+pub struct ParsedPass {
+    /// Stable shader/pass id used for cache keys and diagnostics.
+    pub label: String,
+    /// Reflected compute thread-group size, or `None` when the reflection
+    /// omits it.
+    pub thread_group_size: Option<[u32; 3]>,
+    /// Parsed Slang reflection used for bind groups.
+    pub reflection: SlangReflection,
+}
+
+impl ParsedPass {
+    /// Storage buffers the compute stage binds.
+    pub fn storage_buffer_count(&self) -> Result<usize> {
+        reflected_compute_storage_buffer_count(&self.reflection)
+    }
+
+    /// Checks the reflected resources against device `limits`.
+    pub fn check_limits(&self, limits: &wgpu::Limits) -> Result<()> {
+        validate_reflected_compute_limits(&self.reflection, &self.label, limits)
+    }
+
+    /// Creates the pipeline and bind group layouts on `device`, inside a wgpu
+    /// validation scope when `validation_scopes` is set.
+    pub fn realize(
+        self,
+        device: &wgpu::Device,
+        entry: &str,
+        spirv: &[u8],
+        validation_scopes: bool,
+    ) -> Result<PassData> {
+        let label = self.label;
+        validate_reflected_compute_limits(&self.reflection, &label, &device.limits())?;
+        let init_scope = validation_scope(device, validation_scopes);
+        let init_result = (|| {
+            let owned_bgls = bgls_from_reflection(device, &self.reflection)?;
+            let bgl_refs: Vec<&wgpu::BindGroupLayout> = owned_bgls.iter().collect();
+            let pipeline = pipeline_from_spirv_and_bgls(device, &label, entry, spirv, &bgl_refs)?;
+            Ok::<_, anyhow::Error>((owned_bgls, pipeline))
+        })();
+        if init_scope.is_some() {
+            let _ = device.poll(wgpu::PollType::Poll);
+        }
+        if let Some(err) = pop_validation_scope(init_scope) {
+            return Err(anyhow!(
+                "validation while creating GPU pass {label}: {err:?}"
+            ));
+        }
+        let (owned_bgls, pipeline) = init_result?;
+        let tgs = self.thread_group_size.unwrap_or_else(|| {
+            warn!("missing thread_group_size in reflection for {label}; defaulting to [1,1,1]");
+            [1, 1, 1]
+        });
+        debug_assert!(
+            tgs[0] > 0 && tgs[1] > 0 && tgs[2] > 0,
+            "thread_group_size must be non-zero"
+        );
+        Ok(PassData {
+            pipeline: Arc::new(pipeline),
+            bind_group_layouts: owned_bgls.into_iter().map(Arc::new).collect(),
+            shader_id: label,
+            thread_group_size: tgs,
+            reflection: Arc::new(self.reflection),
+        })
+    }
+}
+
+/// Parses Slang reflection JSON into a [`ParsedPass`] without a device.
+///
+/// Fails when the reflected bindings differ from a non-empty
+/// `expected_bindings`; see [`validate_expected_bindings`].
+pub fn parse_pass_data(
+    label: &str,
+    reflection_json: &[u8],
+    expected_bindings: &[&str],
+) -> Result<ParsedPass> {
+    let reflection: SlangReflection =
+        parse_reflection_from_bytes(reflection_json).map_err(anyhow::Error::msg)?;
+    validate_expected_bindings(label, &reflection, expected_bindings)?;
+    Ok(ParsedPass {
+        label: label.to_string(),
+        thread_group_size: get_thread_group_size(&reflection),
+        reflection,
+    })
+}
+
+/// Builds `PassData` from SPIR-V bytes and Slang reflection JSON, without a
+/// validation scope; [`ParsedPass::realize`] takes one explicitly.
+///
+/// Fails before creating the pipeline when the reflected bindings differ from
+/// a non-empty `expected_bindings`; see [`validate_expected_bindings`].
 pub fn make_pass_data(
     device: &wgpu::Device,
     label: &str,
     entry: &str,
     spirv: &[u8],
     reflection_json: &[u8],
+    expected_bindings: &[&str],
 ) -> Result<PassData> {
-    let reflection: SlangReflection =
-        parse_reflection_from_bytes(reflection_json).map_err(anyhow::Error::msg)?;
-    validate_reflected_compute_limits(&reflection, label, &device.limits())?;
-    let init_scope = validation_scope(device, validation_scopes_enabled());
-    let init_result = (|| {
-        let owned_bgls = bgls_from_reflection(device, &reflection)?;
-        let bgl_refs: Vec<&wgpu::BindGroupLayout> = owned_bgls.iter().collect();
-        let pipeline = pipeline_from_spirv_and_bgls(device, label, entry, spirv, &bgl_refs);
-        Ok::<_, anyhow::Error>((owned_bgls, pipeline))
-    })();
-    if init_scope.is_some() {
-        let _ = device.poll(wgpu::PollType::Poll);
-    }
-    if let Some(err) = pop_validation_scope(init_scope) {
-        return Err(anyhow!(
-            "validation while creating GPU pass {label}: {err:?}"
-        ));
-    }
-    let (owned_bgls, pipeline) = init_result?;
-    let tgs = get_thread_group_size(&reflection).unwrap_or_else(|| {
-        warn!("missing thread_group_size in reflection for {label}; defaulting to [1,1,1]");
-        [1, 1, 1]
-    });
-    debug_assert!(
-        tgs[0] > 0 && tgs[1] > 0 && tgs[2] > 0,
-        "thread_group_size must be non-zero"
-    );
-    Ok(PassData {
-        pipeline: Arc::new(pipeline),
-        bind_group_layouts: owned_bgls.into_iter().map(Arc::new).collect(),
-        shader_id: label.to_string(),
-        thread_group_size: tgs,
-        reflection: Arc::new(reflection),
-    })
+    parse_pass_data(label, reflection_json, expected_bindings)?.realize(device, entry, spirv, false)
 }
 
 #[cfg(all(debug_assertions, not(target_arch = "wasm32")))]
@@ -900,6 +1275,7 @@ pub fn make_pass_data_from_artifact_files<P, R>(
     entry: &str,
     spv_path: P,
     reflection_path: R,
+    expected_bindings: &[&str],
 ) -> Result<PassData>
 where
     P: AsRef<std::path::Path>,
@@ -915,7 +1291,14 @@ where
             reflection_path.display()
         )
     })?;
-    make_pass_data(device, label, entry, &spirv, &reflection_json)
+    make_pass_data(
+        device,
+        label,
+        entry,
+        &spirv,
+        &reflection_json,
+        expected_bindings,
+    )
 }
 
 /// Builds `PassData` from a shader artifact key without extensions.
@@ -925,15 +1308,40 @@ pub fn make_pass_data_from_shader_key(
     entry: &str,
     shader: &str,
 ) -> Result<PassData> {
-    make_pass_data_from_shader_artifacts(
+    make_checked_pass_data_from_shader_key(device, label, entry, shader, &[])
+}
+
+/// Builds `PassData` from a shader artifact key, checking the reflected
+/// bindings against `expected_bindings`.
+pub fn make_checked_pass_data_from_shader_key(
+    device: &wgpu::Device,
+    label: &str,
+    entry: &str,
+    shader: &str,
+    expected_bindings: &[&str],
+) -> Result<PassData> {
+    pass_data_from_shader_artifacts(
         device,
         label,
         entry,
         &format!("{shader}.spv"),
         &format!("{shader}.reflect.json"),
+        expected_bindings,
     )
 }
 
+/// Resolves a shader artifact name, honoring hot-reload overrides when enabled.
+fn shader_artifact_path(file: &str) -> std::path::PathBuf {
+    #[cfg(feature = "shader-hot-reload")]
+    {
+        crate::gpu::hot_reload::artifact_path(file)
+    }
+    #[cfg(not(feature = "shader-hot-reload"))]
+    {
+        crate::shader_artifacts::artifact_path(file)
+    }
+}
+
 /// Builds `PassData` from explicit SPIR-V and reflection artifact names.
 pub fn make_pass_data_from_shader_artifacts(
     device: &wgpu::Device,
@@ -942,20 +1350,34 @@ pub fn make_pass_data_from_shader_artifacts(
     spv: &str,
     reflection: &str,
 ) -> Result<PassData> {
+    pass_data_from_shader_artifacts(device, label, entry, spv, reflection, &[])
+}
+
+fn pass_data_from_shader_artifacts(
+    device: &wgpu::Device,
+    label: &str,
+    entry: &str,
+    spv: &str,
+    reflection: &str,
+    expected_bindings: &[&str],
+) -> Result<PassData> {
+    #[cfg(feature = "shader-hot-reload")]
+    crate::gpu::hot_reload::note_loaded(spv, reflection);
     #[cfg(all(debug_assertions, not(target_arch = "wasm32")))]
     {
         return make_pass_data_from_artifact_files(
             device,
             label,
             entry,
-            crate::shader_artifacts::artifact_path(spv),
-            crate::shader_artifacts::artifact_path(reflection),
+            shader_artifact_path(spv),
+            shader_artifact_path(reflection),
+            expected_bindings,
         );
     }
     #[cfg(any(not(debug_assertions), target_arch = "wasm32"))]
     {
-        let spv_path = crate::shader_artifacts::artifact_path(spv);
-        let reflection_path = crate::shader_artifacts::artifact_path(reflection);
+        let spv_path = shader_artifact_path(spv);
+        let reflection_path = shader_artifact_path(reflection);
         let spv_bytes = std::fs::read(&spv_path)
             .map_err(|err| anyhow!("read shader SPIR-V {}: {err}", spv_path.display()))?;
         let reflection_bytes = std::fs::read(&reflection_path).map_err(|err| {
@@ -964,7 +1386,14 @@ pub fn make_pass_data_from_shader_artifacts(
                 reflection_path.display()
             )
         })?;
-        make_pass_data(device, label, entry, &spv_bytes, &reflection_bytes)
+        make_pass_data(
+            device,
+            label,
+            entry,
+            &spv_bytes,
+            &reflection_bytes,
+            expected_bindings,
+        )
     }
 }
 
@@ -1036,6 +1465,19 @@ macro_rules! impl_static_shader_pass {
                 )?;
                 Ok(Self { data })
             }
+
+            /// Returns the contract dry runs plan this pass's direct
+            /// dispatches with: one thread per element of a 1D input, and no
+            /// declared bindings to check.
+            pub fn binding_contract() -> $crate::gpu::passes_core::BindingContract {
+                $crate::gpu::passes_core::BindingContract {
+                    label: $label,
+                    reflection: concat!($shader, ".reflect.json"),
+                    expected_bindings: &[],
+                    dim: $crate::gpu::passes_core::DispatchDim::D1,
+                    dispatch: $crate::gpu::passes_core::DispatchConvention::PerElementThreads,
+                }
+            }
         }
     };
     ($pass:ident, label: $label:expr, shader: $shader:literal) => {
@@ -1062,6 +1504,55 @@ macro_rules! impl_static_shader_pass {
     };
 }
 
+/// Like [`impl_static_shader_pass`] for a type implementing [`Pass`]: `new`
+/// checks the shader's reflected bindings against
+/// [`Pass::expected_bindings`] and its thread-group size against
+/// [`Pass::DISPATCH`], and `binding_contract` exposes both checks without a
+/// device.
+macro_rules! impl_checked_shader_pass {
+    ($pass:ident, label: $label:expr, entry: $entry:expr, shader: $shader:literal) => {
+        impl $pass {
+            /// Creates this static shader pass for `device`, checking its
+            /// reflected bindings against `expected_bindings` and its
+            /// thread-group size against `DISPATCH`.
+            pub fn new(device: &wgpu::Device) -> anyhow::Result<Self> {
+                let data = $crate::gpu::passes_core::make_checked_pass_data_from_shader_key(
+                    device,
+                    $label,
+                    $entry,
+                    $shader,
+                    <Self as $crate::gpu::passes_core::Pass<_, _>>::expected_bindings(),
+                )?;
+                <Self as $crate::gpu::passes_core::Pass<_, _>>::check_dispatch(
+                    data.thread_group_size,
+                )?;
+                Ok(Self { data })
+            }
+
+            /// Returns this pass's binding contract.
+            pub fn binding_contract() -> $crate::gpu::passes_core::BindingContract {
+                $crate::gpu::passes_core::BindingContract {
+                    label: $label,
+                    reflection: concat!($shader, ".reflect.json"),
+                    expected_bindings:
+                        <Self as $crate::gpu::passes_core::Pass<_, _>>::expected_bindings(),
+                    dim: <Self as $crate::gpu::passes_core::Pass<_, _>>::DIM,
+                    dispatch: <Self as $crate::gpu::passes_core::Pass<_, _>>::DISPATCH,
+                }
+            }
+        }
+    };
+    ($pass:ident, label: $label:expr, shader: $shader:literal) => {
+        $crate::gpu::passes_core::impl_checked_shader_pass!(
+            $pass,
+            label: $label,
+            entry: "main",
+            shader: $shader
+        );
+    };
+}
+
+pub(crate) use impl_checked_shader_pass;
 pub(crate) use impl_static_shader_pass;
 pub(crate) use make_main_pass;
 pub(crate) use make_shader_pass;
@@ -1292,6 +1783,24 @@ pub mod bind_group {
 /// WebGPU maximum workgroup count per dispatch dimension used by the planner.
 pub const MAX_GROUPS_PER_DIM: u32 = 65_535;
 
+/// Computes (gx, gy, gz) for a pass under its declared convention.
+///
+/// Every [`Pass`] dispatch sizes its grid here; [`plan_workgroups`] only
+/// applies the per-dimension limit and tiling.
+pub fn plan_dispatch(
+    convention: DispatchConvention,
+    dim: DispatchDim,
+    input: InputElements,
+    [tgsx, tgsy, _tgsz]: [u32; 3],
+) -> anyhow::Result<(u32, u32, u32)> {
+    match convention {
+        DispatchConvention::PerElementThreads => plan_workgroups(dim, input, [tgsx, tgsy, 1]),
+        // Each element already maps 1:1 to a group.
+        DispatchConvention::PerElementGroups { .. } => plan_workgroups(dim, input, [1, 1, 1]),
+        DispatchConvention::SingleGroup => Ok((1, 1, 1)),
+    }
+}
+
 /// Compute (gx, gy, gz) for a pass, reusing the same rules everywhere.
 /// This is the *only* place that knows about the 65_535 limit and D1-to-D2 tiling.
 pub fn plan_workgroups(
@@ -1332,6 +1841,88 @@ pub fn plan_workgroups(
     }
 }
 
+#[cfg(test)]
+mod dispatch_convention_tests {
+    use super::*;
+
+    /// Test-only pass claiming 64-lane groups for a shader reflected at 256.
+    struct LyingGroupsPass;
+
+    impl Pass<(), ()> for LyingGroupsPass {
+        const NAME: &'static str = "lying_groups";
+        const DIM: DispatchDim = DispatchDim::D1;
+        const DISPATCH: DispatchConvention =
+            DispatchConvention::PerElementGroups { group_size: 64 };
+
+        fn from_data(_: PassData) -> Self {
+            Self
+        }
+
+        fn data(&self) -> &PassData {
+            unreachable!("never dispatched")
+        }
+
+        fn create_resource_map<'a>(&self, _: &'a ()) -> HashMap<String, wgpu::BindingResource<'a>> {
+            HashMap::new()
+        }
+    }
+
+    #[test]
+    fn construction_check_rejects_a_mismatched_group_size() {
+        let err = LyingGroupsPass::check_dispatch([256, 1, 1]).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("lying_groups"), "{message}");
+        assert!(message.contains("[64, 1, 1]"), "{message}");
+        assert!(LyingGroupsPass::check_dispatch([64, 1, 1]).is_ok());
+    }
+
+    #[test]
+    fn thread_conventions_reject_tiles_the_planner_ignores() {
+        let threads = DispatchConvention::PerElementThreads;
+        assert!(threads.validate("p", DispatchDim::D1, [256, 1, 1]).is_ok());
+        assert!(threads.validate("p", DispatchDim::D1, [16, 16, 1]).is_err());
+        assert!(threads.validate("p", DispatchDim::D2, [16, 16, 1]).is_ok());
+        assert!(threads.validate("p", DispatchDim::D1, [0, 1, 1]).is_err());
+        let single = DispatchConvention::SingleGroup;
+        assert!(single.validate("p", DispatchDim::D1, [1, 1, 2]).is_err());
+    }
+
+    #[test]
+    fn plan_dispatch_sizes_each_convention() {
+        let n = InputElements::Elements1D(70_000);
+        assert_eq!(
+            plan_dispatch(
+                DispatchConvention::PerElementThreads,
+                DispatchDim::D1,
+                n,
+                [256, 1, 1]
+            )
+            .unwrap(),
+            (274, 1, 1)
+        );
+        assert_eq!(
+            plan_dispatch(
+                DispatchConvention::PerElementGroups { group_size: 256 },
+                DispatchDim::D1,
+                n,
+                [256, 1, 1]
+            )
+            .unwrap(),
+            (MAX_GROUPS_PER_DIM, 2, 1)
+        );
+        assert_eq!(
+            plan_dispatch(
+                DispatchConvention::SingleGroup,
+                DispatchDim::D1,
+                n,
+                [1, 1, 1]
+            )
+            .unwrap(),
+            (1, 1, 1)
+        );
+    }
+}
+
 /// Generic per-dispatch context shared across passes (lexer, parser, etc.).
 /// `B` is the concrete buffers type for the pipeline; `D` is the debug output type.
 pub struct PassContext<'a, B, D> {
@@ -1343,6 +1934,15 @@ pub struct PassContext<'a, B, D> {
     /// Optional bind group cache: when present, record_pass will reuse cached
     /// bind groups keyed by shader id and set index, and populate it on miss.
     pub bg_cache: Option<&'a mut BindGroupCache>,
+    /// Wrap each logical pass (and each scan round) in an encoder debug group.
+    pub debug_groups: bool,
+    /// Wrap each recorded pass in a wgpu validation scope.
+    pub validation_scopes: bool,
+    /// Let compatible passes share one `wgpu::ComputePass`; ignored while
+    /// validation scopes are on.
+    pub batch_compute_passes: bool,
+    /// Optional sink for the planned shape of every dispatch recorded here.
+    pub dispatch_records: Option<&'a mut Vec<DispatchRecord>>,
 }
 
 #[derive(Default)]
@@ -1350,23 +1950,107 @@ pub struct PassContext<'a, B, D> {
 pub struct BindGroupCache {
     // Keyed by shader id (label) to its vector of bind groups (per set index)
     map: HashMap<String, Vec<Arc<wgpu::BindGroup>>>,
+    // Last binding variant recorded by `retain_variant`, keyed by shader id.
+    variants: HashMap<String, u64>,
+    // Bind groups of inactive variants, restored when their variant returns.
+    parked: HashMap<(String, u64), Vec<Arc<wgpu::BindGroup>>>,
+    hits: u64,
+    misses: u64,
+}
+
+/// Lookup counters and current size of a [`BindGroupCache`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BindGroupCacheStats {
+    /// Lookups served from cached bind groups.
+    pub hits: u64,
+    /// Lookups that had to create bind groups.
+    pub misses: u64,
+    /// Cached entries currently retained, inactive variants included.
+    pub entries: usize,
+}
+
+/// What a driver's `warm_up` allocated and recorded ahead of real work.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WarmupReport {
+    /// Bytes of resident buffers sized by the warm-up.
+    pub buffers_bytes: u64,
+    /// Bind groups created while recording the warm-up passes.
+    pub bind_groups_created: u64,
+    /// Full pass sequences recorded and submitted, one per synthetic input.
+    pub passes_executed: u64,
 }
 
 impl BindGroupCache {
     /// Creates an empty bind group cache.
     pub fn new() -> Self {
-        Self {
-            map: HashMap::new(),
-        }
+        Self::default()
     }
     /// Clears all cached bind groups.
+    ///
+    /// Hit and miss counters are monotonic and survive clearing.
     pub fn clear(&mut self) {
         self.map.clear();
+        self.variants.clear();
+        self.parked.clear();
     }
 
     /// Removes cached bind groups for one shader id.
     pub fn remove(&mut self, shader_id: &str) {
         self.map.remove(shader_id);
+        self.variants.remove(shader_id);
+        self.parked.retain(|(id, _), _| id != shader_id);
+    }
+
+    /// Switches cached bind groups for `shader_id` to `variant`, parking the
+    /// previous variant's groups until it is selected again.
+    ///
+    /// Passes whose resource map depends on runtime sizes (for example the
+    /// ping/pong parity of a preceding scan) record that choice here instead of
+    /// unconditionally removing their entry before every dispatch.
+    pub fn retain_variant(&mut self, shader_id: &str, variant: u64) {
+        let previous = self.variants.insert(shader_id.to_string(), variant);
+        if previous == Some(variant) {
+            return;
+        }
+        if let Some(groups) = self.map.remove(shader_id)
+            && let Some(previous) = previous
+        {
+            self.parked
+                .insert((shader_id.to_string(), previous), groups);
+        }
+        if let Some(groups) = self.parked.remove(&(shader_id.to_string(), variant)) {
+            self.map.insert(shader_id.to_string(), groups);
+        }
+    }
+
+    /// Returns hit/miss counters and the current entry count.
+    pub fn stats(&self) -> BindGroupCacheStats {
+        BindGroupCacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.map.len() + self.parked.len(),
+        }
+    }
+
+    /// Returns cached bind groups for `key`, creating and inserting them on a miss.
+    ///
+    /// Entries whose group count differs from `expected_len` are treated as stale.
+    pub(crate) fn get_or_create(
+        &mut self,
+        key: &str,
+        expected_len: usize,
+        create: impl FnOnce() -> Result<Vec<Arc<wgpu::BindGroup>>, anyhow::Error>,
+    ) -> Result<Vec<Arc<wgpu::BindGroup>>, anyhow::Error> {
+        if let Some(groups) = self.map.get(key)
+            && groups.len() == expected_len
+        {
+            self.hits += 1;
+            return Ok(groups.clone());
+        }
+        self.misses += 1;
+        let groups = create()?;
+        self.map.insert(key.to_string(), groups.clone());
+        Ok(groups)
     }
 
     /// Returns reflected bind groups for raw `PassData`, reusing them while the
@@ -1379,32 +2063,97 @@ impl BindGroupCache {
         resources: &HashMap<String, wgpu::BindingResource<'a>>,
     ) -> Result<Vec<Arc<wgpu::BindGroup>>, anyhow::Error> {
         let cache_key = format!("{}::raw::{label}", pass.shader_id);
-        if let Some(groups) = self.map.get(&cache_key)
-            && groups.len() == pass.bind_group_layouts.len()
-        {
-            return Ok(groups.clone());
+        self.get_or_create(&cache_key, pass.bind_group_layouts.len(), || {
+            create_reflected_bind_groups(device, label, pass, resources)
+        })
+    }
+}
+
+#[cfg(test)]
+mod bind_group_cache_tests {
+    use super::*;
+
+    #[test]
+    fn stats_count_hits_misses_and_entries() {
+        let mut cache = BindGroupCache::new();
+        for _ in 0..3 {
+            cache
+                .get_or_create("scan::round0::gen1", 0, || Ok(Vec::new()))
+                .unwrap();
         }
-        let groups = pass
-            .bind_group_layouts
-            .iter()
-            .enumerate()
-            .map(|(set_index, layout)| {
-                bind_group::create_bind_group_from_reflection(
-                    device,
-                    Some(label),
-                    layout,
-                    &pass.reflection,
-                    set_index,
-                    resources,
-                )
-                .map(Arc::new)
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-        self.map.insert(cache_key, groups.clone());
-        Ok(groups)
+        cache
+            .get_or_create("scan::round1::gen1", 0, || Ok(Vec::new()))
+            .unwrap();
+
+        assert_eq!(
+            cache.stats(),
+            BindGroupCacheStats {
+                hits: 2,
+                misses: 2,
+                entries: 2,
+            }
+        );
+
+        cache.clear();
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (2, 2, 0));
+    }
+
+    #[test]
+    fn retain_variant_parks_entries_of_the_previous_variant() {
+        let mut cache = BindGroupCache::new();
+        cache.retain_variant("apply", 1);
+        cache.get_or_create("apply", 0, || Ok(Vec::new())).unwrap();
+
+        cache.retain_variant("apply", 1);
+        cache.get_or_create("apply", 0, || Ok(Vec::new())).unwrap();
+        assert_eq!((cache.stats().hits, cache.stats().misses), (1, 1));
+
+        // A new variant misses once, then both variants are served from cache.
+        cache.retain_variant("apply", 0);
+        cache.get_or_create("apply", 0, || Ok(Vec::new())).unwrap();
+        assert_eq!(cache.stats().misses, 2);
+        for variant in [1, 0, 1] {
+            cache.retain_variant("apply", variant);
+            cache.get_or_create("apply", 0, || Ok(Vec::new())).unwrap();
+        }
+        assert_eq!(
+            cache.stats(),
+            BindGroupCacheStats {
+                hits: 4,
+                misses: 2,
+                entries: 2,
+            }
+        );
+
+        cache.remove("apply");
+        assert_eq!(cache.stats().entries, 0);
     }
 }
 
+fn create_reflected_bind_groups(
+    device: &wgpu::Device,
+    label: &str,
+    pass: &PassData,
+    resources: &HashMap<String, wgpu::BindingResource<'_>>,
+) -> Result<Vec<Arc<wgpu::BindGroup>>, anyhow::Error> {
+    pass.bind_group_layouts
+        .iter()
+        .enumerate()
+        .map(|(set_index, layout)| {
+            bind_group::create_bind_group_from_reflection(
+                device,
+                Some(label),
+                layout,
+                &pass.reflection,
+                set_index,
+                resources,
+            )
+            .map(Arc::new)
+        })
+        .collect()
+}
+
 fn bind_groups_for_pass<P, Buffers, DebugOutput>(
     device: &wgpu::Device,
     pass: &P,
@@ -1415,40 +2164,22 @@ where
     P: Pass<Buffers, DebugOutput> + ?Sized,
 {
     let pd = pass.data();
-    let resources = pass.create_resource_map(buffers);
-    let mut cached_entries: Option<Vec<Arc<wgpu::BindGroup>>> = None;
-    if let Some(cache) = cache.as_ref()
-        && let Some(v) = cache.map.get(&pd.shader_id)
-        && v.len() == pd.bind_group_layouts.len()
-    {
-        cached_entries = Some(v.clone());
-    }
-    if let Some(v) = cached_entries {
-        return Ok(v);
-    }
-
-    let mut bind_groups = Vec::with_capacity(pd.bind_group_layouts.len());
-    for (set_idx, bgl) in pd.bind_group_layouts.iter().enumerate() {
-        let bg = bind_group::create_bind_group_from_reflection(
-            device,
-            Some(P::NAME),
-            bgl,
-            &pd.reflection,
-            set_idx,
-            &resources,
-        )?;
-        bind_groups.push(Arc::new(bg));
-    }
-    if let Some(cache) = cache {
-        cache.map.insert(pd.shader_id.clone(), bind_groups.clone());
+    let create = || {
+        let resources = pass.create_resource_map(buffers);
+        create_reflected_bind_groups(device, P::NAME, pd, &resources)
+    };
+    match cache {
+        Some(cache) => cache.get_or_create(&pd.shader_id, pd.bind_group_layouts.len(), create),
+        None => create(),
     }
-    Ok(bind_groups)
 }
 
 /// Records multiple compatible passes into one compute pass.
 pub struct ComputePassBatch<'encoder> {
     pass: wgpu::ComputePass<'encoder>,
     retained_bind_groups: Vec<Vec<Arc<wgpu::BindGroup>>>,
+    debug_groups: bool,
+    dispatch_records: Option<&'encoder mut Vec<DispatchRecord>>,
 }
 
 impl<'encoder> ComputePassBatch<'encoder> {
@@ -1461,9 +2192,22 @@ impl<'encoder> ComputePassBatch<'encoder> {
         Self {
             pass,
             retained_bind_groups: Vec::new(),
+            debug_groups: false,
+            dispatch_records: None,
         }
     }
 
+    /// Labels each cached pass with a debug group and/or logs its dispatch shape.
+    pub fn instrumented(
+        mut self,
+        debug_groups: bool,
+        dispatch_records: Option<&'encoder mut Vec<DispatchRecord>>,
+    ) -> Self {
+        self.debug_groups = debug_groups;
+        self.dispatch_records = dispatch_records;
+        self
+    }
+
     /// Records one pre-bound direct dispatch into this compute pass.
     pub(crate) fn record_raw(
         &mut self,
@@ -1508,22 +2252,32 @@ impl<'encoder> ComputePassBatch<'encoder> {
         P: Pass<Buffers, DebugOutput>,
     {
         let pd = pass.data();
+        P::check_dispatch(pd.thread_group_size)?;
         let bind_groups =
             bind_groups_for_pass::<P, Buffers, DebugOutput>(device, pass, buffers, Some(cache))?;
-        let [tgsx, tgsy, _tgsz] = pd.thread_group_size;
-        let (gx, gy, gz) = plan_workgroups(P::DIM, input, [tgsx, tgsy, 1])?;
+        let (gx, gy, gz) = plan_dispatch(P::DISPATCH, P::DIM, input, pd.thread_group_size)?;
         assert!(gx <= MAX_GROUPS_PER_DIM);
         assert!(gy <= MAX_GROUPS_PER_DIM);
         debug_assert!(
             gx >= 1 && gy >= 1 && gz >= 1,
             "dispatch must issue at least one group"
         );
+        if self.debug_groups {
+            self.pass.push_debug_group(P::NAME);
+        }
         self.pass.set_pipeline(&pd.pipeline);
         for (i, bg) in bind_groups.iter().enumerate() {
             self.pass
                 .set_bind_group(i as u32, Option::<&wgpu::BindGroup>::Some(&*bg), &[]);
         }
         self.pass.dispatch_workgroups(gx, gy, gz);
+        if self.debug_groups {
+            self.pass.pop_debug_group();
+        }
+        crate::gpu::poll::note_dispatch(P::NAME);
+        if let Some(records) = self.dispatch_records.as_deref_mut() {
+            records.push(DispatchRecord::direct(P::NAME, (gx, gy, gz), input));
+        }
         self.retained_bind_groups.push(bind_groups);
         Ok(())
     }
@@ -1541,14 +2295,25 @@ impl<'encoder> ComputePassBatch<'encoder> {
         P: Pass<Buffers, DebugOutput>,
     {
         let pd = pass.data();
+        P::check_dispatch(pd.thread_group_size)?;
         let bind_groups =
             bind_groups_for_pass::<P, Buffers, DebugOutput>(device, pass, buffers, Some(cache))?;
+        if self.debug_groups {
+            self.pass.push_debug_group(P::NAME);
+        }
         self.pass.set_pipeline(&pd.pipeline);
         for (i, bg) in bind_groups.iter().enumerate() {
             self.pass
                 .set_bind_group(i as u32, Option::<&wgpu::BindGroup>::Some(&*bg), &[]);
         }
         self.pass.dispatch_workgroups_indirect(dispatch_args, 0);
+        if self.debug_groups {
+            self.pass.pop_debug_group();
+        }
+        crate::gpu::poll::note_dispatch(P::NAME);
+        if let Some(records) = self.dispatch_records.as_deref_mut() {
+            records.push(DispatchRecord::indirect(P::NAME));
+        }
         self.retained_bind_groups.push(bind_groups);
         Ok(())
     }
@@ -1567,6 +2332,9 @@ pub trait Pass<Buffers, DebugOutput> {
     /// Logical input shape used to translate an element count into workgroups.
     const DIM: DispatchDim;
 
+    /// How elements map onto workgroups; see [`DispatchConvention`].
+    const DISPATCH: DispatchConvention;
+
     /// Builds the wrapper from precompiled pipeline and reflection data.
     fn from_data(data: PassData) -> Self
     where
@@ -1575,6 +2343,31 @@ pub trait Pass<Buffers, DebugOutput> {
     /// Returns the reflected pipeline data shared by all dispatch paths.
     fn data(&self) -> &PassData;
 
+    /// Shader binding names this pass's resource map supplies, checked against
+    /// reflection when the pass is built. Empty means undeclared and skips
+    /// the check.
+    fn expected_bindings() -> &'static [&'static str]
+    where
+        Self: Sized,
+    {
+        &[]
+    }
+
+    /// Debug-capture keys [`Pass::record_debug`] writes, audited by
+    /// [`DebugOutput::audit`](crate::gpu::debug::DebugOutput::audit). Empty
+    /// means the pass captures nothing.
+    fn expected_debug_keys() -> &'static [&'static str]
+    where
+        Self: Sized,
+    {
+        &[]
+    }
+
+    /// Checks a reflected `thread_group_size` against [`Pass::DISPATCH`].
+    fn check_dispatch(thread_group_size: [u32; 3]) -> Result<()> {
+        Self::DISPATCH.validate(Self::NAME, Self::DIM, thread_group_size)
+    }
+
     /// Maps shader binding names to the resident buffers used by this pass.
     fn create_resource_map<'a>(
         &self,
@@ -1586,12 +2379,17 @@ pub trait Pass<Buffers, DebugOutput> {
         &self,
         ctx: &mut PassContext<'a, Buffers, DebugOutput>,
         input: InputElements,
-    ) -> Result<(), anyhow::Error> {
-        let use_scopes = validation_scopes_enabled(); // enable per-pass validation only when asked
+    ) -> Result<(), anyhow::Error>
+    where
+        Self: Sized,
+        DebugOutput: DebugSink,
+    {
+        let use_scopes = ctx.validation_scopes; // enable per-pass validation only when asked
 
         let validation_scope = validation_scope(ctx.device, use_scopes);
 
         let pd = self.data();
+        Self::check_dispatch(pd.thread_group_size)?;
         let bind_groups = bind_groups_for_pass::<Self, Buffers, DebugOutput>(
             ctx.device,
             self,
@@ -1599,8 +2397,7 @@ pub trait Pass<Buffers, DebugOutput> {
             ctx.bg_cache.as_deref_mut(),
         )?;
 
-        let [tgsx, tgsy, _tgsz] = pd.thread_group_size;
-        let (gx, gy, gz) = plan_workgroups(Self::DIM, input, [tgsx, tgsy, 1])?;
+        let (gx, gy, gz) = plan_dispatch(Self::DISPATCH, Self::DIM, input, pd.thread_group_size)?;
 
         assert!(gx <= MAX_GROUPS_PER_DIM);
         assert!(gy <= MAX_GROUPS_PER_DIM);
@@ -1609,18 +2406,31 @@ pub trait Pass<Buffers, DebugOutput> {
             "dispatch must issue at least one group"
         );
 
+        crate::gpu::poll::note_dispatch(Self::NAME);
+        if let Some(records) = ctx.dispatch_records.as_deref_mut() {
+            records.push(DispatchRecord::direct(Self::NAME, (gx, gy, gz), input));
+        }
+
         if !defer_compute_direct_bind_groups(pd, &bind_groups, (gx, gy, gz)) {
-            let mut pass = ctx
-                .encoder
-                .begin_compute_pass(&wgpu::ComputePassDescriptor {
-                    label: Some(Self::NAME),
-                    timestamp_writes: None,
-                });
-            pass.set_pipeline(&pd.pipeline);
-            for (i, bg) in bind_groups.iter().enumerate() {
-                pass.set_bind_group(i as u32, Option::<&wgpu::BindGroup>::Some(&*bg), &[]);
+            if ctx.debug_groups {
+                ctx.encoder.push_debug_group(Self::NAME);
+            }
+            {
+                let mut pass = ctx
+                    .encoder
+                    .begin_compute_pass(&wgpu::ComputePassDescriptor {
+                        label: Some(Self::NAME),
+                        timestamp_writes: None,
+                    });
+                pass.set_pipeline(&pd.pipeline);
+                for (i, bg) in bind_groups.iter().enumerate() {
+                    pass.set_bind_group(i as u32, Option::<&wgpu::BindGroup>::Some(bg), &[]);
+                }
+                pass.dispatch_workgroups(gx, gy, gz);
+            }
+            if ctx.debug_groups {
+                ctx.encoder.pop_debug_group();
             }
-            pass.dispatch_workgroups(gx, gy, gz);
         }
 
         if let Some(t) = ctx.maybe_timer.as_deref_mut() {
@@ -1632,7 +2442,7 @@ pub trait Pass<Buffers, DebugOutput> {
         }
 
         if let Some(d) = ctx.maybe_dbg.as_deref_mut() {
-            self.record_debug(ctx.device, ctx.encoder, ctx.buffers, d);
+            self.record_declared_debug(ctx.device, ctx.encoder, ctx.buffers, d);
         }
         Ok(())
     }
@@ -1642,12 +2452,17 @@ pub trait Pass<Buffers, DebugOutput> {
         &self,
         ctx: &mut PassContext<'a, Buffers, DebugOutput>,
         dispatch_args: &wgpu::Buffer,
-    ) -> Result<(), anyhow::Error> {
-        let use_scopes = validation_scopes_enabled();
+    ) -> Result<(), anyhow::Error>
+    where
+        Self: Sized,
+        DebugOutput: DebugSink,
+    {
+        let use_scopes = ctx.validation_scopes;
 
         let validation_scope = validation_scope(ctx.device, use_scopes);
 
         let pd = self.data();
+        Self::check_dispatch(pd.thread_group_size)?;
         let bind_groups = bind_groups_for_pass::<Self, Buffers, DebugOutput>(
             ctx.device,
             self,
@@ -1655,18 +2470,31 @@ pub trait Pass<Buffers, DebugOutput> {
             ctx.bg_cache.as_deref_mut(),
         )?;
 
+        crate::gpu::poll::note_dispatch(Self::NAME);
+        if let Some(records) = ctx.dispatch_records.as_deref_mut() {
+            records.push(DispatchRecord::indirect(Self::NAME));
+        }
+
         if !defer_compute_indirect_bind_groups(pd, &bind_groups, dispatch_args) {
-            let mut pass = ctx
-                .encoder
-                .begin_compute_pass(&wgpu::ComputePassDescriptor {
-                    label: Some(Self::NAME),
-                    timestamp_writes: None,
-                });
-            pass.set_pipeline(&pd.pipeline);
-            for (i, bg) in bind_groups.iter().enumerate() {
-                pass.set_bind_group(i as u32, Option::<&wgpu::BindGroup>::Some(&*bg), &[]);
+            if ctx.debug_groups {
+                ctx.encoder.push_debug_group(Self::NAME);
+            }
+            {
+                let mut pass = ctx
+                    .encoder
+                    .begin_compute_pass(&wgpu::ComputePassDescriptor {
+                        label: Some(Self::NAME),
+                        timestamp_writes: None,
+                    });
+                pass.set_pipeline(&pd.pipeline);
+                for (i, bg) in bind_groups.iter().enumerate() {
+                    pass.set_bind_group(i as u32, Option::<&wgpu::BindGroup>::Some(bg), &[]);
+                }
+                pass.dispatch_workgroups_indirect(dispatch_args, 0);
+            }
+            if ctx.debug_groups {
+                ctx.encoder.pop_debug_group();
             }
-            pass.dispatch_workgroups_indirect(dispatch_args, 0);
         }
 
         if let Some(t) = ctx.maybe_timer.as_deref_mut() {
@@ -1678,11 +2506,27 @@ pub trait Pass<Buffers, DebugOutput> {
         }
 
         if let Some(d) = ctx.maybe_dbg.as_deref_mut() {
-            self.record_debug(ctx.device, ctx.encoder, ctx.buffers, d);
+            self.record_declared_debug(ctx.device, ctx.encoder, ctx.buffers, d);
         }
         Ok(())
     }
 
+    /// Declares [`Pass::expected_debug_keys`] in `dbg`, then records the
+    /// pass's captures with [`Pass::record_debug`].
+    fn record_declared_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &Buffers,
+        dbg: &mut DebugOutput,
+    ) where
+        Self: Sized,
+        DebugOutput: DebugSink,
+    {
+        dbg.declare_keys(Self::NAME, Self::expected_debug_keys());
+        self.record_debug(device, encoder, b, dbg);
+    }
+
     /// Records any phase-specific debug readback work after the main dispatch.
     fn record_debug(
         &self,
@@ -0,0 +1,258 @@
+//! Bounded device waits that fail with a diagnostic dump instead of hanging.
+//!
+//! A wedged driver or a map request for work that was never submitted
+//! otherwise blocks forever with no output. Blocking waits go through
+//! [`wait_with_timeout`] and surface [`GpuError::Timeout`] with the recent
+//! submissions, recently recorded passes, and live buffer ledger attached.
+//!
+//! [`wait_with_timeout`]: crate::gpu::poll::wait_with_timeout
+//! [`GpuError::Timeout`]: crate::gpu::poll::GpuError::Timeout
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Result, anyhow};
+
+const RECENT_CAPACITY: usize = 32;
+const REPORTED_BUFFER_LABELS: usize = 8;
+
+static RECENT_SUBMISSIONS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static RECENT_PASSES: Mutex<VecDeque<&'static str>> = Mutex::new(VecDeque::new());
+
+fn push_recent<T>(ring: &Mutex<VecDeque<T>>, value: T) {
+    let mut ring = ring
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if ring.len() == RECENT_CAPACITY {
+        ring.pop_front();
+    }
+    ring.push_back(value);
+}
+
+/// Remembers a queue submission for timeout diagnostics.
+pub(crate) fn note_submission(label: &str) {
+    push_recent(&RECENT_SUBMISSIONS, label.to_string());
+}
+
+/// Remembers a recorded dispatch for timeout diagnostics.
+pub(crate) fn note_dispatch(pass: &'static str) {
+    push_recent(&RECENT_PASSES, pass);
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Result of one bounded device wait.
+pub enum PollOutcome {
+    /// All submitted work finished and pending callbacks ran.
+    Complete,
+    /// The wait gave up before the device went idle.
+    TimedOut { after: Duration },
+    /// wgpu rejected the wait.
+    Failed(String),
+}
+
+/// Blocks until submitted work completes or `timeout` elapses; `None` waits forever.
+pub fn wait_with_timeout(device: &wgpu::Device, timeout: Option<Duration>) -> PollOutcome {
+    let started = Instant::now();
+    match device.poll(wgpu::PollType::Wait {
+        submission_index: None,
+        timeout,
+    }) {
+        Ok(_) => {
+            crate::gpu::teardown::drain_graveyard();
+            PollOutcome::Complete
+        }
+        Err(wgpu::PollError::Timeout) => PollOutcome::TimedOut {
+            after: started.elapsed(),
+        },
+        Err(err) => PollOutcome::Failed(err.to_string()),
+    }
+}
+
+/// Waits under the process [`DeviceOptions`](crate::gpu::device::DeviceOptions)
+/// timeout, turning a timeout into [`GpuError::Timeout`].
+pub(crate) fn wait_for_submitted_work(device: &wgpu::Device, label: &str) -> Result<()> {
+    let timeout = crate::gpu::device::device_options().wait_timeout;
+    match wait_with_timeout(device, timeout) {
+        PollOutcome::Complete => Ok(()),
+        PollOutcome::TimedOut { after } => Err(GpuError::timeout(label, after, None).into()),
+        PollOutcome::Failed(err) => Err(anyhow!("{label} GPU wait failed: {err}")),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// What was in flight when a GPU wait timed out.
+pub struct TimeoutDiagnostics {
+    /// Wait site that gave up.
+    pub label: String,
+    /// Most recent queue submissions, oldest first.
+    pub recent_submissions: Vec<String>,
+    /// Most recently recorded passes, oldest first.
+    pub recent_passes: Vec<String>,
+    /// Live tracked buffer bytes.
+    pub live_buffer_bytes: u64,
+    /// Largest live tracked buffers by label, largest first.
+    pub largest_buffers: Vec<(String, u64)>,
+    /// Whether the pending map callback ran; `None` when the site does not track it.
+    pub map_callback_fired: Option<bool>,
+}
+
+impl TimeoutDiagnostics {
+    /// Snapshots the submission/pass rings and buffer ledger.
+    pub fn capture(label: &str, map_callback_fired: Option<bool>) -> Self {
+        let recent_submissions = RECENT_SUBMISSIONS
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .iter()
+            .cloned()
+            .collect();
+        let recent_passes = RECENT_PASSES
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .iter()
+            .map(|pass| pass.to_string())
+            .collect();
+        let mut largest_buffers = crate::gpu::buffers::tracked_buffer_allocation_stats_by_label()
+            .into_iter()
+            .map(|row| (row.label.to_string(), row.bytes))
+            .collect::<Vec<_>>();
+        largest_buffers.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        largest_buffers.truncate(REPORTED_BUFFER_LABELS);
+        Self {
+            label: label.to_string(),
+            recent_submissions,
+            recent_passes,
+            live_buffer_bytes: crate::gpu::buffers::tracked_buffer_allocation_stats().bytes,
+            largest_buffers,
+            map_callback_fired,
+        }
+    }
+}
+
+impl fmt::Display for TimeoutDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn list(items: &[String]) -> String {
+            if items.is_empty() {
+                "(none)".to_string()
+            } else {
+                items.join(" -> ")
+            }
+        }
+        writeln!(f, "  wait: {}", self.label)?;
+        let callback = match self.map_callback_fired {
+            Some(true) => "fired",
+            Some(false) => "never fired",
+            None => "not tracked",
+        };
+        writeln!(f, "  map callback: {callback}")?;
+        writeln!(
+            f,
+            "  recent submissions: {}",
+            list(&self.recent_submissions)
+        )?;
+        writeln!(f, "  recent passes: {}", list(&self.recent_passes))?;
+        write!(f, "  live buffers: {} bytes", self.live_buffer_bytes)?;
+        for (label, bytes) in &self.largest_buffers {
+            write!(f, "\n    {label}: {bytes} bytes")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Structured GPU failures surfaced through `anyhow`.
+pub enum GpuError {
+    /// A blocking wait exceeded the configured timeout.
+    Timeout {
+        after: Duration,
+        diagnostics: Box<TimeoutDiagnostics>,
+    },
+}
+
+impl GpuError {
+    /// Builds a timeout error with a fresh diagnostics snapshot.
+    pub fn timeout(label: &str, after: Duration, map_callback_fired: Option<bool>) -> Self {
+        Self::Timeout {
+            after,
+            diagnostics: Box::new(TimeoutDiagnostics::capture(label, map_callback_fired)),
+        }
+    }
+}
+
+impl fmt::Display for GpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout { after, diagnostics } => write!(
+                f,
+                "GPU wait for {} timed out after {} ms\n{diagnostics}",
+                diagnostics.label,
+                after.as_millis()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GpuError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeout_report_lists_submissions_passes_and_buffers() {
+        let err = GpuError::Timeout {
+            after: Duration::from_millis(1500),
+            diagnostics: Box::new(TimeoutDiagnostics {
+                label: "parser.brackets".into(),
+                recent_submissions: vec!["lex.batch".into(), "parser.batch".into()],
+                recent_passes: vec!["llp_pairs".into(), "brackets_match".into()],
+                live_buffer_bytes: 4096,
+                largest_buffers: vec![("parser.out_sc".into(), 3072), ("token_count".into(), 4)],
+                map_callback_fired: Some(false),
+            }),
+        };
+
+        assert_eq!(
+            err.to_string(),
+            "GPU wait for parser.brackets timed out after 1500 ms\n\
+             \x20 wait: parser.brackets\n\
+             \x20 map callback: never fired\n\
+             \x20 recent submissions: lex.batch -> parser.batch\n\
+             \x20 recent passes: llp_pairs -> brackets_match\n\
+             \x20 live buffers: 4096 bytes\n\
+             \x20   parser.out_sc: 3072 bytes\n\
+             \x20   token_count: 4 bytes"
+        );
+    }
+
+    #[test]
+    fn empty_timeout_report_marks_missing_history() {
+        let diagnostics = TimeoutDiagnostics {
+            label: "lex.count".into(),
+            recent_submissions: Vec::new(),
+            recent_passes: Vec::new(),
+            live_buffer_bytes: 0,
+            largest_buffers: Vec::new(),
+            map_callback_fired: None,
+        };
+        let text = diagnostics.to_string();
+        assert!(text.contains("map callback: not tracked"), "{text}");
+        assert!(text.contains("recent submissions: (none)"), "{text}");
+        assert!(text.contains("recent passes: (none)"), "{text}");
+    }
+
+    #[test]
+    fn recent_rings_keep_the_newest_entries() {
+        let ring = Mutex::new(VecDeque::new());
+        for i in 0..RECENT_CAPACITY + 3 {
+            push_recent(&ring, i);
+        }
+        let ring = ring.into_inner().unwrap();
+        assert_eq!(ring.len(), RECENT_CAPACITY);
+        assert_eq!(ring.front(), Some(&3));
+        assert_eq!(ring.back(), Some(&(RECENT_CAPACITY + 2)));
+    }
+}
@@ -0,0 +1,13 @@
+use anyhow::Result;
+
+use crate::gpu::decode::decode_one;
+
+/// Decodes exactly `N` little-endian `u32` words from readback bytes.
+pub fn read_u32_words<const N: usize>(bytes: &[u8], context: &str) -> Result<[u32; N]> {
+    decode_one(bytes, context)
+}
+
+/// Decodes exactly `N` little-endian `i32` words from readback bytes.
+pub fn read_i32_words<const N: usize>(bytes: &[u8], context: &str) -> Result<[i32; N]> {
+    decode_one(bytes, context)
+}
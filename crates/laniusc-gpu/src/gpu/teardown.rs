@@ -0,0 +1,142 @@
+//! Orderly release of GPU resources that submitted work may still use.
+//!
+//! Dropping a lexer or parser while a lex is mid-flight (a cancelled wait, an
+//! application tearing down) would free buffers the GPU is still writing.
+//! `retire` instead fences the queue with `on_submitted_work_done`, which
+//! fires once every submission made before it has finished, and waits a
+//! short, bounded time for the fence. Resources whose fence has not fired by
+//! then are parked in a process-wide graveyard; the next completed
+//! [`wait_with_timeout`](crate::gpu::poll::wait_with_timeout), or an explicit
+//! [`drain_graveyard`], frees them.
+//!
+//! The graveyard is a plain `static`, so nothing in it is dropped at process
+//! exit: the OS reclaims the memory without a device poll racing the global
+//! device's own teardown.
+//!
+//! [`drain_graveyard`]: crate::gpu::teardown::drain_graveyard
+
+use std::{
+    any::Any,
+    sync::{
+        Arc,
+        Mutex,
+        PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+/// Longest a `Drop` waits for outstanding work before parking its resources.
+pub const DROP_WAIT: Duration = Duration::from_millis(50);
+
+struct Grave {
+    label: &'static str,
+    done: Arc<AtomicBool>,
+    _resources: Box<dyn Any + Send>,
+}
+
+static GRAVEYARD: Mutex<Vec<Grave>> = Mutex::new(Vec::new());
+
+/// Frees `resources` once all work submitted to `queue` so far has finished.
+///
+/// Waits up to `wait` for that work; if it is still running, `resources` go to
+/// the graveyard instead of blocking the caller. Returns whether they were
+/// freed before returning.
+pub(crate) fn retire<T: Send + 'static>(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    label: &'static str,
+    resources: T,
+    wait: Duration,
+) -> bool {
+    let done = Arc::new(AtomicBool::new(false));
+    let fence = Arc::clone(&done);
+    queue.on_submitted_work_done(move || fence.store(true, Ordering::Release));
+    if !done.load(Ordering::Acquire) {
+        // Completion only runs the fence callback; a timeout or a lost device
+        // leaves the resources to the graveyard.
+        let _ = crate::gpu::poll::wait_with_timeout(device, Some(wait));
+    }
+    if done.load(Ordering::Acquire) {
+        drop(resources);
+        drain_graveyard();
+        return true;
+    }
+    log::debug!(
+        target: crate::logging::GPU,
+        "{label}: submitted work still running after {wait:?}; parking its buffers"
+    );
+    GRAVEYARD
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(Grave {
+            label,
+            done,
+            _resources: Box::new(resources),
+        });
+    false
+}
+
+/// Takes a mutex's value even if a panic poisoned it, as `Drop` must not panic.
+pub(crate) fn take_poisoned<T: Default>(mutex: &mut Mutex<T>) -> T {
+    std::mem::take(mutex.get_mut().unwrap_or_else(PoisonError::into_inner))
+}
+
+/// Frees parked resources whose work has finished and returns how many are
+/// still waiting.
+///
+/// Fences only fire while the device is polled, so call this after a poll.
+pub fn drain_graveyard() -> usize {
+    let finished: Vec<Grave> = {
+        let mut graves = GRAVEYARD.lock().unwrap_or_else(PoisonError::into_inner);
+        let (finished, waiting) = std::mem::take(&mut *graves)
+            .into_iter()
+            .partition(|grave| grave.done.load(Ordering::Acquire));
+        *graves = waiting;
+        finished
+    };
+    for grave in &finished {
+        log::trace!(target: crate::logging::GPU, "{}: freeing parked buffers", grave.label);
+    }
+    // Dropped outside the lock: freeing wgpu resources may take the device's.
+    drop(finished);
+    graveyard_len()
+}
+
+/// Resource sets parked until their work finishes.
+pub fn graveyard_len() -> usize {
+    GRAVEYARD
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Flag(Arc<AtomicBool>);
+
+    impl Drop for Flag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::Release);
+        }
+    }
+
+    #[test]
+    fn graves_are_freed_only_after_their_fence() {
+        let done = Arc::new(AtomicBool::new(false));
+        let freed = Arc::new(AtomicBool::new(false));
+        GRAVEYARD.lock().unwrap().push(Grave {
+            label: "test",
+            done: Arc::clone(&done),
+            _resources: Box::new(Flag(Arc::clone(&freed))),
+        });
+
+        drain_graveyard();
+        assert!(!freed.load(Ordering::Acquire));
+        done.store(true, Ordering::Release);
+        drain_graveyard();
+        assert!(freed.load(Ordering::Acquire));
+    }
+}
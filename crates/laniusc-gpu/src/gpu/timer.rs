@@ -0,0 +1,479 @@
+//! Simple per-encode GPU timestamp helper. Not thread-safe; create per "frame"/encode.
+//!
+//! Stamps go into fixed-size query pools. When one fills, the timer adds
+//! another of the same size, up to [`MAX_TIMER_QUERIES`] queries in all;
+//! stamps past that are dropped and counted in [`TimerHealth::dropped`]
+//! rather than overwriting recorded ones.
+//!
+//! Each [`GpuTimer::resolve`] closes an epoch: stamps recorded after it belong
+//! to a later submission, whose timestamps need not share a timebase with the
+//! earlier ones. [`TimerReadout::intervals`] therefore only measures between
+//! stamps of one epoch, and clamps an interval whose end reads earlier than
+//! its start to zero, counting it in [`TimerHealth::negative_deltas`].
+//!
+//! [`MAX_TIMER_QUERIES`]: crate::gpu::timer::MAX_TIMER_QUERIES
+//! [`TimerHealth::dropped`]: crate::gpu::timer::TimerHealth::dropped
+//! [`GpuTimer::resolve`]: crate::gpu::timer::GpuTimer::resolve
+//! [`TimerReadout::intervals`]: crate::gpu::timer::TimerReadout::intervals
+//! [`TimerHealth::negative_deltas`]: crate::gpu::timer::TimerHealth::negative_deltas
+
+use log::warn;
+use wgpu;
+
+use crate::gpu::decode::decode_all;
+
+/// Default minimum span duration printed by timing helpers.
+pub const MINIMUM_TIME_TO_NOT_ELIDE_MS: f64 = 0.2;
+
+/// Most timestamp queries one [`GpuTimer`] records, across all its pools.
+pub const MAX_TIMER_QUERIES: u32 = 4096;
+
+/// One resolved timestamp.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimerStamp {
+    /// Label the stamp was recorded with.
+    pub label: String,
+    /// Resolve epoch the stamp was recorded in.
+    pub epoch: u32,
+    /// Raw timestamp, in ticks of [`GpuTimer::period_ns`].
+    pub ticks: u64,
+}
+
+/// GPU time between two stamps of one epoch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimerInterval {
+    /// Label of the stamp that ends the interval.
+    pub label: String,
+    /// Resolve epoch of both stamps.
+    pub epoch: u32,
+    /// Sum of the earlier intervals of the readout, in nanoseconds.
+    pub start_ns: f64,
+    /// Length of the interval, in nanoseconds; `0` when clamped.
+    pub gpu_ns: f64,
+    /// The end stamp read earlier than the start and the length was clamped.
+    pub clamped: bool,
+}
+
+/// How faithfully a [`GpuTimer`] recorded what it was asked to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TimerHealth {
+    /// Stamps requested with [`GpuTimer::stamp`] since the last reset.
+    pub requested: u32,
+    /// Stamps written to a query and read back.
+    pub recorded: u32,
+    /// Stamps past [`MAX_TIMER_QUERIES`] that were not written.
+    pub dropped: u32,
+    /// Intervals clamped to zero because their end read before their start.
+    pub negative_deltas: u32,
+    /// Resolve epochs the recorded stamps span.
+    pub epochs: u32,
+}
+
+impl TimerHealth {
+    /// Whether every requested stamp was recorded and no interval was clamped.
+    pub fn is_clean(&self) -> bool {
+        self.dropped == 0 && self.negative_deltas == 0
+    }
+
+    /// Adds `other`'s counts, as for the timers of several chunks.
+    pub fn add(&mut self, other: TimerHealth) {
+        self.requested = self.requested.saturating_add(other.requested);
+        self.recorded = self.recorded.saturating_add(other.recorded);
+        self.dropped = self.dropped.saturating_add(other.dropped);
+        self.negative_deltas = self.negative_deltas.saturating_add(other.negative_deltas);
+        self.epochs = self.epochs.saturating_add(other.epochs);
+    }
+}
+
+/// Stamps read back from a [`GpuTimer`], with its [`TimerHealth`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TimerReadout {
+    /// Recorded stamps in recording order.
+    pub stamps: Vec<TimerStamp>,
+    pub health: TimerHealth,
+}
+
+impl TimerReadout {
+    /// Wraps `stamps` recorded out of `requested`, counting dropped stamps,
+    /// epochs and negative intervals.
+    pub fn new(stamps: Vec<TimerStamp>, requested: u32) -> Self {
+        let recorded = stamps.len() as u32;
+        let mut readout = Self {
+            stamps,
+            health: TimerHealth {
+                requested,
+                recorded,
+                dropped: requested.saturating_sub(recorded),
+                ..TimerHealth::default()
+            },
+        };
+        readout.health.epochs = readout
+            .stamps
+            .windows(2)
+            .filter(|pair| pair[0].epoch != pair[1].epoch)
+            .count() as u32
+            + u32::from(!readout.stamps.is_empty());
+        readout.health.negative_deltas = readout
+            .intervals(1.0)
+            .iter()
+            .filter(|interval| interval.clamped)
+            .count() as u32;
+        readout
+    }
+
+    /// Intervals ending at each stamp but the first of its epoch, with
+    /// `period_ns` nanoseconds per tick.
+    ///
+    /// The first stamp of an epoch only sets that epoch's baseline. A stamp
+    /// reading earlier than the latest one before it ends a clamped,
+    /// zero-length interval and does not move the baseline back.
+    pub fn intervals(&self, period_ns: f32) -> Vec<TimerInterval> {
+        let period_ns = f64::from(period_ns);
+        let mut out = Vec::with_capacity(self.stamps.len());
+        let mut total_ns = 0.0;
+        let mut prev: Option<&TimerStamp> = None;
+        let mut latest = 0u64;
+        for stamp in &self.stamps {
+            match prev {
+                Some(p) if p.epoch == stamp.epoch => {
+                    let clamped = stamp.ticks < latest;
+                    let gpu_ns = stamp.ticks.saturating_sub(latest) as f64 * period_ns;
+                    out.push(TimerInterval {
+                        label: stamp.label.clone(),
+                        epoch: stamp.epoch,
+                        start_ns: total_ns,
+                        gpu_ns,
+                        clamped,
+                    });
+                    total_ns += gpu_ns;
+                    latest = latest.max(stamp.ticks);
+                }
+                _ => latest = stamp.ticks,
+            }
+            prev = Some(stamp);
+        }
+        out
+    }
+}
+
+/// One query set with the buffers its timestamps resolve through.
+struct QueryPool {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+}
+
+impl QueryPool {
+    fn new(device: &wgpu::Device, count: u32) -> Self {
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("LaniusTimestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count,
+        });
+
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("TimestampResolve"),
+            size: (count as u64) * 8,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("TimestampReadback"),
+            size: (count as u64) * 8,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+        }
+    }
+}
+
+/// A timer for measuring GPU execution time.
+pub struct GpuTimer {
+    device: wgpu::Device,
+    period_in_nanoseconds: f32,
+    pools: Vec<QueryPool>,
+    next: u32,
+    capacity: u32,
+    requested: u32,
+    epoch: u32,
+    resolved: bool,
+    per_round: bool,
+    /// Labels recorded for each timestamp query.
+    pub stamp_labels: Vec<String>,
+    stamp_epochs: Vec<u32>,
+}
+
+impl GpuTimer {
+    /// Creates a new GpuTimer whose query pools hold `max_queries` stamps
+    /// each.
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, max_queries: u32) -> Self {
+        let capacity = max_queries.clamp(1, MAX_TIMER_QUERIES);
+        Self {
+            device: device.clone(),
+            period_in_nanoseconds: queue.get_timestamp_period(),
+            pools: vec![QueryPool::new(device, capacity)],
+            next: 0,
+            capacity,
+            requested: 0,
+            epoch: 0,
+            resolved: false,
+            per_round: false,
+            stamp_labels: vec![],
+            stamp_epochs: vec![],
+        }
+    }
+
+    /// Enables [`stamp_round`](Self::stamp_round), which multi-round passes
+    /// call once per round. Off by default, so each logical pass is stamped
+    /// once.
+    pub fn set_per_round(&mut self, enabled: bool) {
+        self.per_round = enabled;
+    }
+
+    /// Whether [`stamp_round`](Self::stamp_round) records stamps.
+    pub fn per_round(&self) -> bool {
+        self.per_round
+    }
+
+    /// Records a timestamp with the given label, returning its query index,
+    /// or `None` when [`MAX_TIMER_QUERIES`] stamps are already recorded.
+    pub fn stamp(
+        &mut self,
+        enc: &mut wgpu::CommandEncoder,
+        label: impl Into<String>,
+    ) -> Option<u32> {
+        self.requested = self.requested.saturating_add(1);
+        if self.resolved {
+            // Stamps after a resolve land in a later submission.
+            self.epoch += 1;
+            self.resolved = false;
+        }
+        if self.next >= MAX_TIMER_QUERIES {
+            if self.requested == MAX_TIMER_QUERIES + 1 {
+                warn!("GPU timer is full at {MAX_TIMER_QUERIES} stamps; dropping later stamps");
+            }
+            return None;
+        }
+        let pool = (self.next / self.capacity) as usize;
+        if pool == self.pools.len() {
+            self.pools.push(QueryPool::new(&self.device, self.capacity));
+        }
+        let index = self.next;
+        self.next += 1;
+        self.stamp_labels.push(label.into());
+        self.stamp_epochs.push(self.epoch);
+        enc.write_timestamp(&self.pools[pool].query_set, index % self.capacity);
+        Some(index)
+    }
+
+    /// Records a timestamp for one round of a multi-round pass when
+    /// per-round stamping is on; otherwise does nothing.
+    pub fn stamp_round(
+        &mut self,
+        enc: &mut wgpu::CommandEncoder,
+        label: impl Into<String>,
+    ) -> Option<u32> {
+        if self.per_round {
+            self.stamp(enc, label)
+        } else {
+            None
+        }
+    }
+
+    /// Resets the timer.
+    pub fn reset(&mut self) {
+        self.stamp_labels.clear();
+        self.stamp_epochs.clear();
+        self.next = 0;
+        self.requested = 0;
+        self.epoch = 0;
+        self.resolved = false;
+    }
+
+    /// Resolves the timestamp queries and closes the current epoch.
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        self.resolved = true;
+        for (pool, count) in self.pool_counts() {
+            encoder.resolve_query_set(&pool.query_set, 0..count, &pool.resolve_buffer, 0);
+            encoder.copy_buffer_to_buffer(
+                &pool.resolve_buffer,
+                0,
+                &pool.readback_buffer,
+                0,
+                (count as u64) * 8,
+            );
+        }
+    }
+
+    /// Attempts to read the recorded timestamps.
+    pub fn try_read(&self, device: &wgpu::Device) -> Option<TimerReadout> {
+        if self.next == 0 {
+            return None;
+        }
+        let pools = self.pool_counts().collect::<Vec<_>>();
+        let mut receivers = Vec::with_capacity(pools.len());
+        crate::gpu::passes_core::trace_gpu_progress("gpu.timer.readback.map.start");
+        for (pool, count) in &pools {
+            let (sender, receiver) = std::sync::mpsc::channel();
+            pool.readback_buffer.slice(..(*count as u64) * 8).map_async(
+                wgpu::MapMode::Read,
+                move |v| {
+                    if let Err(err) = sender.send(v) {
+                        warn!("failed to send timer readback completion signal: {err}");
+                    }
+                },
+            );
+            receivers.push(receiver);
+        }
+        crate::gpu::passes_core::trace_gpu_progress("gpu.timer.readback.map.queued");
+        if let Err(err) =
+            crate::gpu::passes_core::wait_for_map_progress(device, "gpu.timer.readback")
+        {
+            warn!("timer readback wait failed: {err:#}");
+            return None;
+        }
+
+        let mapped = receivers
+            .iter()
+            .all(|receiver| matches!(receiver.try_recv(), Ok(Ok(()))));
+        let mut vals = Vec::with_capacity(self.next as usize);
+        for (pool, count) in &pools {
+            if !mapped {
+                break;
+            }
+            let slice = pool.readback_buffer.slice(..(*count as u64) * 8);
+            let data = slice.get_mapped_range();
+            vals.extend(decode_all::<u64>(&data));
+            drop(data);
+        }
+        for (pool, _) in &pools {
+            pool.readback_buffer.unmap();
+        }
+        if !mapped {
+            return None;
+        }
+
+        let stamps = vals
+            .into_iter()
+            .zip(&self.stamp_labels)
+            .zip(&self.stamp_epochs)
+            .map(|((ticks, label), &epoch)| TimerStamp {
+                label: label.clone(),
+                epoch,
+                ticks,
+            })
+            .collect();
+        Some(TimerReadout::new(stamps, self.requested))
+    }
+
+    /// Returns the timestamp period in nanoseconds.
+    pub fn period_ns(&self) -> f32 {
+        self.period_in_nanoseconds
+    }
+
+    /// Pools holding recorded stamps, with how many each holds.
+    fn pool_counts(&self) -> impl Iterator<Item = (&QueryPool, u32)> {
+        let capacity = self.capacity;
+        let next = self.next;
+        self.pools
+            .iter()
+            .enumerate()
+            .map(move |(i, pool)| (pool, next.saturating_sub(i as u32 * capacity).min(capacity)))
+            .filter(|&(_, count)| count > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stamps(raw: &[(&str, u32, u64)]) -> Vec<TimerStamp> {
+        raw.iter()
+            .map(|&(label, epoch, ticks)| TimerStamp {
+                label: label.to_string(),
+                epoch,
+                ticks,
+            })
+            .collect()
+    }
+
+    fn lengths(intervals: &[TimerInterval]) -> Vec<(&str, f64, bool)> {
+        intervals
+            .iter()
+            .map(|i| (i.label.as_str(), i.gpu_ns, i.clamped))
+            .collect()
+    }
+
+    #[test]
+    fn intervals_follow_monotonic_stamps() {
+        let readout = TimerReadout::new(stamps(&[("BEGIN", 0, 10), ("a", 0, 14), ("b", 0, 20)]), 3);
+        let intervals = readout.intervals(2.0);
+        assert_eq!(lengths(&intervals), [("a", 8.0, false), ("b", 12.0, false)]);
+        assert_eq!(intervals[1].start_ns, 8.0);
+        assert!(readout.health.is_clean());
+        assert_eq!(readout.health.epochs, 1);
+    }
+
+    #[test]
+    fn a_stamp_reading_backwards_is_clamped_without_moving_the_baseline() {
+        let readout =
+            TimerReadout::new(stamps(&[("BEGIN", 0, 100), ("a", 0, 90), ("b", 0, 130)]), 3);
+        assert_eq!(
+            lengths(&readout.intervals(1.0)),
+            [("a", 0.0, true), ("b", 30.0, false)]
+        );
+        assert_eq!(readout.health.negative_deltas, 1);
+        assert!(!readout.health.is_clean());
+    }
+
+    #[test]
+    fn epochs_never_share_an_interval() {
+        // The second submission's timebase starts above the first's end, as
+        // some drivers report; the gap between them must not become a pass.
+        let readout = TimerReadout::new(
+            stamps(&[
+                ("BEGIN", 0, 1_000),
+                ("a", 0, 1_010),
+                ("BEGIN", 1, 5_000_000),
+                ("b", 1, 5_000_004),
+                // And one whose timebase restarts below the first's.
+                ("BEGIN", 2, 3),
+                ("c", 2, 5),
+            ]),
+            6,
+        );
+        let intervals = readout.intervals(1.0);
+        assert_eq!(
+            lengths(&intervals),
+            [("a", 10.0, false), ("b", 4.0, false), ("c", 2.0, false)]
+        );
+        assert_eq!(intervals[2].start_ns, 14.0);
+        assert_eq!(readout.health.epochs, 3);
+        assert_eq!(readout.health.negative_deltas, 0);
+    }
+
+    #[test]
+    fn dropped_stamps_are_counted() {
+        let readout = TimerReadout::new(stamps(&[("BEGIN", 0, 0), ("a", 0, 1)]), 5);
+        assert_eq!(readout.health.recorded, 2);
+        assert_eq!(readout.health.dropped, 3);
+        assert!(!readout.health.is_clean());
+
+        let mut summed = readout.health;
+        summed.add(readout.health);
+        assert_eq!((summed.requested, summed.dropped), (10, 6));
+    }
+
+    #[test]
+    fn empty_readouts_have_no_epochs_or_intervals() {
+        let readout = TimerReadout::new(Vec::new(), 0);
+        assert!(readout.intervals(1.0).is_empty());
+        assert_eq!(readout.health, TimerHealth::default());
+    }
+}
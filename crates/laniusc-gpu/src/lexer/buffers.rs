@@ -0,0 +1,552 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::{LexParams, passes::ScanParams};
+use crate::{
+    gpu::{
+        buffers::{BufferPlan, LaniusBuffer, SplitBinding},
+        limits::LimitError,
+    },
+    lexer::{
+        constants::{
+            COMPRESSED_ESCAPE_RATE_LIMIT,
+            DFA_BLOCK_WIDTH,
+            DFA_CHUNK_COUNT,
+            LEX_ANALYSIS_WORDS,
+            N_STATES,
+            PAIR_BLOCK_WIDTH,
+            SKIP_KIND_SLOTS,
+        },
+        driver::DfaTableBuffers,
+        kind_packing::KindPacking,
+        layout::LAYOUT_WORDS_PER_TOKEN,
+        util::{COMPRESSED_ESCAPE_WORDS, compute_rounds},
+    },
+};
+
+static NEXT_BUFFERS_GENERATION: AtomicU64 = AtomicU64::new(1);
+
+/// Remedy named when an input's buffers pass the device limits.
+pub const CHUNKED_SUGGESTION: &str = "lex it in ranges with GpuLexer::lex_range and parse the \
+     tokens with GpuParser::parse_chunked";
+
+/// Resident GPU buffers used by one lexer instance.
+///
+/// These buffers are reused across lexing calls when capacity permits. The
+/// driver updates runtime sizes and input metadata before recording passes.
+pub struct GpuBuffers {
+    /// Allocation generation; bind groups cached per round are keyed by it.
+    pub generation: u64,
+    /// Current byte length, not including word-alignment padding.
+    pub n: u32,
+    /// Number of 256-byte DFA blocks for the current input.
+    pub nb_dfa: u32,
+    /// Number of 256-byte pair-scan blocks for the current input.
+    pub nb_sum: u32,
+    /// Host-visible copy of `parser_feature_flags` from the last count boundary.
+    pub parser_feature_flags_value: u32,
+    /// Whether the current input records the `escape_spans` pass after the
+    /// lexer steps.
+    pub capture_string_escapes: bool,
+    /// Whether the current input records the layout-fact passes after the
+    /// lexer steps.
+    pub capture_layout: bool,
+    /// Whether the current input records the `tokens_compress` pass after
+    /// the lexer steps.
+    pub compress_readback: bool,
+    /// Lane width of `tok_types`; picks which variant of the passes that
+    /// write and read it is recorded.
+    pub kind_packing: KindPacking,
+    /// Total bytes of every buffer allocated here.
+    pub allocated_bytes: u64,
+
+    /// Uniform parameters shared by lexer shaders.
+    pub params: LaniusBuffer<super::LexParams>,
+    /// Per-round `ScanParams` uniforms shared by the DFA and pair block scans.
+    pub(super) scan_params: Vec<LaniusBuffer<ScanParams>>,
+
+    /// Uploaded source bytes, padded to a word boundary.
+    pub in_bytes: LaniusBuffer<u8>,
+    /// Packed DFA transition and emit table, two `u16` entries per `u32`;
+    /// this and the other two tables are shared with every allocation.
+    pub next_emit: LaniusBuffer<u32>,
+    /// Packed byte-indexed DFA transition table, four states per `u32`.
+    pub next_u8: LaniusBuffer<u32>,
+    /// Map from DFA accepting state to token kind or `INVALID_TOKEN`.
+    pub token_map: LaniusBuffer<u32>,
+
+    /// Ping buffer for DFA block-prefix scans.
+    pub dfa_02_ping: LaniusBuffer<u32>,
+    /// Pong buffer for DFA block-prefix scans.
+    pub dfa_02_pong: LaniusBuffer<u32>,
+    /// Per-block all-boundary counts written by the fused prefix pass of
+    /// [`LexPipeline::FusedPairSeed`](super::LexPipeline::FusedPairSeed).
+    pub block_totals_pair: LaniusBuffer<u32>,
+    /// Per-block DFA summaries retained for prefix application.
+    pub dfa_chunk_summaries: LaniusBuffer<u32>,
+    /// Packed pre-skip EMIT/EOF token kinds by byte boundary, laid out by
+    /// `kind_packing`.
+    pub tok_types: LaniusBuffer<u32>,
+    /// Packed boundary flags emitted by DFA prefix application.
+    pub flags_packed: LaniusBuffer<u32>,
+    /// DFA state after each byte, two `u16` states per `u32`; written only
+    /// when accept-state or string-escape capture is on.
+    pub dfa_states: LaniusBuffer<u32>,
+    /// Compact rank for every token boundary, including skipped tokens;
+    /// reused for the inclusive kept rank per all-boundary token once ALL
+    /// compaction has consumed it.
+    pub s_all_final: LaniusBuffer<u32>,
+    /// End positions for every token, in all-boundary order. The default
+    /// pipeline parks the inclusive kept rank per boundary here until kept
+    /// compaction has consumed it.
+    pub end_positions_all: LaniusBuffer<u32>,
+
+    /// End positions for kept tokens.
+    pub end_positions: LaniusBuffer<u32>,
+    /// Token kinds compacted to kept-token order.
+    pub types_compact: LaniusBuffer<u32>,
+    /// Index from kept tokens back to the all-boundary stream.
+    pub all_index_compact: LaniusBuffer<u32>,
+    /// Pre-skip token kind for every boundary, in all-boundary order;
+    /// `0xFFFF` marks a boundary closed from a non-accepting state.
+    pub types_all: LaniusBuffer<u32>,
+    /// Number of kept tokens produced by the current input.
+    pub token_count: LaniusBuffer<u32>,
+    /// Number of all-boundary tokens produced by the current input.
+    pub all_token_count: LaniusBuffer<u32>,
+    /// Conservative parser-family flags collected by the GPU token builder.
+    pub parser_feature_flags: LaniusBuffer<u32>,
+    /// `tokens_build` ordering check: `[violations, !first_violating_token]`.
+    pub token_order_status: LaniusBuffer<u32>,
+    /// `tokens_build` length check over the all-boundary stream:
+    /// `[tokens_over_limit, !first_all_index_over_limit]`.
+    pub token_len_status: LaniusBuffer<u32>,
+    /// `lexer_analyze` boundary statistics; see
+    /// [`analysis`](crate::lexer::analysis) for the word layout.
+    pub lex_analysis: LaniusBuffer<u32>,
+    /// Accept state of each kept token, two `u16` states per `u32`; written
+    /// only when accept-state capture is on.
+    pub accept_states: LaniusBuffer<u32>,
+    /// Number of escape spans `escape_spans` appended for the current input.
+    pub escape_span_count: LaniusBuffer<u32>,
+    /// `(start, len)` word pairs of escape spans in atomic arrival order;
+    /// written only when string-escape capture is on. Every escape takes at
+    /// least two bytes, opening quote included, so half the input bounds the
+    /// count.
+    pub escape_spans: LaniusBuffer<u32>,
+    /// Number of suspect tokens `tokens_build` appended for the current input.
+    pub suspect_count: LaniusBuffer<u32>,
+    /// `(kept index, SUSPECT_* classes)` word pairs in atomic arrival order;
+    /// written only in paranoia mode. Each kept token appends at most once.
+    pub suspect_tokens: LaniusBuffer<u32>,
+    /// Two words per kept token of newlines, indentation and line comments
+    /// before it; written only when layout-fact capture is on. See
+    /// [`layout`](crate::lexer::layout) for the word layout.
+    pub layout_facts: LaniusBuffer<u32>,
+    /// One packed word per kept token; written only when the tokens are read
+    /// back compressed.
+    pub compressed_tokens: LaniusBuffer<u32>,
+    /// Number of kept tokens `tokens_compress` escaped, including any past
+    /// the capacity of `compressed_escapes`.
+    pub compressed_escape_count: LaniusBuffer<u32>,
+    /// `(kept index, start, len)` word triples of escaped tokens in atomic
+    /// arrival order, with room for one per `COMPRESSED_ESCAPE_RATE_LIMIT`
+    /// input bytes; the driver reads raw tokens past that rate.
+    pub compressed_escapes: LaniusBuffer<u32>,
+
+    /// Final resident token records consumed by parser and readback paths.
+    pub tokens_out: LaniusBuffer<super::GpuToken>,
+    /// How `tokens_build_split` binds `tokens_out` when it passes the
+    /// device's storage binding limit; `None` when it fits one binding.
+    pub tokens_split: Option<SplitBinding>,
+    /// Storage binding limit of the device the buffers were allocated on.
+    pub storage_binding_limit: u64,
+    /// Number of source files represented in the current input.
+    pub source_file_count: LaniusBuffer<u32>,
+    /// Concatenated-input start byte for each source file.
+    pub source_file_start: LaniusBuffer<u32>,
+    /// Byte length for each source file.
+    pub source_file_len: LaniusBuffer<u32>,
+    /// Per-byte flag marking source-file starts.
+    pub source_file_start_flags: LaniusBuffer<u32>,
+    /// Per-byte flag marking source-file ends.
+    pub source_file_end_flags: LaniusBuffer<u32>,
+    /// Source-file index for each final token.
+    pub token_file_id: LaniusBuffer<u32>,
+}
+
+impl GpuBuffers {
+    /// Allocates lexer buffers for a byte capacity and source-file capacity.
+    ///
+    /// The returned buffers are sized for capacity. The driver sets `n`,
+    /// `nb_dfa`, `nb_sum`, input bytes, source-file metadata, and `LexParams`
+    /// before each pass recording. Fails before allocating anything when a
+    /// buffer would exceed the device limits, with `tokens_out` allowed to
+    /// pass the storage binding limit as a [`SplitBinding`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        n: u32,
+        source_file_capacity: u32,
+        start_state: u32,
+        tables: DfaTableBuffers,
+        skip_kinds: [u32; SKIP_KIND_SLOTS],
+        kind_packing: KindPacking,
+    ) -> anyhow::Result<Self> {
+        let plan = Self::plan(
+            n,
+            source_file_capacity,
+            start_state,
+            tables.token_map.count,
+            skip_kinds,
+            kind_packing,
+        );
+        let scan_rounds = Self::scan_rounds(n);
+        let allocated_bytes = plan.total_bytes();
+        let mut b = plan.materialize(device, Some(queue))?;
+        let tokens_out: LaniusBuffer<super::GpuToken> = b.take("tokens_out")?;
+        let limits = device.limits();
+        let tokens_split = SplitBinding::plan(
+            tokens_out.count,
+            tokens_out.byte_size as u64,
+            limits.max_storage_buffer_binding_size,
+            limits.min_storage_buffer_offset_alignment,
+        );
+
+        let buffers = Self {
+            generation: NEXT_BUFFERS_GENERATION.fetch_add(1, Ordering::Relaxed),
+            n,
+            nb_dfa: n.div_ceil(DFA_BLOCK_WIDTH),
+            nb_sum: n.div_ceil(PAIR_BLOCK_WIDTH),
+            parser_feature_flags_value: 0,
+            capture_string_escapes: false,
+            capture_layout: false,
+            compress_readback: false,
+            kind_packing,
+            allocated_bytes,
+            params: b.take("LexParams")?,
+            scan_params: (0..scan_rounds)
+                .map(|r| b.take(&format!("ScanParams[{r}]")))
+                .collect::<anyhow::Result<_>>()?,
+
+            in_bytes: b.take("in_bytes")?,
+            next_emit: tables.next_emit,
+            next_u8: tables.next_u8,
+            token_map: tables.token_map,
+
+            dfa_02_ping: b.take("block_ping")?,
+            dfa_02_pong: b.take("block_pong")?,
+            block_totals_pair: b.take("block_totals_pair")?,
+            dfa_chunk_summaries: b.take("dfa_chunk_summaries")?,
+            tok_types: b.take("tok_types")?,
+            flags_packed: b.take("flags_packed")?,
+            dfa_states: b.take("dfa_states")?,
+
+            s_all_final: b.take("s_all_final")?,
+            end_positions_all: b.take("end_positions_all")?,
+
+            end_positions: b.take("end_positions")?,
+            types_compact: b.take("types_compact")?,
+            all_index_compact: b.take("all_index_compact")?,
+            types_all: b.take("types_all")?,
+            token_count: b.take("token_count")?,
+            all_token_count: b.take("all_token_count")?,
+            parser_feature_flags: b.take("lexer.parser_feature_flags")?,
+            token_order_status: b.take("lexer.token_order_status")?,
+            token_len_status: b.take("lexer.token_len_status")?,
+            lex_analysis: b.take("lexer.analysis")?,
+            accept_states: b.take("lexer.accept_states")?,
+            escape_span_count: b.take("lexer.escape_span_count")?,
+            escape_spans: b.take("lexer.escape_spans")?,
+            suspect_count: b.take("lexer.suspect_count")?,
+            suspect_tokens: b.take("lexer.suspect_tokens")?,
+            layout_facts: b.take("lexer.layout_facts")?,
+            compressed_tokens: b.take("lexer.compressed_tokens")?,
+            compressed_escape_count: b.take("lexer.compressed_escape_count")?,
+            compressed_escapes: b.take("lexer.compressed_escapes")?,
+
+            tokens_out,
+            tokens_split,
+            storage_binding_limit: limits.max_storage_buffer_binding_size,
+            source_file_count: b.take("source_file_count")?,
+            source_file_start: b.take("source_file_start")?,
+            source_file_len: b.take("source_file_len")?,
+            source_file_start_flags: b.take("source_file_start_flags")?,
+            source_file_end_flags: b.take("source_file_end_flags")?,
+            token_file_id: b.take("token_file_id")?,
+        };
+        b.finish()?;
+        Ok(buffers)
+    }
+
+    /// Declares every per-input lexer buffer for a byte capacity without
+    /// allocating. The DFA tables for `n_states` states are shared across
+    /// allocations and not part of the plan; `tok_types` is sized for
+    /// `kind_packing`.
+    pub fn plan(
+        n: u32,
+        source_file_capacity: u32,
+        start_state: u32,
+        n_states: usize,
+        skip_kinds: [u32; SKIP_KIND_SLOTS],
+        kind_packing: KindPacking,
+    ) -> BufferPlan {
+        let nb_dfa = n.div_ceil(DFA_BLOCK_WIDTH);
+        let nb_sum = n.div_ceil(PAIR_BLOCK_WIDTH) as usize;
+        debug_assert!(n_states > 0, "token_map must not be empty");
+
+        let n_bytes = n as usize;
+        let per_block_count = N_STATES * (nb_dfa as usize);
+        let half_n = n.div_ceil(2) as usize;
+        let escape_capacity = n.div_ceil(COMPRESSED_ESCAPE_RATE_LIMIT) as usize;
+        let source_file_capacity = source_file_capacity.max(1) as usize;
+        let mut plan = BufferPlan::new();
+        // Input bytes are filled by the driver via queue.write_buffer.
+        plan.storage_bytes("in_bytes", n_bytes, n_bytes)
+            .storage::<u32>("block_ping", per_block_count)
+            .storage::<u32>("block_pong", per_block_count)
+            .storage::<u32>("block_totals_pair", nb_sum)
+            .storage::<u32>(
+                "dfa_chunk_summaries",
+                per_block_count * DFA_CHUNK_COUNT as usize,
+            )
+            .storage::<u32>("tok_types", kind_packing.words(n_bytes))
+            .storage::<u32>("flags_packed", n_bytes)
+            .storage::<u32>("dfa_states", half_n)
+            // end_excl_by_i eliminated (computed inline); pair scan reuses dfa_02 ping/pong
+            .storage::<u32>("s_all_final", n_bytes)
+            .storage::<u32>("end_positions_all", n_bytes)
+            .storage::<u32>("end_positions", n_bytes)
+            .storage::<u32>("types_compact", n_bytes)
+            .storage::<u32>("all_index_compact", n_bytes)
+            .storage::<u32>("types_all", n_bytes)
+            .storage::<u32>("token_count", 1)
+            .storage::<u32>("all_token_count", 1)
+            .storage::<u32>("lexer.parser_feature_flags", 1)
+            .storage::<u32>("lexer.token_order_status", 2)
+            .storage::<u32>("lexer.token_len_status", 2)
+            .storage::<u32>("lexer.analysis", LEX_ANALYSIS_WORDS as usize)
+            .storage::<u32>("lexer.accept_states", half_n)
+            .storage::<u32>("lexer.escape_span_count", 1)
+            .storage::<u32>("lexer.escape_spans", half_n * 2)
+            .storage::<u32>("lexer.suspect_count", 1)
+            .storage::<u32>("lexer.suspect_tokens", n_bytes * 2)
+            .storage::<u32>("lexer.layout_facts", n_bytes * LAYOUT_WORDS_PER_TOKEN)
+            .storage::<u32>("lexer.compressed_tokens", n_bytes)
+            .storage::<u32>("lexer.compressed_escape_count", 1)
+            .storage::<u32>(
+                "lexer.compressed_escapes",
+                escape_capacity * COMPRESSED_ESCAPE_WORDS,
+            )
+            .storage_splittable::<super::GpuToken>("tokens_out", n_bytes)
+            .storage::<u32>("source_file_count", 1)
+            .storage::<u32>("source_file_start", source_file_capacity)
+            .storage::<u32>("source_file_len", source_file_capacity)
+            .storage::<u32>("source_file_start_flags", n_bytes + 1)
+            .storage::<u32>("source_file_end_flags", n_bytes + 1)
+            .storage::<u32>("token_file_id", n_bytes)
+            .suggest(CHUNKED_SUGGESTION);
+
+        plan.uniform(
+            "LexParams",
+            &LexParams {
+                n,
+                m: n_states as u32,
+                start_state,
+                skip0: skip_kinds[0],
+                skip1: skip_kinds[1],
+                skip2: skip_kinds[2],
+                skip3: skip_kinds[3],
+                capture_accept_states: 0,
+                max_token_len: u32::MAX,
+                capture_string_escapes: 0,
+                suspect_classes: 0,
+                tokens_split_shift: 0,
+            },
+        );
+        // Round r of both block scans uses stride 1 << r and reads ping on even rounds.
+        for r in 0..Self::scan_rounds(n) {
+            plan.uniform(
+                format!("ScanParams[{r}]"),
+                &ScanParams {
+                    stride: 1u32 << r,
+                    use_ping_as_src: u32::from(r % 2 == 0),
+                },
+            );
+        }
+        plan
+    }
+
+    /// Number of pooled block-scan rounds for a byte capacity.
+    fn scan_rounds(n: u32) -> u32 {
+        compute_rounds(
+            n.div_ceil(DFA_BLOCK_WIDTH)
+                .max(n.div_ceil(PAIR_BLOCK_WIDTH)),
+        )
+    }
+
+    /// The bindings of `tokens_out` as the token kernels see it: the whole
+    /// buffer, or with a [`SplitBinding`] its low window as `tokens_out` and
+    /// the rest as `tokens_out_hi`.
+    pub(super) fn tokens_out_bindings(&self) -> Vec<(String, wgpu::BindingResource<'_>)> {
+        match self.tokens_split {
+            None => vec![("tokens_out".into(), self.tokens_out.as_entire_binding())],
+            Some(split) => {
+                let (low, high) =
+                    split.bindings(&self.tokens_out, self.tokens_out.byte_size as u64);
+                vec![("tokens_out".into(), low), ("tokens_out_hi".into(), high)]
+            }
+        }
+    }
+
+    /// Fails when `tokens_out` only fits the device as a [`SplitBinding`],
+    /// for consumers that bind it whole.
+    pub fn require_whole_tokens_out(&self) -> Result<(), LimitError> {
+        match self.tokens_split {
+            None => Ok(()),
+            Some(_) => Err(LimitError::InputExceedsDeviceLimits {
+                limit: "max_storage_buffer_binding_size",
+                needed: self.tokens_out.byte_size as u64,
+                granted: self.storage_binding_limit,
+                suggestion: CHUNKED_SUGGESTION,
+            }),
+        }
+    }
+
+    /// Returns the pooled `ScanParams` uniform for one block-scan round.
+    pub(super) fn scan_params_for_round(
+        &self,
+        round: u32,
+    ) -> anyhow::Result<&LaniusBuffer<ScanParams>> {
+        self.scan_params.get(round as usize).ok_or_else(|| {
+            anyhow::anyhow!(
+                "scan round {round} exceeds the {} pooled ScanParams uniforms",
+                self.scan_params.len()
+            )
+        })
+    }
+}
+
+impl From<LaniusBuffer<u8>> for LaniusBuffer<super::GpuToken> {
+    fn from(b: LaniusBuffer<u8>) -> Self {
+        let count = b.count;
+        b.reinterpret(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Byte sizes of the buffers as `GpuBuffers::new` sized them before the
+    /// port to `BufferPlan`.
+    fn legacy_sizes(n: u32, files: u32) -> Vec<(String, u64)> {
+        let n64 = u64::from(n);
+        let nb_dfa = u64::from(n.div_ceil(DFA_BLOCK_WIDTH));
+        let per_block = N_STATES as u64 * nb_dfa * 4;
+        let half = u64::from(n.div_ceil(2)) * 4;
+        let files = u64::from(files.max(1)) * 4;
+        let mut sizes = vec![
+            ("in_bytes", n64),
+            ("block_ping", per_block),
+            ("block_pong", per_block),
+            (
+                "block_totals_pair",
+                u64::from(n.div_ceil(PAIR_BLOCK_WIDTH)) * 4,
+            ),
+            (
+                "dfa_chunk_summaries",
+                per_block * u64::from(DFA_CHUNK_COUNT),
+            ),
+            ("tok_types", n64 * 4),
+            ("flags_packed", n64 * 4),
+            ("dfa_states", half),
+            ("s_all_final", n64 * 4),
+            ("end_positions_all", n64 * 4),
+            ("end_positions", n64 * 4),
+            ("types_compact", n64 * 4),
+            ("all_index_compact", n64 * 4),
+            ("types_all", n64 * 4),
+            ("token_count", 4),
+            ("all_token_count", 4),
+            ("lexer.parser_feature_flags", 4),
+            ("lexer.token_order_status", 8),
+            ("lexer.token_len_status", 8),
+            ("lexer.analysis", 28),
+            ("lexer.accept_states", half),
+            ("lexer.escape_span_count", 4),
+            ("lexer.escape_spans", half * 2),
+            ("lexer.suspect_count", 4),
+            ("lexer.suspect_tokens", n64 * 8),
+            ("lexer.layout_facts", n64 * 8),
+            ("lexer.compressed_tokens", n64 * 4),
+            ("lexer.compressed_escape_count", 4),
+            ("lexer.compressed_escapes", u64::from(n.div_ceil(4)) * 12),
+            ("tokens_out", n64 * 12),
+            ("source_file_count", 4),
+            ("source_file_start", files),
+            ("source_file_len", files),
+            ("source_file_start_flags", (n64 + 1) * 4),
+            ("source_file_end_flags", (n64 + 1) * 4),
+            ("token_file_id", n64 * 4),
+            ("LexParams", 48),
+        ]
+        .into_iter()
+        .map(|(label, bytes)| (label.to_string(), bytes))
+        .collect::<Vec<_>>();
+        let nb_sum = n.div_ceil(PAIR_BLOCK_WIDTH);
+        for r in 0..compute_rounds(n.div_ceil(DFA_BLOCK_WIDTH).max(nb_sum)) {
+            sizes.push((format!("ScanParams[{r}]"), 8));
+        }
+        sizes
+    }
+
+    #[test]
+    fn planned_sizes_match_legacy_sizing_across_input_sizes() {
+        let n_states = 7;
+        for n in [4, 8, 252, 256, 260, 4096, 65_540, 1 << 20] {
+            for files in [0, 1, 3, 1000] {
+                let plan = GpuBuffers::plan(
+                    n,
+                    files,
+                    0,
+                    n_states,
+                    [0; SKIP_KIND_SLOTS],
+                    KindPacking::Wide,
+                );
+                let planned: Vec<_> = plan
+                    .entries()
+                    .iter()
+                    .map(|e| (e.label.to_string(), e.byte_size))
+                    .collect();
+                let mut expected = legacy_sizes(n, files);
+                let mut actual = planned.clone();
+                expected.sort();
+                actual.sort();
+                assert_eq!(actual, expected, "n={n} files={files}");
+                assert!(
+                    plan.validate(&wgpu::Limits::default()).is_ok(),
+                    "n={n} files={files}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn narrow_kind_packing_halves_tok_types() {
+        let tok_types = |n, packing| {
+            GpuBuffers::plan(n, 1, 0, 7, [0; SKIP_KIND_SLOTS], packing)
+                .entry("tok_types")
+                .unwrap()
+                .byte_size
+        };
+        for n in [4, 260, 1 << 20] {
+            let wide = tok_types(n, KindPacking::Wide);
+            assert_eq!(wide, u64::from(n) * 4);
+            assert_eq!(
+                tok_types(n, KindPacking::Narrow),
+                u64::from(n.div_ceil(2)) * 4
+            );
+        }
+    }
+}
@@ -0,0 +1,6 @@
+// src/lexer/debug.rs
+
+//! Lexer passes record their debug captures under `lexer.*` keys of
+//! the shared [`DebugOutput`](crate::gpu::debug::DebugOutput) registry.
+
+pub use crate::gpu::debug::DebugOutput;
@@ -0,0 +1,3078 @@
+//! GPU lexer driver (device init, pass orchestration, and readback).
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+};
+
+use anyhow::{Result, anyhow};
+use log::warn;
+
+mod global;
+mod highlight;
+mod inputs;
+mod memo;
+mod plan;
+mod range;
+mod readback;
+mod tables;
+mod timing;
+mod warmup;
+
+pub use global::{
+    InitError,
+    force_global_lexer_init_failure,
+    get_global_lexer,
+    lex_on_gpu,
+    lex_on_gpu_retry_init,
+    try_global_lexer,
+};
+pub use plan::LexPlan;
+pub(in crate::lexer) use readback::read_u32s;
+use readback::{
+    read_all_boundary_tokens,
+    read_escape_spans,
+    read_resident_tokens,
+    read_suspect_tokens,
+};
+pub use tables::{DfaTable, DfaTableBuffers};
+use timing::{HostCompileTimer, print_timer_trace};
+
+use super::buffers;
+use crate::{
+    gpu::{
+        buffers::LaniusBuffer,
+        cancel::{CancelToken, Cancelled},
+        decode::decode_all,
+        device::ShaderPath,
+        passes_core::DispatchRecord,
+        teardown,
+        timer::{GpuTimer, MINIMUM_TIME_TO_NOT_ELIDE_MS, TimerHealth},
+    },
+    lexer::{
+        analysis::{self, AnalysisWords},
+        constants::{LEX_ANALYSIS_WORDS, N_STATES, SKIP_KIND_SLOTS},
+        kind_packing::KindPacking,
+        layout,
+        paranoia,
+        passes::{
+            LexerPasses,
+            LexerStep,
+            lexer_steps,
+            record_all_passes,
+            record_stage_passes,
+            record_steps,
+        },
+        query::{DeviceTokens, QueryPasses},
+        tables::{
+            compact::{CHECKED_IN_TABLES, load_compact_tables_from_bytes},
+            tokens::TokenKind,
+        },
+        types::{
+            GpuToken,
+            LayoutFacts,
+            LexAnalysis,
+            LexError,
+            LexOptions,
+            LexOutput,
+            LexPipeline,
+            LexReport,
+            LexSubmissionStats,
+            LexWarning,
+            LexerRuntimeOptions,
+            PipelineStage,
+            ReadbackMode,
+            SubmissionPolicy,
+            Token,
+        },
+        utf8::check_token_boundaries,
+        util::{
+            TokenTransport,
+            apply_raw_kinds_from_mapped,
+            choose_token_transport,
+            decode_compressed_tokens,
+            merge_kept_into_all,
+            read_accept_states_from_mapped,
+            read_tokens_from_mapped,
+            token_transfer_bytes,
+            u32_from_first_4,
+        },
+    },
+    logging::{HostSpan, LEXER_GPU, LEXER_TABLES},
+};
+
+/// Token kinds dropped from kept-token output by default.
+///
+/// Shebang lines are masked to line comments before upload, so the DFA never
+/// emits `Shebang`; it is listed so retagged all-boundary streams agree. A
+/// leading BOM is masked to whitespace the same way, so `Bom` needs no slot.
+const DEFAULT_SKIP_KINDS: [u32; SKIP_KIND_SLOTS] = [
+    TokenKind::White as u32,
+    TokenKind::LineComment as u32,
+    TokenKind::BlockComment as u32,
+    TokenKind::Shebang as u32,
+];
+
+/// Count-readback header: kept-token count, `token_len_status`, the
+/// all-boundary count, then the `tokens_compress` escape count (zero unless
+/// the tokens come back compressed).
+const COUNT_READBACK_BYTES: u64 = 20;
+
+/// GPU lexer instance with loaded DFA tables, shader passes, and resident buffers.
+///
+/// One instance can be reused across lexing calls. Resident buffers are resized
+/// only when the input capacity, block count, or source-pack file capacity
+/// changes.
+pub struct GpuLexer {
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    timers_supported: bool,
+    // Settings fixed at construction; never read from the environment
+    runtime: LexerRuntimeOptions,
+
+    // Precomputed tables loaded once at device init
+    next_emit_words: Vec<u32>,
+    next_u8_packed: Vec<u32>,
+    token_map: Vec<u32>,
+    // Device copies of the tables, each uploaded on first use
+    tables: tables::TableSet,
+
+    passes: LexerPasses,
+    // Token query pipelines, created on the first with_device_tokens() call
+    query_passes: std::sync::OnceLock<QueryPasses>,
+    // Shader artifacts `passes` was built from, for hot reload
+    #[cfg(feature = "shader-hot-reload")]
+    loaded_shaders: Vec<crate::gpu::hot_reload::LoadedShader>,
+
+    // Persistent buffers reused across lex() calls
+    buffers: std::sync::Mutex<Option<buffers::GpuBuffers>>,
+    // Buffers of a cancelled lex, parked here once its submitted work finishes
+    cooled_buffers: Arc<std::sync::Mutex<Option<buffers::GpuBuffers>>>,
+    // Number of resident buffer allocations, including the first
+    buffer_allocations: AtomicU64,
+    // Input length set by warm_up(); smaller inputs reuse buffers sized for it
+    reserved_input_len: AtomicU32,
+    // Command buffers submitted by lex_with_options() calls
+    lex_submissions: AtomicU64,
+    // How lex_with_options() hands its passes to the queue
+    submission_policy: std::sync::Mutex<SubmissionPolicy>,
+    // Which pass sequence lex_with_options() records
+    pipeline: std::sync::Mutex<LexPipeline>,
+    last_lex_stats: std::sync::Mutex<LexSubmissionStats>,
+    // Report of the last lex_with_options() call that asked for one
+    last_report: std::sync::Mutex<Option<LexReport>>,
+    // Timer health of the last timed lex() call
+    last_timer_health: std::sync::Mutex<Option<TimerHealth>>,
+    // Bind group cache to avoid recreating them every dispatch
+    bg_cache: std::sync::Mutex<crate::gpu::passes_core::BindGroupCache>,
+    // Whether lexes fill `LexOutput::dispatch_records`
+    capture_dispatch_metadata: AtomicBool,
+    // Debug captures of the last lex_with_options() call (gpu-debug only)
+    last_debug_output: std::sync::Mutex<Option<crate::lexer::debug::DebugOutput>>,
+}
+
+impl Drop for GpuLexer {
+    /// Hands the resident buffers to `teardown::retire`, so a lex still
+    /// running on the GPU never has its buffers freed under it.
+    fn drop(&mut self) {
+        let resident = (
+            teardown::take_poisoned(&mut self.buffers),
+            teardown::take_poisoned(&mut self.bg_cache),
+        );
+        teardown::retire(
+            &self.device,
+            &self.queue,
+            "GpuLexer",
+            resident,
+            teardown::DROP_WAIT,
+        );
+    }
+}
+
+impl GpuLexer {
+    /// Waits for every lex this lexer submitted, then frees its buffers.
+    ///
+    /// Unlike dropping the lexer, which waits at most
+    /// [`teardown::DROP_WAIT`] and then leaves the buffers to a later poll,
+    /// this waits under the process GPU wait timeout and reports a timeout or
+    /// a lost device as an error.
+    pub fn shutdown(self) -> Result<()> {
+        crate::gpu::poll::wait_for_submitted_work(&self.device, "GpuLexer::shutdown")
+    }
+
+    /// Releases reusable source/token buffers and bind groups while retaining
+    /// the lexer pipelines and immutable tables.
+    pub fn release_current_resident_buffers(&self) {
+        *self
+            .buffers
+            .lock()
+            .expect("GpuLexer.buffers mutex poisoned") = None;
+        *self
+            .cooled_buffers
+            .lock()
+            .expect("GpuLexer.cooled_buffers mutex poisoned") = None;
+        self.bg_cache
+            .lock()
+            .expect("GpuLexer.bg_cache mutex poisoned")
+            .clear();
+    }
+
+    /// Recompiles this lexer's shaders with `slangc` and swaps in the new
+    /// pipelines when any changed.
+    ///
+    /// A failed compile or pipeline creation keeps the current pipelines and
+    /// lists the error in the report.
+    #[cfg(feature = "shader-hot-reload")]
+    pub fn reload_shaders(&mut self) -> Result<crate::gpu::hot_reload::ReloadReport> {
+        let source = crate::gpu::hot_reload::SlangcRebuild::from_env()?;
+        Ok(self.reload_shaders_from(&source))
+    }
+
+    /// Shader artifacts the current passes were built from.
+    #[cfg(feature = "shader-hot-reload")]
+    pub fn loaded_shaders(&self) -> &[crate::gpu::hot_reload::LoadedShader] {
+        &self.loaded_shaders
+    }
+
+    /// Like [`reload_shaders`](Self::reload_shaders) with an explicit artifact source.
+    #[cfg(feature = "shader-hot-reload")]
+    pub fn reload_shaders_from(
+        &mut self,
+        source: &dyn crate::gpu::hot_reload::ShaderRebuild,
+    ) -> crate::gpu::hot_reload::ReloadReport {
+        let device = Arc::clone(&self.device);
+        let (rebuilt, report) =
+            crate::gpu::hot_reload::reload(&self.loaded_shaders, source, || {
+                crate::gpu::hot_reload::build_validated(&device, || LexerPasses::new(&device))
+            });
+        if let Some((passes, loaded)) = rebuilt {
+            self.passes = passes;
+            self.loaded_shaders = loaded;
+            self.bg_cache
+                .lock()
+                .expect("GpuLexer.bg_cache mutex poisoned")
+                .clear();
+        }
+        report
+    }
+
+    /// Returns how many times resident buffers have been allocated, including
+    /// the first allocation and any after `release_current_resident_buffers`.
+    pub fn buffer_allocation_count(&self) -> u64 {
+        self.buffer_allocations.load(Ordering::Relaxed)
+    }
+
+    /// Returns how many command buffers `lex_with_options` calls have
+    /// submitted; a small full-readback lex submits once, a large one twice.
+    pub fn lex_submission_count(&self) -> u64 {
+        self.lex_submissions.load(Ordering::Relaxed)
+    }
+
+    /// Sets how later `lex_with_options` calls submit their passes.
+    pub fn set_submission_policy(&self, policy: SubmissionPolicy) {
+        *self
+            .submission_policy
+            .lock()
+            .expect("GpuLexer.submission_policy mutex poisoned") = policy;
+    }
+
+    /// Returns the current [`SubmissionPolicy`]; `Immediate` by default.
+    pub fn submission_policy(&self) -> SubmissionPolicy {
+        *self
+            .submission_policy
+            .lock()
+            .expect("GpuLexer.submission_policy mutex poisoned")
+    }
+
+    /// Sets which pass sequence later lexes record.
+    pub fn set_pipeline(&self, pipeline: LexPipeline) {
+        *self
+            .pipeline
+            .lock()
+            .expect("GpuLexer.pipeline mutex poisoned") = pipeline;
+    }
+
+    /// Returns the current [`LexPipeline`]; `Split` by default.
+    pub fn pipeline(&self) -> LexPipeline {
+        *self
+            .pipeline
+            .lock()
+            .expect("GpuLexer.pipeline mutex poisoned")
+    }
+
+    /// Returns the settings this lexer was constructed with.
+    pub fn runtime_options(&self) -> LexerRuntimeOptions {
+        self.runtime
+    }
+
+    /// Returns how this lexer's buffers pack `tok_types`.
+    pub fn kind_packing(&self) -> KindPacking {
+        self.runtime.kind_packing()
+    }
+
+    /// Returns the submissions and wall time of the last `lex_with_options`
+    /// call, for tuning [`SubmissionPolicy::Yielding`].
+    pub fn last_lex_stats(&self) -> LexSubmissionStats {
+        *self
+            .last_lex_stats
+            .lock()
+            .expect("GpuLexer.last_lex_stats mutex poisoned")
+    }
+
+    /// Returns bind-group cache hit/miss counters for this lexer.
+    /// Returns the report of the last `lex_with_options` call made with
+    /// [`LexOptions::report`], including calls that failed.
+    pub fn last_report(&self) -> Option<LexReport> {
+        *self
+            .last_report
+            .lock()
+            .expect("GpuLexer.last_report mutex poisoned")
+    }
+
+    /// Returns the [`TimerHealth`] of the last lex timed with
+    /// [`LexerRuntimeOptions::gpu_timing`], or `None` before one.
+    pub fn last_timer_health(&self) -> Option<TimerHealth> {
+        *self
+            .last_timer_health
+            .lock()
+            .expect("GpuLexer.last_timer_health mutex poisoned")
+    }
+
+    pub fn bind_group_cache_stats(&self) -> crate::gpu::passes_core::BindGroupCacheStats {
+        self.bg_cache
+            .lock()
+            .expect("GpuLexer.bg_cache mutex poisoned")
+            .stats()
+    }
+
+    /// Enables filling [`LexOutput::dispatch_records`] with the planned
+    /// dispatch shape of each lex.
+    ///
+    /// Defaults to [`LexerRuntimeOptions::capture_dispatch_metadata`].
+    pub fn set_capture_dispatch_metadata(&self, enabled: bool) {
+        self.capture_dispatch_metadata
+            .store(enabled, Ordering::Relaxed);
+    }
+
+    /// Takes the debug captures of the last `lex()` call, mapped for reading.
+    ///
+    /// Always `None` without the `gpu-debug` feature, and after a yielding
+    /// lex, whose chunks skip debug capture.
+    pub fn take_last_debug_output(&self) -> Result<Option<crate::lexer::debug::DebugOutput>> {
+        let taken = self
+            .last_debug_output
+            .lock()
+            .expect("GpuLexer.last_debug_output mutex poisoned")
+            .take();
+        let Some(output) = taken else {
+            return Ok(None);
+        };
+        output.arena.map_all(&self.device)?;
+        Ok(Some(output))
+    }
+}
+
+/// Cloned buffer handles needed by parser after the lexer guard is released.
+pub struct ResidentLexerParserInputs {
+    /// Current source byte length.
+    pub source_len: u32,
+    /// Resident source byte buffer.
+    pub in_bytes: LaniusBuffer<u8>,
+    /// Resident token record buffer.
+    pub tokens_out: LaniusBuffer<GpuToken>,
+    /// Resident token-count buffer.
+    pub token_count: LaniusBuffer<u32>,
+    /// Resident source-file id for each token.
+    pub token_file_id: LaniusBuffer<u32>,
+    /// Conservative parser-family flags collected at the lexer count boundary.
+    pub parser_feature_flags: u32,
+}
+
+impl ResidentLexerParserInputs {
+    fn from_buffers(bufs: &buffers::GpuBuffers) -> Self {
+        Self {
+            source_len: bufs.n,
+            in_bytes: bufs.in_bytes.clone(),
+            tokens_out: bufs.tokens_out.clone(),
+            token_count: bufs.token_count.clone(),
+            token_file_id: bufs.token_file_id.clone(),
+            parser_feature_flags: bufs.parser_feature_flags_value,
+        }
+    }
+}
+
+impl GpuLexer {
+    /// Creates a lexer on the process-global GPU device with default
+    /// [`LexerRuntimeOptions`].
+    pub async fn new() -> Result<Self> {
+        Self::new_with(LexerRuntimeOptions::default()).await
+    }
+
+    /// Creates a lexer on the process-global GPU device with `options`.
+    pub async fn new_with(options: LexerRuntimeOptions) -> Result<Self> {
+        let ctx = crate::gpu::device::global_result()
+            .map_err(|err| anyhow!("initialize GPU device: {err}"))?;
+        Self::new_with_device_and_options(ctx, options).await
+    }
+
+    /// Creates a lexer on an existing GPU device with default
+    /// [`LexerRuntimeOptions`].
+    pub async fn new_with_device(ctx: &crate::gpu::device::GpuDevice) -> Result<Self> {
+        Self::new_with_device_and_options(ctx, LexerRuntimeOptions::default()).await
+    }
+
+    /// Creates a lexer on an existing GPU device with `options` and loads
+    /// compact DFA tables.
+    pub async fn new_with_device_and_options(
+        ctx: &crate::gpu::device::GpuDevice,
+        options: LexerRuntimeOptions,
+    ) -> Result<Self> {
+        let device = Arc::clone(&ctx.device);
+        let queue = Arc::clone(&ctx.queue);
+        let timers_supported = ctx.timers_supported;
+
+        // Load compact DFA tables and build packed-next table once at init.
+        let load_span = HostSpan::enter(LEXER_TABLES, "load compact tables");
+        let (n_states_from_file, next_emit_words, token_map) =
+            load_compact_tables_from_bytes(CHECKED_IN_TABLES)
+                .map_err(|e| anyhow!("failed to parse compact lexer_tables.bin: {e}"))?;
+        load_span.finish_with(format_args!(
+            "({} bytes, {n_states_from_file} states)",
+            CHECKED_IN_TABLES.len()
+        ));
+
+        // Shaders size their state lanes from the generated N_STATES; tables
+        // built for a different state count would index out of range.
+        if n_states_from_file != N_STATES {
+            return Err(anyhow!(
+                "lexer_tables.bin has {n_states_from_file} DFA states but the shaders were built \
+                 for N_STATES = {N_STATES}; rerun `cargo run --bin lex_gen_tables`"
+            ));
+        }
+
+        // Use dynamic n_states from compact tables for data buffers.
+        debug_assert_eq!(
+            token_map.len(),
+            n_states_from_file,
+            "token_map len != n_states"
+        );
+        let expected_words = ((256 * n_states_from_file) + 1) / 2;
+        debug_assert_eq!(
+            next_emit_words.len(),
+            expected_words,
+            "next_emit_words len mismatch (got {}, expect {})",
+            next_emit_words.len(),
+            expected_words
+        );
+
+        // Build packed-next (u8) table for DFA passes: layout [pack4][byte]
+        let pack_span = HostSpan::enter(LEXER_TABLES, "build packed next_u8 table");
+        let n_states = n_states_from_file;
+        let n_pack4 = (n_states + 3) / 4;
+        let mut next_u8_packed: Vec<u32> = vec![0; 256 * n_pack4];
+        let read_u16 = |i: usize| -> u16 {
+            let w = next_emit_words[i >> 1];
+            if (i & 1) == 0 {
+                (w & 0xFFFF) as u16
+            } else {
+                (w >> 16) as u16
+            }
+        };
+        for b in 0..256usize {
+            for p in 0..n_pack4 {
+                let s0 = p * 4 + 0;
+                let s1 = p * 4 + 1;
+                let s2 = p * 4 + 2;
+                let s3 = p * 4 + 3;
+                let v0 = if s0 < n_states {
+                    (read_u16(b * n_states + s0) & 0x7FFF) as u32
+                } else {
+                    s0 as u32
+                };
+                let v1 = if s1 < n_states {
+                    (read_u16(b * n_states + s1) & 0x7FFF) as u32
+                } else {
+                    s1 as u32
+                };
+                let v2 = if s2 < n_states {
+                    (read_u16(b * n_states + s2) & 0x7FFF) as u32
+                } else {
+                    s2 as u32
+                };
+                let v3 = if s3 < n_states {
+                    (read_u16(b * n_states + s3) & 0x7FFF) as u32
+                } else {
+                    s3 as u32
+                };
+                next_u8_packed[p * 256 + b] =
+                    (v0 & 0xFF) | ((v1 & 0xFF) << 8) | ((v2 & 0xFF) << 16) | ((v3 & 0xFF) << 24);
+            }
+        }
+        pack_span.finish_with(format_args!("({} words)", next_u8_packed.len()));
+
+        #[cfg(feature = "shader-hot-reload")]
+        let (passes, loaded_shaders) = {
+            let (passes, loaded) =
+                crate::gpu::hot_reload::record_loads(|| LexerPasses::new(&device));
+            (passes?, loaded)
+        };
+        #[cfg(not(feature = "shader-hot-reload"))]
+        let passes = LexerPasses::new(&device)?;
+
+        Ok(Self {
+            device,
+            queue,
+            timers_supported,
+            runtime: options,
+            next_emit_words,
+            next_u8_packed,
+            token_map,
+            tables: tables::TableSet::default(),
+            passes,
+            query_passes: std::sync::OnceLock::new(),
+            #[cfg(feature = "shader-hot-reload")]
+            loaded_shaders,
+            buffers: std::sync::Mutex::new(None),
+            cooled_buffers: Arc::new(std::sync::Mutex::new(None)),
+            buffer_allocations: AtomicU64::new(0),
+            reserved_input_len: AtomicU32::new(0),
+            lex_submissions: AtomicU64::new(0),
+            submission_policy: std::sync::Mutex::new(SubmissionPolicy::default()),
+            pipeline: std::sync::Mutex::new(LexPipeline::default()),
+            last_lex_stats: std::sync::Mutex::new(LexSubmissionStats::default()),
+            last_report: std::sync::Mutex::new(None),
+            last_timer_health: std::sync::Mutex::new(None),
+            bg_cache: std::sync::Mutex::new(crate::gpu::passes_core::BindGroupCache::new()),
+            capture_dispatch_metadata: AtomicBool::new(options.capture_dispatch_metadata),
+            last_debug_output: std::sync::Mutex::new(None),
+        })
+    }
+
+    /// Lexes one source string and reads kept tokens back to the host.
+    ///
+    /// Reads back what [`LexerRuntimeOptions::readback`] selects; under
+    /// anything but `Full` this still records and submits the GPU work but
+    /// returns an empty vector.
+    pub async fn lex(&self, input: &str) -> Result<Vec<Token>> {
+        let options = LexOptions {
+            readback: self.runtime.readback,
+            ..LexOptions::default()
+        };
+        Ok(self.lex_with_options(input, options).await?.tokens)
+    }
+
+    /// Lexes one source string and reads every token back, skipped
+    /// whitespace and comments included; see [`LexOutput::all_tokens`].
+    pub async fn lex_all(&self, input: &str) -> Result<Vec<Token>> {
+        Ok(self.lex_both(input).await?.1)
+    }
+
+    /// Lexes one source string once and reads back both the kept tokens, as
+    /// [`GpuLexer::lex`] returns them, and the ALL stream of
+    /// [`GpuLexer::lex_all`].
+    pub async fn lex_both(&self, input: &str) -> Result<(Vec<Token>, Vec<Token>)> {
+        let options = LexOptions {
+            all_tokens: true,
+            ..LexOptions::default()
+        };
+        let output = self.lex_with_options(input, options).await?;
+        Ok((output.tokens, output.all_tokens))
+    }
+
+    /// Lexes one source string and reads back the kept tokens, as
+    /// [`GpuLexer::lex`] returns them, with the [`LayoutFacts`] of each; see
+    /// [`LexOptions::layout_facts`].
+    pub async fn lex_with_layout(&self, input: &str) -> Result<(Vec<Token>, Vec<LayoutFacts>)> {
+        let options = LexOptions {
+            layout_facts: true,
+            ..LexOptions::default()
+        };
+        let output = self.lex_with_options(input, options).await?;
+        Ok((output.tokens, output.layout_facts))
+    }
+
+    /// Lexes one source string and reads kept tokens plus the extras requested
+    /// by `options` back to the host.
+    ///
+    /// `options.readback` selects how much is read back; every mode records
+    /// and submits the same GPU work.
+    pub async fn lex_with_options(&self, input: &str, options: LexOptions) -> Result<LexOutput> {
+        self.lex_with_stats(input, options, None).await
+    }
+
+    /// Like [`GpuLexer::lex`], but stops once `token` is cancelled.
+    ///
+    /// A cancelled call fails with a
+    /// [`Cancelled`] error. Cancelling before
+    /// submission does no GPU work. Afterwards the call stops waiting and
+    /// skips its readbacks; the submitted work still runs, so its resident
+    /// buffers are set aside until the queue reports it done, and the next
+    /// lex uses fresh ones in the meantime.
+    pub async fn lex_cancellable(&self, input: &str, token: CancelToken) -> Result<Vec<Token>> {
+        let options = LexOptions {
+            readback: self.runtime.readback,
+            ..LexOptions::default()
+        };
+        Ok(self
+            .lex_with_options_cancellable(input, options, &token)
+            .await?
+            .tokens)
+    }
+
+    /// Like [`GpuLexer::lex_with_options`], but stops once `token` is
+    /// cancelled; see [`GpuLexer::lex_cancellable`].
+    pub async fn lex_with_options_cancellable(
+        &self,
+        input: &str,
+        options: LexOptions,
+        token: &CancelToken,
+    ) -> Result<LexOutput> {
+        self.lex_with_stats(input, options, Some(token)).await
+    }
+
+    async fn lex_with_stats(
+        &self,
+        input: &str,
+        options: LexOptions,
+        cancel: Option<&CancelToken>,
+    ) -> Result<LexOutput> {
+        let started = std::time::Instant::now();
+        let submissions_before = self.lex_submissions.load(Ordering::Relaxed);
+        let mut report = LexReport {
+            input_len: input.len(),
+            readback_mode: options.readback,
+            ..LexReport::default()
+        };
+        let mut dispatch_records = self
+            .capture_dispatch_metadata
+            .load(Ordering::Relaxed)
+            .then(Vec::new);
+        let mut output = self
+            .lex_with_options_inner(input, options, cancel, &mut report, &mut dispatch_records)
+            .await;
+        if let Ok(output) = output.as_mut() {
+            output.dispatch_records = dispatch_records.unwrap_or_default();
+        }
+        let stats = LexSubmissionStats {
+            submissions: self.lex_submissions.load(Ordering::Relaxed) - submissions_before,
+            wall_time: started.elapsed(),
+        };
+        log::debug!(
+            target: LEXER_GPU,
+            "lex {} bytes: {} submissions in {:.3}ms",
+            input.len(),
+            stats.submissions,
+            stats.wall_time.as_secs_f64() * 1000.0
+        );
+        *self
+            .last_lex_stats
+            .lock()
+            .expect("GpuLexer.last_lex_stats mutex poisoned") = stats;
+        if options.report {
+            report.submissions = stats.submissions as u32;
+            if let Ok(output) = output.as_mut() {
+                let violations = report.violations();
+                debug_assert!(
+                    violations.is_empty(),
+                    "lex report {report} broke invariants: {violations:?}"
+                );
+                for violation in &violations {
+                    warn!(target: LEXER_GPU, "lex report: {violation}");
+                }
+                output.report = Some(report);
+                output
+                    .warnings
+                    .extend(violations.into_iter().map(LexWarning::Report));
+            }
+            *self
+                .last_report
+                .lock()
+                .expect("GpuLexer.last_report mutex poisoned") = Some(report);
+        }
+        output
+    }
+
+    async fn lex_with_options_inner(
+        &self,
+        input: &str,
+        options: LexOptions,
+        cancel: Option<&CancelToken>,
+        report: &mut LexReport,
+        dispatch_records: &mut Option<Vec<DispatchRecord>>,
+    ) -> Result<LexOutput> {
+        if let Some(cancel) = cancel {
+            cancel.check("lex.prepare", false)?;
+        }
+
+        #[cfg(feature = "graphics_debugger")]
+        unsafe {
+            self.device.start_graphics_debugger_capture()
+        };
+
+        let start_state = 0u32;
+
+        let n = input.as_bytes().len() as u32;
+
+        let skip_kinds = DEFAULT_SKIP_KINDS;
+
+        let (mut guard, capacity_change) =
+            self.prepare_buffers_for_input_tracking(input, start_state, skip_kinds, options)?;
+        let bufs = guard
+            .as_mut()
+            .expect("GpuLexer buffers must exist after preparation");
+        report.capacity = bufs.tokens_out.count;
+        report.grew = capacity_change == inputs::CapacityChange::Grew;
+        report.shrank = capacity_change == inputs::CapacityChange::Shrank;
+
+        let use_scopes = self.runtime.validation_scopes;
+
+        let timers_on =
+            self.timers_supported && (self.runtime.gpu_timing || crate::gpu::trace::enabled());
+
+        let mut maybe_timer = if timers_on {
+            let mut timer = GpuTimer::new(&self.device, &self.queue, 128);
+            timer.set_per_round(self.runtime.gpu_timing_per_round);
+            Some(timer)
+        } else {
+            None
+        };
+
+        // Optional debug capture handle that all passes can use
+        #[cfg(feature = "gpu-debug")]
+        let mut debug_output = crate::lexer::debug::DebugOutput::default();
+        #[cfg(feature = "gpu-debug")]
+        let maybe_dbg: Option<&mut crate::lexer::debug::DebugOutput> = Some(&mut debug_output);
+        #[cfg(not(feature = "gpu-debug"))]
+        let maybe_dbg: Option<&mut crate::lexer::debug::DebugOutput> = None;
+
+        let encode_span = HostSpan::enter(LEXER_GPU, "encode");
+        let mut enc = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("lex-enc"),
+            });
+
+        if let Some(t) = maybe_timer.as_mut() {
+            t.reset();
+            t.stamp(&mut enc, "BEGIN");
+        }
+
+        // Build a single shared PassContext and run all passes with it.
+        let mut timer_ref = maybe_timer.as_mut();
+        let mut dbg_ref = maybe_dbg;
+        let mut cache_guard = self
+            .bg_cache
+            .lock()
+            .expect("GpuLexer.bg_cache mutex poisoned");
+
+        let debug_groups = self.runtime.debug_groups;
+
+        let passes = &self.passes;
+
+        let readback = options.readback;
+
+        // Small outputs are copied whole next to the count, so one submission
+        // and one map cover the readback; the count then slices the copy.
+        let tokens_capacity = bufs.tokens_out.byte_size as u64;
+        let raw_kinds_capacity = bufs.types_compact.byte_size as u64;
+        let accept_states_capacity = if options.capture_accept_states {
+            bufs.accept_states.byte_size as u64
+        } else {
+            0
+        };
+        let outputs_capacity = tokens_capacity + raw_kinds_capacity + accept_states_capacity;
+        let single_submission = readback == ReadbackMode::Full
+            && outputs_capacity <= options.single_submission_max_bytes
+            && COUNT_READBACK_BYTES + outputs_capacity <= self.device.limits().max_buffer_size;
+        // Larger outputs copy the kept tokens once the count is known, so
+        // they can come back packed.
+        // `tokens_compress` binds `tokens_out` whole, so split token buffers
+        // come back raw.
+        bufs.compress_readback = options.compress_readback
+            && readback == ReadbackMode::Full
+            && !single_submission
+            && bufs.tokens_split.is_none();
+
+        if options.capture_accept_states || options.capture_string_escapes {
+            // Both captures are scattered with atomic OR, one `u16` lane at a time.
+            enc.clear_buffer(&bufs.dfa_states, 0, None);
+            enc.clear_buffer(&bufs.accept_states, 0, None);
+        }
+        if options.capture_string_escapes {
+            enc.clear_buffer(&bufs.escape_span_count, 0, None);
+        }
+        if options.paranoia_level > 0 {
+            enc.clear_buffer(&bufs.suspect_count, 0, None);
+        }
+        if bufs.compress_readback {
+            enc.clear_buffer(&bufs.compressed_escape_count, 0, None);
+        }
+
+        // Command buffers submitted ahead of `enc` in its `queue.submit`.
+        let mut earlier_chunks = Vec::new();
+        // Whether a yielding chunk already reached the queue.
+        let mut submitted = false;
+        let policy = self.submission_policy();
+        let pipeline = self.pipeline();
+        if policy == SubmissionPolicy::Immediate {
+            let ctx = crate::gpu::passes_core::PassContext {
+                device: &self.device,
+                encoder: &mut enc,
+                buffers: &*bufs,
+                maybe_timer: &mut timer_ref,
+                maybe_dbg: &mut dbg_ref,
+                bg_cache: Some(&mut *cache_guard),
+                debug_groups,
+                validation_scopes: use_scopes,
+                batch_compute_passes: self.runtime.batch_compute_passes,
+                dispatch_records: dispatch_records.as_mut(),
+            };
+            record_all_passes(bufs.n, bufs.nb_dfa, bufs.nb_sum, pipeline, ctx, passes)?;
+        } else {
+            // Chunks skip GPU timing and debug capture; their contexts cannot
+            // share the single-use timer and debug borrows.
+            let chunk_passes = policy.passes_per_chunk(lexer_steps(pipeline).len());
+            let mut steps: &[LexerStep] = lexer_steps(pipeline);
+            loop {
+                let ctx = crate::gpu::passes_core::PassContext {
+                    device: &self.device,
+                    encoder: &mut enc,
+                    buffers: &*bufs,
+                    maybe_timer: &mut None,
+                    maybe_dbg: &mut None,
+                    bg_cache: Some(&mut *cache_guard),
+                    debug_groups,
+                    validation_scopes: use_scopes,
+                    batch_compute_passes: self.runtime.batch_compute_passes,
+                    dispatch_records: dispatch_records.as_mut(),
+                };
+                steps = record_steps(
+                    bufs.n,
+                    bufs.nb_dfa,
+                    bufs.nb_sum,
+                    ctx,
+                    passes,
+                    steps,
+                    chunk_passes,
+                )?;
+                if steps.is_empty() {
+                    break;
+                }
+                let chunk = std::mem::replace(
+                    &mut enc,
+                    self.device
+                        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                            label: Some("lex-enc"),
+                        }),
+                )
+                .finish();
+                if let SubmissionPolicy::Yielding { .. } = policy {
+                    if let Some(cancel) = cancel
+                        && let Err(err) = cancel.check("lex.yielding-chunk", submitted)
+                    {
+                        drop(cache_guard);
+                        return Err(self.cool_after_cancel(&mut guard, err.into()));
+                    }
+                    crate::gpu::passes_core::submit_with_optional_validation(
+                        &self.device,
+                        &self.queue,
+                        "lex.yielding-chunk",
+                        chunk,
+                        use_scopes,
+                        "lex chunk",
+                    );
+                    self.lex_submissions.fetch_add(1, Ordering::Relaxed);
+                    submitted = true;
+                    if let Err(err) = crate::gpu::passes_core::wait_for_queue_progress(
+                        &self.device,
+                        &self.queue,
+                        "lex.yielding-chunk",
+                        cancel,
+                    ) {
+                        drop(cache_guard);
+                        return Err(self.cool_after_cancel(&mut guard, err));
+                    }
+                } else {
+                    earlier_chunks.push(chunk);
+                }
+            }
+        }
+        report.passes_recorded = lexer_steps(pipeline).len() as u32;
+        if options.capture_string_escapes {
+            report.passes_recorded += 1;
+        }
+        if options.layout_facts {
+            // layout_01, the layout_02 scan, layout_03 and layout_04.
+            report.passes_recorded += 4;
+        }
+        if bufs.compress_readback {
+            report.passes_recorded += 1;
+        }
+        #[cfg(feature = "gpu-debug")]
+        if policy == SubmissionPolicy::Immediate {
+            *self
+                .last_debug_output
+                .lock()
+                .expect("GpuLexer.last_debug_output mutex poisoned") = Some(debug_output);
+        }
+        encode_span.finish_with(format_args!("({policy:?})"));
+        drop(cache_guard);
+        if let Some(cancel) = cancel
+            && let Err(err) = cancel.check("lex.submit", submitted)
+        {
+            return Err(self.cool_after_cancel(&mut guard, err.into()));
+        }
+
+        // Submit work, optionally also copy back token count when readback is enabled.
+        let (token_count_u32, single_readback, escape_count) = if readback != ReadbackMode::None {
+            if let Some(timer) = maybe_timer.as_mut() {
+                timer.stamp(&mut enc, "before copy count");
+            }
+
+            let staging_size = if single_submission {
+                COUNT_READBACK_BYTES + outputs_capacity
+            } else {
+                COUNT_READBACK_BYTES
+            };
+            let readback_tokens_count = self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(if single_submission {
+                    "rb_count_and_tokens"
+                } else {
+                    "rb_count"
+                }),
+                size: staging_size,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            });
+
+            if debug_groups {
+                enc.insert_debug_marker("lex.readback.count.begin");
+            }
+            enc.copy_buffer_to_buffer(&bufs.token_count, 0, &readback_tokens_count, 0, 4);
+            enc.copy_buffer_to_buffer(&bufs.token_len_status, 0, &readback_tokens_count, 4, 8);
+            enc.copy_buffer_to_buffer(&bufs.all_token_count, 0, &readback_tokens_count, 12, 4);
+            if bufs.compress_readback {
+                enc.copy_buffer_to_buffer(
+                    &bufs.compressed_escape_count,
+                    0,
+                    &readback_tokens_count,
+                    16,
+                    4,
+                );
+            }
+            if single_submission {
+                enc.copy_buffer_to_buffer(
+                    &bufs.tokens_out,
+                    0,
+                    &readback_tokens_count,
+                    COUNT_READBACK_BYTES,
+                    tokens_capacity,
+                );
+                enc.copy_buffer_to_buffer(
+                    &bufs.types_compact,
+                    0,
+                    &readback_tokens_count,
+                    COUNT_READBACK_BYTES + tokens_capacity,
+                    raw_kinds_capacity,
+                );
+                if accept_states_capacity != 0 {
+                    enc.copy_buffer_to_buffer(
+                        &bufs.accept_states,
+                        0,
+                        &readback_tokens_count,
+                        COUNT_READBACK_BYTES + tokens_capacity + raw_kinds_capacity,
+                        accept_states_capacity,
+                    );
+                }
+            }
+            if debug_groups {
+                enc.insert_debug_marker("lex.readback.count.end");
+            }
+
+            if let Some(timer) = maybe_timer.as_mut() {
+                timer.stamp(&mut enc, "after copy count");
+                timer.resolve(&mut enc);
+            }
+
+            earlier_chunks.push(enc.finish());
+            log::debug!(
+                target: LEXER_GPU,
+                "submit lex.batch-with-count: {} command buffers, {staging_size} staging bytes",
+                earlier_chunks.len()
+            );
+            crate::gpu::passes_core::submit_all_with_optional_validation(
+                &self.device,
+                &self.queue,
+                "lex.batch-with-count",
+                earlier_chunks,
+                use_scopes,
+                "lex batch",
+            );
+            self.lex_submissions.fetch_add(1, Ordering::Relaxed);
+
+            let wait_span = HostSpan::enter(LEXER_GPU, "map wait lex.count");
+            crate::gpu::passes_core::map_readback_for_progress(
+                &readback_tokens_count.slice(..),
+                "lex.count",
+            );
+            if let Err(err) = crate::gpu::passes_core::wait_for_map_progress_cancellable(
+                &self.device,
+                "lex.count",
+                cancel,
+            ) {
+                drop(readback_tokens_count);
+                return Err(self.cool_after_cancel(&mut guard, err));
+            }
+            wait_span.finish();
+            let count_bytes = readback_tokens_count.slice(..).get_mapped_range();
+            let token_count_u32 = u32_from_first_4(&count_bytes) as usize;
+            report.token_count = token_count_u32;
+            // An empty input records no boundaries; its count word is stale.
+            report.all_boundary_count = if n == 0 {
+                0
+            } else {
+                u32_from_first_4(&count_bytes[12..]) as usize
+            };
+            if token_count_u32 > report.capacity {
+                report.overflow_flagged = true;
+                drop(count_bytes);
+                readback_tokens_count.unmap();
+                return Err(anyhow!(
+                    "lexer produced {token_count_u32} kept tokens for {} token slots",
+                    report.capacity
+                ));
+            }
+            // Both words are order-independent reductions (atomic add, and
+            // atomic max of `!index`), so they are stable under Strict.
+            let tokens_over_limit = u32_from_first_4(&count_bytes[4..]);
+            let first_over_limit = !u32_from_first_4(&count_bytes[8..]);
+            if tokens_over_limit != 0 {
+                drop(count_bytes);
+                readback_tokens_count.unmap();
+                return Err(self.token_too_long_error(input, bufs, first_over_limit, options)?);
+            }
+            let escape_count = u32_from_first_4(&count_bytes[16..]) as usize;
+            report.transfer_bytes = staging_size;
+            let single_readback = if single_submission && token_count_u32 != 0 {
+                let decode_span = HostSpan::enter(LEXER_GPU, "decode");
+                let decode_started = std::time::Instant::now();
+                let header = COUNT_READBACK_BYTES as usize;
+                let tokens_end = header + tokens_capacity as usize;
+                let raw_kinds_end = tokens_end + raw_kinds_capacity as usize;
+                let mut tokens =
+                    read_tokens_from_mapped(&count_bytes[header..tokens_end], token_count_u32)
+                        .map_err(anyhow::Error::msg)?;
+                apply_raw_kinds_from_mapped(&mut tokens, &count_bytes[tokens_end..raw_kinds_end])
+                    .map_err(anyhow::Error::msg)?;
+                let accept_states = if accept_states_capacity != 0 {
+                    read_accept_states_from_mapped(&count_bytes[raw_kinds_end..], token_count_u32)
+                } else {
+                    Vec::new()
+                };
+                report.decode_time = decode_started.elapsed();
+                decode_span.finish_with(format_args!("({token_count_u32} tokens)"));
+                Some((tokens, accept_states))
+            } else {
+                None
+            };
+            drop(count_bytes);
+            readback_tokens_count.unmap();
+            if token_count_u32 == 0 {
+                // A source of only skipped tokens still has an ALL stream.
+                let all_tokens = if options.all_tokens && readback == ReadbackMode::Full {
+                    self.read_all_tokens(input, bufs, &[])?
+                } else {
+                    Vec::new()
+                };
+                return Ok(LexOutput {
+                    all_tokens,
+                    ..LexOutput::default()
+                });
+            }
+            (token_count_u32, single_readback, escape_count)
+        } else {
+            if let Some(timer) = maybe_timer.as_mut() {
+                // No count copy; still resolve timer queries for printing later.
+                timer.resolve(&mut enc);
+            }
+            earlier_chunks.push(enc.finish());
+            log::debug!(
+                target: LEXER_GPU,
+                "submit lex.batch-without-count: {} command buffers",
+                earlier_chunks.len()
+            );
+            crate::gpu::passes_core::submit_all_with_optional_validation(
+                &self.device,
+                &self.queue,
+                "lex.batch-without-count",
+                earlier_chunks,
+                use_scopes,
+                "lex batch",
+            );
+            self.lex_submissions.fetch_add(1, Ordering::Relaxed);
+            // We intentionally skip token-count readback when readback is disabled.
+            (0usize, None, 0usize)
+        };
+
+        if readback != ReadbackMode::Full {
+            self.log_timer(maybe_timer);
+
+            // Count-only or no readback; skip the token copy entirely.
+            return Ok(LexOutput {
+                token_count: token_count_u32,
+                ..LexOutput::default()
+            });
+        }
+
+        if let Some((tokens, accept_states)) = single_readback {
+            return self.finish_full_readback(
+                input,
+                bufs,
+                maybe_timer,
+                tokens,
+                accept_states,
+                options,
+            );
+        }
+
+        let transport = if bufs.compress_readback {
+            choose_token_transport(token_count_u32, escape_count)
+        } else {
+            TokenTransport::Raw
+        };
+        report.compressed = transport == TokenTransport::Compressed;
+        let need_bytes = token_transfer_bytes(transport, token_count_u32, escape_count);
+        let raw_kind_bytes = (token_count_u32 * std::mem::size_of::<u32>()) as u64;
+        let accept_state_bytes = if options.capture_accept_states {
+            (token_count_u32.div_ceil(2) * 4) as u64
+        } else {
+            0
+        };
+
+        let readback_tokens_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("rb_tokens_partial"),
+            size: need_bytes,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder_two = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("lex-enc-readback-tokens"),
+            });
+
+        if debug_groups {
+            encoder_two.insert_debug_marker("lex.readback.tokens.begin");
+        }
+        match transport {
+            TokenTransport::Raw => encoder_two.copy_buffer_to_buffer(
+                &bufs.tokens_out,
+                0,
+                &readback_tokens_buffer,
+                0,
+                need_bytes,
+            ),
+            TokenTransport::Compressed => {
+                let words_bytes = (token_count_u32 * 4) as u64;
+                encoder_two.copy_buffer_to_buffer(
+                    &bufs.compressed_tokens,
+                    0,
+                    &readback_tokens_buffer,
+                    0,
+                    words_bytes,
+                );
+                if need_bytes > words_bytes {
+                    encoder_two.copy_buffer_to_buffer(
+                        &bufs.compressed_escapes,
+                        0,
+                        &readback_tokens_buffer,
+                        words_bytes,
+                        need_bytes - words_bytes,
+                    );
+                }
+            }
+        }
+        let readback_raw_kinds = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("rb_raw_kinds"),
+            size: raw_kind_bytes,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        encoder_two.copy_buffer_to_buffer(
+            &bufs.types_compact,
+            0,
+            &readback_raw_kinds,
+            0,
+            raw_kind_bytes,
+        );
+        let readback_accept_states = (accept_state_bytes != 0).then(|| {
+            let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("rb_accept_states"),
+                size: accept_state_bytes,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            });
+            encoder_two.copy_buffer_to_buffer(
+                &bufs.accept_states,
+                0,
+                &buffer,
+                0,
+                accept_state_bytes,
+            );
+            buffer
+        });
+        if debug_groups {
+            encoder_two.insert_debug_marker("lex.readback.tokens.end");
+        }
+        report.transfer_bytes += need_bytes + raw_kind_bytes + accept_state_bytes;
+        log::debug!(
+            target: LEXER_GPU,
+            "submit lex.token-readback ({transport:?}): {} bytes",
+            need_bytes + raw_kind_bytes + accept_state_bytes
+        );
+        crate::gpu::passes_core::submit_with_progress(
+            &self.queue,
+            "lex.token-readback",
+            encoder_two.finish(),
+        );
+        self.lex_submissions.fetch_add(1, Ordering::Relaxed);
+
+        let wait_span = HostSpan::enter(LEXER_GPU, "map wait lex.tokens");
+
+        crate::gpu::passes_core::map_readback_for_progress(
+            &readback_tokens_buffer.slice(0..need_bytes),
+            "lex.tokens",
+        );
+        crate::gpu::passes_core::map_readback_for_progress(
+            &readback_raw_kinds.slice(..),
+            "lex.raw_kinds",
+        );
+        if let Some(buffer) = &readback_accept_states {
+            crate::gpu::passes_core::map_readback_for_progress(
+                &buffer.slice(..),
+                "lex.accept_states",
+            );
+        }
+        if let Err(err) = crate::gpu::passes_core::wait_for_map_progress_cancellable(
+            &self.device,
+            "lex.tokens",
+            cancel,
+        ) {
+            return Err(self.cool_after_cancel(&mut guard, err));
+        }
+        wait_span.finish();
+
+        let decode_span = HostSpan::enter(LEXER_GPU, "decode");
+        let decode_started = std::time::Instant::now();
+        let mapped = readback_tokens_buffer
+            .slice(0..need_bytes)
+            .get_mapped_range();
+        let mut tokens = match transport {
+            TokenTransport::Raw => read_tokens_from_mapped(&mapped, token_count_u32),
+            TokenTransport::Compressed => {
+                let (words, escapes) = mapped.split_at(token_count_u32 * 4);
+                decode_compressed_tokens(words, escapes, token_count_u32)
+            }
+        }
+        .map_err(anyhow::Error::msg)?;
+        drop(mapped);
+        readback_tokens_buffer.unmap();
+
+        let mapped = readback_raw_kinds.slice(..).get_mapped_range();
+        apply_raw_kinds_from_mapped(&mut tokens, &mapped).map_err(anyhow::Error::msg)?;
+        drop(mapped);
+        readback_raw_kinds.unmap();
+
+        let accept_states = match readback_accept_states {
+            Some(buffer) => {
+                let mapped = buffer.slice(..).get_mapped_range();
+                let states = read_accept_states_from_mapped(&mapped, token_count_u32);
+                drop(mapped);
+                buffer.unmap();
+                states
+            }
+            None => Vec::new(),
+        };
+        report.decode_time = decode_started.elapsed();
+        decode_span.finish_with(format_args!("({token_count_u32} tokens, {transport:?})"));
+
+        self.finish_full_readback(input, bufs, maybe_timer, tokens, accept_states, options)
+    }
+
+    /// Sets the resident buffers aside when `err` cancelled a lex after
+    /// submission, so no later lex writes them while that work still runs.
+    ///
+    /// The queue's work-done callback parks them in `cooled_buffers`, where
+    /// [`GpuLexer::ensure_capacity`] picks them up again.
+    fn cool_after_cancel(
+        &self,
+        guard: &mut std::sync::MutexGuard<'_, Option<buffers::GpuBuffers>>,
+        err: anyhow::Error,
+    ) -> anyhow::Error {
+        if !err
+            .downcast_ref::<Cancelled>()
+            .is_some_and(|cancelled| cancelled.submitted)
+        {
+            return err;
+        }
+        if let Some(bufs) = guard.take() {
+            let cooled = Arc::clone(&self.cooled_buffers);
+            self.queue.on_submitted_work_done(move || {
+                *cooled
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(bufs);
+            });
+            self.clear_bind_group_cache("failed to clear lexer bind-group cache");
+        }
+        err
+    }
+
+    /// Finishes a full readback, reading the ALL stream too when
+    /// [`LexOptions::all_tokens`] is set, the escape spans when
+    /// [`LexOptions::capture_string_escapes`] is, the layout facts when
+    /// [`LexOptions::layout_facts`] is, and the suspect list in paranoia mode, whose tokens it then rechecks. Debug builds, or
+    /// [`LexerRuntimeOptions::check_token_boundaries`], also reject tokens
+    /// that split a UTF-8 character of `input`.
+    fn finish_full_readback(
+        &self,
+        input: &str,
+        bufs: &buffers::GpuBuffers,
+        timer: Option<GpuTimer>,
+        tokens: Vec<Token>,
+        accept_states: Vec<u16>,
+        options: LexOptions,
+    ) -> Result<LexOutput> {
+        self.log_timer(timer);
+
+        if options.determinism.is_strict() {
+            // Kept tokens are scattered to scan-computed ranks, so their order
+            // is part of the output rather than an atomic arrival order.
+            debug_assert!(
+                tokens
+                    .windows(2)
+                    .all(|pair| pair[0].end() <= pair[1].start()),
+                "kept tokens are not in source order"
+            );
+        }
+
+        #[cfg(feature = "graphics_debugger")]
+        unsafe {
+            self.device.stop_graphics_debugger_capture()
+        };
+
+        if cfg!(debug_assertions) || self.runtime.check_token_boundaries {
+            let violations = check_token_boundaries(input, &tokens);
+            if !violations.is_empty() {
+                let listed: Vec<String> = violations
+                    .iter()
+                    .take(16)
+                    .map(ToString::to_string)
+                    .collect();
+                return Err(anyhow!(
+                    "lexer produced {} token(s) off UTF-8 boundaries: {}",
+                    violations.len(),
+                    listed.join(", ")
+                ));
+            }
+        }
+
+        let all_tokens = if options.all_tokens {
+            self.read_all_tokens(input, bufs, &tokens)?
+        } else {
+            Vec::new()
+        };
+        let escape_spans = if options.capture_string_escapes {
+            let spans = read_escape_spans(&self.device, &self.queue, bufs)?;
+            // One submission for the count, then one for the spans.
+            let submissions = if spans.is_empty() { 1 } else { 2 };
+            self.lex_submissions
+                .fetch_add(submissions, Ordering::Relaxed);
+            spans
+        } else {
+            Vec::new()
+        };
+        let layout_facts = if options.layout_facts {
+            let words = read_u32s(
+                &self.device,
+                &self.queue,
+                &bufs.layout_facts,
+                tokens.len() * layout::LAYOUT_WORDS_PER_TOKEN,
+                "lex.layout_facts",
+            )?;
+            self.lex_submissions.fetch_add(1, Ordering::Relaxed);
+            layout::decode(input.as_bytes(), &words)
+        } else {
+            Vec::new()
+        };
+        let (suspects, warnings) = if options.paranoia_level > 0 {
+            self.recheck_suspects(input, bufs, &tokens, options)?
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
+        Ok(LexOutput {
+            token_count: tokens.len(),
+            tokens,
+            accept_states,
+            all_tokens,
+            escape_spans,
+            suspects,
+            layout_facts,
+            warnings,
+            ..LexOutput::default()
+        })
+    }
+
+    /// Reads the suspect list of the last lex and relexes the bytes around
+    /// each suspect on the test CPU oracle. A mismatch fails the lex under
+    /// [`LexOptions::fail_on_suspect_mismatch`] and is returned as a warning
+    /// otherwise.
+    fn recheck_suspects(
+        &self,
+        input: &str,
+        bufs: &buffers::GpuBuffers,
+        tokens: &[Token],
+        options: LexOptions,
+    ) -> Result<(Vec<paranoia::SuspectToken>, Vec<LexWarning>)> {
+        let suspects = read_suspect_tokens(&self.device, &self.queue, bufs)?;
+        // One submission for the count, then one for the list.
+        let submissions = if suspects.is_empty() { 1 } else { 2 };
+        self.lex_submissions
+            .fetch_add(submissions, Ordering::Relaxed);
+        let warnings = paranoia::recheck_suspects(input, tokens, &suspects);
+        log::debug!(
+            target: LEXER_GPU,
+            "paranoia: rechecked {} suspect tokens, {} mismatched",
+            suspects.len(),
+            warnings.len()
+        );
+        if options.fail_on_suspect_mismatch
+            && let Some(LexWarning::SuspectMismatch {
+                token_index,
+                window,
+            }) = warnings.first()
+        {
+            return Err(LexError::SuspectMismatch {
+                token_index: *token_index,
+                window: *window,
+            }
+            .into());
+        }
+        for warning in &warnings {
+            warn!(target: LEXER_GPU, "paranoia: {warning}");
+        }
+        Ok((suspects, warnings))
+    }
+
+    /// Reads the ALL stream of the last lex and substitutes its `kept` tokens,
+    /// so kept entries carry final kinds and ranges and skipped entries keep
+    /// their pre-skip DFA kinds.
+    fn read_all_tokens(
+        &self,
+        input: &str,
+        bufs: &buffers::GpuBuffers,
+        kept: &[Token],
+    ) -> Result<Vec<Token>> {
+        let mut all = read_all_boundary_tokens(&self.device, &self.queue, bufs)?;
+        // One submission for the count, then one each for ends and kinds.
+        let submissions = match (bufs.n, all.is_empty()) {
+            (0, _) => 0,
+            (_, true) => 1,
+            (_, false) => 3,
+        };
+        self.lex_submissions
+            .fetch_add(submissions, Ordering::Relaxed);
+        super::bom::retag_bom(input.as_bytes(), &mut all);
+        super::shebang::retag_shebang(input.as_bytes(), &mut all);
+        merge_kept_into_all(&mut all, kept).map_err(anyhow::Error::msg)?;
+        Ok(all)
+    }
+
+    /// Builds [`LexError::TokenTooLong`] for the ALL-stream token that
+    /// `tokens_build` flagged, reading the skipped tokens back to find its kind.
+    fn token_too_long_error(
+        &self,
+        input: &str,
+        bufs: &buffers::GpuBuffers,
+        all_index: u32,
+        options: LexOptions,
+    ) -> Result<anyhow::Error> {
+        let mut all = read_all_boundary_tokens(&self.device, &self.queue, bufs)?;
+        let read_back = all.len();
+        super::bom::retag_bom(input.as_bytes(), &mut all);
+        super::shebang::retag_shebang(input.as_bytes(), &mut all);
+        // A split-out BOM shifts the GPU's ALL indices by one.
+        let index = all_index as usize + (all.len() - read_back);
+        let token = all.get(index).ok_or_else(|| {
+            anyhow!(
+                "token_len_status names ALL token {all_index}, but only {read_back} were read back"
+            )
+        })?;
+        Ok(LexError::TokenTooLong {
+            kind: token.kind,
+            start: token.start(),
+            limit: options.max_token_len,
+        }
+        .into())
+    }
+
+    /// Logs resolved per-stage GPU timestamps, eliding very short stages,
+    /// and keeps the timer's health for
+    /// [`last_timer_health`](Self::last_timer_health).
+    fn log_timer(&self, timer: Option<GpuTimer>) {
+        if let Some(timer) = timer
+            && let Some(readout) = timer.try_read(&self.device)
+            && !readout.stamps.is_empty()
+        {
+            log::info!(
+                target: LEXER_GPU,
+                "gpu_timer shader_path={}",
+                ShaderPath::for_device(&self.device)
+            );
+            for interval in readout.intervals(timer.period_ns()) {
+                let dt_ms = interval.gpu_ns / 1.0e6;
+                let total_ms = (interval.start_ns + interval.gpu_ns) / 1.0e6;
+                if dt_ms < MINIMUM_TIME_TO_NOT_ELIDE_MS {
+                    continue;
+                }
+                log::info!(
+                    target: LEXER_GPU,
+                    "gpu_timer {}: {dt_ms:.3}ms (total {total_ms:.3}ms)",
+                    interval.label
+                );
+            }
+            if !readout.health.is_clean() {
+                log::warn!(target: LEXER_GPU, "gpu_timer {:?}", readout.health);
+            }
+            *self
+                .last_timer_health
+                .lock()
+                .expect("GpuLexer.last_timer_health mutex poisoned") = Some(readout.health);
+        }
+    }
+
+    /// Runs the lexer only through its boundary scans
+    /// ([`PipelineStage::Scan`]) and reduces boundary statistics from them,
+    /// without compacting or building tokens.
+    ///
+    /// Reads back [`LEX_ANALYSIS_WORDS`] words instead of a token stream. The
+    /// counts match [`LexAnalysis::from_all_tokens`] over the all-boundary
+    /// stream of an input the lexer accepts, and are unspecified for one it
+    /// rejects.
+    pub async fn analyze(&self, input: &str) -> Result<LexAnalysis> {
+        Ok(self.analyze_with_dispatch_records(input).await?.0)
+    }
+
+    /// Like [`GpuLexer::analyze`], also returning the dispatches it recorded,
+    /// in submission order; empty unless dispatch-metadata capture is on.
+    pub async fn analyze_with_dispatch_records(
+        &self,
+        input: &str,
+    ) -> Result<(LexAnalysis, Vec<DispatchRecord>)> {
+        use crate::{gpu::passes_core::InputElements::Elements1D as E1, lexer::Pass};
+
+        if input.is_empty() {
+            return Ok((LexAnalysis::default(), Vec::new()));
+        }
+        let mut guard =
+            self.prepare_buffers_for_input(input, 0, DEFAULT_SKIP_KINDS, LexOptions::default())?;
+        let bufs = guard
+            .as_mut()
+            .expect("GpuLexer buffers must exist after preparation");
+        let use_scopes = self.runtime.validation_scopes;
+        let mut dispatch_records = self
+            .capture_dispatch_metadata
+            .load(Ordering::Relaxed)
+            .then(Vec::new);
+
+        let mut enc = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("lex-analyze-enc"),
+            });
+        enc.clear_buffer(&bufs.lex_analysis, 0, None);
+        {
+            let mut cache_guard = self
+                .bg_cache
+                .lock()
+                .expect("GpuLexer.bg_cache mutex poisoned");
+            // The scan and the analysis take separate contexts, so neither
+            // can hold the single-use timer and debug borrows.
+            let ctx = crate::gpu::passes_core::PassContext {
+                device: &self.device,
+                encoder: &mut enc,
+                buffers: &*bufs,
+                maybe_timer: &mut None,
+                maybe_dbg: &mut None,
+                bg_cache: Some(&mut *cache_guard),
+                debug_groups: self.runtime.debug_groups,
+                validation_scopes: use_scopes,
+                batch_compute_passes: self.runtime.batch_compute_passes,
+                dispatch_records: dispatch_records.as_mut(),
+            };
+            record_stage_passes(
+                bufs.n,
+                bufs.nb_dfa,
+                bufs.nb_sum,
+                self.pipeline(),
+                PipelineStage::Scan,
+                ctx,
+                &self.passes,
+            )?;
+            let mut ctx = crate::gpu::passes_core::PassContext {
+                device: &self.device,
+                encoder: &mut enc,
+                buffers: &*bufs,
+                maybe_timer: &mut None,
+                maybe_dbg: &mut None,
+                bg_cache: Some(&mut *cache_guard),
+                debug_groups: self.runtime.debug_groups,
+                validation_scopes: use_scopes,
+                batch_compute_passes: self.runtime.batch_compute_passes,
+                dispatch_records: dispatch_records.as_mut(),
+            };
+            if bufs.kind_packing == KindPacking::Narrow {
+                self.passes
+                    .analyze_narrow
+                    .record_pass(&mut ctx, E1(bufs.n))?;
+            } else {
+                self.passes.analyze.record_pass(&mut ctx, E1(bufs.n))?;
+            }
+        }
+        crate::gpu::passes_core::submit_with_optional_validation(
+            &self.device,
+            &self.queue,
+            "lex.analyze",
+            enc.finish(),
+            use_scopes,
+            "lexer analysis",
+        );
+        let words = read_u32s(
+            &self.device,
+            &self.queue,
+            &bufs.lex_analysis,
+            LEX_ANALYSIS_WORDS as usize,
+            "rb.lexer.analysis",
+        )?;
+        let words = AnalysisWords::try_from(words)
+            .map_err(|words| anyhow!("read {} lexer analysis words", words.len()))?;
+        Ok((
+            analysis::decode(input.as_bytes(), &words),
+            dispatch_records.unwrap_or_default(),
+        ))
+    }
+
+    /// Lexes one source and reads the one-word conservative parser-family summary.
+    #[doc(hidden)]
+    pub async fn debug_parser_feature_flags(&self, input: &str) -> Result<u32> {
+        self.lex(input).await?;
+        let guard = self
+            .buffers
+            .lock()
+            .expect("GpuLexer.buffers mutex poisoned");
+        let bufs = guard
+            .as_ref()
+            .expect("GpuLexer buffers must exist after lexing");
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("rb.lexer.parser_feature_flags.debug"),
+            size: 4,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("lexer.parser_feature_flags.debug.encoder"),
+            });
+        encoder.copy_buffer_to_buffer(&bufs.parser_feature_flags, 0, &readback, 0, 4);
+        crate::gpu::passes_core::submit_with_progress(
+            &self.queue,
+            "lexer.parser-feature-flags.debug",
+            encoder.finish(),
+        );
+        let slice = readback.slice(..);
+        crate::gpu::passes_core::map_readback_blocking(
+            &self.device,
+            &slice,
+            "lexer.parser_feature_flags.debug",
+        )?;
+        let mapped = slice.get_mapped_range();
+        let value = u32_from_first_4(&mapped);
+        drop(mapped);
+        readback.unmap();
+        Ok(value)
+    }
+
+    /// Lexes one source and reads the `tokens_build` ordering check.
+    ///
+    /// Returns the first kept token whose start, recovered from the
+    /// all-boundary stream, differs from the end of the kept token before it
+    /// even though no skipped token lies between them.
+    #[doc(hidden)]
+    pub async fn debug_token_order_violation(&self, input: &str) -> Result<Option<u32>> {
+        self.lex(input).await?;
+        let guard = self
+            .buffers
+            .lock()
+            .expect("GpuLexer.buffers mutex poisoned");
+        let bufs = guard
+            .as_ref()
+            .expect("GpuLexer buffers must exist after lexing");
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("rb.lexer.token_order_status.debug"),
+            size: 8,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("lexer.token_order_status.debug.encoder"),
+            });
+        encoder.copy_buffer_to_buffer(&bufs.token_order_status, 0, &readback, 0, 8);
+        crate::gpu::passes_core::submit_with_progress(
+            &self.queue,
+            "lexer.token-order-status.debug",
+            encoder.finish(),
+        );
+        let slice = readback.slice(..);
+        crate::gpu::passes_core::map_readback_blocking(
+            &self.device,
+            &slice,
+            "lexer.token_order_status.debug",
+        )?;
+        let mapped = slice.get_mapped_range();
+        let violations = u32_from_first_4(&mapped);
+        let first = !u32_from_first_4(&mapped[4..]);
+        drop(mapped);
+        readback.unmap();
+        Ok((violations != 0).then_some(first))
+    }
+
+    /// Lexes one source and reads back the DFA state after each byte, as
+    /// captured by `dfa_03_apply_block_prefix`.
+    ///
+    /// The states are for the bytes as uploaded, after BOM and shebang
+    /// masking. Compare against [`crate::lexer::tables::compact::walk`].
+    #[doc(hidden)]
+    pub async fn debug_dfa_walk(&self, input: &str) -> Result<Vec<u32>> {
+        if input.is_empty() {
+            return Ok(Vec::new());
+        }
+        let options = LexOptions {
+            capture_accept_states: true,
+            readback: ReadbackMode::CountOnly,
+            ..LexOptions::default()
+        };
+        self.lex_with_options(input, options).await?;
+        let guard = self
+            .buffers
+            .lock()
+            .expect("GpuLexer.buffers mutex poisoned");
+        let bufs = guard
+            .as_ref()
+            .expect("GpuLexer buffers must exist after lexing");
+        // Two `u16` states per word.
+        let size = (input.len().div_ceil(2) * 4) as u64;
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("rb.lexer.dfa_states.debug"),
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("lexer.dfa_states.debug.encoder"),
+            });
+        encoder.copy_buffer_to_buffer(&bufs.dfa_states, 0, &readback, 0, size);
+        crate::gpu::passes_core::submit_with_progress(
+            &self.queue,
+            "lexer.dfa-states.debug",
+            encoder.finish(),
+        );
+        let slice = readback.slice(..);
+        crate::gpu::passes_core::map_readback_blocking(
+            &self.device,
+            &slice,
+            "lexer.dfa_states.debug",
+        )?;
+        let mapped = slice.get_mapped_range();
+        let lanes = mapped.len().min(input.len() * 2);
+        let states = decode_all::<u16>(&mapped[..lanes])
+            .into_iter()
+            .map(u32::from)
+            .collect();
+        drop(mapped);
+        readback.unmap();
+        Ok(states)
+    }
+
+    /// Lexes one source and reads back the all-boundary stream.
+    ///
+    /// Unlike [`GpuLexer::lex`], the result includes skipped whitespace and
+    /// comment tokens and carries raw DFA kinds, before keyword and range
+    /// retagging, except that a leading shebang line is reported as
+    /// `Shebang` and a leading BOM as `Bom`. Compare against `test_cpu::lex_on_test_cpu_all`.
+    #[doc(hidden)]
+    pub async fn debug_all_tokens(&self, input: &str) -> Result<Vec<Token>> {
+        let mut tokens = self
+            .with_resident_tokens(input, read_all_boundary_tokens)
+            .await??;
+        super::bom::retag_bom(input.as_bytes(), &mut tokens);
+        super::shebang::retag_shebang(input.as_bytes(), &mut tokens);
+        Ok(tokens)
+    }
+
+    /// Lexes one source string and exposes resident buffers to a continuation.
+    ///
+    /// The continuation runs after lexer work has been submitted and before the
+    /// lexer buffer guard is released.
+    pub async fn with_resident_tokens<R>(
+        &self,
+        input: &str,
+        consume: impl FnOnce(&wgpu::Device, &wgpu::Queue, &buffers::GpuBuffers) -> R,
+    ) -> Result<R> {
+        #[cfg(feature = "graphics_debugger")]
+        unsafe {
+            self.device.start_graphics_debugger_capture()
+        };
+
+        let start_state = 0u32;
+        let skip_kinds = DEFAULT_SKIP_KINDS;
+        let mut guard =
+            self.prepare_buffers_for_input(input, start_state, skip_kinds, LexOptions::default())?;
+        let bufs = guard
+            .as_mut()
+            .expect("GpuLexer buffers must exist after preparation");
+        bufs.require_whole_tokens_out()?;
+
+        let use_scopes = self.runtime.validation_scopes;
+
+        #[cfg(feature = "gpu-debug")]
+        let mut debug_output = crate::lexer::debug::DebugOutput::default();
+        #[cfg(feature = "gpu-debug")]
+        let maybe_dbg: Option<&mut crate::lexer::debug::DebugOutput> = Some(&mut debug_output);
+        #[cfg(not(feature = "gpu-debug"))]
+        let maybe_dbg: Option<&mut crate::lexer::debug::DebugOutput> = None;
+
+        let mut enc = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("lex-resident-enc"),
+            });
+
+        {
+            let mut timer_ref: Option<&mut GpuTimer> = None;
+            let mut dbg_ref = maybe_dbg;
+            let mut cache_guard = self
+                .bg_cache
+                .lock()
+                .expect("GpuLexer.bg_cache mutex poisoned");
+            let ctx = crate::gpu::passes_core::PassContext {
+                device: &self.device,
+                encoder: &mut enc,
+                buffers: &*bufs,
+                maybe_timer: &mut timer_ref,
+                maybe_dbg: &mut dbg_ref,
+                bg_cache: Some(&mut *cache_guard),
+                debug_groups: self.runtime.debug_groups,
+                validation_scopes: use_scopes,
+                batch_compute_passes: self.runtime.batch_compute_passes,
+                dispatch_records: None,
+            };
+            record_all_passes(
+                bufs.n,
+                bufs.nb_dfa,
+                bufs.nb_sum,
+                self.pipeline(),
+                ctx,
+                &self.passes,
+            )?;
+        }
+
+        crate::gpu::passes_core::submit_with_optional_validation(
+            &self.device,
+            &self.queue,
+            "lex.resident",
+            enc.finish(),
+            use_scopes,
+            "resident lex batch",
+        );
+
+        let result = consume(&self.device, &self.queue, bufs);
+
+        #[cfg(feature = "graphics_debugger")]
+        unsafe {
+            self.device.stop_graphics_debugger_capture()
+        };
+
+        Ok(result)
+    }
+
+    /// Lexes one source string and exposes its resident tokens to a
+    /// continuation that can [`query`](DeviceTokens::query) them without
+    /// reading every token back.
+    pub async fn with_device_tokens<R>(
+        &self,
+        input: &str,
+        consume: impl FnOnce(&wgpu::Device, &wgpu::Queue, &DeviceTokens<'_>) -> R,
+    ) -> Result<R> {
+        let query_passes = match self.query_passes.get() {
+            Some(passes) => passes,
+            None => {
+                let passes = QueryPasses::new(&self.device)?;
+                self.query_passes.get_or_init(|| passes)
+            }
+        };
+        let lexer_passes = &self.passes;
+        let runtime = self.runtime;
+        self.with_resident_tokens(input, |device, queue, bufs| {
+            consume(
+                device,
+                queue,
+                &DeviceTokens::new(bufs, lexer_passes, query_passes, runtime),
+            )
+        })
+        .await
+    }
+
+    /// Lexes a source pack and reads kept tokens back to the host.
+    pub async fn lex_source_pack<S: AsRef<str>>(&self, sources: &[S]) -> Result<Vec<Token>> {
+        self.with_resident_source_pack_tokens(sources, read_resident_tokens)
+            .await?
+    }
+
+    /// Lexes a source pack and exposes resident buffers to a continuation.
+    ///
+    /// Source strings are concatenated for GPU work; source-file metadata
+    /// buffers preserve file ownership for each final token.
+    pub async fn with_resident_source_pack_tokens<S: AsRef<str>, R>(
+        &self,
+        sources: &[S],
+        consume: impl FnOnce(&wgpu::Device, &wgpu::Queue, &buffers::GpuBuffers) -> R,
+    ) -> Result<R> {
+        #[cfg(feature = "graphics_debugger")]
+        unsafe {
+            self.device.start_graphics_debugger_capture()
+        };
+
+        let start_state = 0u32;
+        let skip_kinds = DEFAULT_SKIP_KINDS;
+        let mut guard = self.prepare_buffers_for_source_pack(sources, start_state, skip_kinds)?;
+        let bufs = guard
+            .as_mut()
+            .expect("GpuLexer buffers must exist after source pack preparation");
+        bufs.require_whole_tokens_out()?;
+
+        let use_scopes = self.runtime.validation_scopes;
+
+        #[cfg(feature = "gpu-debug")]
+        let mut debug_output = crate::lexer::debug::DebugOutput::default();
+        #[cfg(feature = "gpu-debug")]
+        let maybe_dbg: Option<&mut crate::lexer::debug::DebugOutput> = Some(&mut debug_output);
+        #[cfg(not(feature = "gpu-debug"))]
+        let maybe_dbg: Option<&mut crate::lexer::debug::DebugOutput> = None;
+
+        let mut enc = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("lex-source-pack-resident-enc"),
+            });
+
+        {
+            let mut timer_ref: Option<&mut GpuTimer> = None;
+            let mut dbg_ref = maybe_dbg;
+            let mut cache_guard = self
+                .bg_cache
+                .lock()
+                .expect("GpuLexer.bg_cache mutex poisoned");
+            let ctx = crate::gpu::passes_core::PassContext {
+                device: &self.device,
+                encoder: &mut enc,
+                buffers: &*bufs,
+                maybe_timer: &mut timer_ref,
+                maybe_dbg: &mut dbg_ref,
+                bg_cache: Some(&mut *cache_guard),
+                debug_groups: self.runtime.debug_groups,
+                validation_scopes: use_scopes,
+                batch_compute_passes: self.runtime.batch_compute_passes,
+                dispatch_records: None,
+            };
+            record_all_passes(
+                bufs.n,
+                bufs.nb_dfa,
+                bufs.nb_sum,
+                self.pipeline(),
+                ctx,
+                &self.passes,
+            )?;
+        }
+
+        crate::gpu::passes_core::submit_with_optional_validation(
+            &self.device,
+            &self.queue,
+            "lex.source-pack.resident",
+            enc.finish(),
+            use_scopes,
+            "source-pack resident lex batch",
+        );
+
+        let result = consume(&self.device, &self.queue, bufs);
+
+        #[cfg(feature = "graphics_debugger")]
+        unsafe {
+            self.device.stop_graphics_debugger_capture()
+        };
+
+        Ok(result)
+    }
+
+    /// Records source-pack lexing and caller-provided GPU work in one command stream.
+    ///
+    /// This variant does not read back token count before `record_more`; callers
+    /// should use byte-capacity-sized downstream buffers or perform their own
+    /// bounded sizing.
+    pub async fn with_recorded_resident_source_pack_tokens<S, T, R, E>(
+        &self,
+        sources: &[S],
+        record_more: impl FnOnce(
+            &wgpu::Device,
+            &wgpu::Queue,
+            &buffers::GpuBuffers,
+            &mut wgpu::CommandEncoder,
+            Option<&mut GpuTimer>,
+        ) -> std::result::Result<T, E>,
+        consume_after_submit: impl FnOnce(
+            &wgpu::Device,
+            &wgpu::Queue,
+            &buffers::GpuBuffers,
+            T,
+        ) -> std::result::Result<R, E>,
+    ) -> Result<std::result::Result<R, E>>
+    where
+        S: AsRef<str>,
+    {
+        #[cfg(feature = "graphics_debugger")]
+        unsafe {
+            self.device.start_graphics_debugger_capture()
+        };
+
+        let start_state = 0u32;
+        let skip_kinds = DEFAULT_SKIP_KINDS;
+        let mut guard = self.prepare_buffers_for_source_pack(sources, start_state, skip_kinds)?;
+        let bufs = guard
+            .as_mut()
+            .expect("GpuLexer buffers must exist after source pack preparation");
+        bufs.require_whole_tokens_out()?;
+
+        let use_scopes = self.runtime.validation_scopes;
+
+        #[cfg(feature = "gpu-debug")]
+        let mut debug_output = crate::lexer::debug::DebugOutput::default();
+        #[cfg(feature = "gpu-debug")]
+        let maybe_dbg: Option<&mut crate::lexer::debug::DebugOutput> = Some(&mut debug_output);
+        #[cfg(not(feature = "gpu-debug"))]
+        let maybe_dbg: Option<&mut crate::lexer::debug::DebugOutput> = None;
+
+        let mut enc = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("lex-source-pack-resident-recorded-enc"),
+            });
+        let timers_on =
+            self.timers_supported && (self.runtime.compile_timing || crate::gpu::trace::enabled());
+        let mut maybe_timer = if timers_on {
+            Some(GpuTimer::new(&self.device, &self.queue, 512))
+        } else {
+            None
+        };
+        if let Some(timer) = maybe_timer.as_mut() {
+            timer.stamp(&mut enc, "compile.source_pack.start");
+        }
+
+        {
+            let mut timer_ref = maybe_timer.as_mut();
+            let mut dbg_ref = maybe_dbg;
+            let mut cache_guard = self
+                .bg_cache
+                .lock()
+                .expect("GpuLexer.bg_cache mutex poisoned");
+            let ctx = crate::gpu::passes_core::PassContext {
+                device: &self.device,
+                encoder: &mut enc,
+                buffers: &*bufs,
+                maybe_timer: &mut timer_ref,
+                maybe_dbg: &mut dbg_ref,
+                bg_cache: Some(&mut *cache_guard),
+                debug_groups: self.runtime.debug_groups,
+                validation_scopes: use_scopes,
+                batch_compute_passes: self.runtime.batch_compute_passes,
+                dispatch_records: None,
+            };
+            record_all_passes(
+                bufs.n,
+                bufs.nb_dfa,
+                bufs.nb_sum,
+                self.pipeline(),
+                ctx,
+                &self.passes,
+            )?;
+        }
+        if let Some(timer) = maybe_timer.as_mut() {
+            timer.stamp(&mut enc, "lexer.source_pack.done");
+        }
+
+        let recorded_more = match record_more(
+            &self.device,
+            &self.queue,
+            bufs,
+            &mut enc,
+            maybe_timer.as_mut(),
+        ) {
+            Ok(recorded) => recorded,
+            Err(err) => return Ok(Err(err)),
+        };
+        if let Some(timer) = maybe_timer.as_mut() {
+            timer.stamp(&mut enc, "compile.source_pack.recorded");
+            timer.resolve(&mut enc);
+        }
+
+        let submit_timing = crate::gpu::passes_core::submit_with_optional_validation(
+            &self.device,
+            &self.queue,
+            "lex.source-pack.recorded-with-code",
+            enc.finish(),
+            use_scopes,
+            "recorded source-pack resident lex batch",
+        );
+
+        let result = consume_after_submit(&self.device, &self.queue, bufs, recorded_more);
+        if let Some(timer) = maybe_timer
+            .as_ref()
+            .and_then(|timer| timer.try_read(&self.device))
+        {
+            print_timer_trace(
+                &self.runtime,
+                &timer,
+                maybe_timer.as_ref().expect("timer exists").period_ns(),
+                submit_timing.gpu_anchor,
+            );
+        }
+
+        #[cfg(feature = "graphics_debugger")]
+        unsafe {
+            self.device.stop_graphics_debugger_capture()
+        };
+
+        Ok(result)
+    }
+
+    /// Lexes a source pack, reads token count, then records caller GPU work.
+    ///
+    /// The count boundary is a deliberate synchronization point for downstream
+    /// phases that need exact token counts before recording their dispatches.
+    pub async fn with_recorded_resident_source_pack_tokens_after_count<S, T, R, E>(
+        &self,
+        sources: &[S],
+        record_more: impl FnOnce(
+            &wgpu::Device,
+            &wgpu::Queue,
+            &buffers::GpuBuffers,
+            u32,
+            &mut wgpu::CommandEncoder,
+            Option<&mut GpuTimer>,
+        ) -> std::result::Result<T, E>,
+        consume_after_submit: impl FnOnce(&wgpu::Device, &wgpu::Queue, T) -> std::result::Result<R, E>,
+    ) -> Result<std::result::Result<R, E>>
+    where
+        S: AsRef<str>,
+    {
+        #[cfg(feature = "graphics_debugger")]
+        unsafe {
+            self.device.start_graphics_debugger_capture()
+        };
+
+        let start_state = 0u32;
+        let skip_kinds = DEFAULT_SKIP_KINDS;
+        let mut guard = self.prepare_buffers_for_source_pack(sources, start_state, skip_kinds)?;
+        let bufs = guard
+            .as_mut()
+            .expect("GpuLexer buffers must exist after source pack preparation");
+        bufs.require_whole_tokens_out()?;
+
+        let use_scopes = self.runtime.validation_scopes;
+        let mut host_timer = HostCompileTimer::new(self.runtime.host_timing);
+
+        #[cfg(feature = "gpu-debug")]
+        let mut debug_output = crate::lexer::debug::DebugOutput::default();
+        #[cfg(feature = "gpu-debug")]
+        let maybe_dbg: Option<&mut crate::lexer::debug::DebugOutput> = Some(&mut debug_output);
+        #[cfg(not(feature = "gpu-debug"))]
+        let maybe_dbg: Option<&mut crate::lexer::debug::DebugOutput> = None;
+
+        let mut lex_encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("lex-source-pack-resident-count-boundary-enc"),
+            });
+
+        {
+            let mut timer_ref: Option<&mut GpuTimer> = None;
+            let mut dbg_ref = maybe_dbg;
+            let mut cache_guard = self
+                .bg_cache
+                .lock()
+                .expect("GpuLexer.bg_cache mutex poisoned");
+            let ctx = crate::gpu::passes_core::PassContext {
+                device: &self.device,
+                encoder: &mut lex_encoder,
+                buffers: &*bufs,
+                maybe_timer: &mut timer_ref,
+                maybe_dbg: &mut dbg_ref,
+                bg_cache: Some(&mut *cache_guard),
+                debug_groups: self.runtime.debug_groups,
+                validation_scopes: use_scopes,
+                batch_compute_passes: self.runtime.batch_compute_passes,
+                dispatch_records: None,
+            };
+            record_all_passes(
+                bufs.n,
+                bufs.nb_dfa,
+                bufs.nb_sum,
+                self.pipeline(),
+                ctx,
+                &self.passes,
+            )?;
+        }
+
+        let token_count_readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("rb.lex.source_pack.resident.token_count"),
+            size: 8,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        lex_encoder.copy_buffer_to_buffer(&bufs.token_count, 0, &token_count_readback, 0, 4);
+        lex_encoder.copy_buffer_to_buffer(
+            &bufs.parser_feature_flags,
+            0,
+            &token_count_readback,
+            4,
+            4,
+        );
+
+        crate::gpu::passes_core::submit_with_optional_validation(
+            &self.device,
+            &self.queue,
+            "lex.source-pack.resident-count-boundary",
+            lex_encoder.finish(),
+            use_scopes,
+            "source-pack resident lex count boundary",
+        );
+
+        let count_slice = token_count_readback.slice(..);
+        crate::gpu::passes_core::map_readback_for_progress(
+            &count_slice,
+            "lex.source-pack.resident.count",
+        );
+        crate::gpu::passes_core::wait_for_map_progress(
+            &self.device,
+            "lex.source-pack.resident.count",
+        )?;
+        let count_bytes = count_slice.get_mapped_range();
+        let token_count = u32_from_first_4(&count_bytes);
+        bufs.parser_feature_flags_value = u32_from_first_4(&count_bytes[4..]);
+        drop(count_bytes);
+        token_count_readback.unmap();
+        if token_count > bufs.n {
+            anyhow::bail!(
+                "source-pack lexer token count unexpectedly exceeds byte capacity: count={}, capacity={}",
+                token_count,
+                bufs.n
+            );
+        }
+        host_timer.stamp("lex.source-pack.count_boundary");
+
+        let mut code_encoder =
+            self.device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("compile-source-pack-after-token-count-enc"),
+                });
+        let timers_on =
+            self.timers_supported && (self.runtime.compile_timing || crate::gpu::trace::enabled());
+        let mut maybe_timer = if timers_on {
+            Some(GpuTimer::new(&self.device, &self.queue, 512))
+        } else {
+            None
+        };
+        if let Some(timer) = maybe_timer.as_mut() {
+            timer.stamp(&mut code_encoder, "compile.after_count.start");
+        }
+        let recorded_more = match record_more(
+            &self.device,
+            &self.queue,
+            bufs,
+            token_count,
+            &mut code_encoder,
+            maybe_timer.as_mut(),
+        ) {
+            Ok(recorded) => recorded,
+            Err(err) => return Ok(Err(err)),
+        };
+        host_timer.stamp("compile.source-pack.record_more");
+        if let Some(timer) = maybe_timer.as_mut() {
+            timer.stamp(&mut code_encoder, "compile.after_count.recorded");
+            timer.resolve(&mut code_encoder);
+        }
+
+        let command_buffer = code_encoder.finish();
+        host_timer.stamp("compile.source-pack.encoder_finish");
+        let submit_timing = crate::gpu::passes_core::submit_with_optional_validation(
+            &self.device,
+            &self.queue,
+            "compile.source-pack.after-token-count",
+            command_buffer,
+            use_scopes,
+            "source-pack compile after token count",
+        );
+        host_timer.stamp("compile.source-pack.submit");
+
+        drop(guard);
+        host_timer.stamp("lex.source-pack.resident.retained");
+
+        let result = consume_after_submit(&self.device, &self.queue, recorded_more);
+        host_timer.stamp("compile.source-pack.finish");
+        if let Some(stamps) = maybe_timer
+            .as_ref()
+            .and_then(|timer| timer.try_read(&self.device))
+        {
+            print_timer_trace(
+                &self.runtime,
+                &stamps,
+                maybe_timer.as_ref().expect("timer exists").period_ns(),
+                submit_timing.gpu_anchor,
+            );
+        }
+        host_timer.stamp("compile.source-pack.timer_readback");
+
+        #[cfg(feature = "graphics_debugger")]
+        unsafe {
+            self.device.stop_graphics_debugger_capture()
+        };
+
+        Ok(result)
+    }
+
+    /// Records single-source lexing and caller-provided GPU work in one command stream.
+    pub async fn with_recorded_resident_tokens<S, R, E>(
+        &self,
+        input: &str,
+        record_more: impl FnOnce(
+            &wgpu::Device,
+            &wgpu::Queue,
+            &buffers::GpuBuffers,
+            &mut wgpu::CommandEncoder,
+            Option<&mut GpuTimer>,
+        ) -> std::result::Result<S, E>,
+        consume_after_submit: impl FnOnce(
+            &wgpu::Device,
+            &wgpu::Queue,
+            &buffers::GpuBuffers,
+            S,
+        ) -> std::result::Result<R, E>,
+    ) -> Result<std::result::Result<R, E>> {
+        #[cfg(feature = "graphics_debugger")]
+        unsafe {
+            self.device.start_graphics_debugger_capture()
+        };
+
+        let start_state = 0u32;
+        let skip_kinds = DEFAULT_SKIP_KINDS;
+        let mut guard =
+            self.prepare_buffers_for_input(input, start_state, skip_kinds, LexOptions::default())?;
+        let bufs = guard
+            .as_mut()
+            .expect("GpuLexer buffers must exist after preparation");
+        bufs.require_whole_tokens_out()?;
+
+        let use_scopes = self.runtime.validation_scopes;
+
+        #[cfg(feature = "gpu-debug")]
+        let mut debug_output = crate::lexer::debug::DebugOutput::default();
+        #[cfg(feature = "gpu-debug")]
+        let maybe_dbg: Option<&mut crate::lexer::debug::DebugOutput> = Some(&mut debug_output);
+        #[cfg(not(feature = "gpu-debug"))]
+        let maybe_dbg: Option<&mut crate::lexer::debug::DebugOutput> = None;
+
+        let mut enc = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("lex-resident-recorded-enc"),
+            });
+        let timers_on =
+            self.timers_supported && (self.runtime.compile_timing || crate::gpu::trace::enabled());
+        let mut maybe_timer = if timers_on {
+            Some(GpuTimer::new(&self.device, &self.queue, 512))
+        } else {
+            None
+        };
+        if let Some(timer) = maybe_timer.as_mut() {
+            timer.stamp(&mut enc, "compile.start");
+        }
+
+        {
+            let mut timer_ref = maybe_timer.as_mut();
+            let mut dbg_ref = maybe_dbg;
+            let mut cache_guard = self
+                .bg_cache
+                .lock()
+                .expect("GpuLexer.bg_cache mutex poisoned");
+            let ctx = crate::gpu::passes_core::PassContext {
+                device: &self.device,
+                encoder: &mut enc,
+                buffers: &*bufs,
+                maybe_timer: &mut timer_ref,
+                maybe_dbg: &mut dbg_ref,
+                bg_cache: Some(&mut *cache_guard),
+                debug_groups: self.runtime.debug_groups,
+                validation_scopes: use_scopes,
+                batch_compute_passes: self.runtime.batch_compute_passes,
+                dispatch_records: None,
+            };
+            record_all_passes(
+                bufs.n,
+                bufs.nb_dfa,
+                bufs.nb_sum,
+                self.pipeline(),
+                ctx,
+                &self.passes,
+            )?;
+        }
+        if let Some(timer) = maybe_timer.as_mut() {
+            timer.stamp(&mut enc, "lexer.done");
+        }
+
+        let recorded_more = match record_more(
+            &self.device,
+            &self.queue,
+            bufs,
+            &mut enc,
+            maybe_timer.as_mut(),
+        ) {
+            Ok(recorded) => recorded,
+            Err(err) => return Ok(Err(err)),
+        };
+        if let Some(timer) = maybe_timer.as_mut() {
+            timer.stamp(&mut enc, "compile.recorded");
+            timer.resolve(&mut enc);
+        }
+
+        let submit_timing = crate::gpu::passes_core::submit_with_optional_validation(
+            &self.device,
+            &self.queue,
+            "lex.recorded-with-code",
+            enc.finish(),
+            use_scopes,
+            "recorded resident lex batch",
+        );
+
+        let result = consume_after_submit(&self.device, &self.queue, bufs, recorded_more);
+        if let Some(timer) = maybe_timer
+            .as_ref()
+            .and_then(|timer| timer.try_read(&self.device))
+        {
+            print_timer_trace(
+                &self.runtime,
+                &timer,
+                maybe_timer.as_ref().expect("timer exists").period_ns(),
+                submit_timing.gpu_anchor,
+            );
+        }
+
+        #[cfg(feature = "graphics_debugger")]
+        unsafe {
+            self.device.stop_graphics_debugger_capture()
+        };
+
+        Ok(result)
+    }
+
+    /// Lexes one source, reads token count, then records caller GPU work.
+    pub async fn with_recorded_resident_tokens_after_count<S, R, E>(
+        &self,
+        input: &str,
+        record_more: impl FnOnce(
+            &wgpu::Device,
+            &wgpu::Queue,
+            &buffers::GpuBuffers,
+            u32,
+            &mut wgpu::CommandEncoder,
+            Option<&mut GpuTimer>,
+        ) -> std::result::Result<S, E>,
+        consume_after_submit: impl FnOnce(
+            &wgpu::Device,
+            &wgpu::Queue,
+            &buffers::GpuBuffers,
+            S,
+        ) -> std::result::Result<R, E>,
+    ) -> Result<std::result::Result<R, E>> {
+        #[cfg(feature = "graphics_debugger")]
+        unsafe {
+            self.device.start_graphics_debugger_capture()
+        };
+
+        let start_state = 0u32;
+        let skip_kinds = DEFAULT_SKIP_KINDS;
+        let mut guard =
+            self.prepare_buffers_for_input(input, start_state, skip_kinds, LexOptions::default())?;
+        let bufs = guard
+            .as_mut()
+            .expect("GpuLexer buffers must exist after preparation");
+        bufs.require_whole_tokens_out()?;
+
+        let use_scopes = self.runtime.validation_scopes;
+        let mut host_timer = HostCompileTimer::new(self.runtime.host_timing);
+
+        #[cfg(feature = "gpu-debug")]
+        let mut debug_output = crate::lexer::debug::DebugOutput::default();
+        #[cfg(feature = "gpu-debug")]
+        let maybe_dbg: Option<&mut crate::lexer::debug::DebugOutput> = Some(&mut debug_output);
+        #[cfg(not(feature = "gpu-debug"))]
+        let maybe_dbg: Option<&mut crate::lexer::debug::DebugOutput> = None;
+
+        let mut lex_encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("lex-resident-count-boundary-enc"),
+            });
+
+        {
+            let mut timer_ref: Option<&mut GpuTimer> = None;
+            let mut dbg_ref = maybe_dbg;
+            let mut cache_guard = self
+                .bg_cache
+                .lock()
+                .expect("GpuLexer.bg_cache mutex poisoned");
+            let ctx = crate::gpu::passes_core::PassContext {
+                device: &self.device,
+                encoder: &mut lex_encoder,
+                buffers: &*bufs,
+                maybe_timer: &mut timer_ref,
+                maybe_dbg: &mut dbg_ref,
+                bg_cache: Some(&mut *cache_guard),
+                debug_groups: self.runtime.debug_groups,
+                validation_scopes: use_scopes,
+                batch_compute_passes: self.runtime.batch_compute_passes,
+                dispatch_records: None,
+            };
+            record_all_passes(
+                bufs.n,
+                bufs.nb_dfa,
+                bufs.nb_sum,
+                self.pipeline(),
+                ctx,
+                &self.passes,
+            )?;
+        }
+
+        let token_count_readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("rb.lex.resident.token_count"),
+            size: 8,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        lex_encoder.copy_buffer_to_buffer(&bufs.token_count, 0, &token_count_readback, 0, 4);
+        lex_encoder.copy_buffer_to_buffer(
+            &bufs.parser_feature_flags,
+            0,
+            &token_count_readback,
+            4,
+            4,
+        );
+
+        crate::gpu::passes_core::submit_with_optional_validation(
+            &self.device,
+            &self.queue,
+            "lex.resident-count-boundary",
+            lex_encoder.finish(),
+            use_scopes,
+            "resident lex count boundary",
+        );
+
+        let count_slice = token_count_readback.slice(..);
+        crate::gpu::passes_core::map_readback_for_progress(&count_slice, "lex.resident.count");
+        crate::gpu::passes_core::wait_for_map_progress(&self.device, "lex.resident.count")?;
+        let count_bytes = count_slice.get_mapped_range();
+        let token_count = u32_from_first_4(&count_bytes);
+        bufs.parser_feature_flags_value = u32_from_first_4(&count_bytes[4..]);
+        drop(count_bytes);
+        token_count_readback.unmap();
+        if token_count > bufs.n {
+            anyhow::bail!(
+                "lexer token count unexpectedly exceeds byte capacity: count={}, capacity={}",
+                token_count,
+                bufs.n
+            );
+        }
+        host_timer.stamp("lex.resident.count_boundary");
+
+        let mut code_encoder =
+            self.device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("compile-after-token-count-enc"),
+                });
+        let timers_on =
+            self.timers_supported && (self.runtime.compile_timing || crate::gpu::trace::enabled());
+        let mut maybe_timer = if timers_on {
+            Some(GpuTimer::new(&self.device, &self.queue, 512))
+        } else {
+            None
+        };
+        if let Some(timer) = maybe_timer.as_mut() {
+            timer.stamp(&mut code_encoder, "compile.after_count.start");
+        }
+        let recorded_more = match record_more(
+            &self.device,
+            &self.queue,
+            bufs,
+            token_count,
+            &mut code_encoder,
+            maybe_timer.as_mut(),
+        ) {
+            Ok(recorded) => recorded,
+            Err(err) => return Ok(Err(err)),
+        };
+        host_timer.stamp("compile.record_more");
+        if let Some(timer) = maybe_timer.as_mut() {
+            timer.stamp(&mut code_encoder, "compile.after_count.recorded");
+            timer.resolve(&mut code_encoder);
+        }
+
+        let code_command_buffer = code_encoder.finish();
+        host_timer.stamp("compile.encoder_finish");
+        let submit_timing = crate::gpu::passes_core::submit_with_optional_validation(
+            &self.device,
+            &self.queue,
+            "compile.after-token-count",
+            code_command_buffer,
+            use_scopes,
+            "compile after token count",
+        );
+        host_timer.stamp("compile.submit");
+
+        let result = consume_after_submit(&self.device, &self.queue, bufs, recorded_more);
+        host_timer.stamp("compile.finish");
+        if let Some(stamps) = maybe_timer
+            .as_ref()
+            .and_then(|timer| timer.try_read(&self.device))
+        {
+            print_timer_trace(
+                &self.runtime,
+                &stamps,
+                maybe_timer.as_ref().expect("timer exists").period_ns(),
+                submit_timing.gpu_anchor,
+            );
+        }
+        host_timer.stamp("compile.timer_readback");
+
+        #[cfg(feature = "graphics_debugger")]
+        unsafe {
+            self.device.stop_graphics_debugger_capture()
+        };
+
+        Ok(result)
+    }
+
+    /// Lexes one source, reads token count, releases lexer buffers, then records parser work.
+    ///
+    /// The parser receives cloned handles in `ResidentLexerParserInputs`. This
+    /// reduces resident memory pressure before downstream buffers are allocated.
+    pub async fn with_recorded_resident_parser_inputs_after_count_releasing_lexer<S, R, E>(
+        &self,
+        input: &str,
+        record_more: impl FnOnce(
+            &wgpu::Device,
+            &wgpu::Queue,
+            &ResidentLexerParserInputs,
+            u32,
+            &mut wgpu::CommandEncoder,
+            Option<&mut GpuTimer>,
+        ) -> std::result::Result<S, E>,
+        consume_after_submit: impl FnOnce(
+            &wgpu::Device,
+            &wgpu::Queue,
+            &ResidentLexerParserInputs,
+            S,
+        ) -> std::result::Result<R, E>,
+    ) -> Result<std::result::Result<R, E>> {
+        #[cfg(feature = "graphics_debugger")]
+        unsafe {
+            self.device.start_graphics_debugger_capture()
+        };
+
+        let start_state = 0u32;
+        let skip_kinds = DEFAULT_SKIP_KINDS;
+        let mut guard =
+            self.prepare_buffers_for_input(input, start_state, skip_kinds, LexOptions::default())?;
+        let bufs = guard
+            .as_mut()
+            .expect("GpuLexer buffers must exist after preparation");
+        bufs.require_whole_tokens_out()?;
+
+        let use_scopes = self.runtime.validation_scopes;
+        let mut host_timer = HostCompileTimer::new(self.runtime.host_timing);
+
+        #[cfg(feature = "gpu-debug")]
+        let mut debug_output = crate::lexer::debug::DebugOutput::default();
+        #[cfg(feature = "gpu-debug")]
+        let maybe_dbg: Option<&mut crate::lexer::debug::DebugOutput> = Some(&mut debug_output);
+        #[cfg(not(feature = "gpu-debug"))]
+        let maybe_dbg: Option<&mut crate::lexer::debug::DebugOutput> = None;
+
+        let mut lex_encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("lex-resident-count-boundary-enc"),
+            });
+
+        {
+            let mut timer_ref: Option<&mut GpuTimer> = None;
+            let mut dbg_ref = maybe_dbg;
+            let mut cache_guard = self
+                .bg_cache
+                .lock()
+                .expect("GpuLexer.bg_cache mutex poisoned");
+            let ctx = crate::gpu::passes_core::PassContext {
+                device: &self.device,
+                encoder: &mut lex_encoder,
+                buffers: &*bufs,
+                maybe_timer: &mut timer_ref,
+                maybe_dbg: &mut dbg_ref,
+                bg_cache: Some(&mut *cache_guard),
+                debug_groups: self.runtime.debug_groups,
+                validation_scopes: use_scopes,
+                batch_compute_passes: self.runtime.batch_compute_passes,
+                dispatch_records: None,
+            };
+            record_all_passes(
+                bufs.n,
+                bufs.nb_dfa,
+                bufs.nb_sum,
+                self.pipeline(),
+                ctx,
+                &self.passes,
+            )?;
+        }
+
+        let token_count_readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("rb.lex.resident.token_count"),
+            size: 8,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        lex_encoder.copy_buffer_to_buffer(&bufs.token_count, 0, &token_count_readback, 0, 4);
+        lex_encoder.copy_buffer_to_buffer(
+            &bufs.parser_feature_flags,
+            0,
+            &token_count_readback,
+            4,
+            4,
+        );
+
+        crate::gpu::passes_core::submit_with_optional_validation(
+            &self.device,
+            &self.queue,
+            "lex.resident-count-boundary",
+            lex_encoder.finish(),
+            use_scopes,
+            "resident lex count boundary",
+        );
+
+        let count_slice = token_count_readback.slice(..);
+        crate::gpu::passes_core::map_readback_for_progress(&count_slice, "lex.resident.count");
+        crate::gpu::passes_core::wait_for_map_progress(&self.device, "lex.resident.count")?;
+        let count_bytes = count_slice.get_mapped_range();
+        let token_count = u32_from_first_4(&count_bytes);
+        bufs.parser_feature_flags_value = u32_from_first_4(&count_bytes[4..]);
+        drop(count_bytes);
+        token_count_readback.unmap();
+        if token_count > bufs.n {
+            anyhow::bail!(
+                "lexer token count unexpectedly exceeds byte capacity: count={}, capacity={}",
+                token_count,
+                bufs.n
+            );
+        }
+        host_timer.stamp("lex.resident.count_boundary");
+
+        let parser_inputs = ResidentLexerParserInputs::from_buffers(bufs);
+        *guard = None;
+        drop(guard);
+        if let Ok(mut cache) = self.bg_cache.lock() {
+            cache.clear();
+        } else {
+            warn!("failed to clear lexer bind-group cache (poisoned mutex)");
+        }
+        crate::gpu::poll::wait_for_submitted_work(&self.device, "lex.resident.release")?;
+        host_timer.stamp("lex.resident.released_before_parser");
+
+        let mut code_encoder =
+            self.device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("compile-after-token-count-enc"),
+                });
+        let timers_on =
+            self.timers_supported && (self.runtime.compile_timing || crate::gpu::trace::enabled());
+        let mut maybe_timer = if timers_on {
+            Some(GpuTimer::new(&self.device, &self.queue, 512))
+        } else {
+            None
+        };
+        if let Some(timer) = maybe_timer.as_mut() {
+            timer.stamp(&mut code_encoder, "compile.after_count.start");
+        }
+        let recorded_more = match record_more(
+            &self.device,
+            &self.queue,
+            &parser_inputs,
+            token_count,
+            &mut code_encoder,
+            maybe_timer.as_mut(),
+        ) {
+            Ok(recorded) => recorded,
+            Err(err) => return Ok(Err(err)),
+        };
+        host_timer.stamp("compile.record_more");
+        if let Some(timer) = maybe_timer.as_mut() {
+            timer.stamp(&mut code_encoder, "compile.after_count.recorded");
+            timer.resolve(&mut code_encoder);
+        }
+
+        let code_command_buffer = code_encoder.finish();
+        host_timer.stamp("compile.encoder_finish");
+        let submit_timing = crate::gpu::passes_core::submit_with_optional_validation(
+            &self.device,
+            &self.queue,
+            "compile.after-token-count",
+            code_command_buffer,
+            use_scopes,
+            "compile after token count",
+        );
+        host_timer.stamp("compile.submit");
+
+        let result = consume_after_submit(&self.device, &self.queue, &parser_inputs, recorded_more);
+        host_timer.stamp("compile.finish");
+        if let Some(stamps) = maybe_timer
+            .as_ref()
+            .and_then(|timer| timer.try_read(&self.device))
+        {
+            print_timer_trace(
+                &self.runtime,
+                &stamps,
+                maybe_timer.as_ref().expect("timer exists").period_ns(),
+                submit_timing.gpu_anchor,
+            );
+        }
+        host_timer.stamp("compile.timer_readback");
+
+        #[cfg(feature = "graphics_debugger")]
+        unsafe {
+            self.device.stop_graphics_debugger_capture()
+        };
+
+        Ok(result)
+    }
+
+    /// Lexes one source, reads token count, records work, and releases lexer buffers.
+    ///
+    /// Unlike the parser-input variant, `record_more` still receives `GpuBuffers`
+    /// before the guard is dropped. The buffers must not be used after submit.
+    pub async fn with_recorded_resident_tokens_after_count_releasing_lexer<S, R, E>(
+        &self,
+        input: &str,
+        record_more: impl FnOnce(
+            &wgpu::Device,
+            &wgpu::Queue,
+            &buffers::GpuBuffers,
+            u32,
+            &mut wgpu::CommandEncoder,
+            Option<&mut GpuTimer>,
+        ) -> std::result::Result<S, E>,
+        consume_after_submit: impl FnOnce(&wgpu::Device, &wgpu::Queue, S) -> std::result::Result<R, E>,
+    ) -> Result<std::result::Result<R, E>> {
+        #[cfg(feature = "graphics_debugger")]
+        unsafe {
+            self.device.start_graphics_debugger_capture()
+        };
+
+        let start_state = 0u32;
+        let skip_kinds = DEFAULT_SKIP_KINDS;
+        let mut guard =
+            self.prepare_buffers_for_input(input, start_state, skip_kinds, LexOptions::default())?;
+        let bufs = guard
+            .as_mut()
+            .expect("GpuLexer buffers must exist after preparation");
+        bufs.require_whole_tokens_out()?;
+
+        let use_scopes = self.runtime.validation_scopes;
+        let mut host_timer = HostCompileTimer::new(self.runtime.host_timing);
+
+        #[cfg(feature = "gpu-debug")]
+        let mut debug_output = crate::lexer::debug::DebugOutput::default();
+        #[cfg(feature = "gpu-debug")]
+        let maybe_dbg: Option<&mut crate::lexer::debug::DebugOutput> = Some(&mut debug_output);
+        #[cfg(not(feature = "gpu-debug"))]
+        let maybe_dbg: Option<&mut crate::lexer::debug::DebugOutput> = None;
+
+        let mut lex_encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("lex-resident-count-boundary-enc"),
+            });
+
+        {
+            let mut timer_ref: Option<&mut GpuTimer> = None;
+            let mut dbg_ref = maybe_dbg;
+            let mut cache_guard = self
+                .bg_cache
+                .lock()
+                .expect("GpuLexer.bg_cache mutex poisoned");
+            let ctx = crate::gpu::passes_core::PassContext {
+                device: &self.device,
+                encoder: &mut lex_encoder,
+                buffers: &*bufs,
+                maybe_timer: &mut timer_ref,
+                maybe_dbg: &mut dbg_ref,
+                bg_cache: Some(&mut *cache_guard),
+                debug_groups: self.runtime.debug_groups,
+                validation_scopes: use_scopes,
+                batch_compute_passes: self.runtime.batch_compute_passes,
+                dispatch_records: None,
+            };
+            record_all_passes(
+                bufs.n,
+                bufs.nb_dfa,
+                bufs.nb_sum,
+                self.pipeline(),
+                ctx,
+                &self.passes,
+            )?;
+        }
+
+        let token_count_readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("rb.lex.resident.token_count"),
+            size: 8,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        lex_encoder.copy_buffer_to_buffer(&bufs.token_count, 0, &token_count_readback, 0, 4);
+        lex_encoder.copy_buffer_to_buffer(
+            &bufs.parser_feature_flags,
+            0,
+            &token_count_readback,
+            4,
+            4,
+        );
+
+        crate::gpu::passes_core::submit_with_optional_validation(
+            &self.device,
+            &self.queue,
+            "lex.resident-count-boundary",
+            lex_encoder.finish(),
+            use_scopes,
+            "resident lex count boundary",
+        );
+
+        let count_slice = token_count_readback.slice(..);
+        crate::gpu::passes_core::map_readback_for_progress(&count_slice, "lex.resident.count");
+        crate::gpu::passes_core::wait_for_map_progress(&self.device, "lex.resident.count")?;
+        let count_bytes = count_slice.get_mapped_range();
+        let token_count = u32_from_first_4(&count_bytes);
+        bufs.parser_feature_flags_value = u32_from_first_4(&count_bytes[4..]);
+        drop(count_bytes);
+        token_count_readback.unmap();
+        if token_count > bufs.n {
+            anyhow::bail!(
+                "lexer token count unexpectedly exceeds byte capacity: count={}, capacity={}",
+                token_count,
+                bufs.n
+            );
+        }
+        host_timer.stamp("lex.resident.count_boundary");
+
+        let mut code_encoder =
+            self.device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("compile-after-token-count-enc"),
+                });
+        let timers_on =
+            self.timers_supported && (self.runtime.compile_timing || crate::gpu::trace::enabled());
+        let mut maybe_timer = if timers_on {
+            Some(GpuTimer::new(&self.device, &self.queue, 512))
+        } else {
+            None
+        };
+        if let Some(timer) = maybe_timer.as_mut() {
+            timer.stamp(&mut code_encoder, "compile.after_count.start");
+        }
+        let recorded_more = match record_more(
+            &self.device,
+            &self.queue,
+            bufs,
+            token_count,
+            &mut code_encoder,
+            maybe_timer.as_mut(),
+        ) {
+            Ok(recorded) => recorded,
+            Err(err) => return Ok(Err(err)),
+        };
+        host_timer.stamp("compile.record_more");
+        if let Some(timer) = maybe_timer.as_mut() {
+            timer.stamp(&mut code_encoder, "compile.after_count.recorded");
+            timer.resolve(&mut code_encoder);
+        }
+
+        let code_command_buffer = code_encoder.finish();
+        host_timer.stamp("compile.encoder_finish");
+        let submit_timing = crate::gpu::passes_core::submit_with_optional_validation(
+            &self.device,
+            &self.queue,
+            "compile.after-token-count",
+            code_command_buffer,
+            use_scopes,
+            "compile after token count",
+        );
+        host_timer.stamp("compile.submit");
+
+        *guard = None;
+        drop(guard);
+        if let Ok(mut cache) = self.bg_cache.lock() {
+            cache.clear();
+        } else {
+            warn!("failed to clear lexer bind-group cache (poisoned mutex)");
+        }
+        host_timer.stamp("lex.resident.released");
+
+        let result = consume_after_submit(&self.device, &self.queue, recorded_more);
+        host_timer.stamp("compile.finish");
+        if let Some(stamps) = maybe_timer
+            .as_ref()
+            .and_then(|timer| timer.try_read(&self.device))
+        {
+            print_timer_trace(
+                &self.runtime,
+                &stamps,
+                maybe_timer.as_ref().expect("timer exists").period_ns(),
+                submit_timing.gpu_anchor,
+            );
+        }
+        host_timer.stamp("compile.timer_readback");
+
+        #[cfg(feature = "graphics_debugger")]
+        unsafe {
+            self.device.stop_graphics_debugger_capture()
+        };
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use anyhow::{Result, anyhow};
+
+use super::GpuLexer;
+use crate::lexer::types::Token;
+
+static GPU_LEXER: OnceLock<GpuLexer> = OnceLock::new();
+// The last initialization failure, returned by every later call until
+// `lex_on_gpu_retry_init` clears it. Holding the lock also serializes attempts.
+static INIT_FAILURE: Mutex<Option<InitError>> = Mutex::new(None);
+static FORCED_INIT_FAILURE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Why the process-global lexer could not be created.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitError {
+    causes: Vec<String>,
+}
+
+impl InitError {
+    fn from_chain(err: &anyhow::Error) -> Self {
+        Self {
+            causes: err.chain().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// The failure and its underlying causes, outermost first: the device,
+    /// adapter, or table error that stopped initialization ends the list.
+    pub fn causes(&self) -> &[String] {
+        &self.causes
+    }
+}
+
+impl std::fmt::Display for InitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "initialize GPU lexer: {}", self.causes.join(": "))
+    }
+}
+
+impl std::error::Error for InitError {}
+
+/// Returns the lazily initialized process-global lexer.
+///
+/// A failed initialization is not retried: this and every later call return
+/// the same [`InitError`] until [`lex_on_gpu_retry_init`] is called.
+pub fn try_global_lexer() -> Result<&'static GpuLexer, InitError> {
+    if let Some(lexer) = GPU_LEXER.get() {
+        return Ok(lexer);
+    }
+    let mut failure = INIT_FAILURE.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(lexer) = GPU_LEXER.get() {
+        return Ok(lexer);
+    }
+    if let Some(err) = failure.as_ref() {
+        return Err(err.clone());
+    }
+    match create_global_lexer() {
+        Ok(lexer) => Ok(GPU_LEXER.get_or_init(|| lexer)),
+        Err(err) => {
+            let err = InitError::from_chain(&err);
+            *failure = Some(err.clone());
+            Err(err)
+        }
+    }
+}
+
+/// Forgets a retained initialization failure and tries to create the
+/// process-global lexer again.
+pub fn lex_on_gpu_retry_init() -> Result<&'static GpuLexer, InitError> {
+    INIT_FAILURE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take();
+    try_global_lexer()
+}
+
+/// Makes later process-global lexer initializations fail with `reason` before
+/// touching the GPU device, or stops doing so when `None`. Tests use this to
+/// exercise the failure and retry paths without a broken driver.
+#[doc(hidden)]
+pub fn force_global_lexer_init_failure(reason: Option<&str>) {
+    *FORCED_INIT_FAILURE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = reason.map(str::to_owned);
+}
+
+fn create_global_lexer() -> Result<GpuLexer> {
+    let forced = FORCED_INIT_FAILURE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    if let Some(reason) = forced {
+        return Err(anyhow!("{reason}").context("initialization forced to fail"));
+    }
+    pollster::block_on(GpuLexer::new())
+}
+
+/// Returns the process-global lexer, panicking if GPU initialization fails.
+pub async fn get_global_lexer() -> &'static GpuLexer {
+    try_global_lexer().unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Lexes one source string through the process-global lexer.
+///
+/// Fails with the retained [`InitError`] when the global lexer could not be
+/// created.
+pub async fn lex_on_gpu(input: &str) -> Result<Vec<Token>> {
+    try_global_lexer()?.lex(input).await
+}
@@ -0,0 +1,55 @@
+use std::ops::Range;
+
+use anyhow::Result;
+
+use super::GpuLexer;
+use crate::lexer::{
+    LexOptions,
+    highlight::{HighlightRangeOutput, HighlightSpan, highlight_spans, highlight_spans_in_range},
+    range::LexRangeOptions,
+};
+
+impl GpuLexer {
+    /// Lexes `input` under the [`LexOptions::highlight`] profile and returns
+    /// every token but whitespace with its highlight scope, comments
+    /// included, in source order.
+    ///
+    /// Kept tokens carry the kinds [`GpuLexer::lex`] returns them with; the
+    /// token values, parser inputs, and optional outputs are never read back.
+    pub async fn highlight(&self, input: &str) -> Result<Vec<HighlightSpan>> {
+        let output = self
+            .lex_with_options(input, LexOptions::highlight())
+            .await?;
+        Ok(highlight_spans(&output.all_tokens))
+    }
+
+    /// Like [`GpuLexer::highlight`], but lexes only the window
+    /// [`GpuLexer::lex_range`] picks around `range` of `full_src` and
+    /// returns the spans overlapping it, with absolute offsets and full
+    /// extents.
+    ///
+    /// Uses [`LexRangeOptions::default`].
+    pub async fn relex_highlight(
+        &self,
+        full_src: &str,
+        range: Range<usize>,
+    ) -> Result<HighlightRangeOutput> {
+        let window = self
+            .lex_window(
+                full_src,
+                &range,
+                LexRangeOptions::default(),
+                LexOptions::highlight(),
+            )
+            .await?;
+        Ok(HighlightRangeOutput {
+            spans: highlight_spans_in_range(
+                &window.output.all_tokens,
+                window.span.range().start,
+                &range,
+            ),
+            window: window.span,
+            approximate: window.approximate,
+        })
+    }
+}
@@ -0,0 +1,380 @@
+use std::sync::atomic::Ordering;
+
+use anyhow::{Result, anyhow};
+use log::warn;
+
+use super::GpuLexer;
+use crate::{
+    gpu::limits::{MAX_INPUT_BYTES, narrow_u32},
+    lexer::{
+        bom,
+        buffers,
+        buffers::GpuBuffers,
+        constants::{DFA_BLOCK_WIDTH, N_STATES, PAIR_BLOCK_WIDTH, SKIP_KIND_SLOTS},
+        shebang,
+        types::LexOptions,
+    },
+    logging::{HostSpan, LEXER_GPU},
+};
+
+#[derive(Debug, Clone)]
+struct SourceFileMetadata {
+    starts: Vec<u32>,
+    lens: Vec<u32>,
+}
+
+impl SourceFileMetadata {
+    fn count(&self) -> u32 {
+        self.starts.len() as u32
+    }
+
+    fn capacity(&self) -> u32 {
+        self.count().max(1)
+    }
+}
+
+fn build_source_pack<S: AsRef<str>>(sources: &[S]) -> Result<(Vec<u8>, SourceFileMetadata)> {
+    let file_count = u32::try_from(sources.len())
+        .map_err(|_| anyhow!("source pack has too many source files"))?;
+    let mut bytes = Vec::new();
+    let mut starts = Vec::with_capacity(file_count as usize);
+    let mut lens = Vec::with_capacity(file_count as usize);
+    let mut total_len = 0u64;
+
+    for (file_i, source) in sources.iter().enumerate() {
+        let source_bytes = source.as_ref().as_bytes();
+        let len = u32::try_from(source_bytes.len())
+            .map_err(|_| anyhow!("source file {file_i} is too large to lex"))?;
+        starts.push(narrow_u32("source pack bytes", total_len, MAX_INPUT_BYTES)?);
+        lens.push(len);
+        let file_start = bytes.len();
+        bytes.extend_from_slice(source_bytes);
+        bom::mask_bom(&mut bytes[file_start..]);
+        shebang::mask_shebang(&mut bytes[file_start..]);
+        total_len += u64::from(len);
+    }
+    narrow_u32("source pack bytes", total_len, MAX_INPUT_BYTES)?;
+
+    Ok((bytes, SourceFileMetadata { starts, lens }))
+}
+
+/// Resident buffer shape required by one lexer input.
+///
+/// Buffers are sized to fit the current input exactly, or the
+/// [`GpuLexer::warm_up`] reservation when that is longer, so any change in
+/// shape reallocates. `source_files` is `None` for single-source inputs, which
+/// fit any source-file capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct BufferShape {
+    pub(super) byte_capacity: u32,
+    dfa_blocks: u32,
+    source_files: Option<u32>,
+}
+
+impl BufferShape {
+    pub(super) fn for_input(n: u32, source_files: Option<u32>) -> Self {
+        Self {
+            byte_capacity: align_to_word(n).max(1),
+            dfa_blocks: dfa_blocks(n),
+            source_files,
+        }
+    }
+
+    fn fits(&self, bufs: &GpuBuffers) -> bool {
+        let cap_nb_dfa = (bufs.dfa_02_ping.count / N_STATES) as u32;
+        self.byte_capacity == bufs.in_bytes.byte_size as u32
+            && self.dfa_blocks == cap_nb_dfa
+            && self
+                .source_files
+                .is_none_or(|files| files == bufs.source_file_start.count as u32)
+    }
+}
+
+/// How [`GpuLexer::ensure_capacity`] changed the resident byte capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum CapacityChange {
+    Unchanged,
+    /// Reallocated larger, or allocated for the first time.
+    Grew,
+    Shrank,
+}
+
+impl GpuLexer {
+    /// Locks the resident buffers, reallocating them first when `shape` does
+    /// not fit.
+    ///
+    /// A reallocation swaps the buffers and clears the bind-group cache before
+    /// the lock is released, so callers always write inputs into, and bind, the
+    /// buffers they record against.
+    /// Fails without allocating when the new shape exceeds the device limits.
+    fn ensure_capacity(
+        &self,
+        shape: BufferShape,
+        start_state: u32,
+        skip_kinds: [u32; SKIP_KIND_SLOTS],
+    ) -> Result<(
+        std::sync::MutexGuard<'_, Option<buffers::GpuBuffers>>,
+        CapacityChange,
+    )> {
+        let mut guard = self
+            .buffers
+            .lock()
+            .expect("GpuLexer.buffers mutex poisoned");
+        if guard.as_ref().is_some_and(|bufs| shape.fits(bufs)) {
+            return Ok((guard, CapacityChange::Unchanged));
+        }
+        if guard.is_none() {
+            // Buffers a cancelled lex set aside are safe to reuse once its
+            // submitted work has finished and parked them.
+            let cooled = self
+                .cooled_buffers
+                .lock()
+                .expect("GpuLexer.cooled_buffers mutex poisoned")
+                .take();
+            if let Some(bufs) = cooled.filter(|bufs| shape.fits(bufs)) {
+                *guard = Some(bufs);
+                self.clear_bind_group_cache("failed to clear lexer bind-group cache");
+                return Ok((guard, CapacityChange::Unchanged));
+            }
+        }
+
+        // Drop the old buffers before allocating their replacement.
+        let replaced = guard
+            .take()
+            .map(|bufs| (bufs.allocated_bytes, bufs.in_bytes.byte_size as u32));
+        let replaced_bytes = replaced.map(|(bytes, _)| bytes);
+        let change = match replaced {
+            Some((_, old)) if old == shape.byte_capacity => CapacityChange::Unchanged,
+            Some((_, old)) if old > shape.byte_capacity => CapacityChange::Shrank,
+            _ => CapacityChange::Grew,
+        };
+        let alloc_span = HostSpan::enter(LEXER_GPU, "allocate buffers");
+        let bufs = GpuBuffers::new(
+            &self.device,
+            &self.queue,
+            shape.byte_capacity,
+            shape.source_files.unwrap_or(1).max(1),
+            start_state,
+            self.device_tables()?,
+            skip_kinds,
+            self.runtime.kind_packing(),
+        )?;
+        alloc_span.finish_with(format_args!(
+            "({} bytes for {} input bytes, replacing {replaced_bytes:?} bytes)",
+            bufs.allocated_bytes, shape.byte_capacity
+        ));
+        *guard = Some(bufs);
+        self.buffer_allocations.fetch_add(1, Ordering::Relaxed);
+        self.clear_bind_group_cache("failed to clear lexer bind-group cache");
+        Ok((guard, change))
+    }
+
+    /// Prepares resident buffers and metadata for one source string.
+    pub(super) fn prepare_buffers_for_input<'a>(
+        &'a self,
+        input: &str,
+        start_state: u32,
+        skip_kinds: [u32; SKIP_KIND_SLOTS],
+        options: LexOptions,
+    ) -> Result<std::sync::MutexGuard<'a, Option<buffers::GpuBuffers>>> {
+        self.prepare_buffers_for_input_tracking(input, start_state, skip_kinds, options)
+            .map(|(guard, _)| guard)
+    }
+
+    /// Like [`GpuLexer::prepare_buffers_for_input`], also returning how the
+    /// resident capacity changed.
+    pub(super) fn prepare_buffers_for_input_tracking<'a>(
+        &'a self,
+        input: &str,
+        start_state: u32,
+        skip_kinds: [u32; SKIP_KIND_SLOTS],
+        options: LexOptions,
+    ) -> Result<(
+        std::sync::MutexGuard<'a, Option<buffers::GpuBuffers>>,
+        CapacityChange,
+    )> {
+        let input_bytes = input.as_bytes();
+        let n = narrow_u32("source bytes", input_bytes.len() as u64, MAX_INPUT_BYTES)?;
+
+        let shape = BufferShape::for_input(self.reserved_len(n), None);
+        let (mut guard, change) = self.ensure_capacity(shape, start_state, skip_kinds)?;
+        let bufs = guard
+            .as_mut()
+            .expect("GpuLexer buffers must exist after allocation");
+        self.write_lex_inputs(bufs, input_bytes, n, start_state, skip_kinds, options);
+        self.write_current_source_file_metadata(bufs, n);
+        Ok((guard, change))
+    }
+
+    /// Prepares resident buffers and source-file metadata for a source pack.
+    pub(super) fn prepare_buffers_for_source_pack<'a, S: AsRef<str>>(
+        &'a self,
+        sources: &[S],
+        start_state: u32,
+        skip_kinds: [u32; SKIP_KIND_SLOTS],
+    ) -> Result<std::sync::MutexGuard<'a, Option<buffers::GpuBuffers>>> {
+        let (input_bytes, source_files) = build_source_pack(sources)?;
+        let n = narrow_u32(
+            "source pack bytes",
+            input_bytes.len() as u64,
+            MAX_INPUT_BYTES,
+        )?;
+
+        let shape = BufferShape::for_input(self.reserved_len(n), Some(source_files.capacity()));
+        let (mut guard, _) = self.ensure_capacity(shape, start_state, skip_kinds)?;
+        let bufs = guard
+            .as_mut()
+            .expect("GpuLexer buffers must exist after allocation");
+        self.write_lex_inputs(
+            bufs,
+            &input_bytes,
+            n,
+            start_state,
+            skip_kinds,
+            LexOptions::default(),
+        );
+        self.write_source_pack_metadata(bufs, &source_files);
+        Ok(guard)
+    }
+
+    /// Uploads input bytes and `LexParams` and sets runtime sizes for `n` bytes.
+    fn write_lex_inputs(
+        &self,
+        bufs: &mut buffers::GpuBuffers,
+        input_bytes: &[u8],
+        n: u32,
+        start_state: u32,
+        skip_kinds: [u32; SKIP_KIND_SLOTS],
+        options: LexOptions,
+    ) {
+        self.write_input_bytes(bufs, input_bytes, n);
+        self.write_lex_params(bufs, n, start_state, skip_kinds, options);
+        set_runtime_sizes(bufs, n, dfa_blocks(n), sum_blocks(n));
+        bufs.capture_string_escapes = options.capture_string_escapes;
+        bufs.capture_layout = options.layout_facts;
+        // Only the two-submission readback of `lex_with_options` decodes the
+        // packed tokens; it turns this on once it knows it copies that way.
+        bufs.compress_readback = false;
+    }
+
+    fn write_input_bytes(&self, bufs: &buffers::GpuBuffers, input_bytes: &[u8], n: u32) {
+        if n == 0 {
+            return;
+        }
+
+        let aligned_len = align_to_word(n) as usize;
+        if aligned_len == input_bytes.len()
+            && bom::bom_len(input_bytes) == 0
+            && shebang::shebang_len(input_bytes).is_none()
+        {
+            self.queue.write_buffer(&bufs.in_bytes, 0, input_bytes);
+        } else {
+            let mut tmp = Vec::with_capacity(aligned_len);
+            tmp.extend_from_slice(input_bytes);
+            tmp.resize(aligned_len, 0u8);
+            bom::mask_bom(&mut tmp);
+            shebang::mask_shebang(&mut tmp);
+            self.queue.write_buffer(&bufs.in_bytes, 0, &tmp);
+        }
+    }
+
+    fn write_lex_params(
+        &self,
+        bufs: &buffers::GpuBuffers,
+        n: u32,
+        start_state: u32,
+        skip_kinds: [u32; SKIP_KIND_SLOTS],
+        options: LexOptions,
+    ) {
+        let params = crate::lexer::types::LexParams {
+            n,
+            m: self.token_map.len() as u32,
+            start_state,
+            skip0: skip_kinds[0],
+            skip1: skip_kinds[1],
+            skip2: skip_kinds[2],
+            skip3: skip_kinds[3],
+            capture_accept_states: u32::from(options.capture_accept_states),
+            max_token_len: options.max_token_len,
+            capture_string_escapes: u32::from(options.capture_string_escapes),
+            suspect_classes: crate::lexer::paranoia::suspect_classes(options.paranoia_level),
+            tokens_split_shift: bufs.tokens_split.map_or(0, |split| split.shift),
+        };
+        let mut uniform = encase::UniformBuffer::new(Vec::<u8>::new());
+        uniform.write(&params).expect("failed to encode LexParams");
+        self.queue.write_buffer(&bufs.params, 0, uniform.as_ref());
+        self.queue
+            .write_buffer(&bufs.token_count, 0, &0u32.to_le_bytes());
+        self.queue
+            .write_buffer(&bufs.parser_feature_flags, 0, &0u32.to_le_bytes());
+    }
+
+    /// Writes single-file source metadata into resident buffers.
+    pub(super) fn write_current_source_file_metadata(&self, bufs: &buffers::GpuBuffers, n: u32) {
+        self.queue
+            .write_buffer(&bufs.source_file_count, 0, &1u32.to_le_bytes());
+        self.queue
+            .write_buffer(&bufs.source_file_start, 0, &0u32.to_le_bytes());
+        self.queue
+            .write_buffer(&bufs.source_file_len, 0, &n.to_le_bytes());
+    }
+
+    fn write_source_pack_metadata(
+        &self,
+        bufs: &buffers::GpuBuffers,
+        source_files: &SourceFileMetadata,
+    ) {
+        self.queue.write_buffer(
+            &bufs.source_file_count,
+            0,
+            &source_files.count().to_le_bytes(),
+        );
+        self.write_u32_slice(&bufs.source_file_start, &source_files.starts);
+        self.write_u32_slice(&bufs.source_file_len, &source_files.lens);
+    }
+
+    fn write_u32_slice(&self, buffer: &wgpu::Buffer, values: &[u32]) {
+        if values.is_empty() {
+            return;
+        }
+        let mut bytes = Vec::with_capacity(values.len() * 4);
+        for value in values {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        self.queue.write_buffer(buffer, 0, &bytes);
+    }
+
+    /// Byte capacity to size buffers for an `n`-byte input.
+    fn reserved_len(&self, n: u32) -> u32 {
+        n.max(self.reserved_input_len.load(Ordering::Relaxed))
+    }
+
+    pub(super) fn clear_bind_group_cache(&self, message: &str) {
+        if let Ok(mut cache) = self.bg_cache.lock() {
+            cache.clear();
+        } else {
+            warn!("{message} (poisoned mutex)");
+        }
+    }
+}
+
+/// Rounds `n` up to whole `u32` words; exact for every `n` up to
+/// [`MAX_INPUT_BYTES`].
+fn align_to_word(n: u32) -> u32 {
+    debug_assert!(n <= MAX_INPUT_BYTES, "input length {n} passed the limit");
+    n.next_multiple_of(4)
+}
+
+pub(super) fn dfa_blocks(n: u32) -> u32 {
+    n.div_ceil(DFA_BLOCK_WIDTH)
+}
+
+pub(super) fn sum_blocks(n: u32) -> u32 {
+    n.div_ceil(PAIR_BLOCK_WIDTH)
+}
+
+fn set_runtime_sizes(bufs: &mut buffers::GpuBuffers, n: u32, nb_dfa: u32, nb_sum: u32) {
+    bufs.n = n;
+    bufs.nb_dfa = nb_dfa;
+    bufs.nb_sum = nb_sum;
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+
+use super::GpuLexer;
+use crate::lexer::{
+    memo::{MemoLexOutput, MemoOptions, expand, plan},
+    tables::dfa::StreamingDfa,
+};
+
+impl GpuLexer {
+    /// Lexes `input` one distinct line at a time and returns the same kept
+    /// tokens as [`GpuLexer::lex`].
+    ///
+    /// Distinct lines, and regions where a line does not end in whitespace,
+    /// are lexed once each as files of one source pack; the tokens of a line
+    /// are then copied to each place it occurs. Inputs below the thresholds
+    /// in `options`, and inputs the host DFA walk cannot split, are lexed
+    /// whole with [`MemoLexOutput::memoized`] unset.
+    pub async fn lex_memoized(&self, input: &str, options: MemoOptions) -> Result<MemoLexOutput> {
+        let dfa = StreamingDfa::new();
+        let Some(plan) = plan(&dfa, input, &options) else {
+            return Ok(MemoLexOutput {
+                tokens: self.lex(input).await?,
+                lexed_bytes: input.len(),
+                ..MemoLexOutput::default()
+            });
+        };
+        let pieces: Vec<&str> = plan
+            .pieces
+            .iter()
+            .map(|piece| &input[piece.clone()])
+            .collect();
+        let packed = self.lex_source_pack(&pieces).await?;
+        Ok(MemoLexOutput {
+            tokens: expand(&plan, &packed),
+            memoized: true,
+            lines: plan.lines,
+            unique_lines: plan.unique_lines,
+            fallback_bytes: plan.fallback_bytes,
+            lexed_bytes: pieces.iter().map(|piece| piece.len()).sum(),
+        })
+    }
+}
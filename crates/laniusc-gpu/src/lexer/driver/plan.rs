@@ -0,0 +1,349 @@
+//! Adapterless dry runs of one lex.
+
+use anyhow::Result;
+
+use super::{COUNT_READBACK_BYTES, DEFAULT_SKIP_KINDS, DfaTable, GpuLexer, inputs};
+use crate::{
+    gpu::{
+        dry_run::{ArtifactShapes, PassShapes, PipelinePlan},
+        limits::{MAX_INPUT_BYTES, narrow_u32},
+        passes_core::{InputElements, Pass},
+    },
+    lexer::{
+        buffers::GpuBuffers,
+        constants::N_STATES,
+        passes::{
+            LEXER_STEPS,
+            escape_spans::EscapeSpansPass,
+            plan_layout_passes,
+            plan_steps,
+            tokens_compress::TokensCompressPass,
+        },
+        types::{LexOptions, LexerRuntimeOptions, ReadbackMode},
+    },
+};
+
+/// Buffers, shared DFA tables, and dispatches of one planned lex.
+pub type LexPlan = PipelinePlan;
+
+/// Byte size of each shared DFA table for `n_states` states.
+fn table_bytes(table: DfaTable, n_states: usize) -> u64 {
+    let words = match table {
+        DfaTable::NextEmit => (256 * n_states).div_ceil(2),
+        DfaTable::NextU8 => 256 * n_states.div_ceil(4),
+        DfaTable::TokenMap => n_states,
+    };
+    words as u64 * 4
+}
+
+impl GpuLexer {
+    /// Plans one lex of an `input_len`-byte source without creating any
+    /// device objects, taking pass shapes from the built reflection
+    /// artifacts.
+    ///
+    /// The plan matches what [`GpuLexer::lex_with_options`] would allocate
+    /// and dispatch for a fresh lexer; `options` decides the readback
+    /// staging, and `tok_types` takes the default
+    /// [`LexerRuntimeOptions::kind_packing`]. Fails when the input exceeds [`MAX_INPUT_BYTES`] or an
+    /// artifact does not match its pass.
+    pub fn plan(input_len: usize, options: LexOptions) -> Result<LexPlan> {
+        Self::plan_with_shapes(input_len, options, &ArtifactShapes)
+    }
+
+    /// Like [`GpuLexer::plan`], with pass shapes from `shapes`.
+    pub fn plan_with_shapes(
+        input_len: usize,
+        options: LexOptions,
+        shapes: &dyn PassShapes,
+    ) -> Result<LexPlan> {
+        let n = narrow_u32("source bytes", input_len as u64, MAX_INPUT_BYTES)?;
+        let byte_capacity = inputs::BufferShape::for_input(n, None).byte_capacity;
+        let buffers = GpuBuffers::plan(
+            byte_capacity,
+            1,
+            0,
+            N_STATES,
+            DEFAULT_SKIP_KINDS,
+            LexerRuntimeOptions::default().kind_packing(),
+        );
+
+        let outputs = ["tokens_out", "types_compact"]
+            .into_iter()
+            .chain(
+                options
+                    .capture_accept_states
+                    .then_some("lexer.accept_states"),
+            )
+            .filter_map(|label| buffers.entry(label))
+            .map(|entry| entry.byte_size)
+            .sum::<u64>();
+        let readback_bytes = match options.readback {
+            ReadbackMode::Full => COUNT_READBACK_BYTES + outputs,
+            ReadbackMode::CountOnly => COUNT_READBACK_BYTES,
+            ReadbackMode::None => 0,
+        };
+
+        let mut plan = LexPlan::new(buffers);
+        plan.readback_bytes = readback_bytes;
+        for table in DfaTable::ALL {
+            plan.share_table(table.label(), table_bytes(table, N_STATES));
+        }
+        plan_steps(
+            &mut plan,
+            shapes,
+            n,
+            inputs::dfa_blocks(n),
+            inputs::sum_blocks(n),
+            1,
+            &LEXER_STEPS,
+        )?;
+        if options.capture_string_escapes {
+            plan.dispatch(
+                shapes,
+                &EscapeSpansPass::binding_contract(),
+                EscapeSpansPass::NAME,
+                InputElements::Elements1D(n),
+            )?;
+        }
+        if options.compress_readback
+            && options.readback == ReadbackMode::Full
+            && outputs > options.single_submission_max_bytes
+        {
+            plan.dispatch(
+                shapes,
+                &TokensCompressPass::binding_contract(),
+                TokensCompressPass::NAME,
+                InputElements::Elements1D(n),
+            )?;
+        }
+        if options.layout_facts {
+            plan_layout_passes(&mut plan, shapes, n, inputs::sum_blocks(n))?;
+        }
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gpu::dry_run::DeclaredShapes,
+        lexer::{
+            constants::{DFA_BLOCK_WIDTH, PAIR_BLOCK_WIDTH},
+            kind_packing::KindPacking,
+        },
+    };
+
+    const SHAPES: DeclaredShapes = DeclaredShapes {
+        group_width: 256,
+        storage_buffers: 8,
+    };
+
+    fn plan(input_len: usize) -> LexPlan {
+        GpuLexer::plan_with_shapes(input_len, LexOptions::default(), &SHAPES).unwrap()
+    }
+
+    #[test]
+    fn buffers_match_the_word_aligned_capacity() {
+        let plan = plan(1001);
+        let in_bytes = plan.buffers.entry("in_bytes").unwrap();
+        assert_eq!(in_bytes.byte_size, 1004);
+        let tokens = plan.buffers.entry("tokens_out").unwrap();
+        assert_eq!(tokens.count, 1004);
+        assert_eq!(
+            plan.buffers.entries().len(),
+            GpuBuffers::plan(1004, 1, 0, N_STATES, DEFAULT_SKIP_KINDS, KindPacking::Wide)
+                .entries()
+                .len()
+        );
+    }
+
+    #[test]
+    fn narrow_kind_packing_saves_half_of_tok_types() {
+        let n = 1 << 20;
+        let plan = plan(n);
+        let wide = GpuBuffers::plan(
+            n as u32,
+            1,
+            0,
+            N_STATES,
+            DEFAULT_SKIP_KINDS,
+            KindPacking::Wide,
+        );
+        let tok_types = plan.buffers.entry("tok_types").unwrap().byte_size;
+        let wide_tok_types = wide.entry("tok_types").unwrap().byte_size;
+        assert_eq!(tok_types * 2, wide_tok_types);
+        assert_eq!(
+            wide.total_bytes() - plan.buffers.total_bytes(),
+            wide_tok_types / 2
+        );
+    }
+
+    #[test]
+    fn passes_follow_the_recorded_step_order() {
+        let n = 3 * DFA_BLOCK_WIDTH * PAIR_BLOCK_WIDTH;
+        let plan = plan(n as usize);
+        let labels: Vec<&str> = plan
+            .dispatches
+            .iter()
+            .map(|d| d.label.split('[').next().unwrap())
+            .collect();
+        let mut deduped = labels.clone();
+        deduped.dedup();
+        assert_eq!(
+            deduped,
+            [
+                "source_file_boundaries",
+                "dfa_01_scan_inblock",
+                "dfa_02",
+                "dfa_03_apply_block_prefix",
+                "pair_01_sum_inblock_with_keep",
+                "pair_02",
+                "pair_03_apply_block_prefix_with_keep",
+                "compact_boundaries",
+                "tokens_build",
+            ]
+        );
+        let compactions: Vec<&str> = plan
+            .dispatches
+            .iter()
+            .map(|d| d.label.as_str())
+            .filter(|l| l.starts_with("compact_boundaries"))
+            .collect();
+        assert_eq!(
+            compactions,
+            ["compact_boundaries[KEPT]", "compact_boundaries[ALL]"]
+        );
+        let dfa_rounds = labels.iter().filter(|&&l| l == "dfa_02").count();
+        assert_eq!(
+            dfa_rounds,
+            crate::lexer::util::compute_rounds(n.div_ceil(DFA_BLOCK_WIDTH)) as usize
+        );
+        assert_eq!(
+            plan.dispatches[1].elements,
+            Some(crate::gpu::passes_core::InputElements::Elements1D(n))
+        );
+    }
+
+    #[test]
+    fn escape_capture_adds_one_pass_after_tokens_build() {
+        let plain = plan(4096);
+        let escapes = GpuLexer::plan_with_shapes(
+            4096,
+            LexOptions {
+                capture_string_escapes: true,
+                ..LexOptions::default()
+            },
+            &SHAPES,
+        )
+        .unwrap();
+        assert_eq!(escapes.dispatches.len(), plain.dispatches.len() + 1);
+        let last = escapes.dispatches.last().unwrap();
+        assert_eq!(last.label, "escape_spans");
+        assert_eq!(
+            last.elements,
+            Some(crate::gpu::passes_core::InputElements::Elements1D(4096))
+        );
+    }
+
+    #[test]
+    fn layout_facts_add_their_passes_after_tokens_build() {
+        let plain = plan(4096);
+        let layout = GpuLexer::plan_with_shapes(
+            4096,
+            LexOptions {
+                layout_facts: true,
+                ..LexOptions::default()
+            },
+            &SHAPES,
+        )
+        .unwrap();
+        let added: Vec<_> = layout.dispatches[plain.dispatches.len()..]
+            .iter()
+            .map(|dispatch| dispatch.label.as_str())
+            .collect();
+        // layout_02 reruns the pair block scan, one dispatch per round.
+        let pair_rounds = plain
+            .dispatches
+            .iter()
+            .filter(|dispatch| dispatch.label.starts_with("pair_02["))
+            .count();
+        let scan_rounds = added[1..]
+            .iter()
+            .take_while(|label| label.starts_with("layout_02["))
+            .count();
+        assert!(scan_rounds > 0);
+        assert_eq!(scan_rounds, pair_rounds);
+        assert_eq!(added.first(), Some(&"layout_01_facts"));
+        assert_eq!(
+            &added[scan_rounds + 1..],
+            ["layout_03_apply_block_prefix", "layout_04_propagate_indent"]
+        );
+    }
+
+    #[test]
+    fn compressed_readback_packs_only_two_submission_lexes() {
+        let plain = plan(1 << 20);
+        let compressed = |input_len, single_submission_max_bytes| {
+            GpuLexer::plan_with_shapes(
+                input_len,
+                LexOptions {
+                    compress_readback: true,
+                    single_submission_max_bytes,
+                    ..LexOptions::default()
+                },
+                &SHAPES,
+            )
+            .unwrap()
+        };
+
+        let large = compressed(1 << 20, 0);
+        assert_eq!(large.dispatches.len(), plain.dispatches.len() + 1);
+        assert_eq!(large.dispatches.last().unwrap().label, "tokens_compress");
+        let small = compressed(1 << 20, u64::MAX);
+        assert_eq!(small.dispatches.len(), plain.dispatches.len());
+    }
+
+    #[test]
+    fn readback_and_tables_follow_the_options() {
+        let full = plan(4096);
+        let count_only = GpuLexer::plan_with_shapes(
+            4096,
+            LexOptions {
+                readback: ReadbackMode::CountOnly,
+                ..LexOptions::default()
+            },
+            &SHAPES,
+        )
+        .unwrap();
+        assert_eq!(count_only.readback_bytes, COUNT_READBACK_BYTES);
+        let tokens = full.buffers.entry("tokens_out").unwrap().byte_size;
+        let kinds = full.buffers.entry("types_compact").unwrap().byte_size;
+        assert_eq!(full.readback_bytes, COUNT_READBACK_BYTES + tokens + kinds);
+        assert_eq!(
+            full.estimated_vram_bytes() - count_only.estimated_vram_bytes(),
+            tokens + kinds
+        );
+
+        let tables: Vec<_> = full.shared_tables.iter().map(|&(label, _)| label).collect();
+        assert_eq!(tables, DfaTable::ALL.map(DfaTable::label));
+        assert_eq!(
+            table_bytes(DfaTable::NextEmit, N_STATES),
+            ((256 * N_STATES + 1) / 2 * 4) as u64
+        );
+    }
+
+    #[test]
+    fn oversized_inputs_fail_before_planning() {
+        let err = GpuLexer::plan_with_shapes(
+            MAX_INPUT_BYTES as usize + 1,
+            LexOptions::default(),
+            &SHAPES,
+        )
+        .unwrap_err();
+        assert!(
+            err.downcast_ref::<crate::gpu::limits::LimitError>()
+                .is_some()
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use std::ops::Range;
+
+use anyhow::{Result, bail};
+
+use super::GpuLexer;
+use crate::{
+    lexer::{
+        LexOptions,
+        LexOutput,
+        range::{
+            LexRangeOptions,
+            RangeLexOutput,
+            tokens_in_range,
+            trusted_window_end,
+            window_start,
+        },
+        tables::dfa::StreamingDfa,
+    },
+    span::Span,
+};
+
+impl GpuLexer {
+    /// Lexes only a window of `full_src` around `range` and returns the kept
+    /// tokens overlapping it, with absolute offsets and full spans.
+    ///
+    /// Uses [`LexRangeOptions::default`]; see [`GpuLexer::lex_range_with_options`].
+    pub async fn lex_range(&self, full_src: &str, range: Range<usize>) -> Result<RangeLexOutput> {
+        self.lex_range_with_options(full_src, range, LexRangeOptions::default())
+            .await
+    }
+
+    /// Like [`GpuLexer::lex_range`], with explicit window sizing.
+    ///
+    /// The window starts at a character-boundary token start where a full
+    /// lex provably resynchronizes (see [`crate::lexer::resync`]), searched
+    /// within `backward_slop` and then `max_backward` bytes before the range;
+    /// failing that, it falls back to a top-level line within `max_backward`
+    /// bytes and sets [`RangeLexOutput::approximate`]. The window end grows from
+    /// `forward_slop` up to `max_forward` bytes past the range until the last
+    /// token in the range is known to be complete.
+    pub async fn lex_range_with_options(
+        &self,
+        full_src: &str,
+        range: Range<usize>,
+        options: LexRangeOptions,
+    ) -> Result<RangeLexOutput> {
+        let lex_options = LexOptions {
+            all_tokens: true,
+            ..LexOptions::default()
+        };
+        let window = self
+            .lex_window(full_src, &range, options, lex_options)
+            .await?;
+        Ok(RangeLexOutput {
+            tokens: tokens_in_range(&window.output.tokens, window.span.range().start, &range),
+            window: window.span,
+            approximate: window.approximate,
+        })
+    }
+
+    /// Lexes the window [`GpuLexer::lex_range_with_options`] picks for
+    /// `range` with `lex_options`, which must read the ALL stream back.
+    pub(super) async fn lex_window(
+        &self,
+        full_src: &str,
+        range: &Range<usize>,
+        options: LexRangeOptions,
+        lex_options: LexOptions,
+    ) -> Result<LexedWindow> {
+        if range.start > range.end || range.end > full_src.len() {
+            bail!(
+                "range {}..{} is out of bounds for a source of {} bytes",
+                range.start,
+                range.end,
+                full_src.len()
+            );
+        }
+        if !full_src.is_char_boundary(range.start) || !full_src.is_char_boundary(range.end) {
+            bail!(
+                "range {}..{} does not lie on UTF-8 character boundaries",
+                range.start,
+                range.end
+            );
+        }
+
+        let dfa = StreamingDfa::new();
+        let (start, mut approximate) = window_start(&dfa, full_src, range.start, &options);
+        let mut extra = options.forward_slop.max(1);
+        loop {
+            let end =
+                full_src.ceil_char_boundary(range.end.saturating_add(extra).min(full_src.len()));
+            let output = self
+                .lex_with_options(&full_src[start..end], lex_options)
+                .await?;
+            let done = end == full_src.len()
+                || range.end <= start + trusted_window_end(&output.all_tokens);
+            if done || extra >= options.max_forward {
+                approximate |= !done;
+                return Ok(LexedWindow {
+                    output,
+                    span: Span::from_usize(start, end - start),
+                    approximate,
+                });
+            }
+            extra = (extra * 2).min(options.max_forward);
+        }
+    }
+}
+
+/// Lex of the window around a range, with window-relative tokens.
+pub(super) struct LexedWindow {
+    pub(super) output: LexOutput,
+    /// Absolute byte window that was lexed.
+    pub(super) span: Span,
+    /// As [`RangeLexOutput::approximate`].
+    pub(super) approximate: bool,
+}
@@ -0,0 +1,228 @@
+use anyhow::{Result, anyhow};
+
+use super::buffers;
+use crate::{
+    gpu::decode::decode_u32s,
+    lexer::{
+        paranoia::SuspectToken,
+        types::{EscapeSpan, GpuToken, Token},
+        util::{
+            apply_raw_kinds_from_mapped,
+            read_tokens_from_mapped,
+            tokens_from_all_boundaries,
+            u32_from_first_4,
+        },
+    },
+    span::Span,
+};
+
+/// Reads resident source-pack token buffers back to host `Token` records.
+pub(super) fn read_resident_tokens(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    bufs: &buffers::GpuBuffers,
+) -> Result<Vec<Token>> {
+    let count_readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("rb.lex.source_pack.count"),
+        size: 4,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut count_encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("lex-source-pack-count-readback"),
+    });
+    count_encoder.copy_buffer_to_buffer(&bufs.token_count, 0, &count_readback, 0, 4);
+    crate::gpu::passes_core::submit_with_progress(
+        queue,
+        "lex.source-pack.count-readback",
+        count_encoder.finish(),
+    );
+
+    let count_slice = count_readback.slice(..);
+    crate::gpu::passes_core::map_readback_for_progress(&count_slice, "lex.source-pack.count");
+    crate::gpu::passes_core::wait_for_map_progress(device, "lex.source-pack.count")?;
+    let count_bytes = count_slice.get_mapped_range();
+    let token_count = u32_from_first_4(&count_bytes) as usize;
+    drop(count_bytes);
+    count_readback.unmap();
+    if token_count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let need_bytes = (token_count * std::mem::size_of::<GpuToken>()) as u64;
+    let tokens_readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("rb.lex.source_pack.tokens"),
+        size: need_bytes,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut tokens_encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("lex-source-pack-token-readback"),
+    });
+    tokens_encoder.copy_buffer_to_buffer(&bufs.tokens_out, 0, &tokens_readback, 0, need_bytes);
+    let raw_kind_bytes = (token_count * std::mem::size_of::<u32>()) as u64;
+    let raw_kinds_readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("rb.lex.source_pack.raw_kinds"),
+        size: raw_kind_bytes,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    tokens_encoder.copy_buffer_to_buffer(
+        &bufs.types_compact,
+        0,
+        &raw_kinds_readback,
+        0,
+        raw_kind_bytes,
+    );
+    crate::gpu::passes_core::submit_with_progress(
+        queue,
+        "lex.source-pack.token-readback",
+        tokens_encoder.finish(),
+    );
+
+    let tokens_slice = tokens_readback.slice(0..need_bytes);
+    crate::gpu::passes_core::map_readback_for_progress(&tokens_slice, "lex.source-pack.tokens");
+    let raw_kinds_slice = raw_kinds_readback.slice(..);
+    crate::gpu::passes_core::map_readback_for_progress(
+        &raw_kinds_slice,
+        "lex.source-pack.raw_kinds",
+    );
+    crate::gpu::passes_core::wait_for_map_progress(device, "lex.source-pack.tokens")?;
+    let mapped = tokens_slice.get_mapped_range();
+    let mut tokens = read_tokens_from_mapped(&mapped, token_count).map_err(anyhow::Error::msg)?;
+    drop(mapped);
+    tokens_readback.unmap();
+    let mapped = raw_kinds_slice.get_mapped_range();
+    apply_raw_kinds_from_mapped(&mut tokens, &mapped).map_err(anyhow::Error::msg)?;
+    drop(mapped);
+    raw_kinds_readback.unmap();
+    Ok(tokens)
+}
+
+/// Reads the all-boundary stream, including skipped tokens, back to host
+/// `Token` records with their pre-skip DFA kinds.
+pub(super) fn read_all_boundary_tokens(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    bufs: &buffers::GpuBuffers,
+) -> Result<Vec<Token>> {
+    if bufs.n == 0 {
+        return Ok(Vec::new());
+    }
+    let count = read_u32s(device, queue, &bufs.all_token_count, 1, "lex.all.count")?[0] as usize;
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+    let end_positions = read_u32s(
+        device,
+        queue,
+        &bufs.end_positions_all,
+        count,
+        "lex.all.ends",
+    )?;
+    let kinds = read_u32s(device, queue, &bufs.types_all, count, "lex.all.kinds")?;
+    tokens_from_all_boundaries(&end_positions, &kinds).map_err(anyhow::Error::msg)
+}
+
+/// Reads the escape spans of the last lex, sorted by start; `escape_spans`
+/// appends them in atomic arrival order.
+pub(super) fn read_escape_spans(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    bufs: &buffers::GpuBuffers,
+) -> Result<Vec<EscapeSpan>> {
+    let count = read_u32s(
+        device,
+        queue,
+        &bufs.escape_span_count,
+        1,
+        "lex.escapes.count",
+    )?[0] as usize;
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+    anyhow::ensure!(
+        count * 2 <= bufs.escape_spans.count,
+        "lexer reported {count} escape spans for {} span slots",
+        bufs.escape_spans.count / 2
+    );
+    let words = read_u32s(
+        device,
+        queue,
+        &bufs.escape_spans,
+        count * 2,
+        "lex.escapes.spans",
+    )?;
+    let mut spans: Vec<_> = words
+        .chunks_exact(2)
+        .map(|pair| {
+            Span::from_start_len(pair[0], pair[1])
+                .map(|span| EscapeSpan { span })
+                .ok_or_else(|| anyhow!("escape span {}+{} passes u32::MAX", pair[0], pair[1]))
+        })
+        .collect::<Result<_>>()?;
+    spans.sort_unstable();
+    Ok(spans)
+}
+
+/// Reads the paranoia-mode suspect list of the last lex, sorted by kept
+/// index; `tokens_build` appends it in atomic arrival order.
+pub(super) fn read_suspect_tokens(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    bufs: &buffers::GpuBuffers,
+) -> Result<Vec<SuspectToken>> {
+    let count = read_u32s(device, queue, &bufs.suspect_count, 1, "lex.suspects.count")?[0] as usize;
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+    anyhow::ensure!(
+        count * 2 <= bufs.suspect_tokens.count,
+        "lexer reported {count} suspect tokens for {} suspect slots",
+        bufs.suspect_tokens.count / 2
+    );
+    let words = read_u32s(
+        device,
+        queue,
+        &bufs.suspect_tokens,
+        count * 2,
+        "lex.suspects.tokens",
+    )?;
+    let mut suspects: Vec<_> = words
+        .chunks_exact(2)
+        .map(|pair| SuspectToken {
+            token_index: pair[0] as usize,
+            classes: pair[1],
+        })
+        .collect();
+    suspects.sort_unstable();
+    Ok(suspects)
+}
+
+pub(in crate::lexer) fn read_u32s(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    buffer: &wgpu::Buffer,
+    count: usize,
+    label: &str,
+) -> Result<Vec<u32>> {
+    let size = (count * std::mem::size_of::<u32>()) as u64;
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        size,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder =
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some(label) });
+    encoder.copy_buffer_to_buffer(buffer, 0, &readback, 0, size);
+    crate::gpu::passes_core::submit_with_progress(queue, label, encoder.finish());
+
+    let slice = readback.slice(..);
+    crate::gpu::passes_core::map_readback_blocking(device, &slice, label)?;
+    let mapped = slice.get_mapped_range();
+    let words = decode_u32s(&mapped);
+    drop(mapped);
+    readback.unmap();
+    Ok(words)
+}
@@ -0,0 +1,229 @@
+//! Device copies of the DFA tables, uploaded on first use.
+//!
+//! The host tables live in [`GpuLexer`] for its lifetime. Each device copy is
+//! created the first time a buffer allocation binds it, under the resident
+//! buffers mutex, and is then shared by every later allocation instead of
+//! being uploaded again.
+
+use std::sync::OnceLock;
+
+use anyhow::{Result, anyhow};
+
+use super::GpuLexer;
+use crate::gpu::{
+    buffers::{LaniusBuffer, storage_ro_from_u32s_with_queue},
+    decode::decode_u32s,
+};
+
+/// Words summed from each end of a table by the upload check.
+const CHECKSUM_EDGE_WORDS: usize = 64;
+
+/// A DFA table the lexer passes bind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DfaTable {
+    /// Packed transition and emit table, two `u16` entries per `u32`.
+    NextEmit,
+    /// Byte-indexed transition table, four `u8` states per `u32`.
+    NextU8,
+    /// Accepting state to token kind.
+    TokenMap,
+}
+
+impl DfaTable {
+    /// Every table, in upload order.
+    pub const ALL: [Self; 3] = [Self::NextEmit, Self::NextU8, Self::TokenMap];
+
+    /// Label of the table's device buffer in the memory report.
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::NextEmit => "lexer.table.next_emit",
+            Self::NextU8 => "lexer.table.next_u8",
+            Self::TokenMap => "lexer.table.token_map",
+        }
+    }
+}
+
+/// Device copies of the DFA tables shared by every resident buffer set.
+#[derive(Clone)]
+pub struct DfaTableBuffers {
+    pub next_emit: LaniusBuffer<u32>,
+    pub next_u8: LaniusBuffer<u32>,
+    pub token_map: LaniusBuffer<u32>,
+}
+
+/// Lazily uploaded device copies, one slot per [`DfaTable`].
+#[derive(Default)]
+pub(super) struct TableSet {
+    slots: [OnceLock<LaniusBuffer<u32>>; 3],
+}
+
+impl TableSet {
+    fn slot(&self, table: DfaTable) -> &OnceLock<LaniusBuffer<u32>> {
+        &self.slots[table as usize]
+    }
+
+    /// Tables uploaded so far.
+    pub(super) fn uploaded(&self) -> Vec<DfaTable> {
+        DfaTable::ALL
+            .into_iter()
+            .filter(|&table| self.slot(table).get().is_some())
+            .collect()
+    }
+}
+
+/// Wrapping sum of the first and last [`CHECKSUM_EDGE_WORDS`] words, which a
+/// stale or truncated upload is bound to disturb.
+fn edge_checksum(words: &[u32]) -> u32 {
+    let head = &words[..words.len().min(CHECKSUM_EDGE_WORDS)];
+    let tail = &words[words.len().saturating_sub(CHECKSUM_EDGE_WORDS)..];
+    head.iter()
+        .chain(tail)
+        .fold(0u32, |sum, &word| sum.wrapping_add(word))
+}
+
+impl GpuLexer {
+    fn host_table(&self, table: DfaTable) -> &[u32] {
+        match table {
+            DfaTable::NextEmit => &self.next_emit_words,
+            DfaTable::NextU8 => &self.next_u8_packed,
+            DfaTable::TokenMap => &self.token_map,
+        }
+    }
+
+    /// Returns the device copy of `table`, uploading it on first use.
+    ///
+    /// Callers hold the resident buffers mutex, so a table is uploaded once.
+    /// With `LANIUS_VERIFY_LEXER_TABLES` set, a fresh upload is read back and
+    /// checked before it is used.
+    fn device_table(&self, table: DfaTable) -> Result<LaniusBuffer<u32>> {
+        if let Some(buffer) = self.tables.slot(table).get() {
+            return Ok(buffer.clone());
+        }
+        let buffer = storage_ro_from_u32s_with_queue(
+            &self.device,
+            &self.queue,
+            table.label(),
+            self.host_table(table),
+        );
+        if self.runtime.verify_tables {
+            self.verify_table(table, &buffer)?;
+        }
+        Ok(self.tables.slot(table).get_or_init(|| buffer).clone())
+    }
+
+    /// Returns device copies of every table, uploading any not yet resident.
+    pub(super) fn device_tables(&self) -> Result<DfaTableBuffers> {
+        Ok(DfaTableBuffers {
+            next_emit: self.device_table(DfaTable::NextEmit)?,
+            next_u8: self.device_table(DfaTable::NextU8)?,
+            token_map: self.device_table(DfaTable::TokenMap)?,
+        })
+    }
+
+    /// DFA tables uploaded to the device so far.
+    pub fn uploaded_tables(&self) -> Vec<DfaTable> {
+        self.tables.uploaded()
+    }
+
+    /// Reads back the edges of every uploaded table and checks them against
+    /// the host copies, failing on the first table whose upload was stale or
+    /// truncated.
+    pub fn verify_tables(&self) -> Result<()> {
+        for table in self.tables.uploaded() {
+            let buffer = self.tables.slot(table).get().expect("uploaded table");
+            self.verify_table(table, buffer)?;
+        }
+        Ok(())
+    }
+
+    fn verify_table(&self, table: DfaTable, buffer: &LaniusBuffer<u32>) -> Result<()> {
+        let host = self.host_table(table);
+        let edge = host.len().min(CHECKSUM_EDGE_WORDS) as u64 * 4;
+        if edge == 0 {
+            return Ok(());
+        }
+        let label = table.label();
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: edge * 2,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some(label) });
+        encoder.copy_buffer_to_buffer(buffer, 0, &readback, 0, edge);
+        encoder.copy_buffer_to_buffer(buffer, host.len() as u64 * 4 - edge, &readback, edge, edge);
+        crate::gpu::passes_core::submit_with_progress(&self.queue, label, encoder.finish());
+
+        let slice = readback.slice(..);
+        crate::gpu::passes_core::map_readback_blocking(&self.device, &slice, label)?;
+        let mapped = slice.get_mapped_range();
+        let device_sum = decode_u32s(&mapped)
+            .into_iter()
+            .fold(0u32, u32::wrapping_add);
+        drop(mapped);
+        readback.unmap();
+
+        let host_sum = edge_checksum(host);
+        if device_sum != host_sum {
+            return Err(anyhow!(
+                "lexer DFA table {label} failed its upload check: device checksum \
+                 {device_sum:#010x} != host checksum {host_sum:#010x}; the upload was stale or \
+                 truncated"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Flips bits in the host copy of `table`, so [`GpuLexer::verify_tables`]
+    /// sees an upload that no longer matches it.
+    #[doc(hidden)]
+    pub fn debug_corrupt_host_table(&mut self, table: DfaTable) {
+        let words = match table {
+            DfaTable::NextEmit => &mut self.next_emit_words,
+            DfaTable::NextU8 => &mut self.next_u8_packed,
+            DfaTable::TokenMap => &mut self.token_map,
+        };
+        if let Some(word) = words.first_mut() {
+            *word ^= 0xA5A5_A5A5;
+        }
+    }
+
+    /// Makes the device token map send `state` to `kind`, while the test CPU
+    /// oracle keeps the real entry, so tests can plant a known-bad table entry.
+    ///
+    /// # Panics
+    ///
+    /// When the token map is already uploaded; call this before the first lex.
+    #[doc(hidden)]
+    pub fn debug_override_token_map(&mut self, state: usize, kind: u32) {
+        assert!(
+            self.tables.slot(DfaTable::TokenMap).get().is_none(),
+            "the token map is already on the device"
+        );
+        self.token_map[state] = kind;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edge_checksum_sums_both_ends_of_a_table() {
+        assert_eq!(edge_checksum(&[]), 0);
+        // Short tables are covered twice, as the device readback copies both
+        // overlapping edges.
+        assert_eq!(edge_checksum(&[1, 2, 3]), 12);
+
+        let words: Vec<u32> = (0..1000).collect();
+        let head: u32 = (0..64).sum();
+        let tail: u32 = (936..1000).sum();
+        assert_eq!(edge_checksum(&words), head + tail);
+
+        let mut corrupt = words.clone();
+        corrupt[999] += 1;
+        assert_ne!(edge_checksum(&corrupt), edge_checksum(&words));
+    }
+}
@@ -1,28 +1,24 @@
-use crate::gpu::timer::MINIMUM_TIME_TO_NOT_ELIDE_MS;
+use crate::{gpu::timer::TimerReadout, lexer::types::LexerRuntimeOptions, logging::LEXER_GPU};
 
-/// Prints and records GPU timing spans for combined lexer/compile submissions.
+/// Logs and records GPU timing spans for combined lexer/compile submissions.
 pub(super) fn print_timer_trace(
-    stamps: &[(String, u64)],
+    options: &LexerRuntimeOptions,
+    readout: &TimerReadout,
     period_ns: f32,
     gpu_anchor: std::time::Instant,
 ) {
-    if stamps.len() < 2 {
-        return;
-    }
-    let min_ms = std::env::var("LANIUS_GPU_COMPILE_TIMING_MIN_MS")
-        .ok()
-        .and_then(|value| value.parse::<f64>().ok())
-        .unwrap_or(MINIMUM_TIME_TO_NOT_ELIDE_MS);
-    let print_enabled = crate::gpu::env::env_bool_truthy("LANIUS_GPU_COMPILE_TIMING", false)
-        || crate::gpu::env::env_bool_truthy("LANIUS_GPU_TIMING", false);
-    let mut last = stamps[0].1;
-    let mut total = 0.0f64;
-    for (label, value) in stamps.iter().skip(1) {
-        let dt_ms = value.saturating_sub(last) as f64 * period_ns as f64 / 1_000_000.0;
-        let start_ms = total;
-        total += dt_ms;
+    let min_ms = options.compile_timing_min_ms;
+    let print_enabled = options.compile_timing || options.gpu_timing;
+    for interval in readout.intervals(period_ns) {
+        let label = interval.label.as_str();
+        let dt_ms = interval.gpu_ns / 1_000_000.0;
+        let start_ms = interval.start_ns / 1_000_000.0;
+        let total = start_ms + dt_ms;
         if print_enabled && dt_ms >= min_ms {
-            eprintln!("[gpu_compile_timer] {label}: {dt_ms:.3}ms (total {total:.3}ms)");
+            log::info!(
+                target: LEXER_GPU,
+                "gpu_compile_timer {label}: {dt_ms:.3}ms (total {total:.3}ms)"
+            );
         }
         let lane = if label.starts_with("x86.") {
             "gpu.x86"
@@ -30,7 +26,9 @@ pub(super) fn print_timer_trace(
             "gpu.frontend"
         };
         crate::gpu::trace::record_gpu_span(lane, label, gpu_anchor, start_ms, dt_ms);
-        last = *value;
+    }
+    if print_enabled && !readout.health.is_clean() {
+        log::warn!(target: LEXER_GPU, "gpu_compile_timer {:?}", readout.health);
     }
 }
 
@@ -44,13 +42,10 @@ pub(super) struct HostCompileTimer {
 
 impl HostCompileTimer {
     /// Starts a host timer for compile paths that cross a lexer count boundary.
-    pub(super) fn new() -> Self {
+    pub(super) fn new(print_enabled: bool) -> Self {
         let now = std::time::Instant::now();
         Self {
-            print_enabled: crate::gpu::env::env_bool_truthy(
-                "LANIUS_GPU_COMPILE_HOST_TIMING",
-                false,
-            ),
+            print_enabled,
             trace_enabled: crate::gpu::trace::enabled(),
             start: now,
             last: now,
@@ -66,7 +61,10 @@ impl HostCompileTimer {
         let dt_ms = now.duration_since(self.last).as_secs_f64() * 1000.0;
         let total_ms = now.duration_since(self.start).as_secs_f64() * 1000.0;
         if self.print_enabled {
-            eprintln!("[gpu_compile_host_timer] {label}: {dt_ms:.3}ms (total {total_ms:.3}ms)");
+            log::info!(
+                target: LEXER_GPU,
+                "gpu_compile_host_timer {label}: {dt_ms:.3}ms (total {total_ms:.3}ms)"
+            );
         }
         if self.trace_enabled {
             crate::gpu::trace::record_host_span("host.lexer", label, self.last, now);
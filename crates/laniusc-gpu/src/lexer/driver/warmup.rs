@@ -0,0 +1,145 @@
+use std::sync::atomic::Ordering;
+
+use anyhow::Result;
+
+use super::{DEFAULT_SKIP_KINDS, GpuLexer};
+use crate::{
+    gpu::{
+        limits::{MAX_INPUT_BYTES, narrow_u32},
+        passes_core::{PassContext, WarmupReport},
+    },
+    lexer::{
+        constants::{DFA_BLOCK_WIDTH, PAIR_BLOCK_WIDTH},
+        passes::{prefix_variants, record_all_passes},
+        types::LexOptions,
+    },
+};
+
+impl GpuLexer {
+    /// Sizes resident buffers for inputs of up to `expected_input_len` bytes
+    /// and runs the full pass sequence over synthetic inputs of that capacity,
+    /// so the first real lex pays no allocation, bind-group, or first-dispatch
+    /// cost.
+    ///
+    /// Later inputs of at most `expected_input_len` bytes reuse the warmed
+    /// buffers and bind groups; a longer input reallocates to fit it. Results
+    /// are discarded, and submission counts, [`GpuLexer::last_lex_stats`], and
+    /// dispatch records are left untouched.
+    pub async fn warm_up(&self, expected_input_len: usize) -> Result<WarmupReport> {
+        let reserved = narrow_u32(
+            "warm-up input bytes",
+            expected_input_len as u64,
+            MAX_INPUT_BYTES,
+        )?;
+        self.reserved_input_len.store(reserved, Ordering::Relaxed);
+
+        let misses_before = self.bind_group_cache_stats().misses;
+        let lens = warm_up_lens(reserved);
+        for &len in &lens {
+            self.warm_up_run(len)?;
+        }
+        let buffers_bytes = self
+            .buffers
+            .lock()
+            .expect("GpuLexer.buffers mutex poisoned")
+            .as_ref()
+            .map_or(0, |bufs| bufs.allocated_bytes);
+        Ok(WarmupReport {
+            buffers_bytes,
+            bind_groups_created: self.bind_group_cache_stats().misses - misses_before,
+            passes_executed: lens.len() as u64,
+        })
+    }
+
+    /// Records, submits, and waits for every lexer pass over `len` spaces.
+    fn warm_up_run(&self, len: u32) -> Result<()> {
+        let input = " ".repeat(len as usize);
+        let mut guard =
+            self.prepare_buffers_for_input(&input, 0, DEFAULT_SKIP_KINDS, LexOptions::default())?;
+        let bufs = guard
+            .as_mut()
+            .expect("GpuLexer buffers must exist after preparation");
+        let mut enc = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("lex-warm-up-enc"),
+            });
+        {
+            let mut cache_guard = self
+                .bg_cache
+                .lock()
+                .expect("GpuLexer.bg_cache mutex poisoned");
+            let ctx = PassContext {
+                device: &self.device,
+                encoder: &mut enc,
+                buffers: &*bufs,
+                maybe_timer: &mut None,
+                maybe_dbg: &mut None,
+                bg_cache: Some(&mut *cache_guard),
+                debug_groups: self.runtime.debug_groups,
+                validation_scopes: self.runtime.validation_scopes,
+                batch_compute_passes: self.runtime.batch_compute_passes,
+                dispatch_records: None,
+            };
+            record_all_passes(
+                bufs.n,
+                bufs.nb_dfa,
+                bufs.nb_sum,
+                self.pipeline(),
+                ctx,
+                &self.passes,
+            )?;
+        }
+        crate::gpu::passes_core::submit_with_progress(&self.queue, "lex.warm-up", enc.finish());
+        crate::gpu::poll::wait_for_submitted_work(&self.device, "lex.warm-up")
+    }
+}
+
+/// Input lengths whose runs bind every prefix-pass variant reachable by
+/// inputs of at most `max_len` bytes, starting with `max_len` itself.
+fn warm_up_lens(max_len: u32) -> Vec<u32> {
+    let mut dfa_seen = [false; 2];
+    let mut pair_seen = [false; 2];
+    let mut lens = Vec::new();
+    let step = DFA_BLOCK_WIDTH.min(PAIR_BLOCK_WIDTH) as usize;
+    for len in std::iter::once(max_len).chain((0..max_len).step_by(step)) {
+        let (dfa, pair) = len_variants(len);
+        if !dfa_seen[dfa as usize] || !pair_seen[pair as usize] {
+            lens.push(len);
+            dfa_seen[dfa as usize] = true;
+            pair_seen[pair as usize] = true;
+        }
+        if dfa_seen == [true; 2] && pair_seen == [true; 2] {
+            break;
+        }
+    }
+    lens
+}
+
+fn len_variants(len: u32) -> (u64, u64) {
+    prefix_variants(
+        len.div_ceil(DFA_BLOCK_WIDTH),
+        len.div_ceil(PAIR_BLOCK_WIDTH),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warm_up_lens_cover_every_reachable_prefix_variant() {
+        for max_len in [0, 1, 255, 256, 257, 1024, 10_000, 1 << 24] {
+            let lens = warm_up_lens(max_len);
+            assert_eq!(lens[0], max_len);
+            assert!(lens.iter().all(|&len| len <= max_len));
+
+            let covered: Vec<_> = lens.iter().map(|&len| len_variants(len)).collect();
+            for len in (0..=max_len.min(1 << 16)).step_by(64) {
+                let (dfa, pair) = len_variants(len);
+                assert!(covered.iter().any(|&(d, _)| d == dfa), "{max_len}: {len}");
+                assert!(covered.iter().any(|&(_, p)| p == pair), "{max_len}: {len}");
+            }
+        }
+    }
+}
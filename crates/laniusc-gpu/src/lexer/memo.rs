@@ -0,0 +1,395 @@
+//! Line-level memoization for repetitive sources.
+//!
+//! [`GpuLexer::lex_memoized`](crate::lexer::GpuLexer::lex_memoized) lexes each
+//! distinct line once, as one file of a source pack, and copies its tokens to
+//! every place the line occurs. That is only sound for a line the full lex
+//! enters at its start state. `plan` proves this on the host: it runs the
+//! DFA over each distinct line from the start state, and a line that ends in
+//! a whitespace run holding its newline leaves the next line at the start
+//! state too, since a whitespace run ends before any kept token. Any other
+//! line, such as one that opens a block comment, starts a fallback region
+//! that runs on in full-lex context until a line ends in whitespace again;
+//! the region is then memoized as a whole.
+
+use std::{collections::HashMap, ops::Range};
+
+use crate::{
+    lexer::{
+        bom::{UTF8_BOM, bom_len},
+        shebang::shebang_len,
+        tables::{
+            dfa::StreamingDfa,
+            tokens::{INVALID_TOKEN, TokenKind},
+        },
+        types::Token,
+    },
+    span::Span,
+};
+
+/// When [`GpuLexer::lex_memoized`](crate::lexer::GpuLexer::lex_memoized)
+/// splits its input into lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoOptions {
+    /// Shortest input, in bytes, that is split; shorter inputs lex whole.
+    pub min_input_bytes: usize,
+    /// Smallest share of lines, in percent, that must repeat an earlier line
+    /// for the split to pay off; below it the input lexes whole.
+    pub min_duplicate_percent: u8,
+}
+
+impl Default for MemoOptions {
+    fn default() -> Self {
+        Self {
+            min_input_bytes: 1 << 20,
+            min_duplicate_percent: 50,
+        }
+    }
+}
+
+/// Result of one [`GpuLexer::lex_memoized`](crate::lexer::GpuLexer::lex_memoized)
+/// call.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoLexOutput {
+    /// Kept tokens with absolute offsets, as [`GpuLexer::lex`](crate::lexer::GpuLexer::lex)
+    /// returns them.
+    pub tokens: Vec<Token>,
+    /// The input was split into lines; otherwise it was lexed whole and the
+    /// counts below are zero.
+    pub memoized: bool,
+    /// Lines in the input, counting a last line without a newline.
+    pub lines: usize,
+    /// Distinct lines lexed once each.
+    pub unique_lines: usize,
+    /// Input bytes in fallback regions, which are lexed once per distinct
+    /// region rather than per line.
+    pub fallback_bytes: usize,
+    /// Bytes uploaded to the GPU.
+    pub lexed_bytes: usize,
+}
+
+/// How a split input is uploaded and put back together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MemoPlan {
+    /// Source-pack files, as input byte ranges: each distinct line and each
+    /// distinct fallback region once, in order of first occurrence.
+    pub(crate) pieces: Vec<Range<usize>>,
+    /// Piece and absolute start of every line or region, in source order.
+    pub(crate) occurrences: Vec<(usize, usize)>,
+    pub(crate) lines: usize,
+    pub(crate) unique_lines: usize,
+    pub(crate) fallback_bytes: usize,
+}
+
+impl MemoPlan {
+    /// Records the fallback region `range`, lexing it once per distinct text:
+    /// like a line, a region starts at the start state.
+    fn close_region<'a>(
+        &mut self,
+        regions: &mut HashMap<&'a [u8], usize>,
+        bytes: &'a [u8],
+        range: Range<usize>,
+    ) {
+        self.fallback_bytes += range.len();
+        let start = range.start;
+        let piece = *regions.entry(&bytes[range.clone()]).or_insert_with(|| {
+            self.pieces.push(range);
+            self.pieces.len() - 1
+        });
+        self.occurrences.push((piece, start));
+    }
+}
+
+/// Runs the DFA from `state` over `bytes`; `None` when the run rejects or
+/// ends a token in a non-accepting state, which only an invalid input does.
+fn run(dfa: &StreamingDfa, mut state: u16, bytes: &[u8]) -> Option<u16> {
+    for &byte in bytes {
+        let next = dfa.next[state as usize][byte as usize];
+        if next.state == dfa.reject || (next.emit && dfa.token_map[state as usize] == INVALID_TOKEN)
+        {
+            return None;
+        }
+        state = next.state;
+    }
+    Some(state)
+}
+
+/// Whether a line leaving the DFA in `state` lets the next line start fresh.
+fn ends_clean(dfa: &StreamingDfa, line: &[u8], state: u16) -> bool {
+    line.ends_with(b"\n") && dfa.token_map[state as usize] == TokenKind::White as u32
+}
+
+/// Splits `src` into distinct lines and fallback regions, or `None` when it
+/// should be lexed whole: it is short, too few lines repeat, it does not lex,
+/// or a line after the first starts with a BOM or `#!`, which a source-pack
+/// file would mask.
+pub(crate) fn plan(dfa: &StreamingDfa, src: &str, options: &MemoOptions) -> Option<MemoPlan> {
+    if src.is_empty() || src.len() < options.min_input_bytes {
+        return None;
+    }
+    let bytes = src.as_bytes();
+    let mut memo: HashMap<&[u8], Result<usize, u16>> = HashMap::new();
+    let mut regions: HashMap<&[u8], usize> = HashMap::new();
+    let mut plan = MemoPlan {
+        pieces: Vec::new(),
+        occurrences: Vec::new(),
+        lines: 0,
+        unique_lines: 0,
+        fallback_bytes: 0,
+    };
+    // Start and DFA state of the open fallback region.
+    let mut region: Option<(usize, u16)> = None;
+    let mut duplicates = 0usize;
+    let mut start = 0;
+
+    for line in bytes.split_inclusive(|&b| b == b'\n') {
+        let line_start = start;
+        start += line.len();
+        if plan.lines > 0 && (line.starts_with(UTF8_BOM) || line.starts_with(b"#!")) {
+            return None;
+        }
+        plan.lines += 1;
+
+        if let Some((region_start, state)) = region {
+            let state = run(dfa, state, line)?;
+            if ends_clean(dfa, line, state) {
+                plan.close_region(&mut regions, bytes, region_start..start);
+                region = None;
+            } else {
+                region = Some((region_start, state));
+            }
+            continue;
+        }
+
+        let entry = match memo.get(line) {
+            Some(&entry) => {
+                duplicates += 1;
+                entry
+            }
+            None => {
+                let prefix = if line_start == 0 {
+                    bom_len(line).max(shebang_len(line).unwrap_or(0))
+                } else {
+                    0
+                };
+                let state = run(dfa, dfa.start, &line[prefix..])?;
+                let entry = if ends_clean(dfa, line, state) {
+                    plan.pieces.push(line_start..start);
+                    plan.unique_lines += 1;
+                    Ok(plan.pieces.len() - 1)
+                } else {
+                    Err(state)
+                };
+                memo.insert(line, entry);
+                entry
+            }
+        };
+        match entry {
+            Ok(piece) => plan.occurrences.push((piece, line_start)),
+            Err(state) => region = Some((line_start, state)),
+        }
+    }
+    if let Some((region_start, _)) = region {
+        plan.close_region(&mut regions, bytes, region_start..src.len());
+    }
+
+    let enough = duplicates * 100 >= plan.lines * usize::from(options.min_duplicate_percent);
+    (enough && plan.unique_lines > 0).then_some(plan)
+}
+
+/// Puts the kept tokens of a source-pack lex of `plan.pieces` back at every
+/// occurrence, in source order.
+pub(crate) fn expand(plan: &MemoPlan, packed: &[Token]) -> Vec<Token> {
+    let mut templates: Vec<&[Token]> = Vec::with_capacity(plan.pieces.len());
+    let mut rest = packed;
+    let mut piece_start = 0;
+    for piece in &plan.pieces {
+        let piece_end = piece_start + piece.len();
+        let count = rest
+            .iter()
+            .position(|token| token.start() >= piece_end)
+            .unwrap_or(rest.len());
+        templates.push(&rest[..count]);
+        rest = &rest[count..];
+        piece_start = piece_end;
+    }
+
+    let mut piece_starts = Vec::with_capacity(plan.pieces.len());
+    let mut packed_start = 0;
+    for piece in &plan.pieces {
+        piece_starts.push(packed_start);
+        packed_start += piece.len();
+    }
+
+    let mut tokens = Vec::with_capacity(
+        plan.occurrences
+            .iter()
+            .map(|&(piece, _)| templates[piece].len())
+            .sum(),
+    );
+    for &(piece, start) in &plan.occurrences {
+        tokens.extend(templates[piece].iter().map(|token| Token {
+            span: Span::from_usize(token.start() - piece_starts[piece] + start, token.len()),
+            ..*token
+        }));
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::test_runner::{Config, TestCaseError, TestRunner};
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{
+        dev::generator::{arb_source_gen_config, gen_source},
+        lexer::test_cpu::lex_on_test_cpu,
+    };
+
+    const ALWAYS: MemoOptions = MemoOptions {
+        min_input_bytes: 0,
+        min_duplicate_percent: 0,
+    };
+
+    /// Host model of `lex_memoized`, with the test CPU oracle lexing each
+    /// source-pack file in place of the GPU.
+    fn oracle_lex_memoized(src: &str, options: &MemoOptions) -> (Vec<Token>, Option<MemoPlan>) {
+        let dfa = StreamingDfa::new();
+        let Some(plan) = plan(&dfa, src, options) else {
+            return (lex_on_test_cpu(src).expect("lex whole"), None);
+        };
+        let mut packed = Vec::new();
+        let mut base = 0;
+        for piece in &plan.pieces {
+            let file = &src[piece.clone()];
+            packed.extend(
+                lex_on_test_cpu(file)
+                    .expect("lex piece")
+                    .into_iter()
+                    .map(|token| Token {
+                        span: Span::from_usize(token.start() + base, token.len()),
+                        ..token
+                    }),
+            );
+            base += file.len();
+        }
+        (expand(&plan, &packed), Some(plan))
+    }
+
+    #[test]
+    fn repeated_lines_are_lexed_once() {
+        let src = "let x = 1;\n".repeat(50) + &"x += 2; // bump\n".repeat(50);
+        let (tokens, plan) = oracle_lex_memoized(&src, &ALWAYS);
+        assert_eq!(tokens, lex_on_test_cpu(&src).unwrap());
+        let plan = plan.expect("memoized");
+        assert_eq!((plan.lines, plan.unique_lines), (100, 2));
+        assert_eq!(plan.pieces, [0..11, 550..566]);
+        assert_eq!(plan.fallback_bytes, 0);
+    }
+
+    #[test]
+    fn multi_line_constructs_fall_back_to_regions() {
+        let line = "let x = 1;\n";
+        let src = format!(
+            "{}/* opened\n{}closed */ x\n{}let y = x\n{}",
+            line.repeat(4),
+            line.repeat(3),
+            line.repeat(4),
+            line.repeat(2),
+        );
+        let (tokens, plan) = oracle_lex_memoized(&src, &ALWAYS);
+        assert_eq!(tokens, lex_on_test_cpu(&src).unwrap());
+        let plan = plan.expect("memoized");
+        // The comment region runs from `/*` through the line that closes it;
+        // `let y = x` after it is a distinct line like any other.
+        let region_start = line.len() * 4;
+        let region_end = src.find(" x\n").unwrap() + 3;
+        assert!(plan.pieces.contains(&(region_start..region_end)));
+        assert_eq!(plan.fallback_bytes, region_end - region_start);
+        assert_eq!(plan.unique_lines, 2);
+
+        // A repeated region is lexed once.
+        let twice = format!("{src}\n{}", &src[region_start..region_end]);
+        let (tokens, plan) = oracle_lex_memoized(&twice, &ALWAYS);
+        assert_eq!(tokens, lex_on_test_cpu(&twice).unwrap());
+        let plan = plan.expect("memoized");
+        assert_eq!(plan.pieces.len(), 4);
+        assert_eq!(plan.fallback_bytes, 2 * (region_end - region_start));
+
+        // A last line without a newline is its own region.
+        let src = line.repeat(8) + "let y = x";
+        let (tokens, plan) = oracle_lex_memoized(&src, &ALWAYS);
+        assert_eq!(tokens, lex_on_test_cpu(&src).unwrap());
+        assert_eq!(plan.unwrap().fallback_bytes, "let y = x".len());
+    }
+
+    #[test]
+    fn invalid_or_prefixed_inputs_lex_whole() {
+        let dfa = StreamingDfa::new();
+        let line = "let x = 1;\n".repeat(4);
+        // Strings do not span lines, so this does not lex at all.
+        let string = format!("{line}let s = \"a\nb\";\n{line}");
+        assert_eq!(plan(&dfa, &string, &ALWAYS), None);
+        let shebang = format!("{line}#!/bin/lanius\n");
+        assert_eq!(plan(&dfa, &shebang, &ALWAYS), None);
+        let bom = format!("{line}\u{feff}{line}");
+        assert_eq!(plan(&dfa, &bom, &ALWAYS), None);
+
+        // Either prefix is fine on the first line.
+        for prefix in ["#!/bin/lanius\n", "\u{feff}"] {
+            let src = format!("{prefix}{line}");
+            let (tokens, plan) = oracle_lex_memoized(&src, &ALWAYS);
+            assert!(plan.is_some(), "{prefix:?}");
+            assert_eq!(tokens, lex_on_test_cpu(&src).unwrap(), "{prefix:?}");
+        }
+    }
+
+    #[test]
+    fn thresholds_leave_short_and_varied_inputs_whole() {
+        let dfa = StreamingDfa::new();
+        let src = "let x = 1;\n".repeat(10);
+        assert_eq!(plan(&dfa, "", &ALWAYS), None);
+        let short = MemoOptions {
+            min_input_bytes: src.len() + 1,
+            ..ALWAYS
+        };
+        assert_eq!(plan(&dfa, &src, &short), None);
+        // Nine of the ten lines repeat the first.
+        let picky = MemoOptions {
+            min_duplicate_percent: 91,
+            ..ALWAYS
+        };
+        assert_eq!(plan(&dfa, &src, &picky), None);
+        let lenient = MemoOptions {
+            min_duplicate_percent: 90,
+            ..ALWAYS
+        };
+        assert!(plan(&dfa, &src, &lenient).is_some());
+    }
+
+    #[test]
+    fn generated_sources_match_a_whole_lex() {
+        let mut runner = TestRunner::new(Config {
+            cases: 200,
+            failure_persistence: None,
+            ..Config::default()
+        });
+        runner
+            .run(
+                &(arb_source_gen_config(), proptest::num::u64::ANY),
+                |(config, seed)| {
+                    let unique = gen_source(&mut StdRng::seed_from_u64(seed), &config);
+                    let Ok(expected) = lex_on_test_cpu(&unique.repeat(3)) else {
+                        return Ok(());
+                    };
+                    let src = unique.repeat(3);
+                    let (tokens, _) = oracle_lex_memoized(&src, &ALWAYS);
+                    if tokens != expected {
+                        return Err(TestCaseError::fail(format!("source: {src:?}")));
+                    }
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+}
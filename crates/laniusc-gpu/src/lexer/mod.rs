@@ -0,0 +1,109 @@
+//! GPU lexer phase.
+//!
+//! The lexer owns compact DFA table loading, source byte upload, source-pack
+//! file metadata, the GPU pass sequence that emits token boundaries, and the
+//! resident token buffers consumed by parser and compile paths.
+
+/// Boundary-level lex statistics reduced without building tokens.
+pub use laniusc_core::lexer::analysis;
+/// Leading UTF-8 byte order mark handling shared by the driver and the test
+/// oracle.
+pub use laniusc_core::lexer::bom;
+/// Per-byte token-boundary decision shared with the lexer shaders.
+pub use laniusc_core::lexer::boundary;
+/// Resident lexer buffer model.
+pub mod buffers;
+/// Constants shared with the lexer shaders through a generated header.
+pub use laniusc_core::lexer::constants;
+/// Optional lexer debug readback buffers.
+pub mod debug;
+/// Delimiter and terminator conventions of string, char, and comment tokens.
+pub use laniusc_core::lexer::delimiters;
+/// Token-stream divergence classification and mismatch reports.
+pub use laniusc_core::lexer::diff;
+/// GPU lexer driver and global lexer entry points.
+pub mod driver;
+/// Escape spans inside string and char literals, and their containing tokens.
+pub use laniusc_core::lexer::escapes;
+/// GPU-produced conservative parser-family feature flags.
+pub use laniusc_core::lexer::features;
+/// Coarse token classes for syntax highlighting.
+pub use laniusc_core::lexer::highlight;
+/// Lane widths of the per-byte token-kind buffer the GPU passes share.
+pub use laniusc_core::lexer::kind_packing;
+/// Per-token newline, indentation and comment facts for formatters.
+pub use laniusc_core::lexer::layout;
+/// Host-side lints for block comment terminators and nesting attempts.
+pub use laniusc_core::lexer::lints;
+/// Line-level memoization of repetitive sources.
+pub mod memo;
+/// Integer literal decoding keyed on DFA accept states.
+pub use laniusc_core::lexer::numeric;
+/// Which pipeline stage produces each token kind.
+pub use laniusc_core::lexer::origin;
+/// Paranoia mode: CPU rechecks of the tokens the GPU flags as suspect.
+pub use laniusc_core::lexer::paranoia;
+/// GPU shader pass declarations for lexing.
+pub mod passes;
+/// Kind and byte-range token queries over GPU-resident lexer output.
+pub mod query;
+/// Window selection and token clipping for range-restricted lexing.
+pub use laniusc_core::lexer::range;
+/// Safe lex restart points before arbitrary byte offsets.
+pub use laniusc_core::lexer::resync;
+/// Source round-trip checks over all-boundary and kept token streams.
+pub use laniusc_core::lexer::roundtrip;
+/// Leading `#!` line handling shared by the driver and the test oracle.
+pub use laniusc_core::lexer::shebang;
+/// Offset remapping for sources assembled from several named pieces.
+pub mod source_map;
+/// Lexer DFA and token tables.
+pub use laniusc_core::lexer::tables;
+/// Flat binary token-stream files for non-Rust tools.
+pub use laniusc_core::lexer::tokens_io;
+/// Host and GPU token record types.
+pub mod types;
+pub use driver::{GpuLexer, InitError, LexPlan, lex_on_gpu, lex_on_gpu_retry_init};
+pub use highlight::{HighlightRangeOutput, HighlightScope, HighlightSpan};
+/// TEST-ONLY CPU lexer oracle.
+///
+/// This module exists for integration tests and fuzz-test tooling that compare
+/// GPU lexer output against an intentionally named host oracle. Compiler code
+/// must not use it as a fallback; the opt-in paranoia recheck is its only
+/// caller outside tests and tooling.
+#[doc(hidden)]
+pub use laniusc_core::lexer::test_cpu;
+/// UTF-8 boundary policy for token byte ranges.
+pub use laniusc_core::lexer::utf8;
+/// Small lexer helpers shared by driver and tests.
+pub use laniusc_core::lexer::util;
+/// Known-good reference hashes of token and parser emit streams.
+pub use laniusc_core::lexer::verify;
+pub use memo::{MemoLexOutput, MemoOptions};
+pub use query::{DeviceTokens, KindMask, QueryOutput, QuerySpec};
+pub use range::{LexRangeOptions, RangeLexOutput, RangeToken};
+pub use source_map::{LineMap, MappedToken, SourceLocation, SourceMap, lex_mapped};
+pub(super) use types::LexParams;
+pub use types::{
+    DEFAULT_SINGLE_SUBMISSION_MAX_BYTES,
+    EscapeSpan,
+    GpuToken,
+    LayoutFacts,
+    LexAnalysis,
+    LexError,
+    LexOptions,
+    LexOutput,
+    LexPipeline,
+    LexReport,
+    LexReportViolation,
+    LexSubmissionStats,
+    LexWarning,
+    LexerRuntimeOptions,
+    PipelineStage,
+    ReadbackMode,
+    SubmissionPolicy,
+    Token,
+};
+pub use utf8::{BoundaryViolation, LexemeError, LexemePolicy, check_token_boundaries, lexemes};
+
+pub use crate::gpu::{debug::DebugBuffer, passes_core::Pass};
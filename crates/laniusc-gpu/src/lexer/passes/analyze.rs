@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use crate::{
+    gpu::passes_core::{DispatchConvention, DispatchDim, PassData},
+    lexer::{buffers::GpuBuffers, debug::DebugOutput},
+};
+
+/// Boundary analysis pass: reduces boundary statistics after the
+/// [`PipelineStage::Scan`](crate::lexer::PipelineStage::Scan) passes, with no
+/// token compacted or built.
+pub struct LexerAnalyzePass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    LexerAnalyzePass,
+    label: "lexer_analyze",
+    entry: "lexer_analyze",
+    shader: "lexer/analyze"
+);
+
+/// [`LexerAnalyzePass`] reading `tok_types` with
+/// [`KindPacking::Narrow`](crate::lexer::kind_packing::KindPacking::Narrow)
+/// lanes; recorded in its place for buffers planned with that packing.
+pub struct LexerAnalyzeNarrowPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    LexerAnalyzeNarrowPass,
+    label: "lexer_analyze_narrow",
+    entry: "lexer_analyze_narrow",
+    shader: "lexer/analyze_narrow"
+);
+
+const BINDINGS: [&str; 6] = [
+    "gParams",
+    "in_bytes",
+    "flags_packed",
+    "tok_types",
+    "s_all_final",
+    "lex_analysis",
+];
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for LexerAnalyzePass {
+    const NAME: &'static str = "lexer_analyze";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &BINDINGS
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &["lexer.analysis"]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        resource_map(b)
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        record_analysis(device, encoder, b, dbg);
+    }
+}
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for LexerAnalyzeNarrowPass {
+    // Same step as the wide pass, so it shares its timer and dispatch name.
+    const NAME: &'static str = "lexer_analyze";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &BINDINGS
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &["lexer.analysis"]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        resource_map(b)
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        record_analysis(device, encoder, b, dbg);
+    }
+}
+
+/// Bindings shared by both packings.
+fn resource_map(b: &GpuBuffers) -> HashMap<String, wgpu::BindingResource<'_>> {
+    use wgpu::BindingResource::*;
+    HashMap::from([
+        (
+            "gParams".into(),
+            Buffer(b.params.as_entire_buffer_binding()),
+        ),
+        ("in_bytes".into(), b.in_bytes.as_entire_binding()),
+        ("flags_packed".into(), b.flags_packed.as_entire_binding()),
+        ("tok_types".into(), b.tok_types.as_entire_binding()),
+        ("s_all_final".into(), b.s_all_final.as_entire_binding()),
+        ("lex_analysis".into(), b.lex_analysis.as_entire_binding()),
+    ])
+}
+
+/// Debug tap shared by both packings.
+fn record_analysis(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    b: &GpuBuffers,
+    dbg: &mut DebugOutput,
+) {
+    dbg.capture(
+        "lexer.analysis",
+        device,
+        encoder,
+        &b.lex_analysis,
+        b.lex_analysis.byte_size,
+    );
+}
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use crate::{
+    gpu::passes_core::{DispatchConvention, DispatchDim, PassData},
+    lexer::{buffers::GpuBuffers, debug::DebugOutput},
+};
+
+/// Compaction pass for all token boundaries, including skipped tokens.
+pub struct CompactBoundariesAllPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    CompactBoundariesAllPass,
+    label: "compact_boundaries_all",
+    entry: "compact_boundaries_all",
+    shader: "lexer/compact_boundaries"
+);
+
+/// [`CompactBoundariesAllPass`] reading `tok_types` with
+/// [`KindPacking::Narrow`](crate::lexer::kind_packing::KindPacking::Narrow)
+/// lanes; recorded in its place for buffers planned with that packing.
+pub struct CompactBoundariesAllNarrowPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    CompactBoundariesAllNarrowPass,
+    label: "compact_boundaries_all_narrow",
+    entry: "compact_boundaries_all_narrow",
+    shader: "lexer/compact_boundaries_narrow"
+);
+
+const BINDINGS: [&str; 7] = [
+    "gParams",
+    "s_all_final",
+    "flags_packed",
+    "tok_types",
+    "end_positions_all",
+    "types_all",
+    "all_token_count",
+];
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for CompactBoundariesAllPass {
+    const NAME: &'static str = "compact_boundaries[ALL]";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &BINDINGS
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &[
+            "lexer.end_positions_all",
+            "lexer.types_all",
+            "lexer.token_count_all",
+        ]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        resource_map(b)
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        record_compacted(device, encoder, b, dbg);
+    }
+}
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for CompactBoundariesAllNarrowPass {
+    // Same step as the wide pass, so it shares its timer and dispatch name.
+    const NAME: &'static str = "compact_boundaries[ALL]";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &BINDINGS
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &[
+            "lexer.end_positions_all",
+            "lexer.types_all",
+            "lexer.token_count_all",
+        ]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        resource_map(b)
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        record_compacted(device, encoder, b, dbg);
+    }
+}
+
+/// Bindings shared by both packings.
+fn resource_map(b: &GpuBuffers) -> HashMap<String, wgpu::BindingResource<'_>> {
+    use wgpu::BindingResource::*;
+    HashMap::from([
+        (
+            "gParams".into(),
+            Buffer(b.params.as_entire_buffer_binding()),
+        ),
+        ("s_all_final".into(), b.s_all_final.as_entire_binding()),
+        ("flags_packed".into(), b.flags_packed.as_entire_binding()),
+        ("tok_types".into(), b.tok_types.as_entire_binding()),
+        (
+            "end_positions_all".into(),
+            b.end_positions_all.as_entire_binding(),
+        ),
+        ("types_all".into(), b.types_all.as_entire_binding()),
+        (
+            "all_token_count".into(),
+            b.all_token_count.as_entire_binding(),
+        ),
+    ])
+}
+
+/// Debug taps shared by both packings.
+fn record_compacted(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    b: &GpuBuffers,
+    dbg: &mut DebugOutput,
+) {
+    dbg.capture(
+        "lexer.end_positions_all",
+        device,
+        encoder,
+        &b.end_positions_all,
+        b.end_positions_all.byte_size,
+    );
+    dbg.capture(
+        "lexer.types_all",
+        device,
+        encoder,
+        &b.types_all,
+        b.types_all.byte_size,
+    );
+    dbg.capture(
+        "lexer.token_count_all",
+        device,
+        encoder,
+        &b.all_token_count,
+        b.all_token_count.byte_size,
+    );
+}
@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+
+use crate::{
+    gpu::passes_core::{DispatchConvention, DispatchDim, PassData},
+    lexer::{buffers::GpuBuffers, debug::DebugOutput},
+};
+
+/// Filters the compacted all-boundary stream down to kept tokens, using the
+/// kept ranks written by `keep_03`.
+pub struct CompactBoundariesKeptPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    CompactBoundariesKeptPass,
+    label: "compact_kept",
+    entry: "compact_kept",
+    shader: "lexer/compact_kept"
+);
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for CompactBoundariesKeptPass {
+    const NAME: &'static str = "compact_boundaries[KEPT]";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gParams",
+            "s_keep_final",
+            "end_positions_all",
+            "types_all",
+            "end_positions",
+            "types_compact",
+            "all_index_compact",
+            "token_count",
+        ]
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &[
+            "lexer.end_positions",
+            "lexer.types_compact",
+            "lexer.all_index_compact",
+            "lexer.token_count",
+        ]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        use wgpu::BindingResource::*;
+        HashMap::from([
+            (
+                "gParams".into(),
+                Buffer(b.params.as_entire_buffer_binding()),
+            ),
+            // keep_03 writes kept ranks over the consumed s_all_final
+            ("s_keep_final".into(), b.s_all_final.as_entire_binding()),
+            (
+                "end_positions_all".into(),
+                b.end_positions_all.as_entire_binding(),
+            ),
+            ("types_all".into(), b.types_all.as_entire_binding()),
+            ("end_positions".into(), b.end_positions.as_entire_binding()),
+            ("types_compact".into(), b.types_compact.as_entire_binding()),
+            (
+                "all_index_compact".into(),
+                b.all_index_compact.as_entire_binding(),
+            ),
+            ("token_count".into(), b.token_count.as_entire_binding()),
+        ])
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        record_compacted(device, encoder, b, dbg);
+    }
+}
+
+/// Keep-channel compaction of kept token boundaries, straight from the keep
+/// flags and the kept ranks `pair_03_apply_block_prefix_with_keep` wrote; the
+/// late filter's [`CompactBoundariesKeptPass`] does this from the compacted
+/// all-boundary stream instead.
+pub struct CompactBoundariesKeptFromFlagsPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    CompactBoundariesKeptFromFlagsPass,
+    label: "compact_boundaries_kept",
+    entry: "compact_boundaries_kept",
+    shader: "lexer/compact_boundaries_kept"
+);
+
+/// [`CompactBoundariesKeptFromFlagsPass`] reading `tok_types` with
+/// [`KindPacking::Narrow`](crate::lexer::kind_packing::KindPacking::Narrow)
+/// lanes; recorded in its place for buffers planned with that packing.
+pub struct CompactBoundariesKeptFromFlagsNarrowPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    CompactBoundariesKeptFromFlagsNarrowPass,
+    label: "compact_boundaries_kept_narrow",
+    entry: "compact_boundaries_kept_narrow",
+    shader: "lexer/compact_boundaries_kept_narrow"
+);
+
+const FROM_FLAGS_BINDINGS: [&str; 9] = [
+    "gParams",
+    "s_keep_final",
+    "s_all_final",
+    "flags_packed",
+    "tok_types",
+    "end_positions",
+    "types_compact",
+    "all_index_compact",
+    "token_count",
+];
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for CompactBoundariesKeptFromFlagsPass {
+    // Same step as the late filter's compaction, so it shares its timer and
+    // dispatch name.
+    const NAME: &'static str = "compact_boundaries[KEPT]";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &FROM_FLAGS_BINDINGS
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        CompactBoundariesKeptPass::expected_debug_keys()
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        from_flags_resource_map(b)
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        record_compacted(device, encoder, b, dbg);
+    }
+}
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput>
+    for CompactBoundariesKeptFromFlagsNarrowPass
+{
+    // Same step as the wide pass, so it shares its timer and dispatch name.
+    const NAME: &'static str = "compact_boundaries[KEPT]";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &FROM_FLAGS_BINDINGS
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        CompactBoundariesKeptPass::expected_debug_keys()
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        from_flags_resource_map(b)
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        record_compacted(device, encoder, b, dbg);
+    }
+}
+
+/// Bindings shared by both packings of the keep-channel compaction.
+fn from_flags_resource_map(b: &GpuBuffers) -> HashMap<String, wgpu::BindingResource<'_>> {
+    use wgpu::BindingResource::*;
+    HashMap::from([
+        (
+            "gParams".into(),
+            Buffer(b.params.as_entire_buffer_binding()),
+        ),
+        // pair_03_apply_block_prefix_with_keep parks the kept ranks here
+        // until compact_boundaries[ALL] writes the real end positions
+        (
+            "s_keep_final".into(),
+            b.end_positions_all.as_entire_binding(),
+        ),
+        ("s_all_final".into(), b.s_all_final.as_entire_binding()),
+        ("flags_packed".into(), b.flags_packed.as_entire_binding()),
+        ("tok_types".into(), b.tok_types.as_entire_binding()),
+        ("end_positions".into(), b.end_positions.as_entire_binding()),
+        ("types_compact".into(), b.types_compact.as_entire_binding()),
+        (
+            "all_index_compact".into(),
+            b.all_index_compact.as_entire_binding(),
+        ),
+        ("token_count".into(), b.token_count.as_entire_binding()),
+    ])
+}
+
+/// Debug taps shared by every kept compaction.
+fn record_compacted(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    b: &GpuBuffers,
+    dbg: &mut DebugOutput,
+) {
+    dbg.capture(
+        "lexer.end_positions",
+        device,
+        encoder,
+        &b.end_positions,
+        b.end_positions.byte_size,
+    );
+    dbg.capture(
+        "lexer.types_compact",
+        device,
+        encoder,
+        &b.types_compact,
+        b.types_compact.byte_size,
+    );
+    dbg.capture(
+        "lexer.all_index_compact",
+        device,
+        encoder,
+        &b.all_index_compact,
+        b.all_index_compact.byte_size,
+    );
+    dbg.capture(
+        "lexer.token_count",
+        device,
+        encoder,
+        &b.token_count,
+        b.token_count.byte_size,
+    );
+}
@@ -0,0 +1,5 @@
+/// Compaction pass for all token boundaries.
+pub mod all;
+/// Compaction passes for kept token boundaries: from the keep flags, or
+/// filtered late from the all-boundary stream.
+pub mod kept;
@@ -0,0 +1,2 @@
+/// All-boundary compaction and both kept-token compactions.
+pub mod boundaries;
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use crate::{
+    gpu::passes_core::{DispatchConvention, DispatchDim, PassData},
+    lexer::{buffers::GpuBuffers, debug::DebugOutput, util::compute_rounds},
+};
+
+/// Third DFA pass: applies block prefixes and emits token boundary flags.
+pub struct Dfa03ApplyBlockPrefixPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    Dfa03ApplyBlockPrefixPass,
+    label: "dfa_03_apply_block_prefix",
+    entry: "dfa_03_apply_block_prefix",
+    shader: "lexer/dfa/03_apply_block_prefix"
+);
+
+/// [`Dfa03ApplyBlockPrefixPass`] writing `tok_types` with
+/// [`KindPacking::Narrow`](crate::lexer::kind_packing::KindPacking::Narrow)
+/// lanes; recorded in its place for buffers planned with that packing.
+pub struct Dfa03ApplyBlockPrefixNarrowPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    Dfa03ApplyBlockPrefixNarrowPass,
+    label: "dfa_03_apply_block_prefix_narrow",
+    entry: "dfa_03_apply_block_prefix_narrow",
+    shader: "lexer/dfa/03_apply_block_prefix_narrow"
+);
+
+const BINDINGS: [&str; 11] = [
+    "gParams",
+    "in_bytes",
+    "source_file_start_flags",
+    "source_file_end_flags",
+    "block_prefix",
+    "chunk_summaries",
+    "token_map",
+    "next_emit",
+    "flags_packed",
+    "tok_types",
+    "dfa_states",
+];
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for Dfa03ApplyBlockPrefixPass {
+    const NAME: &'static str = "dfa_03_apply_block_prefix";
+    const DIM: DispatchDim = DispatchDim::D2; // shader uses 2D tiling over blocks
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &BINDINGS
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &["lexer.block_prefix.applied"]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        apply_block_prefix_resources(b)
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        record_applied_block_prefix(device, encoder, b, dbg);
+    }
+}
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for Dfa03ApplyBlockPrefixNarrowPass {
+    // Same step as the wide pass, so it shares its timer and dispatch name.
+    const NAME: &'static str = "dfa_03_apply_block_prefix";
+    const DIM: DispatchDim = DispatchDim::D2;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &BINDINGS
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &["lexer.block_prefix.applied"]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        apply_block_prefix_resources(b)
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        record_applied_block_prefix(device, encoder, b, dbg);
+    }
+}
+
+/// Bindings shared by every prefix-application variant.
+pub(super) fn apply_block_prefix_resources(
+    b: &GpuBuffers,
+) -> HashMap<String, wgpu::BindingResource<'_>> {
+    use wgpu::BindingResource::*;
+
+    // Pick last-writer of the block scan (dfa_02)
+    let rounds = compute_rounds(b.nb_dfa);
+    let block_prefix_binding = if (rounds % 2) == 1 {
+        b.dfa_02_pong.as_entire_binding()
+    } else {
+        b.dfa_02_ping.as_entire_binding()
+    };
+    debug_assert!(rounds == 0 || b.dfa_02_ping.count == b.dfa_02_pong.count);
+
+    // Bind exactly what the fused Slang shader declares
+    HashMap::from([
+        (
+            "gParams".into(),
+            Buffer(b.params.as_entire_buffer_binding()),
+        ),
+        ("in_bytes".into(), b.in_bytes.as_entire_binding()),
+        (
+            "source_file_start_flags".into(),
+            b.source_file_start_flags.as_entire_binding(),
+        ),
+        (
+            "source_file_end_flags".into(),
+            b.source_file_end_flags.as_entire_binding(),
+        ),
+        ("block_prefix".into(), block_prefix_binding),
+        (
+            "chunk_summaries".into(),
+            b.dfa_chunk_summaries.as_entire_binding(),
+        ),
+        ("token_map".into(), b.token_map.as_entire_binding()),
+        ("next_emit".into(), b.next_emit.as_entire_binding()),
+        ("flags_packed".into(), b.flags_packed.as_entire_binding()),
+        ("tok_types".into(), b.tok_types.as_entire_binding()),
+        ("dfa_states".into(), b.dfa_states.as_entire_binding()),
+    ])
+}
+
+/// Debug tap shared by every prefix-application variant.
+pub(super) fn record_applied_block_prefix(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    b: &GpuBuffers,
+    dbg: &mut DebugOutput,
+) {
+    // Keep a useful tap: show which block-prefix (inclusive scan of block delta) was applied.
+    let rounds = compute_rounds(b.nb_dfa);
+    let last = if (rounds % 2) == 1 {
+        &b.dfa_02_pong
+    } else {
+        &b.dfa_02_ping
+    };
+    dbg.capture(
+        "lexer.block_prefix.applied",
+        device,
+        encoder,
+        last,
+        last.byte_size,
+    );
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use super::apply_block_prefix::{apply_block_prefix_resources, record_applied_block_prefix};
+use crate::{
+    gpu::passes_core::{DispatchConvention, DispatchDim, PassData},
+    lexer::{buffers::GpuBuffers, debug::DebugOutput},
+};
+
+/// Third DFA pass fused with `pair_01`: applies block prefixes, emits token
+/// boundary flags, and writes each block's all-boundary count.
+///
+/// Used by [`LexPipeline::FusedPairSeed`](crate::lexer::LexPipeline::FusedPairSeed)
+/// in place of `dfa_03` followed by `pair_01`.
+pub struct Dfa03ApplyBlockPrefixSeedPairsPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    Dfa03ApplyBlockPrefixSeedPairsPass,
+    label: "dfa_03_apply_block_prefix_seed_pairs",
+    entry: "dfa_03_apply_block_prefix_seed_pairs",
+    shader: "lexer/dfa/03_apply_block_prefix_seed_pairs"
+);
+
+/// [`Dfa03ApplyBlockPrefixSeedPairsPass`] writing `tok_types` with
+/// [`KindPacking::Narrow`](crate::lexer::kind_packing::KindPacking::Narrow)
+/// lanes; recorded in its place for buffers planned with that packing.
+pub struct Dfa03ApplyBlockPrefixSeedPairsNarrowPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    Dfa03ApplyBlockPrefixSeedPairsNarrowPass,
+    label: "dfa_03_apply_block_prefix_seed_pairs_narrow",
+    entry: "dfa_03_apply_block_prefix_seed_pairs_narrow",
+    shader: "lexer/dfa/03_apply_block_prefix_seed_pairs_narrow"
+);
+
+const BINDINGS: [&str; 12] = [
+    "gParams",
+    "in_bytes",
+    "source_file_start_flags",
+    "source_file_end_flags",
+    "block_prefix",
+    "chunk_summaries",
+    "token_map",
+    "next_emit",
+    "flags_packed",
+    "tok_types",
+    "dfa_states",
+    "block_totals_pair",
+];
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for Dfa03ApplyBlockPrefixSeedPairsPass {
+    const NAME: &'static str = "dfa_03_apply_block_prefix_seed_pairs";
+    const DIM: DispatchDim = DispatchDim::D2; // same block tiling as dfa_03
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &BINDINGS
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &["lexer.block_prefix.applied"]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        seed_pairs_resources(b)
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        record_applied_block_prefix(device, encoder, b, dbg);
+    }
+}
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput>
+    for Dfa03ApplyBlockPrefixSeedPairsNarrowPass
+{
+    // Same step as the wide pass, so it shares its timer and dispatch name.
+    const NAME: &'static str = "dfa_03_apply_block_prefix_seed_pairs";
+    const DIM: DispatchDim = DispatchDim::D2;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &BINDINGS
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &["lexer.block_prefix.applied"]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        seed_pairs_resources(b)
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        record_applied_block_prefix(device, encoder, b, dbg);
+    }
+}
+
+/// Bindings shared by both packings of the fused pass.
+fn seed_pairs_resources(b: &GpuBuffers) -> HashMap<String, wgpu::BindingResource<'_>> {
+    let mut res = apply_block_prefix_resources(b);
+    // dfa_03 may still be reading the DFA ping buffer as its block
+    // prefix, so the totals go to their own buffer; see
+    // `copy_pair_seed_totals`.
+    res.insert(
+        "block_totals_pair".into(),
+        b.block_totals_pair.as_entire_binding(),
+    );
+    res
+}
+
+/// Copies the fused pass's block totals into the DFA ping buffer, where
+/// `pair_02` round 0 reads what `pair_01` writes in the split pipeline.
+pub fn copy_pair_seed_totals(encoder: &mut wgpu::CommandEncoder, b: &GpuBuffers) {
+    let bytes = u64::from(b.nb_sum) * std::mem::size_of::<u32>() as u64;
+    if bytes > 0 {
+        encoder.copy_buffer_to_buffer(&b.block_totals_pair, 0, &b.dfa_02_ping, 0, bytes);
+    }
+}
@@ -1,5 +1,7 @@
 /// Applies DFA block prefixes to per-byte state.
 pub mod apply_block_prefix;
+/// Applies DFA block prefixes and sums pair seeds in one pass.
+pub mod apply_block_prefix_seed_pairs;
 /// Prefix-scans DFA block summaries.
 pub mod scan_block_summaries;
 /// Scans DFA state transitions inside blocks.
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use crate::{
+    gpu::passes_core::{
+        DispatchConvention,
+        DispatchDim,
+        DispatchRecord,
+        InputElements,
+        PassData,
+        bind_group::create_bind_group_from_reflection,
+    },
+    lexer::{
+        buffers::GpuBuffers,
+        debug::DebugOutput,
+        passes::{scan_round_bind_group, scan_round_label},
+        tables::dfa::N_STATES,
+        util::compute_rounds,
+    },
+};
+
+/// Shader `WORKGROUP_SIZE`: one group per block, one lane per DFA state.
+const DFA_02_GROUP_SIZE: u32 = 256;
+const _: () = assert!(N_STATES <= DFA_02_GROUP_SIZE as usize);
+
+/// Second DFA pass: prefix-scans block summary functions.
+pub struct Dfa02ScanBlockSummariesPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    Dfa02ScanBlockSummariesPass,
+    label: "dfa_02_scan_block_summaries",
+    entry: "dfa_02_scan_block_summaries",
+    shader: "lexer/dfa/02_scan_block_summaries"
+);
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for Dfa02ScanBlockSummariesPass {
+    const NAME: &'static str = "dfa_02_scan_block_summaries";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementGroups {
+        group_size: DFA_02_GROUP_SIZE,
+    };
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &["gParams", "gScan", "block_ping", "block_pong"]
+    }
+
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &["lexer.block_ping", "lexer.block_pong", "lexer.block_prefix"]
+    }
+
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        _b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        panic!(
+            "we implement this in record_pass to deal with uniforms, which is actually hacky and bad but whatever"
+        );
+    }
+
+    fn record_pass<'a>(
+        &self,
+        ctx: &mut crate::gpu::passes_core::PassContext<'a, GpuBuffers, DebugOutput>,
+        input: crate::gpu::passes_core::InputElements,
+    ) -> anyhow::Result<(), anyhow::Error> {
+        let device = ctx.device;
+        let encoder = &mut ctx.encoder;
+        let b = ctx.buffers;
+        let maybe_timer = &mut ctx.maybe_timer;
+        let maybe_dbg = &mut ctx.maybe_dbg;
+        let mut bg_cache = ctx.bg_cache.as_deref_mut();
+        let debug_groups = ctx.debug_groups;
+        let mut dispatch_records = ctx.dispatch_records.as_deref_mut();
+
+        let use_scopes = ctx.validation_scopes;
+
+        let validation_scope = crate::gpu::passes_core::validation_scope(device, use_scopes);
+
+        let n = match input {
+            InputElements::Elements1D(n) => n,
+            _ => unreachable!(),
+        };
+
+        let rounds = compute_rounds(n);
+
+        let pd = self.data();
+        Self::check_dispatch(pd.thread_group_size)?;
+        let (gx, gy, gz) = crate::gpu::passes_core::plan_dispatch(
+            Self::DISPATCH,
+            Self::DIM,
+            input,
+            pd.thread_group_size,
+        )?;
+
+        let layout0 = &pd.bind_group_layouts[0];
+        let pipeline = &pd.pipeline;
+        let reflection = &pd.reflection;
+
+        let can_batch =
+            maybe_timer.is_none() && maybe_dbg.is_none() && ctx.batch_compute_passes && !use_scopes;
+        let mut retained_bind_groups = Vec::with_capacity(rounds as usize);
+
+        let mut round_bind_group = |r: u32| {
+            scan_round_bind_group(bg_cache.as_deref_mut(), Self::NAME, r, b.generation, || {
+                let scan_params = b.scan_params_for_round(r)?;
+                let res = HashMap::from([
+                    (
+                        "gParams".into(),
+                        wgpu::BindingResource::Buffer(b.params.as_entire_buffer_binding()),
+                    ),
+                    (
+                        "gScan".into(),
+                        wgpu::BindingResource::Buffer(scan_params.as_entire_buffer_binding()),
+                    ),
+                    ("block_ping".into(), b.dfa_02_ping.as_entire_binding()),
+                    ("block_pong".into(), b.dfa_02_pong.as_entire_binding()),
+                ]);
+                create_bind_group_from_reflection(
+                    device,
+                    Some(&format!("func_blocks_bg[{r}]")),
+                    layout0,
+                    reflection,
+                    0,
+                    &res,
+                )
+            })
+        };
+
+        if debug_groups {
+            encoder.push_debug_group(Self::NAME);
+        }
+
+        if can_batch {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some(Self::NAME),
+                timestamp_writes: None,
+            });
+            for r in 0..rounds {
+                let bg = round_bind_group(r)?;
+
+                let label = scan_round_label("dfa_02", r);
+                if debug_groups {
+                    pass.push_debug_group(&label);
+                }
+                pass.set_pipeline(pipeline);
+                pass.set_bind_group(0, &*bg, &[]);
+                pass.dispatch_workgroups(gx, gy, gz);
+                if debug_groups {
+                    pass.pop_debug_group();
+                }
+                if let Some(records) = dispatch_records.as_deref_mut() {
+                    records.push(DispatchRecord::direct(label, (gx, gy, gz), input));
+                }
+                retained_bind_groups.push(bg);
+            }
+        } else {
+            for r in 0..rounds {
+                let bg = round_bind_group(r)?;
+
+                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: Some(Self::NAME),
+                    timestamp_writes: None,
+                });
+                let label = scan_round_label("dfa_02", r);
+                if debug_groups {
+                    pass.push_debug_group(&label);
+                }
+                pass.set_pipeline(pipeline);
+                pass.set_bind_group(0, &*bg, &[]);
+                pass.dispatch_workgroups(gx, gy, gz);
+                if debug_groups {
+                    pass.pop_debug_group();
+                }
+                drop(pass);
+                if let Some(t) = maybe_timer.as_deref_mut() {
+                    t.stamp_round(encoder, label.as_str());
+                }
+                if let Some(records) = dispatch_records.as_deref_mut() {
+                    records.push(DispatchRecord::direct(label, (gx, gy, gz), input));
+                }
+
+                retained_bind_groups.push(bg);
+
+                #[cfg(feature = "gpu-debug")]
+                if let Some(dbg) = maybe_dbg.as_deref_mut() {
+                    let last_writer = if r % 2 == 0 {
+                        &b.dfa_02_pong
+                    } else {
+                        &b.dfa_02_ping
+                    };
+                    let per_round_bytes = ((n as usize) * N_STATES * std::mem::size_of::<u32>())
+                        .min(last_writer.byte_size);
+                    dbg.capture_round(
+                        "lexer.func_scan_round",
+                        r,
+                        device,
+                        encoder,
+                        last_writer,
+                        per_round_bytes,
+                    );
+                }
+            }
+        }
+
+        if debug_groups {
+            encoder.pop_debug_group();
+        }
+
+        if let Some(t) = maybe_timer {
+            t.stamp(encoder, Self::NAME.to_string());
+        }
+
+        if let Some(err) = crate::gpu::passes_core::pop_validation_scope(validation_scope) {
+            return Err(anyhow::anyhow!(
+                "validation in pass {}: {:?}",
+                Self::NAME,
+                err
+            ));
+        }
+
+        if let Some(d) = maybe_dbg.as_deref_mut() {
+            self.record_declared_debug(device, encoder, b, d);
+        }
+        Ok(())
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        dbg.capture(
+            "lexer.block_ping",
+            device,
+            encoder,
+            &b.dfa_02_ping,
+            b.dfa_02_ping.byte_size,
+        );
+        dbg.capture(
+            "lexer.block_pong",
+            device,
+            encoder,
+            &b.dfa_02_pong,
+            b.dfa_02_pong.byte_size,
+        );
+
+        let rounds = compute_rounds(b.nb_dfa);
+
+        let last = if (rounds % 2) == 1 {
+            &b.dfa_02_pong
+        } else {
+            &b.dfa_02_ping
+        };
+        dbg.capture("lexer.block_prefix", device, encoder, last, last.byte_size);
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, PassData},
     lexer::{buffers::GpuBuffers, debug::DebugOutput},
 };
 
@@ -10,7 +10,7 @@ pub struct Dfa01ScanInblockPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     Dfa01ScanInblockPass,
     label: "dfa_01_scan_inblock",
     entry: "dfa_01_scan_inblock",
@@ -20,6 +20,7 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for Dfa01ScanInblockPass {
     const NAME: &'static str = "dfa_01_scan_inblock";
     const DIM: DispatchDim = DispatchDim::D2;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn data(&self) -> &PassData {
         &self.data
@@ -29,6 +30,17 @@ impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for Dfa01ScanInblock
         Self { data }
     }
 
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gParams",
+            "in_bytes",
+            "source_file_start_flags",
+            "next_u8",
+            "block_summaries",
+            "chunk_summary_out",
+        ]
+    }
+
     fn create_resource_map<'a>(
         &self,
         b: &'a GpuBuffers,
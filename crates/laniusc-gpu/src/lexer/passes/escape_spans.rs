@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use crate::{
+    gpu::passes_core::{DispatchConvention, DispatchDim, PassData},
+    lexer::{buffers::GpuBuffers, debug::DebugOutput},
+};
+
+/// Appends an escape span for every byte whose DFA state is `StringEscape` or
+/// `CharEscape`; recorded after `tokens_build` only when string-escape
+/// capture is on.
+pub struct EscapeSpansPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    EscapeSpansPass,
+    label: "escape_spans",
+    entry: "escape_spans",
+    shader: "lexer/escape_spans"
+);
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for EscapeSpansPass {
+    const NAME: &'static str = "escape_spans";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gParams",
+            "in_bytes",
+            "dfa_states",
+            "source_file_end_flags",
+            "escape_span_count",
+            "escape_spans",
+        ]
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &["lexer.escape_spans"]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        HashMap::from([
+            (
+                "gParams".into(),
+                wgpu::BindingResource::Buffer(b.params.as_entire_buffer_binding()),
+            ),
+            ("in_bytes".into(), b.in_bytes.as_entire_binding()),
+            ("dfa_states".into(), b.dfa_states.as_entire_binding()),
+            (
+                "source_file_end_flags".into(),
+                b.source_file_end_flags.as_entire_binding(),
+            ),
+            (
+                "escape_span_count".into(),
+                b.escape_span_count.as_entire_binding(),
+            ),
+            ("escape_spans".into(), b.escape_spans.as_entire_binding()),
+        ])
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        dbg.capture(
+            "lexer.escape_spans",
+            device,
+            encoder,
+            &b.escape_spans,
+            b.escape_spans.byte_size,
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use crate::{
+    gpu::passes_core::{DispatchConvention, DispatchDim, PassData},
+    lexer::{buffers::GpuBuffers, debug::DebugOutput, passes::pair},
+};
+
+/// Third keep pass: applies kept-count block prefixes and writes the
+/// inclusive kept rank of every all-boundary token.
+pub struct Keep03ApplyBlockPrefixPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    Keep03ApplyBlockPrefixPass,
+    label: "keep_03_apply_block_prefix",
+    entry: "keep_03_apply_block_prefix",
+    shader: "lexer/keep/03_apply_block_prefix"
+);
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for Keep03ApplyBlockPrefixPass {
+    const NAME: &'static str = "keep_03_apply_block_prefix";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gParams",
+            "types_all",
+            "all_token_count",
+            "block_prefix_keep",
+            "s_keep_final",
+        ]
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &["lexer.s_keep_final"]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        use wgpu::BindingResource::*;
+
+        // keep_02 is the pair block scan rerun, so it ends on the same plane.
+        let block_prefix_keep: wgpu::BindingResource<'a> =
+            if pair::block_total_scan_last_writer_is_ping(b.nb_sum) {
+                b.dfa_02_ping.as_entire_binding()
+            } else {
+                b.dfa_02_pong.as_entire_binding()
+            };
+
+        HashMap::from([
+            (
+                "gParams".into(),
+                Buffer(b.params.as_entire_buffer_binding()),
+            ),
+            ("types_all".into(), b.types_all.as_entire_binding()),
+            (
+                "all_token_count".into(),
+                b.all_token_count.as_entire_binding(),
+            ),
+            ("block_prefix_keep".into(), block_prefix_keep),
+            // compact_boundaries[ALL] has consumed s_all_final
+            ("s_keep_final".into(), b.s_all_final.as_entire_binding()),
+        ])
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        dbg.capture(
+            "lexer.s_keep_final",
+            device,
+            encoder,
+            &b.s_all_final,
+            b.s_all_final.byte_size,
+        );
+    }
+}
@@ -0,0 +1,4 @@
+/// Applies kept-rank block prefixes over the all-boundary stream.
+pub mod apply_block_prefix;
+/// Counts kept tokens inside each all-boundary block.
+pub mod sum_inblock;
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use crate::{
+    gpu::passes_core::{DispatchConvention, DispatchDim, PassData},
+    lexer::{buffers::GpuBuffers, debug::DebugOutput},
+};
+
+/// First keep pass: counts kept tokens of the compacted all-boundary stream
+/// inside each block.
+pub struct Keep01SumInblockPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    Keep01SumInblockPass,
+    label: "keep_01_sum_inblock",
+    entry: "keep_01_sum_inblock",
+    shader: "lexer/keep/01_sum_inblock"
+);
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for Keep01SumInblockPass {
+    const NAME: &'static str = "keep_01_sum_inblock";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gParams",
+            "types_all",
+            "all_token_count",
+            "block_totals_keep",
+        ]
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &["lexer.block_totals_keep"]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        use wgpu::BindingResource::*;
+        HashMap::from([
+            (
+                "gParams".into(),
+                Buffer(b.params.as_entire_buffer_binding()),
+            ),
+            ("types_all".into(), b.types_all.as_entire_binding()),
+            (
+                "all_token_count".into(),
+                b.all_token_count.as_entire_binding(),
+            ),
+            (
+                "block_totals_keep".into(),
+                // The pair scan is done with DFA block ping; reuse it again
+                b.dfa_02_ping.as_entire_binding(),
+            ),
+        ])
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        dbg.capture(
+            "lexer.block_totals_keep",
+            device,
+            encoder,
+            &b.dfa_02_ping,
+            b.dfa_02_ping.byte_size,
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use crate::{
+    gpu::passes_core::{DispatchConvention, DispatchDim, PassData},
+    lexer::{buffers::GpuBuffers, debug::DebugOutput, passes::pair},
+};
+
+/// Third layout pass: applies line-start block prefixes and writes the
+/// one-based line number of every kept token.
+pub struct Layout03ApplyBlockPrefixPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    Layout03ApplyBlockPrefixPass,
+    label: "layout_03_apply_block_prefix",
+    entry: "layout_03_apply_block_prefix",
+    shader: "lexer/layout/03_apply_block_prefix"
+);
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for Layout03ApplyBlockPrefixPass {
+    const NAME: &'static str = "layout_03_apply_block_prefix";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gParams",
+            "token_count",
+            "layout_facts",
+            "block_prefix_layout",
+            "s_line_final",
+        ]
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &["lexer.s_line_final"]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        use wgpu::BindingResource::*;
+
+        // layout_02 is the pair block scan rerun, so it ends on the same plane.
+        let block_prefix_layout: wgpu::BindingResource<'a> =
+            if pair::block_total_scan_last_writer_is_ping(b.nb_sum) {
+                b.dfa_02_ping.as_entire_binding()
+            } else {
+                b.dfa_02_pong.as_entire_binding()
+            };
+
+        HashMap::from([
+            (
+                "gParams".into(),
+                Buffer(b.params.as_entire_buffer_binding()),
+            ),
+            ("token_count".into(), b.token_count.as_entire_binding()),
+            ("layout_facts".into(), b.layout_facts.as_entire_binding()),
+            ("block_prefix_layout".into(), block_prefix_layout),
+            // tokens_build has consumed the kept ranks in s_all_final
+            ("s_line_final".into(), b.s_all_final.as_entire_binding()),
+        ])
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        dbg.capture(
+            "lexer.s_line_final",
+            device,
+            encoder,
+            &b.s_all_final,
+            b.s_all_final.byte_size,
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+use crate::{
+    gpu::passes_core::{DispatchConvention, DispatchDim, PassData},
+    lexer::{buffers::GpuBuffers, debug::DebugOutput},
+};
+
+/// First layout pass: writes the newlines and line comments before each kept
+/// token, the indentation of tokens that start a line, and the line starts
+/// inside each block.
+pub struct Layout01FactsPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    Layout01FactsPass,
+    label: "layout_01_facts",
+    entry: "layout_01_facts",
+    shader: "lexer/layout/01_facts"
+);
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for Layout01FactsPass {
+    const NAME: &'static str = "layout_01_facts";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gParams",
+            "in_bytes",
+            "token_count",
+            "all_index_compact",
+            "end_positions_all",
+            "types_all",
+            "layout_facts",
+            "block_totals_layout",
+        ]
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &["lexer.block_totals_layout"]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        use wgpu::BindingResource::*;
+        HashMap::from([
+            (
+                "gParams".into(),
+                Buffer(b.params.as_entire_buffer_binding()),
+            ),
+            ("in_bytes".into(), b.in_bytes.as_entire_binding()),
+            ("token_count".into(), b.token_count.as_entire_binding()),
+            (
+                "all_index_compact".into(),
+                b.all_index_compact.as_entire_binding(),
+            ),
+            (
+                "end_positions_all".into(),
+                b.end_positions_all.as_entire_binding(),
+            ),
+            ("types_all".into(), b.types_all.as_entire_binding()),
+            ("layout_facts".into(), b.layout_facts.as_entire_binding()),
+            (
+                "block_totals_layout".into(),
+                // The kept-rank scan is done with DFA block ping; reuse it again
+                b.dfa_02_ping.as_entire_binding(),
+            ),
+        ])
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        dbg.capture(
+            "lexer.block_totals_layout",
+            device,
+            encoder,
+            &b.dfa_02_ping,
+            b.dfa_02_ping.byte_size,
+        );
+    }
+}
@@ -0,0 +1,6 @@
+/// Numbers the lines of kept tokens from the scanned block totals.
+pub mod apply_block_prefix;
+/// Measures the trivia before each kept token and counts line starts.
+pub mod facts;
+/// Copies each line's indentation to the rest of its tokens.
+pub mod propagate_indent;
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use crate::{
+    gpu::passes_core::{DispatchConvention, DispatchDim, PassData},
+    lexer::{buffers::GpuBuffers, debug::DebugOutput},
+};
+
+/// Last layout pass: gives every kept token that does not start a line the
+/// indentation of the token that does.
+pub struct Layout04PropagateIndentPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    Layout04PropagateIndentPass,
+    label: "layout_04_propagate_indent",
+    entry: "layout_04_propagate_indent",
+    shader: "lexer/layout/04_propagate_indent"
+);
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for Layout04PropagateIndentPass {
+    const NAME: &'static str = "layout_04_propagate_indent";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &["gParams", "token_count", "s_line_final", "layout_facts"]
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &["lexer.layout_facts"]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        use wgpu::BindingResource::*;
+        HashMap::from([
+            (
+                "gParams".into(),
+                Buffer(b.params.as_entire_buffer_binding()),
+            ),
+            ("token_count".into(), b.token_count.as_entire_binding()),
+            ("s_line_final".into(), b.s_all_final.as_entire_binding()),
+            ("layout_facts".into(), b.layout_facts.as_entire_binding()),
+        ])
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        dbg.capture(
+            "lexer.layout_facts",
+            device,
+            encoder,
+            &b.layout_facts,
+            b.layout_facts.byte_size,
+        );
+    }
+}
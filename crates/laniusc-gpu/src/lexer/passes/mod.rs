@@ -0,0 +1,1158 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use encase::ShaderType;
+
+use crate::{
+    gpu::{
+        dry_run::{PassShapes, PipelinePlan},
+        passes_core::{BindGroupCache, BindingContract, ComputePassBatch, InputElements},
+    },
+    lexer::{
+        LexPipeline,
+        Pass,
+        PipelineStage,
+        buffers::GpuBuffers,
+        kind_packing::KindPacking,
+        util::compute_rounds,
+    },
+};
+
+/// Boundary statistics reduced after the pair scans.
+pub mod analyze;
+/// Boundary compaction passes.
+pub mod compact;
+/// DFA scanning passes.
+pub mod dfa;
+/// Opt-in string and char escape-span collection pass.
+pub mod escape_spans;
+/// Kept-rank prefix-sum passes over the all-boundary stream.
+pub mod keep;
+/// Opt-in per-token layout-fact passes.
+pub mod layout;
+/// Token-boundary prefix-sum passes.
+pub mod pair;
+/// Source-pack source-file boundary pass.
+pub mod source_file_boundaries;
+/// Final token-record construction pass.
+pub mod tokens_build;
+/// Opt-in packing of kept tokens for compressed readback.
+pub mod tokens_compress;
+
+#[derive(ShaderType, Debug, Clone, Copy)]
+/// Uniform parameters for one prefix-scan round.
+pub(super) struct ScanParams {
+    /// Prefix-scan stride for this round.
+    pub stride: u32,
+    /// Whether the ping buffer is the source for this round.
+    pub use_ping_as_src: u32,
+}
+
+/// Returns the bind group for one block-scan round, reusing a cached group
+/// for the same pass, round, and buffer generation when a cache is present.
+pub(super) fn scan_round_bind_group(
+    cache: Option<&mut BindGroupCache>,
+    pass_name: &str,
+    round: u32,
+    buffers_generation: u64,
+    create: impl FnOnce() -> Result<wgpu::BindGroup>,
+) -> Result<Arc<wgpu::BindGroup>> {
+    let Some(cache) = cache else {
+        return create().map(Arc::new);
+    };
+    let key = format!("{pass_name}::round{round}::gen{buffers_generation}");
+    let groups = cache.get_or_create(&key, 1, || Ok(vec![Arc::new(create()?)]))?;
+    Ok(groups[0].clone())
+}
+
+/// Debug-group and dispatch-record label for one block-scan round, e.g.
+/// `dfa_02[round=1, stride=2]`.
+pub(super) fn scan_round_label(scan: &str, round: u32) -> String {
+    format!("{scan}[round={round}, stride={}]", 1u32 << round)
+}
+
+/// All GPU passes that make up one lexer pipeline.
+pub struct LexerPasses {
+    /// Scans DFA state transitions inside each byte block.
+    pub dfa_01: dfa::scan_inblock::Dfa01ScanInblockPass,
+    /// Prefix-scans DFA block summaries.
+    pub dfa_02: dfa::scan_block_summaries::Dfa02ScanBlockSummariesPass,
+    /// Applies DFA block prefixes and emits boundary flags.
+    pub dfa_03: dfa::apply_block_prefix::Dfa03ApplyBlockPrefixPass,
+    /// `dfa_03` fused with `pair_01`, for [`LexPipeline::FusedPairSeed`].
+    pub dfa_03_seed_pairs: dfa::apply_block_prefix_seed_pairs::Dfa03ApplyBlockPrefixSeedPairsPass,
+    /// `dfa_03` for buffers with narrow `tok_types`; recorded in its place then.
+    pub dfa_03_narrow: dfa::apply_block_prefix::Dfa03ApplyBlockPrefixNarrowPass,
+    /// `dfa_03_seed_pairs` for buffers with narrow `tok_types`; recorded in
+    /// its place then.
+    pub dfa_03_seed_pairs_narrow:
+        dfa::apply_block_prefix_seed_pairs::Dfa03ApplyBlockPrefixSeedPairsNarrowPass,
+    /// Marks source-pack file start/end byte offsets.
+    pub source_file_boundaries: source_file_boundaries::SourceFileBoundariesPass,
+
+    /// Counts all and kept token boundaries inside each block, for
+    /// [`LexPipeline::Split`].
+    pub pair_01_with_keep: pair::sum_inblock::Pair01SumInblockWithKeepPass,
+    /// Prefix-scans per-block all and kept boundary totals.
+    pub pair_02_with_keep: pair::scan_block_totals::Pair02ScanBlockTotalsWithKeepPass,
+    /// Applies pair prefixes to produce all and kept token ranks.
+    pub pair_03_with_keep: pair::apply_block_prefix::Pair03ApplyBlockPrefixWithKeepPass,
+    /// Compacts kept token boundaries from the keep flags.
+    pub compact_kept_from_flags: compact::boundaries::kept::CompactBoundariesKeptFromFlagsPass,
+    /// `compact_kept_from_flags` for buffers with narrow `tok_types`;
+    /// recorded in its place then.
+    pub compact_kept_from_flags_narrow:
+        compact::boundaries::kept::CompactBoundariesKeptFromFlagsNarrowPass,
+
+    /// Counts all token boundaries inside each block, for the late kept
+    /// filter.
+    pub pair_01: pair::sum_inblock::Pair01SumInblockPass,
+    /// Prefix-scans per-block token-boundary totals.
+    pub pair_02: pair::scan_block_totals::Pair02ScanBlockTotalsPass,
+    /// Applies pair prefixes to produce compacted token ranks.
+    pub pair_03: pair::apply_block_prefix::Pair03ApplyBlockPrefixPass,
+
+    /// Compacts all token boundaries, including skipped tokens.
+    pub compact_all: compact::boundaries::all::CompactBoundariesAllPass,
+    /// `compact_all` for buffers with narrow `tok_types`; recorded in its
+    /// place then.
+    pub compact_all_narrow: compact::boundaries::all::CompactBoundariesAllNarrowPass,
+    /// Counts kept tokens inside each all-boundary block; `pair_02` then
+    /// scans the block totals again as `keep_02`.
+    pub keep_01: keep::sum_inblock::Keep01SumInblockPass,
+    /// Applies kept-count prefixes to produce kept ranks.
+    pub keep_03: keep::apply_block_prefix::Keep03ApplyBlockPrefixPass,
+    /// Filters the all-boundary stream down to kept tokens.
+    pub compact_kept: compact::boundaries::kept::CompactBoundariesKeptPass,
+    /// Builds final resident token records.
+    pub tokens_build: tokens_build::TokensBuildPass,
+    /// `tokens_build` for buffers whose `tokens_out` is split; recorded in
+    /// its place then.
+    pub tokens_build_split: tokens_build::TokensBuildSplitPass,
+    /// Collects escape spans; outside the step lists, recorded only when
+    /// string-escape capture is on.
+    pub escape_spans: escape_spans::EscapeSpansPass,
+    /// Packs kept tokens; outside the step lists, recorded only when the
+    /// tokens are read back compressed.
+    pub tokens_compress: tokens_compress::TokensCompressPass,
+    /// Measures the trivia before each kept token; outside the step lists,
+    /// recorded with the rest of the layout passes only when layout-fact
+    /// capture is on. `pair_02` then scans its line starts as `layout_02`.
+    pub layout_01: layout::facts::Layout01FactsPass,
+    /// Numbers the lines of kept tokens.
+    pub layout_03: layout::apply_block_prefix::Layout03ApplyBlockPrefixPass,
+    /// Copies line indentation to the tokens that do not start a line.
+    pub layout_04: layout::propagate_indent::Layout04PropagateIndentPass,
+    /// Reduces boundary statistics; outside the step lists, recorded only by
+    /// [`GpuLexer::analyze`](crate::lexer::GpuLexer::analyze).
+    pub analyze: analyze::LexerAnalyzePass,
+    /// `analyze` for buffers with narrow `tok_types`; recorded in its place
+    /// then.
+    pub analyze_narrow: analyze::LexerAnalyzeNarrowPass,
+}
+
+impl LexerPasses {
+    /// Creates every lexer shader pass for a device.
+    pub fn new(device: &wgpu::Device) -> Result<Self> {
+        Ok(Self {
+            dfa_01: dfa::scan_inblock::Dfa01ScanInblockPass::new(device)?,
+            dfa_02: dfa::scan_block_summaries::Dfa02ScanBlockSummariesPass::new(device)?,
+            dfa_03: dfa::apply_block_prefix::Dfa03ApplyBlockPrefixPass::new(device)?,
+            dfa_03_seed_pairs:
+                dfa::apply_block_prefix_seed_pairs::Dfa03ApplyBlockPrefixSeedPairsPass::new(device)?,
+            dfa_03_narrow: dfa::apply_block_prefix::Dfa03ApplyBlockPrefixNarrowPass::new(device)?,
+            dfa_03_seed_pairs_narrow:
+                dfa::apply_block_prefix_seed_pairs::Dfa03ApplyBlockPrefixSeedPairsNarrowPass::new(
+                    device,
+                )?,
+            source_file_boundaries: source_file_boundaries::SourceFileBoundariesPass::new(device)?,
+            pair_01_with_keep: pair::sum_inblock::Pair01SumInblockWithKeepPass::new(device)?,
+            pair_02_with_keep: pair::scan_block_totals::Pair02ScanBlockTotalsWithKeepPass::new(
+                device,
+            )?,
+            pair_03_with_keep: pair::apply_block_prefix::Pair03ApplyBlockPrefixWithKeepPass::new(
+                device,
+            )?,
+            compact_kept_from_flags:
+                compact::boundaries::kept::CompactBoundariesKeptFromFlagsPass::new(device)?,
+            compact_kept_from_flags_narrow:
+                compact::boundaries::kept::CompactBoundariesKeptFromFlagsNarrowPass::new(device)?,
+            pair_01: pair::sum_inblock::Pair01SumInblockPass::new(device)?,
+            pair_02: pair::scan_block_totals::Pair02ScanBlockTotalsPass::new(device)?,
+            pair_03: pair::apply_block_prefix::Pair03ApplyBlockPrefixPass::new(device)?,
+            compact_all: compact::boundaries::all::CompactBoundariesAllPass::new(device)?,
+            compact_all_narrow: compact::boundaries::all::CompactBoundariesAllNarrowPass::new(
+                device,
+            )?,
+            keep_01: keep::sum_inblock::Keep01SumInblockPass::new(device)?,
+            keep_03: keep::apply_block_prefix::Keep03ApplyBlockPrefixPass::new(device)?,
+            compact_kept: compact::boundaries::kept::CompactBoundariesKeptPass::new(device)?,
+            tokens_build: tokens_build::TokensBuildPass::new(device)?,
+            tokens_build_split: tokens_build::TokensBuildSplitPass::new(device)?,
+            escape_spans: escape_spans::EscapeSpansPass::new(device)?,
+            tokens_compress: tokens_compress::TokensCompressPass::new(device)?,
+            layout_01: layout::facts::Layout01FactsPass::new(device)?,
+            layout_03: layout::apply_block_prefix::Layout03ApplyBlockPrefixPass::new(device)?,
+            layout_04: layout::propagate_indent::Layout04PropagateIndentPass::new(device)?,
+            analyze: analyze::LexerAnalyzePass::new(device)?,
+            analyze_narrow: analyze::LexerAnalyzeNarrowPass::new(device)?,
+        })
+    }
+}
+
+/// Binding contracts of every lexer pass, checkable without a device.
+pub fn binding_contracts() -> Vec<BindingContract> {
+    vec![
+        dfa::scan_inblock::Dfa01ScanInblockPass::binding_contract(),
+        dfa::scan_block_summaries::Dfa02ScanBlockSummariesPass::binding_contract(),
+        dfa::apply_block_prefix::Dfa03ApplyBlockPrefixPass::binding_contract(),
+        dfa::apply_block_prefix_seed_pairs::Dfa03ApplyBlockPrefixSeedPairsPass::binding_contract(),
+        dfa::apply_block_prefix::Dfa03ApplyBlockPrefixNarrowPass::binding_contract(),
+        dfa::apply_block_prefix_seed_pairs::Dfa03ApplyBlockPrefixSeedPairsNarrowPass::binding_contract(
+        ),
+        source_file_boundaries::SourceFileBoundariesPass::binding_contract(),
+        pair::sum_inblock::Pair01SumInblockWithKeepPass::binding_contract(),
+        pair::scan_block_totals::Pair02ScanBlockTotalsWithKeepPass::binding_contract(),
+        pair::apply_block_prefix::Pair03ApplyBlockPrefixWithKeepPass::binding_contract(),
+        compact::boundaries::kept::CompactBoundariesKeptFromFlagsPass::binding_contract(),
+        compact::boundaries::kept::CompactBoundariesKeptFromFlagsNarrowPass::binding_contract(),
+        pair::sum_inblock::Pair01SumInblockPass::binding_contract(),
+        pair::scan_block_totals::Pair02ScanBlockTotalsPass::binding_contract(),
+        pair::apply_block_prefix::Pair03ApplyBlockPrefixPass::binding_contract(),
+        compact::boundaries::all::CompactBoundariesAllPass::binding_contract(),
+        compact::boundaries::all::CompactBoundariesAllNarrowPass::binding_contract(),
+        keep::sum_inblock::Keep01SumInblockPass::binding_contract(),
+        keep::apply_block_prefix::Keep03ApplyBlockPrefixPass::binding_contract(),
+        compact::boundaries::kept::CompactBoundariesKeptPass::binding_contract(),
+        tokens_build::TokensBuildPass::binding_contract(),
+        tokens_build::TokensBuildSplitPass::binding_contract(),
+        escape_spans::EscapeSpansPass::binding_contract(),
+        tokens_compress::TokensCompressPass::binding_contract(),
+        layout::facts::Layout01FactsPass::binding_contract(),
+        layout::apply_block_prefix::Layout03ApplyBlockPrefixPass::binding_contract(),
+        layout::propagate_indent::Layout04PropagateIndentPass::binding_contract(),
+        analyze::LexerAnalyzePass::binding_contract(),
+        analyze::LexerAnalyzeNarrowPass::binding_contract(),
+    ]
+}
+
+/// Records the full lexer pass sequence of `pipeline` for the current
+/// resident buffers.
+pub fn record_all_passes(
+    n: u32,
+    nb_dfa: u32,
+    nb_sum: u32,
+    pipeline: LexPipeline,
+    ctx: crate::gpu::passes_core::PassContext<'_, GpuBuffers, super::debug::DebugOutput>,
+    p: &LexerPasses,
+) -> Result<(), anyhow::Error> {
+    record_stage_passes(n, nb_dfa, nb_sum, pipeline, PipelineStage::Build, ctx, p)
+}
+
+/// Records the lexer pass sequence of `pipeline` through the end of `stage`
+/// for the current resident buffers. `escape_spans`, `tokens_compress` and
+/// the layout passes read token records, so only [`PipelineStage::Build`]
+/// records them.
+pub fn record_stage_passes(
+    n: u32,
+    nb_dfa: u32,
+    nb_sum: u32,
+    pipeline: LexPipeline,
+    stage: PipelineStage,
+    mut ctx: crate::gpu::passes_core::PassContext<'_, GpuBuffers, super::debug::DebugOutput>,
+    p: &LexerPasses,
+) -> Result<(), anyhow::Error> {
+    use InputElements::Elements1D as E1;
+    let source_file_capacity = ctx.buffers.source_file_start.count as u32;
+    let (dfa_prefix_variant, pair_prefix_variant) = prefix_variants(nb_dfa, nb_sum);
+
+    let can_batch = ctx.maybe_timer.is_none()
+        && ctx.maybe_dbg.is_none()
+        && ctx.bg_cache.is_some()
+        && ctx.batch_compute_passes
+        && !ctx.validation_scopes;
+    if can_batch {
+        clear_lex_status(ctx.encoder, ctx.buffers);
+        {
+            let bg_cache = ctx
+                .bg_cache
+                .as_deref_mut()
+                .expect("batching requires bind-group cache");
+            let mut batch = ComputePassBatch::begin(ctx.encoder, "lexer.dfa-local.batch")
+                .instrumented(ctx.debug_groups, ctx.dispatch_records.as_deref_mut());
+            batch.record_pass_cached(
+                ctx.device,
+                ctx.buffers,
+                bg_cache,
+                &p.source_file_boundaries,
+                E1(source_file_capacity),
+            )?;
+            batch.record_pass_cached(ctx.device, ctx.buffers, bg_cache, &p.dfa_01, E1(n))?;
+        }
+        p.dfa_02.record_pass(&mut ctx, E1(nb_dfa))?;
+        {
+            let bg_cache = ctx
+                .bg_cache
+                .as_deref_mut()
+                .expect("batching requires bind-group cache");
+            let mut batch = ComputePassBatch::begin(ctx.encoder, "lexer.dfa-pair-local.batch")
+                .instrumented(ctx.debug_groups, ctx.dispatch_records.as_deref_mut());
+            let narrow = ctx.buffers.kind_packing == KindPacking::Narrow;
+            match pipeline {
+                LexPipeline::Split | LexPipeline::LateKeptFilter if narrow => {
+                    bg_cache.retain_variant(&p.dfa_03_narrow.data().shader_id, dfa_prefix_variant);
+                    batch.record_pass_cached(
+                        ctx.device,
+                        ctx.buffers,
+                        bg_cache,
+                        &p.dfa_03_narrow,
+                        E1(n),
+                    )?;
+                }
+                LexPipeline::Split | LexPipeline::LateKeptFilter => {
+                    bg_cache.retain_variant(&p.dfa_03.data().shader_id, dfa_prefix_variant);
+                    batch.record_pass_cached(
+                        ctx.device,
+                        ctx.buffers,
+                        bg_cache,
+                        &p.dfa_03,
+                        E1(n),
+                    )?;
+                }
+                LexPipeline::FusedPairSeed if narrow => {
+                    bg_cache.retain_variant(
+                        &p.dfa_03_seed_pairs_narrow.data().shader_id,
+                        dfa_prefix_variant,
+                    );
+                    batch.record_pass_cached(
+                        ctx.device,
+                        ctx.buffers,
+                        bg_cache,
+                        &p.dfa_03_seed_pairs_narrow,
+                        E1(n),
+                    )?;
+                }
+                LexPipeline::FusedPairSeed => {
+                    bg_cache
+                        .retain_variant(&p.dfa_03_seed_pairs.data().shader_id, dfa_prefix_variant);
+                    batch.record_pass_cached(
+                        ctx.device,
+                        ctx.buffers,
+                        bg_cache,
+                        &p.dfa_03_seed_pairs,
+                        E1(n),
+                    )?;
+                }
+            }
+            match pipeline {
+                LexPipeline::Split => batch.record_pass_cached(
+                    ctx.device,
+                    ctx.buffers,
+                    bg_cache,
+                    &p.pair_01_with_keep,
+                    E1(n),
+                )?,
+                LexPipeline::LateKeptFilter => batch.record_pass_cached(
+                    ctx.device,
+                    ctx.buffers,
+                    bg_cache,
+                    &p.pair_01,
+                    E1(n),
+                )?,
+                LexPipeline::FusedPairSeed => {}
+            }
+        }
+        if pipeline == LexPipeline::Split {
+            p.pair_02_with_keep.record_pass(&mut ctx, E1(nb_sum))?;
+            {
+                let bg_cache = ctx
+                    .bg_cache
+                    .as_deref_mut()
+                    .expect("batching requires bind-group cache");
+                bg_cache.retain_variant(&p.pair_03_with_keep.data().shader_id, pair_prefix_variant);
+                let mut batch = ComputePassBatch::begin(ctx.encoder, "lexer.emit.batch")
+                    .instrumented(ctx.debug_groups, ctx.dispatch_records.as_deref_mut());
+                batch.record_pass_cached(
+                    ctx.device,
+                    ctx.buffers,
+                    bg_cache,
+                    &p.pair_03_with_keep,
+                    E1(n),
+                )?;
+                if stage == PipelineStage::Scan {
+                    return Ok(());
+                }
+                // Kept compaction reads the kept ranks parked in
+                // end_positions_all, so it runs before ALL compaction.
+                if ctx.buffers.kind_packing == KindPacking::Narrow {
+                    batch.record_pass_cached(
+                        ctx.device,
+                        ctx.buffers,
+                        bg_cache,
+                        &p.compact_kept_from_flags_narrow,
+                        E1(n),
+                    )?;
+                    batch.record_pass_cached(
+                        ctx.device,
+                        ctx.buffers,
+                        bg_cache,
+                        &p.compact_all_narrow,
+                        E1(n),
+                    )?;
+                } else {
+                    batch.record_pass_cached(
+                        ctx.device,
+                        ctx.buffers,
+                        bg_cache,
+                        &p.compact_kept_from_flags,
+                        E1(n),
+                    )?;
+                    batch.record_pass_cached(
+                        ctx.device,
+                        ctx.buffers,
+                        bg_cache,
+                        &p.compact_all,
+                        E1(n),
+                    )?;
+                }
+                if stage == PipelineStage::Compact {
+                    return Ok(());
+                }
+                if ctx.buffers.tokens_split.is_some() {
+                    batch.record_pass_cached(
+                        ctx.device,
+                        ctx.buffers,
+                        bg_cache,
+                        &p.tokens_build_split,
+                        E1(n),
+                    )?;
+                } else {
+                    batch.record_pass_cached(
+                        ctx.device,
+                        ctx.buffers,
+                        bg_cache,
+                        &p.tokens_build,
+                        E1(n),
+                    )?;
+                }
+            }
+        } else {
+            if pipeline == LexPipeline::FusedPairSeed {
+                dfa::apply_block_prefix_seed_pairs::copy_pair_seed_totals(ctx.encoder, ctx.buffers);
+            }
+            p.pair_02.record_pass(&mut ctx, E1(nb_sum))?;
+            {
+                let bg_cache = ctx
+                    .bg_cache
+                    .as_deref_mut()
+                    .expect("batching requires bind-group cache");
+                bg_cache.retain_variant(&p.pair_03.data().shader_id, pair_prefix_variant);
+                let mut batch = ComputePassBatch::begin(ctx.encoder, "lexer.compact-all.batch")
+                    .instrumented(ctx.debug_groups, ctx.dispatch_records.as_deref_mut());
+                batch.record_pass_cached(ctx.device, ctx.buffers, bg_cache, &p.pair_03, E1(n))?;
+                if stage == PipelineStage::Scan {
+                    return Ok(());
+                }
+                if ctx.buffers.kind_packing == KindPacking::Narrow {
+                    batch.record_pass_cached(
+                        ctx.device,
+                        ctx.buffers,
+                        bg_cache,
+                        &p.compact_all_narrow,
+                        E1(n),
+                    )?;
+                } else {
+                    batch.record_pass_cached(
+                        ctx.device,
+                        ctx.buffers,
+                        bg_cache,
+                        &p.compact_all,
+                        E1(n),
+                    )?;
+                }
+                batch.record_pass_cached(ctx.device, ctx.buffers, bg_cache, &p.keep_01, E1(n))?;
+            }
+            p.pair_02
+                .record_scan(&mut ctx, E1(nb_sum), KEEP_SCAN, KEEP_SCAN_NAME)?;
+            {
+                let bg_cache = ctx
+                    .bg_cache
+                    .as_deref_mut()
+                    .expect("batching requires bind-group cache");
+                bg_cache.retain_variant(&p.keep_03.data().shader_id, pair_prefix_variant);
+                let mut batch = ComputePassBatch::begin(ctx.encoder, "lexer.emit.batch")
+                    .instrumented(ctx.debug_groups, ctx.dispatch_records.as_deref_mut());
+                batch.record_pass_cached(ctx.device, ctx.buffers, bg_cache, &p.keep_03, E1(n))?;
+                batch.record_pass_cached(
+                    ctx.device,
+                    ctx.buffers,
+                    bg_cache,
+                    &p.compact_kept,
+                    E1(n),
+                )?;
+                if stage == PipelineStage::Compact {
+                    return Ok(());
+                }
+                if ctx.buffers.tokens_split.is_some() {
+                    batch.record_pass_cached(
+                        ctx.device,
+                        ctx.buffers,
+                        bg_cache,
+                        &p.tokens_build_split,
+                        E1(n),
+                    )?;
+                } else {
+                    batch.record_pass_cached(
+                        ctx.device,
+                        ctx.buffers,
+                        bg_cache,
+                        &p.tokens_build,
+                        E1(n),
+                    )?;
+                }
+            }
+        }
+        if ctx.buffers.capture_string_escapes {
+            p.escape_spans.record_pass(&mut ctx, E1(n))?;
+        }
+        if ctx.buffers.compress_readback {
+            p.tokens_compress.record_pass(&mut ctx, E1(n))?;
+        }
+        if ctx.buffers.capture_layout {
+            record_layout_passes(n, nb_dfa, nb_sum, &mut ctx, p)?;
+        }
+        return Ok(());
+    }
+
+    record_steps(
+        n,
+        nb_dfa,
+        nb_sum,
+        ctx,
+        p,
+        stage_steps(pipeline, stage),
+        usize::MAX,
+    )?;
+    Ok(())
+}
+
+/// Bind-group variants of the prefix passes for `nb_dfa` and `nb_sum` blocks:
+/// `dfa_03`'s (or its fused variant's), then the one `pair_03`, its keep
+/// variant and `keep_03` share.
+///
+/// Each binds whichever block-scan buffer its preceding scan wrote last.
+pub(crate) fn prefix_variants(nb_dfa: u32, nb_sum: u32) -> (u64, u64) {
+    (
+        u64::from(compute_rounds(nb_dfa) % 2),
+        u64::from(pair::block_total_scan_last_writer_is_ping(nb_sum)),
+    )
+}
+
+/// Zeroes the buffers every lex expects to start cleared.
+fn clear_lex_status(encoder: &mut wgpu::CommandEncoder, bufs: &GpuBuffers) {
+    // Ensure flags_packed is zeroed so dfa_03 can write flags only at boundaries
+    // and leave non-boundaries as 0 without per-byte stores.
+    encoder.clear_buffer(&bufs.flags_packed, 0, None);
+    encoder.clear_buffer(&bufs.source_file_start_flags, 0, None);
+    encoder.clear_buffer(&bufs.source_file_end_flags, 0, None);
+    encoder.clear_buffer(&bufs.token_order_status, 0, None);
+    encoder.clear_buffer(&bufs.token_len_status, 0, None);
+}
+
+/// Round label of the kept-rank block scan, which reruns `pair_02`.
+const KEEP_SCAN: &str = "keep_02";
+/// Pass name of the kept-rank block scan for timers and debug groups.
+const KEEP_SCAN_NAME: &str = "keep_02_scan_block_totals";
+/// Round label of the line-number block scan, which reruns `pair_02`.
+const LAYOUT_SCAN: &str = "layout_02";
+/// Pass name of the line-number block scan for timers and debug groups.
+const LAYOUT_SCAN_NAME: &str = "layout_02_scan_block_totals";
+
+/// One recordable step of the lexer pass sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexerStep {
+    /// Clears per-lex status buffers, then marks source-file boundaries.
+    SourceFileBoundaries,
+    Dfa01,
+    Dfa02,
+    Dfa03,
+    /// `dfa_03` and `pair_01` in one pass, then a copy of the block totals
+    /// into the buffer `pair_02` scans.
+    Dfa03SeedPairs,
+    Pair01,
+    Pair02,
+    Pair03,
+    /// `pair_01` counting all and kept boundaries from the keep flags.
+    Pair01WithKeep,
+    /// `pair_02` over the `uint2` totals of `Pair01WithKeep`.
+    Pair02WithKeep,
+    /// `pair_03` writing all and kept ranks.
+    Pair03WithKeep,
+    /// Compacts kept boundaries by the kept ranks of `Pair03WithKeep`.
+    CompactKeptFromFlags,
+    CompactAll,
+    Keep01,
+    Keep02,
+    Keep03,
+    CompactKept,
+    TokensBuild,
+}
+
+/// The full unbatched lexer pass sequence, in recording order.
+pub const LEXER_STEPS: [LexerStep; 10] = [
+    LexerStep::SourceFileBoundaries,
+    LexerStep::Dfa01,
+    LexerStep::Dfa02,
+    LexerStep::Dfa03,
+    LexerStep::Pair01WithKeep,
+    LexerStep::Pair02WithKeep,
+    LexerStep::Pair03WithKeep,
+    // The kept ranks are parked in end_positions_all, which ALL compaction
+    // overwrites.
+    LexerStep::CompactKeptFromFlags,
+    LexerStep::CompactAll,
+    LexerStep::TokensBuild,
+];
+
+/// The unbatched sequence of [`LexPipeline::LateKeptFilter`].
+pub const LATE_KEPT_FILTER_STEPS: [LexerStep; 13] = [
+    LexerStep::SourceFileBoundaries,
+    LexerStep::Dfa01,
+    LexerStep::Dfa02,
+    LexerStep::Dfa03,
+    LexerStep::Pair01,
+    LexerStep::Pair02,
+    LexerStep::Pair03,
+    // The kept stream is filtered from the compacted ALL stream, and the
+    // kept ranks reuse s_all_final once ALL compaction has consumed it.
+    LexerStep::CompactAll,
+    LexerStep::Keep01,
+    LexerStep::Keep02,
+    LexerStep::Keep03,
+    LexerStep::CompactKept,
+    LexerStep::TokensBuild,
+];
+
+/// [`LATE_KEPT_FILTER_STEPS`] with `Dfa03` and `Pair01` replaced by
+/// `Dfa03SeedPairs`.
+pub const FUSED_LEXER_STEPS: [LexerStep; 12] = [
+    LexerStep::SourceFileBoundaries,
+    LexerStep::Dfa01,
+    LexerStep::Dfa02,
+    LexerStep::Dfa03SeedPairs,
+    LexerStep::Pair02,
+    LexerStep::Pair03,
+    LexerStep::CompactAll,
+    LexerStep::Keep01,
+    LexerStep::Keep02,
+    LexerStep::Keep03,
+    LexerStep::CompactKept,
+    LexerStep::TokensBuild,
+];
+
+/// The unbatched pass sequence `pipeline` records.
+pub fn lexer_steps(pipeline: LexPipeline) -> &'static [LexerStep] {
+    match pipeline {
+        LexPipeline::Split => &LEXER_STEPS,
+        LexPipeline::LateKeptFilter => &LATE_KEPT_FILTER_STEPS,
+        LexPipeline::FusedPairSeed => &FUSED_LEXER_STEPS,
+    }
+}
+
+/// The prefix of [`lexer_steps`] that runs `pipeline` through `stage`.
+pub fn stage_steps(pipeline: LexPipeline, stage: PipelineStage) -> &'static [LexerStep] {
+    let steps = lexer_steps(pipeline);
+    let is_last = |step: LexerStep| match stage {
+        PipelineStage::Scan => matches!(step, LexerStep::Pair03 | LexerStep::Pair03WithKeep),
+        PipelineStage::Compact => matches!(
+            step,
+            LexerStep::CompactAll | LexerStep::CompactKept | LexerStep::CompactKeptFromFlags
+        ),
+        PipelineStage::Build => step == LexerStep::TokensBuild,
+    };
+    let end = steps
+        .iter()
+        .rposition(|&step| is_last(step))
+        .expect("every step list has each stage's last step");
+    &steps[..=end]
+}
+
+impl LexerStep {
+    /// Contract of the pass this step dispatches and the label its direct
+    /// dispatches are recorded under; `Keep02` reruns `pair_02`.
+    pub fn pass(self) -> (BindingContract, &'static str) {
+        use crate::lexer::debug::DebugOutput;
+
+        fn pass_of<P: Pass<GpuBuffers, DebugOutput>>(
+            contract: fn() -> BindingContract,
+        ) -> (BindingContract, &'static str) {
+            (contract(), P::NAME)
+        }
+
+        use compact::boundaries::{
+            all::CompactBoundariesAllPass,
+            kept::{CompactBoundariesKeptFromFlagsPass, CompactBoundariesKeptPass},
+        };
+        use dfa::{
+            apply_block_prefix::Dfa03ApplyBlockPrefixPass,
+            apply_block_prefix_seed_pairs::Dfa03ApplyBlockPrefixSeedPairsPass,
+            scan_block_summaries::Dfa02ScanBlockSummariesPass,
+            scan_inblock::Dfa01ScanInblockPass,
+        };
+        use keep::{
+            apply_block_prefix::Keep03ApplyBlockPrefixPass,
+            sum_inblock::Keep01SumInblockPass,
+        };
+        use pair::{
+            apply_block_prefix::{Pair03ApplyBlockPrefixPass, Pair03ApplyBlockPrefixWithKeepPass},
+            scan_block_totals::{Pair02ScanBlockTotalsPass, Pair02ScanBlockTotalsWithKeepPass},
+            sum_inblock::{Pair01SumInblockPass, Pair01SumInblockWithKeepPass},
+        };
+        use source_file_boundaries::SourceFileBoundariesPass;
+        use tokens_build::TokensBuildPass;
+        match self {
+            Self::SourceFileBoundaries => {
+                pass_of::<SourceFileBoundariesPass>(SourceFileBoundariesPass::binding_contract)
+            }
+            Self::Dfa01 => pass_of::<Dfa01ScanInblockPass>(Dfa01ScanInblockPass::binding_contract),
+            Self::Dfa02 => pass_of::<Dfa02ScanBlockSummariesPass>(
+                Dfa02ScanBlockSummariesPass::binding_contract,
+            ),
+            Self::Dfa03 => {
+                pass_of::<Dfa03ApplyBlockPrefixPass>(Dfa03ApplyBlockPrefixPass::binding_contract)
+            }
+            Self::Dfa03SeedPairs => pass_of::<Dfa03ApplyBlockPrefixSeedPairsPass>(
+                Dfa03ApplyBlockPrefixSeedPairsPass::binding_contract,
+            ),
+            Self::Pair01 => pass_of::<Pair01SumInblockPass>(Pair01SumInblockPass::binding_contract),
+            Self::Pair02 | Self::Keep02 => {
+                pass_of::<Pair02ScanBlockTotalsPass>(Pair02ScanBlockTotalsPass::binding_contract)
+            }
+            Self::Pair03 => {
+                pass_of::<Pair03ApplyBlockPrefixPass>(Pair03ApplyBlockPrefixPass::binding_contract)
+            }
+            Self::Pair01WithKeep => pass_of::<Pair01SumInblockWithKeepPass>(
+                Pair01SumInblockWithKeepPass::binding_contract,
+            ),
+            Self::Pair02WithKeep => pass_of::<Pair02ScanBlockTotalsWithKeepPass>(
+                Pair02ScanBlockTotalsWithKeepPass::binding_contract,
+            ),
+            Self::Pair03WithKeep => pass_of::<Pair03ApplyBlockPrefixWithKeepPass>(
+                Pair03ApplyBlockPrefixWithKeepPass::binding_contract,
+            ),
+            Self::CompactKeptFromFlags => pass_of::<CompactBoundariesKeptFromFlagsPass>(
+                CompactBoundariesKeptFromFlagsPass::binding_contract,
+            ),
+            Self::CompactAll => {
+                pass_of::<CompactBoundariesAllPass>(CompactBoundariesAllPass::binding_contract)
+            }
+            Self::Keep01 => pass_of::<Keep01SumInblockPass>(Keep01SumInblockPass::binding_contract),
+            Self::Keep03 => {
+                pass_of::<Keep03ApplyBlockPrefixPass>(Keep03ApplyBlockPrefixPass::binding_contract)
+            }
+            Self::CompactKept => {
+                pass_of::<CompactBoundariesKeptPass>(CompactBoundariesKeptPass::binding_contract)
+            }
+            Self::TokensBuild => pass_of::<TokensBuildPass>(TokensBuildPass::binding_contract),
+        }
+    }
+}
+
+/// Plans the dispatches [`record_steps`] records for `steps`, without a
+/// device: the same inputs per step, and one record per block-scan round.
+pub fn plan_steps(
+    plan: &mut PipelinePlan,
+    shapes: &dyn PassShapes,
+    n: u32,
+    nb_dfa: u32,
+    nb_sum: u32,
+    source_file_capacity: u32,
+    steps: &[LexerStep],
+) -> Result<()> {
+    use InputElements::Elements1D as E1;
+    for &step in steps {
+        let (contract, label) = step.pass();
+        match step {
+            LexerStep::SourceFileBoundaries => {
+                plan.dispatch(shapes, &contract, label, E1(source_file_capacity))?
+            }
+            LexerStep::Dfa02 => {
+                for r in 0..compute_rounds(nb_dfa) {
+                    plan.dispatch(shapes, &contract, scan_round_label("dfa_02", r), E1(nb_dfa))?;
+                }
+            }
+            LexerStep::Pair02 | LexerStep::Pair02WithKeep | LexerStep::Keep02 => {
+                let scan = if step == LexerStep::Keep02 {
+                    KEEP_SCAN
+                } else {
+                    "pair_02"
+                };
+                for r in 0..pair::block_total_scan_steps(nb_sum).len() as u32 {
+                    plan.dispatch(shapes, &contract, scan_round_label(scan, r), E1(nb_sum))?;
+                }
+            }
+            _ => plan.dispatch(shapes, &contract, label, E1(n))?,
+        }
+    }
+    Ok(())
+}
+
+/// Records at most `max_steps` of `steps` and returns the ones still to record.
+/// The chunk that records `TokensBuild` also records `escape_spans` when the
+/// buffers capture string escapes, `tokens_compress` when they compress
+/// the token readback, and the layout passes when they capture layout facts.
+///
+/// Passing the returned slice back with a fresh `PassContext` resumes the
+/// sequence, so it can be split across command buffers; with a bind-group
+/// cache the later chunks reuse the groups built for earlier lexes.
+pub fn record_steps<'s>(
+    n: u32,
+    nb_dfa: u32,
+    nb_sum: u32,
+    mut ctx: crate::gpu::passes_core::PassContext<'_, GpuBuffers, super::debug::DebugOutput>,
+    p: &LexerPasses,
+    steps: &'s [LexerStep],
+    max_steps: usize,
+) -> Result<&'s [LexerStep]> {
+    use InputElements::Elements1D as E1;
+    let (now, rest) = steps.split_at(max_steps.min(steps.len()));
+    for step in now {
+        match step {
+            LexerStep::SourceFileBoundaries => {
+                clear_lex_status(ctx.encoder, ctx.buffers);
+                let source_file_capacity = ctx.buffers.source_file_start.count as u32;
+                p.source_file_boundaries
+                    .record_pass(&mut ctx, E1(source_file_capacity))?;
+            }
+            LexerStep::Dfa01 => p.dfa_01.record_pass(&mut ctx, E1(n))?,
+            LexerStep::Dfa02 => p.dfa_02.record_pass(&mut ctx, E1(nb_dfa))?,
+            LexerStep::Dfa03 if ctx.buffers.kind_packing == KindPacking::Narrow => {
+                if let Some(cache) = ctx.bg_cache.as_deref_mut() {
+                    let (variant, _) = prefix_variants(nb_dfa, nb_sum);
+                    cache.retain_variant(&p.dfa_03_narrow.data().shader_id, variant);
+                }
+                p.dfa_03_narrow.record_pass(&mut ctx, E1(n))?;
+            }
+            LexerStep::Dfa03 => {
+                if let Some(cache) = ctx.bg_cache.as_deref_mut() {
+                    let (variant, _) = prefix_variants(nb_dfa, nb_sum);
+                    cache.retain_variant(&p.dfa_03.data().shader_id, variant);
+                }
+                p.dfa_03.record_pass(&mut ctx, E1(n))?;
+            }
+            LexerStep::Dfa03SeedPairs => {
+                let narrow = ctx.buffers.kind_packing == KindPacking::Narrow;
+                if let Some(cache) = ctx.bg_cache.as_deref_mut() {
+                    let (variant, _) = prefix_variants(nb_dfa, nb_sum);
+                    let shader_id = if narrow {
+                        &p.dfa_03_seed_pairs_narrow.data().shader_id
+                    } else {
+                        &p.dfa_03_seed_pairs.data().shader_id
+                    };
+                    cache.retain_variant(shader_id, variant);
+                }
+                if narrow {
+                    p.dfa_03_seed_pairs_narrow.record_pass(&mut ctx, E1(n))?;
+                } else {
+                    p.dfa_03_seed_pairs.record_pass(&mut ctx, E1(n))?;
+                }
+                dfa::apply_block_prefix_seed_pairs::copy_pair_seed_totals(ctx.encoder, ctx.buffers);
+            }
+            LexerStep::Pair01 => p.pair_01.record_pass(&mut ctx, E1(n))?,
+            LexerStep::Pair02 => p.pair_02.record_pass(&mut ctx, E1(nb_sum))?,
+            LexerStep::Pair03 => {
+                if let Some(cache) = ctx.bg_cache.as_deref_mut() {
+                    let (_, variant) = prefix_variants(nb_dfa, nb_sum);
+                    cache.retain_variant(&p.pair_03.data().shader_id, variant);
+                }
+                p.pair_03.record_pass(&mut ctx, E1(n))?;
+            }
+            LexerStep::Pair01WithKeep => p.pair_01_with_keep.record_pass(&mut ctx, E1(n))?,
+            LexerStep::Pair02WithKeep => p.pair_02_with_keep.record_pass(&mut ctx, E1(nb_sum))?,
+            LexerStep::Pair03WithKeep => {
+                if let Some(cache) = ctx.bg_cache.as_deref_mut() {
+                    let (_, variant) = prefix_variants(nb_dfa, nb_sum);
+                    cache.retain_variant(&p.pair_03_with_keep.data().shader_id, variant);
+                }
+                p.pair_03_with_keep.record_pass(&mut ctx, E1(n))?;
+            }
+            LexerStep::CompactKeptFromFlags if ctx.buffers.kind_packing == KindPacking::Narrow => p
+                .compact_kept_from_flags_narrow
+                .record_pass(&mut ctx, E1(n))?,
+            LexerStep::CompactKeptFromFlags => {
+                p.compact_kept_from_flags.record_pass(&mut ctx, E1(n))?
+            }
+            LexerStep::CompactAll if ctx.buffers.kind_packing == KindPacking::Narrow => {
+                p.compact_all_narrow.record_pass(&mut ctx, E1(n))?
+            }
+            LexerStep::CompactAll => p.compact_all.record_pass(&mut ctx, E1(n))?,
+            LexerStep::Keep01 => p.keep_01.record_pass(&mut ctx, E1(n))?,
+            LexerStep::Keep02 => {
+                p.pair_02
+                    .record_scan(&mut ctx, E1(nb_sum), KEEP_SCAN, KEEP_SCAN_NAME)?
+            }
+            LexerStep::Keep03 => {
+                if let Some(cache) = ctx.bg_cache.as_deref_mut() {
+                    let (_, variant) = prefix_variants(nb_dfa, nb_sum);
+                    cache.retain_variant(&p.keep_03.data().shader_id, variant);
+                }
+                p.keep_03.record_pass(&mut ctx, E1(n))?;
+            }
+            LexerStep::CompactKept => p.compact_kept.record_pass(&mut ctx, E1(n))?,
+            LexerStep::TokensBuild if ctx.buffers.tokens_split.is_some() => {
+                p.tokens_build_split.record_pass(&mut ctx, E1(n))?
+            }
+            LexerStep::TokensBuild => p.tokens_build.record_pass(&mut ctx, E1(n))?,
+        }
+    }
+    if now.last() == Some(&LexerStep::TokensBuild) {
+        if ctx.buffers.capture_string_escapes {
+            p.escape_spans.record_pass(&mut ctx, E1(n))?;
+        }
+        if ctx.buffers.compress_readback {
+            p.tokens_compress.record_pass(&mut ctx, E1(n))?;
+        }
+        if ctx.buffers.capture_layout {
+            record_layout_passes(n, nb_dfa, nb_sum, &mut ctx, p)?;
+        }
+    }
+    Ok(rest)
+}
+
+/// Records `keep_01..03` over the compacted ALL stream, leaving the kept
+/// rank per ALL slot in `s_all_final` whichever pipeline produced it.
+pub(crate) fn record_kept_ranks(
+    n: u32,
+    nb_dfa: u32,
+    nb_sum: u32,
+    ctx: &mut crate::gpu::passes_core::PassContext<'_, GpuBuffers, super::debug::DebugOutput>,
+    p: &LexerPasses,
+) -> Result<()> {
+    use InputElements::Elements1D as E1;
+    p.keep_01.record_pass(ctx, E1(n))?;
+    p.pair_02
+        .record_scan(ctx, E1(nb_sum), KEEP_SCAN, KEEP_SCAN_NAME)?;
+    if let Some(cache) = ctx.bg_cache.as_deref_mut() {
+        let (_, variant) = prefix_variants(nb_dfa, nb_sum);
+        cache.retain_variant(&p.keep_03.data().shader_id, variant);
+    }
+    p.keep_03.record_pass(ctx, E1(n))
+}
+
+/// Records the layout-fact passes over the kept tokens `tokens_build` left.
+/// `layout_02` reruns the pair block scan over the line starts `layout_01`
+/// counted, as `keep_02` does over kept tokens.
+fn record_layout_passes(
+    n: u32,
+    nb_dfa: u32,
+    nb_sum: u32,
+    ctx: &mut crate::gpu::passes_core::PassContext<'_, GpuBuffers, super::debug::DebugOutput>,
+    p: &LexerPasses,
+) -> Result<()> {
+    use InputElements::Elements1D as E1;
+    p.layout_01.record_pass(ctx, E1(n))?;
+    p.pair_02
+        .record_scan(ctx, E1(nb_sum), LAYOUT_SCAN, LAYOUT_SCAN_NAME)?;
+    if let Some(cache) = ctx.bg_cache.as_deref_mut() {
+        let (_, variant) = prefix_variants(nb_dfa, nb_sum);
+        cache.retain_variant(&p.layout_03.data().shader_id, variant);
+    }
+    p.layout_03.record_pass(ctx, E1(n))?;
+    p.layout_04.record_pass(ctx, E1(n))?;
+    Ok(())
+}
+
+/// Plans the dispatches of `record_layout_passes`, as [`plan_steps`] does
+/// for the step lists.
+pub fn plan_layout_passes(
+    plan: &mut PipelinePlan,
+    shapes: &dyn PassShapes,
+    n: u32,
+    nb_sum: u32,
+) -> Result<()> {
+    use InputElements::Elements1D as E1;
+    use layout::{
+        apply_block_prefix::Layout03ApplyBlockPrefixPass,
+        facts::Layout01FactsPass,
+        propagate_indent::Layout04PropagateIndentPass,
+    };
+    plan.dispatch(
+        shapes,
+        &Layout01FactsPass::binding_contract(),
+        Layout01FactsPass::NAME,
+        E1(n),
+    )?;
+    let scan = pair::scan_block_totals::Pair02ScanBlockTotalsPass::binding_contract();
+    for r in 0..pair::block_total_scan_steps(nb_sum).len() as u32 {
+        plan.dispatch(shapes, &scan, scan_round_label(LAYOUT_SCAN, r), E1(nb_sum))?;
+    }
+    plan.dispatch(
+        shapes,
+        &Layout03ApplyBlockPrefixPass::binding_contract(),
+        Layout03ApplyBlockPrefixPass::NAME,
+        E1(n),
+    )?;
+    plan.dispatch(
+        shapes,
+        &Layout04PropagateIndentPass::binding_contract(),
+        Layout04PropagateIndentPass::NAME,
+        E1(n),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scan_round_labels_carry_round_and_stride() {
+        assert_eq!(scan_round_label("dfa_02", 0), "dfa_02[round=0, stride=1]");
+        assert_eq!(scan_round_label("pair_02", 3), "pair_02[round=3, stride=8]");
+    }
+
+    #[test]
+    fn submission_policies_split_the_step_list() {
+        use crate::lexer::types::SubmissionPolicy;
+
+        let chunks = |policy: SubmissionPolicy| {
+            LEXER_STEPS
+                .chunks(policy.passes_per_chunk(LEXER_STEPS.len()))
+                .count()
+        };
+        assert_eq!(chunks(SubmissionPolicy::Immediate), 1);
+        assert_eq!(chunks(SubmissionPolicy::Batched { max_encoders: 3 }), 3);
+        assert_eq!(chunks(SubmissionPolicy::Batched { max_encoders: 0 }), 1);
+        assert_eq!(chunks(SubmissionPolicy::Batched { max_encoders: 64 }), 10);
+        assert_eq!(chunks(SubmissionPolicy::Yielding { chunk_passes: 4 }), 3);
+        assert_eq!(chunks(SubmissionPolicy::Yielding { chunk_passes: 0 }), 10);
+        // The natural cut points: after dfa_03 and pair_03.
+        assert_eq!(LEXER_STEPS[3], LexerStep::Dfa03);
+        assert_eq!(LEXER_STEPS[6], LexerStep::Pair03WithKeep);
+        assert_eq!(LATE_KEPT_FILTER_STEPS[10], LexerStep::Keep03);
+    }
+
+    #[test]
+    fn stages_are_nested_prefixes_of_the_step_lists() {
+        let pipelines = [
+            (
+                LexPipeline::Split,
+                LexerStep::Pair03WithKeep,
+                LexerStep::CompactAll,
+            ),
+            (
+                LexPipeline::LateKeptFilter,
+                LexerStep::Pair03,
+                LexerStep::CompactKept,
+            ),
+            (
+                LexPipeline::FusedPairSeed,
+                LexerStep::Pair03,
+                LexerStep::CompactKept,
+            ),
+        ];
+        for (pipeline, scan_last, compact_last) in pipelines {
+            let steps = lexer_steps(pipeline);
+            let scan = stage_steps(pipeline, PipelineStage::Scan);
+            let compact = stage_steps(pipeline, PipelineStage::Compact);
+            assert_eq!(scan.last(), Some(&scan_last), "{pipeline:?}");
+            assert_eq!(compact.last(), Some(&compact_last), "{pipeline:?}");
+            assert!(compact.starts_with(scan), "{pipeline:?}");
+            assert_eq!(stage_steps(pipeline, PipelineStage::Build), steps);
+        }
+    }
+
+    #[test]
+    fn analysis_never_binds_compacted_or_token_buffers() {
+        const LATER: [&str; 8] = [
+            "end_positions_all",
+            "types_all",
+            "all_token_count",
+            "end_positions",
+            "types_compact",
+            "all_index_compact",
+            "token_count",
+            "tokens_out",
+        ];
+        let contracts = [
+            analyze::LexerAnalyzePass::binding_contract(),
+            analyze::LexerAnalyzeNarrowPass::binding_contract(),
+        ];
+        for pipeline in [
+            LexPipeline::Split,
+            LexPipeline::LateKeptFilter,
+            LexPipeline::FusedPairSeed,
+        ] {
+            let scan = stage_steps(pipeline, PipelineStage::Scan)
+                .iter()
+                .map(|step| step.pass().0);
+            for contract in scan.chain(contracts) {
+                for binding in contract.expected_bindings {
+                    assert!(
+                        !LATER.contains(binding),
+                        "{} binds {binding}",
+                        contract.label
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fused_steps_replace_dfa_03_and_pair_01() {
+        let late: Vec<_> = LATE_KEPT_FILTER_STEPS
+            .iter()
+            .copied()
+            .filter(|&step| step != LexerStep::Pair01)
+            .map(|step| match step {
+                LexerStep::Dfa03 => LexerStep::Dfa03SeedPairs,
+                step => step,
+            })
+            .collect();
+        assert_eq!(lexer_steps(LexPipeline::FusedPairSeed), late.as_slice());
+        assert_eq!(lexer_steps(LexPipeline::default()), LEXER_STEPS.as_slice());
+        assert_eq!(
+            LexerStep::Dfa03SeedPairs.pass().1,
+            "dfa_03_apply_block_prefix_seed_pairs"
+        );
+    }
+
+    #[test]
+    fn keep_channel_replaces_the_late_kept_filter() {
+        let late_only = [
+            LexerStep::Pair01,
+            LexerStep::Pair02,
+            LexerStep::Pair03,
+            LexerStep::Keep01,
+            LexerStep::Keep02,
+            LexerStep::Keep03,
+            LexerStep::CompactKept,
+        ];
+        let shared: Vec<_> = LATE_KEPT_FILTER_STEPS
+            .iter()
+            .copied()
+            .filter(|step| !late_only.contains(step))
+            .collect();
+        let split: Vec<_> = LEXER_STEPS
+            .iter()
+            .copied()
+            .filter(|step| {
+                !matches!(
+                    step,
+                    LexerStep::Pair01WithKeep
+                        | LexerStep::Pair02WithKeep
+                        | LexerStep::Pair03WithKeep
+                        | LexerStep::CompactKeptFromFlags
+                )
+            })
+            .collect();
+        assert_eq!(split, shared);
+        assert_eq!(
+            LexerStep::Pair02WithKeep.pass().1,
+            "pair_02_scan_block_totals_with_keep"
+        );
+        assert_eq!(
+            LexerStep::CompactKeptFromFlags.pass().1,
+            "compact_boundaries[KEPT]"
+        );
+    }
+}
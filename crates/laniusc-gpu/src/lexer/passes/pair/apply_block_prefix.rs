@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use crate::{
+    gpu::passes_core::{DispatchConvention, DispatchDim, PassData},
+    lexer::{buffers::GpuBuffers, debug::DebugOutput},
+};
+
+/// Third pair pass: applies boundary prefixes and writes all-boundary ranks.
+pub struct Pair03ApplyBlockPrefixPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    Pair03ApplyBlockPrefixPass,
+    label: "pair_03_apply_block_prefix",
+    entry: "pair_03_apply_block_prefix",
+    shader: "lexer/pair/03_apply_block_prefix"
+);
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for Pair03ApplyBlockPrefixPass {
+    const NAME: &'static str = "pair_03_apply_block_prefix";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gParams",
+            "flags_packed",
+            "block_prefix_pair",
+            "s_all_final",
+        ]
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &["lexer.s_all_final", "lexer.block_prefix_pair.applied"]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        resource_map(b)
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        record_applied(device, encoder, b, dbg);
+    }
+}
+
+/// Third pair pass of the keep channel: applies boundary prefixes and writes
+/// both all-boundary and kept-boundary ranks.
+///
+/// The kept ranks go to `end_positions_all`, which `compact_boundaries[KEPT]`
+/// consumes before `compact_boundaries[ALL]` overwrites it.
+pub struct Pair03ApplyBlockPrefixWithKeepPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    Pair03ApplyBlockPrefixWithKeepPass,
+    label: "pair_03_apply_block_prefix_with_keep",
+    entry: "pair_03_apply_block_prefix_with_keep",
+    shader: "lexer/pair/03_apply_block_prefix_with_keep"
+);
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for Pair03ApplyBlockPrefixWithKeepPass {
+    const NAME: &'static str = "pair_03_apply_block_prefix_with_keep";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gParams",
+            "flags_packed",
+            "block_prefix_pair",
+            "s_all_final",
+            "s_keep_final",
+        ]
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &[
+            "lexer.s_all_final",
+            "lexer.s_keep_final",
+            "lexer.block_prefix_pair.applied",
+        ]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        let mut resources = resource_map(b);
+        // compact_boundaries[ALL] overwrites it once compact_boundaries[KEPT]
+        // has consumed the kept ranks
+        resources.insert(
+            "s_keep_final".into(),
+            b.end_positions_all.as_entire_binding(),
+        );
+        resources
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        dbg.capture(
+            "lexer.s_keep_final",
+            device,
+            encoder,
+            &b.end_positions_all,
+            b.end_positions_all.byte_size,
+        );
+        record_applied(device, encoder, b, dbg);
+    }
+}
+
+/// Bindings shared by both pair prefix passes.
+fn resource_map(b: &GpuBuffers) -> HashMap<String, wgpu::BindingResource<'_>> {
+    use wgpu::BindingResource::*;
+
+    let final_prefix_is_ping = super::block_total_scan_last_writer_is_ping(b.nb_sum);
+
+    #[cfg(feature = "gpu-debug")]
+    {
+        let plane = if final_prefix_is_ping { "PING" } else { "PONG" };
+        log::debug!(
+            target: crate::logging::LEXER_GPU,
+            "pair_03: final pair-prefix last-writer={}",
+            plane
+        );
+    }
+
+    // Reuse DFA block ping/pong as the pair prefix source
+    let block_prefix_pair_binding = if final_prefix_is_ping {
+        b.dfa_02_ping.as_entire_binding()
+    } else {
+        b.dfa_02_pong.as_entire_binding()
+    };
+
+    HashMap::from([
+        (
+            "gParams".into(),
+            Buffer(b.params.as_entire_buffer_binding()),
+        ),
+        ("flags_packed".into(), b.flags_packed.as_entire_binding()),
+        ("block_prefix_pair".into(), block_prefix_pair_binding),
+        ("s_all_final".into(), b.s_all_final.as_entire_binding()),
+    ])
+}
+
+/// Debug taps shared by both pair prefix passes.
+fn record_applied(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    b: &GpuBuffers,
+    dbg: &mut DebugOutput,
+) {
+    dbg.capture(
+        "lexer.s_all_final",
+        device,
+        encoder,
+        &b.s_all_final,
+        b.s_all_final.byte_size,
+    );
+
+    let last = if super::block_total_scan_last_writer_is_ping(b.nb_sum) {
+        &b.dfa_02_ping
+    } else {
+        &b.dfa_02_pong
+    };
+    dbg.capture(
+        "lexer.block_prefix_pair.applied",
+        device,
+        encoder,
+        last,
+        last.byte_size,
+    );
+}
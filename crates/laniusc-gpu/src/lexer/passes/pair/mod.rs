@@ -21,7 +21,7 @@ pub(super) fn block_total_scan_steps(n_blocks: u32) -> Vec<PingPongScanStep> {
 }
 
 /// Returns whether the pair block-prefix scan leaves its final prefix in ping.
-pub(super) fn block_total_scan_last_writer_is_ping(n_blocks: u32) -> bool {
+pub(in crate::lexer) fn block_total_scan_last_writer_is_ping(n_blocks: u32) -> bool {
     block_total_scan_steps(n_blocks)
         .last()
         .map(|step| step.write_to_a)
@@ -0,0 +1,396 @@
+use std::collections::HashMap;
+
+use crate::{
+    gpu::{
+        passes_core::{
+            DispatchConvention,
+            DispatchDim,
+            DispatchRecord,
+            InputElements,
+            PassData,
+            bind_group::create_bind_group_from_reflection,
+        },
+        scan::PingPongScanStep,
+    },
+    lexer::{
+        buffers::GpuBuffers,
+        debug::DebugOutput,
+        passes::{scan_round_bind_group, scan_round_label},
+    },
+};
+
+/// Second pair pass: prefix-scans per-block boundary totals.
+///
+/// The kept-rank scan reuses it as `keep_02` over the same ping/pong buffers;
+/// see [`Self::record_scan`].
+pub struct Pair02ScanBlockTotalsPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    Pair02ScanBlockTotalsPass,
+    label: "pair_02_scan_block_totals",
+    entry: "pair_02_scan_block_totals",
+    shader: "lexer/pair/02_scan_block_totals"
+);
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for Pair02ScanBlockTotalsPass {
+    const NAME: &'static str = "pair_02_scan_block_totals";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &["gParams", "gScan", "block_pair_in", "block_pair_out"]
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &[
+            "lexer.block_pair_ping",
+            "lexer.block_pair_pong",
+            "lexer.block_prefix_pair",
+        ]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        _b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        panic!(
+            "we implement this in record_pass to deal with uniforms, which is actually hacky and bad but whatever"
+        );
+    }
+
+    fn record_pass<'a>(
+        &self,
+        ctx: &mut crate::gpu::passes_core::PassContext<'a, GpuBuffers, DebugOutput>,
+        input: crate::gpu::passes_core::InputElements,
+    ) -> anyhow::Result<(), anyhow::Error> {
+        self.record_scan(ctx, input, "pair_02", Self::NAME)
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        record_scanned(device, encoder, b, dbg);
+    }
+}
+
+impl Pair02ScanBlockTotalsPass {
+    /// Records the block-total scan rounds, labelling them `{scan}[round=..]`
+    /// and stamping timers as `pass_name`. Debug snapshots are taken for the
+    /// pair scan only.
+    pub fn record_scan<'a>(
+        &self,
+        ctx: &mut crate::gpu::passes_core::PassContext<'a, GpuBuffers, DebugOutput>,
+        input: crate::gpu::passes_core::InputElements,
+        scan: &str,
+        pass_name: &'static str,
+    ) -> anyhow::Result<(), anyhow::Error> {
+        record_block_total_scan(self, ctx, input, scan, pass_name, size_of::<u32>())
+    }
+}
+
+/// Second pair pass of the keep channel: prefix-scans the per-block
+/// `uint2` (all, kept) boundary totals over the same ping/pong buffers.
+pub struct Pair02ScanBlockTotalsWithKeepPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    Pair02ScanBlockTotalsWithKeepPass,
+    label: "pair_02_scan_block_totals_with_keep",
+    entry: "pair_02_scan_block_totals_with_keep",
+    shader: "lexer/pair/02_scan_block_totals_with_keep"
+);
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for Pair02ScanBlockTotalsWithKeepPass {
+    const NAME: &'static str = "pair_02_scan_block_totals_with_keep";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        Pair02ScanBlockTotalsPass::expected_bindings()
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        Pair02ScanBlockTotalsPass::expected_debug_keys()
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        _b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        panic!("bound per round in record_pass, like pair_02");
+    }
+
+    fn record_pass<'a>(
+        &self,
+        ctx: &mut crate::gpu::passes_core::PassContext<'a, GpuBuffers, DebugOutput>,
+        input: crate::gpu::passes_core::InputElements,
+    ) -> anyhow::Result<(), anyhow::Error> {
+        record_block_total_scan(
+            self,
+            ctx,
+            input,
+            "pair_02",
+            Self::NAME,
+            size_of::<[u32; 2]>(),
+        )
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        record_scanned(device, encoder, b, dbg);
+    }
+}
+
+/// Records the block-total scan rounds of `pass`, whose blocks are
+/// `block_bytes` wide, labelling them `{scan}[round=..]` and stamping timers
+/// as `pass_name`. Debug snapshots are taken only when `pass_name` is the
+/// pass's own name.
+fn record_block_total_scan<'a, P: crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput>>(
+    pass: &P,
+    ctx: &mut crate::gpu::passes_core::PassContext<'a, GpuBuffers, DebugOutput>,
+    input: crate::gpu::passes_core::InputElements,
+    scan: &str,
+    pass_name: &'static str,
+    block_bytes: usize,
+) -> anyhow::Result<(), anyhow::Error> {
+    let device = ctx.device;
+    let encoder = &mut ctx.encoder;
+    let b = ctx.buffers;
+    let maybe_timer = &mut ctx.maybe_timer;
+    let maybe_dbg = &mut ctx.maybe_dbg;
+    let mut bg_cache = ctx.bg_cache.as_deref_mut();
+    let debug_groups = ctx.debug_groups;
+    let mut dispatch_records = ctx.dispatch_records.as_deref_mut();
+
+    let use_scopes = ctx.validation_scopes;
+
+    let validation_scope = crate::gpu::passes_core::validation_scope(device, use_scopes);
+
+    let n = match input {
+        InputElements::Elements1D(n) => n,
+        _ => unreachable!(),
+    };
+
+    let scan_steps = super::block_total_scan_steps(n);
+    #[cfg(not(feature = "gpu-debug"))]
+    let _ = block_bytes;
+
+    let pd = pass.data();
+    P::check_dispatch(pd.thread_group_size)?;
+    let (gx, gy, gz) =
+        crate::gpu::passes_core::plan_dispatch(P::DISPATCH, P::DIM, input, pd.thread_group_size)?;
+
+    let layout0 = &pd.bind_group_layouts[0];
+    let pipeline = &pd.pipeline;
+    let reflection = &pd.reflection;
+
+    let snapshot = pass_name == P::NAME;
+
+    let can_batch =
+        maybe_timer.is_none() && maybe_dbg.is_none() && ctx.batch_compute_passes && !use_scopes;
+    let mut retained_bind_groups = Vec::with_capacity(scan_steps.len());
+
+    let mut round_bind_group = |r: u32, step: PingPongScanStep| {
+        debug_assert_eq!(
+            step.scan_step,
+            1u32 << r,
+            "pooled ScanParams stride mismatch"
+        );
+        debug_assert_eq!(
+            step.read_from_a,
+            r.is_multiple_of(2),
+            "pooled ScanParams parity mismatch"
+        );
+        scan_round_bind_group(bg_cache.as_deref_mut(), P::NAME, r, b.generation, || {
+            let scan_params = b.scan_params_for_round(r)?;
+            let block_pair_in = if step.read_from_a {
+                &b.dfa_02_ping
+            } else {
+                &b.dfa_02_pong
+            };
+            let block_pair_out = if step.write_to_a {
+                &b.dfa_02_ping
+            } else {
+                &b.dfa_02_pong
+            };
+
+            let res = HashMap::from([
+                (
+                    "gParams".into(),
+                    wgpu::BindingResource::Buffer(b.params.as_entire_buffer_binding()),
+                ),
+                (
+                    "gScan".into(),
+                    wgpu::BindingResource::Buffer(scan_params.as_entire_buffer_binding()),
+                ),
+                // Reuse DFA ping/pong for pair scan
+                ("block_pair_in".into(), block_pair_in.as_entire_binding()),
+                ("block_pair_out".into(), block_pair_out.as_entire_binding()),
+            ]);
+            create_bind_group_from_reflection(
+                device,
+                Some(&format!("pair_blocks_bg[{r}]")),
+                layout0,
+                reflection,
+                0,
+                &res,
+            )
+        })
+    };
+
+    if debug_groups {
+        encoder.push_debug_group(pass_name);
+    }
+
+    if can_batch {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some(pass_name),
+            timestamp_writes: None,
+        });
+        for (r, step) in scan_steps.iter().copied().enumerate() {
+            let bg = round_bind_group(r as u32, step)?;
+
+            let label = scan_round_label(scan, r as u32);
+            if debug_groups {
+                pass.push_debug_group(&label);
+            }
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &*bg, &[]);
+            pass.dispatch_workgroups(gx, gy, gz);
+            if debug_groups {
+                pass.pop_debug_group();
+            }
+            if let Some(records) = dispatch_records.as_deref_mut() {
+                records.push(DispatchRecord::direct(label, (gx, gy, gz), input));
+            }
+            retained_bind_groups.push(bg);
+        }
+    } else {
+        for (r, step) in scan_steps.iter().copied().enumerate() {
+            let bg = round_bind_group(r as u32, step)?;
+
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some(pass_name),
+                timestamp_writes: None,
+            });
+            let label = scan_round_label(scan, r as u32);
+            if debug_groups {
+                pass.push_debug_group(&label);
+            }
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &*bg, &[]);
+            pass.dispatch_workgroups(gx, gy, gz);
+            if debug_groups {
+                pass.pop_debug_group();
+            }
+            drop(pass);
+            if let Some(t) = maybe_timer.as_deref_mut() {
+                t.stamp_round(encoder, label.as_str());
+            }
+            if let Some(records) = dispatch_records.as_deref_mut() {
+                records.push(DispatchRecord::direct(label, (gx, gy, gz), input));
+            }
+
+            retained_bind_groups.push(bg);
+
+            #[cfg(feature = "gpu-debug")]
+            if snapshot && let Some(dbg) = maybe_dbg.as_deref_mut() {
+                // Debug: snapshot reused DFA block ping/pong
+                let last_writer = if step.write_to_a {
+                    &b.dfa_02_ping
+                } else {
+                    &b.dfa_02_pong
+                };
+                dbg.capture_round(
+                    "lexer.pair_scan_round",
+                    r as u32,
+                    device,
+                    encoder,
+                    last_writer,
+                    n as usize * block_bytes,
+                );
+            }
+        }
+    }
+
+    if debug_groups {
+        encoder.pop_debug_group();
+    }
+
+    if let Some(t) = maybe_timer {
+        t.stamp(encoder, pass_name.to_string());
+    }
+
+    if let Some(err) = crate::gpu::passes_core::pop_validation_scope(validation_scope) {
+        return Err(anyhow::anyhow!(
+            "validation in pass {}: {:?}",
+            pass_name,
+            err
+        ));
+    }
+
+    if snapshot && let Some(d) = maybe_dbg.as_deref_mut() {
+        pass.record_declared_debug(device, encoder, b, d);
+    }
+    Ok(())
+}
+
+/// Debug taps shared by both pair scans.
+fn record_scanned(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    b: &GpuBuffers,
+    dbg: &mut DebugOutput,
+) {
+    dbg.capture(
+        "lexer.block_pair_ping",
+        device,
+        encoder,
+        &b.dfa_02_ping,
+        b.dfa_02_ping.byte_size,
+    );
+    dbg.capture(
+        "lexer.block_pair_pong",
+        device,
+        encoder,
+        &b.dfa_02_pong,
+        b.dfa_02_pong.byte_size,
+    );
+
+    let last = if super::block_total_scan_last_writer_is_ping(b.nb_sum) {
+        &b.dfa_02_ping
+    } else {
+        &b.dfa_02_pong
+    };
+    dbg.capture(
+        "lexer.block_prefix_pair",
+        device,
+        encoder,
+        last,
+        last.byte_size,
+    );
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use crate::{
+    gpu::passes_core::{DispatchConvention, DispatchDim, PassData},
+    lexer::{buffers::GpuBuffers, debug::DebugOutput},
+};
+
+/// First pair pass: counts all token boundaries inside each block.
+pub struct Pair01SumInblockPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    Pair01SumInblockPass,
+    label: "pair_01_sum_inblock",
+    entry: "pair_01_sum_inblock",
+    shader: "lexer/pair/01_sum_inblock"
+);
+
+/// First pair pass of the keep channel: counts all and kept token
+/// boundaries inside each block, one `uint2` total per block.
+pub struct Pair01SumInblockWithKeepPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    Pair01SumInblockWithKeepPass,
+    label: "pair_01_sum_inblock_with_keep",
+    entry: "pair_01_sum_inblock_with_keep",
+    shader: "lexer/pair/01_sum_inblock_with_keep"
+);
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for Pair01SumInblockPass {
+    const NAME: &'static str = "pair_01_sum_inblock";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &["gParams", "flags_packed", "block_totals_pair"]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        resource_map(b)
+    }
+    // fn record_debug(
+    //     &self,
+    //     device: &wgpu::Device,
+    //     encoder: &mut wgpu::CommandEncoder,
+    //     b: &GpuBuffers,
+    //     dbg: &mut DebugOutput,
+    // ) {
+    //     dbg.gpu.block_totals_pair.set_from_copy(
+    //         device,
+    //         encoder,
+    //         &b.block_totals_pair,
+    //         "dbg.block_totals_pair",
+    //         b.block_totals_pair.byte_size,
+    //     );
+    // }
+}
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for Pair01SumInblockWithKeepPass {
+    const NAME: &'static str = "pair_01_sum_inblock_with_keep";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &["gParams", "flags_packed", "block_totals_pair"]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        resource_map(b)
+    }
+}
+
+/// Bindings shared by both pair seed passes; the `uint2` totals of the keep
+/// channel fit the same DFA ping buffer.
+fn resource_map(b: &GpuBuffers) -> HashMap<String, wgpu::BindingResource<'_>> {
+    use wgpu::BindingResource::*;
+    HashMap::from([
+        (
+            "gParams".into(),
+            Buffer(b.params.as_entire_buffer_binding()),
+        ),
+        ("flags_packed".into(), b.flags_packed.as_entire_binding()),
+        (
+            "block_totals_pair".into(),
+            // Reuse DFA block ping as pair ping
+            b.dfa_02_ping.as_entire_binding(),
+        ),
+    ])
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, PassData},
     lexer::{buffers::GpuBuffers, debug::DebugOutput},
 };
 
@@ -10,7 +10,7 @@ pub struct SourceFileBoundariesPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     SourceFileBoundariesPass,
     label: "source_file_boundaries",
     entry: "source_file_boundaries",
@@ -20,6 +20,7 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for SourceFileBoundariesPass {
     const NAME: &'static str = "source_file_boundaries";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn data(&self) -> &PassData {
         &self.data
@@ -29,6 +30,16 @@ impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for SourceFileBounda
         Self { data }
     }
 
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "source_file_count",
+            "source_file_start",
+            "source_file_len",
+            "source_file_start_flags",
+            "source_file_end_flags",
+        ]
+    }
+
     fn create_resource_map<'a>(
         &self,
         b: &'a GpuBuffers,
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+
+use crate::{
+    gpu::passes_core::{DispatchConvention, DispatchDim, PassData},
+    lexer::{buffers::GpuBuffers, debug::DebugOutput},
+};
+
+/// Builds final `GpuToken` records and token source-file ids, plus each kept
+/// token's accept state when capture is on and the suspect list in paranoia
+/// mode.
+pub struct TokensBuildPass {
+    data: PassData,
+}
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    TokensBuildPass,
+    label: "tokens_build",
+    entry: "tokens_build",
+    shader: "lexer/tokens_build"
+);
+
+/// [`TokensBuildPass`] for a `tokens_out` bound as its two
+/// [`SplitBinding`](crate::gpu::buffers::SplitBinding) windows.
+pub struct TokensBuildSplitPass {
+    data: PassData,
+}
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    TokensBuildSplitPass,
+    label: "tokens_build_split",
+    entry: "tokens_build_split",
+    shader: "lexer/tokens_build_split"
+);
+
+/// Bindings both token builders share; the split one adds `tokens_out_hi`.
+const BINDINGS: [&str; 20] = [
+    "gParams",
+    "in_bytes",
+    "token_count",
+    "end_positions",
+    "types_compact",
+    "all_index_compact",
+    "end_positions_all",
+    "source_file_count",
+    "source_file_start",
+    "source_file_len",
+    "tokens_out",
+    "token_file_id",
+    "parser_feature_flags",
+    "token_order_status",
+    "token_len_status",
+    "all_token_count",
+    "dfa_states",
+    "accept_states",
+    "suspect_count",
+    "suspect_tokens",
+];
+
+/// [`BINDINGS`] plus the high window of a split `tokens_out`.
+const SPLIT_BINDINGS: [&str; 21] = {
+    let mut bindings = [""; 21];
+    let mut i = 0;
+    while i < BINDINGS.len() {
+        bindings[i] = BINDINGS[i];
+        i += 1;
+    }
+    bindings[BINDINGS.len()] = "tokens_out_hi";
+    bindings
+};
+
+/// Resources of [`BINDINGS`], with `tokens_out` bound as `b` splits it.
+fn resource_map(b: &GpuBuffers) -> HashMap<String, wgpu::BindingResource<'_>> {
+    let mut resources = HashMap::from([
+        (
+            "gParams".into(),
+            wgpu::BindingResource::Buffer(b.params.as_entire_buffer_binding()),
+        ),
+        ("in_bytes".into(), b.in_bytes.as_entire_binding()),
+        ("token_count".into(), b.token_count.as_entire_binding()),
+        ("end_positions".into(), b.end_positions.as_entire_binding()),
+        ("types_compact".into(), b.types_compact.as_entire_binding()),
+        (
+            "all_index_compact".into(),
+            b.all_index_compact.as_entire_binding(),
+        ),
+        (
+            "end_positions_all".into(),
+            b.end_positions_all.as_entire_binding(),
+        ),
+        (
+            "source_file_count".into(),
+            b.source_file_count.as_entire_binding(),
+        ),
+        (
+            "source_file_start".into(),
+            b.source_file_start.as_entire_binding(),
+        ),
+        (
+            "source_file_len".into(),
+            b.source_file_len.as_entire_binding(),
+        ),
+        ("token_file_id".into(), b.token_file_id.as_entire_binding()),
+        (
+            "parser_feature_flags".into(),
+            b.parser_feature_flags.as_entire_binding(),
+        ),
+        (
+            "token_order_status".into(),
+            b.token_order_status.as_entire_binding(),
+        ),
+        (
+            "token_len_status".into(),
+            b.token_len_status.as_entire_binding(),
+        ),
+        (
+            "all_token_count".into(),
+            b.all_token_count.as_entire_binding(),
+        ),
+        ("dfa_states".into(), b.dfa_states.as_entire_binding()),
+        ("accept_states".into(), b.accept_states.as_entire_binding()),
+        ("suspect_count".into(), b.suspect_count.as_entire_binding()),
+        (
+            "suspect_tokens".into(),
+            b.suspect_tokens.as_entire_binding(),
+        ),
+    ]);
+    resources.extend(b.tokens_out_bindings());
+    resources
+}
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for TokensBuildPass {
+    const NAME: &'static str = "tokens_build";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &BINDINGS
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &["lexer.tokens_out"]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        resource_map(b)
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        dbg.capture(
+            "lexer.tokens_out",
+            device,
+            encoder,
+            &b.tokens_out,
+            b.tokens_out.byte_size,
+        );
+    }
+}
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for TokensBuildSplitPass {
+    const NAME: &'static str = "tokens_build_split";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &SPLIT_BINDINGS
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &["lexer.tokens_out"]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        resource_map(b)
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        dbg.capture(
+            "lexer.tokens_out",
+            device,
+            encoder,
+            &b.tokens_out,
+            b.tokens_out.byte_size,
+        );
+    }
+}
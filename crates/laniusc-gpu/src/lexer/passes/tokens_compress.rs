@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use crate::{
+    gpu::passes_core::{DispatchConvention, DispatchDim, PassData},
+    lexer::{buffers::GpuBuffers, debug::DebugOutput},
+};
+
+/// Packs each kept token into one word plus an escape list for the
+/// compressed host readback; recorded after `tokens_build` only when the
+/// lex reads its tokens back compressed.
+pub struct TokensCompressPass {
+    data: PassData,
+}
+
+crate::gpu::passes_core::impl_checked_shader_pass!(
+    TokensCompressPass,
+    label: "tokens_compress",
+    entry: "tokens_compress",
+    shader: "lexer/tokens_compress"
+);
+
+impl crate::gpu::passes_core::Pass<GpuBuffers, DebugOutput> for TokensCompressPass {
+    const NAME: &'static str = "tokens_compress";
+    const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
+
+    fn from_data(data: PassData) -> Self {
+        Self { data }
+    }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gParams",
+            "token_count",
+            "tokens_out",
+            "compressed_tokens",
+            "compressed_escape_count",
+            "compressed_escapes",
+        ]
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &["lexer.compressed_tokens"]
+    }
+    fn data(&self) -> &PassData {
+        &self.data
+    }
+    fn create_resource_map<'a>(
+        &self,
+        b: &'a GpuBuffers,
+    ) -> HashMap<String, wgpu::BindingResource<'a>> {
+        HashMap::from([
+            (
+                "gParams".into(),
+                wgpu::BindingResource::Buffer(b.params.as_entire_buffer_binding()),
+            ),
+            ("token_count".into(), b.token_count.as_entire_binding()),
+            ("tokens_out".into(), b.tokens_out.as_entire_binding()),
+            (
+                "compressed_tokens".into(),
+                b.compressed_tokens.as_entire_binding(),
+            ),
+            (
+                "compressed_escape_count".into(),
+                b.compressed_escape_count.as_entire_binding(),
+            ),
+            (
+                "compressed_escapes".into(),
+                b.compressed_escapes.as_entire_binding(),
+            ),
+        ])
+    }
+
+    fn record_debug(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        b: &GpuBuffers,
+        dbg: &mut DebugOutput,
+    ) {
+        dbg.capture(
+            "lexer.compressed_tokens",
+            device,
+            encoder,
+            &b.compressed_tokens,
+            b.compressed_tokens.byte_size,
+        );
+    }
+}
@@ -0,0 +1,484 @@
+//! Token queries over GPU-resident lexer output.
+//!
+//! A query selects the tokens of some kinds that intersect a byte range and
+//! reads back only those, instead of every token. It runs over the
+//! all-boundary stream, so skipped kinds such as comments can be selected;
+//! kept slots report their final kept record, as
+//! [`merge_kept_into_all`](crate::lexer::util::merge_kept_into_all) does on
+//! the host.
+//!
+//! Matches are compacted by rank with the lexer's block scan rather than by
+//! atomic appends, so results are in ascending start order on every run.
+
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow};
+use encase::ShaderType;
+
+use crate::{
+    gpu::{
+        buffers::{LaniusBuffer, storage_ro_from_u32s, storage_rw_uninit_bytes, uniform_from_val},
+        passes_core::{
+            DispatchDim,
+            InputElements,
+            PassContext,
+            PassData,
+            bind_group::create_bind_group_from_reflection,
+            plan_workgroups,
+        },
+    },
+    lexer::{
+        buffers::GpuBuffers,
+        driver::read_u32s,
+        passes::{LexerPasses, pair::block_total_scan_last_writer_is_ping, record_kept_ranks},
+        tables::tokens::{N_KINDS, TokenKind},
+        types::{LexerRuntimeOptions, Token},
+    },
+    span::Span,
+};
+
+/// `u32` words in a [`KindMask`], enough for every token id.
+pub const KIND_MASK_WORDS: usize = N_KINDS.div_ceil(32) as usize;
+
+/// Round label of the query block scan, which reruns `pair_02`.
+const QUERY_SCAN: &str = "query_02";
+/// Pass name of the query block scan for timers and debug groups.
+const QUERY_SCAN_NAME: &str = "query_02_scan_block_totals";
+
+/// Set of token kinds selected by a [`QuerySpec`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct KindMask {
+    words: [u32; KIND_MASK_WORDS],
+}
+
+impl KindMask {
+    /// Returns a mask selecting no kinds.
+    pub const fn empty() -> Self {
+        Self {
+            words: [0; KIND_MASK_WORDS],
+        }
+    }
+
+    /// Returns a mask selecting every kind.
+    pub fn all() -> Self {
+        TokenKind::ALL.iter().copied().collect()
+    }
+
+    /// Returns the mask of the kinds the lexer keeps by default, the
+    /// complement of [`is_skip_kind`](crate::lexer::boundary::is_skip_kind).
+    pub fn kept() -> Self {
+        TokenKind::ALL
+            .iter()
+            .copied()
+            .filter(|&kind| !crate::lexer::boundary::is_skip_kind(kind))
+            .collect()
+    }
+
+    /// Adds `kind` to the mask.
+    pub fn insert(&mut self, kind: TokenKind) {
+        let id = kind as usize;
+        self.words[id / 32] |= 1 << (id % 32);
+    }
+
+    /// Returns the mask with `kind` added.
+    pub fn with(mut self, kind: TokenKind) -> Self {
+        self.insert(kind);
+        self
+    }
+
+    /// Whether `kind` is selected.
+    pub fn contains(&self, kind: TokenKind) -> bool {
+        let id = kind as usize;
+        self.words[id / 32] & (1 << (id % 32)) != 0
+    }
+
+    /// Bitset words as uploaded to the query shaders; bit `id % 32` of word
+    /// `id / 32` selects token id `id`.
+    pub fn words(&self) -> &[u32; KIND_MASK_WORDS] {
+        &self.words
+    }
+}
+
+impl FromIterator<TokenKind> for KindMask {
+    fn from_iter<I: IntoIterator<Item = TokenKind>>(iter: I) -> Self {
+        let mut mask = Self::empty();
+        for kind in iter {
+            mask.insert(kind);
+        }
+        mask
+    }
+}
+
+/// What [`DeviceTokens::query`] selects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuerySpec {
+    /// Kinds to select, compared against the final (retagged) kind.
+    pub kinds_mask: KindMask,
+    /// Bytes a selected token must intersect; `None` selects every offset.
+    pub byte_range: Option<Span>,
+    /// Most tokens returned; later matches only count toward the total.
+    pub max_results: u32,
+}
+
+impl QuerySpec {
+    /// Whether `token` is selected. This is the host form of the GPU
+    /// predicate.
+    pub fn matches(&self, token: &Token) -> bool {
+        if !self.kinds_mask.contains(token.kind) {
+            return false;
+        }
+        let range = self.range_bounds();
+        token.span.start < range.end && token.span.end > range.start
+    }
+
+    fn range_bounds(&self) -> Span {
+        self.byte_range.unwrap_or(Span::new(0, u32::MAX))
+    }
+}
+
+/// Tokens selected by one [`DeviceTokens::query`].
+#[derive(Debug, Clone)]
+pub struct QueryOutput {
+    /// The first `max_results` matches in ascending start order.
+    pub tokens: Vec<Token>,
+    /// Number of tokens matching the query, returned or not.
+    pub total_matches: u32,
+    /// Whether more than `max_results` tokens matched.
+    pub overflowed: bool,
+}
+
+#[derive(ShaderType, Debug, Clone, Copy)]
+/// Uniform parameters of the query passes.
+struct QueryParams {
+    mask_words: u32,
+    range_lo: u32,
+    range_hi: u32,
+    max_results: u32,
+}
+
+/// Count and scatter pipelines of the token query.
+pub(crate) struct QueryPasses {
+    count: PassData,
+    scatter: PassData,
+}
+
+impl QueryPasses {
+    /// Creates the query pipelines for a device.
+    pub(crate) fn new(device: &wgpu::Device) -> Result<Self> {
+        Ok(Self {
+            count: crate::gpu::passes_core::make_shader_pass!(
+                device,
+                "query_01_count_inblock",
+                entry: "query_01_count_inblock",
+                shader: "lexer/query/01_count_inblock"
+            )?,
+            scatter: crate::gpu::passes_core::make_shader_pass!(
+                device,
+                "query_03_scatter",
+                entry: "query_03_scatter",
+                shader: "lexer/query/03_scatter"
+            )?,
+        })
+    }
+}
+
+/// Lexer output still resident on the GPU, valid for the duration of a
+/// [`GpuLexer::with_device_tokens`](crate::lexer::GpuLexer::with_device_tokens)
+/// continuation.
+pub struct DeviceTokens<'a> {
+    bufs: &'a GpuBuffers,
+    lexer: &'a LexerPasses,
+    passes: &'a QueryPasses,
+    runtime: LexerRuntimeOptions,
+}
+
+impl<'a> DeviceTokens<'a> {
+    pub(crate) fn new(
+        bufs: &'a GpuBuffers,
+        lexer: &'a LexerPasses,
+        passes: &'a QueryPasses,
+        runtime: LexerRuntimeOptions,
+    ) -> Self {
+        Self {
+            bufs,
+            lexer,
+            passes,
+            runtime,
+        }
+    }
+
+    /// Selects the tokens matching `spec` on the GPU and reads back only
+    /// those, in ascending start order.
+    ///
+    /// The query reuses the lexer's block-scan scratch buffers, so it has to
+    /// run before the resident buffers are handed to another lex.
+    pub fn query(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        spec: &QuerySpec,
+    ) -> Result<QueryOutput> {
+        let b = self.bufs;
+        if b.n == 0 {
+            return Ok(QueryOutput {
+                tokens: Vec::new(),
+                total_matches: 0,
+                overflowed: false,
+            });
+        }
+
+        // A query cannot return more tokens than the input has bytes.
+        let capacity = spec.max_results.min(b.n).max(1) as usize;
+        let range = spec.range_bounds();
+        let params = uniform_from_val(
+            device,
+            "lex.query.params",
+            &QueryParams {
+                mask_words: KIND_MASK_WORDS as u32,
+                range_lo: range.start,
+                range_hi: range.end,
+                max_results: spec.max_results,
+            },
+        );
+        let kind_mask =
+            storage_ro_from_u32s(device, "lex.query.kind_mask", spec.kinds_mask.words());
+        let results = storage_rw_uninit_bytes(
+            device,
+            "lex.query.results",
+            capacity * QUERY_TOKEN_BYTES,
+            capacity,
+        );
+        let status = storage_rw_uninit_bytes(device, "lex.query.status", 4, 1);
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("lex-query-enc"),
+        });
+        self.record(device, &mut encoder, &params, &kind_mask, &results, &status)?;
+        crate::gpu::passes_core::submit_with_progress(queue, "lex.query", encoder.finish());
+
+        let total_matches = read_u32s(device, queue, &status, 1, "lex.query.count")?[0];
+        let returned = total_matches.min(spec.max_results) as usize;
+        let tokens = if returned == 0 {
+            Vec::new()
+        } else {
+            let words = read_u32s(device, queue, &results, returned * 4, "lex.query.results")?;
+            tokens_from_query_words(&words)?
+        };
+        Ok(QueryOutput {
+            tokens,
+            total_matches,
+            overflowed: total_matches > spec.max_results,
+        })
+    }
+
+    fn record(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        params: &LaniusBuffer<QueryParams>,
+        kind_mask: &LaniusBuffer<u32>,
+        results: &LaniusBuffer<u8>,
+        status: &LaniusBuffer<u8>,
+    ) -> Result<()> {
+        let b = self.bufs;
+        let token_inputs = || -> HashMap<String, wgpu::BindingResource<'_>> {
+            HashMap::from([
+                ("gParams".into(), b.params.as_entire_binding()),
+                ("gQuery".into(), params.as_entire_binding()),
+                ("kind_mask".into(), kind_mask.as_entire_binding()),
+                (
+                    "all_token_count".into(),
+                    b.all_token_count.as_entire_binding(),
+                ),
+                ("types_all".into(), b.types_all.as_entire_binding()),
+                (
+                    "end_positions_all".into(),
+                    b.end_positions_all.as_entire_binding(),
+                ),
+                // keep_03 leaves the kept rank per ALL slot in s_all_final
+                ("s_keep_final".into(), b.s_all_final.as_entire_binding()),
+                ("tokens_out".into(), b.tokens_out.as_entire_binding()),
+                ("types_compact".into(), b.types_compact.as_entire_binding()),
+            ])
+        };
+
+        let mut ctx = PassContext {
+            device,
+            encoder: &mut *encoder,
+            buffers: b,
+            maybe_timer: &mut None,
+            maybe_dbg: &mut None,
+            bg_cache: None,
+            debug_groups: self.runtime.debug_groups,
+            validation_scopes: self.runtime.validation_scopes,
+            batch_compute_passes: self.runtime.batch_compute_passes,
+            dispatch_records: None,
+        };
+        // Only the late kept filter leaves kept ranks per ALL slot, and the
+        // layout passes overwrite them, so rank the compacted ALL stream again.
+        record_kept_ranks(b.n, b.nb_dfa, b.nb_sum, &mut ctx, self.lexer)?;
+
+        let mut count_res = token_inputs();
+        count_res.insert(
+            "block_totals_query".into(),
+            b.dfa_02_ping.as_entire_binding(),
+        );
+        dispatch(device, ctx.encoder, &self.passes.count, &count_res, b.n)?;
+
+        self.lexer.pair_02.record_scan(
+            &mut ctx,
+            InputElements::Elements1D(b.nb_sum),
+            QUERY_SCAN,
+            QUERY_SCAN_NAME,
+        )?;
+
+        let block_prefix = if block_total_scan_last_writer_is_ping(b.nb_sum) {
+            &b.dfa_02_ping
+        } else {
+            &b.dfa_02_pong
+        };
+        let mut scatter_res = token_inputs();
+        scatter_res.extend([
+            (
+                "block_prefix_query".into(),
+                block_prefix.as_entire_binding(),
+            ),
+            ("query_results".into(), results.as_entire_binding()),
+            ("query_status".into(), status.as_entire_binding()),
+        ]);
+        dispatch(device, encoder, &self.passes.scatter, &scatter_res, b.n)
+    }
+}
+
+/// Bytes per result record: kind, raw kind, start, and length words.
+const QUERY_TOKEN_BYTES: usize = 16;
+
+fn dispatch(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    pd: &PassData,
+    resources: &HashMap<String, wgpu::BindingResource<'_>>,
+    n: u32,
+) -> Result<()> {
+    let bind_group = create_bind_group_from_reflection(
+        device,
+        Some(&pd.shader_id),
+        &pd.bind_group_layouts[0],
+        &pd.reflection,
+        0,
+        resources,
+    )?;
+    let (gx, gy, gz) = plan_workgroups(
+        DispatchDim::D1,
+        InputElements::Elements1D(n),
+        pd.thread_group_size,
+    )?;
+    let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+        label: Some(&pd.shader_id),
+        timestamp_writes: None,
+    });
+    pass.set_pipeline(&pd.pipeline);
+    pass.set_bind_group(0, Some(&bind_group), &[]);
+    pass.dispatch_workgroups(gx, gy, gz);
+    Ok(())
+}
+
+fn tokens_from_query_words(words: &[u32]) -> Result<Vec<Token>> {
+    words
+        .chunks_exact(4)
+        .enumerate()
+        .map(|(i, record)| {
+            let kind = |id: u32| {
+                TokenKind::from_u32(id)
+                    .ok_or_else(|| anyhow!("token query: invalid token kind {id} at result {i}"))
+            };
+            let span = Span::from_start_len(record[2], record[3]).ok_or_else(|| {
+                anyhow!(
+                    "token query: span {}+{} at result {i} passes u32::MAX",
+                    record[2],
+                    record[3]
+                )
+            })?;
+            Ok(Token {
+                kind: kind(record[0])?,
+                raw_kind: kind(record[1])?,
+                span,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(kind: TokenKind, start: usize, len: usize) -> Token {
+        Token::new(kind, start, len)
+    }
+
+    #[test]
+    fn kind_mask_covers_every_token_id() {
+        let all = KindMask::all();
+        for &kind in TokenKind::ALL {
+            assert!(all.contains(kind), "{kind:?}");
+        }
+        assert!(KIND_MASK_WORDS * 32 >= N_KINDS as usize);
+        assert!(KIND_MASK_WORDS > 2, "more kinds than two u64 words cover");
+
+        let comments = KindMask::empty()
+            .with(TokenKind::LineComment)
+            .with(TokenKind::BlockComment);
+        assert!(comments.contains(TokenKind::BlockComment));
+        assert!(!comments.contains(TokenKind::Ident));
+        assert_eq!(
+            comments.words().iter().map(|w| w.count_ones()).sum::<u32>(),
+            2
+        );
+
+        let kept = KindMask::kept();
+        assert!(kept.contains(TokenKind::Ident));
+        assert!(!kept.contains(TokenKind::White));
+        assert!(!kept.contains(TokenKind::LineComment));
+    }
+
+    #[test]
+    fn query_spec_selects_kinds_intersecting_the_range() {
+        let spec = QuerySpec {
+            kinds_mask: KindMask::empty().with(TokenKind::Ident),
+            byte_range: Some(Span::new(4, 8)),
+            max_results: 16,
+        };
+        assert!(spec.matches(&token(TokenKind::Ident, 2, 3)));
+        assert!(spec.matches(&token(TokenKind::Ident, 7, 5)));
+        assert!(!spec.matches(&token(TokenKind::Ident, 1, 3)));
+        assert!(!spec.matches(&token(TokenKind::Ident, 8, 1)));
+        assert!(!spec.matches(&token(TokenKind::Int, 5, 1)));
+
+        let unbounded = QuerySpec {
+            byte_range: None,
+            ..spec
+        };
+        assert!(unbounded.matches(&token(TokenKind::Ident, 1, 3)));
+    }
+
+    #[test]
+    fn query_records_decode_kind_raw_kind_and_span() {
+        let words = [
+            TokenKind::Let as u32,
+            TokenKind::Ident as u32,
+            0,
+            3,
+            TokenKind::LineComment as u32,
+            TokenKind::LineComment as u32,
+            4,
+            9,
+        ];
+        let tokens = tokens_from_query_words(&words).expect("valid records");
+        assert_eq!(tokens[0].raw_kind, TokenKind::Ident);
+        assert_eq!(
+            (tokens[1].kind, tokens[1].start(), tokens[1].len()),
+            (TokenKind::LineComment, 4, 9)
+        );
+        assert!(tokens_from_query_words(&[0xFFFF, 0, 0, 0]).is_err());
+    }
+}
@@ -0,0 +1,410 @@
+//! Host-side offset remapping for sources assembled from several pieces.
+//!
+//! A build that concatenates a prelude and a user file before lexing gets
+//! token offsets into the concatenation. [`SourceMap`] records where each
+//! named piece landed so offsets can be resolved back to a file, a
+//! file-relative offset, and a line/column.
+//!
+//! Pieces that do not end in `\n`, including empty ones, are followed by one
+//! injected newline so a token can never fuse across files; the injected byte
+//! belongs to no piece and resolves to the end of the piece before it.
+//!
+//! A piece's leading UTF-8 BOM is left out of the assembled text, since only
+//! offset 0 may hold one; piece-relative offsets still count its three bytes.
+
+use anyhow::Result;
+
+use crate::{
+    lexer::{GpuLexer, Token, bom::bom_len},
+    span::Span,
+};
+
+/// Line start offsets for one text, resolving byte offsets to 1-based
+/// line/column pairs.
+///
+/// A leading UTF-8 BOM is not a column, as in editors: the first line starts
+/// after it, and offsets inside it resolve to line 1, column 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineMap {
+    line_starts: Vec<usize>,
+    len: usize,
+}
+
+impl LineMap {
+    /// Indexes the line starts of `text`.
+    pub fn new(text: &str) -> Self {
+        let mut line_starts = vec![bom_len(text.as_bytes())];
+        line_starts.extend(
+            text.bytes()
+                .enumerate()
+                .filter(|&(_, b)| b == b'\n')
+                .map(|(i, _)| i + 1),
+        );
+        Self {
+            line_starts,
+            len: text.len(),
+        }
+    }
+
+    /// Number of lines, counting a trailing empty line after a final `\n`.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Returns the 1-based line and byte column of `offset`, clamped to the
+    /// end of the text.
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.len).max(self.line_starts[0]);
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        (line + 1, offset - self.line_starts[line] + 1)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SourcePiece {
+    name: String,
+    /// Offset of the piece in the assembled text, after any leading BOM.
+    start: usize,
+    /// Length of the leading BOM left out of the assembled text.
+    bom: usize,
+    len: usize,
+    lines: LineMap,
+}
+
+impl SourcePiece {
+    /// Bytes of the piece in the assembled text.
+    fn assembled_len(&self) -> usize {
+        self.len - self.bom
+    }
+}
+
+/// A logical source assembled from named pieces, with the offset of each
+/// piece in the assembled text.
+#[derive(Debug, Clone)]
+pub struct SourceMap {
+    text: String,
+    pieces: Vec<SourcePiece>,
+}
+
+/// Where an assembled-text offset lands in its source piece.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// Index of the piece in construction order.
+    pub file_id: u32,
+    /// Byte offset relative to the start of the piece.
+    pub offset: usize,
+    /// 1-based line within the piece.
+    pub line: usize,
+    /// 1-based byte column within the line.
+    pub column: usize,
+}
+
+/// A lexed token attributed to the piece where it starts.
+#[derive(Debug, Clone)]
+pub struct MappedToken {
+    /// Token with its span still in assembled-text coordinates.
+    pub token: Token,
+    /// Piece the token starts in.
+    pub file_id: u32,
+    /// Token bytes relative to the start of the piece; past the piece's end
+    /// when `crosses_boundary` is set.
+    pub span: Span,
+    /// Whether the token runs past the end of its piece.
+    pub crosses_boundary: bool,
+}
+
+impl SourceMap {
+    /// Assembles `pieces` in order, injecting a newline after each piece
+    /// except the last when it does not already end with one.
+    pub fn new<N, T>(pieces: impl IntoIterator<Item = (N, T)>) -> Self
+    where
+        N: Into<String>,
+        T: AsRef<str>,
+    {
+        let mut text = String::new();
+        let mut out = Vec::<SourcePiece>::new();
+        for (name, piece) in pieces {
+            if out.last().is_some() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            if let Some(prev) = out.last()
+                && prev.assembled_len() == 0
+                && text.len() == prev.start
+            {
+                // An empty piece still owns a separator so every piece has a
+                // distinct start offset.
+                text.push('\n');
+            }
+            let piece = piece.as_ref();
+            let bom = bom_len(piece.as_bytes());
+            out.push(SourcePiece {
+                name: name.into(),
+                start: text.len(),
+                bom,
+                len: piece.len(),
+                lines: LineMap::new(piece),
+            });
+            text.push_str(&piece[bom..]);
+        }
+        Self { text, pieces: out }
+    }
+
+    /// The assembled text that is handed to the lexer.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Number of pieces.
+    pub fn file_count(&self) -> usize {
+        self.pieces.len()
+    }
+
+    /// Name of piece `file_id`.
+    pub fn name(&self, file_id: u32) -> Option<&str> {
+        self.pieces
+            .get(file_id as usize)
+            .map(|piece| piece.name.as_str())
+    }
+
+    /// Offset of piece `file_id` in the assembled text, after any leading BOM.
+    pub fn piece_start(&self, file_id: u32) -> Option<usize> {
+        self.pieces.get(file_id as usize).map(|piece| piece.start)
+    }
+
+    /// Resolves an assembled-text offset to its piece, or `None` when the
+    /// offset is past the end of the text or there are no pieces.
+    ///
+    /// An injected separator resolves to the end of the piece before it.
+    pub fn resolve(&self, offset: usize) -> Option<SourceLocation> {
+        if offset > self.text.len() {
+            return None;
+        }
+        let file_id = self.file_id_at(offset)?;
+        let piece = &self.pieces[file_id as usize];
+        let offset = (offset - piece.start + piece.bom).min(piece.len);
+        let (line, column) = piece.lines.line_col(offset);
+        Some(SourceLocation {
+            file_id,
+            offset,
+            line,
+            column,
+        })
+    }
+
+    /// Attributes assembled-text tokens to the pieces they start in.
+    pub fn map_tokens(&self, tokens: Vec<Token>) -> Vec<MappedToken> {
+        tokens
+            .into_iter()
+            .map(|token| {
+                let file_id = self.file_id_at(token.start()).unwrap_or(0);
+                let (start, crosses_boundary) = match self.pieces.get(file_id as usize) {
+                    Some(piece) => (
+                        token.start() - piece.start + piece.bom,
+                        token.end() > piece.start + piece.assembled_len(),
+                    ),
+                    None => (token.start(), false),
+                };
+                MappedToken {
+                    token,
+                    file_id,
+                    span: Span::from_usize(start, token.len()),
+                    crosses_boundary,
+                }
+            })
+            .collect()
+    }
+
+    fn file_id_at(&self, offset: usize) -> Option<u32> {
+        let idx = self
+            .pieces
+            .partition_point(|piece| piece.start <= offset)
+            .checked_sub(1)?;
+        Some(idx as u32)
+    }
+}
+
+/// Lexes the assembled text of `map` and attributes each token to its piece.
+pub async fn lex_mapped(lexer: &GpuLexer, map: &SourceMap) -> Result<Vec<MappedToken>> {
+    Ok(map.map_tokens(lexer.lex(map.text()).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::{tables::tokens::TokenKind, test_cpu::lex_on_test_cpu};
+
+    fn assembled() -> SourceMap {
+        SourceMap::new([
+            ("prelude.lani", "fn id(x: i32) -> i32 { return x; }"),
+            ("empty.lani", ""),
+            ("main.lani", "let a = 1;\nlet b = id(a);\n"),
+            ("tail.lani", "b"),
+        ])
+    }
+
+    fn oracle_tokens(text: &str) -> Vec<Token> {
+        lex_on_test_cpu(text).expect("test CPU oracle")
+    }
+
+    #[test]
+    fn separators_are_injected_only_where_needed() {
+        let map = assembled();
+        assert_eq!(
+            map.text(),
+            "fn id(x: i32) -> i32 { return x; }\n\nlet a = 1;\nlet b = id(a);\nb"
+        );
+        assert_eq!(map.piece_start(0), Some(0));
+        assert_eq!(map.piece_start(1), Some(35));
+        assert_eq!(map.piece_start(2), Some(36));
+        assert_eq!(map.piece_start(3), Some(62));
+        assert_eq!(map.name(2), Some("main.lani"));
+        assert_eq!(map.name(4), None);
+    }
+
+    #[test]
+    fn resolve_maps_offsets_back_to_piece_lines() {
+        let map = assembled();
+        let b_in_main = map.text().find("let b").unwrap();
+        assert_eq!(
+            map.resolve(b_in_main),
+            Some(SourceLocation {
+                file_id: 2,
+                offset: 11,
+                line: 2,
+                column: 1,
+            })
+        );
+        // The separator after the prelude belongs to the end of the prelude.
+        assert_eq!(
+            map.resolve(34),
+            Some(SourceLocation {
+                file_id: 0,
+                offset: 34,
+                line: 1,
+                column: 35,
+            })
+        );
+        assert_eq!(map.resolve(35).map(|l| (l.file_id, l.offset)), Some((1, 0)));
+        assert_eq!(
+            map.resolve(map.text().len()).map(|l| (l.file_id, l.offset)),
+            Some((3, 1))
+        );
+        assert_eq!(map.resolve(map.text().len() + 1), None);
+        assert_eq!(SourceMap::new::<&str, &str>([]).resolve(0), None);
+    }
+
+    #[test]
+    fn mapped_tokens_start_in_their_own_piece() {
+        let map = assembled();
+        let mapped = map.map_tokens(oracle_tokens(map.text()));
+
+        let idents: Vec<_> = mapped
+            .iter()
+            .filter(|t| t.token.kind == TokenKind::Ident)
+            .map(|t| (t.file_id, t.span.start))
+            .collect();
+        assert_eq!(
+            idents,
+            [
+                (0, 3),
+                (0, 6),
+                (0, 9),
+                (0, 17),
+                (0, 30),
+                (2, 4),
+                (2, 15),
+                (2, 19),
+                (2, 22),
+                (3, 0)
+            ]
+        );
+        assert!(mapped.iter().all(|t| !t.crosses_boundary));
+        for t in &mapped {
+            let text = &map.text()[t.token.span.range()];
+            let piece_start = map.piece_start(t.file_id).unwrap();
+            assert_eq!(
+                t.token.start(),
+                piece_start + t.span.start as usize,
+                "{text}"
+            );
+            assert_eq!(t.token.len(), t.span.len() as usize, "{text}");
+        }
+    }
+
+    #[test]
+    fn injected_newline_keeps_tokens_from_fusing() {
+        let map = SourceMap::new([("a", "abc"), ("b", "def"), ("c", "1")]);
+        let mapped = map.map_tokens(oracle_tokens(map.text()));
+        let spans: Vec<_> = mapped
+            .iter()
+            .map(|t| (t.file_id, t.span.start, t.span.len()))
+            .collect();
+        assert_eq!(spans, [(0, 0, 3), (1, 0, 3), (2, 0, 1)]);
+    }
+
+    #[test]
+    fn token_running_past_its_piece_is_flagged() {
+        let map = SourceMap::new([("a", "x /* open"), ("b", "close */ y"), ("c", "z")]);
+        let comment = Token::new(TokenKind::BlockComment, 2, "/* open\nclose */".len());
+        let mapped = map.map_tokens(vec![comment]);
+        assert_eq!(mapped[0].file_id, 0);
+        assert_eq!(
+            mapped[0].span,
+            Span::new(2, 2 + "/* open\nclose */".len() as u32)
+        );
+        assert!(mapped[0].crosses_boundary);
+    }
+
+    #[test]
+    fn line_map_clamps_and_counts_trailing_lines() {
+        let lines = LineMap::new("ab\ncd\n");
+        assert_eq!(lines.line_count(), 3);
+        assert_eq!(lines.line_col(0), (1, 1));
+        assert_eq!(lines.line_col(2), (1, 3));
+        assert_eq!(lines.line_col(4), (2, 2));
+        assert_eq!(lines.line_col(6), (3, 1));
+        assert_eq!(lines.line_col(100), (3, 1));
+    }
+
+    #[test]
+    fn line_map_skips_a_leading_bom() {
+        let lines = LineMap::new("\u{feff}ab\ncd");
+        assert_eq!(lines.line_col(0), (1, 1));
+        assert_eq!(lines.line_col(3), (1, 1));
+        assert_eq!(lines.line_col(4), (1, 2));
+        assert_eq!(lines.line_col(6), (2, 1));
+        assert_eq!(LineMap::new("\u{feff}").line_col(3), (1, 1));
+    }
+
+    #[test]
+    fn pieces_drop_their_bom_but_keep_file_offsets() {
+        let map = SourceMap::new([
+            ("a", "\u{feff}x = 1;"),
+            ("b", "\u{feff}"),
+            ("c", "\u{feff}y"),
+        ]);
+        assert_eq!(map.text(), "x = 1;\n\ny");
+        assert_eq!(map.piece_start(2), Some(8));
+
+        let mapped = map.map_tokens(vec![
+            Token::new(TokenKind::Ident, 0, 1),
+            Token::new(TokenKind::Ident, 8, 1),
+        ]);
+        assert_eq!(
+            mapped
+                .iter()
+                .map(|t| (t.file_id, t.span.start, t.crosses_boundary))
+                .collect::<Vec<_>>(),
+            [(0, 3, false), (2, 3, false)]
+        );
+        assert_eq!(
+            map.resolve(0),
+            Some(SourceLocation {
+                file_id: 0,
+                offset: 3,
+                line: 1,
+                column: 1,
+            })
+        );
+    }
+}
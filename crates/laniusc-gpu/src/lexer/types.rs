@@ -0,0 +1,269 @@
+//! GPU-side lexer types: uniforms, device token records, and driver settings.
+
+use encase::ShaderType;
+pub use laniusc_core::lexer::types::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    gpu::decode::{GpuDecodable, le_u32_at},
+    lexer::kind_packing::KindPacking,
+};
+
+#[repr(C)]
+#[derive(Clone, Copy, ShaderType)]
+/// Uniform parameters shared by lexer GPU passes.
+pub struct LexParams {
+    /// Current source byte length.
+    pub n: u32,
+    /// Number of DFA states in the loaded table.
+    pub m: u32,
+    /// Initial DFA state for the stream.
+    pub start_state: u32,
+    /// First token kind excluded from final kept-token output.
+    pub skip0: u32,
+    /// Second token kind excluded from final kept-token output.
+    pub skip1: u32,
+    /// Third token kind excluded from final kept-token output.
+    pub skip2: u32,
+    /// Fourth token kind excluded from final kept-token output.
+    pub skip3: u32,
+    /// Nonzero when `tokens_build` should record each kept token's accept state.
+    pub capture_accept_states: u32,
+    /// Longest token, in bytes, `tokens_build` accepts before clamping and
+    /// flagging it in `token_len_status`.
+    pub max_token_len: u32,
+    /// Nonzero when `escape_spans` should collect string and char escapes.
+    pub capture_string_escapes: u32,
+    /// `SUSPECT_*` classes `tokens_build` lists in `suspect_tokens`; zero
+    /// outside paranoia mode.
+    pub suspect_classes: u32,
+    /// [`SplitBinding::shift`](crate::gpu::buffers::SplitBinding::shift) of
+    /// `tokens_out` when `tokens_build_split` binds it as two windows; zero
+    /// otherwise.
+    pub tokens_split_shift: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// Settings a [`GpuLexer`](crate::lexer::GpuLexer) keeps for its whole
+/// lifetime, fixed at construction.
+///
+/// The lexer never consults the environment; binaries that honour the
+/// `LANIUS_*` variables translate them with [`LexerRuntimeOptions::from_env`].
+pub struct LexerRuntimeOptions {
+    /// Wrap submissions and passes in wgpu validation scopes, and record
+    /// every pass on its own.
+    pub validation_scopes: bool,
+    /// Wrap each pass in an encoder debug group. On by default in debug
+    /// builds.
+    pub debug_groups: bool,
+    /// Let compatible passes share one `wgpu::ComputePass`.
+    pub batch_compute_passes: bool,
+    /// What [`GpuLexer::lex`](crate::lexer::GpuLexer::lex) and
+    /// [`GpuLexer::lex_cancellable`](crate::lexer::GpuLexer::lex_cancellable)
+    /// read back; `lex_with_options` takes it from [`LexOptions`].
+    pub readback: ReadbackMode,
+    /// Time every pass of a lex on the GPU and log the timings.
+    pub gpu_timing: bool,
+    /// With `gpu_timing`, also stamp each round of the block scans instead
+    /// of once per scan.
+    pub gpu_timing_per_round: bool,
+    /// Time the GPU work of the resident compile paths and log the timings.
+    pub compile_timing: bool,
+    /// Shortest compile timing span, in milliseconds, that is logged.
+    pub compile_timing_min_ms: f64,
+    /// Log host-side phase timings of the resident compile paths.
+    pub host_timing: bool,
+    /// Check kept tokens against UTF-8 boundaries in release builds too.
+    pub check_token_boundaries: bool,
+    /// Read every DFA table back after upload and compare it with the host copy.
+    pub verify_tables: bool,
+    /// Initial value of
+    /// [`GpuLexer::set_capture_dispatch_metadata`](crate::lexer::GpuLexer::set_capture_dispatch_metadata).
+    pub capture_dispatch_metadata: bool,
+    /// Keep `tok_types` in 16-bit kind lanes even when every token kind fits
+    /// the 8-bit ones; see
+    /// [`KindPacking`].
+    pub wide_token_kinds: bool,
+}
+
+impl Default for LexerRuntimeOptions {
+    fn default() -> Self {
+        Self {
+            validation_scopes: false,
+            debug_groups: cfg!(debug_assertions),
+            batch_compute_passes: true,
+            readback: ReadbackMode::Full,
+            gpu_timing: false,
+            gpu_timing_per_round: false,
+            compile_timing: false,
+            compile_timing_min_ms: crate::gpu::timer::MINIMUM_TIME_TO_NOT_ELIDE_MS,
+            host_timing: false,
+            check_token_boundaries: false,
+            verify_tables: false,
+            capture_dispatch_metadata: false,
+            wide_token_kinds: false,
+        }
+    }
+}
+
+impl LexerRuntimeOptions {
+    /// The options the `LANIUS_*` environment variables used to select:
+    /// `LANIUS_VALIDATION_SCOPES`, `LANIUS_DEBUG_GROUPS`,
+    /// `LANIUS_BATCH_COMPUTE_PASSES`, `LANIUS_READBACK`/`PERF_ONE_READBACK`,
+    /// `LANIUS_GPU_TIMING`, `LANIUS_GPU_TIMING_PER_ROUND`,
+    /// `LANIUS_GPU_COMPILE_TIMING`, `LANIUS_GPU_COMPILE_TIMING_MIN_MS`,
+    /// `LANIUS_GPU_COMPILE_HOST_TIMING`, `LANIUS_CHECK_TOKEN_BOUNDARIES`,
+    /// `LANIUS_VERIFY_LEXER_TABLES`,
+    /// `LANIUS_CAPTURE_DISPATCH_METADATA` and `LANIUS_WIDE_TOKEN_KINDS`.
+    pub fn from_env() -> Self {
+        use crate::gpu::{env::env_bool_truthy, passes_core};
+        let defaults = Self::default();
+        Self {
+            validation_scopes: passes_core::validation_scopes_enabled(),
+            debug_groups: passes_core::debug_groups_enabled(),
+            batch_compute_passes: passes_core::compute_pass_batching_enabled(),
+            readback: ReadbackMode::from_env(),
+            gpu_timing: env_bool_truthy("LANIUS_GPU_TIMING", false),
+            gpu_timing_per_round: env_bool_truthy("LANIUS_GPU_TIMING_PER_ROUND", false),
+            compile_timing: env_bool_truthy("LANIUS_GPU_COMPILE_TIMING", false),
+            compile_timing_min_ms: crate::gpu::env::env_f64(
+                "LANIUS_GPU_COMPILE_TIMING_MIN_MS",
+                defaults.compile_timing_min_ms,
+            ),
+            host_timing: env_bool_truthy("LANIUS_GPU_COMPILE_HOST_TIMING", false),
+            check_token_boundaries: env_bool_truthy("LANIUS_CHECK_TOKEN_BOUNDARIES", false),
+            verify_tables: env_bool_truthy("LANIUS_VERIFY_LEXER_TABLES", false),
+            capture_dispatch_metadata: env_bool_truthy("LANIUS_CAPTURE_DISPATCH_METADATA", false),
+            wide_token_kinds: env_bool_truthy("LANIUS_WIDE_TOKEN_KINDS", false),
+        }
+    }
+
+    /// The `tok_types` packing these options select: the narrowest one the
+    /// language's token kinds fit, unless [`wide_token_kinds`](Self::wide_token_kinds)
+    /// forces 16-bit lanes.
+    pub fn kind_packing(&self) -> KindPacking {
+        if self.wide_token_kinds {
+            KindPacking::Wide
+        } else {
+            KindPacking::for_language(crate::language::definition())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// How a [`GpuLexer`](crate::lexer::GpuLexer) hands its passes to the queue.
+pub enum SubmissionPolicy {
+    /// Record every pass into one command buffer and submit it at once.
+    #[default]
+    Immediate,
+    /// Record the passes into at most `max_encoders` command buffers and
+    /// submit them together in one `queue.submit`.
+    Batched {
+        /// Upper bound on command buffers per lex; `0` is treated as `1`.
+        max_encoders: usize,
+    },
+    /// Submit `chunk_passes` passes at a time and wait for each chunk to
+    /// finish before recording the next, so other queue users can interleave.
+    Yielding {
+        /// Passes per submission; `0` is treated as `1`.
+        chunk_passes: usize,
+    },
+}
+
+impl SubmissionPolicy {
+    /// Passes recorded into each command buffer out of `total`.
+    pub fn passes_per_chunk(self, total: usize) -> usize {
+        match self {
+            Self::Immediate => total.max(1),
+            Self::Batched { max_encoders } => total.div_ceil(max_encoders.max(1)).max(1),
+            Self::Yielding { chunk_passes } => chunk_passes.max(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// Which pass sequence a [`GpuLexer`](crate::lexer::GpuLexer) records.
+///
+/// All three produce identical token streams. `LateKeptFilter` and
+/// `FusedPairSeed` stay opt-in until `fuzz_lex_bytes` has run the whole
+/// corpus against them on real devices and `perf_matrix` has recorded their
+/// timing against `Split`; see `fuzz/README.md`.
+pub enum LexPipeline {
+    /// `dfa_03` writes the boundary and keep flags, and the pair scans rank
+    /// the all and kept boundaries together (`pair_01..03_with_keep`) so the
+    /// kept stream is compacted straight from the flags.
+    #[default]
+    Split,
+    /// The pair scans rank only the all boundaries. The kept stream is
+    /// filtered from the compacted all-boundary stream afterwards by a
+    /// second rank scan over its kinds (`keep_01..03`) and `compact_kept`.
+    LateKeptFilter,
+    /// [`LateKeptFilter`](Self::LateKeptFilter) with one pass applying the
+    /// DFA block prefixes and counting each block's boundaries from the flags
+    /// it just computed, skipping `pair_01`'s full read of `flags_packed`.
+    FusedPairSeed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+/// How far into the lexer pass sequence a recording runs; each stage runs
+/// every pass of the ones before it.
+pub enum PipelineStage {
+    /// Through the boundary rank scan (`pair_03`): boundary flags, their
+    /// kinds in `tok_types`, and inclusive ALL ranks in `s_all_final`, with
+    /// nothing compacted.
+    Scan,
+    /// Through both compactions: the compacted ALL and kept boundary
+    /// streams, without token records.
+    Compact,
+    /// The full sequence, ending in `tokens_build`.
+    Build,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Queue usage of the last [`GpuLexer::lex_with_options`](crate::lexer::GpuLexer::lex_with_options) call.
+pub struct LexSubmissionStats {
+    /// `queue.submit` calls, including readback submissions.
+    pub submissions: u64,
+    /// Host time from entering the call to returning, waits included.
+    pub wall_time: std::time::Duration,
+}
+
+impl std::fmt::Display for LexSubmissionStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "wall={:.3}ms submissions={}",
+            self.wall_time.as_secs_f64() * 1e3,
+            self.submissions
+        )
+    }
+}
+
+#[derive(Clone, Copy, ShaderType, Default)]
+/// GPU token record written by `tokens_build`.
+///
+/// This is the element layout of the resident token buffers handed to the
+/// parser; host callers read [`Token`] instead.
+pub struct GpuToken {
+    /// Numeric `TokenKind` discriminant.
+    pub kind: u32,
+    /// Start byte offset in the concatenated source input.
+    pub start: u32,
+    /// Token byte length.
+    pub len: u32,
+}
+
+impl GpuDecodable for GpuToken {
+    const SIZE: usize = 12;
+
+    fn decode(bytes: &[u8]) -> Self {
+        Self {
+            kind: le_u32_at(bytes, 0),
+            start: le_u32_at(bytes, 1),
+            len: le_u32_at(bytes, 2),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! GPU-resident compiler for the Lanius language.
+//!
+//! The crate is organized around the same phase boundaries used by the runtime
+//! compiler: source loading, lexing, parsing/HIR construction, resident GPU type
+//! checking, backend lowering, and source-pack planning/execution. The
+//! maintainer guide for those boundaries lives in `docs/compiler/`.
+
+/// Build provenance reported by `--version` and bug reports.
+mod build_info;
+
+/// C ABI over the GPU lexer for non-Rust hosts.
+#[cfg(feature = "capi")]
+pub mod capi;
+
+/// Command-line entry points, argument validation, and user-facing command
+/// output.
+pub mod cli;
+
+/// Backend lowering and source-pack unit planning.
+pub mod codegen;
+
+/// Public compile/check APIs, diagnostics, source-pack manifests, and
+/// cross-phase orchestration.
+pub mod compiler;
+
+/// Development helpers and generated-workload support.
+#[cfg(any(test, feature = "dev"))]
+pub use laniusc_core::dev;
+
+/// Source formatter support.
+pub mod formatter;
+
+/// Shared GPU device, buffer, pass, readback, scan, timing, and tracing
+/// utilities.
+pub mod gpu;
+
+/// The language revision, token kinds, keywords, and lexer features this
+/// build implements.
+pub use laniusc_core::language;
+
+/// GPU lexer driver, buffers, token records, and lexer table integration.
+pub mod lexer;
+
+/// Log targets used by library code and the default stderr logger.
+pub use laniusc_core::logging;
+
+/// GPU parser driver, LL tables, HIR records, and parser readback helpers.
+pub mod parser;
+
+/// Curated re-exports for `use laniusc_compiler::prelude::*`.
+pub mod prelude;
+
+/// Slang reflection parsing and bind-layout interpretation.
+pub mod reflection;
+
+/// Generated shader artifact catalog used by runtime pass construction.
+#[allow(dead_code)]
+pub(crate) mod shader_artifacts;
+
+/// Startup self test over embedded lexer and parser vectors.
+pub mod self_test;
+
+/// Half-open byte spans shared by tokens, escapes, lints, and queries.
+pub use laniusc_core::span;
+/// Binary container format shared by the generated lexer and parser tables.
+pub use laniusc_core::tables;
+
+/// Resident GPU type checking and retained semantic metadata for codegen.
+pub mod type_checker;
+
+pub use build_info::{BuildInfo, build_info};
+pub use prelude::*;
+pub use self_test::self_test;
@@ -0,0 +1,348 @@
+//! Host-side syntax trees rebuilt from a preorder production stream.
+//!
+//! [`Ast::from_productions`] replays a production stream, such as
+//! `ParseResult::emit_stream`, against the tables' RHS data: every production
+//! node gets one child per RHS symbol, and every terminal child records its
+//! index in the unframed token stream.
+//!
+//! Grammars that declare `%binop` operators parse operator chains through
+//! generated precedence-ladder nonterminals, one per precedence level.
+//! [`Ast::collapse_precedence_chains`] replaces each ladder subtree with
+//! [`AstNode::Binary`] nodes, associated by the operator precedences recorded
+//! in the tables, so consumers never see the ladder levels.
+//!
+//! [`Ast::from_productions`]: crate::parser::ast::Ast::from_productions
+//! [`Ast::collapse_precedence_chains`]: crate::parser::ast::Ast::collapse_precedence_chains
+//! [`AstNode::Binary`]: crate::parser::ast::AstNode::Binary
+
+use super::tables::{OperatorPrecedence, PrecomputedParseTables};
+use crate::lexer::tables::tokens::TokenKind;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// One node of an [`Ast`].
+pub enum AstNode {
+    /// Production `prod` with one child per RHS symbol.
+    Production { prod: u32, children: Vec<AstNode> },
+    /// Terminal with its lexer kind and index in the unframed token stream.
+    Token { kind: u32, index: u32 },
+    /// Binary operator application left by collapsing a precedence ladder.
+    Binary {
+        op_kind: u32,
+        op_index: u32,
+        lhs: Box<AstNode>,
+        rhs: Box<AstNode>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Production stream rejected by [`Ast::from_productions`].
+pub enum AstError {
+    /// The tables carry no LL(1) RHS data to replay the stream against.
+    NoRhsData,
+    /// Entry `index` of the stream is not a production id.
+    BadProduction { index: usize, prod: u32 },
+    /// Entry `index` expands a different nonterminal than the tree needs next.
+    UnexpectedProduction {
+        index: usize,
+        prod: u32,
+        expected_nonterminal: u32,
+    },
+    /// The stream ended while `nonterminal` was still unexpanded.
+    Truncated { nonterminal: u32 },
+    /// The tree was complete before entry `index`.
+    Trailing { index: usize },
+    /// The stream is empty but the grammar's start symbol is not nullable.
+    EmptyInputNotAllowed,
+}
+
+impl std::fmt::Display for AstError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoRhsData => write!(f, "parse tables carry no production RHS data"),
+            Self::BadProduction { index, prod } => {
+                write!(
+                    f,
+                    "production stream entry {index} = {prod} is not a production id"
+                )
+            }
+            Self::UnexpectedProduction {
+                index,
+                prod,
+                expected_nonterminal,
+            } => write!(
+                f,
+                "production stream entry {index} = {prod} does not expand nonterminal \
+                 {expected_nonterminal}"
+            ),
+            Self::Truncated { nonterminal } => write!(
+                f,
+                "production stream ended before nonterminal {nonterminal} was expanded"
+            ),
+            Self::Trailing { index } => {
+                write!(
+                    f,
+                    "production stream continues past the tree at entry {index}"
+                )
+            }
+            Self::EmptyInputNotAllowed => {
+                write!(f, "the grammar does not accept an empty program")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AstError {}
+
+/// Syntax tree of one parse, tied to the tables that produced it.
+#[derive(Debug, Clone)]
+pub struct Ast<'t> {
+    tables: &'t PrecomputedParseTables,
+    pub root: AstNode,
+}
+
+struct Frame<'a> {
+    prod: u32,
+    rhs: &'a [u32],
+    next: usize,
+    children: Vec<AstNode>,
+}
+
+impl<'t> Ast<'t> {
+    /// Tree of an input with no tokens besides skipped whitespace and
+    /// comments, when the tables allow one.
+    pub fn empty_program(tables: &'t PrecomputedParseTables) -> Result<Self, AstError> {
+        if tables.n_nonterminals == 0 {
+            return Err(AstError::NoRhsData);
+        }
+        let productions = tables
+            .empty_input_productions()
+            .ok_or(AstError::EmptyInputNotAllowed)?;
+        Self::from_productions(tables, &productions)
+    }
+
+    /// Rebuilds the tree of a preorder production stream, which must expand
+    /// exactly the start nonterminal.
+    ///
+    /// An empty stream, which the parser emits for an input of only
+    /// sentinels, yields [`Self::empty_program`].
+    pub fn from_productions(
+        tables: &'t PrecomputedParseTables,
+        productions: &[u32],
+    ) -> Result<Self, AstError> {
+        if tables.n_nonterminals == 0 {
+            return Err(AstError::NoRhsData);
+        }
+        if productions.is_empty() {
+            return Self::empty_program(tables);
+        }
+        let mut stream = productions.iter().copied().enumerate();
+        let mut expand = |nt: u32| -> Result<Frame<'t>, AstError> {
+            let (index, prod) = stream
+                .next()
+                .ok_or(AstError::Truncated { nonterminal: nt })?;
+            if prod >= tables.n_productions {
+                return Err(AstError::BadProduction { index, prod });
+            }
+            if tables
+                .prod_lhs
+                .get(prod as usize)
+                .is_some_and(|&lhs| lhs != nt)
+            {
+                return Err(AstError::UnexpectedProduction {
+                    index,
+                    prod,
+                    expected_nonterminal: nt,
+                });
+            }
+            let off = tables.prod_rhs_off[prod as usize] as usize;
+            let len = tables.prod_rhs_len[prod as usize] as usize;
+            Ok(Frame {
+                prod,
+                rhs: &tables.prod_rhs[off..off + len],
+                next: 0,
+                children: Vec::with_capacity(len),
+            })
+        };
+
+        // Explicit stack: operator tails and list productions nest once per
+        // element, which would overflow a recursive builder on long inputs.
+        let mut frames = vec![expand(tables.start_nonterminal)?];
+        let mut token_index = 0u32;
+        let root = loop {
+            let top = frames.last_mut().expect("frame stack is never empty here");
+            let Some(&symbol) = top.rhs.get(top.next) else {
+                let done = frames.pop().expect("frame stack is never empty here");
+                let node = AstNode::Production {
+                    prod: done.prod,
+                    children: done.children,
+                };
+                match frames.last_mut() {
+                    Some(parent) => parent.children.push(node),
+                    None => break node,
+                }
+                continue;
+            };
+            top.next += 1;
+            if symbol < tables.n_kinds {
+                top.children.push(AstNode::Token {
+                    kind: tables.lexer_kind(symbol),
+                    index: token_index,
+                });
+                token_index += 1;
+            } else {
+                let frame = expand(symbol - tables.n_kinds)?;
+                frames.push(frame);
+            }
+        };
+        if let Some((index, _)) = stream.next() {
+            return Err(AstError::Trailing { index });
+        }
+        Ok(Self { tables, root })
+    }
+
+    /// Replaces every precedence-ladder subtree with [`AstNode::Binary`]
+    /// nodes over its operands, associated by the tables' operator
+    /// precedences.
+    ///
+    /// A no-op for tables without a ladder or without production left-hand
+    /// sides.
+    pub fn collapse_precedence_chains(&mut self) {
+        if self.tables.ladder_nonterminals.is_empty() || self.tables.prod_lhs.is_empty() {
+            return;
+        }
+        let root = std::mem::replace(&mut self.root, placeholder());
+        self.root = self.collapse(root);
+    }
+
+    /// Renders the tree as an s-expression: `(tag child ...)` for
+    /// productions, `Kind@index` for tokens, and `(Op lhs rhs)` for binary
+    /// nodes.
+    pub fn to_sexpr(&self) -> String {
+        let mut out = String::new();
+        self.write_sexpr(&self.root, &mut out);
+        out
+    }
+
+    fn write_sexpr(&self, node: &AstNode, out: &mut String) {
+        match node {
+            AstNode::Production { prod, children } => {
+                out.push('(');
+                out.push_str(&self.tables.production_name(*prod));
+                for child in children {
+                    out.push(' ');
+                    self.write_sexpr(child, out);
+                }
+                out.push(')');
+            }
+            AstNode::Token { kind, index } => {
+                out.push_str(&format!("{}@{index}", kind_name(*kind)));
+            }
+            AstNode::Binary {
+                op_kind, lhs, rhs, ..
+            } => {
+                out.push_str(&format!("({} ", kind_name(*op_kind)));
+                self.write_sexpr(lhs, out);
+                out.push(' ');
+                self.write_sexpr(rhs, out);
+                out.push(')');
+            }
+        }
+    }
+
+    fn is_ladder(&self, node: &AstNode) -> bool {
+        let AstNode::Production { prod, .. } = node else {
+            return false;
+        };
+        self.tables
+            .prod_lhs
+            .get(*prod as usize)
+            .is_some_and(|lhs| self.tables.ladder_nonterminals.contains(lhs))
+    }
+
+    fn collapse(&self, node: AstNode) -> AstNode {
+        if self.is_ladder(&node) {
+            return self.collapse_ladder(node);
+        }
+        match node {
+            AstNode::Production { prod, children } => AstNode::Production {
+                prod,
+                children: children
+                    .into_iter()
+                    .map(|child| self.collapse(child))
+                    .collect(),
+            },
+            other => other,
+        }
+    }
+
+    /// Flattens a ladder into `operand (op operand)*` and rebuilds it with
+    /// operator-precedence parsing.
+    fn collapse_ladder(&self, ladder: AstNode) -> AstNode {
+        let mut operands = Vec::new();
+        let mut operators: Vec<(OperatorPrecedence, u32)> = Vec::new();
+        let mut pending = vec![ladder];
+        while let Some(node) = pending.pop() {
+            if self.is_ladder(&node) {
+                let AstNode::Production { children, .. } = node else {
+                    unreachable!("ladder nodes are productions");
+                };
+                pending.extend(children.into_iter().rev());
+                continue;
+            }
+            let operator = match &node {
+                AstNode::Token { kind, index } => self
+                    .tables
+                    .operator_precedence(*kind)
+                    .map(|op| (op, *index)),
+                _ => None,
+            };
+            match operator {
+                Some((op, index)) => {
+                    while let Some(&(top, _)) = operators.last()
+                        && binds_before(top, op)
+                    {
+                        reduce(&mut operands, &mut operators);
+                    }
+                    operators.push((op, index));
+                }
+                None => operands.push(self.collapse(node)),
+            }
+        }
+        while !operators.is_empty() {
+            reduce(&mut operands, &mut operators);
+        }
+        debug_assert_eq!(operands.len(), 1, "ladder operands and operators alternate");
+        operands.pop().unwrap_or_else(placeholder)
+    }
+}
+
+/// Whether `top`, already on the operator stack, applies before `next`.
+fn binds_before(top: OperatorPrecedence, next: OperatorPrecedence) -> bool {
+    top.precedence > next.precedence || (top.precedence == next.precedence && !next.right_assoc)
+}
+
+fn reduce(operands: &mut Vec<AstNode>, operators: &mut Vec<(OperatorPrecedence, u32)>) {
+    let (op, op_index) = operators.pop().expect("reduce needs an operator");
+    let rhs = operands.pop().expect("binary operator has a right operand");
+    let lhs = operands.pop().expect("binary operator has a left operand");
+    operands.push(AstNode::Binary {
+        op_kind: op.kind,
+        op_index,
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+    });
+}
+
+fn placeholder() -> AstNode {
+    AstNode::Production {
+        prod: 0,
+        children: Vec::new(),
+    }
+}
+
+fn kind_name(kind: u32) -> String {
+    match TokenKind::from_u32(kind) {
+        Some(kind) => format!("{kind:?}"),
+        None => format!("kind{kind}"),
+    }
+}
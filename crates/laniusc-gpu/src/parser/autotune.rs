@@ -0,0 +1,252 @@
+//! Chunk sizes for [`GpuParser::parse_chunked_sized`](crate::parser::GpuParser::parse_chunked_sized).
+//!
+//! Each timed pass of an earlier parse is one sample of GPU nanoseconds per
+//! element of the count it scales with ([`PassElements`]). The model keeps
+//! the element-weighted median of those samples per pass, so short parses
+//! whose times are mostly launch noise count for little, and converts every
+//! pass to nanoseconds per token pair with the stack-change and emit counts
+//! seen per pair. A chunk is then as many tokens as fit the target latency
+//! with some headroom for the fixed submission cost the model leaves out.
+
+use std::collections::BTreeMap;
+
+use crate::parser::{ParseResult, ParseTimings, PassElements};
+
+/// Suggestion when no earlier parse was timed.
+pub const DEFAULT_CHUNK_TOKENS: u32 = 1 << 16;
+/// Smallest suggestion; below it the per-submission cost dominates.
+pub const MIN_CHUNK_TOKENS: u32 = 1 << 10;
+/// Largest suggestion.
+pub const MAX_CHUNK_TOKENS: u32 = 1 << 22;
+/// Share of the target latency the modelled pass time may fill.
+const HEADROOM: f64 = 0.75;
+
+/// How [`GpuParser::parse_chunked_sized`](crate::parser::GpuParser::parse_chunked_sized)
+/// cuts its input.
+#[derive(Clone, Copy)]
+pub enum ChunkSize<'h> {
+    /// A fixed number of token kinds per chunk.
+    Tokens(u32),
+    /// [`suggest_chunk_size`] over `history`.
+    Auto {
+        history: &'h [ParseResult],
+        target_ms: f64,
+    },
+}
+
+impl ChunkSize<'_> {
+    /// Token kinds per chunk.
+    pub fn tokens(self) -> u32 {
+        match self {
+            Self::Tokens(tokens) => tokens,
+            Self::Auto { history, target_ms } => suggest_chunk_size(history, target_ms),
+        }
+    }
+}
+
+/// Tokens per chunk that keep one chunk's GPU passes under `target_ms`,
+/// fitted to the [`ParseResult::timings`] in `history`.
+///
+/// Returns [`DEFAULT_CHUNK_TOKENS`] when no result in `history` was timed,
+/// and otherwise a size within [`MIN_CHUNK_TOKENS`]`..=`[`MAX_CHUNK_TOKENS`].
+pub fn suggest_chunk_size(history: &[ParseResult], target_ms: f64) -> u32 {
+    let Some(ns_per_pair) = ns_per_pair(history.iter().filter_map(|r| r.timings.as_ref())) else {
+        return DEFAULT_CHUNK_TOKENS;
+    };
+    if target_ms.is_nan() || target_ms <= 0.0 {
+        return MIN_CHUNK_TOKENS;
+    }
+    if ns_per_pair <= 0.0 {
+        return MAX_CHUNK_TOKENS;
+    }
+    let pairs = target_ms * 1.0e6 * HEADROOM / ns_per_pair;
+    (pairs.min(f64::from(MAX_CHUNK_TOKENS)) as u32).clamp(MIN_CHUNK_TOKENS, MAX_CHUNK_TOKENS)
+}
+
+/// Modelled GPU nanoseconds per token pair, or `None` without a timed
+/// parse of at least one pair.
+fn ns_per_pair<'t>(timings: impl Iterator<Item = &'t ParseTimings>) -> Option<f64> {
+    // Per pass: what it scales with and its (ns per element, elements) samples.
+    let mut samples = BTreeMap::<&str, (PassElements, Vec<(f64, f64)>)>::new();
+    let (mut pairs, mut sc, mut emit) = (0f64, 0f64, 0f64);
+    for timing in timings.filter(|timing| timing.work.n_pairs > 0) {
+        pairs += f64::from(timing.work.n_pairs);
+        sc += f64::from(timing.work.total_sc);
+        emit += f64::from(timing.work.total_emit);
+        for pass in timing.passes.iter().filter(|pass| pass.elements > 0) {
+            let elements = f64::from(pass.elements);
+            samples
+                .entry(pass.label.as_str())
+                .or_insert_with(|| (pass.scales_with, Vec::new()))
+                .1
+                .push((pass.gpu_ns / elements, elements));
+        }
+    }
+    if pairs == 0.0 {
+        return None;
+    }
+    let per_pair = |elements| match elements {
+        PassElements::Pairs => 1.0,
+        PassElements::StackChanges => sc / pairs,
+        PassElements::Emits => emit / pairs,
+    };
+    Some(
+        samples
+            .into_values()
+            .map(|(elements, mut samples)| weighted_median(&mut samples) * per_pair(elements))
+            .sum(),
+    )
+}
+
+/// Median of `(value, weight)` samples, each counted `weight` times.
+fn weighted_median(samples: &mut [(f64, f64)]) -> f64 {
+    samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let half = samples.iter().map(|&(_, weight)| weight).sum::<f64>() / 2.0;
+    let mut seen = 0.0;
+    for &(value, weight) in samples.iter() {
+        seen += weight;
+        if seen >= half {
+            return value;
+        }
+    }
+    samples.last().map_or(0.0, |&(value, _)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{ParseWork, PassTiming};
+
+    /// True per-element costs of the synthetic passes.
+    const PASSES: [(&str, PassElements, f64); 4] = [
+        ("llp_pairs", PassElements::Pairs, 2.0),
+        ("pack_varlen", PassElements::Pairs, 1.0),
+        ("brackets_01_scan_inblock", PassElements::StackChanges, 4.0),
+        ("tree_parent", PassElements::Emits, 1.5),
+    ];
+
+    /// A timed parse of `n_pairs` pairs with half a stack change and two
+    /// emits per pair, each pass's time scaled by the next `noise` factor.
+    fn timed(n_pairs: u32, noise: &mut impl FnMut() -> f64) -> ParseResult {
+        let work = ParseWork {
+            n_pairs,
+            total_sc: n_pairs / 2,
+            total_emit: n_pairs * 2,
+        };
+        let passes = PASSES
+            .iter()
+            .map(|&(label, scales_with, ns)| {
+                let elements = work.count(scales_with);
+                PassTiming {
+                    label: label.to_string(),
+                    gpu_ns: ns * f64::from(elements) * noise(),
+                    scales_with,
+                    elements,
+                }
+            })
+            .collect();
+        ParseResult {
+            timings: Some(ParseTimings {
+                work,
+                passes,
+                ..ParseTimings::default()
+            }),
+            ..ParseResult::empty()
+        }
+    }
+
+    /// Deterministic factors spread uniformly over `lo..hi`.
+    fn uniform(lo: f64, hi: f64) -> impl FnMut() -> f64 {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            lo + (hi - lo) * (state >> 11) as f64 / (1u64 << 53) as f64
+        }
+    }
+
+    /// Noise-free model: 2 + 1 + 4 / 2 + 1.5 * 2 = 8 ns per pair.
+    fn exact(target_ms: f64) -> u32 {
+        (target_ms * 1.0e6 * HEADROOM / 8.0) as u32
+    }
+
+    fn assert_near(suggested: u32, expected: u32, tolerance: f64) {
+        let ratio = f64::from(suggested) / f64::from(expected);
+        assert!(
+            (1.0 - tolerance..=1.0 + tolerance).contains(&ratio),
+            "suggested {suggested}, expected about {expected}"
+        );
+    }
+
+    #[test]
+    fn empty_or_untimed_history_gets_the_default() {
+        assert_eq!(suggest_chunk_size(&[], 4.0), DEFAULT_CHUNK_TOKENS);
+        let untimed = [ParseResult::empty(), ParseResult::empty()];
+        assert_eq!(suggest_chunk_size(&untimed, 4.0), DEFAULT_CHUNK_TOKENS);
+        let empty_parse = [timed(0, &mut || 1.0)];
+        assert_eq!(suggest_chunk_size(&empty_parse, 4.0), DEFAULT_CHUNK_TOKENS);
+        assert_eq!(
+            ChunkSize::Auto {
+                history: &[],
+                target_ms: 4.0
+            }
+            .tokens(),
+            DEFAULT_CHUNK_TOKENS
+        );
+        assert_eq!(ChunkSize::Tokens(77).tokens(), 77);
+    }
+
+    #[test]
+    fn exact_samples_recover_the_cost_model() {
+        let history = [timed(10_000, &mut || 1.0), timed(250_000, &mut || 1.0)];
+        assert_eq!(suggest_chunk_size(&history, 4.0), exact(4.0));
+        assert_eq!(suggest_chunk_size(&history, 16.0), exact(16.0));
+    }
+
+    #[test]
+    fn suggestions_grow_with_the_target_and_stay_in_bounds() {
+        let history = [timed(100_000, &mut || 1.0)];
+        let sizes = [0.5, 2.0, 8.0, 32.0].map(|ms| suggest_chunk_size(&history, ms));
+        assert!(sizes.is_sorted(), "{sizes:?}");
+        assert_eq!(suggest_chunk_size(&history, 1.0e-6), MIN_CHUNK_TOKENS);
+        assert_eq!(suggest_chunk_size(&history, 0.0), MIN_CHUNK_TOKENS);
+        assert_eq!(suggest_chunk_size(&history, f64::NAN), MIN_CHUNK_TOKENS);
+        assert_eq!(suggest_chunk_size(&history, 1.0e6), MAX_CHUNK_TOKENS);
+        let free = [timed(1_000, &mut || 0.0)];
+        assert_eq!(suggest_chunk_size(&free, 4.0), MAX_CHUNK_TOKENS);
+    }
+
+    #[test]
+    fn noisy_samples_stay_near_the_cost_model() {
+        let mut noise = uniform(0.7, 1.3);
+        let history = (1..=40)
+            .map(|i| timed(i * 5_000, &mut noise))
+            .collect::<Vec<_>>();
+        assert_near(suggest_chunk_size(&history, 4.0), exact(4.0), 0.15);
+    }
+
+    #[test]
+    fn small_noisy_parses_do_not_outweigh_large_ones() {
+        // Short parses are dominated by launch latency and scatter widely;
+        // long ones are accurate. Their per-element means would be far off.
+        let mut wild = uniform(0.5, 20.0);
+        let mut steady = uniform(0.95, 1.05);
+        let mut history = (0..30).map(|_| timed(200, &mut wild)).collect::<Vec<_>>();
+        history.extend((1..=5).map(|i| timed(i * 200_000, &mut steady)));
+        assert_near(suggest_chunk_size(&history, 4.0), exact(4.0), 0.1);
+
+        // Plain outliers among similar sizes are ignored too.
+        let mut spiky = {
+            let mut i = 0;
+            move || {
+                i += 1;
+                if i % 5 == 0 { 50.0 } else { 1.0 }
+            }
+        };
+        let history = (0..20)
+            .map(|_| timed(50_000, &mut spiky))
+            .collect::<Vec<_>>();
+        assert_near(suggest_chunk_size(&history, 4.0), exact(4.0), 0.01);
+    }
+}
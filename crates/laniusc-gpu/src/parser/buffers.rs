@@ -2,10 +2,12 @@
 
 mod constructors;
 mod model;
+mod plans;
 mod scan_steps;
 mod scans;
 mod sizing;
 mod storage;
+use anyhow::Result;
 pub use model::{
     ActionHeader,
     GpuHirView,
@@ -33,470 +35,254 @@ pub use model::{
     TokenBraceMatchParams,
     TokenDelimiterParams,
 };
+pub(crate) use plans::resident_pair_plan;
 pub use scan_steps::*;
 use scans::*;
 pub(crate) use sizing::resident_partial_parse_tree_capacity_for_tables;
 use sizing::{
+    PackTotals,
+    ParserBufferSizing,
     ParserFamilyCapacities,
+    TokenCapacities,
+    one_shot_pack_totals,
+    resident_pack_totals,
     resident_partial_parse_tree_capacity,
-    resident_virtual_pair_width,
 };
 use storage::{
     alias_storage_buffer,
     dispatch_args_buffer,
     dispatch_args_schedule_buffer,
     dispatch_args_schedule_with_count_buffer,
+    planned,
     reuse_or_allocate_u32_workspace,
 };
 pub(crate) use storage::{dispatch_args_schedule_count_offset, pointer_jump_step_capacity};
 
-use crate::gpu::buffers::{
-    LaniusBuffer,
-    storage_ro_from_bytes,
-    storage_ro_from_u32s,
-    storage_rw_for_array,
-    uniform_from_val,
+use crate::gpu::{
+    buffers::{
+        LaniusBuffer,
+        storage_ro_from_bytes,
+        storage_ro_from_u32s,
+        storage_rw_for_array,
+        uniform_from_val,
+    },
+    limits::{MAX_EMITS, narrow_u32},
 };
 
+/// Uploads the packed action-header grid; an empty grid becomes one zero header.
+pub(crate) fn upload_action_table(
+    device: &wgpu::Device,
+    action_table_bytes: &[u8],
+) -> LaniusBuffer<u8> {
+    if action_table_bytes.is_empty() {
+        let one = vec![0u8; core::mem::size_of::<ActionHeader>()];
+        storage_ro_from_bytes::<u8>(device, "parser.action_table", &one, one.len())
+    } else {
+        storage_ro_from_bytes::<u8>(
+            device,
+            "parser.action_table",
+            action_table_bytes,
+            action_table_bytes.len(),
+        )
+    }
+}
+
 impl ParserBuffers {
+    /// Sizes every parser buffer family. `sizing.headers_only` sizes the
+    /// packed stream, bracket, and tree families as empty so only the pair
+    /// headers and their offset scans are backed by real storage.
     fn new_with_sizing(
         device: &wgpu::Device,
-        n_tokens: u32,
-        source_capacity: u32,
         token_kinds_u32: Option<&[u32]>,
         n_kinds: u32,
-        action_table_bytes: &[u8],
+        action_table: LaniusBuffer<u8>,
         tables: &crate::parser::tables::PrecomputedParseTables,
-        resident_partial_parse_capacity: bool,
-        retain_debug_hir_buffers: bool,
-        tree_capacity_override: Option<u32>,
-        parser_feature_flags: u32,
-    ) -> Self {
-        let n_pairs = n_tokens.saturating_sub(1) as usize;
-        let token_input_capacity = n_tokens.saturating_sub(2).max(1);
-        let token_delimiter_n_blocks = token_input_capacity.div_ceil(256).max(1);
-        let pair_capacity = n_pairs.max(1);
-        let ll1_stack_capacity = 1;
-        let empty = [0u32];
-        let ll1_predict_src = if tables.ll1_predict.is_empty() {
-            &empty[..]
-        } else {
-            &tables.ll1_predict
-        };
-        let ll1_rhs_off_src = if tables.prod_rhs_off.is_empty() {
-            &empty[..]
-        } else {
-            &tables.prod_rhs_off
-        };
-        let ll1_rhs_len_src = if tables.prod_rhs_len.is_empty() {
-            &empty[..]
-        } else {
-            &tables.prod_rhs_len
-        };
-        let ll1_rhs_src = if tables.prod_rhs.is_empty() {
-            &empty[..]
+        sizing: ParserBufferSizing,
+    ) -> Result<Self> {
+        let ParserBufferSizing {
+            n_tokens,
+            source_capacity,
+            headers_only,
+            resident_partial_parse_capacity,
+            retain_debug_hir_buffers,
+            tree_capacity_override,
+            parser_feature_flags,
+        } = sizing;
+        // Check the packed-stream totals before allocating anything.
+        let totals = if headers_only {
+            // Packed streams stay unallocated; their totals come from the offset scan.
+            PackTotals::default()
+        } else if resident_partial_parse_capacity {
+            resident_pack_totals(n_tokens.saturating_sub(1), tables)?
         } else {
-            &tables.prod_rhs
+            let token_kinds_u32 =
+                token_kinds_u32.expect("non-resident parser sizing requires explicit token kinds");
+            one_shot_pack_totals(token_kinds_u32, tables)?
         };
-        let ll1_predict = storage_ro_from_u32s(device, "parser.ll1_predict", ll1_predict_src);
-        let ll1_prod_rhs_off =
-            storage_ro_from_u32s(device, "parser.ll1_prod_rhs_off", ll1_rhs_off_src);
-        let ll1_prod_rhs_len =
-            storage_ro_from_u32s(device, "parser.ll1_prod_rhs_len", ll1_rhs_len_src);
-        let ll1_prod_rhs = storage_ro_from_u32s(device, "parser.ll1_prod_rhs", ll1_rhs_src);
-        let ll1_emit =
-            storage_rw_for_array::<u32>(device, "parser.ll1_emit", ll1_stack_capacity as usize);
-        let ll1_emit_pos =
-            storage_rw_for_array::<u32>(device, "parser.ll1_emit_pos", ll1_stack_capacity as usize);
-        let ll1_status = storage_rw_for_array::<u32>(device, "parser.ll1_status", 6);
-
-        let stream_has_soi = token_kinds_u32
-            .map(|kinds| kinds.first().copied() == Some(0))
-            .unwrap_or(true);
-        let first_input = if n_tokens > 1 && stream_has_soi { 1 } else { 0 };
-        // Match the canonical LL(1) stream: the last token is the EOF sentinel and is not
-        // consumed as ordinary input.
-        let input_end = n_tokens.saturating_sub(1);
-        let n_input_tokens = input_end.saturating_sub(first_input);
-        let token_count = storage_ro_from_u32s(device, "parser.token_count", &[n_input_tokens]);
+        let caps = TokenCapacities::new(n_tokens);
+        let token_input_capacity = caps.input;
+        let token_delimiter_n_blocks = caps.delimiter_blocks;
+        let token_brace_match_min_tree_base = caps.min_tree_base;
+        let pair_capacity = caps.pairs;
+        let mut header_bufs = plans::pair_header_plan(caps, token_kinds_u32, n_kinds, tables)
+            .materialize(device, None)?;
+        let ll1_predict = planned(&mut header_bufs, "parser.ll1_predict")?;
+        let ll1_prod_rhs_off = planned(&mut header_bufs, "parser.ll1_prod_rhs_off")?;
+        let ll1_prod_rhs_len = planned(&mut header_bufs, "parser.ll1_prod_rhs_len")?;
+        let ll1_prod_rhs = planned(&mut header_bufs, "parser.ll1_prod_rhs")?;
+        let ll1_emit = planned(&mut header_bufs, "parser.ll1_emit")?;
+        let ll1_emit_pos = planned(&mut header_bufs, "parser.ll1_emit_pos")?;
+        let ll1_status = planned(&mut header_bufs, "parser.ll1_status")?;
+        let token_count = planned(&mut header_bufs, "parser.token_count")?;
         let active_pair_thread_dispatch_args =
             dispatch_args_buffer(device, "parser.active_pair_thread_dispatch_args");
         let active_pair_group_dispatch_args =
             dispatch_args_buffer(device, "parser.active_pair_group_dispatch_args");
         // ---------- Pair-to-header ----------
-        let semantic_token_kinds = if let Some(kinds) = token_kinds_u32 {
-            // Test/debug one-shot parsing receives already-classified parser
-            // token kinds. Resident compilation fills this buffer on the GPU
-            // with `tokens_to_kinds` instead.
-            storage_ro_from_u32s(device, "parser.semantic_token_kinds.input", kinds)
-        } else {
-            storage_rw_for_array::<u32>(device, "parser.semantic_token_kinds", n_tokens as usize)
-        };
-        let token_delimiter_params = uniform_from_val(
-            device,
-            "parser.token_delimiters.params",
-            &TokenDelimiterParams {
-                n_tokens: token_input_capacity,
-                n_blocks: token_delimiter_n_blocks,
-                scan_step: 0,
+        let semantic_token_kinds = planned(
+            &mut header_bufs,
+            if token_kinds_u32.is_some() {
+                "parser.semantic_token_kinds.input"
+            } else {
+                "parser.semantic_token_kinds"
             },
-        );
+        )?;
+        let token_delimiter_params = planned(&mut header_bufs, "parser.token_delimiters.params")?;
         let token_delimiter_scan_steps =
             make_token_delimiter_scan_steps(device, token_input_capacity, token_delimiter_n_blocks);
-        let token_depth_paren_inblock = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_depth_paren_inblock",
-            token_input_capacity as usize,
-        );
-        let token_depth_brace_inblock = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_depth_brace_inblock",
-            token_input_capacity as usize,
-        );
-        let token_depth_bracket_inblock = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_depth_bracket_inblock",
-            n_tokens.max(1) as usize,
-        );
-        let token_depth_angle_inblock = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_depth_angle_inblock",
-            token_input_capacity as usize,
-        );
-        let token_block_sum_paren = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_block_sum_paren",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_block_sum_brace = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_block_sum_brace",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_block_sum_bracket = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_block_sum_bracket",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_block_sum_angle = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_block_sum_angle",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_prefix_paren_a = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_prefix_paren_a",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_prefix_paren_b = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_prefix_paren_b",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_block_prefix_paren = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_block_prefix_paren",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_prefix_brace_a = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_prefix_brace_a",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_prefix_brace_b = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_prefix_brace_b",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_block_prefix_brace = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_block_prefix_brace",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_prefix_bracket_a = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_prefix_bracket_a",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_prefix_bracket_b = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_prefix_bracket_b",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_block_prefix_bracket = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_block_prefix_bracket",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_prefix_angle_a = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_prefix_angle_a",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_prefix_angle_b = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_prefix_angle_b",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_block_prefix_angle = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_block_prefix_angle",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_top_brace_owner_block = storage_rw_for_array::<u32>(
-            device,
-            "parser.token_top_brace_owner_block",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_top_brace_owner_prefix_a = storage_rw_for_array::<u32>(
-            device,
-            "parser.token_top_brace_owner_prefix_a",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_top_brace_owner_prefix_b = storage_rw_for_array::<u32>(
-            device,
-            "parser.token_top_brace_owner_prefix_b",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_top_brace_owner_block_prefix = storage_rw_for_array::<u32>(
-            device,
+        let token_depth_paren_inblock =
+            planned(&mut header_bufs, "parser.token_depth_paren_inblock")?;
+        let token_depth_brace_inblock =
+            planned(&mut header_bufs, "parser.token_depth_brace_inblock")?;
+        let token_depth_bracket_inblock =
+            planned(&mut header_bufs, "parser.token_depth_bracket_inblock")?;
+        let token_depth_angle_inblock =
+            planned(&mut header_bufs, "parser.token_depth_angle_inblock")?;
+        let token_block_sum_paren = planned(&mut header_bufs, "parser.token_block_sum_paren")?;
+        let token_block_sum_brace = planned(&mut header_bufs, "parser.token_block_sum_brace")?;
+        let token_block_sum_bracket = planned(&mut header_bufs, "parser.token_block_sum_bracket")?;
+        let token_block_sum_angle = planned(&mut header_bufs, "parser.token_block_sum_angle")?;
+        let token_prefix_paren_a = planned(&mut header_bufs, "parser.token_prefix_paren_a")?;
+        let token_prefix_paren_b = planned(&mut header_bufs, "parser.token_prefix_paren_b")?;
+        let token_block_prefix_paren =
+            planned(&mut header_bufs, "parser.token_block_prefix_paren")?;
+        let token_prefix_brace_a = planned(&mut header_bufs, "parser.token_prefix_brace_a")?;
+        let token_prefix_brace_b = planned(&mut header_bufs, "parser.token_prefix_brace_b")?;
+        let token_block_prefix_brace =
+            planned(&mut header_bufs, "parser.token_block_prefix_brace")?;
+        let token_prefix_bracket_a = planned(&mut header_bufs, "parser.token_prefix_bracket_a")?;
+        let token_prefix_bracket_b = planned(&mut header_bufs, "parser.token_prefix_bracket_b")?;
+        let token_block_prefix_bracket =
+            planned(&mut header_bufs, "parser.token_block_prefix_bracket")?;
+        let token_prefix_angle_a = planned(&mut header_bufs, "parser.token_prefix_angle_a")?;
+        let token_prefix_angle_b = planned(&mut header_bufs, "parser.token_prefix_angle_b")?;
+        let token_block_prefix_angle =
+            planned(&mut header_bufs, "parser.token_block_prefix_angle")?;
+        let token_top_brace_owner_block =
+            planned(&mut header_bufs, "parser.token_top_brace_owner_block")?;
+        let token_top_brace_owner_prefix_a =
+            planned(&mut header_bufs, "parser.token_top_brace_owner_prefix_a")?;
+        let token_top_brace_owner_prefix_b =
+            planned(&mut header_bufs, "parser.token_top_brace_owner_prefix_b")?;
+        let token_top_brace_owner_block_prefix = planned(
+            &mut header_bufs,
             "parser.token_top_brace_owner_block_prefix",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_statement_event_block = storage_rw_for_array::<u32>(
-            device,
-            "parser.token_statement_event_block",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_statement_event_prefix_a = storage_rw_for_array::<u32>(
-            device,
-            "parser.token_statement_event_prefix_a",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_statement_event_prefix_b = storage_rw_for_array::<u32>(
-            device,
-            "parser.token_statement_event_prefix_b",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_statement_event_block_prefix = storage_rw_for_array::<u32>(
-            device,
+        )?;
+        let token_statement_event_block =
+            planned(&mut header_bufs, "parser.token_statement_event_block")?;
+        let token_statement_event_prefix_a =
+            planned(&mut header_bufs, "parser.token_statement_event_prefix_a")?;
+        let token_statement_event_prefix_b =
+            planned(&mut header_bufs, "parser.token_statement_event_prefix_b")?;
+        let token_statement_event_block_prefix = planned(
+            &mut header_bufs,
             "parser.token_statement_event_block_prefix",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_brace_semantic_kind = storage_rw_for_array::<u32>(
-            device,
-            "parser.token_brace_semantic_kind",
-            n_tokens.max(1) as usize,
-        );
-        let token_braced_rhs_statement_kind = storage_rw_for_array::<u32>(
-            device,
-            "parser.token_braced_rhs_statement_kind",
-            token_input_capacity as usize,
-        );
-        let token_bracket_semantic_kind = storage_rw_for_array::<u32>(
-            device,
-            "parser.token_bracket_semantic_kind",
-            token_input_capacity as usize,
-        );
-        let token_statement_context_kind = storage_rw_for_array::<u32>(
-            device,
-            "parser.token_statement_context_kind",
-            token_input_capacity as usize,
-        );
-        let token_impl_header_kind = storage_rw_for_array::<u32>(
-            device,
-            "parser.token_impl_header_kind",
-            token_input_capacity as usize,
-        );
-        let token_impl_context_event = storage_rw_for_array::<u32>(
-            device,
-            "parser.token_impl_context_event",
-            token_input_capacity as usize,
-        );
-        let token_type_path_context_kind = storage_rw_for_array::<u32>(
-            device,
-            "parser.token_type_path_context_kind",
-            token_input_capacity as usize,
-        );
-        let token_where_context_event = storage_rw_for_array::<u32>(
-            device,
-            "parser.token_where_context_event",
-            token_input_capacity as usize,
-        );
-        let token_match_pattern_context_event = storage_rw_for_array::<u32>(
-            device,
-            "parser.token_match_pattern_context_event",
-            token_input_capacity as usize,
-        );
-        let token_generic_shr_block_sum = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_generic_shr.block_sum",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_generic_shr_block_min = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_generic_shr.block_min",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_generic_shr_prefix_sum_a = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_generic_shr.prefix_sum_a",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_generic_shr_prefix_sum_b = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_generic_shr.prefix_sum_b",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_generic_shr_prefix_min_a = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_generic_shr.prefix_min_a",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_generic_shr_prefix_min_b = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_generic_shr.prefix_min_b",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_generic_shr_block_prefix_sum = storage_rw_for_array::<i32>(
-            device,
+        )?;
+        let token_brace_semantic_kind =
+            planned(&mut header_bufs, "parser.token_brace_semantic_kind")?;
+        let token_braced_rhs_statement_kind =
+            planned(&mut header_bufs, "parser.token_braced_rhs_statement_kind")?;
+        let token_bracket_semantic_kind =
+            planned(&mut header_bufs, "parser.token_bracket_semantic_kind")?;
+        let token_statement_context_kind =
+            planned(&mut header_bufs, "parser.token_statement_context_kind")?;
+        let token_impl_header_kind = planned(&mut header_bufs, "parser.token_impl_header_kind")?;
+        let token_impl_context_event =
+            planned(&mut header_bufs, "parser.token_impl_context_event")?;
+        let token_type_path_context_kind =
+            planned(&mut header_bufs, "parser.token_type_path_context_kind")?;
+        let token_where_context_event =
+            planned(&mut header_bufs, "parser.token_where_context_event")?;
+        let token_match_pattern_context_event =
+            planned(&mut header_bufs, "parser.token_match_pattern_context_event")?;
+        let token_generic_shr_block_sum =
+            planned(&mut header_bufs, "parser.token_generic_shr.block_sum")?;
+        let token_generic_shr_block_min =
+            planned(&mut header_bufs, "parser.token_generic_shr.block_min")?;
+        let token_generic_shr_prefix_sum_a =
+            planned(&mut header_bufs, "parser.token_generic_shr.prefix_sum_a")?;
+        let token_generic_shr_prefix_sum_b =
+            planned(&mut header_bufs, "parser.token_generic_shr.prefix_sum_b")?;
+        let token_generic_shr_prefix_min_a =
+            planned(&mut header_bufs, "parser.token_generic_shr.prefix_min_a")?;
+        let token_generic_shr_prefix_min_b =
+            planned(&mut header_bufs, "parser.token_generic_shr.prefix_min_b")?;
+        let token_generic_shr_block_prefix_sum = planned(
+            &mut header_bufs,
             "parser.token_generic_shr.block_prefix_sum",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_generic_shr_block_prefix_min = storage_rw_for_array::<i32>(
-            device,
+        )?;
+        let token_generic_shr_block_prefix_min = planned(
+            &mut header_bufs,
             "parser.token_generic_shr.block_prefix_min",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_brace_match_params = uniform_from_val(
-            device,
-            "parser.token_brace_match.params",
-            &TokenBraceMatchParams {
-                n_tokens: token_input_capacity,
-            },
-        );
-        let token_brace_match_depth = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_brace_match_depth",
-            token_input_capacity as usize,
-        );
-        let token_brace_match_block_min = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_brace_match_block_min",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_brace_match_min_tree_base =
-            next_power_of_two_u32(token_delimiter_n_blocks).max(1);
-        let token_brace_match_min_tree = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_brace_match_min_tree",
-            token_brace_match_min_tree_base.saturating_mul(2) as usize,
-        );
+        )?;
+        let token_brace_match_params =
+            planned(&mut header_bufs, "parser.token_brace_match.params")?;
+        let token_brace_match_depth = planned(&mut header_bufs, "parser.token_brace_match_depth")?;
+        let token_brace_match_block_min =
+            planned(&mut header_bufs, "parser.token_brace_match_block_min")?;
+        let token_brace_match_min_tree =
+            planned(&mut header_bufs, "parser.token_brace_match_min_tree")?;
         let token_brace_match_min_tree_steps = make_tree_prefix_max_build_steps(
             device,
             token_delimiter_n_blocks,
             token_brace_match_min_tree_base,
         );
-        let token_bracket_match_depth = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_bracket_match_depth",
-            token_input_capacity as usize,
-        );
-        let token_bracket_match_block_min = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_bracket_match_block_min",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_bracket_match_min_tree = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_bracket_match_min_tree",
-            token_brace_match_min_tree_base.saturating_mul(2) as usize,
-        );
-        let token_paren_match_depth = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_paren_match_depth",
-            token_input_capacity as usize,
-        );
-        let token_paren_match_block_min = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_paren_match_block_min",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_paren_match_min_tree = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_paren_match_min_tree",
-            token_brace_match_min_tree_base.saturating_mul(2) as usize,
-        );
-        let token_angle_match_depth = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_angle_match_depth",
-            token_input_capacity as usize,
-        );
-        let token_angle_match_block_min = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_angle_match_block_min",
-            token_delimiter_n_blocks as usize,
-        );
-        let token_angle_match_min_tree = storage_rw_for_array::<i32>(
-            device,
-            "parser.token_angle_match_min_tree",
-            token_brace_match_min_tree_base.saturating_mul(2) as usize,
-        );
-        let token_feature_flags = if token_kinds_u32.is_some() {
-            storage_ro_from_u32s(
-                device,
-                "parser.token_feature_flags.conservative",
-                &[u32::MAX],
-            )
-        } else {
-            storage_ro_from_u32s(device, "parser.token_feature_flags", &[0])
-        };
+        let token_bracket_match_depth =
+            planned(&mut header_bufs, "parser.token_bracket_match_depth")?;
+        let token_bracket_match_block_min =
+            planned(&mut header_bufs, "parser.token_bracket_match_block_min")?;
+        let token_bracket_match_min_tree =
+            planned(&mut header_bufs, "parser.token_bracket_match_min_tree")?;
+        let token_paren_match_depth = planned(&mut header_bufs, "parser.token_paren_match_depth")?;
+        let token_paren_match_block_min =
+            planned(&mut header_bufs, "parser.token_paren_match_block_min")?;
+        let token_paren_match_min_tree =
+            planned(&mut header_bufs, "parser.token_paren_match_min_tree")?;
+        let token_angle_match_depth = planned(&mut header_bufs, "parser.token_angle_match_depth")?;
+        let token_angle_match_block_min =
+            planned(&mut header_bufs, "parser.token_angle_match_block_min")?;
+        let token_angle_match_min_tree =
+            planned(&mut header_bufs, "parser.token_angle_match_min_tree")?;
+        let token_feature_flags = planned(
+            &mut header_bufs,
+            if token_kinds_u32.is_some() {
+                "parser.token_feature_flags.conservative"
+            } else {
+                "parser.token_feature_flags"
+            },
+        )?;
 
-        let params_llp = uniform_from_val(
-            device,
-            "parser.params_llp",
-            &super::passes::llp_pairs::LLPParams { n_tokens, n_kinds },
-        );
+        let params_llp = planned(&mut header_bufs, "parser.params_llp")?;
 
-        let action_table = if action_table_bytes.is_empty() {
-            let one = vec![0u8; core::mem::size_of::<ActionHeader>()];
-            storage_ro_from_bytes::<u8>(device, "parser.action_table", &one, one.len())
-        } else {
-            storage_ro_from_bytes::<u8>(
-                device,
-                "parser.action_table",
-                action_table_bytes,
-                action_table_bytes.len(),
-            )
-        };
+        let kind_remap = planned(&mut header_bufs, "parser.kind_remap")?;
 
-        let out_headers: LaniusBuffer<ActionHeader> = storage_rw_for_array::<ActionHeader>(
-            device,
-            "parser.out_headers",
-            pair_capacity.saturating_add(1),
-        );
+        let out_headers: LaniusBuffer<ActionHeader> =
+            planned(&mut header_bufs, "parser.out_headers")?;
+        header_bufs.finish()?;
 
         // ---------- Pack varlen ----------
-        let (mut acc_sc, mut acc_emit) = (0u32, 0u32);
-
-        if resident_partial_parse_capacity {
-            let max_sc_len = resident_virtual_pair_width(&tables.sc_len, n_kinds);
-            let max_emit_len = resident_virtual_pair_width(&tables.pp_len, n_kinds);
-            acc_sc = (n_pairs as u32).saturating_mul(max_sc_len);
-            acc_emit = (n_pairs as u32).saturating_mul(max_emit_len);
-        } else {
-            let token_kinds_u32 =
-                token_kinds_u32.expect("non-resident parser sizing requires explicit token kinds");
-            for i in 0..n_pairs {
-                let prev = token_kinds_u32[i];
-                let thisk = token_kinds_u32[i + 1];
-                let idx2d = (prev as usize) * (n_kinds as usize) + (thisk as usize);
-                acc_sc += tables.sc_len[idx2d];
-                acc_emit += tables.pp_len[idx2d];
-            }
-        }
-        let total_sc = acc_sc;
-        let total_emit = acc_emit;
+        let total_sc = totals.sc;
+        let total_emit = totals.emit;
         let tree_count_uses_status = true;
         let tree_capacity = tree_capacity_override
             .unwrap_or_else(|| {
@@ -507,11 +293,13 @@ impl ParserBuffers {
                 }
             })
             .max(1);
+        // The tree prefix scan covers one position past the last node.
+        let tree_capacity = narrow_u32("parser tree capacity", tree_capacity.into(), MAX_EMITS)?;
         let parser_workspace_plan = storage::parser_phase_workspace_plan(tree_capacity);
-        if crate::gpu::env::env_bool_truthy("LANIUS_GPU_BUFFER_BREAKDOWN", false) {
+        if log::log_enabled!(target: crate::logging::PARSER_GPU, log::Level::Debug) {
             for assignment in &parser_workspace_plan.assignments {
                 let slot = &parser_workspace_plan.slots[assignment.slot as usize];
-                eprintln!(
+                log::debug!(target: crate::logging::PARSER_GPU,
                     "gpu_workspace logical={:?} slot={} bytes={} usage={:?}",
                     assignment.name, assignment.slot, slot.bytes, slot.usage,
                 );
@@ -523,32 +311,7 @@ impl ParserBuffers {
             total_emit.max(1)
         };
 
-        let mut blob: Vec<u32> = Vec::with_capacity(
-            tables.sc_superseq.len()
-                + tables.sc_off.len()
-                + tables.sc_len.len()
-                + tables.pp_superseq.len()
-                + tables.pp_off.len()
-                + tables.pp_len.len(),
-        );
-
-        let sc_superseq_off = blob.len() as u32;
-        blob.extend_from_slice(&tables.sc_superseq);
-
-        let sc_off_off = blob.len() as u32;
-        blob.extend_from_slice(&tables.sc_off);
-
-        let sc_len_off = blob.len() as u32;
-        blob.extend_from_slice(&tables.sc_len);
-
-        let pp_superseq_off = blob.len() as u32;
-        blob.extend_from_slice(&tables.pp_superseq);
-
-        let pp_off_off = blob.len() as u32;
-        blob.extend_from_slice(&tables.pp_off);
-
-        let pp_len_off = blob.len() as u32;
-        blob.extend_from_slice(&tables.pp_len);
+        let blob = tables.to_gpu_blob();
 
         let params_pack = uniform_from_val(
             device,
@@ -560,46 +323,52 @@ impl ParserBuffers {
                 total_emit,
                 sc_capacity: total_sc.max(1),
                 emit_capacity,
-                sc_superseq_off,
-                sc_off_off,
-                sc_len_off,
-                pp_superseq_off,
-                pp_off_off,
-                pp_len_off,
+                sc_superseq_off: blob.sc_superseq_off,
+                pp_superseq_off: blob.pp_superseq_off,
+                cell_palette_off: blob.cell_palette_off,
+                cell_index_off: blob.cell_index_off,
             },
         );
 
-        let n_pack_pairs = pair_capacity;
-        let sc_offsets = storage_rw_for_array::<u32>(device, "pack.sc_offsets", n_pack_pairs);
-        let emit_offsets = storage_rw_for_array::<u32>(device, "pack.emit_offsets", n_pack_pairs);
-        let pack_sc_prefix_a =
-            storage_rw_for_array::<u32>(device, "pack.sc_prefix_a", n_pack_pairs);
-        let pack_sc_prefix_b =
-            storage_rw_for_array::<u32>(device, "pack.sc_prefix_b", n_pack_pairs);
-        let pack_emit_prefix_a =
-            storage_rw_for_array::<u32>(device, "pack.emit_prefix_a", n_pack_pairs);
-        let pack_emit_prefix_b =
-            storage_rw_for_array::<u32>(device, "pack.emit_prefix_b", n_pack_pairs);
+        // Resident parsing validates stack effects before publishing
+        // acceptance, so bracket scratch is sized to the conservative stack
+        // capacity. Production validation only writes the match table when a
+        // later raw tree consumer or a debug readback needs it. Otherwise its
+        // allocation is phase-colored HIR scratch and only needs dense tree
+        // capacity.
+        let emit_stack_matches = retain_debug_hir_buffers || !resident_partial_parse_capacity;
+        let stack_effect = plans::StackEffectSizing {
+            pairs: pair_capacity,
+            totals,
+            emit_capacity,
+            match_capacity: if emit_stack_matches {
+                total_sc.max(1)
+            } else {
+                tree_capacity
+            },
+        };
+        let mut stack_effect_bufs =
+            plans::stack_effect_plan(stack_effect, &blob.words).materialize(device, None)?;
+        let sc_offsets = planned(&mut stack_effect_bufs, "pack.sc_offsets")?;
+        let emit_offsets = planned(&mut stack_effect_bufs, "pack.emit_offsets")?;
+        let pack_sc_prefix_a = planned(&mut stack_effect_bufs, "pack.sc_prefix_a")?;
+        let pack_sc_prefix_b = planned(&mut stack_effect_bufs, "pack.sc_prefix_b")?;
+        let pack_emit_prefix_a = planned(&mut stack_effect_bufs, "pack.emit_prefix_a")?;
+        let pack_emit_prefix_b = planned(&mut stack_effect_bufs, "pack.emit_prefix_b")?;
         let pack_offset_scan_steps =
             make_pack_offset_scan_steps(device, n_tokens.saturating_sub(1));
         let pack_total_reduce_steps =
             make_pack_total_reduce_steps(device, n_tokens.saturating_sub(1));
-        let partial_parse_status =
-            storage_rw_for_array::<u32>(device, "pack.partial_parse_status", 6);
-        let tables_blob = storage_ro_from_u32s(device, "pack.tables_blob", &blob);
+        let partial_parse_status = planned(&mut stack_effect_bufs, "pack.partial_parse_status")?;
+        let tables_blob = planned(&mut stack_effect_bufs, "pack.tables_blob")?;
 
-        let out_sc = storage_rw_for_array::<u32>(device, "pack.out_sc", total_sc.max(1) as usize);
-        let out_emit = storage_rw_for_array::<u32>(device, "pack.out_emit", emit_capacity as usize);
-        let out_emit_pos =
-            storage_rw_for_array::<u32>(device, "pack.out_emit_pos", emit_capacity as usize);
+        let out_sc = planned(&mut stack_effect_bufs, "pack.out_sc")?;
+        let out_emit = planned(&mut stack_effect_bufs, "pack.out_emit")?;
+        let out_emit_pos = planned(&mut stack_effect_bufs, "pack.out_emit_pos")?;
 
         // ---------- Brackets (parallel) ----------
-        //
-        // Resident parsing validates stack effects before publishing acceptance,
-        // so bracket scratch is sized to the conservative stack capacity.
         const WG: u32 = 256;
-        let bracket_capacity = total_sc.max(1);
-        let n_blocks = total_sc.div_ceil(WG).max(1);
+        let n_blocks = stack_effect.bracket_blocks();
 
         let b01_params = uniform_from_val(
             device,
@@ -627,14 +396,13 @@ impl ParserBuffers {
             },
         );
 
-        let emit_stack_matches = retain_debug_hir_buffers || !resident_partial_parse_capacity;
         let b07_params = uniform_from_val(
             device,
             "brackets.b07.params",
             &super::passes::brackets::pse_pair::Params {
                 n_sc: total_sc,
                 n_blocks,
-                leaf_base: next_power_of_two_u32(n_blocks).max(1),
+                leaf_base: stack_effect.min_tree_base(),
                 typed_check: 1,
                 emit_matches: u32::from(emit_stack_matches),
             },
@@ -644,61 +412,33 @@ impl ParserBuffers {
             "brackets.clear_matches.params",
             &super::passes::brackets::clear_matches::Params { n_sc: total_sc },
         );
-        let b_min_tree_base = next_power_of_two_u32(n_blocks).max(1);
-        let b_min_tree = storage_rw_for_array::<i32>(
-            device,
-            "brackets.min_tree",
-            b_min_tree_base.saturating_mul(2) as usize,
-        );
-        let b_min_tree_steps = make_tree_prefix_max_build_steps(device, n_blocks, b_min_tree_base);
+        let b_min_tree = planned(&mut stack_effect_bufs, "brackets.min_tree")?;
+        let b_min_tree_steps =
+            make_tree_prefix_max_build_steps(device, n_blocks, stack_effect.min_tree_base());
 
-        let b_exscan_inblock = storage_rw_for_array::<i32>(
-            device,
-            "brackets.exscan_inblock",
-            bracket_capacity as usize,
-        );
-        let b_block_sum =
-            storage_rw_for_array::<i32>(device, "brackets.block_sum", n_blocks as usize);
-        let b_block_minpref =
-            storage_rw_for_array::<i32>(device, "brackets.block_minpref", n_blocks as usize);
-        let b_block_row_min =
-            storage_rw_for_array::<i32>(device, "brackets.block_row_min", n_blocks as usize);
-        let b_block_maxdepth =
-            storage_rw_for_array::<i32>(device, "brackets.block_maxdepth", n_blocks as usize);
-        let b_block_prefix =
-            storage_rw_for_array::<i32>(device, "brackets.block_prefix", n_blocks as usize);
-        let b_block_prefix_sum_a =
-            storage_rw_for_array::<i32>(device, "brackets.block_prefix_sum_a", n_blocks as usize);
-        let b_block_prefix_sum_b =
-            storage_rw_for_array::<i32>(device, "brackets.block_prefix_sum_b", n_blocks as usize);
-        let b_block_prefix_min_a =
-            storage_rw_for_array::<i32>(device, "brackets.block_prefix_min_a", n_blocks as usize);
-        let b_block_prefix_min_b =
-            storage_rw_for_array::<i32>(device, "brackets.block_prefix_min_b", n_blocks as usize);
+        let b_exscan_inblock = planned(&mut stack_effect_bufs, "brackets.exscan_inblock")?;
+        let b_block_sum = planned(&mut stack_effect_bufs, "brackets.block_sum")?;
+        let b_block_minpref = planned(&mut stack_effect_bufs, "brackets.block_minpref")?;
+        let b_block_row_min = planned(&mut stack_effect_bufs, "brackets.block_row_min")?;
+        let b_block_maxdepth = planned(&mut stack_effect_bufs, "brackets.block_maxdepth")?;
+        let b_block_prefix = planned(&mut stack_effect_bufs, "brackets.block_prefix")?;
+        let b_block_prefix_sum_a = planned(&mut stack_effect_bufs, "brackets.block_prefix_sum_a")?;
+        let b_block_prefix_sum_b = planned(&mut stack_effect_bufs, "brackets.block_prefix_sum_b")?;
+        let b_block_prefix_min_a = planned(&mut stack_effect_bufs, "brackets.block_prefix_min_a")?;
+        let b_block_prefix_min_b = planned(&mut stack_effect_bufs, "brackets.block_prefix_min_b")?;
 
-        let depths_out = storage_rw_for_array::<i32>(device, "brackets.depths_out", 3);
-        let valid_out = storage_rw_for_array::<u32>(device, "brackets.valid_out", 1);
+        let depths_out = planned(&mut stack_effect_bufs, "brackets.depths_out")?;
+        let valid_out = planned(&mut stack_effect_bufs, "brackets.valid_out")?;
+        let bracket_frontier = planned(&mut stack_effect_bufs, "brackets.frontier")?;
 
-        let b_layer =
-            storage_rw_for_array::<u32>(device, "brackets.layer", bracket_capacity as usize);
-        // Production validation only writes the match table when a later raw
-        // tree consumer or a debug readback needs it. Otherwise its allocation
-        // is phase-colored HIR scratch and only needs dense tree capacity.
-        let match_capacity = if emit_stack_matches {
-            bracket_capacity
-        } else {
-            tree_capacity
-        };
-        let match_for_index = storage_rw_for_array::<u32>(
-            device,
-            "brackets.match_for_index",
-            match_capacity as usize,
-        );
+        let b_layer = planned(&mut stack_effect_bufs, "brackets.layer")?;
+        let match_for_index = planned(&mut stack_effect_bufs, "brackets.match_for_index")?;
+        stack_effect_bufs.finish()?;
 
         // ---------- Tree parent recovery ----------
         let family_capacities = ParserFamilyCapacities::new(tree_capacity, parser_feature_flags);
         let tree_n_node_blocks = tree_capacity.div_ceil(WG).max(1);
-        let tree_n_prefix_blocks = tree_capacity.saturating_add(1).div_ceil(WG).max(1);
+        let tree_n_prefix_blocks = (tree_capacity + 1).div_ceil(WG).max(1);
         let tree_prefix_params_base = super::passes::tree::prefix::local::Params {
             n: tree_capacity,
             uses_status_count: u32::from(tree_count_uses_status),
@@ -1337,7 +1077,7 @@ impl ParserBuffers {
         let hir_param_record = storage_rw_for_array::<u32>(
             device,
             "parser.hir_param_record",
-            tree_capacity.saturating_mul(4) as usize,
+            tree_capacity as usize * 4,
         );
         let hir_param_type_node = storage_rw_for_array::<u32>(
             device,
@@ -1444,7 +1184,7 @@ impl ParserBuffers {
             device,
             "parser.hir_variant_payload_node",
             if enum_match_required {
-                tree_capacity.saturating_mul(4) as usize
+                tree_capacity as usize * 4
             } else {
                 1
             },
@@ -1702,7 +1442,7 @@ impl ParserBuffers {
         let hir_expr_record = storage_rw_for_array::<u32>(
             device,
             "parser.hir_expr_record",
-            tree_capacity.saturating_mul(4) as usize,
+            tree_capacity as usize * 4,
         );
         let hir_expr_name_role = storage_rw_for_array::<u32>(
             device,
@@ -1806,7 +1546,7 @@ impl ParserBuffers {
         let hir_stmt_record = storage_rw_for_array::<u32>(
             device,
             "parser.hir_stmt_record",
-            tree_capacity.saturating_mul(4) as usize,
+            tree_capacity as usize * 4,
         );
         let hir_stmt_scope_end = storage_rw_for_array::<u32>(
             device,
@@ -2329,7 +2069,7 @@ impl ParserBuffers {
             hir_canonical_capacity as usize,
         );
 
-        Self {
+        Ok(Self {
             source_capacity: source_capacity.max(1),
             n_tokens,
             n_kinds,
@@ -2424,6 +2164,7 @@ impl ParserBuffers {
             active_pair_thread_dispatch_args,
             active_pair_group_dispatch_args,
             action_table,
+            kind_remap,
             out_headers,
 
             params_pack,
@@ -2448,7 +2189,8 @@ impl ParserBuffers {
             b07_params,
             b_clear_matches_params,
             emit_stack_matches,
-            b_min_tree_base,
+            stack_effect_cached: false,
+            b_min_tree_base: stack_effect.min_tree_base(),
             b_min_tree,
             b_min_tree_steps,
 
@@ -2465,6 +2207,7 @@ impl ParserBuffers {
 
             depths_out,
             valid_out,
+            bracket_frontier,
 
             b_layer,
             match_for_index,
@@ -2878,6 +2621,6 @@ impl ParserBuffers {
             hir_struct_rank_node,
             hir_struct_rank_count,
             hir_struct_rank_dispatch_args,
-        }
+        })
     }
 }
@@ -0,0 +1,219 @@
+use anyhow::Result;
+
+use super::{ParserBuffers, sizing::ParserBufferSizing, upload_action_table};
+use crate::{gpu::buffers::LaniusBuffer, lexer::features::CONSERVATIVE_PARSER_FEATURES};
+
+impl ParserBuffers {
+    /// Allocates one-shot parser buffers from already-classified parser token kinds.
+    ///
+    /// Fails with [`LimitError::InputTooLarge`], before allocating, when the
+    /// token count or the packed-stream totals overflow a GPU-side `u32`, and
+    /// with [`LimitError::InputExceedsDeviceLimits`] when a buffer binding
+    /// outgrows the device.
+    ///
+    /// [`LimitError::InputTooLarge`]: crate::gpu::limits::LimitError::InputTooLarge
+    /// [`LimitError::InputExceedsDeviceLimits`]: crate::gpu::limits::LimitError::InputExceedsDeviceLimits
+    pub fn new(
+        device: &wgpu::Device,
+        token_kinds_u32: &[u32],
+        n_kinds: u32,
+        action_table_bytes: &[u8],
+        tables: &crate::parser::tables::PrecomputedParseTables,
+    ) -> Result<Self> {
+        Self::new_with_action_table(
+            device,
+            token_kinds_u32,
+            n_kinds,
+            upload_action_table(device, action_table_bytes),
+            tables,
+        )
+    }
+
+    /// Allocates one-shot parser buffers around an already-uploaded action table.
+    pub(crate) fn new_with_action_table(
+        device: &wgpu::Device,
+        token_kinds_u32: &[u32],
+        n_kinds: u32,
+        action_table: LaniusBuffer<u8>,
+        tables: &crate::parser::tables::PrecomputedParseTables,
+    ) -> Result<Self> {
+        Self::new_with_sizing(
+            device,
+            Some(token_kinds_u32),
+            n_kinds,
+            action_table,
+            tables,
+            ParserBufferSizing::one_shot(token_kinds_u32.len())?,
+        )
+    }
+
+    /// Allocates one-shot buffers for pair headers and their offset scans only;
+    /// the packed stream, bracket, and tree buffers are left at one element.
+    pub(crate) fn new_headers_only(
+        device: &wgpu::Device,
+        token_kinds_u32: &[u32],
+        n_kinds: u32,
+        action_table: LaniusBuffer<u8>,
+        tables: &crate::parser::tables::PrecomputedParseTables,
+    ) -> Result<Self> {
+        Self::new_with_sizing(
+            device,
+            Some(token_kinds_u32),
+            n_kinds,
+            action_table,
+            tables,
+            ParserBufferSizing {
+                headers_only: true,
+                retain_debug_hir_buffers: false,
+                ..ParserBufferSizing::one_shot(token_kinds_u32.len())?
+            },
+        )
+    }
+
+    /// Allocates one-shot buffers for the pair headers, packed streams, and
+    /// bracket matching; the tree and HIR families are left at one element.
+    pub(crate) fn new_pairs_only(
+        device: &wgpu::Device,
+        token_kinds_u32: &[u32],
+        n_kinds: u32,
+        action_table: LaniusBuffer<u8>,
+        tables: &crate::parser::tables::PrecomputedParseTables,
+    ) -> Result<Self> {
+        Self::new_with_sizing(
+            device,
+            Some(token_kinds_u32),
+            n_kinds,
+            action_table,
+            tables,
+            ParserBufferSizing {
+                tree_capacity_override: Some(1),
+                ..ParserBufferSizing::one_shot(token_kinds_u32.len())?
+            },
+        )
+    }
+
+    /// Allocates resident parser buffers sized by lexer token capacity.
+    ///
+    /// Fails with [`LimitError::InputTooLarge`] when the worst-case
+    /// packed-stream totals of `token_capacity` overflow a GPU-side `u32`, and
+    /// with [`LimitError::InputExceedsDeviceLimits`] when a buffer binding
+    /// outgrows the device.
+    ///
+    /// [`LimitError::InputTooLarge`]: crate::gpu::limits::LimitError::InputTooLarge
+    /// [`LimitError::InputExceedsDeviceLimits`]: crate::gpu::limits::LimitError::InputExceedsDeviceLimits
+    pub fn new_resident_capacity(
+        device: &wgpu::Device,
+        token_capacity: u32,
+        n_kinds: u32,
+        action_table_bytes: &[u8],
+        tables: &crate::parser::tables::PrecomputedParseTables,
+    ) -> Result<Self> {
+        Self::new_resident_capacity_with_tree_capacity(
+            device,
+            token_capacity,
+            n_kinds,
+            action_table_bytes,
+            tables,
+            None,
+        )
+    }
+
+    /// Allocates resident parser buffers with an optional tree-capacity override.
+    pub fn new_resident_capacity_with_tree_capacity(
+        device: &wgpu::Device,
+        token_capacity: u32,
+        n_kinds: u32,
+        action_table_bytes: &[u8],
+        tables: &crate::parser::tables::PrecomputedParseTables,
+        tree_capacity_override: Option<u32>,
+    ) -> Result<Self> {
+        Self::new_resident_capacity_with_tree_capacity_and_debug(
+            device,
+            token_capacity,
+            n_kinds,
+            action_table_bytes,
+            tables,
+            tree_capacity_override,
+            false,
+        )
+    }
+
+    /// Allocates resident parser buffers with optional debug HIR retention.
+    pub fn new_resident_capacity_with_tree_capacity_and_debug(
+        device: &wgpu::Device,
+        token_capacity: u32,
+        n_kinds: u32,
+        action_table_bytes: &[u8],
+        tables: &crate::parser::tables::PrecomputedParseTables,
+        tree_capacity_override: Option<u32>,
+        retain_debug_hir_buffers: bool,
+    ) -> Result<Self> {
+        Self::new_resident_capacity_with_tree_capacity_debug_and_features(
+            device,
+            token_capacity,
+            n_kinds,
+            action_table_bytes,
+            tables,
+            tree_capacity_override,
+            retain_debug_hir_buffers,
+            CONSERVATIVE_PARSER_FEATURES,
+        )
+    }
+
+    /// Allocates resident buffers with optional-family capacities derived from
+    /// conservative GPU lexer feature flags.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new_resident_capacity_with_tree_capacity_debug_and_features(
+        device: &wgpu::Device,
+        token_capacity: u32,
+        n_kinds: u32,
+        action_table_bytes: &[u8],
+        tables: &crate::parser::tables::PrecomputedParseTables,
+        tree_capacity_override: Option<u32>,
+        retain_debug_hir_buffers: bool,
+        parser_feature_flags: u32,
+    ) -> Result<Self> {
+        Self::new_with_sizing(
+            device,
+            None,
+            n_kinds,
+            upload_action_table(device, action_table_bytes),
+            tables,
+            ParserBufferSizing::resident(
+                token_capacity,
+                token_capacity,
+                tree_capacity_override,
+                retain_debug_hir_buffers,
+                parser_feature_flags,
+            )?,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new_resident_capacity_with_source_and_tree_capacity_debug_and_features(
+        device: &wgpu::Device,
+        token_capacity: u32,
+        source_capacity: u32,
+        n_kinds: u32,
+        action_table_bytes: &[u8],
+        tables: &crate::parser::tables::PrecomputedParseTables,
+        tree_capacity_override: Option<u32>,
+        retain_debug_hir_buffers: bool,
+        parser_feature_flags: u32,
+    ) -> Result<Self> {
+        Self::new_with_sizing(
+            device,
+            None,
+            n_kinds,
+            upload_action_table(device, action_table_bytes),
+            tables,
+            ParserBufferSizing::resident(
+                token_capacity,
+                source_capacity,
+                tree_capacity_override,
+                retain_debug_hir_buffers,
+                parser_feature_flags,
+            )?,
+        )
+    }
+}
@@ -9,7 +9,10 @@ use super::{
     TreePrefixMaxBuildStep,
     TreePrefixScanStep,
 };
-use crate::gpu::buffers::LaniusBuffer;
+use crate::gpu::{
+    buffers::LaniusBuffer,
+    decode::{GpuDecodable, le_u32_at},
+};
 
 #[repr(C)]
 #[derive(Clone, Copy, ShaderType, Default)]
@@ -21,6 +24,19 @@ pub struct ActionHeader {
     pub pop_count: u32,
 }
 
+impl GpuDecodable for ActionHeader {
+    const SIZE: usize = 16;
+
+    fn decode(bytes: &[u8]) -> Self {
+        Self {
+            push_len: le_u32_at(bytes, 0),
+            emit_len: le_u32_at(bytes, 1),
+            pop_tag: le_u32_at(bytes, 2),
+            pop_count: le_u32_at(bytes, 3),
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, ShaderType)]
 /// Uniform parameters for parser token-delimiter scans.
@@ -415,6 +431,8 @@ pub struct ParserBuffers {
     pub active_pair_thread_dispatch_args: LaniusBuffer<u32>,
     pub active_pair_group_dispatch_args: LaniusBuffer<u32>,
     pub action_table: LaniusBuffer<u8>,
+    /// Lexer kind -> action-grid kind lookup shared by `llp_pairs` and `pack_varlen`.
+    pub kind_remap: LaniusBuffer<u32>,
     pub out_headers: LaniusBuffer<ActionHeader>,
 
     // pack varlen
@@ -441,6 +459,10 @@ pub struct ParserBuffers {
     pub b07_params: LaniusBuffer<super::super::passes::brackets::pse_pair::Params>, // PSE-style pair-by-layer
     pub b_clear_matches_params: LaniusBuffer<super::super::passes::brackets::clear_matches::Params>,
     pub emit_stack_matches: bool,
+    /// Depths, validity, frontier and matches were uploaded from a
+    /// [`BracketCache`](crate::parser::cache::BracketCache) hit, so
+    /// stack-effect validation only folds them into the parse status.
+    pub stack_effect_cached: bool,
     pub b_min_tree_base: u32,
     pub b_min_tree: LaniusBuffer<i32>,
     pub b_min_tree_steps: Vec<TreePrefixMaxBuildStep>,
@@ -457,6 +479,8 @@ pub struct ParserBuffers {
 
     pub depths_out: LaniusBuffer<i32>, // [final, min, conservative max active layer]
     pub valid_out: LaniusBuffer<u32>,
+    /// `[!first negative-depth pop, 1 + first push open at EOF]`; 0 = none.
+    pub bracket_frontier: LaniusBuffer<u32>,
 
     pub b_layer: LaniusBuffer<u32>,
     pub match_for_index: LaniusBuffer<u32>,
@@ -0,0 +1,358 @@
+//! Buffer plans for parser families whose sizes the host knows before parsing.
+
+use super::{
+    ActionHeader,
+    TokenBraceMatchParams,
+    TokenDelimiterParams,
+    scans::next_power_of_two_u32,
+    sizing::{
+        PackTotals,
+        TokenCapacities,
+        resident_pack_totals,
+        resident_partial_parse_tree_capacity,
+        resident_token_slots,
+    },
+};
+use crate::{
+    gpu::{
+        buffers::BufferPlan,
+        limits::{LimitError, MAX_EMITS, narrow_u32},
+    },
+    parser::tables::PrecomputedParseTables,
+};
+
+/// Substitutes one zero word for an empty table so the binding stays non-empty.
+fn non_empty(words: &[u32]) -> &[u32] {
+    if words.is_empty() { &[0] } else { words }
+}
+
+/// Declares the LL(1) tables, token delimiter scans, and pair-header buffers.
+///
+/// `token_kinds_u32` is present for one-shot parses over already-classified
+/// kinds; resident compilation classifies tokens on the GPU instead.
+pub(super) fn pair_header_plan(
+    caps: TokenCapacities,
+    token_kinds_u32: Option<&[u32]>,
+    n_kinds: u32,
+    tables: &PrecomputedParseTables,
+) -> BufferPlan {
+    let n_tokens = caps.n_tokens;
+    let input = caps.input as usize;
+    let tokens = n_tokens.max(1) as usize;
+    let blocks = caps.delimiter_blocks as usize;
+    let min_tree = caps.min_tree_base.saturating_mul(2) as usize;
+
+    let stream_has_soi = token_kinds_u32
+        .map(|kinds| kinds.first().copied() == Some(0))
+        .unwrap_or(true);
+    let first_input = if n_tokens > 1 && stream_has_soi { 1 } else { 0 };
+    // Match the canonical LL(1) stream: the last token is the EOF sentinel and is not
+    // consumed as ordinary input.
+    let n_input_tokens = n_tokens.saturating_sub(1).saturating_sub(first_input);
+
+    let mut plan = BufferPlan::new();
+    plan.storage_u32s("parser.ll1_predict", non_empty(&tables.ll1_predict))
+        .storage_u32s("parser.ll1_prod_rhs_off", non_empty(&tables.prod_rhs_off))
+        .storage_u32s("parser.ll1_prod_rhs_len", non_empty(&tables.prod_rhs_len))
+        .storage_u32s("parser.ll1_prod_rhs", non_empty(&tables.prod_rhs))
+        .storage::<u32>("parser.ll1_emit", 1)
+        .storage::<u32>("parser.ll1_emit_pos", 1)
+        .storage::<u32>("parser.ll1_status", 6)
+        .storage_u32s("parser.token_count", &[n_input_tokens]);
+    match token_kinds_u32 {
+        // Test/debug one-shot parsing receives already-classified parser
+        // token kinds. Resident compilation fills this buffer on the GPU
+        // with `tokens_to_kinds` instead.
+        Some(kinds) => plan
+            .storage_u32s("parser.semantic_token_kinds.input", kinds)
+            .storage_u32s("parser.token_feature_flags.conservative", &[u32::MAX]),
+        None => plan
+            .storage::<u32>("parser.semantic_token_kinds", n_tokens as usize)
+            .storage_u32s("parser.token_feature_flags", &[0]),
+    };
+    plan.uniform(
+        "parser.token_delimiters.params",
+        &TokenDelimiterParams {
+            n_tokens: caps.input,
+            n_blocks: caps.delimiter_blocks,
+            scan_step: 0,
+        },
+    )
+    .uniform(
+        "parser.token_brace_match.params",
+        &TokenBraceMatchParams {
+            n_tokens: caps.input,
+        },
+    )
+    .storage::<i32>("parser.token_depth_paren_inblock", input)
+    .storage::<i32>("parser.token_depth_brace_inblock", input)
+    .storage::<i32>("parser.token_depth_bracket_inblock", tokens)
+    .storage::<i32>("parser.token_depth_angle_inblock", input)
+    .storage::<i32>("parser.token_block_sum_paren", blocks)
+    .storage::<i32>("parser.token_block_sum_brace", blocks)
+    .storage::<i32>("parser.token_block_sum_bracket", blocks)
+    .storage::<i32>("parser.token_block_sum_angle", blocks)
+    .storage::<i32>("parser.token_prefix_paren_a", blocks)
+    .storage::<i32>("parser.token_prefix_paren_b", blocks)
+    .storage::<i32>("parser.token_block_prefix_paren", blocks)
+    .storage::<i32>("parser.token_prefix_brace_a", blocks)
+    .storage::<i32>("parser.token_prefix_brace_b", blocks)
+    .storage::<i32>("parser.token_block_prefix_brace", blocks)
+    .storage::<i32>("parser.token_prefix_bracket_a", blocks)
+    .storage::<i32>("parser.token_prefix_bracket_b", blocks)
+    .storage::<i32>("parser.token_block_prefix_bracket", blocks)
+    .storage::<i32>("parser.token_prefix_angle_a", blocks)
+    .storage::<i32>("parser.token_prefix_angle_b", blocks)
+    .storage::<i32>("parser.token_block_prefix_angle", blocks)
+    .storage::<u32>("parser.token_top_brace_owner_block", blocks)
+    .storage::<u32>("parser.token_top_brace_owner_prefix_a", blocks)
+    .storage::<u32>("parser.token_top_brace_owner_prefix_b", blocks)
+    .storage::<u32>("parser.token_top_brace_owner_block_prefix", blocks)
+    .storage::<u32>("parser.token_statement_event_block", blocks)
+    .storage::<u32>("parser.token_statement_event_prefix_a", blocks)
+    .storage::<u32>("parser.token_statement_event_prefix_b", blocks)
+    .storage::<u32>("parser.token_statement_event_block_prefix", blocks)
+    .storage::<u32>("parser.token_brace_semantic_kind", tokens)
+    .storage::<u32>("parser.token_braced_rhs_statement_kind", input)
+    .storage::<u32>("parser.token_bracket_semantic_kind", input)
+    .storage::<u32>("parser.token_statement_context_kind", input)
+    .storage::<u32>("parser.token_impl_header_kind", input)
+    .storage::<u32>("parser.token_impl_context_event", input)
+    .storage::<u32>("parser.token_type_path_context_kind", input)
+    .storage::<u32>("parser.token_where_context_event", input)
+    .storage::<u32>("parser.token_match_pattern_context_event", input)
+    .storage::<i32>("parser.token_generic_shr.block_sum", blocks)
+    .storage::<i32>("parser.token_generic_shr.block_min", blocks)
+    .storage::<i32>("parser.token_generic_shr.prefix_sum_a", blocks)
+    .storage::<i32>("parser.token_generic_shr.prefix_sum_b", blocks)
+    .storage::<i32>("parser.token_generic_shr.prefix_min_a", blocks)
+    .storage::<i32>("parser.token_generic_shr.prefix_min_b", blocks)
+    .storage::<i32>("parser.token_generic_shr.block_prefix_sum", blocks)
+    .storage::<i32>("parser.token_generic_shr.block_prefix_min", blocks)
+    .storage::<i32>("parser.token_brace_match_depth", input)
+    .storage::<i32>("parser.token_brace_match_block_min", blocks)
+    .storage::<i32>("parser.token_brace_match_min_tree", min_tree)
+    .storage::<i32>("parser.token_bracket_match_depth", input)
+    .storage::<i32>("parser.token_bracket_match_block_min", blocks)
+    .storage::<i32>("parser.token_bracket_match_min_tree", min_tree)
+    .storage::<i32>("parser.token_paren_match_depth", input)
+    .storage::<i32>("parser.token_paren_match_block_min", blocks)
+    .storage::<i32>("parser.token_paren_match_min_tree", min_tree)
+    .storage::<i32>("parser.token_angle_match_depth", input)
+    .storage::<i32>("parser.token_angle_match_block_min", blocks)
+    .storage::<i32>("parser.token_angle_match_min_tree", min_tree)
+    .uniform(
+        "parser.params_llp",
+        &super::super::passes::llp_pairs::LLPParams { n_tokens, n_kinds },
+    )
+    .storage_u32s("parser.kind_remap", &tables.kind_lookup_words())
+    .storage::<ActionHeader>("parser.out_headers", caps.pairs + 1);
+    plan
+}
+
+/// Sizes of the packed streams and the stack-effect validation scratch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct StackEffectSizing {
+    /// Adjacent token pairs, at least one.
+    pub pairs: usize,
+    /// Packed totals the streams are sized for.
+    pub totals: PackTotals,
+    /// Emit-stream slots.
+    pub emit_capacity: u32,
+    /// Bracket match-table slots.
+    pub match_capacity: u32,
+}
+
+impl StackEffectSizing {
+    /// Stack-change slots, at least one.
+    pub fn bracket_capacity(&self) -> u32 {
+        self.totals.sc.max(1)
+    }
+
+    /// 256-change blocks covering the stack-change stream.
+    pub fn bracket_blocks(&self) -> u32 {
+        self.totals.sc.div_ceil(256).max(1)
+    }
+
+    /// Leaf count of the bracket block-minimum tree.
+    pub fn min_tree_base(&self) -> u32 {
+        next_power_of_two_u32(self.bracket_blocks()).max(1)
+    }
+}
+
+/// Declares the pack offsets, packed streams, and bracket validation buffers.
+pub(super) fn stack_effect_plan(sizing: StackEffectSizing, tables_blob: &[u32]) -> BufferPlan {
+    let pairs = sizing.pairs;
+    let brackets = sizing.bracket_capacity() as usize;
+    let blocks = sizing.bracket_blocks() as usize;
+    let emits = sizing.emit_capacity as usize;
+
+    let mut plan = BufferPlan::new();
+    plan.storage::<u32>("pack.sc_offsets", pairs)
+        .storage::<u32>("pack.emit_offsets", pairs)
+        .storage::<u32>("pack.sc_prefix_a", pairs)
+        .storage::<u32>("pack.sc_prefix_b", pairs)
+        .storage::<u32>("pack.emit_prefix_a", pairs)
+        .storage::<u32>("pack.emit_prefix_b", pairs)
+        .storage::<u32>("pack.partial_parse_status", 6)
+        .storage_u32s("pack.tables_blob", non_empty(tables_blob))
+        .storage::<u32>("pack.out_sc", brackets)
+        .storage::<u32>("pack.out_emit", emits)
+        .storage::<u32>("pack.out_emit_pos", emits)
+        .storage::<i32>(
+            "brackets.min_tree",
+            sizing.min_tree_base().saturating_mul(2) as usize,
+        )
+        .storage::<i32>("brackets.exscan_inblock", brackets)
+        .storage::<i32>("brackets.block_sum", blocks)
+        .storage::<i32>("brackets.block_minpref", blocks)
+        .storage::<i32>("brackets.block_row_min", blocks)
+        .storage::<i32>("brackets.block_maxdepth", blocks)
+        .storage::<i32>("brackets.block_prefix", blocks)
+        .storage::<i32>("brackets.block_prefix_sum_a", blocks)
+        .storage::<i32>("brackets.block_prefix_sum_b", blocks)
+        .storage::<i32>("brackets.block_prefix_min_a", blocks)
+        .storage::<i32>("brackets.block_prefix_min_b", blocks)
+        .storage::<i32>("brackets.depths_out", 3)
+        .storage::<u32>("brackets.valid_out", 1)
+        .storage::<u32>("brackets.frontier", 2)
+        .storage::<u32>("brackets.layer", brackets)
+        .storage::<u32>("brackets.match_for_index", sizing.match_capacity as usize);
+    plan
+}
+
+/// Buffers and stream sizes of the pair-header, packed-stream, and
+/// stack-effect stages of a production resident parse.
+pub(crate) struct ResidentPairPlan {
+    pub buffers: BufferPlan,
+    /// Token slots, sentinels included.
+    pub n_tokens: u32,
+    /// Worst-case stack changes the packed stream is sized for.
+    pub stack_changes: u32,
+}
+
+/// Plans the buffers a resident parse of `token_capacity` lexer tokens
+/// allocates before its tree stage, sized as [`super::ParserBuffers`] sizes
+/// them without debug HIR retention.
+pub(crate) fn resident_pair_plan(
+    token_capacity: u32,
+    tables: &PrecomputedParseTables,
+) -> Result<ResidentPairPlan, LimitError> {
+    let n_tokens = resident_token_slots(token_capacity)?;
+    let totals = resident_pack_totals(n_tokens.saturating_sub(1), tables)?;
+    let tree_capacity = narrow_u32(
+        "parser tree capacity",
+        resident_partial_parse_tree_capacity(totals.emit).into(),
+        MAX_EMITS,
+    )?;
+    let caps = TokenCapacities::new(n_tokens);
+    let sizing = StackEffectSizing {
+        pairs: caps.pairs,
+        totals,
+        emit_capacity: tree_capacity,
+        match_capacity: tree_capacity,
+    };
+    let mut buffers = pair_header_plan(caps, None, tables.n_kinds, tables);
+    buffers.append(stack_effect_plan(sizing, &tables.to_gpu_blob().words));
+    Ok(ResidentPairPlan {
+        buffers,
+        n_tokens,
+        stack_changes: totals.sc,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu::buffers::{BufferKind, storage_element_size};
+
+    /// Storage sizes as `new_with_sizing` computed them inline before the plan.
+    fn legacy_storage_bytes(label: &str, n_tokens: u32, one_shot: bool) -> Option<u64> {
+        let token_input_capacity = n_tokens.saturating_sub(2).max(1) as u64;
+        let n_blocks = (token_input_capacity as u32).div_ceil(256).max(1);
+        let min_tree = u64::from(n_blocks.next_power_of_two().max(1)) * 2;
+        let pair_capacity = (n_tokens.saturating_sub(1) as u64).max(1);
+        let words = match label {
+            "parser.ll1_emit" | "parser.ll1_emit_pos" => 1,
+            "parser.ll1_status" => 6,
+            "parser.semantic_token_kinds" if !one_shot => u64::from(n_tokens),
+            "parser.token_depth_bracket_inblock" | "parser.token_brace_semantic_kind" => {
+                u64::from(n_tokens.max(1))
+            }
+            "parser.out_headers" => {
+                let header = storage_element_size::<ActionHeader>() as u64;
+                return Some((pair_capacity + 1) * header);
+            }
+            l if l.ends_with("_min_tree") => min_tree,
+            l if l.ends_with("_inblock") => token_input_capacity,
+            l if l.contains("block")
+                || l.contains("prefix_")
+                || l.starts_with("parser.token_generic_shr.") =>
+            {
+                u64::from(n_blocks)
+            }
+            l if l.starts_with("parser.token_") => token_input_capacity,
+            _ => return None,
+        };
+        Some(words * 4)
+    }
+
+    #[test]
+    fn planned_sizes_match_legacy_sizing_across_token_counts() {
+        let tables = PrecomputedParseTables::new(4, 1);
+        for n_tokens in [0, 1, 2, 3, 257, 258, 259, 4096, 70_000, 1 << 20] {
+            let kinds: Vec<u32> = (0..n_tokens).collect();
+            for token_kinds in [None, Some(&kinds[..])] {
+                let plan =
+                    pair_header_plan(TokenCapacities::new(n_tokens), token_kinds, 4, &tables);
+                if token_kinds.is_none() && n_tokens == 0 {
+                    // Resident streams always carry both sentinels, so no slots is a bug.
+                    assert!(plan.validate(&wgpu::Limits::default()).is_err());
+                    continue;
+                }
+                plan.validate(&wgpu::Limits::default()).unwrap();
+                let mut storage = 0;
+                for entry in plan.entries() {
+                    if entry.kind == BufferKind::Uniform || entry.init().is_some() {
+                        continue;
+                    }
+                    storage += 1;
+                    assert_eq!(
+                        Some(entry.byte_size),
+                        legacy_storage_bytes(&entry.label, n_tokens, token_kinds.is_some()),
+                        "{} at n_tokens={n_tokens}",
+                        entry.label
+                    );
+                }
+                assert_eq!(storage, if token_kinds.is_some() { 61 } else { 62 });
+            }
+        }
+    }
+
+    #[test]
+    fn uploads_keep_one_word_tables_and_the_input_token_count() {
+        let tables = PrecomputedParseTables::new(4, 1);
+        let kinds = [0, 5, 6, 7, 1];
+        let plan = pair_header_plan(TokenCapacities::new(5), Some(&kinds), 4, &tables);
+        let init = |label: &str| plan.entry(label).and_then(|e| e.init()).unwrap().to_vec();
+        assert_eq!(init("parser.ll1_predict"), vec![0; 4]);
+        // SOI is skipped and EOF is not consumed as input.
+        assert_eq!(init("parser.token_count"), 3u32.to_le_bytes());
+        assert_eq!(
+            init("parser.token_feature_flags.conservative"),
+            u32::MAX.to_le_bytes()
+        );
+        assert_eq!(init("parser.semantic_token_kinds.input").len(), 20);
+
+        let resident = pair_header_plan(TokenCapacities::new(5), None, 4, &tables);
+        assert!(
+            resident
+                .entry("parser.semantic_token_kinds.input")
+                .is_none()
+        );
+        assert_eq!(
+            resident.entry("parser.token_count").unwrap().init(),
+            Some(&3u32.to_le_bytes()[..])
+        );
+    }
+}
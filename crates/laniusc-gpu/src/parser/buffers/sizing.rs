@@ -0,0 +1,386 @@
+use super::scans::next_power_of_two_u32;
+use crate::{
+    gpu::limits::{LimitError, MAX_EMITS, MAX_PARSER_TOKENS, MAX_STACK_CHANGES, narrow_u32},
+    lexer::features::{
+        CONSERVATIVE_PARSER_FEATURES,
+        PARSER_FEATURE_ARRAYS,
+        PARSER_FEATURE_ENUMS,
+        PARSER_FEATURE_MATCHES,
+        PARSER_FEATURE_STRUCTS,
+    },
+    parser::tables::PrecomputedParseTables,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct ParserFamilyCapacities {
+    pub arrays: u32,
+    pub enum_match: u32,
+    pub structs: u32,
+}
+
+impl ParserFamilyCapacities {
+    pub(super) fn new(tree_capacity: u32, parser_feature_flags: u32) -> Self {
+        let tree_capacity = tree_capacity.max(1);
+        Self {
+            arrays: feature_capacity(tree_capacity, parser_feature_flags, PARSER_FEATURE_ARRAYS),
+            enum_match: feature_capacity(
+                tree_capacity,
+                parser_feature_flags,
+                PARSER_FEATURE_ENUMS | PARSER_FEATURE_MATCHES,
+            ),
+            structs: feature_capacity(tree_capacity, parser_feature_flags, PARSER_FEATURE_STRUCTS),
+        }
+    }
+}
+
+/// Token-stream capacities shared by the pair-header plan and the token scans.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct TokenCapacities {
+    /// Token slots including the SOI/EOF sentinels.
+    pub n_tokens: u32,
+    /// Input tokens between the sentinels, at least one.
+    pub input: u32,
+    /// 256-token blocks covering `input`.
+    pub delimiter_blocks: u32,
+    /// Adjacent token pairs, at least one.
+    pub pairs: usize,
+    /// Leaf count of the block-minimum trees used by delimiter matching.
+    pub min_tree_base: u32,
+}
+
+impl TokenCapacities {
+    pub(super) fn new(n_tokens: u32) -> Self {
+        let input = n_tokens.saturating_sub(2).max(1);
+        let delimiter_blocks = input.div_ceil(256).max(1);
+        Self {
+            n_tokens,
+            input,
+            delimiter_blocks,
+            pairs: (n_tokens.saturating_sub(1) as usize).max(1),
+            min_tree_base: next_power_of_two_u32(delimiter_blocks).max(1),
+        }
+    }
+}
+
+/// Slot counts and family switches for one `ParserBuffers` allocation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct ParserBufferSizing {
+    /// Token slots including the SOI/EOF sentinels.
+    pub n_tokens: u32,
+    /// Source bytes the string-literal families are sized for.
+    pub source_capacity: u32,
+    /// Size the packed stream, bracket, and tree families as empty.
+    pub headers_only: bool,
+    /// Size packed streams and emits for the worst case of a resident parse.
+    pub resident_partial_parse_capacity: bool,
+    /// Keep the HIR buffers only debug readback uses.
+    pub retain_debug_hir_buffers: bool,
+    /// Tree rows to allocate instead of the capacity derived from the tables.
+    pub tree_capacity_override: Option<u32>,
+    /// Lexer feature flags that gate the optional parser families.
+    pub parser_feature_flags: u32,
+}
+
+impl ParserBufferSizing {
+    /// One-shot sizing for `n_slots` token slots with debug HIR retained.
+    pub(super) fn one_shot(n_slots: usize) -> Result<Self, LimitError> {
+        let n_tokens = one_shot_token_slots(n_slots)?;
+        Ok(Self {
+            n_tokens,
+            source_capacity: n_tokens,
+            headers_only: false,
+            resident_partial_parse_capacity: false,
+            retain_debug_hir_buffers: true,
+            tree_capacity_override: None,
+            parser_feature_flags: CONSERVATIVE_PARSER_FEATURES,
+        })
+    }
+
+    /// Resident sizing for `token_capacity` lexer tokens and `source_capacity` bytes.
+    pub(super) fn resident(
+        token_capacity: u32,
+        source_capacity: u32,
+        tree_capacity_override: Option<u32>,
+        retain_debug_hir_buffers: bool,
+        parser_feature_flags: u32,
+    ) -> Result<Self, LimitError> {
+        Ok(Self {
+            n_tokens: resident_token_slots(token_capacity)?,
+            source_capacity,
+            headers_only: false,
+            resident_partial_parse_capacity: true,
+            retain_debug_hir_buffers,
+            tree_capacity_override,
+            parser_feature_flags,
+        })
+    }
+}
+
+fn feature_capacity(tree_capacity: u32, parser_feature_flags: u32, mask: u32) -> u32 {
+    if parser_feature_flags & mask == 0 {
+        1
+    } else {
+        tree_capacity
+    }
+}
+
+/// Packed stack-change and emit totals of one parse, within the GPU limits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(super) struct PackTotals {
+    pub sc: u32,
+    pub emit: u32,
+}
+
+/// Token slots of a one-shot parse input, sentinels included.
+pub(super) fn one_shot_token_slots(n_slots: usize) -> Result<u32, LimitError> {
+    narrow_u32("parser token slots", n_slots as u64, MAX_PARSER_TOKENS + 2)
+}
+
+/// Token slots of a resident parse sized for `token_capacity` lexer tokens.
+pub(super) fn resident_token_slots(token_capacity: u32) -> Result<u32, LimitError> {
+    Ok(narrow_u32(
+        "parser tokens",
+        u64::from(token_capacity),
+        MAX_PARSER_TOKENS,
+    )? + 2)
+}
+
+/// Sums the table lengths of every adjacent pair of `token_kinds`, in `u64`.
+/// Pairs with a kind outside the grid contribute nothing, as on the GPU.
+pub(super) fn one_shot_pack_totals(
+    token_kinds: &[u32],
+    tables: &PrecomputedParseTables,
+) -> Result<PackTotals, LimitError> {
+    let n_kinds = tables.n_kinds;
+    let (mut sc, mut emit) = (0u64, 0u64);
+    for pair in token_kinds.windows(2) {
+        let prev = tables.grid_kind(pair[0]);
+        let this = tables.grid_kind(pair[1]);
+        if prev >= n_kinds || this >= n_kinds {
+            continue;
+        }
+        let idx2d = (prev as usize) * (n_kinds as usize) + (this as usize);
+        sc += u64::from(tables.sc_len[idx2d]);
+        emit += u64::from(tables.pp_len[idx2d]);
+    }
+    pack_totals(sc, emit)
+}
+
+/// Worst-case totals of a resident parse: every pair at the widest virtual
+/// pair of the tables.
+pub(super) fn resident_pack_totals(
+    n_pairs: u32,
+    tables: &PrecomputedParseTables,
+) -> Result<PackTotals, LimitError> {
+    let max_sc_len = resident_virtual_pair_width(&tables.sc_len, tables.n_kinds);
+    let max_emit_len = resident_virtual_pair_width(&tables.pp_len, tables.n_kinds);
+    pack_totals(
+        u64::from(n_pairs) * u64::from(max_sc_len),
+        u64::from(n_pairs) * u64::from(max_emit_len),
+    )
+}
+
+fn pack_totals(sc: u64, emit: u64) -> Result<PackTotals, LimitError> {
+    Ok(PackTotals {
+        sc: narrow_u32("parser stack changes", sc, MAX_STACK_CHANGES)?,
+        emit: narrow_u32("parser partial-parse emits", emit, MAX_EMITS)?,
+    })
+}
+
+/// Derives resident tree capacity from token count and partial-parse emit width.
+///
+/// Fails with [`LimitError::InputTooLarge`] when the worst-case emit total of
+/// `n_tokens` exceeds [`MAX_EMITS`].
+pub(crate) fn resident_partial_parse_tree_capacity_for_tables(
+    n_tokens: u32,
+    tables: &PrecomputedParseTables,
+) -> Result<u32, LimitError> {
+    let totals = resident_pack_totals(n_tokens.saturating_sub(1), tables)?;
+    Ok(resident_partial_parse_tree_capacity(totals.emit))
+}
+
+/// Maximum table width emitted by one physical adjacent pair. A contextual
+/// `>>` concatenates `(previous, inner-close)` and
+/// `(inner-close, outer-close)`; no other physical pair expands.
+pub(super) fn resident_virtual_pair_width(widths: &[u32], n_kinds: u32) -> u32 {
+    let mut maximum = u64::from(widths.iter().copied().max().unwrap_or(0));
+    const GENERIC_CLOSE_KINDS: [u32; 4] = [132, 134, 176, 185];
+    for previous in 0..n_kinds {
+        for inner in GENERIC_CLOSE_KINDS {
+            for outer in GENERIC_CLOSE_KINDS {
+                let first = widths
+                    .get((previous * n_kinds + inner) as usize)
+                    .copied()
+                    .unwrap_or(0);
+                let second = widths
+                    .get((inner * n_kinds + outer) as usize)
+                    .copied()
+                    .unwrap_or(0);
+                maximum = maximum.max(u64::from(first) + u64::from(second));
+            }
+        }
+    }
+    // Two table entries; valid tables keep each far below `u32::MAX / 2`.
+    u32::try_from(maximum).expect("contextual pair width exceeds u32")
+}
+
+/// Normalizes resident tree capacity to at least one row.
+pub(super) fn resident_partial_parse_tree_capacity(total_emit: u32) -> u32 {
+    total_emit.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn resident_tree_capacity_is_capacity_derived_and_bounded() {
+        assert_eq!(resident_partial_parse_tree_capacity(1_000_000), 1_000_000);
+        assert_eq!(resident_partial_parse_tree_capacity(25_000), 25_000);
+        assert_eq!(resident_partial_parse_tree_capacity(0), 1);
+    }
+
+    #[test]
+    fn resident_tree_capacity_from_tables_is_bounded_by_partial_parse_table() {
+        let mut tables = PrecomputedParseTables::new(4, 1);
+        tables.pp_len = vec![0, 7, 3, 1];
+
+        assert_eq!(
+            resident_partial_parse_tree_capacity_for_tables(10_000, &tables),
+            Ok(69_993)
+        );
+    }
+
+    /// Two-kind tables whose only pair, kind 1 after kind 1, has the given
+    /// lengths; no stream data backs them, so nothing large is allocated.
+    fn wide_pair_tables(sc_len: u32, pp_len: u32) -> PrecomputedParseTables {
+        let mut tables = PrecomputedParseTables::new(2, 1);
+        tables.sc_len = vec![0, 0, 0, sc_len];
+        tables.pp_len = vec![0, 0, 0, pp_len];
+        tables
+    }
+
+    #[test]
+    fn token_slot_limits_reject_counts_past_u32() {
+        assert_eq!(one_shot_token_slots(7), Ok(7));
+        assert_eq!(one_shot_token_slots(u32::MAX as usize), Ok(u32::MAX));
+        assert_eq!(
+            one_shot_token_slots(u32::MAX as usize + 1),
+            Err(LimitError::InputTooLarge {
+                what: "parser token slots",
+                value: u64::from(u32::MAX) + 1,
+                limit: u64::from(u32::MAX),
+            })
+        );
+        assert_eq!(resident_token_slots(MAX_PARSER_TOKENS), Ok(u32::MAX));
+        assert!(resident_token_slots(MAX_PARSER_TOKENS + 1).is_err());
+    }
+
+    #[test]
+    fn one_shot_totals_detect_stack_change_and_emit_overflow() {
+        let half = u32::MAX / 2 + 1;
+        assert_eq!(
+            one_shot_pack_totals(&[1, 1, 1], &wide_pair_tables(half - 1, 3)),
+            Ok(PackTotals {
+                sc: 2 * (half - 1),
+                emit: 6,
+            })
+        );
+        assert_eq!(
+            one_shot_pack_totals(&[1, 1, 1], &wide_pair_tables(half, 3)),
+            Err(LimitError::InputTooLarge {
+                what: "parser stack changes",
+                value: 2 * u64::from(half),
+                limit: u64::from(MAX_STACK_CHANGES),
+            })
+        );
+        assert_eq!(
+            one_shot_pack_totals(&[1, 1, 1], &wide_pair_tables(3, half)),
+            Err(LimitError::InputTooLarge {
+                what: "parser partial-parse emits",
+                value: 2 * u64::from(half),
+                limit: u64::from(MAX_EMITS),
+            })
+        );
+        // A sentinel pair outside the grid adds nothing.
+        assert_eq!(
+            one_shot_pack_totals(&[7, 1], &wide_pair_tables(half, half)),
+            Ok(PackTotals::default())
+        );
+    }
+
+    #[test]
+    fn resident_totals_detect_worst_case_overflow() {
+        let pairs = MAX_EMITS / 2;
+        assert_eq!(
+            resident_pack_totals(pairs, &wide_pair_tables(1, 2)),
+            Ok(PackTotals {
+                sc: pairs,
+                emit: MAX_EMITS,
+            })
+        );
+        assert!(matches!(
+            resident_pack_totals(pairs, &wide_pair_tables(4, 2)),
+            Err(LimitError::InputTooLarge {
+                what: "parser stack changes",
+                ..
+            })
+        ));
+        assert!(resident_pack_totals(u32::MAX, &wide_pair_tables(1, 2)).is_err());
+    }
+
+    #[test]
+    fn resident_tree_capacity_errors_instead_of_saturating() {
+        assert!(matches!(
+            resident_partial_parse_tree_capacity_for_tables(u32::MAX, &wide_pair_tables(1, 2)),
+            Err(LimitError::InputTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn absent_optional_parser_families_use_sentinel_capacity() {
+        assert_eq!(
+            ParserFamilyCapacities::new(1_000_000, 0),
+            ParserFamilyCapacities {
+                arrays: 1,
+                enum_match: 1,
+                structs: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn present_optional_parser_families_retain_full_tree_address_space() {
+        assert_eq!(
+            ParserFamilyCapacities::new(1_000_000, PARSER_FEATURE_ARRAYS),
+            ParserFamilyCapacities {
+                arrays: 1_000_000,
+                enum_match: 1,
+                structs: 1,
+            }
+        );
+        assert_eq!(
+            ParserFamilyCapacities::new(1_000_000, PARSER_FEATURE_ENUMS),
+            ParserFamilyCapacities {
+                arrays: 1,
+                enum_match: 1_000_000,
+                structs: 1,
+            }
+        );
+        assert_eq!(
+            ParserFamilyCapacities::new(1_000_000, PARSER_FEATURE_MATCHES),
+            ParserFamilyCapacities {
+                arrays: 1,
+                enum_match: 1_000_000,
+                structs: 1,
+            }
+        );
+        assert_eq!(
+            ParserFamilyCapacities::new(1_000_000, PARSER_FEATURE_STRUCTS),
+            ParserFamilyCapacities {
+                arrays: 1,
+                enum_match: 1,
+                structs: 1_000_000,
+            }
+        );
+    }
+}
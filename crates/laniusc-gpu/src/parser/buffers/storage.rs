@@ -1,5 +1,7 @@
+use anyhow::{Context, Result};
+
 use crate::gpu::{
-    buffers::{LaniusBuffer, storage_rw_for_array},
+    buffers::{LaniusBuffer, PlannedBuffers, storage_rw_for_array},
     compiler_graph::{
         CompilerGraphBuilder,
         CompilerPhase,
@@ -104,6 +106,12 @@ pub(super) fn parser_phase_workspace_plan(tree_capacity: u32) -> WorkspacePlan {
         .clone()
 }
 
+/// Takes a buffer from a materialized parser plan, naming the plan in the
+/// error when the label or element type does not match.
+pub(super) fn planned<T>(buffers: &mut PlannedBuffers, label: &str) -> Result<LaniusBuffer<T>> {
+    buffers.take(label).context("parser buffer plan")
+}
+
 /// Reinterprets one typed storage buffer as another typed buffer with a new element count.
 pub(super) fn alias_storage_buffer<T, U>(
     source: &LaniusBuffer<T>,
@@ -0,0 +1,538 @@
+//! Bracket-match results reused across parses of an unchanged kind stream.
+//!
+//! Stack-effect validation reads only the stack-change stream, and the pack
+//! passes derive that stream from the parse tables and the kinds of adjacent
+//! tokens. Two inputs with the same kept kind sequence therefore have the
+//! same [`BracketsMatchResult`], whatever their bytes: editing a comment,
+//! whitespace or the spelling of an identifier cannot change it.
+//!
+//! A [`BracketCache`] attached with
+//! [`GpuParser::set_bracket_cache`](crate::parser::GpuParser::set_bracket_cache)
+//! keys each result by a hash of the table fingerprint and the kind stream
+//! the parser uploads. On a hit the driver writes the cached depths,
+//! validity, frontier and `match_for_index` into the parse's buffers and
+//! skips the stack-effect passes; the pack passes still run, since later
+//! passes read their output.
+//!
+//! Invalidation is by construction:
+//!
+//! - Any change to the kind stream (a token inserted, removed or of another
+//!   kind) or to the tables is a different key, so it misses.
+//! - Entries keep their full kind stream and a lookup compares it, so a hash
+//!   collision misses rather than returning another stream's matches.
+//! - `match_for_index` indexes the stack-change stream of one exact kind
+//!   stream, so entries are never patched for an edit; they age out instead.
+//!
+//! The cache holds at most [`BracketCacheLimits::max_entries`] entries of
+//! [`BracketCacheLimits::max_bytes`] in total, evicting the least recently
+//! used first. It is shared behind a mutex, so parsers on several threads
+//! may use one cache at once.
+//!
+//! Resident parses of the lexer's token buffers, which `compile_source`
+//! and `laniusc check` run, key the cache by a [`ResidentBracketKey`]. Its
+//! stream is the semantic kind stream followed by the token file ids, since
+//! a source-file boundary also changes the pair headers. Capacity
+//! measurement reads this stream back in the same submission, and the parse
+//! that follows consults the cache with it. Resident parses do not read back
+//! `match_for_index`, so their entries hold only the depths, validity and
+//! frontier; they are never shared with one-shot parses, which need the full
+//! match table.
+//!
+//! [`GpuCompiler`](crate::compiler::GpuCompiler) attaches a cache bounded by
+//! [`GpuCompilerOptions::bracket_cache`](crate::compiler::GpuCompilerOptions::bracket_cache)
+//! to its parser; one-shot [`GpuParser`](crate::parser::GpuParser)s use
+//! none unless one is attached.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+
+use crate::parser::{buffers::ParserBuffers, driver::BracketsMatchResult};
+
+/// Bounds on what a [`BracketCache`] keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BracketCacheLimits {
+    /// Most entries kept at once.
+    pub max_entries: usize,
+    /// Most bytes of kind streams and match tables kept at once; a result
+    /// larger than this on its own is never stored.
+    pub max_bytes: usize,
+}
+
+impl Default for BracketCacheLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: 64,
+            max_bytes: 64 << 20,
+        }
+    }
+}
+
+/// Counters a [`BracketCache`] keeps since it was created.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BracketCacheStats {
+    /// Lookups that found their kind stream.
+    pub hits: u64,
+    /// Lookups that did not.
+    pub misses: u64,
+    /// Entries dropped to stay within the limits.
+    pub evictions: u64,
+    /// Entries currently kept.
+    pub entries: usize,
+    /// Bytes currently kept, as counted against
+    /// [`BracketCacheLimits::max_bytes`].
+    pub bytes: usize,
+}
+
+/// Bracket-match results of recently parsed kind streams.
+#[derive(Debug, Default)]
+pub struct BracketCache {
+    limits: BracketCacheLimits,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: Vec<Entry>,
+    clock: u64,
+    stats: BracketCacheStats,
+}
+
+#[derive(Debug)]
+struct Entry {
+    key: u64,
+    table_fingerprint: u64,
+    kinds: Box<[u32]>,
+    brackets: Arc<BracketsMatchResult>,
+    last_used: u64,
+}
+
+impl Entry {
+    fn matches(&self, key: u64, table_fingerprint: u64, kinds: &[u32]) -> bool {
+        self.key == key && self.table_fingerprint == table_fingerprint && *self.kinds == *kinds
+    }
+
+    fn bytes(&self) -> usize {
+        entry_bytes(&self.kinds, &self.brackets)
+    }
+}
+
+fn entry_bytes(kinds: &[u32], brackets: &BracketsMatchResult) -> usize {
+    size_of::<Entry>()
+        + size_of::<BracketsMatchResult>()
+        + size_of_val(kinds)
+        + size_of_val(brackets.match_for_index.as_slice())
+}
+
+fn stream_key(table_fingerprint: u64, kinds: &[u32]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    table_fingerprint.hash(&mut hasher);
+    kinds.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl BracketCache {
+    /// Creates an empty cache bounded by `limits`.
+    pub fn new(limits: BracketCacheLimits) -> Self {
+        Self {
+            limits,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Returns the bounds this cache was created with.
+    pub fn limits(&self) -> BracketCacheLimits {
+        self.limits
+    }
+
+    /// Returns the hit, miss and eviction counters and the current size.
+    pub fn stats(&self) -> BracketCacheStats {
+        self.lock().stats
+    }
+
+    /// Drops every entry; the counters keep counting.
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.entries.clear();
+        state.stats.entries = 0;
+        state.stats.bytes = 0;
+    }
+
+    /// Returns the result cached for `kinds` under tables with
+    /// `table_fingerprint`, counting a hit or a miss.
+    pub(crate) fn get(
+        &self,
+        table_fingerprint: u64,
+        kinds: &[u32],
+    ) -> Option<Arc<BracketsMatchResult>> {
+        let key = stream_key(table_fingerprint, kinds);
+        let mut state = self.lock();
+        state.clock += 1;
+        let now = state.clock;
+        let found = state
+            .entries
+            .iter_mut()
+            .find(|entry| entry.matches(key, table_fingerprint, kinds))
+            .map(|entry| {
+                entry.last_used = now;
+                Arc::clone(&entry.brackets)
+            });
+        match found {
+            Some(_) => state.stats.hits += 1,
+            None => state.stats.misses += 1,
+        }
+        found
+    }
+
+    /// Stores the result of parsing `kinds`, replacing any entry for the same
+    /// stream and evicting the least recently used entries to fit.
+    pub(crate) fn insert(
+        &self,
+        table_fingerprint: u64,
+        kinds: &[u32],
+        brackets: BracketsMatchResult,
+    ) {
+        let bytes = entry_bytes(kinds, &brackets);
+        if self.limits.max_entries == 0 || bytes > self.limits.max_bytes {
+            return;
+        }
+        let key = stream_key(table_fingerprint, kinds);
+        let mut state = self.lock();
+        state.clock += 1;
+        let now = state.clock;
+        // Two parses of one stream may both miss; the later one replaces it.
+        if let Some(pos) = state
+            .entries
+            .iter()
+            .position(|entry| entry.matches(key, table_fingerprint, kinds))
+        {
+            let old = state.entries.swap_remove(pos);
+            state.stats.bytes -= old.bytes();
+        }
+        while state.entries.len() >= self.limits.max_entries
+            || state.stats.bytes + bytes > self.limits.max_bytes
+        {
+            let Some(oldest) = (0..state.entries.len()).min_by_key(|&i| state.entries[i].last_used)
+            else {
+                break;
+            };
+            let evicted = state.entries.swap_remove(oldest);
+            state.stats.bytes -= evicted.bytes();
+            state.stats.evictions += 1;
+        }
+        state.entries.push(Entry {
+            key,
+            table_fingerprint,
+            kinds: kinds.into(),
+            brackets: Arc::new(brackets),
+            last_used: now,
+        });
+        state.stats.bytes += bytes;
+        state.stats.entries = state.entries.len();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        self.state.lock().expect("BracketCache mutex poisoned")
+    }
+}
+
+/// Cache key of a resident parse, measured by
+/// [`GpuParser::measure_resident_partial_parse_capacity`](crate::parser::GpuParser::measure_resident_partial_parse_capacity)
+/// when a [`BracketCache`] is attached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResidentBracketKey {
+    table_fingerprint: u64,
+    stream: Box<[u32]>,
+    total_sc: u32,
+}
+
+impl ResidentBracketKey {
+    /// Keys the semantic kinds `kinds`, sentinels included, and the token
+    /// file ids `file_ids` of a stream with `total_sc` stack changes.
+    pub(crate) fn new(
+        table_fingerprint: u64,
+        kinds: &[u32],
+        file_ids: &[u32],
+        total_sc: u32,
+    ) -> Self {
+        Self {
+            table_fingerprint,
+            stream: kinds.iter().chain(file_ids).copied().collect(),
+            total_sc,
+        }
+    }
+}
+
+/// Byte offset of the stack-effect words in a resident status readback that
+/// fills the cache; the parser status words come first.
+pub(crate) const RESIDENT_BRACKET_READBACK_OFFSET: u64 = 32;
+
+/// Bytes of stack-effect words a resident miss reads back: final and minimum
+/// depth, validity, then the two frontier words.
+pub(crate) const RESIDENT_BRACKET_READBACK_BYTES: u64 = 20;
+
+/// A resident parse that missed the cache and stores its own result once its
+/// status readback is mapped.
+#[derive(Debug)]
+pub(crate) struct ResidentBracketFill {
+    cache: Arc<BracketCache>,
+    key: ResidentBracketKey,
+}
+
+impl ResidentBracketFill {
+    /// Copies the words [`ResidentBracketFill::store`] decodes into
+    /// `readback` at [`RESIDENT_BRACKET_READBACK_OFFSET`].
+    pub(crate) fn record_readback(
+        encoder: &mut wgpu::CommandEncoder,
+        bufs: &ParserBuffers,
+        readback: &wgpu::Buffer,
+    ) {
+        let offset = RESIDENT_BRACKET_READBACK_OFFSET;
+        crate::gpu::passes_core::flush_deferred_compute(encoder);
+        encoder.copy_buffer_to_buffer(&bufs.depths_out, 0, readback, offset, 8);
+        encoder.copy_buffer_to_buffer(&bufs.valid_out, 0, readback, offset + 8, 4);
+        encoder.copy_buffer_to_buffer(&bufs.bracket_frontier, 0, readback, offset + 12, 8);
+    }
+
+    /// Decodes the stack-effect words of a mapped status readback and stores
+    /// them under the parse's key.
+    pub(crate) fn store(&self, mapped: &[u8]) -> Result<()> {
+        let start = RESIDENT_BRACKET_READBACK_OFFSET as usize;
+        let end = start + RESIDENT_BRACKET_READBACK_BYTES as usize;
+        let [
+            final_depth,
+            min_depth,
+            valid,
+            first_negative_inverted,
+            unclosed_plus_one,
+        ] = crate::gpu::readback::read_u32_words::<5>(
+            mapped.get(start..end).unwrap_or_default(),
+            "parser.recorded-ll1-hir.brackets",
+        )?;
+        let valid_up_to = if first_negative_inverted == 0 {
+            self.key.total_sc
+        } else {
+            !first_negative_inverted
+        };
+        self.cache.insert(
+            self.key.table_fingerprint,
+            &self.key.stream,
+            BracketsMatchResult {
+                valid: valid != 0,
+                final_depth: final_depth as i32,
+                min_depth: min_depth as i32,
+                match_for_index: Vec::new(),
+                valid_up_to,
+                first_unclosed_push: unclosed_plus_one.checked_sub(1),
+            },
+        );
+        Ok(())
+    }
+}
+
+/// Looks `key` up for a resident parse into `bufs` under tables with
+/// `table_fingerprint`. A hit is uploaded with [`upload_cached`]; a miss
+/// returns the fill that stores the parse's own result. A key measured under
+/// other tables, or buffers that emit `match_for_index`, do neither.
+pub(crate) fn consult_resident(
+    cache: &Arc<BracketCache>,
+    queue: &wgpu::Queue,
+    bufs: &mut ParserBuffers,
+    table_fingerprint: u64,
+    key: &ResidentBracketKey,
+) -> Option<ResidentBracketFill> {
+    if key.table_fingerprint != table_fingerprint || bufs.emit_stack_matches {
+        return None;
+    }
+    match cache.get(key.table_fingerprint, &key.stream) {
+        Some(brackets) => {
+            upload_cached(queue, bufs, &brackets, key.total_sc);
+            None
+        }
+        None => Some(ResidentBracketFill {
+            cache: Arc::clone(cache),
+            key: key.clone(),
+        }),
+    }
+}
+
+/// Writes `brackets` into the buffers stack-effect validation fills, in the
+/// layout the readback decodes, and marks the passes that fill them skipped.
+/// `total_sc` is the length of the stack-change stream the result covers.
+pub(crate) fn upload_cached(
+    queue: &wgpu::Queue,
+    bufs: &mut ParserBuffers,
+    brackets: &BracketsMatchResult,
+    total_sc: u32,
+) {
+    // Word 2 is the scan's active-layer bound, read only by the skipped passes.
+    let depths = [brackets.final_depth, brackets.min_depth, 0];
+    let first_negative_inverted = if brackets.valid_up_to >= total_sc {
+        0
+    } else {
+        !brackets.valid_up_to
+    };
+    let unclosed_plus_one = brackets.first_unclosed_push.map_or(0, |push| push + 1);
+    let frontier = [first_negative_inverted, unclosed_plus_one];
+    let bytes = |words: &[u32]| -> Vec<u8> { words.iter().flat_map(|w| w.to_le_bytes()).collect() };
+    let depth_bytes: Vec<u8> = depths.iter().flat_map(|d| d.to_le_bytes()).collect();
+    queue.write_buffer(&bufs.depths_out, 0, &depth_bytes);
+    queue.write_buffer(&bufs.valid_out, 0, &u32::from(brackets.valid).to_le_bytes());
+    queue.write_buffer(&bufs.bracket_frontier, 0, &bytes(&frontier));
+    if bufs.emit_stack_matches && !brackets.match_for_index.is_empty() {
+        queue.write_buffer(&bufs.match_for_index, 0, &bytes(&brackets.match_for_index));
+    }
+    bufs.stack_effect_cached = true;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brackets(matches: usize) -> BracketsMatchResult {
+        BracketsMatchResult {
+            valid: true,
+            final_depth: 0,
+            min_depth: 0,
+            match_for_index: (0..matches as u32).rev().collect(),
+            valid_up_to: matches as u32,
+            first_unclosed_push: None,
+        }
+    }
+
+    #[test]
+    fn lookups_compare_tables_and_the_whole_kind_stream() {
+        let cache = BracketCache::default();
+        cache.insert(1, &[3, 4, 5], brackets(2));
+        assert_eq!(cache.get(1, &[3, 4, 5]).as_deref(), Some(&brackets(2)));
+        assert!(cache.get(2, &[3, 4, 5]).is_none());
+        assert!(cache.get(1, &[3, 4]).is_none());
+        assert!(cache.get(1, &[3, 5, 4]).is_none());
+
+        // Parsing the same stream again replaces the entry.
+        cache.insert(1, &[3, 4, 5], brackets(2));
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 3, 1));
+        assert_eq!(stats.bytes, entry_bytes(&[3, 4, 5], &brackets(2)));
+    }
+
+    #[test]
+    fn resident_fills_decode_depths_validity_and_frontier() {
+        let cache = Arc::new(BracketCache::default());
+        let key = ResidentBracketKey::new(7, &[0, 3, 4, 0], &[0, 0], 6);
+        let fill = ResidentBracketFill {
+            cache: Arc::clone(&cache),
+            key: key.clone(),
+        };
+        let readback = |words: [u32; 5]| -> Vec<u8> {
+            let mut bytes = vec![0; RESIDENT_BRACKET_READBACK_OFFSET as usize];
+            bytes.extend(words.iter().flat_map(|w| w.to_le_bytes()));
+            bytes
+        };
+
+        // Overclosed at stack change 2, with push 4 left open.
+        fill.store(&readback([(-1i32) as u32, (-2i32) as u32, 0, !2, 5]))
+            .expect("decode");
+        let stored = cache.get(7, &key.stream).expect("stored");
+        assert_eq!(
+            *stored,
+            BracketsMatchResult {
+                valid: false,
+                final_depth: -1,
+                min_depth: -2,
+                match_for_index: Vec::new(),
+                valid_up_to: 2,
+                first_unclosed_push: Some(4),
+            }
+        );
+
+        // A zero frontier is valid through the whole stream.
+        fill.store(&readback([0, 0, 1, 0, 0])).expect("decode");
+        let stored = cache.get(7, &key.stream).expect("stored");
+        assert!(stored.valid);
+        assert_eq!((stored.valid_up_to, stored.first_unclosed_push), (6, None));
+
+        // Another file layout of the same kinds is another key.
+        assert!(
+            cache
+                .get(
+                    7,
+                    &ResidentBracketKey::new(7, &[0, 3, 4, 0], &[0, 1], 6).stream
+                )
+                .is_none()
+        );
+        assert!(fill.store(&readback([0; 5])[..40]).is_err());
+    }
+
+    #[test]
+    fn least_recently_used_entries_go_first() {
+        let cache = BracketCache::new(BracketCacheLimits {
+            max_entries: 2,
+            ..BracketCacheLimits::default()
+        });
+        cache.insert(0, &[1], brackets(1));
+        cache.insert(0, &[2], brackets(1));
+        assert!(cache.get(0, &[1]).is_some());
+        cache.insert(0, &[3], brackets(1));
+        assert!(cache.get(0, &[1]).is_some());
+        assert!(cache.get(0, &[2]).is_none());
+        assert!(cache.get(0, &[3]).is_some());
+        assert_eq!(cache.stats().evictions, 1);
+
+        cache.clear();
+        assert_eq!(cache.stats().entries, 0);
+        assert_eq!(cache.stats().bytes, 0);
+        assert!(cache.get(0, &[1]).is_none());
+    }
+
+    #[test]
+    fn byte_bound_evicts_and_refuses_oversized_results() {
+        let one = entry_bytes(&[0; 100], &brackets(100));
+        let cache = BracketCache::new(BracketCacheLimits {
+            max_entries: 8,
+            max_bytes: one * 2,
+        });
+        cache.insert(0, &[1; 100], brackets(100));
+        cache.insert(0, &[2; 100], brackets(100));
+        cache.insert(0, &[3; 100], brackets(100));
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.evictions), (2, 1));
+        assert!(stats.bytes <= one * 2);
+
+        cache.insert(0, &[4; 1000], brackets(1000));
+        assert!(cache.get(0, &[4; 1000]).is_none());
+        assert_eq!(cache.stats().entries, 2);
+    }
+
+    #[test]
+    fn concurrent_parses_share_one_cache() {
+        let cache = Arc::new(BracketCache::new(BracketCacheLimits {
+            max_entries: 4,
+            ..BracketCacheLimits::default()
+        }));
+        std::thread::scope(|scope| {
+            for t in 0..8u32 {
+                let cache = Arc::clone(&cache);
+                scope.spawn(move || {
+                    for i in 0..100u32 {
+                        let kinds = [t % 4, i % 3];
+                        if cache.get(0, &kinds).is_none() {
+                            cache.insert(0, &kinds, brackets(2));
+                        }
+                    }
+                });
+            }
+        });
+        let stats = cache.stats();
+        assert_eq!(stats.hits + stats.misses, 800);
+        assert!(stats.entries <= 4);
+        assert_eq!(
+            stats.bytes,
+            stats.entries * entry_bytes(&[0, 0], &brackets(2))
+        );
+    }
+}
@@ -0,0 +1,6 @@
+// src/parser/debug.rs
+
+//! Parser passes record their debug captures under `parser.*` keys of
+//! the shared [`DebugOutput`](crate::gpu::debug::DebugOutput) registry.
+
+pub use crate::gpu::debug::DebugOutput;
@@ -2,37 +2,56 @@
 //! GPU parser driver, reshaped to mirror the style used by the lexer driver:
 //! - Pass bundle + `record_all_passes`
 //! - Bind-group cache reuse across passes
-//! - Env-gated timers and validation scopes
-//! - Optional readback (LANIUS_READBACK), returning empty streams when off
+//! - Option-gated timers and validation scopes ([`ParserOptions`])
+//! - Optional readback, returning empty streams when off
 
 use std::{
     collections::HashMap,
     hash::{Hash, Hasher},
-    sync::{Arc, OnceLock},
+    sync::{
+        Arc,
+        OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
 };
 
+mod chunked;
 mod debug;
 mod dispatch_args;
+mod filter;
+mod headers;
+mod plan;
 mod recorded;
 mod resident_buffers;
 mod resident_passes;
 mod resident_tree;
 mod results;
+mod reverse;
 mod support;
 mod token_frontend;
+mod warmup;
 use anyhow::{Result, anyhow};
-use results::ResidentParserBufferCache;
+pub(crate) use chunked::ChunkStitcher;
+pub use plan::ParsePlan;
 pub use results::{
     BracketsMatchResult,
+    LLPHeadersResult,
     Ll1AcceptResult,
     ParseResult,
+    ParseResultSlice,
+    ParseTimings,
+    ParseWork,
     ParserFailure,
     ParserFailureKind,
+    PassElements,
+    PassTiming,
     RecordedHirSemanticCount,
     RecordedResidentLl1HirCheck,
     ResidentParseResult,
     ResidentParserCapacity,
 };
+use results::{ParserStaticTableCache, ResidentParserBufferCache};
+pub use reverse::{ErrorRegion, localize_error};
 pub use support::get_global_parser;
 use support::*;
 use token_frontend::ResidentTokenKindBindGroups;
@@ -40,27 +59,30 @@ use wgpu;
 
 use crate::{
     gpu::{
-        buffers::{storage_ro_from_bytes, storage_ro_from_u32s},
-        device,
+        buffers::{LaniusBuffer, storage_ro_from_bytes, storage_ro_from_u32s},
+        cancel::CancelToken,
+        device::{self, ShaderPath},
         passes_core::{
             BindGroupCache,
             ComputePassBatch,
             DispatchDim,
+            DispatchRecord,
             InputElements,
             Pass,
             PassContext,
             PassData,
             bind_group,
-            compute_pass_batching_enabled,
             plan_workgroups,
-            validation_scopes_enabled,
         },
+        teardown,
         timer::{GpuTimer, MINIMUM_TIME_TO_NOT_ELIDE_MS},
     },
     lexer::{GpuToken, features::CONSERVATIVE_PARSER_FEATURES},
     parser::{
         buffers::{ActionHeader, ParserBuffers, resident_partial_parse_tree_capacity_for_tables},
+        cache::{self, BracketCache, ResidentBracketKey},
         debug::DebugOutput,
+        options::ParserOptions,
         passes::{self, ParserPasses},
         readback,
         tables::PrecomputedParseTables,
@@ -74,6 +96,8 @@ pub struct GpuParser {
     device: Arc<wgpu::Device>,
     queue: Arc<wgpu::Queue>,
     timers_supported: bool,
+    // Settings fixed at construction; never read from the environment
+    options: ParserOptions,
 
     token_delimiters_01: PassData,
     token_delimiters_02: PassData,
@@ -109,6 +133,9 @@ pub struct GpuParser {
     tokens_generic_shr_03_apply: PassData,
     tokens_generic_shr_04_close_kinds: PassData,
     passes: ParserPasses,
+    // Shader artifacts the passes above were built from, for hot reload
+    #[cfg(feature = "shader-hot-reload")]
+    loaded_shaders: Vec<crate::gpu::hot_reload::LoadedShader>,
 
     // Bind group cache so passes do not recreate BGs every dispatch.
     bg_cache: std::sync::Mutex<BindGroupCache>,
@@ -117,27 +144,105 @@ pub struct GpuParser {
     // table identity is unchanged and the previous allocation is large enough.
     resident_buffers: std::sync::Mutex<Option<ResidentParserBufferCache>>,
     resident_token_kind_bind_groups: std::sync::Mutex<Option<ResidentTokenKindBindGroups>>,
+
+    // Action table upload shared by `parse` and `headers` while the tables are unchanged.
+    static_tables: std::sync::Mutex<Option<ParserStaticTableCache>>,
+
+    // Whether one-shot parses fill `ParseResult::dispatch_records`.
+    capture_dispatch_metadata: AtomicBool,
+    // Whether one-shot parses fill `ParseResult::depth_at_token`.
+    capture_bracket_depths: AtomicBool,
+    // Keep-mask compaction pipelines, built on the first `parse_filtered`.
+    filter_passes: OnceLock<filter::TokenFilterPasses>,
+    // Bracket-match results one-shot parses reuse for unchanged kind streams.
+    bracket_cache: std::sync::Mutex<Option<Arc<BracketCache>>>,
+}
+
+impl Drop for GpuParser {
+    /// Hands the resident buffers and table uploads to `teardown::retire`,
+    /// so a parse still running on the GPU never has them freed under it.
+    fn drop(&mut self) {
+        let resident = (
+            teardown::take_poisoned(&mut self.resident_buffers),
+            teardown::take_poisoned(&mut self.resident_token_kind_bind_groups),
+            teardown::take_poisoned(&mut self.static_tables),
+            teardown::take_poisoned(&mut self.bg_cache),
+        );
+        teardown::retire(
+            &self.device,
+            &self.queue,
+            "GpuParser",
+            resident,
+            teardown::DROP_WAIT,
+        );
+    }
 }
 
 impl GpuParser {
-    /// Builds a parser using the global GPU device.
+    /// Waits for every parse this parser submitted, then frees its buffers.
+    ///
+    /// See [`GpuLexer::shutdown`](crate::lexer::GpuLexer::shutdown).
+    pub fn shutdown(self) -> Result<()> {
+        crate::gpu::poll::wait_for_submitted_work(&self.device, "GpuParser::shutdown")
+    }
+
+    /// Builds a parser using the global GPU device and default
+    /// [`ParserOptions`].
     pub async fn new() -> Result<Self> {
-        Self::new_with_device(device::global()).await
+        Self::new_with(ParserOptions::default()).await
     }
 
-    /// Loads parser compute passes for a specific GPU device.
+    /// Builds a parser using the global GPU device and `options`.
+    pub async fn new_with(options: ParserOptions) -> Result<Self> {
+        Self::new_with_device_and_options(device::global(), options).await
+    }
+
+    /// Loads parser compute passes for a specific GPU device with default
+    /// [`ParserOptions`].
     pub async fn new_with_device(ctx: &device::GpuDevice) -> Result<Self> {
+        Self::new_with_device_and_options(ctx, ParserOptions::default()).await
+    }
+
+    /// Loads parser compute passes for a specific GPU device with `options`.
+    pub async fn new_with_device_and_options(
+        ctx: &device::GpuDevice,
+        options: ParserOptions,
+    ) -> Result<Self> {
+        // Syntax passes live in process-wide caches, so they are loaded
+        // outside the hot-reload recorder and are never reload candidates.
+        super::syntax::prewarm_passes(&ctx.device)?;
         let device = Arc::clone(&ctx.device);
         let queue = Arc::clone(&ctx.queue);
-        super::syntax::prewarm_passes(&ctx.device)?;
+
+        #[cfg(feature = "shader-hot-reload")]
+        {
+            let (parser, loaded) = crate::gpu::hot_reload::record_loads(|| {
+                Self::load_passes(device, queue, ctx.timers_supported, options)
+            });
+            let mut parser = parser?;
+            parser.loaded_shaders = loaded;
+            Ok(parser)
+        }
+        #[cfg(not(feature = "shader-hot-reload"))]
+        Self::load_passes(device, queue, ctx.timers_supported, options)
+    }
+
+    fn load_passes(
+        device: Arc<wgpu::Device>,
+        queue: Arc<wgpu::Queue>,
+        timers_supported: bool,
+        options: ParserOptions,
+    ) -> Result<Self> {
+        let pass_device = Arc::clone(&device);
         macro_rules! make_parser_pass {
-            ($label:literal, $make:ident) => {{ $make(&ctx.device)? }};
+            ($label:literal, $make:ident) => {{ $make(&pass_device)? }};
         }
 
         Ok(Self {
             device,
             queue,
-            timers_supported: ctx.timers_supported,
+            timers_supported,
+            options,
             token_delimiters_01: make_parser_pass!(
                 "tokens_delimiters_01_local",
                 make_token_delimiters_01_pass
@@ -267,13 +372,113 @@ impl GpuParser {
                 "tokens_generic_shr_04_close_kinds",
                 make_tokens_generic_shr_04_close_kinds_pass
             ),
-            passes: { ParserPasses::new(&ctx.device)? },
+            passes: { ParserPasses::new(&pass_device)? },
+            #[cfg(feature = "shader-hot-reload")]
+            loaded_shaders: Vec::new(),
             bg_cache: std::sync::Mutex::new(BindGroupCache::new()),
             resident_buffers: std::sync::Mutex::new(None),
             resident_token_kind_bind_groups: std::sync::Mutex::new(None),
+            static_tables: std::sync::Mutex::new(None),
+            capture_dispatch_metadata: AtomicBool::new(options.capture_dispatch_metadata),
+            capture_bracket_depths: AtomicBool::new(options.capture_bracket_depths),
+            filter_passes: OnceLock::new(),
+            bracket_cache: std::sync::Mutex::new(None),
         })
     }
 
+    /// Enables filling [`ParseResult::dispatch_records`] with the planned
+    /// dispatch shape of each one-shot parse.
+    ///
+    /// Defaults to [`ParserOptions::capture_dispatch_metadata`].
+    pub fn set_capture_dispatch_metadata(&self, enabled: bool) {
+        self.capture_dispatch_metadata
+            .store(enabled, Ordering::Relaxed);
+    }
+
+    /// Enables filling [`ParseResult::depth_at_token`] from the read-back
+    /// stack-change stream of each one-shot parse.
+    ///
+    /// Defaults to [`ParserOptions::capture_bracket_depths`].
+    pub fn set_capture_bracket_depths(&self, enabled: bool) {
+        self.capture_bracket_depths
+            .store(enabled, Ordering::Relaxed);
+    }
+
+    /// Attaches `cache` to later parses, or detaches it with `None`.
+    ///
+    /// A parse whose kind stream the cache holds skips stack-effect
+    /// validation and reuses the cached [`BracketsMatchResult`]; other parses
+    /// store theirs. One cache may serve several parsers at once. Parses of
+    /// resident lexer tokens consult it with the key
+    /// [`GpuParser::measure_resident_partial_parse_capacity`] reads back.
+    pub fn set_bracket_cache(&self, cache: Option<Arc<BracketCache>>) {
+        *self
+            .bracket_cache
+            .lock()
+            .expect("GpuParser.bracket_cache mutex poisoned") = cache;
+    }
+
+    /// Returns the attached [`BracketCache`], if any.
+    pub fn bracket_cache(&self) -> Option<Arc<BracketCache>> {
+        self.bracket_cache
+            .lock()
+            .expect("GpuParser.bracket_cache mutex poisoned")
+            .clone()
+    }
+
+    /// Returns the options later one-shot parses use: the construction-time
+    /// [`ParserOptions`] with the capture setters applied.
+    pub fn options(&self) -> ParserOptions {
+        ParserOptions {
+            capture_dispatch_metadata: self.capture_dispatch_metadata.load(Ordering::Relaxed),
+            capture_bracket_depths: self.capture_bracket_depths.load(Ordering::Relaxed),
+            ..self.options
+        }
+    }
+
+    /// Returns bind-group cache hit/miss counters for this parser.
+    pub fn bind_group_cache_stats(&self) -> crate::gpu::passes_core::BindGroupCacheStats {
+        self.bg_cache
+            .lock()
+            .expect("parser.bg_cache poisoned")
+            .stats()
+    }
+
+    /// Recompiles this parser's shaders with `slangc` and swaps in the new
+    /// pipelines when any changed.
+    ///
+    /// Syntax passes are shared process-wide and keep their original
+    /// pipelines. A successful reload drops resident buffers and cached bind
+    /// groups along with the old pipelines.
+    #[cfg(feature = "shader-hot-reload")]
+    pub fn reload_shaders(&mut self) -> Result<crate::gpu::hot_reload::ReloadReport> {
+        let source = crate::gpu::hot_reload::SlangcRebuild::from_env()?;
+        Ok(self.reload_shaders_from(&source))
+    }
+
+    /// Like [`reload_shaders`](Self::reload_shaders) with an explicit artifact source.
+    #[cfg(feature = "shader-hot-reload")]
+    pub fn reload_shaders_from(
+        &mut self,
+        source: &dyn crate::gpu::hot_reload::ShaderRebuild,
+    ) -> crate::gpu::hot_reload::ReloadReport {
+        let device = Arc::clone(&self.device);
+        let queue = Arc::clone(&self.queue);
+        let timers_supported = self.timers_supported;
+        let options = self.options();
+        let (rebuilt, report) =
+            crate::gpu::hot_reload::reload(&self.loaded_shaders, source, || {
+                crate::gpu::hot_reload::build_validated(&Arc::clone(&device), || {
+                    Self::load_passes(device, queue, timers_supported, options)
+                })
+            });
+        if let Some((mut parser, loaded)) = rebuilt {
+            parser.loaded_shaders = loaded;
+            *self = parser;
+        }
+        report
+    }
+
     /// Records and checks parser work for resident lexer token buffers.
     pub fn check_resident_tokens(
         &self,
@@ -305,7 +510,7 @@ impl GpuParser {
             .resident_buffers
             .lock()
             .expect("parser.resident_buffers poisoned");
-        let bufs = self.resident_debug_buffers_for(&mut resident_guard, token_capacity, tables);
+        let bufs = self.resident_debug_buffers_for(&mut resident_guard, token_capacity, tables)?;
 
         let mut encoder = self
             .device
@@ -332,7 +537,7 @@ impl GpuParser {
         });
         encoder.copy_buffer_to_buffer(&bufs.ll1_status, 0, &status_readback, 0, 24);
 
-        let use_scopes = bool_from_env("LANIUS_VALIDATION_SCOPES", false);
+        let use_scopes = self.options.validation_scopes;
         crate::gpu::passes_core::submit_with_optional_validation(
             &self.device,
             &self.queue,
@@ -425,6 +630,7 @@ impl GpuParser {
             tables,
             tree_capacity_override,
             CONSERVATIVE_PARSER_FEATURES,
+            None,
             timer_ref,
             consume,
         )
@@ -433,6 +639,12 @@ impl GpuParser {
     #[allow(clippy::too_many_arguments)]
     /// Records LL, tree, and HIR work with exact tree capacity and conservative
     /// GPU-derived optional-family feature flags.
+    ///
+    /// `bracket_key` is the key measured for these same tokens by
+    /// [`GpuParser::measure_resident_partial_parse_capacity`]; with it, an
+    /// attached [`BracketCache`] that holds the stream skips stack-effect
+    /// validation, and one that does not stores this parse's result when its
+    /// status is read.
     pub fn record_checked_resident_ll1_hir_artifacts_with_tree_capacity_and_features<R, E>(
         &self,
         encoder: &mut wgpu::CommandEncoder,
@@ -445,6 +657,7 @@ impl GpuParser {
         tables: &PrecomputedParseTables,
         tree_capacity_override: Option<u32>,
         parser_feature_flags: u32,
+        bracket_key: Option<&ResidentBracketKey>,
         timer_ref: &mut Option<&mut GpuTimer>,
         consume: impl FnOnce(
             &ParserBuffers,
@@ -456,16 +669,28 @@ impl GpuParser {
             .resident_buffers
             .lock()
             .expect("parser.resident_buffers poisoned");
-        let bufs = self.resident_buffers_for_with_tree_capacity_and_source_and_features(
+        self.resident_buffers_for_with_tree_capacity_and_source_and_features(
             &mut resident_guard,
             token_capacity,
             source_len,
             tables,
             tree_capacity_override,
             parser_feature_flags,
-        );
-        if crate::gpu::env::env_bool_truthy("LANIUS_GPU_COMPILE_HOST_TIMING", false) {
-            eprintln!(
+        )?;
+        let bufs = &mut resident_guard
+            .as_mut()
+            .expect("resident parser buffers allocated")
+            .buffers;
+        let bracket_fill = self
+            .bracket_cache()
+            .zip(bracket_key)
+            .and_then(|(cache, key)| {
+                cache::consult_resident(&cache, &self.queue, bufs, table_fingerprint(tables), key)
+                    .map(Arc::new)
+            });
+        let bufs = &*bufs;
+        if self.options.host_timing {
+            log::info!(target: crate::logging::PARSER_GPU,
                 "[gpu_compile_host_timer] parser.optional_capacities: flags=0x{parser_feature_flags:08x} tree={} arrays={} enum_match={} structs={}",
                 bufs.tree_capacity,
                 bufs.hir_array_capacity,
@@ -480,6 +705,7 @@ impl GpuParser {
         let parser_batch = crate::gpu::passes_core::DeferredComputeBatchGuard::begin(
             false,
             "parser.resident.batch",
+            self.options.host_timing,
         );
 
         self.record_tokens_to_kinds_timed(
@@ -505,13 +731,18 @@ impl GpuParser {
         } else {
             parser_clear_buffer(encoder, &bufs.default_token_file_id, 0, None);
         }
-        self.record_ll1_resident_passes(
+        let mut dispatch_records = self
+            .capture_dispatch_metadata
+            .load(Ordering::Relaxed)
+            .then(Vec::new);
+        self.record_ll1_resident_passes_with_records(
             encoder,
             bufs,
             true,
             true,
             Some((source_len, token_buf, source_buf)),
             timer_ref,
+            dispatch_records.as_mut(),
         )?;
         if let Some(timer) = timer_ref.as_deref_mut() {
             timer.stamp(encoder, "parser.done");
@@ -519,7 +750,11 @@ impl GpuParser {
 
         let status_readback = self.device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("rb.parser.recorded_ll1_hir.status"),
-            size: 32,
+            size: if bracket_fill.is_some() {
+                cache::RESIDENT_BRACKET_READBACK_OFFSET + cache::RESIDENT_BRACKET_READBACK_BYTES
+            } else {
+                32
+            },
             usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
             mapped_at_creation: false,
         });
@@ -542,10 +777,24 @@ impl GpuParser {
             28,
             4,
         );
+        if bracket_fill.is_some() {
+            cache::ResidentBracketFill::record_readback(encoder, bufs, &status_readback);
+        }
         drop(parser_batch);
 
         let consumed = consume(bufs, encoder, timer_ref);
-        Ok((RecordedResidentLl1HirCheck { status_readback }, consumed))
+        // The buffers outlive this parse; a cached result applies only to it.
+        if let Some(cached) = resident_guard.as_mut() {
+            cached.buffers.stack_effect_cached = false;
+        }
+        Ok((
+            RecordedResidentLl1HirCheck {
+                status_readback,
+                dispatch_records: dispatch_records.unwrap_or_default(),
+                bracket_fill,
+            },
+            consumed,
+        ))
     }
 
     /// Records partial-parse capacity work and reads back the required tree capacity.
@@ -599,7 +848,7 @@ impl GpuParser {
             token_capacity,
             tables,
             Some(1),
-        );
+        )?;
 
         let mut encoder = self
             .device
@@ -637,6 +886,36 @@ impl GpuParser {
         });
         encoder.copy_buffer_to_buffer(&bufs.partial_parse_status, 0, &status_readback, 0, 24);
         encoder.copy_buffer_to_buffer(&bufs.token_feature_flags, 0, &status_readback, 24, 4);
+        // With a bracket cache attached, read back the stream that keys it:
+        // the token count, the semantic kinds with their two sentinels, then
+        // the token file ids.
+        let kinds_words = u64::from(token_capacity) + 2;
+        let stream_readback = self.bracket_cache().map(|_| {
+            let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("rb.parser.partial_parse_tree_capacity.bracket_key"),
+                size: (1 + kinds_words + u64::from(token_capacity)) * 4,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            });
+            encoder.copy_buffer_to_buffer(token_count_buf, 0, &readback, 0, 4);
+            encoder.copy_buffer_to_buffer(
+                &bufs.semantic_token_kinds,
+                0,
+                &readback,
+                4,
+                kinds_words * 4,
+            );
+            if token_capacity > 0 {
+                encoder.copy_buffer_to_buffer(
+                    &bufs.default_token_file_id,
+                    0,
+                    &readback,
+                    4 + kinds_words * 4,
+                    u64::from(token_capacity) * 4,
+                );
+            }
+            readback
+        });
         crate::gpu::passes_core::submit_with_progress(
             &self.queue,
             "parser.partial-parse-tree-capacity",
@@ -654,6 +933,31 @@ impl GpuParser {
         drop(mapped);
         status_readback.unmap();
 
+        let bracket_key = match stream_readback {
+            Some(readback) => {
+                let slice = readback.slice(..);
+                crate::gpu::passes_core::map_readback_blocking(
+                    &self.device,
+                    &slice,
+                    "parser.partial_parse_tree_capacity.bracket_key",
+                )?;
+                let mapped = slice.get_mapped_range();
+                let stream = read_u32_words(&mapped, 1 + kinds_words as usize)?;
+                let count = stream[0].min(token_capacity) as usize;
+                let file_ids = read_u32_words(&mapped[(stream.len() * 4)..], count)?;
+                drop(mapped);
+                readback.unmap();
+                // Word 4 of the partial-parse status is the stack-change total.
+                Some(ResidentBracketKey::new(
+                    table_fingerprint(tables),
+                    &stream[1..count + 3],
+                    &file_ids,
+                    words[4],
+                ))
+            }
+            None => None,
+        };
+
         // The capacity probe deliberately uses temporary parser buffers, but
         // token-frontend bind groups are cached on `GpuParser`. Do not let
         // those bind groups outlive the temporary buffers and get reused by
@@ -675,6 +979,7 @@ impl GpuParser {
         Ok(ResidentParserCapacity {
             tree_capacity: emit_capacity.max(1),
             parser_feature_flags: words[6],
+            bracket_key,
         })
     }
 
@@ -683,8 +988,11 @@ impl GpuParser {
         &self,
         token_capacity: u32,
         tables: &PrecomputedParseTables,
-    ) -> u32 {
-        resident_partial_parse_tree_capacity_for_tables(token_capacity.max(1), tables)
+    ) -> Result<u32> {
+        Ok(resident_partial_parse_tree_capacity_for_tables(
+            token_capacity.max(1),
+            tables,
+        )?)
     }
 
     /// Borrows current resident parser buffers sized for the provided token capacity.
@@ -693,13 +1001,13 @@ impl GpuParser {
         token_capacity: u32,
         tables: &PrecomputedParseTables,
         consume: impl FnOnce(&ParserBuffers) -> R,
-    ) -> R {
+    ) -> Result<R> {
         let mut resident_guard = self
             .resident_buffers
             .lock()
             .expect("parser.resident_buffers poisoned");
-        let bufs = self.resident_buffers_for(&mut resident_guard, token_capacity, tables);
-        consume(bufs)
+        let bufs = self.resident_buffers_for(&mut resident_guard, token_capacity, tables)?;
+        Ok(consume(bufs))
     }
 
     /// Clones the compact HIR handles from the parser's current resident job.
@@ -724,7 +1032,7 @@ impl GpuParser {
         tables: &PrecomputedParseTables,
         tree_capacity: u32,
         consume: impl FnOnce(&ParserBuffers) -> R,
-    ) -> R {
+    ) -> Result<R> {
         self.with_current_resident_buffers_with_tree_capacity_and_features(
             token_capacity,
             tables,
@@ -742,7 +1050,7 @@ impl GpuParser {
         tree_capacity: u32,
         parser_feature_flags: u32,
         consume: impl FnOnce(&ParserBuffers) -> R,
-    ) -> R {
+    ) -> Result<R> {
         let mut resident_guard = self
             .resident_buffers
             .lock()
@@ -753,8 +1061,8 @@ impl GpuParser {
             tables,
             Some(tree_capacity),
             parser_feature_flags,
-        );
-        consume(bufs)
+        )?;
+        Ok(consume(bufs))
     }
 
     /// Releases resident parser buffers and cached parser bind groups.
@@ -785,7 +1093,7 @@ impl GpuParser {
             .resident_buffers
             .lock()
             .expect("parser.resident_buffers poisoned");
-        let bufs = self.resident_debug_buffers_for(&mut resident_guard, token_capacity, tables);
+        let bufs = self.resident_debug_buffers_for(&mut resident_guard, token_capacity, tables)?;
 
         let mut encoder = self
             .device
@@ -821,7 +1129,7 @@ impl GpuParser {
             .resident_buffers
             .lock()
             .expect("parser.resident_buffers poisoned");
-        let bufs = self.resident_debug_buffers_for(&mut resident_guard, token_capacity, tables);
+        let bufs = self.resident_debug_buffers_for(&mut resident_guard, token_capacity, tables)?;
 
         let mut encoder = self
             .device
@@ -855,7 +1163,8 @@ impl GpuParser {
         token_kinds_u32: &[u32],
         tables: &PrecomputedParseTables,
     ) -> Result<Vec<u32>> {
-        let raw_kinds = raw_token_kinds_without_optional_sentinels(token_kinds_u32);
+        let raw_kinds =
+            raw_token_kinds_without_optional_sentinels(token_kinds_u32, tables.sentinel_kind);
         let token_count = u32::try_from(raw_kinds.len())
             .map_err(|_| anyhow!("one-shot parser token count exceeds u32::MAX"))?;
         let token_capacity = token_count.max(1);
@@ -880,19 +1189,123 @@ impl GpuParser {
         )
     }
 
+    /// Returns the uploaded action-header grid for `tables`, uploading it only
+    /// when the tables differ from the previous one-shot call.
+    ///
+    /// Tables are [validated](PrecomputedParseTables::validate) before their
+    /// first upload, so corrupted offsets fail here instead of as GPU
+    /// out-of-bounds reads.
+    fn shared_action_table(&self, tables: &PrecomputedParseTables) -> Result<LaniusBuffer<u8>> {
+        let fingerprint = table_fingerprint(tables);
+        let mut cache = self
+            .static_tables
+            .lock()
+            .expect("parser.static_tables poisoned");
+        match cache.as_ref() {
+            Some(cached) if cached.table_fingerprint == fingerprint => {
+                Ok(cached.action_table.clone())
+            }
+            _ => {
+                tables.validate().map_err(|violations| {
+                    anyhow!(
+                        "invalid parse tables: {}",
+                        crate::parser::tables::summarize_violations(&violations)
+                    )
+                })?;
+                let action_table = crate::parser::buffers::upload_action_table(
+                    &self.device,
+                    &tables.to_action_header_grid_bytes(),
+                );
+                *cache = Some(ParserStaticTableCache {
+                    table_fingerprint: fingerprint,
+                    action_table: action_table.clone(),
+                });
+                Ok(action_table)
+            }
+        }
+    }
+
     /// One-shot GPU parse pipeline from raw lexer token kinds.
     ///
-    /// The input may include parser sentinel `0` words at the beginning/end; they
-    /// are ignored before the parser token frontend classifies the raw lexer
+    /// The input may include the tables' sentinel kind at the beginning/end;
+    /// it is ignored before the parser token frontend classifies the raw lexer
     /// kinds into the semantic parser alphabet.
     pub async fn parse(
         &self,
         token_kinds_u32: &[u32],
         tables: &PrecomputedParseTables,
     ) -> Result<ParseResult> {
+        self.parse_with_options(token_kinds_u32, tables, self.options())
+            .await
+    }
+
+    /// Like [`Self::parse`], with `options` in place of [`Self::options`]
+    /// for this call only.
+    pub async fn parse_with_options(
+        &self,
+        token_kinds_u32: &[u32],
+        tables: &PrecomputedParseTables,
+        options: ParserOptions,
+    ) -> Result<ParseResult> {
+        let semantic_token_kinds =
+            self.debug_semantic_token_kinds_for_raw_token_kinds(token_kinds_u32, tables)?;
+        self.parse_classified_inner(&semantic_token_kinds, tables, None, &options)
+            .await
+    }
+
+    /// One-shot GPU parse of unframed raw lexer token kinds.
+    ///
+    /// The stream is framed with [`PrecomputedParseTables::wrap_input`], so it
+    /// must not contain the sentinel kind; the error is a [`SentinelError`]
+    /// naming the offending index.
+    ///
+    /// [`SentinelError`]: crate::parser::tables::SentinelError
+    pub async fn parse_tokens(
+        &self,
+        token_kinds_u32: &[u32],
+        tables: &PrecomputedParseTables,
+    ) -> Result<ParseResult> {
+        let wrapped = tables.wrap_input(token_kinds_u32)?;
+        self.parse(&wrapped, tables).await
+    }
+
+    /// One-shot GPU parse of the unframed raw lexer token kinds selected by
+    /// `keep_mask`, for streams lexed with trivia kept.
+    ///
+    /// The other kinds are compacted away on the GPU before the stream is
+    /// framed and parsed as by [`Self::parse_tokens`], so every index in the
+    /// result refers to the filtered stream;
+    /// [`ParseResult::token_index_map`] maps them back to `token_kinds_u32`.
+    /// [`KindMask::kept`](crate::lexer::KindMask::kept) reproduces the lexer's
+    /// default kept stream.
+    pub async fn parse_filtered(
+        &self,
+        token_kinds_u32: &[u32],
+        keep_mask: &crate::lexer::KindMask,
+        tables: &PrecomputedParseTables,
+    ) -> Result<ParseResult> {
+        let filtered = self.filter_token_kinds(token_kinds_u32, keep_mask)?;
+        let mut result = self.parse_tokens(&filtered.kinds, tables).await?;
+        result.token_index_map = filtered.index_map;
+        Ok(result)
+    }
+
+    /// Like [`Self::parse`], but stops once `token` is cancelled.
+    ///
+    /// A cancelled call fails with a
+    /// [`Cancelled`](crate::gpu::cancel::Cancelled) error. Cancelling before
+    /// submission does no GPU work; afterwards the call stops waiting and
+    /// skips its readbacks while the submitted work runs out on the queue.
+    pub async fn parse_cancellable(
+        &self,
+        token_kinds_u32: &[u32],
+        tables: &PrecomputedParseTables,
+        token: CancelToken,
+    ) -> Result<ParseResult> {
+        token.check("parser.classify", false)?;
         let semantic_token_kinds =
             self.debug_semantic_token_kinds_for_raw_token_kinds(token_kinds_u32, tables)?;
-        self.parse_classified_token_kinds(&semantic_token_kinds, tables)
+        self.parse_classified_inner(&semantic_token_kinds, tables, Some(&token), &self.options())
             .await
     }
 
@@ -902,18 +1315,42 @@ impl GpuParser {
         token_kinds_u32: &[u32],
         tables: &PrecomputedParseTables,
     ) -> Result<ParseResult> {
-        // Build the headers grid bytes from the 7-array tables.
-        let action_table_bytes = tables.to_action_header_grid_bytes();
-        let n_kinds = tables.n_kinds;
+        self.parse_classified_inner(token_kinds_u32, tables, None, &self.options())
+            .await
+    }
 
+    async fn parse_classified_inner(
+        &self,
+        token_kinds_u32: &[u32],
+        tables: &PrecomputedParseTables,
+        cancel: Option<&CancelToken>,
+        options: &ParserOptions,
+    ) -> Result<ParseResult> {
+        if let Some(cancel) = cancel {
+            cancel.check("parser.prepare", false)?;
+        }
         // Allocate per-call buffers (they depend on the specific token pair sequence).
-        let bufs = ParserBuffers::new(
+        let mut bufs = ParserBuffers::new_with_action_table(
             &self.device,
             token_kinds_u32,
-            n_kinds,
-            &action_table_bytes,
+            tables.n_kinds,
+            self.shared_action_table(tables)?,
             tables,
-        );
+        )?;
+
+        // Only a parse that reads back its match table can fill the cache.
+        let bracket_cache = self
+            .bracket_cache()
+            .filter(|_| options.readback && bufs.emit_stack_matches)
+            .map(|cache| (cache, table_fingerprint(tables)));
+        let cached_brackets = bracket_cache
+            .as_ref()
+            .and_then(|(cache, fingerprint)| cache.get(*fingerprint, token_kinds_u32))
+            .filter(|brackets| brackets.match_for_index.len() == bufs.total_sc as usize);
+        if let Some(brackets) = &cached_brackets {
+            let total_sc = bufs.total_sc;
+            cache::upload_cached(&self.queue, &mut bufs, brackets, total_sc);
+        }
 
         // Parser buffers are per-call, and cached bind groups hold concrete buffer handles.
         self.bg_cache
@@ -922,9 +1359,9 @@ impl GpuParser {
             .clear();
 
         // Timing is gated the same way as the lexer (and only if supported).
-        let timers_on = self.timers_supported && bool_from_env("LANIUS_GPU_TIMING", false);
+        let timers_on = self.timers_supported && options.gpu_timing;
         let mut maybe_timer = if timers_on {
-            Some(GpuTimer::new(&self.device, &self.queue, 128))
+            Some(GpuTimer::new(&self.device, &self.queue, 512))
         } else {
             None
         };
@@ -944,6 +1381,9 @@ impl GpuParser {
             t.stamp(&mut encoder, "BEGIN");
         }
 
+        let debug_groups = options.debug_groups;
+        let mut dispatch_records = options.capture_dispatch_metadata.then(Vec::new);
+
         // ---- Record passes inside a short scope so borrows end before readbacks/timer use ----
         {
             let mut timer_ref = maybe_timer.as_mut();
@@ -970,6 +1410,10 @@ impl GpuParser {
                 maybe_timer: &mut timer_ref,
                 maybe_dbg: &mut dbg_ref_opt,
                 bg_cache: Some(&mut *cache_guard),
+                debug_groups,
+                validation_scopes: options.validation_scopes,
+                batch_compute_passes: options.batch_compute_passes,
+                dispatch_records: dispatch_records.as_mut(),
             };
 
             // Record all passes in one place (like the lexer).
@@ -977,23 +1421,32 @@ impl GpuParser {
         } // <- drop ctx, timer_ref, dbg_ref_opt, cache_guard
 
         // -------- Submit & (optionally) read back --------
-        let rb_enabled = readback_enabled();
+        let rb_enabled = options.readback;
 
         // Build readback buffers only when needed (keeps resource count and bandwidth low).
         let rb_handles = if rb_enabled {
             let rb = readback::ParserReadbacks::create(&self.device, &bufs);
+            if debug_groups {
+                encoder.insert_debug_marker("parser.readback.begin");
+            }
             rb.encode_copies(&mut encoder, &bufs);
+            if debug_groups {
+                encoder.insert_debug_marker("parser.readback.end");
+            }
             Some(rb)
         } else {
             None
         };
 
         if let Some(t) = maybe_timer.as_mut() {
-            t.stamp(&mut encoder, "resolve timers");
+            t.stamp(&mut encoder, ParseTimings::RESOLVE_LABEL);
             t.resolve(&mut encoder);
         }
 
-        let use_scopes = bool_from_env("LANIUS_VALIDATION_SCOPES", false);
+        if let Some(cancel) = cancel {
+            cancel.check("parser.submit", false)?;
+        }
+        let use_scopes = options.validation_scopes;
         crate::gpu::passes_core::submit_with_optional_validation(
             &self.device,
             &self.queue,
@@ -1002,133 +1455,24 @@ impl GpuParser {
             use_scopes,
             "parser batch",
         );
+        // Parser buffers are per-call, so a cancelled parse only stops
+        // waiting; the queue keeps them alive until its work finishes.
+        if rb_enabled && cancel.is_some() {
+            crate::gpu::cancel::wait_for_submitted_work_cancellable(
+                &self.device,
+                "parser.batch",
+                cancel,
+            )?;
+        }
 
         // If readback is off, return empty result shells (timers still print).
+        let work = ParseWork::of_buffers(&bufs);
         if !rb_enabled {
-            if let Some(timer) = maybe_timer
-                && let Some(vals) = timer.try_read(&self.device)
-                && !vals.is_empty()
-            {
-                let period_ns = timer.period_ns() as f64;
-                let t0 = vals[0].1;
-                let mut prev = t0;
-                for (label, t) in vals {
-                    let dt_ms = ((t - prev) as f64 * period_ns) / 1.0e6;
-                    let total_ms = ((t - t0) as f64 * period_ns) / 1.0e6;
-                    if dt_ms >= MINIMUM_TIME_TO_NOT_ELIDE_MS {
-                        eprintln!("[gpu_timer] {label}: {dt_ms:.3}ms (total {total_ms:.3}ms)");
-                    }
-                    prev = t;
-                }
-            }
-
             return Ok(ParseResult {
-                ll1: Ll1AcceptResult {
-                    accepted: true,
-                    error_pos: 0,
-                    error_code: 0,
-                    detail: 0,
-                    steps: 0,
-                    emit_len: 0,
-                },
-                ll1_emit_stream: Vec::new(),
-                ll1_emit_token_pos: Vec::new(),
-                headers: Vec::new(),
-                sc_stream: Vec::new(),
-                emit_stream: Vec::new(),
-                brackets: BracketsMatchResult {
-                    valid: true,
-                    final_depth: 0,
-                    min_depth: 0,
-                    match_for_index: Vec::new(),
-                },
-                node_kind: Vec::new(),
-                parent: Vec::new(),
-                first_child: Vec::new(),
-                next_sibling: Vec::new(),
-                subtree_end: Vec::new(),
-                hir_kind: Vec::new(),
-                hir_semantic_prefix_before_node: Vec::new(),
-                hir_semantic_dense_node: Vec::new(),
-                hir_semantic_subtree_end: Vec::new(),
-                hir_semantic_parent: Vec::new(),
-                hir_semantic_first_child: Vec::new(),
-                hir_semantic_next_sibling: Vec::new(),
-                hir_semantic_depth: Vec::new(),
-                hir_semantic_child_index: Vec::new(),
-                hir_token_pos: Vec::new(),
-                hir_token_end: Vec::new(),
-                hir_type_form: Vec::new(),
-                hir_type_value_node: Vec::new(),
-                hir_type_len_token: Vec::new(),
-                hir_type_len_value: Vec::new(),
-                hir_type_file_id: Vec::new(),
-                hir_type_path_leaf_node: Vec::new(),
-                hir_type_arg_start: Vec::new(),
-                hir_type_arg_count: Vec::new(),
-                hir_type_arg_next: Vec::new(),
-                hir_type_alias_target_node: Vec::new(),
-                hir_fn_return_type_node: Vec::new(),
-                hir_method_signature_flags: Vec::new(),
-                hir_stmt_record_kind: Vec::new(),
-                hir_stmt_record_operand0: Vec::new(),
-                hir_stmt_record_operand1: Vec::new(),
-                hir_stmt_record_operand2: Vec::new(),
-                hir_stmt_scope_end: Vec::new(),
-                hir_item_kind: Vec::new(),
-                hir_item_name_token: Vec::new(),
-                hir_item_decl_token: Vec::new(),
-                hir_item_namespace: Vec::new(),
-                hir_item_visibility: Vec::new(),
-                hir_item_path_start: Vec::new(),
-                hir_item_path_end: Vec::new(),
-                hir_item_path_node: Vec::new(),
-                hir_item_file_id: Vec::new(),
-                hir_item_import_target_kind: Vec::new(),
-                hir_variant_parent_enum: Vec::new(),
-                hir_variant_ordinal: Vec::new(),
-                hir_variant_payload_start: Vec::new(),
-                hir_variant_payload_count: Vec::new(),
-                hir_variant_payload_node: Vec::new(),
-                hir_match_scrutinee_node: Vec::new(),
-                hir_match_arm_start: Vec::new(),
-                hir_match_arm_count: Vec::new(),
-                hir_match_arm_next: Vec::new(),
-                hir_match_arm_pattern_node: Vec::new(),
-                hir_match_arm_payload_start: Vec::new(),
-                hir_match_arm_payload_count: Vec::new(),
-                hir_match_arm_result_node: Vec::new(),
-                hir_match_payload_owner_arm: Vec::new(),
-                hir_match_payload_match_node: Vec::new(),
-                hir_match_payload_ordinal: Vec::new(),
-                hir_call_callee_node: Vec::new(),
-                hir_call_arg_start: Vec::new(),
-                hir_call_arg_end: Vec::new(),
-                hir_call_arg_count: Vec::new(),
-                hir_call_arg_parent_call: Vec::new(),
-                hir_call_arg_ordinal: Vec::new(),
-                hir_array_lit_first_element: Vec::new(),
-                hir_array_lit_element_count: Vec::new(),
-                hir_array_element_parent_lit: Vec::new(),
-                hir_array_element_ordinal: Vec::new(),
-                hir_array_element_next: Vec::new(),
-                hir_expr_string_start: Vec::new(),
-                hir_expr_string_len: Vec::new(),
-                hir_member_receiver_node: Vec::new(),
-                hir_member_receiver_token: Vec::new(),
-                hir_member_name_token: Vec::new(),
-                hir_struct_field_parent_struct: Vec::new(),
-                hir_struct_field_ordinal: Vec::new(),
-                hir_struct_field_type_node: Vec::new(),
-                hir_struct_decl_field_start: Vec::new(),
-                hir_struct_decl_field_count: Vec::new(),
-                hir_struct_lit_head_node: Vec::new(),
-                hir_struct_lit_field_start: Vec::new(),
-                hir_struct_lit_field_count: Vec::new(),
-                hir_struct_lit_field_parent_lit: Vec::new(),
-                hir_struct_lit_field_value_node: Vec::new(),
-                hir_struct_lit_field_next: Vec::new(),
-                debug: DebugOutput::default(),
+                dispatch_records: dispatch_records.unwrap_or_default(),
+                timings: maybe_timer
+                    .and_then(|timer| read_parse_timings(&self.device, &timer, work)),
+                ..ParseResult::empty()
             });
         }
 
@@ -1140,28 +1484,14 @@ impl GpuParser {
         )?;
 
         // Print timers (same as lexer).
-        if let Some(timer) = maybe_timer
-            && let Some(vals) = timer.try_read(&self.device)
-            && !vals.is_empty()
-        {
-            let period_ns = timer.period_ns() as f64;
-            let t0 = vals[0].1;
-            let mut prev = t0;
-            for (label, t) in vals {
-                let dt_ms = ((t - prev) as f64 * period_ns) / 1.0e6;
-                let total_ms = ((t - t0) as f64 * period_ns) / 1.0e6;
-                if dt_ms >= MINIMUM_TIME_TO_NOT_ELIDE_MS {
-                    eprintln!("[gpu_timer] {label}: {dt_ms:.3}ms (total {total_ms:.3}ms)");
-                }
-                prev = t;
-            }
-        }
+        let timings = maybe_timer.and_then(|timer| read_parse_timings(&self.device, &timer, work));
 
         // Move out the owned debug snapshot (when the feature is on), otherwise default.
         #[allow(unused_mut)]
         let mut debug_sink = {
             #[cfg(feature = "gpu-debug")]
             {
+                debug_output.arena.map_all(&self.device)?;
                 std::mem::take(&mut debug_output)
             }
             #[cfg(not(feature = "gpu-debug"))]
@@ -1170,6 +1500,27 @@ impl GpuParser {
             }
         };
 
+        let (depth_at_token, max_bracket_depth) = results::clamped_bracket_depths(
+            &decoded.headers,
+            &decoded.sc_stream,
+            tables.start_sentinel,
+            options.capture_bracket_depths,
+        );
+
+        let brackets = BracketsMatchResult {
+            valid: decoded.valid,
+            final_depth: decoded.final_depth,
+            min_depth: decoded.min_depth,
+            match_for_index: decoded.match_for_index,
+            valid_up_to: decoded.valid_up_to,
+            first_unclosed_push: decoded.first_unclosed_push,
+        };
+        if let Some((cache, fingerprint)) = &bracket_cache
+            && cached_brackets.is_none()
+        {
+            cache.insert(*fingerprint, token_kinds_u32, brackets.clone());
+        }
+
         Ok(ParseResult {
             ll1: Ll1AcceptResult {
                 accepted: decoded.ll1_status[0] != 0,
@@ -1184,12 +1535,10 @@ impl GpuParser {
             headers: decoded.headers,
             sc_stream: decoded.sc_stream,
             emit_stream: decoded.emit_stream,
-            brackets: BracketsMatchResult {
-                valid: decoded.valid,
-                final_depth: decoded.final_depth,
-                min_depth: decoded.min_depth,
-                match_for_index: decoded.match_for_index,
-            },
+            brackets,
+            depth_at_token,
+            max_bracket_depth,
+            token_index_map: Vec::new(),
             node_kind: decoded.node_kind,
             parent: decoded.parent,
             first_child: decoded.first_child,
@@ -1276,18 +1625,20 @@ impl GpuParser {
             hir_struct_lit_field_parent_lit: decoded.hir_struct_lit_field_parent_lit,
             hir_struct_lit_field_value_node: decoded.hir_struct_lit_field_value_node,
             hir_struct_lit_field_next: decoded.hir_struct_lit_field_next,
+            dispatch_records: dispatch_records.unwrap_or_default(),
+            timings,
             debug: std::mem::take(&mut debug_sink),
         })
     }
 }
 
-fn raw_token_kinds_without_optional_sentinels(token_kinds_u32: &[u32]) -> &[u32] {
+fn raw_token_kinds_without_optional_sentinels(token_kinds_u32: &[u32], sentinel: u32) -> &[u32] {
     let mut start = 0usize;
     let mut end = token_kinds_u32.len();
-    if token_kinds_u32.first().copied() == Some(0) {
+    if token_kinds_u32.first().copied() == Some(sentinel) {
         start = 1;
     }
-    if end > start && token_kinds_u32[end - 1] == 0 {
+    if end > start && token_kinds_u32[end - 1] == sentinel {
         end -= 1;
     }
     &token_kinds_u32[start..end]
@@ -1314,8 +1665,11 @@ fn plan_parser_compute(pass: &PassData, n_elements: u32) -> Result<(u32, u32, u3
     )
 }
 
-fn parser_compute_pass_batching_enabled(_timer_ref: &mut Option<&mut GpuTimer>) -> bool {
-    _timer_ref.is_none() && compute_pass_batching_enabled() && !validation_scopes_enabled()
+fn parser_compute_pass_batching_enabled(
+    options: &ParserOptions,
+    timer_ref: &mut Option<&mut GpuTimer>,
+) -> bool {
+    options.batches_passes(timer_ref.is_some())
 }
 
 fn parser_dependency_batching_enabled(_timer_ref: &mut Option<&mut GpuTimer>) -> bool {
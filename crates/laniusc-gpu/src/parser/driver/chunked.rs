@@ -0,0 +1,437 @@
+//! Streaming parse over chunks of classified token kinds.
+//!
+//! Each chunk runs the pair and bracket passes over the previous chunk's last
+//! kind followed by the chunk, so the pair straddling a boundary is parsed
+//! once, by the later chunk. Headers and packed streams concatenate as they
+//! are; bracket matches found inside a chunk are shifted to stream positions,
+//! and the stack changes a chunk leaves unmatched are resolved on the host
+//! against the pushes still open from earlier chunks.
+
+use super::*;
+use crate::parser::{
+    autotune::ChunkSize,
+    readback::{PairOutputs, PairReadbacks},
+};
+
+const UNMATCHED: u32 = u32::MAX;
+
+impl GpuParser {
+    /// Parses a classified token-kind stream delivered in chunks.
+    ///
+    /// The chunks concatenate to the stream [`Self::parse_classified_token_kinds`]
+    /// takes, sentinels included, and may be of any size, including empty.
+    /// GPU buffers are sized for one chunk at a time and released before the
+    /// next, so memory is bounded by the largest chunk. `headers`,
+    /// `sc_stream`, `emit_stream`, and `brackets` equal those of a monolithic
+    /// parse; the LL(1), tree, and HIR outputs are left empty. With
+    /// [`ParserOptions::gpu_timing`], `timings` sums each pass over the
+    /// chunks.
+    pub async fn parse_chunked<'k>(
+        &self,
+        chunks: impl IntoIterator<Item = &'k [u32]>,
+        tables: &PrecomputedParseTables,
+    ) -> Result<ParseResult> {
+        self.parse_chunked_inner(chunks, tables, None)
+    }
+
+    /// [`Self::parse_chunked`] over `token_kinds_u32` cut into chunks of
+    /// `size` tokens; [`ChunkSize::Auto`] picks the size from the timings of
+    /// earlier parses.
+    pub async fn parse_chunked_sized(
+        &self,
+        token_kinds_u32: &[u32],
+        size: ChunkSize<'_>,
+        tables: &PrecomputedParseTables,
+    ) -> Result<ParseResult> {
+        let tokens = size.tokens().max(1) as usize;
+        self.parse_chunked_inner(token_kinds_u32.chunks(tokens), tables, None)
+    }
+
+    /// Like [`Self::parse_chunked`], but stops once `token` is cancelled.
+    ///
+    /// Cancellation is checked before each chunk and while waiting on one,
+    /// so a long stream stops within about a chunk's recording time; the
+    /// error is a [`Cancelled`](crate::gpu::cancel::Cancelled).
+    pub async fn parse_chunked_cancellable<'k>(
+        &self,
+        chunks: impl IntoIterator<Item = &'k [u32]>,
+        tables: &PrecomputedParseTables,
+        token: CancelToken,
+    ) -> Result<ParseResult> {
+        self.parse_chunked_inner(chunks, tables, Some(&token))
+    }
+
+    fn parse_chunked_inner<'k>(
+        &self,
+        chunks: impl IntoIterator<Item = &'k [u32]>,
+        tables: &PrecomputedParseTables,
+        cancel: Option<&CancelToken>,
+    ) -> Result<ParseResult> {
+        let mut stitcher = ChunkStitcher::default();
+        for window in pair_windows(chunks) {
+            if let Some(cancel) = cancel {
+                cancel.check("parser.chunk", false)?;
+            }
+            let (outputs, timings) = self.parse_pair_window(&window, tables, cancel)?;
+            stitcher.push(outputs)?;
+            if let Some(timings) = timings {
+                stitcher.add_timings(&timings);
+            }
+        }
+        Ok(stitcher.finish())
+    }
+
+    /// Runs the pair and bracket passes over one window and reads them back.
+    pub(crate) fn parse_pair_window(
+        &self,
+        token_kinds_u32: &[u32],
+        tables: &PrecomputedParseTables,
+        cancel: Option<&CancelToken>,
+    ) -> Result<(PairOutputs, Option<ParseTimings>)> {
+        let bufs = ParserBuffers::new_pairs_only(
+            &self.device,
+            token_kinds_u32,
+            tables.n_kinds,
+            self.shared_action_table(tables)?,
+            tables,
+        )?;
+
+        // Parser buffers are per-chunk, and cached bind groups hold concrete buffer handles.
+        self.bg_cache
+            .lock()
+            .expect("parser.bg_cache poisoned")
+            .clear();
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("parser.chunk.encoder"),
+            });
+        let mut maybe_timer = (self.timers_supported && self.options.gpu_timing)
+            .then(|| GpuTimer::new(&self.device, &self.queue, 64));
+        if let Some(t) = maybe_timer.as_mut() {
+            t.stamp(&mut encoder, "BEGIN");
+        }
+        {
+            let mut timer_ref = maybe_timer.as_mut();
+            let mut dbg_ref_opt: Option<&mut DebugOutput> = None;
+            let mut cache_guard = self.bg_cache.lock().expect("parser.bg_cache poisoned");
+            let mut ctx = PassContext {
+                device: &self.device,
+                encoder: &mut encoder,
+                buffers: &bufs,
+                maybe_timer: &mut timer_ref,
+                maybe_dbg: &mut dbg_ref_opt,
+                bg_cache: Some(&mut *cache_guard),
+                debug_groups: self.options.debug_groups,
+                validation_scopes: self.options.validation_scopes,
+                batch_compute_passes: self.options.batch_compute_passes,
+                dispatch_records: None,
+            };
+            passes::record_pair_passes(&mut ctx, &self.passes)?;
+        }
+        crate::gpu::passes_core::flush_deferred_compute(&mut encoder);
+
+        let rb = PairReadbacks::create(&self.device, &bufs);
+        rb.encode_copies(&mut encoder, &bufs);
+        if let Some(t) = maybe_timer.as_mut() {
+            t.stamp(&mut encoder, ParseTimings::RESOLVE_LABEL);
+            t.resolve(&mut encoder);
+        }
+        crate::gpu::passes_core::submit_with_optional_validation(
+            &self.device,
+            &self.queue,
+            "parser.chunk",
+            encoder.finish(),
+            self.options.validation_scopes,
+            "parser chunk",
+        );
+        crate::gpu::cancel::wait_for_submitted_work_cancellable(
+            &self.device,
+            "parser.chunk",
+            cancel,
+        )?;
+        let outputs = rb.map_and_decode(&self.device, &bufs)?;
+        let timings = maybe_timer.and_then(|timer| {
+            read_parse_timings(&self.device, &timer, ParseWork::of_buffers(&bufs))
+        });
+        Ok((outputs, timings))
+    }
+}
+
+/// Token windows whose pairs are the stream's pairs, each exactly once: every
+/// window but the first starts with the last kind of the previous non-empty
+/// chunk. Windows with no pair are skipped.
+fn pair_windows<'k>(chunks: impl IntoIterator<Item = &'k [u32]>) -> impl Iterator<Item = Vec<u32>> {
+    let mut prev_kind = None;
+    chunks.into_iter().filter_map(move |chunk| {
+        let &last = chunk.last()?;
+        let window = prev_kind
+            .into_iter()
+            .chain(chunk.iter().copied())
+            .collect::<Vec<_>>();
+        prev_kind = Some(last);
+        (window.len() >= 2).then_some(window)
+    })
+}
+
+/// Concatenates per-chunk pair outputs and carries bracket state across
+/// chunk boundaries.
+#[derive(Clone, Default)]
+pub(crate) struct ChunkStitcher {
+    headers: Vec<ActionHeader>,
+    sc_stream: Vec<u32>,
+    emit_stream: Vec<u32>,
+    match_for_index: Vec<u32>,
+    /// Stream positions of the pushes still open, oldest first.
+    open: Vec<u32>,
+    depth: i32,
+    min_depth: i32,
+    first_negative: Option<u32>,
+    mismatched: bool,
+    /// Pass timings summed over the timed chunks.
+    timings: Option<ParseTimings>,
+}
+
+impl ChunkStitcher {
+    /// Pairs stitched so far.
+    pub(crate) fn pairs(&self) -> usize {
+        self.headers.len()
+    }
+
+    /// Whether the chunks stitched so far form a complete, valid stack-change
+    /// stream, the test [`Self::finish`] applies to `brackets.valid`.
+    pub(crate) fn accepted(&self) -> bool {
+        !self.mismatched && self.min_depth >= 0 && self.depth == 0
+    }
+
+    /// Adds one chunk's pass timings to the sums [`Self::finish`] reports.
+    pub(crate) fn add_timings(&mut self, timings: &ParseTimings) {
+        self.timings
+            .get_or_insert_with(ParseTimings::default)
+            .accumulate(timings);
+    }
+
+    pub(crate) fn push(&mut self, chunk: PairOutputs) -> Result<()> {
+        let base = u32::try_from(self.sc_stream.len())
+            .ok()
+            .filter(|base| base.checked_add(chunk.sc_stream.len() as u32).is_some())
+            .ok_or_else(|| anyhow!("chunked parse stack-change stream exceeds u32::MAX"))?;
+        self.headers.extend(chunk.headers);
+        self.emit_stream.extend(chunk.emit_stream);
+        self.match_for_index
+            .extend(chunk.match_for_index.iter().map(|&matched| {
+                if matched == UNMATCHED {
+                    UNMATCHED
+                } else {
+                    matched + base
+                }
+            }));
+
+        for (i, &code) in chunk.sc_stream.iter().enumerate() {
+            let pos = base + i as u32;
+            self.min_depth = self.min_depth.min(self.depth);
+            self.sc_stream.push(code);
+            if code & 1 == 1 {
+                self.depth += 1;
+                self.open.push(pos);
+                continue;
+            }
+            if self.depth == 0 && self.first_negative.is_none() {
+                self.first_negative = Some(pos);
+            }
+            self.depth -= 1;
+            let Some(opener) = self.open.pop() else {
+                continue;
+            };
+            if opener >= base {
+                // Matched inside the chunk, or rejected there as a type mismatch.
+                match self.match_for_index[pos as usize] {
+                    UNMATCHED => self.mismatched = true,
+                    matched if matched == opener => {}
+                    matched => {
+                        return Err(anyhow!(
+                            "chunk matched stack change {pos} to {matched}, expected {opener}"
+                        ));
+                    }
+                }
+            } else if self.sc_stream[opener as usize] >> 1 == code >> 1 {
+                self.match_for_index[opener as usize] = pos;
+                self.match_for_index[pos as usize] = opener;
+            } else {
+                self.mismatched = true;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn finish(self) -> ParseResult {
+        let brackets = BracketsMatchResult {
+            valid: self.accepted(),
+            final_depth: self.depth,
+            min_depth: self.min_depth,
+            valid_up_to: self.first_negative.unwrap_or(self.sc_stream.len() as u32),
+            first_unclosed_push: self.open.first().copied(),
+            match_for_index: self.match_for_index,
+        };
+        let (_, max_bracket_depth) =
+            super::results::clamped_bracket_depths(&self.headers, &self.sc_stream, true, false);
+        ParseResult {
+            max_bracket_depth,
+            headers: self.headers,
+            sc_stream: self.sc_stream,
+            emit_stream: self.emit_stream,
+            brackets,
+            timings: self.timings,
+            ..ParseResult::empty()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::tables::{encode_pop, encode_push};
+
+    /// Host model of one chunk's bracket pass: typed matching by depth,
+    /// leaving pops without a local opener unmatched. Each stack change is
+    /// its own pair, emitting the change's symbol.
+    fn local_outputs(sc_stream: &[u32]) -> PairOutputs {
+        let mut match_for_index = vec![UNMATCHED; sc_stream.len()];
+        let mut open = Vec::new();
+        for (i, &code) in sc_stream.iter().enumerate() {
+            if code & 1 == 1 {
+                open.push(i);
+            } else if let Some(j) = open.pop()
+                && sc_stream[j] >> 1 == code >> 1
+            {
+                match_for_index[i] = j as u32;
+                match_for_index[j] = i as u32;
+            }
+        }
+        PairOutputs {
+            headers: sc_stream
+                .iter()
+                .map(|&code| ActionHeader {
+                    push_len: code & 1,
+                    emit_len: 1,
+                    pop_tag: 0,
+                    pop_count: 1 - (code & 1),
+                })
+                .collect(),
+            sc_stream: sc_stream.to_vec(),
+            emit_stream: sc_stream.iter().map(|code| code >> 1).collect(),
+            match_for_index,
+        }
+    }
+
+    fn stitched(sc_stream: &[u32], cuts: &[usize]) -> ParseResult {
+        let mut stitcher = ChunkStitcher::default();
+        let mut start = 0;
+        for &end in cuts.iter().chain([&sc_stream.len()]) {
+            stitcher
+                .push(local_outputs(&sc_stream[start..end]))
+                .unwrap();
+            start = end;
+        }
+        stitcher.finish()
+    }
+
+    fn bracket_summary(result: &ParseResult) -> (bool, i32, i32, Vec<u32>, u32, Option<u32>) {
+        let b = &result.brackets;
+        (
+            b.valid,
+            b.final_depth,
+            b.min_depth,
+            b.match_for_index.clone(),
+            b.valid_up_to,
+            b.first_unclosed_push,
+        )
+    }
+
+    fn codes(text: &str) -> Vec<u32> {
+        text.chars()
+            .map(|c| match c {
+                '(' => encode_push(0),
+                ')' => encode_pop(0),
+                '[' => encode_push(1),
+                ']' => encode_pop(1),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn stitched_brackets_match_a_single_chunk_at_every_cut() {
+        for text in ["(([]))[()]", "(()", "())(", "([)]()", "]((", "", "(]("] {
+            let sc = codes(text);
+            let whole = stitched(&sc, &[]);
+            for a in 0..=sc.len() {
+                for b in a..=sc.len() {
+                    let split = stitched(&sc, &[a, b]);
+                    assert_eq!(
+                        bracket_summary(&split),
+                        bracket_summary(&whole),
+                        "{text:?} cut at {a},{b}"
+                    );
+                    assert_eq!(split.emit_stream, whole.emit_stream);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stitched_brackets_report_boundary_failures_once() {
+        // Openers left open at a cut are closed by the next chunk.
+        let open_at_cut = stitched(&codes("(([]))"), &[2]);
+        assert!(open_at_cut.brackets.valid);
+        assert_eq!(open_at_cut.brackets.first_unclosed_push, None);
+        assert_eq!(open_at_cut.brackets.match_for_index, [5, 4, 3, 2, 1, 0]);
+
+        let stray = stitched(&codes("()](("), &[3]);
+        assert!(!stray.brackets.valid);
+        assert_eq!(stray.brackets.valid_up_to, 2);
+        assert_eq!(stray.brackets.first_unclosed_push, Some(3));
+        assert_eq!(stray.brackets.min_depth, -1);
+
+        let mismatch = stitched(&codes("([)]"), &[1, 3]);
+        assert!(!mismatch.brackets.valid);
+        assert_eq!(mismatch.brackets.valid_up_to, 4);
+        assert_eq!(mismatch.brackets.first_unclosed_push, None);
+    }
+
+    #[test]
+    fn truncated_chunks_stitch_like_the_kept_prefix() {
+        let sc = codes("(([])[)");
+        for pairs in 0..=sc.len() {
+            let mut truncated = local_outputs(&sc);
+            truncated.truncate_pairs(pairs);
+            assert_eq!(truncated.emit_stream.len(), pairs);
+            let mut stitcher = ChunkStitcher::default();
+            stitcher.push(truncated).unwrap();
+            assert_eq!(stitcher.pairs(), pairs);
+            let prefix = stitched(&sc[..pairs], &[]);
+            assert_eq!(stitcher.accepted(), prefix.brackets.valid, "{pairs}");
+            assert_eq!(
+                bracket_summary(&stitcher.finish()),
+                bracket_summary(&prefix),
+                "{pairs} pairs"
+            );
+        }
+    }
+
+    #[test]
+    fn pair_windows_cover_every_pair_exactly_once() {
+        let kinds: Vec<u32> = (0..11).collect();
+        let expected = kinds.windows(2).map(<[u32]>::to_vec).collect::<Vec<_>>();
+        for size in 1..=kinds.len() + 1 {
+            let mut chunks = kinds.chunks(size).collect::<Vec<_>>();
+            chunks.insert(1, &[]);
+            let pairs = pair_windows(chunks)
+                .flat_map(|window| window.windows(2).map(<[u32]>::to_vec).collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            assert_eq!(pairs, expected, "chunk size {size}");
+        }
+        assert_eq!(pair_windows([&[7u32][..]]).count(), 0);
+    }
+}
@@ -68,7 +68,7 @@ impl GpuParser {
             tables,
             tree_capacity_override,
             parser_feature_flags,
-        );
+        )?;
 
         let mut encoder = self
             .device
@@ -142,7 +142,7 @@ impl GpuParser {
             .resident_buffers
             .lock()
             .expect("parser.resident_buffers poisoned");
-        let bufs = self.resident_buffers_for(&mut resident_guard, token_capacity, tables);
+        let bufs = self.resident_buffers_for(&mut resident_guard, token_capacity, tables)?;
 
         let mut encoder = self
             .device
@@ -224,7 +224,7 @@ impl GpuParser {
             .resident_buffers
             .lock()
             .expect("parser.resident_buffers poisoned");
-        let bufs = self.resident_buffers_for(&mut resident_guard, token_capacity, tables);
+        let bufs = self.resident_buffers_for(&mut resident_guard, token_capacity, tables)?;
 
         let mut encoder = self
             .device
@@ -132,6 +132,10 @@ impl GpuParser {
             maybe_timer: &mut no_timer,
             maybe_dbg: &mut dbg_ref,
             bg_cache: Some(&mut *cache_guard),
+            debug_groups: self.options.debug_groups,
+            validation_scopes: self.options.validation_scopes,
+            batch_compute_passes: self.options.batch_compute_passes,
+            dispatch_records: None,
         };
 
         self.record_active_pair_dispatch_args(ctx.encoder, bufs)?;
@@ -0,0 +1,271 @@
+//! Keep-mask compaction of raw token kinds ahead of the one-shot parser.
+//!
+//! [`GpuParser::parse_filtered`] parses a token stream lexed with trivia kept
+//! as if the trivia had been skipped. The kinds in the keep mask are compacted
+//! by rank with a block scan, like the lexer's token query, so the filtered
+//! stream keeps input order and comes with a map from each filtered index back
+//! to its input index.
+
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow};
+use encase::ShaderType;
+
+use super::*;
+use crate::{
+    gpu::{
+        buffers::{storage_rw_uninit_bytes, uniform_from_val},
+        scan::{PingPongScanStep, ScanFinalize, ping_pong_scan_steps},
+    },
+    lexer::{KindMask, query::KIND_MASK_WORDS},
+};
+
+/// Tokens per filter block, matching `PAIR_BLOCK_WIDTH` in the shaders.
+const FILTER_BLOCK_WIDTH: u32 = 256;
+
+#[derive(ShaderType, Debug, Clone, Copy)]
+/// Uniform parameters of the count and scatter passes.
+struct FilterParams {
+    n: u32,
+    mask_words: u32,
+}
+
+#[derive(ShaderType, Debug, Clone, Copy)]
+/// Uniform parameters of one block-total scan round.
+struct FilterScanParams {
+    n_blocks: u32,
+    stride: u32,
+}
+
+/// Count, block-scan, and scatter pipelines of the token filter.
+pub(super) struct TokenFilterPasses {
+    count: PassData,
+    scan: PassData,
+    scatter: PassData,
+}
+
+impl TokenFilterPasses {
+    /// Creates the filter pipelines for a device.
+    pub(super) fn new(device: &wgpu::Device) -> Result<Self> {
+        Ok(Self {
+            count: crate::gpu::passes_core::make_shader_pass!(
+                device,
+                "filter_01_count_inblock",
+                entry: "filter_01_count_inblock",
+                shader: "parser/filter/01_count_inblock"
+            )?,
+            scan: crate::gpu::passes_core::make_shader_pass!(
+                device,
+                "filter_02_scan_block_totals",
+                entry: "filter_02_scan_block_totals",
+                shader: "parser/filter/02_scan_block_totals"
+            )?,
+            scatter: crate::gpu::passes_core::make_shader_pass!(
+                device,
+                "filter_03_scatter",
+                entry: "filter_03_scatter",
+                shader: "parser/filter/03_scatter"
+            )?,
+        })
+    }
+}
+
+/// Token kinds left by the filter and the input index of each.
+pub(super) struct FilteredTokenKinds {
+    pub(super) kinds: Vec<u32>,
+    pub(super) index_map: Vec<u32>,
+}
+
+impl GpuParser {
+    /// Compacts `token_kinds_u32` to the kinds in `keep_mask` on the GPU and
+    /// reads back the kept kinds with their input indices.
+    pub(super) fn filter_token_kinds(
+        &self,
+        token_kinds_u32: &[u32],
+        keep_mask: &KindMask,
+    ) -> Result<FilteredTokenKinds> {
+        if token_kinds_u32.is_empty() {
+            return Ok(FilteredTokenKinds {
+                kinds: Vec::new(),
+                index_map: Vec::new(),
+            });
+        }
+        let n = u32::try_from(token_kinds_u32.len())
+            .map_err(|_| anyhow!("filtered parser token count exceeds u32::MAX"))?;
+        let passes = match self.filter_passes.get() {
+            Some(passes) => passes,
+            None => {
+                let passes = TokenFilterPasses::new(&self.device)?;
+                self.filter_passes.get_or_init(|| passes)
+            }
+        };
+        let device = &*self.device;
+        let n_blocks = n.div_ceil(FILTER_BLOCK_WIDTH);
+
+        let params = uniform_from_val(
+            device,
+            "parser.filter.params",
+            &FilterParams {
+                n,
+                mask_words: KIND_MASK_WORDS as u32,
+            },
+        );
+        let keep_mask = storage_ro_from_u32s(device, "parser.filter.keep_mask", keep_mask.words());
+        let token_kinds =
+            storage_ro_from_u32s(device, "parser.filter.token_kinds", token_kinds_u32);
+        let block_bytes = n_blocks as usize * 4;
+        let ping = storage_rw_uninit_bytes(
+            device,
+            "parser.filter.block_totals_ping",
+            block_bytes,
+            n_blocks as usize,
+        );
+        let pong = storage_rw_uninit_bytes(
+            device,
+            "parser.filter.block_totals_pong",
+            block_bytes,
+            n_blocks as usize,
+        );
+        let token_bytes = n as usize * 4;
+        let filtered_kinds = storage_rw_uninit_bytes(
+            device,
+            "parser.filter.filtered_kinds",
+            token_bytes,
+            n as usize,
+        );
+        let index_map =
+            storage_rw_uninit_bytes(device, "parser.filter.index_map", token_bytes, n as usize);
+        let status = storage_rw_uninit_bytes(device, "parser.filter.status", 4, 1);
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("parser.filter.encoder"),
+        });
+        let token_inputs = || -> HashMap<String, wgpu::BindingResource<'_>> {
+            HashMap::from([
+                ("gFilter".into(), params.as_entire_binding()),
+                ("keep_mask".into(), keep_mask.as_entire_binding()),
+                ("token_kinds".into(), token_kinds.as_entire_binding()),
+            ])
+        };
+
+        let mut count_res = token_inputs();
+        count_res.insert("block_totals_filter".into(), ping.as_entire_binding());
+        dispatch(device, &mut encoder, &passes.count, &count_res, n)?;
+
+        let steps: Vec<PingPongScanStep> = ping_pong_scan_steps(n_blocks, ScanFinalize::None)
+            .into_iter()
+            .skip(1)
+            .collect();
+        // Round uniforms must outlive the encoder they are recorded into.
+        let mut round_params = Vec::with_capacity(steps.len());
+        for step in &steps {
+            round_params.push(uniform_from_val(
+                device,
+                "parser.filter.scan_params",
+                &FilterScanParams {
+                    n_blocks,
+                    stride: step.scan_step,
+                },
+            ));
+        }
+        for (step, scan_params) in steps.iter().zip(&round_params) {
+            let (src, dst) = if step.read_from_a {
+                (&ping, &pong)
+            } else {
+                (&pong, &ping)
+            };
+            let scan_res = HashMap::from([
+                ("gScan".into(), scan_params.as_entire_binding()),
+                ("block_totals_in".into(), src.as_entire_binding()),
+                ("block_totals_out".into(), dst.as_entire_binding()),
+            ]);
+            dispatch(device, &mut encoder, &passes.scan, &scan_res, n_blocks)?;
+        }
+
+        let last_writer_is_ping = steps.last().is_none_or(|step| step.write_to_a);
+        let block_prefix = if last_writer_is_ping { &ping } else { &pong };
+        let mut scatter_res = token_inputs();
+        scatter_res.extend([
+            (
+                "block_prefix_filter".into(),
+                block_prefix.as_entire_binding(),
+            ),
+            ("filtered_kinds".into(), filtered_kinds.as_entire_binding()),
+            ("index_map".into(), index_map.as_entire_binding()),
+            ("filter_status".into(), status.as_entire_binding()),
+        ]);
+        dispatch(device, &mut encoder, &passes.scatter, &scatter_res, n)?;
+
+        // One staging buffer: kept count, then kinds, then the index map.
+        let staging_size = 4 + 2 * token_bytes as u64;
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("parser.filter.readback"),
+            size: staging_size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        encoder.copy_buffer_to_buffer(&status, 0, &staging, 0, 4);
+        encoder.copy_buffer_to_buffer(&filtered_kinds, 0, &staging, 4, token_bytes as u64);
+        encoder.copy_buffer_to_buffer(
+            &index_map,
+            0,
+            &staging,
+            4 + token_bytes as u64,
+            token_bytes as u64,
+        );
+        crate::gpu::passes_core::submit_with_progress(
+            &self.queue,
+            "parser.filter",
+            encoder.finish(),
+        );
+
+        let slice = staging.slice(..);
+        crate::gpu::passes_core::map_readback_blocking(device, &slice, "parser.filter.readback")?;
+        let mapped = slice.get_mapped_range();
+        let words = crate::gpu::decode::decode_u32s(&mapped);
+        drop(mapped);
+        staging.unmap();
+
+        let kept = words[0] as usize;
+        if kept > n as usize {
+            return Err(anyhow!(
+                "token filter kept {kept} of {n} tokens; the GPU count is corrupt"
+            ));
+        }
+        let (kinds, index_map) = words[1..].split_at(n as usize);
+        Ok(FilteredTokenKinds {
+            kinds: kinds[..kept].to_vec(),
+            index_map: index_map[..kept].to_vec(),
+        })
+    }
+}
+
+fn dispatch(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    pd: &PassData,
+    resources: &HashMap<String, wgpu::BindingResource<'_>>,
+    n: u32,
+) -> Result<()> {
+    let bind_group = bind_group::create_bind_group_from_reflection(
+        device,
+        Some(&pd.shader_id),
+        &pd.bind_group_layouts[0],
+        &pd.reflection,
+        0,
+        resources,
+    )?;
+    let (gx, gy, gz) = plan_workgroups(
+        DispatchDim::D1,
+        InputElements::Elements1D(n),
+        pd.thread_group_size,
+    )?;
+    let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+        label: Some(&pd.shader_id),
+        timestamp_writes: None,
+    });
+    pass.set_pipeline(&pd.pipeline);
+    pass.set_bind_group(0, Some(&bind_group), &[]);
+    pass.dispatch_workgroups(gx, gy, gz);
+    Ok(())
+}
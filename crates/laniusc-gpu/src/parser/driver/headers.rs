@@ -0,0 +1,85 @@
+use super::*;
+
+/// Pair counts at or above this scan their offsets on the GPU; smaller inputs
+/// read back only the headers and scan them on the host.
+pub(super) const GPU_HEADER_OFFSETS_MIN_PAIRS: u32 = 1 << 14;
+
+impl GpuParser {
+    /// Headers-only GPU pass over already-classified semantic parser token kinds.
+    ///
+    /// Computes the per-pair action headers and their packed-stream offsets
+    /// without allocating the packed streams, bracket matching, or tree
+    /// buffers, and shares the uploaded action table with [`Self::parse`].
+    pub async fn headers(
+        &self,
+        token_kinds_u32: &[u32],
+        tables: &PrecomputedParseTables,
+    ) -> Result<LLPHeadersResult> {
+        let bufs = ParserBuffers::new_headers_only(
+            &self.device,
+            token_kinds_u32,
+            tables.n_kinds,
+            self.shared_action_table(tables)?,
+            tables,
+        )?;
+        let n_pairs = bufs.n_tokens.saturating_sub(1);
+        let gpu_offsets = n_pairs >= GPU_HEADER_OFFSETS_MIN_PAIRS;
+
+        // Parser buffers are per-call, and cached bind groups hold concrete buffer handles.
+        self.bg_cache
+            .lock()
+            .expect("parser.bg_cache poisoned")
+            .clear();
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("parser.headers.encoder"),
+            });
+        {
+            let mut timer_ref: Option<&mut GpuTimer> = None;
+            let mut dbg_ref_opt: Option<&mut DebugOutput> = None;
+            let mut cache_guard = self.bg_cache.lock().expect("parser.bg_cache poisoned");
+            let mut ctx = PassContext {
+                device: &self.device,
+                encoder: &mut encoder,
+                buffers: &bufs,
+                maybe_timer: &mut timer_ref,
+                maybe_dbg: &mut dbg_ref_opt,
+                bg_cache: Some(&mut *cache_guard),
+                debug_groups: self.options.debug_groups,
+                validation_scopes: self.options.validation_scopes,
+                batch_compute_passes: self.options.batch_compute_passes,
+                dispatch_records: None,
+            };
+            self.passes
+                .llp_pairs
+                .record_pass(&mut ctx, InputElements::Elements1D(n_pairs))?;
+            if gpu_offsets {
+                self.passes
+                    .pack_offsets
+                    .record_scan(ctx.device, ctx.encoder, ctx.buffers)?;
+            }
+        }
+        crate::gpu::passes_core::flush_deferred_compute(&mut encoder);
+
+        let rb = readback::HeaderReadbacks::create(&self.device, &bufs, gpu_offsets);
+        rb.encode_copies(&mut encoder, &bufs);
+        crate::gpu::passes_core::submit_with_optional_validation(
+            &self.device,
+            &self.queue,
+            "parser.headers",
+            encoder.finish(),
+            self.options.validation_scopes,
+            "parser headers",
+        );
+
+        let (headers, offsets) = rb.map_and_decode(&self.device, n_pairs as usize)?;
+        Ok(match offsets {
+            Some((sc_offsets, emit_offsets)) => {
+                LLPHeadersResult::from_scanned_offsets(headers, sc_offsets, emit_offsets)?
+            }
+            None => LLPHeadersResult::from_headers(headers)?,
+        })
+    }
+}
@@ -0,0 +1,147 @@
+//! Adapterless dry runs of one resident parse.
+
+use anyhow::Result;
+
+use super::GpuParser;
+use crate::{
+    gpu::{
+        dry_run::{ArtifactShapes, PassShapes, PipelinePlan},
+        limits::{MAX_PARSER_TOKENS, narrow_u32},
+    },
+    parser::{
+        buffers::{ActionHeader, resident_pair_plan},
+        passes::plan_pair_passes,
+        tables::PrecomputedParseTables,
+    },
+};
+
+/// Buffers, the shared action table, and dispatches of one planned parse.
+pub type ParsePlan = PipelinePlan;
+
+impl GpuParser {
+    /// Plans a resident parse of `n_tokens` lexer tokens without creating
+    /// any device objects, taking pass shapes from the built reflection
+    /// artifacts.
+    ///
+    /// The plan covers the stages sized on the host before parsing: the
+    /// pair headers, the packed streams at their worst-case totals for
+    /// `tables`, and stack-effect validation. Resident parses dispatch the
+    /// pair stage indirectly, so its planned workgroup counts are upper
+    /// bounds. The tree and HIR stages, sized from the GPU-counted tree, are
+    /// not planned. Fails when the token count or a packed total exceeds its
+    /// GPU limit, or an artifact does not match its pass.
+    pub fn plan(n_tokens: usize, tables: &PrecomputedParseTables) -> Result<ParsePlan> {
+        Self::plan_with_shapes(n_tokens, tables, &ArtifactShapes)
+    }
+
+    /// Like [`GpuParser::plan`], with pass shapes from `shapes`.
+    pub fn plan_with_shapes(
+        n_tokens: usize,
+        tables: &PrecomputedParseTables,
+        shapes: &dyn PassShapes,
+    ) -> Result<ParsePlan> {
+        let token_capacity = narrow_u32("parser tokens", n_tokens as u64, MAX_PARSER_TOKENS)?;
+        let pairs = resident_pair_plan(token_capacity, tables)?;
+        let action_table = tables
+            .to_action_header_grid_bytes()
+            .len()
+            .max(core::mem::size_of::<ActionHeader>());
+
+        let mut plan = ParsePlan::new(pairs.buffers);
+        plan.share_table("parser.action_table", action_table as u64);
+        plan_pair_passes(
+            &mut plan,
+            shapes,
+            pairs.n_tokens,
+            pairs.stack_changes,
+            false,
+        )?;
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu::{dry_run::DeclaredShapes, limits::LimitError, passes_core::InputElements};
+
+    const SHAPES: DeclaredShapes = DeclaredShapes {
+        group_width: 256,
+        storage_buffers: 8,
+    };
+
+    fn tables() -> PrecomputedParseTables {
+        PrecomputedParseTables::load_bin_bytes(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tables/parse_tables.bin"
+        )))
+        .expect("load generated parse tables")
+    }
+
+    #[test]
+    fn streams_are_sized_for_the_widest_pair() {
+        let plan = GpuParser::plan_with_shapes(1000, &tables(), &SHAPES).unwrap();
+        // 1000 tokens plus both sentinels make 1001 pairs.
+        let count = |label: &str| plan.buffers.entry(label).unwrap().count;
+        assert_eq!(count("pack.sc_offsets"), 1001);
+        let stack_changes = count("pack.out_sc");
+        assert!(stack_changes > 1001 && stack_changes % 1001 == 0);
+        assert_eq!(count("pack.out_emit") % 1001, 0);
+        assert_eq!(count("brackets.block_sum"), stack_changes.div_ceil(256));
+        assert_eq!(count("brackets.layer"), stack_changes);
+        // Production parses reuse the match table as tree-sized scratch.
+        assert_eq!(count("brackets.match_for_index"), count("pack.out_emit"));
+        assert!(plan.buffers.entry("parser.out_headers").is_some());
+        assert_eq!(plan.shared_tables[0].0, "parser.action_table");
+        plan.check_limits(&wgpu::Limits::default()).unwrap();
+    }
+
+    #[test]
+    fn passes_follow_the_recorded_pair_order() {
+        let plan = GpuParser::plan_with_shapes(1000, &tables(), &SHAPES).unwrap();
+        let mut labels: Vec<&str> = plan
+            .dispatches
+            .iter()
+            .map(|d| d.label.split('[').next().unwrap())
+            .collect();
+        let blocks = plan.buffers.entry("brackets.block_sum").unwrap().count as u32;
+        let min_tree_steps = labels
+            .iter()
+            .filter(|&&l| l == "brackets_04_build_min_tree")
+            .count();
+        // The leaves, then one step per level up to the root.
+        assert_eq!(
+            min_tree_steps as u32,
+            blocks.next_power_of_two().trailing_zeros() + 1
+        );
+        labels.dedup();
+        assert_eq!(
+            labels,
+            [
+                "llp_pairs",
+                "pack_offsets_scan",
+                "pack_offsets_status",
+                "pack_varlen",
+                "brackets_01_scan_inblock",
+                "brackets_02_scan_block_prefix",
+                "brackets_03_apply_prefix",
+                "brackets_04_build_min_tree",
+                "brackets_pse_04_pair_by_layer",
+                "parser_status_from_brackets",
+            ]
+        );
+        let varlen = plan
+            .dispatches
+            .iter()
+            .find(|d| d.label == "pack_varlen")
+            .unwrap();
+        assert_eq!(varlen.elements, Some(InputElements::Elements1D(1001 * 256)));
+        assert_eq!(varlen.workgroups, Some((1001, 1, 1)));
+    }
+
+    #[test]
+    fn oversized_totals_fail_before_planning() {
+        let err = GpuParser::plan_with_shapes(1 << 31, &tables(), &SHAPES).unwrap_err();
+        assert!(err.downcast_ref::<LimitError>().is_some(), "{err}");
+    }
+}
@@ -5,7 +5,7 @@ use super::{
     Ll1AcceptResult,
     RecordedHirSemanticCount,
     RecordedResidentLl1HirCheck,
-    support::{bool_from_env, read_u32_words, stamp_timer},
+    support::{read_u32_words, stamp_timer},
 };
 use crate::{
     gpu::timer::GpuTimer,
@@ -104,8 +104,8 @@ impl GpuParser {
         let words = read_u32_words(&mapped, 30)?;
         drop(mapped);
         recorded.count_readback.unmap();
-        if crate::gpu::env::env_bool_truthy("LANIUS_GPU_BUFFER_BREAKDOWN", false) {
-            eprintln!(
+        if log::log_enabled!(target: crate::logging::PARSER_GPU, log::Level::Debug) {
+            log::debug!(target: crate::logging::PARSER_GPU,
                 "gpu_hir_rows semantic={} canonical={} candidates={} unique_anchors={} call_args={} params={} type_args={} generic_params={} paths={} path_segments={} fields={} variants={} variant_payloads={} match_arms={} match_payloads={} array_elements={} strings={} methods={} predicates={} max_anchor={} anchor_sum={} capacity={} status={} detail_row={} reason_bits={} bad_ref_raw_plus_one={} bad_ref_input_plus_one={} bad_ref_anchor_plus_one={} bad_ref_winner_plus_one={}",
                 words[0],
                 words[1],
@@ -176,7 +176,7 @@ impl GpuParser {
             .resident_buffers
             .lock()
             .expect("parser.resident_buffers poisoned");
-        let bufs = self.resident_debug_buffers_for(&mut resident_guard, token_capacity, tables);
+        let bufs = self.resident_debug_buffers_for(&mut resident_guard, token_capacity, tables)?;
 
         let mut encoder = self
             .device
@@ -202,13 +202,17 @@ impl GpuParser {
         });
         encoder.copy_buffer_to_buffer(&bufs.ll1_status, 0, &status_readback, 0, 24);
 
-        let recorded_parser = RecordedResidentLl1HirCheck { status_readback };
+        let recorded_parser = RecordedResidentLl1HirCheck {
+            status_readback,
+            dispatch_records: Vec::new(),
+            bracket_fill: None,
+        };
         let recorded_more = match record_more(bufs, &mut encoder) {
             Ok(recorded) => recorded,
             Err(err) => return Ok(Err(err)),
         };
 
-        let use_scopes = bool_from_env("LANIUS_VALIDATION_SCOPES", false);
+        let use_scopes = self.options.validation_scopes;
         crate::gpu::passes_core::submit_with_optional_validation(
             &self.device,
             &self.queue,
@@ -244,6 +248,9 @@ impl GpuParser {
         )?;
         let mapped = slice.get_mapped_range();
         let words = read_u32_words(&mapped, 6)?;
+        if let Some(fill) = &recorded.bracket_fill {
+            fill.store(&mapped)?;
+        }
         drop(mapped);
         recorded.status_readback.unmap();
 
@@ -1,3 +1,5 @@
+use anyhow::Result;
+
 use super::{GpuParser, ResidentParserBufferCache, support::table_fingerprint};
 use crate::{
     lexer::features::CONSERVATIVE_PARSER_FEATURES,
@@ -11,7 +13,7 @@ impl GpuParser {
         slot: &'a mut Option<ResidentParserBufferCache>,
         token_capacity: u32,
         tables: &PrecomputedParseTables,
-    ) -> &'a ParserBuffers {
+    ) -> Result<&'a ParserBuffers> {
         self.resident_buffers_for_with_tree_capacity_and_debug(
             slot,
             token_capacity,
@@ -29,7 +31,7 @@ impl GpuParser {
         slot: &'a mut Option<ResidentParserBufferCache>,
         token_capacity: u32,
         tables: &PrecomputedParseTables,
-    ) -> &'a ParserBuffers {
+    ) -> Result<&'a ParserBuffers> {
         self.resident_buffers_for_with_tree_capacity_and_debug(
             slot,
             token_capacity,
@@ -48,7 +50,7 @@ impl GpuParser {
         token_capacity: u32,
         tables: &PrecomputedParseTables,
         tree_capacity_override: Option<u32>,
-    ) -> &'a ParserBuffers {
+    ) -> Result<&'a ParserBuffers> {
         self.resident_buffers_for_with_tree_capacity_and_debug(
             slot,
             token_capacity,
@@ -69,7 +71,7 @@ impl GpuParser {
         tables: &PrecomputedParseTables,
         tree_capacity_override: Option<u32>,
         parser_feature_flags: u32,
-    ) -> &'a ParserBuffers {
+    ) -> Result<&'a ParserBuffers> {
         self.resident_buffers_for_with_tree_capacity_and_debug(
             slot,
             token_capacity,
@@ -91,7 +93,7 @@ impl GpuParser {
         tables: &PrecomputedParseTables,
         tree_capacity_override: Option<u32>,
         parser_feature_flags: u32,
-    ) -> &'a ParserBuffers {
+    ) -> Result<&'a ParserBuffers> {
         self.resident_buffers_for_with_tree_capacity_and_debug(
             slot,
             token_capacity,
@@ -112,17 +114,16 @@ impl GpuParser {
         tree_capacity_override: Option<u32>,
         retain_debug_hir_buffers: bool,
         parser_feature_flags: u32,
-    ) -> &'a ParserBuffers {
+    ) -> Result<&'a ParserBuffers> {
         let fingerprint = table_fingerprint(tables);
         let wanted_capacity = token_capacity.max(1);
-        let wanted_tree_capacity = tree_capacity_override
-            .map(|capacity| capacity.max(1))
-            .unwrap_or_else(|| {
-                crate::parser::buffers::resident_partial_parse_tree_capacity_for_tables(
-                    wanted_capacity,
-                    tables,
-                )
-            });
+        let wanted_tree_capacity = match tree_capacity_override {
+            Some(capacity) => capacity.max(1),
+            None => crate::parser::buffers::resident_partial_parse_tree_capacity_for_tables(
+                wanted_capacity,
+                tables,
+            )?,
+        };
         let needs_allocate = slot.as_ref().is_none_or(|cached| {
             cached.table_fingerprint != fingerprint
                 || cached.token_capacity != wanted_capacity
@@ -132,9 +133,9 @@ impl GpuParser {
                 || cached.buffers.source_capacity < source_capacity.max(1)
         });
 
-        if crate::gpu::env::env_bool_truthy("LANIUS_GPU_COMPILE_HOST_TIMING", false) {
+        if self.options.host_timing {
             if let Some(cached) = slot.as_ref() {
-                eprintln!(
+                log::info!(target: crate::logging::PARSER_GPU,
                     "[gpu_compile_host_timer] parser.resident_cache: allocate={needs_allocate} wanted_tokens={wanted_capacity} cached_tokens={} wanted_tree={wanted_tree_capacity} cached_tree={} wanted_features=0x{parser_feature_flags:08x} cached_features=0x{:08x} wanted_debug={retain_debug_hir_buffers} cached_debug={}",
                     cached.token_capacity,
                     cached.buffers.tree_capacity,
@@ -142,7 +143,7 @@ impl GpuParser {
                     cached.retain_debug_hir_buffers,
                 );
             } else {
-                eprintln!(
+                log::info!(target: crate::logging::PARSER_GPU,
                     "[gpu_compile_host_timer] parser.resident_cache: allocate=true reason=empty wanted_tokens={wanted_capacity} wanted_tree={wanted_tree_capacity} wanted_features=0x{parser_feature_flags:08x} wanted_debug={retain_debug_hir_buffers}"
                 );
             }
@@ -158,7 +159,11 @@ impl GpuParser {
                 .resident_token_kind_bind_groups
                 .lock()
                 .expect("parser.resident_token_kind_bind_groups poisoned") = None;
-            let _ = self.device.poll(wgpu::PollType::wait_indefinitely());
+            if let Err(err) =
+                crate::gpu::poll::wait_for_submitted_work(&self.device, "parser.resident.realloc")
+            {
+                log::warn!("waiting before parser buffer reallocation failed: {err:#}");
+            }
 
             // Resident parser buffers dominate VRAM because tree/HIR scratch scales
             // from token capacity. Allocate the exact required capacity instead of
@@ -180,16 +185,20 @@ impl GpuParser {
                     tree_capacity_override,
                     retain_debug_hir_buffers,
                     parser_feature_flags,
-                ),
+                )?,
             });
             self.bg_cache
                 .lock()
                 .expect("parser.bg_cache poisoned")
                 .clear();
         }
-        &slot
-            .as_ref()
+        let buffers = &mut slot
+            .as_mut()
             .expect("resident parser buffers allocated")
-            .buffers
+            .buffers;
+        // A cached bracket result applies only to the parse that uploaded it,
+        // even if that parse failed before clearing the flag.
+        buffers.stack_effect_cached = false;
+        Ok(buffers)
     }
 }
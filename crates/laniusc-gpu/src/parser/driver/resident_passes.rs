@@ -12,6 +12,30 @@ impl GpuParser {
         include_hir_spans: bool,
         literal_source: Option<(u32, &wgpu::Buffer, &wgpu::Buffer)>,
         timer_ref: &mut Option<&mut GpuTimer>,
+    ) -> Result<()> {
+        self.record_ll1_resident_passes_with_records(
+            encoder,
+            bufs,
+            include_tree,
+            include_hir_spans,
+            literal_source,
+            timer_ref,
+            None,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    /// Records the resident pipeline, appending planned dispatches to
+    /// `dispatch_records` when given.
+    pub(super) fn record_ll1_resident_passes_with_records(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        bufs: &ParserBuffers,
+        include_tree: bool,
+        include_hir_spans: bool,
+        literal_source: Option<(u32, &wgpu::Buffer, &wgpu::Buffer)>,
+        timer_ref: &mut Option<&mut GpuTimer>,
+        dispatch_records: Option<&mut Vec<DispatchRecord>>,
     ) -> Result<()> {
         let mut no_timer: Option<&mut GpuTimer> = None;
         let mut dbg_ref: Option<&mut DebugOutput> = None;
@@ -23,6 +47,10 @@ impl GpuParser {
             maybe_timer: &mut no_timer,
             maybe_dbg: &mut dbg_ref,
             bg_cache: Some(&mut *cache_guard),
+            debug_groups: self.options.debug_groups,
+            validation_scopes: self.options.validation_scopes,
+            batch_compute_passes: self.options.batch_compute_passes,
+            dispatch_records,
         };
 
         self.record_active_pair_dispatch_args(ctx.encoder, bufs)?;
@@ -69,9 +97,7 @@ impl GpuParser {
             stamp_timer(timer_ref, ctx.encoder, "parser.tree_prefix_02");
             self.passes.tree_prefix_03.record_pass(
                 &mut ctx,
-                crate::gpu::passes_core::InputElements::Elements1D(
-                    bufs.tree_capacity.saturating_add(1),
-                ),
+                crate::gpu::passes_core::InputElements::Elements1D(bufs.tree_capacity + 1),
             )?;
             stamp_timer(timer_ref, ctx.encoder, "parser.tree_prefix_03");
             self.passes
@@ -206,7 +232,7 @@ impl GpuParser {
                 ctx.buffers,
             )?;
             stamp_timer(timer_ref, ctx.encoder, "parser.hir_semantic_parent_step");
-            if parser_compute_pass_batching_enabled(timer_ref) {
+            if parser_compute_pass_batching_enabled(&self.options, timer_ref) {
                 self.passes
                     .hir_semantic_parent_scatter
                     .record_pass_indirect(&mut ctx, &bufs.hir_semantic_dispatch_args)?;
@@ -675,7 +701,7 @@ impl GpuParser {
                     .hir_param_id_apply
                     .record_pass_indirect(&mut ctx, &bufs.hir_list_rank_dispatch_args)?;
                 stamp_timer(timer_ref, ctx.encoder, "parser.hir_param_id_apply");
-                if parser_compute_pass_batching_enabled(timer_ref) {
+                if parser_compute_pass_batching_enabled(&self.options, timer_ref) {
                     {
                         let bg_cache = ctx
                             .bg_cache
@@ -1,11 +1,6 @@
 use anyhow::Result;
 
-use super::{
-    GpuParser,
-    Ll1AcceptResult,
-    ResidentParseResult,
-    support::{bool_from_env, read_u32_words},
-};
+use super::{GpuParser, Ll1AcceptResult, ResidentParseResult, support::read_u32_words};
 use crate::parser::{
     buffers::ParserBuffers,
     readback::{
@@ -2432,7 +2427,7 @@ impl GpuParser {
         let readbacks = ResidentTreeReadbacks::create(&self.device, bufs);
         readbacks.encode_copies(&mut encoder, bufs);
 
-        let use_scopes = bool_from_env("LANIUS_VALIDATION_SCOPES", false);
+        let use_scopes = self.options.validation_scopes;
         crate::gpu::passes_core::submit_with_optional_validation(
             &self.device,
             &self.queue,
@@ -2443,11 +2438,7 @@ impl GpuParser {
         );
 
         readbacks.map_all();
-        crate::gpu::passes_core::wait_for_map_progress(
-            &self.device,
-            "parser.resident-tree",
-            wgpu::PollType::wait_indefinitely(),
-        );
+        crate::gpu::passes_core::wait_for_map_progress(&self.device, "parser.resident-tree")?;
         readbacks.decode(bufs)
     }
 }
@@ -0,0 +1,1389 @@
+use std::ops::Range;
+
+use super::*;
+use crate::{
+    gpu::{
+        buffers::LaniusBuffer,
+        limits::{LimitError, MAX_EMITS, MAX_STACK_CHANGES, narrow_u32},
+        timer::{TimerHealth, TimerReadout},
+    },
+    parser::{
+        cache::ResidentBracketFill,
+        tables::{Ll1RejectionContext, PrecomputedParseTables},
+    },
+};
+
+/// Debug readback for delimiter-pair validation.
+///
+/// An empty stack-change stream (for example a lone sentinel, or one token
+/// whose pair has no stack effect) is valid with both depths `0` and an
+/// empty `match_for_index`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BracketsMatchResult {
+    pub valid: bool,
+    pub final_depth: i32,
+    pub min_depth: i32,
+    pub match_for_index: Vec<u32>,
+    /// Stack-change index of the first pop that takes the depth negative, or
+    /// `sc_stream.len()` when the depth never goes negative.
+    pub valid_up_to: u32,
+    /// Stack-change index of the first push still open at end of input.
+    pub first_unclosed_push: Option<u32>,
+}
+
+/// Per-pair action headers and packed-stream offsets from [`GpuParser::headers`].
+pub struct LLPHeadersResult {
+    pub headers: Vec<ActionHeader>,
+    /// Exclusive prefix sum of `push_len + pop_count` over the headers.
+    pub sc_offsets: Vec<u32>,
+    /// Exclusive prefix sum of `emit_len` over the headers.
+    pub emit_offsets: Vec<u32>,
+    pub total_sc: u32,
+    pub total_emit: u32,
+}
+
+impl LLPHeadersResult {
+    /// Scans header lengths into offsets on the host.
+    ///
+    /// Fails when a running total overflows the GPU stream limits.
+    pub(super) fn from_headers(headers: Vec<ActionHeader>) -> Result<Self, LimitError> {
+        let mut sc_offsets = Vec::with_capacity(headers.len());
+        let mut emit_offsets = Vec::with_capacity(headers.len());
+        let (mut total_sc, mut total_emit) = (0u64, 0u64);
+        for header in &headers {
+            sc_offsets.push(narrow_u32("stack changes", total_sc, MAX_STACK_CHANGES)?);
+            emit_offsets.push(narrow_u32("emits", total_emit, MAX_EMITS)?);
+            total_sc += u64::from(header.push_len) + u64::from(header.pop_count);
+            total_emit += u64::from(header.emit_len);
+        }
+        Ok(Self {
+            headers,
+            sc_offsets,
+            emit_offsets,
+            total_sc: narrow_u32("stack changes", total_sc, MAX_STACK_CHANGES)?,
+            total_emit: narrow_u32("emits", total_emit, MAX_EMITS)?,
+        })
+    }
+
+    /// Completes GPU-scanned offsets with totals taken from the last header.
+    ///
+    /// Fails when the last offset plus its header overflows the GPU stream
+    /// limits.
+    pub(super) fn from_scanned_offsets(
+        headers: Vec<ActionHeader>,
+        sc_offsets: Vec<u32>,
+        emit_offsets: Vec<u32>,
+    ) -> Result<Self, LimitError> {
+        let (total_sc, total_emit) = match (headers.last(), sc_offsets.last(), emit_offsets.last())
+        {
+            (Some(header), Some(&sc), Some(&emit)) => (
+                u64::from(sc) + u64::from(header.push_len) + u64::from(header.pop_count),
+                u64::from(emit) + u64::from(header.emit_len),
+            ),
+            _ => (0, 0),
+        };
+        Ok(Self {
+            headers,
+            sc_offsets,
+            emit_offsets,
+            total_sc: narrow_u32("stack changes", total_sc, MAX_STACK_CHANGES)?,
+            total_emit: narrow_u32("emits", total_emit, MAX_EMITS)?,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Six-word LL/parser status decoded into host fields.
+pub struct Ll1AcceptResult {
+    pub accepted: bool,
+    pub error_pos: u32,
+    pub error_code: u32,
+    pub detail: u32,
+    pub steps: u32,
+    pub emit_len: u32,
+}
+
+impl Ll1AcceptResult {
+    /// Decodes the six-word parser status buffer used by GPU parser passes.
+    pub(crate) fn from_status_words(words: &[u32]) -> Self {
+        Self {
+            accepted: words[0] != 0,
+            error_pos: words[1],
+            error_code: words[2],
+            detail: words[3],
+            steps: words[4],
+            emit_len: words[5],
+        }
+    }
+
+    /// Formats a parser rejection without exposing internal GPU pass names.
+    pub fn rejection_message(&self) -> String {
+        if self.accepted {
+            return "parse accepted".to_string();
+        }
+
+        match self.error_code {
+            3 => format!(
+                "parse error: this input is too large for the current parser buffers \
+                 (needed {} output entries, capacity {})",
+                self.detail, self.emit_len
+            ),
+            4 => "parse error: input is incomplete or contains mismatched syntax".to_string(),
+            _ => "parse error: could not match the grammar".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Parser rejection class owned by the parser boundary.
+pub enum ParserFailureKind {
+    /// The LL(1) acceptance pass rejected the token-kind stream.
+    Ll1Rejected,
+}
+
+#[derive(Clone, Debug)]
+/// Structured parser failure payload consumed by compiler diagnostics.
+pub struct ParserFailure {
+    pub kind: ParserFailureKind,
+    ll1: Ll1AcceptResult,
+    semantic_token_kinds: Option<Vec<u32>>,
+    ll1_rejection: Option<Ll1RejectionContext>,
+}
+
+impl ParserFailure {
+    /// Builds a parser failure from a rejected LL(1) status and optional token-kind readback.
+    pub fn from_ll1_rejection(
+        ll1: Ll1AcceptResult,
+        parse_tables: &PrecomputedParseTables,
+        semantic_token_kinds: Option<Vec<u32>>,
+    ) -> Self {
+        debug_assert!(
+            !ll1.accepted,
+            "accepted LL(1) status should not be reported as a parser failure"
+        );
+        let ll1_rejection = semantic_token_kinds
+            .as_deref()
+            .and_then(|kinds| parse_tables.diagnose_ll1_rejection(kinds));
+        Self {
+            kind: ParserFailureKind::Ll1Rejected,
+            ll1,
+            semantic_token_kinds,
+            ll1_rejection,
+        }
+    }
+
+    /// Raw LL(1) status. This remains useful for low-level fallback positioning.
+    pub fn ll1(&self) -> &Ll1AcceptResult {
+        &self.ll1
+    }
+
+    /// Current parser token-kind stream, when diagnostic readback succeeded.
+    pub fn semantic_token_kinds(&self) -> Option<&[u32]> {
+        self.semantic_token_kinds.as_deref()
+    }
+
+    /// Table-derived expected/found context, when available.
+    pub fn ll1_rejection(&self) -> Option<&Ll1RejectionContext> {
+        self.ll1_rejection.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        LLPHeadersResult,
+        LimitError,
+        Ll1AcceptResult,
+        MAX_EMITS,
+        MAX_STACK_CHANGES,
+        PairPrefix,
+        ParseResult,
+        ParseTimings,
+        ParseWork,
+        ParserFailure,
+        PassElements,
+        clamped_bracket_depths,
+        valid_pair_prefix,
+    };
+    use crate::{
+        gpu::timer::{TimerReadout, TimerStamp},
+        lexer::{
+            Token,
+            tables::tokens::{N_KINDS, TokenKind},
+        },
+        parser::{
+            buffers::ActionHeader,
+            tables::{
+                INVALID_TABLE_ENTRY,
+                Ll1ParseErrorCode,
+                PrecomputedParseTables,
+                build_mvp_precomputed_tables,
+                encode_pop,
+                encode_push,
+            },
+        },
+    };
+
+    fn tiny_ident_semicolon_table() -> PrecomputedParseTables {
+        let mut tables = PrecomputedParseTables::new(4, 1);
+        tables.n_nonterminals = 1;
+        tables.start_nonterminal = 0;
+        tables.ll1_predict = vec![INVALID_TABLE_ENTRY; 4];
+        tables.ll1_predict[1] = 0;
+        tables.prod_rhs_off = vec![0];
+        tables.prod_rhs_len = vec![2];
+        tables.prod_rhs = vec![1, 3];
+        tables
+    }
+
+    fn bracket_kinds(source: &str) -> Vec<u32> {
+        let mut kinds = vec![0];
+        kinds.extend(source.split_whitespace().map(|token| match token {
+            "(" => TokenKind::GroupLParen as u32,
+            ")" => TokenKind::GroupRParen as u32,
+            "[" => TokenKind::IndexLBracket as u32,
+            "]" => TokenKind::IndexRBracket as u32,
+            "{" => TokenKind::LBrace as u32,
+            "}" => TokenKind::RBrace as u32,
+            _ => TokenKind::Ident as u32,
+        }));
+        kinds.push(0);
+        kinds
+    }
+
+    /// Host pair headers and streams for bracket tables that emit each token kind.
+    fn cpu_pair_streams(kinds: &[u32]) -> (Vec<ActionHeader>, Vec<u32>, Vec<u32>) {
+        let mut tables = build_mvp_precomputed_tables(N_KINDS, vec![0]);
+        for prev in 0..N_KINDS {
+            for this in 1..N_KINDS {
+                tables.set_pp_for_pair(prev, this, &[this]);
+            }
+            tables.set_sc_for_pair(prev, TokenKind::LBrace as u32, &[encode_push(2)]);
+            tables.set_sc_for_pair(prev, TokenKind::RBrace as u32, &[encode_pop(2)]);
+        }
+        let mut headers = Vec::new();
+        let mut sc_stream = Vec::new();
+        for pair in kinds.windows(2) {
+            let sc = tables.test_cpu_stack_change_stream(pair);
+            let pushes = sc.iter().filter(|&&code| code & 1 == 1).count() as u32;
+            headers.push(ActionHeader {
+                push_len: pushes,
+                emit_len: tables.test_cpu_partial_parse_stream(pair).len() as u32,
+                pop_count: sc.len() as u32 - pushes,
+                ..ActionHeader::default()
+            });
+            sc_stream.extend(sc);
+        }
+        (
+            headers,
+            sc_stream,
+            tables.test_cpu_partial_parse_stream(kinds),
+        )
+    }
+
+    #[test]
+    fn valid_prefix_stops_before_the_pair_with_the_stray_closer() {
+        let (headers, sc_stream, emit_stream) = cpu_pair_streams(&bracket_kinds("a ( b ) ) c ( d"));
+        let (_, clean_sc, clean_emit) = cpu_pair_streams(&bracket_kinds("a ( b )"));
+
+        // The stray `)` is the third stack change: push `(`, pop `)`, pop `)`.
+        let prefix = valid_pair_prefix(&headers, 2);
+
+        assert_eq!(prefix.pairs, 4);
+        assert_eq!(sc_stream[..prefix.sc_len], clean_sc[..]);
+        assert_eq!(emit_stream[..prefix.emit_len], clean_emit[..]);
+    }
+
+    #[test]
+    fn valid_prefix_keeps_every_pair_when_depth_never_goes_negative() {
+        let (headers, sc_stream, emit_stream) = cpu_pair_streams(&bracket_kinds("( a ( b"));
+
+        assert_eq!(
+            valid_pair_prefix(&headers, sc_stream.len() as u32),
+            PairPrefix {
+                pairs: headers.len(),
+                sc_len: sc_stream.len(),
+                emit_len: emit_stream.len(),
+            }
+        );
+    }
+
+    #[test]
+    fn bracket_depths_report_the_depth_outside_openers_and_inside_closers() {
+        let (headers, sc_stream, _) =
+            cpu_pair_streams(&bracket_kinds("f ( a [ b ( c ) ] , { d } )"));
+
+        let (depths, max_depth) = clamped_bracket_depths(&headers, &sc_stream, true, true);
+
+        assert_eq!(depths, [0, 0, 1, 1, 2, 2, 3, 3, 2, 1, 1, 2, 2, 1]);
+        assert_eq!(max_depth, 3);
+        assert_eq!(
+            clamped_bracket_depths(&headers, &sc_stream, true, false),
+            (Vec::new(), 3)
+        );
+    }
+
+    #[test]
+    fn timer_stamps_become_per_pass_times_over_their_element_counts() {
+        let work = ParseWork {
+            n_pairs: 10,
+            total_sc: 4,
+            total_emit: 7,
+        };
+        let stamps = [
+            ("BEGIN", 100),
+            ("llp_pairs", 120),
+            ("brackets_01_scan_inblock", 125),
+            ("tree_parent", 155),
+            (ParseTimings::RESOLVE_LABEL, 300),
+        ]
+        .map(|(label, ticks)| TimerStamp {
+            label: label.to_string(),
+            epoch: 0,
+            ticks,
+        });
+        let readout = TimerReadout::new(stamps.into(), 5);
+        let timings = ParseTimings::from_readout(&readout, 2.0, work);
+        let passes = timings
+            .passes
+            .iter()
+            .map(|p| (p.label.as_str(), p.gpu_ns, p.scales_with, p.elements))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            passes,
+            [
+                ("llp_pairs", 40.0, PassElements::Pairs, 10),
+                (
+                    "brackets_01_scan_inblock",
+                    10.0,
+                    PassElements::StackChanges,
+                    4
+                ),
+                ("tree_parent", 60.0, PassElements::Emits, 7),
+            ]
+        );
+        assert_eq!(timings.total_ms(), 110.0e-6);
+
+        // Chunks sum per pass and per count.
+        let mut summed = timings.clone();
+        summed.accumulate(&timings);
+        assert_eq!(summed.work.total_emit, 14);
+        assert_eq!(summed.passes.len(), 3);
+        assert_eq!(summed.passes[0].gpu_ns, 80.0);
+        assert_eq!(summed.passes[0].elements, 20);
+        assert_eq!(summed.timer_health.recorded, 10);
+        assert!(summed.timer_health.is_clean());
+    }
+
+    #[test]
+    fn bracket_depths_clamp_at_zero_after_a_stray_closer() {
+        let (headers, sc_stream, _) = cpu_pair_streams(&bracket_kinds("( a ) ) ( b } c"));
+
+        let (depths, max_depth) = clamped_bracket_depths(&headers, &sc_stream, true, true);
+
+        assert_eq!(depths, [0, 1, 1, 0, 0, 1, 1, 0]);
+        assert_eq!(max_depth, 1);
+    }
+
+    #[test]
+    fn depth_spans_merge_equal_depth_runs_into_byte_ranges() {
+        let source = "f(a[b(c)], {d})";
+        let tokens: Vec<Token> = source
+            .char_indices()
+            .filter(|(_, c)| !c.is_whitespace())
+            .map(|(start, _)| Token::new(TokenKind::Ident, start, 1))
+            .collect();
+        let spaced: Vec<String> = source
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(String::from)
+            .collect();
+        let (headers, sc_stream, _) = cpu_pair_streams(&bracket_kinds(&spaced.join(" ")));
+        let mut result = ParseResult::empty();
+        (result.depth_at_token, result.max_bracket_depth) =
+            clamped_bracket_depths(&headers, &sc_stream, true, true);
+
+        assert_eq!(
+            result.depth_spans(&tokens),
+            [
+                (0..2, 0),
+                (2..4, 1),
+                (4..6, 2),
+                (6..8, 3),
+                (8..9, 2),
+                (9..12, 1),
+                (12..14, 2),
+                (14..15, 1),
+            ]
+        );
+        assert!(ParseResult::empty().depth_spans(&tokens).is_empty());
+    }
+
+    #[test]
+    fn stack_changes_map_to_their_pair_end_token_and_through_the_filter() {
+        let tables = build_mvp_precomputed_tables(N_KINDS, vec![0]);
+        let (headers, sc_stream, _) = cpu_pair_streams(&bracket_kinds("a ( b ) c"));
+        let mut result = ParseResult::empty();
+        result.headers = headers;
+        result.sc_stream = sc_stream;
+
+        assert_eq!(result.sc_stream.len(), 2);
+        assert_eq!(result.token_of_stack_change(0, &tables), Some(1));
+        assert_eq!(result.token_of_stack_change(1, &tables), Some(3));
+        assert_eq!(result.token_of_stack_change(2, &tables), None);
+        assert_eq!(result.input_token_index(3), Some(3));
+
+        // Every other input token was trivia the filter dropped.
+        result.token_index_map = vec![1, 3, 5, 7, 9];
+        assert_eq!(result.input_token_index(3), Some(7));
+        assert_eq!(result.input_token_index(5), None);
+    }
+
+    #[test]
+    fn parser_rejection_message_hides_gpu_ll1_details() {
+        let result = Ll1AcceptResult {
+            accepted: false,
+            error_pos: 7,
+            error_code: 2,
+            detail: 19,
+            steps: 11,
+            emit_len: 0,
+        };
+
+        let message = result.rejection_message();
+        assert_eq!(message, "parse error: could not match the grammar");
+        assert!(!message.contains("near token"));
+        assert!(!message.contains("status token"));
+        assert!(!message.contains("GPU LL(1)"));
+    }
+
+    #[test]
+    fn parser_rejection_message_decodes_stack_effect_depth() {
+        let result = Ll1AcceptResult {
+            accepted: false,
+            error_pos: 0,
+            error_code: 4,
+            detail: (-2i32) as u32,
+            steps: 14,
+            emit_len: 0,
+        };
+
+        let message = result.rejection_message();
+        assert_eq!(
+            message,
+            "parse error: input is incomplete or contains mismatched syntax"
+        );
+        assert!(!message.contains("depth"));
+        assert!(!message.contains("GPU LL(1)"));
+    }
+
+    #[test]
+    fn parser_status_words_decode_to_accept_result() {
+        let result = Ll1AcceptResult::from_status_words(&[0, 3, 4, 5, 6, 7]);
+
+        assert!(!result.accepted);
+        assert_eq!(result.error_pos, 3);
+        assert_eq!(result.error_code, 4);
+        assert_eq!(result.detail, 5);
+        assert_eq!(result.steps, 6);
+        assert_eq!(result.emit_len, 7);
+    }
+
+    #[test]
+    fn host_and_scanned_header_offsets_agree() {
+        let header = |push_len, pop_count, emit_len| ActionHeader {
+            push_len,
+            emit_len,
+            pop_tag: 0,
+            pop_count,
+        };
+        let headers = vec![header(1, 0, 2), header(0, 2, 0), header(3, 1, 1)];
+
+        let host = LLPHeadersResult::from_headers(headers.clone()).expect("small totals");
+        assert_eq!(host.sc_offsets, vec![0, 1, 3]);
+        assert_eq!(host.emit_offsets, vec![0, 2, 2]);
+        assert_eq!((host.total_sc, host.total_emit), (7, 3));
+
+        let scanned = LLPHeadersResult::from_scanned_offsets(
+            headers,
+            host.sc_offsets.clone(),
+            host.emit_offsets.clone(),
+        )
+        .expect("small totals");
+        assert_eq!(
+            (scanned.total_sc, scanned.total_emit),
+            (host.total_sc, host.total_emit)
+        );
+        assert_eq!(
+            LLPHeadersResult::from_headers(Vec::new())
+                .expect("empty headers")
+                .total_sc,
+            0
+        );
+    }
+
+    #[test]
+    fn header_totals_past_the_gpu_limits_are_rejected() {
+        let header = |push_len, emit_len| ActionHeader {
+            push_len,
+            emit_len,
+            pop_tag: 0,
+            pop_count: 0,
+        };
+        let err = LLPHeadersResult::from_headers(vec![header(u32::MAX, 0), header(1, 0)])
+            .err()
+            .expect("stack-change total overflows");
+        assert_eq!(
+            err,
+            LimitError::InputTooLarge {
+                what: "stack changes",
+                value: u64::from(u32::MAX) + 1,
+                limit: u64::from(MAX_STACK_CHANGES),
+            }
+        );
+        assert!(LLPHeadersResult::from_headers(vec![header(0, MAX_EMITS), header(0, 1)]).is_err());
+
+        let scanned =
+            LLPHeadersResult::from_scanned_offsets(vec![header(2, 0)], vec![u32::MAX - 1], vec![0]);
+        assert!(scanned.is_err());
+    }
+
+    #[test]
+    fn parser_failure_captures_table_rejection_context() {
+        let failure = ParserFailure::from_ll1_rejection(
+            Ll1AcceptResult {
+                accepted: false,
+                error_pos: 2,
+                error_code: 2,
+                detail: 0,
+                steps: 3,
+                emit_len: 0,
+            },
+            &tiny_ident_semicolon_table(),
+            Some(vec![0, 1, 2, 0]),
+        );
+
+        let rejection = failure
+            .ll1_rejection()
+            .expect("table replay should explain the rejected token");
+        assert_eq!(rejection.pos, 2);
+        assert_eq!(rejection.code, Ll1ParseErrorCode::TerminalMismatch);
+        assert_eq!(rejection.found, 2);
+        assert_eq!(rejection.expected, vec![3]);
+        assert_eq!(failure.semantic_token_kinds(), Some(&[0, 1, 2, 0][..]));
+        assert_eq!(failure.ll1().error_pos, 2);
+    }
+}
+
+/// Elements the passes of one parse ran over.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParseWork {
+    /// Adjacent token pairs, sentinels included.
+    pub n_pairs: u32,
+    /// Packed stack changes.
+    pub total_sc: u32,
+    /// Packed emitted productions.
+    pub total_emit: u32,
+}
+
+impl ParseWork {
+    /// The counts `bufs` were sized for, which one-shot and pair-only
+    /// buffers size exactly.
+    pub(super) fn of_buffers(bufs: &ParserBuffers) -> Self {
+        Self {
+            n_pairs: bufs.n_tokens.saturating_sub(1),
+            total_sc: bufs.total_sc,
+            total_emit: bufs.total_emit,
+        }
+    }
+
+    /// The count a pass scaling with `elements` ran over.
+    pub fn count(self, elements: PassElements) -> u32 {
+        match elements {
+            PassElements::Pairs => self.n_pairs,
+            PassElements::StackChanges => self.total_sc,
+            PassElements::Emits => self.total_emit,
+        }
+    }
+
+    fn add(&mut self, other: ParseWork) {
+        self.n_pairs = self.n_pairs.saturating_add(other.n_pairs);
+        self.total_sc = self.total_sc.saturating_add(other.total_sc);
+        self.total_emit = self.total_emit.saturating_add(other.total_emit);
+    }
+}
+
+/// Which [`ParseWork`] count a parser pass's cost grows with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PassElements {
+    /// Pair headers and stream packing: one thread per pair.
+    Pairs,
+    /// Bracket matching over the packed stack changes.
+    StackChanges,
+    /// Tree and HIR construction over the emitted productions.
+    Emits,
+}
+
+impl PassElements {
+    /// Classifies a pass by its timer label.
+    pub fn of_pass(label: &str) -> Self {
+        if label.starts_with("llp_pairs") || label.starts_with("pack_") {
+            Self::Pairs
+        } else if label.starts_with("brackets_") {
+            Self::StackChanges
+        } else {
+            Self::Emits
+        }
+    }
+}
+
+/// GPU time of one timed parser pass.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PassTiming {
+    /// Pass name the timer stamped.
+    pub label: String,
+    /// GPU time since the previous stamp, which includes any untimed scan
+    /// recorded between the two.
+    pub gpu_ns: f64,
+    /// The count the pass's cost grows with.
+    pub scales_with: PassElements,
+    /// That count for this parse.
+    pub elements: u32,
+}
+
+/// Per-pass GPU timings of one parse, with the work they covered.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParseTimings {
+    pub work: ParseWork,
+    /// Timed passes in submission order. A chunked parse sums each pass
+    /// over its chunks.
+    pub passes: Vec<PassTiming>,
+    /// Stamps dropped and intervals clamped while timing, summed over chunks.
+    pub timer_health: TimerHealth,
+}
+
+impl ParseTimings {
+    /// Timer label stamped after the readback copies, which is not a pass.
+    pub(super) const RESOLVE_LABEL: &'static str = "resolve timers";
+
+    /// Turns each interval of `readout` into the time of the pass it ends.
+    pub(super) fn from_readout(readout: &TimerReadout, period_ns: f32, work: ParseWork) -> Self {
+        let passes = readout
+            .intervals(period_ns)
+            .into_iter()
+            .filter(|interval| interval.label != Self::RESOLVE_LABEL)
+            .map(|interval| {
+                let scales_with = PassElements::of_pass(&interval.label);
+                PassTiming {
+                    label: interval.label,
+                    gpu_ns: interval.gpu_ns,
+                    scales_with,
+                    elements: work.count(scales_with),
+                }
+            })
+            .collect();
+        Self {
+            work,
+            passes,
+            timer_health: readout.health,
+        }
+    }
+
+    /// Total GPU time of the timed passes.
+    pub fn total_ms(&self) -> f64 {
+        self.passes.iter().map(|pass| pass.gpu_ns).sum::<f64>() / 1.0e6
+    }
+
+    /// Adds `other`'s work and pass times, matching passes by label.
+    pub(super) fn accumulate(&mut self, other: &ParseTimings) {
+        self.work.add(other.work);
+        self.timer_health.add(other.timer_health);
+        for pass in &other.passes {
+            match self.passes.iter_mut().find(|p| p.label == pass.label) {
+                Some(p) => {
+                    p.gpu_ns += pass.gpu_ns;
+                    p.elements = p.elements.saturating_add(pass.elements);
+                }
+                None => self.passes.push(pass.clone()),
+            }
+        }
+    }
+}
+
+/// Full one-shot parser debug readback result.
+pub struct ParseResult {
+    pub ll1: Ll1AcceptResult,
+    pub ll1_emit_stream: Vec<u32>,
+    pub ll1_emit_token_pos: Vec<u32>,
+    pub headers: Vec<ActionHeader>,
+    pub sc_stream: Vec<u32>,
+    pub emit_stream: Vec<u32>,
+    pub brackets: BracketsMatchResult,
+    /// Bracket nesting depth in effect at each kept token, when
+    /// [`GpuParser::set_capture_bracket_depths`] is on; see
+    /// `clamped_bracket_depths` for the convention.
+    pub depth_at_token: Vec<u16>,
+    /// Deepest bracket nesting reached anywhere in `sc_stream`.
+    pub max_bracket_depth: u32,
+    /// Input index of each parsed token after
+    /// [`GpuParser::parse_filtered`]; empty for other parses, whose token
+    /// indices are input indices already.
+    pub token_index_map: Vec<u32>,
+
+    pub node_kind: Vec<u32>,
+    pub parent: Vec<u32>,
+    pub first_child: Vec<u32>,
+    pub next_sibling: Vec<u32>,
+    pub subtree_end: Vec<u32>,
+    pub hir_kind: Vec<u32>,
+    pub hir_semantic_prefix_before_node: Vec<u32>,
+    pub hir_semantic_dense_node: Vec<u32>,
+    pub hir_semantic_subtree_end: Vec<u32>,
+    pub hir_semantic_parent: Vec<u32>,
+    pub hir_semantic_first_child: Vec<u32>,
+    pub hir_semantic_next_sibling: Vec<u32>,
+    pub hir_semantic_depth: Vec<u32>,
+    pub hir_semantic_child_index: Vec<u32>,
+    pub hir_token_pos: Vec<u32>,
+    pub hir_token_end: Vec<u32>,
+    pub hir_type_form: Vec<u32>,
+    pub hir_type_value_node: Vec<u32>,
+    pub hir_type_len_token: Vec<u32>,
+    pub hir_type_len_value: Vec<u32>,
+    pub hir_type_file_id: Vec<u32>,
+    pub hir_type_path_leaf_node: Vec<u32>,
+    pub hir_type_arg_start: Vec<u32>,
+    pub hir_type_arg_count: Vec<u32>,
+    pub hir_type_arg_next: Vec<u32>,
+    pub hir_type_alias_target_node: Vec<u32>,
+    pub hir_fn_return_type_node: Vec<u32>,
+    pub hir_method_signature_flags: Vec<u32>,
+    pub hir_stmt_record_kind: Vec<u32>,
+    pub hir_stmt_record_operand0: Vec<u32>,
+    pub hir_stmt_record_operand1: Vec<u32>,
+    pub hir_stmt_record_operand2: Vec<u32>,
+    pub hir_stmt_scope_end: Vec<u32>,
+    pub hir_item_kind: Vec<u32>,
+    pub hir_item_name_token: Vec<u32>,
+    pub hir_item_decl_token: Vec<u32>,
+    pub hir_item_namespace: Vec<u32>,
+    pub hir_item_visibility: Vec<u32>,
+    pub hir_item_path_start: Vec<u32>,
+    pub hir_item_path_end: Vec<u32>,
+    pub hir_item_path_node: Vec<u32>,
+    pub hir_item_file_id: Vec<u32>,
+    pub hir_item_import_target_kind: Vec<u32>,
+    pub hir_variant_parent_enum: Vec<u32>,
+    pub hir_variant_ordinal: Vec<u32>,
+    pub hir_variant_payload_start: Vec<u32>,
+    pub hir_variant_payload_count: Vec<u32>,
+    pub hir_variant_payload_node: Vec<u32>,
+    pub hir_match_scrutinee_node: Vec<u32>,
+    pub hir_match_arm_start: Vec<u32>,
+    pub hir_match_arm_count: Vec<u32>,
+    pub hir_match_arm_next: Vec<u32>,
+    pub hir_match_arm_pattern_node: Vec<u32>,
+    pub hir_match_arm_payload_start: Vec<u32>,
+    pub hir_match_arm_payload_count: Vec<u32>,
+    pub hir_match_arm_result_node: Vec<u32>,
+    pub hir_match_payload_owner_arm: Vec<u32>,
+    pub hir_match_payload_match_node: Vec<u32>,
+    pub hir_match_payload_ordinal: Vec<u32>,
+    pub hir_call_callee_node: Vec<u32>,
+    pub hir_call_arg_start: Vec<u32>,
+    pub hir_call_arg_end: Vec<u32>,
+    pub hir_call_arg_count: Vec<u32>,
+    pub hir_call_arg_parent_call: Vec<u32>,
+    pub hir_call_arg_ordinal: Vec<u32>,
+    pub hir_array_lit_first_element: Vec<u32>,
+    pub hir_array_lit_element_count: Vec<u32>,
+    pub hir_array_element_parent_lit: Vec<u32>,
+    pub hir_array_element_ordinal: Vec<u32>,
+    pub hir_array_element_next: Vec<u32>,
+    pub hir_expr_string_start: Vec<u32>,
+    pub hir_expr_string_len: Vec<u32>,
+    pub hir_member_receiver_node: Vec<u32>,
+    pub hir_member_receiver_token: Vec<u32>,
+    pub hir_member_name_token: Vec<u32>,
+    pub hir_struct_field_parent_struct: Vec<u32>,
+    pub hir_struct_field_ordinal: Vec<u32>,
+    pub hir_struct_field_type_node: Vec<u32>,
+    pub hir_struct_decl_field_start: Vec<u32>,
+    pub hir_struct_decl_field_count: Vec<u32>,
+    pub hir_struct_lit_head_node: Vec<u32>,
+    pub hir_struct_lit_field_start: Vec<u32>,
+    pub hir_struct_lit_field_count: Vec<u32>,
+    pub hir_struct_lit_field_parent_lit: Vec<u32>,
+    pub hir_struct_lit_field_value_node: Vec<u32>,
+    pub hir_struct_lit_field_next: Vec<u32>,
+
+    /// Planned dispatches, when [`GpuParser::set_capture_dispatch_metadata`] is on.
+    pub dispatch_records: Vec<DispatchRecord>,
+    /// Per-pass GPU timings, when [`ParserOptions::gpu_timing`] is on and
+    /// the device supports timestamp queries; feeds
+    /// [`suggest_chunk_size`](crate::parser::autotune::suggest_chunk_size).
+    pub timings: Option<ParseTimings>,
+
+    pub debug: DebugOutput,
+}
+
+#[derive(Clone, Debug)]
+/// Resident parser debug readback result from compiler-owned token buffers.
+pub struct ResidentParseResult {
+    pub ll1: Ll1AcceptResult,
+    pub ll1_emit_stream: Vec<u32>,
+    pub ll1_emit_token_pos: Vec<u32>,
+    pub node_kind: Vec<u32>,
+    pub parent: Vec<u32>,
+    pub first_child: Vec<u32>,
+    pub next_sibling: Vec<u32>,
+    pub subtree_end: Vec<u32>,
+    pub hir_kind: Vec<u32>,
+    pub hir_semantic_prefix_before_node: Vec<u32>,
+    pub hir_semantic_dense_node: Vec<u32>,
+    pub hir_semantic_subtree_end: Vec<u32>,
+    pub hir_semantic_parent: Vec<u32>,
+    pub hir_semantic_first_child: Vec<u32>,
+    pub hir_semantic_next_sibling: Vec<u32>,
+    pub hir_semantic_depth: Vec<u32>,
+    pub hir_semantic_child_index: Vec<u32>,
+    pub hir_token_pos: Vec<u32>,
+    pub hir_token_end: Vec<u32>,
+    pub hir_node_file_id: Vec<u32>,
+    pub hir_type_form: Vec<u32>,
+    pub hir_type_value_node: Vec<u32>,
+    pub hir_type_len_token: Vec<u32>,
+    pub hir_type_len_value: Vec<u32>,
+    pub hir_type_file_id: Vec<u32>,
+    pub hir_type_path_leaf_node: Vec<u32>,
+    pub hir_type_arg_start: Vec<u32>,
+    pub hir_type_arg_count: Vec<u32>,
+    pub hir_type_arg_next: Vec<u32>,
+    pub hir_type_alias_target_node: Vec<u32>,
+    pub hir_fn_return_type_node: Vec<u32>,
+    pub hir_method_signature_flags: Vec<u32>,
+    pub hir_stmt_record_kind: Vec<u32>,
+    pub hir_stmt_record_operand0: Vec<u32>,
+    pub hir_stmt_record_operand1: Vec<u32>,
+    pub hir_stmt_record_operand2: Vec<u32>,
+    pub hir_stmt_scope_end: Vec<u32>,
+    pub hir_item_kind: Vec<u32>,
+    pub hir_item_name_token: Vec<u32>,
+    pub hir_item_decl_token: Vec<u32>,
+    pub hir_item_namespace: Vec<u32>,
+    pub hir_item_visibility: Vec<u32>,
+    pub hir_item_path_start: Vec<u32>,
+    pub hir_item_path_end: Vec<u32>,
+    pub hir_item_path_node: Vec<u32>,
+    pub hir_item_file_id: Vec<u32>,
+    pub hir_item_import_target_kind: Vec<u32>,
+    pub hir_variant_parent_enum: Vec<u32>,
+    pub hir_variant_ordinal: Vec<u32>,
+    pub hir_variant_payload_start: Vec<u32>,
+    pub hir_variant_payload_count: Vec<u32>,
+    pub hir_variant_payload_node: Vec<u32>,
+    pub hir_match_scrutinee_node: Vec<u32>,
+    pub hir_match_arm_start: Vec<u32>,
+    pub hir_match_arm_count: Vec<u32>,
+    pub hir_match_arm_next: Vec<u32>,
+    pub hir_match_arm_pattern_node: Vec<u32>,
+    pub hir_match_arm_payload_start: Vec<u32>,
+    pub hir_match_arm_payload_count: Vec<u32>,
+    pub hir_match_arm_result_node: Vec<u32>,
+    pub hir_match_payload_owner_arm: Vec<u32>,
+    pub hir_match_payload_match_node: Vec<u32>,
+    pub hir_match_payload_ordinal: Vec<u32>,
+    pub hir_call_callee_node: Vec<u32>,
+    pub hir_call_callee_path_node: Vec<u32>,
+    pub hir_call_parent_by_callee: Vec<u32>,
+    pub hir_call_context_stmt_node: Vec<u32>,
+    pub hir_call_arg_start: Vec<u32>,
+    pub hir_call_arg_end: Vec<u32>,
+    pub hir_call_arg_count: Vec<u32>,
+    pub hir_call_arg_parent_call: Vec<u32>,
+    pub hir_call_arg_ordinal: Vec<u32>,
+    pub hir_array_lit_first_element: Vec<u32>,
+    pub hir_array_lit_element_count: Vec<u32>,
+    pub hir_array_lit_context_stmt_node: Vec<u32>,
+    pub hir_array_element_parent_lit: Vec<u32>,
+    pub hir_array_element_ordinal: Vec<u32>,
+    pub hir_array_element_next: Vec<u32>,
+    pub hir_expr_name_role: Vec<u32>,
+    pub hir_expr_result_root_node: Vec<u32>,
+    pub hir_expr_parent_node: Vec<u32>,
+    pub hir_expr_forest_root_node: Vec<u32>,
+    pub hir_expr_forest_status: u32,
+    pub hir_member_receiver_node: Vec<u32>,
+    pub hir_member_receiver_token: Vec<u32>,
+    pub hir_member_name_token: Vec<u32>,
+    pub hir_nearest_stmt_node: Vec<u32>,
+    pub hir_nearest_block_node: Vec<u32>,
+    pub hir_nearest_enclosing_control_node: Vec<u32>,
+    pub hir_nearest_loop_node: Vec<u32>,
+    pub hir_nearest_fn_node: Vec<u32>,
+    pub hir_nearest_array_element_node: Vec<u32>,
+    pub hir_struct_field_parent_struct: Vec<u32>,
+    pub hir_struct_field_ordinal: Vec<u32>,
+    pub hir_struct_field_type_node: Vec<u32>,
+    pub hir_struct_decl_field_start: Vec<u32>,
+    pub hir_struct_decl_field_count: Vec<u32>,
+    pub hir_struct_lit_head_node: Vec<u32>,
+    pub hir_struct_lit_context_stmt_node: Vec<u32>,
+    pub hir_struct_lit_field_start: Vec<u32>,
+    pub hir_struct_lit_field_count: Vec<u32>,
+    pub hir_struct_lit_field_parent_lit: Vec<u32>,
+    pub hir_struct_lit_field_value_node: Vec<u32>,
+    pub hir_struct_lit_field_next: Vec<u32>,
+    pub hir_canonical_count: u32,
+    pub hir_canonical_dense_to_raw: Vec<u32>,
+    pub hir_canonical_raw_to_dense: Vec<u32>,
+    pub hir_param_range_start: Vec<u32>,
+    pub hir_param_range_count: Vec<u32>,
+    pub hir_type_arg_range_start: Vec<u32>,
+    pub hir_type_arg_range_count: Vec<u32>,
+    pub hir_generic_param_owner: Vec<u32>,
+    pub hir_generic_param_name_token: Vec<u32>,
+    pub hir_generic_param_kind: Vec<u32>,
+    pub hir_generic_param_file_id: Vec<u32>,
+    pub hir_generic_param_range_start: Vec<u32>,
+    pub hir_generic_param_range_count: Vec<u32>,
+    pub hir_path_owner: Vec<u32>,
+    pub hir_path_segment_start: Vec<u32>,
+    pub hir_path_segment_count: Vec<u32>,
+    pub hir_path_kind: Vec<u32>,
+    pub hir_path_segment_path: Vec<u32>,
+    pub hir_path_segment_name_token: Vec<u32>,
+    pub hir_path_segment_ordinal: Vec<u32>,
+    pub hir_path_segment_file_id: Vec<u32>,
+    pub hir_field_owner: Vec<u32>,
+    pub hir_field_name_token: Vec<u32>,
+    pub hir_field_value: Vec<u32>,
+    pub hir_field_ordinal: Vec<u32>,
+    pub hir_compact_variant_owner: Vec<u32>,
+    pub hir_compact_variant_name_token: Vec<u32>,
+    pub hir_compact_variant_ordinal: Vec<u32>,
+    pub hir_compact_variant_file_id: Vec<u32>,
+    pub hir_compact_variant_payload_start: Vec<u32>,
+    pub hir_compact_variant_payload_count: Vec<u32>,
+    pub hir_compact_variant_payload_variant: Vec<u32>,
+    pub hir_compact_variant_payload_type_node: Vec<u32>,
+    pub hir_compact_variant_payload_ordinal: Vec<u32>,
+    pub hir_compact_variant_payload_file_id: Vec<u32>,
+    pub hir_compact_match_arm_owner: Vec<u32>,
+    pub hir_compact_match_arm_pattern: Vec<u32>,
+    pub hir_compact_match_arm_result: Vec<u32>,
+    pub hir_compact_match_arm_ordinal: Vec<u32>,
+    pub hir_compact_match_payload_start: Vec<u32>,
+    pub hir_compact_match_payload_count: Vec<u32>,
+    pub hir_compact_match_payload_arm: Vec<u32>,
+    pub hir_compact_match_payload_pattern: Vec<u32>,
+    pub hir_compact_match_payload_ordinal: Vec<u32>,
+    pub hir_compact_match_payload_file_id: Vec<u32>,
+    pub hir_compact_array_element_start: Vec<u32>,
+    pub hir_compact_array_element_count: Vec<u32>,
+    pub hir_compact_array_element_array: Vec<u32>,
+    pub hir_compact_array_element_value: Vec<u32>,
+    pub hir_compact_array_element_ordinal: Vec<u32>,
+    pub hir_compact_array_element_file_id: Vec<u32>,
+    pub hir_compact_string_node: Vec<u32>,
+    pub hir_compact_string_data_offset: Vec<u32>,
+    pub hir_compact_string_decoded_len: Vec<u32>,
+    pub hir_compact_string_file_id: Vec<u32>,
+    pub hir_compact_method_node: Vec<u32>,
+    pub hir_compact_method_owner: Vec<u32>,
+    pub hir_compact_method_impl_node: Vec<u32>,
+    pub hir_compact_method_name_token: Vec<u32>,
+    pub hir_compact_method_first_param_token: Vec<u32>,
+    pub hir_compact_method_impl_receiver_type: Vec<u32>,
+    pub hir_compact_method_receiver_mode: Vec<u32>,
+    pub hir_compact_method_metadata: Vec<u32>,
+    pub hir_compact_predicate_owner: Vec<u32>,
+    pub hir_compact_predicate_subject: Vec<u32>,
+    pub hir_compact_predicate_bound: Vec<u32>,
+    pub hir_compact_predicate_metadata: Vec<u32>,
+}
+
+impl ParseResult {
+    /// A result with every stream empty, an accepting LL(1) status, and a
+    /// valid empty bracket stream.
+    pub(crate) fn empty() -> Self {
+        Self {
+            ll1: Ll1AcceptResult {
+                accepted: true,
+                error_pos: 0,
+                error_code: 0,
+                detail: 0,
+                steps: 0,
+                emit_len: 0,
+            },
+            ll1_emit_stream: Vec::new(),
+            ll1_emit_token_pos: Vec::new(),
+            headers: Vec::new(),
+            sc_stream: Vec::new(),
+            emit_stream: Vec::new(),
+            brackets: BracketsMatchResult {
+                valid: true,
+                final_depth: 0,
+                min_depth: 0,
+                match_for_index: Vec::new(),
+                valid_up_to: 0,
+                first_unclosed_push: None,
+            },
+            depth_at_token: Vec::new(),
+            max_bracket_depth: 0,
+            token_index_map: Vec::new(),
+            node_kind: Vec::new(),
+            parent: Vec::new(),
+            first_child: Vec::new(),
+            next_sibling: Vec::new(),
+            subtree_end: Vec::new(),
+            hir_kind: Vec::new(),
+            hir_semantic_prefix_before_node: Vec::new(),
+            hir_semantic_dense_node: Vec::new(),
+            hir_semantic_subtree_end: Vec::new(),
+            hir_semantic_parent: Vec::new(),
+            hir_semantic_first_child: Vec::new(),
+            hir_semantic_next_sibling: Vec::new(),
+            hir_semantic_depth: Vec::new(),
+            hir_semantic_child_index: Vec::new(),
+            hir_token_pos: Vec::new(),
+            hir_token_end: Vec::new(),
+            hir_type_form: Vec::new(),
+            hir_type_value_node: Vec::new(),
+            hir_type_len_token: Vec::new(),
+            hir_type_len_value: Vec::new(),
+            hir_type_file_id: Vec::new(),
+            hir_type_path_leaf_node: Vec::new(),
+            hir_type_arg_start: Vec::new(),
+            hir_type_arg_count: Vec::new(),
+            hir_type_arg_next: Vec::new(),
+            hir_type_alias_target_node: Vec::new(),
+            hir_fn_return_type_node: Vec::new(),
+            hir_method_signature_flags: Vec::new(),
+            hir_stmt_record_kind: Vec::new(),
+            hir_stmt_record_operand0: Vec::new(),
+            hir_stmt_record_operand1: Vec::new(),
+            hir_stmt_record_operand2: Vec::new(),
+            hir_stmt_scope_end: Vec::new(),
+            hir_item_kind: Vec::new(),
+            hir_item_name_token: Vec::new(),
+            hir_item_decl_token: Vec::new(),
+            hir_item_namespace: Vec::new(),
+            hir_item_visibility: Vec::new(),
+            hir_item_path_start: Vec::new(),
+            hir_item_path_end: Vec::new(),
+            hir_item_path_node: Vec::new(),
+            hir_item_file_id: Vec::new(),
+            hir_item_import_target_kind: Vec::new(),
+            hir_variant_parent_enum: Vec::new(),
+            hir_variant_ordinal: Vec::new(),
+            hir_variant_payload_start: Vec::new(),
+            hir_variant_payload_count: Vec::new(),
+            hir_variant_payload_node: Vec::new(),
+            hir_match_scrutinee_node: Vec::new(),
+            hir_match_arm_start: Vec::new(),
+            hir_match_arm_count: Vec::new(),
+            hir_match_arm_next: Vec::new(),
+            hir_match_arm_pattern_node: Vec::new(),
+            hir_match_arm_payload_start: Vec::new(),
+            hir_match_arm_payload_count: Vec::new(),
+            hir_match_arm_result_node: Vec::new(),
+            hir_match_payload_owner_arm: Vec::new(),
+            hir_match_payload_match_node: Vec::new(),
+            hir_match_payload_ordinal: Vec::new(),
+            hir_call_callee_node: Vec::new(),
+            hir_call_arg_start: Vec::new(),
+            hir_call_arg_end: Vec::new(),
+            hir_call_arg_count: Vec::new(),
+            hir_call_arg_parent_call: Vec::new(),
+            hir_call_arg_ordinal: Vec::new(),
+            hir_array_lit_first_element: Vec::new(),
+            hir_array_lit_element_count: Vec::new(),
+            hir_array_element_parent_lit: Vec::new(),
+            hir_array_element_ordinal: Vec::new(),
+            hir_array_element_next: Vec::new(),
+            hir_expr_string_start: Vec::new(),
+            hir_expr_string_len: Vec::new(),
+            hir_member_receiver_node: Vec::new(),
+            hir_member_receiver_token: Vec::new(),
+            hir_member_name_token: Vec::new(),
+            hir_struct_field_parent_struct: Vec::new(),
+            hir_struct_field_ordinal: Vec::new(),
+            hir_struct_field_type_node: Vec::new(),
+            hir_struct_decl_field_start: Vec::new(),
+            hir_struct_decl_field_count: Vec::new(),
+            hir_struct_lit_head_node: Vec::new(),
+            hir_struct_lit_field_start: Vec::new(),
+            hir_struct_lit_field_count: Vec::new(),
+            hir_struct_lit_field_parent_lit: Vec::new(),
+            hir_struct_lit_field_value_node: Vec::new(),
+            hir_struct_lit_field_next: Vec::new(),
+            dispatch_records: Vec::new(),
+            timings: None,
+            debug: DebugOutput::default(),
+        }
+    }
+
+    /// Renders each production in `emit_stream` in grammar syntax, such as
+    /// `expr -> term expr_tail`. Tables without grammar names render the
+    /// left-hand side as `prod{N}`.
+    pub fn explain_emits(&self, tables: &PrecomputedParseTables) -> Vec<String> {
+        tables.explain_productions(&self.emit_stream)
+    }
+
+    /// Input index of parsed token `token`, through
+    /// [`Self::token_index_map`] when the parse was filtered.
+    pub fn input_token_index(&self, token: usize) -> Option<usize> {
+        if self.token_index_map.is_empty() {
+            return Some(token);
+        }
+        self.token_index_map.get(token).map(|&index| index as usize)
+    }
+
+    /// Parsed token whose pair produced stack change `sc_index`: pair `p`
+    /// ends in framed token `p + 1`. `None` past the stream or for the pair
+    /// ending in the end sentinel.
+    pub fn token_of_stack_change(
+        &self,
+        sc_index: usize,
+        tables: &PrecomputedParseTables,
+    ) -> Option<usize> {
+        let mut end = 0usize;
+        let pair = self.headers.iter().position(|header| {
+            end += (header.push_len + header.pop_count) as usize;
+            sc_index < end
+        })?;
+        let token = (pair + 1).checked_sub(tables.start_sentinel as usize)?;
+        let tokens = self
+            .headers
+            .len()
+            .saturating_sub(tables.start_sentinel as usize);
+        (token < tokens).then_some(token)
+    }
+
+    /// Merges runs of consecutive tokens at equal [`Self::depth_at_token`]
+    /// into byte ranges, from the start of a run's first token to the end of
+    /// its last.
+    ///
+    /// `tokens` are the lexer tokens whose kinds were parsed; empty when depth
+    /// capture was off.
+    pub fn depth_spans(&self, tokens: &[crate::lexer::Token]) -> Vec<(Range<usize>, u16)> {
+        let mut spans: Vec<(Range<usize>, u16)> = Vec::new();
+        for (token, &depth) in tokens.iter().zip(&self.depth_at_token) {
+            let end = token.end();
+            match spans.last_mut() {
+                Some((range, last)) if *last == depth => range.end = end,
+                _ => spans.push((token.start()..end, depth)),
+            }
+        }
+        spans
+    }
+
+    /// Grammar production name of each tree node, indexed like `node_kind`.
+    pub fn node_production_names<'t>(
+        &self,
+        tables: &'t PrecomputedParseTables,
+    ) -> Vec<std::borrow::Cow<'t, str>> {
+        self.node_kind
+            .iter()
+            .map(|&prod| tables.production_name(prod))
+            .collect()
+    }
+
+    /// Restricts the pair outputs to pairs whose stack changes all lie before
+    /// [`BracketsMatchResult::valid_up_to`].
+    ///
+    /// The slice is the longest prefix the bracket pass can vouch for: every
+    /// pop in it has an opener. Openers left unclosed at end of input do not
+    /// shorten it.
+    pub fn truncate_to_valid(&self) -> ParseResultSlice<'_> {
+        let prefix = valid_pair_prefix(&self.headers, self.brackets.valid_up_to);
+        ParseResultSlice {
+            headers: &self.headers[..prefix.pairs],
+            sc_stream: &self.sc_stream[..prefix.sc_len.min(self.sc_stream.len())],
+            emit_stream: &self.emit_stream[..prefix.emit_len.min(self.emit_stream.len())],
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+/// Pair outputs of a [`ParseResult`] that precede the first bracket failure.
+pub struct ParseResultSlice<'a> {
+    pub headers: &'a [ActionHeader],
+    pub sc_stream: &'a [u32],
+    pub emit_stream: &'a [u32],
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// Lengths of the pair, stack-change, and emit prefixes kept by a truncation.
+struct PairPrefix {
+    pairs: usize,
+    sc_len: usize,
+    emit_len: usize,
+}
+
+/// Counts the leading pairs whose stack changes end at or before `valid_up_to`.
+fn valid_pair_prefix(headers: &[ActionHeader], valid_up_to: u32) -> PairPrefix {
+    let mut prefix = PairPrefix::default();
+    for header in headers {
+        let sc_end = prefix.sc_len + (header.push_len + header.pop_count) as usize;
+        if sc_end > valid_up_to as usize {
+            break;
+        }
+        prefix.pairs += 1;
+        prefix.sc_len = sc_end;
+        prefix.emit_len += header.emit_len as usize;
+    }
+    prefix
+}
+
+/// Replays `sc_stream` as a bracket depth and returns the depth at each kept
+/// token (when `per_token`) and the maximum depth.
+///
+/// A token's depth is the depth after every stack change of the pairs
+/// strictly before it, so an opener reports the depth outside itself and a
+/// closer the depth inside. A pop at depth zero, the point where bracket
+/// matching fails, clamps the depth at zero instead of going negative; depths
+/// after it continue from there.
+pub(super) fn clamped_bracket_depths(
+    headers: &[ActionHeader],
+    sc_stream: &[u32],
+    start_sentinel: bool,
+    per_token: bool,
+) -> (Vec<u16>, u32) {
+    let (mut depth, mut max_depth) = (0u32, 0u32);
+    let mut depth_before_pair = Vec::with_capacity(if per_token { headers.len() } else { 0 });
+    let mut codes = sc_stream.iter();
+    for header in headers {
+        if per_token {
+            depth_before_pair.push(depth);
+        }
+        for &code in codes
+            .by_ref()
+            .take((header.push_len + header.pop_count) as usize)
+        {
+            if code & 1 == 1 {
+                depth += 1;
+                max_depth = max_depth.max(depth);
+            } else {
+                depth = depth.saturating_sub(1);
+            }
+        }
+    }
+    if !per_token {
+        return (Vec::new(), max_depth);
+    }
+    // Pair `p` ends in framed token `p + 1`; kept token `j` is framed token
+    // `j + 1` with a start sentinel and `j` without.
+    let start = start_sentinel as usize;
+    let tokens = headers.len().saturating_sub(start);
+    let depth_at_token = (0..tokens)
+        .map(|j| {
+            let pairs_before = (j + start).saturating_sub(1);
+            depth_before_pair[pairs_before].min(u16::MAX as u32) as u16
+        })
+        .collect();
+    (depth_at_token, max_depth)
+}
+
+/// Recorded parser status readback for deferred LL/HIR validation.
+pub struct RecordedResidentLl1HirCheck {
+    pub(super) status_readback: wgpu::Buffer,
+    pub(super) dispatch_records: Vec<DispatchRecord>,
+    pub(super) bracket_fill: Option<Arc<ResidentBracketFill>>,
+}
+
+/// Parser status map queued while the host records independent downstream work.
+pub(crate) struct PendingResidentLl1HirStatus {
+    status_readback: wgpu::Buffer,
+    bracket_fill: Option<Arc<ResidentBracketFill>>,
+    map: crate::gpu::passes_core::PendingReadbackMap,
+}
+
+impl RecordedResidentLl1HirCheck {
+    /// Planned dispatches, when [`GpuParser::set_capture_dispatch_metadata`] is on.
+    pub fn dispatch_records(&self) -> &[DispatchRecord] {
+        &self.dispatch_records
+    }
+
+    /// Reads the recorded parser status buffer into a host status result.
+    pub fn read_status_result(&self, device: &wgpu::Device) -> anyhow::Result<Ll1AcceptResult> {
+        self.read_status_feature_flags_and_pointer_jump_steps_result(device)
+            .map(|(status, _feature_flags, _pointer_jump_steps)| status)
+    }
+
+    /// Reads parser status, feature mask, and the depth-bounded round count.
+    pub(crate) fn read_status_feature_flags_and_pointer_jump_steps_result(
+        &self,
+        device: &wgpu::Device,
+    ) -> anyhow::Result<(Ll1AcceptResult, u32, u32)> {
+        self.begin_status_and_feature_flags_read().finish(device)
+    }
+
+    /// Queues parser status mapping without waiting for GPU completion.
+    pub(crate) fn begin_status_and_feature_flags_read(&self) -> PendingResidentLl1HirStatus {
+        let slice = self.status_readback.slice(..);
+        let map =
+            crate::gpu::passes_core::begin_readback_map(&slice, "parser.recorded-ll1-hir.status");
+        PendingResidentLl1HirStatus {
+            status_readback: self.status_readback.clone(),
+            bracket_fill: self.bracket_fill.clone(),
+            map,
+        }
+    }
+}
+
+impl PendingResidentLl1HirStatus {
+    /// Waits for the queued map and decodes status plus GPU scheduling metadata.
+    pub(crate) fn finish(
+        self,
+        device: &wgpu::Device,
+    ) -> anyhow::Result<(Ll1AcceptResult, u32, u32)> {
+        crate::gpu::passes_core::finish_readback_map_blocking(device, self.map)?;
+        let mapped = self.status_readback.slice(..).get_mapped_range();
+        let words =
+            crate::gpu::readback::read_u32_words::<8>(&mapped, "parser.recorded-ll1-hir.status")?;
+        if let Some(fill) = &self.bracket_fill {
+            fill.store(&mapped)?;
+        }
+        drop(mapped);
+        self.status_readback.unmap();
+
+        Ok((
+            Ll1AcceptResult::from_status_words(&words[..6]),
+            words[6],
+            words[7],
+        ))
+    }
+}
+
+/// Recorded semantic-HIR count readback used by backend capacity planning.
+pub struct RecordedHirSemanticCount {
+    pub(super) count_readback: wgpu::Buffer,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Exact parser allocation inputs measured by GPU token classification and
+/// partial-parse emission before full tree/HIR allocation.
+pub struct ResidentParserCapacity {
+    pub tree_capacity: u32,
+    pub parser_feature_flags: u32,
+    /// Bracket-cache key of the measured stream, when a
+    /// [`BracketCache`](crate::parser::BracketCache) is attached.
+    pub bracket_key: Option<ResidentBracketKey>,
+}
+
+/// Uploaded static parse-table buffers shared by one-shot parse and header passes.
+pub(super) struct ParserStaticTableCache {
+    pub(super) table_fingerprint: u64,
+    pub(super) action_table: LaniusBuffer<u8>,
+}
+
+/// Cached resident parser buffers keyed by capacity, tables, and debug mode.
+pub(super) struct ResidentParserBufferCache {
+    pub(super) token_capacity: u32,
+    pub(super) table_fingerprint: u64,
+    pub(super) retain_debug_hir_buffers: bool,
+    pub(super) parser_feature_flags: u32,
+    pub(super) buffers: ParserBuffers,
+}
@@ -0,0 +1,278 @@
+//! Right-to-left (suffix) parsing used to localize syntax errors.
+
+use super::*;
+use crate::parser::{
+    buffers::ActionHeader,
+    tables::{PrecomputedParseTables, mirror_stack_changes},
+};
+
+const UNMATCHED: u32 = u32::MAX;
+
+/// Token range that lies outside both the longest valid prefix and the
+/// longest valid suffix of a token stream.
+///
+/// Indices are forward token positions, excluding the parser sentinels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ErrorRegion {
+    /// First token in the region.
+    pub start: usize,
+    /// One past the last token in the region.
+    pub end: usize,
+}
+
+impl ErrorRegion {
+    /// Returns whether neither direction reported an error.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl GpuParser {
+    /// Parses a raw token-kind stream right-to-left with tables built by
+    /// [`PrecomputedParseTables::reverse`].
+    ///
+    /// Tokens are classified in forward order before the semantic kind stream is
+    /// reversed, so context-dependent retagging matches [`GpuParser::parse`].
+    /// The pair-analysis outputs (`headers`, `sc_stream`, and
+    /// `brackets.match_for_index`) are mapped back to forward coordinates; the
+    /// LL(1), tree, and HIR outputs describe the reversed stream and are not
+    /// meaningful for reversed tables.
+    pub async fn parse_reverse(
+        &self,
+        token_kinds_u32: &[u32],
+        rev_tables: &PrecomputedParseTables,
+    ) -> Result<ParseResult> {
+        let mut semantic_token_kinds =
+            self.debug_semantic_token_kinds_for_raw_token_kinds(token_kinds_u32, rev_tables)?;
+        semantic_token_kinds.reverse();
+        let mut result = self
+            .parse_classified_token_kinds(&semantic_token_kinds, rev_tables)
+            .await?;
+        remap_reversed_pair_outputs(
+            &mut result.headers,
+            &mut result.sc_stream,
+            &mut result.brackets.match_for_index,
+        );
+        Ok(result)
+    }
+}
+
+/// Localizes a syntax error from a forward parse and a [`GpuParser::parse_reverse`] parse.
+///
+/// The forward stack-change stream stays viable up to its first unmatched pop
+/// and the backward stream stays viable back to its last unmatched push; the
+/// tokens between those two frontiers form the region. When the frontiers
+/// overlap, the region falls back to the single token that broke the forward
+/// (or, failing that, the backward) scan. Both results need readback enabled so
+/// `match_for_index` is populated.
+pub fn localize_error(forward: &ParseResult, backward: &ParseResult) -> ErrorRegion {
+    localize_pair_error(PairStreams::of(forward), PairStreams::of(backward))
+}
+
+#[derive(Clone, Copy)]
+struct PairStreams<'a> {
+    headers: &'a [ActionHeader],
+    sc_stream: &'a [u32],
+    match_for_index: &'a [u32],
+}
+
+impl<'a> PairStreams<'a> {
+    fn of(result: &'a ParseResult) -> Self {
+        Self {
+            headers: &result.headers,
+            sc_stream: &result.sc_stream,
+            match_for_index: &result.brackets.match_for_index,
+        }
+    }
+
+    /// Number of user tokens; the final pair joins the last token to the end sentinel.
+    fn token_count(&self) -> usize {
+        self.headers.len().saturating_sub(1)
+    }
+
+    /// Forward token positions of unmatched stack changes of one direction.
+    ///
+    /// Pair `i` joins parser positions `i` and `i + 1`; its stack changes are
+    /// attributed to the right-hand token, which is user token `i`.
+    fn unmatched_tokens(self, pushes: bool) -> impl Iterator<Item = usize> + 'a {
+        self.headers
+            .iter()
+            .enumerate()
+            .flat_map(|(pair, header)| {
+                std::iter::repeat_n(pair, (header.push_len + header.pop_count) as usize)
+            })
+            .zip(self.sc_stream.iter().zip(self.match_for_index))
+            .filter(move |(_, (code, matched))| {
+                **matched == UNMATCHED && ((**code & 1) == 1) == pushes
+            })
+            .map(|(pair, _)| pair)
+    }
+}
+
+fn localize_pair_error(forward: PairStreams<'_>, backward: PairStreams<'_>) -> ErrorRegion {
+    let n_tokens = forward.token_count();
+    let forward_error = forward.unmatched_tokens(false).next();
+    let backward_error = backward.unmatched_tokens(true).last();
+
+    let prefix_end = forward_error.unwrap_or(n_tokens);
+    let suffix_start = backward_error.map_or(0, |token| token + 1);
+    let (start, end) = if prefix_end < suffix_start {
+        (prefix_end, suffix_start)
+    } else if let Some(token) = forward_error.or(backward_error) {
+        (token, token + 1)
+    } else {
+        (n_tokens, n_tokens)
+    };
+    ErrorRegion {
+        start: start.min(n_tokens),
+        end: end.min(n_tokens),
+    }
+}
+
+/// Maps reversed-stream pair outputs back to forward coordinates.
+fn remap_reversed_pair_outputs(
+    headers: &mut [ActionHeader],
+    sc_stream: &mut Vec<u32>,
+    match_for_index: &mut [u32],
+) {
+    headers.reverse();
+    *sc_stream = mirror_stack_changes(sc_stream);
+    match_for_index.reverse();
+    let last = match_for_index.len().saturating_sub(1) as u32;
+    for matched in match_for_index.iter_mut() {
+        if *matched != UNMATCHED {
+            *matched = last - *matched;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        lexer::tables::tokens::{N_KINDS, TokenKind},
+        parser::tables::build_mvp_precomputed_tables,
+    };
+
+    struct CpuPairOutputs {
+        headers: Vec<ActionHeader>,
+        sc_stream: Vec<u32>,
+        match_for_index: Vec<u32>,
+    }
+
+    impl CpuPairOutputs {
+        fn streams(&self) -> PairStreams<'_> {
+            PairStreams {
+                headers: &self.headers,
+                sc_stream: &self.sc_stream,
+                match_for_index: &self.match_for_index,
+            }
+        }
+    }
+
+    /// Host model of the pair headers, stack-change stream, and typed matching.
+    fn cpu_pair_outputs(tables: &PrecomputedParseTables, kinds: &[u32]) -> CpuPairOutputs {
+        let mut headers = Vec::new();
+        let mut sc_stream = Vec::new();
+        for pair in kinds.windows(2) {
+            let sc = tables.test_cpu_stack_change_stream(pair);
+            let pushes = sc.iter().filter(|&&code| code & 1 == 1).count() as u32;
+            headers.push(ActionHeader {
+                push_len: pushes,
+                pop_count: sc.len() as u32 - pushes,
+                ..ActionHeader::default()
+            });
+            sc_stream.extend(sc);
+        }
+        let mut match_for_index = vec![UNMATCHED; sc_stream.len()];
+        let mut open = Vec::new();
+        for (i, &code) in sc_stream.iter().enumerate() {
+            if code & 1 == 1 {
+                open.push(i);
+            } else if let Some(&j) = open.last()
+                && sc_stream[j] >> 1 == code >> 1
+            {
+                open.pop();
+                match_for_index[i] = j as u32;
+                match_for_index[j] = i as u32;
+            }
+        }
+        CpuPairOutputs {
+            headers,
+            sc_stream,
+            match_for_index,
+        }
+    }
+
+    fn localize(kinds: &[u32]) -> ErrorRegion {
+        let tables = build_mvp_precomputed_tables(N_KINDS, vec![0]);
+        let forward = cpu_pair_outputs(&tables, kinds);
+        let reversed_kinds = kinds.iter().rev().copied().collect::<Vec<_>>();
+        let mut backward = cpu_pair_outputs(&tables.reverse(), &reversed_kinds);
+        remap_reversed_pair_outputs(
+            &mut backward.headers,
+            &mut backward.sc_stream,
+            &mut backward.match_for_index,
+        );
+        localize_pair_error(forward.streams(), backward.streams())
+    }
+
+    fn bracket_kinds(source: &str) -> Vec<u32> {
+        let mut kinds = vec![0];
+        kinds.extend(source.split_whitespace().map(|token| match token {
+            "(" => TokenKind::GroupLParen as u32,
+            ")" => TokenKind::GroupRParen as u32,
+            _ => TokenKind::Ident as u32,
+        }));
+        kinds.push(0);
+        kinds
+    }
+
+    #[test]
+    fn stray_closer_is_localized_to_its_token() {
+        let kinds = bracket_kinds("( ( a ) ) ) ( ( b ) )");
+
+        assert_eq!(localize(&kinds), ErrorRegion { start: 5, end: 6 });
+    }
+
+    #[test]
+    fn unclosed_opener_is_localized_by_backward_scan() {
+        let kinds = bracket_kinds("( ( a )");
+
+        assert_eq!(localize(&kinds), ErrorRegion { start: 0, end: 1 });
+    }
+
+    #[test]
+    fn balanced_stream_has_empty_region() {
+        let kinds = bracket_kinds("( a ( b ) )");
+
+        let region = localize(&kinds);
+        assert!(region.is_empty());
+        assert_eq!(region.start, 6);
+    }
+
+    #[test]
+    fn remapped_backward_streams_match_forward_streams() {
+        let tables = build_mvp_precomputed_tables(N_KINDS, vec![0]);
+        let kinds = bracket_kinds("( ( a ) b )");
+        let forward = cpu_pair_outputs(&tables, &kinds);
+        let reversed_kinds = kinds.iter().rev().copied().collect::<Vec<_>>();
+        let mut backward = cpu_pair_outputs(&tables.reverse(), &reversed_kinds);
+
+        remap_reversed_pair_outputs(
+            &mut backward.headers,
+            &mut backward.sc_stream,
+            &mut backward.match_for_index,
+        );
+
+        assert_eq!(backward.sc_stream, forward.sc_stream);
+        assert_eq!(backward.match_for_index, forward.match_for_index);
+        let counts = |headers: &[ActionHeader]| {
+            headers
+                .iter()
+                .map(|header| header.push_len + header.pop_count)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(counts(&backward.headers), counts(&forward.headers));
+    }
+}
@@ -1,15 +1,6 @@
 use super::*;
 use crate::gpu::buffers::LaniusBuffer;
 
-/// Reads a parser boolean environment flag with either truthy or strict semantics.
-pub(super) fn bool_from_env(name: &str, default_true: bool) -> bool {
-    if default_true {
-        crate::gpu::env::env_bool_truthy(name, true)
-    } else {
-        crate::gpu::env::env_bool_strict(name, false)
-    }
-}
-
 /// Emits a parser GPU timer stamp when timing is enabled.
 pub(super) fn stamp_timer(
     timer_ref: &mut Option<&mut GpuTimer>,
@@ -21,9 +12,32 @@ pub(super) fn stamp_timer(
     }
 }
 
-/// Mirrors the lexer: allow disabling readback with `LANIUS_READBACK=0`.
-pub(super) fn readback_enabled() -> bool {
-    bool_from_env("LANIUS_READBACK", true)
+/// Reads a one-shot parse's timer, logs each pass like the lexer does, and
+/// returns the stamps as [`ParseTimings`] over `work`.
+pub(super) fn read_parse_timings(
+    device: &wgpu::Device,
+    timer: &GpuTimer,
+    work: ParseWork,
+) -> Option<ParseTimings> {
+    let readout = timer
+        .try_read(device)
+        .filter(|readout| !readout.stamps.is_empty())?;
+    log::info!(target: crate::logging::PARSER_GPU, "[gpu_timer] shader_path={}", ShaderPath::for_device(device));
+    for interval in readout.intervals(timer.period_ns()) {
+        let dt_ms = interval.gpu_ns / 1.0e6;
+        let total_ms = (interval.start_ns + interval.gpu_ns) / 1.0e6;
+        if dt_ms >= MINIMUM_TIME_TO_NOT_ELIDE_MS {
+            log::info!(target: crate::logging::PARSER_GPU, "[gpu_timer] {}: {dt_ms:.3}ms (total {total_ms:.3}ms)", interval.label);
+        }
+    }
+    if !readout.health.is_clean() {
+        log::warn!(target: crate::logging::PARSER_GPU, "[gpu_timer] {:?}", readout.health);
+    }
+    Some(ParseTimings::from_readout(
+        &readout,
+        timer.period_ns(),
+        work,
+    ))
 }
 
 /// Hashes parse-table contents that affect resident parser buffer reuse.
@@ -44,6 +58,10 @@ pub(super) fn table_fingerprint(tables: &PrecomputedParseTables) -> u64 {
     tables.prod_rhs_off.hash(&mut hasher);
     tables.prod_rhs_len.hash(&mut hasher);
     tables.prod_rhs.hash(&mut hasher);
+    tables.kind_map.hash(&mut hasher);
+    tables.optimized.hash(&mut hasher);
+    tables.layout.hot_cells.hash(&mut hasher);
+    tables.layout.inline_max_len.hash(&mut hasher);
     hasher.finish()
 }
 
@@ -386,12 +404,5 @@ pub(super) fn make_active_pair_dispatch_args_pass(device: &wgpu::Device) -> Resu
 
 /// Reads little-endian `u32` words from parser status or debug readback bytes.
 pub(super) fn read_u32_words(bytes: &[u8], count: usize) -> Result<Vec<u32>> {
-    if bytes.len() < count * 4 {
-        anyhow::bail!("parser status readback was truncated");
-    }
-    let mut out = Vec::with_capacity(count);
-    for chunk in bytes.chunks_exact(4).take(count) {
-        out.push(u32::from_le_bytes(chunk.try_into().unwrap()));
-    }
-    Ok(out)
+    crate::gpu::decode::decode_structs(bytes, count, "parser status")
 }
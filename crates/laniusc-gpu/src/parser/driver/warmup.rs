@@ -0,0 +1,78 @@
+use anyhow::{Result, anyhow};
+
+use super::{GpuParser, raw_token_kind_rows};
+use crate::{
+    gpu::{
+        buffers::{storage_ro_from_bytes, storage_ro_from_u32s, tracked_buffer_allocation_stats},
+        passes_core::WarmupReport,
+    },
+    lexer::GpuToken,
+    parser::tables::PrecomputedParseTables,
+};
+
+impl GpuParser {
+    /// Allocates resident parser buffers for `expected_tokens` tokens and
+    /// records the resident LL(1), tree, and HIR passes once over an empty
+    /// synthetic token stream, so the first real resident parse against
+    /// `tables` skips buffer allocation and first-dispatch cost.
+    ///
+    /// Resident buffers are keyed on exact token capacity, so pass the
+    /// capacity real parses will use, such as a warmed lexer's token capacity.
+    /// Token-frontend bind groups bind the caller's token buffers and are
+    /// still built on the first real parse. `buffers_bytes` is the net growth
+    /// of the process-wide buffer ledger, so it omits any resident buffers the
+    /// warm-up replaced.
+    pub fn warm_up(
+        &self,
+        expected_tokens: usize,
+        tables: &PrecomputedParseTables,
+    ) -> Result<WarmupReport> {
+        let token_capacity = u32::try_from(expected_tokens.max(1))
+            .map_err(|_| anyhow!("warm-up token count exceeds parser capacity"))?;
+        let token_buf = storage_ro_from_bytes::<GpuToken>(
+            &self.device,
+            "parser.warm_up.tokens",
+            &raw_token_kind_rows(&[], token_capacity as usize),
+            token_capacity as usize,
+        );
+        let token_count_buf =
+            storage_ro_from_u32s(&self.device, "parser.warm_up.token_count", &[0]);
+        let source_buf = storage_ro_from_u32s(&self.device, "parser.warm_up.source", &[0]);
+
+        let bytes_before = tracked_buffer_allocation_stats().bytes;
+        let misses_before = self.bind_group_cache_stats().misses;
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("parser.warm_up.encoder"),
+            });
+        let (_check, consumed) = self.record_checked_resident_ll1_hir_artifacts(
+            &mut encoder,
+            token_capacity,
+            &token_buf,
+            &token_count_buf,
+            None,
+            0,
+            &source_buf,
+            tables,
+            &mut None,
+            |_, _, _| Ok::<(), anyhow::Error>(()),
+        )?;
+        consumed?;
+        let buffers_bytes = tracked_buffer_allocation_stats()
+            .bytes
+            .saturating_sub(bytes_before);
+        crate::gpu::passes_core::submit_with_progress(
+            &self.queue,
+            "parser.warm-up",
+            encoder.finish(),
+        );
+        crate::gpu::poll::wait_for_submitted_work(&self.device, "parser.warm-up")?;
+
+        Ok(WarmupReport {
+            buffers_bytes,
+            bind_groups_created: self.bind_group_cache_stats().misses - misses_before,
+            passes_executed: 1,
+        })
+    }
+}
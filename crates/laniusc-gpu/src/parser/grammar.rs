@@ -0,0 +1,322 @@
+//! Grammar terminals checked against the token kinds the parser will see.
+//!
+//! A grammar names its terminals by [`TokenKind`], but only some kinds reach
+//! the parser: trivia is dropped from the kept stream, and the parser token
+//! passes rewrite many raw kinds into context-specific retags (see
+//! [`TokenKind::origin`]). [`check_against_lexer`] reports terminals the parser
+//! can never receive, lexer kinds no production consumes, and retags a grammar
+//! skipped while referencing their raw kind.
+//!
+//! [`TokenKind`]: crate::lexer::tables::tokens::TokenKind
+//! [`TokenKind::origin`]: crate::lexer::tables::tokens::TokenKind::origin
+//! [`check_against_lexer`]: crate::parser::grammar::check_against_lexer
+
+use std::collections::BTreeSet;
+
+use crate::lexer::{origin::KindOrigin, tables::tokens::TokenKind};
+
+/// The terminal view of a grammar that [`check_against_lexer`] needs.
+#[derive(Debug, Clone, Default)]
+pub struct Grammar {
+    pub productions: Vec<GrammarProduction>,
+}
+
+/// One production's tag and the lexer kind ids of its right-hand-side
+/// terminals, in order.
+#[derive(Debug, Clone)]
+pub struct GrammarProduction {
+    pub tag: String,
+    pub terminals: Vec<u32>,
+}
+
+impl Grammar {
+    /// Every terminal id referenced by some production.
+    pub fn terminals(&self) -> BTreeSet<u32> {
+        self.productions
+            .iter()
+            .flat_map(|prod| prod.terminals.iter().copied())
+            .collect()
+    }
+
+    /// `TokenKind` names indexed by lexer kind id, empty for kinds the grammar
+    /// does not reference, for
+    /// [`PrecomputedParseTables::terminal_names`](crate::parser::tables::PrecomputedParseTables::terminal_names).
+    pub fn terminal_names(&self) -> Vec<String> {
+        let terminals = self.terminals();
+        let Some(&max) = terminals.last() else {
+            return Vec::new();
+        };
+        let mut names = vec![String::new(); max as usize + 1];
+        for id in terminals {
+            if let Some(kind) = TokenKind::from_u32(id) {
+                names[id as usize] = format!("{kind:?}");
+            }
+        }
+        names
+    }
+}
+
+/// A mismatch between a grammar's terminals and the lexer's kinds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompatIssue {
+    /// A terminal id that is not a `TokenKind` at all.
+    UnknownTerminal { production: String, id: u32 },
+    /// A terminal the parser never receives: trivia or an unproduced kind.
+    UnproducedTerminal {
+        production: String,
+        kind: TokenKind,
+        origin: KindOrigin,
+    },
+    /// A kind that reaches the parser but that no production consumes, by
+    /// itself or through a retag.
+    DeadToken { kind: TokenKind },
+    /// The grammar references `raw` but not `retag`, which the parser token
+    /// passes write in its place in some contexts.
+    MissingRetag { raw: TokenKind, retag: TokenKind },
+}
+
+impl CompatIssue {
+    /// Whether tables built from the grammar cannot parse correctly; other
+    /// issues are warnings.
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            Self::UnknownTerminal { .. } | Self::UnproducedTerminal { .. }
+        )
+    }
+}
+
+impl std::fmt::Display for CompatIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownTerminal { production, id } => {
+                write!(
+                    f,
+                    "production {production} references terminal {id}, which is not a TokenKind"
+                )
+            }
+            Self::UnproducedTerminal {
+                production,
+                kind,
+                origin,
+            } => {
+                let why = match origin {
+                    KindOrigin::Trivia => "trivia the parser never sees",
+                    _ => "never produced by the lexer or the parser token passes",
+                };
+                write!(
+                    f,
+                    "production {production} expects '{kind:?}', which is {why}"
+                )
+            }
+            Self::DeadToken { kind } => {
+                write!(f, "lexer kind '{kind:?}' is not consumed by any production")
+            }
+            Self::MissingRetag { raw, retag } => write!(
+                f,
+                "grammar references '{raw:?}' but not its retag '{retag:?}', which replaces it \
+                 in some contexts"
+            ),
+        }
+    }
+}
+
+/// Cross-references `grammar`'s terminals against [`TokenKind::origin`].
+///
+/// Errors come first, in production order; then dead tokens and missing
+/// retags, in kind order.
+pub fn check_against_lexer(grammar: &Grammar) -> Vec<CompatIssue> {
+    let mut issues = Vec::new();
+    for prod in &grammar.productions {
+        let mut seen = BTreeSet::new();
+        for &id in prod.terminals.iter().filter(|&&id| seen.insert(id)) {
+            let production = prod.tag.clone();
+            match TokenKind::from_u32(id) {
+                None => issues.push(CompatIssue::UnknownTerminal { production, id }),
+                Some(kind) if !kind.origin().reaches_parser() => {
+                    issues.push(CompatIssue::UnproducedTerminal {
+                        production,
+                        kind,
+                        origin: kind.origin(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    let terminals = grammar.terminals();
+    let referenced = |kind: TokenKind| terminals.contains(&(kind as u32));
+    for &kind in TokenKind::ALL {
+        if kind.origin() == KindOrigin::Lexed && !referenced(kind) && !kind.retags().any(referenced)
+        {
+            issues.push(CompatIssue::DeadToken { kind });
+        }
+    }
+    for &raw in TokenKind::ALL.iter().filter(|&&raw| referenced(raw)) {
+        for retag in raw.retags().filter(|&retag| !referenced(retag)) {
+            issues.push(CompatIssue::MissingRetag { raw, retag });
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grammar(productions: &[(&str, &[TokenKind])]) -> Grammar {
+        grammar_ids(
+            &productions
+                .iter()
+                .map(|(tag, kinds)| (*tag, kinds.iter().map(|&k| k as u32).collect()))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn grammar_ids(productions: &[(&str, Vec<u32>)]) -> Grammar {
+        Grammar {
+            productions: productions
+                .iter()
+                .map(|(tag, terminals)| GrammarProduction {
+                    tag: tag.to_string(),
+                    terminals: terminals.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    fn errors(grammar: &Grammar) -> Vec<String> {
+        check_against_lexer(grammar)
+            .into_iter()
+            .filter(CompatIssue::is_error)
+            .map(|issue| issue.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn terminals_that_never_reach_the_parser_are_errors() {
+        use TokenKind::*;
+        let g = grammar(&[
+            ("stmt_let", &[Let, LetIdent, LetAssign, LetSemicolon]),
+            ("stmt_ws", &[White, Ident, White]),
+            ("stmt_decl", &[DeclAssign]),
+        ]);
+        assert_eq!(
+            errors(&g),
+            [
+                "production stmt_ws expects 'White', which is trivia the parser never sees",
+                "production stmt_decl expects 'DeclAssign', which is never produced by the lexer \
+                 or the parser token passes",
+            ]
+        );
+    }
+
+    #[test]
+    fn ids_outside_the_enum_are_errors() {
+        let past_end = TokenKind::ALL.len() as u32 + 1;
+        let g = grammar_ids(&[("atom", vec![0, TokenKind::Int as u32, past_end])]);
+        assert_eq!(
+            errors(&g),
+            [
+                "production atom references terminal 0, which is not a TokenKind".to_string(),
+                format!("production atom references terminal {past_end}, which is not a TokenKind"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unconsumed_lexer_kinds_are_dead_token_warnings() {
+        use TokenKind::*;
+        let g = grammar(&[("atom", &[Int]), ("sum", &[InfixPlus])]);
+        let issues = check_against_lexer(&g);
+        assert!(issues.iter().all(|issue| !issue.is_error()), "{issues:?}");
+        let dead: Vec<_> = issues
+            .iter()
+            .filter_map(|issue| match issue {
+                CompatIssue::DeadToken { kind } => Some(*kind),
+                _ => None,
+            })
+            .collect();
+        assert!(
+            dead.contains(&Star) && dead.contains(&AngleGeneric),
+            "{dead:?}"
+        );
+        // Consumed directly, consumed through a retag, dropped, or unproduced.
+        for kind in [Int, Plus, White, Shebang, DeclAssign, InfixPlus] {
+            assert!(!dead.contains(&kind), "{kind:?} reported dead");
+        }
+        assert_eq!(
+            CompatIssue::DeadToken { kind: Star }.to_string(),
+            "lexer kind 'Star' is not consumed by any production"
+        );
+    }
+
+    #[test]
+    fn referencing_a_raw_kind_requires_its_retags() {
+        use TokenKind::*;
+        let g = grammar(&[
+            (
+                "gt",
+                &[Ident, Gt, TypeArgGt, GenericParamGt, BoundTypeArgGt],
+            ),
+            ("sub", &[Minus]),
+        ]);
+        let missing: Vec<_> = check_against_lexer(&g)
+            .into_iter()
+            .filter(|issue| matches!(issue, CompatIssue::MissingRetag { raw, .. } if *raw != Ident))
+            .map(|issue| issue.to_string())
+            .collect();
+        assert_eq!(
+            missing,
+            [
+                "grammar references 'Gt' but not its retag 'PathTypeArgGt', which replaces it in \
+                 some contexts",
+                "grammar references 'Minus' but not its retag 'PrefixMinus', which replaces it in \
+                 some contexts",
+                "grammar references 'Minus' but not its retag 'InfixMinus', which replaces it in \
+                 some contexts",
+            ]
+        );
+    }
+
+    #[test]
+    fn the_compiler_grammar_has_no_errors_or_missing_retags() {
+        let src = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../grammar/lanius.bnf"
+        ));
+        let productions: Vec<_> = src
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('#') && line.contains("->"))
+            .map(|line| GrammarProduction {
+                tag: line
+                    .split("->")
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+                terminals: line
+                    .split('\'')
+                    .skip(1)
+                    .step_by(2)
+                    .map(|name| TokenKind::from_name(name).expect("terminal") as u32)
+                    .collect(),
+            })
+            .collect();
+        let issues = check_against_lexer(&Grammar { productions });
+        let bad: Vec<_> = issues
+            .iter()
+            .filter(|issue| !matches!(issue, CompatIssue::DeadToken { .. }))
+            .collect();
+        assert!(bad.is_empty(), "{bad:?}");
+    }
+
+    #[test]
+    fn terminal_names_are_indexed_by_kind_id() {
+        let g = grammar(&[("atom", &[TokenKind::Int, TokenKind::Ident])]);
+        assert_eq!(g.terminal_names(), ["", "Ident", "Int"]);
+        assert!(Grammar::default().terminal_names().is_empty());
+    }
+}
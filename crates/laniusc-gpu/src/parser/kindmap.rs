@@ -0,0 +1,425 @@
+//! Dense renumbering of the token kinds a parse grammar references.
+//!
+//! The pair tables are `n_kinds²` grids, so every lexer kind the grammar
+//! never mentions still costs a full row and column. [`renumber_kinds`]
+//! keeps only referenced kinds, numbers them densely (kind `0`, the stream
+//! sentinel, always stays `0`), and records the forward and backward maps in
+//! the tables. Parser token streams keep lexer kind numbering; the GPU pair
+//! passes apply the forward map through [`KindMap::lookup_words`] before
+//! indexing the grid, and host LL(1) replay maps reported kinds back.
+//!
+//! [`renumber_kinds`]: crate::parser::kindmap::renumber_kinds
+//! [`KindMap::lookup_words`]: crate::parser::kindmap::KindMap::lookup_words
+
+use std::fmt;
+
+use super::tables::{INVALID_TABLE_ENTRY, PrecomputedParseTables};
+
+/// Entries in the GPU forward-map lookup buffer.
+pub const KIND_REMAP_WIDTH: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Forward (lexer kind -> grid kind) and backward (grid kind -> lexer kind) maps.
+pub struct KindMap {
+    forward: Vec<u32>,
+    backward: Vec<u32>,
+}
+
+impl KindMap {
+    /// Builds a dense map keeping every kind flagged in `referenced`, plus kind `0`.
+    pub fn from_referenced(referenced: &[bool]) -> Self {
+        let mut forward = vec![INVALID_TABLE_ENTRY; referenced.len().max(1)];
+        let mut backward = Vec::new();
+        for (kind, slot) in forward.iter_mut().enumerate() {
+            if kind == 0 || referenced.get(kind).copied().unwrap_or(false) {
+                *slot = backward.len() as u32;
+                backward.push(kind as u32);
+            }
+        }
+        Self { forward, backward }
+    }
+
+    /// Rebuilds a map from serialized parts, checking that they are inverses.
+    pub fn from_parts(forward: Vec<u32>, backward: Vec<u32>) -> Result<Self, String> {
+        if forward.len() > KIND_REMAP_WIDTH {
+            return Err(format!(
+                "kind map covers {} kinds; the GPU lookup holds {KIND_REMAP_WIDTH}",
+                forward.len()
+            ));
+        }
+        for (dense, &kind) in backward.iter().enumerate() {
+            if forward.get(kind as usize).copied() != Some(dense as u32) {
+                return Err(format!("kind map does not round-trip grid kind {dense}"));
+            }
+        }
+        let mapped = forward
+            .iter()
+            .filter(|&&dense| dense != INVALID_TABLE_ENTRY)
+            .count();
+        if mapped != backward.len() {
+            return Err("kind map forward and backward sizes disagree".into());
+        }
+        Ok(Self { forward, backward })
+    }
+
+    /// Lexer kind count covered by the forward map.
+    pub fn source_kinds(&self) -> u32 {
+        self.forward.len() as u32
+    }
+
+    /// Grid width after renumbering.
+    pub fn dense_kinds(&self) -> u32 {
+        self.backward.len() as u32
+    }
+
+    /// Grid kind for a lexer kind, or `None` when the grammar never uses it.
+    pub fn dense(&self, kind: u32) -> Option<u32> {
+        self.forward
+            .get(kind as usize)
+            .copied()
+            .filter(|&dense| dense != INVALID_TABLE_ENTRY)
+    }
+
+    /// Lexer kind for a grid kind.
+    pub fn source(&self, dense: u32) -> Option<u32> {
+        self.backward.get(dense as usize).copied()
+    }
+
+    /// Lexer kind -> grid kind, `INVALID_TABLE_ENTRY` where unmapped.
+    pub fn forward(&self) -> &[u32] {
+        &self.forward
+    }
+
+    /// Grid kind -> lexer kind.
+    pub fn backward(&self) -> &[u32] {
+        &self.backward
+    }
+
+    /// Forward map padded to [`KIND_REMAP_WIDTH`] words for the GPU lookup buffer.
+    pub fn lookup_words(&self) -> Vec<u32> {
+        let mut words = vec![INVALID_TABLE_ENTRY; KIND_REMAP_WIDTH];
+        words[..self.forward.len()].copy_from_slice(&self.forward);
+        words
+    }
+
+    /// Renumbers one stack or RHS symbol: terminals through the forward map,
+    /// nonterminals by shifting past the dense terminal range.
+    fn symbol(&self, symbol: u32) -> u32 {
+        let source_kinds = self.source_kinds();
+        if symbol < source_kinds {
+            self.dense(symbol)
+                .expect("grammar-referenced terminal must be mapped")
+        } else {
+            self.dense_kinds() + (symbol - source_kinds)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Grid sizes before and after renumbering.
+pub struct KindDensity {
+    pub source_kinds: u32,
+    pub dense_kinds: u32,
+}
+
+impl KindDensity {
+    /// Pair-grid cells before renumbering.
+    pub fn source_cells(&self) -> usize {
+        (self.source_kinds as usize).pow(2)
+    }
+
+    /// Pair-grid cells after renumbering.
+    pub fn dense_cells(&self) -> usize {
+        (self.dense_kinds as usize).pow(2)
+    }
+}
+
+impl fmt::Display for KindDensity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pct = if self.source_cells() == 0 {
+            100.0
+        } else {
+            100.0 * self.dense_cells() as f64 / self.source_cells() as f64
+        };
+        write!(
+            f,
+            "kinds {} -> {}, grid cells {} -> {} ({pct:.1}%)",
+            self.source_kinds,
+            self.dense_kinds,
+            self.source_cells(),
+            self.dense_cells()
+        )
+    }
+}
+
+/// Marks the kinds that any grid cell, LL(1) prediction, or RHS terminal uses.
+pub fn referenced_kinds(tables: &PrecomputedParseTables) -> Vec<bool> {
+    let n = tables.n_kinds as usize;
+    let mut referenced = vec![false; n];
+    if n == 0 {
+        return referenced;
+    }
+    for (idx, (&sc, &pp)) in tables.sc_len.iter().zip(&tables.pp_len).enumerate() {
+        if sc != 0 || pp != 0 {
+            referenced[idx / n] = true;
+            referenced[idx % n] = true;
+        }
+    }
+    for row in tables.ll1_predict.chunks(n) {
+        for (kind, &prod) in row.iter().enumerate() {
+            if prod != INVALID_TABLE_ENTRY {
+                referenced[kind] = true;
+            }
+        }
+    }
+    let sc_terminals = tables.sc_superseq.iter().map(|&code| code >> 1);
+    for symbol in tables.prod_rhs.iter().copied().chain(sc_terminals) {
+        if let Some(slot) = referenced.get_mut(symbol as usize) {
+            *slot = true;
+        }
+    }
+    referenced
+}
+
+/// Returns tables whose grids cover only grammar-referenced kinds.
+///
+/// Stack-change codes and LL(1) RHS symbols are renumbered with the same map,
+/// so nonterminals move down to start right after the dense terminals.
+/// Tables that are already renumbered are returned unchanged.
+pub fn renumber_kinds(tables: &PrecomputedParseTables) -> PrecomputedParseTables {
+    if tables.kind_map.is_some() {
+        return tables.clone();
+    }
+    let map = KindMap::from_referenced(&referenced_kinds(tables));
+    let dense = map.dense_kinds();
+    let mut out = PrecomputedParseTables::new(dense, tables.n_productions);
+    out.prod_arity = tables.prod_arity.clone();
+
+    let n = tables.n_kinds;
+    for (dense_prev, &prev) in map.backward().iter().enumerate() {
+        for (dense_this, &this) in map.backward().iter().enumerate() {
+            let idx = (prev as usize) * (n as usize) + this as usize;
+            let sc_off = tables.sc_off[idx] as usize;
+            let sc = tables.sc_superseq[sc_off..sc_off + tables.sc_len[idx] as usize]
+                .iter()
+                .map(|&code| (map.symbol(code >> 1) << 1) | (code & 1))
+                .collect::<Vec<_>>();
+            let pp_off = tables.pp_off[idx] as usize;
+            let pp = &tables.pp_superseq[pp_off..pp_off + tables.pp_len[idx] as usize];
+            out.set_sc_for_pair(dense_prev as u32, dense_this as u32, &sc);
+            out.set_pp_for_pair(dense_prev as u32, dense_this as u32, pp);
+        }
+    }
+
+    out.n_nonterminals = tables.n_nonterminals;
+    out.start_nonterminal = tables.start_nonterminal;
+    if !tables.ll1_predict.is_empty() {
+        out.ll1_predict = tables
+            .ll1_predict
+            .chunks(n as usize)
+            .flat_map(|row| map.backward().iter().map(|&kind| row[kind as usize]))
+            .collect();
+    }
+    out.prod_rhs_off = tables.prod_rhs_off.clone();
+    out.prod_rhs_len = tables.prod_rhs_len.clone();
+    out.prod_rhs = tables
+        .prod_rhs
+        .iter()
+        .map(|&symbol| map.symbol(symbol))
+        .collect();
+
+    let max_symbol_id = out
+        .sc_superseq
+        .iter()
+        .map(|&code| code >> 1)
+        .chain(out.prod_rhs.iter().copied())
+        .max()
+        .unwrap_or(0);
+    out.finalize_bit_widths(max_symbol_id);
+    out.kind_map = Some(map);
+    out.copy_grammar_names(tables);
+    out.copy_precedence_ladder(tables);
+    out.sentinel_kind = tables.sentinel_kind;
+    out.start_sentinel = tables.start_sentinel;
+    out.allows_empty_input = tables.allows_empty_input;
+    out.sync_sets = tables.sync_sets.clone();
+    out
+}
+
+/// Grid sizes for `tables` compared with their lexer kind count.
+pub fn density(tables: &PrecomputedParseTables) -> KindDensity {
+    KindDensity {
+        source_kinds: tables
+            .kind_map
+            .as_ref()
+            .map_or(tables.n_kinds, KindMap::source_kinds),
+        dense_kinds: tables.n_kinds,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        lexer::tables::tokens::{N_KINDS, TokenKind},
+        parser::tables::{encode_pop, encode_push},
+    };
+
+    /// `S -> Ident S_tail ; S_tail -> ',' Ident S_tail | ';'` over the full
+    /// lexer kind space, plus bracket pairs, so ten kinds (with `0`) appear.
+    fn ten_kind_tables() -> PrecomputedParseTables {
+        let kinds = [
+            0,
+            TokenKind::Ident as u32,
+            TokenKind::Comma as u32,
+            TokenKind::Semicolon as u32,
+            TokenKind::GroupLParen as u32,
+            TokenKind::GroupRParen as u32,
+            TokenKind::Int as u32,
+            TokenKind::Plus as u32,
+            TokenKind::Minus as u32,
+            TokenKind::Star as u32,
+        ];
+        let mut tables = PrecomputedParseTables::new(N_KINDS, 3);
+        tables.prod_arity = vec![1, 1, 0];
+        for (i, &prev) in kinds.iter().enumerate() {
+            for (j, &this) in kinds.iter().enumerate() {
+                if (i + j) % 3 == 0 {
+                    tables.set_sc_for_pair(prev, this, &[encode_push(N_KINDS + 1)]);
+                    tables.set_pp_for_pair(prev, this, &[(i % 3) as u32]);
+                } else if (i + j) % 3 == 1 {
+                    tables.set_sc_for_pair(prev, this, &[encode_pop(this)]);
+                }
+            }
+        }
+        tables.n_nonterminals = 2;
+        tables.start_nonterminal = 0;
+        tables.ll1_predict = vec![INVALID_TABLE_ENTRY; 2 * N_KINDS as usize];
+        tables.ll1_predict[TokenKind::Ident as usize] = 0;
+        tables.ll1_predict[N_KINDS as usize + TokenKind::Comma as usize] = 1;
+        tables.ll1_predict[N_KINDS as usize + TokenKind::Semicolon as usize] = 2;
+        tables.prod_rhs_off = vec![0, 2, 5];
+        tables.prod_rhs_len = vec![2, 3, 1];
+        tables.prod_rhs = vec![
+            TokenKind::Ident as u32,
+            N_KINDS + 1,
+            TokenKind::Comma as u32,
+            TokenKind::Ident as u32,
+            N_KINDS + 1,
+            TokenKind::Semicolon as u32,
+        ];
+        tables
+    }
+
+    #[test]
+    fn ten_referenced_kinds_produce_ten_by_ten_grids() {
+        let tables = ten_kind_tables();
+        let dense = renumber_kinds(&tables);
+
+        assert_eq!(dense.n_kinds, 10);
+        for grid in [&dense.sc_off, &dense.sc_len, &dense.pp_off, &dense.pp_len] {
+            assert_eq!(grid.len(), 100);
+        }
+        assert_eq!(dense.ll1_predict.len(), 2 * 10);
+        assert_eq!(dense.to_action_header_grid_bytes().len(), 100 * 16);
+        let map = dense
+            .kind_map
+            .as_ref()
+            .expect("renumbered tables carry a map");
+        assert_eq!(map.source_kinds(), N_KINDS);
+        assert_eq!(map.dense(0), Some(0));
+        assert_eq!(map.dense(TokenKind::Let as u32), None);
+        assert_eq!(density(&dense).dense_cells(), 100);
+    }
+
+    #[test]
+    fn renumbered_tables_replay_like_the_source_tables() {
+        let tables = ten_kind_tables();
+        let dense = renumber_kinds(&tables);
+        let ident = TokenKind::Ident as u32;
+        let comma = TokenKind::Comma as u32;
+        let semi = TokenKind::Semicolon as u32;
+
+        let accepted = [0, ident, comma, ident, semi, 0];
+        assert_eq!(
+            dense.test_cpu_ll1_production_stream_with_positions(&accepted),
+            tables.test_cpu_ll1_production_stream_with_positions(&accepted)
+        );
+        assert_eq!(
+            dense.test_cpu_partial_parse_stream(&accepted),
+            tables.test_cpu_partial_parse_stream(&accepted)
+        );
+
+        for rejected in [
+            vec![0, ident, ident, 0],
+            vec![0, ident, TokenKind::Let as u32, 0],
+            vec![0, TokenKind::Let as u32, 0],
+            vec![0, ident, semi, semi, 0],
+        ] {
+            assert_eq!(
+                dense.diagnose_ll1_rejection(&rejected),
+                tables.diagnose_ll1_rejection(&rejected),
+                "{rejected:?}"
+            );
+            assert_eq!(
+                dense.test_cpu_ll1_production_stream(&rejected),
+                tables.test_cpu_ll1_production_stream(&rejected),
+                "{rejected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn renumbering_preserves_stack_change_matching() {
+        let tables = ten_kind_tables();
+        let dense = renumber_kinds(&tables);
+        let kinds = [
+            0,
+            TokenKind::GroupLParen as u32,
+            TokenKind::Ident as u32,
+            TokenKind::GroupRParen as u32,
+            0,
+        ];
+        let source = tables.test_cpu_stack_change_stream(&kinds);
+        let renumbered = dense.test_cpu_stack_change_stream(&kinds);
+        assert_eq!(source.len(), renumbered.len());
+        for (a, b) in source.iter().zip(&renumbered) {
+            assert_eq!(a & 1, b & 1);
+        }
+        let equal_pairs = |s: &[u32]| {
+            let mut pairs = Vec::new();
+            for i in 0..s.len() {
+                for j in 0..s.len() {
+                    pairs.push(s[i] >> 1 == s[j] >> 1);
+                }
+            }
+            pairs
+        };
+        assert_eq!(equal_pairs(&source), equal_pairs(&renumbered));
+    }
+
+    #[test]
+    fn kind_map_round_trips_through_parts_and_binary() {
+        let dense = renumber_kinds(&ten_kind_tables());
+        let map = dense.kind_map.clone().unwrap();
+        assert_eq!(
+            KindMap::from_parts(map.forward().to_vec(), map.backward().to_vec()),
+            Ok(map.clone())
+        );
+        assert!(KindMap::from_parts(map.forward().to_vec(), vec![0, 1]).is_err());
+        assert_eq!(map.lookup_words().len(), KIND_REMAP_WIDTH);
+
+        let path = std::env::temp_dir().join(format!(
+            "laniusc-kindmap-{}-{:?}.bin",
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        dense.save_bin(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let loaded = PrecomputedParseTables::load_bin_bytes(&bytes).unwrap();
+        assert_eq!(loaded.kind_map, Some(map));
+        assert_eq!(loaded.sc_superseq, dense.sc_superseq);
+        assert_eq!(loaded.ll1_predict, dense.ll1_predict);
+    }
+}
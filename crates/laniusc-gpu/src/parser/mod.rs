@@ -5,28 +5,55 @@
 //! topology, semantic HIR topology, and typed HIR record arrays for type
 //! checking and backend lowering.
 
+/// Host-side syntax trees and precedence-chain collapsing.
+pub mod ast;
+
+/// Chunk sizes for chunked parses from the pass timings of earlier parses.
+pub mod autotune;
+
 /// Parser buffer models and GPU buffer allocation helpers.
 pub mod buffers;
 
+/// Bracket-match results reused across parses of an unchanged kind stream.
+pub mod cache;
+
 /// Debug buffer snapshots and parser debug output containers.
 pub mod debug;
 
 /// Resident GPU parser driver and high-level parser entry points.
 pub mod driver;
 
+/// Grammar terminals checked against the kinds the lexer produces.
+pub mod grammar;
+
 /// Compact helpers for parser-owned HIR record words.
 pub mod hir_records;
 
+/// Dense token-kind renumbering for parse-table grids.
+pub mod kindmap;
+
+/// Construction-time parser settings.
+pub mod options;
+
 /// Parser compute pass wrappers grouped by pipeline stage.
 pub mod passes;
 
 /// Debug/readback conversion and parser-owned HIR validation.
 pub mod readback;
 
+/// Syntax-error recovery over sync sets and chunked re-dispatch.
+pub mod recovery;
+
+/// Host mirror of the kept-token rule that retags raw `(` and `[`.
+pub mod retag;
+
 /// Standalone syntax-checking entry points.
 pub mod syntax;
 
 /// Precomputed parser table data and CPU table oracles.
 pub mod tables;
 
+pub use autotune::ChunkSize;
+pub use cache::{BracketCache, BracketCacheLimits, BracketCacheStats, ResidentBracketKey};
 pub use driver::*;
+pub use options::ParserOptions;
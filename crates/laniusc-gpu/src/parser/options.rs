@@ -0,0 +1,120 @@
+//! Construction-time settings for [`GpuParser`](crate::parser::GpuParser).
+
+use serde::{Deserialize, Serialize};
+
+use crate::gpu::determinism::Determinism;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+/// Settings a [`GpuParser`](crate::parser::GpuParser) is built with.
+///
+/// The parser never consults the environment; binaries that honour the
+/// `LANIUS_*` variables translate them with [`ParserOptions::from_env`].
+/// [`GpuParser::parse_with_options`](crate::parser::GpuParser::parse_with_options)
+/// overrides them for one one-shot parse.
+pub struct ParserOptions {
+    /// Wrap submissions and passes in wgpu validation scopes, and record
+    /// every pass on its own.
+    pub validation_scopes: bool,
+    /// Wrap each pass in an encoder debug group. On by default in debug
+    /// builds.
+    pub debug_groups: bool,
+    /// Let compatible passes share one `wgpu::ComputePass`.
+    pub batch_compute_passes: bool,
+    /// Read one-shot parse results back; when off, the GPU work is still
+    /// submitted and the result is empty.
+    pub readback: bool,
+    /// Time every pass of one-shot and chunked parses on the GPU, log the
+    /// timings, and return them in
+    /// [`ParseResult::timings`](crate::parser::ParseResult::timings).
+    pub gpu_timing: bool,
+    /// Log resident buffer cache decisions and optional HIR capacities.
+    pub host_timing: bool,
+    /// Fill [`ParseResult::dispatch_records`](crate::parser::ParseResult::dispatch_records).
+    pub capture_dispatch_metadata: bool,
+    /// Fill [`ParseResult::depth_at_token`](crate::parser::ParseResult::depth_at_token).
+    pub capture_bracket_depths: bool,
+    /// Reproducibility parses must provide. No parser pass reduces through
+    /// atomic arrival order yet, so both modes currently give identical
+    /// results; passes that do must honour `Strict`.
+    pub determinism: Determinism,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            validation_scopes: false,
+            debug_groups: cfg!(debug_assertions),
+            batch_compute_passes: true,
+            readback: true,
+            gpu_timing: false,
+            host_timing: false,
+            capture_dispatch_metadata: false,
+            capture_bracket_depths: false,
+            determinism: Determinism::Strict,
+        }
+    }
+}
+
+impl ParserOptions {
+    /// The options the `LANIUS_*` environment variables used to select:
+    /// `LANIUS_VALIDATION_SCOPES`, `LANIUS_DEBUG_GROUPS`,
+    /// `LANIUS_BATCH_COMPUTE_PASSES`, `LANIUS_READBACK`, `LANIUS_GPU_TIMING`,
+    /// `LANIUS_GPU_COMPILE_HOST_TIMING` and `LANIUS_CAPTURE_DISPATCH_METADATA`.
+    pub fn from_env() -> Self {
+        use crate::gpu::{
+            env::{env_bool_strict, env_bool_truthy},
+            passes_core,
+        };
+        Self {
+            validation_scopes: passes_core::validation_scopes_enabled(),
+            debug_groups: passes_core::debug_groups_enabled(),
+            batch_compute_passes: passes_core::compute_pass_batching_enabled(),
+            readback: env_bool_truthy("LANIUS_READBACK", true),
+            gpu_timing: env_bool_strict("LANIUS_GPU_TIMING", false),
+            host_timing: env_bool_truthy("LANIUS_GPU_COMPILE_HOST_TIMING", false),
+            capture_dispatch_metadata: env_bool_truthy("LANIUS_CAPTURE_DISPATCH_METADATA", false),
+            capture_bracket_depths: false,
+            determinism: Determinism::Strict,
+        }
+    }
+
+    /// Whether parser passes may share a compute pass while `timed` says
+    /// whether a GPU timer is stamping between them.
+    pub(crate) fn batches_passes(&self, timed: bool) -> bool {
+        !timed && self.batch_compute_passes && !self.validation_scopes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_ignore_the_environment() {
+        // Defaults are plain values: nothing here can observe a leftover export.
+        let defaults = ParserOptions::default();
+        assert!(!defaults.validation_scopes);
+        assert!(defaults.batch_compute_passes);
+        assert!(defaults.readback);
+        assert!(!defaults.capture_dispatch_metadata);
+        assert_eq!(defaults.debug_groups, cfg!(debug_assertions));
+    }
+
+    #[test]
+    fn validation_scopes_and_timers_stop_pass_batching() {
+        let options = ParserOptions::default();
+        assert!(options.batches_passes(false));
+        assert!(!options.batches_passes(true));
+        let scoped = ParserOptions {
+            validation_scopes: true,
+            ..options
+        };
+        assert!(!scoped.batches_passes(false));
+        let unbatched = ParserOptions {
+            batch_compute_passes: false,
+            ..options
+        };
+        assert!(!unbatched.batches_passes(false));
+    }
+}
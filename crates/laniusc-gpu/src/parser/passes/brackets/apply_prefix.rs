@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use encase::ShaderType;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -20,7 +20,7 @@ pub struct BracketsApplyPrefixPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     BracketsApplyPrefixPass,
     label: "brackets_03_apply_prefix",
     shader: "parser/brackets/03_apply_prefix"
@@ -29,10 +29,24 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for BracketsApplyPrefixPass {
     const NAME: &'static str = "brackets_03_apply_prefix";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gParams",
+            "sc_stream",
+            "partial_parse_status",
+            "exscan_inblock",
+            "block_prefix",
+            "out_depths_ro",
+            "layer",
+            "frontier",
+        ]
+    }
     fn data(&self) -> &PassData {
         &self.data
     }
@@ -56,6 +70,7 @@ impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for BracketsApplyPre
             // read-only view of depths for offset
             ("out_depths_ro".into(), b.depths_out.as_entire_binding()),
             ("layer".into(), b.b_layer.as_entire_binding()),
+            ("frontier".into(), b.bracket_frontier.as_entire_binding()),
         ])
     }
 }
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use encase::ShaderType;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -17,7 +17,7 @@ pub struct BracketsClearMatchesPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     BracketsClearMatchesPass,
     label: "brackets_04_clear_matches",
     shader: "parser/brackets/04_clear_matches"
@@ -26,11 +26,16 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for BracketsClearMatchesPass {
     const NAME: &'static str = "brackets_04_clear_matches";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
 
+    fn expected_bindings() -> &'static [&'static str] {
+        &["gClear", "partial_parse_status", "match_for_index"]
+    }
+
     fn data(&self) -> &PassData {
         &self.data
     }
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use encase::ShaderType;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -23,7 +23,7 @@ pub struct BracketsPsePairPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     BracketsPsePairPass,
     label: "brackets_pse_04_pair_by_layer",
     shader: "parser/brackets/pse_04_pair_by_layer"
@@ -32,10 +32,25 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for BracketsPsePairPass {
     const NAME: &'static str = "brackets_pse_04_pair_by_layer";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gParams",
+            "sc_stream",
+            "partial_parse_status",
+            "layer",
+            "block_row_min",
+            "block_prefix",
+            "min_tree",
+            "match_for_index",
+            "out_valid",
+        ]
+    }
     fn data(&self) -> &PassData {
         &self.data
     }
@@ -4,7 +4,15 @@ use anyhow::Result;
 use encase::ShaderType;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, InputElements, Pass, PassData, bind_group, plan_workgroups},
+    gpu::passes_core::{
+        DispatchConvention,
+        DispatchDim,
+        InputElements,
+        Pass,
+        PassData,
+        bind_group,
+        plan_workgroups,
+    },
     parser::buffers::{BracketsBlockPrefixScanStep, ParserBuffers},
 };
 
@@ -21,7 +29,7 @@ pub struct BracketsScanBlockPrefixPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     BracketsScanBlockPrefixPass,
     label: "brackets_02_scan_block_prefix",
     shader: "parser/brackets/02_scan_block_prefix"
@@ -119,10 +127,27 @@ impl BracketsScanBlockPrefixPass {
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for BracketsScanBlockPrefixPass {
     const NAME: &'static str = "brackets_02_scan_block_prefix";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gParams",
+            "block_sum",
+            "block_minpref",
+            "block_maxdepth",
+            "prefix_sum_in",
+            "prefix_min_in",
+            "prefix_sum_out",
+            "prefix_min_out",
+            "block_prefix",
+            "out_depths",
+            "out_valid",
+        ]
+    }
     fn data(&self) -> &PassData {
         &self.data
     }
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use encase::ShaderType;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -20,7 +20,7 @@ pub struct BracketsScanInblockPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     BracketsScanInblockPass,
     label: "brackets_01_scan_inblock",
     shader: "parser/brackets/01_scan_inblock"
@@ -29,10 +29,24 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for BracketsScanInblockPass {
     const NAME: &'static str = "brackets_01_scan_inblock";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gParams",
+            "sc_stream",
+            "partial_parse_status",
+            "exscan_inblock",
+            "block_sum",
+            "block_minpref",
+            "block_row_min",
+            "block_maxdepth",
+        ]
+    }
     fn data(&self) -> &PassData {
         &self.data
     }
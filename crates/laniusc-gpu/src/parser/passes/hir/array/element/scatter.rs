@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -10,7 +10,7 @@ pub struct HirArrayElementScatterPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     HirArrayElementScatterPass,
     label: "hir_array_element_scatter",
     shader: "parser/hir/array/element/scatter"
@@ -19,6 +19,7 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirArrayElementScatterPass {
     const NAME: &'static str = "hir_array_element_scatter";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
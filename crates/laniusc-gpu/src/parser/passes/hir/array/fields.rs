@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use encase::ShaderType;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -20,7 +20,7 @@ pub struct HirArrayFieldsPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     HirArrayFieldsPass,
     label: "hir_array_fields",
     shader: "parser/hir/array/fields"
@@ -29,6 +29,7 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirArrayFieldsPass {
     const NAME: &'static str = "hir_array_fields";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
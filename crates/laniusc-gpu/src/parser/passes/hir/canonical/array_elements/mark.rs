@@ -1,17 +1,18 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirCanonicalArrayElementMarkPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirCanonicalArrayElementMarkPass, label: "hir_canonical_array_element_mark", shader: "parser/hir/canonical/array_elements/mark");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirCanonicalArrayElementMarkPass, label: "hir_canonical_array_element_mark", shader: "parser/hir/canonical/array_elements/mark");
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirCanonicalArrayElementMarkPass {
     const NAME: &'static str = "hir_canonical_array_element_mark";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
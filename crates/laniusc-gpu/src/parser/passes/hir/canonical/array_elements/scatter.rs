@@ -1,19 +1,20 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirCanonicalArrayElementScatterPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirCanonicalArrayElementScatterPass, label: "hir_canonical_array_element_scatter", shader: "parser/hir/canonical/array_elements/scatter");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirCanonicalArrayElementScatterPass, label: "hir_canonical_array_element_scatter", shader: "parser/hir/canonical/array_elements/scatter");
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput>
     for HirCanonicalArrayElementScatterPass
 {
     const NAME: &'static str = "hir_canonical_array_element_scatter";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
@@ -1,17 +1,18 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirCanonicalCallArgMarkPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirCanonicalCallArgMarkPass, label: "hir_canonical_call_arg_mark", shader: "parser/hir/canonical/call_args/mark");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirCanonicalCallArgMarkPass, label: "hir_canonical_call_arg_mark", shader: "parser/hir/canonical/call_args/mark");
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirCanonicalCallArgMarkPass {
     const NAME: &'static str = "hir_canonical_call_arg_mark";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
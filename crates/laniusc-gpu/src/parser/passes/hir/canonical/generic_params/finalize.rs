@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -9,7 +9,7 @@ pub struct HirCanonicalGenericParamFinalizePass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     HirCanonicalGenericParamFinalizePass,
     label: "hir_canonical_generic_param_finalize",
     shader: "parser/hir/canonical/generic_params/finalize"
@@ -20,6 +20,7 @@ impl Pass<ParserBuffers, crate::parser::debug::DebugOutput>
 {
     const NAME: &'static str = "hir_canonical_generic_param_finalize";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
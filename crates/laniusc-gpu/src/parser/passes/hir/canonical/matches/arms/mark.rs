@@ -1,18 +1,19 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirCanonicalMatchArmMarkPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirCanonicalMatchArmMarkPass, label: "hir_canonical_match_arm_mark", shader: "parser/hir/canonical/matches/arms/mark");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirCanonicalMatchArmMarkPass, label: "hir_canonical_match_arm_mark", shader: "parser/hir/canonical/matches/arms/mark");
 
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirCanonicalMatchArmMarkPass {
     const NAME: &'static str = "hir_canonical_match_arm_mark";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
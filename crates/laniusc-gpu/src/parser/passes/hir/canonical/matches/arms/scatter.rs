@@ -1,17 +1,18 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirCanonicalMatchArmScatterPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirCanonicalMatchArmScatterPass, label: "hir_canonical_match_arm_scatter", shader: "parser/hir/canonical/matches/arms/scatter");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirCanonicalMatchArmScatterPass, label: "hir_canonical_match_arm_scatter", shader: "parser/hir/canonical/matches/arms/scatter");
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirCanonicalMatchArmScatterPass {
     const NAME: &'static str = "hir_canonical_match_arm_scatter";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
@@ -1,17 +1,18 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirCanonicalMatchPayloadLocalPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirCanonicalMatchPayloadLocalPass, label: "hir_canonical_match_payload_local", shader: "parser/hir/canonical/matches/payloads/local");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirCanonicalMatchPayloadLocalPass, label: "hir_canonical_match_payload_local", shader: "parser/hir/canonical/matches/payloads/local");
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirCanonicalMatchPayloadLocalPass {
     const NAME: &'static str = "hir_canonical_match_payload_local";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
@@ -1,19 +1,20 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirCanonicalMatchPayloadScatterPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirCanonicalMatchPayloadScatterPass, label: "hir_canonical_match_payload_scatter", shader: "parser/hir/canonical/matches/payloads/scatter");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirCanonicalMatchPayloadScatterPass, label: "hir_canonical_match_payload_scatter", shader: "parser/hir/canonical/matches/payloads/scatter");
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput>
     for HirCanonicalMatchPayloadScatterPass
 {
     const NAME: &'static str = "hir_canonical_match_payload_scatter";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
@@ -1,16 +1,17 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 pub struct HirCanonicalMethodMarkPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirCanonicalMethodMarkPass, label: "hir_canonical_method_mark", shader: "parser/hir/canonical/methods/mark");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirCanonicalMethodMarkPass, label: "hir_canonical_method_mark", shader: "parser/hir/canonical/methods/mark");
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirCanonicalMethodMarkPass {
     const NAME: &'static str = "hir_canonical_method_mark";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
@@ -1,16 +1,17 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 pub struct HirCanonicalMethodScatterPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirCanonicalMethodScatterPass, label: "hir_canonical_method_scatter", shader: "parser/hir/canonical/methods/scatter");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirCanonicalMethodScatterPass, label: "hir_canonical_method_scatter", shader: "parser/hir/canonical/methods/scatter");
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirCanonicalMethodScatterPass {
     const NAME: &'static str = "hir_canonical_method_scatter";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
@@ -1,17 +1,18 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirCanonicalPathSegmentScatterPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirCanonicalPathSegmentScatterPass, label: "hir_canonical_path_segment_scatter", shader: "parser/hir/canonical/paths/segments/scatter");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirCanonicalPathSegmentScatterPass, label: "hir_canonical_path_segment_scatter", shader: "parser/hir/canonical/paths/segments/scatter");
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirCanonicalPathSegmentScatterPass {
     const NAME: &'static str = "hir_canonical_path_segment_scatter";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
@@ -1,17 +1,18 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirCanonicalPredicateFinalizePass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirCanonicalPredicateFinalizePass, label: "hir_canonical_predicate_finalize", shader: "parser/hir/canonical/predicates/finalize");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirCanonicalPredicateFinalizePass, label: "hir_canonical_predicate_finalize", shader: "parser/hir/canonical/predicates/finalize");
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirCanonicalPredicateFinalizePass {
     const NAME: &'static str = "hir_canonical_predicate_finalize";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
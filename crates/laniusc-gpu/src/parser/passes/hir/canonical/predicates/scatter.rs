@@ -1,17 +1,18 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirCanonicalPredicateScatterPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirCanonicalPredicateScatterPass, label: "hir_canonical_predicate_scatter", shader: "parser/hir/canonical/predicates/scatter");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirCanonicalPredicateScatterPass, label: "hir_canonical_predicate_scatter", shader: "parser/hir/canonical/predicates/scatter");
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirCanonicalPredicateScatterPass {
     const NAME: &'static str = "hir_canonical_predicate_scatter";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
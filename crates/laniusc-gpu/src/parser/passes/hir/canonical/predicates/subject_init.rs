@@ -1,19 +1,20 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirCanonicalPredicateSubjectInitPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirCanonicalPredicateSubjectInitPass, label: "hir_canonical_predicate_subject_init", shader: "parser/hir/canonical/predicates/subject_init");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirCanonicalPredicateSubjectInitPass, label: "hir_canonical_predicate_subject_init", shader: "parser/hir/canonical/predicates/subject_init");
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput>
     for HirCanonicalPredicateSubjectInitPass
 {
     const NAME: &'static str = "hir_canonical_predicate_subject_init";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
@@ -1,18 +1,19 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirCanonicalVariantLocalPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirCanonicalVariantLocalPass, label: "hir_canonical_variant_local", shader: "parser/hir/canonical/variants/local");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirCanonicalVariantLocalPass, label: "hir_canonical_variant_local", shader: "parser/hir/canonical/variants/local");
 
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirCanonicalVariantLocalPass {
     const NAME: &'static str = "hir_canonical_variant_local";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
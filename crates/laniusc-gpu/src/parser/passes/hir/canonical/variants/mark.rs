@@ -1,18 +1,19 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirCanonicalVariantMarkPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirCanonicalVariantMarkPass, label: "hir_canonical_variant_mark", shader: "parser/hir/canonical/variants/mark");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirCanonicalVariantMarkPass, label: "hir_canonical_variant_mark", shader: "parser/hir/canonical/variants/mark");
 
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirCanonicalVariantMarkPass {
     const NAME: &'static str = "hir_canonical_variant_mark";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
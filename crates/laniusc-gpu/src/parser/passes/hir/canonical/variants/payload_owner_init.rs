@@ -1,20 +1,21 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirCanonicalVariantPayloadOwnerInitPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirCanonicalVariantPayloadOwnerInitPass, label: "hir_canonical_variant_payload_owner_init", shader: "parser/hir/canonical/variants/payload_owner_init");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirCanonicalVariantPayloadOwnerInitPass, label: "hir_canonical_variant_payload_owner_init", shader: "parser/hir/canonical/variants/payload_owner_init");
 
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput>
     for HirCanonicalVariantPayloadOwnerInitPass
 {
     const NAME: &'static str = "hir_canonical_variant_payload_owner_init";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
@@ -1,20 +1,21 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirCanonicalVariantPayloadScatterPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirCanonicalVariantPayloadScatterPass, label: "hir_canonical_variant_payload_scatter", shader: "parser/hir/canonical/variants/payload_scatter");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirCanonicalVariantPayloadScatterPass, label: "hir_canonical_variant_payload_scatter", shader: "parser/hir/canonical/variants/payload_scatter");
 
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput>
     for HirCanonicalVariantPayloadScatterPass
 {
     const NAME: &'static str = "hir_canonical_variant_payload_scatter";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
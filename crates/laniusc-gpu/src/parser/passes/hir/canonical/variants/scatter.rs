@@ -1,18 +1,19 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirCanonicalVariantScatterPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirCanonicalVariantScatterPass, label: "hir_canonical_variant_scatter", shader: "parser/hir/canonical/variants/scatter");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirCanonicalVariantScatterPass, label: "hir_canonical_variant_scatter", shader: "parser/hir/canonical/variants/scatter");
 
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirCanonicalVariantScatterPass {
     const NAME: &'static str = "hir_canonical_variant_scatter";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
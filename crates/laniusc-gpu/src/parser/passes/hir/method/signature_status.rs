@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -17,7 +17,7 @@ pub const HIR_METHOD_SIGNATURE_HAS_WHERE: u32 = 2;
 /// Method is declared by an inherent impl rather than a trait impl.
 pub const HIR_METHOD_SIGNATURE_INHERENT_IMPL: u32 = 4;
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     HirMethodSignatureStatusPass,
     label: "hir_method_signature_status",
     shader: "parser/hir/method/signature_status"
@@ -26,6 +26,7 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirMethodSignatureStatusPass {
     const NAME: &'static str = "hir_method_signature_status";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
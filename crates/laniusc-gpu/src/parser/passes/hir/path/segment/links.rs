@@ -1,18 +1,19 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirPathSegmentLinksPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirPathSegmentLinksPass, label: "hir_path_segment_links", shader: "parser/hir/path/segment/links");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirPathSegmentLinksPass, label: "hir_path_segment_links", shader: "parser/hir/path/segment/links");
 
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirPathSegmentLinksPass {
     const NAME: &'static str = "hir_path_segment_links";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
@@ -1,18 +1,19 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirPathSegmentRootPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirPathSegmentRootPass, label: "hir_path_segment_root", shader: "parser/hir/path/segment/root");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirPathSegmentRootPass, label: "hir_path_segment_root", shader: "parser/hir/path/segment/root");
 
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirPathSegmentRootPass {
     const NAME: &'static str = "hir_path_segment_root";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
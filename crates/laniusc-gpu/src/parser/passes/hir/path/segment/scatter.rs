@@ -1,18 +1,19 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
 pub struct HirPathSegmentScatterPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirPathSegmentScatterPass, label: "hir_path_segment_scatter", shader: "parser/hir/path/segment/scatter");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirPathSegmentScatterPass, label: "hir_path_segment_scatter", shader: "parser/hir/path/segment/scatter");
 
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirPathSegmentScatterPass {
     const NAME: &'static str = "hir_path_segment_scatter";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
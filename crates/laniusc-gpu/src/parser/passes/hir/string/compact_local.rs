@@ -1,16 +1,17 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 pub struct HirStringCompactLocalPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirStringCompactLocalPass, label: "hir_string_compact_local", shader: "parser/hir/string/compact_local");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirStringCompactLocalPass, label: "hir_string_compact_local", shader: "parser/hir/string/compact_local");
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirStringCompactLocalPass {
     const NAME: &'static str = "hir_string_compact_local";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
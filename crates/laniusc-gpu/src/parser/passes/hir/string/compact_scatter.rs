@@ -1,16 +1,17 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 pub struct HirStringCompactScatterPass {
     data: PassData,
 }
-crate::gpu::passes_core::impl_static_shader_pass!(HirStringCompactScatterPass, label: "hir_string_compact_scatter", shader: "parser/hir/string/compact_scatter");
+crate::gpu::passes_core::impl_checked_shader_pass!(HirStringCompactScatterPass, label: "hir_string_compact_scatter", shader: "parser/hir/string/compact_scatter");
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for HirStringCompactScatterPass {
     const NAME: &'static str = "hir_string_compact_scatter";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
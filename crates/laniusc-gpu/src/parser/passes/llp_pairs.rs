@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use encase::ShaderType;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -20,7 +20,7 @@ pub struct LLPPairsPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     LLPPairsPass,
     label: "llp_pairs",
     shader: "parser/llp_pairs"
@@ -29,10 +29,26 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for LLPPairsPass {
     const NAME: &'static str = "llp_pairs";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gParams",
+            "token_kinds",
+            "token_count",
+            "token_file_id",
+            "action_table",
+            "kind_remap",
+            "out_headers",
+        ]
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &["parser.out_headers"]
+    }
     fn data(&self) -> &PassData {
         &self.data
     }
@@ -52,6 +68,7 @@ impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for LLPPairsPass {
                 b.default_token_file_id.as_entire_binding(),
             ),
             ("action_table".into(), b.action_table.as_entire_binding()),
+            ("kind_remap".into(), b.kind_remap.as_entire_binding()),
             ("out_headers".into(), b.out_headers.as_entire_binding()),
             ("gParams".into(), b.params_llp.as_entire_binding()),
         ])
@@ -64,11 +81,11 @@ impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for LLPPairsPass {
         b: &ParserBuffers,
         dbg: &mut crate::parser::debug::DebugOutput,
     ) {
-        dbg.gpu.out_headers.set_from_copy(
+        dbg.capture(
+            "parser.out_headers",
             device,
             encoder,
             &b.out_headers,
-            "parser.dbg.out_headers",
             b.out_headers.byte_size,
         );
     }
@@ -4,7 +4,9 @@ use anyhow::Result;
 
 use crate::{
     gpu::{
-        passes_core::{InputElements, Pass, PassContext},
+        dry_run::{PassShapes, PipelinePlan},
+        passes_core::{BindingContract, InputElements, Pass, PassContext},
+        scan::{ScanFinalize, ping_pong_scan_steps},
         timer::GpuTimer,
     },
     parser::{buffers::ParserBuffers, debug::DebugOutput},
@@ -634,21 +636,49 @@ impl ParserPasses {
     }
 }
 
-/// Records the debug parser pipeline in pass order.
-pub fn record_all_passes(
-    mut ctx: PassContext<'_, ParserBuffers, DebugOutput>,
+/// Binding contracts of the LL(1), pack, bracket, and tree passes, checkable
+/// without a device. HIR passes do not declare theirs yet.
+pub fn binding_contracts() -> Vec<BindingContract> {
+    vec![
+        llp_pairs::LLPPairsPass::binding_contract(),
+        pack::varlen::PackVarlenPass::binding_contract(),
+        status::ParserStatusFromBracketsPass::binding_contract(),
+        source_file_token_end::SourceFileTokenEndPass::binding_contract(),
+        brackets::scan_inblock::BracketsScanInblockPass::binding_contract(),
+        brackets::scan_block_prefix::BracketsScanBlockPrefixPass::binding_contract(),
+        brackets::apply_prefix::BracketsApplyPrefixPass::binding_contract(),
+        brackets::clear_matches::BracketsClearMatchesPass::binding_contract(),
+        brackets::pse_pair::BracketsPsePairPass::binding_contract(),
+        tree::prefix::local::TreePrefixLocalPass::binding_contract(),
+        tree::prefix::scan_blocks::TreePrefixScanBlocksPass::binding_contract(),
+        tree::prefix::apply::TreePrefixApplyPass::binding_contract(),
+        tree::prefix::build_max_tree::TreePrefixMaxBuildPass::binding_contract(),
+        tree::parent::TreeParentPass::binding_contract(),
+        tree::spans::TreeSpansPass::binding_contract(),
+        tree::depth::init::TreeDepthInitPass::binding_contract(),
+        tree::depth::block_max::TreeDepthBlockMaxPass::binding_contract(),
+        tree::depth::schedule::TreeDepthSchedulePass::binding_contract(),
+        tree::prev::sibling::clear::TreePrevSiblingClearPass::binding_contract(),
+        tree::prev::sibling::scatter::TreePrevSiblingScatterPass::binding_contract(),
+    ]
+}
+
+/// Records the pair headers, packed stack-change and emit streams, and
+/// bracket validation: the prefix of the debug pipeline that needs no tree.
+pub fn record_pair_passes(
+    ctx: &mut PassContext<'_, ParserBuffers, DebugOutput>,
     p: &ParserPasses,
 ) -> Result<(), anyhow::Error> {
     use InputElements::Elements1D as E1D;
 
     let n_pairs = ctx.buffers.n_tokens.saturating_sub(1);
-    p.llp_pairs.record_pass(&mut ctx, E1D(n_pairs))?;
+    p.llp_pairs.record_pass(ctx, E1D(n_pairs))?;
     p.pack_offsets
         .record_scan(ctx.device, ctx.encoder, ctx.buffers)?;
     p.pack_offsets_status
         .record_pass(ctx.device, ctx.encoder, ctx.buffers)?;
     p.pack_varlen
-        .record_pass(&mut ctx, E1D(n_pairs.saturating_mul(256)))?;
+        .record_pass(ctx, E1D(n_pairs.saturating_mul(256)))?;
     parser_copy_buffer_to_buffer(
         ctx.encoder,
         &ctx.buffers.partial_parse_status,
@@ -658,12 +688,112 @@ pub fn record_all_passes(
         24,
     );
 
-    record_stack_effect_validation(&mut ctx, p, &mut None)?;
+    record_stack_effect_validation(ctx, p, &mut None)
+}
+
+/// Plans the dispatches [`record_pair_passes`] records for `n_tokens` token
+/// slots and `stack_changes` packed stack changes, without a device.
+///
+/// Multi-step scans and tree builds get one record per step, labelled
+/// `{pass}[step=s]` with the step's scan stride or first node.
+pub fn plan_pair_passes(
+    plan: &mut PipelinePlan,
+    shapes: &dyn PassShapes,
+    n_tokens: u32,
+    stack_changes: u32,
+    emit_stack_matches: bool,
+) -> Result<()> {
+    use InputElements::Elements1D as E1D;
+    use brackets::{
+        apply_prefix::BracketsApplyPrefixPass,
+        clear_matches::BracketsClearMatchesPass,
+        min_tree::BracketsMinTreePass,
+        pse_pair::BracketsPsePairPass,
+        scan_block_prefix::BracketsScanBlockPrefixPass,
+        scan_inblock::BracketsScanInblockPass,
+    };
+    use pack::offsets::{PackOffsetsScanPass, status::PackOffsetsStatusPass};
+
+    let mut pass = |contract: BindingContract, step: Option<u32>, input| {
+        let label = match step {
+            Some(step) => format!("{}[step={step}]", contract.label),
+            None => contract.label.to_string(),
+        };
+        plan.dispatch(shapes, &contract, label, input)
+    };
+
+    let n_pairs = n_tokens.saturating_sub(1);
+    pass(
+        llp_pairs::LLPPairsPass::binding_contract(),
+        None,
+        E1D(n_pairs),
+    )?;
+    for step in ping_pong_scan_steps(n_pairs, ScanFinalize::Always(n_pairs)) {
+        let contract = PackOffsetsScanPass::binding_contract();
+        pass(contract, Some(step.scan_step), E1D(n_pairs))?;
+    }
+    pass(
+        PackOffsetsStatusPass::binding_contract(),
+        None,
+        E1D(n_pairs.max(1)),
+    )?;
+    pass(
+        pack::varlen::PackVarlenPass::binding_contract(),
+        None,
+        E1D(n_pairs.saturating_mul(256)),
+    )?;
+
+    let n_sc = stack_changes.max(1);
+    let n_blocks = stack_changes.div_ceil(256).max(1);
+    pass(BracketsScanInblockPass::binding_contract(), None, E1D(n_sc))?;
+    for step in ping_pong_scan_steps(n_blocks, ScanFinalize::Always(n_blocks)) {
+        let contract = BracketsScanBlockPrefixPass::binding_contract();
+        pass(contract, Some(step.scan_step), E1D(n_blocks))?;
+    }
+    pass(BracketsApplyPrefixPass::binding_contract(), None, E1D(n_sc))?;
+    // The leaves, then each level of combines up to the root, as
+    // `make_tree_prefix_max_build_steps` builds them.
+    let leaf_base = n_blocks.checked_next_power_of_two().unwrap_or(1 << 31);
+    pass(
+        BracketsMinTreePass::binding_contract(),
+        Some(0),
+        E1D(leaf_base),
+    )?;
+    let mut start_node = leaf_base / 2;
+    while start_node > 0 {
+        let contract = BracketsMinTreePass::binding_contract();
+        pass(contract, Some(start_node), E1D(start_node))?;
+        start_node /= 2;
+    }
+    if emit_stack_matches {
+        pass(
+            BracketsClearMatchesPass::binding_contract(),
+            None,
+            E1D(n_sc),
+        )?;
+    }
+    pass(BracketsPsePairPass::binding_contract(), None, E1D(n_sc))?;
+    pass(
+        status::ParserStatusFromBracketsPass::binding_contract(),
+        None,
+        E1D(1),
+    )?;
+    Ok(())
+}
+
+/// Records the debug parser pipeline in pass order.
+pub fn record_all_passes(
+    mut ctx: PassContext<'_, ParserBuffers, DebugOutput>,
+    p: &ParserPasses,
+) -> Result<(), anyhow::Error> {
+    use InputElements::Elements1D as E1D;
+
+    record_pair_passes(&mut ctx, p)?;
 
     // Tree parent recovery: one independent thread per emitted production.
     let n_tree = ctx.buffers.tree_capacity;
     let n_tree_node_threads = ctx.buffers.tree_n_node_blocks.saturating_mul(256);
-    let n_tree_prefix_positions = ctx.buffers.tree_capacity.saturating_add(1);
+    let n_tree_prefix_positions = ctx.buffers.tree_capacity + 1;
     p.tree_prefix_01
         .record_pass(&mut ctx, E1D(n_tree_node_threads))?;
     p.tree_prefix_02
@@ -1346,8 +1476,21 @@ pub fn record_stack_effect_validation(
 ) -> Result<(), anyhow::Error> {
     use InputElements::Elements1D as E1D;
 
+    if ctx.buffers.stack_effect_cached {
+        // The driver wrote a cached result into the buffers these passes
+        // fill; clearing them would drop it.
+        p.status_from_brackets.record_pass(ctx, E1D(1))?;
+        stamp_stack_effect_timer(timer_ref, ctx.encoder, "parser.stack_effect.status");
+        return Ok(());
+    }
+
     let n_sc = ctx.buffers.total_sc.max(1);
+    // The block-prefix finalize step always runs, even over a zero-length
+    // stream, and overwrites these; clearing keeps a skipped or failed scan
+    // from reading back stale depths or a stale valid flag.
     parser_clear_buffer(ctx.encoder, &ctx.buffers.depths_out, 0, None);
+    parser_clear_buffer(ctx.encoder, &ctx.buffers.valid_out, 0, None);
+    parser_clear_buffer(ctx.encoder, &ctx.buffers.bracket_frontier, 0, None);
 
     p.b01.record_pass(ctx, E1D(n_sc))?;
     stamp_stack_effect_timer(timer_ref, ctx.encoder, "parser.stack_effect.histogram");
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use encase::ShaderType;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -18,13 +18,11 @@ pub struct PackParams {
     pub sc_capacity: u32,
     pub emit_capacity: u32,
 
-    // Offsets (u32 elements) inside tables_blob
+    // Offsets (u32 elements) inside tables_blob; see `ParseTablesGpuBlob`
     pub sc_superseq_off: u32,
-    pub sc_off_off: u32,
-    pub sc_len_off: u32,
     pub pp_superseq_off: u32,
-    pub pp_off_off: u32,
-    pub pp_len_off: u32,
+    pub cell_palette_off: u32,
+    pub cell_index_off: u32,
 }
 
 /// Pass that packs stack-change and production streams from pair headers.
@@ -32,7 +30,7 @@ pub struct PackVarlenPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     PackVarlenPass,
     label: "pack_varlen",
     shader: "parser/pack/varlen"
@@ -41,10 +39,35 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for PackVarlenPass {
     const NAME: &'static str = "pack_varlen";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
+
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gParams",
+            "token_kinds",
+            "token_count",
+            "token_file_id",
+            "sc_offsets",
+            "emit_offsets",
+            "tables_blob",
+            "kind_remap",
+            "out_sc",
+            "out_emit",
+            "out_emit_pos",
+        ]
+    }
+    fn expected_debug_keys() -> &'static [&'static str] {
+        &[
+            "parser.sc_offsets",
+            "parser.emit_offsets",
+            "parser.out_sc",
+            "parser.out_emit",
+        ]
+    }
     fn data(&self) -> &PassData {
         &self.data
     }
@@ -66,6 +89,7 @@ impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for PackVarlenPass {
             ("sc_offsets".into(), b.sc_offsets.as_entire_binding()),
             ("emit_offsets".into(), b.emit_offsets.as_entire_binding()),
             ("tables_blob".into(), b.tables_blob.as_entire_binding()),
+            ("kind_remap".into(), b.kind_remap.as_entire_binding()),
             ("out_sc".into(), b.out_sc.as_entire_binding()),
             ("out_emit".into(), b.out_emit.as_entire_binding()),
             ("out_emit_pos".into(), b.out_emit_pos.as_entire_binding()),
@@ -80,34 +104,32 @@ impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for PackVarlenPass {
         b: &ParserBuffers,
         dbg: &mut crate::parser::debug::DebugOutput,
     ) {
-        let g = &mut dbg.gpu;
-
-        g.sc_offsets.set_from_copy(
+        dbg.capture(
+            "parser.sc_offsets",
             device,
             encoder,
             &b.sc_offsets,
-            "parser.dbg.sc_offsets",
             b.sc_offsets.byte_size,
         );
-        g.emit_offsets.set_from_copy(
+        dbg.capture(
+            "parser.emit_offsets",
             device,
             encoder,
             &b.emit_offsets,
-            "parser.dbg.emit_offsets",
             b.emit_offsets.byte_size,
         );
-        g.out_sc.set_from_copy(
+        dbg.capture(
+            "parser.out_sc",
             device,
             encoder,
             &b.out_sc,
-            "parser.dbg.out_sc",
             b.out_sc.byte_size,
         );
-        g.out_emit.set_from_copy(
+        dbg.capture(
+            "parser.out_emit",
             device,
             encoder,
             &b.out_emit,
-            "parser.dbg.out_emit",
             b.out_emit.byte_size,
         );
     }
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use encase::ShaderType;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -19,7 +19,7 @@ pub struct SourceFileTokenEndPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     SourceFileTokenEndPass,
     label: "source_file_token_end",
     shader: "parser/source_file_token_end"
@@ -28,11 +28,21 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for SourceFileTokenEndPass {
     const NAME: &'static str = "source_file_token_end";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
 
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gSourceFile",
+            "token_count",
+            "token_file_id",
+            "source_file_token_end",
+        ]
+    }
+
     fn data(&self) -> &PassData {
         &self.data
     }
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -10,7 +10,7 @@ pub struct ParserStatusFromBracketsPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     ParserStatusFromBracketsPass,
     label: "parser_status_from_brackets",
     shader: "parser/status/from_brackets"
@@ -19,11 +19,21 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for ParserStatusFromBracketsPass {
     const NAME: &'static str = "parser_status_from_brackets";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::SingleGroup;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
 
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "partial_parse_status",
+            "bracket_depths",
+            "bracket_valid",
+            "ll1_status",
+        ]
+    }
+
     fn data(&self) -> &PassData {
         &self.data
     }
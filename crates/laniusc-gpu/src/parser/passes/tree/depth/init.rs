@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -10,7 +10,7 @@ pub struct TreeDepthInitPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     TreeDepthInitPass,
     label: "tree_depth_init",
     shader: "parser/tree/depth/init"
@@ -19,11 +19,22 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for TreeDepthInitPass {
     const NAME: &'static str = "tree_depth_init";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
 
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gTree",
+            "tree_count_status",
+            "parent",
+            "tree_depth_link_a",
+            "tree_depth_value_a",
+        ]
+    }
+
     fn data(&self) -> &PassData {
         &self.data
     }
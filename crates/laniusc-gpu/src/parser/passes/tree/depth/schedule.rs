@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -10,7 +10,7 @@ pub struct TreeDepthSchedulePass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     TreeDepthSchedulePass,
     label: "tree_depth_schedule",
     shader: "parser/tree/depth/schedule"
@@ -19,11 +19,21 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for TreeDepthSchedulePass {
     const NAME: &'static str = "tree_depth_schedule";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
 
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gTree",
+            "tree_active_dispatch_args",
+            "tree_depth_block_max",
+            "tree_pointer_jump_dispatch_args",
+        ]
+    }
+
     fn data(&self) -> &PassData {
         &self.data
     }
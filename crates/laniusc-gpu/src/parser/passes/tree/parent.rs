@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use encase::ShaderType;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -22,7 +22,7 @@ pub struct TreeParentPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     TreeParentPass,
     label: "tree_parent_parallel",
     shader: "parser/tree/parent_parallel"
@@ -31,11 +31,25 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for TreeParentPass {
     const NAME: &'static str = "tree_parent_parallel";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
 
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gTree",
+            "emit_stream",
+            "tree_count_status",
+            "tree_prefix",
+            "prefix_block_max",
+            "prefix_block_max_tree",
+            "node_kind",
+            "parent",
+        ]
+    }
+
     fn data(&self) -> &PassData {
         &self.data
     }
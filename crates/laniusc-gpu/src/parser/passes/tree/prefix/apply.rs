@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -10,7 +10,7 @@ pub struct TreePrefixApplyPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     TreePrefixApplyPass,
     label: "tree_prefix_03_apply",
     shader: "parser/tree/prefix/03_apply"
@@ -19,11 +19,24 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for TreePrefixApplyPass {
     const NAME: &'static str = "tree_prefix_03_apply";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
 
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gTree",
+            "tree_count_status",
+            "prefix_inblock",
+            "block_sum",
+            "block_prefix",
+            "tree_prefix",
+            "prefix_block_max",
+        ]
+    }
+
     fn data(&self) -> &PassData {
         &self.data
     }
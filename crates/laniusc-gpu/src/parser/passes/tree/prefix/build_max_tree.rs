@@ -4,7 +4,15 @@ use anyhow::Result;
 use encase::ShaderType;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, InputElements, Pass, PassData, bind_group, plan_workgroups},
+    gpu::passes_core::{
+        DispatchConvention,
+        DispatchDim,
+        InputElements,
+        Pass,
+        PassData,
+        bind_group,
+        plan_workgroups,
+    },
     parser::buffers::{ParserBuffers, TreePrefixMaxBuildStep},
 };
 
@@ -27,7 +35,7 @@ pub struct TreePrefixMaxBuildPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     TreePrefixMaxBuildPass,
     label: "tree_prefix_04_build_max_tree",
     shader: "parser/tree/prefix/04_build_max_tree"
@@ -93,11 +101,16 @@ impl TreePrefixMaxBuildPass {
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for TreePrefixMaxBuildPass {
     const NAME: &'static str = "tree_prefix_04_build_max_tree";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
 
+    fn expected_bindings() -> &'static [&'static str] {
+        &["gMaxTree", "prefix_block_max", "prefix_block_max_tree"]
+    }
+
     fn data(&self) -> &PassData {
         &self.data
     }
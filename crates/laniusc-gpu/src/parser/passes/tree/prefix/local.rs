@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use encase::ShaderType;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -23,7 +23,7 @@ pub struct TreePrefixLocalPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     TreePrefixLocalPass,
     label: "tree_prefix_01_local",
     shader: "parser/tree/prefix/01_local"
@@ -32,11 +32,24 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for TreePrefixLocalPass {
     const NAME: &'static str = "tree_prefix_01_local";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
 
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gTree",
+            "emit_stream",
+            "tree_count_status",
+            "prod_arity",
+            "node_kind",
+            "prefix_inblock",
+            "block_sum",
+        ]
+    }
+
     fn data(&self) -> &PassData {
         &self.data
     }
@@ -3,7 +3,15 @@ use std::collections::HashMap;
 use anyhow::Result;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, InputElements, Pass, PassData, bind_group, plan_workgroups},
+    gpu::passes_core::{
+        DispatchConvention,
+        DispatchDim,
+        InputElements,
+        Pass,
+        PassData,
+        bind_group,
+        plan_workgroups,
+    },
     parser::buffers::{ParserBuffers, TreePrefixScanStep},
 };
 
@@ -12,7 +20,7 @@ pub struct TreePrefixScanBlocksPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     TreePrefixScanBlocksPass,
     label: "tree_prefix_02_scan_blocks",
     shader: "parser/tree/prefix/02_scan_blocks"
@@ -90,11 +98,22 @@ impl TreePrefixScanBlocksPass {
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for TreePrefixScanBlocksPass {
     const NAME: &'static str = "tree_prefix_02_scan_blocks";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
 
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gTree",
+            "block_sum",
+            "prefix_in",
+            "prefix_out",
+            "block_prefix",
+        ]
+    }
+
     fn data(&self) -> &PassData {
         &self.data
     }
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -10,7 +10,7 @@ pub struct TreePrevSiblingScatterPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     TreePrevSiblingScatterPass,
     label: "tree_prev_sibling_scatter",
     shader: "parser/tree/prev/sibling/scatter"
@@ -19,11 +19,21 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for TreePrevSiblingScatterPass {
     const NAME: &'static str = "tree_prev_sibling_scatter";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
 
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gTreePrev",
+            "tree_count_status",
+            "next_sibling",
+            "prev_sibling",
+        ]
+    }
+
     fn data(&self) -> &PassData {
         &self.data
     }
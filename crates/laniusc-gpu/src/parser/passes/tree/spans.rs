@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use encase::ShaderType;
 
 use crate::{
-    gpu::passes_core::{DispatchDim, Pass, PassData},
+    gpu::passes_core::{DispatchConvention, DispatchDim, Pass, PassData},
     parser::buffers::ParserBuffers,
 };
 
@@ -22,7 +22,7 @@ pub struct TreeSpansPass {
     data: PassData,
 }
 
-crate::gpu::passes_core::impl_static_shader_pass!(
+crate::gpu::passes_core::impl_checked_shader_pass!(
     TreeSpansPass,
     label: "tree_spans",
     shader: "parser/tree/spans"
@@ -31,11 +31,28 @@ crate::gpu::passes_core::impl_static_shader_pass!(
 impl Pass<ParserBuffers, crate::parser::debug::DebugOutput> for TreeSpansPass {
     const NAME: &'static str = "tree_spans";
     const DIM: DispatchDim = DispatchDim::D1;
+    const DISPATCH: DispatchConvention = DispatchConvention::PerElementThreads;
 
     fn from_data(data: PassData) -> Self {
         Self { data }
     }
 
+    fn expected_bindings() -> &'static [&'static str] {
+        &[
+            "gTree",
+            "tree_count_status",
+            "prod_arity",
+            "node_kind",
+            "parent",
+            "tree_prefix",
+            "prefix_block_max",
+            "prefix_block_max_tree",
+            "first_child",
+            "next_sibling",
+            "subtree_end",
+        ]
+    }
+
     fn data(&self) -> &PassData {
         &self.data
     }
@@ -142,6 +142,7 @@ use super::{
         },
     },
 };
+use crate::gpu::decode::{decode_structs, decode_u32s};
 
 const HIR_VARIANT_PAYLOAD_SLOT_STRIDE: u32 = 4;
 const PROD_BOUND_TYPE_IDENT: u32 = 241;
@@ -162,6 +163,7 @@ pub struct ParserReadbacks {
     pub match_idx: wgpu::Buffer,
     pub depths: wgpu::Buffer,
     pub valid: wgpu::Buffer,
+    pub bracket_frontier: wgpu::Buffer,
     pub node_kind: wgpu::Buffer,
     pub parent: wgpu::Buffer,
     pub first_child: wgpu::Buffer,
@@ -272,6 +274,10 @@ impl ParserReadbacks {
         let match_idx = mk("rb.parser.match_for_index", sc_bytes);
         let depths = mk("rb.parser.depths_out", bufs.depths_out.byte_size as u64);
         let valid = mk("rb.parser.valid_out", bufs.valid_out.byte_size as u64);
+        let bracket_frontier = mk(
+            "rb.parser.bracket_frontier",
+            bufs.bracket_frontier.byte_size as u64,
+        );
         let node_kind = mk("rb.parser.node_kind", bufs.node_kind.byte_size as u64);
         let parent = mk("rb.parser.parent", bufs.parent.byte_size as u64);
         let first_child = mk("rb.parser.first_child", bufs.first_child.byte_size as u64);
@@ -597,6 +603,7 @@ impl ParserReadbacks {
             match_idx,
             depths,
             valid,
+            bracket_frontier,
             node_kind,
             parent,
             first_child,
@@ -1307,7 +1314,7 @@ impl ParserReadbacks {
             bufs.hir_struct_lit_field_next.byte_size as u64,
         );
 
-        // depths_out, valid_out
+        // depths_out, valid_out, bracket_frontier
         encoder.copy_buffer_to_buffer(
             &bufs.depths_out,
             0,
@@ -1322,6 +1329,13 @@ impl ParserReadbacks {
             0,
             bufs.valid_out.byte_size as u64,
         );
+        encoder.copy_buffer_to_buffer(
+            &bufs.bracket_frontier,
+            0,
+            &self.bracket_frontier,
+            0,
+            bufs.bracket_frontier.byte_size as u64,
+        );
     }
 }
 
@@ -1338,6 +1352,8 @@ pub struct DecodedParserReadbacks {
     pub final_depth: i32,
     pub min_depth: i32,
     pub valid: bool,
+    pub valid_up_to: u32,
+    pub first_unclosed_push: Option<u32>,
     pub node_kind: Vec<u32>,
     pub parent: Vec<u32>,
     pub first_child: Vec<u32>,
@@ -1449,6 +1465,7 @@ impl DecodedParserReadbacks {
         map("match_idx", &rb.match_idx);
         map("depths", &rb.depths);
         map("valid", &rb.valid);
+        map("bracket_frontier", &rb.bracket_frontier);
         map("node_kind", &rb.node_kind);
         map("parent", &rb.parent);
         map("first_child", &rb.first_child);
@@ -1575,11 +1592,7 @@ impl DecodedParserReadbacks {
         );
         map("hir_struct_lit_field_next", &rb.hir_struct_lit_field_next);
 
-        crate::gpu::passes_core::wait_for_map_progress(
-            device,
-            "parser.readback",
-            wgpu::PollType::wait_indefinitely(),
-        );
+        crate::gpu::passes_core::wait_for_map_progress(device, "parser.readback")?;
 
         let ll1_status = read_u32_array::<6>(&rb.ll1_status, "ll1_status")?;
         let ll1_emit_stream = Vec::new();
@@ -1609,6 +1622,13 @@ impl DecodedParserReadbacks {
         let [read_final_depth, read_min_depth] = read_i32_array::<2>(&rb.depths, "depths")?;
         let read_valid = read_u32_array::<1>(&rb.valid, "valid")?[0] != 0;
         let (final_depth, min_depth, valid) = (read_final_depth, read_min_depth, read_valid);
+        let [first_negative_inverted, unclosed_plus_one] =
+            read_u32_array::<2>(&rb.bracket_frontier, "bracket_frontier")?;
+        let valid_up_to = match first_negative_inverted {
+            0 => stream_len as u32,
+            inverted => !inverted,
+        };
+        let first_unclosed_push = unclosed_plus_one.checked_sub(1);
 
         let node_kind = read_u32_vec(&rb.node_kind, tree_len);
         let parent = read_u32_vec(&rb.parent, tree_len);
@@ -1724,6 +1744,8 @@ impl DecodedParserReadbacks {
             final_depth,
             min_depth,
             valid,
+            valid_up_to,
+            first_unclosed_push,
             node_kind,
             parent,
             first_child,
@@ -1830,6 +1852,205 @@ impl DecodedParserReadbacks {
     }
 }
 
+/// `(sc_offsets, emit_offsets)`, one entry per header.
+pub type HeaderOffsets = (Vec<u32>, Vec<u32>);
+
+/// Staging buffers for the headers-only parser path.
+pub struct HeaderReadbacks {
+    pub headers: wgpu::Buffer,
+    /// `(sc_offsets, emit_offsets)` when the offsets were scanned on the GPU.
+    pub offsets: Option<(wgpu::Buffer, wgpu::Buffer)>,
+}
+
+impl HeaderReadbacks {
+    /// Creates header staging, plus offset staging when `with_offsets` is set.
+    pub fn create(device: &wgpu::Device, bufs: &ParserBuffers, with_offsets: bool) -> Self {
+        let mk = |label: &str, size: u64| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            })
+        };
+        Self {
+            headers: mk("rb.parser.out_headers", bufs.out_headers.byte_size as u64),
+            offsets: with_offsets.then(|| {
+                (
+                    mk("rb.pack.sc_offsets", bufs.sc_offsets.byte_size as u64),
+                    mk("rb.pack.emit_offsets", bufs.emit_offsets.byte_size as u64),
+                )
+            }),
+        }
+    }
+
+    /// Records copies from the header and offset buffers into staging.
+    pub fn encode_copies(&self, encoder: &mut wgpu::CommandEncoder, bufs: &ParserBuffers) {
+        encoder.copy_buffer_to_buffer(
+            &bufs.out_headers,
+            0,
+            &self.headers,
+            0,
+            bufs.out_headers.byte_size as u64,
+        );
+        if let Some((sc, emit)) = &self.offsets {
+            encoder.copy_buffer_to_buffer(
+                &bufs.sc_offsets,
+                0,
+                sc,
+                0,
+                bufs.sc_offsets.byte_size as u64,
+            );
+            encoder.copy_buffer_to_buffer(
+                &bufs.emit_offsets,
+                0,
+                emit,
+                0,
+                bufs.emit_offsets.byte_size as u64,
+            );
+        }
+    }
+
+    /// Maps and decodes `n_pairs` headers and, when staged, their offsets.
+    pub fn map_and_decode(
+        self,
+        device: &wgpu::Device,
+        n_pairs: usize,
+    ) -> Result<(Vec<ActionHeader>, Option<HeaderOffsets>)> {
+        let map = |name: &str, b: &wgpu::Buffer| {
+            crate::gpu::passes_core::map_readback_for_progress(
+                &b.slice(..),
+                &format!("parser.headers.readback.{name}"),
+            );
+        };
+        map("headers", &self.headers);
+        if let Some((sc, emit)) = &self.offsets {
+            map("sc_offsets", sc);
+            map("emit_offsets", emit);
+        }
+        crate::gpu::passes_core::wait_for_map_progress(device, "parser.headers.readback")?;
+
+        let data = self.headers.slice(..).get_mapped_range();
+        let headers = decode_action_headers(&data, n_pairs);
+        drop(data);
+        self.headers.unmap();
+        let offsets = self
+            .offsets
+            .map(|(sc, emit)| (read_u32_vec(&sc, n_pairs), read_u32_vec(&emit, n_pairs)));
+        Ok((headers?, offsets))
+    }
+}
+
+/// Pair-analysis outputs read back by [`PairReadbacks`].
+#[derive(Clone)]
+pub struct PairOutputs {
+    pub headers: Vec<ActionHeader>,
+    pub sc_stream: Vec<u32>,
+    pub emit_stream: Vec<u32>,
+    pub match_for_index: Vec<u32>,
+}
+
+impl PairOutputs {
+    /// Keeps the first `pairs` pairs and their stream entries, as if the
+    /// window had ended after them; matches to a dropped stack change become
+    /// unmatched.
+    pub fn truncate_pairs(&mut self, pairs: usize) {
+        let pairs = pairs.min(self.headers.len());
+        let (sc_len, emit_len) = self.headers[..pairs]
+            .iter()
+            .fold((0, 0), |(sc, emit), header| {
+                (
+                    sc + (header.push_len + header.pop_count) as usize,
+                    emit + header.emit_len as usize,
+                )
+            });
+        self.headers.truncate(pairs);
+        self.sc_stream.truncate(sc_len);
+        self.emit_stream.truncate(emit_len);
+        self.match_for_index.truncate(sc_len);
+        for matched in &mut self.match_for_index {
+            if *matched as usize >= sc_len {
+                *matched = u32::MAX;
+            }
+        }
+    }
+}
+
+/// Staging buffers for the pair headers, packed streams, and bracket matches.
+pub struct PairReadbacks {
+    pub headers: wgpu::Buffer,
+    pub sc: wgpu::Buffer,
+    pub emit: wgpu::Buffer,
+    pub match_idx: wgpu::Buffer,
+}
+
+impl PairReadbacks {
+    /// Creates staging sized for the packed streams of `bufs`.
+    pub fn create(device: &wgpu::Device, bufs: &ParserBuffers) -> Self {
+        let mk = |label: &str, size: u64| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            })
+        };
+        let sc_bytes = (bufs.total_sc.max(1) * 4) as u64;
+        let emit_bytes = (bufs.total_emit.max(1) * 4) as u64;
+        Self {
+            headers: mk("rb.parser.out_headers", bufs.out_headers.byte_size as u64),
+            sc: mk("rb.parser.out_sc", sc_bytes),
+            emit: mk("rb.parser.out_emit", emit_bytes),
+            match_idx: mk("rb.parser.match_for_index", sc_bytes),
+        }
+    }
+
+    /// Records copies from the pair buffers into staging.
+    pub fn encode_copies(&self, encoder: &mut wgpu::CommandEncoder, bufs: &ParserBuffers) {
+        encoder.copy_buffer_to_buffer(&bufs.out_headers, 0, &self.headers, 0, self.headers.size());
+        encoder.copy_buffer_to_buffer(&bufs.out_sc, 0, &self.sc, 0, self.sc.size());
+        encoder.copy_buffer_to_buffer(&bufs.out_emit, 0, &self.emit, 0, self.emit.size());
+        encoder.copy_buffer_to_buffer(
+            &bufs.match_for_index,
+            0,
+            &self.match_idx,
+            0,
+            self.match_idx.size(),
+        );
+    }
+
+    /// Maps and decodes the pair outputs of `bufs`.
+    pub fn map_and_decode(
+        self,
+        device: &wgpu::Device,
+        bufs: &ParserBuffers,
+    ) -> Result<PairOutputs> {
+        for (name, buffer) in [
+            ("headers", &self.headers),
+            ("sc", &self.sc),
+            ("emit", &self.emit),
+            ("match_idx", &self.match_idx),
+        ] {
+            crate::gpu::passes_core::map_readback_for_progress(
+                &buffer.slice(..),
+                &format!("parser.pairs.readback.{name}"),
+            );
+        }
+        crate::gpu::passes_core::wait_for_map_progress(device, "parser.pairs.readback")?;
+
+        let data = self.headers.slice(..).get_mapped_range();
+        let headers = decode_action_headers(&data, bufs.n_tokens.saturating_sub(1) as usize);
+        drop(data);
+        self.headers.unmap();
+        Ok(PairOutputs {
+            headers: headers?,
+            sc_stream: read_u32_vec(&self.sc, bufs.total_sc as usize),
+            emit_stream: read_u32_vec(&self.emit, bufs.total_emit as usize),
+            match_for_index: read_u32_vec(&self.match_idx, bufs.total_sc as usize),
+        })
+    }
+}
+
 fn read_u32_array<const N: usize>(buffer: &wgpu::Buffer, label: &str) -> Result<[u32; N]> {
     let data = buffer.slice(..).get_mapped_range();
     let decoded = crate::gpu::readback::read_u32_words(&data, label);
@@ -1848,12 +2069,8 @@ fn read_i32_array<const N: usize>(buffer: &wgpu::Buffer, label: &str) -> Result<
 
 fn read_u32_vec(buffer: &wgpu::Buffer, len: usize) -> Vec<u32> {
     let data = buffer.slice(..).get_mapped_range();
-    let mut out = Vec::with_capacity(len);
-    for chunk in data.chunks_exact(4).take(len) {
-        out.push(u32::from_le_bytes(
-            chunk.try_into().expect("u32 chunk size mismatch"),
-        ));
-    }
+    let whole = data.len().min(len.saturating_mul(4));
+    let out = decode_u32s(&data[..whole]);
     drop(data);
     buffer.unmap();
     out
@@ -1889,27 +2106,9 @@ fn bounded_readback_len(label: &str, requested: usize, capacity: usize) -> Resul
     Ok(requested)
 }
 
-/// Validates enum variant ownership, ordinal, and payload rows.
+/// Decodes the first `count` action headers of an `out_headers` readback.
 fn decode_action_headers(bytes: &[u8], count: usize) -> Result<Vec<ActionHeader>> {
-    let stride = core::mem::size_of::<ActionHeader>();
-    if bytes.len() < stride * count {
-        return Err(anyhow!("out_headers readback too small"));
-    }
-    let mut out = Vec::with_capacity(count);
-    for i in 0..count {
-        let off = i * stride;
-        let push_len = u32::from_le_bytes(bytes[off + 0..off + 4].try_into().unwrap());
-        let emit_len = u32::from_le_bytes(bytes[off + 4..off + 8].try_into().unwrap());
-        let pop_tag = u32::from_le_bytes(bytes[off + 8..off + 12].try_into().unwrap());
-        let pop_count = u32::from_le_bytes(bytes[off + 12..off + 16].try_into().unwrap());
-        out.push(ActionHeader {
-            push_len,
-            emit_len,
-            pop_tag,
-            pop_count,
-        });
-    }
-    Ok(out)
+    decode_structs(bytes, count, "out_headers")
 }
 
 #[cfg(test)]
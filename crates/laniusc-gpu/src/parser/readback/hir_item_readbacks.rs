@@ -1491,11 +1491,7 @@ impl ParserHirItemReadbacks {
         );
         map("hir_struct_lit_field_next", &self.hir_struct_lit_field_next);
 
-        crate::gpu::passes_core::wait_for_map_progress(
-            device,
-            "parser.hir_item_readback",
-            wgpu::PollType::wait_indefinitely(),
-        );
+        crate::gpu::passes_core::wait_for_map_progress(device, "parser.hir_item_readback")?;
 
         let ll1_status = read_u32_array::<6>(&self.ll1_status, "ll1_status")?;
         let tree_len = active_tree_readback_len(
@@ -230,11 +230,7 @@ impl ParserHirFunctionReturnReadbacks {
         map("hir_item_name_token", &self.hir_item_name_token);
         map("hir_item_file_id", &self.hir_item_file_id);
 
-        crate::gpu::passes_core::wait_for_map_progress(
-            device,
-            "parser.hir_fn_return_readback",
-            wgpu::PollType::wait_indefinitely(),
-        );
+        crate::gpu::passes_core::wait_for_map_progress(device, "parser.hir_fn_return_readback")?;
 
         let ll1_status = read_u32_array::<6>(&self.ll1_status, "ll1_status")?;
         let tree_len = active_tree_readback_len(
@@ -0,0 +1,617 @@
+//! Syntax-error recovery over the tables' sync sets.
+//!
+//! The GPU parse stays the acceptance source. When it rejects the stream, a
+//! host LL(1) replay of the same tables finds the first error after the last
+//! recovery point and skips input up to the next token in the sync set
+//! ([`PrecomputedParseTables::sync_set`]) of a nonterminal open at the error,
+//! where the parse can resume from the state it was in when that nonterminal
+//! started. The pairs before the skip are kept, the pair passes re-run from
+//! the sync token as one more chunk of a chunked parse, and the kept segments
+//! stitch into the parse of the input with every skipped range removed.
+//!
+//! [`PrecomputedParseTables::sync_set`]: crate::parser::tables::PrecomputedParseTables::sync_set
+
+use std::ops::Range;
+
+use anyhow::{Result, bail};
+
+use crate::parser::{
+    driver::{ChunkStitcher, GpuParser, ParseResult},
+    tables::{INVALID_TABLE_ENTRY, Ll1ParseErrorCode, PrecomputedParseTables},
+};
+
+/// Errors [`parse_with_recovery`] reports before it stops recovering.
+pub const MAX_RECOVERED_ERRORS: usize = 100;
+
+/// Stream index of the first input token; the classified stream always
+/// starts with the sentinel.
+const FIRST: usize = 1;
+
+/// Predictions one [`Replay::accepts`] probe may make before giving up, a
+/// guard against tables whose nullable nonterminals predict each other.
+const PROBE_STEPS: usize = 1 << 16;
+
+/// A syntax error found by [`parse_with_recovery`] and the input it skipped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyntaxError {
+    /// Input index of the rejected token; the input length at end of input.
+    pub token: usize,
+    /// Rejection class observed while replaying the table.
+    pub code: Ll1ParseErrorCode,
+    /// Parser token kind at `token`, or `0` for end of input.
+    pub found: u32,
+    /// Parser token kinds accepted at `token`. Empty means unknown.
+    pub expected: Vec<u32>,
+    /// Input tokens dropped to recover. Reaches the input length when no
+    /// sync token was found and recovery stopped here.
+    pub skipped: Range<usize>,
+    /// Nonterminal whose sync set held the token the parse resumed at;
+    /// `None` when it resumed without one or did not resume.
+    pub sync_nonterminal: Option<u32>,
+}
+
+/// A run of input tokens parsed without a skip.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SegmentParse {
+    /// Input indices of the segment's tokens.
+    pub tokens: Range<usize>,
+    /// The segment's pairs in [`RecoveredParse::result`], including the one
+    /// joining it to the previous segment's last token.
+    pub pairs: Range<usize>,
+}
+
+/// Result of [`parse_with_recovery`].
+pub struct RecoveredParse {
+    /// Kept runs of input, in order.
+    pub segments: Vec<SegmentParse>,
+    /// Every error found, in input order, each reported once.
+    pub errors: Vec<SyntaxError>,
+    /// Pair outputs of the segments stitched as [`GpuParser::parse_chunked`]
+    /// returns them: those of a parse of the kept tokens alone.
+    pub result: ParseResult,
+}
+
+impl RecoveredParse {
+    /// Whether the kept tokens parse; false when recovery found no sync
+    /// token or stopped at the error bound.
+    pub fn recovered(&self) -> bool {
+        self.result.brackets.valid
+    }
+}
+
+/// Parses unframed raw lexer token kinds, recovering from up to
+/// [`MAX_RECOVERED_ERRORS`] syntax errors.
+///
+/// The input is framed and classified as by [`GpuParser::parse_tokens`];
+/// see [`parse_with_recovery_bounded`].
+pub async fn parse_with_recovery(
+    kinds: &[u32],
+    tables: &PrecomputedParseTables,
+    parser: &GpuParser,
+) -> Result<RecoveredParse> {
+    parse_with_recovery_bounded(kinds, tables, parser, MAX_RECOVERED_ERRORS).await
+}
+
+/// Like [`parse_with_recovery`], stopping after `max_errors` errors.
+///
+/// Each round runs the pair passes from the last recovery point to the end
+/// of input. When the stitched parse rejects, the host replay locates the
+/// next error, the round's pairs are cut where the skip starts, and the next
+/// round starts at the sync token, its first pair joining the last kept
+/// token to it. Once the bound is reached the last round is kept whole, so
+/// the result reports the remaining input as rejected.
+pub async fn parse_with_recovery_bounded(
+    kinds: &[u32],
+    tables: &PrecomputedParseTables,
+    parser: &GpuParser,
+    max_errors: usize,
+) -> Result<RecoveredParse> {
+    let framed = tables.wrap_input(kinds)?;
+    let stream = parser.debug_semantic_token_kinds_for_raw_token_kinds(&framed, tables)?;
+    if stream.len() != kinds.len() + 2 {
+        bail!(
+            "classified {} parser tokens for {} input tokens",
+            stream.len().saturating_sub(2),
+            kinds.len()
+        );
+    }
+
+    let mut replay = Replay::new(tables);
+    let mut stitcher = ChunkStitcher::default();
+    let mut segments = Vec::new();
+    let mut errors = Vec::new();
+    // Stream index the next round starts at, and the last kept kind before it.
+    let mut seg_start = 0;
+    let mut prev_kind = None;
+    loop {
+        let window = prev_kind
+            .into_iter()
+            .chain(stream[seg_start..].iter().copied())
+            .collect::<Vec<_>>();
+        let (mut outputs, timings) = parser.parse_pair_window(&window, tables, None)?;
+        if let Some(timings) = timings {
+            stitcher.add_timings(&timings);
+        }
+        let mut trial = stitcher.clone();
+        trial.push(outputs.clone())?;
+        if trial.accepted() || errors.len() >= max_errors {
+            push_segment(
+                &mut segments,
+                seg_start..stream.len() - 1,
+                stitcher.pairs(),
+                &trial,
+            );
+            stitcher = trial;
+            break;
+        }
+
+        let Some(recovery) = replay.next_error(&stream, seg_start.max(FIRST)) else {
+            bail!("the GPU parse rejected a stream the LL(1) table accepts");
+        };
+        let skip = recovery.skipped.clone();
+        errors.push(recovery.error);
+        // Pairs of the window whose second token precedes the skip.
+        outputs.truncate_pairs(skip.start - seg_start - usize::from(prev_kind.is_none()));
+        let pairs = stitcher.pairs();
+        stitcher.push(outputs)?;
+        push_segment(&mut segments, seg_start..skip.start, pairs, &stitcher);
+        if !recovery.resumed {
+            break;
+        }
+        if skip.start > seg_start {
+            prev_kind = Some(stream[skip.start - 1]);
+        }
+        seg_start = skip.end;
+    }
+
+    Ok(RecoveredParse {
+        segments,
+        errors,
+        result: stitcher.finish(),
+    })
+}
+
+/// Records the kept stream range `tokens` as a segment whose pairs run from
+/// `first_pair` to the end of `stitcher`.
+fn push_segment(
+    segments: &mut Vec<SegmentParse>,
+    tokens: Range<usize>,
+    first_pair: usize,
+    stitcher: &ChunkStitcher,
+) {
+    let tokens = input_range(tokens);
+    if !tokens.is_empty() {
+        segments.push(SegmentParse {
+            tokens,
+            pairs: first_pair..stitcher.pairs(),
+        });
+    }
+}
+
+/// Input indices of a stream range, dropping the sentinels.
+fn input_range(stream: Range<usize>) -> Range<usize> {
+    stream.start.max(FIRST) - FIRST..stream.end.max(FIRST) - FIRST
+}
+
+/// Parser token kind at stream index `pos`, `0` at and past the end sentinel.
+fn kind_at(tokens: &[u32], pos: usize) -> u32 {
+    if pos + 1 < tokens.len() {
+        tokens[pos]
+    } else {
+        0
+    }
+}
+
+/// A nonterminal whose right-hand side is being derived.
+#[derive(Clone, Copy)]
+struct Open {
+    nt: u32,
+    /// Stream index of its first token.
+    start: usize,
+    /// Stack length below its right-hand side.
+    below: usize,
+}
+
+/// Stack just before the first prediction at `pos`. It depends only on the
+/// tokens before `pos`, so the parse can resume from it at any later token.
+struct Snapshot {
+    pos: usize,
+    stack: Vec<u32>,
+}
+
+/// The first error after the replay's position, as the table sees it.
+struct Failure {
+    pos: usize,
+    code: Ll1ParseErrorCode,
+    found: u32,
+    expected: Vec<u32>,
+    /// Nonterminal that had no prediction for `found`.
+    failing: Option<u32>,
+}
+
+/// A located error and the stream range skipped past it.
+struct Recovery {
+    error: SyntaxError,
+    skipped: Range<usize>,
+    /// Whether the replay resumed after `skipped`; when not, `skipped` runs
+    /// to the end of input.
+    resumed: bool,
+}
+
+/// Host LL(1) replay over a classified stream that can resume past errors.
+///
+/// Like [`PrecomputedParseTables::diagnose_ll1_rejection`], but it tracks the
+/// open nonterminals and their starting states so that [`Self::next_error`]
+/// can skip to a sync token and carry on.
+struct Replay<'t> {
+    tables: &'t PrecomputedParseTables,
+    pos: usize,
+    /// Grammar symbols still to derive: grid kinds for terminals,
+    /// `n_kinds + nt` for nonterminals.
+    stack: Vec<u32>,
+    /// Open nonterminals, outermost first. One whose last symbol is a
+    /// nonterminal closes when that symbol is predicted, so right-recursive
+    /// lists keep this as short as the stack.
+    open: Vec<Open>,
+    /// Snapshots at the starts of the open nonterminals and at `pos`.
+    snapshots: Vec<Snapshot>,
+}
+
+impl<'t> Replay<'t> {
+    fn new(tables: &'t PrecomputedParseTables) -> Self {
+        Self {
+            tables,
+            pos: FIRST,
+            stack: vec![tables.n_kinds + tables.start_nonterminal],
+            open: Vec::new(),
+            snapshots: Vec::new(),
+        }
+    }
+
+    /// Replays to the next error at or after the replay's position and skips
+    /// past it, resuming at the nearest sync token.
+    ///
+    /// A skip starts at the error or at the start of a nonterminal open
+    /// there, no earlier than `floor`, and ends at a later token in that
+    /// nonterminal's sync set (or at end of input) that the state before the
+    /// skip accepts. Nearer sync tokens win, then inner nonterminals. Returns
+    /// `None` when the rest of the stream parses.
+    fn next_error(&mut self, tokens: &[u32], floor: usize) -> Option<Recovery> {
+        let failure = self.run(tokens)?;
+        let end = tokens.len() - 1;
+        let at_error = self.snapshot_at(failure.pos);
+        let mut candidates = vec![(failure.pos, at_error)];
+        for open in self.open.iter().rev() {
+            if open.start < floor {
+                break;
+            }
+            if candidates
+                .last()
+                .is_some_and(|&(from, _)| open.start < from)
+            {
+                candidates.push((open.start, self.snapshot_at(open.start)));
+            }
+        }
+
+        let mut resume = None;
+        'search: for to in failure.pos..=end {
+            let found = kind_at(tokens, to);
+            for (from, stack) in &candidates {
+                let from = *from;
+                if from >= to {
+                    continue;
+                }
+                let nts = self.open.iter().rev().filter(|open| open.start == from);
+                let mut nts = nts
+                    .map(|open| open.nt)
+                    .chain(failure.failing.filter(|_| from == failure.pos));
+                let sync = nts
+                    .clone()
+                    .find(|&nt| self.tables.sync_set(nt).contains(&found));
+                // With no nonterminal to sync on, any token the state accepts will do.
+                let no_nts = nts.next().is_none();
+                if (sync.is_some() || no_nts || to == end) && self.accepts(stack, found) {
+                    resume = Some((from, to, stack.clone(), sync));
+                    break 'search;
+                }
+            }
+        }
+
+        let input_end = end - FIRST;
+        let mut error = SyntaxError {
+            token: failure.pos - FIRST,
+            code: failure.code,
+            found: failure.found,
+            expected: failure.expected,
+            skipped: failure.pos - FIRST..input_end,
+            sync_nonterminal: None,
+        };
+        let Some((from, to, stack, sync)) = resume else {
+            return Some(Recovery {
+                error,
+                skipped: failure.pos..end,
+                resumed: false,
+            });
+        };
+        error.skipped = input_range(from..to);
+        error.sync_nonterminal = sync;
+        // Every open nonterminal started before `to`, below the next floor.
+        self.pos = to;
+        self.stack = stack;
+        self.open.clear();
+        self.snapshots.clear();
+        Some(Recovery {
+            error,
+            skipped: from..to,
+            resumed: true,
+        })
+    }
+
+    /// Runs the table from the replay's position to the first error, leaving
+    /// the failing symbol on the stack.
+    fn run(&mut self, tokens: &[u32]) -> Option<Failure> {
+        let t = self.tables;
+        let end = tokens.len() - 1;
+        while let Some(sym) = self.stack.pop() {
+            let found = kind_at(tokens, self.pos);
+            let lookahead = t.grid_kind(found);
+            let below = self.stack.len();
+
+            if sym < t.n_kinds {
+                self.close_above(below, false);
+                if sym == lookahead {
+                    self.pos += 1;
+                    continue;
+                }
+                self.stack.push(sym);
+                return Some(self.failure(
+                    Ll1ParseErrorCode::TerminalMismatch,
+                    found,
+                    vec![t.lexer_kind(sym)],
+                    None,
+                ));
+            }
+
+            self.close_above(below, true);
+            if self.snapshots.last().is_none_or(|s| s.pos != self.pos) {
+                self.take_snapshot(sym);
+            }
+            let nt = sym - t.n_kinds;
+            let prod = t.predict(nt, lookahead);
+            if prod == INVALID_TABLE_ENTRY || prod >= t.n_productions {
+                self.stack.push(sym);
+                return Some(self.failure(
+                    Ll1ParseErrorCode::NoPrediction,
+                    found,
+                    t.expected_lookaheads_for_nonterminal(nt),
+                    Some(nt),
+                ));
+            }
+            self.open.push(Open {
+                nt,
+                start: self.pos,
+                below,
+            });
+            let off = t.prod_rhs_off[prod as usize] as usize;
+            let len = t.prod_rhs_len[prod as usize] as usize;
+            self.stack
+                .extend(t.prod_rhs[off..off + len].iter().rev().copied());
+        }
+
+        (self.pos != end).then(|| {
+            self.failure(
+                Ll1ParseErrorCode::TrailingInput,
+                tokens[self.pos],
+                vec![0],
+                None,
+            )
+        })
+    }
+
+    fn failure(
+        &self,
+        code: Ll1ParseErrorCode,
+        found: u32,
+        expected: Vec<u32>,
+        failing: Option<u32>,
+    ) -> Failure {
+        Failure {
+            pos: self.pos,
+            code,
+            found,
+            expected,
+            failing,
+        }
+    }
+
+    /// Closes the nonterminals whose right-hand sides lie above stack length
+    /// `below`, and with `tail`, the one whose last symbol was just popped.
+    fn close_above(&mut self, below: usize, tail: bool) {
+        while self
+            .open
+            .last()
+            .is_some_and(|open| open.below > below || (tail && open.below == below))
+        {
+            self.open.pop();
+        }
+    }
+
+    /// Snapshots the stack with `sym` still on top, dropping the snapshots no
+    /// open nonterminal starts at.
+    fn take_snapshot(&mut self, sym: u32) {
+        let mut starts = self.open.iter().map(|open| open.start).peekable();
+        self.snapshots.retain(|snapshot| {
+            while starts.next_if(|&start| start < snapshot.pos).is_some() {}
+            starts.peek() == Some(&snapshot.pos)
+        });
+        let mut stack = self.stack.clone();
+        stack.push(sym);
+        self.snapshots.push(Snapshot {
+            pos: self.pos,
+            stack,
+        });
+    }
+
+    /// Stack before the first prediction at `pos`: its snapshot, or the
+    /// current stack when nothing was predicted there.
+    fn snapshot_at(&self, pos: usize) -> Vec<u32> {
+        self.snapshots
+            .iter()
+            .rev()
+            .find(|snapshot| snapshot.pos == pos)
+            .map_or_else(|| self.stack.clone(), |snapshot| snapshot.stack.clone())
+    }
+
+    /// Whether `stack` can take `found` (`0` for end of input) as its next token.
+    fn accepts(&self, stack: &[u32], found: u32) -> bool {
+        let t = self.tables;
+        let lookahead = t.grid_kind(found);
+        let mut stack = stack.to_vec();
+        for _ in 0..PROBE_STEPS {
+            let Some(sym) = stack.pop() else {
+                return found == 0;
+            };
+            if sym < t.n_kinds {
+                return sym == lookahead;
+            }
+            let prod = t.predict(sym - t.n_kinds, lookahead);
+            if prod == INVALID_TABLE_ENTRY || prod >= t.n_productions {
+                return false;
+            }
+            let off = t.prod_rhs_off[prod as usize] as usize;
+            let len = t.prod_rhs_len[prod as usize] as usize;
+            stack.extend(t.prod_rhs[off..off + len].iter().rev().copied());
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tables::tokens::TokenKind;
+
+    fn checked_in_tables() -> PrecomputedParseTables {
+        PrecomputedParseTables::load_bin_bytes(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tables/parse_tables.bin"
+        )))
+        .unwrap()
+    }
+
+    fn framed(kinds: &[TokenKind]) -> Vec<u32> {
+        let mut stream = vec![0];
+        stream.extend(kinds.iter().map(|&kind| kind as u32));
+        stream.push(0);
+        stream
+    }
+
+    /// Classified `fn main() { ... }` with `n` let statements, and the same
+    /// function with a token inserted into each statement in `corrupted`: a
+    /// doubled `=` in even ones, a stray literal before the `let` in odd
+    /// ones. Also returns the input indices of the inserted tokens.
+    fn program(n: usize, corrupted: &[usize]) -> (Vec<u32>, Vec<u32>, Vec<usize>) {
+        use TokenKind::*;
+        let mut clean = vec![Fn, Ident, ParamLParen, ParamRParen, FnBlockLBrace];
+        let mut broken = clean.clone();
+        let mut inserted = Vec::new();
+        for i in 0..n {
+            let stmt = [Let, LetIdent, LetAssign, Int, LetSemicolon];
+            clean.extend(stmt);
+            if !corrupted.contains(&i) {
+                broken.extend(stmt);
+            } else if i % 2 == 0 {
+                broken.extend(&stmt[..3]);
+                inserted.push(broken.len());
+                broken.extend([Assign, Int, LetSemicolon]);
+            } else {
+                inserted.push(broken.len());
+                broken.push(Int);
+                broken.extend(stmt);
+            }
+        }
+        clean.push(FnBlockRBrace);
+        broken.push(FnBlockRBrace);
+        (framed(&clean), framed(&broken), inserted)
+    }
+
+    /// Host half of [`parse_with_recovery`]: the kept stream and the errors.
+    fn recover_on_host(
+        tables: &PrecomputedParseTables,
+        stream: &[u32],
+    ) -> (Vec<u32>, Vec<SyntaxError>) {
+        let mut replay = Replay::new(tables);
+        let (mut kept, mut errors, mut seg_start) = (Vec::new(), Vec::new(), 0);
+        while let Some(recovery) = replay.next_error(stream, seg_start.max(FIRST)) {
+            kept.extend(&stream[seg_start..recovery.skipped.start]);
+            seg_start = recovery.skipped.end;
+            errors.push(recovery.error);
+            if !recovery.resumed {
+                return (kept, errors);
+            }
+        }
+        kept.extend(&stream[seg_start..]);
+        (kept, errors)
+    }
+
+    #[test]
+    fn checked_in_tables_carry_sync_sets() {
+        use TokenKind::*;
+        let tables = checked_in_tables();
+        assert_eq!(tables.sync_sets.len(), tables.n_nonterminals as usize);
+        let stmt = (0..tables.n_nonterminals)
+            .find(|&nt| tables.nonterminal_name(nt) == "stmt")
+            .unwrap();
+        let sync = tables.sync_set(stmt);
+        // FIRST(stmt) and FOLLOW(stmt), without end of input.
+        for kind in [Let, Return, Int, FnBlockRBrace] {
+            assert!(sync.contains(&(kind as u32)), "{kind:?}");
+        }
+        assert!(!sync.contains(&0));
+    }
+
+    #[test]
+    fn every_error_is_reported_once_and_the_rest_parses_clean() {
+        let tables = checked_in_tables();
+        let fifty = (0..50).collect::<Vec<_>>();
+        for (n, corrupted) in [(4, &[2][..]), (6, &[0, 3, 5]), (60, &fifty)] {
+            let (clean, broken, inserted) = program(n, corrupted);
+            assert!(tables.test_cpu_ll1_production_stream(&clean).is_ok());
+            assert!(tables.test_cpu_ll1_production_stream(&broken).is_err());
+
+            let (kept, errors) = recover_on_host(&tables, &broken);
+            assert_eq!(kept, clean, "{n} statements");
+            let skipped = errors.iter().map(|e| e.skipped.clone()).collect::<Vec<_>>();
+            let expected = inserted.iter().map(|&i| i..i + 1).collect::<Vec<_>>();
+            assert_eq!(skipped, expected, "{n} statements");
+            for error in &errors {
+                assert_eq!(error.code, Ll1ParseErrorCode::NoPrediction);
+                assert!(error.sync_nonterminal.is_some(), "{error:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn input_after_the_last_item_is_skipped_to_the_end() {
+        use TokenKind::*;
+        let tables = checked_in_tables();
+        let clean = [
+            Fn,
+            Ident,
+            ParamLParen,
+            ParamRParen,
+            FnBlockLBrace,
+            FnBlockRBrace,
+        ];
+        let mut broken = clean.to_vec();
+        broken.extend([Int, Int]);
+
+        let (kept, errors) = recover_on_host(&tables, &framed(&broken));
+        assert_eq!(kept, framed(&clean));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].token, clean.len());
+        assert_eq!(errors[0].found, Int as u32);
+        assert_eq!(errors[0].skipped, clean.len()..broken.len());
+        assert_eq!(errors[0].sync_nonterminal, None);
+    }
+}
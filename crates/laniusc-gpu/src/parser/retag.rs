@@ -0,0 +1,131 @@
+//! Host mirror of the primary-adjacency rule that retags raw `(` and `[`.
+//!
+//! The parser token shaders read the kind of the previous *kept* token from
+//! `token_words`, so whitespace and comments between a primary and its `(` or
+//! `[` never change the retag: `foo /* c */ (x)` is a call just like `foo(x)`.
+//! This module applies the same rule to a kept stream on the host for tests.
+//!
+//! Only the adjacency rule is mirrored. The contextual kinds the shaders pick
+//! first (parameter, pattern, and enum-payload parens, type-array brackets)
+//! and the struct-literal `}` primary are not modeled.
+
+use crate::lexer::tables::tokens::TokenKind;
+
+/// Whether `kind` ends a primary expression, as `semantic_token_ends_primary`
+/// in the parser token shaders.
+pub fn ends_primary(kind: TokenKind) -> bool {
+    use TokenKind::*;
+    matches!(
+        kind,
+        Ident
+            | Int
+            | RParen
+            | RBracket
+            | GroupRParen
+            | CallRParen
+            | ArrayRBracket
+            | IndexRBracket
+            | TypeArrayRBracket
+            | PatternRParen
+            | EnumPayloadRParen
+            | String
+            | Float
+            | Char
+            | True
+            | False
+            | SelfValue
+    )
+}
+
+/// `CallLParen` after a primary, otherwise `GroupLParen`.
+pub fn open_paren_kind(prev_kept: Option<TokenKind>) -> TokenKind {
+    if prev_kept.is_some_and(ends_primary) {
+        TokenKind::CallLParen
+    } else {
+        TokenKind::GroupLParen
+    }
+}
+
+/// `IndexLBracket` after a primary, otherwise `ArrayLBracket`.
+pub fn open_bracket_kind(prev_kept: Option<TokenKind>) -> TokenKind {
+    if prev_kept.is_some_and(ends_primary) {
+        TokenKind::IndexLBracket
+    } else {
+        TokenKind::ArrayLBracket
+    }
+}
+
+/// Retags every raw `(` and `[` in the kept stream `kept` by the kept token
+/// before it; other kinds pass through unchanged.
+pub fn retag_open_delimiters(kept: &[TokenKind]) -> Vec<TokenKind> {
+    let mut prev = None;
+    kept.iter()
+        .map(|&kind| {
+            let retagged = match kind {
+                TokenKind::LParen => open_paren_kind(prev),
+                TokenKind::LBracket => open_bracket_kind(prev),
+                _ => kind,
+            };
+            prev = Some(kind);
+            retagged
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::test_cpu::lex_on_test_cpu;
+
+    /// Kept tokens of `source` as `(kind, raw_kind)`, then the retagged kinds.
+    fn retagged(source: &str) -> (Vec<(TokenKind, TokenKind)>, Vec<TokenKind>) {
+        let tokens = lex_on_test_cpu(source).expect("test CPU oracle");
+        let lexed: Vec<_> = tokens.iter().map(|t| (t.kind, t.raw_kind)).collect();
+        let kinds: Vec<_> = tokens.iter().map(|t| t.kind).collect();
+        (lexed, retag_open_delimiters(&kinds))
+    }
+
+    #[test]
+    fn call_paren_retag_is_transparent_to_trivia() {
+        use TokenKind::*;
+
+        let call = [Ident, CallLParen, Ident, RParen];
+        for source in [
+            "foo(x)",
+            "foo (x)",
+            "foo/*c*/(x)",
+            "foo // c\n(x)",
+            "foo\n\n(x)",
+        ] {
+            let (lexed, kinds) = retagged(source);
+            assert_eq!(kinds, call, "{source:?}");
+            // The lexer leaves the paren raw; only the parser retags it.
+            assert_eq!(lexed[1], (LParen, LParen), "{source:?}");
+        }
+
+        let (lexed, kinds) = retagged("(foo)(x)");
+        assert_eq!(
+            kinds,
+            [GroupLParen, Ident, RParen, CallLParen, Ident, RParen]
+        );
+        assert_eq!(lexed[3], (LParen, LParen));
+
+        let (_, kinds) = retagged("+ /* c */ (x)");
+        assert_eq!(kinds, [Plus, GroupLParen, Ident, RParen]);
+    }
+
+    #[test]
+    fn index_bracket_retag_is_transparent_to_trivia() {
+        use TokenKind::*;
+
+        for source in ["arr[0]", "arr /*x*/ [0]", "arr // x\n[0]"] {
+            let (lexed, kinds) = retagged(source);
+            assert_eq!(kinds, [Ident, IndexLBracket, Int, RBracket], "{source:?}");
+            assert_eq!(lexed[1], (LBracket, LBracket), "{source:?}");
+        }
+        let (_, kinds) = retagged("= /*x*/ [0]");
+        assert_eq!(kinds, [Assign, ArrayLBracket, Int, RBracket]);
+        assert_eq!(open_bracket_kind(None), ArrayLBracket);
+        assert_eq!(open_paren_kind(None), GroupLParen);
+    }
+}
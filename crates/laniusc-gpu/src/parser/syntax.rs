@@ -2167,8 +2167,8 @@ fn token_bytes(tokens: &[Token]) -> Vec<u8> {
     let mut out = Vec::with_capacity(tokens.len().max(1) * 12);
     for token in tokens {
         out.extend_from_slice(&(token.kind as u32).to_le_bytes());
-        out.extend_from_slice(&(token.start as u32).to_le_bytes());
-        out.extend_from_slice(&(token.len as u32).to_le_bytes());
+        out.extend_from_slice(&(token.start() as u32).to_le_bytes());
+        out.extend_from_slice(&(token.len() as u32).to_le_bytes());
     }
     if out.is_empty() {
         out.resize(12, 0);
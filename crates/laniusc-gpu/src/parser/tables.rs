@@ -0,0 +1,1924 @@
+//! Offline precomputed tables for the LLP/parser pipeline.
+//!
+//! Tables contain stack-change supersequences, partial-parse streams,
+//! production arity, and full LL(1) prediction/RHS data. Files are compact
+//! little-endian sections in the shared table container of
+//! [`crate::tables::format`]; the loader also reads the older `LXPRSE` layouts.
+
+use std::{fs, io::Write, path::Path};
+
+mod gpu_blob;
+mod layout;
+mod sentinel;
+mod validate;
+
+pub use gpu_blob::{INLINE_SEQUENCE, MAX_PALETTE_ENTRIES, PALETTE_ENTRY_WORDS, ParseTablesGpuBlob};
+pub use layout::{LayoutReport, MAX_INLINE_LEN, TableLayout};
+pub use sentinel::{DEFAULT_SENTINEL_KIND, SentinelError};
+pub(crate) use validate::summarize_violations;
+pub use validate::{MAX_PROD_ARITY, TableInvariantViolation};
+
+use crate::{
+    lexer::tables::tokens::TokenKind,
+    parser::{buffers::ActionHeader, kindmap::KindMap},
+    tables::{
+        FormatError,
+        FormatVersion,
+        format::{self, Container, Section, Tag},
+    },
+};
+
+// ---------- MVP (already in tree): action headers for bracket sanity ----------
+
+/// Returns a zeroed action table of size `(n_kinds * n_kinds) * sizeof(ActionHeader)`.
+pub fn build_dummy_action_table(n_kinds: u32) -> Vec<u8> {
+    let n = (n_kinds as usize) * (n_kinds as usize);
+    vec![0u8; n * std::mem::size_of::<ActionHeader>()]
+}
+
+/// Builds a small delimiter-only action table for parser kernel smoke tests.
+pub fn build_bracket_action_table(n_kinds: u32) -> Vec<u8> {
+    let mut bytes = build_dummy_action_table(n_kinds);
+    let sz = std::mem::size_of::<ActionHeader>();
+
+    // Tags (arbitrary small integers for the MVP)
+    const TAG_GROUP_PAREN: u32 = 1;
+    const TAG_CALL_PAREN: u32 = 2;
+    const TAG_ARRAY_BRACK: u32 = 3;
+    const TAG_INDEX_BRACK: u32 = 4;
+    const TAG_PARAM_PAREN: u32 = 5;
+    const TAG_TYPE_ARRAY_BRACK: u32 = 6;
+
+    // Helper: write header into (prev, this) cell.
+    let mut set = |prev: u32, this: u32, h: ActionHeader| {
+        let idx = (prev as usize) * (n_kinds as usize) + (this as usize);
+        let off = idx * sz;
+        bytes[off..off + 4].copy_from_slice(&h.push_len.to_le_bytes());
+        bytes[off + 4..off + 8].copy_from_slice(&h.emit_len.to_le_bytes());
+        bytes[off + 8..off + 12].copy_from_slice(&h.pop_tag.to_le_bytes());
+        bytes[off + 12..off + 16].copy_from_slice(&h.pop_count.to_le_bytes());
+    };
+
+    let all_prev = 0..n_kinds;
+
+    // Push on opening tokens
+    for p in all_prev.clone() {
+        set(
+            p,
+            TokenKind::GroupLParen as u32,
+            ActionHeader {
+                push_len: 1,
+                emit_len: 0,
+                pop_tag: TAG_GROUP_PAREN,
+                pop_count: 0,
+            },
+        );
+        set(
+            p,
+            TokenKind::CallLParen as u32,
+            ActionHeader {
+                push_len: 1,
+                emit_len: 0,
+                pop_tag: TAG_CALL_PAREN,
+                pop_count: 0,
+            },
+        );
+        set(
+            p,
+            TokenKind::ParamLParen as u32,
+            ActionHeader {
+                push_len: 1,
+                emit_len: 0,
+                pop_tag: TAG_PARAM_PAREN,
+                pop_count: 0,
+            },
+        );
+        set(
+            p,
+            TokenKind::ArrayLBracket as u32,
+            ActionHeader {
+                push_len: 1,
+                emit_len: 0,
+                pop_tag: TAG_ARRAY_BRACK,
+                pop_count: 0,
+            },
+        );
+        set(
+            p,
+            TokenKind::IndexLBracket as u32,
+            ActionHeader {
+                push_len: 1,
+                emit_len: 0,
+                pop_tag: TAG_INDEX_BRACK,
+                pop_count: 0,
+            },
+        );
+        set(
+            p,
+            TokenKind::TypeArrayLBracket as u32,
+            ActionHeader {
+                push_len: 1,
+                emit_len: 0,
+                pop_tag: TAG_TYPE_ARRAY_BRACK,
+                pop_count: 0,
+            },
+        );
+    }
+
+    // Pop on closing tokens (generic pop_tag=0 in MVP)
+    for p in all_prev {
+        for close in [
+            TokenKind::RParen,
+            TokenKind::GroupRParen,
+            TokenKind::CallRParen,
+            TokenKind::ParamRParen,
+        ] {
+            set(
+                p,
+                close as u32,
+                ActionHeader {
+                    push_len: 0,
+                    emit_len: 0,
+                    pop_tag: 0,
+                    pop_count: 1,
+                },
+            );
+        }
+        for close in [
+            TokenKind::RBracket,
+            TokenKind::ArrayRBracket,
+            TokenKind::IndexRBracket,
+            TokenKind::TypeArrayRBracket,
+        ] {
+            set(
+                p,
+                close as u32,
+                ActionHeader {
+                    push_len: 0,
+                    emit_len: 0,
+                    pop_tag: 0,
+                    pop_count: 1,
+                },
+            );
+        }
+        // (Add RBrace if you want block matching in the MVP)
+    }
+
+    bytes
+}
+
+// ---------- Real offline tables (3 data structures / 7 arrays) ----------
+
+const MAGIC_V1: &[u8; 8] = b"LXPRSE01";
+const MAGIC_V2: &[u8; 8] = b"LXPRSE02";
+const MAGIC_V3: &[u8; 8] = b"LXPRSE03";
+/// Container magic of format-version-2 parse tables.
+const PARSER_MAGIC: Tag = *b"LXPARSER";
+/// Kind, production, and nonterminal counts and the stream bit widths.
+const HEAD_TAG: Tag = *b"LXPRHEAD";
+/// Stack-change supersequence, offsets, and lengths.
+const SC_TAG: Tag = *b"LXPRSCSQ";
+/// Partial-parse supersequence, offsets, and lengths.
+const PP_TAG: Tag = *b"LXPRPPSQ";
+/// Production arity.
+const ARITY_TAG: Tag = *b"LXPRARTY";
+/// LL(1) predictions and production right-hand sides.
+const LL1_TAG: Tag = *b"LXPRLL1P";
+/// Optional forward and backward kind maps.
+const KIND_MAP_TAG: Tag = *b"LXPRKMAP";
+/// Tag of the optional grammar-names section. In a V3 payload it trails the
+/// arrays; readers that predate it stop before the section and ignore it.
+const NAMES_SECTION_TAG: &[u8; 8] = b"LXPRNAME";
+/// Tag of the trailing sentinel section; tables without it use kind `0` at
+/// both ends of the stream.
+const SENTINEL_SECTION_TAG: &[u8; 8] = b"LXPRSENT";
+/// Tag of the optional trailing operator-precedence section.
+const PRECEDENCE_SECTION_TAG: &[u8; 8] = b"LXPRPREC";
+/// Tag of the optional trailing terminal-names section.
+const TERMINALS_SECTION_TAG: &[u8; 8] = b"LXPRTERM";
+/// Tag of the optional trailing grammar-flags section; tables without it
+/// have every flag clear.
+const FLAGS_SECTION_TAG: &[u8; 8] = b"LXPRFLAG";
+/// Tag of the optional trailing layout section written for tables that went
+/// through `optimize_layout`; tables without it are unoptimized.
+const LAYOUT_SECTION_TAG: &[u8; 8] = b"LXPRLAYT";
+/// Tag of the optional trailing recovery sync-set section.
+const SYNC_SECTION_TAG: &[u8; 8] = b"LXPRSYNC";
+/// Grammar flag: the start nonterminal derives the empty string.
+const GRAMMAR_FLAG_ALLOWS_EMPTY: u32 = 1;
+/// Sentinel section flag: the stream also starts with the sentinel.
+const SENTINEL_FLAG_START: u32 = 1;
+/// Layout section flag: the supersequences are laid out by `optimize_layout`.
+const LAYOUT_FLAG_OPTIMIZED: u32 = 1;
+/// Sentinel used by parse tables to represent missing entries.
+pub const INVALID_TABLE_ENTRY: u32 = u32::MAX;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Host-side parse-table oracle error.
+pub struct Ll1ParseError {
+    pub pos: usize,
+    pub code: Ll1ParseErrorCode,
+    pub detail: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Reason a host-side LL(1) table oracle rejected a token stream.
+pub enum Ll1ParseErrorCode {
+    TerminalMismatch,
+    NoPrediction,
+    TrailingInput,
+    BadSymbol,
+    TablesUnavailable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Structured LL(1) rejection context for user-facing diagnostics.
+pub struct Ll1RejectionContext {
+    /// Position in the full parser token-kind stream, including sentinels.
+    pub pos: usize,
+    /// Rejection class observed while replaying the table.
+    pub code: Ll1ParseErrorCode,
+    /// Parser token kind found at `pos`, or `0` for end of input.
+    pub found: u32,
+    /// Parser token kinds accepted at this position. Empty means unknown.
+    pub expected: Vec<u32>,
+}
+
+impl std::fmt::Display for Ll1ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.code {
+            Ll1ParseErrorCode::TerminalMismatch => write!(
+                f,
+                "parse error: unexpected token at parser position {}",
+                self.pos
+            ),
+            Ll1ParseErrorCode::NoPrediction => write!(
+                f,
+                "parse error: could not match the grammar at parser position {}",
+                self.pos
+            ),
+            Ll1ParseErrorCode::TrailingInput => write!(
+                f,
+                "parse error: unexpected trailing input at parser position {}",
+                self.pos
+            ),
+            Ll1ParseErrorCode::BadSymbol => {
+                f.write_str("parser table error: an invalid parser symbol was encountered")
+            }
+            Ll1ParseErrorCode::TablesUnavailable => {
+                f.write_str("parser table error: parser tables are unavailable")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Ll1ParseError {}
+
+#[inline]
+/// Encodes a parser stack push operation for a stack symbol id.
+pub fn encode_push(symbol_id: u32) -> u32 {
+    // push = 2*x + 1
+    symbol_id
+        .checked_mul(2)
+        .and_then(|v| v.checked_add(1))
+        .expect("overflow in push encode")
+}
+#[inline]
+/// Encodes a parser stack pop operation for a stack symbol id.
+pub fn encode_pop(symbol_id: u32) -> u32 {
+    // pop = 2*x
+    symbol_id.checked_mul(2).expect("overflow in pop encode")
+}
+
+#[inline]
+/// Mirrors a stack-change sequence for right-to-left analysis.
+///
+/// The sequence is reversed and every push becomes a pop of the same symbol
+/// (and vice versa), which is what reading the same pair backwards does to the
+/// parser stack.
+pub fn mirror_stack_changes(seq: &[u32]) -> Vec<u32> {
+    // push = 2*x + 1 and pop = 2*x differ only in the low bit.
+    seq.iter().rev().map(|&code| code ^ 1).collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Binary operator declared with the grammar's `%binop` directive.
+pub struct OperatorPrecedence {
+    /// Lexer token kind of the operator.
+    pub kind: u32,
+    /// Binding strength; higher binds tighter.
+    pub precedence: u32,
+    pub right_assoc: bool,
+}
+
+#[derive(Debug, Clone)]
+/// Precomputed parser table data consumed by GPU parser passes.
+pub struct PrecomputedParseTables {
+    // basic sizes
+    pub n_kinds: u32,
+    pub n_productions: u32,
+
+    // 1) stack-change supersequence + 2D views (row-major by prev_kind, this_kind)
+    pub sc_superseq: Vec<u32>,
+    pub sc_off: Vec<u32>,    // len = n_kinds * n_kinds
+    pub sc_len: Vec<u32>,    // len = n_kinds * n_kinds
+    pub sc_symbol_bits: u32, // min bits required for stack symbol IDs
+
+    // 2) partial-parse supersequence + 2D views (row-major)
+    pub pp_superseq: Vec<u32>,
+    pub pp_off: Vec<u32>,  // len = n_kinds * n_kinds
+    pub pp_len: Vec<u32>,  // len = n_kinds * n_kinds
+    pub pp_prod_bits: u32, // min bits required for production IDs
+
+    // 3) production arity
+    pub prod_arity: Vec<u32>, // len = n_productions
+
+    // 4) Full LL(1) acceptance tables.
+    // Symbol encoding for rhs streams:
+    //   terminal    = token kind id in [0, n_kinds)
+    //   nonterminal = n_kinds + nonterminal id
+    pub n_nonterminals: u32,
+    pub start_nonterminal: u32,
+    pub ll1_predict: Vec<u32>, // len = n_nonterminals * n_kinds; u32::MAX means error
+    pub prod_rhs_off: Vec<u32>, // len = n_productions
+    pub prod_rhs_len: Vec<u32>, // len = n_productions
+    pub prod_rhs: Vec<u32>,
+
+    // 5) Optional dense kind renumbering (see `parser::kindmap`). When set,
+    // `n_kinds` is the dense grid width and every kind-indexed array above is
+    // in grid numbering; token streams stay in lexer numbering.
+    pub kind_map: Option<KindMap>,
+
+    // 6) Optional grammar metadata for diagnostics. Each vector is either
+    // empty (unknown) or fully populated.
+    pub prod_names: Vec<String>, // len = n_productions; grammar tags
+    pub prod_lhs: Vec<u32>,      // len = n_productions; nonterminal ids
+    pub nonterminal_names: Vec<String>, // len = n_nonterminals
+
+    // 7) Stream framing (see `wrap_input`). `sentinel_kind` is a lexer kind
+    // with a grid row and column of its own.
+    pub sentinel_kind: u32,
+    pub start_sentinel: bool,
+
+    // 8) Optional operator-precedence ladder (see `parser::ast`). Both are
+    // empty when the grammar declares no `%binop` operators.
+    pub operator_precedence: Vec<OperatorPrecedence>,
+    pub ladder_nonterminals: Vec<u32>, // synthesized level and tail nonterminals
+
+    // 9) Optional `TokenKind` names of the grammar's terminals, indexed by
+    // lexer kind and empty for unreferenced kinds (see `parser::grammar`).
+    // `validate` checks them against the running enum.
+    pub terminal_names: Vec<String>,
+
+    // 10) Whether the start nonterminal derives the empty string, so a
+    // stream of only sentinels parses (see `Ast::empty_program`). Set by
+    // the generator from the grammar's nullable analysis.
+    pub allows_empty_input: bool,
+
+    // 11) Whether `optimize_layout` has laid out the supersequences, and
+    // the cell order and inlining it chose for `to_gpu_blob`. Tables built
+    // or rebuilt through `new` start unoptimized.
+    pub optimized: bool,
+    pub layout: TableLayout,
+
+    // 12) Optional error-recovery sync sets (see `parser::recovery`): per
+    // nonterminal id, the lexer kinds of FIRST and FOLLOW that a recovering
+    // parse may resume at, end of input excluded. Either empty or
+    // `n_nonterminals` long.
+    pub sync_sets: Vec<Vec<u32>>,
+}
+
+impl PrecomputedParseTables {
+    /// Creates empty precomputed tables with the requested token and production counts.
+    pub fn new(n_kinds: u32, n_productions: u32) -> Self {
+        let cells = (n_kinds as usize) * (n_kinds as usize);
+        Self {
+            n_kinds,
+            n_productions,
+            sc_superseq: Vec::new(),
+            sc_off: vec![0; cells],
+            sc_len: vec![0; cells],
+            sc_symbol_bits: 0,
+            pp_superseq: Vec::new(),
+            pp_off: vec![0; cells],
+            pp_len: vec![0; cells],
+            pp_prod_bits: 0,
+            prod_arity: vec![0; n_productions as usize],
+            n_nonterminals: 0,
+            start_nonterminal: 0,
+            ll1_predict: Vec::new(),
+            prod_rhs_off: vec![0; n_productions as usize],
+            prod_rhs_len: vec![0; n_productions as usize],
+            prod_rhs: Vec::new(),
+            kind_map: None,
+            prod_names: Vec::new(),
+            prod_lhs: Vec::new(),
+            nonterminal_names: Vec::new(),
+            sentinel_kind: DEFAULT_SENTINEL_KIND,
+            start_sentinel: true,
+            operator_precedence: Vec::new(),
+            ladder_nonterminals: Vec::new(),
+            terminal_names: Vec::new(),
+            allows_empty_input: false,
+            optimized: false,
+            layout: TableLayout::default(),
+            sync_sets: Vec::new(),
+        }
+    }
+
+    /// Copies the grammar names of `other`, which must describe the same
+    /// productions and nonterminals.
+    pub fn copy_grammar_names(&mut self, other: &Self) {
+        self.prod_names = other.prod_names.clone();
+        self.prod_lhs = other.prod_lhs.clone();
+        self.nonterminal_names = other.nonterminal_names.clone();
+        self.terminal_names = other.terminal_names.clone();
+    }
+
+    /// Copies the operator-precedence ladder of `other`, whose nonterminal ids
+    /// must match these tables'.
+    pub fn copy_precedence_ladder(&mut self, other: &Self) {
+        self.operator_precedence = other.operator_precedence.clone();
+        self.ladder_nonterminals = other.ladder_nonterminals.clone();
+    }
+
+    /// Precedence entry of the operator with lexer kind `kind`, if any.
+    pub fn operator_precedence(&self, kind: u32) -> Option<OperatorPrecedence> {
+        self.operator_precedence
+            .iter()
+            .find(|op| op.kind == kind)
+            .copied()
+    }
+
+    /// Grammar tag of `prod`, or `prod{N}` when the tables carry no names.
+    pub fn production_name(&self, prod: u32) -> std::borrow::Cow<'_, str> {
+        match self.prod_names.get(prod as usize) {
+            Some(name) => name.as_str().into(),
+            None => format!("prod{prod}").into(),
+        }
+    }
+
+    /// Grammar name of nonterminal `nt`, or `nt{N}` when unknown.
+    pub fn nonterminal_name(&self, nt: u32) -> std::borrow::Cow<'_, str> {
+        match self.nonterminal_names.get(nt as usize) {
+            Some(name) => name.as_str().into(),
+            None => format!("nt{nt}").into(),
+        }
+    }
+
+    /// Recovery sync set of nonterminal `nt` in lexer numbering; empty when
+    /// the tables carry no sync sets.
+    pub fn sync_set(&self, nt: u32) -> &[u32] {
+        self.sync_sets.get(nt as usize).map_or(&[], Vec::as_slice)
+    }
+
+    /// Renders `prod` in grammar syntax, e.g. `expr -> term 'InfixPlus' expr`.
+    ///
+    /// Falls back to the production name for the left-hand side and to
+    /// numeric symbols when metadata or RHS data is missing.
+    pub fn describe_production(&self, prod: u32) -> String {
+        let lhs = match self.prod_lhs.get(prod as usize) {
+            Some(&nt) => self.nonterminal_name(nt),
+            None => self.production_name(prod),
+        };
+        let off = self.prod_rhs_off.get(prod as usize).copied().unwrap_or(0) as usize;
+        let len = self.prod_rhs_len.get(prod as usize).copied().unwrap_or(0) as usize;
+        let rhs = self
+            .prod_rhs
+            .get(off..off + len)
+            .unwrap_or_default()
+            .iter()
+            .map(|&symbol| {
+                if symbol >= self.n_kinds {
+                    return self.nonterminal_name(symbol - self.n_kinds).into_owned();
+                }
+                let kind = self.lexer_kind(symbol);
+                match TokenKind::from_u32(kind) {
+                    Some(kind) => format!("'{kind:?}'"),
+                    None => format!("'kind{kind}'"),
+                }
+            })
+            .collect::<Vec<_>>();
+        if rhs.is_empty() {
+            format!("{lhs} -> ε")
+        } else {
+            format!("{lhs} -> {}", rhs.join(" "))
+        }
+    }
+
+    /// Renders each production id in `prods` with [`Self::describe_production`].
+    pub fn explain_productions(&self, prods: &[u32]) -> Vec<String> {
+        prods
+            .iter()
+            .map(|&prod| self.describe_production(prod))
+            .collect()
+    }
+
+    /// Lexer kind count accepted in token streams.
+    pub fn source_kinds(&self) -> u32 {
+        self.kind_map
+            .as_ref()
+            .map_or(self.n_kinds, KindMap::source_kinds)
+    }
+
+    /// Grid kind for a lexer kind; `INVALID_TABLE_ENTRY` when renumbering dropped it.
+    pub fn grid_kind(&self, kind: u32) -> u32 {
+        match &self.kind_map {
+            Some(map) => map.dense(kind).unwrap_or(INVALID_TABLE_ENTRY),
+            None => kind,
+        }
+    }
+
+    /// Lexer kind for a grid kind.
+    pub fn lexer_kind(&self, grid_kind: u32) -> u32 {
+        match &self.kind_map {
+            Some(map) => map.source(grid_kind).unwrap_or(INVALID_TABLE_ENTRY),
+            None => grid_kind,
+        }
+    }
+
+    /// Lexer kind -> grid kind lookup words uploaded for the GPU pair passes.
+    pub fn kind_lookup_words(&self) -> Vec<u32> {
+        match &self.kind_map {
+            Some(map) => map.lookup_words(),
+            None => (0..crate::parser::kindmap::KIND_REMAP_WIDTH as u32)
+                .map(|kind| {
+                    if kind < self.n_kinds {
+                        kind
+                    } else {
+                        INVALID_TABLE_ENTRY
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    #[inline]
+    fn cell_index(&self, prev: u32, this: u32) -> usize {
+        (prev as usize) * (self.n_kinds as usize) + (this as usize)
+    }
+
+    /// Append a stack-change sequence for a given (prev,this) token-kind pair.
+    pub fn set_sc_for_pair(&mut self, prev: u32, this: u32, seq: &[u32]) {
+        let idx = self.cell_index(prev, this);
+        let off = self.sc_superseq.len() as u32;
+        self.sc_off[idx] = off;
+        self.sc_len[idx] = seq.len() as u32;
+        self.sc_superseq.extend_from_slice(seq);
+    }
+
+    /// Append a partial-parse sequence (production IDs) for a given (prev,this).
+    pub fn set_pp_for_pair(&mut self, prev: u32, this: u32, seq: &[u32]) {
+        let idx = self.cell_index(prev, this);
+        let off = self.pp_superseq.len() as u32;
+        self.pp_off[idx] = off;
+        self.pp_len[idx] = seq.len() as u32;
+        self.pp_superseq.extend_from_slice(seq);
+    }
+
+    /// Finalizes stack-symbol and production id bit-width metadata.
+    pub fn finalize_bit_widths(&mut self, max_symbol_id: u32) {
+        // ceil(log2(max+1)) as a tiny helper
+        fn bits_for(x: u32) -> u32 {
+            let mut v = x;
+            let mut bits = 0;
+            while v > 0 {
+                v >>= 1;
+                bits += 1;
+            }
+            bits.max(1)
+        }
+        self.sc_symbol_bits = bits_for(max_symbol_id);
+        self.pp_prod_bits = {
+            let max_prod = self.n_productions.saturating_sub(1);
+            if max_prod == 0 {
+                1
+            } else {
+                let mut v = max_prod;
+                let mut b = 0;
+                while v > 0 {
+                    v >>= 1;
+                    b += 1;
+                }
+                b
+            }
+        };
+    }
+
+    /// Preorder productions that derive the empty input, predicted at the
+    /// end-of-input lookahead, or `None` when [`Self::allows_empty_input`] is
+    /// clear or the tables cannot derive it.
+    ///
+    /// The parser emits nothing for a stream of only sentinels, so this is
+    /// the production stream such a parse stands for.
+    pub fn empty_input_productions(&self) -> Option<Vec<u32>> {
+        if !self.allows_empty_input || self.n_nonterminals == 0 {
+            return None;
+        }
+        let lookahead = self.grid_kind(0);
+        let mut out = Vec::new();
+        // A path longer than the nonterminal count repeats one, which an
+        // epsilon derivation never needs.
+        let mut stack = vec![(self.start_nonterminal, 0)];
+        while let Some((nt, depth)) = stack.pop() {
+            if nt >= self.n_nonterminals || depth >= self.n_nonterminals {
+                return None;
+            }
+            let prod = self.predict(nt, lookahead);
+            if prod >= self.n_productions {
+                return None;
+            }
+            out.push(prod);
+            let off = self.prod_rhs_off[prod as usize] as usize;
+            let len = self.prod_rhs_len[prod as usize] as usize;
+            for &sym in self.prod_rhs[off..off + len].iter().rev() {
+                if sym < self.n_kinds {
+                    return None;
+                }
+                stack.push((sym - self.n_kinds, depth + 1));
+            }
+        }
+        Some(out)
+    }
+
+    /// Test-only host LL(1) oracle for parser tests and fuzz tooling.
+    ///
+    /// The compiler must not call this; production parsing is recorded and
+    /// executed by the parser driver.
+    pub fn test_cpu_ll1_production_stream(
+        &self,
+        token_kinds: &[u32],
+    ) -> Result<Vec<u32>, Ll1ParseError> {
+        let (productions, _) = self.test_cpu_ll1_production_stream_with_positions(token_kinds)?;
+        Ok(productions)
+    }
+
+    /// Test-only host LL(1) oracle that also returns production source positions.
+    pub fn test_cpu_ll1_production_stream_with_positions(
+        &self,
+        token_kinds: &[u32],
+    ) -> Result<(Vec<u32>, Vec<u32>), Ll1ParseError> {
+        if self.n_nonterminals == 0 || self.ll1_predict.is_empty() {
+            return Err(Ll1ParseError {
+                pos: 0,
+                code: Ll1ParseErrorCode::TablesUnavailable,
+                detail: 0,
+            });
+        }
+
+        let input_end = token_kinds.len().saturating_sub(1);
+        let first_input = if token_kinds.first().copied() == Some(0) {
+            1
+        } else {
+            0
+        };
+        let mut pos = first_input;
+        let mut stack = vec![self.n_kinds + self.start_nonterminal];
+        let mut out = Vec::new();
+        let mut positions = Vec::new();
+
+        while let Some(sym) = stack.pop() {
+            let found = if pos < input_end { token_kinds[pos] } else { 0 };
+            let lookahead = self.grid_kind(found);
+
+            if sym < self.n_kinds {
+                if sym != lookahead {
+                    return Err(Ll1ParseError {
+                        pos,
+                        code: Ll1ParseErrorCode::TerminalMismatch,
+                        detail: self.lexer_kind(sym),
+                    });
+                }
+                pos += 1;
+                continue;
+            }
+
+            let nt = sym - self.n_kinds;
+            if nt >= self.n_nonterminals || found >= self.source_kinds() {
+                return Err(Ll1ParseError {
+                    pos,
+                    code: Ll1ParseErrorCode::BadSymbol,
+                    detail: sym,
+                });
+            }
+
+            let prod = self.predict(nt, lookahead);
+            if prod == INVALID_TABLE_ENTRY || prod >= self.n_productions {
+                return Err(Ll1ParseError {
+                    pos,
+                    code: Ll1ParseErrorCode::NoPrediction,
+                    detail: nt,
+                });
+            }
+
+            out.push(prod);
+            positions.push(pos.saturating_sub(first_input) as u32);
+            let off = self.prod_rhs_off[prod as usize] as usize;
+            let len = self.prod_rhs_len[prod as usize] as usize;
+            stack.extend(self.prod_rhs[off..off + len].iter().rev().copied());
+        }
+
+        if pos != input_end {
+            return Err(Ll1ParseError {
+                pos,
+                code: Ll1ParseErrorCode::TrailingInput,
+                detail: token_kinds.get(pos).copied().unwrap_or(0),
+            });
+        }
+
+        Ok((out, positions))
+    }
+
+    /// Replays the LL(1) table enough to explain the first rejected token.
+    ///
+    /// This is intentionally diagnostic-only: the GPU parser remains the
+    /// acceptance source, while this host replay turns the same generated table
+    /// into an expected/found payload for errors.
+    pub fn diagnose_ll1_rejection(&self, token_kinds: &[u32]) -> Option<Ll1RejectionContext> {
+        if self.n_nonterminals == 0 || self.ll1_predict.is_empty() {
+            return Some(Ll1RejectionContext {
+                pos: 0,
+                code: Ll1ParseErrorCode::TablesUnavailable,
+                found: token_kinds.first().copied().unwrap_or(0),
+                expected: Vec::new(),
+            });
+        }
+
+        let input_end = token_kinds.len().saturating_sub(1);
+        let first_input = if token_kinds.first().copied() == Some(0) {
+            1
+        } else {
+            0
+        };
+        let mut pos = first_input;
+        let mut stack = vec![self.n_kinds + self.start_nonterminal];
+
+        while let Some(sym) = stack.pop() {
+            let found = if pos < input_end { token_kinds[pos] } else { 0 };
+            let lookahead = self.grid_kind(found);
+
+            if sym < self.n_kinds {
+                if sym != lookahead {
+                    return Some(Ll1RejectionContext {
+                        pos,
+                        code: Ll1ParseErrorCode::TerminalMismatch,
+                        found,
+                        expected: vec![self.lexer_kind(sym)],
+                    });
+                }
+                pos += 1;
+                continue;
+            }
+
+            let nt = sym - self.n_kinds;
+            if nt >= self.n_nonterminals || found >= self.source_kinds() {
+                return Some(Ll1RejectionContext {
+                    pos,
+                    code: Ll1ParseErrorCode::BadSymbol,
+                    found,
+                    expected: Vec::new(),
+                });
+            }
+
+            let prod = self.predict(nt, lookahead);
+            if prod == INVALID_TABLE_ENTRY || prod >= self.n_productions {
+                return Some(Ll1RejectionContext {
+                    pos,
+                    code: Ll1ParseErrorCode::NoPrediction,
+                    found,
+                    expected: self.expected_lookaheads_for_nonterminal(nt),
+                });
+            }
+
+            let off = self.prod_rhs_off[prod as usize] as usize;
+            let len = self.prod_rhs_len[prod as usize] as usize;
+            stack.extend(self.prod_rhs[off..off + len].iter().rev().copied());
+        }
+
+        if pos != input_end {
+            return Some(Ll1RejectionContext {
+                pos,
+                code: Ll1ParseErrorCode::TrailingInput,
+                found: token_kinds.get(pos).copied().unwrap_or(0),
+                expected: vec![0],
+            });
+        }
+
+        None
+    }
+
+    /// LL(1) prediction for a nonterminal and grid-kind lookahead.
+    pub(crate) fn predict(&self, nt: u32, lookahead: u32) -> u32 {
+        if lookahead >= self.n_kinds {
+            return INVALID_TABLE_ENTRY;
+        }
+        self.ll1_predict[(nt as usize) * (self.n_kinds as usize) + lookahead as usize]
+    }
+
+    pub(crate) fn expected_lookaheads_for_nonterminal(&self, nt: u32) -> Vec<u32> {
+        if nt >= self.n_nonterminals || self.n_kinds == 0 {
+            return Vec::new();
+        }
+
+        let row_start = (nt as usize).saturating_mul(self.n_kinds as usize);
+        let row_end = row_start.saturating_add(self.n_kinds as usize);
+        self.ll1_predict
+            .get(row_start..row_end)
+            .into_iter()
+            .flat_map(|row| row.iter().enumerate())
+            .filter(|&(_, &production)| {
+                production != INVALID_TABLE_ENTRY && production < self.n_productions
+            })
+            .map(|(lookahead, _)| self.lexer_kind(lookahead as u32))
+            .collect()
+    }
+
+    /// Test-only host oracle for the adjacent-pair partial-parse stream.
+    pub fn test_cpu_partial_parse_stream(&self, token_kinds: &[u32]) -> Vec<u32> {
+        let mut out = Vec::new();
+        for pair in token_kinds.windows(2) {
+            let prev = self.grid_kind(pair[0]);
+            let this = self.grid_kind(pair[1]);
+            if prev >= self.n_kinds || this >= self.n_kinds {
+                continue;
+            }
+            let idx = self.cell_index(prev, this);
+            let off = self.pp_off[idx] as usize;
+            let len = self.pp_len[idx] as usize;
+            out.extend_from_slice(&self.pp_superseq[off..off + len]);
+        }
+        out
+    }
+
+    /// Test-only host stack-change stream for a token-kind stream.
+    pub fn test_cpu_stack_change_stream(&self, token_kinds: &[u32]) -> Vec<u32> {
+        let mut out = Vec::new();
+        for pair in token_kinds.windows(2) {
+            let prev = self.grid_kind(pair[0]);
+            let this = self.grid_kind(pair[1]);
+            if prev >= self.n_kinds || this >= self.n_kinds {
+                continue;
+            }
+            let idx = self.cell_index(prev, this);
+            let off = self.sc_off[idx] as usize;
+            let len = self.sc_len[idx] as usize;
+            out.extend_from_slice(&self.sc_superseq[off..off + len]);
+        }
+        out
+    }
+
+    /// Builds tables over reversed token pairs for right-to-left (suffix) parsing.
+    ///
+    /// Cell `(this, prev)` of the result holds the mirrored stack changes of
+    /// forward cell `(prev, this)`, so lexing a reversed token stream yields the
+    /// mirror image of the forward stack-change stream. Partial-parse sequences
+    /// are reversed in place. LL(1) prediction data has no right-to-left
+    /// counterpart and is left empty.
+    pub fn reverse(&self) -> Self {
+        let mut rev = Self::new(self.n_kinds, self.n_productions);
+        rev.prod_arity = self.prod_arity.clone();
+        for prev in 0..self.n_kinds {
+            for this in 0..self.n_kinds {
+                let idx = self.cell_index(prev, this);
+                let sc_off = self.sc_off[idx] as usize;
+                let sc = &self.sc_superseq[sc_off..sc_off + self.sc_len[idx] as usize];
+                if !sc.is_empty() {
+                    rev.set_sc_for_pair(this, prev, &mirror_stack_changes(sc));
+                }
+                let pp_off = self.pp_off[idx] as usize;
+                let pp = &self.pp_superseq[pp_off..pp_off + self.pp_len[idx] as usize];
+                if !pp.is_empty() {
+                    let reversed = pp.iter().rev().copied().collect::<Vec<_>>();
+                    rev.set_pp_for_pair(this, prev, &reversed);
+                }
+            }
+        }
+        rev.sc_symbol_bits = self.sc_symbol_bits;
+        rev.pp_prod_bits = self.pp_prod_bits;
+        rev.kind_map = self.kind_map.clone();
+        rev.copy_grammar_names(self);
+        rev.copy_precedence_ladder(self);
+        rev.sentinel_kind = self.sentinel_kind;
+        rev.start_sentinel = self.start_sentinel;
+        rev.allows_empty_input = self.allows_empty_input;
+        rev
+    }
+
+    // ---------- Binary I/O ----------
+
+    /// Writes these parse tables in the current binary format.
+    pub fn save_bin<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        self.save_bin_as(path, FormatVersion::default())
+    }
+
+    /// Writes these parse tables in the `version` layout.
+    pub fn save_bin_as<P: AsRef<Path>>(
+        &self,
+        path: P,
+        version: FormatVersion,
+    ) -> std::io::Result<()> {
+        fs::File::create(path)?.write_all(&self.to_bin_bytes_as(version))
+    }
+
+    /// Encodes these parse tables in the current format read by
+    /// [`Self::load_bin_bytes`].
+    pub fn to_bin_bytes(&self) -> Vec<u8> {
+        self.to_bin_bytes_as(FormatVersion::default())
+    }
+
+    /// Encodes these parse tables in the `version` layout.
+    ///
+    /// Version 2 stores each table group as a container section. Version 1
+    /// is the `LXPRSE03` payload followed by the sentinel section and then
+    /// the grammar names, operator precedences, terminal names, grammar
+    /// flags, layout, and sync sets, when present, as tagged trailing
+    /// sections.
+    pub fn to_bin_bytes_as(&self, version: FormatVersion) -> Vec<u8> {
+        let head = words(&[
+            self.n_kinds,
+            self.n_productions,
+            self.sc_symbol_bits,
+            self.pp_prod_bits,
+            self.n_nonterminals,
+            self.start_nonterminal,
+        ]);
+        let mut sc = Vec::new();
+        write_vec(&mut sc, &self.sc_superseq);
+        write_vec(&mut sc, &self.sc_off);
+        write_vec(&mut sc, &self.sc_len);
+        let mut pp = Vec::new();
+        write_vec(&mut pp, &self.pp_superseq);
+        write_vec(&mut pp, &self.pp_off);
+        write_vec(&mut pp, &self.pp_len);
+        let mut arity = Vec::new();
+        write_vec(&mut arity, &self.prod_arity);
+        let mut ll1 = Vec::new();
+        write_vec(&mut ll1, &self.ll1_predict);
+        write_vec(&mut ll1, &self.prod_rhs_off);
+        write_vec(&mut ll1, &self.prod_rhs_len);
+        write_vec(&mut ll1, &self.prod_rhs);
+        let mut kind_map = Vec::new();
+        if let Some(map) = &self.kind_map {
+            write_vec(&mut kind_map, map.forward());
+            write_vec(&mut kind_map, map.backward());
+        }
+        let sentinel = words(&[
+            self.sentinel_kind,
+            if self.start_sentinel {
+                SENTINEL_FLAG_START
+            } else {
+                0
+            },
+        ]);
+        let names = (!self.prod_names.is_empty()
+            || !self.prod_lhs.is_empty()
+            || !self.nonterminal_names.is_empty())
+        .then(|| {
+            let mut out = Vec::new();
+            write_strings(&mut out, &self.prod_names);
+            write_vec(&mut out, &self.prod_lhs);
+            write_strings(&mut out, &self.nonterminal_names);
+            out
+        });
+        let precedence = (!self.operator_precedence.is_empty()
+            || !self.ladder_nonterminals.is_empty())
+        .then(|| {
+            let mut out = Vec::new();
+            write_u32(&mut out, self.operator_precedence.len() as u32);
+            for op in &self.operator_precedence {
+                write_u32(&mut out, op.kind);
+                write_u32(&mut out, op.precedence);
+                write_u32(&mut out, op.right_assoc as u32);
+            }
+            write_vec(&mut out, &self.ladder_nonterminals);
+            out
+        });
+        let terminals = (!self.terminal_names.is_empty()).then(|| {
+            let mut out = Vec::new();
+            write_strings(&mut out, &self.terminal_names);
+            out
+        });
+        let flags = self
+            .allows_empty_input
+            .then(|| words(&[GRAMMAR_FLAG_ALLOWS_EMPTY]));
+        let layout = self.optimized.then(|| {
+            let mut out = words(&[LAYOUT_FLAG_OPTIMIZED, self.layout.inline_max_len]);
+            write_vec(&mut out, &self.layout.hot_cells);
+            out
+        });
+        let sync = (!self.sync_sets.is_empty()).then(|| {
+            let mut out = Vec::new();
+            write_u32(&mut out, self.sync_sets.len() as u32);
+            for set in &self.sync_sets {
+                write_vec(&mut out, set);
+            }
+            out
+        });
+
+        match version {
+            FormatVersion::V1 => {
+                let mut out = Vec::new();
+                out.extend_from_slice(MAGIC_V3);
+                out.extend_from_slice(&head[..16]);
+                out.extend_from_slice(&sc);
+                out.extend_from_slice(&pp);
+                out.extend_from_slice(&arity);
+                out.extend_from_slice(&head[16..]);
+                out.extend_from_slice(&ll1);
+                if kind_map.is_empty() {
+                    write_vec(&mut out, &[]);
+                    write_vec(&mut out, &[]);
+                } else {
+                    out.extend_from_slice(&kind_map);
+                }
+                for (tag, section) in [
+                    (SENTINEL_SECTION_TAG, Some(&sentinel)),
+                    (NAMES_SECTION_TAG, names.as_ref()),
+                    (PRECEDENCE_SECTION_TAG, precedence.as_ref()),
+                    (TERMINALS_SECTION_TAG, terminals.as_ref()),
+                    (FLAGS_SECTION_TAG, flags.as_ref()),
+                    (LAYOUT_SECTION_TAG, layout.as_ref()),
+                    (SYNC_SECTION_TAG, sync.as_ref()),
+                ] {
+                    if let Some(section) = section {
+                        out.extend_from_slice(tag);
+                        out.extend_from_slice(section);
+                    }
+                }
+                out
+            }
+            FormatVersion::V2 => {
+                let mut sections = vec![
+                    Section::critical(HEAD_TAG, &head),
+                    Section::critical(SC_TAG, &sc),
+                    Section::critical(PP_TAG, &pp),
+                    Section::critical(ARITY_TAG, &arity),
+                    Section::critical(LL1_TAG, &ll1),
+                ];
+                if self.kind_map.is_some() {
+                    sections.push(Section::critical(KIND_MAP_TAG, &kind_map));
+                }
+                sections.push(Section::critical(*SENTINEL_SECTION_TAG, &sentinel));
+                if let Some(names) = &names {
+                    sections.push(Section::new(*NAMES_SECTION_TAG, names));
+                }
+                if let Some(precedence) = &precedence {
+                    sections.push(Section::new(*PRECEDENCE_SECTION_TAG, precedence));
+                }
+                if let Some(terminals) = &terminals {
+                    sections.push(Section::new(*TERMINALS_SECTION_TAG, terminals));
+                }
+                if let Some(flags) = &flags {
+                    sections.push(Section::new(*FLAGS_SECTION_TAG, flags));
+                }
+                if let Some(layout) = &layout {
+                    sections.push(Section::new(*LAYOUT_SECTION_TAG, layout));
+                }
+                if let Some(sync) = &sync {
+                    sections.push(Section::new(*SYNC_SECTION_TAG, sync));
+                }
+                format::encode(&PARSER_MAGIC, &sections)
+            }
+        }
+    }
+
+    /// Loads parse tables from binary bytes in either format version.
+    pub fn load_bin_bytes(data: &[u8]) -> Result<Self, String> {
+        let tables = if data.starts_with(&PARSER_MAGIC) {
+            Self::decode_container(data)?
+        } else {
+            Self::decode_v1(data)?
+        };
+        tables
+            .validate()
+            .map_err(|violations| format!("parse tables: {}", summarize_violations(&violations)))?;
+        Ok(tables)
+    }
+
+    fn decode_container(data: &[u8]) -> Result<Self, String> {
+        let container = Container::parse(
+            data,
+            &PARSER_MAGIC,
+            &[
+                HEAD_TAG,
+                SC_TAG,
+                PP_TAG,
+                ARITY_TAG,
+                LL1_TAG,
+                KIND_MAP_TAG,
+                *SENTINEL_SECTION_TAG,
+                *NAMES_SECTION_TAG,
+                *PRECEDENCE_SECTION_TAG,
+                *TERMINALS_SECTION_TAG,
+                *FLAGS_SECTION_TAG,
+                *LAYOUT_SECTION_TAG,
+                *SYNC_SECTION_TAG,
+            ],
+        )
+        .map_err(|err| format!("parse tables: {err}"))?;
+        // Runs `read` over the payload of `tag` and checks it used every byte.
+        fn read<T>(
+            container: &Container<'_>,
+            tag: Tag,
+            read: impl FnOnce(&mut &[u8]) -> Result<T, String>,
+        ) -> Result<Option<T>, String> {
+            let Some(mut data) = container.section(&tag) else {
+                return Ok(None);
+            };
+            let value = read(&mut data)?;
+            format::expect_consumed(&tag, data).map_err(|err| format!("parse tables: {err}"))?;
+            Ok(Some(value))
+        }
+        let missing = |tag| format!("parse tables: {}", FormatError::MissingSection(tag));
+
+        let [
+            n_kinds,
+            n_productions,
+            sc_symbol_bits,
+            pp_prod_bits,
+            n_nonterminals,
+            start_nonterminal,
+        ] = read(&container, HEAD_TAG, |buf| {
+            let mut head = [0u32; 6];
+            for word in &mut head {
+                *word = take_u32(buf)?;
+            }
+            Ok(head)
+        })?
+        .ok_or_else(|| missing(HEAD_TAG))?;
+        let [sc_superseq, sc_off, sc_len] =
+            read(&container, SC_TAG, take_vecs)?.ok_or_else(|| missing(SC_TAG))?;
+        let [pp_superseq, pp_off, pp_len] =
+            read(&container, PP_TAG, take_vecs)?.ok_or_else(|| missing(PP_TAG))?;
+        let prod_arity =
+            read(&container, ARITY_TAG, take_vec)?.ok_or_else(|| missing(ARITY_TAG))?;
+        let [ll1_predict, prod_rhs_off, prod_rhs_len, prod_rhs] =
+            read(&container, LL1_TAG, take_vecs)?.ok_or_else(|| missing(LL1_TAG))?;
+        let kind_map = read(&container, KIND_MAP_TAG, take_kind_map)?.flatten();
+        let (sentinel_kind, start_sentinel) =
+            read(&container, *SENTINEL_SECTION_TAG, take_sentinel)?
+                .unwrap_or((DEFAULT_SENTINEL_KIND, true));
+        let (prod_names, prod_lhs, nonterminal_names) =
+            read(&container, *NAMES_SECTION_TAG, take_names)?.unwrap_or_default();
+        let (operator_precedence, ladder_nonterminals) =
+            read(&container, *PRECEDENCE_SECTION_TAG, take_precedence)?.unwrap_or_default();
+        let terminal_names =
+            read(&container, *TERMINALS_SECTION_TAG, take_strings)?.unwrap_or_default();
+        let flags = read(&container, *FLAGS_SECTION_TAG, take_u32)?.unwrap_or(0);
+        let (optimized, layout) =
+            read(&container, *LAYOUT_SECTION_TAG, take_layout)?.unwrap_or_default();
+        let sync_sets = read(&container, *SYNC_SECTION_TAG, take_sync_sets)?.unwrap_or_default();
+
+        Ok(Self {
+            n_kinds,
+            n_productions,
+            sc_superseq,
+            sc_off,
+            sc_len,
+            sc_symbol_bits,
+            pp_superseq,
+            pp_off,
+            pp_len,
+            pp_prod_bits,
+            prod_arity,
+            n_nonterminals,
+            start_nonterminal,
+            ll1_predict,
+            prod_rhs_off,
+            prod_rhs_len,
+            prod_rhs,
+            kind_map,
+            prod_names,
+            prod_lhs,
+            nonterminal_names,
+            sentinel_kind,
+            start_sentinel,
+            operator_precedence,
+            ladder_nonterminals,
+            terminal_names,
+            allows_empty_input: flags & GRAMMAR_FLAG_ALLOWS_EMPTY != 0,
+            optimized,
+            layout,
+            sync_sets,
+        })
+    }
+
+    /// Reads the version-1 `LXPRSE01`..`LXPRSE03` layouts.
+    fn decode_v1(mut data: &[u8]) -> Result<Self, String> {
+        // header
+        let magic = take::<8>(&mut data)?;
+        if &magic != MAGIC_V1 && &magic != MAGIC_V2 && &magic != MAGIC_V3 {
+            return Err("bad magic in parse tables .bin".into());
+        }
+        let is_v3 = &magic == MAGIC_V3;
+        let is_v2 = is_v3 || &magic == MAGIC_V2;
+        let n_kinds = take_u32(&mut data)?;
+        let n_productions = take_u32(&mut data)?;
+        let sc_symbol_bits = take_u32(&mut data)?;
+        let pp_prod_bits = take_u32(&mut data)?;
+
+        let [sc_superseq, sc_off, sc_len] = take_vecs(&mut data)?;
+        let [pp_superseq, pp_off, pp_len] = take_vecs(&mut data)?;
+        let prod_arity = take_vec(&mut data)?;
+        let (n_nonterminals, start_nonterminal, ll1_predict, prod_rhs_off, prod_rhs_len, prod_rhs) =
+            if is_v2 {
+                let n_nonterminals = take_u32(&mut data)?;
+                let start_nonterminal = take_u32(&mut data)?;
+                let [ll1_predict, prod_rhs_off, prod_rhs_len, prod_rhs] = take_vecs(&mut data)?;
+                (
+                    n_nonterminals,
+                    start_nonterminal,
+                    ll1_predict,
+                    prod_rhs_off,
+                    prod_rhs_len,
+                    prod_rhs,
+                )
+            } else {
+                (
+                    0,
+                    0,
+                    Vec::new(),
+                    vec![0; n_productions as usize],
+                    vec![0; n_productions as usize],
+                    Vec::new(),
+                )
+            };
+        let kind_map = if is_v3 {
+            take_kind_map(&mut data)?
+        } else {
+            None
+        };
+        let (mut sentinel_kind, mut start_sentinel) = (DEFAULT_SENTINEL_KIND, true);
+        let (mut prod_names, mut prod_lhs, mut nonterminal_names) =
+            (Vec::new(), Vec::new(), Vec::new());
+        let (mut operator_precedence, mut ladder_nonterminals) = (Vec::new(), Vec::new());
+        let mut terminal_names = Vec::new();
+        let mut flags = 0;
+        let (mut optimized, mut layout) = (false, TableLayout::default());
+        let mut sync_sets = Vec::new();
+        while is_v3 && !data.is_empty() {
+            match &take::<8>(&mut data)? {
+                SENTINEL_SECTION_TAG => {
+                    (sentinel_kind, start_sentinel) = take_sentinel(&mut data)?;
+                }
+                NAMES_SECTION_TAG => {
+                    (prod_names, prod_lhs, nonterminal_names) = take_names(&mut data)?;
+                }
+                PRECEDENCE_SECTION_TAG => {
+                    (operator_precedence, ladder_nonterminals) = take_precedence(&mut data)?;
+                }
+                TERMINALS_SECTION_TAG => terminal_names = take_strings(&mut data)?,
+                FLAGS_SECTION_TAG => flags = take_u32(&mut data)?,
+                LAYOUT_SECTION_TAG => (optimized, layout) = take_layout(&mut data)?,
+                SYNC_SECTION_TAG => sync_sets = take_sync_sets(&mut data)?,
+                _ => return Err("parse tables: unknown trailing section".into()),
+            }
+        }
+
+        Ok(Self {
+            n_kinds,
+            n_productions,
+            sc_superseq,
+            sc_off,
+            sc_len,
+            sc_symbol_bits,
+            pp_superseq,
+            pp_off,
+            pp_len,
+            pp_prod_bits,
+            prod_arity,
+            n_nonterminals,
+            start_nonterminal,
+            ll1_predict,
+            prod_rhs_off,
+            prod_rhs_len,
+            prod_rhs,
+            kind_map,
+            prod_names,
+            prod_lhs,
+            nonterminal_names,
+            sentinel_kind,
+            start_sentinel,
+            operator_precedence,
+            ladder_nonterminals,
+            terminal_names,
+            allows_empty_input: flags & GRAMMAR_FLAG_ALLOWS_EMPTY != 0,
+            optimized,
+            layout,
+            sync_sets,
+        })
+    }
+}
+
+fn write_u32(out: &mut Vec<u8>, x: u32) {
+    out.extend_from_slice(&x.to_le_bytes());
+}
+
+fn words(v: &[u32]) -> Vec<u8> {
+    v.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+fn write_vec(out: &mut Vec<u8>, v: &[u32]) {
+    write_u32(out, v.len() as u32);
+    for &x in v {
+        write_u32(out, x);
+    }
+}
+
+fn write_strings(out: &mut Vec<u8>, v: &[String]) {
+    write_u32(out, v.len() as u32);
+    for s in v {
+        write_u32(out, s.len() as u32);
+        out.extend_from_slice(s.as_bytes());
+    }
+}
+
+fn take<const N: usize>(buf: &mut &[u8]) -> Result<[u8; N], String> {
+    if buf.len() < N {
+        return Err("truncated parse tables".into());
+    }
+    let mut out = [0u8; N];
+    out.copy_from_slice(&buf[..N]);
+    *buf = &buf[N..];
+    Ok(out)
+}
+
+fn take_u32(buf: &mut &[u8]) -> Result<u32, String> {
+    let le = take::<4>(buf)?;
+    Ok(u32::from_le_bytes(le))
+}
+
+fn take_vec(buf: &mut &[u8]) -> Result<Vec<u32>, String> {
+    let len = take_u32(buf)? as usize;
+    let mut v = Vec::with_capacity(len.min(buf.len() / 4));
+    for _ in 0..len {
+        v.push(take_u32(buf)?);
+    }
+    Ok(v)
+}
+
+/// `N` length-prefixed vectors back to back.
+fn take_vecs<const N: usize>(buf: &mut &[u8]) -> Result<[Vec<u32>; N], String> {
+    let mut out = [(); N].map(|()| Vec::new());
+    for v in &mut out {
+        *v = take_vec(buf)?;
+    }
+    Ok(out)
+}
+
+fn take_strings(buf: &mut &[u8]) -> Result<Vec<String>, String> {
+    let count = take_u32(buf)? as usize;
+    let mut v = Vec::with_capacity(count.min(buf.len()));
+    for _ in 0..count {
+        let len = take_u32(buf)? as usize;
+        if buf.len() < len {
+            return Err("truncated parse tables".into());
+        }
+        let name = std::str::from_utf8(&buf[..len])
+            .map_err(|_| "parse tables: grammar name is not UTF-8".to_string())?;
+        v.push(name.to_string());
+        *buf = &buf[len..];
+    }
+    Ok(v)
+}
+
+/// A count of nonterminals, then one length-prefixed sync set for each.
+fn take_sync_sets(buf: &mut &[u8]) -> Result<Vec<Vec<u32>>, String> {
+    let count = take_u32(buf)? as usize;
+    let mut sets = Vec::with_capacity(count.min(buf.len() / 4));
+    for _ in 0..count {
+        sets.push(take_vec(buf)?);
+    }
+    Ok(sets)
+}
+
+/// Forward and backward kind maps; both empty means no map.
+fn take_kind_map(buf: &mut &[u8]) -> Result<Option<KindMap>, String> {
+    let [forward, backward] = take_vecs(buf)?;
+    if forward.is_empty() && backward.is_empty() {
+        return Ok(None);
+    }
+    KindMap::from_parts(forward, backward)
+        .map(Some)
+        .map_err(|err| format!("parse tables: {err}"))
+}
+
+fn take_sentinel(buf: &mut &[u8]) -> Result<(u32, bool), String> {
+    let kind = take_u32(buf)?;
+    Ok((kind, take_u32(buf)? & SENTINEL_FLAG_START != 0))
+}
+
+type GrammarNames = (Vec<String>, Vec<u32>, Vec<String>);
+
+fn take_names(buf: &mut &[u8]) -> Result<GrammarNames, String> {
+    let prod_names = take_strings(buf)?;
+    let prod_lhs = take_vec(buf)?;
+    Ok((prod_names, prod_lhs, take_strings(buf)?))
+}
+
+fn take_precedence(buf: &mut &[u8]) -> Result<(Vec<OperatorPrecedence>, Vec<u32>), String> {
+    let count = take_u32(buf)? as usize;
+    let mut operator_precedence = Vec::with_capacity(count.min(buf.len() / 12));
+    for _ in 0..count {
+        operator_precedence.push(OperatorPrecedence {
+            kind: take_u32(buf)?,
+            precedence: take_u32(buf)?,
+            right_assoc: take_u32(buf)? != 0,
+        });
+    }
+    Ok((operator_precedence, take_vec(buf)?))
+}
+
+fn take_layout(buf: &mut &[u8]) -> Result<(bool, TableLayout), String> {
+    let flags = take_u32(buf)?;
+    let inline_max_len = take_u32(buf)?;
+    let layout = TableLayout {
+        hot_cells: take_vec(buf)?,
+        inline_max_len,
+    };
+    Ok((flags & LAYOUT_FLAG_OPTIMIZED != 0, layout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiny_ident_semicolon_table() -> PrecomputedParseTables {
+        let mut tables = PrecomputedParseTables::new(4, 1);
+        tables.n_nonterminals = 1;
+        tables.start_nonterminal = 0;
+        tables.ll1_predict = vec![INVALID_TABLE_ENTRY; 4];
+        tables.ll1_predict[1] = 0;
+        tables.prod_rhs_off = vec![0];
+        tables.prod_rhs_len = vec![2];
+        tables.prod_rhs = vec![1, 3];
+        tables
+    }
+
+    #[test]
+    fn ll1_diagnostic_rejection_reports_expected_terminal() {
+        let tables = tiny_ident_semicolon_table();
+
+        let context = tables
+            .diagnose_ll1_rejection(&[0, 1, 2, 0])
+            .expect("terminal mismatch should produce diagnostic context");
+
+        assert_eq!(context.pos, 2);
+        assert_eq!(context.code, Ll1ParseErrorCode::TerminalMismatch);
+        assert_eq!(context.found, 2);
+        assert_eq!(context.expected, vec![3]);
+    }
+
+    #[test]
+    fn ll1_diagnostic_rejection_reports_expected_lookaheads() {
+        let tables = tiny_ident_semicolon_table();
+
+        let context = tables
+            .diagnose_ll1_rejection(&[0, 2, 0])
+            .expect("missing prediction should produce diagnostic context");
+
+        assert_eq!(context.pos, 1);
+        assert_eq!(context.code, Ll1ParseErrorCode::NoPrediction);
+        assert_eq!(context.found, 2);
+        assert_eq!(context.expected, vec![1]);
+    }
+
+    #[test]
+    fn ll1_parse_error_display_avoids_internal_detail() {
+        let error = Ll1ParseError {
+            pos: 2,
+            code: Ll1ParseErrorCode::TerminalMismatch,
+            detail: 3,
+        };
+
+        let message = error.to_string();
+        assert_eq!(
+            message,
+            "parse error: unexpected token at parser position 2"
+        );
+        assert!(!message.contains("LL(1)"));
+        assert!(!message.contains("TerminalMismatch"));
+        assert!(!message.contains("(3)"));
+    }
+
+    #[test]
+    fn mirror_stack_changes_reverses_order_and_swaps_push_pop() {
+        let seq = [encode_push(3), encode_pop(1), encode_push(0)];
+
+        let mirrored = mirror_stack_changes(&seq);
+
+        assert_eq!(mirrored, vec![encode_pop(0), encode_push(1), encode_pop(3)]);
+        assert_eq!(mirror_stack_changes(&mirrored), seq);
+    }
+
+    #[test]
+    fn reversed_tables_mirror_transposed_pairs() {
+        let mut tables = PrecomputedParseTables::new(3, 2);
+        tables.set_sc_for_pair(1, 2, &[encode_pop(0), encode_push(1)]);
+        tables.set_pp_for_pair(1, 2, &[0, 1]);
+        tables.set_sc_for_pair(2, 2, &[encode_push(0)]);
+        tables.finalize_bit_widths(1);
+
+        let rev = tables.reverse();
+
+        assert_eq!(rev.n_nonterminals, 0);
+        assert!(rev.ll1_predict.is_empty());
+        assert_eq!(
+            rev.test_cpu_stack_change_stream(&[2, 1]),
+            vec![encode_pop(1), encode_push(0)]
+        );
+        assert_eq!(rev.test_cpu_partial_parse_stream(&[2, 1]), vec![1, 0]);
+        assert_eq!(rev.test_cpu_stack_change_stream(&[1, 2]), Vec::<u32>::new());
+        assert_eq!(
+            rev.test_cpu_stack_change_stream(&[2, 2]),
+            vec![encode_pop(0)]
+        );
+        assert_eq!(rev.sc_symbol_bits, tables.sc_symbol_bits);
+    }
+
+    #[test]
+    fn reversed_stream_is_mirror_of_forward_stream() {
+        let n_kinds = crate::lexer::tables::tokens::N_KINDS;
+        let tables = build_mvp_precomputed_tables(n_kinds, vec![0]);
+        let rev = tables.reverse();
+        let mut kinds = vec![
+            0,
+            TokenKind::GroupLParen as u32,
+            TokenKind::ArrayLBracket as u32,
+            TokenKind::Ident as u32,
+            TokenKind::ArrayRBracket as u32,
+            TokenKind::GroupRParen as u32,
+            0,
+        ];
+
+        let forward = tables.test_cpu_stack_change_stream(&kinds);
+        kinds.reverse();
+        let backward = rev.test_cpu_stack_change_stream(&kinds);
+
+        assert_eq!(backward, mirror_stack_changes(&forward));
+    }
+
+    #[test]
+    fn ll1_parse_table_error_display_avoids_internal_detail() {
+        let error = Ll1ParseError {
+            pos: 0,
+            code: Ll1ParseErrorCode::TablesUnavailable,
+            detail: 0,
+        };
+
+        let message = error.to_string();
+        assert_eq!(message, "parser table error: parser tables are unavailable");
+        assert!(!message.contains("LL(1)"));
+        assert!(!message.contains("TablesUnavailable"));
+        assert!(!message.contains("(0)"));
+    }
+
+    #[test]
+    fn allows_empty_input_round_trips_in_both_formats() {
+        let mut tables = tiny_ident_semicolon_table();
+        let bare = tables.to_bin_bytes_as(FormatVersion::V1);
+        assert!(
+            !PrecomputedParseTables::load_bin_bytes(&bare)
+                .unwrap()
+                .allows_empty_input
+        );
+
+        tables.allows_empty_input = true;
+        for version in [FormatVersion::V1, FormatVersion::V2] {
+            let bytes = tables.to_bin_bytes_as(version);
+            let loaded = PrecomputedParseTables::load_bin_bytes(&bytes).unwrap();
+            assert!(loaded.allows_empty_input, "{version:?}");
+            assert!(loaded.reverse().allows_empty_input, "{version:?}");
+            assert_eq!(loaded.to_bin_bytes_as(version), bytes, "{version:?}");
+        }
+    }
+
+    #[test]
+    fn sync_sets_round_trip_and_are_checked_on_load() {
+        let mut tables = tiny_ident_semicolon_table();
+        let bare = PrecomputedParseTables::load_bin_bytes(&tables.to_bin_bytes()).unwrap();
+        assert!(bare.sync_sets.is_empty());
+        assert_eq!(bare.sync_set(0), &[] as &[u32]);
+
+        tables.sync_sets = vec![vec![1, 3]];
+        for version in [FormatVersion::V1, FormatVersion::V2] {
+            let bytes = tables.to_bin_bytes_as(version);
+            let loaded = PrecomputedParseTables::load_bin_bytes(&bytes).unwrap();
+            assert_eq!(loaded.sync_sets, tables.sync_sets, "{version:?}");
+            assert_eq!(loaded.sync_set(0), &[1, 3], "{version:?}");
+            assert_eq!(loaded.to_bin_bytes_as(version), bytes, "{version:?}");
+        }
+
+        tables.sync_sets = vec![vec![4]];
+        let err = PrecomputedParseTables::load_bin_bytes(&tables.to_bin_bytes()).unwrap_err();
+        assert!(err.contains("unknown kind 4"), "{err}");
+        tables.sync_sets = vec![Vec::new(), Vec::new()];
+        let err = PrecomputedParseTables::load_bin_bytes(&tables.to_bin_bytes()).unwrap_err();
+        assert!(err.contains("sync_sets has 2 entries"), "{err}");
+    }
+
+    #[test]
+    fn grammar_names_section_is_optional_on_load() {
+        let mut tables = tiny_ident_semicolon_table();
+        let bare_bytes = tables.to_bin_bytes();
+        let bare = PrecomputedParseTables::load_bin_bytes(&bare_bytes).unwrap();
+        assert!(bare.prod_names.is_empty() && bare.nonterminal_names.is_empty());
+        assert_eq!(bare.production_name(0), "prod0");
+        assert_eq!(bare.describe_production(0), "prod0 -> 'Ident' 'White'");
+
+        tables.prod_names = vec!["stmt".into()];
+        tables.prod_lhs = vec![0];
+        tables.nonterminal_names = vec!["item".into()];
+        let named_bytes = tables.to_bin_bytes();
+        // In version 1 the V3 payload is unchanged, so older readers stop
+        // before the names.
+        let named_v1 = tables.to_bin_bytes_as(FormatVersion::V1);
+        assert!(
+            named_v1.starts_with(&tiny_ident_semicolon_table().to_bin_bytes_as(FormatVersion::V1))
+        );
+        let named_v1 = PrecomputedParseTables::load_bin_bytes(&named_v1).unwrap();
+        assert_eq!(named_v1.nonterminal_names, tables.nonterminal_names);
+        let named = PrecomputedParseTables::load_bin_bytes(&named_bytes).unwrap();
+        assert_eq!(named.prod_names, tables.prod_names);
+        assert_eq!(named.prod_lhs, tables.prod_lhs);
+        assert_eq!(named.nonterminal_names, tables.nonterminal_names);
+        assert_eq!(named.production_name(0), "stmt");
+        assert_eq!(named.explain_productions(&[0]), ["item -> 'Ident' 'White'"]);
+        assert_eq!(named.to_bin_bytes(), named_bytes);
+
+        tables.prod_lhs = vec![1];
+        assert!(PrecomputedParseTables::load_bin_bytes(&tables.to_bin_bytes()).is_err());
+        let mut unknown = tiny_ident_semicolon_table().to_bin_bytes_as(FormatVersion::V1);
+        unknown.extend_from_slice(b"LXPRXXXX");
+        assert!(PrecomputedParseTables::load_bin_bytes(&unknown).is_err());
+    }
+
+    #[test]
+    fn precedence_section_round_trips_and_checks_ladder_nonterminals() {
+        let mut tables = tiny_ident_semicolon_table();
+        let bare_bytes = tables.to_bin_bytes_as(FormatVersion::V1);
+        tables.operator_precedence = vec![
+            OperatorPrecedence {
+                kind: TokenKind::Assign as u32,
+                precedence: 1,
+                right_assoc: true,
+            },
+            OperatorPrecedence {
+                kind: TokenKind::Star as u32,
+                precedence: 3,
+                right_assoc: false,
+            },
+        ];
+        tables.ladder_nonterminals = vec![0];
+        let bytes = tables.to_bin_bytes_as(FormatVersion::V1);
+        assert!(bytes.starts_with(&bare_bytes));
+        let loaded = PrecomputedParseTables::load_bin_bytes(&bytes).unwrap();
+        assert_eq!(loaded.operator_precedence, tables.operator_precedence);
+        let loaded = PrecomputedParseTables::load_bin_bytes(&tables.to_bin_bytes()).unwrap();
+        assert_eq!(loaded.operator_precedence, tables.operator_precedence);
+        assert_eq!(loaded.ladder_nonterminals, [0]);
+        assert_eq!(
+            loaded.operator_precedence(TokenKind::Star as u32),
+            Some(tables.operator_precedence[1])
+        );
+        assert_eq!(loaded.operator_precedence(TokenKind::Ident as u32), None);
+        assert_eq!(
+            loaded.reverse().operator_precedence,
+            tables.operator_precedence
+        );
+
+        tables.ladder_nonterminals = vec![1];
+        assert!(PrecomputedParseTables::load_bin_bytes(&tables.to_bin_bytes()).is_err());
+    }
+
+    #[test]
+    fn sentinel_section_round_trips_and_must_have_grid_cells() {
+        let mut tables = tiny_ident_semicolon_table();
+        let loaded = PrecomputedParseTables::load_bin_bytes(&tables.to_bin_bytes()).unwrap();
+        assert_eq!(loaded.sentinel_kind, 0);
+        assert!(loaded.start_sentinel);
+
+        tables.sentinel_kind = 3;
+        tables.start_sentinel = false;
+        let loaded = PrecomputedParseTables::load_bin_bytes(&tables.to_bin_bytes()).unwrap();
+        assert_eq!(loaded.sentinel_kind, 3);
+        assert!(!loaded.start_sentinel);
+        assert_eq!(loaded.wrap_input(&[1, 2]).unwrap(), [1, 2, 3]);
+
+        tables.sentinel_kind = tables.n_kinds;
+        assert!(PrecomputedParseTables::load_bin_bytes(&tables.to_bin_bytes()).is_err());
+    }
+
+    #[test]
+    fn checked_in_v1_tables_round_trip_through_v2() {
+        let v1 = include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tables/parse_tables.bin"
+        ));
+        let tables = PrecomputedParseTables::load_bin_bytes(v1).unwrap();
+        let v2 = tables.to_bin_bytes();
+        assert!(v2.starts_with(&PARSER_MAGIC));
+        let reloaded = PrecomputedParseTables::load_bin_bytes(&v2).unwrap();
+        // The version-1 writer reproduces the checked-in blob byte for byte.
+        assert_eq!(reloaded.to_bin_bytes_as(FormatVersion::V1), v1);
+        assert_eq!(reloaded.to_bin_bytes(), v2);
+    }
+
+    #[test]
+    fn v2_tables_skip_unknown_sections_and_need_the_core_ones() {
+        let tables = tiny_ident_semicolon_table();
+        let v2 = tables.to_bin_bytes();
+        let container = Container::parse(
+            &v2,
+            &PARSER_MAGIC,
+            &[
+                HEAD_TAG,
+                SC_TAG,
+                PP_TAG,
+                ARITY_TAG,
+                LL1_TAG,
+                *SENTINEL_SECTION_TAG,
+            ],
+        )
+        .unwrap();
+        let mut sections = container.sections().to_vec();
+
+        let future = format::encode(
+            &PARSER_MAGIC,
+            &[sections.clone(), vec![Section::new(*b"LXPRFUTR", b"later")]].concat(),
+        );
+        let loaded = PrecomputedParseTables::load_bin_bytes(&future).unwrap();
+        assert_eq!(loaded.to_bin_bytes(), v2);
+
+        let critical = format::encode(
+            &PARSER_MAGIC,
+            &[
+                sections.clone(),
+                vec![Section::critical(*b"LXPRFUTR", b"later")],
+            ]
+            .concat(),
+        );
+        let err = PrecomputedParseTables::load_bin_bytes(&critical).unwrap_err();
+        assert!(err.contains("unknown critical section LXPRFUTR"), "{err}");
+
+        sections.retain(|section| section.tag != LL1_TAG);
+        let err = PrecomputedParseTables::load_bin_bytes(&format::encode(&PARSER_MAGIC, &sections))
+            .unwrap_err();
+        assert!(err.contains("missing section LXPRLL1P"), "{err}");
+    }
+}
+
+// ---------- Generator seed table ----------
+
+/// Builds a minimal, correctly shaped table set for bracket push/pop handling.
+///
+/// This is used by table-generation tooling as an initial data shape, not as a
+/// runtime parser fallback.
+/// - Stack symbols: 0=Paren, 1=Bracket
+/// - Partial parse: empty everywhere (we will fill after we wire real grammar).
+/// - Production arity: uses `prod_arity` passed in (possibly from a grammar scan).
+pub fn build_mvp_precomputed_tables(n_kinds: u32, prod_arity: Vec<u32>) -> PrecomputedParseTables {
+    let n_productions = prod_arity.len() as u32;
+    let mut t = PrecomputedParseTables::new(n_kinds, n_productions);
+    t.prod_arity = prod_arity;
+
+    const SYM_PAREN: u32 = 0;
+    const SYM_BRACK: u32 = 1;
+
+    // For every prev kind, set sequences on open/close delimiters.
+    for prev in 0..n_kinds {
+        // Opens = push
+        t.set_sc_for_pair(
+            prev,
+            TokenKind::GroupLParen as u32,
+            &[encode_push(SYM_PAREN)],
+        );
+        t.set_sc_for_pair(
+            prev,
+            TokenKind::CallLParen as u32,
+            &[encode_push(SYM_PAREN)],
+        );
+        t.set_sc_for_pair(
+            prev,
+            TokenKind::ParamLParen as u32,
+            &[encode_push(SYM_PAREN)],
+        );
+        t.set_sc_for_pair(
+            prev,
+            TokenKind::ArrayLBracket as u32,
+            &[encode_push(SYM_BRACK)],
+        );
+        t.set_sc_for_pair(
+            prev,
+            TokenKind::IndexLBracket as u32,
+            &[encode_push(SYM_BRACK)],
+        );
+        t.set_sc_for_pair(
+            prev,
+            TokenKind::TypeArrayLBracket as u32,
+            &[encode_push(SYM_BRACK)],
+        );
+
+        // Closes = typed pops by physical delimiter. Retagged closes are the normal path;
+        // raw closes recover malformed token streams.
+        t.set_sc_for_pair(prev, TokenKind::RParen as u32, &[encode_pop(SYM_PAREN)]);
+        t.set_sc_for_pair(
+            prev,
+            TokenKind::GroupRParen as u32,
+            &[encode_pop(SYM_PAREN)],
+        );
+        t.set_sc_for_pair(prev, TokenKind::CallRParen as u32, &[encode_pop(SYM_PAREN)]);
+        t.set_sc_for_pair(
+            prev,
+            TokenKind::ParamRParen as u32,
+            &[encode_pop(SYM_PAREN)],
+        );
+        t.set_sc_for_pair(prev, TokenKind::RBracket as u32, &[encode_pop(SYM_BRACK)]);
+        t.set_sc_for_pair(
+            prev,
+            TokenKind::ArrayRBracket as u32,
+            &[encode_pop(SYM_BRACK)],
+        );
+        t.set_sc_for_pair(
+            prev,
+            TokenKind::IndexRBracket as u32,
+            &[encode_pop(SYM_BRACK)],
+        );
+        t.set_sc_for_pair(
+            prev,
+            TokenKind::TypeArrayRBracket as u32,
+            &[encode_pop(SYM_BRACK)],
+        );
+
+        // Empty partial parse everywhere (MVP).
+        t.set_pp_for_pair(prev, TokenKind::GroupLParen as u32, &[]);
+        t.set_pp_for_pair(prev, TokenKind::CallLParen as u32, &[]);
+        t.set_pp_for_pair(prev, TokenKind::ParamLParen as u32, &[]);
+        t.set_pp_for_pair(prev, TokenKind::ArrayLBracket as u32, &[]);
+        t.set_pp_for_pair(prev, TokenKind::IndexLBracket as u32, &[]);
+        t.set_pp_for_pair(prev, TokenKind::TypeArrayLBracket as u32, &[]);
+        t.set_pp_for_pair(prev, TokenKind::RParen as u32, &[]);
+        t.set_pp_for_pair(prev, TokenKind::GroupRParen as u32, &[]);
+        t.set_pp_for_pair(prev, TokenKind::CallRParen as u32, &[]);
+        t.set_pp_for_pair(prev, TokenKind::ParamRParen as u32, &[]);
+        t.set_pp_for_pair(prev, TokenKind::RBracket as u32, &[]);
+        t.set_pp_for_pair(prev, TokenKind::ArrayRBracket as u32, &[]);
+        t.set_pp_for_pair(prev, TokenKind::IndexRBracket as u32, &[]);
+        t.set_pp_for_pair(prev, TokenKind::TypeArrayRBracket as u32, &[]);
+    }
+
+    // Bit widths (symbol ids go up to 1 in MVP)
+    t.finalize_bit_widths(1);
+    t
+}
+
+impl PrecomputedParseTables {
+    /// Produce a contiguous (n_kinds*n_kinds) array of ActionHeader as bytes,
+    /// matching what `llp_pairs.slang` reads.
+    pub fn to_action_header_grid_bytes(&self) -> Vec<u8> {
+        use std::mem::size_of;
+        let n = self.n_kinds as usize;
+        let cell_count = n * n;
+        let sz = size_of::<crate::parser::buffers::ActionHeader>();
+        let mut out = vec![0u8; cell_count * sz];
+
+        for prev in 0..self.n_kinds {
+            for this in 0..self.n_kinds {
+                let idx2d = (prev as usize) * n + (this as usize);
+
+                // Stack-change seq for (prev,this)
+                let sc_off = self.sc_off[idx2d] as usize;
+                let sc_len = self.sc_len[idx2d] as usize;
+                let sc = &self.sc_superseq[sc_off..sc_off + sc_len];
+
+                // Count pushes/pops: push=odd, pop=even (encode_push/encode_pop)
+                let mut push_len = 0u32;
+                let mut pop_count = 0u32;
+                for &code in sc {
+                    if (code & 1) == 1 {
+                        push_len += 1;
+                    } else {
+                        pop_count += 1;
+                    }
+                }
+
+                // Partial-parse length is emit_len for this pair
+                let emit_len = self.pp_len[idx2d];
+
+                let hdr = crate::parser::buffers::ActionHeader {
+                    push_len,
+                    emit_len,
+                    pop_tag: 0, // typed matching comes from the packed streams later
+                    pop_count,
+                };
+
+                let off = idx2d * sz;
+                out[off..off + 4].copy_from_slice(&hdr.push_len.to_le_bytes());
+                out[off + 4..off + 8].copy_from_slice(&hdr.emit_len.to_le_bytes());
+                out[off + 8..off + 12].copy_from_slice(&hdr.pop_tag.to_le_bytes());
+                out[off + 12..off + 16].copy_from_slice(&hdr.pop_count.to_le_bytes());
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,409 @@
+//! Compressed GPU upload layout for the pair grids.
+//!
+//! The four `n_kinds * n_kinds` grids (`sc_off`, `sc_len`, `pp_off`, `pp_len`)
+//! are mostly empty or repeat a handful of sequences. Cells whose stack-change
+//! and partial-parse sequences are identical share one palette entry
+//! `[sc_off, sc_len, pp_off, pp_len]`, and each cell stores only a `u16`
+//! palette index, two cells per word. Decoding a cell costs one extra load.
+//! The uploaded supersequences keep each distinct sequence once.
+//!
+//! Blob layout (u32 words):
+//! `[ sc_superseq | pp_superseq | palette (4 words/entry) | cell indices ]`
+//!
+//! Tables that went through [`PrecomputedParseTables::optimize_layout`] are
+//! uploaded with their supersequences as laid out, give the hot cells the
+//! first palette entries, and store sequences of up to
+//! [`TableLayout::inline_max_len`](super::TableLayout::inline_max_len)
+//! 16-bit values in the entry itself: the length word carries
+//! [`INLINE_SEQUENCE`] and the offset word holds the values, first in the
+//! low half. The shader then reads such a cell without touching a
+//! supersequence.
+
+use std::collections::HashMap;
+
+use super::PrecomputedParseTables;
+
+/// Words per palette entry: `sc_off`, `sc_len`, `pp_off`, `pp_len`.
+pub const PALETTE_ENTRY_WORDS: usize = 4;
+
+/// Largest palette addressable by the packed `u16` cell indices.
+pub const MAX_PALETTE_ENTRIES: usize = 1 << 16;
+
+/// Length-word tag of a sequence stored inline in its palette entry.
+pub const INLINE_SEQUENCE: u32 = 1 << 31;
+
+/// Parse tables in the layout `pack_varlen` reads from `tables_blob`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseTablesGpuBlob {
+    pub words: Vec<u32>,
+    /// Offsets in u32 words inside `words`, recorded in `PackParams`.
+    pub sc_superseq_off: u32,
+    pub pp_superseq_off: u32,
+    pub cell_palette_off: u32,
+    pub cell_index_off: u32,
+    /// Number of distinct cell entries in the palette.
+    pub palette_len: u32,
+}
+
+impl ParseTablesGpuBlob {
+    /// Decodes one cell to `[sc_off, sc_len, pp_off, pp_len]`, mirroring the
+    /// shader lookup.
+    pub fn cell(&self, idx2d: usize) -> [u32; PALETTE_ENTRY_WORDS] {
+        let word = self.words[self.cell_index_off as usize + idx2d / 2];
+        let entry = ((word >> ((idx2d % 2) * 16)) & 0xffff) as usize;
+        let base = self.cell_palette_off as usize + entry * PALETTE_ENTRY_WORDS;
+        self.words[base..base + PALETTE_ENTRY_WORDS]
+            .try_into()
+            .expect("palette entry is four words")
+    }
+
+    /// Stack-change sequence of one cell, decoded like the shader does.
+    pub fn sc_sequence(&self, idx2d: usize) -> Vec<u32> {
+        let [off, len, _, _] = self.cell(idx2d);
+        self.sequence(self.sc_superseq_off, off, len)
+    }
+
+    /// Partial-parse sequence of one cell, decoded like the shader does.
+    pub fn pp_sequence(&self, idx2d: usize) -> Vec<u32> {
+        let [_, _, off, len] = self.cell(idx2d);
+        self.sequence(self.pp_superseq_off, off, len)
+    }
+
+    fn sequence(&self, superseq_off: u32, off: u32, len: u32) -> Vec<u32> {
+        if len & INLINE_SEQUENCE != 0 {
+            let len = len & !INLINE_SEQUENCE;
+            return (0..len).map(|lane| (off >> (lane * 16)) & 0xffff).collect();
+        }
+        let base = (superseq_off + off) as usize;
+        self.words[base..base + len as usize].to_vec()
+    }
+
+    /// Upload size in bytes.
+    pub fn byte_len(&self) -> usize {
+        self.words.len() * 4
+    }
+}
+
+impl PrecomputedParseTables {
+    /// Builds the compressed blob uploaded to `pack.tables_blob`.
+    ///
+    /// Cells are deduplicated by sequence contents rather than raw offsets, so
+    /// tables that append one copy per cell still share palette entries.
+    /// Unoptimized tables re-emit only the distinct sequences the palette
+    /// references into the uploaded supersequences; optimized tables upload
+    /// theirs as laid out and inline short sequences (see the module docs).
+    pub fn to_gpu_blob(&self) -> ParseTablesGpuBlob {
+        let cells = self.sc_off.len();
+        let mut sc_superseq = SequencePool::default();
+        let mut pp_superseq = SequencePool::default();
+        if self.optimized {
+            sc_superseq.words = self.sc_superseq.clone();
+            pp_superseq.words = self.pp_superseq.clone();
+        }
+        let inline_max_len = if self.optimized {
+            self.layout.inline_max_len
+        } else {
+            0
+        };
+        let mut palette: Vec<u32> = Vec::new();
+        let mut seen: HashMap<(&[u32], &[u32]), u16> = HashMap::new();
+        let mut indices: Vec<u16> = vec![0; cells];
+        let hot = self.layout.hot_cells.iter().map(|&idx| idx as usize);
+        let order = hot.chain(0..cells).filter(|&idx| idx < cells);
+        for idx2d in order {
+            let (sc, pp) = (self.sc_seq(idx2d), self.pp_seq(idx2d));
+            let entry = *seen.entry((sc, pp)).or_insert_with(|| {
+                let entry = palette.len() / PALETTE_ENTRY_WORDS;
+                assert!(
+                    entry < MAX_PALETTE_ENTRIES,
+                    "parse-table palette exceeds {MAX_PALETTE_ENTRIES} entries"
+                );
+                let [sc_off, sc_len] = if self.optimized {
+                    inline_or(sc, inline_max_len, self.sc_off[idx2d])
+                } else {
+                    [sc_superseq.intern(sc), sc.len() as u32]
+                };
+                let [pp_off, pp_len] = if self.optimized {
+                    inline_or(pp, inline_max_len, self.pp_off[idx2d])
+                } else {
+                    [pp_superseq.intern(pp), pp.len() as u32]
+                };
+                palette.extend_from_slice(&[sc_off, sc_len, pp_off, pp_len]);
+                entry as u16
+            });
+            indices[idx2d] = entry;
+        }
+
+        let mut words = Vec::with_capacity(
+            sc_superseq.words.len() + pp_superseq.words.len() + palette.len() + cells.div_ceil(2),
+        );
+        let sc_superseq_off = words.len() as u32;
+        words.extend_from_slice(&sc_superseq.words);
+        let pp_superseq_off = words.len() as u32;
+        words.extend_from_slice(&pp_superseq.words);
+        let cell_palette_off = words.len() as u32;
+        words.extend_from_slice(&palette);
+        let cell_index_off = words.len() as u32;
+        words.extend(indices.chunks(2).map(|pair| {
+            let hi = pair.get(1).copied().unwrap_or(0);
+            u32::from(pair[0]) | (u32::from(hi) << 16)
+        }));
+
+        ParseTablesGpuBlob {
+            words,
+            sc_superseq_off,
+            pp_superseq_off,
+            cell_palette_off,
+            cell_index_off,
+            palette_len: (palette.len() / PALETTE_ENTRY_WORDS) as u32,
+        }
+    }
+
+    /// Test-only host mirror of `pack_varlen`: the stack-change and
+    /// partial-parse streams of a wrapped token-kind stream, decoded from
+    /// `blob` rather than the grids. Covers plain pairs only, like
+    /// [`Self::test_cpu_stack_change_stream`].
+    pub fn test_cpu_blob_streams(
+        &self,
+        blob: &ParseTablesGpuBlob,
+        token_kinds: &[u32],
+    ) -> (Vec<u32>, Vec<u32>) {
+        let (mut sc, mut pp) = (Vec::new(), Vec::new());
+        for pair in token_kinds.windows(2) {
+            let (prev, this) = (self.grid_kind(pair[0]), self.grid_kind(pair[1]));
+            if prev < self.n_kinds && this < self.n_kinds {
+                let idx2d = self.cell_index(prev, this);
+                sc.extend(blob.sc_sequence(idx2d));
+                pp.extend(blob.pp_sequence(idx2d));
+            }
+        }
+        (sc, pp)
+    }
+
+    /// Byte size of the uncompressed layout: both supersequences plus all four
+    /// grids verbatim. Used to report compression ratios.
+    pub fn uncompressed_gpu_blob_bytes(&self) -> usize {
+        4 * (self.sc_superseq.len()
+            + self.pp_superseq.len()
+            + self.sc_off.len()
+            + self.sc_len.len()
+            + self.pp_off.len()
+            + self.pp_len.len())
+    }
+
+    pub(super) fn sc_seq(&self, idx2d: usize) -> &[u32] {
+        let off = self.sc_off[idx2d] as usize;
+        &self.sc_superseq[off..off + self.sc_len[idx2d] as usize]
+    }
+
+    pub(super) fn pp_seq(&self, idx2d: usize) -> &[u32] {
+        let off = self.pp_off[idx2d] as usize;
+        &self.pp_superseq[off..off + self.pp_len[idx2d] as usize]
+    }
+}
+
+/// `[off, len]` palette words for `seq`: the values themselves when at most
+/// `inline_max_len` of them fit in 16 bits each, otherwise `superseq_off`.
+fn inline_or(seq: &[u32], inline_max_len: u32, superseq_off: u32) -> [u32; 2] {
+    let len = seq.len() as u32;
+    if (1..=inline_max_len).contains(&len) && seq.iter().all(|&value| value <= 0xffff) {
+        let packed = seq
+            .iter()
+            .enumerate()
+            .fold(0, |word, (lane, &value)| word | (value << (lane * 16)));
+        [packed, len | INLINE_SEQUENCE]
+    } else {
+        [superseq_off, len]
+    }
+}
+
+/// Supersequence holding each distinct sequence once.
+#[derive(Default)]
+struct SequencePool<'a> {
+    words: Vec<u32>,
+    offsets: HashMap<&'a [u32], u32>,
+}
+
+impl<'a> SequencePool<'a> {
+    /// Offset of `seq` in the pool, appending it on first use.
+    fn intern(&mut self, seq: &'a [u32]) -> u32 {
+        *self.offsets.entry(seq).or_insert_with(|| {
+            let off = self.words.len() as u32;
+            self.words.extend_from_slice(seq);
+            off
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{
+        dev::generator::{PROFILES, SourceGenConfig, gen_source},
+        lexer::{
+            tables::tokens::{N_KINDS, TokenKind},
+            test_cpu::lex_on_test_cpu,
+        },
+        parser::{
+            retag::retag_open_delimiters,
+            tables::{MAX_INLINE_LEN, build_mvp_precomputed_tables},
+        },
+        tables::FormatVersion,
+    };
+
+    fn assert_cells_decode_to_same_sequences(tables: &PrecomputedParseTables) {
+        let blob = tables.to_gpu_blob();
+        for idx2d in 0..tables.sc_off.len() {
+            assert_eq!(
+                blob.sc_sequence(idx2d),
+                tables.sc_seq(idx2d),
+                "sc cell {idx2d}"
+            );
+            assert_eq!(
+                blob.pp_sequence(idx2d),
+                tables.pp_seq(idx2d),
+                "pp cell {idx2d}"
+            );
+        }
+    }
+
+    fn generated_tables() -> PrecomputedParseTables {
+        PrecomputedParseTables::load_bin_bytes(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tables/parse_tables.bin"
+        )))
+        .expect("load generated parse tables")
+    }
+
+    /// Retagged kept kinds of the lexer cases and a few generated sources,
+    /// the programs the fuzz corpus is seeded from.
+    fn corpus_kinds() -> Vec<Vec<u32>> {
+        let cases = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/cases/lexer");
+        let mut sources: Vec<String> = std::fs::read_dir(cases)
+            .expect("lexer cases")
+            .map(|entry| entry.expect("case entry").path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "lani"))
+            .map(|path| std::fs::read_to_string(path).expect("read case"))
+            .collect();
+        let mut rng = StdRng::seed_from_u64(930);
+        for profile in PROFILES {
+            let config = SourceGenConfig::from_profile(profile).expect("PROFILES names a preset");
+            sources.push(gen_source(
+                &mut rng,
+                &SourceGenConfig {
+                    target_len: 2000,
+                    ..config
+                },
+            ));
+        }
+        sources
+            .iter()
+            .filter_map(|source| lex_on_test_cpu(source).ok())
+            .map(|tokens| {
+                let kinds: Vec<TokenKind> = tokens.iter().map(|token| token.kind).collect();
+                retag_open_delimiters(&kinds)
+                    .into_iter()
+                    .map(|kind| kind as u32)
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mvp_blob_shrinks_at_least_four_times() {
+        let tables = build_mvp_precomputed_tables(N_KINDS, vec![0]);
+        let blob = tables.to_gpu_blob();
+        assert_cells_decode_to_same_sequences(&tables);
+        assert!(
+            blob.byte_len() * 4 <= tables.uncompressed_gpu_blob_bytes(),
+            "{} compressed bytes vs {} uncompressed",
+            blob.byte_len(),
+            tables.uncompressed_gpu_blob_bytes()
+        );
+    }
+
+    #[test]
+    fn generated_tables_decode_every_cell() {
+        let tables = PrecomputedParseTables::load_bin_bytes(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tables/parse_tables.bin"
+        )))
+        .expect("load generated parse tables");
+        assert_cells_decode_to_same_sequences(&tables);
+        assert!(tables.to_gpu_blob().byte_len() * 4 <= tables.uncompressed_gpu_blob_bytes());
+    }
+
+    #[test]
+    fn odd_cell_counts_pack_the_last_index_low() {
+        let mut tables = PrecomputedParseTables::new(3, 1);
+        tables.set_sc_for_pair(2, 2, &[1]);
+        let blob = tables.to_gpu_blob();
+        assert_eq!(blob.palette_len, 2);
+        assert_eq!(blob.words.len() as u32, blob.cell_index_off + 5);
+        assert_eq!(blob.cell(8), [0, 1, 0, 0]);
+        assert_eq!(blob.cell(7), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn optimized_blob_inlines_short_sequences_and_puts_hot_cells_first() {
+        let corpus = corpus_kinds();
+        let mut optimized = generated_tables();
+        optimized.optimize_layout(&corpus);
+        assert_cells_decode_to_same_sequences(&optimized);
+
+        let blob = optimized.to_gpu_blob();
+        let hottest = optimized.layout.hot_cells[0] as usize;
+        let word = blob.words[blob.cell_index_off as usize + hottest / 2];
+        assert_eq!((word >> ((hottest % 2) * 16)) & 0xffff, 0);
+        let inline = (0..optimized.sc_off.len())
+            .filter(|&idx2d| blob.cell(idx2d)[1] & INLINE_SEQUENCE != 0)
+            .count();
+        assert!(inline > 0, "no stack-change sequence was inlined");
+        for idx2d in 0..optimized.sc_off.len() {
+            let [_, sc_len, _, pp_len] = blob.cell(idx2d);
+            for (len, raw) in [
+                (sc_len, optimized.sc_len[idx2d]),
+                (pp_len, optimized.pp_len[idx2d]),
+            ] {
+                assert_eq!(len & !INLINE_SEQUENCE, raw, "cell {idx2d}");
+                assert!(len & INLINE_SEQUENCE == 0 || (1..=MAX_INLINE_LEN).contains(&raw));
+            }
+        }
+    }
+
+    #[test]
+    fn optimized_and_unoptimized_blobs_pack_the_same_streams() {
+        let tables = generated_tables();
+        let corpus = corpus_kinds();
+        assert!(corpus.len() > PROFILES.len(), "lexer cases are missing");
+        let mut optimized = tables.clone();
+        optimized.optimize_layout(&corpus);
+        for version in [FormatVersion::V1, FormatVersion::V2] {
+            let bytes = optimized.to_bin_bytes_as(version);
+            let reloaded = PrecomputedParseTables::load_bin_bytes(&bytes).expect("tables load");
+            assert!(reloaded.optimized, "{version:?}");
+            assert_eq!(reloaded.layout, optimized.layout, "{version:?}");
+            assert_eq!(
+                reloaded.to_gpu_blob(),
+                optimized.to_gpu_blob(),
+                "{version:?}"
+            );
+        }
+
+        let (blob, optimized_blob) = (tables.to_gpu_blob(), optimized.to_gpu_blob());
+        for kinds in &corpus {
+            let wrapped = tables.wrap_input(kinds).expect("wrap corpus kinds");
+            let expected = (
+                tables.test_cpu_stack_change_stream(&wrapped),
+                tables.test_cpu_partial_parse_stream(&wrapped),
+            );
+            assert_eq!(tables.test_cpu_blob_streams(&blob, &wrapped), expected);
+            assert_eq!(
+                optimized.test_cpu_blob_streams(&optimized_blob, &wrapped),
+                expected
+            );
+        }
+    }
+}
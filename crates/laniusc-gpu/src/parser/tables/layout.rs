@@ -0,0 +1,265 @@
+//! Offline layout pass that tunes the pair grids for `pack_varlen`.
+//!
+//! [`PrecomputedParseTables::optimize_layout`] rewrites both supersequences
+//! so that each distinct sequence appears once, sequences contained in
+//! another share its words, and the rest are placed hottest first with
+//! suffix/prefix overlap. Cell heat comes from a sample of token-kind
+//! streams; the hottest cells are recorded in [`TableLayout::hot_cells`] so
+//! [`PrecomputedParseTables::to_gpu_blob`] can give them the first palette
+//! entries, and short sequences are stored inline in their palette entry
+//! (see [`super::INLINE_SEQUENCE`]).
+//!
+//! The pass only moves sequences around: every cell decodes to the same
+//! words before and after, so the CPU streams are unchanged.
+
+use std::collections::HashMap;
+
+use super::PrecomputedParseTables;
+
+/// Longest sequence an optimized blob stores inline in its palette entry;
+/// two 16-bit values fill the offset word.
+pub const MAX_INLINE_LEN: u32 = 2;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Layout metadata recorded by [`PrecomputedParseTables::optimize_layout`].
+pub struct TableLayout {
+    /// Grid cells seen in the sample, hottest first.
+    pub hot_cells: Vec<u32>,
+    /// Sequences up to this length are inlined in the GPU palette; `0`
+    /// disables inlining.
+    pub inline_max_len: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// What [`PrecomputedParseTables::optimize_layout`] changed.
+pub struct LayoutReport {
+    /// Token pairs looked up in the sample streams.
+    pub sampled_pairs: u64,
+    /// Distinct cells among those pairs.
+    pub hot_cells: usize,
+    /// Sampled pairs whose cell holds sequences of at most
+    /// [`MAX_INLINE_LEN`] entries, so the lookup needs no supersequence read.
+    pub inline_pairs: u64,
+    pub sc_words_before: usize,
+    pub sc_words_after: usize,
+    pub pp_words_before: usize,
+    pub pp_words_after: usize,
+}
+
+impl std::fmt::Display for LayoutReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pct = |part: u64, whole: u64| {
+            if whole == 0 {
+                0.0
+            } else {
+                100.0 * part as f64 / whole as f64
+            }
+        };
+        write!(
+            f,
+            "{} sampled pairs over {} hot cells ({:.1}% inline); sc_superseq {} -> {} words, \
+             pp_superseq {} -> {} words",
+            self.sampled_pairs,
+            self.hot_cells,
+            pct(self.inline_pairs, self.sampled_pairs),
+            self.sc_words_before,
+            self.sc_words_after,
+            self.pp_words_before,
+            self.pp_words_after,
+        )
+    }
+}
+
+impl PrecomputedParseTables {
+    /// Rewrites the supersequences and records a cell order for the GPU
+    /// blob, using `samples` (unwrapped, retagged lexer-kind streams) to
+    /// find the hot cells.
+    ///
+    /// Run after [`crate::parser::kindmap::renumber_kinds`]: renumbering
+    /// rebuilds the grids and drops the layout.
+    pub fn optimize_layout(&mut self, samples: &[Vec<u32>]) -> LayoutReport {
+        let mut heat = vec![0u64; self.sc_off.len()];
+        let mut sampled_pairs = 0;
+        for sample in samples {
+            let wrapped = self.wrap_input(sample).unwrap_or_else(|_| sample.clone());
+            for pair in wrapped.windows(2) {
+                let (prev, this) = (self.grid_kind(pair[0]), self.grid_kind(pair[1]));
+                if prev < self.n_kinds && this < self.n_kinds {
+                    heat[self.cell_index(prev, this)] += 1;
+                    sampled_pairs += 1;
+                }
+            }
+        }
+
+        let (sc_words_before, pp_words_before) = (self.sc_superseq.len(), self.pp_superseq.len());
+        (self.sc_superseq, self.sc_off) =
+            merge_sequences(&self.sc_superseq, &self.sc_off, &self.sc_len, &heat);
+        (self.pp_superseq, self.pp_off) =
+            merge_sequences(&self.pp_superseq, &self.pp_off, &self.pp_len, &heat);
+
+        let mut hot_cells: Vec<u32> = (0..heat.len() as u32)
+            .filter(|&idx| heat[idx as usize] > 0)
+            .collect();
+        hot_cells.sort_by_key(|&idx| std::cmp::Reverse(heat[idx as usize]));
+        let inline_pairs = hot_cells
+            .iter()
+            .map(|&idx| idx as usize)
+            .filter(|&idx| self.sc_len[idx] <= MAX_INLINE_LEN && self.pp_len[idx] <= MAX_INLINE_LEN)
+            .map(|idx| heat[idx])
+            .sum();
+        let report = LayoutReport {
+            sampled_pairs,
+            hot_cells: hot_cells.len(),
+            inline_pairs,
+            sc_words_before,
+            sc_words_after: self.sc_superseq.len(),
+            pp_words_before,
+            pp_words_after: self.pp_superseq.len(),
+        };
+        self.layout = TableLayout {
+            hot_cells,
+            inline_max_len: MAX_INLINE_LEN,
+        };
+        self.optimized = true;
+        report
+    }
+}
+
+/// Rebuilds one supersequence: distinct sequences that no other contains
+/// are laid out by descending heat, each overlapping the longest suffix of
+/// the words so far that prefixes it. Returns the new words and offsets;
+/// empty cells point at `0`.
+fn merge_sequences(
+    superseq: &[u32],
+    offs: &[u32],
+    lens: &[u32],
+    heat: &[u64],
+) -> (Vec<u32>, Vec<u32>) {
+    let seq = |idx: usize| {
+        let off = offs[idx] as usize;
+        &superseq[off..off + lens[idx] as usize]
+    };
+    let mut seq_heat: HashMap<&[u32], u64> = HashMap::new();
+    for idx in 0..offs.len() {
+        if lens[idx] > 0 {
+            *seq_heat.entry(seq(idx)).or_default() += heat[idx];
+        }
+    }
+    // Longest first, so a sequence is checked against every one that could
+    // contain it; ties in a fixed order keep the output reproducible.
+    let mut distinct: Vec<(&[u32], u64)> = seq_heat.into_iter().collect();
+    distinct.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then(a.0.cmp(b.0)));
+
+    let mut kept: Vec<(&[u32], u64)> = Vec::new();
+    let mut container: HashMap<&[u32], usize> = HashMap::new();
+    for (seq, heat) in distinct {
+        if let Some(&outer) = container.get(seq) {
+            kept[outer].1 += heat;
+            continue;
+        }
+        for start in 0..seq.len() {
+            for end in start + 1..=seq.len() {
+                container.entry(&seq[start..end]).or_insert(kept.len());
+            }
+        }
+        kept.push((seq, heat));
+    }
+    kept.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+    let mut words: Vec<u32> = Vec::new();
+    for (seq, _) in &kept {
+        let overlap = (1..=seq.len().min(words.len()))
+            .rev()
+            .find(|&n| words[words.len() - n..] == seq[..n])
+            .unwrap_or(0);
+        words.extend_from_slice(&seq[overlap..]);
+    }
+
+    let max_len = lens.iter().copied().max().unwrap_or(0) as usize;
+    let mut found: HashMap<&[u32], u32> = HashMap::new();
+    for start in 0..words.len() {
+        for end in start + 1..=(start + max_len).min(words.len()) {
+            found.entry(&words[start..end]).or_insert(start as u32);
+        }
+    }
+    let new_offs = (0..offs.len())
+        .map(|idx| if lens[idx] == 0 { 0 } else { found[seq(idx)] })
+        .collect();
+    (words, new_offs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        lexer::{tables::tokens::TokenKind, test_cpu::lex_on_test_cpu},
+        parser::retag::retag_open_delimiters,
+    };
+
+    fn generated_tables() -> PrecomputedParseTables {
+        PrecomputedParseTables::load_bin_bytes(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tables/parse_tables.bin"
+        )))
+        .expect("load generated parse tables")
+    }
+
+    /// Retagged kept kinds of `source`, as the GPU parser sees them.
+    fn parser_kinds(source: &str) -> Vec<u32> {
+        let kinds: Vec<TokenKind> = lex_on_test_cpu(source)
+            .expect("test CPU oracle")
+            .iter()
+            .map(|token| token.kind)
+            .collect();
+        retag_open_delimiters(&kinds)
+            .into_iter()
+            .map(|kind| kind as u32)
+            .collect()
+    }
+
+    #[test]
+    fn merging_overlaps_suffixes_and_drops_contained_sequences() {
+        let superseq = [1, 2, 3, 2, 3, 4, 3, 1, 2, 3];
+        let offs = [0, 3, 4, 7, 0];
+        let lens = [3, 3, 1, 3, 0];
+        let heat = [1, 5, 9, 1, 0];
+        let (words, new_offs) = merge_sequences(&superseq, &offs, &lens, &heat);
+        // The two [1, 2, 3] cells share one copy, [3] lends its heat to it, and
+        // [2, 3, 4] overlaps its [2, 3] tail.
+        assert_eq!(words, [1, 2, 3, 4]);
+        assert_eq!(new_offs, [0, 1, 2, 0, 0]);
+    }
+
+    #[test]
+    fn optimized_tables_keep_every_cell() {
+        let tables = generated_tables();
+        let sources = [
+            "fn main() { let x = f(1, 2)[0]; return x; }",
+            "",
+            "let a = [1, 2];",
+        ];
+        let samples: Vec<Vec<u32>> = sources.iter().map(|source| parser_kinds(source)).collect();
+        let mut optimized = tables.clone();
+        let report = optimized.optimize_layout(&samples);
+        assert!(optimized.optimized);
+        assert_eq!(optimized.validate(), Ok(()));
+        assert!(report.sampled_pairs > 0 && report.hot_cells > 0, "{report}");
+        assert!(report.sc_words_after <= report.sc_words_before, "{report}");
+        assert!(report.pp_words_after <= report.pp_words_before, "{report}");
+        for idx in 0..tables.sc_off.len() {
+            assert_eq!(optimized.sc_seq(idx), tables.sc_seq(idx), "sc cell {idx}");
+            assert_eq!(optimized.pp_seq(idx), tables.pp_seq(idx), "pp cell {idx}");
+        }
+    }
+
+    #[test]
+    fn layout_is_reproducible() {
+        let samples = vec![parser_kinds("fn f(a: i32) -> i32 { return a * 2; }")];
+        let mut a = generated_tables();
+        let mut b = generated_tables();
+        assert_eq!(a.optimize_layout(&samples), b.optimize_layout(&samples));
+        assert_eq!(a.sc_superseq, b.sc_superseq);
+        assert_eq!(a.pp_off, b.pp_off);
+        assert_eq!(a.layout, b.layout);
+    }
+}
@@ -0,0 +1,117 @@
+//! Sentinel kinds that frame a parser token-kind stream.
+//!
+//! Every pair-table lookup reads a `(prev, this)` cell, so the first and last
+//! tokens only get a cell when the stream is framed by the sentinel kind the
+//! tables were generated for: an optional start-of-input sentinel before the
+//! first token and an end-of-input sentinel after the last. The generator
+//! reserves kind `0` for it, which the GPU passes also use to pad token rows.
+//! [`PrecomputedParseTables::wrap_input`] adds the frame, so callers never
+//! place sentinels by hand.
+
+use super::PrecomputedParseTables;
+
+/// Sentinel kind the generator reserves; no lexer token kind uses it.
+pub const DEFAULT_SENTINEL_KIND: u32 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Token-kind stream rejected by [`PrecomputedParseTables::wrap_input`].
+pub enum SentinelError {
+    /// The stream is already framed: its first (with a start sentinel) or last
+    /// token is the sentinel, which would be added a second time.
+    AlreadyWrapped { index: usize, sentinel: u32 },
+    /// The sentinel appears between real tokens.
+    MidStream { index: usize, sentinel: u32 },
+}
+
+impl SentinelError {
+    /// Index of the offending token in the unwrapped stream.
+    pub fn index(&self) -> usize {
+        match *self {
+            Self::AlreadyWrapped { index, .. } | Self::MidStream { index, .. } => index,
+        }
+    }
+}
+
+impl std::fmt::Display for SentinelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AlreadyWrapped { index, sentinel } => write!(
+                f,
+                "token kind stream already contains sentinel kind {sentinel} at its edge \
+                 (index {index}); pass unwrapped kinds"
+            ),
+            Self::MidStream { index, sentinel } => write!(
+                f,
+                "sentinel kind {sentinel} appears mid-stream at token index {index}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SentinelError {}
+
+impl PrecomputedParseTables {
+    /// Frames lexer token kinds with these tables' sentinels: the
+    /// end-of-input sentinel always, and the start-of-input sentinel when
+    /// [`Self::start_sentinel`] is set.
+    pub fn wrap_input(&self, kinds: &[u32]) -> Result<Vec<u32>, SentinelError> {
+        let sentinel = self.sentinel_kind;
+        if let Some(index) = kinds.iter().position(|&kind| kind == sentinel) {
+            let at_edge = index + 1 == kinds.len() || (index == 0 && self.start_sentinel);
+            return Err(if at_edge {
+                SentinelError::AlreadyWrapped { index, sentinel }
+            } else {
+                SentinelError::MidStream { index, sentinel }
+            });
+        }
+        let mut wrapped = Vec::with_capacity(kinds.len() + 2);
+        if self.start_sentinel {
+            wrapped.push(sentinel);
+        }
+        wrapped.extend_from_slice(kinds);
+        wrapped.push(sentinel);
+        Ok(wrapped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_input_frames_with_the_configured_sentinels() {
+        let mut tables = PrecomputedParseTables::new(4, 1);
+        assert_eq!(tables.wrap_input(&[1, 2]).unwrap(), [0, 1, 2, 0]);
+        assert_eq!(tables.wrap_input(&[]).unwrap(), [0, 0]);
+
+        tables.start_sentinel = false;
+        tables.sentinel_kind = 3;
+        assert_eq!(tables.wrap_input(&[1, 0, 2]).unwrap(), [1, 0, 2, 3]);
+    }
+
+    #[test]
+    fn wrap_input_rejects_sentinels_already_in_the_stream() {
+        let mut tables = PrecomputedParseTables::new(4, 1);
+        assert_eq!(
+            tables.wrap_input(&[1, 2, 0]),
+            Err(SentinelError::AlreadyWrapped {
+                index: 2,
+                sentinel: 0
+            })
+        );
+        assert_eq!(tables.wrap_input(&[0, 1]).unwrap_err().index(), 0);
+        assert_eq!(
+            tables.wrap_input(&[1, 0, 2]),
+            Err(SentinelError::MidStream {
+                index: 1,
+                sentinel: 0
+            })
+        );
+
+        tables.start_sentinel = false;
+        assert!(matches!(
+            tables.wrap_input(&[0, 1]),
+            Err(SentinelError::MidStream { index: 0, .. })
+        ));
+    }
+}
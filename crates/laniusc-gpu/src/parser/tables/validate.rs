@@ -0,0 +1,573 @@
+//! Structural consistency checks for [`PrecomputedParseTables`].
+//!
+//! The GPU passes index the pair grids, supersequences and per-production
+//! arrays without bounds checks, so a corrupted or hand-edited table shows up
+//! as out-of-bounds reads in `pack_varlen` rather than as an error. These
+//! checks run on load, before the first upload, and in the generator before
+//! saving. Tables that record their terminal names are also checked against
+//! the running `TokenKind` enum, so a reordered enum fails here by name.
+
+use super::{INVALID_TABLE_ENTRY, MAX_INLINE_LEN, PrecomputedParseTables};
+use crate::lexer::tables::tokens::TokenKind;
+
+/// Largest production arity accepted; the tree passes subtract arities in
+/// signed arithmetic, and real grammar rules stay far below this.
+pub const MAX_PROD_ARITY: u32 = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// One broken invariant found by [`PrecomputedParseTables::validate`].
+pub enum TableInvariantViolation {
+    /// An array's length disagrees with the counts it is indexed by.
+    Length {
+        table: &'static str,
+        len: usize,
+        expected: usize,
+    },
+    /// A `(prev, this)` cell's `off + len` runs past the end of its
+    /// supersequence (`table` is `"sc"` or `"pp"`).
+    CellOutOfBounds {
+        table: &'static str,
+        prev: u32,
+        this: u32,
+        off: u32,
+        len: u32,
+        superseq_len: usize,
+    },
+    /// A stack-change code names a symbol wider than `sc_symbol_bits`.
+    StackSymbolTooWide { index: usize, code: u32, bits: u32 },
+    /// A production id at `index` of `table` is not below `n_productions`.
+    BadProduction {
+        table: &'static str,
+        index: usize,
+        prod: u32,
+    },
+    /// A production's arity exceeds its RHS length or [`MAX_PROD_ARITY`].
+    BadArity { prod: u32, arity: u32 },
+    /// A production's RHS slice runs past the end of `prod_rhs`.
+    RhsOutOfBounds {
+        prod: u32,
+        off: u32,
+        len: u32,
+        rhs_len: usize,
+    },
+    /// An RHS symbol is neither a grid kind nor a known nonterminal.
+    BadRhsSymbol { index: usize, symbol: u32 },
+    /// The LL(1) start nonterminal is out of range.
+    BadStartNonterminal { start: u32, n_nonterminals: u32 },
+    /// A production's left-hand side names an unknown nonterminal.
+    BadProductionLhs { prod: u32, lhs: u32 },
+    /// A precedence-ladder entry names an unknown nonterminal.
+    BadLadderNonterminal { index: usize, nt: u32 },
+    /// A recovery sync set names a kind with no grid column.
+    BadSyncKind { nt: u32, kind: u32 },
+    /// The kind map's dense width disagrees with `n_kinds`.
+    KindMapWidth { dense_kinds: u32, n_kinds: u32 },
+    /// The sentinel kind has no row and column in the pair grid.
+    SentinelWithoutCells { sentinel: u32 },
+    /// A recorded hot cell is not a grid cell.
+    BadHotCell { index: usize, cell: u32 },
+    /// The layout inlines sequences longer than [`MAX_INLINE_LEN`].
+    InlineTooLong { len: u32 },
+    /// A terminal recorded as lexer kind `kind` is `current` in the running
+    /// `TokenKind` enum (`None` when the name is gone): the tables were built
+    /// against a different enum.
+    StaleTerminal {
+        kind: u32,
+        name: String,
+        current: Option<u32>,
+    },
+}
+
+impl std::fmt::Display for TableInvariantViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Length {
+                table,
+                len,
+                expected,
+            } => write!(f, "{table} has {len} entries, expected {expected}"),
+            Self::CellOutOfBounds {
+                table,
+                prev,
+                this,
+                off,
+                len,
+                superseq_len,
+            } => write!(
+                f,
+                "{table} cell ({prev}, {this}) spans {off}..{} past the {superseq_len}-entry \
+                 supersequence",
+                *off as u64 + *len as u64
+            ),
+            Self::StackSymbolTooWide { index, code, bits } => write!(
+                f,
+                "sc_superseq[{index}] = {code} names symbol {} wider than {bits} bits",
+                code >> 1
+            ),
+            Self::BadProduction { table, index, prod } => {
+                write!(f, "{table}[{index}] = {prod} is not a production id")
+            }
+            Self::BadArity { prod, arity } => {
+                write!(f, "production {prod} has implausible arity {arity}")
+            }
+            Self::RhsOutOfBounds {
+                prod,
+                off,
+                len,
+                rhs_len,
+            } => write!(
+                f,
+                "production {prod} RHS spans {off}..{} past the {rhs_len}-entry prod_rhs",
+                *off as u64 + *len as u64
+            ),
+            Self::BadRhsSymbol { index, symbol } => {
+                write!(f, "prod_rhs[{index}] = {symbol} is not a grammar symbol")
+            }
+            Self::BadStartNonterminal {
+                start,
+                n_nonterminals,
+            } => write!(f, "start nonterminal {start} is not below {n_nonterminals}"),
+            Self::BadProductionLhs { prod, lhs } => {
+                write!(f, "production {prod} has unknown left-hand side {lhs}")
+            }
+            Self::BadLadderNonterminal { index, nt } => {
+                write!(
+                    f,
+                    "ladder_nonterminals[{index}] = {nt} is not a nonterminal"
+                )
+            }
+            Self::BadSyncKind { nt, kind } => {
+                write!(f, "sync set of nonterminal {nt} names unknown kind {kind}")
+            }
+            Self::KindMapWidth {
+                dense_kinds,
+                n_kinds,
+            } => write!(
+                f,
+                "kind map is {dense_kinds} kinds wide but n_kinds is {n_kinds}"
+            ),
+            Self::SentinelWithoutCells { sentinel } => {
+                write!(f, "sentinel kind {sentinel} has no grid row")
+            }
+            Self::BadHotCell { index, cell } => {
+                write!(f, "layout.hot_cells[{index}] = {cell} is not a grid cell")
+            }
+            Self::InlineTooLong { len } => write!(
+                f,
+                "layout inlines sequences of up to {len} entries; the GPU blob holds at most \
+                 {MAX_INLINE_LEN}"
+            ),
+            Self::StaleTerminal {
+                kind,
+                name,
+                current,
+            } => {
+                write!(
+                    f,
+                    "terminal '{name}' was lexer kind {kind} when the tables were built, "
+                )?;
+                match current {
+                    Some(current) => write!(f, "but is kind {current} now")?,
+                    None => write!(f, "but TokenKind has no such kind now")?,
+                }
+                write!(
+                    f,
+                    "; regenerate the tables against the current TokenKind enum"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TableInvariantViolation {}
+
+/// Joins violations into one message: the first, plus a count of the rest.
+pub(crate) fn summarize_violations(violations: &[TableInvariantViolation]) -> String {
+    match violations {
+        [] => "no violations".into(),
+        [only] => only.to_string(),
+        [first, rest @ ..] => format!("{first} (and {} more)", rest.len()),
+    }
+}
+
+impl PrecomputedParseTables {
+    /// Checks the structural invariants the GPU passes rely on.
+    ///
+    /// Array lengths are checked first; when any is wrong the remaining
+    /// checks, which index those arrays, are skipped.
+    pub fn validate(&self) -> Result<(), Vec<TableInvariantViolation>> {
+        let mut out = self.length_violations();
+        if out.is_empty() {
+            self.check_pair_cells(&mut out);
+            self.check_stack_symbols(&mut out);
+            self.check_productions(&mut out);
+            self.check_framing(&mut out);
+            self.check_layout(&mut out);
+            self.check_terminal_names(&mut out);
+        }
+        if out.is_empty() { Ok(()) } else { Err(out) }
+    }
+
+    fn length_violations(&self) -> Vec<TableInvariantViolation> {
+        let cells = (self.n_kinds as usize) * (self.n_kinds as usize);
+        let prods = self.n_productions as usize;
+        let names = |len: usize, want: usize| if len == 0 { 0 } else { want };
+        let mut expected = vec![
+            ("sc_off", self.sc_off.len(), cells),
+            ("sc_len", self.sc_len.len(), cells),
+            ("pp_off", self.pp_off.len(), cells),
+            ("pp_len", self.pp_len.len(), cells),
+            ("prod_arity", self.prod_arity.len(), prods),
+            ("prod_rhs_off", self.prod_rhs_off.len(), prods),
+            ("prod_rhs_len", self.prod_rhs_len.len(), prods),
+            (
+                "prod_names",
+                self.prod_names.len(),
+                names(self.prod_names.len(), prods),
+            ),
+            (
+                "prod_lhs",
+                self.prod_lhs.len(),
+                names(self.prod_lhs.len(), prods),
+            ),
+            (
+                "nonterminal_names",
+                self.nonterminal_names.len(),
+                names(self.nonterminal_names.len(), self.n_nonterminals as usize),
+            ),
+            (
+                "sync_sets",
+                self.sync_sets.len(),
+                names(self.sync_sets.len(), self.n_nonterminals as usize),
+            ),
+        ];
+        if self.n_nonterminals > 0 {
+            let predict_cells = (self.n_nonterminals as usize) * (self.n_kinds as usize);
+            expected.push(("ll1_predict", self.ll1_predict.len(), predict_cells));
+        }
+        expected
+            .into_iter()
+            .filter(|&(_, len, expected)| len != expected)
+            .map(|(table, len, expected)| TableInvariantViolation::Length {
+                table,
+                len,
+                expected,
+            })
+            .collect()
+    }
+
+    fn check_pair_cells(&self, out: &mut Vec<TableInvariantViolation>) {
+        let grids = [
+            ("sc", &self.sc_off, &self.sc_len, self.sc_superseq.len()),
+            ("pp", &self.pp_off, &self.pp_len, self.pp_superseq.len()),
+        ];
+        for (table, offs, lens, superseq_len) in grids {
+            for (idx, (&off, &len)) in offs.iter().zip(lens).enumerate() {
+                if off as u64 + len as u64 > superseq_len as u64 {
+                    out.push(TableInvariantViolation::CellOutOfBounds {
+                        table,
+                        prev: (idx / self.n_kinds as usize) as u32,
+                        this: (idx % self.n_kinds as usize) as u32,
+                        off,
+                        len,
+                        superseq_len,
+                    });
+                }
+            }
+        }
+    }
+
+    fn check_stack_symbols(&self, out: &mut Vec<TableInvariantViolation>) {
+        let bits = self.sc_symbol_bits;
+        for (index, &code) in self.sc_superseq.iter().enumerate() {
+            // push = 2*x + 1 and pop = 2*x, so the symbol is the code's high bits.
+            if (code >> 1).checked_shr(bits).unwrap_or(0) != 0 {
+                out.push(TableInvariantViolation::StackSymbolTooWide { index, code, bits });
+            }
+        }
+    }
+
+    fn check_productions(&self, out: &mut Vec<TableInvariantViolation>) {
+        for (index, &prod) in self.pp_superseq.iter().enumerate() {
+            if prod >= self.n_productions {
+                out.push(TableInvariantViolation::BadProduction {
+                    table: "pp_superseq",
+                    index,
+                    prod,
+                });
+            }
+        }
+        for (index, &prod) in self.ll1_predict.iter().enumerate() {
+            if prod != INVALID_TABLE_ENTRY && prod >= self.n_productions {
+                out.push(TableInvariantViolation::BadProduction {
+                    table: "ll1_predict",
+                    index,
+                    prod,
+                });
+            }
+        }
+
+        let has_rhs = self.n_nonterminals > 0;
+        for prod in 0..self.n_productions {
+            let p = prod as usize;
+            let (off, len) = (self.prod_rhs_off[p], self.prod_rhs_len[p]);
+            let arity = self.prod_arity[p];
+            if arity > MAX_PROD_ARITY || (has_rhs && arity > len) {
+                out.push(TableInvariantViolation::BadArity { prod, arity });
+            }
+            if off as u64 + len as u64 > self.prod_rhs.len() as u64 {
+                out.push(TableInvariantViolation::RhsOutOfBounds {
+                    prod,
+                    off,
+                    len,
+                    rhs_len: self.prod_rhs.len(),
+                });
+            }
+            if let Some(&lhs) = self.prod_lhs.get(p)
+                && lhs >= self.n_nonterminals
+            {
+                out.push(TableInvariantViolation::BadProductionLhs { prod, lhs });
+            }
+        }
+        let n_symbols = self.n_kinds as u64 + self.n_nonterminals as u64;
+        for (index, &symbol) in self.prod_rhs.iter().enumerate() {
+            if symbol as u64 >= n_symbols {
+                out.push(TableInvariantViolation::BadRhsSymbol { index, symbol });
+            }
+        }
+        if has_rhs && self.start_nonterminal >= self.n_nonterminals {
+            out.push(TableInvariantViolation::BadStartNonterminal {
+                start: self.start_nonterminal,
+                n_nonterminals: self.n_nonterminals,
+            });
+        }
+        for (index, &nt) in self.ladder_nonterminals.iter().enumerate() {
+            if nt >= self.n_nonterminals {
+                out.push(TableInvariantViolation::BadLadderNonterminal { index, nt });
+            }
+        }
+        for (nt, set) in self.sync_sets.iter().enumerate() {
+            for &kind in set {
+                if self.grid_kind(kind) >= self.n_kinds {
+                    out.push(TableInvariantViolation::BadSyncKind {
+                        nt: nt as u32,
+                        kind,
+                    });
+                }
+            }
+        }
+    }
+
+    fn check_framing(&self, out: &mut Vec<TableInvariantViolation>) {
+        if let Some(map) = &self.kind_map
+            && map.dense_kinds() != self.n_kinds
+        {
+            out.push(TableInvariantViolation::KindMapWidth {
+                dense_kinds: map.dense_kinds(),
+                n_kinds: self.n_kinds,
+            });
+        }
+        if self.grid_kind(self.sentinel_kind) >= self.n_kinds {
+            out.push(TableInvariantViolation::SentinelWithoutCells {
+                sentinel: self.sentinel_kind,
+            });
+        }
+    }
+
+    fn check_layout(&self, out: &mut Vec<TableInvariantViolation>) {
+        let cells = self.sc_off.len();
+        for (index, &cell) in self.layout.hot_cells.iter().enumerate() {
+            if cell as usize >= cells {
+                out.push(TableInvariantViolation::BadHotCell { index, cell });
+            }
+        }
+        if self.layout.inline_max_len > MAX_INLINE_LEN {
+            out.push(TableInvariantViolation::InlineTooLong {
+                len: self.layout.inline_max_len,
+            });
+        }
+    }
+
+    fn check_terminal_names(&self, out: &mut Vec<TableInvariantViolation>) {
+        for (kind, name) in self.terminal_names.iter().enumerate() {
+            if name.is_empty() {
+                continue;
+            }
+            let current = TokenKind::from_name(name).map(|k| k as u32);
+            if current != Some(kind as u32) {
+                out.push(TableInvariantViolation::StaleTerminal {
+                    kind: kind as u32,
+                    name: name.clone(),
+                    current,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        lexer::tables::tokens::{N_KINDS, TokenKind},
+        parser::tables::{build_mvp_precomputed_tables, encode_push},
+    };
+
+    fn mvp() -> PrecomputedParseTables {
+        let mut tables = build_mvp_precomputed_tables(N_KINDS, vec![0, 2, 1]);
+        tables.set_pp_for_pair(0, TokenKind::Ident as u32, &[1, 2]);
+        tables
+    }
+
+    fn single(tables: &PrecomputedParseTables) -> TableInvariantViolation {
+        let mut violations = tables.validate().expect_err("corrupted tables validate");
+        assert_eq!(violations.len(), 1, "{violations:?}");
+        violations.remove(0)
+    }
+
+    #[test]
+    fn builder_and_generated_tables_validate() {
+        assert_eq!(mvp().validate(), Ok(()));
+        assert_eq!(mvp().reverse().validate(), Ok(()));
+        let generated = PrecomputedParseTables::load_bin_bytes(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tables/parse_tables.bin"
+        )))
+        .expect("load generated parse tables");
+        assert_eq!(generated.validate(), Ok(()));
+    }
+
+    #[test]
+    fn cell_offset_past_the_supersequence_names_the_cell() {
+        let mut tables = mvp();
+        let (prev, this) = (3, TokenKind::GroupLParen as u32);
+        let idx = (prev * N_KINDS + this) as usize;
+        tables.sc_off[idx] = tables.sc_superseq.len() as u32;
+        assert_eq!(
+            single(&tables),
+            TableInvariantViolation::CellOutOfBounds {
+                table: "sc",
+                prev,
+                this,
+                off: tables.sc_superseq.len() as u32,
+                len: 1,
+                superseq_len: tables.sc_superseq.len(),
+            }
+        );
+    }
+
+    #[test]
+    fn bad_production_id_is_reported_with_its_index() {
+        let mut tables = mvp();
+        let index = tables.pp_superseq.len() - 1;
+        tables.pp_superseq[index] = 3;
+        assert_eq!(
+            single(&tables),
+            TableInvariantViolation::BadProduction {
+                table: "pp_superseq",
+                index,
+                prod: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn wrong_arity_table_length_is_reported_alone() {
+        let mut tables = mvp();
+        tables.prod_arity.pop();
+        tables.sc_off[0] = u32::MAX;
+        assert_eq!(
+            single(&tables),
+            TableInvariantViolation::Length {
+                table: "prod_arity",
+                len: 2,
+                expected: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn stack_symbols_and_arities_are_range_checked() {
+        let mut tables = mvp();
+        tables.sc_superseq[0] = encode_push(2);
+        assert!(matches!(
+            single(&tables),
+            TableInvariantViolation::StackSymbolTooWide {
+                index: 0,
+                bits: 1,
+                ..
+            }
+        ));
+
+        let mut tables = mvp();
+        tables.prod_arity[1] = MAX_PROD_ARITY + 1;
+        assert_eq!(
+            single(&tables),
+            TableInvariantViolation::BadArity {
+                prod: 1,
+                arity: MAX_PROD_ARITY + 1,
+            }
+        );
+    }
+
+    #[test]
+    fn load_rejects_tables_that_fail_validation() {
+        let mut tables = mvp();
+        tables.pp_superseq[0] = 7;
+        let err = PrecomputedParseTables::load_bin_bytes(&tables.to_bin_bytes()).unwrap_err();
+        assert_eq!(
+            err,
+            "parse tables: pp_superseq[0] = 7 is not a production id"
+        );
+    }
+
+    #[test]
+    fn terminal_names_from_an_older_enum_are_rejected_by_name() {
+        let mut tables = mvp();
+        tables.terminal_names = vec![String::new(), "Ident".into(), "Int".into()];
+        let loaded = PrecomputedParseTables::load_bin_bytes(&tables.to_bin_bytes())
+            .expect("current names load");
+        assert_eq!(loaded.terminal_names, tables.terminal_names);
+
+        // As if `Int` had been declared before `Ident` when the tables were built.
+        tables.terminal_names = vec![String::new(), "Int".into(), "Ident".into(), "Gone".into()];
+        let violations = tables.validate().expect_err("shifted names validate");
+        assert_eq!(
+            violations[0],
+            TableInvariantViolation::StaleTerminal {
+                kind: 1,
+                name: "Int".into(),
+                current: Some(TokenKind::Int as u32),
+            }
+        );
+        assert_eq!(violations.len(), 3, "{violations:?}");
+        assert_eq!(
+            violations[2].to_string(),
+            "terminal 'Gone' was lexer kind 3 when the tables were built, but TokenKind has no \
+             such kind now; regenerate the tables against the current TokenKind enum"
+        );
+        let err = PrecomputedParseTables::load_bin_bytes(&tables.to_bin_bytes()).unwrap_err();
+        assert!(err.contains("terminal 'Int' was lexer kind 1"), "{err}");
+    }
+
+    #[test]
+    fn layout_hot_cells_and_inline_length_are_range_checked() {
+        let mut tables = mvp();
+        let cells = tables.sc_off.len() as u32;
+        tables.optimized = true;
+        tables.layout.hot_cells = vec![0, cells];
+        tables.layout.inline_max_len = MAX_INLINE_LEN + 1;
+        assert_eq!(
+            tables.validate(),
+            Err(vec![
+                TableInvariantViolation::BadHotCell {
+                    index: 1,
+                    cell: cells
+                },
+                TableInvariantViolation::InlineTooLong {
+                    len: MAX_INLINE_LEN + 1
+                },
+            ])
+        );
+    }
+}
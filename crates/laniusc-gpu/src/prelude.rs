@@ -0,0 +1,34 @@
+//! Commonly used lexer, parser, and compile entry points.
+//!
+//! `use laniusc_compiler::prelude::*;` brings the GPU lexer and parser drivers,
+//! their host-readable results, and the phase error enums into scope. Every
+//! item is also reachable through its defining module.
+
+pub use crate::{
+    compiler::CompileError,
+    gpu::device::GpuDeviceInitializationError,
+    lexer::{
+        GpuLexer,
+        LexOptions,
+        LexOutput,
+        LexRangeOptions,
+        LexReport,
+        LexemePolicy,
+        LexerRuntimeOptions,
+        RangeLexOutput,
+        ReadbackMode,
+        Token,
+        lex_on_gpu,
+        lexemes,
+        tables::tokens::TokenKind,
+    },
+    parser::{
+        ParserOptions,
+        ast::{Ast, AstNode},
+        driver::{GpuParser, ParseResult},
+        syntax::GpuSyntaxError,
+        tables::{Ll1ParseErrorCode, PrecomputedParseTables},
+    },
+    span::Span,
+    type_checker::GpuTypeCheckError,
+};
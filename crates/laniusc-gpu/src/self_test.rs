@@ -0,0 +1,356 @@
+//! Startup self test: runs embedded vectors through the GPU pipeline.
+//!
+//! Driver bugs in scan-heavy workloads do show up on some adapters, so a
+//! host can call [`self_test`] once before trusting the device. Each check
+//! lexes (and, for one, parses) a small input chosen to cross DFA and pair
+//! block boundaries or end mid-token, and compares the result with
+//! expectations recorded by `gen_selftest_vectors` from the test CPU oracle
+//! (see [`vectors`]). The expectations are embedded, so the self test never
+//! runs the CPU lexer itself.
+//!
+//! A [`CheckStatus::WrongResult`] means the device ran the work and got it
+//! wrong, which points at the driver or a shader; a
+//! [`CheckStatus::Unsupported`] means the device could not run it at all.
+//! Either way a host can fall back to CPU lexing.
+//!
+//! [`self_test`]: fn@crate::self_test::self_test
+//! [`vectors`]: crate::self_test::vectors
+//! [`CheckStatus::WrongResult`]: crate::self_test::CheckStatus::WrongResult
+//! [`CheckStatus::Unsupported`]: crate::self_test::CheckStatus::Unsupported
+
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use anyhow::Error;
+
+use crate::{
+    gpu::{
+        device::{self, DeviceOptions, GpuDeviceInitializationError},
+        limits::LimitError,
+    },
+    lexer::{GpuLexer, Token, tables::tokens::N_KINDS},
+    parser::{
+        driver::{GpuParser, ParseResult},
+        tables::{PrecomputedParseTables, build_mvp_precomputed_tables},
+    },
+};
+
+pub mod vectors;
+
+use vectors::{CheckKind, Expected, SelfTestVectors, VectorCheck};
+
+/// How one check went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckStatus {
+    Passed,
+    /// The device ran the check and produced different output.
+    WrongResult(String),
+    /// The device could not run the check: no adapter, a pipeline it cannot
+    /// create, or a limit below what the input needs.
+    Unsupported(String),
+    /// The check started but failed to finish, e.g. a wait timed out.
+    Errored(String),
+}
+
+impl CheckStatus {
+    pub fn passed(&self) -> bool {
+        matches!(self, Self::Passed)
+    }
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Passed => write!(f, "passed"),
+            Self::WrongResult(detail) => write!(f, "wrong result: {detail}"),
+            Self::Unsupported(detail) => write!(f, "unsupported: {detail}"),
+            Self::Errored(detail) => write!(f, "error: {detail}"),
+        }
+    }
+}
+
+/// Outcome of one named check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckReport {
+    pub name: String,
+    pub status: CheckStatus,
+    /// Host wall time of the check, zero when it never ran.
+    pub elapsed: Duration,
+}
+
+/// Every check of one self-test run, in vector order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelfTestReport {
+    pub checks: Vec<CheckReport>,
+}
+
+impl SelfTestReport {
+    /// Whether every check passed.
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.status.passed())
+    }
+
+    /// Checks that did not pass.
+    pub fn failures(&self) -> impl Iterator<Item = &CheckReport> {
+        self.checks.iter().filter(|check| !check.status.passed())
+    }
+
+    /// Whether any check produced wrong output, the case a driver or shader
+    /// bug explains and a retry does not.
+    pub fn has_wrong_results(&self) -> bool {
+        self.checks
+            .iter()
+            .any(|check| matches!(check.status, CheckStatus::WrongResult(_)))
+    }
+
+    /// The check named `name`.
+    pub fn check(&self, name: &str) -> Option<&CheckReport> {
+        self.checks.iter().find(|check| check.name == name)
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            writeln!(
+                f,
+                "{:<28} {:>9.3} ms  {}",
+                check.name,
+                check.elapsed.as_secs_f64() * 1e3,
+                check.status
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Parser tables of the [`CheckKind::Parse`] checks: the bracket-only MVP
+/// set, whose stack changes depend on delimiter family alone, so parser
+/// retags of `(` and `[` do not change the expected stream.
+pub fn parse_tables() -> PrecomputedParseTables {
+    build_mvp_precomputed_tables(N_KINDS, vec![0])
+}
+
+/// Runs the embedded vectors on the process device; see the module docs.
+///
+/// `options` are applied with [`device::set_device_options`] first, so they
+/// only take effect when no device has been created yet in this process.
+pub async fn self_test(options: DeviceOptions) -> SelfTestReport {
+    self_test_with_vectors(options, &SelfTestVectors::embedded()).await
+}
+
+/// [`self_test`] over caller-supplied vectors.
+pub async fn self_test_with_vectors(
+    options: DeviceOptions,
+    vectors: &SelfTestVectors,
+) -> SelfTestReport {
+    device::set_device_options(options);
+    let skip_all = |status: CheckStatus| SelfTestReport {
+        checks: vectors
+            .checks
+            .iter()
+            .map(|check| CheckReport {
+                name: check.name.clone(),
+                status: status.clone(),
+                elapsed: Duration::ZERO,
+            })
+            .collect(),
+    };
+    let ctx = match device::global_result() {
+        Ok(ctx) => ctx,
+        Err(err) => return skip_all(CheckStatus::Unsupported(err.to_string())),
+    };
+    let lexer = match GpuLexer::new_with_device(ctx).await {
+        Ok(lexer) => lexer,
+        Err(err) => {
+            return skip_all(CheckStatus::Unsupported(format!(
+                "creating the GPU lexer failed: {err:#}"
+            )));
+        }
+    };
+    let parser = GpuParser::new_with_device(ctx)
+        .await
+        .map_err(|err| format!("creating the GPU parser failed: {err:#}"));
+    let tables = parse_tables();
+
+    let mut checks = Vec::with_capacity(vectors.checks.len());
+    for check in &vectors.checks {
+        let start = Instant::now();
+        let status = run_check(&lexer, parser.as_ref(), &tables, check)
+            .await
+            .unwrap_or_else(|err| status_of_error(&err));
+        checks.push(CheckReport {
+            name: check.name.clone(),
+            status,
+            elapsed: start.elapsed(),
+        });
+    }
+    SelfTestReport { checks }
+}
+
+async fn run_check(
+    lexer: &GpuLexer,
+    parser: Result<&GpuParser, &String>,
+    tables: &PrecomputedParseTables,
+    check: &VectorCheck,
+) -> anyhow::Result<CheckStatus> {
+    let expected = &check.expected;
+    let mismatch = match check.kind {
+        CheckKind::Lex => {
+            let output = lexer
+                .lex_with_options(&check.sources[0], check.options.lex_options())
+                .await?;
+            let mut mismatch = compare_tokens("kept", &output.tokens, &expected.tokens);
+            if check.options.all_tokens {
+                mismatch = mismatch
+                    .or_else(|| compare_tokens("all", &output.all_tokens, &expected.all_tokens));
+            }
+            if check.options.capture_string_escapes {
+                mismatch = mismatch.or_else(|| {
+                    compare_words("escape spans", &output.escape_spans, &expected.escape_spans)
+                });
+            }
+            mismatch
+        }
+        CheckKind::SourcePack => {
+            let tokens = lexer.lex_source_pack(&check.sources).await?;
+            compare_tokens("kept", &tokens, &expected.tokens)
+        }
+        CheckKind::Parse => {
+            let parser = match parser {
+                Ok(parser) => parser,
+                Err(detail) => return Ok(CheckStatus::Unsupported(detail.clone())),
+            };
+            let tokens = lexer.lex(&check.sources[0]).await?;
+            match compare_tokens("kept", &tokens, &expected.tokens) {
+                Some(mismatch) => Some(mismatch),
+                None => {
+                    let kinds: Vec<u32> = tokens.iter().map(|t| t.kind as u32).collect();
+                    let result = parser.parse_tokens(&kinds, tables).await?;
+                    compare_parse(&result, expected)
+                }
+            }
+        }
+    };
+    Ok(mismatch.map_or(CheckStatus::Passed, CheckStatus::WrongResult))
+}
+
+fn compare_parse(result: &ParseResult, expected: &Expected) -> Option<String> {
+    compare_words("stack changes", &result.sc_stream, &expected.sc_stream)
+        .or_else(|| compare_words("emit stream", &result.emit_stream, &expected.emit_stream))
+        .or_else(|| {
+            (result.brackets.valid != expected.brackets_valid).then(|| {
+                format!(
+                    "brackets valid is {}, expected {}",
+                    result.brackets.valid, expected.brackets_valid
+                )
+            })
+        })
+}
+
+fn compare_tokens(what: &str, actual: &[Token], expected: &[Token]) -> Option<String> {
+    let shape = |t: &Token| (t.kind, t.span);
+    let first = actual
+        .iter()
+        .zip(expected)
+        .position(|(a, e)| shape(a) != shape(e));
+    match first {
+        Some(i) => Some(format!(
+            "{what} token {i} is {:?} at {}, expected {:?} at {}",
+            actual[i].kind, actual[i].span, expected[i].kind, expected[i].span
+        )),
+        None => (actual.len() != expected.len()).then(|| {
+            format!(
+                "{} {what} tokens, expected {}",
+                actual.len(),
+                expected.len()
+            )
+        }),
+    }
+}
+
+fn compare_words<T: PartialEq + fmt::Debug>(
+    what: &str,
+    actual: &[T],
+    expected: &[T],
+) -> Option<String> {
+    match actual.iter().zip(expected).position(|(a, e)| a != e) {
+        Some(i) => Some(format!(
+            "{what}[{i}] is {:?}, expected {:?}",
+            actual[i], expected[i]
+        )),
+        None => (actual.len() != expected.len())
+            .then(|| format!("{} {what}, expected {}", actual.len(), expected.len())),
+    }
+}
+
+/// Limit and device errors mean the device cannot run the check; anything
+/// else stopped a check it could run.
+fn status_of_error(err: &Error) -> CheckStatus {
+    let unsupported = err
+        .chain()
+        .any(|cause| cause.is::<LimitError>() || cause.is::<GpuDeviceInitializationError>());
+    if unsupported {
+        CheckStatus::Unsupported(format!("{err:#}"))
+    } else {
+        CheckStatus::Errored(format!("{err:#}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::tables::tokens::TokenKind, span::Span};
+
+    fn token(kind: TokenKind, start: u32, len: u32) -> Token {
+        Token {
+            kind,
+            raw_kind: kind,
+            span: Span::from_start_len(start, len).unwrap(),
+        }
+    }
+
+    #[test]
+    fn token_comparison_names_the_first_difference() {
+        let expected = [token(TokenKind::Ident, 0, 1), token(TokenKind::Int, 2, 2)];
+        assert_eq!(compare_tokens("kept", &expected, &expected), None);
+
+        let mut wrong = expected;
+        wrong[1].raw_kind = TokenKind::Float;
+        assert_eq!(compare_tokens("kept", &wrong, &expected), None);
+        wrong[1].span = Span::new(2, 3);
+        assert_eq!(
+            compare_tokens("kept", &wrong, &expected).unwrap(),
+            "kept token 1 is Int at 2..3, expected Int at 2..4"
+        );
+        assert_eq!(
+            compare_tokens("all", &expected[..1], &expected).unwrap(),
+            "1 all tokens, expected 2"
+        );
+    }
+
+    #[test]
+    fn limit_and_device_errors_are_unsupported() {
+        let limit = Error::new(LimitError::InputExceedsDeviceLimits {
+            limit: "max_storage_buffer_binding_size",
+            needed: 2,
+            granted: 1,
+            suggestion: "split it",
+        })
+        .context("lex");
+        assert!(matches!(
+            status_of_error(&limit),
+            CheckStatus::Unsupported(_)
+        ));
+        assert!(matches!(
+            status_of_error(&Error::new(GpuDeviceInitializationError::NoAdapter)),
+            CheckStatus::Unsupported(_)
+        ));
+        assert!(matches!(
+            status_of_error(&anyhow::anyhow!("wait timed out")),
+            CheckStatus::Errored(_)
+        ));
+    }
+}
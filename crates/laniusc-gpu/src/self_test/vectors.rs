@@ -0,0 +1,485 @@
+//! The embedded self-test vector blob.
+//!
+//! `gen_selftest_vectors` writes it from the test CPU oracle; the self test
+//! only reads it, so it runs without the CPU lexer. Integers are unsigned
+//! LEB128 varints unless marked `u8`:
+//!
+//! ```text
+//! magic b"LXSELF01"
+//! check count, then per check:
+//!   name        length, UTF-8 bytes
+//!   kind        u8: 0 lex, 1 source pack, 2 parse
+//!   options     u8 bit set: 1 all tokens, 2 string escapes,
+//!               4 compressed readback, 8 two submissions
+//!   sources     count, then per source: length, UTF-8 bytes
+//!   tokens      kept tokens, as a token list
+//!   all tokens  a token list, only with option 1
+//!   escapes     count, then per escape: gap, length; only with option 2
+//!   parse       stack-change word count and words, emit word count and
+//!               words, u8 brackets valid; only for kind 2
+//! token list: count, then per token: u8 kind, gap, length
+//! ```
+//!
+//! A gap is the distance from the end of the previous token (or escape) to
+//! the start of this one, so records of a few KB of source stay small.
+//! [`SelfTestVectors::decode`] rejects anything it cannot read back exactly.
+
+use anyhow::{Context, Result, anyhow, bail, ensure};
+
+use crate::{
+    lexer::{EscapeSpan, LexOptions, Token, tables::tokens::TokenKind},
+    span::Span,
+};
+
+/// Leading magic of a vector blob, which also carries the version.
+pub const VECTORS_MAGIC: [u8; 8] = *b"LXSELF01";
+
+/// The checked-in blob the self test runs by default.
+pub const EMBEDDED_VECTORS: &[u8] = include_bytes!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../tables/selftest_vectors.bin"
+));
+
+const OPTION_ALL_TOKENS: u8 = 1 << 0;
+const OPTION_STRING_ESCAPES: u8 = 1 << 1;
+const OPTION_COMPRESS_READBACK: u8 = 1 << 2;
+const OPTION_TWO_SUBMISSIONS: u8 = 1 << 3;
+
+/// What one check runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckKind {
+    /// Lexes the single source with the check's options.
+    Lex,
+    /// Lexes every source as one source pack; token offsets run across the
+    /// concatenated files.
+    SourcePack,
+    /// Lexes the single source, then parses its kept kinds with
+    /// [`parse_tables`](super::parse_tables).
+    Parse,
+}
+
+/// Lexer options a check turns on; everything else keeps its default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VectorOptions {
+    pub all_tokens: bool,
+    pub capture_string_escapes: bool,
+    pub compress_readback: bool,
+    /// Reads the token count before the tokens, as large inputs do.
+    pub two_submissions: bool,
+}
+
+impl VectorOptions {
+    /// The lexer options of a check.
+    pub fn lex_options(self) -> LexOptions {
+        let mut options = LexOptions {
+            all_tokens: self.all_tokens,
+            capture_string_escapes: self.capture_string_escapes,
+            compress_readback: self.compress_readback,
+            ..LexOptions::default()
+        };
+        if self.two_submissions {
+            options.single_submission_max_bytes = 0;
+        }
+        options
+    }
+
+    fn bits(self) -> u8 {
+        let mut bits = 0;
+        for (on, bit) in [
+            (self.all_tokens, OPTION_ALL_TOKENS),
+            (self.capture_string_escapes, OPTION_STRING_ESCAPES),
+            (self.compress_readback, OPTION_COMPRESS_READBACK),
+            (self.two_submissions, OPTION_TWO_SUBMISSIONS),
+        ] {
+            if on {
+                bits |= bit;
+            }
+        }
+        bits
+    }
+
+    fn from_bits(bits: u8) -> Option<Self> {
+        let known = OPTION_ALL_TOKENS
+            | OPTION_STRING_ESCAPES
+            | OPTION_COMPRESS_READBACK
+            | OPTION_TWO_SUBMISSIONS;
+        (bits & !known == 0).then_some(Self {
+            all_tokens: bits & OPTION_ALL_TOKENS != 0,
+            capture_string_escapes: bits & OPTION_STRING_ESCAPES != 0,
+            compress_readback: bits & OPTION_COMPRESS_READBACK != 0,
+            two_submissions: bits & OPTION_TWO_SUBMISSIONS != 0,
+        })
+    }
+}
+
+/// Known-good output of one check. Tokens compare by kind and span; their
+/// `raw_kind` is not recorded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Expected {
+    pub tokens: Vec<Token>,
+    /// Every token, trivia included, with [`VectorOptions::all_tokens`].
+    pub all_tokens: Vec<Token>,
+    /// With [`VectorOptions::capture_string_escapes`].
+    pub escape_spans: Vec<EscapeSpan>,
+    /// Parser stack-change stream, for [`CheckKind::Parse`].
+    pub sc_stream: Vec<u32>,
+    /// Parser emit stream, for [`CheckKind::Parse`].
+    pub emit_stream: Vec<u32>,
+    /// Whether every bracket matched, for [`CheckKind::Parse`].
+    pub brackets_valid: bool,
+}
+
+/// One named input and what the GPU must produce for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VectorCheck {
+    pub name: String,
+    pub kind: CheckKind,
+    pub options: VectorOptions,
+    /// One source, or the files of a [`CheckKind::SourcePack`].
+    pub sources: Vec<String>,
+    pub expected: Expected,
+}
+
+/// Every check of one vector blob, in run order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelfTestVectors {
+    pub checks: Vec<VectorCheck>,
+}
+
+impl SelfTestVectors {
+    /// Decodes [`EMBEDDED_VECTORS`].
+    pub fn embedded() -> Self {
+        Self::decode(EMBEDDED_VECTORS).expect("checked-in self-test vectors decode")
+    }
+
+    /// Serializes the vectors in the blob format above.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = VECTORS_MAGIC.to_vec();
+        put_len(&mut out, self.checks.len());
+        for check in &self.checks {
+            put_bytes(&mut out, check.name.as_bytes());
+            out.push(match check.kind {
+                CheckKind::Lex => 0,
+                CheckKind::SourcePack => 1,
+                CheckKind::Parse => 2,
+            });
+            out.push(check.options.bits());
+            put_len(&mut out, check.sources.len());
+            for source in &check.sources {
+                put_bytes(&mut out, source.as_bytes());
+            }
+            let expected = &check.expected;
+            put_tokens(&mut out, &expected.tokens);
+            if check.options.all_tokens {
+                put_tokens(&mut out, &expected.all_tokens);
+            }
+            if check.options.capture_string_escapes {
+                put_len(&mut out, expected.escape_spans.len());
+                let mut end = 0;
+                for escape in &expected.escape_spans {
+                    put_varint(&mut out, escape.span.start - end);
+                    put_varint(&mut out, escape.span.len());
+                    end = escape.span.end;
+                }
+            }
+            if check.kind == CheckKind::Parse {
+                put_words(&mut out, &expected.sc_stream);
+                put_words(&mut out, &expected.emit_stream);
+                out.push(expected.brackets_valid as u8);
+            }
+        }
+        out
+    }
+
+    /// Reads a blob written by [`encode`](Self::encode).
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        ensure!(
+            bytes.starts_with(&VECTORS_MAGIC),
+            "self-test vectors do not start with {:?}",
+            "LXSELF01"
+        );
+        let mut reader = Reader {
+            bytes,
+            at: VECTORS_MAGIC.len(),
+        };
+        let count = reader.len()?;
+        let mut checks = Vec::new();
+        for index in 0..count {
+            checks.push(
+                reader
+                    .check()
+                    .with_context(|| format!("self-test check #{index}"))?,
+            );
+        }
+        ensure!(
+            reader.at == bytes.len(),
+            "self-test vectors have {} trailing bytes",
+            bytes.len() - reader.at
+        );
+        Ok(Self { checks })
+    }
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn put_len(out: &mut Vec<u8>, len: usize) {
+    put_varint(
+        out,
+        u32::try_from(len).expect("self-test vectors stay small"),
+    );
+}
+
+fn put_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    put_len(out, bytes.len());
+    out.extend_from_slice(bytes);
+}
+
+fn put_words(out: &mut Vec<u8>, words: &[u32]) {
+    put_len(out, words.len());
+    for &word in words {
+        put_varint(out, word);
+    }
+}
+
+fn put_tokens(out: &mut Vec<u8>, tokens: &[Token]) {
+    put_len(out, tokens.len());
+    let mut end = 0;
+    for token in tokens {
+        out.push(u8::try_from(token.kind as u32).expect("token kinds fit a byte"));
+        put_varint(out, token.span.start - end);
+        put_varint(out, token.span.len());
+        end = token.span.end;
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8> {
+        let byte = *self
+            .bytes
+            .get(self.at)
+            .ok_or_else(|| anyhow!("self-test vectors end early at byte {}", self.at))?;
+        self.at += 1;
+        Ok(byte)
+    }
+
+    fn varint(&mut self) -> Result<u32> {
+        let mut value = 0u32;
+        for shift in (0..35).step_by(7) {
+            let byte = self.byte()?;
+            let bits = u32::from(byte & 0x7f);
+            ensure!(
+                shift < 28 || bits >> (32 - shift) == 0,
+                "varint at byte {} overflows u32",
+                self.at
+            );
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        bail!("varint at byte {} runs past five bytes", self.at)
+    }
+
+    /// A count or length, which can never exceed the bytes left.
+    fn len(&mut self) -> Result<usize> {
+        let len = self.varint()? as usize;
+        ensure!(
+            len <= self.bytes.len() - self.at,
+            "length {len} at byte {} passes the end of the vectors",
+            self.at
+        );
+        Ok(len)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.len()?;
+        let bytes = &self.bytes[self.at..self.at + len];
+        self.at += len;
+        Ok(std::str::from_utf8(bytes)
+            .context("self-test string is not UTF-8")?
+            .to_owned())
+    }
+
+    /// The span `gap` bytes after `end`, `len` bytes long.
+    fn span(&mut self, end: u32) -> Result<Span> {
+        let (gap, len) = (self.varint()?, self.varint()?);
+        end.checked_add(gap)
+            .and_then(|start| Span::from_start_len(start, len))
+            .ok_or_else(|| anyhow!("span {end}+{gap}+{len} passes u32::MAX"))
+    }
+
+    fn tokens(&mut self) -> Result<Vec<Token>> {
+        let count = self.len()?;
+        let mut tokens = Vec::with_capacity(count);
+        let mut end = 0;
+        for index in 0..count {
+            let raw = self.byte()?;
+            let kind = TokenKind::from_u32(raw.into())
+                .ok_or_else(|| anyhow!("token #{index} has unknown kind {raw}"))?;
+            let span = self.span(end)?;
+            end = span.end;
+            tokens.push(Token {
+                kind,
+                raw_kind: kind,
+                span,
+            });
+        }
+        Ok(tokens)
+    }
+
+    fn words(&mut self) -> Result<Vec<u32>> {
+        let count = self.len()?;
+        (0..count).map(|_| self.varint()).collect()
+    }
+
+    fn check(&mut self) -> Result<VectorCheck> {
+        let name = self.string()?;
+        let kind = match self.byte()? {
+            0 => CheckKind::Lex,
+            1 => CheckKind::SourcePack,
+            2 => CheckKind::Parse,
+            other => bail!("check {name:?} has unknown kind {other}"),
+        };
+        let bits = self.byte()?;
+        let options = VectorOptions::from_bits(bits)
+            .ok_or_else(|| anyhow!("check {name:?} has unknown option bits {bits:#x}"))?;
+        let sources = (0..self.len()?)
+            .map(|_| self.string())
+            .collect::<Result<Vec<_>>>()?;
+        ensure!(
+            kind == CheckKind::SourcePack || sources.len() == 1,
+            "check {name:?} needs exactly one source, has {}",
+            sources.len()
+        );
+        let mut expected = Expected {
+            tokens: self.tokens()?,
+            ..Expected::default()
+        };
+        if options.all_tokens {
+            expected.all_tokens = self.tokens()?;
+        }
+        if options.capture_string_escapes {
+            let mut end = 0;
+            for _ in 0..self.len()? {
+                let span = self.span(end)?;
+                end = span.end;
+                expected.escape_spans.push(EscapeSpan { span });
+            }
+        }
+        if kind == CheckKind::Parse {
+            expected.sc_stream = self.words()?;
+            expected.emit_stream = self.words()?;
+            expected.brackets_valid = match self.byte()? {
+                0 => false,
+                1 => true,
+                other => bail!("check {name:?} has brackets-valid byte {other}"),
+            };
+        }
+        Ok(VectorCheck {
+            name,
+            kind,
+            options,
+            sources,
+            expected,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(kind: TokenKind, start: u32, len: u32) -> Token {
+        Token {
+            kind,
+            raw_kind: kind,
+            span: Span::from_start_len(start, len).unwrap(),
+        }
+    }
+
+    fn sample() -> SelfTestVectors {
+        SelfTestVectors {
+            checks: vec![
+                VectorCheck {
+                    name: "escapes".into(),
+                    kind: CheckKind::Lex,
+                    options: VectorOptions {
+                        all_tokens: true,
+                        capture_string_escapes: true,
+                        ..VectorOptions::default()
+                    },
+                    sources: vec!["x \"\\n\"".into()],
+                    expected: Expected {
+                        tokens: vec![
+                            token(TokenKind::Ident, 0, 1),
+                            token(TokenKind::String, 2, 4),
+                        ],
+                        all_tokens: vec![
+                            token(TokenKind::Ident, 0, 1),
+                            token(TokenKind::White, 1, 1),
+                            token(TokenKind::String, 2, 4),
+                        ],
+                        escape_spans: vec![EscapeSpan {
+                            span: Span::new(3, 5),
+                        }],
+                        ..Expected::default()
+                    },
+                },
+                VectorCheck {
+                    name: "parse".into(),
+                    kind: CheckKind::Parse,
+                    options: VectorOptions::default(),
+                    sources: vec!["(x)".into()],
+                    expected: Expected {
+                        tokens: vec![
+                            token(TokenKind::LParen, 0, 1),
+                            token(TokenKind::Ident, 1, 1),
+                            token(TokenKind::RParen, 2, 1),
+                        ],
+                        sc_stream: vec![1, 300_000],
+                        brackets_valid: true,
+                        ..Expected::default()
+                    },
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn vectors_round_trip_through_the_blob() {
+        let vectors = sample();
+        assert_eq!(SelfTestVectors::decode(&vectors.encode()).unwrap(), vectors);
+    }
+
+    #[test]
+    fn truncated_or_padded_blobs_are_rejected() {
+        let bytes = sample().encode();
+        for len in 0..bytes.len() {
+            assert!(SelfTestVectors::decode(&bytes[..len]).is_err(), "{len}");
+        }
+        let mut padded = bytes.clone();
+        padded.push(0);
+        assert!(SelfTestVectors::decode(&padded).is_err());
+    }
+
+    #[test]
+    fn embedded_vectors_decode_with_unique_names() {
+        let vectors = SelfTestVectors::embedded();
+        assert!(!vectors.checks.is_empty());
+        let mut names: Vec<_> = vectors.checks.iter().map(|c| c.name.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), vectors.checks.len());
+    }
+}
@@ -38,6 +38,16 @@ pub(crate) fn size_guard_max_bytes_text() -> String {
     value("size_guard_max_bytes").unwrap_or_else(|| UNKNOWN.to_string())
 }
 
+/// Returns `compiled` or `vendored`, the source of the shader artifact set.
+pub(crate) fn build_mode() -> String {
+    value("mode").unwrap_or_else(|| UNKNOWN.to_string())
+}
+
+/// Returns the `slangc` version that built the shader artifact set.
+pub(crate) fn slangc_version() -> Option<String> {
+    value("slangc_version").filter(|version| version != UNKNOWN)
+}
+
 /// Returns the recorded shader artifact count when it is numeric.
 pub(crate) fn count() -> Option<u64> {
     parse_u64(&count_text())
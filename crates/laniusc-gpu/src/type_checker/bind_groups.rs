@@ -83,7 +83,7 @@ impl GpuTypeChecker {
             ($stage:literal) => {
                 if allocation_timing {
                     let now = std::time::Instant::now();
-                    eprintln!(
+                    log::info!(target: crate::logging::TYPE_CHECKER,
                         "[gpu_compile_host_timer] typecheck.resident.{}: {:.3}ms (total {:.3}ms)",
                         $stage,
                         now.duration_since(allocation_last).as_secs_f64() * 1000.0,
@@ -47,7 +47,7 @@ pub(in crate::type_checker) fn stamp_typecheck_timer(
     label: &'static str,
 ) {
     if crate::gpu::env::env_bool_truthy("LANIUS_GPU_COMPILE_HOST_TIMING", false) {
-        eprintln!(
+        log::info!(target: crate::logging::TYPE_CHECKER,
             "[gpu_compile_host_timer] typecheck.pass_checkpoint: label={label} total_compute_passes={}",
             recorded_compute_pass_count(),
         );
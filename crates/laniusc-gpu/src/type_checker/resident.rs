@@ -208,7 +208,7 @@ impl TypeCheckRecordHostTimer {
         let total_ms = now.duration_since(self.start).as_secs_f64() * 1000.0;
         let compute_passes = recorded_compute_pass_count();
         let stage_compute_passes = compute_passes.saturating_sub(self.last_compute_passes);
-        eprintln!(
+        log::info!(target: crate::logging::TYPE_CHECKER,
             "[gpu_compile_host_timer] typecheck.record.{stage}: {dt_ms:.3}ms (total {total_ms:.3}ms compute_passes={stage_compute_passes} total_compute_passes={compute_passes})"
         );
         self.last = now;
@@ -547,6 +547,7 @@ impl GpuTypeChecker {
         let _compute_batch = crate::gpu::passes_core::DeferredComputeBatchGuard::begin(
             false,
             "type_check.resident.batch",
+            false,
         );
         let params = TypeCheckParams {
             n_tokens: token_capacity,
@@ -2265,7 +2266,7 @@ impl GpuTypeChecker {
             let payload_base = hir * 4;
             let scalar_base = payload_base + hir * 4;
             let call_base = scalar_base + hir;
-            eprintln!("[typecheck.semantic_rows] hir_rows={hir}");
+            log::info!(target: crate::logging::TYPE_CHECKER, "[typecheck.semantic_rows] hir_rows={hir}");
             for row in 0..hir {
                 let core = &values[core_base + row * 4..core_base + row * 4 + 4];
                 if core[0] == 0 || core[0] == u32::MAX {
@@ -2273,7 +2274,7 @@ impl GpuTypeChecker {
                 }
                 let payload = &values[payload_base + row * 4..payload_base + row * 4 + 4];
                 let call = &values[call_base + row * 8..call_base + row * 8 + 8];
-                eprintln!(
+                log::info!(target: crate::logging::TYPE_CHECKER,
                     "[typecheck.semantic_rows] hir[{row}] core={core:?} payload={payload:?} scalar={:#010x} call={call:?}",
                     values[scalar_base + row]
                 );
@@ -2287,7 +2288,7 @@ impl GpuTypeChecker {
                 let backend_target = values[backend_base + token];
                 let return_type = values[return_base + token];
                 if semantic_target != u32::MAX || backend_target != u32::MAX || return_type != 0 {
-                    eprintln!(
+                    log::info!(target: crate::logging::TYPE_CHECKER,
                         "[typecheck.semantic_rows] token[{token}] semantic_target={semantic_target} backend_target={backend_target} return_type={return_type}"
                     );
                 }
@@ -2297,7 +2298,7 @@ impl GpuTypeChecker {
         }
 
         if std::env::var_os("LANIUS_DEBUG_STAGE_ERRORS").is_some() {
-            eprintln!("GPU type-check status words: {words:?}");
+            log::info!(target: crate::logging::TYPE_CHECKER, "GPU type-check status words: {words:?}");
         }
 
         if words[0] != 0 {
@@ -3220,10 +3220,7 @@ fn readback_words(device: &wgpu::Device, buffer: &wgpu::Buffer, label: &str) ->
             "{label} readback byte length is not word aligned"
         ));
     }
-    let words = mapped
-        .chunks_exact(4)
-        .map(|bytes| u32::from_le_bytes(bytes.try_into().expect("four-byte chunk")))
-        .collect();
+    let words = crate::gpu::decode::decode_u32s(&mapped);
     drop(mapped);
     buffer.unmap();
     Ok(words)
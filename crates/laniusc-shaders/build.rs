@@ -6,14 +6,24 @@ use std::{
     fs,
     io,
     path::{Path, PathBuf},
-    process::{Command, Output, Stdio},
+    process::Command,
     sync::{Arc, Mutex, mpsc},
     thread,
-    time::{Duration, Instant, SystemTime},
+    time::{Duration, SystemTime},
 };
 
 use anyhow::{Context, Result, anyhow};
 
+#[allow(dead_code)]
+#[path = "src/slang_compile.rs"]
+mod slang_compile;
+
+#[allow(dead_code)]
+#[path = "src/vendored.rs"]
+mod vendored;
+
+use slang_compile::{SlangcFlags, command_output_with_timeout};
+
 fn main() -> Result<()> {
     const DEFAULT_SHADER_COMPILE_TIMEOUT_MS: u64 = 120_000;
 
@@ -30,14 +40,17 @@ fn main() -> Result<()> {
     println!("cargo:rerun-if-env-changed=LANIUS_SHADER_COMPILE_TIMEOUT_MS");
     println!("cargo:rerun-if-env-changed=LANIUS_SHADER_BUILD_JOBS");
     println!("cargo:rerun-if-env-changed=SLANGC_EXTRA_FLAGS");
+    println!("cargo:rerun-if-env-changed=LANIUS_SLANGC_VERSION_TIMEOUT_MS");
 
     let workspace_root = workspace_root()?;
     let shader_root = workspace_root.join("shaders");
-    let compiler_source_root = workspace_root.join("crates/laniusc-compiler/src");
+    let compiler_source_root = workspace_root.join("crates/laniusc-gpu/src");
+    let core_source_root = workspace_root.join("crates/laniusc-core/src");
     track_dir_recursively(&shader_root);
     track_dir_recursively(&compiler_source_root);
-    let slangc = find_slangc()
-        .context("could not locate `slangc` binary. Set $SLANGC or add it to PATH.")?;
+    track_dir_recursively(&core_source_root);
+    generate_shader_constants(&core_source_root, &shader_root)?;
+    let slangc = find_slangc().ok();
     let shader_compile_timeout = timeout_from_env_ms(
         "LANIUS_SHADER_COMPILE_TIMEOUT_MS",
         DEFAULT_SHADER_COMPILE_TIMEOUT_MS,
@@ -59,6 +72,7 @@ fn main() -> Result<()> {
     let max_shader_spv_bytes = shader_max_spv_bytes()?;
 
     // Only compile files that contain an entrypoint attribute, e.g. [shader("compute")]
+    let mut entrypoints = Vec::new();
     for ep in sources {
         if ep.extension().and_then(|e| e.to_str()) != Some("slang") {
             continue;
@@ -71,81 +85,160 @@ fn main() -> Result<()> {
         if !runtime_shader_keys.contains(&artifact_key) {
             continue;
         }
-        let spv_out = shader_out_dir.join(format!("{artifact_key}.spv"));
-        let refl_out = shader_out_dir.join(format!("{artifact_key}.reflect.json"));
-        let stamp_out = shader_out_dir.join(format!("{artifact_key}.stamp"));
-        if let Some(parent) = spv_out.parent() {
-            fs::create_dir_all(parent)
-                .with_context(|| format!("create shader artifact dir {}", parent.display()))?;
-        }
-        let extra = env::var("SLANGC_EXTRA_FLAGS").unwrap_or_default();
-        let extra_args = slangc_extra_args(&extra)?;
-        let opt_level = shader_opt_level_for_artifact(&artifact_key)?;
-        let minimum_slang_opt = shader_minimum_slang_optimization();
-        let disable_non_essential_validations =
-            env_truthy("LANIUS_SHADER_DISABLE_NON_ESSENTIAL_VALIDATIONS");
-        let skip_spirv_validation = env_truthy("LANIUS_SHADER_SKIP_SPIRV_VALIDATION");
-        let report_downstream_time = env_truthy("LANIUS_SHADER_REPORT_DOWNSTREAM_TIME");
-        let report_perf = env_truthy("LANIUS_SHADER_REPORT_PERF");
-        let report_detailed_perf = env_truthy("LANIUS_SHADER_REPORT_DETAILED_PERF");
         let source_digest = shader_source_digest(&shader_root, &ep)?;
-        let compile_stamp = format!(
-            "slangc={}\nopt={}\nminimum_slang_opt={}\ndisable_non_essential_validations={}\nskip_spirv_validation={}\nreport_downstream_time={}\nreport_perf={}\nreport_detailed_perf={}\nsource_digest={}\nextra={}\n",
-            slangc.display(),
-            opt_level,
-            minimum_slang_opt,
-            disable_non_essential_validations,
-            skip_spirv_validation,
-            report_downstream_time,
-            report_perf,
-            report_detailed_perf,
-            source_digest,
-            extra
-        );
-        if shader_outputs_fresh(
-            &shader_root,
-            &ep,
-            &spv_out,
-            &refl_out,
-            &stamp_out,
-            &compile_stamp,
-        )? {
-            validate_shader_artifact_size(&ep, &spv_out, max_shader_spv_bytes)?;
-            shader_artifacts.push((artifact_key, spv_out, refl_out));
-            continue;
-        }
+        entrypoints.push((ep, artifact_key, source_digest));
+    }
 
-        shader_compile_jobs.push(ShaderCompileJob {
-            ep,
-            artifact_key,
-            spv_out,
-            refl_out,
-            stamp_out,
-            opt_level,
-            minimum_slang_opt,
-            disable_non_essential_validations,
-            skip_spirv_validation,
-            report_downstream_time,
-            report_perf,
-            report_detailed_perf,
-            debug: env_truthy("LANIUS_SHADER_DEBUG"),
-            extra_args,
-            compile_stamp,
+    let prebuilt_dir = shader_root.join(vendored::PREBUILT_DIR);
+    let prebuilt = if env::var_os("CARGO_FEATURE_VENDORED_SHADERS").is_some() {
+        read_prebuilt_manifest(&prebuilt_dir)?
+    } else {
+        None
+    };
+    let vendored_state = prebuilt
+        .as_ref()
+        .map_or(vendored::VendoredState::Missing, |manifest| {
+            manifest.state(
+                entrypoints
+                    .iter()
+                    .map(|(_, key, digest)| (key.as_str(), digest.as_str())),
+                |key| {
+                    prebuilt_blobs(&prebuilt_dir, key)
+                        .iter()
+                        .all(|p| p.is_file())
+                },
+            )
         });
-    }
-    shader_artifacts.extend(compile_shader_jobs(
-        shader_compile_jobs,
-        &shader_root,
-        &slangc,
-        shader_compile_timeout,
-        max_shader_spv_bytes,
-    )?);
+    let source_digests = entrypoints
+        .iter()
+        .map(|(_, key, digest)| (key.clone(), digest.clone()))
+        .collect();
+    let build_mode = vendored::choose_build_mode(slangc.is_some(), vendored_state)
+        .ok_or_else(|| {
+            anyhow!(
+                "could not locate `slangc` binary. Set $SLANGC or add it to PATH, or build with the `vendored-shaders` feature and a complete vendored set in {} (see tools/vendor_shaders.sh).",
+                prebuilt_dir.display()
+            )
+        })?;
+
+    let slangc_version = match (build_mode, &slangc, &prebuilt) {
+        (vendored::BuildMode::Vendored, _, Some(manifest)) => {
+            if slangc.is_none() {
+                println!(
+                    "cargo:warning=`slangc` not found; using vendored shaders from {}",
+                    prebuilt_dir.display()
+                );
+            }
+            if vendored_state == vendored::VendoredState::Stale {
+                println!(
+                    "cargo:warning=vendored shaders in {} were built from older Slang sources",
+                    prebuilt_dir.display()
+                );
+            }
+            for (_, artifact_key, _) in &entrypoints {
+                let [spv, _] = prebuilt_blobs(&prebuilt_dir, artifact_key);
+                let (spv_out, refl_out) =
+                    copy_prebuilt_artifact(&prebuilt_dir, &shader_out_dir, artifact_key)?;
+                validate_shader_artifact_size(&spv, &spv_out, max_shader_spv_bytes)?;
+                shader_artifacts.push((artifact_key.clone(), spv_out, refl_out));
+            }
+            manifest.slangc_version.clone()
+        }
+        (_, Some(slangc), _) => {
+            for (ep, artifact_key, source_digest) in entrypoints {
+                let spv_out = shader_out_dir.join(format!("{artifact_key}.spv"));
+                let refl_out = shader_out_dir.join(format!("{artifact_key}.reflect.json"));
+                let stamp_out = shader_out_dir.join(format!("{artifact_key}.stamp"));
+                if let Some(parent) = spv_out.parent() {
+                    fs::create_dir_all(parent).with_context(|| {
+                        format!("create shader artifact dir {}", parent.display())
+                    })?;
+                }
+                let extra = env::var("SLANGC_EXTRA_FLAGS").unwrap_or_default();
+                let extra_args = slangc_extra_args(&extra)?;
+                let opt_level = shader_opt_level_for_artifact(&artifact_key)?;
+                let minimum_slang_opt = shader_minimum_slang_optimization();
+                let disable_non_essential_validations =
+                    env_truthy("LANIUS_SHADER_DISABLE_NON_ESSENTIAL_VALIDATIONS");
+                let skip_spirv_validation = env_truthy("LANIUS_SHADER_SKIP_SPIRV_VALIDATION");
+                let report_downstream_time = env_truthy("LANIUS_SHADER_REPORT_DOWNSTREAM_TIME");
+                let report_perf = env_truthy("LANIUS_SHADER_REPORT_PERF");
+                let report_detailed_perf = env_truthy("LANIUS_SHADER_REPORT_DETAILED_PERF");
+                let compile_stamp = format!(
+                    "slangc={}\nopt={}\nminimum_slang_opt={}\ndisable_non_essential_validations={}\nskip_spirv_validation={}\nreport_downstream_time={}\nreport_perf={}\nreport_detailed_perf={}\nsource_digest={}\nextra={}\n",
+                    slangc.display(),
+                    opt_level,
+                    minimum_slang_opt,
+                    disable_non_essential_validations,
+                    skip_spirv_validation,
+                    report_downstream_time,
+                    report_perf,
+                    report_detailed_perf,
+                    source_digest,
+                    extra
+                );
+                if shader_outputs_fresh(
+                    &shader_root,
+                    &ep,
+                    &spv_out,
+                    &refl_out,
+                    &stamp_out,
+                    &compile_stamp,
+                )? {
+                    validate_shader_artifact_size(&ep, &spv_out, max_shader_spv_bytes)?;
+                    shader_artifacts.push((artifact_key, spv_out, refl_out));
+                    continue;
+                }
+
+                shader_compile_jobs.push(ShaderCompileJob {
+                    ep,
+                    artifact_key,
+                    spv_out,
+                    refl_out,
+                    stamp_out,
+                    flags: SlangcFlags {
+                        opt_level,
+                        minimum_slang_opt,
+                        disable_non_essential_validations,
+                        skip_spirv_validation,
+                        report_downstream_time,
+                        report_perf,
+                        report_detailed_perf,
+                        debug: env_truthy("LANIUS_SHADER_DEBUG"),
+                        extra_args,
+                    },
+                    compile_stamp,
+                });
+            }
+            shader_artifacts.extend(compile_shader_jobs(
+                shader_compile_jobs,
+                &shader_root,
+                slangc,
+                shader_compile_timeout,
+                max_shader_spv_bytes,
+            )?);
+            slangc_version(slangc)?
+        }
+        _ => unreachable!("choose_build_mode only compiles when slangc was found"),
+    };
+    let prebuilt_manifest = vendored::PrebuiltManifest {
+        slangc_version,
+        source_digests,
+    };
+    let manifest_path = shader_out_dir.join(vendored::MANIFEST);
+    write_if_changed(&manifest_path, prebuilt_manifest.render().as_bytes()).with_context(|| {
+        format!(
+            "write shader vendoring manifest {}",
+            manifest_path.display()
+        )
+    })?;
     let active_artifact_keys: HashSet<String> = shader_artifacts
         .iter()
         .map(|(artifact_key, _, _)| artifact_key.clone())
         .collect();
     remove_stale_shader_artifacts(&shader_out_dir, &active_artifact_keys)?;
     write_generated_shader_artifacts(&out_dir, &shader_artifacts)?;
+    write_shader_source_manifest(&shader_out_dir, &shader_root, &shader_artifacts)?;
     let shader_digest = shader_artifact_digest(&shader_artifacts)?;
     let shader_size_summary = shader_artifact_size_summary(&shader_artifacts)?;
     let (shader_size_guard_status, shader_size_guard_max_bytes) =
@@ -157,6 +250,8 @@ fn main() -> Result<()> {
         max_spv_name: shader_size_summary.max_spv_name,
         size_guard_status: shader_size_guard_status.to_string(),
         size_guard_max_bytes: shader_size_guard_max_bytes,
+        mode: build_mode.as_str().to_string(),
+        slangc_version: prebuilt_manifest.slangc_version,
     };
     write_shader_artifact_metadata(&shader_out_dir, &shader_metadata)?;
     if !runtime_loaded_debug_shader_artifacts() {
@@ -225,87 +320,12 @@ fn shader_opt_level() -> Result<String> {
 
 fn shader_opt_level_for_artifact(artifact_key: &str) -> Result<String> {
     let default = shader_opt_level()?;
-    if default != "0" && force_minimum_complex_artifact_optimization(artifact_key) {
+    if default != "0" && slang_compile::forces_minimum_optimization(artifact_key) {
         return Ok("0".to_string());
     }
     Ok(default)
 }
 
-fn force_minimum_complex_artifact_optimization(artifact_key: &str) -> bool {
-    matches!(
-        artifact_key,
-        "parser/tokens/to/kinds"
-            | "parser/syntax/tokens"
-            | "type_checker/predicates/01_collect"
-            | "type_checker/predicates/01a_validate_bound_args"
-            | "type_checker/predicates/01_collect_impls"
-            | "type_checker/predicates/02_obligations"
-            | "type_checker/calls/03a_collect_row_args"
-            | "codegen/x86/node/inst/gen"
-            | "codegen/x86/node/inst/gen/calls"
-            | "codegen/x86/node/inst/gen/statements"
-            | "codegen/x86/node/inst/gen/matches"
-            | "codegen/x86/node/inst/gen/host_calls"
-            | "codegen/x86/node/inst/counts"
-            | "codegen/x86/encode"
-            | "codegen/x86/inst_size"
-            | "codegen/wasm/module"
-            | "codegen/wasm/hir/body_plan"
-            | "codegen/wasm/hir/body_plan_collect"
-            | "codegen/wasm/hir/body_plan_validate"
-            | "codegen/wasm/hir/body_plan_validate_return"
-            | "codegen/wasm/hir/body_plan_validate_return_call"
-            | "codegen/wasm/hir/body_plan_validate_return_agg_call"
-            | "codegen/wasm/hir/body_plan_validate_assign"
-            | "codegen/wasm/hir/body_plan_validate_control"
-            | "codegen/wasm/hir/body_plan_validate_agg_range_control"
-            | "codegen/wasm/hir/body_plan_validate_if_simple"
-            | "codegen/wasm/hir/body_plan_validate_print_simple"
-            | "codegen/wasm/hir/body_plan_validate_call"
-            | "codegen/wasm/hir/body_plan_validate_host_void_call"
-            | "codegen/wasm/hir/body_plan_validate_let_host"
-            | "codegen/wasm/hir/body_plan_validate_let_host_env"
-            | "codegen/wasm/hir/body_plan_validate_let_host_io"
-            | "codegen/wasm/hir/body_plan_validate_let_host_string"
-            | "codegen/wasm/hir/body_plan_validate_return_host_io"
-            | "codegen/wasm/hir/body_plan_validate_return_host_string"
-            | "codegen/wasm/hir/body_plan_validate_let_direct_call"
-            | "codegen/wasm/hir/body_plan_validate_let_call"
-            | "codegen/wasm/hir/body_plan_validate_let_call_status"
-            | "codegen/wasm/hir/body_plan_agg_direct_call"
-            | "codegen/wasm/hir/body_plan_agg_struct"
-            | "codegen/wasm/hir/body_plan_arrays"
-            | "codegen/wasm/hir/body_agg_call_arg_counts"
-            | "codegen/wasm/hir/body_agg_call_arg_records"
-            | "codegen/wasm/hir/body_agg_call_finalize"
-            | "codegen/wasm/hir/body_direct_call_arg_records"
-            | "codegen/wasm/hir/body_direct_call_finalize"
-            | "codegen/wasm/hir/body_scatter_frame"
-            | "codegen/wasm/hir/body_scatter_if_simple"
-            | "codegen/wasm/hir/body_scatter_return_scalar"
-            | "codegen/wasm/hir/body_scatter_return_expr"
-            | "codegen/wasm/hir/body_scatter_conversion_expr"
-            | "codegen/wasm/hir/body_scatter"
-            | "codegen/wasm/hir/body_scatter_direct_nested_call"
-            | "codegen/wasm/hir/body_scatter_array_lean"
-            | "codegen/wasm/hir/body_scatter_let_const"
-            | "codegen/wasm/hir/body_scatter_expr_control"
-            | "codegen/wasm/hir/body_scatter_agg_range_control"
-            | "codegen/wasm/hir/body_scatter_host_io"
-            | "codegen/wasm/hir/body_scatter_host"
-            | "codegen/wasm/hir/body_scatter_stored_expr"
-            // O1 currently expands this pass from about 104 KiB to about 5.9 MiB.
-            | "codegen/wasm/hir/body_scatter_agg_copy"
-            | "codegen/wasm/hir/body_scatter_member_assign"
-            | "codegen/wasm/hir/body_scatter_agg_call_args"
-            | "codegen/wasm/hir/body_scatter_nested_call_args"
-            | "codegen/wasm/hir/body_scatter_agg_direct_call"
-            | "codegen/wasm/hir/body_scatter_return_member"
-            | "codegen/wasm/hir/body_scatter_binary_direct_call"
-            | "codegen/wasm/hir/body_scatter_return_agg_direct_call"
-    )
-}
-
 fn shader_minimum_slang_optimization() -> bool {
     env::var("LANIUS_SHADER_MINIMUM_SLANG_OPT")
         .map(|value| {
@@ -408,15 +428,7 @@ struct ShaderCompileJob {
     spv_out: PathBuf,
     refl_out: PathBuf,
     stamp_out: PathBuf,
-    opt_level: String,
-    minimum_slang_opt: bool,
-    disable_non_essential_validations: bool,
-    skip_spirv_validation: bool,
-    report_downstream_time: bool,
-    report_perf: bool,
-    report_detailed_perf: bool,
-    debug: bool,
-    extra_args: Vec<String>,
+    flags: SlangcFlags,
     compile_stamp: String,
 }
 
@@ -508,54 +520,14 @@ fn compile_shader_job(
     timeout: Option<Duration>,
     max_shader_spv_bytes: Option<u64>,
 ) -> Result<(String, PathBuf, PathBuf)> {
-    let mut cmd = Command::new(slangc);
-    cmd.arg("-target")
-        .arg("spirv")
-        .arg("-profile")
-        .arg("glsl_450")
-        .arg("-fvk-use-entrypoint-name")
-        .arg("-reflection-json")
-        .arg(&job.refl_out)
-        .arg("-emit-spirv-directly")
-        .arg(format!("-O{}", job.opt_level))
-        .arg("-I")
-        .arg(shader_root)
-        .arg("-I")
-        .arg(shader_root.join("lexer"))
-        .arg("-I")
-        .arg(shader_root.join("parser"))
-        .arg("-I")
-        .arg(shader_root.join("type_checker"))
-        .arg("-I")
-        .arg(shader_root.join("codegen"))
-        .arg("-o")
-        .arg(&job.spv_out);
-
-    if job.minimum_slang_opt {
-        cmd.arg("-minimum-slang-optimization");
-    }
-    if job.disable_non_essential_validations {
-        cmd.arg("-disable-non-essential-validations");
-    }
-    if job.skip_spirv_validation {
-        cmd.arg("-skip-spirv-validation");
-    }
-    if job.report_downstream_time {
-        cmd.arg("-report-downstream-time");
-    }
-    if job.report_perf {
-        cmd.arg("-report-perf-benchmark");
-    }
-    if job.report_detailed_perf {
-        cmd.arg("-report-detailed-perf-benchmark");
-    }
-    if job.debug {
-        cmd.arg("-g3");
-    }
-    for arg in &job.extra_args {
-        cmd.arg(arg);
-    }
-    cmd.arg(&job.ep);
+    let mut cmd = slang_compile::slangc_command(
+        slangc,
+        shader_root,
+        &job.ep,
+        &job.spv_out,
+        &job.refl_out,
+        &job.flags,
+    );
 
     let out = command_output_with_timeout(&mut cmd, timeout)
         .with_context(|| format!("failed running slangc for {:?}", job.ep))?;
@@ -582,37 +554,6 @@ fn compile_shader_job(
     Ok((job.artifact_key, job.spv_out, job.refl_out))
 }
 
-fn command_output_with_timeout(
-    command: &mut Command,
-    timeout: Option<Duration>,
-) -> io::Result<Output> {
-    let Some(timeout) = timeout else {
-        return command.output();
-    };
-
-    command.stdout(Stdio::piped()).stderr(Stdio::piped());
-    let mut child = command.spawn()?;
-    let start = Instant::now();
-    loop {
-        if child.try_wait()?.is_some() {
-            return child.wait_with_output();
-        }
-        if start.elapsed() >= timeout {
-            if let Err(err) = child.kill()
-                && err.kind() != io::ErrorKind::InvalidInput
-            {
-                return Err(err);
-            }
-            let _ = child.wait_with_output();
-            return Err(io::Error::new(
-                io::ErrorKind::TimedOut,
-                format!("command timed out after {} ms", timeout.as_millis()),
-            ));
-        }
-        thread::sleep(Duration::from_millis(10));
-    }
-}
-
 fn shader_artifact_digest(artifacts: &[(String, PathBuf, PathBuf)]) -> Result<String> {
     let mut hash = StableHasher::new();
     for (name, spv, refl) in artifacts {
@@ -644,6 +585,8 @@ struct ShaderArtifactBuildMetadata {
     max_spv_name: String,
     size_guard_status: String,
     size_guard_max_bytes: String,
+    mode: String,
+    slangc_version: String,
 }
 
 fn write_shader_artifact_metadata(
@@ -652,18 +595,39 @@ fn write_shader_artifact_metadata(
 ) -> Result<()> {
     let path = shader_out_dir.join("artifacts.env");
     let text = format!(
-        "digest={}\ncount={}\nmax_spv_bytes={}\nmax_spv_name={}\nsize_guard_status={}\nsize_guard_max_bytes={}\n",
+        "digest={}\ncount={}\nmax_spv_bytes={}\nmax_spv_name={}\nsize_guard_status={}\nsize_guard_max_bytes={}\nmode={}\nslangc_version={}\n",
         metadata.digest,
         metadata.count,
         metadata.max_spv_bytes,
         metadata.max_spv_name,
         metadata.size_guard_status,
         metadata.size_guard_max_bytes,
+        metadata.mode,
+        metadata.slangc_version,
     );
     write_if_changed(&path, text.as_bytes())
         .with_context(|| format!("write shader artifact metadata {}", path.display()))
 }
 
+/// Writes the artifact-key to Slang-source manifest read by shader hot reload.
+fn write_shader_source_manifest(
+    shader_out_dir: &Path,
+    shader_root: &Path,
+    artifacts: &[(String, PathBuf, PathBuf)],
+) -> Result<()> {
+    let path = shader_out_dir.join(slang_compile::SOURCE_MANIFEST);
+    let mut keys: Vec<&str> = artifacts.iter().map(|(key, _, _)| key.as_str()).collect();
+    keys.sort_unstable();
+    let text: String = keys
+        .into_iter()
+        .map(|key| {
+            slang_compile::source_manifest_line(key, &shader_root.join(format!("{key}.slang")))
+        })
+        .collect();
+    write_if_changed(&path, text.as_bytes())
+        .with_context(|| format!("write shader source manifest {}", path.display()))
+}
+
 fn write_generated_shader_artifacts(
     out_dir: &Path,
     artifacts: &[(String, PathBuf, PathBuf)],
@@ -694,6 +658,57 @@ fn write_generated_shader_artifacts(
         .with_context(|| format!("write generated shader artifact lookup {}", path.display()))
 }
 
+/// Writes `shaders/generated_constants.slang` from the `// SHADER_CONST` items
+/// in `laniusc-core`'s `src/lexer/constants.rs`, so Rust stays the single
+/// source of truth.
+fn generate_shader_constants(core_source_root: &Path, shader_root: &Path) -> Result<()> {
+    let source = core_source_root.join("lexer/constants.rs");
+    let text = fs::read_to_string(&source)
+        .with_context(|| format!("read shared shader constants {}", source.display()))?;
+    let mut generated = String::from(
+        "// Generated by the laniusc-shaders build script from\n\
+         // crates/laniusc-core/src/lexer/constants.rs. Do not edit by hand.\n\n\
+         module generated_constants;\n\n",
+    );
+    let mut lines = text.lines().map(str::trim);
+    let mut count = 0usize;
+    while let Some(line) = lines.next() {
+        if line != "// SHADER_CONST" {
+            continue;
+        }
+        let declaration = lines
+            .by_ref()
+            .find(|line| !line.starts_with("///"))
+            .ok_or_else(|| anyhow!("SHADER_CONST marker at end of {}", source.display()))?;
+        let (name, value) = parse_shader_const(declaration)
+            .ok_or_else(|| anyhow!("malformed SHADER_CONST item {declaration:?}"))?;
+        generated.push_str(&format!("public static const uint {name} = {value}u;\n"));
+        count += 1;
+    }
+    if count == 0 {
+        return Err(anyhow!("{} has no SHADER_CONST items", source.display()));
+    }
+    let path = shader_root.join("generated_constants.slang");
+    write_if_changed(&path, generated.as_bytes())
+        .with_context(|| format!("write generated shader constants {}", path.display()))
+}
+
+/// Parses `pub const NAME: TYPE = VALUE;` where `VALUE` is `N` or `1 << N`.
+fn parse_shader_const(line: &str) -> Option<(&str, u32)> {
+    let (name, rest) = line.strip_prefix("pub const ")?.split_once(':')?;
+    let (_ty, value) = rest.split_once('=')?;
+    let value = value.trim().strip_suffix(';')?;
+    let value = match value.split_once("<<") {
+        Some((base, shift)) => base
+            .trim()
+            .parse::<u32>()
+            .ok()?
+            .checked_shl(shift.trim().parse().ok()?)?,
+        None => value.parse().ok()?,
+    };
+    Some((name.trim(), value))
+}
+
 fn write_if_changed(path: &Path, bytes: &[u8]) -> Result<()> {
     if fs::read(path).ok().as_deref() == Some(bytes) {
         return Ok(());
@@ -788,6 +803,11 @@ fn emit_shader_artifact_rustc_env(metadata: &ShaderArtifactBuildMetadata) {
         "cargo:rustc-env=LANIUS_SHADER_SIZE_GUARD_MAX_BYTES={}",
         metadata.size_guard_max_bytes
     );
+    println!("cargo:rustc-env=LANIUS_SHADER_BUILD_MODE={}", metadata.mode);
+    println!(
+        "cargo:rustc-env=LANIUS_SHADER_SLANGC_VERSION={}",
+        metadata.slangc_version
+    );
 }
 
 fn runtime_loaded_debug_shader_artifacts() -> bool {
@@ -1110,6 +1130,83 @@ fn find_slangc() -> Result<PathBuf> {
     Err(anyhow!("`slangc` not found"))
 }
 
+fn slangc_version(slangc: &Path) -> Result<String> {
+    const DEFAULT_SLANGC_VERSION_TIMEOUT_MS: u64 = 2_000;
+
+    let timeout = timeout_from_env_ms(
+        "LANIUS_SLANGC_VERSION_TIMEOUT_MS",
+        DEFAULT_SLANGC_VERSION_TIMEOUT_MS,
+    )?;
+    let mut command = Command::new(slangc);
+    command.arg("-version");
+    let version = command_output_with_timeout(&mut command, timeout)
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| {
+            [out.stdout, out.stderr]
+                .into_iter()
+                .map(|bytes| String::from_utf8_lossy(&bytes).trim().to_string())
+                .find(|text| !text.is_empty())
+        });
+    // The version lands on one `key=value` line of the artifact metadata.
+    Ok(version.map_or_else(|| "unknown".to_string(), |v| v.replace(['\n', '\r'], " ")))
+}
+
+/// Reads `shaders/prebuilt/prebuilt.manifest`; `None` when nothing is vendored.
+fn read_prebuilt_manifest(prebuilt_dir: &Path) -> Result<Option<vendored::PrebuiltManifest>> {
+    let path = prebuilt_dir.join(vendored::MANIFEST);
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(err)
+                .with_context(|| format!("read vendored shader manifest {}", path.display()));
+        }
+    };
+    vendored::PrebuiltManifest::parse(&text)
+        .map(Some)
+        .ok_or_else(|| anyhow!("malformed vendored shader manifest {}", path.display()))
+}
+
+/// Vendored `.spv` and `.reflect.json` paths of one artifact key.
+fn prebuilt_blobs(prebuilt_dir: &Path, artifact_key: &str) -> [PathBuf; 2] {
+    [
+        prebuilt_dir.join(format!("{artifact_key}.spv")),
+        prebuilt_dir.join(format!("{artifact_key}.reflect.json")),
+    ]
+}
+
+/// Copies one vendored artifact into the artifact root and drops its compile
+/// stamp, so a later build with `slangc` recompiles it.
+fn copy_prebuilt_artifact(
+    prebuilt_dir: &Path,
+    shader_out_dir: &Path,
+    artifact_key: &str,
+) -> Result<(PathBuf, PathBuf)> {
+    let spv_out = shader_out_dir.join(format!("{artifact_key}.spv"));
+    let refl_out = shader_out_dir.join(format!("{artifact_key}.reflect.json"));
+    if let Some(parent) = spv_out.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("create shader artifact dir {}", parent.display()))?;
+    }
+    for (from, to) in prebuilt_blobs(prebuilt_dir, artifact_key)
+        .iter()
+        .zip([&spv_out, &refl_out])
+    {
+        let bytes =
+            fs::read(from).with_context(|| format!("read vendored shader {}", from.display()))?;
+        write_if_changed(to, &bytes)
+            .with_context(|| format!("copy vendored shader to {}", to.display()))?;
+    }
+    let stamp_out = shader_out_dir.join(format!("{artifact_key}.stamp"));
+    match fs::remove_file(&stamp_out) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => {
+            Err(err).with_context(|| format!("remove shader stamp {}", stamp_out.display()))
+        }
+        _ => Ok((spv_out, refl_out)),
+    }
+}
+
 fn track_dir_recursively<P: AsRef<Path>>(dir: P) {
     let path = dir.as_ref();
 
@@ -1,5 +1,8 @@
 use std::path::PathBuf;
 
+pub mod slang_compile;
+pub mod vendored;
+
 const UNKNOWN: &str = "unknown";
 const ARTIFACT_ROOT: &str = env!("LANIUS_SHADER_ARTIFACT_ROOT");
 
@@ -47,6 +50,16 @@ pub fn size_guard_max_bytes_text() -> String {
     value("size_guard_max_bytes").unwrap_or_else(|| UNKNOWN.to_string())
 }
 
+/// Returns `compiled` or `vendored`, the source of the shader artifact set.
+pub fn build_mode() -> String {
+    value("mode").unwrap_or_else(|| UNKNOWN.to_string())
+}
+
+/// Returns the `slangc` version that built the shader artifact set.
+pub fn slangc_version() -> String {
+    value("slangc_version").unwrap_or_else(|| UNKNOWN.to_string())
+}
+
 pub fn count() -> Option<u64> {
     parse_u64(&count_text())
 }
@@ -73,6 +86,8 @@ fn value(key: &str) -> Option<String> {
         "max_spv_name" => option_env!("LANIUS_SHADER_ARTIFACT_MAX_NAME"),
         "size_guard_status" => option_env!("LANIUS_SHADER_SIZE_GUARD_STATUS"),
         "size_guard_max_bytes" => option_env!("LANIUS_SHADER_SIZE_GUARD_MAX_BYTES"),
+        "mode" => option_env!("LANIUS_SHADER_BUILD_MODE"),
+        "slangc_version" => option_env!("LANIUS_SHADER_SLANGC_VERSION"),
         _ => None,
     }
     .map(str::to_string)
@@ -0,0 +1,232 @@
+//! `slangc` invocation shared by the build script and the compiler's
+//! `shader-hot-reload` feature.
+//!
+//! This module only uses `std` so the build script can include it by path and
+//! the compiler can reuse it without depending on this crate.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// File name of the artifact-key to Slang-source manifest in the artifact root.
+pub const SOURCE_MANIFEST: &str = "sources.manifest";
+
+/// Shader subdirectories passed to `slangc` as include roots.
+const INCLUDE_SUBDIRS: [&str; 4] = ["lexer", "parser", "type_checker", "codegen"];
+
+/// Per-artifact `slangc` flags owned by the build policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlangcFlags {
+    pub opt_level: String,
+    pub minimum_slang_opt: bool,
+    pub disable_non_essential_validations: bool,
+    pub skip_spirv_validation: bool,
+    pub report_downstream_time: bool,
+    pub report_perf: bool,
+    pub report_detailed_perf: bool,
+    pub debug: bool,
+    pub extra_args: Vec<String>,
+}
+
+impl Default for SlangcFlags {
+    fn default() -> Self {
+        Self {
+            opt_level: "1".into(),
+            minimum_slang_opt: true,
+            disable_non_essential_validations: false,
+            skip_spirv_validation: false,
+            report_downstream_time: false,
+            report_perf: false,
+            report_detailed_perf: false,
+            debug: false,
+            extra_args: Vec::new(),
+        }
+    }
+}
+
+/// Builds the `slangc` command that compiles `source` to SPIR-V plus
+/// reflection JSON.
+pub fn slangc_command(
+    slangc: &Path,
+    shader_root: &Path,
+    source: &Path,
+    spv_out: &Path,
+    reflection_out: &Path,
+    flags: &SlangcFlags,
+) -> Command {
+    let mut cmd = Command::new(slangc);
+    cmd.arg("-target")
+        .arg("spirv")
+        .arg("-profile")
+        .arg("glsl_450")
+        .arg("-fvk-use-entrypoint-name")
+        .arg("-reflection-json")
+        .arg(reflection_out)
+        .arg("-emit-spirv-directly")
+        .arg(format!("-O{}", flags.opt_level))
+        .arg("-I")
+        .arg(shader_root);
+    for subdir in INCLUDE_SUBDIRS {
+        cmd.arg("-I").arg(shader_root.join(subdir));
+    }
+    cmd.arg("-o").arg(spv_out);
+
+    if flags.minimum_slang_opt {
+        cmd.arg("-minimum-slang-optimization");
+    }
+    if flags.disable_non_essential_validations {
+        cmd.arg("-disable-non-essential-validations");
+    }
+    if flags.skip_spirv_validation {
+        cmd.arg("-skip-spirv-validation");
+    }
+    if flags.report_downstream_time {
+        cmd.arg("-report-downstream-time");
+    }
+    if flags.report_perf {
+        cmd.arg("-report-perf-benchmark");
+    }
+    if flags.report_detailed_perf {
+        cmd.arg("-report-detailed-perf-benchmark");
+    }
+    if flags.debug {
+        cmd.arg("-g3");
+    }
+    for arg in &flags.extra_args {
+        cmd.arg(arg);
+    }
+    cmd.arg(source);
+    cmd
+}
+
+/// Runs `command`, killing it once `timeout` elapses; `None` waits forever.
+pub fn command_output_with_timeout(
+    command: &mut Command,
+    timeout: Option<Duration>,
+) -> io::Result<Output> {
+    let Some(timeout) = timeout else {
+        return command.output();
+    };
+
+    command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = command.spawn()?;
+    let start = Instant::now();
+    loop {
+        if child.try_wait()?.is_some() {
+            return child.wait_with_output();
+        }
+        if start.elapsed() >= timeout {
+            if let Err(err) = child.kill()
+                && err.kind() != io::ErrorKind::InvalidInput
+            {
+                return Err(err);
+            }
+            let _ = child.wait_with_output();
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("command timed out after {} ms", timeout.as_millis()),
+            ));
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Formats one `key<TAB>source` manifest line.
+pub fn source_manifest_line(artifact_key: &str, source: &Path) -> String {
+    format!("{artifact_key}\t{}\n", source.display())
+}
+
+/// Parses manifest text into `(artifact key, source path)` rows, skipping
+/// malformed lines.
+pub fn parse_source_manifest(text: &str) -> Vec<(String, PathBuf)> {
+    text.lines()
+        .filter_map(|line| {
+            let (key, source) = line.split_once('\t')?;
+            (!key.is_empty() && !source.is_empty())
+                .then(|| (key.to_string(), PathBuf::from(source)))
+        })
+        .collect()
+}
+
+/// Whether `artifact_key` is built at `-O0` regardless of the configured level.
+///
+/// These shaders have control flow that higher Slang optimization levels
+/// expand past the SPIR-V size guard.
+pub fn forces_minimum_optimization(artifact_key: &str) -> bool {
+    matches!(
+        artifact_key,
+        "parser/tokens/to/kinds"
+            | "parser/syntax/tokens"
+            | "type_checker/predicates/01_collect"
+            | "type_checker/predicates/01a_validate_bound_args"
+            | "type_checker/predicates/01_collect_impls"
+            | "type_checker/predicates/02_obligations"
+            | "type_checker/calls/03a_collect_row_args"
+            | "codegen/x86/node/inst/gen"
+            | "codegen/x86/node/inst/gen/calls"
+            | "codegen/x86/node/inst/gen/statements"
+            | "codegen/x86/node/inst/gen/matches"
+            | "codegen/x86/node/inst/gen/host_calls"
+            | "codegen/x86/node/inst/counts"
+            | "codegen/x86/encode"
+            | "codegen/x86/inst_size"
+            | "codegen/wasm/module"
+            | "codegen/wasm/hir/body_plan"
+            | "codegen/wasm/hir/body_plan_collect"
+            | "codegen/wasm/hir/body_plan_validate"
+            | "codegen/wasm/hir/body_plan_validate_return"
+            | "codegen/wasm/hir/body_plan_validate_return_call"
+            | "codegen/wasm/hir/body_plan_validate_return_agg_call"
+            | "codegen/wasm/hir/body_plan_validate_assign"
+            | "codegen/wasm/hir/body_plan_validate_control"
+            | "codegen/wasm/hir/body_plan_validate_agg_range_control"
+            | "codegen/wasm/hir/body_plan_validate_if_simple"
+            | "codegen/wasm/hir/body_plan_validate_print_simple"
+            | "codegen/wasm/hir/body_plan_validate_call"
+            | "codegen/wasm/hir/body_plan_validate_host_void_call"
+            | "codegen/wasm/hir/body_plan_validate_let_host"
+            | "codegen/wasm/hir/body_plan_validate_let_host_env"
+            | "codegen/wasm/hir/body_plan_validate_let_host_io"
+            | "codegen/wasm/hir/body_plan_validate_let_host_string"
+            | "codegen/wasm/hir/body_plan_validate_return_host_io"
+            | "codegen/wasm/hir/body_plan_validate_return_host_string"
+            | "codegen/wasm/hir/body_plan_validate_let_direct_call"
+            | "codegen/wasm/hir/body_plan_validate_let_call"
+            | "codegen/wasm/hir/body_plan_validate_let_call_status"
+            | "codegen/wasm/hir/body_plan_agg_direct_call"
+            | "codegen/wasm/hir/body_plan_agg_struct"
+            | "codegen/wasm/hir/body_plan_arrays"
+            | "codegen/wasm/hir/body_agg_call_arg_counts"
+            | "codegen/wasm/hir/body_agg_call_arg_records"
+            | "codegen/wasm/hir/body_agg_call_finalize"
+            | "codegen/wasm/hir/body_direct_call_arg_records"
+            | "codegen/wasm/hir/body_direct_call_finalize"
+            | "codegen/wasm/hir/body_scatter_frame"
+            | "codegen/wasm/hir/body_scatter_if_simple"
+            | "codegen/wasm/hir/body_scatter_return_scalar"
+            | "codegen/wasm/hir/body_scatter_return_expr"
+            | "codegen/wasm/hir/body_scatter_conversion_expr"
+            | "codegen/wasm/hir/body_scatter"
+            | "codegen/wasm/hir/body_scatter_direct_nested_call"
+            | "codegen/wasm/hir/body_scatter_array_lean"
+            | "codegen/wasm/hir/body_scatter_let_const"
+            | "codegen/wasm/hir/body_scatter_expr_control"
+            | "codegen/wasm/hir/body_scatter_agg_range_control"
+            | "codegen/wasm/hir/body_scatter_host_io"
+            | "codegen/wasm/hir/body_scatter_host"
+            | "codegen/wasm/hir/body_scatter_stored_expr"
+            // O1 currently expands this pass from about 104 KiB to about 5.9 MiB.
+            | "codegen/wasm/hir/body_scatter_agg_copy"
+            | "codegen/wasm/hir/body_scatter_member_assign"
+            | "codegen/wasm/hir/body_scatter_agg_call_args"
+            | "codegen/wasm/hir/body_scatter_nested_call_args"
+            | "codegen/wasm/hir/body_scatter_agg_direct_call"
+            | "codegen/wasm/hir/body_scatter_return_member"
+            | "codegen/wasm/hir/body_scatter_binary_direct_call"
+            | "codegen/wasm/hir/body_scatter_return_agg_direct_call"
+    )
+}
@@ -0,0 +1,172 @@
+//! Vendored SPIR-V fallback shared by the build script and its tests.
+//!
+//! `shaders/prebuilt/` mirrors the build's artifact root: a `.spv` and a
+//! `.reflect.json` per artifact key, plus a [`MANIFEST`] that records the
+//! `slangc` version and the source digest each pair was compiled from. The
+//! build script writes that manifest next to every compiled artifact set, and
+//! `tools/vendor_shaders.sh` copies the set into `shaders/prebuilt/`.
+//!
+//! This module only uses `std` so the build script can include it by path.
+
+use std::collections::BTreeMap;
+
+/// Directory under `shaders/` holding the vendored artifact set.
+pub const PREBUILT_DIR: &str = "prebuilt";
+
+/// File name of the vendored-set manifest, in both the artifact root and
+/// `shaders/prebuilt/`.
+pub const MANIFEST: &str = "prebuilt.manifest";
+
+/// Where the shader artifacts of a build came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildMode {
+    /// Compiled from the Slang sources by `slangc`.
+    Compiled,
+    /// Copied from `shaders/prebuilt/`.
+    Vendored,
+}
+
+impl BuildMode {
+    /// Name recorded in `artifacts.env` and reported by `--version`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Compiled => "compiled",
+            Self::Vendored => "vendored",
+        }
+    }
+}
+
+/// How the vendored set relates to the current Slang sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VendoredState {
+    /// Some artifact has no vendored blob, or vendoring is not enabled.
+    Missing,
+    /// Every artifact is vendored, but some sources changed since.
+    Stale,
+    /// Every artifact is vendored from the current sources.
+    Current,
+}
+
+/// Picks the artifact source for a build, or `None` if neither is usable.
+///
+/// `slangc` is preferred whenever the vendored set is missing or stale; a
+/// current vendored set is used as is, and a stale one only without `slangc`.
+pub fn choose_build_mode(slangc_found: bool, vendored: VendoredState) -> Option<BuildMode> {
+    match (slangc_found, vendored) {
+        (_, VendoredState::Current) => Some(BuildMode::Vendored),
+        (true, _) => Some(BuildMode::Compiled),
+        (false, VendoredState::Stale) => Some(BuildMode::Vendored),
+        (false, VendoredState::Missing) => None,
+    }
+}
+
+/// Contents of a [`MANIFEST`] file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrebuiltManifest {
+    /// `slangc -version` output of the compiler that built the set.
+    pub slangc_version: String,
+    /// Source digest of each artifact key.
+    pub source_digests: BTreeMap<String, String>,
+}
+
+impl PrebuiltManifest {
+    /// Parses `slangc_version=...` followed by one `key digest` line per
+    /// artifact.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut lines = text.lines();
+        let slangc_version = lines.next()?.strip_prefix("slangc_version=")?.to_string();
+        let source_digests = lines
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let (key, digest) = line.split_once(' ')?;
+                Some((key.to_string(), digest.to_string()))
+            })
+            .collect::<Option<_>>()?;
+        Some(Self {
+            slangc_version,
+            source_digests,
+        })
+    }
+
+    /// Renders the manifest in the form [`Self::parse`] reads.
+    pub fn render(&self) -> String {
+        let mut text = format!("slangc_version={}\n", self.slangc_version);
+        for (key, digest) in &self.source_digests {
+            text.push_str(&format!("{key} {digest}\n"));
+        }
+        text
+    }
+
+    /// Compares the vendored set against the current source digests; `has_blobs`
+    /// reports whether both files of an artifact key are present.
+    pub fn state<'a>(
+        &self,
+        current: impl IntoIterator<Item = (&'a str, &'a str)>,
+        has_blobs: impl Fn(&str) -> bool,
+    ) -> VendoredState {
+        let mut state = VendoredState::Current;
+        for (key, digest) in current {
+            if !has_blobs(key) {
+                return VendoredState::Missing;
+            }
+            match self.source_digests.get(key) {
+                Some(vendored) if vendored == digest => {}
+                Some(_) => state = VendoredState::Stale,
+                None => return VendoredState::Missing,
+            }
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_mode_covers_the_three_scenarios() {
+        use VendoredState::*;
+
+        // Fresh compilation wins when slangc is found and sources moved on.
+        assert_eq!(choose_build_mode(true, Stale), Some(BuildMode::Compiled));
+        assert_eq!(choose_build_mode(true, Missing), Some(BuildMode::Compiled));
+        assert_eq!(choose_build_mode(true, Current), Some(BuildMode::Vendored));
+        // Without slangc the vendored set is used, stale or not.
+        assert_eq!(choose_build_mode(false, Current), Some(BuildMode::Vendored));
+        assert_eq!(choose_build_mode(false, Stale), Some(BuildMode::Vendored));
+        // Neither is available.
+        assert_eq!(choose_build_mode(false, Missing), None);
+    }
+
+    #[test]
+    fn manifest_round_trips_and_rates_the_vendored_set() {
+        let manifest = PrebuiltManifest {
+            slangc_version: "v2025.1".into(),
+            source_digests: [("lexer/a", "11"), ("parser/b", "22")]
+                .into_iter()
+                .map(|(key, digest)| (key.to_string(), digest.to_string()))
+                .collect(),
+        };
+        assert_eq!(
+            PrebuiltManifest::parse(&manifest.render()),
+            Some(manifest.clone())
+        );
+        assert_eq!(PrebuiltManifest::parse("lexer/a 11\n"), None);
+
+        let all = |_: &str| true;
+        let current = [("lexer/a", "11"), ("parser/b", "22")];
+        assert_eq!(manifest.state(current, all), VendoredState::Current);
+        assert_eq!(
+            manifest.state([("lexer/a", "11"), ("parser/b", "23")], all),
+            VendoredState::Stale
+        );
+        assert_eq!(
+            manifest.state([("lexer/a", "11"), ("codegen/c", "33")], all),
+            VendoredState::Missing
+        );
+        assert_eq!(
+            manifest.state(current, |key| key != "parser/b"),
+            VendoredState::Missing
+        );
+    }
+}
@@ -0,0 +1,170 @@
+// Writes seed corpora for the cargo-fuzz targets in fuzz/.
+//
+// Seeds are the lexer golden cases under tests/cases, the self-test vector
+// sources (picked to cross DFA blocks and end mid-token), and a few sources
+// from every generator preset. Run from the repository root:
+//
+//     cargo run -p laniusc-tools --bin fuzz_seed_corpus [-- OUT_DIR]
+//
+// OUT_DIR defaults to fuzz/corpus; each target gets the same seeds in its
+// own subdirectory, where `cargo fuzz run <target>` picks them up.
+
+use std::{fs, path::Path};
+
+use laniusc_compiler::{
+    dev::generator::{PROFILES, SourceGenConfig, gen_source},
+    self_test::vectors::SelfTestVectors,
+};
+use log::warn;
+use rand::{SeedableRng, rngs::StdRng};
+
+const DEFAULT_OUT_DIR: &str = "fuzz/corpus";
+const TARGETS: &[&str] = &["fuzz_cpu_invariants", "fuzz_lex_bytes", "fuzz_table_layout"];
+const CASE_DIRS: &[&str] = &["tests/cases/lexer", "tests/cases/edge"];
+/// Generated seed lengths per preset; libFuzzer's default `-max_len` is 4096.
+const GENERATED_LENS: &[usize] = &[64, 512, 4000];
+const SEED: u64 = 929;
+
+/// One named seed input.
+struct Seed {
+    name: String,
+    bytes: Vec<u8>,
+}
+
+fn main() -> std::io::Result<()> {
+    let _ = laniusc_compiler::logging::init_default();
+    let mut args = std::env::args().skip(1);
+    let out_dir = args.next().unwrap_or_else(|| DEFAULT_OUT_DIR.into());
+    if let Some(arg) = args.next() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("unexpected argument {arg:?}; fuzz_seed_corpus takes one output directory"),
+        ));
+    }
+
+    let mut seeds = case_seeds()?;
+    seeds.extend(vector_seeds());
+    seeds.extend(generated_seeds());
+    for target in TARGETS {
+        let dir = Path::new(&out_dir).join(target);
+        fs::create_dir_all(&dir)?;
+        for seed in &seeds {
+            fs::write(dir.join(&seed.name), &seed.bytes)?;
+        }
+    }
+    let bytes: usize = seeds.iter().map(|seed| seed.bytes.len()).sum();
+    println!(
+        "[fuzz_seed_corpus] wrote {} seeds ({bytes} bytes) for {} → {out_dir}",
+        seeds.len(),
+        TARGETS.join(", ")
+    );
+    Ok(())
+}
+
+/// The `.lani` lexer cases, as they are on disk.
+fn case_seeds() -> std::io::Result<Vec<Seed>> {
+    let mut seeds = Vec::new();
+    for dir in CASE_DIRS {
+        let path = Path::new(dir);
+        if !path.is_dir() {
+            warn!("case directory {dir} does not exist; run from the repository root");
+            continue;
+        }
+        let group = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("");
+        for entry in fs::read_dir(path)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "lani")
+                && let Some(stem) = path.file_stem().and_then(|stem| stem.to_str())
+            {
+                seeds.push(Seed {
+                    name: format!("case-{group}-{stem}"),
+                    bytes: fs::read(&path)?,
+                });
+            }
+        }
+    }
+    seeds.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(seeds)
+}
+
+/// Every source of the embedded self-test vectors.
+fn vector_seeds() -> Vec<Seed> {
+    SelfTestVectors::embedded()
+        .checks
+        .into_iter()
+        .flat_map(|check| {
+            let name = check.name;
+            check
+                .sources
+                .into_iter()
+                .enumerate()
+                .map(move |(i, source)| Seed {
+                    name: format!("selftest-{name}-{i}"),
+                    bytes: source.into_bytes(),
+                })
+        })
+        .collect()
+}
+
+/// One source per preset and length in [`GENERATED_LENS`], from a fixed seed.
+fn generated_seeds() -> Vec<Seed> {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut seeds = Vec::new();
+    for profile in PROFILES {
+        let config = SourceGenConfig::from_profile(profile).expect("PROFILES names a preset");
+        for &target_len in GENERATED_LENS {
+            let source = gen_source(
+                &mut rng,
+                &SourceGenConfig {
+                    target_len,
+                    ..config.clone()
+                },
+            );
+            seeds.push(Seed {
+                name: format!("gen-{profile}-{target_len}"),
+                bytes: source.into_bytes(),
+            });
+        }
+    }
+    seeds
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use laniusc_compiler::lexer::test_cpu::lex_on_test_cpu_bytes;
+
+    use super::*;
+
+    #[test]
+    fn generated_and_vector_seeds_are_named_uniquely_and_lex() {
+        let seeds: Vec<_> = vector_seeds()
+            .into_iter()
+            .chain(generated_seeds())
+            .collect();
+        assert_eq!(
+            seeds.len(),
+            SelfTestVectors::embedded()
+                .checks
+                .iter()
+                .map(|check| check.sources.len())
+                .sum::<usize>()
+                + PROFILES.len() * GENERATED_LENS.len()
+        );
+        let names: HashSet<_> = seeds.iter().map(|seed| seed.name.as_str()).collect();
+        assert_eq!(names.len(), seeds.len());
+        for seed in &seeds {
+            lex_on_test_cpu_bytes(&seed.bytes).unwrap_or_else(|err| panic!("{}: {err}", seed.name));
+        }
+    }
+
+    #[test]
+    fn generated_seeds_are_reproducible() {
+        let bytes = |seeds: Vec<Seed>| -> Vec<_> { seeds.into_iter().map(|s| s.bytes).collect() };
+        assert_eq!(bytes(generated_seeds()), bytes(generated_seeds()));
+    }
+}
@@ -0,0 +1,304 @@
+// Writes tables/selftest_vectors.bin, the vectors `laniusc_compiler::self_test`
+// runs at startup. Every expectation comes from the test CPU oracle; the
+// inputs are picked to cross DFA and pair block edges, end mid-token, and
+// reach each lexer pass once. Rerun it whenever the lexer or the MVP parser
+// tables change what these inputs produce.
+
+use std::{fs, path::Path};
+
+use laniusc_compiler::{
+    lexer::{
+        Token,
+        constants::DFA_BLOCK_WIDTH,
+        test_cpu::{lex_on_test_cpu, lex_on_test_cpu_with_options},
+    },
+    parser::retag::retag_open_delimiters,
+    self_test::{
+        parse_tables,
+        vectors::{CheckKind, Expected, SelfTestVectors, VectorCheck, VectorOptions},
+    },
+    span::Span,
+};
+
+const OUT_PATH: &str = "tables/selftest_vectors.bin";
+
+/// A small program touching every token family, trivia and escapes included.
+const EVERY_PASS: &str = "// line comment\n\
+fn main() -> i32 {\n\
+    /* block */ let s = \"tab\\t quote\\\" \\u{48}\";\n\
+    let c = '\\'';\n\
+    let r = 0..10;\n\
+    let f = 1.5e3 + 0x1F as f32;\n\
+    if s != \"\" && true || !false { return [1, 2][0] % 3; }\n\
+    while r.start <= 9 { break; }\n\
+    return (c as i32) << 2 >> 1;\n\
+}\n";
+
+/// Brackets of every family inside calls, indexing, and grouping.
+const PARSE_SOURCE: &str = "fn main() {\n\
+    let xs = [1, 2, [3, 4][0]];\n\
+    return f(xs[0], (xs[1] + 2) * g()[1]);\n\
+}\n";
+
+fn main() -> std::io::Result<()> {
+    let _ = laniusc_compiler::logging::init_default();
+    if let Some(arg) = std::env::args().nth(1) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("unknown argument {arg:?}; gen_selftest_vectors takes none"),
+        ));
+    }
+    let vectors = build_vectors();
+    let bytes = vectors.encode();
+    let out_path = Path::new(OUT_PATH);
+    if let Some(dir) = out_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(out_path, &bytes)?;
+    let source_bytes: usize = vectors
+        .checks
+        .iter()
+        .flat_map(|check| &check.sources)
+        .map(String::len)
+        .sum();
+    println!(
+        "[gen_selftest_vectors] wrote {} checks over {source_bytes} source bytes in {} bytes → {}",
+        vectors.checks.len(),
+        bytes.len(),
+        out_path.display()
+    );
+    Ok(())
+}
+
+fn build_vectors() -> SelfTestVectors {
+    let two_submissions = VectorOptions {
+        two_submissions: true,
+        ..VectorOptions::default()
+    };
+    let dense = dense_program(32);
+    let mut checks = vec![
+        lex_check("empty", "", VectorOptions::default()),
+        lex_check(
+            "every_pass",
+            EVERY_PASS,
+            VectorOptions {
+                all_tokens: true,
+                capture_string_escapes: true,
+                ..VectorOptions::default()
+            },
+        ),
+        lex_check("pair_blocks_two_submissions", &dense, two_submissions),
+        lex_check(
+            "pair_blocks_compressed",
+            &dense,
+            VectorOptions {
+                compress_readback: true,
+                ..two_submissions
+            },
+        ),
+        lex_check(
+            "dfa_block_edges",
+            &block_edge_source(),
+            VectorOptions {
+                all_tokens: true,
+                capture_string_escapes: true,
+                ..VectorOptions::default()
+            },
+        ),
+    ];
+    // A whole block, one byte short of it, and one byte into the next, each
+    // cutting the repeated statement mid-token.
+    let width = DFA_BLOCK_WIDTH as usize;
+    for (name, len) in [
+        ("block_minus_one", width - 1),
+        ("block_exact", width),
+        ("block_plus_one", width + 1),
+    ] {
+        let source: String = "let ab = cd + 12;\n".chars().cycle().take(len).collect();
+        checks.push(lex_check(name, &source, VectorOptions::default()));
+    }
+    for (name, source) in [
+        ("eof_ident", "let x = abc"),
+        ("eof_float_dot", "let x = 1."),
+        ("eof_range", "for i in 0.."),
+        ("eof_line_comment", "x // no newline"),
+        ("eof_string", "let s = \"done\""),
+    ] {
+        checks.push(lex_check(name, source, VectorOptions::default()));
+    }
+    checks.push(source_pack_check(
+        "source_pack",
+        &["fn a() {}\n", "", "\u{feff}let b = \"x\";", "// tail"],
+    ));
+    checks.push(parse_check("parse_round_trip", PARSE_SOURCE));
+    SelfTestVectors { checks }
+}
+
+/// `statements` short statements of thirteen kept tokens each; 32 of them
+/// cross the first pair block edge.
+fn dense_program(statements: usize) -> String {
+    (0..statements)
+        .map(|i| format!("x{i}=(a+b)*c[{i}];"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Tokens that straddle the first DFA block edges: an identifier, a string
+/// escape, a block comment, and a float whose dot starts the next block.
+fn block_edge_source() -> String {
+    let width = DFA_BLOCK_WIDTH as usize;
+    let mut source = String::new();
+    let mut place = |at: usize, text: &str| {
+        while source.len() < at {
+            source.push(if source.len() % 32 == 31 { '\n' } else { ' ' });
+        }
+        source.push_str(text);
+    };
+    place(width - 6, "long_identifier = 1;");
+    place(2 * width - 8, "s = \"ab\\ncd\";");
+    place(3 * width - 4, "/* across */ y");
+    place(4 * width - 1, "1.25;");
+    source
+}
+
+fn lex_check(name: &str, source: &str, options: VectorOptions) -> VectorCheck {
+    let output = lex_on_test_cpu_with_options(source, options.lex_options())
+        .unwrap_or_else(|err| panic!("{name}: test CPU oracle rejected the source: {err}"));
+    VectorCheck {
+        name: name.into(),
+        kind: CheckKind::Lex,
+        options,
+        sources: vec![source.into()],
+        expected: Expected {
+            tokens: output.tokens,
+            all_tokens: output.all_tokens,
+            escape_spans: output.escape_spans,
+            ..Expected::default()
+        },
+    }
+}
+
+fn oracle_tokens(name: &str, source: &str) -> Vec<Token> {
+    lex_on_test_cpu(source)
+        .unwrap_or_else(|err| panic!("{name}: test CPU oracle rejected the source: {err}"))
+}
+
+fn source_pack_check(name: &str, files: &[&str]) -> VectorCheck {
+    let mut tokens = Vec::new();
+    let mut base = 0;
+    for file in files {
+        tokens.extend(oracle_tokens(name, file).into_iter().map(|token| Token {
+            span: Span::from_usize(base + token.start(), token.len()),
+            ..token
+        }));
+        base += file.len();
+    }
+    VectorCheck {
+        name: name.into(),
+        kind: CheckKind::SourcePack,
+        options: VectorOptions::default(),
+        sources: files.iter().map(|file| file.to_string()).collect(),
+        expected: Expected {
+            tokens,
+            ..Expected::default()
+        },
+    }
+}
+
+fn parse_check(name: &str, source: &str) -> VectorCheck {
+    let tokens = oracle_tokens(name, source);
+    let tables = parse_tables();
+    // The GPU parser retags raw `(` and `[` before the pair lookup; the MVP
+    // tables only push for retagged openers.
+    let kept: Vec<_> = tokens.iter().map(|t| t.kind).collect();
+    let kinds: Vec<u32> = retag_open_delimiters(&kept)
+        .into_iter()
+        .map(|kind| kind as u32)
+        .collect();
+    let wrapped = tables
+        .wrap_input(&kinds)
+        .unwrap_or_else(|err| panic!("{name}: {err}"));
+    let sc_stream = tables.test_cpu_stack_change_stream(&wrapped);
+    let emit_stream = tables.test_cpu_partial_parse_stream(&wrapped);
+    let brackets_valid = brackets_match(&sc_stream);
+    assert!(brackets_valid, "{name}: the parse source must balance");
+    VectorCheck {
+        name: name.into(),
+        kind: CheckKind::Parse,
+        options: VectorOptions::default(),
+        sources: vec![source.into()],
+        expected: Expected {
+            tokens,
+            sc_stream,
+            emit_stream,
+            brackets_valid,
+            ..Expected::default()
+        },
+    }
+}
+
+/// Whether every pop closes the most recent open push of its symbol and
+/// nothing is left open; pushes are odd codes, pops even.
+fn brackets_match(sc_stream: &[u32]) -> bool {
+    let mut open = Vec::new();
+    for &code in sc_stream {
+        if code & 1 == 1 {
+            open.push(code >> 1);
+        } else if open.pop() != Some(code >> 1) {
+            return false;
+        }
+    }
+    open.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use laniusc_compiler::{
+        lexer::constants::PAIR_BLOCK_WIDTH,
+        self_test::vectors::EMBEDDED_VECTORS,
+    };
+
+    use super::*;
+
+    #[test]
+    fn checked_in_vectors_match_the_oracle() {
+        assert!(
+            build_vectors().encode() == EMBEDDED_VECTORS,
+            "{OUT_PATH} is stale; rerun `cargo run -p laniusc-tools --bin gen_selftest_vectors`"
+        );
+    }
+
+    #[test]
+    fn block_edge_tokens_straddle_the_edges() {
+        let options = VectorOptions {
+            all_tokens: true,
+            ..VectorOptions::default()
+        };
+        let tokens = lex_on_test_cpu_with_options(&block_edge_source(), options.lex_options())
+            .unwrap()
+            .all_tokens;
+        for edge in 1..=4 {
+            let edge = edge * DFA_BLOCK_WIDTH;
+            assert!(
+                tokens
+                    .iter()
+                    .any(|t| t.span.start < edge && edge < t.span.end),
+                "no token crosses byte {edge}"
+            );
+        }
+    }
+
+    #[test]
+    fn dense_program_crosses_a_pair_block() {
+        let tokens = oracle_tokens("dense", &dense_program(32));
+        assert!(tokens.len() > PAIR_BLOCK_WIDTH as usize, "{}", tokens.len());
+    }
+
+    #[test]
+    fn bracket_matching_needs_typed_pairs() {
+        assert!(brackets_match(&[1, 3, 2, 0]));
+        assert!(!brackets_match(&[1, 3, 0, 2]));
+        assert!(!brackets_match(&[1]));
+        assert!(!brackets_match(&[0]));
+    }
+}
@@ -28,12 +28,14 @@ use laniusc_compiler::{
         FilesystemArtifactStore,
         GpuCompiler,
         GpuCompilerBackends,
+        GpuCompilerOptions,
         PreparedBuild,
         prepare_artifact_build_chunk,
         resume_metadata_chunk_for_target,
+        set_compiler_options,
     },
-    gpu::{buffers, device, trace},
-    parser::tables::PrecomputedParseTables,
+    gpu::{buffers, device, poll, trace},
+    parser::{BracketCacheLimits, tables::PrecomputedParseTables},
 };
 use sources::{SourceArtifact, make_source_artifact};
 
@@ -72,6 +74,16 @@ impl SourcePackDescriptorRunConfig {
 }
 
 fn main() {
+    let _ = laniusc_compiler::logging::init_default();
+    // Repeated runs over one source would otherwise reuse its bracket
+    // matches and time a parse without stack-effect validation.
+    set_compiler_options(GpuCompilerOptions {
+        bracket_cache: BracketCacheLimits {
+            max_entries: 0,
+            ..BracketCacheLimits::default()
+        },
+        ..GpuCompilerOptions::from_env()
+    });
     if let Err(err) = pollster::block_on(run()) {
         eprintln!("gpu_compile_bench: {err}");
         std::process::exit(1);
@@ -620,9 +632,13 @@ async fn run_source_suite(
         }
         if allow_large {
             compiler = None;
-            let _ = device::global()
-                .device
-                .poll(wgpu::PollType::wait_indefinitely());
+            let outcome = poll::wait_with_timeout(
+                &device::global().device,
+                device::device_options().wait_timeout,
+            );
+            if outcome != poll::PollOutcome::Complete {
+                eprintln!("waiting for released compiler buffers: {outcome:?}");
+            }
         }
     }
     if suite_sources != 0 && !estimate_only && !estimate_live {
@@ -0,0 +1,109 @@
+// Times the syntax-highlight lex profile against the default profile.
+//
+// Both profiles lex the same generated source and read back its ALL stream:
+// the default one as `GpuLexer::lex_all` does, the highlight one through
+// `GpuLexer::highlight`, which packs kept tokens on the GPU and skips the
+// report and every optional pass. Runs alternate between the two and must
+// agree on every highlighted span. Run from the repository root:
+//
+//     cargo run --release -p laniusc-tools --bin highlight_bench -- \
+//         [--bytes N] [--runs N] [--seed N]
+
+use std::{env, time::Instant};
+
+use anyhow::{Context, Result, bail, ensure};
+use laniusc_compiler::{
+    dev::generator::gen_valid_source,
+    lexer::{LexSubmissionStats, highlight::highlight_spans},
+    prelude::*,
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+const DEFAULT_BYTES: usize = 1 << 20;
+const DEFAULT_RUNS: usize = 15;
+const DEFAULT_SEED: u64 = 42;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    let _ = laniusc_compiler::logging::init_default();
+    let mut bytes = DEFAULT_BYTES;
+    let mut runs = DEFAULT_RUNS;
+    let mut seed = DEFAULT_SEED;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().with_context(|| format!("{flag} needs a value"));
+        match arg.as_str() {
+            "--bytes" => bytes = value("--bytes")?.parse().context("--bytes")?,
+            "--runs" => runs = value("--runs")?.parse().context("--runs")?,
+            "--seed" => seed = value("--seed")?.parse().context("--seed")?,
+            _ => bail!("unknown argument {arg:?}; expected --bytes N, --runs N, or --seed N"),
+        }
+    }
+    ensure!(bytes > 0 && runs > 0, "--bytes and --runs must be positive");
+
+    let source = gen_valid_source(&mut StdRng::seed_from_u64(seed), bytes);
+    let lexer = GpuLexer::new().await?;
+    // Warm both profiles up so buffer growth and pipeline creation stay out
+    // of the timed runs.
+    lexer.lex_all(&source).await?;
+    lexer.highlight(&source).await?;
+    println!(
+        "[highlight_bench] {} bytes, seed {seed}, {runs} runs each",
+        source.len()
+    );
+
+    let (mut default_ms, mut highlight_ms) = (Vec::new(), Vec::new());
+    let (mut default_stats, mut highlight_stats) = (None, None);
+    for run in 0..runs {
+        let started = Instant::now();
+        let all = lexer.lex_all(&source).await?;
+        default_ms.push(started.elapsed().as_secs_f64() * 1e3);
+        default_stats = Some(lexer.last_lex_stats());
+
+        let started = Instant::now();
+        let spans = lexer.highlight(&source).await?;
+        highlight_ms.push(started.elapsed().as_secs_f64() * 1e3);
+        highlight_stats = Some(lexer.last_lex_stats());
+
+        ensure!(
+            spans == highlight_spans(&all),
+            "run {run}: the highlight profile disagrees with the default profile"
+        );
+    }
+
+    print_profile("default", &mut default_ms, default_stats);
+    print_profile("highlight", &mut highlight_ms, highlight_stats);
+    let (base, fast) = (median(&mut default_ms), median(&mut highlight_ms));
+    println!(
+        "[highlight_bench] median: {base:.3} ms -> {fast:.3} ms ({:+.1}%)",
+        100.0 * (fast - base) / base.max(f64::MIN_POSITIVE)
+    );
+    Ok(())
+}
+
+fn print_profile(name: &str, ms: &mut [f64], stats: Option<LexSubmissionStats>) {
+    let submissions = stats.map_or(0, |stats| stats.submissions);
+    println!(
+        "[highlight_bench] {name:>9}: median {:.3} ms, min {:.3} ms, {submissions} submissions",
+        median(ms),
+        ms[0]
+    );
+}
+
+/// Sorts `values` and returns their median.
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(f64::total_cmp);
+    values[values.len() / 2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn median_sorts_in_place() {
+        let mut values = [3.0, 1.0, 2.0];
+        assert_eq!(median(&mut values), 2.0);
+        assert_eq!(values, [1.0, 2.0, 3.0]);
+    }
+}
@@ -0,0 +1,1085 @@
+// Developer lexer fuzz tool.
+//
+// This binary is not part of the compiler pipeline. It may call the explicitly
+// named test CPU lexer oracle to compare against GPU lexer output.
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    thread,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use laniusc_compiler::{
+    dev::generator::{PROFILES, SourceGenConfig, gen_source},
+    lexer::{
+        EscapeSpan,
+        LexOptions,
+        LexOutput,
+        LexemeError,
+        LexemePolicy,
+        ReadbackMode,
+        Token,
+        boundary::is_kept,
+        diff::{MismatchReport, first_divergence, preview_lossy},
+        driver::get_global_lexer,
+        roundtrip,
+        tables::dfa::S,
+        test_cpu::{
+            Coverage,
+            TestCpuLexer,
+            coverage::{UNREACHABLE_STATES, state_from_name},
+            lex_on_test_cpu_all_parallel,
+            lex_on_test_cpu_with_coverage_parallel,
+        },
+    },
+    prelude::*,
+};
+use log::warn;
+use rand::{SeedableRng, rngs::StdRng};
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct Golden {
+    tokens: Vec<GoldenTok>,
+}
+#[derive(serde::Deserialize, serde::Serialize)]
+struct GoldenTok {
+    kind: String,
+    /// Pre-retag DFA kind; absent when it equals `kind`.
+    #[serde(rename = "rawKind", default, skip_serializing_if = "Option::is_none")]
+    raw_kind: Option<String>,
+    text: String,
+}
+
+/// Threads the test CPU oracle splits each lex across, from `FUZZ_THREADS`
+/// (default 1; 0 uses every available core). Large `FUZZ_LEN` runs spend
+/// most of their time in the oracle otherwise.
+static ORACLE_THREADS: LazyLock<usize> =
+    LazyLock::new(|| match parse_env_or_default("FUZZ_THREADS", 1usize) {
+        0 => thread::available_parallelism().map_or(1, usize::from),
+        threads => threads,
+    });
+
+/// `(kind, raw_kind, start, len)` of one token, from either lexer.
+type NormTok = (TokenKind, TokenKind, usize, usize);
+
+fn kind_from_str(s: &str) -> Option<TokenKind> {
+    use TokenKind::*;
+    Some(match s {
+        "Ident" => Ident,
+        "Int" => Int,
+        "Float" => Float,
+        "Char" => Char,
+        "White" => White,
+        "LParen" => LParen,
+        "RParen" => RParen,
+        "Plus" => Plus,
+        "Inc" => Inc,
+        "Star" => Star,
+        "Tilde" => Tilde,
+        "Assign" => Assign,
+        "PlusAssign" => PlusAssign,
+        "MinusAssign" => MinusAssign,
+        "StarAssign" => StarAssign,
+        "SlashAssign" => SlashAssign,
+        "PercentAssign" => PercentAssign,
+        "CaretAssign" => CaretAssign,
+        "ShlAssign" => ShlAssign,
+        "ShrAssign" => ShrAssign,
+        "AmpAssign" => AmpAssign,
+        "PipeAssign" => PipeAssign,
+        "TildeAssign" => TildeAssign,
+        "Bom" => Bom,
+        "Slash" => Slash,
+        "LineComment" => LineComment,
+        "BlockComment" => BlockComment,
+        "Dot" => Dot,
+        "DotDot" => DotDot,
+        "Comma" => Comma,
+        "Semicolon" => Semicolon,
+        "Colon" => Colon,
+        "Question" => Question,
+        "Lt" => Lt,
+        "Gt" => Gt,
+        "Le" => Le,
+        "Ge" => Ge,
+        "EqEq" => EqEq,
+        "NotEqual" => NotEqual,
+        "Percent" => Percent,
+        "Caret" => Caret,
+        "Shl" => Shl,
+        "Shr" => Shr,
+        "AndAnd" => AndAnd,
+        "OrOr" => OrOr,
+        "Not" => Not,
+        "Dec" => Dec,
+        "LBracket" => LBracket,
+        "RBracket" => RBracket,
+        "LBrace" => LBrace,
+        "RBrace" => RBrace,
+        "String" => String,
+        "RawString" => RawString,
+        "Shebang" => Shebang,
+        "GroupLParen" => GroupLParen,
+        "CallLParen" => CallLParen,
+        "ParamLParen" => ParamLParen,
+        "IndexLBracket" => IndexLBracket,
+        "ArrayLBracket" => ArrayLBracket,
+        "AngleGeneric" => AngleGeneric,
+        "Ampersand" => Ampersand,
+        "Pipe" => Pipe,
+        "Minus" => Minus,
+        "PrefixPlus" => PrefixPlus,
+        "InfixPlus" => InfixPlus,
+        "PrefixMinus" => PrefixMinus,
+        "InfixMinus" => InfixMinus,
+        "GroupRParen" => GroupRParen,
+        "CallRParen" => CallRParen,
+        "ParamRParen" => ParamRParen,
+        "ArrayRBracket" => ArrayRBracket,
+        "IndexRBracket" => IndexRBracket,
+        "LetIdent" => LetIdent,
+        "ParamIdent" => ParamIdent,
+        "TypeIdent" => TypeIdent,
+        "LetAssign" => LetAssign,
+        "ArgComma" => ArgComma,
+        "ArrayComma" => ArrayComma,
+        "ParamComma" => ParamComma,
+        "TypeArrayLBracket" => TypeArrayLBracket,
+        "TypeArrayRBracket" => TypeArrayRBracket,
+        "TypeSemicolon" => TypeSemicolon,
+        "IfLBrace" => IfLBrace,
+        "IfRBrace" => IfRBrace,
+        _ => return None,
+    })
+}
+
+fn load_golden_for(base_lan: &Path) -> Option<Golden> {
+    let candidates = [
+        base_lan.with_extension("tokens.json"),
+        base_lan.with_extension("golden.json"),
+        base_lan.with_extension("json"),
+    ];
+    for p in candidates {
+        if p.exists() {
+            let s = match fs::read_to_string(&p) {
+                Ok(s) => s,
+                Err(err) => {
+                    warn!("failed to read lex fuzz golden {}: {err}", p.display());
+                    return None;
+                }
+            };
+            match serde_json::from_str::<Golden>(&s) {
+                Ok(g) => return Some(g),
+                Err(e) => {
+                    warn!("[golden] failed to parse {}: {e}", p.display());
+                    return None;
+                }
+            }
+        }
+    }
+    None
+}
+
+fn tokens_as_kind_text<'a, T>(
+    src: &'a str,
+    toks: T,
+    policy: LexemePolicy,
+) -> Result<Vec<(TokenKind, TokenKind, String)>, LexemeError>
+where
+    T: IntoIterator<Item = &'a NormTok>,
+{
+    toks.into_iter()
+        .map(|&(k, raw, start, len)| {
+            let text = laniusc_compiler::lexer::utf8::lexeme(src, start, len, policy)?;
+            Ok((k, raw, text.into_owned()))
+        })
+        .collect()
+}
+
+/// Policy for golden token text, from `LANIUS_LEXEME_POLICY` (`strict` default,
+/// or `lossy`).
+fn golden_lexeme_policy() -> LexemePolicy {
+    match std::env::var("LANIUS_LEXEME_POLICY").as_deref() {
+        Ok("lossy") => LexemePolicy::Lossy,
+        _ => LexemePolicy::Strict,
+    }
+}
+
+/// Writes a `.tokens.json` sidecar for `base_lan` from the test CPU oracle tokens.
+fn write_golden_for(
+    base_lan: &Path,
+    src: &str,
+    toks: &[NormTok],
+    policy: LexemePolicy,
+) -> anyhow::Result<PathBuf> {
+    let tokens = tokens_as_kind_text(src, toks.iter(), policy)?
+        .into_iter()
+        .map(|(kind, raw, text)| GoldenTok {
+            kind: format!("{kind:?}"),
+            raw_kind: (raw != kind).then(|| format!("{raw:?}")),
+            text,
+        })
+        .collect();
+    let path = base_lan.with_extension("tokens.json");
+    fs::write(
+        &path,
+        serde_json::to_string_pretty(&Golden { tokens })? + "\n",
+    )?;
+    Ok(path)
+}
+
+fn check_against_golden(label: &str, src: &str, toks: &[NormTok], golden: &Golden) -> bool {
+    let got = match tokens_as_kind_text(src, toks.iter(), golden_lexeme_policy()) {
+        Ok(got) => got,
+        Err(err) => {
+            eprintln!("[golden:{label}] {err}");
+            return false;
+        }
+    };
+    if got.len() != golden.tokens.len() {
+        eprintln!(
+            "[golden:{label}] count mismatch: got={} expected={}",
+            got.len(),
+            golden.tokens.len()
+        );
+        dump_kind_text_diff(&got, &golden.tokens, 0);
+        return false;
+    }
+    for (i, ((gk, graw, gtxt), exp)) in got.iter().zip(golden.tokens.iter()).enumerate() {
+        let exp_raw = exp.raw_kind.as_deref().unwrap_or(&exp.kind);
+        let (Some(ek), Some(eraw)) = (kind_from_str(&exp.kind), kind_from_str(exp_raw)) else {
+            eprintln!(
+                "[golden:{label}] unknown kind '{}' (raw '{}') at index {}",
+                exp.kind, exp_raw, i
+            );
+            return false;
+        };
+        if *gk as u32 != ek as u32 || *graw as u32 != eraw as u32 || gtxt != &exp.text {
+            eprintln!(
+                "[golden:{label}] mismatch at {}:\n  got:  kind={:?} raw={:?} text={:?}\n  want: kind={}   raw={}   text={:?}",
+                i, gk, graw, gtxt, exp.kind, exp_raw, exp.text
+            );
+            dump_kind_text_diff(&got, &golden.tokens, i.saturating_sub(2));
+            return false;
+        }
+    }
+    true
+}
+
+fn dump_kind_text_diff(got: &[(TokenKind, TokenKind, String)], exp: &[GoldenTok], from: usize) {
+    let hi = (from + 8).min(got.len().max(exp.len()));
+    eprintln!("--- golden context [{from}..{hi}) ---");
+    for i in from..hi {
+        eprintln!(
+            "#{:06} got={:?} want={:?}",
+            i,
+            got.get(i).map(|(k, raw, t)| (k, raw, t)),
+            exp.get(i).map(|e| (&e.kind, &e.raw_kind, &e.text))
+        );
+    }
+}
+
+fn main() {
+    let _ = laniusc_compiler::logging::init_default();
+    if let Err(err) = pollster::block_on(lex_on_gpu("warmup")) {
+        warn!("GPU warmup lex failed: {err}");
+        std::process::exit(1);
+    }
+    if let Ok(path) = std::env::var("FUZZ_INPUT") {
+        eprintln!("[replay] reading {path}");
+        let s = fs::read_to_string(&path).expect("failed to read FUZZ_INPUT");
+        let report_path = Path::new(&path).with_extension("report.json");
+        pollster::block_on(run_once(&s, None, None, None, None, Some(&report_path)));
+        return;
+    }
+
+    let examples = collect_examples();
+    if !examples.is_empty() {
+        eprintln!("[ex] running {} handcrafted example(s)…", examples.len());
+        for (j, p) in examples.iter().enumerate() {
+            match fs::read_to_string(p) {
+                Ok(s) => {
+                    eprintln!("[ex {j}] {}", p.display());
+                    if !pollster::block_on(run_once(&s, None, None, None, Some(p.as_path()), None))
+                    {
+                        std::process::exit(1);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("[ex {j}] failed to read {}: {e}", p.display());
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+
+    let save_cases = env_bool_flag("FUZZ_SAVE", false);
+    let out_dir = std::env::var("FUZZ_DIR").unwrap_or_else(|_| {
+        warn!("FUZZ_DIR is unset; using default fuzz-cases");
+        "fuzz-cases".to_string()
+    });
+    let len: usize = parse_env_or_default("FUZZ_LEN", 1_000_000usize);
+    let iters: usize = parse_env_or_default("FUZZ_ITERS", 3usize);
+    let seed: u64 = parse_env_or_default("FUZZ_SEED", 42u64);
+    let profile = std::env::var("FUZZ_PROFILE").unwrap_or_else(|_| {
+        warn!("FUZZ_PROFILE is unset; using default profile");
+        "default".to_string()
+    });
+    let Some(config) = SourceGenConfig::from_profile(&profile) else {
+        eprintln!(
+            "error: unknown FUZZ_PROFILE {profile:?} (expected one of {})",
+            PROFILES.join(", ")
+        );
+        std::process::exit(2);
+    };
+    let config = SourceGenConfig {
+        target_len: len,
+        ..config
+    };
+
+    // Every state outside the allowlist must be entered this often across all
+    // iterations; 0 turns the check off.
+    let coverage_min: u32 = parse_env_or_default("FUZZ_COVERAGE_MIN", 1u32);
+    let coverage_allow = coverage_allowlist();
+    let coverage_report_path = std::env::var("FUZZ_COVERAGE_REPORT").ok();
+
+    eprintln!(
+        "[fuzz] len={len} iters={iters} seed={seed} profile={profile} oracle_threads={}",
+        *ORACLE_THREADS
+    );
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    if save_cases && let Err(e) = fs::create_dir_all(&out_dir) {
+        eprintln!("error: failed to create {out_dir}: {e}");
+        std::process::exit(1);
+    }
+
+    let mut coverage = Coverage::new();
+    pollster::block_on(async {
+        for i in 0..iters {
+            eprintln!("[fuzz] iter {i} ----------------");
+            let s = gen_source(&mut rng, &config);
+            eprintln!("[fuzz] iter {i}: generated {} bytes", s.len());
+
+            let report_path = save_cases.then(|| {
+                let path = save_case(&out_dir, seed, i, &s);
+                eprintln!("[save] wrote {}", path.display());
+                path.with_extension("report.json")
+            });
+
+            let ok = run_once(
+                &s,
+                Some(seed),
+                Some(i),
+                Some(len),
+                None,
+                report_path.as_deref(),
+            )
+            .await;
+            if !ok {
+                std::process::exit(1);
+            }
+            lex_on_test_cpu_with_coverage_parallel(&s, *ORACLE_THREADS, &mut coverage)
+                .expect("test CPU oracle accepted this source above");
+        }
+        eprintln!("[fuzz] all iterations matched ✅");
+    });
+
+    let report = coverage.report(coverage_min, &coverage_allow);
+    eprint!("[coverage] {report}");
+    if let Some(path) = &coverage_report_path {
+        let json = serde_json::to_string_pretty(&report).expect("coverage report serializes");
+        if let Err(err) = fs::write(path, json + "\n") {
+            eprintln!("error: failed to write coverage report {path}: {err}");
+            std::process::exit(1);
+        }
+        eprintln!("[coverage] wrote {path}");
+    }
+    if !report.is_complete() {
+        eprintln!(
+            "[coverage] {} DFA state(s) entered fewer than {coverage_min} time(s): {}",
+            report.uncovered.len(),
+            report.uncovered.join(", ")
+        );
+        std::process::exit(1);
+    }
+}
+
+/// States exempt from the coverage check, from the comma-separated
+/// `FUZZ_COVERAGE_ALLOW` (default `Reject`).
+fn coverage_allowlist() -> Vec<S> {
+    let Ok(raw) = std::env::var("FUZZ_COVERAGE_ALLOW") else {
+        return UNREACHABLE_STATES.to_vec();
+    };
+    raw.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            state_from_name(name).unwrap_or_else(|| {
+                eprintln!("error: unknown DFA state {name:?} in FUZZ_COVERAGE_ALLOW");
+                std::process::exit(2);
+            })
+        })
+        .collect()
+}
+
+async fn run_once(
+    src: &str,
+    seed: Option<u64>,
+    iter: Option<usize>,
+    len: Option<usize>,
+    golden_for: Option<&Path>,
+    report_path: Option<&Path>,
+) -> bool {
+    let t0 = Instant::now();
+    let oracle = TestCpuLexer::new(LexOptions {
+        capture_accept_states: true,
+        capture_string_escapes: true,
+        ..LexOptions::default()
+    })
+    .with_threads(*ORACLE_THREADS);
+    let (test_cpu, test_cpu_states, test_cpu_escapes) = match oracle.lex(src) {
+        Ok(LexOutput {
+            tokens,
+            accept_states,
+            escape_spans,
+            ..
+        }) => (tokens, accept_states, escape_spans),
+        Err(e) => {
+            eprintln!("\n[test CPU oracle] {e}");
+            let tail = src.len().saturating_sub(64);
+            eprintln!(
+                "[tail] {:?}",
+                String::from_utf8_lossy(&src.as_bytes()[tail..])
+            );
+            panic!("test CPU oracle lex failed");
+        }
+    };
+    let t1 = Instant::now();
+    let gpu_output = get_global_lexer()
+        .await
+        .lex_with_options(
+            src,
+            LexOptions {
+                capture_accept_states: true,
+                capture_string_escapes: true,
+                report: true,
+                ..LexOptions::default()
+            },
+        )
+        .await
+        .expect("GPU lex failed");
+    let gpu_report = gpu_output.report.expect("report was requested");
+    let gpu = gpu_output.tokens;
+    let t2 = Instant::now();
+
+    let eq = compare_streams(src, &test_cpu, &gpu)
+        && compare_accept_states(src, &test_cpu, &test_cpu_states, &gpu_output.accept_states)
+        && compare_escape_spans(src, &test_cpu_escapes, &gpu_output.escape_spans)
+        && gpu_output.token_count == gpu.len()
+        && gpu_report.token_count == gpu.len()
+        && gpu_output.warnings.is_empty();
+    let test_cpu_ms = (t1 - t0).as_millis();
+    let gpu_ms = (t2 - t1).as_millis();
+
+    let prefix = match (seed, iter, len) {
+        (Some(_seed), Some(i), Some(_l)) => format!("[fuzz] iter {i}:"),
+        _ => "[replay]".to_string(),
+    };
+    eprintln!(
+        "{prefix} test CPU oracle/GPU {test_cpu_ms} ms/{gpu_ms} ms  |  test CPU oracle tokens={}  |  GPU {gpu_report}  -> {}",
+        test_cpu.len(),
+        if eq { "OK" } else { "MISMATCH!" }
+    );
+    for warning in &gpu_output.warnings {
+        eprintln!("[warning] {warning}");
+    }
+
+    // The all-boundary stream includes skipped tokens and raw DFA kinds.
+    let test_cpu_all = lex_on_test_cpu_all_parallel(src, *ORACLE_THREADS)
+        .expect("test CPU oracle accepted the kept stream");
+    let gpu_all = get_global_lexer()
+        .await
+        .debug_all_tokens(src)
+        .await
+        .expect("GPU all-boundary lex failed");
+    let all_eq = compare_streams(src, &test_cpu_all, &gpu_all);
+    eprintln!(
+        "[all] test CPU oracle/GPU boundaries = {}/{}  -> {}",
+        test_cpu_all.len(),
+        gpu_all.len(),
+        if all_eq { "OK" } else { "MISMATCH!" }
+    );
+
+    let roundtrip_ok = check_roundtrip(src, &gpu, &gpu_all);
+    let modes_ok = check_readback_modes(src, &gpu).await;
+    let both_ok = check_lex_both(src, &gpu, &gpu_all).await;
+
+    let mut ok = eq && all_eq && roundtrip_ok && modes_ok && both_ok;
+    if !ok {
+        report_mismatch(src, &test_cpu, &gpu, &test_cpu_all, &gpu_all, report_path);
+    }
+
+    if let Some(p) = golden_for {
+        if let Some(g) = load_golden_for(p) {
+            let test_cpu_norm: Vec<NormTok> = test_cpu
+                .iter()
+                .map(|t| (t.kind, t.raw_kind, t.start(), t.len()))
+                .collect();
+            let gpu_norm: Vec<NormTok> = gpu
+                .iter()
+                .map(|t| (t.kind, t.raw_kind, t.start(), t.len()))
+                .collect();
+
+            let test_cpu_ok = check_against_golden("test_cpu", src, &test_cpu_norm, &g);
+            let gpu_ok = check_against_golden("gpu", src, &gpu_norm, &g);
+
+            if !test_cpu_ok || !gpu_ok {
+                ok = false;
+            }
+        } else if std::env::var_os("LANIUS_FUZZ_WRITE_GOLDEN").is_some() {
+            let test_cpu_norm: Vec<NormTok> = test_cpu
+                .iter()
+                .map(|t| (t.kind, t.raw_kind, t.start(), t.len()))
+                .collect();
+            match write_golden_for(p, src, &test_cpu_norm, golden_lexeme_policy()) {
+                Ok(path) => eprintln!("[golden] wrote {}", path.display()),
+                Err(err) => {
+                    eprintln!(
+                        "[golden] failed to write sidecar for {}: {err}",
+                        p.display()
+                    );
+                    ok = false;
+                }
+            }
+        } else {
+            eprintln!("[golden] no sidecar found for {}", p.display());
+        }
+    }
+    ok
+}
+
+/// Checks that the GPU all-boundary stream tiles `src` and that the kept
+/// stream, respaced, re-lexes to the same kinds.
+fn check_roundtrip(src: &str, gpu: &[Token], gpu_all: &[Token]) -> bool {
+    let partition = roundtrip::verify_partition(src, gpu_all);
+    let relex = roundtrip::verify_kept_relex(src, gpu);
+    let ok = partition.is_ok() && relex.is_ok();
+    eprintln!(
+        "[roundtrip] partition {}  kept re-lex {}  -> {}",
+        partition.map_or_else(|err| err.to_string(), |()| "exact".into()),
+        relex.map_or_else(|err| err.to_string(), |()| "same kinds".into()),
+        if ok { "OK" } else { "MISMATCH!" }
+    );
+    ok
+}
+
+/// Re-lexes `src` with count-only and no readback, checking them against the
+/// full-readback token count and that neither path reallocates resident buffers.
+/// Full readback is also forced through both the single-submission and the
+/// two-submission strategies, which must return the same tokens.
+async fn check_readback_modes(src: &str, full: &[Token]) -> bool {
+    let lexer = get_global_lexer().await;
+    let full_count = full.len();
+    let spans = |tokens: &[Token]| -> Vec<_> {
+        tokens
+            .iter()
+            .map(|t| (t.kind, t.raw_kind, t.start(), t.len()))
+            .collect()
+    };
+    let mut strategies_ok = true;
+    for (single_submission_max_bytes, compress_readback) in
+        [(0, false), (0, true), (u64::MAX, false)]
+    {
+        let forced = lexer
+            .lex_with_options(
+                src,
+                LexOptions {
+                    single_submission_max_bytes,
+                    compress_readback,
+                    ..LexOptions::default()
+                },
+            )
+            .await
+            .expect("GPU lex with forced readback strategy failed");
+        strategies_ok &= spans(&forced.tokens) == spans(full);
+    }
+    let allocations = lexer.buffer_allocation_count();
+
+    let count_only = lexer
+        .lex_with_options(
+            src,
+            LexOptions {
+                readback: ReadbackMode::CountOnly,
+                ..LexOptions::default()
+            },
+        )
+        .await
+        .expect("GPU count-only lex failed");
+    let none = lexer
+        .lex_with_options(
+            src,
+            LexOptions {
+                readback: ReadbackMode::None,
+                ..LexOptions::default()
+            },
+        )
+        .await
+        .expect("GPU no-readback lex failed");
+
+    let count_ok = count_only.token_count == full_count && count_only.tokens.is_empty();
+    let none_ok = none.token_count == 0 && none.tokens.is_empty();
+    let allocations_after = lexer.buffer_allocation_count();
+    let ok = strategies_ok && count_ok && none_ok && allocations_after == allocations;
+    eprintln!(
+        "[modes] single/two-submission/compressed {}  count-only/full tokens = {}/{}  resident allocations {}->{}  -> {}",
+        if strategies_ok { "agree" } else { "DIFFER" },
+        count_only.token_count,
+        full_count,
+        allocations,
+        allocations_after,
+        if ok { "OK" } else { "MISMATCH!" }
+    );
+    ok
+}
+
+fn collect_examples() -> Vec<PathBuf> {
+    if let Ok(list) = std::env::var("FUZZ_EX") {
+        let mut out = Vec::new();
+        for part in list.split([',', ':']) {
+            let p = PathBuf::from(part.trim());
+            if !p.as_os_str().is_empty() && p.exists() {
+                out.push(p);
+            }
+        }
+        if !out.is_empty() {
+            return out;
+        }
+        warn!("FUZZ_EX is set but did not yield any existing .lani files");
+    } else {
+        warn!("FUZZ_EX is unset; checking FUZZ_EX_DIR");
+    }
+
+    let dir = std::env::var("FUZZ_EX_DIR").unwrap_or_else(|_| {
+        warn!("FUZZ_EX_DIR is unset; using default tests/cases/lexer");
+        "tests/cases/lexer".into()
+    });
+    let p = Path::new(&dir);
+    if !p.exists() {
+        warn!("example directory {dir} does not exist");
+        return Vec::new();
+    }
+    if !p.is_dir() {
+        warn!("FUZZ_EX_DIR value {dir} is not a directory");
+        return Vec::new();
+    }
+
+    let mut out = Vec::new();
+    let Ok(rd) = fs::read_dir(p) else {
+        warn!("failed to read example directory {}", p.display());
+        return Vec::new();
+    };
+    {
+        for ent in rd {
+            match ent {
+                Ok(ent) => {
+                    let path = ent.path();
+                    if path
+                        .extension()
+                        .and_then(|e| e.to_str())
+                        .map(|e| e.eq_ignore_ascii_case("lani"))
+                        .unwrap_or(false)
+                    {
+                        out.push(path);
+                    }
+                }
+                Err(err) => warn!("failed to read an example entry from {dir}: {err}"),
+            }
+        }
+    }
+    out.sort();
+    out
+}
+
+fn env_bool_flag(name: &str, default: bool) -> bool {
+    let default_label = if default { "true" } else { "false" };
+    match std::env::var(name) {
+        Ok(value) => match value.trim().to_ascii_lowercase().as_str() {
+            "1" | "true" => true,
+            "0" | "false" => false,
+            _ => {
+                warn!("{name} has value '{value}'; using default {default_label}");
+                default
+            }
+        },
+        Err(_) => {
+            warn!("{name} is unset; using default {default_label}");
+            default
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct CaseMeta<'a> {
+    unix_ts: u64,
+    seed: Option<u64>,
+    iter: Option<usize>,
+    requested_len: Option<usize>,
+    actual_bytes: usize,
+    note: &'a str,
+}
+
+fn save_case(dir: &str, seed: u64, iter: usize, src: &str) -> PathBuf {
+    let ts = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_secs(),
+        Err(err) => {
+            warn!("system time before UNIX_EPOCH: {err}");
+            0
+        }
+    };
+
+    let base = format!("case_s{seed}_i{iter}_n{}.lani", src.len());
+    let path = Path::new(dir).join(base);
+
+    if let Err(err) = fs::write(&path, src.as_bytes()) {
+        warn!("failed to write fuzz case {}: {err}", path.display());
+    }
+
+    let meta = CaseMeta {
+        unix_ts: ts,
+        seed: Some(seed),
+        iter: Some(iter),
+        requested_len: None,
+        actual_bytes: src.len(),
+        note: "Replay with: FUZZ_INPUT=<this file> cargo run --bin lex_fuzz",
+    };
+    let meta_path = path.with_extension("json");
+    match fs::File::create(&meta_path) {
+        Ok(mut f) => {
+            let meta = serde_json::to_string_pretty(&meta).unwrap_or_else(|err| {
+                let msg = format!("failed to serialize meta for {path:?}: {err}");
+                warn!("{msg}");
+                format!("{{\"error\": \"{}\"}}", msg.replace('"', "\\\""))
+            });
+            if let Err(err) = writeln!(f, "{}", meta) {
+                warn!("failed to write fuzz meta {}: {err}", meta_path.display());
+            }
+        }
+        Err(err) => warn!("failed to create fuzz meta {}: {err}", meta_path.display()),
+    }
+
+    path
+}
+
+fn parse_env_or_default<T>(name: &str, default: T) -> T
+where
+    T: std::str::FromStr + Copy + std::fmt::Debug,
+    <T as std::str::FromStr>::Err: std::fmt::Display,
+{
+    match std::env::var(name) {
+        Ok(raw) => match raw.parse::<T>() {
+            Ok(value) => value,
+            Err(err) => {
+                warn!("invalid {name} value '{raw}': {err}; using default {default:?}");
+                default
+            }
+        },
+        Err(_) => {
+            warn!("{name} is unset; using default {default:?}");
+            default
+        }
+    }
+}
+
+/// Checks that `lex_both` serves both consumers from one lex: its kept stream
+/// equals `lex()`, its ALL stream has the boundaries of `debug_all_tokens`,
+/// and filtering the ALL stream to kept kinds gives back the kept stream.
+async fn check_lex_both(src: &str, kept: &[Token], all_raw: &[Token]) -> bool {
+    let (both_kept, both_all) = get_global_lexer()
+        .await
+        .lex_both(src)
+        .await
+        .expect("GPU lex_both failed");
+    let spans = |tokens: &[Token]| -> Vec<_> {
+        tokens
+            .iter()
+            .map(|t| (t.kind, t.raw_kind, t.start(), t.len()))
+            .collect()
+    };
+    let kept_ok = spans(&both_kept) == spans(kept);
+    let skipped_ok = both_all.len() == all_raw.len()
+        && both_all.iter().zip(all_raw).all(|(merged, raw)| {
+            is_kept(Some(raw.kind))
+                || (merged.kind, merged.start(), merged.len()) == (raw.kind, raw.start(), raw.len())
+        });
+    let filtered: Vec<Token> = both_all
+        .iter()
+        .filter(|t| is_kept(Some(t.kind)))
+        .cloned()
+        .collect();
+    let filter_ok = spans(&filtered) == spans(kept);
+    let ok = kept_ok && skipped_ok && filter_ok;
+    eprintln!(
+        "[both] kept/all tokens = {}/{}  kept {}  skipped {}  filtered {}  -> {}",
+        both_kept.len(),
+        both_all.len(),
+        if kept_ok { "agree" } else { "DIFFER" },
+        if skipped_ok { "agree" } else { "DIFFER" },
+        if filter_ok { "agree" } else { "DIFFER" },
+        if ok { "OK" } else { "MISMATCH!" }
+    );
+    ok
+}
+
+fn compare_streams(src: &str, test_cpu: &[Token], gpu: &[Token]) -> bool {
+    if test_cpu.len() != gpu.len() {
+        let i = first_divergence_idx(test_cpu, gpu);
+        eprintln!(
+            "[diff] token count mismatch: test_cpu={} gpu={} (first divergence at index {})",
+            test_cpu.len(),
+            gpu.len(),
+            i
+        );
+        dump_near(src, test_cpu, gpu, i.saturating_sub(1));
+
+        let min_len = test_cpu.len().min(gpu.len());
+        if i == min_len {
+            if test_cpu.len() > gpu.len() {
+                eprintln!("--- extra test CPU oracle tokens starting at {min_len} ---");
+                for j in min_len..(min_len + 6).min(test_cpu.len()) {
+                    let t = &test_cpu[j];
+                    let text = &src.as_bytes()[t.span.range()];
+                    eprintln!(
+                        "#{:06} test CPU oracle extra = {:?} @{}+{} {:?}",
+                        j,
+                        t.kind,
+                        t.start(),
+                        t.len(),
+                        String::from_utf8_lossy(text)
+                    );
+                }
+            } else {
+                eprintln!("--- extra GPU tokens starting at {min_len} ---");
+                for j in min_len..(min_len + 6).min(gpu.len()) {
+                    let t = &gpu[j];
+                    let text = &src.as_bytes()[t.span.range()];
+                    eprintln!(
+                        "#{:06} GPU extra = {:?} @{}+{} {:?}",
+                        j,
+                        t.kind,
+                        t.start(),
+                        t.len(),
+                        String::from_utf8_lossy(text)
+                    );
+                }
+            }
+        }
+        return false;
+    }
+
+    for (idx, (ct, gt)) in test_cpu.iter().zip(gpu.iter()).enumerate() {
+        if ct.kind as u32 != gt.kind as u32
+            || ct.raw_kind as u32 != gt.raw_kind as u32
+            || ct.start() != gt.start()
+            || ct.len() != gt.len()
+        {
+            eprintln!(
+                "[diff] token {} mismatch:\n  test CPU oracle: kind={:?} raw={:?} start={} len={}\n  GPU: kind={:?} raw={:?} start={} len={}",
+                idx,
+                ct.kind,
+                ct.raw_kind,
+                ct.start(),
+                ct.len(),
+                gt.kind,
+                gt.raw_kind,
+                gt.start(),
+                gt.len()
+            );
+
+            dump_src_window(src, ct.start(), ct.len(), "test CPU oracle", idx);
+            dump_src_window(src, gt.start(), gt.len(), "GPU", idx);
+
+            dump_near(src, test_cpu, gpu, idx.saturating_sub(1));
+            return false;
+        }
+    }
+    true
+}
+
+fn compare_accept_states(
+    src: &str,
+    test_cpu: &[Token],
+    test_cpu_states: &[u16],
+    gpu_states: &[u16],
+) -> bool {
+    if test_cpu_states.len() != gpu_states.len() {
+        eprintln!(
+            "[diff] accept state count mismatch: test_cpu={} gpu={}",
+            test_cpu_states.len(),
+            gpu_states.len()
+        );
+        return false;
+    }
+    let Some(idx) = (0..gpu_states.len()).find(|&i| test_cpu_states[i] != gpu_states[i]) else {
+        return true;
+    };
+    eprintln!(
+        "[diff] token {idx} accept state mismatch: test CPU oracle={} GPU={}",
+        test_cpu_states[idx], gpu_states[idx]
+    );
+    let t = &test_cpu[idx];
+    dump_src_window(src, t.start(), t.len(), "test CPU oracle", idx);
+    false
+}
+
+fn compare_escape_spans(src: &str, test_cpu: &[EscapeSpan], gpu: &[EscapeSpan]) -> bool {
+    let Some(idx) = (0..test_cpu.len().max(gpu.len())).find(|&i| test_cpu.get(i) != gpu.get(i))
+    else {
+        return true;
+    };
+    eprintln!(
+        "[diff] escape span {idx} mismatch: test CPU oracle={:?} GPU={:?} (counts {}/{})",
+        test_cpu.get(idx),
+        gpu.get(idx),
+        test_cpu.len(),
+        gpu.len()
+    );
+    if let Some(escape) = test_cpu.get(idx).or(gpu.get(idx)) {
+        let span = escape.span;
+        dump_src_window(
+            src,
+            span.start as usize,
+            span.len() as usize,
+            "escape span",
+            idx,
+        );
+    }
+    false
+}
+
+fn first_divergence_idx(test_cpu: &[Token], gpu: &[Token]) -> usize {
+    first_divergence(test_cpu, gpu).map_or(test_cpu.len().min(gpu.len()), |d| d.index)
+}
+
+/// Prints the dedupe signature of the first differing stream and writes its
+/// report to `report_path` when given.
+fn report_mismatch(
+    src: &str,
+    test_cpu: &[Token],
+    gpu: &[Token],
+    test_cpu_all: &[Token],
+    gpu_all: &[Token],
+    report_path: Option<&Path>,
+) {
+    const REPORT_WINDOW: usize = 16;
+    let report = [(test_cpu, gpu, "kept"), (test_cpu_all, gpu_all, "all")]
+        .into_iter()
+        .find_map(|(expected, actual, stream)| {
+            MismatchReport::new(src, expected, actual, REPORT_WINDOW).map(|r| (r, stream))
+        });
+    let Some((report, stream)) = report else {
+        return;
+    };
+    eprintln!(
+        "[report] stream={stream} signature={} class={:?} index={}",
+        report.signature, report.class, report.first_divergence
+    );
+    if let Some(path) = report_path {
+        match report.write_json(path) {
+            Ok(()) => eprintln!("[report] wrote {}", path.display()),
+            Err(err) => warn!(
+                "failed to write mismatch report {}: {err:#}",
+                path.display()
+            ),
+        }
+    }
+}
+
+fn line_col_at(src: &str, byte_idx: usize) -> (usize, usize) {
+    let mut line = 1usize;
+    let mut col = 1usize;
+    for (i, b) in src.as_bytes().iter().enumerate() {
+        if i == byte_idx {
+            break;
+        }
+        if *b == b'\n' {
+            line += 1;
+            col = 1;
+        } else {
+            col += 1;
+        }
+    }
+    (line, col)
+}
+
+const MAX_SNIP_WINDOW: usize = 1024;
+const TOK_HEAD_BYTES: usize = 10;
+const TOK_TAIL_BYTES: usize = 10;
+
+fn dump_src_window(src: &str, start: usize, len: usize, who: &str, idx: usize) {
+    let bytes = src.as_bytes();
+    let full_lo = start.saturating_sub(64);
+    let full_hi = (start + len + 64).min(src.len());
+    let full_len = full_hi.saturating_sub(full_lo);
+    let (line, col) = line_col_at(src, start);
+
+    eprintln!(
+        "[src:{who} idx={idx}] token @{start}+{len} (line {line}, col {col})  window [{full_lo}..{full_hi}]"
+    );
+
+    if full_len <= MAX_SNIP_WINDOW {
+        let snippet = String::from_utf8_lossy(&bytes[full_lo..full_hi]);
+        eprintln!("    {snippet:?}");
+    } else {
+        let before = &bytes[full_lo..start];
+        let token_end = (start + len).min(src.len());
+        let token = &bytes[start..token_end];
+        let after_end = (token_end + 64).min(src.len());
+        let after = &bytes[token_end..after_end];
+
+        let snippet = format!(
+            "{}{}{}",
+            String::from_utf8_lossy(&before[..before.len().min(64)]),
+            preview_lossy(token, TOK_HEAD_BYTES, TOK_TAIL_BYTES),
+            String::from_utf8_lossy(after)
+        );
+        eprintln!("    {snippet:?}");
+    }
+
+    let caret_pos = start.saturating_sub(full_lo);
+    let caret_len = len.max(1).min(80);
+    let mut underline = String::new();
+    underline.push_str(&" ".repeat(caret_pos));
+    underline.push_str(&"^".repeat(caret_len));
+    eprintln!("    {underline}");
+}
+
+fn dump_near(src: &str, test_cpu: &[Token], gpu: &[Token], from_idx: usize) {
+    let lo = from_idx;
+    let last_index = test_cpu.len().min(gpu.len());
+    let hi = (from_idx + 3).min(last_index);
+    eprintln!("gpu len {} test_cpu len {}", gpu.len(), test_cpu.len());
+    eprintln!("--- context tokens [{lo}..{hi}) ---");
+    let bytes = src.as_bytes();
+    for i in lo..hi {
+        let test_cpu_dbg = test_cpu.get(i).map(|t| {
+            let len = t.len().min(src.len() - t.start());
+            let s = &bytes[t.start()..t.start() + len];
+            (t.kind, t.raw_kind, t.start(), len, preview_lossy(s, 10, 10))
+        });
+        let gpu_dbg = gpu.get(i).map(|t| {
+            let len = t.len().min(src.len() - t.start());
+            let s = &bytes[t.start()..t.start() + len];
+            (t.kind, t.raw_kind, t.start(), len, preview_lossy(s, 10, 10))
+        });
+        let same = if test_cpu_dbg == gpu_dbg {
+            "\u{2705}"
+        } else {
+            "\u{274c}"
+        };
+        eprintln!("{same} #{i:06} test_cpu={test_cpu_dbg:?} GPU={gpu_dbg:?}");
+    }
+}
@@ -0,0 +1,210 @@
+// Writes the compact DFA tables the GPU lexer loads. With `--merge-tables`
+// it also writes the full summary-function merge tables
+// (tables/lexer_merge_tables.bin); `--low-memory` streams their m*m merge
+// rows to disk instead of building them in memory first. Both files use the
+// versioned table container; `--legacy-format` writes the version-1
+// LXDFA001/LXTBLE02 layouts instead.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    env,
+    fs,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use laniusc_compiler::{
+    lexer::tables::{
+        build::FunctionClosure,
+        compact::encode_compact_tables,
+        dfa::{N_STATES, StreamingDfa},
+        save_tables_bin_as,
+        tokens::{INVALID_TOKEN, N_KINDS, TokenKind},
+    },
+    tables::FormatVersion,
+};
+
+/// Counts live heap bytes so the merge-table path can report its peak.
+struct CountingAlloc;
+
+static HEAP_LIVE: AtomicUsize = AtomicUsize::new(0);
+static HEAP_PEAK: AtomicUsize = AtomicUsize::new(0);
+
+fn record_alloc(size: usize) {
+    let live = HEAP_LIVE.fetch_add(size, Ordering::Relaxed) + size;
+    HEAP_PEAK.fetch_max(live, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        HEAP_LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            HEAP_LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+            record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+fn main() -> std::io::Result<()> {
+    let _ = laniusc_compiler::logging::init_default();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let low_memory = args.iter().any(|arg| arg == "--low-memory");
+    let merge_tables = low_memory || args.iter().any(|arg| arg == "--merge-tables");
+    let version = if args.iter().any(|arg| arg == "--legacy-format") {
+        FormatVersion::V1
+    } else {
+        FormatVersion::V2
+    };
+    if let Some(arg) = args.iter().find(|arg| {
+        !matches!(
+            arg.as_str(),
+            "--low-memory" | "--merge-tables" | "--legacy-format"
+        )
+    }) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "unknown argument {arg:?}; expected --merge-tables, --low-memory, or \
+                 --legacy-format"
+            ),
+        ));
+    }
+
+    println!("[gen_tables] building compact DFA tables (no merge)...");
+    let dfa = StreamingDfa::new();
+
+    // Two (emit<<15 | next_low15) transitions per word, byte-major.
+    let total = 256 * N_STATES;
+    let mut next_emit_words = vec![0u32; total.div_ceil(2)];
+    for b in 0u32..=255 {
+        for s in 0..N_STATES {
+            let nx = dfa.next[s][b as usize];
+            let next = nx.state & 0x7FFF;
+            let emit = if nx.emit { 1u16 } else { 0u16 };
+            let i = b as usize * N_STATES + s;
+            next_emit_words[i >> 1] |= (((emit << 15) | next) as u32) << (16 * (i & 1));
+        }
+    }
+
+    let out_path = Path::new("tables/lexer_tables.bin");
+    if let Some(dir) = out_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let data = encode_compact_tables(N_STATES, &next_emit_words, &dfa.token_map, version)
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+    fs::write(out_path, &data)?;
+
+    let bytes = data.len();
+    println!(
+        "[gen_tables] wrote {} bytes (~{:.1} KiB) → {}",
+        bytes,
+        bytes as f64 / 1024.0,
+        out_path.display()
+    );
+
+    write_generated_token_ids(Path::new("shaders/generated_token_ids.slang"))?;
+    if merge_tables {
+        write_merge_tables(
+            Path::new("tables/lexer_merge_tables.bin"),
+            low_memory,
+            version,
+        )?;
+    }
+    Ok(())
+}
+
+fn write_merge_tables(
+    out_path: &Path,
+    low_memory: bool,
+    version: FormatVersion,
+) -> std::io::Result<()> {
+    let baseline = HEAP_LIVE.load(Ordering::Relaxed);
+    HEAP_PEAK.store(baseline, Ordering::Relaxed);
+    println!(
+        "[gen_tables] building merge tables ({})...",
+        if low_memory { "streaming" } else { "buffered" }
+    );
+    let closure = FunctionClosure::full();
+    let m = closure.m();
+    if low_memory {
+        closure.save_bin_streaming(out_path, version)?;
+    } else {
+        save_tables_bin_as(out_path, &closure.into_tables(), version)?;
+    }
+    let peak = HEAP_PEAK.load(Ordering::Relaxed).saturating_sub(baseline);
+    println!(
+        "[gen_tables] wrote {} (m={m}); peak heap {:.1} MiB",
+        out_path.display(),
+        peak as f64 / (1024.0 * 1024.0)
+    );
+    Ok(())
+}
+
+fn write_generated_token_ids(out_path: &Path) -> std::io::Result<()> {
+    if let Some(dir) = out_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut text = String::from(
+        "// Generated by `cargo run --bin lex_gen_tables` from src/lexer/tables/tokens.rs.\n",
+    );
+    text.push_str("// Do not edit by hand.\n\n");
+    text.push_str(&format!(
+        "static const uint TOKEN_KIND_COUNT = {}u;\n",
+        N_KINDS
+    ));
+    text.push_str(&format!(
+        "static const uint TOKEN_INVALID = {}u;\n\n",
+        INVALID_TOKEN
+    ));
+    for &kind in TokenKind::ALL {
+        text.push_str(&format!(
+            "static const uint TK_{} = {}u;\n",
+            screaming_snake_token_name(kind),
+            kind as u32
+        ));
+    }
+
+    fs::write(out_path, text)?;
+    println!("[gen_tables] wrote {}", out_path.display());
+    Ok(())
+}
+
+fn screaming_snake_token_name(kind: TokenKind) -> String {
+    let name = format!("{kind:?}");
+    match name.as_str() {
+        "EqEq" => return "EQEQ".to_string(),
+        "AndAnd" => return "ANDAND".to_string(),
+        "OrOr" => return "OROR".to_string(),
+        _ => {}
+    }
+
+    let mut out = String::with_capacity(name.len() + 4);
+    let mut prev_was_lower_or_digit = false;
+    for ch in name.chars() {
+        if ch.is_ascii_uppercase() && prev_was_lower_or_digit {
+            out.push('_');
+        }
+        out.push(ch.to_ascii_uppercase());
+        prev_was_lower_or_digit = ch.is_ascii_lowercase() || ch.is_ascii_digit();
+    }
+    out
+}
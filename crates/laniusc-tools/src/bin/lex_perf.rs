@@ -0,0 +1,466 @@
+use std::{
+    env,
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use laniusc_compiler::{
+    dev::generator::gen_valid_source,
+    lexer::{
+        LexOptions,
+        MemoOptions,
+        ReadbackMode,
+        tokens_io::source_hash,
+        verify::{Reference, Verdict, verify_against, write_reference},
+    },
+    prelude::*,
+};
+use log::warn;
+use rand::{SeedableRng, rngs::StdRng};
+
+fn fmt_mib(bytes: u64) -> String {
+    let mib = (bytes as f64) / (1024.0 * 1024.0);
+    format!("{mib:.2} MiB")
+}
+
+fn throughput_mibs(bytes: u64, ms: f64) -> f64 {
+    if ms <= 0.0 {
+        return 0.0;
+    }
+    (bytes as f64) / (1024.0 * 1024.0) / (ms / 1_000.0)
+}
+
+fn parse_target_len() -> usize {
+    let default = 10_000_000usize;
+    match env::var("LEX_PERF_LEN") {
+        Ok(value) => match value.parse::<usize>() {
+            Ok(len) if len > 0 => len,
+            Ok(len) => {
+                warn!("LEX_PERF_LEN value {len} must be > 0; using default {default}");
+                default
+            }
+            Err(err) => {
+                warn!("invalid LEX_PERF_LEN '{value}': {err}; using default {default}");
+                default
+            }
+        },
+        Err(_) => {
+            warn!("LEX_PERF_LEN is unset; using default {default}");
+            default
+        }
+    }
+}
+
+fn parse_seed() -> u64 {
+    let default = 42u64;
+    match env::var("LEX_PERF_SEED") {
+        Ok(value) => match value.parse::<u64>() {
+            Ok(seed) => seed,
+            Err(err) => {
+                warn!("invalid LEX_PERF_SEED '{value}': {err}; using default {default}");
+                default
+            }
+        },
+        Err(_) => {
+            warn!("LEX_PERF_SEED is unset; using default {default}");
+            default
+        }
+    }
+}
+
+fn parse_warmup() -> usize {
+    let default = 1usize;
+    match env::var("LEX_PERF_WARMUP") {
+        Ok(value) => match value.parse::<usize>() {
+            Ok(warmup) if warmup > 0 => warmup,
+            Ok(warmup) => {
+                warn!("LEX_PERF_WARMUP value {warmup} must be > 0; using default {default}");
+                default
+            }
+            Err(err) => {
+                warn!("invalid LEX_PERF_WARMUP '{value}': {err}; using default {default}");
+                default
+            }
+        },
+        Err(_) => {
+            warn!("LEX_PERF_WARMUP is unset; using default {default}");
+            default
+        }
+    }
+}
+
+/// Whether `LEX_PERF_PREWARM` asks for `GpuLexer::warm_up` before the first lex.
+fn parse_prewarm() -> bool {
+    matches!(
+        env::var("LEX_PERF_PREWARM").as_deref(),
+        Ok("1" | "true" | "yes" | "on")
+    )
+}
+
+fn parse_reps() -> usize {
+    let default = 10usize;
+    match env::var("LEX_PERF_REPS") {
+        Ok(value) => match value.parse::<usize>() {
+            Ok(reps) if reps > 0 => reps,
+            Ok(reps) => {
+                warn!("LEX_PERF_REPS value {reps} must be > 0; using default {default}");
+                default
+            }
+            Err(err) => {
+                warn!("invalid LEX_PERF_REPS '{value}': {err}; using default {default}");
+                default
+            }
+        },
+        Err(_) => {
+            warn!("LEX_PERF_REPS is unset; using default {default}");
+            default
+        }
+    }
+}
+
+/// `LEX_PERF_DUP_PERCENT`: share of generated lines that repeat the line
+/// before them, for inputs such as serialized data tables.
+fn parse_dup_percent() -> Option<u8> {
+    let value = env::var("LEX_PERF_DUP_PERCENT").ok()?;
+    match value.parse::<u8>() {
+        Ok(percent) if percent < 100 => Some(percent),
+        Ok(percent) => {
+            warn!("LEX_PERF_DUP_PERCENT value {percent} must be < 100; ignoring it");
+            None
+        }
+        Err(err) => {
+            warn!("invalid LEX_PERF_DUP_PERCENT '{value}': {err}; ignoring it");
+            None
+        }
+    }
+}
+
+/// Whether `LEX_PERF_MEMO` asks to also time `GpuLexer::lex_memoized`.
+fn parse_memo() -> bool {
+    matches!(
+        env::var("LEX_PERF_MEMO").as_deref(),
+        Ok("1" | "true" | "yes" | "on")
+    )
+}
+
+/// Whether `LEX_PERF_COMPRESS` asks for `LexOptions::compress_readback`.
+fn parse_compress() -> bool {
+    matches!(
+        env::var("LEX_PERF_COMPRESS").as_deref(),
+        Ok("1" | "true" | "yes" | "on")
+    )
+}
+
+/// Directory searched for known-good references unless
+/// `LEX_PERF_REFERENCE_DIR` names another.
+const DEFAULT_REFERENCE_DIR: &str = "target/lex-perf-reference";
+
+/// `LEX_PERF_REFERENCE_DIR`: where runs look for a reference to verify
+/// against.
+fn parse_reference_dir() -> PathBuf {
+    env::var_os("LEX_PERF_REFERENCE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_REFERENCE_DIR))
+}
+
+struct Args {
+    path: Option<PathBuf>,
+    /// `--record-reference <dir>`: write this run's output as the reference
+    /// instead of verifying against one.
+    record_reference: Option<PathBuf>,
+}
+
+fn parse_args() -> Args {
+    let usage = "usage: lex_perf [--record-reference <dir>] [file]";
+    let mut args = Args {
+        path: None,
+        record_reference: None,
+    };
+    let mut argv = env::args().skip(1);
+    while let Some(arg) = argv.next() {
+        match arg.as_str() {
+            "--record-reference" => match argv.next() {
+                Some(dir) => args.record_reference = Some(PathBuf::from(dir)),
+                None => {
+                    eprintln!("--record-reference needs a directory\n{usage}");
+                    std::process::exit(2);
+                }
+            },
+            flag if flag.starts_with("--") => {
+                eprintln!("unknown flag {flag}\n{usage}");
+                std::process::exit(2);
+            }
+            _ if args.path.is_none() => args.path = Some(PathBuf::from(arg)),
+            _ => {
+                eprintln!("unexpected argument {arg}\n{usage}");
+                std::process::exit(2);
+            }
+        }
+    }
+    args
+}
+
+/// Records `actual` under `key` in `record_dir`, or verifies it against the
+/// reference for `key` in `verify_dir`.
+fn check_reference(
+    actual: Option<Reference>,
+    key: &str,
+    record_dir: Option<&Path>,
+    verify_dir: &Path,
+) -> Verdict {
+    let Some(actual) = actual else {
+        return Verdict::Unverified("readback is disabled".to_string());
+    };
+    if let Some(dir) = record_dir {
+        match write_reference(dir, key, &actual) {
+            Ok(path) => println!("Reference: wrote {}", path.display()),
+            Err(e) => {
+                eprintln!("Failed to record reference: {e:#}");
+                std::process::exit(2);
+            }
+        }
+    }
+    verify_against(record_dir.unwrap_or(verify_dir), key, &actual)
+        .unwrap_or_else(|e| Verdict::Unverified(format!("{e:#}")))
+}
+
+/// Generates about `target_len` bytes in which each line of a smaller
+/// generated source is repeated so that `dup_percent` of lines are copies.
+fn gen_repetitive_source(rng: &mut StdRng, target_len: usize, dup_percent: u8) -> String {
+    let unique_share = 100 - usize::from(dup_percent);
+    let repeat = 100 / unique_share;
+    let unique = gen_valid_source(rng, (target_len * unique_share / 100).max(1));
+    let mut out = String::with_capacity(unique.len() * repeat);
+    for line in unique.split_inclusive('\n') {
+        let copies = if line.ends_with('\n') { repeat } else { 1 };
+        for _ in 0..copies {
+            out.push_str(line);
+        }
+    }
+    out
+}
+
+fn percentile(sorted_ms: &[f64], p: f64) -> f64 {
+    if sorted_ms.is_empty() {
+        return 0.0;
+    }
+    let idx = ((p.clamp(0.0, 1.0)) * (sorted_ms.len() as f64 - 1.0)).round() as usize;
+    sorted_ms[idx]
+}
+
+fn print_stats(label: &str, ms_list: &[f64], bytes: u64) {
+    if ms_list.is_empty() {
+        println!("{label}: no samples");
+        return;
+    }
+    let mut s = ms_list.to_vec();
+    s.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let best = s[0];
+    let p50 = percentile(&s, 0.50);
+    let p95 = percentile(&s, 0.95);
+    println!(
+        "{label}: best={:.3} ms | p50={:.3} ms | p95={:.3} ms | throughput(best)={:.1} MiB/s",
+        best,
+        p50,
+        p95,
+        throughput_mibs(bytes, best)
+    );
+}
+
+fn main() {
+    let _ = laniusc_compiler::logging::init_default();
+    pollster::block_on(async {
+        let args = parse_args();
+
+        let (text, reference_key) = if let Some(p) = args.path {
+            let load_t0 = Instant::now();
+            let src = match fs::read_to_string(&p) {
+                Ok(s) => s,
+                Err(e) => {
+                    eprintln!("Failed to read {}: {e}", p.display());
+                    std::process::exit(2);
+                }
+            };
+            let load_ms = load_t0.elapsed().as_secs_f64() * 1e3;
+            let bytes = src.len() as u64;
+            println!(
+                "Input: file={}  ({} | {} bytes)",
+                p.display(),
+                fmt_mib(bytes),
+                bytes
+            );
+            println!("Load:  {load_ms:.3} ms");
+            let key = format!("file-{:016x}", source_hash(&src));
+            (src, key)
+        } else {
+            let target_len = parse_target_len();
+            let seed = parse_seed();
+            let gen_t0 = Instant::now();
+            let mut rng = StdRng::seed_from_u64(seed);
+            let dup_percent = parse_dup_percent();
+            let src = match dup_percent {
+                Some(percent) => gen_repetitive_source(&mut rng, target_len, percent),
+                None => gen_valid_source(&mut rng, target_len),
+            };
+            let gen_ms = gen_t0.elapsed().as_secs_f64() * 1e3;
+            let bytes = src.len() as u64;
+            println!(
+                "Input: generated in-memory (len={} | {}) [seed={}, dup={}%]",
+                bytes,
+                fmt_mib(bytes),
+                seed,
+                dup_percent.unwrap_or(0)
+            );
+            println!("Gen:   {gen_ms:.3} ms");
+            let mut key = format!("seed{seed}-len{target_len}");
+            if let Some(percent) = dup_percent {
+                key.push_str(&format!("-dup{percent}"));
+            }
+            (src, key)
+        };
+
+        let bytes = text.len() as u64;
+        let warmup = parse_warmup();
+        let reps = parse_reps();
+
+        let gpu_init_t0 = Instant::now();
+        let gpu = match GpuLexer::new_with(LexerRuntimeOptions::from_env()).await {
+            Ok(g) => g,
+            Err(e) => {
+                eprintln!("GPU init failed: {e:?}");
+                std::process::exit(1);
+            }
+        };
+        let gpu_init_ms = gpu_init_t0.elapsed().as_secs_f64() * 1e3;
+        println!("GPU:  init={gpu_init_ms:.3} ms");
+
+        if parse_prewarm() {
+            let warm_up_t0 = Instant::now();
+            match gpu.warm_up(text.len()).await {
+                Ok(report) => println!(
+                    "GPU:  warm-up={:.3} ms | buffers={} | bind_groups={} | pass_sequences={}",
+                    warm_up_t0.elapsed().as_secs_f64() * 1e3,
+                    fmt_mib(report.buffers_bytes),
+                    report.bind_groups_created,
+                    report.passes_executed
+                ),
+                Err(e) => {
+                    eprintln!("GPU warm-up failed: {e:?}");
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        let mut gpu_runs = Vec::with_capacity(reps);
+        let options = LexOptions {
+            readback: ReadbackMode::from_env(),
+            report: true,
+            compress_readback: parse_compress(),
+            ..LexOptions::default()
+        };
+        if args.record_reference.is_some() && options.readback != ReadbackMode::Full {
+            eprintln!("--record-reference needs full readback");
+            std::process::exit(2);
+        }
+        // Every rep must produce the first rep's stream, which is then
+        // checked against the recorded reference.
+        let mut actual: Option<Reference> = None;
+        let mut rep_mismatch = None;
+        for i in 0..(warmup + reps) {
+            let t0 = Instant::now();
+            let output = match gpu.lex_with_options(&text, options).await {
+                Ok(output) => output,
+                Err(e) => {
+                    eprintln!("GPU lex failed: {e:?}");
+                    std::process::exit(1);
+                }
+            };
+            let ms = t0.elapsed().as_secs_f64() * 1e3;
+            if i == 0 {
+                // Compare with p50 to see what `LEX_PERF_PREWARM=1` saves.
+                println!("GPU:  first-call={ms:.3} ms");
+            }
+            let report = output.report.expect("report was requested");
+            println!("GPU:  lex[{i}]={ms:.3} ms | {report}");
+            for warning in &output.warnings {
+                warn!("lex[{i}]: {warning}");
+            }
+            if options.readback == ReadbackMode::Full {
+                let reference = Reference::for_tokens(&text, &output.tokens);
+                match actual {
+                    None => actual = Some(reference),
+                    Some(first) if first != reference && rep_mismatch.is_none() => {
+                        rep_mismatch =
+                            Some(Verdict::Mismatch(format!("lex[{i}] differs from lex[0]")));
+                    }
+                    Some(_) => {}
+                }
+            }
+            if i >= warmup {
+                gpu_runs.push(ms);
+            }
+        }
+        print_stats("GPU", &gpu_runs, bytes);
+        let verdict = rep_mismatch.unwrap_or_else(|| {
+            check_reference(
+                actual,
+                &reference_key,
+                args.record_reference.as_deref(),
+                &parse_reference_dir(),
+            )
+        });
+        println!("Verify: {verdict}");
+
+        if let Some(&best_gpu) = gpu_runs.iter().min_by(|a, b| a.partial_cmp(b).unwrap()) {
+            let best_total = gpu_init_ms + best_gpu;
+            println!(
+                "GPU:  total(best)={:.3} ms | throughput(total)={:.1} MiB/s",
+                best_total,
+                throughput_mibs(bytes, best_total)
+            );
+        }
+
+        if parse_memo() {
+            let expected = match gpu.lex(&text).await {
+                Ok(tokens) => tokens,
+                Err(e) => {
+                    eprintln!("GPU lex failed: {e:?}");
+                    std::process::exit(1);
+                }
+            };
+            let mut memo_runs = Vec::with_capacity(reps);
+            for i in 0..(warmup + reps) {
+                let t0 = Instant::now();
+                let output = match gpu.lex_memoized(&text, MemoOptions::default()).await {
+                    Ok(output) => output,
+                    Err(e) => {
+                        eprintln!("GPU memoized lex failed: {e:?}");
+                        std::process::exit(1);
+                    }
+                };
+                let ms = t0.elapsed().as_secs_f64() * 1e3;
+                println!(
+                    "Memo: lex[{i}]={ms:.3} ms | memoized={} | lines={} | unique={} | fallback={} | lexed={}",
+                    output.memoized,
+                    output.lines,
+                    output.unique_lines,
+                    fmt_mib(output.fallback_bytes as u64),
+                    fmt_mib(output.lexed_bytes as u64)
+                );
+                if output.tokens != expected {
+                    eprintln!("memoized tokens differ from a whole lex");
+                    std::process::exit(1);
+                }
+                if i >= warmup {
+                    memo_runs.push(ms);
+                }
+            }
+            print_stats("Memo", &memo_runs, bytes);
+        }
+
+        if verdict.is_mismatch() {
+            std::process::exit(1);
+        }
+    });
+}
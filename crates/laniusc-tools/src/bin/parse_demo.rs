@@ -1,12 +1,10 @@
 // src/bin/parse_demo.rs
 use anyhow::Result;
-use laniusc_compiler::{
-    lexer::driver::GpuLexer,
-    parser::{driver::GpuParser, tables::PrecomputedParseTables},
-};
+use laniusc_compiler::prelude::*;
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<()> {
+    let _ = laniusc_compiler::logging::init_default();
     // Pick a small sample; allow overriding with CLI arg.
     let args: Vec<String> = std::env::args().collect();
     let input = if args.len() > 1 {
@@ -16,7 +14,7 @@ async fn main() -> Result<()> {
     };
 
     // 1) GPU lex
-    let lexer = GpuLexer::new().await?;
+    let lexer = GpuLexer::new_with(LexerRuntimeOptions::from_env()).await?;
     let tokens = lexer.lex(&input).await?;
 
     // Build token_kinds (post-retag) from tokens_out; append a sentinel 0.
@@ -34,9 +32,9 @@ async fn main() -> Result<()> {
             println!(
                 "token[{i}] kind={} start={} len={} text={:?}",
                 t.kind as u32,
-                t.start,
-                t.len,
-                &input[t.start..t.start + t.len]
+                t.start(),
+                t.len(),
+                &input[t.span.range()]
             );
         }
     }
@@ -51,7 +49,7 @@ async fn main() -> Result<()> {
     println!("[parse_demo] using tables/parse_tables.bin");
 
     // 3) GPU parser (pairs → headers → pack → brackets → tree)
-    let parser = GpuParser::new().await?;
+    let parser = GpuParser::new_with(ParserOptions::from_env()).await?;
     if std::env::var_os("LANIUS_PARSE_DEMO_RESIDENT").is_some() {
         let parsed = lexer
             .with_resident_tokens(&input, |_, _, bufs| {
@@ -172,11 +170,11 @@ async fn main() -> Result<()> {
                     .unwrap_or(u32::MAX);
                 let member_name_text = tokens
                     .get(member_name_token as usize)
-                    .map(|t| &input[t.start..t.start + t.len])
+                    .map(|t| &input[t.span.range()])
                     .unwrap_or("");
                 let token_text = tokens
                     .get(pos as usize)
-                    .map(|t| &input[t.start..t.start + t.len])
+                    .map(|t| &input[t.span.range()])
                     .unwrap_or("");
                 println!(
                     "  node[{i}] prod={kind} hir={hir} pos={pos} end={end} parent={parent} child={first_child} next={next_sibling} subtree_end={subtree_end} callee={callee} args=({arg_start},{arg_end},{arg_count}) member=({member_receiver},{member_receiver_token},{member_name_token},{member_name_text:?}) array=({array_first},{array_count},{array_parent},{array_ordinal},{array_next}) match=({match_arm_start},{match_arm_count}) arm=({match_pattern},{match_payload_start},{match_payload_count},{match_result},{match_next}) token={token_text:?}"
@@ -235,13 +233,17 @@ async fn main() -> Result<()> {
         print!("{}", res.emit_stream[i]);
     }
     println!("]");
+    for (i, rule) in res.explain_emits(&tables).iter().take(to_show).enumerate() {
+        println!("  emit[{i}] {rule}");
+    }
 
     // NEW: quick tree summary (now part of ParseResult)
+    let node_names = res.node_production_names(&tables);
     println!("nodes: {}", res.node_kind.len());
     for i in 0..res.node_kind.len().min(16) {
         println!(
-            "  node[{i}] kind={} parent={}",
-            res.node_kind[i], res.parent[i]
+            "  node[{i}] kind={} ({}) parent={}",
+            res.node_kind[i], node_names[i], res.parent[i]
         );
     }
     if std::env::var_os("LANIUS_PARSE_DEMO_FULL").is_some() {
@@ -255,10 +257,11 @@ async fn main() -> Result<()> {
             let subtree_end = res.subtree_end.get(i).copied().unwrap_or(u32::MAX);
             let token_text = tokens
                 .get(pos as usize)
-                .map(|t| &input[t.start..t.start + t.len])
+                .map(|t| &input[t.span.range()])
                 .unwrap_or("");
+            let name = &node_names[i];
             println!(
-                "  node[{i}] prod={kind} hir={hir} pos={pos} end={end} parent={parent} child={first_child} next={next_sibling} subtree_end={subtree_end} token={token_text:?}"
+                "  node[{i}] prod={kind} ({name}) hir={hir} pos={pos} end={end} parent={parent} child={first_child} next={next_sibling} subtree_end={subtree_end} token={token_text:?}"
             );
         }
     }
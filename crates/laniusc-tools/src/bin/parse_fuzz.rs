@@ -15,14 +15,7 @@ use std::{
 };
 
 use anyhow::{Context, Result};
-use laniusc_compiler::{
-    lexer::driver::GpuLexer,
-    parser::{
-        buffers::ActionHeader,
-        driver::{GpuParser, ParseResult},
-        tables::PrecomputedParseTables,
-    },
-};
+use laniusc_compiler::{parser::buffers::ActionHeader, prelude::*};
 use log::warn;
 use rand::{SeedableRng, rngs::StdRng};
 use serde::Deserialize;
@@ -692,6 +685,7 @@ fn parse_cli_args(args: &[String]) -> (Vec<PathBuf>, FuzzCfg) {
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<()> {
+    let _ = laniusc_compiler::logging::init_default();
     let args: Vec<String> = std::env::args().skip(1).collect();
     let (mut paths, fuzz) = parse_cli_args(&args);
 
@@ -705,8 +699,12 @@ async fn main() -> Result<()> {
         );
     }
 
-    let lexer = GpuLexer::new().await.context("init GpuLexer")?;
-    let parser = GpuParser::new().await.context("init GpuParser")?;
+    let lexer = GpuLexer::new_with(LexerRuntimeOptions::from_env())
+        .await
+        .context("init GpuLexer")?;
+    let parser = GpuParser::new_with(ParserOptions::from_env())
+        .await
+        .context("init GpuParser")?;
     let tables = load_tables()?;
 
     let mut passed = 0usize;
@@ -210,6 +210,34 @@ pub(super) fn compute_follow(
     }
 }
 
+/// Error-recovery sync sets in nonterminal-id order: FIRST(N) and FOLLOW(N)
+/// of each nonterminal, without end of input. A recovering parse drops
+/// input until a token of one of these sets lets it restart or close `N`.
+pub(super) fn recovery_sync_sets(
+    spec: &GrammarSpec,
+    nonterminals: &BTreeSet<String>,
+    nullable: &BTreeSet<String>,
+) -> Vec<Vec<u32>> {
+    let first = compute_first(&spec.productions, nonterminals, nullable);
+    let follow = compute_follow(
+        &spec.productions,
+        nonterminals,
+        nullable,
+        &first,
+        &spec.start,
+    );
+    nonterminals
+        .iter()
+        .map(|nt| {
+            first[nt]
+                .union(&follow[nt])
+                .copied()
+                .filter(|&token| token != EOF_TOKEN)
+                .collect()
+        })
+        .collect()
+}
+
 pub(super) fn first_of_sequence(
     seq: &[Sym],
     nullable: &BTreeSet<String>,
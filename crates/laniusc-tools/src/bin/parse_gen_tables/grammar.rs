@@ -0,0 +1,303 @@
+// src/bin/parse_gen_tables/grammar.rs
+
+use super::*;
+
+pub(super) fn parse_grammar(src: &str) -> Result<GrammarSpec> {
+    let mut prods = Vec::new();
+    let mut tag_counts: HashMap<String, usize> = HashMap::new();
+    let mut start: Option<String> = None;
+    let mut ladder: Option<(usize, String, String)> = None;
+    let mut binops = Vec::new();
+
+    for (line_number, raw_line) in src.lines().enumerate() {
+        let line_number = line_number + 1;
+        let line = strip_comment(raw_line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("%start") {
+            if start.is_some() {
+                bail!("line {line_number}: duplicate %start directive");
+            }
+            let name = directive_body(rest, "%start", line_number)?;
+            if !is_ident(name) {
+                bail!("line {line_number}: invalid start nonterminal '{name}'");
+            }
+            start = Some(name.to_string());
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("%ladder") {
+            if ladder.is_some() {
+                bail!("line {line_number}: duplicate %ladder directive");
+            }
+            let body = directive_body(rest, "%ladder", line_number)?;
+            let Some((top, operand)) = body.split_once("->") else {
+                bail!("line {line_number}: expected '%ladder top -> operand;'");
+            };
+            let (top, operand) = (top.trim(), operand.trim());
+            for name in [top, operand] {
+                if !is_ident(name) {
+                    bail!("line {line_number}: invalid ladder nonterminal '{name}'");
+                }
+            }
+            ladder = Some((line_number, top.to_string(), operand.to_string()));
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("%binop") {
+            let body = directive_body(rest, "%binop", line_number)?;
+            let [token, precedence, assoc] = body.split_whitespace().collect::<Vec<_>>()[..] else {
+                bail!("line {line_number}: expected \"%binop 'Token' <precedence> left|right;\"");
+            };
+            let Some(token) = parse_terminal(token, line_number)? else {
+                bail!("line {line_number}: %binop operator must be a quoted token kind");
+            };
+            let precedence = precedence.parse::<u32>().map_err(|_| {
+                anyhow!("line {line_number}: invalid operator precedence '{precedence}'")
+            })?;
+            let right_assoc = match assoc {
+                "left" => false,
+                "right" => true,
+                _ => bail!("line {line_number}: associativity must be 'left' or 'right'"),
+            };
+            if binops
+                .iter()
+                .any(|(_, op): &(usize, BinaryOperator)| op.token == token)
+            {
+                bail!(
+                    "line {line_number}: duplicate %binop for {}",
+                    format_token(token)
+                );
+            }
+            binops.push((
+                line_number,
+                BinaryOperator {
+                    token,
+                    precedence,
+                    right_assoc,
+                },
+            ));
+            continue;
+        }
+
+        if !line.ends_with(';') {
+            bail!("line {line_number}: production must end with ';'");
+        }
+
+        let Some((lhs_part, rhs_part0)) = line.split_once("->") else {
+            bail!("line {line_number}: expected production with '->'");
+        };
+        let rhs_part = rhs_part0.trim_end_matches(';').trim();
+
+        let lhs_part = lhs_part.trim();
+        let (lhs_name, tag_base) = parse_lhs(lhs_part, line_number)?;
+
+        let tag = unique_tag(&mut tag_counts, tag_base);
+
+        let mut rhs_syms = Vec::new();
+        for tok in rhs_part.split_whitespace() {
+            if let Some(token) = parse_terminal(tok, line_number)? {
+                rhs_syms.push(Sym::Terminal(token));
+            } else if is_ident(tok) {
+                rhs_syms.push(Sym::NonTerminal(tok.to_string()));
+            } else {
+                bail!("line {line_number}: invalid grammar symbol '{tok}'");
+            }
+        }
+
+        prods.push(Production {
+            line: line_number,
+            lhs: lhs_name,
+            tag,
+            rhs_syms,
+        });
+    }
+
+    let ladder = match (ladder, binops.as_slice()) {
+        (None, []) => None,
+        (None, [(line_number, _), ..]) => {
+            bail!("line {line_number}: %binop without a %ladder directive")
+        }
+        (Some((line_number, ..)), []) => {
+            bail!("line {line_number}: %ladder declares no %binop operators")
+        }
+        (Some((line_number, top, operand)), _) => Some(synthesize_ladder(
+            &mut prods,
+            &mut tag_counts,
+            line_number,
+            &top,
+            &operand,
+            &binops,
+        )?),
+    };
+
+    let start = start
+        .or_else(|| prods.first().map(|prod| prod.lhs.clone()))
+        .ok_or_else(|| anyhow!("grammar contains no productions"))?;
+
+    Ok(GrammarSpec {
+        start,
+        productions: prods,
+        ladder,
+    })
+}
+
+/// Appends one nonterminal per precedence level, lowest first, so that
+/// `top` derives operator chains over `operand`:
+///
+/// ```text
+/// level      -> next level_tail;
+/// level_tail -> 'op' next level_tail;   # left-associative level
+/// level_tail -> 'op' level;             # right-associative level
+/// level_tail -> ;
+/// ```
+///
+/// `next` is the following level, or `operand` after the highest one. The
+/// lowest level is `top` itself; the others are named `{top}_p{precedence}`.
+fn synthesize_ladder(
+    prods: &mut Vec<Production>,
+    tag_counts: &mut HashMap<String, usize>,
+    line: usize,
+    top: &str,
+    operand: &str,
+    binops: &[(usize, BinaryOperator)],
+) -> Result<PrecedenceLadder> {
+    let mut levels: BTreeMap<u32, Vec<BinaryOperator>> = BTreeMap::new();
+    for &(line_number, op) in binops {
+        let level = levels.entry(op.precedence).or_default();
+        if level
+            .first()
+            .is_some_and(|first| first.right_assoc != op.right_assoc)
+        {
+            bail!(
+                "line {line_number}: precedence {} mixes left- and right-associative operators",
+                op.precedence
+            );
+        }
+        level.push(op);
+    }
+
+    let names: Vec<String> = levels
+        .keys()
+        .enumerate()
+        .map(|(i, precedence)| {
+            if i == 0 {
+                top.to_string()
+            } else {
+                format!("{top}_p{precedence}")
+            }
+        })
+        .collect();
+    let mut nonterminals = Vec::new();
+    for name in &names {
+        nonterminals.push(name.clone());
+        nonterminals.push(format!("{name}_tail"));
+    }
+    if let Some(prod) = prods.iter().find(|prod| nonterminals.contains(&prod.lhs)) {
+        bail!(
+            "line {}: '{}' is a %ladder nonterminal and cannot have hand-written productions",
+            prod.line,
+            prod.lhs
+        );
+    }
+    if nonterminals.iter().any(|name| name == operand) {
+        bail!("line {line}: %ladder operand '{operand}' is one of its own levels");
+    }
+
+    let mut push = |lhs: &str, tag: String, rhs_syms: Vec<Sym>| {
+        prods.push(Production {
+            line,
+            lhs: lhs.to_string(),
+            tag: unique_tag(tag_counts, tag),
+            rhs_syms,
+        });
+    };
+    for (i, ops) in levels.values().enumerate() {
+        let level = &names[i];
+        let tail = format!("{level}_tail");
+        let next = names.get(i + 1).map_or(operand, String::as_str);
+        let nt = |name: &str| Sym::NonTerminal(name.to_string());
+        push(level, level.clone(), vec![nt(next), nt(&tail)]);
+        for op in ops {
+            let rhs = if op.right_assoc {
+                vec![Sym::Terminal(op.token), nt(level)]
+            } else {
+                vec![Sym::Terminal(op.token), nt(next), nt(&tail)]
+            };
+            push(&tail, format!("{tail}_{}", format_token(op.token)), rhs);
+        }
+        push(&tail, format!("{tail}_end"), Vec::new());
+    }
+
+    Ok(PrecedenceLadder {
+        operators: binops.iter().map(|&(_, op)| op).collect(),
+        nonterminals,
+    })
+}
+
+/// Returns `base`, suffixed with `#N` for its Nth use.
+fn unique_tag(tag_counts: &mut HashMap<String, usize>, base: String) -> String {
+    let count = tag_counts.entry(base.clone()).or_default();
+    *count += 1;
+    if *count == 1 {
+        base
+    } else {
+        format!("{base}#{count}")
+    }
+}
+
+/// Body of a `%directive ...;` line after its name, without the `;`.
+fn directive_body<'a>(rest: &'a str, directive: &str, line_number: usize) -> Result<&'a str> {
+    let Some(body) = rest.trim().strip_suffix(';') else {
+        bail!("line {line_number}: {directive} directive must end with ';'");
+    };
+    Ok(body.trim())
+}
+
+/// Token kind of a single-quoted terminal, or `None` for any other symbol.
+fn parse_terminal(tok: &str, line_number: usize) -> Result<Option<u32>> {
+    if !(tok.starts_with('\'') && tok.ends_with('\'') && tok.len() >= 2) {
+        return Ok(None);
+    }
+    let terminal_name = tok.trim_matches('\'');
+    let token = TokenKind::from_name(terminal_name).ok_or_else(|| {
+        anyhow!("line {line_number}: unknown terminal token kind '{terminal_name}'")
+    })?;
+    Ok(Some(token as u32))
+}
+
+pub(super) fn strip_comment(line: &str) -> &str {
+    line.split_once('#').map_or(line, |(before, _)| before)
+}
+
+pub(super) fn parse_lhs(lhs_part: &str, line_number: usize) -> Result<(String, String)> {
+    let (lhs_name, tag_base) = if let Some((lhs, tag_part0)) = lhs_part.split_once('[') {
+        let tag_part = tag_part0.trim();
+        let Some(tag) = tag_part.strip_suffix(']') else {
+            bail!("line {line_number}: production tag must end with ']'");
+        };
+        (lhs.trim(), tag.trim())
+    } else {
+        (lhs_part, lhs_part)
+    };
+
+    if !is_ident(lhs_name) {
+        bail!("line {line_number}: invalid production lhs '{lhs_name}'");
+    }
+    if !is_ident(tag_base) {
+        bail!("line {line_number}: invalid production tag '{tag_base}'");
+    }
+
+    Ok((lhs_name.to_string(), tag_base.to_string()))
+}
+
+pub(super) fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    (first == '_' || first.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
@@ -0,0 +1,59 @@
+// src/bin/parse_gen_tables/layout.rs
+
+use std::path::Path;
+
+use laniusc_compiler::{
+    lexer::{Token, test_cpu::lex_on_test_cpu},
+    parser::retag::retag_open_delimiters,
+};
+
+use super::*;
+
+/// Parser token kinds of every `.lani` file under `dirs`, for
+/// `PrecomputedParseTables::optimize_layout`: kept tokens from the CPU
+/// lexer, with `(` and `[` retagged as the GPU parser does. Files the lexer
+/// rejects are skipped with a warning.
+pub(super) fn layout_samples(dirs: &[PathBuf]) -> Result<Vec<Vec<u32>>> {
+    let mut files = Vec::new();
+    for dir in dirs {
+        collect_lani_files(dir, &mut files)
+            .with_context(|| format!("read layout samples under {}", dir.display()))?;
+    }
+    files.sort();
+    let mut samples = Vec::with_capacity(files.len());
+    for path in files {
+        let source = fs::read_to_string(&path)
+            .with_context(|| format!("read layout sample {}", path.display()))?;
+        match lex_on_test_cpu(&source) {
+            Ok(tokens) => samples.push(sample_kinds(&tokens)),
+            Err(err) => eprintln!(
+                "[gen_parse_tables] warning: skipping layout sample {}: {err}",
+                path.display()
+            ),
+        }
+    }
+    if samples.is_empty() {
+        bail!("no .lani layout samples under {dirs:?}");
+    }
+    Ok(samples)
+}
+
+fn sample_kinds(tokens: &[Token]) -> Vec<u32> {
+    let kinds: Vec<TokenKind> = tokens.iter().map(|token| token.kind).collect();
+    retag_open_delimiters(&kinds)
+        .into_iter()
+        .map(|kind| kind as u32)
+        .collect()
+}
+
+fn collect_lani_files(dir: &Path, out: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_lani_files(&path, out)?;
+        } else if path.extension().is_some_and(|ext| ext == "lani") {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
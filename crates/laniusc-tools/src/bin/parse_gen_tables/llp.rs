@@ -642,7 +642,11 @@ pub(super) fn llp_augmented_spec(spec: &GrammarSpec) -> GrammarSpec {
             Sym::Terminal(EOF_TOKEN),
         ],
     });
-    GrammarSpec { start, productions }
+    GrammarSpec {
+        start,
+        productions,
+        ladder: spec.ladder.clone(),
+    }
 }
 
 pub(super) fn compute_prod_arity(prods: &[Production]) -> Vec<u32> {
@@ -686,7 +690,11 @@ pub(super) fn install_ll1_runtime_tables(
     let nt_ids = nonterminal_ids(&nonterminals);
     let n_nonterminals = nt_ids.len() as u32;
 
+    let nullable = compute_nullable(&spec.productions, &nonterminals);
+
     tables.n_nonterminals = n_nonterminals;
+    tables.allows_empty_input = nullable.contains(&spec.start);
+    tables.sync_sets = recovery_sync_sets(spec, &nonterminals, &nullable);
     tables.start_nonterminal = *nt_ids
         .get(&spec.start)
         .ok_or_else(|| anyhow!("start nonterminal '{}' is not defined", spec.start))?;
@@ -724,6 +732,36 @@ pub(super) fn install_ll1_runtime_tables(
         }
     }
 
+    // BTreeSet order matches the ids, so names line up with nonterminal ids.
+    tables.nonterminal_names = nonterminals.into_iter().collect();
+    tables.prod_names = spec
+        .productions
+        .iter()
+        .map(|prod| prod.tag.clone())
+        .collect();
+    tables.prod_lhs = spec
+        .productions
+        .iter()
+        .map(|prod| nt_ids[&prod.lhs])
+        .collect();
+
+    if let Some(ladder) = &spec.ladder {
+        tables.operator_precedence = ladder
+            .operators
+            .iter()
+            .map(|op| OperatorPrecedence {
+                kind: op.token,
+                precedence: op.precedence,
+                right_assoc: op.right_assoc,
+            })
+            .collect();
+        tables.ladder_nonterminals = ladder
+            .nonterminals
+            .iter()
+            .map(|name| nt_ids[name])
+            .collect();
+    }
+
     Ok(())
 }
 
@@ -751,6 +789,11 @@ pub(super) fn build_llp_precomputed_tables(
 
     let mut tables = build_mvp_precomputed_tables(N_KINDS, prod_arity);
     install_ll1_runtime_tables(&mut tables, spec, predictions)?;
+    // The sentinel takes the one kind id no lexer token uses, and frames the
+    // stream at both ends so the first and last tokens each get a pair cell.
+    debug_assert!(TokenKind::from_u32(DEFAULT_SENTINEL_KIND).is_none());
+    tables.sentinel_kind = DEFAULT_SENTINEL_KIND;
+    tables.start_sentinel = true;
 
     tables.sc_superseq.clear();
     tables.sc_off.fill(0);
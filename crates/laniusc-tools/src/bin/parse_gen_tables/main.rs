@@ -4,11 +4,24 @@
 //
 // Current behavior:
 //   * Parses production lines and a `%start NonTerminal;` directive.
+//   * Synthesizes operator-precedence ladder productions from `%ladder` and
+//     `%binop` directives and records the operator precedences in the tables.
 //   * Resolves quoted terminal names to lexer TokenKind discriminants.
 //   * Validates the grammar boundary before table generation.
+//   * Checks the terminals against the kinds the lexer and parser token passes
+//     produce (see `parser::grammar`), and records their names in the tables.
 //   * Emits Pareas-style LLP(1, 1) stack-change and partial-parse tables.
 //   * Emits LL(1) runtime tables while the GPU replay path remains available for
 //     cross-checking and diagnostics.
+//   * Renumbers token kinds densely so the pair grids only cover kinds the
+//     grammar references (see `parser::kindmap`).
+//   * Writes the versioned table container; `--legacy-format` writes the
+//     version-1 LXPRSE03 layout instead.
+//   * `--out PATH` writes only the tables, to PATH, for grammars other than
+//     the compiler's (no metadata or shader constants).
+//   * `--layout-sample DIR` (repeatable) lays the supersequences out for the
+//     pair cells that the `.lani` files under DIR hit most, and marks the
+//     tables optimized (see `PrecomputedParseTables::optimize_layout`).
 //
 // Grammar line examples:
 //   %start expr;
@@ -16,6 +29,9 @@
 //   sum [sum_add]       -> 'InfixPlus' atom sum;
 //   sum [sum_end]       -> ;
 //   atom [atom_paren]   -> 'GroupLParen' expr 'GroupRParen';
+//   %ladder expr -> atom;
+//   %binop 'InfixPlus' 1 left;
+//   %binop 'Star' 2 left;
 //
 // Notes:
 //   - Terminals appear as single-quoted TokenKind names.
@@ -32,23 +48,32 @@ use std::{
 use anyhow::{Context, Result, anyhow, bail};
 use laniusc_compiler::{
     lexer::tables::tokens::{N_KINDS, TokenKind},
-    parser::tables::{
-        INVALID_TABLE_ENTRY,
-        PrecomputedParseTables,
-        build_mvp_precomputed_tables,
-        encode_pop,
-        encode_push,
+    parser::{
+        grammar::{Grammar, GrammarProduction, check_against_lexer},
+        kindmap::{density, renumber_kinds},
+        tables::{
+            DEFAULT_SENTINEL_KIND,
+            INVALID_TABLE_ENTRY,
+            OperatorPrecedence,
+            PrecomputedParseTables,
+            build_mvp_precomputed_tables,
+            encode_pop,
+            encode_push,
+        },
     },
+    tables::FormatVersion,
 };
 use serde::Serialize;
 
 mod analysis;
 mod grammar;
+mod layout;
 mod llp;
 mod output;
 
 use analysis::*;
 use grammar::*;
+use layout::*;
 use llp::*;
 use output::*;
 
@@ -60,6 +85,22 @@ const EOF_TOKEN: u32 = 0;
 struct GrammarSpec {
     start: String,
     productions: Vec<Production>,
+    ladder: Option<PrecedenceLadder>,
+}
+
+/// Binary operators declared with `%binop`, and the nonterminals of the
+/// ladder productions synthesized for them.
+#[derive(Debug, Clone)]
+struct PrecedenceLadder {
+    operators: Vec<BinaryOperator>,
+    nonterminals: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BinaryOperator {
+    token: u32,
+    precedence: u32,
+    right_assoc: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -135,6 +176,7 @@ struct ProductionMeta {
 struct Ll1RuntimeMeta {
     nonterminals: usize,
     start_nonterminal: String,
+    allows_empty_input: bool,
     predict_cells: usize,
     rhs_symbols: usize,
 }
@@ -224,10 +266,56 @@ fn format_token(token: u32) -> String {
     }
 }
 
+/// The terminal view of `spec` that `check_against_lexer` reads.
+fn terminal_grammar(spec: &GrammarSpec) -> Grammar {
+    Grammar {
+        productions: spec
+            .productions
+            .iter()
+            .map(|prod| GrammarProduction {
+                tag: prod.tag.clone(),
+                terminals: prod
+                    .rhs_syms
+                    .iter()
+                    .filter_map(|sym| match sym {
+                        Sym::Terminal(id) => Some(*id),
+                        Sym::NonTerminal(_) => None,
+                    })
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
 fn main() -> Result<()> {
-    let grammar_path = env::args()
-        .nth(1)
-        .unwrap_or_else(|| "grammar/lanius.bnf".to_string());
+    let _ = laniusc_compiler::logging::init_default();
+    let mut version = FormatVersion::V2;
+    let mut grammar_path = None;
+    let mut tables_only_path = None;
+    let mut layout_sample_dirs = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--legacy-format" => version = FormatVersion::V1,
+            "--out" => {
+                let path = args.next().context("--out needs a path")?;
+                tables_only_path = Some(PathBuf::from(path));
+            }
+            "--layout-sample" => {
+                let dir = args.next().context("--layout-sample needs a directory")?;
+                layout_sample_dirs.push(PathBuf::from(dir));
+            }
+            flag if flag.starts_with("--") => {
+                bail!(
+                    "unknown argument {flag:?}; expected --legacy-format, --out PATH, \
+                     --layout-sample DIR, or a grammar path"
+                )
+            }
+            _ if grammar_path.is_none() => grammar_path = Some(arg),
+            _ => bail!("unexpected argument {arg:?}; expected one grammar path"),
+        }
+    }
+    let grammar_path = grammar_path.unwrap_or_else(|| "grammar/lanius.bnf".to_string());
     let out_path = PathBuf::from("tables/parse_tables.bin");
     let meta_path = PathBuf::from("tables/parse_tables.meta.json");
     let production_ids_path = PathBuf::from("shaders/parser/generated_parse_production_ids.slang");
@@ -248,6 +336,24 @@ fn main() -> Result<()> {
         eprintln!("[gen_parse_tables] warning: unreachable nonterminal '{unreachable}'");
     }
 
+    let terminals = terminal_grammar(&spec);
+    let (errors, warnings): (Vec<_>, Vec<_>) = check_against_lexer(&terminals)
+        .into_iter()
+        .partition(|issue| issue.is_error());
+    if !errors.is_empty() {
+        bail!(
+            "grammar does not match the lexer's token kinds:\n{}",
+            errors
+                .iter()
+                .map(|issue| format!("  - {issue}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+    for warning in &warnings {
+        eprintln!("[gen_parse_tables] warning: {warning}");
+    }
+
     let predictions = build_ll1_predictions(&spec, &analysis)?;
     let prod_arity = compute_prod_arity(&spec.productions);
 
@@ -256,11 +362,55 @@ fn main() -> Result<()> {
         GeneratedPairTables,
         usize,
     ) = build_llp_precomputed_tables(&spec, &predictions, prod_arity.clone())?;
+    let mut tables = renumber_kinds(&tables);
+    tables.terminal_names = terminals.terminal_names();
+    println!("[gen_parse_tables] kind renumbering: {}", density(&tables));
+    if !layout_sample_dirs.is_empty() {
+        let samples = layout_samples(&layout_sample_dirs)?;
+        let report = tables.optimize_layout(&samples);
+        println!(
+            "[gen_parse_tables] layout from {} samples: {report}",
+            samples.len()
+        );
+    }
+    let gpu_blob = tables.to_gpu_blob();
+    println!(
+        "[gen_parse_tables] gpu blob: {} -> {} bytes ({:.1}x, palette={} entries)",
+        tables.uncompressed_gpu_blob_bytes(),
+        gpu_blob.byte_len(),
+        tables.uncompressed_gpu_blob_bytes() as f64 / gpu_blob.byte_len().max(1) as f64,
+        gpu_blob.palette_len
+    );
+
+    if let Err(violations) = tables.validate() {
+        bail!(
+            "generated parse tables are inconsistent:\n{}",
+            violations
+                .iter()
+                .map(|violation| format!("  - {violation}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+
+    if let Some(path) = tables_only_path {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        tables.save_bin_as(&path, version)?;
+        println!(
+            "[gen_parse_tables] wrote {} (start={}, productions={})",
+            path.display(),
+            spec.start,
+            spec.productions.len()
+        );
+        return Ok(());
+    }
 
     if let Some(parent) = out_path.parent() {
         fs::create_dir_all(parent)?;
     }
-    tables.save_bin(&out_path)?;
+    tables.save_bin_as(&out_path, version)?;
     let meta = build_meta(
         &grammar_path,
         &spec,
@@ -269,6 +419,7 @@ fn main() -> Result<()> {
         &prod_arity,
         &pair_tables,
         witness_inputs,
+        tables.n_kinds,
     );
     let meta_json = serde_json::to_string_pretty(&meta)?;
     fs::write(&meta_path, meta_json)
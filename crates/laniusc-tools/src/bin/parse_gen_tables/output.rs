@@ -74,6 +74,7 @@ pub(super) fn build_meta(
     prod_arity: &[u32],
     pair_tables: &GeneratedPairTables,
     witness_inputs: usize,
+    grid_kinds: u32,
 ) -> ParseTablesMeta {
     ParseTablesMeta {
         grammar: grammar_path.to_string(),
@@ -101,7 +102,9 @@ pub(super) fn build_meta(
             Ll1RuntimeMeta {
                 nonterminals: nonterminals.len(),
                 start_nonterminal: spec.start.clone(),
-                predict_cells: nonterminals.len() * N_KINDS as usize,
+                allows_empty_input: compute_nullable(&spec.productions, &nonterminals)
+                    .contains(&spec.start),
+                predict_cells: nonterminals.len() * grid_kinds as usize,
                 rhs_symbols,
             }
         },
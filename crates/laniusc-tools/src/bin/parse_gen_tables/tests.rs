@@ -0,0 +1,853 @@
+use std::sync::OnceLock;
+
+use laniusc_compiler::{
+    lexer::GpuLexer,
+    parser::{
+        GpuParser,
+        ast::{Ast, AstError, AstNode},
+    },
+};
+
+use super::*;
+
+struct CurrentGrammar {
+    spec: GrammarSpec,
+    analysis: GrammarAnalysis,
+    predictions: Vec<Prediction>,
+}
+
+fn current_grammar() -> &'static CurrentGrammar {
+    static CURRENT_GRAMMAR: OnceLock<CurrentGrammar> = OnceLock::new();
+    CURRENT_GRAMMAR.get_or_init(|| {
+        let spec = parse_grammar(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../grammar/lanius.bnf"
+        )))
+        .expect("parse grammar");
+        let analysis = analyze_grammar(&spec);
+        let predictions = build_ll1_predictions(&spec, &analysis).expect("ll1 predictions");
+        CurrentGrammar {
+            spec,
+            analysis,
+            predictions,
+        }
+    })
+}
+
+fn parse_with_predictions(
+    spec: &GrammarSpec,
+    predictions: &[Prediction],
+    input: &[TokenKind],
+) -> Result<Vec<String>> {
+    let predict_map = build_predict_map(predictions);
+
+    let mut stack = vec![Sym::NonTerminal(spec.start.clone())];
+    let mut pos = 0usize;
+    let mut emitted = Vec::new();
+
+    while let Some(top) = stack.pop() {
+        match top {
+            Sym::Terminal(token) => {
+                if input.get(pos).map(|kind| *kind as u32) != Some(token) {
+                    bail!(
+                        "terminal mismatch at pos {}: expected {:?}, found {:?}",
+                        pos,
+                        format_token(token),
+                        input.get(pos)
+                    );
+                }
+                pos += 1;
+            }
+            Sym::NonTerminal(name) => {
+                let lookahead = input
+                    .get(pos)
+                    .map(|token| *token as u32)
+                    .unwrap_or(EOF_TOKEN);
+                let Some(&prod_id) = predict_map.get(&(name.clone(), lookahead)) else {
+                    bail!(
+                        "no prediction for {} on {} at pos {}",
+                        name,
+                        format_token(lookahead),
+                        pos
+                    );
+                };
+                let prod = &spec.productions[prod_id];
+                emitted.push(prod.tag.clone());
+                stack.extend(prod.rhs_syms.iter().rev().cloned());
+            }
+        }
+    }
+
+    if pos != input.len() {
+        bail!("parser stopped at token {} of {}", pos, input.len());
+    }
+
+    Ok(emitted)
+}
+
+fn prediction_chunks_by_pair(
+    spec: &GrammarSpec,
+    predictions: &[Prediction],
+    input: &[TokenKind],
+) -> Result<BTreeMap<(u32, u32), Vec<String>>> {
+    let predict_map = build_predict_map(predictions);
+
+    let mut chunks_by_pos = vec![Vec::new(); input.len() + 1];
+    let mut stack = vec![Sym::NonTerminal(spec.start.clone())];
+    let mut pos = 0usize;
+
+    while let Some(top) = stack.pop() {
+        match top {
+            Sym::Terminal(token) => {
+                if input.get(pos).map(|kind| *kind as u32) != Some(token) {
+                    bail!(
+                        "terminal mismatch at pos {}: expected {:?}, found {:?}",
+                        pos,
+                        format_token(token),
+                        input.get(pos)
+                    );
+                }
+                pos += 1;
+            }
+            Sym::NonTerminal(name) => {
+                let lookahead = input
+                    .get(pos)
+                    .map(|token| *token as u32)
+                    .unwrap_or(EOF_TOKEN);
+                let Some(&prod_id) = predict_map.get(&(name.clone(), lookahead)) else {
+                    bail!(
+                        "no prediction for {} on {} at pos {}",
+                        name,
+                        format_token(lookahead),
+                        pos
+                    );
+                };
+                let prod = &spec.productions[prod_id];
+                chunks_by_pos[pos].push(prod.tag.clone());
+                stack.extend(prod.rhs_syms.iter().rev().cloned());
+            }
+        }
+    }
+
+    if pos != input.len() {
+        bail!("parser stopped at token {} of {}", pos, input.len());
+    }
+
+    let mut out = BTreeMap::new();
+    for (pos, chunk) in chunks_by_pos.into_iter().enumerate() {
+        let prev = if pos == 0 {
+            EOF_TOKEN
+        } else {
+            input[pos - 1] as u32
+        };
+        let current = input
+            .get(pos)
+            .map(|token| *token as u32)
+            .unwrap_or(EOF_TOKEN);
+        out.insert((prev, current), chunk);
+    }
+    Ok(out)
+}
+
+#[test]
+fn parses_explicit_start_directive() {
+    let spec = parse_grammar(
+        "
+        %start expr;
+        expr -> 'Ident';
+        ",
+    )
+    .expect("parse grammar");
+
+    assert_eq!(spec.start, "expr");
+    assert_eq!(spec.productions.len(), 1);
+}
+
+#[test]
+fn current_grammar_is_clean_at_generator_boundary() {
+    let current = current_grammar();
+
+    assert_eq!(current.spec.start, "file");
+    assert!(!current.predictions.is_empty());
+    assert!(
+        !diagnostics_are_fatal(&current.analysis.diagnostics),
+        "{}",
+        format_diagnostics(&current.analysis.diagnostics)
+    );
+}
+
+#[test]
+fn ll1_predictions_parse_expression_stream() {
+    let current = current_grammar();
+    let tags = parse_with_predictions(
+        &current.spec,
+        &current.predictions,
+        &[
+            TokenKind::Fn,
+            TokenKind::Ident,
+            TokenKind::ParamLParen,
+            TokenKind::ParamRParen,
+            TokenKind::FnBlockLBrace,
+            TokenKind::Return,
+            TokenKind::Ident,
+            TokenKind::InfixPlus,
+            TokenKind::Int,
+            TokenKind::ReturnSemicolon,
+            TokenKind::FnBlockRBrace,
+        ],
+    )
+    .expect("parse function token stream");
+
+    assert_eq!(tags.first().map(String::as_str), Some("file"));
+    assert!(tags.iter().any(|tag| tag == "fn"));
+    assert!(tags.iter().any(|tag| tag == "expr"));
+    assert!(tags.iter().any(|tag| tag == "ident"));
+    assert!(tags.iter().any(|tag| tag == "add_tail"));
+    assert!(tags.iter().any(|tag| tag == "int"));
+    assert!(tags.iter().any(|tag| tag == "assign_end"));
+}
+
+#[test]
+fn ll1_predictions_parse_empty_array() {
+    let current = current_grammar();
+    let tags = parse_with_predictions(
+        &current.spec,
+        &current.predictions,
+        &[
+            TokenKind::Fn,
+            TokenKind::Ident,
+            TokenKind::ParamLParen,
+            TokenKind::ParamRParen,
+            TokenKind::FnBlockLBrace,
+            TokenKind::Return,
+            TokenKind::ArrayLBracket,
+            TokenKind::ArrayRBracket,
+            TokenKind::ReturnSemicolon,
+            TokenKind::FnBlockRBrace,
+        ],
+    )
+    .expect("parse function token stream");
+
+    assert!(tags.iter().any(|tag| tag == "array_lit"));
+    assert!(tags.iter().any(|tag| tag == "array_none"));
+}
+
+#[test]
+fn simple_llp_grammar_builds_paper_style_pair_tables() {
+    let spec = parse_grammar(
+        "
+        %start file;
+        file [file_ident] -> 'Ident' 'Semicolon';
+        ",
+    )
+    .expect("parse grammar");
+    let analysis = analyze_grammar(&spec);
+    assert!(
+        !diagnostics_are_fatal(&analysis.diagnostics),
+        "{}",
+        format_diagnostics(&analysis.diagnostics)
+    );
+    let predictions = build_ll1_predictions(&spec, &analysis).expect("ll1 predictions");
+    let prod_arity = compute_prod_arity(&spec.productions);
+
+    let (tables, pair_tables, witness_inputs) =
+        build_llp_precomputed_tables(&spec, &predictions, prod_arity)
+            .expect("build paper-style LLP tables");
+
+    assert_eq!(witness_inputs, 0);
+    assert!(!pair_tables.stack_change.cells.is_empty());
+    assert!(!pair_tables.partial_parse.cells.is_empty());
+    assert!(!tables.sc_superseq.is_empty());
+    assert!(!tables.pp_superseq.is_empty());
+}
+
+#[test]
+fn ten_kind_grammar_renumbers_to_ten_by_ten_grids() {
+    let spec = parse_grammar(
+        "
+        %start file;
+        file [file_stmt]    -> 'Let' 'Ident' 'Assign' expr 'Semicolon';
+        expr [expr_atom]    -> 'Int' expr_tail;
+        expr [expr_group]   -> 'GroupLParen' expr 'GroupRParen' expr_tail;
+        expr_tail [add]     -> 'Plus' expr;
+        expr_tail [mul]     -> 'Star' expr;
+        expr_tail [end]     -> ;
+        ",
+    )
+    .expect("parse grammar");
+    let analysis = analyze_grammar(&spec);
+    let predictions = build_ll1_predictions(&spec, &analysis).expect("ll1 predictions");
+    let prod_arity = compute_prod_arity(&spec.productions);
+    let (tables, _, _) =
+        build_llp_precomputed_tables(&spec, &predictions, prod_arity).expect("build LLP tables");
+    assert_eq!(tables.n_kinds, N_KINDS);
+    assert_eq!(tables.sentinel_kind, 0);
+    assert!(tables.start_sentinel);
+
+    let dense = renumber_kinds(&tables);
+    assert_eq!(dense.n_kinds, 10);
+    assert_eq!(dense.sc_len.len(), 100);
+    assert_eq!(dense.pp_len.len(), 100);
+    assert_eq!(density(&dense).source_kinds, N_KINDS);
+
+    let kinds = [
+        TokenKind::Let,
+        TokenKind::Ident,
+        TokenKind::Assign,
+        TokenKind::GroupLParen,
+        TokenKind::Int,
+        TokenKind::Plus,
+        TokenKind::Int,
+        TokenKind::GroupRParen,
+        TokenKind::Plus,
+        TokenKind::Int,
+        TokenKind::Semicolon,
+    ];
+    let mut stream = vec![0];
+    stream.extend(kinds.iter().map(|&kind| kind as u32));
+    stream.push(0);
+    assert!(tables.test_cpu_ll1_production_stream(&stream).is_ok());
+    assert_eq!(
+        dense.test_cpu_ll1_production_stream(&stream),
+        tables.test_cpu_ll1_production_stream(&stream)
+    );
+    assert_eq!(
+        dense.test_cpu_partial_parse_stream(&stream),
+        tables.test_cpu_partial_parse_stream(&stream)
+    );
+
+    stream[4] = TokenKind::Minus as u32;
+    assert_eq!(
+        dense.diagnose_ll1_rejection(&stream),
+        tables.diagnose_ll1_rejection(&stream)
+    );
+}
+
+#[test]
+fn psls_conflict_report_names_productions_and_gammas() {
+    let spec = parse_grammar(
+        "
+        %start file;
+        file [file_item] -> item;
+        item [item_impl] -> 'Impl' block;
+        block [block] -> 'LBrace' 'RBrace';
+        ",
+    )
+    .expect("parse grammar");
+    let conflicts = vec![PslsConflict {
+        pair: (TokenKind::RBrace as u32, EOF_TOKEN),
+        existing_prod: 1,
+        prod: 2,
+        existing_gamma: vec![Sym::Terminal(TokenKind::RBrace as u32)],
+        gamma: vec![
+            Sym::NonTerminal("block".to_string()),
+            Sym::Terminal(TokenKind::RBrace as u32),
+        ],
+    }];
+
+    let report = format_psls_conflicts(&spec, &conflicts, 20);
+
+    assert!(report.contains("(RBrace, $)"), "{report}");
+    assert!(report.contains("item [item_impl]"), "{report}");
+    assert!(report.contains("block [block]"), "{report}");
+    assert!(report.contains("existing gamma: 'RBrace'"), "{report}");
+    assert!(
+        report.contains("incoming gamma: block 'RBrace'"),
+        "{report}"
+    );
+}
+
+#[test]
+fn semantic_closing_delimiters_use_distinct_pair_table_entries() {
+    let current = current_grammar();
+
+    let group = prediction_chunks_by_pair(
+        &current.spec,
+        &current.predictions,
+        &[
+            TokenKind::Fn,
+            TokenKind::Ident,
+            TokenKind::ParamLParen,
+            TokenKind::ParamRParen,
+            TokenKind::FnBlockLBrace,
+            TokenKind::Return,
+            TokenKind::GroupLParen,
+            TokenKind::Ident,
+            TokenKind::GroupRParen,
+            TokenKind::ReturnSemicolon,
+            TokenKind::FnBlockRBrace,
+        ],
+    )
+    .expect("parse grouped expression");
+    let call = prediction_chunks_by_pair(
+        &current.spec,
+        &current.predictions,
+        &[
+            TokenKind::Fn,
+            TokenKind::Ident,
+            TokenKind::ParamLParen,
+            TokenKind::ParamRParen,
+            TokenKind::FnBlockLBrace,
+            TokenKind::Return,
+            TokenKind::Ident,
+            TokenKind::CallLParen,
+            TokenKind::Ident,
+            TokenKind::CallRParen,
+            TokenKind::ReturnSemicolon,
+            TokenKind::FnBlockRBrace,
+        ],
+    )
+    .expect("parse call expression");
+
+    let group_key = (TokenKind::Ident as u32, TokenKind::GroupRParen as u32);
+    let call_key = (TokenKind::Ident as u32, TokenKind::CallRParen as u32);
+    let group_chunk = group
+        .get(&group_key)
+        .expect("group Ident/GroupRParen chunk");
+    let call_chunk = call.get(&call_key).expect("call Ident/CallRParen chunk");
+
+    assert!(!group_chunk.is_empty());
+    assert!(!call_chunk.is_empty());
+}
+
+#[test]
+fn explain_emits_renders_tiny_expression_grammar() {
+    let spec = parse_grammar(
+        "
+        %start expr;
+        expr                    -> term expr_tail;
+        expr_tail [add]         -> 'InfixPlus' term expr_tail;
+        expr_tail [expr_end]    -> ;
+        term                    -> atom term_tail;
+        term_tail [mul]         -> 'Star' atom term_tail;
+        term_tail [term_end]    -> ;
+        atom [ident]            -> 'Ident';
+        atom [group]            -> 'GroupLParen' expr 'GroupRParen';
+        ",
+    )
+    .expect("parse grammar");
+    let analysis = analyze_grammar(&spec);
+    let predictions = build_ll1_predictions(&spec, &analysis).expect("ll1 predictions");
+    let (tables, _, _) =
+        build_llp_precomputed_tables(&spec, &predictions, compute_prod_arity(&spec.productions))
+            .expect("build tables");
+    let tables = renumber_kinds(&tables);
+    let bytes = tables.to_bin_bytes();
+    let tables = PrecomputedParseTables::load_bin_bytes(&bytes).expect("reload tables");
+
+    // Sentinel-wrapped, as the parser driver sees it.
+    let input = [
+        0,
+        TokenKind::Ident as u32,
+        TokenKind::InfixPlus as u32,
+        TokenKind::Ident as u32,
+        TokenKind::Star as u32,
+        TokenKind::Ident as u32,
+        0,
+    ];
+    let emits = tables
+        .test_cpu_ll1_production_stream(&input)
+        .expect("parse tiny expression");
+
+    assert_eq!(
+        tables.explain_productions(&emits),
+        [
+            "expr -> term expr_tail",
+            "term -> atom term_tail",
+            "atom -> 'Ident'",
+            "term_tail -> ε",
+            "expr_tail -> 'InfixPlus' term expr_tail",
+            "term -> atom term_tail",
+            "atom -> 'Ident'",
+            "term_tail -> 'Star' atom term_tail",
+            "atom -> 'Ident'",
+            "term_tail -> ε",
+            "expr_tail -> ε",
+        ]
+    );
+    let names: Vec<_> = emits.iter().map(|&p| tables.production_name(p)).collect();
+    assert_eq!(names[..4], ["expr", "term", "ident", "term_end"]);
+}
+
+#[test]
+fn detects_direct_left_recursion() {
+    let spec = parse_grammar(
+        "
+        %start expr;
+        expr -> expr 'InfixPlus' atom;
+        expr -> atom;
+        atom -> 'Ident';
+        ",
+    )
+    .expect("parse grammar");
+    let analysis = analyze_grammar(&spec);
+
+    assert!(
+        analysis
+            .diagnostics
+            .left_recursions
+            .iter()
+            .any(|cycle| cycle == "expr -> expr")
+    );
+}
+
+#[test]
+fn detects_ll1_first_conflict() {
+    let spec = parse_grammar(
+        "
+        %start expr;
+        expr [a] -> 'Ident';
+        expr [b] -> 'Ident';
+        ",
+    )
+    .expect("parse grammar");
+    let analysis = analyze_grammar(&spec);
+
+    assert!(
+        analysis
+            .diagnostics
+            .ll1_conflicts
+            .iter()
+            .any(|conflict| conflict.contains("share FIRST {Ident}"))
+    );
+}
+
+#[test]
+fn detects_undefined_nonterminals() {
+    let spec = parse_grammar(
+        "
+        %start expr;
+        expr -> atom;
+        ",
+    )
+    .expect("parse grammar");
+    let analysis = analyze_grammar(&spec);
+
+    assert!(
+        analysis
+            .diagnostics
+            .undefined_nonterminals
+            .iter()
+            .any(|missing| missing.contains("'atom'"))
+    );
+}
+
+const LADDER_GRAMMAR: &str = "
+    %start expr;
+    %ladder expr -> atom;
+    %binop 'Assign' 1 right;
+    %binop 'InfixPlus' 2 left;
+    %binop 'InfixMinus' 2 left;
+    %binop 'Star' 3 left;
+    atom [ident] -> 'Ident';
+    atom [group] -> 'GroupLParen' expr 'GroupRParen';
+";
+
+const LADDER_CASES: [(&str, &str); 4] = [
+    ("a + b * c - d", "((a + (b * c)) - d)"),
+    ("a - b - c", "((a - b) - c)"),
+    ("a = b = c", "(a = (b = c))"),
+    ("( a = b ) * c", "(( (a = b) ) * c)"),
+];
+
+fn ladder_tables() -> PrecomputedParseTables {
+    tables_for(LADDER_GRAMMAR)
+}
+
+fn tables_for(grammar: &str) -> PrecomputedParseTables {
+    let spec = parse_grammar(grammar).expect("parse grammar");
+    let analysis = analyze_grammar(&spec);
+    assert!(
+        !diagnostics_are_fatal(&analysis.diagnostics),
+        "{}",
+        format_diagnostics(&analysis.diagnostics)
+    );
+    let predictions = build_ll1_predictions(&spec, &analysis).expect("ll1 predictions");
+    let (tables, _, _) =
+        build_llp_precomputed_tables(&spec, &predictions, compute_prod_arity(&spec.productions))
+            .expect("build LLP tables");
+    let bytes = renumber_kinds(&tables).to_bin_bytes();
+    PrecomputedParseTables::load_bin_bytes(&bytes).expect("reload tables")
+}
+
+fn ladder_kind(token: &str) -> u32 {
+    (match token {
+        "+" => TokenKind::InfixPlus,
+        "-" => TokenKind::InfixMinus,
+        "*" => TokenKind::Star,
+        "=" => TokenKind::Assign,
+        "(" => TokenKind::GroupLParen,
+        ")" => TokenKind::GroupRParen,
+        _ => TokenKind::Ident,
+    }) as u32
+}
+
+/// Parenthesized infix rendering, with token text looked up by token index.
+fn infix(node: &AstNode, texts: &[&str]) -> String {
+    match node {
+        AstNode::Binary {
+            op_index, lhs, rhs, ..
+        } => format!(
+            "({} {} {})",
+            infix(lhs, texts),
+            texts[*op_index as usize],
+            infix(rhs, texts)
+        ),
+        AstNode::Token { index, .. } => texts[*index as usize].to_string(),
+        AstNode::Production { children, .. } => children
+            .iter()
+            .map(|child| infix(child, texts))
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+#[test]
+fn ladder_directives_synthesize_one_nonterminal_per_level() {
+    let spec = parse_grammar(LADDER_GRAMMAR).expect("parse grammar");
+    let rendered: Vec<_> = spec
+        .productions
+        .iter()
+        .map(|prod| {
+            let rhs: Vec<_> = prod
+                .rhs_syms
+                .iter()
+                .map(|sym| match sym {
+                    Sym::Terminal(token) => format!("'{}'", format_token(*token)),
+                    Sym::NonTerminal(name) => name.clone(),
+                })
+                .collect();
+            format!("{} [{}] -> {}", prod.lhs, prod.tag, rhs.join(" "))
+        })
+        .collect();
+    assert_eq!(
+        rendered,
+        [
+            "atom [ident] -> 'Ident'",
+            "atom [group] -> 'GroupLParen' expr 'GroupRParen'",
+            "expr [expr] -> expr_p2 expr_tail",
+            "expr_tail [expr_tail_Assign] -> 'Assign' expr",
+            "expr_tail [expr_tail_end] -> ",
+            "expr_p2 [expr_p2] -> expr_p3 expr_p2_tail",
+            "expr_p2_tail [expr_p2_tail_InfixPlus] -> 'InfixPlus' expr_p3 expr_p2_tail",
+            "expr_p2_tail [expr_p2_tail_InfixMinus] -> 'InfixMinus' expr_p3 expr_p2_tail",
+            "expr_p2_tail [expr_p2_tail_end] -> ",
+            "expr_p3 [expr_p3] -> atom expr_p3_tail",
+            "expr_p3_tail [expr_p3_tail_Star] -> 'Star' atom expr_p3_tail",
+            "expr_p3_tail [expr_p3_tail_end] -> ",
+        ]
+    );
+    let ladder = spec.ladder.expect("ladder");
+    assert_eq!(ladder.operators.len(), 4);
+    assert_eq!(
+        ladder.nonterminals,
+        [
+            "expr",
+            "expr_tail",
+            "expr_p2",
+            "expr_p2_tail",
+            "expr_p3",
+            "expr_p3_tail"
+        ]
+    );
+
+    let tables = ladder_tables();
+    assert_eq!(tables.ladder_nonterminals.len(), 6);
+    let assign = tables
+        .operator_precedence(TokenKind::Assign as u32)
+        .expect("Assign precedence");
+    assert_eq!((assign.precedence, assign.right_assoc), (1, true));
+}
+
+#[test]
+fn ladder_directives_reject_inconsistent_declarations() {
+    for (grammar, message) in [
+        (
+            "%binop 'Star' 1 left;\natom -> 'Ident';",
+            "%binop without a %ladder",
+        ),
+        ("%ladder expr -> atom;\natom -> 'Ident';", "no %binop"),
+        (
+            "%ladder expr -> atom;\n%binop 'Star' 1 left;\n%binop 'Assign' 1 right;\natom -> 'Ident';",
+            "mixes left- and right-associative",
+        ),
+        (
+            "%ladder expr -> atom;\n%binop 'Star' 1 left;\nexpr -> 'Ident';\natom -> 'Ident';",
+            "cannot have hand-written productions",
+        ),
+        (
+            "%ladder expr -> atom;\n%binop 'Star' 1 sideways;\natom -> 'Ident';",
+            "associativity",
+        ),
+    ] {
+        let err = parse_grammar(grammar).expect_err(grammar).to_string();
+        assert!(err.contains(message), "{err}");
+    }
+}
+
+#[test]
+fn collapsed_ladder_trees_follow_precedence_and_associativity() {
+    let tables = ladder_tables();
+    for (source, expected) in LADDER_CASES {
+        let texts: Vec<&str> = source.split_whitespace().collect();
+        let kinds: Vec<u32> = texts.iter().map(|token| ladder_kind(token)).collect();
+        let emits = tables
+            .test_cpu_ll1_production_stream(&tables.wrap_input(&kinds).unwrap())
+            .expect("parse ladder expression");
+
+        let mut ast = Ast::from_productions(&tables, &emits).expect("rebuild tree");
+        assert!(
+            ast.to_sexpr().contains("expr_p2_tail"),
+            "{}",
+            ast.to_sexpr()
+        );
+        ast.collapse_precedence_chains();
+        assert_eq!(infix(&ast.root, &texts), expected, "{source}");
+        assert!(!ast.to_sexpr().contains("expr_p"), "{}", ast.to_sexpr());
+    }
+
+    let emits = tables
+        .test_cpu_ll1_production_stream(&tables.wrap_input(&[ladder_kind("a")]).unwrap())
+        .unwrap();
+    assert!(matches!(
+        Ast::from_productions(&tables, &emits[..emits.len() - 1]),
+        Err(AstError::Truncated { .. })
+    ));
+    let mut trailing = emits.clone();
+    trailing.push(emits[0]);
+    assert_eq!(
+        Ast::from_productions(&tables, &trailing).unwrap_err(),
+        AstError::Trailing { index: emits.len() }
+    );
+}
+
+#[test]
+fn skip_only_inputs_parse_to_the_empty_program_when_the_start_is_nullable() {
+    let nullable = tables_for("file -> items;\nitems [more] -> 'Ident' items;\nitems [done] -> ;");
+    let required =
+        tables_for("file -> 'Ident' rest;\nrest [more] -> 'Ident' rest;\nrest [done] -> ;");
+    assert!(nullable.allows_empty_input);
+    assert!(!required.allows_empty_input);
+    assert_eq!(required.empty_input_productions(), None);
+
+    let empty = Ast::empty_program(&nullable).expect("empty program");
+    assert_eq!(empty.to_sexpr(), "(file (done))");
+    assert_eq!(
+        Ast::empty_program(&required).unwrap_err(),
+        AstError::EmptyInputNotAllowed
+    );
+    assert_eq!(
+        AstError::EmptyInputNotAllowed.to_string(),
+        "the grammar does not accept an empty program"
+    );
+
+    for source in ["", "   \n\t", "// only a comment", "/* block */ // line\n"] {
+        let tokens = laniusc_compiler::lexer::test_cpu::lex_on_test_cpu(source).expect("lex");
+        assert!(tokens.is_empty(), "{source:?}");
+        let kinds = nullable.wrap_input(&[]).unwrap();
+        let emits = nullable
+            .test_cpu_ll1_production_stream(&kinds)
+            .expect("empty input parses");
+        assert_eq!(Some(&emits), nullable.empty_input_productions().as_ref());
+        let ast = Ast::from_productions(&nullable, &emits).expect("rebuild tree");
+        assert_eq!(ast.root, empty.root, "{source:?}");
+        // The parser emits nothing for a lone sentinel.
+        assert_eq!(
+            Ast::from_productions(&nullable, &[])
+                .expect("empty stream")
+                .root,
+            empty.root
+        );
+
+        assert!(
+            required
+                .test_cpu_ll1_production_stream(&required.wrap_input(&[]).unwrap())
+                .is_err(),
+            "{source:?}"
+        );
+        assert_eq!(
+            Ast::from_productions(&required, &[]).unwrap_err(),
+            AstError::EmptyInputNotAllowed
+        );
+    }
+
+    let reloaded = PrecomputedParseTables::load_bin_bytes(&nullable.to_bin_bytes()).unwrap();
+    assert!(reloaded.allows_empty_input);
+    let reloaded = PrecomputedParseTables::load_bin_bytes(&required.to_bin_bytes()).unwrap();
+    assert!(!reloaded.allows_empty_input);
+}
+
+#[test]
+fn current_grammar_accepts_an_empty_file() {
+    let tables = PrecomputedParseTables::load_bin_bytes(include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../tables/parse_tables.bin"
+    )))
+    .expect("load generated tables");
+    assert!(tables.allows_empty_input);
+    let emits = tables
+        .test_cpu_ll1_production_stream(&tables.wrap_input(&[]).unwrap())
+        .expect("empty file parses");
+    assert_eq!(Some(emits), tables.empty_input_productions());
+    Ast::empty_program(&tables).expect("empty program");
+}
+
+#[test]
+fn sync_sets_are_first_and_follow_without_end_of_input() {
+    use TokenKind::*;
+    let tables = tables_for(
+        "file -> stmts;
+        stmts [more] -> stmt stmts;
+        stmts [done] -> ;
+        stmt [let] -> 'Let' 'LetIdent' 'LetAssign' expr 'LetSemicolon';
+        stmt [expr] -> expr 'ExprSemicolon';
+        expr [int] -> 'Int';
+        expr [group] -> 'GroupLParen' expr 'GroupRParen';",
+    );
+    let sync = |name: &str| {
+        let nt = (0..tables.n_nonterminals)
+            .find(|&nt| tables.nonterminal_name(nt) == name)
+            .expect(name);
+        tables.sync_set(nt).to_vec()
+    };
+    let kinds = |kinds: &[TokenKind]| {
+        let mut kinds = kinds.iter().map(|&kind| kind as u32).collect::<Vec<_>>();
+        kinds.sort_unstable();
+        kinds
+    };
+    // FOLLOW(file) and FOLLOW(stmts) hold only end of input.
+    assert_eq!(sync("file"), kinds(&[Let, Int, GroupLParen]));
+    assert_eq!(sync("stmts"), kinds(&[Let, Int, GroupLParen]));
+    assert_eq!(sync("stmt"), kinds(&[Let, Int, GroupLParen]));
+    assert_eq!(
+        sync("expr"),
+        kinds(&[Int, GroupLParen, LetSemicolon, ExprSemicolon, GroupRParen])
+    );
+}
+
+#[test]
+fn gpu_parsed_ladder_expressions_collapse_to_the_expected_shapes() {
+    let tables = ladder_tables();
+    pollster::block_on(async {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let parser = GpuParser::new().await.expect("create GPU parser");
+        for (source, expected) in LADDER_CASES {
+            let tokens = lexer.lex(source).await.expect("GPU lex");
+            let texts: Vec<&str> = tokens
+                .iter()
+                .map(|token| &source[token.span.range()])
+                .collect();
+            let kinds: Vec<u32> = tokens.iter().map(|token| token.kind as u32).collect();
+            let result = parser
+                .parse_tokens(&kinds, &tables)
+                .await
+                .expect("GPU parse");
+            assert!(result.ll1.accepted, "{source}");
+
+            let mut ast = Ast::from_productions(&tables, &result.emit_stream)
+                .unwrap_or_else(|err| panic!("{source}: {err}"));
+            ast.collapse_precedence_chains();
+            assert_eq!(infix(&ast.root, &texts), expected, "{source}");
+        }
+    });
+}
@@ -0,0 +1,205 @@
+// Times `pack_varlen` with the checked-in parse tables and with a copy laid
+// out by `PrecomputedParseTables::optimize_layout`.
+//
+// The layout is trained on the `.lani` files under the sample directories;
+// the benchmark stream repeats their token kinds until it holds `--tokens`
+// kinds. Both tables parse the same stream on the GPU with pass timing on,
+// alternating runs, and must give identical stack-change and production
+// streams. Run from the repository root:
+//
+//     cargo run --release -p laniusc-tools --bin parse_layout_bench -- \
+//         [--tokens N] [--runs N] [--sample DIR]...
+//
+// Sample directories default to sample_programs and stdlib.
+
+use std::{
+    env,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail, ensure};
+use laniusc_compiler::{
+    lexer::test_cpu::lex_on_test_cpu,
+    parser::retag::retag_open_delimiters,
+    prelude::*,
+};
+
+const DEFAULT_SAMPLE_DIRS: &[&str] = &["sample_programs", "stdlib"];
+const DEFAULT_TOKENS: usize = 1 << 18;
+const DEFAULT_RUNS: usize = 9;
+const PACK_LABEL: &str = "pack_varlen";
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    let _ = laniusc_compiler::logging::init_default();
+    let mut tokens = DEFAULT_TOKENS;
+    let mut runs = DEFAULT_RUNS;
+    let mut sample_dirs = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().with_context(|| format!("{flag} needs a value"));
+        match arg.as_str() {
+            "--tokens" => tokens = value("--tokens")?.parse().context("--tokens")?,
+            "--runs" => runs = value("--runs")?.parse().context("--runs")?,
+            "--sample" => sample_dirs.push(PathBuf::from(value("--sample")?)),
+            _ => bail!("unknown argument {arg:?}; expected --tokens N, --runs N, or --sample DIR"),
+        }
+    }
+    ensure!(
+        tokens > 0 && runs > 0,
+        "--tokens and --runs must be positive"
+    );
+    if sample_dirs.is_empty() {
+        sample_dirs = DEFAULT_SAMPLE_DIRS.iter().map(PathBuf::from).collect();
+    }
+
+    let bytes = fs::read("tables/parse_tables.bin").context("read tables/parse_tables.bin")?;
+    let tables = PrecomputedParseTables::load_bin_bytes(&bytes)
+        .map_err(|err| anyhow::anyhow!("load tables/parse_tables.bin: {err}"))?;
+    let samples = sample_kinds(&sample_dirs)?;
+    let mut optimized = tables.clone();
+    let report = optimized.optimize_layout(&samples);
+    println!("[parse_layout_bench] layout: {report}");
+    let (blob, optimized_blob) = (tables.to_gpu_blob(), optimized.to_gpu_blob());
+    println!(
+        "[parse_layout_bench] blob: {} -> {} bytes, palette {} -> {} entries",
+        blob.byte_len(),
+        optimized_blob.byte_len(),
+        blob.palette_len,
+        optimized_blob.palette_len
+    );
+
+    let stream = repeat_to_len(&samples, tokens);
+    println!(
+        "[parse_layout_bench] {} token kinds from {} samples, {runs} runs each",
+        stream.len(),
+        samples.len()
+    );
+    let parser = GpuParser::new_with(ParserOptions {
+        gpu_timing: true,
+        ..ParserOptions::from_env()
+    })
+    .await?;
+    let (mut base_ms, mut optimized_ms) = (Vec::new(), Vec::new());
+    for run in 0..runs {
+        let base = parser.parse_tokens(&stream, &tables).await?;
+        let laid_out = parser.parse_tokens(&stream, &optimized).await?;
+        ensure!(
+            base.sc_stream == laid_out.sc_stream && base.emit_stream == laid_out.emit_stream,
+            "run {run}: optimized tables packed different streams"
+        );
+        base_ms.push(pack_ms(&base)?);
+        optimized_ms.push(pack_ms(&laid_out)?);
+    }
+
+    let (base, laid_out) = (median(&mut base_ms), median(&mut optimized_ms));
+    println!(
+        "[parse_layout_bench] {PACK_LABEL} median: {base:.3} ms -> {laid_out:.3} ms ({:+.1}%)",
+        100.0 * (laid_out - base) / base.max(f64::MIN_POSITIVE)
+    );
+    Ok(())
+}
+
+/// Parser token kinds of every `.lani` file under `dirs`: kept CPU-lexer
+/// tokens with `(` and `[` retagged as the GPU parser does.
+fn sample_kinds(dirs: &[PathBuf]) -> Result<Vec<Vec<u32>>> {
+    let mut files = Vec::new();
+    for dir in dirs {
+        collect_lani_files(dir, &mut files)
+            .with_context(|| format!("read samples under {}", dir.display()))?;
+    }
+    files.sort();
+    let mut samples = Vec::new();
+    for path in files {
+        let source = fs::read_to_string(&path)?;
+        match lex_on_test_cpu(&source) {
+            Ok(tokens) if !tokens.is_empty() => samples.push(parser_kinds(&tokens)),
+            Ok(_) => {}
+            Err(err) => log::warn!("skipping {}: {err}", path.display()),
+        }
+    }
+    ensure!(!samples.is_empty(), "no .lani samples under {dirs:?}");
+    Ok(samples)
+}
+
+fn parser_kinds(tokens: &[Token]) -> Vec<u32> {
+    let kinds: Vec<TokenKind> = tokens.iter().map(|token| token.kind).collect();
+    retag_open_delimiters(&kinds)
+        .into_iter()
+        .map(|kind| kind as u32)
+        .collect()
+}
+
+fn collect_lani_files(dir: &Path, out: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_lani_files(&path, out)?;
+        } else if path.extension().is_some_and(|ext| ext == "lani") {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Whole samples back to back, cycling until at least `len` kinds.
+fn repeat_to_len(samples: &[Vec<u32>], len: usize) -> Vec<u32> {
+    let mut stream = Vec::with_capacity(len);
+    for sample in samples.iter().cycle() {
+        if stream.len() >= len {
+            break;
+        }
+        stream.extend_from_slice(sample);
+    }
+    stream
+}
+
+/// GPU time of the `pack_varlen` pass in one timed parse.
+fn pack_ms(result: &ParseResult) -> Result<f64> {
+    let timings = result
+        .timings
+        .as_ref()
+        .context("the parser returned no pass timings; timestamp queries are unsupported")?;
+    let ns: f64 = timings
+        .passes
+        .iter()
+        .filter(|pass| pass.label == PACK_LABEL)
+        .map(|pass| pass.gpu_ns)
+        .sum();
+    Ok(ns / 1e6)
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(f64::total_cmp);
+    values[values.len() / 2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_repeats_whole_samples_past_the_target() {
+        let samples = vec![vec![1, 2, 3], vec![4]];
+        assert_eq!(repeat_to_len(&samples, 5), [1, 2, 3, 4, 1, 2, 3]);
+        assert_eq!(repeat_to_len(&samples, 3), [1, 2, 3]);
+    }
+
+    #[test]
+    fn default_samples_lex_and_train_a_valid_layout() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let dirs: Vec<_> = DEFAULT_SAMPLE_DIRS
+            .iter()
+            .map(|dir| root.join(dir))
+            .collect();
+        let samples = sample_kinds(&dirs).expect("default samples");
+        let mut tables = PrecomputedParseTables::load_bin_bytes(
+            &fs::read(root.join("tables/parse_tables.bin")).expect("read tables"),
+        )
+        .expect("load tables");
+        let report = tables.optimize_layout(&samples);
+        assert!(report.inline_pairs > 0, "{report}");
+        assert_eq!(tables.validate(), Ok(()));
+    }
+}
@@ -0,0 +1,809 @@
+// Times lex and parse over every combination of the pipeline options listed
+// in a JSON config, the benchmark to run before merging changes that touch
+// performance.
+//
+// Each cell generates its input as `lex_perf` does, runs `warmup` untimed and
+// `reps` timed lex-then-parse rounds, and checks its output three ways:
+// every rep must match the first, every cell must match the first cell of the
+// same input, and that input's reference in `reference_dir` (shared with
+// `lex_perf`, keyed `seed{seed}-len{bytes}`) must match when one exists. One
+// JSON line per cell goes to `--out`; a table of the cells and the
+// geometric-mean throughput per value of each varied dimension follows on
+// stdout. Any mismatch fails the run. From the repository root:
+//
+//     cargo run --release -p laniusc-tools --bin perf_matrix -- \
+//         --config perf_matrix.json [--out FILE] [--record-reference DIR]
+//
+// Every config key is optional; `lexer`, `parser`, and `lex` take the fields
+// of `LexerRuntimeOptions`, `ParserOptions`, and `LexOptions`:
+//
+//     {"sizes": [1048576, 10000000], "seeds": [42], "warmup": 1, "reps": 10,
+//      "parse": true, "lex": {"report": false},
+//      "matrix": {"readback": ["full", "count_only"],
+//                 "packing": ["wide", "narrow"], "compress": [null, true],
+//                 "pipeline": ["split", "late_kept_filter", "fused_pair_seed"],
+//                 "submission": ["immediate", {"yielding": {"chunk_passes": 8}}],
+//                 "profile": ["default", "highlight"],
+//                 "chunk_tokens": [null, 65536]}}
+//
+// There is no allocation-strategy switch, and block widths are shader
+// constants, so neither is a dimension.
+
+use std::{
+    env,
+    fs,
+    hint::black_box,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::{Context, Result, anyhow, bail, ensure};
+use laniusc_compiler::{
+    dev::generator::gen_valid_source,
+    lexer::{
+        highlight::highlight_spans,
+        kind_packing::KindPacking,
+        types::{LexPipeline, SubmissionPolicy},
+        verify::{Reference, Verdict, verify_against, write_reference},
+    },
+    parser::autotune::ChunkSize,
+    prelude::*,
+};
+use rand::{SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_OUT: &str = "target/perf-matrix.jsonl";
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+/// The `--config` file.
+struct Config {
+    /// Generated input sizes, in bytes.
+    sizes: Vec<usize>,
+    /// Generator seeds; every size is generated from each.
+    seeds: Vec<u64>,
+    /// Untimed rounds before the timed ones of each cell.
+    warmup: usize,
+    /// Timed rounds of each cell.
+    reps: usize,
+    /// Also parse the kept tokens of every full-readback cell.
+    parse: bool,
+    /// Where the known-good references are looked up.
+    reference_dir: PathBuf,
+    /// Precomputed parse tables the parser loads.
+    parse_tables: PathBuf,
+    /// Construction options of every lexer; `packing` sets
+    /// `wide_token_kinds`.
+    lexer: LexerRuntimeOptions,
+    /// Construction options of the parser.
+    parser: ParserOptions,
+    /// Per-call options of the `default` profile.
+    lex: LexOptions,
+    matrix: Matrix,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            sizes: vec![1 << 20],
+            seeds: vec![42],
+            warmup: 1,
+            reps: 10,
+            parse: true,
+            reference_dir: PathBuf::from("target/lex-perf-reference"),
+            parse_tables: PathBuf::from("tables/parse_tables.bin"),
+            lexer: LexerRuntimeOptions::default(),
+            parser: ParserOptions::default(),
+            lex: LexOptions::default(),
+            matrix: Matrix::default(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+/// Values of each dimension; the cells are their cartesian product with the
+/// sizes and seeds.
+struct Matrix {
+    readback: Vec<ReadbackMode>,
+    packing: Vec<KindPacking>,
+    /// `null` keeps the profile's `compress_readback`.
+    compress: Vec<Option<bool>>,
+    pipeline: Vec<LexPipeline>,
+    submission: Vec<SubmissionPolicy>,
+    profile: Vec<Profile>,
+    /// `null` parses in one shot; a number parses in chunks of that many
+    /// token kinds.
+    chunk_tokens: Vec<Option<u32>>,
+}
+
+impl Default for Matrix {
+    fn default() -> Self {
+        Self {
+            readback: vec![ReadbackMode::Full],
+            packing: vec![KindPacking::default()],
+            compress: vec![None],
+            pipeline: vec![LexPipeline::default()],
+            submission: vec![SubmissionPolicy::default()],
+            profile: vec![Profile::default()],
+            chunk_tokens: vec![None],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// Which per-call lex options a cell starts from.
+enum Profile {
+    /// The config's `lex` options.
+    #[default]
+    Default,
+    /// `LexOptions::highlight`, timed through the scope assignment of the
+    /// ALL stream.
+    Highlight,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+/// One combination of input and options.
+struct Cell {
+    bytes: usize,
+    seed: u64,
+    readback: ReadbackMode,
+    packing: KindPacking,
+    compress: Option<bool>,
+    pipeline: LexPipeline,
+    submission: SubmissionPolicy,
+    profile: Profile,
+    chunk_tokens: Option<u32>,
+}
+
+impl Cell {
+    /// Reference key of this cell's input, as `lex_perf` names it.
+    fn key(&self) -> String {
+        format!("seed{}-len{}", self.seed, self.bytes)
+    }
+
+    fn lex_options(&self, base: LexOptions) -> LexOptions {
+        let profile = match self.profile {
+            Profile::Default => base,
+            Profile::Highlight => LexOptions::highlight(),
+        };
+        LexOptions {
+            readback: self.readback,
+            compress_readback: self.compress.unwrap_or(profile.compress_readback),
+            ..profile
+        }
+    }
+
+    /// Each dimension's name and this cell's value of it.
+    fn dimensions(&self) -> [(&'static str, String); 9] {
+        [
+            ("bytes", self.bytes.to_string()),
+            ("seed", self.seed.to_string()),
+            ("readback", label(&self.readback)),
+            ("packing", label(&self.packing)),
+            (
+                "compress",
+                self.compress
+                    .map_or_else(|| "profile".to_string(), |on| on.to_string()),
+            ),
+            ("pipeline", label(&self.pipeline)),
+            ("submission", label(&self.submission)),
+            ("profile", label(&self.profile)),
+            (
+                "chunk_tokens",
+                self.chunk_tokens
+                    .map_or_else(|| "whole".to_string(), |tokens| tokens.to_string()),
+            ),
+        ]
+    }
+}
+
+/// `value` as it is spelled in the config.
+fn label<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        Ok(other) => other.to_string(),
+        Err(err) => format!("<{err}>"),
+    }
+}
+
+/// Every cell of `config`, sizes outermost so each input is generated once.
+fn cells(config: &Config) -> Vec<Cell> {
+    let matrix = &config.matrix;
+    let cells = vec![Cell::default()];
+    let cells = expand(cells, &config.sizes, |cell, bytes| cell.bytes = bytes);
+    let cells = expand(cells, &config.seeds, |cell, seed| cell.seed = seed);
+    let cells = expand(cells, &matrix.readback, |cell, mode| cell.readback = mode);
+    let cells = expand(cells, &matrix.packing, |cell, packing| {
+        cell.packing = packing
+    });
+    let cells = expand(cells, &matrix.compress, |cell, on| cell.compress = on);
+    let cells = expand(cells, &matrix.pipeline, |cell, pipeline| {
+        cell.pipeline = pipeline
+    });
+    let cells = expand(cells, &matrix.submission, |cell, policy| {
+        cell.submission = policy
+    });
+    let cells = expand(cells, &matrix.profile, |cell, profile| {
+        cell.profile = profile
+    });
+    expand(cells, &matrix.chunk_tokens, |cell, tokens| {
+        cell.chunk_tokens = tokens
+    })
+}
+
+/// `cells` times `values`, each copy with one value set.
+fn expand<T: Copy>(cells: Vec<Cell>, values: &[T], set: impl Fn(&mut Cell, T)) -> Vec<Cell> {
+    let set = &set;
+    cells
+        .into_iter()
+        .flat_map(|cell| {
+            values.iter().map(move |&value| {
+                let mut cell = cell;
+                set(&mut cell, value);
+                cell
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+/// One line of the `--out` file.
+struct CellResult {
+    config: Cell,
+    /// Generated source length.
+    source_bytes: usize,
+    /// Kept tokens; `null` under `none` readback.
+    token_count: Option<usize>,
+    /// Whether each round also parsed.
+    parsed: bool,
+    best_ms: f64,
+    p50_ms: f64,
+    p95_ms: f64,
+    /// Source MiB per second at `best_ms`.
+    best_mibs: f64,
+    /// Source MiB per second at `p50_ms`.
+    p50_mibs: f64,
+    verification: Verification,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Verification {
+    status: Status,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Verified,
+    Unverified,
+    Mismatch,
+}
+
+impl From<Verdict> for Verification {
+    fn from(verdict: Verdict) -> Self {
+        let (status, detail) = match verdict {
+            Verdict::Verified => (Status::Verified, None),
+            Verdict::Unverified(reason) => (Status::Unverified, Some(reason)),
+            Verdict::Mismatch(detail) => (Status::Mismatch, Some(detail)),
+        };
+        Self { status, detail }
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    let _ = laniusc_compiler::logging::init_default();
+    let mut config_path = None;
+    let mut out = PathBuf::from(DEFAULT_OUT);
+    let mut record_dir = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().with_context(|| format!("{flag} needs a value"));
+        match arg.as_str() {
+            "--config" => config_path = Some(PathBuf::from(value("--config")?)),
+            "--out" => out = PathBuf::from(value("--out")?),
+            "--record-reference" => record_dir = Some(PathBuf::from(value("--record-reference")?)),
+            _ => bail!(
+                "unknown argument {arg:?}; expected --config FILE, --out FILE, or --record-reference DIR"
+            ),
+        }
+    }
+    let config_path = config_path.context("--config FILE is required")?;
+    let json = fs::read_to_string(&config_path)
+        .with_context(|| format!("read {}", config_path.display()))?;
+    let config: Config =
+        serde_json::from_str(&json).with_context(|| format!("parse {}", config_path.display()))?;
+
+    let results = run(&config, &out, record_dir.as_deref()).await?;
+    print_summary(&results);
+    println!("[perf_matrix] wrote {}", out.display());
+    let mismatches = results
+        .iter()
+        .filter(|result| result.verification.status == Status::Mismatch)
+        .count();
+    ensure!(
+        mismatches == 0,
+        "{mismatches} of {} cells failed verification",
+        results.len()
+    );
+    Ok(())
+}
+
+/// The lexers and parser every cell shares.
+struct Bench {
+    lexers: Vec<(KindPacking, GpuLexer)>,
+    parser: Option<(GpuParser, PrecomputedParseTables)>,
+}
+
+impl Bench {
+    async fn new(config: &Config) -> Result<Self> {
+        let mut lexers = Vec::new();
+        for &packing in &config.matrix.packing {
+            if lexers.iter().any(|(built, _)| *built == packing) {
+                continue;
+            }
+            let options = LexerRuntimeOptions {
+                wide_token_kinds: packing == KindPacking::Wide,
+                ..config.lexer
+            };
+            if options.kind_packing() != packing {
+                log::warn!(
+                    "the token kinds do not fit {} packing; its cells run {}",
+                    label(&packing),
+                    label(&options.kind_packing())
+                );
+            }
+            lexers.push((packing, GpuLexer::new_with(options).await?));
+        }
+        let parser = if config.parse {
+            let path = &config.parse_tables;
+            let bytes = fs::read(path).with_context(|| format!("read {}", path.display()))?;
+            let tables = PrecomputedParseTables::load_bin_bytes(&bytes)
+                .map_err(|err| anyhow!("load {}: {err}", path.display()))?;
+            Some((GpuParser::new_with(config.parser).await?, tables))
+        } else {
+            None
+        };
+        Ok(Self { lexers, parser })
+    }
+
+    fn lexer(&self, packing: KindPacking) -> &GpuLexer {
+        let (_, lexer) = self
+            .lexers
+            .iter()
+            .find(|(built, _)| *built == packing)
+            .expect("a lexer is built for every packing in the matrix");
+        lexer
+    }
+}
+
+/// What the rounds of one cell measured and produced.
+struct Measured {
+    ms: Vec<f64>,
+    token_count: Option<usize>,
+    parsed: bool,
+    /// The first round's output; `None` unless readback is full.
+    reference: Option<Reference>,
+    /// The first round that disagreed with the first one.
+    rep_mismatch: Option<String>,
+}
+
+/// Runs every cell of `config`, appending its line to `out` as it finishes.
+async fn run(config: &Config, out: &Path, record_dir: Option<&Path>) -> Result<Vec<CellResult>> {
+    ensure!(config.reps > 0, "reps must be positive");
+    let cells = cells(config);
+    ensure!(
+        !cells.is_empty(),
+        "the matrix is empty; every size, seed, and dimension list needs a value"
+    );
+    if let Some(parent) = out.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    let mut writer =
+        BufWriter::new(fs::File::create(out).with_context(|| format!("create {}", out.display()))?);
+    let bench = Bench::new(config).await?;
+    println!(
+        "[perf_matrix] {} cells, {} warmup + {} timed rounds each",
+        cells.len(),
+        config.warmup,
+        config.reps
+    );
+
+    let mut source: Option<((usize, u64), String)> = None;
+    let mut baselines: Vec<(String, Reference)> = Vec::new();
+    let mut results = Vec::with_capacity(cells.len());
+    for cell in cells {
+        let input = (cell.bytes, cell.seed);
+        if source.as_ref().is_none_or(|(cached, _)| *cached != input) {
+            let text = gen_valid_source(&mut StdRng::seed_from_u64(cell.seed), cell.bytes);
+            source = Some((input, text));
+        }
+        let (_, text) = source.as_ref().expect("generated above");
+        let measured = measure(&bench, config, &cell, text)
+            .await
+            .with_context(|| format!("cell {}", describe(&cell)))?;
+        let verdict = check(
+            &cell,
+            &measured,
+            &mut baselines,
+            record_dir,
+            &config.reference_dir,
+        )?;
+
+        let mut sorted = measured.ms;
+        sorted.sort_by(f64::total_cmp);
+        let (best, p50, p95) = (
+            sorted[0],
+            percentile(&sorted, 0.50),
+            percentile(&sorted, 0.95),
+        );
+        let result = CellResult {
+            config: cell,
+            source_bytes: text.len(),
+            token_count: measured.token_count,
+            parsed: measured.parsed,
+            best_ms: best,
+            p50_ms: p50,
+            p95_ms: p95,
+            best_mibs: throughput_mibs(text.len(), best),
+            p50_mibs: throughput_mibs(text.len(), p50),
+            verification: verdict.into(),
+        };
+        serde_json::to_writer(&mut writer, &result)?;
+        writeln!(writer)?;
+        writer.flush()?;
+        results.push(result);
+    }
+    Ok(results)
+}
+
+async fn measure(bench: &Bench, config: &Config, cell: &Cell, source: &str) -> Result<Measured> {
+    let lexer = bench.lexer(cell.packing);
+    lexer.set_pipeline(cell.pipeline);
+    lexer.set_submission_policy(cell.submission);
+    let options = cell.lex_options(config.lex);
+    let parser = bench
+        .parser
+        .as_ref()
+        .filter(|_| cell.readback == ReadbackMode::Full);
+
+    let mut measured = Measured {
+        ms: Vec::with_capacity(config.reps),
+        token_count: None,
+        parsed: parser.is_some(),
+        reference: None,
+        rep_mismatch: None,
+    };
+    for round in 0..config.warmup + config.reps {
+        let started = Instant::now();
+        let output = lexer.lex_with_options(source, options).await?;
+        if cell.profile == Profile::Highlight {
+            black_box(highlight_spans(&output.all_tokens));
+        }
+        let emit = match parser {
+            Some((parser, tables)) => Some(
+                parse(parser, tables, &output.tokens, cell.chunk_tokens)
+                    .await?
+                    .emit_stream,
+            ),
+            None => None,
+        };
+        let ms = started.elapsed().as_secs_f64() * 1e3;
+        if round >= config.warmup {
+            measured.ms.push(ms);
+        }
+
+        let token_count = (cell.readback != ReadbackMode::None).then_some(output.token_count);
+        let reference = (cell.readback == ReadbackMode::Full).then(|| {
+            let reference = Reference::for_tokens(source, &output.tokens);
+            match &emit {
+                Some(emit) => reference.with_emit(emit),
+                None => reference,
+            }
+        });
+        if round == 0 {
+            measured.token_count = token_count;
+            measured.reference = reference;
+        } else if measured.rep_mismatch.is_none()
+            && (token_count != measured.token_count || reference != measured.reference)
+        {
+            measured.rep_mismatch = Some(format!("round {round} differs from round 0"));
+        }
+    }
+    Ok(measured)
+}
+
+/// Parses kept tokens in one shot, or in chunks of `chunk_tokens` kinds.
+async fn parse(
+    parser: &GpuParser,
+    tables: &PrecomputedParseTables,
+    tokens: &[Token],
+    chunk_tokens: Option<u32>,
+) -> Result<ParseResult> {
+    let kinds: Vec<u32> = tokens.iter().map(|token| token.kind as u32).collect();
+    match chunk_tokens {
+        None => parser.parse_tokens(&kinds, tables).await,
+        Some(tokens) => {
+            let framed = tables.wrap_input(&kinds)?;
+            let classified =
+                parser.debug_semantic_token_kinds_for_raw_token_kinds(&framed, tables)?;
+            parser
+                .parse_chunked_sized(&classified, ChunkSize::Tokens(tokens), tables)
+                .await
+        }
+    }
+}
+
+/// Checks `measured` against its own rounds, the first cell of the same
+/// input, and the reference file, recording the reference first when
+/// `record_dir` is set.
+///
+/// The first full-readback cell of an input becomes its baseline, upgraded
+/// once by the first cell that also parsed.
+fn check(
+    cell: &Cell,
+    measured: &Measured,
+    baselines: &mut Vec<(String, Reference)>,
+    record_dir: Option<&Path>,
+    reference_dir: &Path,
+) -> Result<Verdict> {
+    if let Some(detail) = &measured.rep_mismatch {
+        return Ok(Verdict::Mismatch(detail.clone()));
+    }
+    let key = cell.key();
+    let baseline = baselines
+        .iter_mut()
+        .find(|(known, _)| *known == key)
+        .map(|(_, reference)| reference);
+    let Some(actual) = measured.reference else {
+        // Without the tokens only the count can be compared.
+        if let (Some(baseline), Some(count)) = (&baseline, measured.token_count)
+            && baseline.token_count != count
+        {
+            return Ok(Verdict::Mismatch(format!(
+                "token count {count} (expected {})",
+                baseline.token_count
+            )));
+        }
+        return Ok(Verdict::Unverified(format!(
+            "readback is {}",
+            label(&cell.readback)
+        )));
+    };
+    let record = match baseline {
+        Some(baseline) => {
+            if let Verdict::Mismatch(detail) = baseline.verify(&actual) {
+                return Ok(Verdict::Mismatch(format!(
+                    "differs from an earlier cell: {detail}"
+                )));
+            }
+            let upgrade = baseline.parse_hash.is_none() && actual.parse_hash.is_some();
+            if upgrade {
+                *baseline = actual;
+            }
+            upgrade
+        }
+        None => {
+            baselines.push((key.clone(), actual));
+            true
+        }
+    };
+    if record && let Some(dir) = record_dir {
+        let path = write_reference(dir, &key, &actual)?;
+        println!("[perf_matrix] recorded {}", path.display());
+    }
+    Ok(
+        verify_against(record_dir.unwrap_or(reference_dir), &key, &actual)
+            .unwrap_or_else(|err| Verdict::Unverified(format!("{err:#}"))),
+    )
+}
+
+fn percentile(sorted_ms: &[f64], p: f64) -> f64 {
+    if sorted_ms.is_empty() {
+        return 0.0;
+    }
+    let idx = (p.clamp(0.0, 1.0) * (sorted_ms.len() as f64 - 1.0)).round() as usize;
+    sorted_ms[idx]
+}
+
+fn throughput_mibs(bytes: usize, ms: f64) -> f64 {
+    if ms <= 0.0 {
+        return 0.0;
+    }
+    (bytes as f64) / (1024.0 * 1024.0) / (ms / 1_000.0)
+}
+
+/// The cell's dimension values, space separated.
+fn describe(cell: &Cell) -> String {
+    cell.dimensions()
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Per dimension that takes more than one value: each value with its cell
+/// count and the geometric mean of those cells' p50 throughput.
+fn aggregate(results: &[CellResult]) -> Vec<(&'static str, Vec<(String, usize, f64)>)> {
+    let Some(first) = results.first() else {
+        return Vec::new();
+    };
+    let mut dimensions = Vec::new();
+    for (index, (name, _)) in first.config.dimensions().into_iter().enumerate() {
+        // (value, cells, sum of ln throughput), in first-seen order.
+        let mut values: Vec<(String, usize, f64)> = Vec::new();
+        for result in results {
+            let (_, value) = &result.config.dimensions()[index];
+            let ln = result.p50_mibs.max(f64::MIN_POSITIVE).ln();
+            match values.iter_mut().find(|(known, ..)| known == value) {
+                Some((_, cells, sum)) => {
+                    *cells += 1;
+                    *sum += ln;
+                }
+                None => values.push((value.clone(), 1, ln)),
+            }
+        }
+        if values.len() > 1 {
+            for (_, cells, sum) in &mut values {
+                *sum = (*sum / *cells as f64).exp();
+            }
+            dimensions.push((name, values));
+        }
+    }
+    dimensions
+}
+
+fn print_summary(results: &[CellResult]) {
+    println!(
+        "[perf_matrix] {:>10} {:>10} {:>10} {:>10}  {:<10}  cell",
+        "best ms", "p50 ms", "p95 ms", "p50 MiB/s", "verify"
+    );
+    for result in results {
+        println!(
+            "[perf_matrix] {:>10.3} {:>10.3} {:>10.3} {:>10.1}  {:<10}  {}",
+            result.best_ms,
+            result.p50_ms,
+            result.p95_ms,
+            result.p50_mibs,
+            label(&result.verification.status),
+            describe(&result.config)
+        );
+    }
+    for (name, values) in aggregate(results) {
+        let values: Vec<String> = values
+            .iter()
+            .map(|(value, cells, mibs)| format!("{value} {mibs:.1} MiB/s ({cells} cells)"))
+            .collect();
+        println!("[perf_matrix] {name}: {}", values.join(" | "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(json: &str) -> Config {
+        serde_json::from_str(json).expect("config parses")
+    }
+
+    #[test]
+    fn cells_are_the_product_of_every_dimension() {
+        let config = config(
+            r#"{"sizes": [10, 20], "seeds": [1, 2, 3],
+                "matrix": {"readback": ["full", "none"],
+                           "submission": ["immediate", {"batched": {"max_encoders": 2}}]}}"#,
+        );
+        let cells = cells(&config);
+        assert_eq!(cells.len(), 2 * 3 * 2 * 2);
+        assert_eq!((cells[0].bytes, cells[0].seed), (10, 1));
+        assert_eq!(
+            cells[1].submission,
+            SubmissionPolicy::Batched { max_encoders: 2 }
+        );
+        assert!(cells[..12].iter().all(|cell| cell.bytes == 10));
+        assert_eq!(cells[0].key(), "seed1-len10");
+        assert_eq!(
+            describe(&cells[1]),
+            "bytes=10 seed=1 readback=full packing=wide compress=profile pipeline=split \
+             submission={\"batched\":{\"max_encoders\":2}} profile=default chunk_tokens=whole"
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let err =
+            serde_json::from_str::<Config>(r#"{"matrix": {"block_width": [256]}}"#).unwrap_err();
+        assert!(err.to_string().contains("block_width"), "{err}");
+    }
+
+    #[test]
+    fn compress_override_keeps_the_profile_otherwise() {
+        let cell = Cell {
+            profile: Profile::Highlight,
+            ..Cell::default()
+        };
+        assert!(cell.lex_options(LexOptions::default()).compress_readback);
+        let off = Cell {
+            compress: Some(false),
+            readback: ReadbackMode::CountOnly,
+            ..cell
+        };
+        let options = off.lex_options(LexOptions::default());
+        assert!(!options.compress_readback && options.all_tokens);
+        assert_eq!(options.readback, ReadbackMode::CountOnly);
+    }
+
+    #[test]
+    fn aggregation_takes_the_geometric_mean_of_varied_dimensions() {
+        let result = |readback, p50_mibs| CellResult {
+            config: Cell {
+                readback,
+                ..Cell::default()
+            },
+            source_bytes: 1,
+            token_count: None,
+            parsed: false,
+            best_ms: 1.0,
+            p50_ms: 1.0,
+            p95_ms: 1.0,
+            best_mibs: p50_mibs,
+            p50_mibs,
+            verification: Verdict::Verified.into(),
+        };
+        let results = [
+            result(ReadbackMode::Full, 2.0),
+            result(ReadbackMode::Full, 8.0),
+            result(ReadbackMode::None, 5.0),
+        ];
+        let aggregated = aggregate(&results);
+        assert_eq!(aggregated.len(), 1);
+        let (name, values) = &aggregated[0];
+        assert_eq!(*name, "readback");
+        assert_eq!(values[0].0, "full");
+        assert_eq!(values[0].1, 2);
+        assert!((values[0].2 - 4.0).abs() < 1e-9, "{values:?}");
+        assert!((values[1].2 - 5.0).abs() < 1e-9, "{values:?}");
+    }
+
+    #[test]
+    fn smoke_two_by_two_matrix_writes_one_valid_line_per_cell() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let dir = env::temp_dir().join(format!("perf_matrix_smoke_{}", std::process::id()));
+        let mut config = config(
+            r#"{"sizes": [512], "warmup": 0, "reps": 2,
+                "matrix": {"readback": ["full", "count_only"],
+                           "chunk_tokens": [null, 16]}}"#,
+        );
+        config.parse_tables = root.join("tables/parse_tables.bin");
+        config.reference_dir = dir.join("reference");
+        let out = dir.join("results.jsonl");
+
+        let results = pollster::block_on(run(&config, &out, None)).expect("matrix runs");
+        assert_eq!(results.len(), 4);
+        let text = fs::read_to_string(&out).expect("results file");
+        let lines: Vec<CellResult> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("line matches the schema"))
+            .collect();
+        assert_eq!(lines.len(), 4);
+        for line in &lines {
+            assert!(line.best_ms <= line.p50_ms && line.p50_ms <= line.p95_ms);
+            assert!(line.best_mibs >= line.p50_mibs);
+            assert_eq!(line.source_bytes, lines[0].source_bytes);
+            assert!(line.token_count.is_some_and(|count| count > 0));
+            assert_ne!(line.verification.status, Status::Mismatch, "{line:?}");
+            assert_eq!(line.parsed, line.config.readback == ReadbackMode::Full);
+            // No reference was recorded, so nothing is verified against one.
+            assert_eq!(line.verification.status, Status::Unverified);
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
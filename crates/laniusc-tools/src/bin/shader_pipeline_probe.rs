@@ -45,7 +45,7 @@ fn run() -> Result<()> {
         "main",
         &spirv,
         &layout_refs,
-    );
+    )?;
     let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
     drop(pipeline);
 
@@ -56,6 +56,7 @@ fn run() -> Result<()> {
             "spv": spv_path.to_string_lossy(),
             "reflection": reflection_path.to_string_lossy(),
             "spv_bytes": spirv.len(),
+            "shader_path": gpu.shader_path.as_str(),
             "reflected_parameters": reflection.parameters.len(),
             "pipeline_create_ms": elapsed_ms,
         })
@@ -64,6 +65,7 @@ fn run() -> Result<()> {
 }
 
 fn main() -> ExitCode {
+    let _ = laniusc_compiler::logging::init_default();
     match run() {
         Ok(()) => ExitCode::SUCCESS,
         Err(err) => {
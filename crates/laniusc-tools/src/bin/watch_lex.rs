@@ -0,0 +1,100 @@
+//! Watches the shader tree and hot-reloads the GPU lexer whenever a `.slang`
+//! file changes, re-lexing a sample input after each successful reload.
+//!
+//! Usage: `cargo run --features shader-hot-reload --bin watch_lex [input]`
+
+use std::{
+    collections::HashMap,
+    env,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use laniusc_compiler::prelude::*;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const DEFAULT_INPUT: &str = "fn main() {\n    let x = 0x2A + 1;\n    return x; // done\n}\n";
+
+fn slang_mtimes(dir: &Path, out: &mut HashMap<PathBuf, SystemTime>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            slang_mtimes(&path, out);
+        } else if path.extension().is_some_and(|ext| ext == "slang")
+            && let Ok(modified) = entry.metadata().and_then(|m| m.modified())
+        {
+            out.insert(path, modified);
+        }
+    }
+}
+
+fn snapshot(root: &Path) -> HashMap<PathBuf, SystemTime> {
+    let mut out = HashMap::new();
+    slang_mtimes(root, &mut out);
+    out
+}
+
+async fn lex_and_print(lexer: &GpuLexer, text: &str) {
+    let t0 = Instant::now();
+    match lexer.lex(text).await {
+        Ok(tokens) => println!(
+            "Lex:  {} tokens in {:.3} ms",
+            tokens.len(),
+            t0.elapsed().as_secs_f64() * 1e3
+        ),
+        Err(e) => eprintln!("GPU lex failed: {e:?}"),
+    }
+}
+
+fn main() {
+    let _ = laniusc_compiler::logging::init_default();
+    pollster::block_on(async {
+        let text = match env::args().nth(1) {
+            Some(path) => match fs::read_to_string(&path) {
+                Ok(s) => s,
+                Err(e) => {
+                    eprintln!("Failed to read {path}: {e}");
+                    std::process::exit(2);
+                }
+            },
+            None => DEFAULT_INPUT.to_string(),
+        };
+
+        let mut lexer = match GpuLexer::new_with(LexerRuntimeOptions::from_env()).await {
+            Ok(g) => g,
+            Err(e) => {
+                eprintln!("GPU init failed: {e:?}");
+                std::process::exit(1);
+            }
+        };
+        let root = laniusc_compiler::gpu::hot_reload::shader_root();
+        println!("Watching {} (Ctrl-C to stop)", root.display());
+        lex_and_print(&lexer, &text).await;
+
+        let mut seen = snapshot(&root);
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let current = snapshot(&root);
+            if current == seen {
+                continue;
+            }
+            seen = current;
+
+            let t0 = Instant::now();
+            match lexer.reload_shaders() {
+                Ok(report) => {
+                    println!("{report} ({:.0} ms)", t0.elapsed().as_secs_f64() * 1e3);
+                    if report.swapped {
+                        lex_and_print(&lexer, &text).await;
+                    }
+                }
+                Err(e) => eprintln!("Reload unavailable: {e:?}"),
+            }
+        }
+    });
+}
@@ -0,0 +1,26 @@
+//! Lexes a file on the GPU and prints each kept token with its text.
+//!
+//! ```text
+//! cargo run --example lex_file -- path/to/file.lani
+//! ```
+
+use anyhow::{Context, Result};
+use laniusc_compiler::prelude::*;
+
+fn main() -> Result<()> {
+    let path = std::env::args().nth(1).context("usage: lex_file <path>")?;
+    let src = std::fs::read_to_string(&path).with_context(|| format!("read {path}"))?;
+
+    let tokens = pollster::block_on(async {
+        let lexer = GpuLexer::new().await?;
+        lexer.lex(&src).await
+    })?;
+
+    for (token, text) in lexemes(&src, &tokens, LexemePolicy::Lossy) {
+        let text = text.context("token range past the end of the source")?;
+        let kind = format!("{:?}", token.kind);
+        println!("{:>8}  {kind:<16} {text:?}", token.start());
+    }
+    println!("{} tokens", tokens.len());
+    Ok(())
+}
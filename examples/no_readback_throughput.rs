@@ -0,0 +1,58 @@
+//! Lexes one generated input under each `ReadbackMode` and prints the best
+//! time, the lexer's report, and its timing summary, showing what skipping
+//! token readback saves when only GPU-resident tokens are needed.
+//!
+//! ```text
+//! cargo run --release --example no_readback_throughput -- [MiB]
+//! ```
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use laniusc_compiler::prelude::*;
+
+const REPS: usize = 5;
+
+fn main() -> Result<()> {
+    let mib: usize = match std::env::args().nth(1) {
+        Some(arg) => arg.parse().context("size must be a number of MiB")?,
+        None => 8,
+    };
+    let line = "fn f(x: i32) -> i32 { return x * 0x2A + 1; } // note\n";
+    let src = line.repeat((mib << 20).div_ceil(line.len()));
+
+    pollster::block_on(async {
+        let lexer = GpuLexer::new().await?;
+        for readback in [
+            ReadbackMode::Full,
+            ReadbackMode::CountOnly,
+            ReadbackMode::None,
+        ] {
+            let options = LexOptions {
+                readback,
+                report: true,
+                ..LexOptions::default()
+            };
+            // Warm up buffers for this mode before timing it.
+            lexer.lex_with_options(&src, options).await?;
+
+            let mut best = Duration::MAX;
+            let mut last = None;
+            for _ in 0..REPS {
+                let output = lexer.lex_with_options(&src, options).await?;
+                let stats = lexer.last_lex_stats();
+                best = best.min(stats.wall_time);
+                last = Some((output, stats));
+            }
+            let (output, stats) = last.expect("REPS is nonzero");
+            let report = output.report.context("report was requested")?;
+            let best_ms = best.as_secs_f64() * 1e3;
+            println!(
+                "{readback:?}: best {best_ms:.3} ms ({:.1} MiB/s) | tokens read back {} | {stats} | {report}",
+                src.len() as f64 / (1 << 20) as f64 / best.as_secs_f64(),
+                output.tokens.len()
+            );
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,38 @@
+//! Parses one arithmetic expression on the GPU and prints its tree as an
+//! s-expression.
+//!
+//! The parse tables are generated from `examples/expr.bnf` and embedded, so
+//! the example does not need the compiler's own grammar.
+//!
+//! ```text
+//! cargo run --example parse_expr -- "a = b + c * (d - 1)"
+//! ```
+
+use anyhow::{Context, Result, anyhow, bail};
+use laniusc_compiler::prelude::*;
+
+const EXPR_TABLES: &[u8] = include_bytes!("expr_tables.bin");
+
+fn main() -> Result<()> {
+    let expr = std::env::args()
+        .nth(1)
+        .context("usage: parse_expr <expression>")?;
+    let tables = PrecomputedParseTables::load_bin_bytes(EXPR_TABLES)
+        .map_err(|err| anyhow!("load embedded expression tables: {err}"))?;
+
+    let result = pollster::block_on(async {
+        let lexer = GpuLexer::new().await?;
+        let parser = GpuParser::new().await?;
+        let tokens = lexer.lex(&expr).await?;
+        let kinds: Vec<u32> = tokens.iter().map(|token| token.kind as u32).collect();
+        parser.parse_tokens(&kinds, &tables).await
+    })?;
+    if !result.ll1.accepted {
+        bail!("{expr:?}: {}", result.ll1.rejection_message());
+    }
+
+    let mut ast = Ast::from_productions(&tables, &result.emit_stream)?;
+    ast.collapse_precedence_chains();
+    println!("{}", ast.to_sexpr());
+    Ok(())
+}
@@ -0,0 +1,66 @@
+//! Applies synthetic edits to a generated source and relexes only a window
+//! around each edit with `GpuLexer::lex_range`, timing it against a full
+//! relex and checking that both agree on the edited range whenever the
+//! window lex is exact.
+//!
+//! ```text
+//! cargo run --release --example watch_relex -- [edits]
+//! ```
+
+use std::time::Instant;
+
+use anyhow::{Context, Result, bail};
+use laniusc_compiler::prelude::*;
+
+const LINES: usize = 20_000;
+
+fn main() -> Result<()> {
+    let edits: usize = match std::env::args().nth(1) {
+        Some(arg) => arg.parse().context("edits must be a number")?,
+        None => 8,
+    };
+    let mut src: String = (0..LINES)
+        .map(|i| format!("let v{i} = v{} * {i} + 1; // row {i}\n", i / 2))
+        .collect();
+
+    pollster::block_on(async {
+        let lexer = GpuLexer::new().await?;
+        // The first lex pays for buffer allocation; keep it out of the timings.
+        lexer.lex(&src).await?;
+
+        for edit in 0..edits {
+            // Insert a statement at a line start spread through the source.
+            let near = src.len() * (2 * edit + 1) / (2 * edits.max(1));
+            let at = src[..near].rfind('\n').map_or(0, |i| i + 1);
+            let inserted = format!("let edit{edit} = \"typed {edit}\";\n");
+            src.insert_str(at, &inserted);
+            let range = at..at + inserted.len();
+
+            let t0 = Instant::now();
+            let window = lexer.lex_range(&src, range.clone()).await?;
+            let window_ms = t0.elapsed().as_secs_f64() * 1e3;
+
+            let t0 = Instant::now();
+            let full = lexer.lex(&src).await?;
+            let full_ms = t0.elapsed().as_secs_f64() * 1e3;
+
+            let expected: Vec<&Token> = full
+                .iter()
+                .filter(|token| token.start() < range.end && token.end() > range.start)
+                .collect();
+            let relexed: Vec<&Token> = window.tokens.iter().map(|token| &token.token).collect();
+            println!(
+                "edit {edit}: +{} bytes at {at} | window {} {window_ms:.3} ms, {} tokens | full {full_ms:.3} ms, {} tokens",
+                inserted.len(),
+                window.window,
+                relexed.len(),
+                full.len()
+            );
+            if !window.approximate && relexed != expected {
+                bail!("edit {edit}: window relex disagrees with the full relex");
+            }
+        }
+        println!("{edits} edits relexed");
+        Ok(())
+    })
+}
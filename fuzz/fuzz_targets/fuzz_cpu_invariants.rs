@@ -0,0 +1,81 @@
+// CPU-only invariants of the test CPU lexer oracle over arbitrary bytes.
+//
+// Needs no GPU, so coverage-guided fuzzing of the DFA walk runs anywhere:
+//
+//     cargo +nightly fuzz run fuzz_cpu_invariants
+//
+// Every input must lex without panicking, tile into tokens and error spans,
+// and cost one DFA step per byte. Inputs the strict oracle accepts must lex
+// the same way with recovery, in parallel, and from any token boundary, and
+// their kept tokens must survive a respaced re-lex.
+
+#![no_main]
+
+use laniusc_compiler::lexer::{
+    bom::bom_len,
+    roundtrip::{verify_kept_relex, verify_recovered_partition},
+    shebang::shebang_len,
+    test_cpu::{
+        lex_on_test_cpu,
+        lex_on_test_cpu_all,
+        lex_on_test_cpu_parallel,
+        lex_on_test_cpu_recovering,
+    },
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let recovered = lex_on_test_cpu_recovering(data);
+    if let Err(err) = verify_recovered_partition(data, &recovered) {
+        panic!("recovered lex does not tile the input: {err:?}");
+    }
+    assert!(
+        recovered.dfa_steps <= data.len(),
+        "{} DFA steps over {} bytes",
+        recovered.dfa_steps,
+        data.len()
+    );
+
+    // Re-lexing from a token start replays the rest of the walk, unless the
+    // suffix would begin with a prefix only the start of a file may have.
+    if let Some(token) = recovered
+        .all_tokens
+        .get(1 + data.len() % recovered.all_tokens.len().max(1))
+    {
+        let from = token.start();
+        let suffix = &data[from..];
+        if bom_len(suffix) == 0 && shebang_len(suffix).is_none() {
+            let rest = lex_on_test_cpu_recovering(suffix);
+            let expected = recovered.all_tokens.iter().filter(|t| t.start() >= from);
+            assert!(
+                rest.all_tokens
+                    .iter()
+                    .map(|t| (t.kind, t.start() + from, t.len()))
+                    .eq(expected.map(|t| (t.kind, t.start(), t.len()))),
+                "re-lex from byte {from} diverges"
+            );
+        }
+    }
+
+    let Ok(src) = std::str::from_utf8(data) else {
+        return;
+    };
+    let all = lex_on_test_cpu_all(src);
+    assert_eq!(
+        recovered.errors.is_empty(),
+        all.is_ok(),
+        "recovery disagrees with the strict walk: {:?} vs {:?}",
+        recovered.errors,
+        all.err()
+    );
+    let Ok(all) = all else {
+        return;
+    };
+    assert_eq!(recovered.all_tokens, all);
+    let kept = lex_on_test_cpu(src).expect("the strict walk accepted the source");
+    assert_eq!(recovered.kept_tokens(data), kept);
+    assert_eq!(lex_on_test_cpu_parallel(src, 3), Ok(kept.clone()));
+    if let Err(err) = verify_kept_relex(src, &kept) {
+        panic!("kept tokens do not re-lex to themselves: {err:?}");
+    }
+});
@@ -0,0 +1,101 @@
+// Compares the GPU lexer with the recovering test CPU lexer on arbitrary
+// bytes. Needs a GPU adapter: the process-global lexer is created on the
+// first input and reused for the rest of the run.
+//
+//     cargo +nightly fuzz run fuzz_lex_bytes
+//
+// The GPU lexer takes UTF-8 only and has no recovery mode, so inputs that
+// are not UTF-8 or that the oracle has to recover from are checked on the
+// CPU alone. The rest must give the oracle's kept and all-boundary streams
+// on the GPU under every `LexPipeline`, and the GPU all-boundary stream must
+// tile the input. The GPU
+// boundary analysis must match its CPU mirror and the full lex, and the GPU
+// layout facts their CPU mirror.
+
+#![no_main]
+
+use laniusc_compiler::lexer::{
+    LexAnalysis,
+    LexOptions,
+    LexPipeline,
+    Token,
+    driver::try_global_lexer,
+    roundtrip::{verify_partition, verify_recovered_partition},
+    test_cpu::{analyze_on_test_cpu, lex_on_test_cpu_recovering, lex_on_test_cpu_with_options},
+};
+use libfuzzer_sys::fuzz_target;
+
+fn shape(tokens: &[Token]) -> Vec<(u32, usize, usize)> {
+    tokens
+        .iter()
+        .map(|t| (t.kind as u32, t.start(), t.len()))
+        .collect()
+}
+
+fuzz_target!(|data: &[u8]| {
+    let recovered = lex_on_test_cpu_recovering(data);
+    if let Err(err) = verify_recovered_partition(data, &recovered) {
+        panic!("recovered lex does not tile the input: {err:?}");
+    }
+    let Ok(src) = std::str::from_utf8(data) else {
+        return;
+    };
+    if !recovered.errors.is_empty() {
+        return;
+    }
+
+    let options = LexOptions {
+        all_tokens: true,
+        layout_facts: true,
+        ..LexOptions::default()
+    };
+    let expected = lex_on_test_cpu_with_options(src, options)
+        .expect("the oracle lexed this source without errors");
+    assert_eq!(shape(&recovered.kept_tokens(data)), shape(&expected.tokens));
+
+    let lexer =
+        try_global_lexer().unwrap_or_else(|err| panic!("fuzz_lex_bytes needs a GPU: {err}"));
+    let mut output = None;
+    for pipeline in [
+        LexPipeline::LateKeptFilter,
+        LexPipeline::FusedPairSeed,
+        LexPipeline::Split,
+    ] {
+        lexer.set_pipeline(pipeline);
+        let lexed = pollster::block_on(lexer.lex_with_options(src, options))
+            .unwrap_or_else(|err| panic!("{pipeline:?} GPU lex failed: {err:#}"));
+        assert_eq!(
+            shape(&lexed.tokens),
+            shape(&expected.tokens),
+            "{pipeline:?} kept tokens"
+        );
+        assert_eq!(
+            shape(&lexed.all_tokens),
+            shape(&expected.all_tokens),
+            "{pipeline:?} all-boundary tokens"
+        );
+        assert_eq!(
+            lexed.layout_facts, expected.layout_facts,
+            "{pipeline:?} layout facts"
+        );
+        output = Some(lexed);
+    }
+    // The loop ends on the default pipeline, which the analysis runs under.
+    let output = output.expect("at least one pipeline");
+    if let Err(err) = verify_partition(src, &output.all_tokens) {
+        panic!("GPU all-boundary stream does not tile the input: {err:?}");
+    }
+
+    let analysis = pollster::block_on(lexer.analyze(src))
+        .unwrap_or_else(|err| panic!("GPU analysis failed: {err:#}"));
+    assert_eq!(
+        Ok(analysis),
+        analyze_on_test_cpu(src),
+        "CPU analysis mirror"
+    );
+    assert_eq!(
+        analysis,
+        LexAnalysis::from_all_tokens(src, &output.all_tokens),
+        "analysis of the full lex"
+    );
+});
@@ -0,0 +1,90 @@
+// Checks that laid-out parse tables pack the same streams as the checked-in
+// ones, decoding both through the GPU blob on the CPU. Needs no GPU:
+//
+//     cargo +nightly fuzz run fuzz_table_layout
+//
+// The optimized copy is built once, with its hot cells taken from the
+// self-test vector sources. Inputs the lexer oracle accepts are retagged as
+// the GPU parser does, framed with the tables' sentinels, and must give the
+// grids' stack-change and partial-parse streams from both blobs.
+
+#![no_main]
+
+use std::sync::OnceLock;
+
+use laniusc_compiler::{
+    lexer::{Token, test_cpu::lex_on_test_cpu_bytes},
+    parser::{
+        retag::retag_open_delimiters,
+        tables::{ParseTablesGpuBlob, PrecomputedParseTables},
+    },
+    self_test::vectors::SelfTestVectors,
+};
+use libfuzzer_sys::fuzz_target;
+
+struct Layouts {
+    tables: PrecomputedParseTables,
+    blob: ParseTablesGpuBlob,
+    optimized: PrecomputedParseTables,
+    optimized_blob: ParseTablesGpuBlob,
+}
+
+fn parser_kinds(tokens: &[Token]) -> Vec<u32> {
+    let kinds: Vec<_> = tokens.iter().map(|token| token.kind).collect();
+    retag_open_delimiters(&kinds)
+        .into_iter()
+        .map(|kind| kind as u32)
+        .collect()
+}
+
+fn layouts() -> &'static Layouts {
+    static LAYOUTS: OnceLock<Layouts> = OnceLock::new();
+    LAYOUTS.get_or_init(|| {
+        let tables =
+            PrecomputedParseTables::load_bin_bytes(include_bytes!("../../tables/parse_tables.bin"))
+                .expect("load the checked-in parse tables");
+        let samples: Vec<Vec<u32>> = SelfTestVectors::embedded()
+            .checks
+            .iter()
+            .flat_map(|check| &check.sources)
+            .filter_map(|source| lex_on_test_cpu_bytes(source.as_bytes()).ok())
+            .map(|tokens| parser_kinds(&tokens))
+            .collect();
+        let mut optimized = tables.clone();
+        optimized.optimize_layout(&samples);
+        Layouts {
+            blob: tables.to_gpu_blob(),
+            optimized_blob: optimized.to_gpu_blob(),
+            tables,
+            optimized,
+        }
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(tokens) = lex_on_test_cpu_bytes(data) else {
+        return;
+    };
+    let layouts = layouts();
+    let Ok(wrapped) = layouts.tables.wrap_input(&parser_kinds(&tokens)) else {
+        return;
+    };
+    let expected = (
+        layouts.tables.test_cpu_stack_change_stream(&wrapped),
+        layouts.tables.test_cpu_partial_parse_stream(&wrapped),
+    );
+    assert_eq!(
+        layouts
+            .tables
+            .test_cpu_blob_streams(&layouts.blob, &wrapped),
+        expected,
+        "unoptimized blob"
+    );
+    assert_eq!(
+        layouts
+            .optimized
+            .test_cpu_blob_streams(&layouts.optimized_blob, &wrapped),
+        expected,
+        "optimized blob"
+    );
+});
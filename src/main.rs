@@ -1,3 +1,4 @@
 fn main() -> std::process::ExitCode {
+    let _ = laniusc_compiler::logging::init_default();
     laniusc_compiler::cli::run_from_env()
 }
@@ -0,0 +1,32 @@
+mod common;
+
+use std::{path::PathBuf, process::Command};
+
+const SOURCE: &str = "fn main() { let x = 0x2A + 1; return x; }\n";
+
+fn laniusc_bin() -> PathBuf {
+    option_env!("CARGO_BIN_EXE_laniusc")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target/debug/laniusc"))
+}
+
+#[test]
+fn cli_plan_prints_both_plans_without_a_device() {
+    let source = common::TempArtifact::new("laniusc_cli_plan", "source", Some("lani"));
+    source.write_bytes(SOURCE);
+
+    let mut plan = Command::new(laniusc_bin());
+    plan.args(["plan", "--tokens", "64"]).arg(source.path());
+    let output = common::command_output_with_timeout("laniusc plan", &mut plan);
+    common::assert_command_success("laniusc plan", &output);
+
+    let stdout = String::from_utf8(output.stdout).expect("plan output is UTF-8");
+    assert!(
+        stdout.contains(&format!("({} bytes)", SOURCE.len())),
+        "{stdout}"
+    );
+    assert!(stdout.contains("# parser: 64 tokens"), "{stdout}");
+    assert!(stdout.contains("tokens_build"), "{stdout}");
+    assert!(stdout.contains("pack_varlen"), "{stdout}");
+    assert_eq!(stdout.matches("estimated VRAM:").count(), 2, "{stdout}");
+}
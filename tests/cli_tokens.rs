@@ -0,0 +1,77 @@
+mod common;
+
+use std::{path::PathBuf, process::Command};
+
+use laniusc_compiler::lexer::{
+    test_cpu::lex_on_test_cpu,
+    tokens_io::{read_tokens, source_hash},
+};
+
+const SOURCE: &str = "fn main() { let x = 0x2A + 1; return x; } // done\n";
+
+fn laniusc_bin() -> PathBuf {
+    option_env!("CARGO_BIN_EXE_laniusc")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target/debug/laniusc"))
+}
+
+#[test]
+fn cli_lex_bin_output_round_trips_through_tokens_dump() {
+    let source = common::TempArtifact::new("laniusc_cli_tokens", "source", Some("lani"));
+    source.write_bytes(SOURCE);
+    let tokens = common::TempArtifact::new("laniusc_cli_tokens", "out", Some("toks"));
+
+    let mut lex = Command::new(laniusc_bin());
+    lex.args(["lex", "--format", "bin", "-o"])
+        .arg(tokens.path())
+        .arg(source.path());
+    let output = common::command_output_with_timeout("laniusc lex --format bin", &mut lex);
+    common::assert_command_success("laniusc lex --format bin", &output);
+
+    let bytes = std::fs::read(tokens.path()).expect("read token file");
+    let file = read_tokens(&bytes).expect("parse token file");
+    assert_eq!(file.source_hash, Some(source_hash(SOURCE)));
+    let expected = lex_on_test_cpu(SOURCE).expect("CPU lex");
+    assert_eq!(
+        file.tokens
+            .iter()
+            .map(|t| (t.kind, t.start(), t.len()))
+            .collect::<Vec<_>>(),
+        expected
+            .iter()
+            .map(|t| (t.kind, t.start(), t.len()))
+            .collect::<Vec<_>>()
+    );
+
+    let mut dump = Command::new(laniusc_bin());
+    dump.arg("tokens-dump").arg(tokens.path());
+    let output = common::command_output_with_timeout("laniusc tokens-dump", &mut dump);
+    common::assert_command_success("laniusc tokens-dump", &output);
+    let listing = String::from_utf8(output.stdout).expect("UTF-8 listing");
+    let mut text_lex = Command::new(laniusc_bin());
+    text_lex.arg("lex").arg(source.path());
+    let output = common::command_output_with_timeout("laniusc lex", &mut text_lex);
+    common::assert_command_success("laniusc lex", &output);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), listing);
+    assert!(listing.starts_with(&format!("# {} tokens, source fnv1a64:", expected.len())));
+    assert!(listing.contains("\tFn\t0\t2\n"), "{listing}");
+}
+
+#[test]
+fn cli_tokens_dump_rejects_truncated_files() {
+    let tokens = common::TempArtifact::new("laniusc_cli_tokens", "truncated", Some("toks"));
+    let mut bytes = b"LXTOKS01".to_vec();
+    bytes.extend_from_slice(&1000u32.to_le_bytes());
+    bytes.extend_from_slice(&[0; 12]);
+    tokens.write_bytes(&bytes);
+
+    let mut dump = Command::new(laniusc_bin());
+    dump.arg("tokens-dump").arg(tokens.path());
+    let output = common::command_output_with_timeout("laniusc tokens-dump", &mut dump);
+    assert!(!output.status.success(), "truncated file should fail");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("declares 1000 tokens but holds only 12 record bytes"),
+        "{stderr}"
+    );
+}
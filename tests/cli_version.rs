@@ -128,6 +128,7 @@ fn cli_version_reports_distribution_contract_without_compiling_source() {
         "wgpu",
         "build-profile",
         "shader-artifact-digest",
+        "shader-artifact-mode",
         "shader-artifact-count",
         "shader-artifact-max-bytes",
         "shader-artifact-max-name",
@@ -156,6 +157,13 @@ fn cli_version_reports_distribution_contract_without_compiling_source() {
             .is_ok_and(|bytes| bytes > 0),
         "--version should publish the largest active shader artifact size\nstdout:\n{stdout}"
     );
+    assert!(
+        matches!(
+            fields["shader-artifact-mode"].as_str(),
+            "compiled" | "vendored"
+        ),
+        "--version should say whether shaders were compiled or vendored\nstdout:\n{stdout}"
+    );
     assert!(
         fields["shader-artifact-size-guard"] == "enforced"
             || fields["shader-artifact-size-guard"] == "disabled",
@@ -309,6 +317,13 @@ fn cli_doctor_reports_no_run_toolchain_contract_without_compiling_source() {
             .is_some_and(|digest| digest != "unknown" && !digest.trim().is_empty()),
         "doctor should publish shader artifact digest metadata\nstdout:\n{stdout}"
     );
+    assert!(
+        matches!(
+            shader_artifacts["mode"].as_str(),
+            Some("compiled") | Some("vendored")
+        ),
+        "doctor should say whether shaders were compiled or vendored\nstdout:\n{stdout}"
+    );
     assert!(
         shader_artifacts["count"]
             .as_u64()
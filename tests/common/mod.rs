@@ -1,6 +1,8 @@
 #![allow(dead_code)]
 
+pub mod parse_tables;
 pub mod sample_programs;
+pub mod tokens;
 
 use std::{
     collections::HashMap,
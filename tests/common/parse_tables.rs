@@ -0,0 +1,15 @@
+use laniusc_compiler::{
+    lexer::tables::tokens::N_KINDS,
+    parser::tables::{PrecomputedParseTables, build_mvp_precomputed_tables},
+};
+
+/// Bracket tables whose partial parse emits the kind of each non-sentinel token.
+pub fn echo_bracket_tables() -> PrecomputedParseTables {
+    let mut tables = build_mvp_precomputed_tables(N_KINDS, vec![0]);
+    for prev in 0..N_KINDS {
+        for this in 1..N_KINDS {
+            tables.set_pp_for_pair(prev, this, &[this]);
+        }
+    }
+    tables
+}
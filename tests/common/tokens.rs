@@ -0,0 +1,9 @@
+use laniusc_compiler::lexer::{Token, tables::tokens::TokenKind};
+
+/// `(kind, start, len)` of each token, for comparing streams in assertions.
+pub fn shapes(tokens: &[Token]) -> Vec<(TokenKind, usize, usize)> {
+    tokens
+        .iter()
+        .map(|token| (token.kind, token.start(), token.len()))
+        .collect()
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use std::{path::PathBuf, process::Command};
+
+use laniusc_compiler::{
+    lexer::GpuLexer,
+    parser::{GpuParser, ast::Ast, tables::PrecomputedParseTables},
+};
+
+const SKIP_ONLY_SOURCES: &[&str] = &[
+    "",
+    "   \n\t\r\n",
+    "// only a comment",
+    "/* block */ // line\n",
+];
+
+fn laniusc_bin() -> PathBuf {
+    option_env!("CARGO_BIN_EXE_laniusc")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target/debug/laniusc"))
+}
+
+fn production_tables() -> PrecomputedParseTables {
+    PrecomputedParseTables::load_bin_bytes(include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tables/parse_tables.bin"
+    )))
+    .expect("load parse tables")
+}
+
+#[test]
+fn skip_only_sources_parse_to_the_empty_program() {
+    common::block_on_gpu_with_timeout("empty source parse", async move {
+        let tables = production_tables();
+        assert!(tables.allows_empty_input);
+        let empty = Ast::empty_program(&tables).expect("empty program");
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let parser = GpuParser::new().await.expect("create GPU parser");
+
+        for &source in SKIP_ONLY_SOURCES {
+            let tokens = lexer.lex(source).await.expect("GPU lex");
+            assert!(tokens.is_empty(), "{source:?}");
+            let result = parser.parse_tokens(&[], &tables).await.expect("GPU parse");
+            assert!(result.brackets.valid, "{source:?}");
+            let ast = Ast::from_productions(&tables, &result.emit_stream)
+                .unwrap_or_else(|err| panic!("{source:?}: {err}"));
+            assert_eq!(ast.root, empty.root, "{source:?}");
+        }
+    });
+}
+
+#[test]
+fn skip_only_sources_type_check() {
+    for &source in SKIP_ONLY_SOURCES {
+        common::type_check_source_with_timeout(source)
+            .unwrap_or_else(|err| panic!("{source:?} should type check: {err:?}"));
+    }
+}
+
+#[test]
+fn cli_check_accepts_an_empty_file() {
+    for (stem, source) in [("empty", ""), ("comments", "// nothing yet\n/* later */\n")] {
+        let artifact = common::TempArtifact::new("laniusc_empty_sources", stem, Some("lani"));
+        artifact.write_str(source);
+        let mut command = Command::new(laniusc_bin());
+        command.arg("check").arg(artifact.path());
+        let output = common::command_output_with_timeout("laniusc check empty file", &mut command);
+        common::assert_command_success(format!("laniusc check on {stem} file"), &output);
+    }
+}
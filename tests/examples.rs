@@ -0,0 +1,61 @@
+mod common;
+
+use std::{env, path::PathBuf, process::Command};
+
+/// Built examples live in `target/<profile>/examples`, a sibling of the
+/// `deps` directory holding this test binary.
+fn example_bin(name: &str) -> PathBuf {
+    let exe = env::current_exe().expect("test binary path");
+    let profile_dir = exe
+        .parent()
+        .and_then(|deps| deps.parent())
+        .expect("test binary lives in target/<profile>/deps");
+    profile_dir
+        .join("examples")
+        .join(format!("{name}{}", env::consts::EXE_SUFFIX))
+}
+
+fn run_example(name: &str, args: &[&str]) -> String {
+    let context = format!("example {name}");
+    let mut command = Command::new(example_bin(name));
+    command.args(args);
+    let output = common::command_output_with_timeout(&context, &mut command);
+    common::assert_command_success(&context, &output);
+    common::stdout_utf8(context, output.stdout)
+}
+
+#[test]
+fn lex_file_prints_each_token_with_its_text() {
+    let source = common::TempArtifact::new("laniusc_examples", "lex_file", Some("lani"));
+    source.write_str("fn main() { let x = 0x2A; }\n");
+    let path = source.path().to_str().expect("UTF-8 temp path");
+
+    let stdout = run_example("lex_file", &[path]);
+    assert!(stdout.contains("Fn"), "{stdout}");
+    assert!(stdout.contains("\"0x2A\""), "{stdout}");
+    assert!(stdout.ends_with("11 tokens\n"), "{stdout}");
+}
+
+#[test]
+fn parse_expr_prints_the_collapsed_tree() {
+    let stdout = run_example("parse_expr", &["a = b + c * (d - 1)"]);
+    for node in ["(Assign", "(InfixPlus", "(Star", "(InfixMinus"] {
+        assert!(stdout.contains(node), "missing {node}: {stdout}");
+    }
+}
+
+#[test]
+fn watch_relex_reports_each_edit() {
+    let stdout = run_example("watch_relex", &["2"]);
+    assert!(stdout.contains("edit 0:"), "{stdout}");
+    assert!(stdout.contains("edit 1:"), "{stdout}");
+    assert!(stdout.ends_with("2 edits relexed\n"), "{stdout}");
+}
+
+#[test]
+fn no_readback_throughput_covers_every_readback_mode() {
+    let stdout = run_example("no_readback_throughput", &["1"]);
+    for mode in ["Full:", "CountOnly:", "None:"] {
+        assert!(stdout.contains(mode), "missing {mode}: {stdout}");
+    }
+}
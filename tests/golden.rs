@@ -0,0 +1,500 @@
+//! Golden cases under `tests/cases/**/*.lani`.
+//!
+//! Each case may carry up to three sidecars next to it:
+//!
+//! - `<name>.tokens.json`: the kept tokens of the test CPU oracle, and the
+//!   oracle's error message for sources it rejects. Both lexers are checked
+//!   against it; the GPU lexer only for sources the oracle accepts. It also
+//!   records the language revision it was blessed under; another revision
+//!   fails the case unless `LANIUS_LANGUAGE_REVISION_MISMATCH=warn`.
+//! - `<name>.parse.json`: the GPU parse of the GPU tokens with the checked-in
+//!   `tables/parse_tables.bin`, as the bracket summary, the production name of
+//!   each partial-parse emit, and the source span of each matched stack-change
+//!   pair. The parse is first checked against the host partial-parse oracle.
+//! - `<name>.lints.json`: the [`lints::scan`] findings over the all-boundary
+//!   stream, checked for both lexers when present.
+//!
+//! `GOLDEN_BLESS=1 cargo test --test golden` rewrites the sidecars: token and
+//! lint goldens from the test CPU oracle, parse goldens from the checked GPU
+//! parse. Lint goldens are only added for cases under `lints/`.
+//! Without a GPU adapter the GPU half skips and says why.
+
+mod common;
+
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+use laniusc_compiler::{
+    language::{LANGUAGE_REVISION, RevisionMismatch},
+    lexer::{
+        GpuLexer,
+        LexOptions,
+        LexemePolicy,
+        Token,
+        lints,
+        tables::tokens::TokenKind,
+        test_cpu::{TestCpuLexer, lex_on_test_cpu_all},
+    },
+    parser::{
+        driver::{GpuParser, ParseResult},
+        tables::PrecomputedParseTables,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+const CASES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cases");
+const NO_MATCH: u32 = u32::MAX;
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct TokensGolden {
+    /// Language revision the golden was blessed under.
+    #[serde(
+        rename = "languageRevision",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    language_revision: Option<u32>,
+    tokens: Vec<GoldenToken>,
+    /// Oracle error after `tokens`, which are the tokens lexed before it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct GoldenToken {
+    kind: String,
+    /// Pre-retag DFA kind; absent when it equals `kind`.
+    #[serde(rename = "rawKind", default, skip_serializing_if = "Option::is_none")]
+    raw_kind: Option<String>,
+    text: String,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct LintsGolden {
+    lints: Vec<GoldenLint>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct GoldenLint {
+    kind: String,
+    /// `[start, end)` source bytes of the offending marker.
+    span: [u32; 2],
+    text: String,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ParseGolden {
+    brackets: GoldenBrackets,
+    /// Production name of each partial-parse emit, in stream order.
+    emit: Vec<String>,
+    /// `[start, end)` source bytes from the token of each push to the token of
+    /// its matching pop, ordered by push. A pop in the end-sentinel pair ends
+    /// at the end of the source.
+    regions: Vec<[usize; 2]>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GoldenBrackets {
+    valid: bool,
+    final_depth: i32,
+    min_depth: i32,
+}
+
+struct Case {
+    path: PathBuf,
+    source: String,
+}
+
+impl Case {
+    fn name(&self) -> String {
+        let relative = self.path.strip_prefix(CASES_DIR).unwrap_or(&self.path);
+        relative.display().to_string()
+    }
+
+    fn sidecar(&self, extension: &str) -> PathBuf {
+        self.path.with_extension(extension)
+    }
+
+    /// Whether blessing writes a lint golden for this case.
+    fn wants_lints(&self) -> bool {
+        self.path.starts_with(Path::new(CASES_DIR).join("lints"))
+            || self.sidecar("lints.json").exists()
+    }
+}
+
+fn blessing() -> bool {
+    std::env::var("GOLDEN_BLESS").is_ok_and(|value| value == "1")
+}
+
+fn cases() -> Vec<Case> {
+    fn walk(dir: &Path, out: &mut Vec<PathBuf>) {
+        let entries =
+            std::fs::read_dir(dir).unwrap_or_else(|err| panic!("read {}: {err}", dir.display()));
+        for entry in entries {
+            let path = entry
+                .unwrap_or_else(|err| panic!("{} entry: {err}", dir.display()))
+                .path();
+            if path.is_dir() {
+                walk(&path, out);
+            } else if path.extension().is_some_and(|ext| ext == "lani") {
+                out.push(path);
+            }
+        }
+    }
+
+    let mut paths = Vec::new();
+    walk(Path::new(CASES_DIR), &mut paths);
+    paths.sort();
+    assert!(!paths.is_empty(), "no .lani cases under {CASES_DIR}");
+    paths
+        .into_iter()
+        .map(|path| {
+            let source = std::fs::read_to_string(&path)
+                .unwrap_or_else(|err| panic!("read {}: {err}", path.display()));
+            Case { path, source }
+        })
+        .collect()
+}
+
+fn read_sidecar<T: for<'de> Deserialize<'de>>(path: &Path) -> Option<T> {
+    let text = std::fs::read_to_string(path).ok()?;
+    Some(
+        serde_json::from_str(&text)
+            .unwrap_or_else(|err| panic!("parse golden {}: {err}", path.display())),
+    )
+}
+
+fn write_sidecar<T: Serialize>(path: &Path, golden: &T) {
+    let text = serde_json::to_string_pretty(golden).expect("serialize golden") + "\n";
+    std::fs::write(path, text).unwrap_or_else(|err| panic!("write {}: {err}", path.display()));
+}
+
+fn golden_tokens(source: &str, tokens: &[Token]) -> Vec<GoldenToken> {
+    tokens
+        .iter()
+        .map(|token| GoldenToken {
+            kind: format!("{:?}", token.kind),
+            raw_kind: (token.raw_kind != token.kind).then(|| format!("{:?}", token.raw_kind)),
+            text: token
+                .lexeme(source, LexemePolicy::Strict)
+                .unwrap_or_else(|err| panic!("token text: {err}"))
+                .into_owned(),
+        })
+        .collect()
+}
+
+/// Tokens the test CPU oracle lexes from `source`, up to its first error.
+fn test_cpu_golden(source: &str) -> TokensGolden {
+    let mut tokens = Vec::new();
+    let mut error = None;
+    for token in TestCpuLexer::default().iter(source) {
+        match token {
+            Ok(token) => tokens.push(token),
+            Err(err) => {
+                error = Some(err);
+                break;
+            }
+        }
+    }
+    TokensGolden {
+        language_revision: Some(LANGUAGE_REVISION),
+        tokens: golden_tokens(source, &tokens),
+        error,
+    }
+}
+
+fn golden_lints(source: &str, all_tokens: &[Token]) -> LintsGolden {
+    LintsGolden {
+        lints: lints::scan(all_tokens, source)
+            .into_iter()
+            .map(|lint| GoldenLint {
+                kind: format!("{:?}", lint.kind),
+                span: [lint.span.start, lint.span.end],
+                text: source[lint.span.range()].to_string(),
+            })
+            .collect(),
+    }
+}
+
+/// `None` when `actual` matches `expected`, otherwise the first difference.
+fn diff<T: std::fmt::Debug + PartialEq>(expected: &[T], actual: &[T]) -> Option<String> {
+    let index = expected
+        .iter()
+        .zip(actual)
+        .position(|(expected, actual)| expected != actual)
+        .or_else(|| (expected.len() != actual.len()).then(|| expected.len().min(actual.len())))?;
+    Some(format!(
+        "first difference at #{index} of {} expected / {} actual: expected {:?}, got {:?}",
+        expected.len(),
+        actual.len(),
+        expected.get(index),
+        actual.get(index)
+    ))
+}
+
+fn report(failures: &[String]) {
+    if failures.is_empty() {
+        return;
+    }
+    let mut message = format!("{} golden case(s) failed:\n", failures.len());
+    for failure in failures {
+        let _ = writeln!(message, "  {failure}");
+    }
+    message.push_str("rerun with GOLDEN_BLESS=1 if the change is intended");
+    panic!("{message}");
+}
+
+fn load_tables() -> PrecomputedParseTables {
+    PrecomputedParseTables::load_bin_bytes(include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tables/parse_tables.bin"
+    )))
+    .expect("load precomputed parse tables")
+}
+
+fn parse_golden(
+    source: &str,
+    tokens: &[Token],
+    result: &ParseResult,
+    tables: &PrecomputedParseTables,
+) -> ParseGolden {
+    let span_start = |sc_index: usize| {
+        result
+            .token_of_stack_change(sc_index, tables)
+            .map_or(0, |token| tokens[token].start())
+    };
+    let span_end = |sc_index: usize| {
+        result
+            .token_of_stack_change(sc_index, tables)
+            .map_or(source.len(), |token| tokens[token].end())
+    };
+    let regions = result
+        .brackets
+        .match_for_index
+        .iter()
+        .enumerate()
+        .filter(|&(push, &pop)| pop != NO_MATCH && (pop as usize) > push)
+        .map(|(push, &pop)| [span_start(push), span_end(pop as usize)])
+        .collect();
+    ParseGolden {
+        brackets: GoldenBrackets {
+            valid: result.brackets.valid,
+            final_depth: result.brackets.final_depth,
+            min_depth: result.brackets.min_depth,
+        },
+        emit: result
+            .emit_stream
+            .iter()
+            .map(|&prod| tables.production_name(prod).into_owned())
+            .collect(),
+        regions,
+    }
+}
+
+#[test]
+fn test_cpu_lexer_matches_token_goldens() {
+    let bless = blessing();
+    let mismatch = RevisionMismatch::from_env();
+    let mut failures = Vec::new();
+    for case in cases() {
+        let actual = test_cpu_golden(&case.source);
+        let path = case.sidecar("tokens.json");
+        if bless {
+            write_sidecar(&path, &actual);
+            continue;
+        }
+        let Some(expected) = read_sidecar::<TokensGolden>(&path) else {
+            failures.push(format!("{}: missing {}", case.name(), path.display()));
+            continue;
+        };
+        if let Err(err) = mismatch.check("the token golden", expected.language_revision) {
+            failures.push(format!("{}: {err}", case.name()));
+            continue;
+        }
+        if let Some(diff) = diff(&expected.tokens, &actual.tokens) {
+            failures.push(format!("{}: test CPU tokens: {diff}", case.name()));
+        }
+        if expected.error != actual.error {
+            failures.push(format!(
+                "{}: test CPU error {:?}, expected {:?}",
+                case.name(),
+                actual.error,
+                expected.error
+            ));
+        }
+    }
+    report(&failures);
+}
+
+#[test]
+fn test_cpu_lints_match_lint_goldens() {
+    let bless = blessing();
+    let mut failures = Vec::new();
+    for case in cases().into_iter().filter(Case::wants_lints) {
+        let Ok(all_tokens) = lex_on_test_cpu_all(&case.source) else {
+            failures.push(format!("{}: lint cases must lex", case.name()));
+            continue;
+        };
+        let actual = golden_lints(&case.source, &all_tokens);
+        let path = case.sidecar("lints.json");
+        if bless {
+            write_sidecar(&path, &actual);
+            continue;
+        }
+        let Some(expected) = read_sidecar::<LintsGolden>(&path) else {
+            failures.push(format!("{}: missing {}", case.name(), path.display()));
+            continue;
+        };
+        if let Some(diff) = diff(&expected.lints, &actual.lints) {
+            failures.push(format!("{}: test CPU lints: {diff}", case.name()));
+        }
+    }
+    report(&failures);
+}
+
+#[test]
+fn gpu_lints_match_lint_goldens() {
+    common::block_on_gpu_with_timeout("lint golden cases", async move {
+        let lexer = match GpuLexer::new().await {
+            Ok(lexer) => lexer,
+            Err(err) => {
+                eprintln!("SKIP gpu_lints_match_lint_goldens: no GPU lexer: {err:#}");
+                return;
+            }
+        };
+        let options = LexOptions {
+            all_tokens: true,
+            ..LexOptions::default()
+        };
+        let mut failures = Vec::new();
+        for case in cases() {
+            let Some(expected) = read_sidecar::<LintsGolden>(&case.sidecar("lints.json")) else {
+                continue;
+            };
+            let output = match lexer.lex_with_options(&case.source, options).await {
+                Ok(output) => output,
+                Err(err) => {
+                    failures.push(format!("{}: GPU lex: {err:#}", case.name()));
+                    continue;
+                }
+            };
+            let actual = golden_lints(&case.source, &output.all_tokens);
+            if let Some(diff) = diff(&expected.lints, &actual.lints) {
+                failures.push(format!("{}: GPU lints: {diff}", case.name()));
+            }
+        }
+        report(&failures);
+    });
+}
+
+#[test]
+fn gpu_lex_and_parse_match_goldens() {
+    common::block_on_gpu_with_timeout("golden cases", async move {
+        let lexer = match GpuLexer::new().await {
+            Ok(lexer) => lexer,
+            Err(err) => {
+                eprintln!("SKIP gpu_lex_and_parse_match_goldens: no GPU lexer: {err:#}");
+                return;
+            }
+        };
+        let parser = match GpuParser::new().await {
+            Ok(parser) => parser,
+            Err(err) => {
+                eprintln!("SKIP gpu_lex_and_parse_match_goldens: no GPU parser: {err:#}");
+                return;
+            }
+        };
+        let tables = load_tables();
+        let bless = blessing();
+        let mut failures = Vec::new();
+
+        for case in cases() {
+            let name = case.name();
+            let golden = read_sidecar::<TokensGolden>(&case.sidecar("tokens.json"))
+                .unwrap_or_else(|| test_cpu_golden(&case.source));
+            if let Some(err) = &golden.error {
+                // The GPU lexer has no reject state to report.
+                eprintln!("{name}: GPU lex and parse skipped; the test CPU oracle fails: {err}");
+                continue;
+            }
+
+            let tokens = match lexer.lex(&case.source).await {
+                Ok(tokens) => tokens,
+                Err(err) => {
+                    failures.push(format!("{name}: GPU lex: {err:#}"));
+                    continue;
+                }
+            };
+            if let Some(diff) = diff(&golden.tokens, &golden_tokens(&case.source, &tokens)) {
+                failures.push(format!("{name}: GPU tokens: {diff}"));
+                continue;
+            }
+
+            let mut kinds: Vec<u32> = tokens.iter().map(|t| t.kind as u32).collect();
+            kinds.insert(0, 0);
+            kinds.push(0);
+            let result = match parser.parse(&kinds, &tables).await {
+                Ok(result) => result,
+                Err(err) => {
+                    failures.push(format!("{name}: GPU parse: {err:#}"));
+                    continue;
+                }
+            };
+            let semantic = parser
+                .debug_semantic_token_kinds_for_raw_token_kinds(&kinds, &tables)
+                .expect("classify parser token kinds");
+            let oracle = tables.test_cpu_partial_parse_stream(&semantic);
+            if let Some(diff) = diff(&oracle, &result.emit_stream) {
+                failures.push(format!("{name}: GPU emit stream vs host oracle: {diff}"));
+                continue;
+            }
+
+            let actual = parse_golden(&case.source, &tokens, &result, &tables);
+            let path = case.sidecar("parse.json");
+            if bless {
+                write_sidecar(&path, &actual);
+                continue;
+            }
+            let Some(expected) = read_sidecar::<ParseGolden>(&path) else {
+                eprintln!("{name}: no parse golden; GOLDEN_BLESS=1 writes one");
+                continue;
+            };
+            if expected.brackets != actual.brackets {
+                failures.push(format!(
+                    "{name}: brackets {:?}, expected {:?}",
+                    actual.brackets, expected.brackets
+                ));
+            }
+            if let Some(diff) = diff(&expected.emit, &actual.emit) {
+                failures.push(format!("{name}: emit stream: {diff}"));
+            }
+            if let Some(diff) = diff(&expected.regions, &actual.regions) {
+                failures.push(format!("{name}: matched regions: {diff}"));
+            }
+        }
+        report(&failures);
+    });
+}
+
+#[test]
+fn token_goldens_name_known_kinds() {
+    for case in cases() {
+        let path = case.sidecar("tokens.json");
+        let Some(golden) = read_sidecar::<TokensGolden>(&path) else {
+            continue;
+        };
+        for token in &golden.tokens {
+            for kind in std::iter::once(&token.kind).chain(&token.raw_kind) {
+                assert!(
+                    TokenKind::from_name(kind).is_some(),
+                    "{}: unknown token kind {kind:?}",
+                    path.display()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,31 @@
+#![cfg(feature = "gpu-debug")]
+
+mod common;
+
+use laniusc_compiler::lexer::{GpuLexer, LexPipeline};
+
+#[test]
+fn lexer_debug_captures_match_the_declared_keys() {
+    common::block_on_gpu_with_timeout("lexer debug captures", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        for pipeline in [
+            LexPipeline::Split,
+            LexPipeline::LateKeptFilter,
+            LexPipeline::FusedPairSeed,
+        ] {
+            lexer.set_pipeline(pipeline);
+            let tokens = lexer.lex("let x = 1 + 2; // sum\n").await.expect("lex");
+            let debug = lexer
+                .take_last_debug_output()
+                .expect("map debug captures")
+                .expect("gpu-debug lexes keep their captures");
+
+            let audit = debug.audit();
+            assert!(audit.is_clean(), "{pipeline:?}:\n{audit}");
+            assert!(debug.keys().contains(&"lexer.tokens_out"), "{pipeline:?}");
+            let count = debug.get_u32s("lexer.token_count").expect("token count");
+            assert_eq!(count[0] as usize, tokens.len(), "{pipeline:?}");
+        }
+        lexer.set_pipeline(LexPipeline::Split);
+    });
+}
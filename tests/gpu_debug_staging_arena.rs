@@ -0,0 +1,37 @@
+#![cfg(feature = "gpu-debug")]
+
+mod common;
+
+use laniusc_compiler::{
+    gpu::debug::debug_staging_buffer_count,
+    lexer::{GpuLexer, test_cpu::lex_on_test_cpu},
+};
+
+#[test]
+fn debug_snapshots_of_a_large_lex_share_a_few_staging_chunks() {
+    common::block_on_gpu_with_timeout("gpu-debug staging arena", async move {
+        let source: String = "let value = foo(1, \"s\") + 2; // note\n"
+            .chars()
+            .cycle()
+            .take(10 << 20)
+            .collect();
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+
+        let before = debug_staging_buffer_count();
+        let gpu = lexer.lex(&source).await.expect("GPU lex");
+        let created = debug_staging_buffer_count() - before;
+
+        // One buffer per snapshot would be dozens, counting per-round scans.
+        assert!(created >= 1, "debug snapshots were not captured");
+        assert!(created <= 8, "{created} staging buffers for one lex");
+
+        let expected = lex_on_test_cpu(&source).expect("test CPU oracle");
+        assert_eq!(gpu.len(), expected.len());
+        for (got, want) in gpu.iter().zip(&expected) {
+            assert_eq!(
+                (got.kind, got.start(), got.len()),
+                (want.kind, want.start(), want.len())
+            );
+        }
+    });
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use laniusc_compiler::{
+    gpu::determinism::Determinism,
+    lexer::{GpuLexer, LexOptions, tokens_io::write_tokens},
+    parser::{ParserOptions, driver::GpuParser, tables::PrecomputedParseTables},
+};
+
+const RUNS: usize = 20;
+
+const SOURCE: &str = r#"
+fn add(a: i32, b: i32) -> i32 {
+    return a + b;
+}
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn main(scale: i32) -> i32 {
+    let p: Point = Point { x: 1, y: 2 };
+    let sum: i32 = add(p.x * scale, p.y - 1);
+    if (sum > 3 && !(scale == 0)) {
+        return add(sum, 1);
+    }
+    return sum; // trailing comment
+}
+"#;
+
+fn u32_bytes(out: &mut Vec<u8>, words: &[u32]) {
+    out.extend_from_slice(&(words.len() as u32).to_le_bytes());
+    for word in words {
+        out.extend_from_slice(&word.to_le_bytes());
+    }
+}
+
+#[test]
+fn strict_lex_and_parse_outputs_are_byte_identical_across_runs() {
+    common::block_on_gpu_with_timeout("strict determinism repeatability", async move {
+        let tables = PrecomputedParseTables::load_bin_bytes(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tables/parse_tables.bin"
+        )))
+        .expect("load precomputed parse tables");
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let parser = GpuParser::new_with(ParserOptions {
+            determinism: Determinism::Strict,
+            ..ParserOptions::default()
+        })
+        .await
+        .expect("create GPU parser");
+        let options = LexOptions {
+            capture_accept_states: true,
+            determinism: Determinism::Strict,
+            ..LexOptions::default()
+        };
+
+        let mut first: Option<Vec<u8>> = None;
+        for run in 0..RUNS {
+            let lexed = lexer
+                .lex_with_options(SOURCE, options)
+                .await
+                .expect("strict lex");
+            let mut bytes = Vec::new();
+            write_tokens(&mut bytes, &lexed.tokens, None).expect("serialize tokens");
+            let accept_states: Vec<u32> =
+                lexed.accept_states.iter().map(|&s| u32::from(s)).collect();
+            u32_bytes(&mut bytes, &accept_states);
+
+            let parsed = lexer
+                .with_resident_tokens(SOURCE, |_, _, buffers| {
+                    parser.parse_resident_tokens_with_source(
+                        buffers.n,
+                        &buffers.tokens_out,
+                        &buffers.token_count,
+                        buffers.n,
+                        &buffers.in_bytes,
+                        &tables,
+                    )
+                })
+                .await
+                .expect("resident lex")
+                .expect("resident parse");
+            assert!(
+                parsed.ll1.accepted,
+                "run {run}: parser rejected fixture: error_pos={} code={}",
+                parsed.ll1.error_pos, parsed.ll1.error_code
+            );
+            for words in [
+                &parsed.ll1_emit_stream,
+                &parsed.node_kind,
+                &parsed.parent,
+                &parsed.first_child,
+                &parsed.next_sibling,
+                &parsed.subtree_end,
+                &parsed.hir_kind,
+                &parsed.hir_semantic_parent,
+                &parsed.hir_item_kind,
+            ] {
+                u32_bytes(&mut bytes, words);
+            }
+
+            match &first {
+                None => first = Some(bytes),
+                Some(expected) => assert!(
+                    &bytes == expected,
+                    "run {run} serialized differently from run 0"
+                ),
+            }
+        }
+    });
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use std::time::Duration;
+
+use laniusc_compiler::{
+    dev::generator::gen_valid_source,
+    gpu::{
+        cancel::CancelToken,
+        device,
+        poll::{PollOutcome, wait_with_timeout},
+        teardown::graveyard_len,
+    },
+    lexer::{GpuLexer, LexOptions, ReadbackMode},
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+fn large_source() -> String {
+    "let value = 1 + 2;\n"
+        .chars()
+        .cycle()
+        .take(8 << 20)
+        .collect()
+}
+
+/// Waits for the device to go idle, which also drains the graveyard.
+fn wait_idle() {
+    let outcome = wait_with_timeout(&device::global().device, Some(Duration::from_secs(30)));
+    assert_eq!(outcome, PollOutcome::Complete);
+}
+
+#[test]
+fn dropping_a_lexer_right_after_a_no_readback_lex_is_clean() {
+    // The error scope guard is not `Send`, so the whole test runs on one thread.
+    common::run_with_timeout("lexer drop mid-flight", || {
+        let gpu = device::global();
+        let source = large_source();
+        let scope = gpu.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let lexer = pollster::block_on(GpuLexer::new()).expect("create GPU lexer");
+        let options = LexOptions {
+            readback: ReadbackMode::None,
+            ..LexOptions::default()
+        };
+        pollster::block_on(lexer.lex_with_options(&source, options)).expect("submit lex");
+        drop(lexer);
+        wait_idle();
+
+        assert_eq!(
+            pollster::block_on(scope.pop()).map(|err| err.to_string()),
+            None
+        );
+        assert_eq!(graveyard_len(), 0);
+    });
+}
+
+#[test]
+fn drops_after_a_cancellation_storm_leave_no_errors() {
+    common::run_with_timeout("lexer drops after cancel storm", || {
+        let gpu = device::global();
+        let mut rng = StdRng::seed_from_u64(933);
+        let scope = gpu.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        for round in 0..8 {
+            let lexer = pollster::block_on(GpuLexer::new()).expect("create GPU lexer");
+            for _ in 0..3 {
+                let len = 500 << rng.random_range(0..8);
+                let source = gen_valid_source(&mut rng, len);
+                let token = CancelToken::new();
+                let cancel = token.clone();
+                let delay = Duration::from_micros(rng.random_range(0..3_000));
+                std::thread::spawn(move || {
+                    std::thread::sleep(delay);
+                    cancel.cancel();
+                });
+                let _ = pollster::block_on(lexer.lex_cancellable(&source, token));
+            }
+            // Alternate best-effort drops with explicit shutdowns.
+            if round % 2 == 0 {
+                drop(lexer);
+            } else {
+                lexer.shutdown().expect("shut down lexer");
+            }
+        }
+        wait_idle();
+
+        assert_eq!(
+            pollster::block_on(scope.pop()).map(|err| err.to_string()),
+            None
+        );
+        assert_eq!(graveyard_len(), 0);
+    });
+}
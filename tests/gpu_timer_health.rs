@@ -0,0 +1,40 @@
+mod common;
+
+use laniusc_compiler::lexer::{GpuLexer, LexerRuntimeOptions};
+
+const LINE: &str = "fn f(a: i32) -> i32 { let b = [a, (a + 1) * 2]; return b[0]; } // c\n";
+
+#[test]
+fn per_round_timing_on_a_large_input_reports_healthy_stamps() {
+    common::block_on_gpu_with_timeout("gpu timer health", async move {
+        let source = LINE.repeat((4 << 20) / LINE.len());
+        let untimed = GpuLexer::new().await.expect("create GPU lexer");
+        let expected = untimed.lex(&source).await.expect("untimed lex");
+
+        let mut recorded = Vec::new();
+        for per_round in [false, true] {
+            let lexer = GpuLexer::new_with(LexerRuntimeOptions {
+                gpu_timing: true,
+                gpu_timing_per_round: per_round,
+                ..LexerRuntimeOptions::default()
+            })
+            .await
+            .expect("create GPU lexer");
+            assert!(lexer.last_timer_health().is_none());
+            let tokens = lexer.lex(&source).await.expect("timed lex");
+            assert_eq!(tokens, expected, "per_round={per_round}");
+
+            // Devices without timestamp queries record no health.
+            let Some(health) = lexer.last_timer_health() else {
+                return;
+            };
+            assert_eq!(health.dropped, 0, "{health:?}");
+            assert_eq!(health.recorded, health.requested, "{health:?}");
+            assert!(health.epochs >= 1, "{health:?}");
+            assert!(health.negative_deltas < health.recorded, "{health:?}");
+            recorded.push(health.recorded);
+        }
+        // Each block-scan round adds a stamp of its own.
+        assert!(recorded[1] > recorded[0], "{recorded:?}");
+    });
+}
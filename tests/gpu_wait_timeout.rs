@@ -0,0 +1,57 @@
+mod common;
+
+use std::time::Duration;
+
+use laniusc_compiler::{
+    gpu::{
+        device::{DeviceOptions, device_options, set_device_options},
+        poll::GpuError,
+    },
+    lexer::GpuLexer,
+};
+
+/// Restores the process-wide device options on drop, so a failing test does
+/// not leave the 1 ns timeout behind.
+struct DeviceOptionsGuard(DeviceOptions);
+
+impl DeviceOptionsGuard {
+    fn set(options: DeviceOptions) -> Self {
+        let previous = device_options();
+        set_device_options(options);
+        Self(previous)
+    }
+}
+
+impl Drop for DeviceOptionsGuard {
+    fn drop(&mut self) {
+        set_device_options(self.0);
+    }
+}
+
+fn source_of_len(len: usize) -> String {
+    "let value = 1 + 2;\n".chars().cycle().take(len).collect()
+}
+
+#[test]
+fn tiny_wait_timeout_fails_with_diagnostics() {
+    common::block_on_gpu_with_timeout("gpu wait timeout", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let source = source_of_len(10_000_000);
+
+        let guard = DeviceOptionsGuard::set(DeviceOptions {
+            wait_timeout: Some(Duration::from_nanos(1)),
+            ..device_options()
+        });
+        let result = lexer.lex(&source).await;
+        drop(guard);
+
+        let err = result.expect_err("a 1 ns wait cannot cover a 10 MB lex");
+        let Some(GpuError::Timeout { diagnostics, .. }) = err.downcast_ref::<GpuError>() else {
+            panic!("expected GpuError::Timeout, got {err:#}");
+        };
+        assert!(diagnostics.label.starts_with("lex."), "{diagnostics}");
+        assert!(!diagnostics.recent_submissions.is_empty(), "{diagnostics}");
+        assert!(!diagnostics.recent_passes.is_empty(), "{diagnostics}");
+        assert!(diagnostics.live_buffer_bytes > 0, "{diagnostics}");
+    });
+}
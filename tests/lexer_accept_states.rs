@@ -0,0 +1,103 @@
+mod common;
+
+use laniusc_compiler::{
+    gpu::determinism::Determinism,
+    lexer::{
+        DEFAULT_SINGLE_SUBMISSION_MAX_BYTES,
+        GpuLexer,
+        LexOptions,
+        ReadbackMode,
+        numeric::decode_int,
+        tables::dfa::S,
+        test_cpu::lex_on_test_cpu_with_accept_states,
+    },
+};
+
+const CAPTURE: LexOptions = LexOptions {
+    capture_accept_states: true,
+    readback: ReadbackMode::Full,
+    single_submission_max_bytes: DEFAULT_SINGLE_SUBMISSION_MAX_BYTES,
+    max_token_len: u32::MAX,
+    determinism: Determinism::Strict,
+    all_tokens: false,
+    report: false,
+    capture_string_escapes: false,
+    paranoia_level: 0,
+    fail_on_suspect_mismatch: false,
+    compress_readback: false,
+    layout_facts: false,
+};
+
+const NUMERIC_SOURCE: &str = "0 7 1_000 1_ 0x1F 0xF_F 0b101 0b1_0 0o17 0o1_7 1.5 x..=y 1..";
+
+#[test]
+fn numeric_literal_forms_report_their_accept_states() {
+    common::block_on_gpu_with_timeout("lexer accept states", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let out = lexer
+            .lex_with_options(NUMERIC_SOURCE, CAPTURE)
+            .await
+            .expect("GPU lex");
+        let (cpu_tokens, cpu_states) =
+            lex_on_test_cpu_with_accept_states(NUMERIC_SOURCE).expect("test CPU oracle");
+
+        assert_eq!(out.tokens.len(), cpu_tokens.len());
+        assert_eq!(out.accept_states, cpu_states);
+
+        let expected = [
+            ("0", S::Zero, Some(0)),
+            ("7", S::Int, Some(7)),
+            ("1_000", S::Int, Some(1000)),
+            ("1_", S::IntAfterUnderscore, Some(1)),
+            ("0x1F", S::Hex, Some(0x1f)),
+            ("0xF_F", S::Hex, Some(0xff)),
+            ("0b101", S::Bin, Some(0b101)),
+            ("0b1_0", S::Bin, Some(0b10)),
+            ("0o17", S::Oct, Some(0o17)),
+            ("0o1_7", S::Oct, Some(0o17)),
+            ("1.5", S::FloatFrac, None),
+            ("x", S::Ident, None),
+            ("..", S::DotDotDone, None),
+            ("=", S::AfterAssign, None),
+            ("y", S::Ident, None),
+            ("1", S::Int, Some(1)),
+            ("..", S::DotDotDone, None),
+        ];
+        assert_eq!(out.tokens.len(), expected.len());
+        for ((token, &state), (text, want_state, want_value)) in
+            out.tokens.iter().zip(&out.accept_states).zip(expected)
+        {
+            let bytes = &NUMERIC_SOURCE.as_bytes()[token.span.range()];
+            assert_eq!(bytes, text.as_bytes());
+            assert_eq!(S::from_idx(state as usize), Some(want_state), "{text}");
+            assert_eq!(decode_int(bytes, state), want_value, "{text}");
+        }
+    });
+}
+
+#[test]
+fn captured_states_match_the_oracle_and_default_lex_skips_them() {
+    common::block_on_gpu_with_timeout("lexer accept states oracle", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let source = format!(
+            "{}\nfn f(a: i32) -> i32 {{ return a >> 0b1 + 0x_ff; }} // done\n",
+            "let v = 0o7_7 + 12e + 3.25e+1 + \"s\" + 'c';\n".repeat(5_000)
+        );
+
+        let captured = lexer
+            .lex_with_options(&source, CAPTURE)
+            .await
+            .expect("GPU lex with capture");
+        let (cpu_tokens, cpu_states) =
+            lex_on_test_cpu_with_accept_states(&source).expect("test CPU oracle");
+        assert_eq!(captured.tokens.len(), cpu_tokens.len());
+        assert_eq!(captured.accept_states, cpu_states);
+
+        let plain = lexer
+            .lex_with_options(&source, LexOptions::default())
+            .await
+            .expect("GPU lex without capture");
+        assert!(plain.accept_states.is_empty());
+        assert_eq!(plain.tokens.len(), captured.tokens.len());
+    });
+}
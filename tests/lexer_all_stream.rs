@@ -0,0 +1,35 @@
+mod common;
+
+use laniusc_compiler::{
+    dev::generator::gen_valid_source,
+    lexer::{GpuLexer, test_cpu::lex_on_test_cpu_all},
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+#[test]
+fn all_boundary_stream_matches_test_cpu_oracle() {
+    common::block_on_gpu_with_timeout("lexer all-boundary stream", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let mut sources = vec![
+            "let x = 1..2 // c\n/* d */ y".to_string(),
+            "a = 1// tail".to_string(),
+            "pub fn f() {\n\treturn 0;\n}\n".to_string(),
+            "#!/usr/bin/env lanius\nfn main() {}\n".to_string(),
+        ];
+        let mut rng = StdRng::seed_from_u64(848);
+        sources.extend((0..4).map(|_| gen_valid_source(&mut rng, 3000)));
+
+        for source in &sources {
+            let expected = lex_on_test_cpu_all(source).expect("test CPU oracle");
+            let expected = expected
+                .iter()
+                .map(|token| (token.kind, token.start(), token.len()))
+                .collect::<Vec<_>>();
+            let gpu = lexer
+                .debug_all_tokens(source)
+                .await
+                .expect("GPU all-boundary lex");
+            assert_eq!(common::tokens::shapes(&gpu), expected, "{source:?}");
+        }
+    });
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use laniusc_compiler::lexer::{
+    GpuLexer,
+    LexOptions,
+    ReadbackMode,
+    boundary::is_kept,
+    test_cpu::lex_on_test_cpu_all,
+};
+
+// One lex serves both consumers: the kept stream is a filter over the ALL
+// stream, whose skipped entries match the test CPU all-boundary oracle.
+#[test]
+fn lex_both_filters_kept_tokens_from_the_all_stream() {
+    common::block_on_gpu_with_timeout("lexer all tokens", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+
+        for source in [
+            "",
+            " \n\t",
+            "// only a comment",
+            "#!/usr/bin/env lanius\nfn main() {}\n",
+            "let x = 1..2; /* block */ x // trailing",
+            "fn main() { let s = \"a b\"; return 0x2A; }\n",
+        ] {
+            let kept = lexer.lex(source).await.expect("lex");
+            let (both_kept, all) = lexer.lex_both(source).await.expect("lex_both");
+            assert_eq!(
+                common::tokens::shapes(&both_kept),
+                common::tokens::shapes(&kept),
+                "{source:?}"
+            );
+            assert_eq!(
+                common::tokens::shapes(&lexer.lex_all(source).await.expect("lex_all")),
+                common::tokens::shapes(&all),
+                "{source:?}"
+            );
+
+            let filtered: Vec<_> = all
+                .iter()
+                .filter(|t| is_kept(Some(t.kind)))
+                .cloned()
+                .collect();
+            assert_eq!(
+                common::tokens::shapes(&filtered),
+                common::tokens::shapes(&kept),
+                "{source:?}"
+            );
+
+            let oracle = lex_on_test_cpu_all(source).expect("test CPU all-boundary lex");
+            assert_eq!(all.len(), oracle.len(), "{source:?}");
+            for (token, expected) in all.iter().zip(&oracle) {
+                if !is_kept(Some(expected.kind)) {
+                    assert_eq!(
+                        (token.kind, token.start(), token.len()),
+                        (expected.kind, expected.start(), expected.len()),
+                        "{source:?}"
+                    );
+                }
+            }
+        }
+    });
+}
+
+#[test]
+fn all_tokens_are_read_only_under_full_readback() {
+    common::block_on_gpu_with_timeout("lexer all tokens readback", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let source = "x // comment\n";
+        for readback in [ReadbackMode::CountOnly, ReadbackMode::None] {
+            let out = lexer
+                .lex_with_options(
+                    source,
+                    LexOptions {
+                        readback,
+                        all_tokens: true,
+                        ..LexOptions::default()
+                    },
+                )
+                .await
+                .expect("lex");
+            assert!(out.all_tokens.is_empty(), "{readback:?}");
+        }
+    });
+}
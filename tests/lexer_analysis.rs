@@ -0,0 +1,97 @@
+mod common;
+
+use std::path::Path;
+
+use laniusc_compiler::lexer::{
+    GpuLexer,
+    LexAnalysis,
+    LexOptions,
+    LexPipeline,
+    test_cpu::analyze_on_test_cpu,
+};
+
+/// The UTF-8 cases of the byte-lexer fuzz corpus.
+fn sources() -> Vec<String> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus/fuzz_lex_bytes");
+    let mut entries: Vec<_> = std::fs::read_dir(&dir)
+        .expect("read fuzz corpus")
+        .map(|entry| entry.expect("corpus entry").path())
+        .collect();
+    entries.sort();
+    entries
+        .iter()
+        .filter_map(|path| String::from_utf8(std::fs::read(path).expect("read case")).ok())
+        .collect()
+}
+
+#[test]
+fn analysis_matches_a_full_lex_on_the_corpus() {
+    common::block_on_gpu_with_timeout("lexer analysis", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let options = LexOptions {
+            all_tokens: true,
+            ..LexOptions::default()
+        };
+
+        for pipeline in [
+            LexPipeline::Split,
+            LexPipeline::LateKeptFilter,
+            LexPipeline::FusedPairSeed,
+        ] {
+            lexer.set_pipeline(pipeline);
+            for source in sources() {
+                // Counts are only specified for inputs the lexer accepts.
+                let Ok(mirrored) = analyze_on_test_cpu(&source) else {
+                    continue;
+                };
+                let lexed = lexer.lex_with_options(&source, options).await.expect("lex");
+                let expected = LexAnalysis::from_all_tokens(&source, &lexed.all_tokens);
+                let actual = lexer.analyze(&source).await.expect("analyze");
+                assert_eq!(actual, expected, "{pipeline:?} {source:?}");
+                assert_eq!(mirrored, expected, "{source:?}");
+            }
+        }
+    });
+}
+
+#[test]
+fn analysis_dispatches_stop_before_compaction() {
+    common::block_on_gpu_with_timeout("lexer analysis dispatches", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        lexer.set_capture_dispatch_metadata(true);
+        let source: String = "let value = 1 + 2; // sum\n"
+            .chars()
+            .cycle()
+            .take(4 * 256 + 17)
+            .collect();
+
+        for pipeline in [
+            LexPipeline::Split,
+            LexPipeline::LateKeptFilter,
+            LexPipeline::FusedPairSeed,
+        ] {
+            lexer.set_pipeline(pipeline);
+            let (analysis, records) = lexer
+                .analyze_with_dispatch_records(&source)
+                .await
+                .expect("analyze");
+            assert_eq!(analysis.line_count, source.lines().count());
+            let labels: Vec<_> = records.into_iter().map(|record| record.label).collect();
+            assert_eq!(labels.last().map(String::as_str), Some("lexer_analyze"));
+            assert!(
+                labels
+                    .iter()
+                    .any(|label| label.starts_with("pair_03_apply_block_prefix")),
+                "{labels:?}"
+            );
+            for label in &labels {
+                assert!(
+                    !label.starts_with("compact_boundaries")
+                        && !label.starts_with("keep_")
+                        && !label.starts_with("tokens_"),
+                    "{pipeline:?} dispatched {label}"
+                );
+            }
+        }
+    });
+}
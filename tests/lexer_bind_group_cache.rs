@@ -0,0 +1,61 @@
+mod common;
+
+use laniusc_compiler::lexer::GpuLexer;
+
+fn source_of_len(len: usize) -> String {
+    "let value = 1 + 2;\n".chars().cycle().take(len).collect()
+}
+
+#[test]
+fn repeated_same_size_lex_creates_no_bind_groups() {
+    common::block_on_gpu_with_timeout("lexer bind-group cache reuse", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        // Several DFA blocks so both block scans record multiple rounds.
+        let source = source_of_len(4 * 256 + 17);
+
+        let first = lexer.lex(&source).await.expect("first lex");
+        let after_first = lexer.bind_group_cache_stats();
+        assert!(
+            after_first.misses > 0,
+            "first lex should populate the cache"
+        );
+
+        let second = lexer.lex(&source).await.expect("second lex");
+        let after_second = lexer.bind_group_cache_stats();
+        assert_eq!(
+            common::tokens::shapes(&first),
+            common::tokens::shapes(&second)
+        );
+        assert_eq!(
+            after_second.misses, after_first.misses,
+            "second same-size lex must not create bind groups"
+        );
+        assert!(after_second.hits > after_first.hits);
+    });
+}
+
+#[test]
+fn grown_buffers_rebuild_cached_scan_bind_groups() {
+    common::block_on_gpu_with_timeout("lexer bind-group cache grow path", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let small = source_of_len(300);
+        let large = source_of_len(9 * 256 + 3);
+
+        lexer.lex(&small).await.expect("small lex");
+        let after_small = lexer.bind_group_cache_stats();
+
+        let tokens = lexer.lex(&large).await.expect("large lex");
+        let after_large = lexer.bind_group_cache_stats();
+        assert!(
+            after_large.misses > after_small.misses,
+            "grown buffers must not reuse bind groups from the old allocation"
+        );
+
+        let repeated = lexer.lex(&large).await.expect("repeat large lex");
+        assert_eq!(
+            common::tokens::shapes(&repeated),
+            common::tokens::shapes(&tokens)
+        );
+        assert_eq!(lexer.bind_group_cache_stats().misses, after_large.misses);
+    });
+}
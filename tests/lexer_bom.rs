@@ -0,0 +1,104 @@
+mod common;
+
+use laniusc_compiler::lexer::{
+    GpuLexer,
+    LineMap,
+    tables::tokens::TokenKind,
+    test_cpu::{lex_on_test_cpu, lex_on_test_cpu_all},
+};
+
+fn golden_path(name: &str) -> String {
+    format!("{}/tests/cases/lexer/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+// A leading BOM is a skipped `Bom` token on the GPU exactly as on the test
+// CPU, and a BOM inside a string literal is ordinary string content.
+#[test]
+fn leading_bom_is_skipped_like_test_cpu_oracle() {
+    common::block_on_gpu_with_timeout("lexer BOM", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+
+        for source in [
+            "\u{feff}",
+            "\u{feff}x",
+            "\u{feff}\n\nfn main() {}\n",
+            "x = \"\u{feff}\"",
+            "\u{feff}s = \"\u{feff}\" // \u{feff}",
+        ] {
+            let kept: Vec<_> = lex_on_test_cpu(source)
+                .expect("test CPU oracle")
+                .into_iter()
+                .map(|t| (t.kind, t.start(), t.len()))
+                .collect();
+            let all: Vec<_> = lex_on_test_cpu_all(source)
+                .expect("test CPU oracle")
+                .into_iter()
+                .map(|t| (t.kind, t.start(), t.len()))
+                .collect();
+            let (gpu_kept, gpu_all) = lexer.lex_both(source).await.expect("GPU lex");
+            assert_eq!(common::tokens::shapes(&gpu_kept), kept, "{source:?}");
+            assert_eq!(common::tokens::shapes(&gpu_all), all, "{source:?}");
+            assert_eq!(
+                gpu_all.first().map(|t| t.kind == TokenKind::Bom),
+                Some(source.starts_with('\u{feff}')),
+                "{source:?}"
+            );
+        }
+
+        for source in ["x\u{feff}", " \u{feff}x", "\u{feff}\u{feff}"] {
+            assert!(lex_on_test_cpu(source).is_err(), "{source:?}");
+            assert!(lexer.lex(source).await.is_err(), "{source:?}");
+        }
+    });
+}
+
+// Token offsets stay in file bytes, and `LineMap` gives the columns an editor
+// shows, where the BOM takes no column.
+#[test]
+fn bom_golden_positions_match_editor_columns() {
+    common::block_on_gpu_with_timeout("lexer BOM goldens", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+
+        let source = std::fs::read_to_string(golden_path("bom_only.lani")).expect("read golden");
+        assert!(lexer.lex(&source).await.expect("GPU lex").is_empty());
+
+        let source = std::fs::read_to_string(golden_path("bom_code.lani")).expect("read golden");
+        let tokens = lexer.lex(&source).await.expect("GPU lex");
+        assert_eq!(tokens[0].start(), 3);
+        let lines = LineMap::new(&source);
+        let rendered: String = tokens
+            .iter()
+            .map(|t| {
+                let (line, column) = lines.line_col(t.start());
+                let text = &source[t.span.range()];
+                format!("{line}:{column} {:?} {text:?}\n", t.kind)
+            })
+            .collect();
+        let expected =
+            std::fs::read_to_string(golden_path("bom_code.positions")).expect("read positions");
+        assert_eq!(rendered, expected);
+    });
+}
+
+// Each source-pack file may start with its own BOM.
+#[test]
+fn source_pack_files_each_accept_a_leading_bom() {
+    common::block_on_gpu_with_timeout("lexer BOM source pack", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let files = ["\u{feff}let a = 1;\n", "\u{feff}let b = a;\n"];
+        let tokens = lexer.lex_source_pack(&files).await.expect("GPU lex");
+
+        let mut expected = Vec::new();
+        let mut base = 0;
+        for file in files {
+            expected.extend(
+                lex_on_test_cpu(file)
+                    .expect("test CPU oracle")
+                    .into_iter()
+                    .map(|t| (t.kind, base + t.start(), t.len())),
+            );
+            base += file.len();
+        }
+        assert_eq!(common::tokens::shapes(&tokens), expected);
+    });
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use laniusc_compiler::lexer::{GpuLexer, test_cpu::lex_on_test_cpu};
+
+fn source_of_len(len: usize) -> String {
+    "let value = 1 + 2;\n".chars().cycle().take(len).collect()
+}
+
+#[test]
+fn interleaved_sizes_reallocate_once_per_shape_change() {
+    common::block_on_gpu_with_timeout("lexer buffer reallocation", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        assert_eq!(lexer.buffer_allocation_count(), 0);
+
+        // Buffers fit each input exactly, so every size change reallocates
+        // and a repeated size reuses the resident buffers.
+        for (len, allocations) in [(10, 1), (10, 1), (10_000_000, 2), (10, 3), (10_000_001, 4)] {
+            let source = source_of_len(len);
+            let gpu = lexer.lex(&source).await.expect("GPU lex");
+            let expected = lex_on_test_cpu(&source).expect("test CPU oracle");
+
+            assert_eq!(gpu.len(), expected.len(), "token count for len={len}");
+            for (got, want) in gpu.iter().zip(&expected) {
+                assert_eq!(
+                    (got.kind, got.start(), got.len()),
+                    (want.kind, want.start(), want.len()),
+                    "len={len}"
+                );
+            }
+            assert_eq!(
+                lexer.buffer_allocation_count(),
+                allocations,
+                "allocations after len={len}"
+            );
+        }
+    });
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use std::{
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+use laniusc_compiler::{
+    dev::generator::gen_valid_source,
+    gpu::cancel::{CancelToken, Cancelled},
+    lexer::{GpuLexer, tables::tokens::TokenKind, test_cpu::lex_on_test_cpu},
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+/// Longest a cancelled call may keep running after `cancel()`.
+const CANCEL_BOUND: Duration = Duration::from_millis(500);
+
+fn oracle(source: &str) -> Vec<(TokenKind, usize, usize)> {
+    common::tokens::shapes(&lex_on_test_cpu(source).expect("test CPU oracle"))
+}
+
+/// Cancels `token` after `delay` on another thread and reports when it did.
+fn cancel_after(token: &CancelToken, delay: Duration) -> mpsc::Receiver<Instant> {
+    let token = token.clone();
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        let at = Instant::now();
+        token.cancel();
+        let _ = tx.send(at);
+    });
+    rx
+}
+
+#[test]
+fn cancelling_before_submission_does_no_gpu_work() {
+    common::block_on_gpu_with_timeout("lexer cancel before submit", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let token = CancelToken::new();
+        token.cancel();
+
+        let submissions = lexer.lex_submission_count();
+        let allocations = lexer.buffer_allocation_count();
+        let err = lexer
+            .lex_cancellable("let x = 1;", token)
+            .await
+            .expect_err("cancelled token");
+        assert_eq!(
+            err.downcast_ref::<Cancelled>(),
+            Some(&Cancelled {
+                stage: "lex.prepare".into(),
+                submitted: false,
+            }),
+            "{err:#}"
+        );
+        assert_eq!(lexer.lex_submission_count(), submissions);
+        assert_eq!(lexer.buffer_allocation_count(), allocations);
+    });
+}
+
+#[test]
+fn cancelling_mid_wait_returns_promptly_and_the_lexer_recovers() {
+    common::block_on_gpu_with_timeout("lexer cancel mid-wait", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let source: String = "let value = 1 + 2;\n"
+            .chars()
+            .cycle()
+            .take(8 << 20)
+            .collect();
+        let expected = oracle(&source);
+
+        let mut in_flight = 0;
+        for delay_ms in [0, 1, 2, 4, 8, 16, 32, 64] {
+            let token = CancelToken::new();
+            let cancelled_at = cancel_after(&token, Duration::from_millis(delay_ms));
+            let result = lexer.lex_cancellable(&source, token).await;
+            let returned_at = Instant::now();
+            match result {
+                Ok(tokens) => assert_eq!(
+                    common::tokens::shapes(&tokens),
+                    expected,
+                    "delay {delay_ms} ms"
+                ),
+                Err(err) => {
+                    let cancelled = err
+                        .downcast_ref::<Cancelled>()
+                        .unwrap_or_else(|| panic!("delay {delay_ms} ms: {err:#}"));
+                    let cancelled_at = cancelled_at.recv().expect("cancel time");
+                    let late = returned_at.saturating_duration_since(cancelled_at);
+                    assert!(
+                        late < CANCEL_BOUND,
+                        "delay {delay_ms} ms: returned {late:?} after cancel ({cancelled})"
+                    );
+                    in_flight += usize::from(cancelled.submitted);
+                }
+            }
+            // The next lex must not race the abandoned one's buffers.
+            let tokens = lexer.lex(&source).await.expect("lex after cancel");
+            assert_eq!(
+                common::tokens::shapes(&tokens),
+                expected,
+                "after delay {delay_ms} ms"
+            );
+        }
+        assert!(
+            in_flight > 0,
+            "no cancellation landed while GPU work was in flight"
+        );
+    });
+}
+
+#[test]
+fn fuzz_corpus_survives_a_storm_of_random_cancellations() {
+    common::block_on_gpu_with_timeout("lexer cancel storm", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let mut rng = StdRng::seed_from_u64(902);
+        let mut sources: Vec<String> = (0..24)
+            .map(|i| gen_valid_source(&mut rng, 500 << (i % 8)))
+            .collect();
+        let corpus = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cases/lexer");
+        for entry in std::fs::read_dir(corpus).expect("read tests/cases/lexer") {
+            let path = entry.expect("tests/cases/lexer entry").path();
+            if path.extension().is_some_and(|ext| ext == "lani") {
+                sources.push(std::fs::read_to_string(&path).expect("read corpus file"));
+            }
+        }
+
+        for source in &sources {
+            let expected = oracle(source);
+            for _ in 0..3 {
+                let token = CancelToken::new();
+                let _cancelled_at =
+                    cancel_after(&token, Duration::from_micros(rng.random_range(0..3_000)));
+                match lexer.lex_cancellable(source, token).await {
+                    Ok(tokens) => {
+                        assert_eq!(common::tokens::shapes(&tokens), expected, "{source:?}")
+                    }
+                    Err(err) => assert!(err.downcast_ref::<Cancelled>().is_some(), "{err:#}"),
+                }
+            }
+            let tokens = lexer.lex(source).await.expect("lex after cancellations");
+            assert_eq!(common::tokens::shapes(&tokens), expected, "{source:?}");
+        }
+    });
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use laniusc_compiler::lexer::{
+    GpuLexer,
+    LexOptions,
+    LexOutput,
+    passes::LEXER_STEPS,
+    test_cpu::lex_on_test_cpu,
+    verify::stream_hash,
+};
+
+/// Two-submission options, the readback path that can compress.
+fn two_submission(compress_readback: bool) -> LexOptions {
+    LexOptions {
+        single_submission_max_bytes: 0,
+        compress_readback,
+        report: true,
+        ..LexOptions::default()
+    }
+}
+
+async fn lex(lexer: &GpuLexer, source: &str, options: LexOptions) -> LexOutput {
+    let output = lexer
+        .lex_with_options(source, options)
+        .await
+        .expect("GPU lex");
+    let expected = lex_on_test_cpu(source).expect("test CPU oracle");
+    assert_eq!(
+        common::tokens::shapes(&output.tokens),
+        common::tokens::shapes(&expected),
+        "{options:?}"
+    );
+    output
+}
+
+#[test]
+fn adjacent_short_tokens_come_back_compressed() {
+    common::block_on_gpu_with_timeout("lexer compressed readback", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let source = "(a+b)*c;".repeat(4096);
+
+        let raw = lex(&lexer, &source, two_submission(false)).await;
+        let packed = lex(&lexer, &source, two_submission(true)).await;
+        assert_eq!(packed.tokens, raw.tokens);
+
+        let raw = raw.report.expect("report was requested");
+        let packed = packed.report.expect("report was requested");
+        assert!(!raw.compressed);
+        assert!(packed.compressed);
+        assert_eq!(packed.passes_recorded, LEXER_STEPS.len() as u32 + 1);
+        // Raw kinds still travel at 4 bytes a token, so the whole transfer
+        // drops from 16 to 8 bytes a token.
+        assert!(
+            packed.transfer_bytes * 3 / 2 < raw.transfer_bytes,
+            "{packed} vs {raw}"
+        );
+    });
+}
+
+#[test]
+fn tokens_between_long_comments_fall_back_to_raw() {
+    common::block_on_gpu_with_timeout("lexer compressed readback fallback", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let comment = format!("/*{}*/", "x".repeat(70_000));
+        let source = (0..8).map(|i| format!("{comment}v{i}")).collect::<String>();
+
+        let raw = lex(&lexer, &source, two_submission(false)).await;
+        let fallback = lex(&lexer, &source, two_submission(true)).await;
+        assert_eq!(fallback.tokens, raw.tokens);
+
+        let raw = raw.report.expect("report was requested");
+        let fallback = fallback.report.expect("report was requested");
+        assert!(!fallback.compressed, "{fallback}");
+        // The pass still ran to count the escapes.
+        assert_eq!(fallback.passes_recorded, raw.passes_recorded + 1);
+        assert_eq!(fallback.transfer_bytes, raw.transfer_bytes);
+    });
+}
+
+#[test]
+fn single_submission_readback_ignores_compression() {
+    common::block_on_gpu_with_timeout("lexer compressed single submission", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let output = lex(
+            &lexer,
+            "let x = 1;",
+            LexOptions {
+                compress_readback: true,
+                report: true,
+                ..LexOptions::default()
+            },
+        )
+        .await;
+        let report = output.report.expect("report was requested");
+        assert!(!report.compressed);
+        assert_eq!(report.passes_recorded, LEXER_STEPS.len() as u32);
+        assert_eq!(report.submissions, 1);
+    });
+}
+
+#[test]
+fn stream_hash_is_the_same_for_every_readback_variant() {
+    common::block_on_gpu_with_timeout("lexer stream hash readback variants", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let source = "let s = \"a\\n\"; // c\nfn f(x) { x * 2 }\n".repeat(2048);
+        let expected = stream_hash(&lex_on_test_cpu(&source).expect("test CPU oracle"));
+
+        for options in [
+            two_submission(false),
+            two_submission(true),
+            LexOptions::default(),
+            LexOptions {
+                compress_readback: true,
+                ..LexOptions::default()
+            },
+        ] {
+            let output = lex(&lexer, &source, options).await;
+            assert_eq!(stream_hash(&output.tokens), expected, "{options:?}");
+        }
+    });
+}
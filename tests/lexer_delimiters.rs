@@ -0,0 +1,65 @@
+mod common;
+
+use laniusc_compiler::lexer::{
+    GpuLexer,
+    Token,
+    tables::tokens::TokenKind,
+    test_cpu::lex_on_test_cpu_all,
+};
+
+fn golden(name: &str) -> String {
+    std::fs::read_to_string(format!(
+        "{}/tests/cases/lexer/{name}.lani",
+        env!("CARGO_MANIFEST_DIR")
+    ))
+    .expect("read golden source")
+}
+
+fn contents<'a>(source: &'a str, tokens: &[Token]) -> Vec<(TokenKind, &'a str)> {
+    tokens
+        .iter()
+        .filter(|token| !token.kind.info().opener.is_empty())
+        .map(|token| (token.kind, &source[token.content_range(source)]))
+        .collect()
+}
+
+// Delimited tokens at EOF and directly against other tokens keep the same
+// boundaries on the GPU as on the test CPU, and strip per `TokenKindInfo`.
+#[test]
+fn delimited_token_boundaries_match_test_cpu() {
+    common::block_on_gpu_with_timeout("lexer delimiters", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let cases = [
+            ("string_eof", vec![(TokenKind::String, "a\\\"b")]),
+            (
+                "char_adjacent",
+                vec![
+                    (TokenKind::Char, "a"),
+                    (TokenKind::Char, "\\'"),
+                    (TokenKind::Char, "b"),
+                ],
+            ),
+            (
+                "block_comment_eof",
+                vec![(TokenKind::BlockComment, " end ")],
+            ),
+        ];
+        for (name, expected) in cases {
+            let source = golden(name);
+            let gpu = lexer.lex_all(&source).await.expect("GPU all-token lex");
+            let cpu = lex_on_test_cpu_all(&source).expect("test CPU oracle");
+            let spans = |tokens: &[Token]| {
+                tokens
+                    .iter()
+                    .map(|token| (token.kind, token.start(), token.len()))
+                    .collect::<Vec<_>>()
+            };
+            let cpu_spans = cpu
+                .iter()
+                .map(|token| (token.kind, token.start(), token.len()))
+                .collect::<Vec<_>>();
+            assert_eq!(spans(&gpu), cpu_spans, "{name}");
+            assert_eq!(contents(&source, &gpu), expected, "{name}");
+        }
+    });
+}
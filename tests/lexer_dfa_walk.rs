@@ -0,0 +1,70 @@
+mod common;
+
+use laniusc_compiler::lexer::{
+    GpuLexer,
+    tables::compact::{CompactTables, walk},
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+const CORPUS: &[&str] = &[
+    "fn main() { let x = 0x2A + 1; return x; } // done\n",
+    "let v = 0o7_7 + 12e + 3.25e+1 + \"s\\\"q\" + 'c' + r\"raw\";\n",
+    "a >>= b <<= c != d == e && f || g ..= h -> i => j ++ k -- l",
+    "/* block ∀x */ s = \"héllo → wörld 🦀\"; // ünïcödé",
+    "x /* unterminated",
+    "\"unterminated string\n0b 0x 0o 1_ 1.e",
+    "\t \r\n\n  ",
+    "@ # $ ` \\ valid after rejects",
+];
+
+/// Pieces random inputs are stitched from: single bytes of every DFA
+/// character class plus multi-byte prefixes that branch inside a token.
+const PIECES: &[&str] = &[
+    "a", "r", "_", "e", "x", "0", "1", "9", " ", "\n", "\t", ".", "..", "+", "-", "*", "/", "//",
+    "/*", "*/", "=", "<", ">", "!", "&", "|", "^", "%", "~", ",", ";", ":", "?", "(", ")", "[",
+    "]", "{", "}", "\"", "'", "\\", "#", "@", "é", "🦀", "0x", "0b", "0o", "r\"",
+];
+
+fn random_source(rng: &mut StdRng) -> String {
+    let pieces = rng.random_range(1..=24);
+    let mut src: String = (0..pieces)
+        .map(|_| PIECES[rng.random_range(0..PIECES.len())])
+        .collect();
+    // The driver masks a leading shebang before upload; the walk would not.
+    if src.starts_with("#!") {
+        src.insert(0, ' ');
+    }
+    src
+}
+
+#[test]
+fn gpu_dfa_states_match_the_compact_table_walk() {
+    common::block_on_gpu_with_timeout("lexer DFA walk", async move {
+        let lexer = match GpuLexer::new().await {
+            Ok(lexer) => lexer,
+            Err(err) => {
+                eprintln!(
+                    "SKIP gpu_dfa_states_match_the_compact_table_walk: no GPU lexer: {err:#}"
+                );
+                return;
+            }
+        };
+        let tables = CompactTables::checked_in();
+        let mut rng = StdRng::seed_from_u64(912);
+        let mut sources: Vec<String> = CORPUS.iter().map(|src| src.to_string()).collect();
+        sources.extend((0..100).map(|_| random_source(&mut rng)));
+
+        for source in &sources {
+            let expected = walk(source.as_bytes(), &tables).f_final;
+            let gpu = lexer.debug_dfa_walk(source).await.expect("GPU DFA walk");
+            if let Some(i) = (0..expected.len()).find(|&i| gpu.get(i) != Some(&expected[i])) {
+                panic!(
+                    "{source:?}: state after byte {i} is {:?} on the GPU, {} in the tables",
+                    gpu.get(i),
+                    expected[i]
+                );
+            }
+            assert_eq!(gpu.len(), expected.len(), "{source:?}");
+        }
+    });
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use laniusc_compiler::{
+    gpu::passes_core::InputElements,
+    lexer::{GpuLexer, LexOptions, LexPipeline, util::compute_rounds},
+};
+
+fn scan_labels(scan: &str, n_blocks: u32) -> Vec<String> {
+    (0..compute_rounds(n_blocks))
+        .map(|round| format!("{scan}[round={round}, stride={}]", 1u32 << round))
+        .collect()
+}
+
+#[test]
+fn dispatch_records_follow_lexer_pass_sequence() {
+    common::block_on_gpu_with_timeout("lexer dispatch records", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        lexer.set_capture_dispatch_metadata(true);
+        // Five 256-byte blocks: three rounds in each block scan.
+        let source: String = "let value = 1 + 2;\n"
+            .chars()
+            .cycle()
+            .take(4 * 256 + 17)
+            .collect();
+        let n_blocks = (source.len() as u32).div_ceil(256);
+
+        let mut prefix = vec![
+            "source_file_boundaries".to_string(),
+            "dfa_01_scan_inblock".to_string(),
+        ];
+        prefix.extend(scan_labels("dfa_02", n_blocks));
+        prefix.push("dfa_03_apply_block_prefix".to_string());
+
+        let mut late = prefix.clone();
+        late.push("pair_01_sum_inblock".to_string());
+        late.extend(scan_labels("pair_02", n_blocks));
+        late.extend(
+            [
+                "pair_03_apply_block_prefix",
+                "compact_boundaries[ALL]",
+                "keep_01_sum_inblock",
+            ]
+            .map(String::from),
+        );
+        late.extend(scan_labels("keep_02", n_blocks));
+        late.extend(
+            [
+                "keep_03_apply_block_prefix",
+                "compact_boundaries[KEPT]",
+                "tokens_build",
+            ]
+            .map(String::from),
+        );
+        lexer.set_pipeline(LexPipeline::LateKeptFilter);
+        let late_records = lexer
+            .lex_with_options(&source, LexOptions::default())
+            .await
+            .expect("late-filter lex")
+            .dispatch_records;
+        let labels = late_records
+            .iter()
+            .map(|record| record.label.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(labels, late);
+
+        let mut expected = prefix;
+        expected.push("pair_01_sum_inblock_with_keep".to_string());
+        expected.extend(scan_labels("pair_02", n_blocks));
+        expected.extend(
+            [
+                "pair_03_apply_block_prefix_with_keep",
+                "compact_boundaries[KEPT]",
+                "compact_boundaries[ALL]",
+                "tokens_build",
+            ]
+            .map(String::from),
+        );
+        lexer.set_pipeline(LexPipeline::Split);
+        let records = lexer
+            .lex_with_options(&source, LexOptions::default())
+            .await
+            .expect("lex")
+            .dispatch_records;
+        let labels = records
+            .iter()
+            .map(|record| record.label.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(labels, expected);
+        assert_eq!(compute_rounds(n_blocks), 3);
+
+        for record in records
+            .iter()
+            .filter(|record| record.label.contains("[round="))
+        {
+            assert_eq!(record.elements, Some(InputElements::Elements1D(n_blocks)));
+        }
+        // The DFA block scan runs one workgroup per block.
+        for record in records
+            .iter()
+            .filter(|record| record.label.starts_with("dfa_02["))
+        {
+            assert_eq!(record.workgroups, Some((n_blocks, 1, 1)));
+        }
+
+        lexer.set_capture_dispatch_metadata(false);
+        let output = lexer
+            .lex_with_options(&source, LexOptions::default())
+            .await
+            .expect("lex without capture");
+        assert!(output.dispatch_records.is_empty());
+    });
+}
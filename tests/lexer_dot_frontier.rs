@@ -1,11 +1,6 @@
 mod common;
 
-use laniusc_compiler::lexer::{
-    GpuLexer,
-    Token,
-    tables::TokenKind,
-    test_cpu::{TestCpuToken, lex_on_test_cpu},
-};
+use laniusc_compiler::lexer::{GpuLexer, Token, tables::TokenKind, test_cpu::lex_on_test_cpu};
 use proptest::{
     collection::vec,
     prelude::*,
@@ -112,12 +107,7 @@ fn lexer_dot_frontier_property_matches_cpu_oracle() {
                     ))
                 })?;
 
-                prop_assert_eq!(
-                    gpu_stream(&gpu),
-                    test_cpu_stream(&cpu),
-                    "source:\n{}",
-                    source
-                );
+                prop_assert_eq!(stream(&gpu), stream(&cpu), "source:\n{}", source);
                 Ok(())
             })
             .expect("dot frontier property should match the test CPU oracle");
@@ -130,7 +120,7 @@ async fn assert_expected_tokens(lexer: &GpuLexer, source: &str, expected: &[(Tok
         .lex(source)
         .await
         .expect("GPU lexer should accept exact frontier case");
-    assert_eq!(gpu_stream(&gpu), test_cpu_stream(&cpu), "source:\n{source}");
+    assert_eq!(stream(&gpu), stream(&cpu), "source:\n{source}");
 
     let actual = token_texts(source, &gpu);
     let expected = expected
@@ -177,17 +167,10 @@ fn small_gap() -> impl Strategy<Value = &'static str> {
     select(vec!["", " ", "\t", "\n"])
 }
 
-fn gpu_stream(tokens: &[Token]) -> Vec<(TokenKind, usize, usize)> {
-    tokens
-        .iter()
-        .map(|token| (token.kind, token.start, token.len))
-        .collect()
-}
-
-fn test_cpu_stream(tokens: &[TestCpuToken]) -> Vec<(TokenKind, usize, usize)> {
+fn stream(tokens: &[Token]) -> Vec<(TokenKind, usize, usize)> {
     tokens
         .iter()
-        .map(|token| (token.kind, token.start, token.len))
+        .map(|token| (token.kind, token.start(), token.len()))
         .collect()
 }
 
@@ -195,8 +178,8 @@ fn token_texts(source: &str, tokens: &[Token]) -> Vec<(TokenKind, String)> {
     tokens
         .iter()
         .map(|token| {
-            let start = token.start;
-            let end = start + token.len;
+            let start = token.start();
+            let end = start + token.len();
             (token.kind, source[start..end].to_string())
         })
         .collect()
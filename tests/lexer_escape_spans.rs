@@ -0,0 +1,184 @@
+//! `LexOptions::capture_string_escapes` against the test CPU oracle, plus a
+//! highlighting snapshot built from the spans.
+//!
+//! `GOLDEN_BLESS=1 cargo test --test lexer_escape_spans` rewrites the
+//! snapshot from the test CPU oracle.
+
+mod common;
+
+use std::fmt::Write as _;
+
+use laniusc_compiler::{
+    lexer::{
+        EscapeSpan,
+        GpuLexer,
+        LexOptions,
+        LexPipeline,
+        Token,
+        escapes::escape_span_tokens,
+        tables::tokens::TokenKind,
+        test_cpu::lex_on_test_cpu_with_escape_spans,
+    },
+    span::Span,
+};
+
+fn capture() -> LexOptions {
+    LexOptions {
+        capture_string_escapes: true,
+        ..LexOptions::default()
+    }
+}
+
+const HIGHLIGHT_SOURCE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/highlighting/escapes.lani"
+);
+const HIGHLIGHT_SNAPSHOT: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/highlighting/escapes.styles"
+);
+
+/// Sources and the `(start, len)` of every escape they contain.
+const CASES: &[(&str, &[(usize, usize)])] = &[
+    // Consecutive escapes.
+    (r#"let s = "\\\n\t";"#, &[(9, 2), (11, 2), (13, 2)]),
+    // An escape right before the closing quote.
+    (r#"f("abc\"");"#, &[(6, 2)]),
+    // Char literals.
+    (r#"let c = '\n'; let q = '\'';"#, &[(9, 2), (23, 2)]),
+    // The escaped character is a whole multi-byte character.
+    ("x(\"\\é\");", &[(3, 3)]),
+    // Raw strings have no escapes.
+    (r#"let r = r"\n\t\\";"#, &[]),
+    // Nor do comments.
+    ("/* \"\\n\" */ // '\\t'\n", &[]),
+];
+
+fn spans(pairs: &[(usize, usize)]) -> Vec<EscapeSpan> {
+    pairs
+        .iter()
+        .map(|&(start, len)| EscapeSpan {
+            span: Span::from_usize(start, len),
+        })
+        .collect()
+}
+
+#[test]
+fn escape_spans_match_the_oracle() {
+    common::block_on_gpu_with_timeout("lexer escape spans", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        for &(source, expected) in CASES {
+            let (cpu_tokens, cpu_spans) =
+                lex_on_test_cpu_with_escape_spans(source).expect("test CPU oracle");
+            assert_eq!(cpu_spans, spans(expected), "oracle on {source:?}");
+
+            let out = lexer
+                .lex_with_options(source, capture())
+                .await
+                .expect("GPU lex");
+            assert_eq!(out.tokens, cpu_tokens, "{source:?}");
+            assert_eq!(out.escape_spans, cpu_spans, "{source:?}");
+            for owner in escape_span_tokens(&out.tokens, &out.escape_spans) {
+                let owner = owner.expect("every span sits in a token");
+                assert!(
+                    matches!(
+                        out.tokens[owner].raw_kind,
+                        TokenKind::String | TokenKind::Char
+                    ),
+                    "{source:?}"
+                );
+            }
+
+            let plain = lexer.lex(source).await.expect("GPU lex without capture");
+            assert_eq!(plain, out.tokens, "{source:?}");
+        }
+    });
+}
+
+#[test]
+fn escape_spans_across_blocks_match_under_both_pipelines() {
+    common::block_on_gpu_with_timeout("lexer escape spans across blocks", async move {
+        // Thousands of lines spread the escapes over many block offsets.
+        let source = "let s = \"a\\tb\\\\\" + '\\'' + r\"\\n\";\n".repeat(2_000);
+        let (cpu_tokens, cpu_spans) =
+            lex_on_test_cpu_with_escape_spans(&source).expect("test CPU oracle");
+        assert_eq!(cpu_spans.len(), 3 * 2_000);
+
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        for pipeline in [
+            LexPipeline::Split,
+            LexPipeline::LateKeptFilter,
+            LexPipeline::FusedPairSeed,
+        ] {
+            lexer.set_pipeline(pipeline);
+            let out = lexer
+                .lex_with_options(&source, capture())
+                .await
+                .expect("GPU lex");
+            assert_eq!(out.tokens, cpu_tokens, "{pipeline:?}");
+            assert_eq!(out.escape_spans, cpu_spans, "{pipeline:?}");
+        }
+    });
+}
+
+/// One line per styled region of each literal: its byte range, style and
+/// text. Everything outside literals is left unstyled.
+fn styled_regions(source: &str, tokens: &[Token], spans: &[EscapeSpan]) -> String {
+    let owners = escape_span_tokens(tokens, spans);
+    let mut out = String::new();
+    let mut push = |style: &str, start: usize, end: usize| {
+        writeln!(out, "{start}..{end} {style} {:?}", &source[start..end]).unwrap();
+    };
+    for (index, token) in tokens.iter().enumerate() {
+        let style = match token.raw_kind {
+            TokenKind::String => "string",
+            TokenKind::Char => "char",
+            TokenKind::RawString => "raw-string",
+            _ => continue,
+        };
+        let mut at = token.start();
+        for (escape, _) in spans
+            .iter()
+            .zip(&owners)
+            .filter(|&(_, &owner)| owner == Some(index))
+        {
+            let span = escape.span.range();
+            if at < span.start {
+                push(style, at, span.start);
+            }
+            push("escape", span.start, span.end);
+            at = span.end;
+        }
+        if at < token.end() {
+            push(style, at, token.end());
+        }
+    }
+    out
+}
+
+#[test]
+fn highlighting_regions_match_the_snapshot() {
+    let source = std::fs::read_to_string(HIGHLIGHT_SOURCE).expect("read highlighting sample");
+    let (cpu_tokens, cpu_spans) =
+        lex_on_test_cpu_with_escape_spans(&source).expect("test CPU oracle");
+    let expected = styled_regions(&source, &cpu_tokens, &cpu_spans);
+    if std::env::var_os("GOLDEN_BLESS").is_some() {
+        std::fs::write(HIGHLIGHT_SNAPSHOT, &expected).expect("write highlighting snapshot");
+    }
+    let snapshot = std::fs::read_to_string(HIGHLIGHT_SNAPSHOT)
+        .expect("read highlighting snapshot; bless it with GOLDEN_BLESS=1");
+    assert_eq!(expected, snapshot, "test CPU oracle regions");
+
+    common::block_on_gpu_with_timeout("lexer escape highlighting", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let out = lexer
+            .lex_with_options(&source, capture())
+            .await
+            .expect("GPU lex");
+        assert_eq!(
+            styled_regions(&source, &out.tokens, &out.escape_spans),
+            snapshot,
+            "GPU regions"
+        );
+    });
+}
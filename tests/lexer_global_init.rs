@@ -0,0 +1,38 @@
+mod common;
+
+use laniusc_compiler::lexer::{
+    driver::force_global_lexer_init_failure,
+    lex_on_gpu,
+    lex_on_gpu_retry_init,
+};
+
+// One test per process: the global lexer and its retained failure are
+// process-wide state.
+#[test]
+fn global_lexer_init_failure_is_retained_until_retry() {
+    force_global_lexer_init_failure(Some("adapter request skipped"));
+
+    let first = pollster::block_on(lex_on_gpu("let x = 1;")).expect_err("forced init failure");
+    let init = first
+        .downcast_ref::<laniusc_compiler::lexer::InitError>()
+        .expect("structured init error")
+        .clone();
+    assert_eq!(
+        init.causes(),
+        ["initialization forced to fail", "adapter request skipped"]
+    );
+
+    // Clearing the hook alone does not retry; the failure is retained.
+    force_global_lexer_init_failure(None);
+    let second = pollster::block_on(lex_on_gpu("let x = 1;")).expect_err("retained failure");
+    assert_eq!(
+        second.downcast_ref::<laniusc_compiler::lexer::InitError>(),
+        Some(&init)
+    );
+
+    common::block_on_gpu_with_timeout("lexer global init retry", async move {
+        lex_on_gpu_retry_init().expect("retry after clearing the hook");
+        let tokens = lex_on_gpu("let x = 1;").await.expect("lex after retry");
+        assert_eq!(tokens.len(), 5);
+    });
+}
@@ -0,0 +1,118 @@
+//! `GpuLexer::highlight` and `GpuLexer::relex_highlight` against a scope
+//! snapshot of a fixture file.
+//!
+//! `GOLDEN_BLESS=1 cargo test --test lexer_highlight` rewrites the snapshot
+//! from the test CPU oracle.
+
+mod common;
+
+use std::fmt::Write as _;
+
+use laniusc_compiler::lexer::{
+    GpuLexer,
+    HighlightSpan,
+    LexOptions,
+    highlight::highlight_spans,
+    test_cpu::{lex_on_test_cpu, lex_on_test_cpu_all},
+    util::merge_kept_into_all,
+};
+
+const SOURCE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/highlighting/scopes.lani"
+);
+const SNAPSHOT: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/highlighting/scopes.scopes"
+);
+
+/// One line per highlighted token: its byte range, scope, kind and text.
+fn render(source: &str, spans: &[HighlightSpan]) -> String {
+    let mut out = String::new();
+    for span in spans {
+        let range = span.span.range();
+        writeln!(
+            out,
+            "{}..{} {:?} {:?} {:?}",
+            range.start,
+            range.end,
+            span.scope,
+            span.kind,
+            &source[span.span.range()]
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Highlight spans of the test CPU oracle's ALL stream with its kept kinds.
+fn oracle_spans(source: &str) -> Vec<HighlightSpan> {
+    let mut all = lex_on_test_cpu_all(source).expect("test CPU all-boundary lex");
+    let kept = lex_on_test_cpu(source).expect("test CPU lex");
+    merge_kept_into_all(&mut all, &kept).expect("merge kept tokens");
+    highlight_spans(&all)
+}
+
+#[test]
+fn highlight_scopes_match_the_snapshot() {
+    let source = std::fs::read_to_string(SOURCE).expect("read highlighting sample");
+    let expected = render(&source, &oracle_spans(&source));
+    if std::env::var_os("GOLDEN_BLESS").is_some() {
+        std::fs::write(SNAPSHOT, &expected).expect("write scope snapshot");
+    }
+    let snapshot = std::fs::read_to_string(SNAPSHOT)
+        .expect("read scope snapshot; bless it with GOLDEN_BLESS=1");
+    assert_eq!(expected, snapshot, "test CPU oracle scopes");
+
+    common::block_on_gpu_with_timeout("lexer highlight", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let spans = lexer.highlight(&source).await.expect("GPU highlight");
+        assert_eq!(render(&source, &spans), snapshot, "GPU scopes");
+
+        // The profile reads back what `lex_all` does, minus whitespace.
+        let all = lexer.lex_all(&source).await.expect("GPU lex_all");
+        assert_eq!(spans, highlight_spans(&all));
+        let out = lexer
+            .lex_with_options(&source, LexOptions::highlight())
+            .await
+            .expect("GPU highlight lex");
+        assert!(out.report.is_none() && out.layout_facts.is_empty());
+        assert!(out.suspects.is_empty() && out.escape_spans.is_empty());
+    });
+}
+
+#[test]
+fn relex_highlight_matches_the_full_highlight_after_an_edit() {
+    let source = std::fs::read_to_string(SOURCE).expect("read highlighting sample");
+    common::block_on_gpu_with_timeout("lexer relex highlight", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        // Turn `p.y * 2` into `p.y * 2.5 /* half */`, then relex the edited
+        // line only.
+        let at = source.find("* 2;").expect("edit site") + 3;
+        let mut edited = source.clone();
+        edited.insert_str(at, ".5 /* half */");
+        let line_start = edited[..at].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = at + edited[at..].find('\n').expect("line end");
+
+        let relexed = lexer
+            .relex_highlight(&edited, line_start..line_end)
+            .await
+            .expect("GPU relex highlight");
+        assert!(!relexed.approximate);
+        let full: Vec<_> = lexer
+            .highlight(&edited)
+            .await
+            .expect("GPU highlight")
+            .into_iter()
+            .filter(|span| {
+                let range = span.span.range();
+                range.start < line_end && range.end > line_start
+            })
+            .collect();
+        assert_eq!(render(&edited, &relexed.spans), render(&edited, &full));
+        assert!(
+            render(&edited, &relexed.spans).contains("Float \"2.5\"")
+                && render(&edited, &relexed.spans).contains("Comment BlockComment")
+        );
+    });
+}
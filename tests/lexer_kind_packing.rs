@@ -0,0 +1,79 @@
+mod common;
+
+use std::path::Path;
+
+use laniusc_compiler::lexer::{
+    GpuLexer,
+    LexOptions,
+    LexPipeline,
+    LexerRuntimeOptions,
+    kind_packing::KindPacking,
+};
+
+/// The byte-lexer fuzz corpus, with invalid UTF-8 replaced, plus inputs that
+/// end on either byte of a narrow `tok_types` word.
+fn sources() -> Vec<String> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus/fuzz_lex_bytes");
+    let mut entries: Vec<_> = std::fs::read_dir(&dir)
+        .expect("read fuzz corpus")
+        .map(|entry| entry.expect("corpus entry").path())
+        .collect();
+    entries.sort();
+    let mut sources: Vec<String> = entries
+        .iter()
+        .map(|path| String::from_utf8_lossy(&std::fs::read(path).expect("read case")).into_owned())
+        .collect();
+    let line = "let s = \"é\"; x += 0x1F; // tail\n";
+    for len in [1, 2, 3, 255, 256, 257] {
+        sources.push(line.chars().cycle().take(len).collect());
+    }
+    sources
+}
+
+#[test]
+fn narrow_and_wide_kind_packing_lex_the_corpus_identically() {
+    common::block_on_gpu_with_timeout("lexer kind packings", async move {
+        let narrow = GpuLexer::new().await.expect("create GPU lexer");
+        assert_eq!(narrow.kind_packing(), KindPacking::Narrow);
+        let wide = GpuLexer::new_with(LexerRuntimeOptions {
+            wide_token_kinds: true,
+            ..LexerRuntimeOptions::default()
+        })
+        .await
+        .expect("create wide GPU lexer");
+        assert_eq!(wide.kind_packing(), KindPacking::Wide);
+        let options = LexOptions {
+            all_tokens: true,
+            ..LexOptions::default()
+        };
+
+        for pipeline in [
+            LexPipeline::Split,
+            LexPipeline::LateKeptFilter,
+            LexPipeline::FusedPairSeed,
+        ] {
+            narrow.set_pipeline(pipeline);
+            wide.set_pipeline(pipeline);
+            for source in sources() {
+                let expected = wide
+                    .lex_with_options(&source, options)
+                    .await
+                    .expect("wide lex");
+                let actual = narrow
+                    .lex_with_options(&source, options)
+                    .await
+                    .expect("narrow lex");
+                assert_eq!(
+                    common::tokens::shapes(&actual.tokens),
+                    common::tokens::shapes(&expected.tokens),
+                    "{pipeline:?} {source:?}"
+                );
+                assert_eq!(
+                    common::tokens::shapes(&actual.all_tokens),
+                    common::tokens::shapes(&expected.all_tokens),
+                    "{pipeline:?} {source:?}"
+                );
+            }
+        }
+    });
+}
@@ -0,0 +1,175 @@
+mod common;
+
+use laniusc_compiler::lexer::{
+    GpuLexer,
+    LayoutFacts,
+    LexOptions,
+    LineMap,
+    SubmissionPolicy,
+    Token,
+    tables::tokens::TokenKind,
+    test_cpu::layout_facts_on_test_cpu,
+};
+
+const FIXTURE: &str = include_str!("fixtures/layout/formatter_input.lani");
+
+/// Lays `tokens` out again from their layout facts alone; only the lexemes
+/// come from `source`. Blank lines collapse to one, a line keeps its
+/// indentation width, tokens on a line sit one space apart, and a marker
+/// line stands in for the comments before a token.
+fn format(source: &str, tokens: &[Token], facts: &[LayoutFacts]) -> String {
+    let mut out = String::new();
+    for (k, (token, fact)) in tokens.iter().zip(facts).enumerate() {
+        if k == 0 || fact.newlines_before > 0 {
+            if k > 0 {
+                out.push('\n');
+            }
+            if fact.newlines_before > 1 {
+                out.push('\n');
+            }
+            let indent = " ".repeat(usize::from(fact.indent_col));
+            if fact.had_line_comment_before {
+                out.push_str(&indent);
+                out.push_str("// ...\n");
+            }
+            out.push_str(&indent);
+        } else {
+            // A line comment runs to the end of its line.
+            assert!(!fact.had_line_comment_before, "token {k}");
+            out.push(' ');
+        }
+        out.push_str(&source[token.span.range()]);
+    }
+    out
+}
+
+/// The facts measured on the source text: line numbers from a [`LineMap`],
+/// the blanks that open each line, and line comments from the all-boundary
+/// stream.
+fn reference_facts(source: &str, tokens: &[Token], all_tokens: &[Token]) -> Vec<LayoutFacts> {
+    let lines = LineMap::new(source);
+    let text = source.strip_prefix('\u{feff}').unwrap_or(source);
+    let line_texts: Vec<&str> = text.split('\n').collect();
+    let saturate = |count: usize| count.min(usize::from(u16::MAX)) as u16;
+    let mut prev_end = 0;
+    tokens
+        .iter()
+        .map(|token| {
+            let (line, _) = lines.line_col(token.start());
+            let (prev_line, _) = lines.line_col(prev_end);
+            let indent = line_texts[line - 1]
+                .bytes()
+                .take_while(|&b| b == b' ' || b == b'\t')
+                .count();
+            let had_line_comment_before = all_tokens.iter().any(|t| {
+                t.kind == TokenKind::LineComment && (prev_end..token.start()).contains(&t.start())
+            });
+            prev_end = token.end();
+            LayoutFacts {
+                newlines_before: saturate(line - prev_line),
+                indent_col: saturate(indent),
+                had_line_comment_before,
+            }
+        })
+        .collect()
+}
+
+/// Layout facts from the GPU, checked against the source analysis and the
+/// test CPU mirror.
+async fn checked_facts(lexer: &GpuLexer, source: &str) -> (Vec<Token>, Vec<LayoutFacts>) {
+    let (tokens, facts) = lexer.lex_with_layout(source).await.expect("lex");
+    let options = LexOptions {
+        all_tokens: true,
+        ..LexOptions::default()
+    };
+    let full = lexer.lex_with_options(source, options).await.expect("lex");
+    assert_eq!(tokens, full.tokens, "{source:?}");
+    assert_eq!(facts.len(), tokens.len(), "{source:?}");
+    assert_eq!(
+        facts,
+        reference_facts(source, &tokens, &full.all_tokens),
+        "{source:?}"
+    );
+    assert_eq!(Ok(facts.clone()), layout_facts_on_test_cpu(source));
+    (tokens, facts)
+}
+
+#[test]
+fn formatting_from_layout_facts_matches_the_source_analysis() {
+    common::block_on_gpu_with_timeout("lexer layout formatter", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        for policy in [
+            SubmissionPolicy::Immediate,
+            SubmissionPolicy::Yielding { chunk_passes: 1 },
+        ] {
+            lexer.set_submission_policy(policy);
+            let (tokens, facts) = checked_facts(&lexer, FIXTURE).await;
+            let options = LexOptions {
+                all_tokens: true,
+                ..LexOptions::default()
+            };
+            let all_tokens = lexer
+                .lex_with_options(FIXTURE, options)
+                .await
+                .expect("lex")
+                .all_tokens;
+            assert_eq!(
+                format(FIXTURE, &tokens, &facts),
+                format(
+                    FIXTURE,
+                    &tokens,
+                    &reference_facts(FIXTURE, &tokens, &all_tokens)
+                ),
+                "{policy:?}"
+            );
+
+            // File-leading trivia: two blank lines, a comment and another
+            // blank line before an indented `fn`.
+            let fact = |newlines_before, indent_col, had_line_comment_before| LayoutFacts {
+                newlines_before,
+                indent_col,
+                had_line_comment_before,
+            };
+            assert_eq!(facts[0], fact(4, 2, true));
+            // `add` follows `fn` on its line after spaces only.
+            assert_eq!(facts[1], fact(0, 2, false));
+            // A tab indents by one column.
+            let ret = tokens
+                .iter()
+                .position(|t| &FIXTURE[t.span.range()] == "return")
+                .unwrap();
+            assert_eq!(facts[ret], fact(1, 1, false));
+        }
+    });
+}
+
+#[test]
+fn layout_facts_cover_prefixes_blocks_and_saturation() {
+    common::block_on_gpu_with_timeout("lexer layout edge cases", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        // Enough lines that the line numbering scans across blocks.
+        let many_lines: String = (0..700)
+            .map(|i| format!("{}let v{i} = {i}; // c\n", " ".repeat(i % 9)))
+            .collect();
+        let sources = [
+            String::new(),
+            "// only a comment\n".into(),
+            "a b  c".into(),
+            "\u{feff}  a\n\tb c".into(),
+            "#!/usr/bin/env lanius\nfn main() {}\n".into(),
+            "#!/usr/bin/env lanius\n// c\n  x".into(),
+            "a /* x\n  y */ b".into(),
+            many_lines,
+        ];
+        for source in &sources {
+            checked_facts(&lexer, source).await;
+        }
+
+        let source = format!("{}{}x y", "\n".repeat(70_000), " ".repeat(70_000));
+        let (_, facts) = checked_facts(&lexer, &source).await;
+        assert_eq!(facts[0].newlines_before, u16::MAX);
+        assert_eq!(facts[0].indent_col, u16::MAX);
+        assert_eq!(facts[1].newlines_before, 0);
+        assert_eq!(facts[1].indent_col, u16::MAX);
+    });
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use std::sync::Mutex;
+
+use laniusc_compiler::{lexer::GpuLexer, logging::LEXER_GPU};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+static RECORDS: Mutex<Vec<(Level, String, String)>> = Mutex::new(Vec::new());
+
+struct CapturingLogger;
+
+impl Log for CapturingLogger {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        RECORDS.lock().unwrap().push((
+            record.level(),
+            record.target().to_string(),
+            record.args().to_string(),
+        ));
+    }
+
+    fn flush(&self) {}
+}
+
+#[test]
+fn one_lex_call_logs_its_host_stages_under_the_lexer_target() {
+    log::set_logger(&CapturingLogger).expect("install capturing logger");
+    log::set_max_level(LevelFilter::Debug);
+
+    common::block_on_gpu_with_timeout("lexer logging", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        RECORDS.lock().unwrap().clear();
+        let tokens = lexer
+            .lex("fn main() { let x = 1 + 2; return x; }\n")
+            .await
+            .expect("GPU lex");
+        assert!(!tokens.is_empty());
+    });
+
+    let records = RECORDS.lock().unwrap();
+    let lexer_messages: Vec<&str> = records
+        .iter()
+        .filter(|(level, target, _)| *level == Level::Debug && target == LEXER_GPU)
+        .map(|(_, _, message)| message.as_str())
+        .collect();
+    for stage in [
+        "allocate buffers",
+        "encode",
+        "submit lex.",
+        "map wait lex.",
+        "decode",
+        "lex ",
+    ] {
+        assert!(
+            lexer_messages.iter().any(|m| m.starts_with(stage)),
+            "no {stage:?} record in {lexer_messages:#?}"
+        );
+    }
+    let decode = lexer_messages
+        .iter()
+        .find(|m| m.starts_with("decode"))
+        .unwrap();
+    assert!(decode.ends_with("tokens)"), "{decode}");
+}
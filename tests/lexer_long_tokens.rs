@@ -0,0 +1,95 @@
+mod common;
+
+use laniusc_compiler::{
+    dev::generator::{SourceGenConfig, gen_source},
+    lexer::{
+        GpuLexer,
+        roundtrip::{detokenize_all, verify_kept_relex, verify_partition},
+        tables::tokens::TokenKind,
+        test_cpu::{lex_on_test_cpu, lex_on_test_cpu_all},
+    },
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+const MIB: usize = 1 << 20;
+
+/// `a /* ... */ b` around one block comment of `len` body bytes.
+fn one_comment(len: usize) -> String {
+    let body: String = "comment body\n".chars().cycle().take(len).collect();
+    format!("a /*{body}*/ b")
+}
+
+/// `s = "..."` around one string of `len` body bytes with escapes.
+fn one_string(len: usize) -> String {
+    let body: String = r#"text \" and \\ then \n "#.chars().cycle().take(len).collect();
+    let body = body.trim_end_matches('\\');
+    format!("s = \"{body}\";")
+}
+
+/// One-byte operators alternating with `count` block comments of `len` bytes.
+fn alternating(count: usize, len: usize) -> String {
+    let body = "x".repeat(len);
+    (0..count)
+        .map(|i| format!("{}/*{body}*/", ["+", ";", "(", ")"][i % 4]))
+        .collect()
+}
+
+/// Full GPU stream comparison plus the partition and round-trip invariants.
+async fn check(lexer: &GpuLexer, name: &str, src: &str) {
+    let (kept, all) = lexer.lex_both(src).await.expect("GPU lex");
+    assert_eq!(
+        kept,
+        lex_on_test_cpu(src).expect("test CPU oracle"),
+        "{name}"
+    );
+    assert_eq!(
+        all,
+        lex_on_test_cpu_all(src).expect("test CPU oracle"),
+        "{name}"
+    );
+    verify_partition(src, &all).unwrap_or_else(|err| panic!("{name}: {err}"));
+    assert!(
+        detokenize_all(src, &all) == src.as_bytes(),
+        "{name}: detokenized bytes differ"
+    );
+    verify_kept_relex(src, &kept).unwrap_or_else(|err| panic!("{name}: {err}"));
+}
+
+#[test]
+fn single_tokens_spanning_many_dfa_blocks_match_the_oracle() {
+    common::block_on_gpu_with_timeout("lexer long tokens", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+
+        // The kept token right after a multi-megabyte comment starts where
+        // the comment ends.
+        let src = one_comment(10 * MIB);
+        let kept = lexer.lex(&src).await.expect("GPU lex");
+        let last = kept.last().expect("kept tokens");
+        assert_eq!(
+            (kept.len(), last.kind, last.start()),
+            (2, TokenKind::Ident, src.len() - 1)
+        );
+        check(&lexer, "10 MiB comment", &src).await;
+
+        check(&lexer, "10 MiB string", &one_string(10 * MIB)).await;
+        check(&lexer, "1 MiB comments", &alternating(8, MIB)).await;
+        // Odd lengths put each boundary at a different offset in its block.
+        check(&lexer, "odd comments", &alternating(64, 256 * 255 + 7)).await;
+
+        for (profile, len) in [
+            ("long_comment", 4 * MIB),
+            ("long_string", 4 * MIB),
+            ("long_alternating", 8 * MIB),
+        ] {
+            let config = SourceGenConfig {
+                target_len: len,
+                ..SourceGenConfig::from_profile(profile).expect("profile")
+            };
+            let mut rng = StdRng::seed_from_u64(910);
+            for i in 0..2 {
+                let src = gen_source(&mut rng, &config);
+                check(&lexer, &format!("{profile} #{i}"), &src).await;
+            }
+        }
+    });
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use laniusc_compiler::{
+    gpu::{
+        device::{self, DeviceOptions, set_device_options},
+        limits::LimitError,
+    },
+    lexer::{GpuLexer, LexOptions, test_cpu::lex_on_test_cpu},
+};
+
+/// Storage binding size requested in place of the adapter's.
+const BINDING_CAP: u64 = 1 << 20;
+
+/// One token per byte, so the kept tokens reach past the low window.
+fn dense_source(len: usize) -> String {
+    "(a+b)*c;".chars().cycle().take(len).collect()
+}
+
+// One test per process: the options apply when the process device is created.
+#[test]
+fn low_binding_limits_split_tokens_out_or_fail_upfront() {
+    set_device_options(DeviceOptions {
+        max_storage_binding_bytes: Some(BINDING_CAP),
+        ..DeviceOptions::from_env()
+    });
+    common::block_on_gpu_with_timeout("lexer low device limits", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let granted = &device::global().granted_limits;
+        assert_eq!(granted.max_storage_buffer_binding_size, BINDING_CAP);
+        assert!(
+            device::global()
+                .adapter_limits
+                .max_storage_buffer_binding_size
+                >= BINDING_CAP
+        );
+
+        // 12-byte token records pass the cap, every other buffer fits it.
+        let source = dense_source(120_000);
+        let expected = common::tokens::shapes(&lex_on_test_cpu(&source).expect("test CPU oracle"));
+        assert!(expected.len() > 1 << 16, "{} tokens", expected.len());
+        for compress_readback in [false, true] {
+            let options = LexOptions {
+                single_submission_max_bytes: 0,
+                compress_readback,
+                ..LexOptions::default()
+            };
+            let output = lexer
+                .lex_with_options(&source, options)
+                .await
+                .expect("lex with tokens_out split");
+            assert_eq!(
+                common::tokens::shapes(&output.tokens),
+                expected,
+                "{options:?}"
+            );
+        }
+
+        // Resident consumers bind tokens_out whole, so they cannot take it.
+        let err = lexer
+            .with_resident_tokens(&source, |_, _, _| ())
+            .await
+            .expect_err("resident tokens past the binding limit");
+        assert!(
+            matches!(
+                err.downcast_ref::<LimitError>(),
+                Some(LimitError::InputExceedsDeviceLimits {
+                    granted: BINDING_CAP,
+                    ..
+                })
+            ),
+            "{err:#}"
+        );
+
+        // Past twice the cap no split fits, and nothing is allocated.
+        let err = lexer
+            .lex(&dense_source(200_000))
+            .await
+            .expect_err("input past the split limit");
+        let Some(LimitError::InputExceedsDeviceLimits {
+            granted,
+            suggestion,
+            ..
+        }) = err.downcast_ref::<LimitError>()
+        else {
+            panic!("expected InputExceedsDeviceLimits, got {err:#}");
+        };
+        assert_eq!(*granted, BINDING_CAP);
+        assert!(suggestion.contains("lex_range"), "{suggestion}");
+
+        // The lexer keeps working on inputs that fit.
+        let small = dense_source(4096);
+        let tokens = lexer.lex(&small).await.expect("lex after a limit error");
+        assert_eq!(
+            common::tokens::shapes(&tokens),
+            common::tokens::shapes(&lex_on_test_cpu(&small).expect("test CPU oracle"))
+        );
+    });
+}
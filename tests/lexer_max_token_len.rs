@@ -0,0 +1,95 @@
+mod common;
+
+use laniusc_compiler::{
+    dev::generator::gen_valid_source,
+    lexer::{
+        GpuLexer,
+        LexError,
+        LexOptions,
+        ReadbackMode,
+        tables::tokens::TokenKind,
+        test_cpu::{lex_on_test_cpu, lex_on_test_cpu_with_max_token_len},
+    },
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+const ONE_MIB: u32 = 1 << 20;
+
+fn capped(readback: ReadbackMode) -> LexOptions {
+    LexOptions {
+        readback,
+        max_token_len: ONE_MIB,
+        ..LexOptions::default()
+    }
+}
+
+#[test]
+fn block_comment_over_the_cap_fails_on_both_backends() {
+    common::block_on_gpu_with_timeout("lexer max token len", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let source = format!("let x = 1;\n/*{}*/\nlet y = x;\n", "c".repeat(3 << 20));
+        let expected = LexError::TokenTooLong {
+            kind: TokenKind::BlockComment,
+            start: 11,
+            limit: ONE_MIB,
+        };
+
+        assert_eq!(
+            lex_on_test_cpu_with_max_token_len(&source, ONE_MIB),
+            Err(expected.to_string())
+        );
+        for readback in [ReadbackMode::Full, ReadbackMode::CountOnly] {
+            let err = lexer
+                .lex_with_options(&source, capped(readback))
+                .await
+                .expect_err("3 MiB block comment must exceed a 1 MiB cap");
+            assert_eq!(
+                err.downcast_ref::<LexError>(),
+                Some(&expected),
+                "{readback:?}: {err:#}"
+            );
+        }
+
+        // The cap only applies to the call that sets it.
+        let uncapped = lexer.lex(&source).await.expect("uncapped GPU lex");
+        assert_eq!(uncapped.len(), lex_on_test_cpu(&source).unwrap().len());
+    });
+}
+
+#[test]
+fn normal_corpora_are_not_clamped() {
+    common::block_on_gpu_with_timeout("lexer max token len corpora", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let mut rng = StdRng::seed_from_u64(869);
+        let mut sources: Vec<String> = (0..4).map(|_| gen_valid_source(&mut rng, 5000)).collect();
+        let corpus = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cases/lexer");
+        for entry in std::fs::read_dir(corpus).expect("read tests/cases/lexer") {
+            let path = entry.expect("tests/cases/lexer entry").path();
+            if path.extension().is_some_and(|ext| ext == "lani") {
+                sources.push(std::fs::read_to_string(&path).expect("read corpus file"));
+            }
+        }
+
+        for source in &sources {
+            let spans = |tokens: Vec<laniusc_compiler::lexer::Token>| {
+                tokens
+                    .into_iter()
+                    .map(|t| (t.kind, t.start(), t.len()))
+                    .collect::<Vec<_>>()
+            };
+            let default = spans(lexer.lex(source).await.expect("default GPU lex"));
+            let capped = spans(
+                lexer
+                    .lex_with_options(source, capped(ReadbackMode::Full))
+                    .await
+                    .expect("capped GPU lex")
+                    .tokens,
+            );
+            assert_eq!(capped, default, "{source:?}");
+            assert_eq!(
+                lex_on_test_cpu_with_max_token_len(source, ONE_MIB),
+                lex_on_test_cpu(source)
+            );
+        }
+    });
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use laniusc_compiler::{
+    dev::generator::gen_valid_source,
+    lexer::{GpuLexer, MemoOptions},
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+const ALWAYS: MemoOptions = MemoOptions {
+    min_input_bytes: 0,
+    min_duplicate_percent: 0,
+};
+
+/// Data-table shaped source: a few distinct rows, each repeated, with a block
+/// comment spanning lines and a last line without a newline.
+fn table_source() -> String {
+    let mut src = String::from("\u{feff}let rows = [\n");
+    for i in 0..400 {
+        src.push_str(match i % 4 {
+            0 => "    (1, 2.5, \"north\"),\n",
+            1 => "    (3, 4.0, 'x'),   // spare\n",
+            2 => "    /* dropped\n    (5, 6, \"row\") */\n",
+            _ => "    (0..=9, r\"raw\", 0x1f),\n",
+        });
+    }
+    src.push_str("];\nlet total = rows.len()");
+    src
+}
+
+#[test]
+fn memoized_lex_matches_a_whole_lex() {
+    common::block_on_gpu_with_timeout("lexer memoized lex", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+
+        let table = table_source();
+        let out = lexer
+            .lex_memoized(&table, ALWAYS)
+            .await
+            .expect("GPU memoized lex");
+        assert!(out.memoized);
+        assert!(out.unique_lines < 16, "{} unique lines", out.unique_lines);
+        assert!(out.lexed_bytes < table.len() / 10);
+        assert_eq!(out.tokens, lexer.lex(&table).await.expect("GPU lex"));
+
+        let mut rng = StdRng::seed_from_u64(909);
+        for _ in 0..4 {
+            let unique = gen_valid_source(&mut rng, 4000);
+            let src: String = unique
+                .split_inclusive('\n')
+                .flat_map(|line| std::iter::repeat_n(line, 3))
+                .collect();
+            let out = lexer
+                .lex_memoized(&src, ALWAYS)
+                .await
+                .expect("GPU memoized lex");
+            assert_eq!(
+                out.tokens,
+                lexer.lex(&src).await.expect("GPU lex"),
+                "memoized={}",
+                out.memoized
+            );
+        }
+
+        // Below the default thresholds the input is lexed whole.
+        let short = "let x = 1;\n".repeat(8);
+        let out = lexer
+            .lex_memoized(&short, MemoOptions::default())
+            .await
+            .expect("GPU memoized lex");
+        assert!(!out.memoized);
+        assert_eq!(out.tokens, lexer.lex(&short).await.expect("GPU lex"));
+    });
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use laniusc_compiler::{
+    dev::operator_adjacency::{
+        cases,
+        kinds_by_line,
+        lex_cases_on_test_cpu,
+        render_snapshot,
+        snapshot_path,
+    },
+    lexer::GpuLexer,
+};
+
+// Cases that lex on the test CPU are joined one per line and lexed in a single
+// GPU pass; their kinds must match the CPU oracle and the checked-in snapshot.
+#[test]
+fn gpu_operator_adjacency_matches_test_cpu_and_snapshot() {
+    common::block_on_gpu_with_timeout("lexer operator adjacency", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let cases = cases();
+        let cpu = lex_cases_on_test_cpu(&cases);
+
+        let lexable: Vec<usize> = (0..cases.len()).filter(|&i| cpu[i].is_ok()).collect();
+        let source = lexable
+            .iter()
+            .map(|&i| cases[i].source())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let tokens = lexer.lex(&source).await.expect("GPU lex");
+        let lines = kinds_by_line(&source, tokens.iter().map(|t| (t.kind, t.start())));
+        assert_eq!(lines.len(), lexable.len());
+
+        let mut gpu = cpu.clone();
+        for (&i, kinds) in lexable.iter().zip(lines) {
+            assert_eq!(Ok(&kinds), cpu[i].as_ref(), "{:?}", cases[i].source());
+            gpu[i] = Ok(kinds);
+        }
+
+        let expected = std::fs::read_to_string(snapshot_path()).expect("read snapshot");
+        assert!(render_snapshot(&cases, &gpu) == expected);
+    });
+}
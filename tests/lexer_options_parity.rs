@@ -0,0 +1,82 @@
+mod common;
+
+use laniusc_compiler::lexer::{
+    GpuLexer,
+    LexError,
+    LexOptions,
+    LexOutput,
+    ReadbackMode,
+    test_cpu::lex_on_test_cpu_with_options,
+};
+
+const CORPUS: &[&str] = &[
+    "",
+    "let x = 1;",
+    "#!/usr/bin/env lanius\nfn main() { return 0; }\n",
+    "\u{feff}let r = 0..=n; for i in 1..2 { f(i)[0] }",
+    "let s = \"héllo 🦀\"; // ünïcödé\n/* block */ let t = r\"日本\";\n",
+    "let e = \"a\\n\\\"\\é\" + '\\'' + r\"\\q\";\n",
+    "pub fn long_name_for_a_function(a: i32, b: i32) -> i32 { a + b }\n",
+];
+
+/// Every combination of the options that change what the lexer returns.
+fn option_matrix() -> Vec<LexOptions> {
+    let mut matrix = Vec::new();
+    for readback in [
+        ReadbackMode::Full,
+        ReadbackMode::CountOnly,
+        ReadbackMode::None,
+    ] {
+        for capture_accept_states in [false, true] {
+            for all_tokens in [false, true] {
+                // 8 bytes fails on the longer tokens of the corpus.
+                for max_token_len in [u32::MAX, 8] {
+                    // 0 forces the two-submission readback, the only one
+                    // that can come back compressed.
+                    for (single_submission_max_bytes, compress_readback) in
+                        [(0, false), (0, true), (1 << 20, false)]
+                    {
+                        // Escape capture shares the per-byte state stream
+                        // with accept-state capture, so they toggle together.
+                        // Paranoia and layout facts each read a list back
+                        // next to the ALL stream, so they ride along with it.
+                        matrix.push(LexOptions {
+                            capture_accept_states,
+                            capture_string_escapes: capture_accept_states,
+                            paranoia_level: if all_tokens { 2 } else { 0 },
+                            layout_facts: all_tokens,
+                            readback,
+                            single_submission_max_bytes,
+                            compress_readback,
+                            max_token_len,
+                            all_tokens,
+                            report: false,
+                            ..LexOptions::default()
+                        });
+                    }
+                }
+            }
+        }
+    }
+    matrix
+}
+
+#[test]
+fn every_option_combination_matches_the_test_cpu_oracle() {
+    common::block_on_gpu_with_timeout("lexer options parity", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        for options in option_matrix() {
+            for source in CORPUS {
+                let expected = lex_on_test_cpu_with_options(source, options);
+                let actual: Result<LexOutput, String> = lexer
+                    .lex_with_options(source, options)
+                    .await
+                    .map_err(|err| match err.downcast_ref::<LexError>() {
+                        Some(err) => err.to_string(),
+                        None => format!("{err:#}"),
+                    });
+                assert_eq!(actual, expected, "{options:?}\nsource: {source:?}");
+            }
+        }
+    });
+}
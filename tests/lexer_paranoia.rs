@@ -0,0 +1,171 @@
+//! Paranoia mode: `tokens_build` lists suspect tokens and the driver relexes
+//! the bytes around them on the test CPU oracle. A token map entry planted on
+//! the device only shows what each mode notices.
+
+mod common;
+
+use std::time::Instant;
+
+use laniusc_compiler::{
+    dev::generator::gen_valid_source,
+    lexer::{
+        GpuLexer,
+        LexError,
+        LexOptions,
+        LexWarning,
+        paranoia::suspect_tokens_of,
+        tables::{dfa::S, tokens::TokenKind},
+        test_cpu::lex_on_test_cpu,
+    },
+    span::Span,
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+/// The last token, `22`, closes the file; the first `1` does not.
+const PLANTED_SOURCE: &str = "let a = 1;\nlet b = 22";
+
+fn quiet(options: LexOptions) -> LexOptions {
+    LexOptions {
+        report: false,
+        ..options
+    }
+}
+
+/// A lexer whose device token map accepts integer literals as identifiers.
+async fn lexer_with_planted_entry() -> GpuLexer {
+    let mut lexer = GpuLexer::new().await.expect("create GPU lexer");
+    lexer.debug_override_token_map(S::Int as usize, TokenKind::Ident as u32);
+    lexer
+}
+
+#[test]
+fn paranoia_catches_a_planted_table_entry_that_normal_mode_misses() {
+    common::block_on_gpu_with_timeout("paranoia planted entry", async move {
+        let lexer = lexer_with_planted_entry().await;
+        let expected = lex_on_test_cpu(PLANTED_SOURCE).expect("test CPU oracle");
+        let last = expected.len() - 1;
+
+        let normal = lexer
+            .lex_with_options(PLANTED_SOURCE, quiet(LexOptions::default()))
+            .await
+            .expect("normal lex");
+        assert_ne!(normal.tokens, expected, "the planted entry took no effect");
+        assert!(normal.warnings.is_empty(), "{:?}", normal.warnings);
+        assert!(normal.suspects.is_empty());
+
+        let paranoid = lexer
+            .lex_with_options(PLANTED_SOURCE, quiet(LexOptions::paranoia(1)))
+            .await
+            .expect("paranoid lex");
+        assert_eq!(paranoid.tokens, normal.tokens);
+        assert_eq!(
+            paranoid.warnings,
+            [LexWarning::SuspectMismatch {
+                token_index: last,
+                window: Span::from_usize(0, PLANTED_SOURCE.len()),
+            }]
+        );
+
+        // Every token is a suspect at level 3, so the first `1` is caught
+        // too.
+        let every = lexer
+            .lex_with_options(PLANTED_SOURCE, quiet(LexOptions::paranoia(3)))
+            .await
+            .expect("fully paranoid lex");
+        let caught: Vec<usize> = every
+            .warnings
+            .iter()
+            .filter_map(|warning| match warning {
+                LexWarning::SuspectMismatch { token_index, .. } => Some(*token_index),
+                LexWarning::Report(_) => None,
+            })
+            .collect();
+        let ints: Vec<usize> = expected
+            .iter()
+            .enumerate()
+            .filter(|(_, token)| token.kind == TokenKind::Int)
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(caught, ints);
+
+        let err = lexer
+            .lex_with_options(
+                PLANTED_SOURCE,
+                LexOptions {
+                    fail_on_suspect_mismatch: true,
+                    ..quiet(LexOptions::paranoia(1))
+                },
+            )
+            .await
+            .expect_err("paranoid lex set to fail");
+        assert_eq!(
+            err.downcast_ref::<LexError>(),
+            Some(&LexError::SuspectMismatch {
+                token_index: last,
+                window: Span::from_usize(0, PLANTED_SOURCE.len()),
+            })
+        );
+    });
+}
+
+#[test]
+fn suspect_lists_match_the_host_model_at_every_level() {
+    common::block_on_gpu_with_timeout("paranoia suspect lists", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let mut rng = StdRng::seed_from_u64(921);
+        let sources = [
+            gen_valid_source(&mut rng, 3000),
+            // One-byte token closing the file after an EMIT on the same byte.
+            "let x = y;\n;".to_string(),
+            // A string crossing a block edge.
+            format!("let s = \"{}\";", "a".repeat(300)),
+        ];
+        for source in &sources {
+            let tokens = lex_on_test_cpu(source).expect("test CPU oracle");
+            for level in 0..=3 {
+                let options = quiet(LexOptions::paranoia(level));
+                let output = lexer
+                    .lex_with_options(source, options)
+                    .await
+                    .expect("paranoid lex");
+                assert_eq!(
+                    output.suspects,
+                    suspect_tokens_of(&tokens, source.len(), &options),
+                    "level {level}\nsource: {source:?}"
+                );
+                assert!(output.warnings.is_empty(), "{:?}", output.warnings);
+            }
+        }
+    });
+}
+
+#[test]
+fn paranoia_overhead_is_recorded() {
+    common::block_on_gpu_with_timeout("paranoia overhead", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let source = gen_valid_source(&mut StdRng::seed_from_u64(9210), 1 << 20);
+        const RUNS: usize = 5;
+
+        let mut best = [f64::INFINITY; 3];
+        for _ in 0..RUNS {
+            for (level, best) in best.iter_mut().enumerate() {
+                let started = Instant::now();
+                let output = lexer
+                    .lex_with_options(&source, quiet(LexOptions::paranoia(level as u8)))
+                    .await
+                    .expect("lex");
+                *best = best.min(started.elapsed().as_secs_f64() * 1e3);
+                assert!(output.warnings.is_empty(), "{:?}", output.warnings);
+            }
+        }
+        // The target at level 1 is a few percent; recorded, not enforced.
+        for (level, ms) in best.iter().enumerate().skip(1) {
+            println!(
+                "paranoia level {level}: {ms:.3} ms vs {:.3} ms off ({:+.1}%) on {} bytes",
+                best[0],
+                (ms / best[0] - 1.0) * 100.0,
+                source.len()
+            );
+        }
+    });
+}
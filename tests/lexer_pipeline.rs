@@ -0,0 +1,109 @@
+mod common;
+
+use laniusc_compiler::lexer::{
+    GpuLexer,
+    LexOptions,
+    LexPipeline,
+    SubmissionPolicy,
+    passes::{FUSED_LEXER_STEPS, LATE_KEPT_FILTER_STEPS},
+};
+
+fn sources() -> Vec<String> {
+    let line = "fn f(a: i32) -> i32 { let b = a * 2; /* scale */ return b + 1; }\n";
+    let mut sources: Vec<String> = [
+        "",
+        "x",
+        "// only a comment",
+        "let s = \"héllo → wörld\"; /* ∀x */ let t = x[1] + f(2, 3);\n",
+    ]
+    .map(String::from)
+    .into();
+    // Inputs ending just before, at, and just past pair-block edges, where
+    // the fused pass's last lane and partial final block matter.
+    for len in [255, 256, 257, 511, 512, 513] {
+        sources.push(line.chars().cycle().take(len).collect());
+    }
+    sources.push(line.repeat(8192));
+    sources
+}
+
+#[test]
+fn opt_in_pipelines_match_the_split_pipeline() {
+    common::block_on_gpu_with_timeout("lexer pipelines", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        assert_eq!(lexer.pipeline(), LexPipeline::Split);
+        let options = LexOptions {
+            all_tokens: true,
+            capture_accept_states: true,
+            ..LexOptions::default()
+        };
+
+        for source in sources() {
+            lexer.set_pipeline(LexPipeline::Split);
+            let expected = lexer
+                .lex_with_options(&source, options)
+                .await
+                .expect("split lex");
+
+            for (pipeline, policy) in [LexPipeline::LateKeptFilter, LexPipeline::FusedPairSeed]
+                .into_iter()
+                .flat_map(|pipeline| {
+                    [
+                        SubmissionPolicy::Immediate,
+                        SubmissionPolicy::Yielding { chunk_passes: 1 },
+                    ]
+                    .map(|policy| (pipeline, policy))
+                })
+            {
+                lexer.set_submission_policy(policy);
+                lexer.set_pipeline(pipeline);
+                let opted = lexer
+                    .lex_with_options(&source, options)
+                    .await
+                    .unwrap_or_else(|err| panic!("{pipeline:?} {policy:?} lex: {err:#}"));
+                let what = format!("{pipeline:?} {policy:?} on a {}-byte source", source.len());
+                assert_eq!(opted.token_count, expected.token_count, "{what}");
+                assert_eq!(
+                    common::tokens::shapes(&opted.tokens),
+                    common::tokens::shapes(&expected.tokens),
+                    "{what}"
+                );
+                assert_eq!(
+                    common::tokens::shapes(&opted.all_tokens),
+                    common::tokens::shapes(&expected.all_tokens),
+                    "{what}"
+                );
+                assert_eq!(opted.accept_states, expected.accept_states, "{what}");
+            }
+            lexer.set_submission_policy(SubmissionPolicy::Immediate);
+        }
+        lexer.set_pipeline(LexPipeline::Split);
+    });
+}
+
+#[test]
+fn fused_pair_seed_pipeline_skips_pair_01() {
+    assert_eq!(FUSED_LEXER_STEPS.len() + 1, LATE_KEPT_FILTER_STEPS.len());
+    common::block_on_gpu_with_timeout("lexer fused dispatches", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        lexer.set_capture_dispatch_metadata(true);
+        lexer.set_pipeline(LexPipeline::FusedPairSeed);
+        let output = lexer
+            .lex_with_options("let value = 1 + 2;\n", LexOptions::default())
+            .await
+            .expect("lex");
+
+        let labels = output
+            .dispatch_records
+            .into_iter()
+            .map(|record| record.label)
+            .collect::<Vec<_>>();
+        assert!(
+            labels.contains(&"dfa_03_apply_block_prefix_seed_pairs".to_string()),
+            "{labels:?}"
+        );
+        for skipped in ["dfa_03_apply_block_prefix", "pair_01_sum_inblock"] {
+            assert!(!labels.contains(&skipped.to_string()), "{labels:?}");
+        }
+    });
+}
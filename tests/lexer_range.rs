@@ -0,0 +1,75 @@
+mod common;
+
+use laniusc_compiler::{
+    dev::generator::gen_valid_source,
+    lexer::{GpuLexer, LexRangeOptions, RangeToken, tables::tokens::TokenKind},
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+fn keys(tokens: &[RangeToken]) -> Vec<(TokenKind, usize, usize, bool, bool)> {
+    tokens
+        .iter()
+        .map(|t| {
+            (
+                t.token.kind,
+                t.token.start(),
+                t.token.len(),
+                t.clipped_start,
+                t.clipped_end,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn range_lex_matches_full_lex_filtered_to_the_range() {
+    common::block_on_gpu_with_timeout("lexer range lex", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let options = LexRangeOptions {
+            backward_slop: 64,
+            max_backward: 4096,
+            forward_slop: 16,
+            max_forward: 4096,
+        };
+        let mut rng = StdRng::seed_from_u64(887);
+        for _ in 0..4 {
+            let src = gen_valid_source(&mut rng, 6000);
+            let full = lexer.lex(&src).await.expect("GPU full lex");
+            for _ in 0..8 {
+                let a = src.floor_char_boundary(rng.random_range(0..=src.len()));
+                let b = src.floor_char_boundary(rng.random_range(a..=src.len()));
+                let out = lexer
+                    .lex_range_with_options(&src, a..b, options)
+                    .await
+                    .expect("GPU range lex");
+                assert!(out.window.range().start <= a && b <= out.window.range().end);
+                if out.approximate {
+                    continue;
+                }
+                let expected = full
+                    .iter()
+                    .filter(|t| t.start() < b && t.end() > a)
+                    .map(|t| (t.kind, t.start(), t.len(), t.start() < a, t.end() > b))
+                    .collect::<Vec<_>>();
+                assert_eq!(keys(&out.tokens), expected, "range {a}..{b}");
+            }
+        }
+    });
+}
+
+#[test]
+fn range_lex_reports_a_token_cut_by_the_range_end() {
+    common::block_on_gpu_with_timeout("lexer range lex clipped end", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let src = format!("let a = 1;\nlet {} = 2;\n", "b".repeat(600));
+        let ident = src.find('b').unwrap();
+        let out = lexer
+            .lex_range(&src, 0..ident + 3)
+            .await
+            .expect("GPU range lex");
+        let last = out.tokens.last().expect("tokens in range");
+        assert!(!out.approximate);
+        assert_eq!((last.token.start(), last.token.len()), (ident, 600));
+        assert!(last.clipped_end && !last.clipped_start);
+    });
+}
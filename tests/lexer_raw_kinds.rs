@@ -0,0 +1,67 @@
+mod common;
+
+use laniusc_compiler::lexer::{
+    GpuLexer,
+    LexOptions,
+    tables::tokens::TokenKind,
+    test_cpu::lex_on_test_cpu,
+};
+
+const SOURCES: [&str; 3] = [
+    "let r = 1..=n;\nfoo(1)\n",
+    "fn main() { for i in 0..10 { return i; } }",
+    "\u{feff}let x = 1.;",
+];
+
+#[test]
+fn raw_kinds_match_the_test_cpu_oracle_under_both_readback_strategies() {
+    common::block_on_gpu_with_timeout("lexer raw kinds", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        for source in SOURCES {
+            let expected: Vec<_> = lex_on_test_cpu(source)
+                .expect("test CPU oracle")
+                .iter()
+                .map(|t| (t.raw_kind, t.kind, t.start(), t.len()))
+                .collect();
+            for single_submission_max_bytes in [u64::MAX, 0] {
+                let output = lexer
+                    .lex_with_options(
+                        source,
+                        LexOptions {
+                            single_submission_max_bytes,
+                            ..LexOptions::default()
+                        },
+                    )
+                    .await
+                    .expect("GPU lex");
+                let actual: Vec<_> = output
+                    .tokens
+                    .iter()
+                    .map(|t| (t.raw_kind, t.kind, t.start(), t.len()))
+                    .collect();
+                assert_eq!(
+                    actual, expected,
+                    "{source:?} max_bytes={single_submission_max_bytes}"
+                );
+            }
+        }
+    });
+}
+
+#[test]
+fn all_stream_keeps_raw_kinds_on_merged_kept_tokens() {
+    common::block_on_gpu_with_timeout("lexer raw kinds all stream", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let (_, all) = lexer.lex_both("let x = 1").await.expect("GPU lex_both");
+        let first = &all[0];
+        assert_eq!(
+            (first.raw_kind, first.kind),
+            (TokenKind::Ident, TokenKind::Let)
+        );
+        assert!(
+            all.iter()
+                .filter(|t| t.kind != TokenKind::Let)
+                .all(|t| t.raw_kind == t.kind)
+        );
+    });
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use laniusc_compiler::lexer::{
+    GpuLexer,
+    LexOptions,
+    ReadbackMode,
+    test_cpu::lex_on_test_cpu_with_accept_states,
+};
+
+fn with_readback(readback: ReadbackMode) -> LexOptions {
+    LexOptions {
+        readback,
+        ..LexOptions::default()
+    }
+}
+
+// All-skip inputs produce a zero kept-token count, which returns early in the
+// counted modes before any token copy.
+#[test]
+fn count_only_and_no_readback_agree_with_full_readback() {
+    common::block_on_gpu_with_timeout("lexer readback modes", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+
+        for source in [
+            "",
+            " ",
+            "\n\t \n",
+            "// only a comment",
+            "/* block */\n// line\n",
+            "#!/usr/bin/env lanius\n",
+            "x",
+            "fn main() { let x = 0x2A + 1; return x; } // done\n",
+        ] {
+            let full = lexer
+                .lex_with_options(source, with_readback(ReadbackMode::Full))
+                .await
+                .expect("full readback");
+            assert_eq!(full.token_count, full.tokens.len(), "{source:?}");
+
+            let allocations = lexer.buffer_allocation_count();
+            let count_only = lexer
+                .lex_with_options(source, with_readback(ReadbackMode::CountOnly))
+                .await
+                .expect("count-only readback");
+            assert_eq!(count_only.token_count, full.tokens.len(), "{source:?}");
+            assert!(count_only.tokens.is_empty(), "{source:?}");
+
+            for _ in 0..2 {
+                let none = lexer
+                    .lex_with_options(source, with_readback(ReadbackMode::None))
+                    .await
+                    .expect("no readback");
+                assert_eq!(none.token_count, 0, "{source:?}");
+                assert!(none.tokens.is_empty(), "{source:?}");
+            }
+            assert_eq!(
+                lexer.buffer_allocation_count(),
+                allocations,
+                "{source:?} reallocated resident buffers"
+            );
+        }
+    });
+}
+
+#[test]
+fn single_submission_readback_slices_stale_tokens_by_count() {
+    common::block_on_gpu_with_timeout("lexer single-submission readback", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        // Same byte length, so the second lex reuses the resident token buffer
+        // that the first one filled far past the second one's count.
+        let dense = "a b c d e f g h i j k l m n o p q r s t u v w x y z";
+        let sparse = format!("{:<width$}", "x = 1", width = dense.len());
+
+        for source in [dense, sparse.as_str()] {
+            let (expected, expected_states) =
+                lex_on_test_cpu_with_accept_states(source).expect("test CPU oracle");
+            let expected: Vec<_> = expected
+                .iter()
+                .map(|t| (t.kind, t.start(), t.len()))
+                .collect();
+
+            for (max_bytes, submissions) in [(u64::MAX, 1), (0, 2)] {
+                let before = lexer.lex_submission_count();
+                let output = lexer
+                    .lex_with_options(
+                        source,
+                        LexOptions {
+                            capture_accept_states: true,
+                            single_submission_max_bytes: max_bytes,
+                            ..LexOptions::default()
+                        },
+                    )
+                    .await
+                    .expect("GPU lex");
+                assert_eq!(lexer.lex_submission_count() - before, submissions);
+                let actual: Vec<_> = output
+                    .tokens
+                    .iter()
+                    .map(|t| (t.kind, t.start(), t.len()))
+                    .collect();
+                assert_eq!(actual, expected, "{source:?} max_bytes={max_bytes}");
+                assert_eq!(output.accept_states, expected_states, "{source:?}");
+                assert_eq!(output.token_count, expected.len());
+            }
+        }
+    });
+}
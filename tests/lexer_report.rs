@@ -0,0 +1,120 @@
+mod common;
+
+use laniusc_compiler::lexer::{GpuLexer, LexOptions, LexReport, ReadbackMode, passes::LEXER_STEPS};
+
+const TINY: &str = "let x = 1;";
+
+fn reported(options: LexOptions) -> LexOptions {
+    LexOptions {
+        report: true,
+        ..options
+    }
+}
+
+async fn lex_report(lexer: &GpuLexer, source: &str, options: LexOptions) -> LexReport {
+    let output = lexer
+        .lex_with_options(source, reported(options))
+        .await
+        .expect("lex");
+    assert!(output.warnings.is_empty(), "{:?}", output.warnings);
+    let report = output.report.expect("report was requested");
+    assert_eq!(lexer.last_report(), Some(report));
+    report
+}
+
+#[test]
+fn tiny_input_reports_exact_counts() {
+    common::block_on_gpu_with_timeout("lex report tiny input", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let report = lex_report(&lexer, TINY, LexOptions::default()).await;
+        assert_eq!(
+            report,
+            LexReport {
+                input_len: 10,
+                // Buffers hold one slot per word-aligned input byte.
+                capacity: 12,
+                token_count: 5,
+                // `let`, `x`, `=`, `1`, `;` and three whitespace runs.
+                all_boundary_count: 8,
+                grew: true,
+                shrank: false,
+                overflow_flagged: false,
+                readback_mode: ReadbackMode::Full,
+                passes_recorded: LEXER_STEPS.len() as u32,
+                // The tokens fit the count readback's submission.
+                submissions: 1,
+                // 20 count-header bytes, then 12 token records of 12 bytes
+                // and 12 raw kinds of 4.
+                transfer_bytes: 212,
+                compressed: false,
+                decode_time: report.decode_time,
+            }
+        );
+    });
+}
+
+#[test]
+fn capacity_changes_report_growth_and_shrinkage() {
+    common::block_on_gpu_with_timeout("lex report capacity changes", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let large = "let value = 1 + 2;\n".repeat(256);
+
+        let first = lex_report(&lexer, TINY, LexOptions::default()).await;
+        assert!(first.grew && !first.shrank);
+
+        let grown = lex_report(&lexer, &large, LexOptions::default()).await;
+        assert!(grown.grew && !grown.shrank);
+        assert_eq!(grown.capacity, large.len());
+        assert_eq!(grown.token_count, 256 * 7);
+        assert_eq!(grown.all_boundary_count, 256 * 13);
+
+        let shrunk = lex_report(&lexer, TINY, LexOptions::default()).await;
+        assert!(!shrunk.grew && shrunk.shrank);
+        assert_eq!(shrunk.capacity, 12);
+
+        let reused = lex_report(&lexer, TINY, LexOptions::default()).await;
+        assert!(!reused.grew && !reused.shrank);
+        assert_eq!(reused.capacity, 12);
+    });
+}
+
+#[test]
+fn count_only_and_no_readback_report_what_was_read() {
+    common::block_on_gpu_with_timeout("lex report readback modes", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+
+        let count_only = LexOptions {
+            readback: ReadbackMode::CountOnly,
+            ..LexOptions::default()
+        };
+        let report = lex_report(&lexer, TINY, count_only).await;
+        assert_eq!(report.readback_mode, ReadbackMode::CountOnly);
+        assert_eq!(report.token_count, 5);
+        assert_eq!(report.all_boundary_count, 8);
+        assert_eq!(report.submissions, 1);
+
+        let none = LexOptions {
+            readback: ReadbackMode::None,
+            ..LexOptions::default()
+        };
+        let report = lex_report(&lexer, TINY, none).await;
+        assert_eq!(report.readback_mode, ReadbackMode::None);
+        assert_eq!((report.token_count, report.all_boundary_count), (0, 0));
+        assert_eq!(report.submissions, 1);
+        assert_eq!(report.passes_recorded, LEXER_STEPS.len() as u32);
+
+        // Calls that do not ask for a report leave the last one in place.
+        let output = lexer
+            .lex_with_options(
+                TINY,
+                LexOptions {
+                    report: false,
+                    ..LexOptions::default()
+                },
+            )
+            .await
+            .expect("lex");
+        assert!(output.report.is_none());
+        assert_eq!(lexer.last_report(), Some(report));
+    });
+}
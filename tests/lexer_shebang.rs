@@ -0,0 +1,31 @@
+mod common;
+
+use laniusc_compiler::lexer::{GpuLexer, test_cpu::lex_on_test_cpu};
+
+#[test]
+fn leading_shebang_line_is_skipped_like_test_cpu_oracle() {
+    common::block_on_gpu_with_timeout("lexer shebang", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+
+        for source in [
+            "#!/usr/bin/env lanius",
+            "#!/usr/bin/env lanius\n",
+            "#!/usr/bin/env lanius\nfn main() { return 0; }\n",
+            "#!\nx = 1",
+        ] {
+            let expected: Vec<_> = lex_on_test_cpu(source)
+                .expect("test CPU oracle")
+                .into_iter()
+                .map(|t| (t.kind, t.start(), t.len()))
+                .collect();
+            let actual: Vec<_> = lexer
+                .lex(source)
+                .await
+                .expect("GPU lex")
+                .into_iter()
+                .map(|t| (t.kind, t.start(), t.len()))
+                .collect();
+            assert_eq!(actual, expected, "{source:?}");
+        }
+    });
+}
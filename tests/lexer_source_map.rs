@@ -0,0 +1,46 @@
+mod common;
+
+use laniusc_compiler::lexer::{GpuLexer, SourceMap, lex_mapped, test_cpu::lex_on_test_cpu};
+
+#[test]
+fn mapped_tokens_match_lexing_each_piece_alone() {
+    common::block_on_gpu_with_timeout("lexer source map", async move {
+        let pieces = [
+            (
+                "prelude.lani",
+                "fn id(x: i32) -> i32 { return x; } // prelude",
+            ),
+            (
+                "user.lani",
+                "let a = 0x1F;\nlet b = id(a) /* note */ + 1;\n",
+            ),
+            ("epilogue.lani", "b"),
+        ];
+        let map = SourceMap::new(pieces);
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let mapped = lex_mapped(&lexer, &map).await.expect("GPU lex");
+
+        assert!(mapped.iter().all(|t| !t.crosses_boundary));
+        for (file_id, (name, text)) in pieces.into_iter().enumerate() {
+            let expected: Vec<_> = lex_on_test_cpu(text)
+                .expect("test CPU oracle")
+                .into_iter()
+                .map(|t| (t.kind, t.start(), t.len()))
+                .collect();
+            let actual: Vec<_> = mapped
+                .iter()
+                .filter(|t| t.file_id == file_id as u32)
+                .map(|t| (t.token.kind, t.span.start as usize, t.span.len() as usize))
+                .collect();
+            assert_eq!(actual, expected, "{name}");
+            assert_eq!(map.name(file_id as u32), Some(name));
+        }
+
+        let last = mapped.last().expect("epilogue token");
+        let location = map.resolve(last.token.start()).expect("resolve last token");
+        assert_eq!(
+            (location.file_id, location.line, location.column),
+            (2, 1, 1)
+        );
+    });
+}
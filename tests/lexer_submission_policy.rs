@@ -0,0 +1,64 @@
+mod common;
+
+use laniusc_compiler::lexer::{GpuLexer, LexOptions, SubmissionPolicy, passes::LEXER_STEPS};
+
+const SOURCES: [&str; 4] = [
+    "",
+    "// only a comment\n",
+    "fn main() { let x = 0x2A + 1; return x; } // done\n",
+    "let s = \"héllo → wörld\"; /* ∀x */ let t = x[1] + f(2, 3);\n",
+];
+
+fn large_source() -> String {
+    "fn f(a: i32) -> i32 { let b = a * 2; /* scale */ return b + 1; }\n".repeat(8192)
+}
+
+#[test]
+fn chunked_submission_policies_produce_identical_tokens() {
+    common::block_on_gpu_with_timeout("lexer submission policies", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let large = large_source();
+        let sources = SOURCES.iter().copied().chain([large.as_str()]);
+
+        for source in sources {
+            lexer.set_submission_policy(SubmissionPolicy::Immediate);
+            let expected = lexer
+                .lex_with_options(source, LexOptions::default())
+                .await
+                .expect("immediate lex");
+            let immediate_stats = lexer.last_lex_stats();
+
+            for policy in [
+                SubmissionPolicy::Batched { max_encoders: 3 },
+                SubmissionPolicy::Yielding { chunk_passes: 1 },
+                SubmissionPolicy::Yielding { chunk_passes: 3 },
+            ] {
+                lexer.set_submission_policy(policy);
+                let output = lexer
+                    .lex_with_options(source, LexOptions::default())
+                    .await
+                    .unwrap_or_else(|err| panic!("{policy:?} lex: {err:#}"));
+                assert_eq!(output.token_count, expected.token_count, "{policy:?}");
+                assert_eq!(
+                    common::tokens::shapes(&output.tokens),
+                    common::tokens::shapes(&expected.tokens),
+                    "{policy:?} changed tokens of a {}-byte source",
+                    source.len()
+                );
+
+                let extra = match policy {
+                    SubmissionPolicy::Yielding { chunk_passes } => {
+                        LEXER_STEPS.len().div_ceil(chunk_passes) - 1
+                    }
+                    _ => 0,
+                };
+                assert_eq!(
+                    lexer.last_lex_stats().submissions,
+                    immediate_stats.submissions + extra as u64,
+                    "{policy:?}"
+                );
+            }
+        }
+        lexer.set_submission_policy(SubmissionPolicy::Immediate);
+    });
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use laniusc_compiler::{
+    gpu::buffers::tracked_buffer_allocation_stats_by_label,
+    lexer::{GpuLexer, driver::DfaTable},
+};
+
+fn live_table_allocations(table: DfaTable) -> u64 {
+    tracked_buffer_allocation_stats_by_label()
+        .into_iter()
+        .find(|row| &*row.label == table.label())
+        .map_or(0, |row| row.allocations)
+}
+
+// Tables are uploaded on the first buffer allocation, shared by later
+// reallocations, reported under their own labels, and checked on demand.
+#[test]
+fn dfa_tables_upload_once_and_fail_their_check_after_host_corruption() {
+    common::block_on_gpu_with_timeout("lexer table residency", async move {
+        let mut lexer = GpuLexer::new().await.expect("create GPU lexer");
+        assert!(lexer.uploaded_tables().is_empty());
+        for table in DfaTable::ALL {
+            assert_eq!(live_table_allocations(table), 0, "{table:?}");
+        }
+
+        lexer.lex("let a = 1;").await.expect("GPU lex");
+        let big = "let a = 1;\n".repeat(20_000);
+        lexer.lex(&big).await.expect("GPU lex");
+        assert!(lexer.buffer_allocation_count() >= 2);
+        assert_eq!(lexer.uploaded_tables(), DfaTable::ALL);
+        for table in DfaTable::ALL {
+            assert_eq!(live_table_allocations(table), 1, "{table:?}");
+        }
+        lexer
+            .verify_tables()
+            .expect("fresh tables pass their check");
+
+        lexer.debug_corrupt_host_table(DfaTable::NextU8);
+        let err = lexer
+            .verify_tables()
+            .expect_err("corrupt host table")
+            .to_string();
+        assert!(
+            err.contains("lexer.table.next_u8") && err.contains("failed its upload check"),
+            "{err}"
+        );
+    });
+}
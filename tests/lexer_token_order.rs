@@ -0,0 +1,41 @@
+mod common;
+
+use laniusc_compiler::{
+    dev::generator::gen_valid_source,
+    lexer::{GpuLexer, test_cpu::lex_on_test_cpu},
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+#[test]
+fn final_byte_boundaries_pass_the_token_order_check() {
+    common::block_on_gpu_with_timeout("lexer token order", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let mut sources = ["a", ")", " ", ")a", "a)", " a", "a ", "//", "a//"]
+            .map(str::to_string)
+            .to_vec();
+        let mut rng = StdRng::seed_from_u64(854);
+        sources.extend((0..4).map(|_| gen_valid_source(&mut rng, 2000)));
+
+        for source in &sources {
+            assert_eq!(
+                lexer
+                    .debug_token_order_violation(source)
+                    .await
+                    .expect("GPU lex"),
+                None,
+                "{source:?}"
+            );
+            let gpu = lexer.lex(source).await.expect("GPU lex");
+            let expected = lex_on_test_cpu(source).expect("test CPU oracle");
+            let spans = gpu
+                .iter()
+                .map(|t| (t.kind, t.start(), t.len()))
+                .collect::<Vec<_>>();
+            let expected = expected
+                .iter()
+                .map(|t| (t.kind, t.start(), t.len()))
+                .collect::<Vec<_>>();
+            assert_eq!(spans, expected, "{source:?}");
+        }
+    });
+}
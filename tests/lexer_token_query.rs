@@ -0,0 +1,150 @@
+mod common;
+
+use laniusc_compiler::{
+    lexer::{
+        GpuLexer,
+        KindMask,
+        LexPipeline,
+        QuerySpec,
+        tables::tokens::TokenKind,
+        test_cpu::{lex_on_test_cpu, lex_on_test_cpu_all},
+    },
+    span::Span,
+};
+
+// Long enough for the match scan to span several 256-slot blocks and rounds.
+fn comment_heavy_source(lines: usize) -> String {
+    let mut source = String::new();
+    for i in 0..lines {
+        match i % 4 {
+            0 => source.push_str(&format!("// TODO item {i}\n")),
+            1 => source.push_str(&format!("fn f{i}() {{ /* body {i} */ return {i}; }}\n")),
+            2 => source.push_str(&format!("let x{i} = {i}; // trailing\n")),
+            _ => source.push_str("/* a\n   multi-line block */\n"),
+        }
+    }
+    source
+}
+
+fn comments() -> KindMask {
+    KindMask::empty()
+        .with(TokenKind::LineComment)
+        .with(TokenKind::BlockComment)
+}
+
+#[test]
+fn comment_query_matches_a_cpu_filter_of_the_full_token_list() {
+    common::block_on_gpu_with_timeout("lexer token query comments", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let source = comment_heavy_source(2000);
+        let ranges = [None, Some(Span::new(1000, 9000)), Some(Span::empty(0))];
+        for byte_range in ranges {
+            let spec = QuerySpec {
+                kinds_mask: comments(),
+                byte_range,
+                max_results: u32::MAX,
+            };
+            let (lo, hi) = byte_range.map_or((0, usize::MAX), |r| (r.range().start, r.range().end));
+            let expected: Vec<_> = lex_on_test_cpu_all(&source)
+                .expect("test CPU oracle")
+                .iter()
+                .filter(|t| spec.kinds_mask.contains(t.kind) && t.start() < hi && t.end() > lo)
+                .map(|t| (t.kind, t.start(), t.len()))
+                .collect();
+            let output = lexer
+                .with_device_tokens(&source, |device, queue, tokens| {
+                    tokens.query(device, queue, &spec)
+                })
+                .await
+                .expect("GPU lex")
+                .expect("GPU query");
+            let actual: Vec<_> = output
+                .tokens
+                .iter()
+                .map(|t| (t.kind, t.start(), t.len()))
+                .collect();
+            assert_eq!(actual, expected, "{byte_range:?}");
+            assert_eq!(output.total_matches as usize, expected.len());
+            assert!(!output.overflowed);
+        }
+    });
+}
+
+#[test]
+fn keyword_query_reports_retagged_kept_tokens() {
+    common::block_on_gpu_with_timeout("lexer token query keywords", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let source = comment_heavy_source(64);
+        let spec = QuerySpec {
+            kinds_mask: KindMask::empty().with(TokenKind::Fn),
+            byte_range: None,
+            max_results: 1024,
+        };
+        let expected: Vec<_> = lex_on_test_cpu(&source)
+            .expect("test CPU oracle")
+            .iter()
+            .filter(|t| t.kind == TokenKind::Fn)
+            .map(|t| (t.kind, t.raw_kind, t.start(), t.len()))
+            .collect();
+        assert!(!expected.is_empty());
+        // The query ranks the kept slots itself, whichever pipeline lexed.
+        for pipeline in [
+            LexPipeline::Split,
+            LexPipeline::LateKeptFilter,
+            LexPipeline::FusedPairSeed,
+        ] {
+            lexer.set_pipeline(pipeline);
+            let output = lexer
+                .with_device_tokens(&source, |device, queue, tokens| {
+                    tokens.query(device, queue, &spec)
+                })
+                .await
+                .expect("GPU lex")
+                .expect("GPU query");
+            let actual: Vec<_> = output
+                .tokens
+                .iter()
+                .map(|t| (t.kind, t.raw_kind, t.start(), t.len()))
+                .collect();
+            assert_eq!(actual, expected, "{pipeline:?}");
+        }
+        lexer.set_pipeline(LexPipeline::Split);
+    });
+}
+
+#[test]
+fn overflowing_query_returns_the_first_max_results_in_start_order() {
+    common::block_on_gpu_with_timeout("lexer token query overflow", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let source = comment_heavy_source(2000);
+        let all_comments: Vec<_> = lex_on_test_cpu_all(&source)
+            .expect("test CPU oracle")
+            .iter()
+            .filter(|t| comments().contains(t.kind))
+            .map(|t| (t.kind, t.start(), t.len()))
+            .collect();
+        let max_results = 300u32;
+        assert!(all_comments.len() > max_results as usize);
+
+        let spec = QuerySpec {
+            kinds_mask: comments(),
+            byte_range: None,
+            max_results,
+        };
+        let output = lexer
+            .with_device_tokens(&source, |device, queue, tokens| {
+                tokens.query(device, queue, &spec)
+            })
+            .await
+            .expect("GPU lex")
+            .expect("GPU query");
+        assert!(output.overflowed);
+        assert_eq!(output.total_matches as usize, all_comments.len());
+        let actual: Vec<_> = output
+            .tokens
+            .iter()
+            .map(|t| (t.kind, t.start(), t.len()))
+            .collect();
+        assert_eq!(actual, all_comments[..max_results as usize]);
+    });
+}
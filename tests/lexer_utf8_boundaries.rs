@@ -0,0 +1,44 @@
+mod common;
+
+use laniusc_compiler::lexer::{
+    GpuLexer,
+    LexOptions,
+    LexemePolicy,
+    check_token_boundaries,
+    test_cpu::lex_on_test_cpu,
+};
+
+const MULTIBYTE_SOURCE: &str = "\
+let greeting = \"héllo → wörld 🦀\";
+let cjk = \"日本語のテキスト\"; // ünïcödé comment
+/* block ∀x ∃y: x ≠ y */
+let mixed = [\"α\", \"βγ\", \"\", \"🦀🦀🦀\", r\"Ω\\n\"];
+fn main() { return 0; }
+";
+
+#[test]
+fn multibyte_string_tokens_stay_on_char_boundaries() {
+    common::block_on_gpu_with_timeout("lexer utf8 boundaries", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+
+        let expected = lex_on_test_cpu(MULTIBYTE_SOURCE).expect("test CPU oracle");
+        assert_eq!(check_token_boundaries(MULTIBYTE_SOURCE, &expected), []);
+
+        let actual = lexer
+            .lex_with_options(MULTIBYTE_SOURCE, LexOptions::default())
+            .await
+            .expect("GPU lex")
+            .tokens;
+        assert_eq!(check_token_boundaries(MULTIBYTE_SOURCE, &actual), []);
+
+        let actual_text: Vec<_> = actual
+            .iter()
+            .map(|t| t.lexeme(MULTIBYTE_SOURCE, LexemePolicy::Strict).unwrap())
+            .collect();
+        let expected_text: Vec<_> = expected
+            .iter()
+            .map(|t| t.lexeme(MULTIBYTE_SOURCE, LexemePolicy::Strict).unwrap())
+            .collect();
+        assert_eq!(actual_text, expected_text);
+    });
+}
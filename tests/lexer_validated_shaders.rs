@@ -0,0 +1,53 @@
+mod common;
+
+use laniusc_compiler::{
+    gpu::device::{self, DeviceOptions, ShaderPath, set_device_options},
+    lexer::{GpuLexer, test_cpu::lex_on_test_cpu},
+};
+
+// One test per process: the options apply when the process device is created.
+#[test]
+fn lexer_corpus_matches_oracle_on_validated_shaders() {
+    set_device_options(DeviceOptions {
+        spirv_passthrough: false,
+        ..DeviceOptions::from_env()
+    });
+    common::block_on_gpu_with_timeout("lexer validated shaders", async move {
+        let lexer = match GpuLexer::new().await {
+            Ok(lexer) => lexer,
+            // A kernel Naga cannot validate yet is a known gap, not a pass;
+            // say which one so the skip never goes unnoticed.
+            Err(err) if format!("{err:#}").contains("failed Naga validation") => {
+                eprintln!("SKIP lexer_corpus_matches_oracle_on_validated_shaders: {err:#}");
+                return;
+            }
+            Err(err) => panic!("create GPU lexer on validated shaders: {err:#}"),
+        };
+        assert_eq!(device::global().shader_path, ShaderPath::Validated);
+
+        let corpus = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cases/lexer");
+        let mut checked = 0;
+        for entry in std::fs::read_dir(corpus).expect("read tests/cases/lexer") {
+            let path = entry.expect("tests/cases/lexer entry").path();
+            if !path.extension().is_some_and(|ext| ext == "lani") {
+                continue;
+            }
+            let source = std::fs::read_to_string(&path).expect("read corpus file");
+            let expected: Vec<_> = lex_on_test_cpu(&source)
+                .unwrap_or_else(|err| panic!("{}: test CPU oracle: {err}", path.display()))
+                .into_iter()
+                .map(|t| (t.kind, t.start(), t.len()))
+                .collect();
+            let got: Vec<_> = lexer
+                .lex(&source)
+                .await
+                .unwrap_or_else(|err| panic!("{}: GPU lex: {err:#}", path.display()))
+                .into_iter()
+                .map(|t| (t.kind, t.start(), t.len()))
+                .collect();
+            assert_eq!(got, expected, "{}", path.display());
+            checked += 1;
+        }
+        assert!(checked > 0, "no .lani files under {corpus}");
+    });
+}
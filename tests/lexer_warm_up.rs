@@ -0,0 +1,70 @@
+mod common;
+
+use laniusc_compiler::{
+    lexer::{GpuLexer, LexSubmissionStats, test_cpu::lex_on_test_cpu},
+    parser::{driver::GpuParser, tables::PrecomputedParseTables},
+};
+
+const WARM_LEN: usize = 100_000;
+
+fn source_of_len(len: usize) -> String {
+    "let value = 1 + 2; // note\n"
+        .chars()
+        .cycle()
+        .take(len)
+        .collect()
+}
+
+#[test]
+fn lexes_after_warm_up_allocate_nothing_up_to_the_warmed_len() {
+    common::block_on_gpu_with_timeout("lexer warm-up", async move {
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let report = lexer.warm_up(WARM_LEN).await.expect("warm up");
+        assert!(report.buffers_bytes > 0);
+        assert!(report.bind_groups_created > 0);
+        assert!(report.passes_executed >= 1);
+
+        // The synthetic runs leave user-visible lex state alone.
+        assert_eq!(lexer.buffer_allocation_count(), 1);
+        assert_eq!(lexer.lex_submission_count(), 0);
+        assert_eq!(lexer.last_lex_stats(), LexSubmissionStats::default());
+
+        let misses = lexer.bind_group_cache_stats().misses;
+        for len in [0, 10, 300, 1_000, 70_000, WARM_LEN] {
+            let source = source_of_len(len);
+            let gpu = lexer.lex(&source).await.expect("GPU lex");
+            let expected = lex_on_test_cpu(&source).expect("test CPU oracle");
+            assert_eq!(gpu.len(), expected.len(), "len={len}");
+            for (got, want) in gpu.iter().zip(&expected) {
+                assert_eq!(
+                    (got.kind, got.start(), got.len()),
+                    (want.kind, want.start(), want.len()),
+                    "len={len}"
+                );
+            }
+            assert_eq!(lexer.buffer_allocation_count(), 1, "len={len}");
+            assert_eq!(lexer.bind_group_cache_stats().misses, misses, "len={len}");
+        }
+
+        lexer
+            .lex(&source_of_len(WARM_LEN + 1))
+            .await
+            .expect("GPU lex past the warmed len");
+        assert_eq!(lexer.buffer_allocation_count(), 2);
+    });
+}
+
+#[test]
+fn parser_warm_up_runs_the_resident_passes() {
+    common::block_on_gpu_with_timeout("parser warm-up", async move {
+        let parser = GpuParser::new().await.expect("create GPU parser");
+        let tables = PrecomputedParseTables::load_bin_bytes(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tables/parse_tables.bin"
+        )))
+        .expect("load parse tables");
+        let report = parser.warm_up(WARM_LEN, &tables).expect("warm up");
+        assert_eq!(report.passes_executed, 1);
+        assert!(report.bind_groups_created > 0);
+    });
+}
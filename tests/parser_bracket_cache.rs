@@ -0,0 +1,246 @@
+mod common;
+
+use std::sync::Arc;
+
+use laniusc_compiler::{
+    compiler::GpuCompiler,
+    gpu::passes_core::DispatchRecord,
+    lexer::driver::GpuLexer,
+    parser::{
+        BracketCache,
+        driver::{GpuParser, Ll1AcceptResult, ParseResult},
+        tables::PrecomputedParseTables,
+    },
+};
+
+fn tables() -> PrecomputedParseTables {
+    PrecomputedParseTables::load_bin_bytes(include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tables/parse_tables.bin"
+    )))
+    .expect("load precomputed parse tables")
+}
+
+async fn framed_kinds(lexer: &GpuLexer, source: &str) -> Vec<u32> {
+    let tokens = lexer.lex(source).await.expect("lex source");
+    let mut kinds: Vec<u32> = tokens.iter().map(|token| token.kind as u32).collect();
+    kinds.insert(0, 0);
+    kinds.push(0);
+    kinds
+}
+
+fn stack_effect_dispatches(result: &ParseResult) -> usize {
+    count_stack_effect(&result.dispatch_records)
+}
+
+fn count_stack_effect(records: &[DispatchRecord]) -> usize {
+    records
+        .iter()
+        .filter(|record| record.label.starts_with("brackets_"))
+        .count()
+}
+
+/// Parses `source` from the lexer's resident token buffers the way
+/// `compile_source` does, returning the parser status and the number of
+/// stack-effect dispatches the parse recorded.
+async fn resident_parse(
+    lexer: &GpuLexer,
+    parser: &GpuParser,
+    tables: &PrecomputedParseTables,
+    source: &str,
+) -> (Ll1AcceptResult, usize) {
+    lexer
+        .with_recorded_resident_tokens_after_count(
+            source,
+            |_, _, bufs, token_count, encoder, mut timer| {
+                let token_capacity = token_count.max(1);
+                let capacity = parser.measure_resident_partial_parse_capacity(
+                    token_capacity,
+                    &bufs.tokens_out,
+                    &bufs.token_count,
+                    Some(&bufs.token_file_id),
+                    tables,
+                )?;
+                let (check, recorded) = parser
+                    .record_checked_resident_ll1_hir_artifacts_with_tree_capacity_and_features(
+                        encoder,
+                        token_capacity,
+                        &bufs.tokens_out,
+                        &bufs.token_count,
+                        Some(&bufs.token_file_id),
+                        bufs.n,
+                        &bufs.in_bytes,
+                        tables,
+                        Some(capacity.tree_capacity),
+                        capacity.parser_feature_flags,
+                        capacity.bracket_key.as_ref(),
+                        &mut timer,
+                        |_, _, _| Ok::<_, anyhow::Error>(()),
+                    )?;
+                recorded?;
+                Ok::<_, anyhow::Error>(check)
+            },
+            |device, _, _, check| {
+                let status = check.read_status_result(device)?;
+                Ok((status, count_stack_effect(check.dispatch_records())))
+            },
+        )
+        .await
+        .expect("resident lex")
+        .expect("resident parse")
+}
+
+fn assert_same_status(cached: &Ll1AcceptResult, fresh: &Ll1AcceptResult, source: &str) {
+    assert_eq!(cached.accepted, fresh.accepted, "{source}");
+    assert_eq!(cached.error_pos, fresh.error_pos, "{source}");
+    assert_eq!(cached.error_code, fresh.error_code, "{source}");
+    assert_eq!(cached.detail, fresh.detail, "{source}");
+    assert_eq!(cached.steps, fresh.steps, "{source}");
+    assert_eq!(cached.emit_len, fresh.emit_len, "{source}");
+}
+
+fn assert_same_parse(cached: &ParseResult, fresh: &ParseResult, source: &str) {
+    assert_eq!(cached.brackets, fresh.brackets, "{source}");
+    assert_eq!(cached.ll1.accepted, fresh.ll1.accepted, "{source}");
+    assert_eq!(cached.ll1.error_code, fresh.ll1.error_code, "{source}");
+    assert_eq!(cached.ll1.detail, fresh.ll1.detail, "{source}");
+    assert_eq!(cached.sc_stream, fresh.sc_stream, "{source}");
+    assert_eq!(cached.emit_stream, fresh.emit_stream, "{source}");
+    assert_eq!(cached.node_kind, fresh.node_kind, "{source}");
+    assert_eq!(cached.parent, fresh.parent, "{source}");
+}
+
+#[test]
+fn comment_edits_reuse_bracket_matches() {
+    common::block_on_gpu_with_timeout("parser bracket cache", async move {
+        let tables = tables();
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let parser = GpuParser::new().await.expect("create GPU parser");
+        let fresh_parser = GpuParser::new().await.expect("create GPU parser");
+        let cache = Arc::new(BracketCache::default());
+        parser.set_bracket_cache(Some(Arc::clone(&cache)));
+        parser.set_capture_dispatch_metadata(true);
+
+        let cases = [
+            (
+                "fn main() { let x = (1 + 2) * 3; return x; } // first\n",
+                "fn main() { let x = (1 + 2) * 3; return x; } // edited comment\n",
+            ),
+            // Unclosed and overclosed streams round-trip their frontier.
+            (
+                "fn main() { return (1; // a\n",
+                "fn main() { return (1; // b, longer\n",
+            ),
+            ("fn main() { } } // a\n", "fn main() { } } /* b */\n"),
+        ];
+        for (before, after) in cases {
+            let first = parser
+                .parse(&framed_kinds(&lexer, before).await, &tables)
+                .await
+                .expect("first parse");
+            assert!(stack_effect_dispatches(&first) > 0, "{before}");
+
+            let kinds = framed_kinds(&lexer, after).await;
+            let hits = cache.stats().hits;
+            let second = parser.parse(&kinds, &tables).await.expect("second parse");
+            assert_eq!(cache.stats().hits, hits + 1, "{after}");
+            assert_eq!(stack_effect_dispatches(&second), 0, "{after}");
+
+            let fresh = fresh_parser
+                .parse(&kinds, &tables)
+                .await
+                .expect("fresh parse");
+            assert_same_parse(&second, &fresh, after);
+        }
+
+        // Changing a token kind misses and validates again.
+        let kinds = framed_kinds(&lexer, "fn main() { return [1; }\n").await;
+        let misses = cache.stats().misses;
+        let changed = parser.parse(&kinds, &tables).await.expect("parse");
+        assert_eq!(cache.stats().misses, misses + 1);
+        assert!(stack_effect_dispatches(&changed) > 0);
+        let fresh = fresh_parser.parse(&kinds, &tables).await.expect("parse");
+        assert_same_parse(&changed, &fresh, "changed kinds");
+
+        // Without a cache every parse validates.
+        parser.set_bracket_cache(None);
+        let uncached = parser.parse(&kinds, &tables).await.expect("parse");
+        assert!(stack_effect_dispatches(&uncached) > 0);
+        assert_eq!(cache.stats().misses, misses + 1);
+    });
+}
+
+#[test]
+fn resident_comment_edits_reuse_bracket_matches() {
+    common::block_on_gpu_with_timeout("resident parser bracket cache", async move {
+        let tables = tables();
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let parser = GpuParser::new().await.expect("create GPU parser");
+        let fresh_parser = GpuParser::new().await.expect("create GPU parser");
+        let cache = Arc::new(BracketCache::default());
+        parser.set_bracket_cache(Some(Arc::clone(&cache)));
+        parser.set_capture_dispatch_metadata(true);
+        fresh_parser.set_capture_dispatch_metadata(true);
+
+        let cases = [
+            (
+                "fn main() { let x = (1 + 2) * 3; return x; } // first\n",
+                "fn main() { let x = (1 + 2) * 3; return x; } // edited comment\n",
+            ),
+            (
+                "fn main() { return (1; // a\n",
+                "fn main() { return (1; // b, longer\n",
+            ),
+            ("fn main() { } } // a\n", "fn main() { } } /* b */\n"),
+        ];
+        for (before, after) in cases {
+            let (_, dispatches) = resident_parse(&lexer, &parser, &tables, before).await;
+            assert!(dispatches > 0, "{before}");
+
+            let hits = cache.stats().hits;
+            let (cached, dispatches) = resident_parse(&lexer, &parser, &tables, after).await;
+            assert_eq!(cache.stats().hits, hits + 1, "{after}");
+            assert_eq!(dispatches, 0, "{after}");
+
+            let (fresh, dispatches) = resident_parse(&lexer, &fresh_parser, &tables, after).await;
+            assert!(dispatches > 0, "{after}");
+            assert_same_status(&cached, &fresh, after);
+        }
+
+        // Changing a token kind misses and validates again.
+        let source = "fn main() { return [1; }\n";
+        let misses = cache.stats().misses;
+        let (changed, dispatches) = resident_parse(&lexer, &parser, &tables, source).await;
+        assert_eq!(cache.stats().misses, misses + 1);
+        assert!(dispatches > 0);
+        let (fresh, _) = resident_parse(&lexer, &fresh_parser, &tables, source).await;
+        assert_same_status(&changed, &fresh, source);
+    });
+}
+
+#[test]
+fn compiler_checks_reuse_bracket_matches_by_default() {
+    common::run_with_timeout("compiler bracket cache", || {
+        pollster::block_on(async {
+            let compiler = GpuCompiler::new().await.expect("create GPU compiler");
+            let cache = compiler
+                .bracket_cache()
+                .expect("compilers attach a bracket cache");
+
+            compiler
+                .type_check_source(
+                    "fn main() -> i32 { let x: i32 = (1 + 2) * 3; return x; } // a\n",
+                )
+                .await
+                .expect("first check");
+            let hits = cache.stats().hits;
+            compiler
+                .type_check_source(
+                    "fn main() -> i32 { let x: i32 = (1 + 2) * 3; return x; } // edited\n",
+                )
+                .await
+                .expect("second check");
+            assert_eq!(cache.stats().hits, hits + 1);
+        })
+    });
+}
@@ -0,0 +1,280 @@
+mod common;
+
+use std::sync::Mutex;
+
+use laniusc_compiler::{
+    gpu::{
+        buffers::{reset_tracked_buffer_allocation_peaks, tracked_buffer_allocation_peak_stats},
+        cancel::{CancelToken, Cancelled},
+    },
+    lexer::tables::tokens::TokenKind,
+    parser::{
+        ParserOptions,
+        autotune::{ChunkSize, MAX_CHUNK_TOKENS, MIN_CHUNK_TOKENS, suggest_chunk_size},
+        driver::{GpuParser, ParseResult},
+    },
+};
+
+// Allocation peaks are process-wide, so the tests in this file run one at a time.
+static SERIAL: Mutex<()> = Mutex::new(());
+
+/// Sentinel-framed stream of `n` tokens mixing paren and bracket groups,
+/// nested up to 64 deep so groups routinely straddle chunk boundaries.
+fn generated_kinds(n: usize, seed: u64) -> Vec<u32> {
+    let mut state = seed;
+    let mut next = move || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 33) as u32
+    };
+    let mut kinds = vec![0];
+    let mut open = Vec::new();
+    while kinds.len() + open.len() < n + 1 {
+        let roll = next() % 8;
+        if roll < 3 && open.len() < 64 {
+            let (opener, closer) = if next() % 2 == 0 {
+                (TokenKind::GroupLParen, TokenKind::GroupRParen)
+            } else {
+                (TokenKind::ArrayLBracket, TokenKind::ArrayRBracket)
+            };
+            kinds.push(opener as u32);
+            open.push(closer as u32);
+        } else if roll < 6
+            && let Some(closer) = open.pop()
+        {
+            kinds.push(closer);
+        } else {
+            kinds.push(TokenKind::Ident as u32);
+        }
+    }
+    kinds.extend(open.into_iter().rev());
+    kinds.push(0);
+    kinds
+}
+
+fn pair_outputs(result: &ParseResult) -> (Vec<[u32; 4]>, &[u32], &[u32], &[u32]) {
+    let headers = result
+        .headers
+        .iter()
+        .map(|h| [h.push_len, h.emit_len, h.pop_tag, h.pop_count])
+        .collect();
+    (
+        headers,
+        &result.sc_stream,
+        &result.emit_stream,
+        &result.brackets.match_for_index,
+    )
+}
+
+fn bracket_summary(result: &ParseResult) -> (bool, i32, i32, u32, Option<u32>) {
+    let b = &result.brackets;
+    (
+        b.valid,
+        b.final_depth,
+        b.min_depth,
+        b.valid_up_to,
+        b.first_unclosed_push,
+    )
+}
+
+#[test]
+fn chunked_parse_matches_monolithic_parse_at_small_chunk_sizes() {
+    let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+    common::block_on_gpu_with_timeout("parser chunked small", async move {
+        let tables = common::parse_tables::echo_bracket_tables();
+        let parser = GpuParser::new().await.expect("create GPU parser");
+
+        let mut broken = generated_kinds(5_000, 7);
+        // A stray closer and an unclosed opener near chunk boundaries.
+        broken.insert(1_000, TokenKind::GroupRParen as u32);
+        broken.insert(4_001, TokenKind::ArrayLBracket as u32);
+
+        for kinds in [generated_kinds(5_000, 3), broken] {
+            let whole = parser
+                .parse_classified_token_kinds(&kinds, &tables)
+                .await
+                .expect("monolithic parse");
+            // 1 and 2 put a chunk boundary between every pair; 1000 and 1001
+            // land the boundary on either side of the inserted tokens.
+            for size in [1, 2, 999, 1000, 1001, kinds.len()] {
+                let chunked = parser
+                    .parse_chunked(kinds.chunks(size), &tables)
+                    .await
+                    .expect("chunked parse");
+                assert_eq!(pair_outputs(&chunked), pair_outputs(&whole), "size {size}");
+                assert_eq!(
+                    bracket_summary(&chunked),
+                    bracket_summary(&whole),
+                    "size {size}"
+                );
+            }
+        }
+    });
+}
+
+#[test]
+fn chunked_parse_of_five_million_tokens_matches_and_stays_chunk_sized() {
+    let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+    common::block_on_gpu_with_timeout("parser chunked 5M", async move {
+        let tables = common::parse_tables::echo_bracket_tables();
+        let parser = GpuParser::new().await.expect("create GPU parser");
+        let kinds = generated_kinds(5_000_000, 11);
+
+        // A single chunk runs the monolithic pair pipeline over the stream.
+        let whole = parser
+            .parse_chunked([&kinds[..]], &tables)
+            .await
+            .expect("monolithic parse");
+        assert!(whole.brackets.valid);
+
+        for size in [1_000, 65_536, 1_000_000] {
+            let baseline = reset_tracked_buffer_allocation_peaks().bytes;
+            let chunked = parser
+                .parse_chunked(kinds.chunks(size), &tables)
+                .await
+                .expect("chunked parse");
+            let chunked_peak = tracked_buffer_allocation_peak_stats().bytes - baseline;
+            assert_eq!(pair_outputs(&chunked), pair_outputs(&whole), "size {size}");
+            assert_eq!(
+                bracket_summary(&chunked),
+                bracket_summary(&whole),
+                "size {size}"
+            );
+
+            let baseline = reset_tracked_buffer_allocation_peaks().bytes;
+            parser
+                .parse_chunked([&kinds[..size + 1]], &tables)
+                .await
+                .expect("single chunk");
+            let single_peak = tracked_buffer_allocation_peak_stats().bytes - baseline;
+            assert!(
+                chunked_peak <= single_peak + single_peak / 4,
+                "size {size}: chunked peak {chunked_peak} vs single-chunk peak {single_peak}"
+            );
+        }
+    });
+}
+
+#[test]
+fn sized_chunked_parse_matches_and_auto_sizes_from_timings() {
+    let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+    common::block_on_gpu_with_timeout("parser chunked sized", async move {
+        let tables = common::parse_tables::echo_bracket_tables();
+        let parser = GpuParser::new_with(ParserOptions {
+            gpu_timing: true,
+            ..ParserOptions::default()
+        })
+        .await
+        .expect("create GPU parser");
+        let kinds = generated_kinds(200_000, 13);
+        let whole = parser
+            .parse_chunked([&kinds[..]], &tables)
+            .await
+            .expect("monolithic parse");
+
+        let fixed = parser
+            .parse_chunked_sized(&kinds, ChunkSize::Tokens(4_096), &tables)
+            .await
+            .expect("fixed-size chunked parse");
+        assert_eq!(pair_outputs(&fixed), pair_outputs(&whole));
+        assert_eq!(bracket_summary(&fixed), bracket_summary(&whole));
+
+        // Without a timed history, Auto falls back to the default size.
+        let untimed = ChunkSize::Auto {
+            history: &[],
+            target_ms: 2.0,
+        };
+        let auto = parser
+            .parse_chunked_sized(&kinds, untimed, &tables)
+            .await
+            .expect("auto-sized chunked parse");
+        assert_eq!(pair_outputs(&auto), pair_outputs(&whole));
+
+        // Devices without timestamp queries leave `timings` empty.
+        let Some(timings) = &fixed.timings else {
+            return;
+        };
+        assert_eq!(timings.work.n_pairs as usize, kinds.len() - 1);
+        assert_eq!(timings.work.total_sc as usize, fixed.sc_stream.len());
+        assert_eq!(timings.work.total_emit as usize, fixed.emit_stream.len());
+        assert_eq!(
+            timings.timer_health.dropped, 0,
+            "{:?}",
+            timings.timer_health
+        );
+        assert!(
+            timings.passes.iter().any(|pass| pass.label == "llp_pairs"),
+            "{:?}",
+            timings.passes
+        );
+        let history = std::slice::from_ref(&fixed);
+        let size = suggest_chunk_size(history, 2.0);
+        assert!((MIN_CHUNK_TOKENS..=MAX_CHUNK_TOKENS).contains(&size));
+        let tuned = parser
+            .parse_chunked_sized(
+                &kinds,
+                ChunkSize::Auto {
+                    history,
+                    target_ms: 2.0,
+                },
+                &tables,
+            )
+            .await
+            .expect("tuned chunked parse");
+        assert_eq!(pair_outputs(&tuned), pair_outputs(&whole));
+    });
+}
+
+#[test]
+fn cancelled_chunked_parse_stops_between_chunks() {
+    let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+    common::block_on_gpu_with_timeout("parser chunked cancel", async move {
+        let tables = common::parse_tables::echo_bracket_tables();
+        let parser = GpuParser::new().await.expect("create GPU parser");
+        let kinds = generated_kinds(10_000, 5);
+
+        let token = CancelToken::new();
+        let chunks = kinds.chunks(1_000).enumerate().map(|(i, chunk)| {
+            if i == 3 {
+                token.cancel();
+            }
+            chunk
+        });
+        let Err(err) = parser
+            .parse_chunked_cancellable(chunks, &tables, token.clone())
+            .await
+        else {
+            panic!("cancelled before the fourth chunk, but the parse finished");
+        };
+        assert_eq!(
+            err.downcast_ref::<Cancelled>(),
+            Some(&Cancelled {
+                stage: "parser.chunk".into(),
+                submitted: false,
+            }),
+            "{err:#}"
+        );
+
+        let Err(err) = parser.parse_cancellable(&kinds, &tables, token).await else {
+            panic!("already cancelled, but the parse finished");
+        };
+        assert!(
+            err.downcast_ref::<Cancelled>()
+                .is_some_and(|cancelled| !cancelled.submitted),
+            "{err:#}"
+        );
+
+        // The parser is unaffected by the abandoned calls.
+        let whole = parser
+            .parse_classified_token_kinds(&kinds, &tables)
+            .await
+            .expect("monolithic parse");
+        let chunked = parser
+            .parse_chunked_cancellable(kinds.chunks(1_000), &tables, CancelToken::new())
+            .await
+            .expect("uncancelled chunked parse");
+        assert_eq!(pair_outputs(&chunked), pair_outputs(&whole));
+        assert_eq!(bracket_summary(&chunked), bracket_summary(&whole));
+    });
+}
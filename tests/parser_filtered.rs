@@ -0,0 +1,179 @@
+mod common;
+
+use laniusc_compiler::{
+    lexer::{GpuLexer, KindMask, QuerySpec, tables::tokens::TokenKind},
+    parser::{
+        driver::{GpuParser, ParseResult},
+        tables::PrecomputedParseTables,
+    },
+};
+
+/// Raw kinds of `source`, with whitespace between tokens and a line comment
+/// after every `)`.
+fn trivia_laden_kinds(source: &str) -> Vec<u32> {
+    let mut kinds = Vec::new();
+    for token in source.split_whitespace() {
+        kinds.push(match token {
+            "(" => TokenKind::GroupLParen as u32,
+            ")" => TokenKind::GroupRParen as u32,
+            _ => TokenKind::Ident as u32,
+        });
+        kinds.push(TokenKind::White as u32);
+        if token == ")" {
+            kinds.push(TokenKind::LineComment as u32);
+        }
+    }
+    kinds
+}
+
+fn is_trivia(kind: u32) -> bool {
+    kind == TokenKind::White as u32 || kind == TokenKind::LineComment as u32
+}
+
+fn kept_kinds(kinds: &[u32]) -> Vec<u32> {
+    kinds
+        .iter()
+        .copied()
+        .filter(|&kind| !is_trivia(kind))
+        .collect()
+}
+
+fn observable(result: &ParseResult) -> (Vec<[u32; 4]>, Vec<u32>, Vec<u32>, Vec<u32>, bool) {
+    let headers = result
+        .headers
+        .iter()
+        .map(|h| [h.push_len, h.emit_len, h.pop_tag, h.pop_count])
+        .collect();
+    (
+        headers,
+        result.sc_stream.clone(),
+        result.emit_stream.clone(),
+        result.brackets.match_for_index.clone(),
+        result.brackets.valid,
+    )
+}
+
+#[test]
+fn filtered_parse_matches_a_parse_of_the_kept_stream() {
+    common::block_on_gpu_with_timeout("parser filtered echo tables", async move {
+        let tables = common::parse_tables::echo_bracket_tables();
+        let parser = GpuParser::new().await.expect("create GPU parser");
+
+        // The last input spans enough blocks for several scan rounds.
+        let long = "( a ( b ) ) c ".repeat(12_000);
+        for source in ["", "a", "( a ( b ) )", "a ( b ) ) c ( d", long.as_str()] {
+            let kinds = trivia_laden_kinds(source);
+            let kept = kept_kinds(&kinds);
+            let filtered = parser
+                .parse_filtered(&kinds, &KindMask::kept(), &tables)
+                .await
+                .expect("filtered parse");
+            let direct = parser
+                .parse_tokens(&kept, &tables)
+                .await
+                .expect("kept-stream parse");
+            assert_eq!(
+                observable(&filtered),
+                observable(&direct),
+                "{}",
+                &source[..source.len().min(32)]
+            );
+
+            let expected_map: Vec<u32> = (0..kinds.len() as u32)
+                .filter(|&i| !is_trivia(kinds[i as usize]))
+                .collect();
+            assert_eq!(filtered.token_index_map, expected_map);
+        }
+    });
+}
+
+#[test]
+fn index_map_relates_bracket_matches_to_input_tokens() {
+    common::block_on_gpu_with_timeout("parser filtered index map", async move {
+        let tables = common::parse_tables::echo_bracket_tables();
+        let parser = GpuParser::new().await.expect("create GPU parser");
+
+        // Input indices: ( 0, a 2, ( 4, b 6, ) 8, ) 11, c 14.
+        let kinds = trivia_laden_kinds("( a ( b ) ) c");
+        let result = parser
+            .parse_filtered(&kinds, &KindMask::kept(), &tables)
+            .await
+            .expect("filtered parse");
+        assert!(result.brackets.valid);
+
+        let input_index = |sc: usize| {
+            let token = result
+                .token_of_stack_change(sc, &tables)
+                .expect("stack change belongs to a token");
+            result.input_token_index(token).expect("mapped token")
+        };
+        let matches: Vec<(usize, usize)> = result
+            .brackets
+            .match_for_index
+            .iter()
+            .enumerate()
+            .filter(|&(sc, &other)| sc < other as usize)
+            .map(|(sc, &other)| (input_index(sc), input_index(other as usize)))
+            .collect();
+        assert_eq!(matches, [(0, 11), (4, 8)]);
+        for (open, close) in matches {
+            assert_eq!(kinds[open], TokenKind::GroupLParen as u32);
+            assert_eq!(kinds[close], TokenKind::GroupRParen as u32);
+        }
+    });
+}
+
+#[test]
+fn filtering_the_lexer_all_stream_matches_its_kept_stream() {
+    common::block_on_gpu_with_timeout("parser filtered lexer streams", async move {
+        let tables = PrecomputedParseTables::load_bin_bytes(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tables/parse_tables.bin"
+        )))
+        .expect("load precomputed parse tables");
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let parser = GpuParser::new().await.expect("create GPU parser");
+
+        let source = "// header\nfn main() {\n    /* block */ let x = (1 + 2) * 3; // tail\n    \
+                      return x;\n}\n";
+        let all = lexer
+            .with_device_tokens(source, |device, queue, tokens| {
+                tokens.query(
+                    device,
+                    queue,
+                    &QuerySpec {
+                        kinds_mask: KindMask::all(),
+                        byte_range: None,
+                        max_results: u32::MAX,
+                    },
+                )
+            })
+            .await
+            .expect("GPU lex")
+            .expect("GPU query");
+        let all_kinds: Vec<u32> = all.tokens.iter().map(|t| t.kind as u32).collect();
+        let kept_kinds: Vec<u32> = lexer
+            .lex(source)
+            .await
+            .expect("GPU lex")
+            .iter()
+            .map(|t| t.kind as u32)
+            .collect();
+        assert!(all_kinds.len() > kept_kinds.len());
+
+        let filtered = parser
+            .parse_filtered(&all_kinds, &KindMask::kept(), &tables)
+            .await
+            .expect("filtered parse");
+        let direct = parser
+            .parse_tokens(&kept_kinds, &tables)
+            .await
+            .expect("kept-stream parse");
+        assert_eq!(observable(&filtered), observable(&direct));
+        assert_eq!(filtered.token_index_map.len(), kept_kinds.len());
+        for (parsed, &kind) in kept_kinds.iter().enumerate() {
+            let input = filtered.input_token_index(parsed).expect("mapped token");
+            assert_eq!(all_kinds[input], kind);
+        }
+    });
+}
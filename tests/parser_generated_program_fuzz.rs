@@ -186,9 +186,9 @@ async fn semantic_rejection_diagnostic(
                     .map(|parser_i| {
                         let raw = parser_i.checked_sub(1).and_then(|token_i| {
                             tokens.get(token_i).map(|token| {
-                                let end = token.start.saturating_add(token.len);
+                                let end = token.end();
                                 let text = source
-                                    .get(token.start..end)
+                                    .get(token.start()..end)
                                     .unwrap_or("<invalid utf8 boundary>");
                                 format!("{:?} {text:?}", token.kind)
                             })
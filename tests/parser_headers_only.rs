@@ -0,0 +1,93 @@
+mod common;
+
+use std::sync::Mutex;
+
+use laniusc_compiler::{
+    gpu::buffers::{reset_tracked_buffer_allocation_peaks, tracked_buffer_allocation_peak_stats},
+    lexer::tables::tokens::TokenKind,
+    parser::{buffers::ActionHeader, driver::GpuParser},
+};
+
+// Allocation peaks are process-wide, so the tests in this file run one at a time.
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn bracket_kinds(groups: usize) -> Vec<u32> {
+    let mut kinds = vec![0];
+    for _ in 0..groups {
+        kinds.extend([
+            TokenKind::LParen as u32,
+            TokenKind::Ident as u32,
+            TokenKind::RParen as u32,
+        ]);
+    }
+    kinds.push(0);
+    kinds
+}
+
+fn header_words(headers: &[ActionHeader]) -> Vec<[u32; 4]> {
+    headers
+        .iter()
+        .map(|h| [h.push_len, h.emit_len, h.pop_tag, h.pop_count])
+        .collect()
+}
+
+#[test]
+fn headers_match_full_parse_on_host_and_gpu_offset_paths() {
+    let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+    common::block_on_gpu_with_timeout("parser headers only", async move {
+        let tables = common::parse_tables::echo_bracket_tables();
+        let parser = GpuParser::new().await.expect("create GPU parser");
+
+        // 7 pairs take the host offset scan; 20k bracket groups take the GPU scan.
+        for groups in [2, 20_000] {
+            let kinds = bracket_kinds(groups);
+            let full = parser
+                .parse_classified_token_kinds(&kinds, &tables)
+                .await
+                .expect("full parse");
+            let headers = parser.headers(&kinds, &tables).await.expect("headers");
+
+            assert_eq!(header_words(&headers.headers), header_words(&full.headers));
+            assert_eq!(headers.total_sc as usize, full.sc_stream.len());
+            assert_eq!(headers.total_emit as usize, full.emit_stream.len());
+            let mut sc = 0;
+            for (i, h) in headers.headers.iter().enumerate() {
+                assert_eq!(headers.sc_offsets[i], sc, "groups={groups} pair={i}");
+                sc += h.push_len + h.pop_count;
+            }
+        }
+    });
+}
+
+#[test]
+fn headers_path_never_allocates_stream_buffers() {
+    let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+    common::block_on_gpu_with_timeout("parser headers only memory", async move {
+        let tables = common::parse_tables::echo_bracket_tables();
+        let parser = GpuParser::new().await.expect("create GPU parser");
+        let kinds = bracket_kinds(20_000);
+        // Warm the shared action table so neither measurement includes its upload.
+        let headers = parser.headers(&kinds, &tables).await.expect("warm headers");
+
+        let baseline = reset_tracked_buffer_allocation_peaks().bytes;
+        parser.headers(&kinds, &tables).await.expect("headers");
+        let headers_peak = tracked_buffer_allocation_peak_stats().bytes - baseline;
+
+        let baseline = reset_tracked_buffer_allocation_peaks().bytes;
+        parser
+            .parse_classified_token_kinds(&kinds, &tables)
+            .await
+            .expect("full parse");
+        let full_peak = tracked_buffer_allocation_peak_stats().bytes - baseline;
+
+        // out_sc and match_for_index hold one word per stack change; out_emit
+        // and out_emit_pos hold one word per emitted production.
+        let stream_bytes =
+            4 * (2 * u64::from(headers.total_sc) + 2 * u64::from(headers.total_emit));
+        assert!(stream_bytes > 0);
+        assert!(
+            headers_peak + stream_bytes <= full_peak,
+            "headers-only peak {headers_peak} + streams {stream_bytes} exceeds full parse peak {full_peak}"
+        );
+    });
+}
@@ -151,6 +151,7 @@ use laniusc_compiler::{
             validate_hir_type_argument_records,
             validate_hir_type_records,
         },
+        retag,
         syntax::{GpuSyntaxCode, GpuSyntaxError},
         tables::PrecomputedParseTables,
     },
@@ -200,11 +201,7 @@ const TK_BOUND_TYPE_AMPERSAND: u32 = 179;
 const TK_EOF: u32 = 0;
 
 fn syntax_token(kind: TokenKind, pos: usize) -> Token {
-    Token {
-        kind,
-        start: pos,
-        len: 1,
-    }
+    Token::new(kind, pos, 1)
 }
 
 fn resolve_expr_forest_node(parsed: &DecodedParserHirItemReadbacks, start: usize) -> usize {
@@ -602,7 +599,7 @@ fn parse_resident_source_pack(sources: &[&str]) -> DecodedParserHirItemReadbacks
                         &tables,
                         recorded.tree_capacity,
                         |parse_buffers| recorded.readbacks.map_and_decode(device, parse_buffers),
-                    )?;
+                    )??;
                     parser.release_current_resident_buffers();
                     Ok::<_, anyhow::Error>(decoded)
                 },
@@ -681,7 +678,7 @@ fn parse_resident_source_pack_fn_returns(
                             |parse_buffers| {
                                 recorded.readbacks.map_and_decode(device, parse_buffers)
                             },
-                        )?;
+                        )??;
                         parser.release_current_resident_buffers();
                         Ok::<_, anyhow::Error>(decoded)
                     },
@@ -1331,6 +1328,76 @@ fn f(p0: i32) {
     );
 }
 
+#[test]
+fn parser_semantic_tokens_retag_open_delimiters_across_trivia() {
+    let cases: [(&str, &[TokenKind]); 6] = [
+        (
+            "foo (x)",
+            &[TokenKind::Ident, TokenKind::CallLParen, TokenKind::Ident],
+        ),
+        (
+            "foo/*c*/(x)",
+            &[TokenKind::Ident, TokenKind::CallLParen, TokenKind::Ident],
+        ),
+        (
+            "foo // c\n(x)",
+            &[TokenKind::Ident, TokenKind::CallLParen, TokenKind::Ident],
+        ),
+        (
+            "foo\n\n(x)",
+            &[TokenKind::Ident, TokenKind::CallLParen, TokenKind::Ident],
+        ),
+        (
+            "(foo)(x)",
+            &[
+                TokenKind::GroupLParen,
+                TokenKind::Ident,
+                TokenKind::GroupRParen,
+                TokenKind::CallLParen,
+            ],
+        ),
+        (
+            "arr /*x*/ [0]",
+            &[TokenKind::Ident, TokenKind::IndexLBracket, TokenKind::Int],
+        ),
+    ];
+    for (expr, expected) in cases {
+        let source = format!("fn f(foo: i32, x: i32, arr: [i32; 1]) {{\n    let y = {expr};\n}}\n");
+        let kinds = parser_semantic_token_kinds_for_source(&source);
+        let expected: Vec<u32> = expected.iter().map(|&kind| kind as u32).collect();
+        assert!(
+            kinds
+                .windows(expected.len())
+                .any(|window| window == expected),
+            "{expr:?}: {:?}",
+            token_kind_names(&kinds)
+        );
+
+        // In the expression, the host mirror of the adjacency rule agrees on
+        // every raw open delimiter, and the lexer leaves those delimiters raw.
+        // The signature's parameter and type-array delimiters are contextual.
+        let lexed = common::block_on_gpu_with_timeout("lex retag source", {
+            let source = source.clone();
+            async move {
+                let lexer = GpuLexer::new().await.expect("create GPU lexer");
+                lexer.lex(&source).await.expect("GPU lex")
+            }
+        });
+        let kept: Vec<TokenKind> = lexed.iter().map(|token| token.kind).collect();
+        let mirrored = retag::retag_open_delimiters(&kept);
+        let expr_start = kept
+            .iter()
+            .position(|&kind| kind == TokenKind::Assign)
+            .expect("let initializer");
+        for (i, token) in lexed.iter().enumerate().skip(expr_start) {
+            if matches!(token.kind, TokenKind::LParen | TokenKind::LBracket) {
+                assert_eq!(token.raw_kind, token.kind, "{expr:?} token {i}");
+                assert_eq!(kinds[i], mirrored[i] as u32, "{expr:?} token {i}");
+            }
+        }
+    }
+}
+
 #[test]
 fn parser_semantic_tokens_classify_call_after_struct_literal() {
     let source = r#"
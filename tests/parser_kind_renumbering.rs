@@ -0,0 +1,113 @@
+mod common;
+
+use laniusc_compiler::{
+    lexer::tables::tokens::{N_KINDS, TokenKind},
+    parser::{
+        buffers::ActionHeader,
+        driver::GpuParser,
+        kindmap::renumber_kinds,
+        tables::{PrecomputedParseTables, encode_pop, encode_push},
+    },
+};
+
+const PAREN_SYMBOL: u32 = N_KINDS;
+
+/// Tables over five kinds of the full lexer kind space: parens push and pop a
+/// stack symbol, and every non-sentinel token emits its own kind.
+fn sparse_paren_tables() -> PrecomputedParseTables {
+    let kinds = [
+        0,
+        TokenKind::LParen as u32,
+        TokenKind::RParen as u32,
+        TokenKind::Ident as u32,
+        TokenKind::Comma as u32,
+    ];
+    let mut tables = PrecomputedParseTables::new(N_KINDS, N_KINDS);
+    tables.prod_arity = vec![0; N_KINDS as usize];
+    for &prev in &kinds {
+        for &this in &kinds[1..] {
+            if this == TokenKind::LParen as u32 {
+                tables.set_sc_for_pair(prev, this, &[encode_push(PAREN_SYMBOL)]);
+            } else if this == TokenKind::RParen as u32 {
+                tables.set_sc_for_pair(prev, this, &[encode_pop(PAREN_SYMBOL)]);
+            }
+            tables.set_pp_for_pair(prev, this, &[this]);
+        }
+    }
+    tables
+}
+
+fn paren_kinds(groups: usize) -> Vec<u32> {
+    let mut kinds = vec![0];
+    for i in 0..groups {
+        kinds.extend([
+            TokenKind::LParen as u32,
+            TokenKind::Ident as u32,
+            TokenKind::Comma as u32,
+            TokenKind::Ident as u32,
+            TokenKind::RParen as u32,
+        ]);
+        if i % 7 == 3 {
+            // Unreferenced by the tables: dropped by renumbering, empty before it.
+            kinds.push(TokenKind::Star as u32);
+        }
+    }
+    kinds.push(0);
+    kinds
+}
+
+fn header_words(headers: &[ActionHeader]) -> Vec<[u32; 4]> {
+    headers
+        .iter()
+        .map(|h| [h.push_len, h.emit_len, h.pop_tag, h.pop_count])
+        .collect()
+}
+
+#[test]
+fn renumbered_tables_parse_identically_on_gpu() {
+    common::block_on_gpu_with_timeout("parser kind renumbering", async move {
+        let tables = sparse_paren_tables();
+        let dense = renumber_kinds(&tables);
+        assert_eq!(dense.n_kinds, 5);
+        let parser = GpuParser::new().await.expect("create GPU parser");
+
+        for groups in [1, 9, 4_000] {
+            let kinds = paren_kinds(groups);
+            let source = parser
+                .parse_classified_token_kinds(&kinds, &tables)
+                .await
+                .expect("parse with lexer-numbered tables");
+            let renumbered = parser
+                .parse_classified_token_kinds(&kinds, &dense)
+                .await
+                .expect("parse with renumbered tables");
+
+            assert_eq!(
+                header_words(&renumbered.headers),
+                header_words(&source.headers)
+            );
+            assert_eq!(renumbered.emit_stream, source.emit_stream);
+            assert_eq!(
+                source.sc_stream,
+                tables.test_cpu_stack_change_stream(&kinds)
+            );
+            assert_eq!(
+                renumbered.sc_stream,
+                dense.test_cpu_stack_change_stream(&kinds)
+            );
+            assert_eq!(renumbered.brackets.valid, source.brackets.valid);
+            assert_eq!(
+                renumbered.brackets.match_for_index,
+                source.brackets.match_for_index
+            );
+            assert_eq!(renumbered.node_kind, source.node_kind);
+            assert_eq!(renumbered.parent, source.parent);
+
+            let headers = parser.headers(&kinds, &dense).await.expect("headers");
+            assert_eq!(
+                header_words(&headers.headers),
+                header_words(&source.headers)
+            );
+        }
+    });
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use laniusc_compiler::{lexer::tables::tokens::TokenKind, parser::driver::GpuParser};
+
+fn raw_bracket_kinds(source: &str) -> Vec<u32> {
+    let mut kinds = vec![0];
+    kinds.extend(source.split_whitespace().map(|token| match token {
+        "(" => TokenKind::LParen as u32,
+        ")" => TokenKind::RParen as u32,
+        _ => TokenKind::Ident as u32,
+    }));
+    kinds.push(0);
+    kinds
+}
+
+#[test]
+fn stray_closer_truncates_to_the_clean_prefix() {
+    common::block_on_gpu_with_timeout("parser partial results", async move {
+        let tables = common::parse_tables::echo_bracket_tables();
+        let parser = GpuParser::new().await.expect("create GPU parser");
+
+        let broken = parser
+            .parse(&raw_bracket_kinds("a ( b ) ) c ( d"), &tables)
+            .await
+            .expect("parse with stray closer");
+        let clean = parser
+            .parse(&raw_bracket_kinds("a ( b )"), &tables)
+            .await
+            .expect("clean parse");
+
+        assert!(!broken.brackets.valid);
+        assert_eq!(broken.brackets.valid_up_to, 2);
+        assert_eq!(broken.sc_stream[2] & 1, 0, "frontier must be a pop");
+        assert_eq!(broken.brackets.first_unclosed_push, Some(3));
+
+        let slice = broken.truncate_to_valid();
+        assert_eq!(slice.sc_stream, &clean.sc_stream[..]);
+        assert_eq!(slice.emit_stream, &clean.emit_stream[..]);
+        assert_eq!(slice.headers.len(), 4);
+    });
+}
+
+#[test]
+fn balanced_parse_is_valid_to_the_end() {
+    common::block_on_gpu_with_timeout("parser partial results balanced", async move {
+        let tables = common::parse_tables::echo_bracket_tables();
+        let parser = GpuParser::new().await.expect("create GPU parser");
+
+        let result = parser
+            .parse(&raw_bracket_kinds("( a ( b ) )"), &tables)
+            .await
+            .expect("balanced parse");
+
+        assert!(result.brackets.valid);
+        assert_eq!(result.brackets.valid_up_to as usize, result.sc_stream.len());
+        assert_eq!(result.brackets.first_unclosed_push, None);
+        assert_eq!(
+            result.truncate_to_valid().emit_stream,
+            &result.emit_stream[..]
+        );
+    });
+}
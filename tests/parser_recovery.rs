@@ -0,0 +1,158 @@
+mod common;
+
+use laniusc_compiler::{
+    lexer::{driver::GpuLexer, tables::tokens::TokenKind},
+    parser::{
+        driver::{GpuParser, ParseResult},
+        recovery::{RecoveredParse, parse_with_recovery, parse_with_recovery_bounded},
+        tables::PrecomputedParseTables,
+    },
+};
+
+fn tables() -> PrecomputedParseTables {
+    PrecomputedParseTables::load_bin_bytes(include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tables/parse_tables.bin"
+    )))
+    .expect("load precomputed parse tables")
+}
+
+/// `fn main` with `n` let statements, and the same function with a token
+/// inserted into each statement in `corrupted`: a doubled `=` in even ones,
+/// a stray literal before the `let` in odd ones.
+fn sources(n: usize, corrupted: &[usize]) -> (String, String) {
+    let (mut clean, mut broken) = (String::from("fn main() {\n"), String::from("fn main() {\n"));
+    for i in 0..n {
+        clean.push_str(&format!("    let v{i} = {i};\n"));
+        broken.push_str(&if !corrupted.contains(&i) {
+            format!("    let v{i} = {i};\n")
+        } else if i % 2 == 0 {
+            format!("    let v{i} = = {i};\n")
+        } else {
+            format!("    7 let v{i} = {i};\n")
+        });
+    }
+    clean.push_str("}\n");
+    broken.push_str("}\n");
+    (clean, broken)
+}
+
+async fn raw_kinds(lexer: &GpuLexer, source: &str) -> Vec<u32> {
+    let tokens = lexer.lex(source).await.expect("lex source");
+    tokens.iter().map(|token| token.kind as u32).collect()
+}
+
+/// Input kinds of the recovered segments, in order.
+fn kept_kinds(recovered: &RecoveredParse, kinds: &[u32]) -> Vec<u32> {
+    recovered
+        .segments
+        .iter()
+        .flat_map(|segment| kinds[segment.tokens.clone()].iter().copied())
+        .collect()
+}
+
+fn pair_outputs(result: &ParseResult) -> (Vec<[u32; 4]>, &[u32], &[u32], &[u32]) {
+    let headers = result
+        .headers
+        .iter()
+        .map(|h| [h.push_len, h.emit_len, h.pop_tag, h.pop_count])
+        .collect();
+    (
+        headers,
+        &result.sc_stream,
+        &result.emit_stream,
+        &result.brackets.match_for_index,
+    )
+}
+
+#[test]
+fn recovered_segments_parse_like_the_text_with_errors_removed() {
+    common::block_on_gpu_with_timeout("parser recovery", async move {
+        let tables = tables();
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let parser = GpuParser::new().await.expect("create GPU parser");
+
+        let fifty = (0..50).collect::<Vec<_>>();
+        for (n, corrupted) in [(4, &[2][..]), (6, &[0, 3, 5]), (60, &fifty)] {
+            let (clean, broken) = sources(n, corrupted);
+            let clean_kinds = raw_kinds(&lexer, &clean).await;
+            let broken_kinds = raw_kinds(&lexer, &broken).await;
+            let recovered = parse_with_recovery(&broken_kinds, &tables, &parser)
+                .await
+                .expect("recovering parse");
+
+            // One error per corruption, each skipping just the inserted token.
+            assert_eq!(recovered.errors.len(), corrupted.len(), "{n} statements");
+            for error in &recovered.errors {
+                assert_eq!(error.skipped, error.token..error.token + 1, "{error:?}");
+                assert!(
+                    [TokenKind::Assign as u32, TokenKind::Int as u32]
+                        .contains(&broken_kinds[error.token]),
+                    "{error:?}"
+                );
+            }
+            assert_eq!(kept_kinds(&recovered, &broken_kinds), clean_kinds);
+            assert!(recovered.recovered(), "{n} statements");
+
+            let whole = parser
+                .parse_tokens(&clean_kinds, &tables)
+                .await
+                .expect("parse clean source");
+            assert!(whole.brackets.valid);
+            assert_eq!(
+                pair_outputs(&recovered.result),
+                pair_outputs(&whole),
+                "{n} statements"
+            );
+            let last = recovered.segments.last().expect("segments");
+            assert_eq!(last.tokens.end, broken_kinds.len());
+            assert_eq!(last.pairs.end, whole.headers.len());
+        }
+    });
+}
+
+#[test]
+fn valid_input_is_one_segment_without_errors() {
+    common::block_on_gpu_with_timeout("parser recovery valid input", async move {
+        let tables = tables();
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let parser = GpuParser::new().await.expect("create GPU parser");
+
+        let (clean, _) = sources(5, &[]);
+        let kinds = raw_kinds(&lexer, &clean).await;
+        let recovered = parse_with_recovery(&kinds, &tables, &parser)
+            .await
+            .expect("recovering parse");
+        let whole = parser
+            .parse_tokens(&kinds, &tables)
+            .await
+            .expect("parse clean source");
+        assert!(recovered.errors.is_empty());
+        assert!(recovered.recovered());
+        assert_eq!(recovered.segments.len(), 1);
+        assert_eq!(recovered.segments[0].tokens, 0..kinds.len());
+        assert_eq!(recovered.segments[0].pairs, 0..whole.headers.len());
+        assert_eq!(pair_outputs(&recovered.result), pair_outputs(&whole));
+    });
+}
+
+#[test]
+fn recovery_stops_at_the_error_bound() {
+    common::block_on_gpu_with_timeout("parser recovery error bound", async move {
+        let tables = tables();
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let parser = GpuParser::new().await.expect("create GPU parser");
+
+        let (_, broken) = sources(6, &[0, 3, 5]);
+        let kinds = raw_kinds(&lexer, &broken).await;
+        let recovered = parse_with_recovery_bounded(&kinds, &tables, &parser, 2)
+            .await
+            .expect("recovering parse");
+        assert_eq!(recovered.errors.len(), 2);
+        assert!(!recovered.recovered());
+        // The rest of the input, third error included, is kept as one segment.
+        let last = recovered.segments.last().expect("segments");
+        assert!(last.tokens.start <= recovered.errors[1].skipped.end);
+        assert_eq!(last.tokens.end, kinds.len());
+    });
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use laniusc_compiler::{
+    lexer::tables::tokens::{N_KINDS, TokenKind},
+    parser::{
+        driver::{ErrorRegion, GpuParser, localize_error},
+        tables::build_mvp_precomputed_tables,
+    },
+};
+
+fn raw_bracket_kinds(source: &str) -> Vec<u32> {
+    let mut kinds = vec![0];
+    kinds.extend(source.split_whitespace().map(|token| match token {
+        "(" => TokenKind::LParen as u32,
+        ")" => TokenKind::RParen as u32,
+        _ => TokenKind::Ident as u32,
+    }));
+    kinds.push(0);
+    kinds
+}
+
+#[test]
+fn reverse_parse_localizes_stray_closer() {
+    common::block_on_gpu_with_timeout("parser reverse error localization", async move {
+        let tables = build_mvp_precomputed_tables(N_KINDS, vec![0]);
+        let rev_tables = tables.reverse();
+        let parser = GpuParser::new().await.expect("create GPU parser");
+        let kinds = raw_bracket_kinds("( ( a ) ) ) ( ( b ) )");
+
+        let forward = parser.parse(&kinds, &tables).await.expect("forward parse");
+        let backward = parser
+            .parse_reverse(&kinds, &rev_tables)
+            .await
+            .expect("reverse parse");
+
+        assert!(!forward.brackets.valid);
+        assert_eq!(backward.sc_stream, forward.sc_stream);
+        assert_eq!(
+            localize_error(&forward, &backward),
+            ErrorRegion { start: 5, end: 6 }
+        );
+    });
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use laniusc_compiler::{
+    lexer::tables::tokens::TokenKind,
+    parser::{
+        driver::{GpuParser, ParseResult},
+        tables::SentinelError,
+    },
+};
+
+fn bracket_kinds(source: &str) -> Vec<u32> {
+    source
+        .split_whitespace()
+        .map(|token| match token {
+            "(" => TokenKind::LParen as u32,
+            ")" => TokenKind::RParen as u32,
+            _ => TokenKind::Ident as u32,
+        })
+        .collect()
+}
+
+fn observable(result: &ParseResult) -> (Vec<[u32; 4]>, Vec<u32>, Vec<u32>, Vec<u32>, bool) {
+    let headers = result
+        .headers
+        .iter()
+        .map(|h| [h.push_len, h.emit_len, h.pop_tag, h.pop_count])
+        .collect();
+    (
+        headers,
+        result.sc_stream.clone(),
+        result.emit_stream.clone(),
+        result.brackets.match_for_index.clone(),
+        result.brackets.valid,
+    )
+}
+
+#[test]
+fn parse_tokens_matches_a_hand_framed_parse() {
+    common::block_on_gpu_with_timeout("parser sentinel framing", async move {
+        let tables = common::parse_tables::echo_bracket_tables();
+        let parser = GpuParser::new().await.expect("create GPU parser");
+
+        for source in ["", "a", "( a ( b ) )", "a ( b ) ) c ( d"] {
+            let kinds = bracket_kinds(source);
+            let mut framed = vec![0];
+            framed.extend_from_slice(&kinds);
+            framed.push(0);
+            assert_eq!(tables.wrap_input(&kinds).unwrap(), framed);
+
+            let wrapped = parser
+                .parse_tokens(&kinds, &tables)
+                .await
+                .expect("parse unframed tokens");
+            let manual = parser.parse(&framed, &tables).await.expect("framed parse");
+            assert_eq!(observable(&wrapped), observable(&manual), "{source:?}");
+        }
+    });
+}
+
+#[test]
+fn parse_tokens_rejects_sentinels_in_the_input() {
+    common::block_on_gpu_with_timeout("parser sentinel rejection", async move {
+        let tables = common::parse_tables::echo_bracket_tables();
+        let parser = GpuParser::new().await.expect("create GPU parser");
+
+        let mut kinds = bracket_kinds("( a b )");
+        kinds.insert(2, 0);
+        let err = parser
+            .parse_tokens(&kinds, &tables)
+            .await
+            .err()
+            .expect("mid-stream sentinel");
+        assert_eq!(
+            err.downcast_ref::<SentinelError>(),
+            Some(&SentinelError::MidStream {
+                index: 2,
+                sentinel: 0
+            })
+        );
+
+        let mut framed = bracket_kinds("( a )");
+        framed.push(0);
+        let err = parser
+            .parse_tokens(&framed, &tables)
+            .await
+            .err()
+            .expect("double-appended sentinel");
+        assert_eq!(
+            err.downcast_ref::<SentinelError>(),
+            Some(&SentinelError::AlreadyWrapped {
+                index: 3,
+                sentinel: 0
+            })
+        );
+    });
+}
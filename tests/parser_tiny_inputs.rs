@@ -0,0 +1,87 @@
+mod common;
+
+use laniusc_compiler::{
+    lexer::tables::tokens::TokenKind,
+    parser::driver::{GpuParser, ParseResult},
+};
+
+fn assert_empty_and_valid(result: &ParseResult) {
+    assert!(result.sc_stream.is_empty());
+    assert!(result.brackets.valid);
+    assert_eq!(result.brackets.final_depth, 0);
+    assert_eq!(result.brackets.min_depth, 0);
+    assert!(result.brackets.match_for_index.is_empty());
+    assert_eq!(result.brackets.valid_up_to, 0);
+    assert_eq!(result.brackets.first_unclosed_push, None);
+}
+
+#[test]
+fn lone_sentinel_parses_to_an_empty_valid_result() {
+    common::block_on_gpu_with_timeout("parser lone sentinel", async move {
+        let tables = common::parse_tables::echo_bracket_tables();
+        let parser = GpuParser::new().await.expect("create GPU parser");
+
+        let raw = parser.parse(&[0], &tables).await.expect("parse sentinel");
+        assert_empty_and_valid(&raw);
+        assert!(raw.emit_stream.is_empty());
+        assert!(raw.node_kind.is_empty());
+
+        let classified = parser
+            .parse_classified_token_kinds(&[0], &tables)
+            .await
+            .expect("parse classified sentinel");
+        assert!(classified.headers.is_empty());
+        assert_empty_and_valid(&classified);
+        assert!(classified.emit_stream.is_empty());
+        assert!(classified.node_kind.is_empty());
+    });
+}
+
+#[test]
+fn single_token_has_an_empty_stack_effect() {
+    common::block_on_gpu_with_timeout("parser single token", async move {
+        let tables = common::parse_tables::echo_bracket_tables();
+        let parser = GpuParser::new().await.expect("create GPU parser");
+        let kinds = [0, TokenKind::Ident as u32];
+
+        let raw = parser.parse(&kinds, &tables).await.expect("parse token");
+        assert_empty_and_valid(&raw);
+
+        let classified = parser
+            .parse_classified_token_kinds(&kinds, &tables)
+            .await
+            .expect("parse classified token");
+        assert_eq!(classified.headers.len(), 1);
+        assert_empty_and_valid(&classified);
+        assert_eq!(classified.emit_stream, [TokenKind::Ident as u32]);
+    });
+}
+
+#[test]
+fn single_unmatched_opener_is_invalid() {
+    common::block_on_gpu_with_timeout("parser single opener", async move {
+        let tables = common::parse_tables::echo_bracket_tables();
+        let parser = GpuParser::new().await.expect("create GPU parser");
+        let raw_kinds = [0, TokenKind::LParen as u32];
+        let classified_kinds = [0, TokenKind::GroupLParen as u32];
+
+        for result in [
+            parser
+                .parse(&raw_kinds, &tables)
+                .await
+                .expect("parse opener"),
+            parser
+                .parse_classified_token_kinds(&classified_kinds, &tables)
+                .await
+                .expect("parse classified opener"),
+        ] {
+            assert_eq!(result.sc_stream.len(), 1);
+            assert_eq!(result.sc_stream[0] & 1, 1, "opener must be a push");
+            assert!(!result.brackets.valid);
+            assert_eq!(result.brackets.final_depth, 1);
+            assert_eq!(result.brackets.min_depth, 0);
+            assert_eq!(result.brackets.valid_up_to, 1);
+            assert_eq!(result.brackets.first_unclosed_push, Some(0));
+        }
+    });
+}
@@ -0,0 +1,102 @@
+//! Compile-time pins for the prelude, crate-root re-exports, and the deep
+//! module paths they alias. Renaming or dropping any of these is a breaking
+//! change to the public surface.
+
+use std::any::TypeId;
+
+fn same_type<A: 'static, B: 'static>() -> bool {
+    TypeId::of::<A>() == TypeId::of::<B>()
+}
+
+#[test]
+fn prelude_glob_names_the_common_entry_points() {
+    use laniusc_compiler::{self as lc, prelude::*};
+
+    // Async entry points are lazy; building the future does no GPU work.
+    drop(lex_on_gpu(""));
+    let token = Token::new(TokenKind::Ident, 0, 1);
+    assert_eq!(token.kind, TokenKind::Ident);
+    assert_eq!(token.span, Span::new(0, 1));
+    assert!(same_type::<GpuLexer, lc::lexer::GpuLexer>());
+    assert!(same_type::<GpuParser, lc::parser::GpuParser>());
+    assert!(same_type::<
+        LexerRuntimeOptions,
+        lc::lexer::LexerRuntimeOptions,
+    >());
+    assert!(same_type::<ParserOptions, lc::parser::ParserOptions>());
+    assert!(same_type::<ParseResult, lc::parser::driver::ParseResult>());
+    assert!(same_type::<
+        PrecomputedParseTables,
+        lc::parser::tables::PrecomputedParseTables,
+    >());
+    assert!(same_type::<CompileError, lc::compiler::CompileError>());
+    assert!(same_type::<
+        GpuSyntaxError,
+        lc::parser::syntax::GpuSyntaxError,
+    >());
+    assert!(same_type::<
+        GpuTypeCheckError,
+        lc::type_checker::GpuTypeCheckError,
+    >());
+    assert!(same_type::<
+        GpuDeviceInitializationError,
+        lc::gpu::device::GpuDeviceInitializationError,
+    >());
+    assert!(same_type::<
+        Ll1ParseErrorCode,
+        lc::parser::tables::Ll1ParseErrorCode,
+    >());
+}
+
+#[test]
+fn crate_root_and_prelude_reexport_the_deep_paths() {
+    use laniusc_compiler as lc;
+
+    assert!(same_type::<lc::Token, lc::lexer::types::Token>());
+    assert!(same_type::<lc::prelude::Token, lc::lexer::Token>());
+    assert!(same_type::<lc::Span, lc::span::Span>());
+    assert!(same_type::<
+        lc::TokenKind,
+        lc::lexer::tables::tokens::TokenKind,
+    >());
+    assert!(same_type::<lc::TokenKind, lc::lexer::tables::TokenKind>());
+    assert!(same_type::<lc::GpuLexer, lc::lexer::driver::GpuLexer>());
+    assert!(same_type::<lc::GpuLexer, lc::lexer::GpuLexer>());
+    assert!(same_type::<lc::GpuParser, lc::parser::driver::GpuParser>());
+    assert!(same_type::<lc::GpuParser, lc::parser::GpuParser>());
+    assert!(same_type::<lc::ParseResult, lc::parser::driver::ParseResult>());
+    assert!(same_type::<
+        lc::PrecomputedParseTables,
+        lc::parser::tables::PrecomputedParseTables,
+    >());
+    assert!(same_type::<lc::CompileError, lc::compiler::CompileError>());
+    assert!(same_type::<
+        lc::GpuSyntaxError,
+        lc::parser::syntax::GpuSyntaxError,
+    >());
+    assert!(same_type::<
+        lc::GpuTypeCheckError,
+        lc::type_checker::GpuTypeCheckError,
+    >());
+    assert!(same_type::<
+        lc::GpuDeviceInitializationError,
+        lc::gpu::device::GpuDeviceInitializationError,
+    >());
+    assert!(same_type::<
+        lc::Ll1ParseErrorCode,
+        lc::parser::tables::Ll1ParseErrorCode,
+    >());
+
+    drop(lc::lex_on_gpu(""));
+    drop(lc::lexer::lex_on_gpu(""));
+    drop(lc::lexer::driver::lex_on_gpu(""));
+}
+
+#[test]
+fn resident_token_layout_stays_reachable_from_the_lexer() {
+    // `GpuToken` is the element type of the public resident token buffers.
+    assert!(same_type::<
+        laniusc_compiler::lexer::GpuToken,
+        laniusc_compiler::lexer::types::GpuToken,
+    >());
+}
@@ -0,0 +1,238 @@
+//! `LexerRuntimeOptions` and `ParserOptions` replace the `LANIUS_*`
+//! environment toggles: the old variables are exported for this whole test
+//! binary and must not change what the lexer, parser, or compiler do, while
+//! the options reproduce each behavior the variables used to select.
+
+mod common;
+
+use std::sync::Once;
+
+use laniusc_compiler::{
+    compiler::{GpuCompilerOptions, compiler_options},
+    lexer::{
+        GpuLexer,
+        LexOptions,
+        LexerRuntimeOptions,
+        ReadbackMode,
+        tables::tokens::TokenKind,
+        test_cpu,
+    },
+    parser::{
+        BracketCacheLimits,
+        ParserOptions,
+        buffers::ActionHeader,
+        driver::{GpuParser, ParseResult},
+    },
+};
+
+const SOURCE: &str = "fn main() { let x = (1 + 2) * [3][0]; }\n";
+
+/// Every variable the lexer and parser used to read, set to its
+/// non-default value.
+const LEFTOVER_EXPORTS: &[(&str, &str)] = &[
+    ("LANIUS_READBACK", "0"),
+    ("PERF_ONE_READBACK", "0"),
+    ("LANIUS_VALIDATION_SCOPES", "1"),
+    ("LANIUS_BATCH_COMPUTE_PASSES", "0"),
+    ("LANIUS_CAPTURE_DISPATCH_METADATA", "1"),
+    ("LANIUS_GPU_TIMING", "1"),
+    ("LANIUS_CHECK_TOKEN_BOUNDARIES", "1"),
+];
+
+fn export_leftovers() {
+    static EXPORTED: Once = Once::new();
+    EXPORTED.call_once(|| {
+        for &(name, value) in LEFTOVER_EXPORTS {
+            // SAFETY: every test calls this before touching the environment
+            // or the GPU, and `Once` blocks them until the writes are done.
+            unsafe { std::env::set_var(name, value) };
+        }
+    });
+}
+
+fn bracket_kinds() -> Vec<u32> {
+    [
+        0,
+        TokenKind::LParen as u32,
+        TokenKind::Ident as u32,
+        TokenKind::LBracket as u32,
+        TokenKind::RBracket as u32,
+        TokenKind::RParen as u32,
+        0,
+    ]
+    .to_vec()
+}
+
+fn header_words(headers: &[ActionHeader]) -> Vec<[u32; 4]> {
+    headers
+        .iter()
+        .map(|h| [h.push_len, h.emit_len, h.pop_tag, h.pop_count])
+        .collect()
+}
+
+fn assert_same_parse(left: &ParseResult, right: &ParseResult) {
+    assert_eq!(header_words(&left.headers), header_words(&right.headers));
+    assert_eq!(left.sc_stream, right.sc_stream);
+    assert_eq!(left.emit_stream, right.emit_stream);
+    assert_eq!(
+        left.brackets.match_for_index,
+        right.brackets.match_for_index
+    );
+}
+
+#[test]
+fn from_env_translates_the_old_variables() {
+    export_leftovers();
+    let lexer = LexerRuntimeOptions::from_env();
+    assert_eq!(lexer.readback, ReadbackMode::None);
+    assert!(lexer.validation_scopes);
+    assert!(!lexer.batch_compute_passes);
+    assert!(lexer.capture_dispatch_metadata);
+    assert!(lexer.gpu_timing);
+    assert!(lexer.check_token_boundaries);
+
+    let parser = ParserOptions::from_env();
+    assert!(!parser.readback);
+    assert!(parser.validation_scopes);
+    assert!(!parser.batch_compute_passes);
+    assert!(parser.capture_dispatch_metadata);
+    assert!(parser.gpu_timing);
+}
+
+#[test]
+fn compiler_options_come_from_binaries_not_the_environment() {
+    export_leftovers();
+    assert_eq!(compiler_options(), GpuCompilerOptions::default());
+    assert_eq!(
+        GpuCompilerOptions::from_env(),
+        GpuCompilerOptions {
+            lexer: LexerRuntimeOptions::from_env(),
+            parser: ParserOptions::from_env(),
+            bracket_cache: BracketCacheLimits::default(),
+        }
+    );
+}
+
+#[test]
+fn leftover_exports_do_not_change_the_lexer() {
+    export_leftovers();
+    common::block_on_gpu_with_timeout("lexer ignores environment", async move {
+        let expected = test_cpu::lex_on_test_cpu(SOURCE).expect("test CPU oracle");
+        let lexer = GpuLexer::new().await.expect("create GPU lexer");
+        assert_eq!(lexer.runtime_options(), LexerRuntimeOptions::default());
+
+        let output = lexer
+            .lex_with_options(SOURCE, LexOptions::default())
+            .await
+            .expect("GPU lex");
+        assert_eq!(output.tokens, expected);
+        assert!(output.dispatch_records.is_empty());
+    });
+}
+
+#[test]
+fn lexer_options_select_the_formerly_env_gated_behaviors() {
+    export_leftovers();
+    common::block_on_gpu_with_timeout("lexer runtime options", async move {
+        let expected = test_cpu::lex_on_test_cpu(SOURCE).expect("test CPU oracle");
+
+        let silent = GpuLexer::new_with(LexerRuntimeOptions {
+            readback: ReadbackMode::None,
+            capture_dispatch_metadata: true,
+            ..LexerRuntimeOptions::default()
+        })
+        .await
+        .expect("create GPU lexer");
+        let output = silent
+            .lex_with_options(
+                SOURCE,
+                LexOptions {
+                    readback: ReadbackMode::None,
+                    ..LexOptions::default()
+                },
+            )
+            .await
+            .expect("GPU lex");
+        assert!(output.tokens.is_empty());
+        assert!(!output.dispatch_records.is_empty());
+
+        let checked = GpuLexer::new_with(LexerRuntimeOptions {
+            validation_scopes: true,
+            batch_compute_passes: false,
+            debug_groups: true,
+            check_token_boundaries: true,
+            ..LexerRuntimeOptions::default()
+        })
+        .await
+        .expect("create GPU lexer");
+        assert_eq!(checked.lex(SOURCE).await.expect("GPU lex"), expected);
+    });
+}
+
+#[test]
+fn leftover_exports_do_not_change_the_parser() {
+    export_leftovers();
+    common::block_on_gpu_with_timeout("parser ignores environment", async move {
+        let tables = common::parse_tables::echo_bracket_tables();
+        let parser = GpuParser::new().await.expect("create GPU parser");
+        assert_eq!(parser.options(), ParserOptions::default());
+
+        let result = parser
+            .parse(&bracket_kinds(), &tables)
+            .await
+            .expect("parse");
+        assert!(!result.sc_stream.is_empty());
+        assert!(result.brackets.valid);
+        assert!(result.dispatch_records.is_empty());
+    });
+}
+
+#[test]
+fn parser_options_select_the_formerly_env_gated_behaviors() {
+    export_leftovers();
+    common::block_on_gpu_with_timeout("parser options", async move {
+        let tables = common::parse_tables::echo_bracket_tables();
+        let kinds = bracket_kinds();
+        let parser = GpuParser::new_with(ParserOptions {
+            capture_dispatch_metadata: true,
+            ..ParserOptions::default()
+        })
+        .await
+        .expect("create GPU parser");
+        let baseline = parser.parse(&kinds, &tables).await.expect("parse");
+        assert!(!baseline.dispatch_records.is_empty());
+
+        // Per-call overrides leave the parser's own options alone.
+        let unread = parser
+            .parse_with_options(
+                &kinds,
+                &tables,
+                ParserOptions {
+                    readback: false,
+                    ..parser.options()
+                },
+            )
+            .await
+            .expect("parse without readback");
+        assert!(unread.sc_stream.is_empty());
+        assert!(unread.headers.is_empty());
+        assert!(parser.options().readback);
+
+        let scoped = parser
+            .parse_with_options(
+                &kinds,
+                &tables,
+                ParserOptions {
+                    validation_scopes: true,
+                    batch_compute_passes: false,
+                    debug_groups: true,
+                    capture_dispatch_metadata: false,
+                    ..ParserOptions::default()
+                },
+            )
+            .await
+            .expect("parse with validation scopes");
+        assert_same_parse(&scoped, &baseline);
+        assert!(scoped.dispatch_records.is_empty());
+    });
+}
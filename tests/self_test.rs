@@ -0,0 +1,55 @@
+mod common;
+
+use laniusc_compiler::{
+    gpu::device::DeviceOptions,
+    lexer::tables::tokens::TokenKind,
+    self_test,
+    self_test::{
+        CheckStatus,
+        self_test_with_vectors,
+        vectors::{CheckKind, SelfTestVectors},
+    },
+};
+
+#[test]
+fn embedded_vectors_pass_on_the_adapter() {
+    common::block_on_gpu_with_timeout("self test", async move {
+        let report = self_test(DeviceOptions::from_env()).await;
+        assert!(report.passed(), "{report}");
+        assert_eq!(
+            report.checks.len(),
+            SelfTestVectors::embedded().checks.len()
+        );
+    });
+}
+
+#[test]
+fn a_corrupted_expectation_fails_only_its_check_as_a_wrong_result() {
+    common::block_on_gpu_with_timeout("self test corrupted vector", async move {
+        let mut vectors = SelfTestVectors::embedded();
+        let lex = vectors
+            .checks
+            .iter_mut()
+            .find(|check| check.kind == CheckKind::Lex && !check.expected.tokens.is_empty())
+            .expect("a lex check with tokens");
+        let corrupted = lex.name.clone();
+        let token = &mut lex.expected.tokens[0];
+        token.kind = if token.kind == TokenKind::Ident {
+            TokenKind::Int
+        } else {
+            TokenKind::Ident
+        };
+
+        let report = self_test_with_vectors(DeviceOptions::from_env(), &vectors).await;
+        let failures: Vec<_> = report.failures().map(|check| check.name.as_str()).collect();
+        assert_eq!(failures, [corrupted.as_str()], "{report}");
+        assert!(
+            matches!(
+                &report.check(&corrupted).unwrap().status,
+                CheckStatus::WrongResult(detail) if detail.contains("kept token 0")
+            ),
+            "{report}"
+        );
+        assert!(report.has_wrong_results());
+    });
+}
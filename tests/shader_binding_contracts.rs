@@ -0,0 +1,20 @@
+use laniusc_compiler::{lexer, parser};
+
+// Checks each declared pass binding list and dispatch convention against its
+// built reflection artifact, so shader/Rust drift fails here rather than at
+// first dispatch.
+#[test]
+fn declared_pass_bindings_match_shader_reflection() {
+    let contracts = lexer::passes::binding_contracts()
+        .into_iter()
+        .chain(parser::passes::binding_contracts())
+        .collect::<Vec<_>>();
+    assert_eq!(contracts.len(), 43);
+
+    let failures = contracts
+        .iter()
+        .filter_map(|contract| contract.check_artifact().err())
+        .map(|err| format!("{err:#}"))
+        .collect::<Vec<_>>();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
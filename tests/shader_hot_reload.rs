@@ -0,0 +1,68 @@
+#![cfg(feature = "shader-hot-reload")]
+
+mod common;
+
+use laniusc_compiler::{
+    gpu::hot_reload::{PrebuiltRebuild, artifact_path},
+    lexer::{GpuLexer, test_cpu::lex_on_test_cpu},
+};
+
+const SOURCE: &str = "fn main() { let x = 0x2A + 1; return x; } // done\n";
+
+async fn assert_lex_matches_oracle(lexer: &GpuLexer) {
+    let actual: Vec<_> = lexer
+        .lex(SOURCE)
+        .await
+        .expect("GPU lex")
+        .into_iter()
+        .map(|t| (t.kind, t.start(), t.len()))
+        .collect();
+    let expected: Vec<_> = lex_on_test_cpu(SOURCE)
+        .expect("test CPU oracle")
+        .into_iter()
+        .map(|t| (t.kind, t.start(), t.len()))
+        .collect();
+    assert_eq!(actual, expected);
+}
+
+// Reload overrides are process-wide, so both scenarios share one test.
+#[test]
+fn lexer_swaps_changed_shaders_and_keeps_old_pipelines_on_failure() {
+    common::block_on_gpu_with_timeout("shader hot reload", async move {
+        let mut lexer = GpuLexer::new().await.expect("create GPU lexer");
+        let shader = lexer.loaded_shaders()[0].clone();
+        let reflection = artifact_path(&shader.reflection);
+
+        // Same module with a different SPIR-V generator word.
+        let mut spirv = std::fs::read(artifact_path(&shader.spv)).expect("read live SPIR-V");
+        spirv[8..12].copy_from_slice(&0xFFFF_0001u32.to_le_bytes());
+        let changed = common::temp_artifact_path("hot_reload", shader.key(), Some("spv"));
+        std::fs::write(&changed, &spirv).expect("write changed SPIR-V");
+
+        let mut source = PrebuiltRebuild::new();
+        source.insert(shader.key(), &changed, &reflection);
+        let report = lexer.reload_shaders_from(&source);
+        assert!(report.is_ok(), "{report}");
+        assert!(report.swapped, "{report}");
+        assert_eq!(report.changed, [shader.key()]);
+        assert_lex_matches_oracle(&lexer).await;
+
+        let again = lexer.reload_shaders_from(&source);
+        assert!(again.is_ok() && !again.swapped, "{again}");
+        assert!(again.changed.is_empty());
+        assert_eq!(again.unchanged, lexer.loaded_shaders().len());
+
+        let garbage = common::temp_artifact_path("hot_reload", shader.key(), Some("spv"));
+        std::fs::write(&garbage, [0u8; 16]).expect("write garbage SPIR-V");
+        let mut broken = PrebuiltRebuild::new();
+        broken.insert(shader.key(), &garbage, &reflection);
+        let report = lexer.reload_shaders_from(&broken);
+        assert_eq!(report.failures.len(), 1, "{report}");
+        assert_eq!(report.failures[0].shader, shader.key());
+        assert!(!report.swapped, "{report}");
+        assert_lex_matches_oracle(&lexer).await;
+
+        let _ = std::fs::remove_file(changed);
+        let _ = std::fs::remove_file(garbage);
+    });
+}
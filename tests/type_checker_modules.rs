@@ -1810,8 +1810,8 @@ fn assert_source_pack_case_accepts(sources: &'static [&'static str], app_source:
 
 #[test]
 fn resident_typechecker_uses_compact_hir_control_and_hir_scope_validation() {
-    let resident = include_str!("../crates/laniusc-compiler/src/type_checker/resident.rs");
-    let pass_loaders = include_str!("../crates/laniusc-compiler/src/type_checker/pass_loaders.rs");
+    let resident = include_str!("../crates/laniusc-gpu/src/type_checker/resident.rs");
+    let pass_loaders = include_str!("../crates/laniusc-gpu/src/type_checker/pass_loaders.rs");
 
     assert!(
         resident.contains("&self.passes.conditions_compact_expr")
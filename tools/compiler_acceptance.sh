@@ -8408,8 +8408,8 @@ run_cargo_bin_test() {
 
 run_cargo_lib_test() {
   local test_name="$1"
-  record_named_test_reference lib laniusc-compiler "$test_name" crates/laniusc-compiler/src
-  run_cmd cargo test -p laniusc-compiler -j1 --lib "$test_name" -- --test-threads=1
+  record_named_test_reference lib laniusc-gpu "$test_name" crates/laniusc-gpu/src
+  run_cmd cargo test -p laniusc-gpu -j1 --lib "$test_name" -- --test-threads=1
 }
 
 describe_tier() {
@@ -8451,10 +8451,10 @@ run_focused() {
     run_cmd cargo test --test cli_version -j1 -- --list
     run_cmd cargo test --test formatter -j1 -- --list
     run_cmd cargo test --test package_manifest -j1 -- --list
-    run_cmd cargo test -p laniusc-compiler diagnostic_renderer_includes_code_span_snippet_label_and_note -j1 --lib -- --list
-    run_cmd cargo test -p laniusc-compiler -j1 --lib contract_file_emission_copies_without_marking_executable -- --list
+    run_cmd cargo test -p laniusc-gpu diagnostic_renderer_includes_code_span_snippet_label_and_note -j1 --lib -- --list
+    run_cmd cargo test -p laniusc-gpu -j1 --lib contract_file_emission_copies_without_marking_executable -- --list
     run_cmd cargo test -j1 --bin laniusc contract_descriptor_emission_rejects_incoherent_json_descriptor -- --list
-    run_cmd cargo test -p laniusc-compiler source_pack_work_queue_progress_page_transitions_match_reference_model -j1 --lib -- --list
+    run_cmd cargo test -p laniusc-gpu source_pack_work_queue_progress_page_transitions_match_reference_model -j1 --lib -- --list
     return
   fi
   run_cmd cargo check --lib -j1
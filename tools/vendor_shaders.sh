@@ -0,0 +1,50 @@
+#!/usr/bin/env bash
+set -euo pipefail
+
+usage() {
+  cat <<'USAGE'
+Usage: tools/vendor_shaders.sh [--profile debug|release]
+
+Compiles the shaders with `slangc` and copies the resulting SPIR-V and
+reflection artifacts, plus their prebuilt.manifest, into shaders/prebuilt/.
+Builds with the `vendored-shaders` feature use that set when `slangc` is not
+available, or when it was vendored from the current Slang sources.
+USAGE
+}
+
+profile=debug
+while [[ $# -gt 0 ]]; do
+  case "$1" in
+    --profile)
+      profile="${2:?--profile needs a value}"
+      shift 2
+      ;;
+    -h|--help)
+      usage
+      exit 0
+      ;;
+    *)
+      usage >&2
+      exit 2
+      ;;
+  esac
+done
+
+root="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+cargo_args=(build -p laniusc-shaders)
+if [[ "$profile" == release ]]; then
+  cargo_args+=(--release)
+fi
+(cd "$root" && cargo "${cargo_args[@]}")
+
+artifacts="${CARGO_TARGET_DIR:-$root/target}/laniusc-shader-artifacts/$profile/shaders"
+if [[ ! -f "$artifacts/prebuilt.manifest" ]]; then
+  echo "no prebuilt.manifest in $artifacts; was the set compiled by slangc?" >&2
+  exit 1
+fi
+
+prebuilt="$root/shaders/prebuilt"
+find "$prebuilt" -type f \( -name '*.spv' -o -name '*.reflect.json' -o -name prebuilt.manifest \) -delete
+(cd "$artifacts" && find . -type f \( -name '*.spv' -o -name '*.reflect.json' -o -name prebuilt.manifest \) -print0 \
+  | xargs -0 -I{} cp --parents {} "$prebuilt")
+echo "vendored $(find "$prebuilt" -name '*.spv' | wc -l) shaders into $prebuilt"